@@ -0,0 +1,86 @@
+/**
+ * Records links signed by this frontend's signing path as fixtures for the
+ * Rust admission check (ubl-server ts_conformance).
+ *
+ * Run after touching src/services/crypto.ts:
+ *   npm run conformance:record
+ *
+ * The key is derived from fixed PRF material, so output is reproducible and
+ * a diff in the fixtures means the signed bytes changed.
+ */
+
+import { writeFileSync } from 'node:fs';
+import { deriveSigningKey, linkSigningBytes, signLink, type LinkToSign } from '../src/services/crypto';
+
+const OUT = new URL('../../../../ubl/kernel/rust/tests/fixtures/ts_commits/frontend.json', import.meta.url);
+
+/** Stand-in for the 32 bytes a passkey PRF would return */
+const PRF_MATERIAL = Uint8Array.from({ length: 32 }, (_, i) => i);
+
+const CASES: Array<{ name: string; link: LinkToSign }> = [
+  {
+    name: 'message_created',
+    link: {
+      version: 1,
+      container_id: 'C.Messenger',
+      expected_sequence: 1,
+      previous_hash: '0x00',
+      atom_hash: 'ab'.repeat(32),
+      intent_class: 'Observation',
+      physics_delta: '0',
+    },
+  },
+  {
+    name: 'entropy_with_pact',
+    link: {
+      version: 1,
+      container_id: 'C.Jobs',
+      expected_sequence: 42,
+      previous_hash: 'cd'.repeat(32),
+      atom_hash: 'ef'.repeat(32),
+      intent_class: 'Entropy',
+      physics_delta: '-1500',
+      pact: {
+        pact_id: 'pact_budget_q3',
+        signatures: [{ signer: '11'.repeat(32), signature: '22'.repeat(64) }],
+      },
+    },
+  },
+  {
+    name: 'escaped_container_max_safe_sequence',
+    link: {
+      version: 1,
+      container_id: 'C.Équipe/日本 "q"\t\u0001',
+      expected_sequence: Number.MAX_SAFE_INTEGER,
+      previous_hash: '00'.repeat(32),
+      atom_hash: '33'.repeat(32),
+      intent_class: 'Observation',
+      physics_delta: '0',
+    },
+  },
+];
+
+async function main() {
+  await deriveSigningKey(PRF_MATERIAL);
+
+  const fixtures = [];
+  for (const { name, link } of CASES) {
+    const signed = await signLink(link);
+    if (!signed) throw new Error('signing key not cached');
+    fixtures.push({
+      name: `frontend/${name}`,
+      producer: 'apps/messenger/frontend signLink',
+      pipeline: 'admission',
+      signing_bytes_hex: Buffer.from(linkSigningBytes(link)).toString('hex'),
+      link: signed,
+    });
+  }
+
+  writeFileSync(OUT, JSON.stringify(fixtures, null, 2) + '\n');
+  console.log(`wrote ${fixtures.length} fixtures to ${OUT.pathname}`);
+}
+
+main().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
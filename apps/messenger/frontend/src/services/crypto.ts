@@ -80,7 +80,7 @@ const P = 2n ** 255n - 19n;
 const L = 2n ** 252n + 27742317777372353535851937790883648493n;
 
 // Base point x coordinate
-const Bx = 15112221349535807912866137220509078935008241517919253862909182699782852816284n;
+const Bx = 15112221349535400772501151409588531511454012693041857206046113283949847762202n;
 // Base point y coordinate
 const By = 46316835694926478169428394003475163141307993866256225615783033603165251855960n;
 
@@ -424,16 +424,11 @@ function canonicalize(obj: any): string {
 }
 
 /**
- * Sign a link with the cached signing key
- * Returns null if no signing key is available
+ * Bytes covered by a link signature: canonical JSON of the signed fields
+ * (must match server verification; pinned by the Rust conformance fixtures
+ * under ubl/kernel/rust/tests/fixtures/ts_commits)
  */
-export async function signLink(link: LinkToSign): Promise<SignedLink | null> {
-  if (!cachedSigningKey) {
-    console.warn('No signing key available - link will not be signed client-side');
-    return null;
-  }
-
-  // Build canonical signing data (must match server verification)
+export function linkSigningBytes(link: LinkToSign): Uint8Array {
   const signingData = {
     version: link.version,
     container_id: link.container_id,
@@ -444,9 +439,20 @@ export async function signLink(link: LinkToSign): Promise<SignedLink | null> {
     physics_delta: link.physics_delta,
     pact: link.pact || null,
   };
+  return new TextEncoder().encode(canonicalize(signingData));
+}
+
+/**
+ * Sign a link with the cached signing key
+ * Returns null if no signing key is available
+ */
+export async function signLink(link: LinkToSign): Promise<SignedLink | null> {
+  if (!cachedSigningKey) {
+    console.warn('No signing key available - link will not be signed client-side');
+    return null;
+  }
 
-  const signingBytes = new TextEncoder().encode(canonicalize(signingData));
-  const signature = await ed25519Sign(signingBytes, cachedSigningKey._seed);
+  const signature = await ed25519Sign(linkSigningBytes(link), cachedSigningKey._seed);
 
   return {
     ...link,
@@ -42,7 +42,8 @@ export class SSEClient {
     }
 
     const url = `${this.baseUrl}/v1/stream?tenant_id=${this.tenantId}${cursor ? `&cursor=${cursor}` : ''}`;
-    this.eventSource = new EventSource(url);
+    // The stream requires a session; send the session cookie cross-origin too
+    this.eventSource = new EventSource(url, { withCredentials: true });
 
     this.eventSource.onopen = () => {
       console.log('✅ SSE connected');
@@ -52,12 +52,47 @@ interface BootstrapApiResponse {
     message_type: string;
     timestamp: string;
   }>;
+  // Served with an ETag; the browser cache revalidates via If-None-Match
+  tenant_id: string;
+  presence: Array<{
+    entity_id: string;
+    state: string;
+    job_id: string | null;
+    since: string;
+    last_seen_at: string;
+  }>;
+  pending_jobs: Array<{
+    job_id: string;
+    conversation_id: string;
+    title: string;
+    state: string;
+    owner_entity_id: string;
+    waiting_on: string[] | null;
+    available_actions: any;
+    updated_at: string;
+    last_event_seq: number;
+  }>;
+  // Unacknowledged @mentions of the caller, per conversation
+  mention_badges: Array<{
+    conversation_id: string;
+    count: number;
+    latest_message_id: string;
+    latest_at: string;
+  }>;
+  cursors: {
+    entities: number;
+    conversations: number;
+    jobs: number;
+    presence: number;
+    mentions: number;
+  };
 }
 
 interface SendMessageApiResponse {
   message_id: string;
   hash: string;
   sequence: number;
+  tentative_id?: string;
 }
 
 interface CreateConversationApiResponse {
@@ -208,18 +243,22 @@ export const ublApi = {
     type?: MessageType;
     // UBL-FIX: Add client_msg_id for idempotency (Diamond Checklist #7)
     clientMsgId?: string;
-  }): Promise<{ messageId: string; hash: string; sequence: number }> {
+    // Optimistic UI id of the pending bubble, echoed back for reconciliation
+    tentativeId?: string;
+  }): Promise<{ messageId: string; hash: string; sequence: number; tentativeId?: string }> {
     const res = await api.post<SendMessageApiResponse>(`/messenger/messages`, {
       conversation_id: input.conversationId,
       content: input.content,
       message_type: input.type || 'text',
       // UBL-FIX: Pass client_msg_id to backend for idempotency
       client_msg_id: input.clientMsgId,
+      tentative_id: input.tentativeId,
     });
     return { 
       messageId: res.message_id, 
       hash: res.hash, 
       sequence: res.sequence,
+      tentativeId: res.tentative_id,
     };
   },
 
@@ -298,7 +337,10 @@ export const ublApi = {
     content: string;
     messageType?: MessageType;
     idempotencyKey?: string;
-  }): Promise<{ messageId: string; hash: string; sequence: number; action: string; signedClientSide: boolean }> {
+    tentativeId?: string;
+    /** Nonce of this send: retries and double clicks with it create one message */
+    clientMsgId?: string;
+  }): Promise<{ messageId: string; hash: string; sequence: number; action: string; signedClientSide: boolean; tentativeId?: string; clientMsgId?: string }> {
     // Check if we can sign client-side
     const canSign = isClientSideSigningAvailable();
     
@@ -307,6 +349,8 @@ export const ublApi = {
       content: input.content,
       message_type: input.messageType || 'text',
       idempotency_key: input.idempotencyKey,
+      tentative_id: input.tentativeId,
+      client_msg_id: input.clientMsgId,
     };
     
     // If client-side signing is available, we'd prepare a pre-signed link
@@ -327,6 +371,8 @@ export const ublApi = {
       hash: string;
       sequence: number;
       action: string;
+      tentative_id?: string;
+      client_msg_id?: string;
     }>(`/v1/conversations/${input.conversationId}/messages`, body);
     
     return {
@@ -335,6 +381,8 @@ export const ublApi = {
       sequence: res.sequence,
       action: res.action,
       signedClientSide: false, // For now, always server-signed
+      tentativeId: res.tentative_id,
+      clientMsgId: res.client_msg_id,
     };
   },
 
@@ -408,6 +456,9 @@ export const ublApi = {
     actionType: string;
     buttonId: string;
     cardId: string;
+    // Provenance: card_hash and card_nonce returned with the card
+    cardHash: string;
+    nonce: string;
     inputData?: any;
     idempotencyKey?: string;
   }): Promise<{ success: boolean; eventIds: string[] }> {
@@ -418,6 +469,8 @@ export const ublApi = {
       action_type: input.actionType,
       button_id: input.buttonId,
       card_id: input.cardId,
+      card_hash: input.cardHash,
+      nonce: input.nonce,
       input_data: input.inputData,
       idempotency_key: input.idempotencyKey,
     });
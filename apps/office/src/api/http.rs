@@ -14,16 +14,20 @@ use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use tower_http::cors::{CorsLayer, Any};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use ubl_errors::{ErrorCode, HasErrorCode};
+use ubl_events::{JobAction, JobCreated, JobEvent, JobEventNotice};
 use uuid::Uuid;
 
 use crate::entity::{Entity, EntityId, EntityParams, EntityType, Instance, EntityRepository};
-use crate::session::{Session, SessionType, SessionMode, SessionConfig, Handover};
-use crate::context::{ContextFrameBuilder, Narrator};
-use crate::governance::{Constitution, DreamingCycle, DreamingConfig, Simulation, SimulationConfig, Action};
+use crate::session::{EntityBudgets, Session, SessionType, SessionMode, SessionConfig, Handover};
+use crate::context::{AffordanceService, BudgetConfig, ContextBudgeter, ContextFrameBuilder, Narrator};
+use crate::governance::{Constitution, DreamingCycle, DreamingConfig, Simulation, SimulationConfig, Action, ProvenanceValidator};
 use crate::ubl_client::UblClient;
 use crate::llm::{LlmProvider, LlmRequest, LlmMessage, SmartRouter, ProviderProfile, default_profiles};
-use crate::job_executor::{JobExecutor, types as job_types};
+use crate::job_executor::{GuardianApprovals, JobExecutor, SummarizeJob, SummarizeRequest, types as job_types};
+use crate::mcp::UnifiedToolRegistry;
+use crate::middleware::{service_auth, ServiceVerifier};
 use crate::routes::{ws, deploy};
 use crate::{OfficeConfig, OfficeError};
 
@@ -35,6 +39,16 @@ pub struct AppState {
     pub smart_router: Arc<SmartRouter>,
     pub entity_repository: Arc<EntityRepository>,
     pub job_executor: Arc<JobExecutor>,
+    /// Guarded entities' mutations waiting on their guardians
+    pub guardian_approvals: Arc<GuardianApprovals>,
+    /// Capability-derived affordances, shared with context-frame builds
+    pub affordances: Arc<AffordanceService>,
+    /// Buttons of cards this Office issued ("no fake buttons")
+    pub provenance: Arc<ProvenanceValidator>,
+    /// Token buckets shared by all sessions of an entity
+    pub entity_budgets: Arc<EntityBudgets>,
+    /// Checks UBL's signature on the gateway-facing routes
+    pub service_verifier: ServiceVerifier,
     pub entities: HashMap<EntityId, Entity>,
     pub sessions: HashMap<String, Session>,
     pub instances: HashMap<String, Instance>,
@@ -64,13 +78,31 @@ impl AppState {
         
         let smart_router = Arc::new(router);
 
+        // Affordances offer one entry per registered tool
+        let tools = Arc::new(UnifiedToolRegistry::with_context(ubl_client.clone(), &config.ubl.container_id));
+        let affordances = Arc::new(AffordanceService::new(ubl_client.clone()).with_tools(tools));
+
+        let guardian_approvals = Arc::new(GuardianApprovals::new(std::time::Duration::from_secs(
+            config.governance.guardian_approval_timeout_secs,
+        )));
+
         // Create job executor
-        let job_executor = Arc::new(JobExecutor::new(
-            ubl_client.clone(),
-            entity_repository.clone(),
-            smart_router.clone(),
-            &config.ubl.container_id,
-        ));
+        let job_executor = Arc::new(
+            JobExecutor::new(
+                ubl_client.clone(),
+                entity_repository.clone(),
+                smart_router.clone(),
+                &config.ubl.container_id,
+            )
+            .with_affordance_service(affordances.clone())
+            .with_guardian_approvals(guardian_approvals.clone())
+            .with_context_budget(Arc::new(
+                ContextBudgeter::new(BudgetConfig::for_window(config.llm.context_window, config.llm.max_tokens))
+                    .with_summarizer(smart_router.clone()),
+            )),
+        );
+
+        let service_verifier = ServiceVerifier::from_config(&config.ubl);
 
         Self {
             config,
@@ -79,6 +111,11 @@ impl AppState {
             smart_router,
             entity_repository,
             job_executor,
+            guardian_approvals,
+            affordances,
+            provenance: Arc::new(ProvenanceValidator::new()),
+            entity_budgets: Arc::new(EntityBudgets::new()),
+            service_verifier,
             entities: HashMap::new(),
             sessions: HashMap::new(),
             instances: HashMap::new(),
@@ -110,9 +147,20 @@ pub fn create_router(state: SharedState) -> Router {
         .route("/office/deploy", axum::routing::post(deploy_handler))
         .with_state(deploy_state);
 
+    // Gateway-facing endpoints, called by UBL with its service signature
+    let gateway_router = Router::new()
+        .route("/v1/office/ingest_message", post(ingest_message))
+        .route("/v1/office/job_action", post(handle_job_action))
+        .route("/v1/office/job_event", post(handle_job_event))
+        .route("/v1/office/asc_expiring", post(handle_asc_expiring))
+        .route("/v1/office/agent_muted", post(handle_agent_muted))
+        .route("/v1/office/summarize", post(handle_summarize))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), service_auth::require_service));
+
     Router::new()
-        // Health
+        // Health and metrics
         .route("/health", get(health))
+        .route("/metrics", get(metrics))
         
         // Workspace and Deploy routes (Prompt 1: Office front-door)
         .merge(ws_router)
@@ -123,12 +171,14 @@ pub fn create_router(state: SharedState) -> Router {
         .route("/entities", get(list_entities))
         .route("/entities/:id", get(get_entity))
         .route("/entities/:id", delete(delete_entity))
+        .route("/entities/:id/replay", post(replay_entity))
 
         // Sessions
         .route("/entities/:id/sessions", post(create_session))
         .route("/entities/:id/sessions/:sid", get(get_session))
         .route("/entities/:id/sessions/:sid", delete(end_session))
         .route("/entities/:id/sessions/:sid/message", post(send_message))
+        .route("/entities/:id/sessions/:sid/export", get(export_session))
         .route("/entities/:id/sessions/:sid/handover", post(create_handover))
         .route("/entities/:id/handovers", get(list_handovers))
         .route("/entities/:id/handovers/latest", get(get_latest_handover))
@@ -157,16 +207,26 @@ pub fn create_router(state: SharedState) -> Router {
         .route("/affordances", get(list_affordances))
         .route("/affordances/:id", get(get_affordance))
 
-        // Gateway-facing endpoints
-        .route("/v1/office/ingest_message", post(ingest_message))
-        .route("/v1/office/job_action", post(handle_job_action))
+        .merge(gateway_router)
 
+        .layer(axum::middleware::from_fn(crate::observability::count_errors))
+        .layer(axum::middleware::from_fn(crate::observability::trace_context))
         .layer(cors)
         .with_state(state)
 }
 
 // ============ Health ============
 
+/// Prometheus scrape; OpenMetrics (with trace exemplars) when the scraper asks for it
+async fn metrics(headers: HeaderMap) -> impl IntoResponse {
+    let openmetrics = headers
+        .get(axum::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/openmetrics-text"));
+    let (content_type, body) = crate::observability::render_metrics(openmetrics);
+    ([(axum::http::header::CONTENT_TYPE, content_type)], body)
+}
+
 async fn health() -> impl IntoResponse {
     Json(serde_json::json!({
         "status": "healthy",
@@ -190,6 +250,9 @@ async fn create_entity(
     State(state): State<SharedState>,
     Json(req): Json<CreateEntityRequest>,
 ) -> std::result::Result<impl IntoResponse, ApiError> {
+    if req.entity_type == EntityType::Guarded && req.guardian_id.is_none() {
+        return Err(ApiError::BadRequest("guarded entities require a guardian_id".to_string()));
+    }
     let params = EntityParams {
         name: req.name,
         entity_type: req.entity_type,
@@ -243,6 +306,87 @@ async fn delete_entity(
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+struct ReplayQuery {
+    /// Overwrite local entity memory with the replayed state
+    #[serde(default)]
+    apply: bool,
+    /// Comma-separated containers; defaults to the entity container and C.Jobs
+    containers: Option<String>,
+}
+
+/// Rebuild an entity's memory from UBL history and report divergence from local state.
+///
+/// With `?apply=true` the local entity is overwritten (created if lost). Keys
+/// are not in the ledger, so a recreated entity gets a fresh identity.
+/// Handovers are not copied: the handover endpoints already read from UBL.
+async fn replay_entity(
+    State(state): State<SharedState>,
+    Path(id): Path<String>,
+    Query(query): Query<ReplayQuery>,
+) -> std::result::Result<impl IntoResponse, ApiError> {
+    use crate::entity::replay;
+
+    let (ubl_client, entity_container) = {
+        let state = state.read().await;
+        (state.ubl_client.clone(), state.config.ubl.container_id.clone())
+    };
+    let containers: Vec<String> = match query.containers.as_deref() {
+        Some(list) => list.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect(),
+        None => vec![entity_container.clone(), replay::JOBS_CONTAINER.to_string()],
+    };
+    if containers.is_empty() {
+        return Err(ApiError::BadRequest("No containers to replay".to_string()));
+    }
+
+    let (replayed, cursors) = replay::replay_entity(&ubl_client, &id, &entity_container, &containers)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Replay failed: {}", e)))?;
+
+    let mut state = state.write().await;
+    let local_handovers = state.handovers.get(&id).cloned().unwrap_or_default();
+    let divergences = replay::diverge(&replayed, state.entities.get(&id), &local_handovers);
+
+    let applied = match (&replayed.entity, query.apply) {
+        (Some(ledger_entity), true) => {
+            if !state.entities.contains_key(&id) {
+                let mut entity = Entity::new(EntityParams {
+                    name: ledger_entity.name.clone(),
+                    entity_type: ledger_entity.entity_type,
+                    guardian_id: None,
+                    constitution: None,
+                    baseline_narrative: None,
+                    metadata: None,
+                })
+                .map_err(|e| ApiError::Internal(e.to_string()))?;
+                entity.id = id.clone();
+                state.entities.insert(id.clone(), entity);
+            }
+            if let Some(entity) = state.entities.get_mut(&id) {
+                replay::restore(entity, ledger_entity);
+            }
+            true
+        }
+        _ => false,
+    };
+
+    info!(
+        "🔁 Replayed entity {}: {} events, {} divergences{}",
+        id,
+        replayed.events_applied,
+        divergences.len(),
+        if applied { ", applied" } else { "" }
+    );
+
+    Ok(Json(replay::ReplayReport {
+        entity_id: id,
+        containers: cursors,
+        state: replayed,
+        divergences,
+        applied,
+    }))
+}
+
 // ============ Sessions ============
 
 #[derive(Debug, Deserialize)]
@@ -276,7 +420,8 @@ async fn create_session(
         session_mode: mode,
     };
 
-    let mut session = Session::new(entity_id.clone(), config, req.initiator);
+    let mut session = Session::new(entity_id.clone(), config, req.initiator)
+        .with_shared_budget(state.entity_budgets.bucket_for(&entity));
 
     if let Some(budget) = req.token_budget {
         session = session.with_budget(budget);
@@ -292,6 +437,7 @@ async fn create_session(
         req.session_type,
         state.ubl_client.clone(),
     )
+    .with_affordance_service(state.affordances.clone())
     .build()
     .await?;
 
@@ -517,7 +663,7 @@ async fn send_message(
     Path((entity_id, session_id)): Path<(String, String)>,
     Json(req): Json<SendMessageRequest>,
 ) -> std::result::Result<impl IntoResponse, ApiError> {
-    let (narrative, remaining_budget, current_instance_id, llm_provider) = {
+    let (narrative, reservation, max_tokens, current_instance_id, llm_provider, ubl_client, container_id) = {
         let state_guard = state.read().await;
 
         let session = state_guard.sessions.get(&session_id)
@@ -543,28 +689,55 @@ async fn send_message(
         // Build narrative
         let narrator = Narrator::default();
         let narrative = narrator.generate(context);
+        // Held against the entity's shared bucket until the call settles
         let remaining = session.remaining_budget();
+        let reservation = session.reserve_tokens(remaining)?;
+        let max_tokens = reservation.as_ref().map_or(remaining, |r| r.tokens());
         let llm_provider = state_guard.llm_provider.clone();
 
-        (narrative, remaining, instance_id, llm_provider)
+        (
+            narrative,
+            reservation,
+            max_tokens,
+            instance_id,
+            llm_provider,
+            state_guard.ubl_client.clone(),
+            state_guard.config.ubl.container_id.clone(),
+        )
     };
 
     // Create LLM request with narrative as system instruction
     let llm_request = LlmRequest::new(vec![
-        LlmMessage::user(req.content),
+        LlmMessage::user(req.content.clone()),
     ])
     .with_system(narrative)  // Use dedicated system field for Gemini compatibility
-    .with_max_tokens(remaining_budget as u32);
+    .with_max_tokens(max_tokens as u32);
 
     // Call LLM
-    let response = llm_provider.chat(llm_request).await?;
+    let started = std::time::Instant::now();
+    let response = llm_provider.chat(llm_request).await;
+    crate::observability::record_llm_call(llm_provider.name(), "chat", started.elapsed(), response.is_ok());
+    let response = response?;
     let tokens_used = response.usage.total_tokens as u64;
 
+    // Both sides of the exchange go to the ledger for transcripts
+    for (role, content, tokens) in [
+        ("user", &req.content, response.usage.input_tokens),
+        ("assistant", &response.content, response.usage.output_tokens),
+    ] {
+        record_message(&ubl_client, &container_id, &entity_id, &session_id, role, content, tokens).await;
+    }
+
     // Update session and instance
     let remaining = {
         let mut state_guard = state.write().await;
-        if let Some(session) = state_guard.sessions.get_mut(&session_id) {
-            session.consume_tokens(tokens_used);
+        match state_guard.sessions.get_mut(&session_id) {
+            Some(session) => session.consume_reserved(reservation, tokens_used),
+            None => {
+                if let Some(reservation) = reservation {
+                    reservation.settle(tokens_used);
+                }
+            }
         }
         if let Some(instance) = state_guard.instances.get_mut(&current_instance_id) {
             instance.consume_tokens(tokens_used);
@@ -581,6 +754,94 @@ async fn send_message(
     }))
 }
 
+/// Commit one session message to the ledger, PII redacted (the ledger is
+/// forever). A failed write is logged, not surfaced: the reply already exists.
+async fn record_message(
+    ubl_client: &UblClient,
+    container_id: &str,
+    entity_id: &str,
+    session_id: &str,
+    role: &str,
+    content: &str,
+    tokens: u32,
+) {
+    let (content, _) = crate::audit::redact_text(content);
+    let event = serde_json::json!({
+        "type": crate::audit::MESSAGE_EVENT,
+        "entity_id": entity_id,
+        "session_id": session_id,
+        "trace_id": crate::observability::current_trace_id().unwrap_or_default(),
+        "role": role,
+        "content": content,
+        "tokens": tokens,
+        "ts_ms": Utc::now().timestamp_millis(),
+    });
+    if let Err(e) = ubl_client.publish_event(container_id, &event).await {
+        error!("Failed to record {} message of session {}: {}", role, session_id, e);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    /// `json` (default) or `markdown`
+    format: Option<String>,
+}
+
+/// Signed, PII-filtered transcript of a session, from the ledger
+async fn export_session(
+    State(state): State<SharedState>,
+    Path((entity_id, session_id)): Path<(String, String)>,
+    Query(query): Query<ExportQuery>,
+) -> std::result::Result<axum::response::Response, ApiError> {
+    let markdown = match query.format.as_deref() {
+        None | Some("json") => false,
+        Some("markdown") | Some("md") => true,
+        Some(other) => return Err(ApiError::BadRequest(format!("Unknown export format: {}", other))),
+    };
+
+    let ubl_client = {
+        let state = state.read().await;
+        if let Some(session) = state.sessions.get(&session_id) {
+            if session.entity_id != entity_id {
+                return Err(ApiError::NotFound("Session not found for entity".to_string()));
+            }
+        }
+        state.ubl_client.clone()
+    };
+
+    // The audit projection serves at most 500 rows per query
+    let events = ubl_client
+        .get_session_events(&session_id, 500)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Could not read session history: {}", e)))?;
+    let bundle = crate::audit::TranscriptBundle::assemble(&ubl_client, &entity_id, &session_id, events)?;
+    if bundle.items.is_empty() {
+        return Err(ApiError::NotFound(format!("No recorded history for session {}", session_id)));
+    }
+
+    info!(
+        "📜 Exported transcript of {} ({} items, {} redactions)",
+        session_id,
+        bundle.items.len(),
+        bundle.pii_policy.redactions_applied.len()
+    );
+
+    let response = if markdown {
+        let disposition = format!("attachment; filename=\"transcript-{}.md\"", session_id);
+        (
+            [
+                (axum::http::header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+                (axum::http::header::CONTENT_DISPOSITION, disposition),
+            ],
+            bundle.to_markdown(),
+        )
+            .into_response()
+    } else {
+        Json(bundle).into_response()
+    };
+    Ok(response)
+}
+
 // ============ Dreaming ============
 
 async fn trigger_dream(
@@ -707,7 +968,8 @@ async fn list_affordances(
     let state = state.read().await;
 
     if let Some(entity_id) = params.get("entity_id") {
-        let affordances = state.ubl_client.get_affordances(entity_id).await
+        let affordances = state.affordances.current(entity_id).await
+            .map(|a| a.as_ref().clone())
             .unwrap_or_default();
         Ok(Json(affordances))
     } else {
@@ -848,7 +1110,7 @@ async fn list_approvals(
     let obligations = state_read.ubl_client.get_obligations(&entity_id).await.unwrap_or_default();
     
     // Convert obligations to approval format
-    let approvals: Vec<serde_json::Value> = obligations.iter().map(|o| {
+    let mut approvals: Vec<serde_json::Value> = obligations.iter().map(|o| {
         serde_json::json!({
             "id": o.id,
             "description": o.description,
@@ -856,6 +1118,18 @@ async fn list_approvals(
             "source": o.source,
         })
     }).collect();
+
+    // Guarded entities blocked on this guardian
+    if let Some(guardian_id) = params.get("guardian_id") {
+        for approval in state_read.guardian_approvals.pending(Some(guardian_id)).await {
+            approvals.push(serde_json::json!({
+                "id": approval.request.approval_id,
+                "description": approval.request.title,
+                "source": "guardian",
+                "approval": approval,
+            }));
+        }
+    }
     
     Json(approvals)
 }
@@ -873,6 +1147,16 @@ async fn submit_approval(
     Path(approval_id): Path<String>,
     Json(req): Json<SubmitApprovalRequest>,
 ) -> std::result::Result<impl IntoResponse, ApiError> {
+    // A guarded entity waiting on its guardian records the decision itself
+    let approvals = state.read().await.guardian_approvals.clone();
+    if let Some(decision) = approvals
+        .decide(&approval_id, &req.decision, &req.decided_by, req.reason.clone())
+        .await?
+    {
+        info!("🛡️ Guardian {} {} {}", decision.decided_by, decision.decision, approval_id);
+        return Ok((StatusCode::OK, Json(decision)));
+    }
+
     let decision = job_types::ApprovalDecision {
         approval_id: approval_id.clone(),
         job_id: String::new(), // Would be looked up from approval
@@ -910,6 +1194,9 @@ struct IngestMessageRequest {
     from: String,
     content: String,
     tenant_id: String,
+    /// Client nonce of the send; stamped on what Office publishes in reply
+    #[serde(default)]
+    client_msg_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -935,11 +1222,12 @@ async fn ingest_message(
     State(state): State<SharedState>,
     Json(req): Json<IngestMessageRequest>,
 ) -> std::result::Result<impl IntoResponse, ApiError> {
-    info!("📨 Office: ingest_message conversation={} message={}", 
-          req.conversation_id, req.message_id);
+    info!("📨 Office: ingest_message conversation={} message={} client_msg_id={:?}", 
+          req.conversation_id, req.message_id, req.client_msg_id);
 
     let state_read = state.read().await;
     let ubl_client = state_read.ubl_client.clone();
+    let provenance = state_read.provenance.clone();
     drop(state_read);
 
     // 1. Build conversation context from UBL projections
@@ -1010,14 +1298,19 @@ async fn ingest_message(
                 plan_hint: None,
             };
 
+            // Only buttons on a card we issued can be clicked back
+            provenance.register_card(&card.base.card_id, &job_id, card.base.buttons.clone()).await;
+
             // 5. Emit job.created event to UBL
-            let event = serde_json::json!({
-                "type": "job.created",
-                "job_id": job_id,
-                "conversation_id": req.conversation_id,
-                "tenant_id": req.tenant_id,
-                "timestamp": Utc::now().to_rfc3339(),
-            });
+            let event = JobEvent::Created(
+                JobCreated::new(&job_id, &card.base.title)
+                    .with_conversation(&req.conversation_id)
+                    .with_tenant(&req.tenant_id)
+                    .with_goal(&req.content)
+                    .with_owner(&card.base.owner.entity_id)
+                    .with_timestamp(Utc::now().to_rfc3339()),
+            )
+            .to_atom();
             let event_id = format!("evt_{}", uuid::Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
             if let Err(e) = ubl_client.publish_event("C.Jobs", &event).await {
                 error!("Failed to publish job.created event: {}", e);
@@ -1037,13 +1330,17 @@ async fn ingest_message(
             let reply = format!("I received your message: {}", req.content);
             
             // Emit message.sent event to UBL
-            let event = serde_json::json!({
+            let mut event = serde_json::json!({
                 "type": "message.sent",
                 "conversation_id": req.conversation_id,
                 "from": "office",
                 "content": reply,
+                "in_reply_to": req.message_id,
                 "timestamp": Utc::now().to_rfc3339(),
             });
+            if let Some(client_msg_id) = &req.client_msg_id {
+                event["client_msg_id"] = serde_json::json!(client_msg_id);
+            }
             let event_id = format!("evt_{}", uuid::Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
             if let Err(e) = ubl_client.publish_event("C.Messenger", &event).await {
                 error!("Failed to publish message.sent event: {}", e);
@@ -1097,11 +1394,20 @@ async fn handle_job_action(
 
     let state_read = state.read().await;
     let ubl_client = state_read.ubl_client.clone();
+    let provenance = state_read.provenance.clone();
     drop(state_read);
 
-    // 1. Validate card provenance by querying UBL for prior card event
-    let prior_events = ubl_client.get_events(&req.job_id, 5).await.unwrap_or_default();
-    // Note: Full validation would check card_id exists in prior events
+    // 1. Validate card provenance: the button must be on a card this Office issued
+    // (the Gateway has already checked card hash + nonce against the ledger)
+    let claimed = crate::governance::claimed_action(&req.action_type, &req.job_id)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown action: {}", req.action_type)))?;
+    provenance
+        .validate_action(&req.card_id, &req.button_id, &claimed, req.input_data.clone())
+        .await
+        .map_err(|e| {
+            error!("🚫 Office: {}", e);
+            ApiError::BadRequest(e.to_string())
+        })?;
 
     // 2. Update job state via FSM
     let mut fsm = crate::job_executor::fsm::JobStateTracker::with_state(
@@ -1118,21 +1424,27 @@ async fn handle_job_action(
     match transition_result {
         Ok(_) => {
             // 3. Emit job action event to UBL
-            let event = serde_json::json!({
-                "type": format!("job.{}", req.action_type),
-                "job_id": req.job_id,
-                "action_type": req.action_type,
-                "button_id": req.button_id,
-                "card_id": req.card_id,
-                "tenant_id": req.tenant_id,
-                "timestamp": Utc::now().to_rfc3339(),
-            });
+            let action = JobAction {
+                job_id: req.job_id.clone(),
+                action_type: req.action_type.clone(),
+                button_id: req.button_id.clone(),
+                card_id: req.card_id.clone(),
+                tenant_id: Some(req.tenant_id.clone()),
+                input_data: req.input_data.clone(),
+                timestamp: Some(Utc::now().to_rfc3339()),
+            };
+            let event = JobEvent::action(&req.action_type, action)
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown action: {}", req.action_type)))?
+                .to_atom();
             let event_id = format!("evt_{}", uuid::Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
             if let Err(e) = ubl_client.publish_event("C.Jobs", &event).await {
                 error!("Failed to publish job action event: {}", e);
             }
             let event_ids = vec![event_id];
 
+            // A card answers once
+            provenance.clear_card(&req.card_id).await;
+
             Ok(Json(JobActionResponse {
                 success: true,
                 updated_card: None, // Card generation handled by separate flow
@@ -1146,38 +1458,185 @@ async fn handle_job_action(
     }
 }
 
+/// UBL-originated job events (e.g. `job.timeout` from the job monitor).
+/// The event is already in the ledger; Office only checks it against its FSM
+/// and tells the owning entity.
+async fn handle_job_event(
+    State(state): State<SharedState>,
+    Json(req): Json<JobEventNotice>,
+) -> std::result::Result<impl IntoResponse, ApiError> {
+    use crate::job_executor::fsm::{JobState, JobStateTracker, TransitionReason};
+
+    let from = JobState::from_str(&req.from_state)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown state: {}", req.from_state)))?;
+    let to = JobState::from_str(&req.to_state)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown state: {}", req.to_state)))?;
+    JobStateTracker::with_state(from)
+        .transition(to, TransitionReason::Custom(req.reason.clone()))
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+    let owner = req.owner_entity_id.as_deref().unwrap_or("unknown");
+    let state_read = state.read().await;
+    let entity_known = state_read.entities.contains_key(owner);
+    drop(state_read);
+
+    info!(
+        "📬 Office: {} job={} tenant={} owner={} {} → {} ({}) entry={}",
+        req.event_type, req.job_id, req.tenant_id, owner, req.from_state, req.to_state, req.reason, req.entry_hash
+    );
+
+    Ok(Json(serde_json::json!({
+        "accepted": true,
+        "entity_known": entity_known,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct AscExpiryNotice {
+    asc_id: String,
+    sid: String,
+    tenant_id: Option<String>,
+    template: Option<String>,
+    not_after_ms: i64,
+    expires_in_secs: i64,
+}
+
+/// UBL warns that an agent's ASC is about to expire. Renewal is a new
+/// template request (`POST /id/agents/:sid/asc/requests`) approved by a
+/// tenant admin, so Office only surfaces the warning.
+async fn handle_asc_expiring(
+    State(state): State<SharedState>,
+    Json(req): Json<AscExpiryNotice>,
+) -> std::result::Result<impl IntoResponse, ApiError> {
+    let entity_known = state.read().await.entities.contains_key(&req.sid);
+
+    warn!(
+        "🪪 Office: ASC {} for {} expires in {}s (not_after={} tenant={} template={})",
+        req.asc_id,
+        req.sid,
+        req.expires_in_secs,
+        req.not_after_ms,
+        req.tenant_id.as_deref().unwrap_or("-"),
+        req.template.as_deref().unwrap_or("-"),
+    );
+
+    Ok(Json(serde_json::json!({
+        "accepted": true,
+        "entity_known": entity_known,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentMutedNotice {
+    mute_id: String,
+    sid: String,
+    tenant_id: String,
+    conversation_id: String,
+    sends_in_window: i32,
+    window_secs: i32,
+    muted_until_ms: i64,
+}
+
+/// UBL muted an agent that flooded a conversation. The mute is already in
+/// force; Office reports it against the entity's guardian, who decides
+/// whether to stop the entity or ask a tenant admin to lift the mute.
+async fn handle_agent_muted(
+    State(state): State<SharedState>,
+    Json(req): Json<AgentMutedNotice>,
+) -> std::result::Result<impl IntoResponse, ApiError> {
+    let state_read = state.read().await;
+    let entity = state_read.entities.get(&req.sid);
+    let entity_known = entity.is_some();
+    let guardian_id = entity.and_then(|e| e.guardian_id.clone());
+    drop(state_read);
+
+    warn!(
+        "🔇 Office: {} muted until {} after {} messages in {}s in {} (tenant={} mute={} guardian={})",
+        req.sid,
+        req.muted_until_ms,
+        req.sends_in_window,
+        req.window_secs,
+        req.conversation_id,
+        req.tenant_id,
+        req.mute_id,
+        guardian_id.as_deref().unwrap_or("-"),
+    );
+
+    Ok(Json(serde_json::json!({
+        "accepted": true,
+        "entity_known": entity_known,
+        "guardian_id": guardian_id,
+    })))
+}
+
+/// UBL's summarizer asks for a summary of a conversation segment. Office
+/// runs the summarization job and returns the text; UBL commits it.
+async fn handle_summarize(
+    State(state): State<SharedState>,
+    Json(req): Json<SummarizeRequest>,
+) -> std::result::Result<impl IntoResponse, ApiError> {
+    if req.messages.is_empty() {
+        return Err(ApiError::BadRequest("Segment has no messages".to_string()));
+    }
+    let router = state.read().await.smart_router.clone();
+
+    let conversation_id = req.conversation_id.clone();
+    let count = req.messages.len();
+    let job = SummarizeJob::new(req);
+    info!("🧾 Office: summarization job {} for {} ({} messages)", job.job_id(), conversation_id, count);
+
+    let response = job.run(&router).await?;
+    Ok(Json(response))
+}
+
 // ============ Error Handling ============
 
 #[derive(Debug)]
 enum ApiError {
     NotFound(String),
     BadRequest(String),
+    Forbidden(String),
+    TooManyRequests(String),
     Internal(String),
+    /// An `OfficeError`, counted under its catalog code rather than its status
+    Office { status: StatusCode, code: ErrorCode, message: String },
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
-        let (status, message) = match self {
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        let (status, code, message) = match self {
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, None, msg),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, None, msg),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, None, msg),
+            ApiError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, None, msg),
+            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, None, msg),
+            ApiError::Office { status, code, message } => (status, Some(code), message),
         };
 
         let body = Json(serde_json::json!({
             "error": message
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Some(code) = code {
+            response.extensions_mut().insert(code);
+        }
+        response
     }
 }
 
 impl From<OfficeError> for ApiError {
     fn from(err: OfficeError) -> Self {
-        match err {
-            OfficeError::EntityNotFound(msg) => ApiError::NotFound(msg),
-            OfficeError::SessionError(msg) => ApiError::BadRequest(msg),
-            _ => ApiError::Internal(err.to_string()),
-        }
+        let code = err.error_code();
+        let (status, message) = match err {
+            OfficeError::EntityNotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            OfficeError::SessionError(msg) => (StatusCode::BAD_REQUEST, msg),
+            OfficeError::GovernanceError(msg) => (StatusCode::BAD_REQUEST, msg),
+            OfficeError::PermitDenied(msg) => (StatusCode::FORBIDDEN, msg),
+            OfficeError::BudgetExhausted(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+        };
+        ApiError::Office { status, code, message }
     }
 }
 
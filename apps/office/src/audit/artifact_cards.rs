@@ -0,0 +1,265 @@
+//! Artifact Cards - Tool results the messenger can render
+//!
+//! A tool.result carries sanitized output and hashes, which the messenger can
+//! only show as text. Results that read better as a table, a diff or an image
+//! also become `tool.artifact_card` atoms, committed right after their
+//! tool.result:
+//!
+//! - **table** — text that is a JSON array of objects
+//! - **diff** — unified diff text (files, +/- counts and the patch)
+//! - **image** — image content, uploaded to UBL's blob store; the card only
+//!   references it by `blake3:` hash
+//!
+//! Everything else stays out of the timeline. Cards are bounded (rows, patch
+//! size, image bytes) so one tool call cannot flood a conversation.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::mcp::{ResourceContent, ToolContent};
+
+/// Atom type of an artifact card
+pub const ARTIFACT_CARD_TYPE: &str = "tool.artifact_card";
+
+/// Rows kept in a table card
+pub const MAX_TABLE_ROWS: usize = 50;
+/// Columns kept in a table card
+pub const MAX_TABLE_COLUMNS: usize = 12;
+/// Characters kept per table cell
+pub const MAX_CELL_CHARS: usize = 200;
+/// Characters of patch kept in a diff card
+pub const MAX_DIFF_CHARS: usize = 20_000;
+/// Largest image uploaded (UBL's default request body limit)
+pub const MAX_IMAGE_BYTES: usize = 1024 * 1024;
+
+/// One renderable artifact of a tool result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactCard {
+    /// `acard_<tool_call_id>_<n>`, stable across retries of the commit
+    pub card_id: String,
+    pub title: String,
+    #[serde(flatten)]
+    pub body: ArtifactBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ArtifactBody {
+    Table {
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+        /// Rows in the result (`rows` holds at most [`MAX_TABLE_ROWS`])
+        total_rows: usize,
+    },
+    Diff {
+        files: Vec<String>,
+        additions: usize,
+        deletions: usize,
+        patch: String,
+        truncated: bool,
+    },
+    Image {
+        /// `blake3:<hex>` of the bytes in UBL's blob store
+        content_hash: String,
+        mime_type: String,
+        size_bytes: u64,
+    },
+}
+
+/// Image bytes that must reach the blob store before their card is committed
+#[derive(Debug, Clone)]
+pub struct PendingBlob {
+    pub content_hash: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Cards found in a tool result, plus the blobs they reference
+#[derive(Debug, Default)]
+pub struct ExtractedArtifacts {
+    pub cards: Vec<ArtifactCard>,
+    pub blobs: Vec<PendingBlob>,
+}
+
+/// `blake3:<hex>`, the blob store's content hash
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("blake3:{}", blake3::hash(bytes).to_hex())
+}
+
+/// Select the parts of a tool result worth rendering as cards
+pub fn extract(tool_call_id: &str, tool_name: &str, content: &[ToolContent]) -> ExtractedArtifacts {
+    let mut extracted = ExtractedArtifacts::default();
+    for item in content {
+        let body = match item {
+            ToolContent::Text { text } => table(text).or_else(|| diff(text)),
+            ToolContent::Image { data, mime_type } => image(data, mime_type, &mut extracted.blobs),
+            ToolContent::Resource {
+                resource: ResourceContent { mime_type: Some(mime_type), blob: Some(data), .. },
+            } if mime_type.starts_with("image/") => image(data, mime_type, &mut extracted.blobs),
+            ToolContent::Resource { resource } => resource.text.as_deref().and_then(|text| table(text).or_else(|| diff(text))),
+        };
+        if let Some(body) = body {
+            let title = match &body {
+                ArtifactBody::Table { total_rows, .. } => format!("{}: {} row(s)", tool_name, total_rows),
+                ArtifactBody::Diff { files, .. } => format!("{}: {} file(s) changed", tool_name, files.len()),
+                ArtifactBody::Image { mime_type, .. } => format!("{}: {}", tool_name, mime_type),
+            };
+            let card_id = format!("acard_{}_{}", tool_call_id, extracted.cards.len());
+            extracted.cards.push(ArtifactCard { card_id, title, body });
+        }
+    }
+    extracted
+}
+
+/// A JSON array of objects, columns in order of first appearance
+fn table(text: &str) -> Option<ArtifactBody> {
+    let value: serde_json::Value = serde_json::from_str(text.trim()).ok()?;
+    let records = value.as_array().filter(|a| !a.is_empty())?;
+    let objects: Vec<&serde_json::Map<String, serde_json::Value>> =
+        records.iter().map(|r| r.as_object()).collect::<Option<_>>()?;
+
+    let mut columns: Vec<String> = Vec::new();
+    for key in objects.iter().flat_map(|o| o.keys()) {
+        if columns.len() < MAX_TABLE_COLUMNS && !columns.contains(key) {
+            columns.push(key.clone());
+        }
+    }
+    let cell = |value: Option<&serde_json::Value>| -> String {
+        let text = match value {
+            None | Some(serde_json::Value::Null) => String::new(),
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        };
+        text.chars().take(MAX_CELL_CHARS).collect()
+    };
+    let rows = objects
+        .iter()
+        .take(MAX_TABLE_ROWS)
+        .map(|o| columns.iter().map(|c| cell(o.get(c))).collect())
+        .collect();
+
+    Some(ArtifactBody::Table { columns, rows, total_rows: objects.len() })
+}
+
+/// Unified diff: needs file headers and at least one hunk
+fn diff(text: &str) -> Option<ArtifactBody> {
+    let lines: Vec<&str> = text.lines().collect();
+    let has_headers = lines.windows(2).any(|w| w[0].starts_with("--- ") && w[1].starts_with("+++ "));
+    if !has_headers || !lines.iter().any(|l| l.starts_with("@@")) {
+        return None;
+    }
+
+    let path = |line: &str, prefix: &str| {
+        let path = line[4..].split('\t').next().unwrap_or_default().trim();
+        path.strip_prefix(prefix).unwrap_or(path).to_string()
+    };
+    let mut files = Vec::new();
+    let (mut additions, mut deletions) = (0, 0);
+    for w in lines.windows(2) {
+        if w[0].starts_with("--- ") && w[1].starts_with("+++ ") {
+            // A deleted file only names its old path
+            let file = match path(w[1], "b/") {
+                p if p == "/dev/null" => path(w[0], "a/"),
+                p => p,
+            };
+            files.push(file);
+        }
+    }
+    for line in &lines {
+        if line.starts_with('+') && !line.starts_with("+++ ") {
+            additions += 1;
+        } else if line.starts_with('-') && !line.starts_with("--- ") {
+            deletions += 1;
+        }
+    }
+
+    let truncated = text.chars().count() > MAX_DIFF_CHARS;
+    let patch = text.chars().take(MAX_DIFF_CHARS).collect();
+    Some(ArtifactBody::Diff { files, additions, deletions, patch, truncated })
+}
+
+/// Decode base64 image data and queue it for upload
+fn image(data: &str, mime_type: &str, blobs: &mut Vec<PendingBlob>) -> Option<ArtifactBody> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(data.trim()).ok()?;
+    if bytes.is_empty() || bytes.len() > MAX_IMAGE_BYTES {
+        return None;
+    }
+    let content_hash = content_hash(&bytes);
+    let size_bytes = bytes.len() as u64;
+    blobs.push(PendingBlob { content_hash: content_hash.clone(), bytes });
+    Some(ArtifactBody::Image { content_hash, mime_type: mime_type.to_string(), size_bytes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> ToolContent {
+        ToolContent::Text { text: s.to_string() }
+    }
+
+    #[test]
+    fn test_table_from_json_records() {
+        let rows: Vec<serde_json::Value> = (0..60)
+            .map(|i| serde_json::json!({ "id": i, "name": format!("row {}", i), "note": if i == 0 { serde_json::Value::Null } else { "x".repeat(300).into() } }))
+            .collect();
+        let extracted = extract("tcall_1", "db.query", &[text(&serde_json::to_string(&rows).unwrap())]);
+        assert_eq!(extracted.cards.len(), 1);
+        let card = &extracted.cards[0];
+        assert_eq!(card.card_id, "acard_tcall_1_0");
+        assert_eq!(card.title, "db.query: 60 row(s)");
+        let ArtifactBody::Table { columns, rows, total_rows } = &card.body else { panic!("not a table") };
+        assert_eq!(columns.len(), 3);
+        assert_eq!((rows.len(), *total_rows), (MAX_TABLE_ROWS, 60));
+        let note = columns.iter().position(|c| c == "note").unwrap();
+        assert_eq!(rows[0][note], "");
+        assert_eq!(rows[1][note].len(), MAX_CELL_CHARS);
+
+        // Plain text and scalar arrays are not tables
+        assert!(extract("tcall_1", "t", &[text("done"), text("[1, 2, 3]"), text("[]")]).cards.is_empty());
+    }
+
+    #[test]
+    fn test_diff_summary() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                     --- a/src/lib.rs\n\
+                     +++ b/src/lib.rs\n\
+                     @@ -1,2 +1,2 @@\n\
+                     -fn old() {}\n\
+                     +fn new() {}\n\
+                     +fn added() {}\n\
+                     --- a/README.md\n\
+                     +++ /dev/null\n\
+                     @@ -1 +0,0 @@\n\
+                     -# Gone\n";
+        let extracted = extract("tcall_2", "git.diff", &[text(patch)]);
+        let ArtifactBody::Diff { files, additions, deletions, truncated, .. } = &extracted.cards[0].body else {
+            panic!("not a diff")
+        };
+        assert_eq!(files, &["src/lib.rs", "README.md"]);
+        assert_eq!((*additions, *deletions, *truncated), (2, 2, false));
+
+        // Dashes in prose are not a diff
+        assert!(extract("tcall_2", "t", &[text("--- notes ---\n+++ ok")]).cards.is_empty());
+    }
+
+    #[test]
+    fn test_image_is_referenced_by_hash() {
+        let bytes = b"\x89PNG fake image".to_vec();
+        let data = base64::engine::general_purpose::STANDARD.encode(&bytes);
+        let content = [
+            ToolContent::Image { data: data.clone(), mime_type: "image/png".into() },
+            ToolContent::Image { data: "not base64!".into(), mime_type: "image/png".into() },
+        ];
+        let extracted = extract("tcall_3", "browser.screenshot", &content);
+        assert_eq!(extracted.cards.len(), 1);
+        assert_eq!(extracted.blobs.len(), 1);
+        assert_eq!(extracted.blobs[0].bytes, bytes);
+
+        let card = serde_json::to_value(&extracted.cards[0]).unwrap();
+        assert_eq!(card["kind"], "image");
+        assert_eq!(card["content_hash"], content_hash(&bytes));
+        assert_eq!(card["size_bytes"], bytes.len());
+        // Only the reference goes into the atom
+        assert!(!card.to_string().contains(&data));
+    }
+}
@@ -6,10 +6,14 @@
 //! "If it's not in the ledger, it didn't happen."
 
 mod tool_audit;
+pub mod artifact_cards;
 mod pii;
 mod events;
+mod transcript;
 
 pub use tool_audit::{ToolAudit, ToolCall, ToolResult, ToolError};
-pub use pii::{PiiPolicy, redact_email, redact_phone, hash_pii};
+pub use artifact_cards::{ArtifactBody, ArtifactCard, ARTIFACT_CARD_TYPE};
+pub use pii::{PiiPolicy, redact_email, redact_phone, redact_text, hash_pii};
 pub use events::{AuditEvent, AuditEventType};
+pub use transcript::{TranscriptBundle, TranscriptItem, TranscriptItemKind, MESSAGE_EVENT, TRANSCRIPT_FORMAT};
 
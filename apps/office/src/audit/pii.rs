@@ -89,6 +89,27 @@ pub fn contains_raw_pii(text: &str) -> bool {
     EMAIL_REGEX.is_match(text) || PHONE_REGEX.is_match(text)
 }
 
+/// Redact emails and phone numbers inside free text, leaving the rest intact.
+/// Returns the redacted text and how many values were redacted.
+pub fn redact_text(text: &str) -> (String, usize) {
+    let mut count = 0;
+    let text = EMAIL_REGEX.replace_all(text, |caps: &regex::Captures| {
+        count += 1;
+        redact_email(&caps[0])
+    });
+    let text = PHONE_REGEX.replace_all(&text, |caps: &regex::Captures| {
+        let matched = &caps[0];
+        // The pattern swallows trailing whitespace; keep it out of the redaction
+        let number = matched.trim_end();
+        if number.chars().filter(|c| c.is_ascii_digit()).count() < 7 {
+            return matched.to_string();
+        }
+        count += 1;
+        format!("{}{}", redact_phone(number), &matched[number.len()..])
+    });
+    (text.into_owned(), count)
+}
+
 /// Sanitize a JSON value, redacting any detected PII
 pub fn sanitize_json(value: &serde_json::Value, tenant_id: &str) -> (serde_json::Value, PiiPolicy) {
     let mut policy = PiiPolicy::new();
@@ -208,6 +229,16 @@ mod tests {
         assert!(!contains_raw_pii("Hello world"));
     }
 
+    #[test]
+    fn test_redact_text() {
+        let (text, count) = redact_text("Mail john@example.com or call +1 555 123 4567 today, ref 2024.");
+        assert_eq!(text, "Mail j***@example.com or call 15***567 today, ref 2024.");
+        assert_eq!(count, 2);
+        assert!(!contains_raw_pii(&text));
+
+        assert_eq!(redact_text("Nothing here"), ("Nothing here".to_string(), 0));
+    }
+
     #[test]
     fn test_sanitize_json() {
         let input = serde_json::json!({
@@ -3,6 +3,9 @@
 //! From the spec:
 //! > tool.called records **intent to execute** with inputs in a **safe form**
 //! > tool.result records **what happened**: success/failure, outputs, artifacts
+//!
+//! Results with tables, diffs or images are also committed as artifact cards
+//! for the messenger (see `artifact_cards`).
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -11,9 +14,13 @@ use std::time::Instant;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use tracing::warn;
+
+use crate::mcp::ToolContent;
 use crate::ubl_client::UblClient;
 use crate::{OfficeError, Result};
 
+use super::artifact_cards::{self, ArtifactCard, ARTIFACT_CARD_TYPE};
 use super::pii::PiiPolicy;
 
 /// A recorded tool call
@@ -201,8 +208,13 @@ impl ToolAudit {
         }
 
         // Build event
+        // `type`, `entity_id` and `session_id` route it into the C.Office
+        // audit log, where session transcripts pick it up
         let event = serde_json::json!({
+            "type": "audit.tool.called",
             "event_type": "tool.called",
+            "entity_id": actor_entity_id,
+            "session_id": conversation_id,
             "job_id": job_id,
             "conversation_id": conversation_id,
             "tenant_id": tenant_id,
@@ -234,6 +246,70 @@ impl ToolAudit {
         tenant_id: &str,
         actor_entity_id: &str,
         result: ToolResult,
+    ) -> Result<()> {
+        self.commit_result(job_id, conversation_id, tenant_id, actor_entity_id, result, &[]).await
+    }
+
+    /// Record a tool result and commit its tables, diffs and images as
+    /// artifact cards right after it. Images are uploaded to UBL's blob store
+    /// first; one that fails to upload loses its card, not the result.
+    pub async fn record_result_with_content(
+        &self,
+        job_id: &str,
+        conversation_id: &str,
+        tenant_id: &str,
+        actor_entity_id: &str,
+        result: ToolResult,
+        content: &[ToolContent],
+    ) -> Result<Vec<ArtifactCard>> {
+        let extracted = artifact_cards::extract(&result.tool_call_id, &result.tool_name, content);
+        let mut failed_blobs = Vec::new();
+        for blob in extracted.blobs {
+            if let Err(e) = self.ubl_client.put_blob(&blob.content_hash, blob.bytes).await {
+                warn!(tool_call_id = %result.tool_call_id, error = %e, "Artifact image upload failed, card dropped");
+                failed_blobs.push(blob.content_hash);
+            }
+        }
+        let cards: Vec<ArtifactCard> = extracted
+            .cards
+            .into_iter()
+            .filter(|card| match &card.body {
+                artifact_cards::ArtifactBody::Image { content_hash, .. } => !failed_blobs.contains(content_hash),
+                _ => true,
+            })
+            .collect();
+
+        let tool_call_id = result.tool_call_id.clone();
+        let tool_name = result.tool_name.clone();
+        self.commit_result(job_id, conversation_id, tenant_id, actor_entity_id, result, &cards).await?;
+
+        for card in &cards {
+            let event = serde_json::json!({
+                "type": ARTIFACT_CARD_TYPE,
+                "job_id": job_id,
+                "conversation_id": conversation_id,
+                "tenant_id": tenant_id,
+                "actor": {
+                    "entity_id": actor_entity_id,
+                    "actor_type": "agent"
+                },
+                "tool_call_id": tool_call_id,
+                "tool_name": tool_name,
+                "card": card,
+            });
+            self.commit_event(event).await?;
+        }
+        Ok(cards)
+    }
+
+    async fn commit_result(
+        &self,
+        job_id: &str,
+        conversation_id: &str,
+        tenant_id: &str,
+        actor_entity_id: &str,
+        result: ToolResult,
+        cards: &[ArtifactCard],
     ) -> Result<()> {
         // Remove from in-flight
         {
@@ -242,8 +318,13 @@ impl ToolAudit {
         }
 
         // Build event
+        // `type`, `entity_id` and `session_id` route it into the C.Office
+        // audit log, where session transcripts pick it up
         let event = serde_json::json!({
+            "type": "audit.tool.result",
             "event_type": "tool.result",
+            "entity_id": actor_entity_id,
+            "session_id": conversation_id,
             "job_id": job_id,
             "conversation_id": conversation_id,
             "tenant_id": tenant_id,
@@ -260,7 +341,8 @@ impl ToolAudit {
                 "artifacts": result.artifacts,
                 "error": result.error,
                 "safety": result.safety,
-                "attempt": result.attempt
+                "attempt": result.attempt,
+                "cards": cards.iter().map(|c| c.card_id.as_str()).collect::<Vec<_>>()
             }
         });
 
@@ -0,0 +1,335 @@
+//! Session Transcripts - A session's ledger history as a signed bundle
+//!
+//! Everything C.Office recorded for one session (messages, tool calls and
+//! results, governance events) is pulled from the UBL audit log, passed
+//! through the PII filters and sealed:
+//!
+//! - every item keeps its ledger receipt (`entry_hash`, `sequence`) and the
+//!   BLAKE3 hash of its canonical (Json✯Atomic) form
+//! - `content_hash` covers the item hashes in order
+//! - `signature` is the exporting Office's Ed25519 signature over the header
+//!   and `content_hash`
+//!
+//! A holder checks the bundle with [`TranscriptBundle::verify`] and each
+//! receipt against the ledger.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ubl_client::{LedgerEvent, UblClient};
+use crate::{OfficeError, Result};
+
+use super::pii::{redact_text, PiiPolicy};
+
+/// Format tag of the bundle
+pub const TRANSCRIPT_FORMAT: &str = "office.transcript.v1";
+
+/// Event type each session message is recorded as
+pub const MESSAGE_EVENT: &str = "audit.session.message";
+
+/// What a transcript item records
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptItemKind {
+    Message,
+    ToolCall,
+    ToolResult,
+    Event,
+}
+
+impl TranscriptItemKind {
+    fn of(event_type: &str) -> Self {
+        match event_type {
+            MESSAGE_EVENT => Self::Message,
+            t if t.ends_with("tool.called") => Self::ToolCall,
+            t if t.ends_with("tool.result") => Self::ToolResult,
+            _ => Self::Event,
+        }
+    }
+}
+
+/// One ledger event of the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptItem {
+    /// Ledger receipt
+    pub sequence: u64,
+    pub entry_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub kind: TranscriptItemKind,
+    pub event_type: String,
+    /// The recorded event, PII-filtered
+    pub data: Value,
+    /// BLAKE3 of the canonical item without this field
+    pub item_hash: String,
+}
+
+impl TranscriptItem {
+    fn compute_hash(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("item_hash");
+        }
+        hash_canonical(&value)
+    }
+
+    /// Message role, for message items
+    pub fn role(&self) -> Option<&str> {
+        self.data.get("role").and_then(Value::as_str)
+    }
+
+    /// Message text, for message items
+    pub fn content(&self) -> Option<&str> {
+        self.data.get("content").and_then(Value::as_str)
+    }
+}
+
+/// A session transcript, PII-filtered and signed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptBundle {
+    pub format: String,
+    pub entity_id: String,
+    pub session_id: String,
+    pub exported_at: DateTime<Utc>,
+    /// In ledger order
+    pub items: Vec<TranscriptItem>,
+    /// Redactions applied on export (paths into `items`)
+    pub pii_policy: PiiPolicy,
+    /// BLAKE3 over the item hashes in order
+    pub content_hash: String,
+    pub signer_pubkey: String,
+    /// Ed25519 over the header and `content_hash`, hex
+    pub signature: String,
+}
+
+impl TranscriptBundle {
+    /// Build and sign the transcript of `session_id` from its audit events.
+    /// Events of other entities are dropped.
+    pub fn assemble(
+        signer: &UblClient,
+        entity_id: &str,
+        session_id: &str,
+        mut events: Vec<LedgerEvent>,
+    ) -> Result<Self> {
+        events.retain(|e| e.data.get("entity_id").and_then(Value::as_str) == Some(entity_id));
+        events.sort_by_key(|e| (e.sequence, e.timestamp));
+
+        let mut pii_policy = PiiPolicy::new();
+        let mut items = Vec::with_capacity(events.len());
+        for (i, event) in events.into_iter().enumerate() {
+            let mut data = event.data;
+            redact_value(&mut data, &format!("items[{}].data", i), &mut pii_policy);
+            let mut item = TranscriptItem {
+                sequence: event.sequence,
+                entry_hash: event.entry_hash,
+                timestamp: event.timestamp,
+                kind: TranscriptItemKind::of(&event.intent_class),
+                event_type: event.intent_class,
+                data,
+                item_hash: String::new(),
+            };
+            item.item_hash = item.compute_hash()?;
+            items.push(item);
+        }
+
+        let mut bundle = Self {
+            format: TRANSCRIPT_FORMAT.to_string(),
+            entity_id: entity_id.to_string(),
+            session_id: session_id.to_string(),
+            exported_at: Utc::now(),
+            content_hash: content_hash(&items),
+            items,
+            pii_policy,
+            signer_pubkey: signer.pubkey_hex().to_string(),
+            signature: String::new(),
+        };
+        bundle.signature = signer.sign(&bundle.signed_payload()?);
+        Ok(bundle)
+    }
+
+    /// Check every item hash, the content hash and the signature
+    pub fn verify(&self) -> Result<()> {
+        for item in &self.items {
+            if item.compute_hash()? != item.item_hash {
+                return Err(OfficeError::CryptoError(format!(
+                    "Transcript item {} does not match its hash",
+                    item.sequence
+                )));
+            }
+        }
+        if content_hash(&self.items) != self.content_hash {
+            return Err(OfficeError::CryptoError("Transcript content hash mismatch".to_string()));
+        }
+
+        let key: [u8; 32] = hex::decode(&self.signer_pubkey)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| OfficeError::CryptoError("Invalid signer public key".to_string()))?;
+        let key = VerifyingKey::from_bytes(&key).map_err(|e| OfficeError::CryptoError(e.to_string()))?;
+        let signature: [u8; 64] = hex::decode(&self.signature)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| OfficeError::CryptoError("Invalid transcript signature".to_string()))?;
+        key.verify(&self.signed_payload()?, &Signature::from_bytes(&signature))
+            .map_err(|_| OfficeError::CryptoError("Transcript signature does not verify".to_string()))
+    }
+
+    fn signed_payload(&self) -> Result<Vec<u8>> {
+        ubl_atom::canonicalize(&serde_json::json!({
+            "format": self.format,
+            "entity_id": self.entity_id,
+            "session_id": self.session_id,
+            "exported_at": self.exported_at.to_rfc3339(),
+            "content_hash": self.content_hash,
+            "signer_pubkey": self.signer_pubkey,
+        }))
+        .map_err(|e| OfficeError::AuditError(format!("Canonicalize failed: {}", e)))
+    }
+
+    /// Human-readable rendering; the integrity data is listed, the JSON
+    /// bundle is what verifies
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Session {}\n", self.session_id);
+        let _ = writeln!(md, "- Entity: `{}`", self.entity_id);
+        let _ = writeln!(md, "- Exported: {}", self.exported_at.to_rfc3339());
+        let _ = writeln!(md, "- Items: {}", self.items.len());
+        let _ = writeln!(md, "- PII redactions: {}", self.pii_policy.redactions_applied.len());
+        let _ = writeln!(md, "- Content hash: `{}`", self.content_hash);
+        let _ = writeln!(md, "- Signed by: `{}`", self.signer_pubkey);
+        let _ = writeln!(md, "- Signature: `{}`", self.signature);
+
+        for item in &self.items {
+            let time = item.timestamp.format("%Y-%m-%d %H:%M:%S UTC");
+            match (item.kind, item.role(), item.content()) {
+                (TranscriptItemKind::Message, Some(role), Some(content)) => {
+                    let _ = writeln!(md, "\n## #{} · {} · {}\n\n{}", item.sequence, time, role, content);
+                }
+                _ => {
+                    let data = serde_json::to_string_pretty(&item.data).unwrap_or_default();
+                    let _ = writeln!(md, "\n## #{} · {} · {}\n\n```json\n{}\n```", item.sequence, time, item.event_type, data);
+                }
+            }
+            let _ = writeln!(md, "\n<sub>entry `{}` · item `{}`</sub>", item.entry_hash, item.item_hash);
+        }
+        md
+    }
+}
+
+fn hash_canonical(value: &Value) -> Result<String> {
+    let canonical = ubl_atom::canonicalize(value)
+        .map_err(|e| OfficeError::AuditError(format!("Canonicalize failed: {}", e)))?;
+    Ok(format!("blake3:{}", blake3::hash(&canonical).to_hex()))
+}
+
+fn content_hash(items: &[TranscriptItem]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for item in items {
+        hasher.update(item.item_hash.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("blake3:{}", hasher.finalize().to_hex())
+}
+
+/// Redact PII inside every string, recording the paths touched
+fn redact_value(value: &mut Value, path: &str, policy: &mut PiiPolicy) {
+    match value {
+        Value::String(s) => {
+            let (redacted, count) = redact_text(s);
+            if count > 0 {
+                *s = redacted;
+                policy.redactions_applied.push(path.to_string());
+            }
+        }
+        Value::Object(map) => {
+            for (key, val) in map.iter_mut() {
+                redact_value(val, &format!("{}.{}", path, key), policy);
+            }
+        }
+        Value::Array(arr) => {
+            for (i, val) in arr.iter_mut().enumerate() {
+                redact_value(val, &format!("{}[{}]", path, i), policy);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(sequence: u64, event_type: &str, data: Value) -> LedgerEvent {
+        LedgerEvent {
+            entry_hash: format!("hash_{}", sequence),
+            sequence,
+            intent_class: event_type.to_string(),
+            timestamp: DateTime::from_timestamp_millis(1_700_000_000_000 + sequence as i64).unwrap(),
+            summary: event_type.to_string(),
+            data,
+            author_pubkey: String::new(),
+        }
+    }
+
+    fn session_events() -> Vec<LedgerEvent> {
+        vec![
+            event(7, "audit.tool.called", json!({
+                "type": "audit.tool.called", "entity_id": "e1", "session_id": "s1",
+                "tool_name": "send_email", "inputs": { "to": "maria@acme.com" }
+            })),
+            event(5, MESSAGE_EVENT, json!({
+                "type": MESSAGE_EVENT, "entity_id": "e1", "session_id": "s1",
+                "role": "user", "content": "Email maria@acme.com the report"
+            })),
+            event(6, "audit.other", json!({ "entity_id": "e2", "session_id": "s1" })),
+        ]
+    }
+
+    #[test]
+    fn test_assemble_orders_filters_and_redacts() {
+        let signer = UblClient::with_generated_key("http://127.0.0.1:9", "office", 200);
+        let bundle = TranscriptBundle::assemble(&signer, "e1", "s1", session_events()).unwrap();
+
+        // Ledger order, other entities dropped
+        assert_eq!(bundle.items.iter().map(|i| i.sequence).collect::<Vec<_>>(), vec![5, 7]);
+        assert_eq!(bundle.items[0].kind, TranscriptItemKind::Message);
+        assert_eq!(bundle.items[0].content(), Some("Email m***@acme.com the report"));
+        assert_eq!(bundle.items[1].kind, TranscriptItemKind::ToolCall);
+        assert_eq!(bundle.items[1].data["inputs"]["to"], "m***@acme.com");
+        assert_eq!(
+            bundle.pii_policy.redactions_applied,
+            vec!["items[0].data.content", "items[1].data.inputs.to"]
+        );
+        assert!(!serde_json::to_string(&bundle).unwrap().contains("maria@"));
+
+        let md = bundle.to_markdown();
+        assert!(md.contains("Email m***@acme.com the report"));
+        assert!(md.contains(&bundle.content_hash));
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let signer = UblClient::with_generated_key("http://127.0.0.1:9", "office", 200);
+        let bundle = TranscriptBundle::assemble(&signer, "e1", "s1", session_events()).unwrap();
+
+        // Survives a JSON round trip
+        let parsed: TranscriptBundle = serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+        parsed.verify().unwrap();
+
+        let mut edited = parsed.clone();
+        edited.items[0].data["content"] = json!("Something else");
+        assert!(edited.verify().is_err());
+
+        let mut dropped = parsed.clone();
+        dropped.items.pop();
+        assert!(dropped.verify().is_err());
+
+        let mut resigned = parsed;
+        resigned.session_id = "s2".to_string();
+        assert!(resigned.verify().is_err());
+    }
+}
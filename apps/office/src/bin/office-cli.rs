@@ -0,0 +1,403 @@
+//! Office CLI
+//!
+//! Drives a running Office over its HTTP API, without the web UI: create
+//! entities, start sessions, send messages, watch jobs. Every command prints
+//! a short human summary, or the raw JSON response with `--json` (one
+//! document per line) for scripts. `chat` is the interactive mode; it also
+//! reads piped stdin, one message per line.
+//!
+//! Usage: office-cli [--url <office_url>] [--json] <command> [args]
+//! The URL defaults to $OFFICE_URL, then http://127.0.0.1:8080.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const USAGE: &str = "\
+Usage: office-cli [--url <office_url>] [--json] <command> [args]
+
+Commands:
+  health                                   Office health
+  entity create <name> [--type autonomous|guarded|development] [--guardian <id>]
+  entity list
+  entity get <entity_id>
+  session start <entity_id> [--type work|assist|deliberate|research] [--initiator <id>] [--budget <tokens>]
+  session get <entity_id> <session_id>
+  session end <entity_id> <session_id>
+  send <entity_id> <session_id> <message...>
+  chat <entity_id> [--type <session_type>] [--initiator <id>]
+                                           Interactive session; /quit or EOF ends it
+  job status <job_id>
+  job watch <job_id> [--interval <secs>]   Print the job's ledger state as it moves
+
+Options:
+  --url <office_url>   Office base URL (default: $OFFICE_URL or http://127.0.0.1:8080)
+  --json               Print raw JSON responses, one per line
+";
+
+// ============================================================================
+// ARGUMENTS
+// ============================================================================
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Health,
+    EntityCreate { name: String, entity_type: String, guardian_id: Option<String> },
+    EntityList,
+    EntityGet { entity_id: String },
+    SessionStart { entity_id: String, session: SessionOptions },
+    SessionGet { entity_id: String, session_id: String },
+    SessionEnd { entity_id: String, session_id: String },
+    Send { entity_id: String, session_id: String, content: String },
+    Chat { entity_id: String, session: SessionOptions },
+    JobStatus { job_id: String },
+    JobWatch { job_id: String, interval_secs: u64 },
+}
+
+#[derive(Debug, PartialEq)]
+struct SessionOptions {
+    session_type: String,
+    initiator: String,
+    token_budget: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Cli {
+    url: String,
+    json: bool,
+    command: Command,
+}
+
+/// Options that take a value; everything else starting with `--` is a switch
+const VALUE_OPTIONS: &[&str] = &["--url", "--type", "--guardian", "--initiator", "--budget", "--interval"];
+
+fn parse_args(args: &[String], default_url: &str) -> std::result::Result<Cli, String> {
+    let mut positional: Vec<&str> = Vec::new();
+    let mut options: Vec<(&str, &str)> = Vec::new();
+    let mut json = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--help" | "-h" => return Err(String::new()),
+            opt if VALUE_OPTIONS.contains(&opt) => {
+                let value = iter.next().ok_or_else(|| format!("{} needs a value", opt))?;
+                options.push((opt, value));
+            }
+            opt if opt.starts_with("--") => return Err(format!("Unknown option {}", opt)),
+            _ => positional.push(arg),
+        }
+    }
+    let option = |name: &str| options.iter().rev().find(|(k, _)| *k == name).map(|(_, v)| v.to_string());
+    let number = |name: &str| -> std::result::Result<Option<u64>, String> {
+        option(name).map(|v| v.parse().map_err(|_| format!("{} must be a number", name))).transpose()
+    };
+    let session = || -> std::result::Result<SessionOptions, String> {
+        Ok(SessionOptions {
+            session_type: option("--type").unwrap_or_else(|| "work".to_string()),
+            initiator: option("--initiator").unwrap_or_else(|| "office-cli".to_string()),
+            token_budget: number("--budget")?,
+        })
+    };
+    let owned = |s: &&str| s.to_string();
+
+    let command = match positional.as_slice() {
+        ["health"] => Command::Health,
+        ["entity", "create", name] => Command::EntityCreate {
+            name: name.to_string(),
+            entity_type: option("--type").unwrap_or_else(|| "development".to_string()),
+            guardian_id: option("--guardian"),
+        },
+        ["entity", "list"] => Command::EntityList,
+        ["entity", "get", id] => Command::EntityGet { entity_id: owned(id) },
+        ["session", "start", id] => Command::SessionStart { entity_id: owned(id), session: session()? },
+        ["session", "get", id, sid] => Command::SessionGet { entity_id: owned(id), session_id: owned(sid) },
+        ["session", "end", id, sid] => Command::SessionEnd { entity_id: owned(id), session_id: owned(sid) },
+        ["send", id, sid, words @ ..] if !words.is_empty() => Command::Send {
+            entity_id: owned(id),
+            session_id: owned(sid),
+            content: words.join(" "),
+        },
+        ["chat", id] => Command::Chat { entity_id: owned(id), session: session()? },
+        ["job", "status", job] => Command::JobStatus { job_id: owned(job) },
+        ["job", "watch", job] => Command::JobWatch {
+            job_id: owned(job),
+            interval_secs: number("--interval")?.unwrap_or(2).max(1),
+        },
+        [] => return Err(String::new()),
+        other => return Err(format!("Unknown command: {}", other.join(" "))),
+    };
+
+    Ok(Cli {
+        url: option("--url").unwrap_or_else(|| default_url.to_string()).trim_end_matches('/').to_string(),
+        json,
+        command,
+    })
+}
+
+// ============================================================================
+// CLIENT
+// ============================================================================
+
+/// Minimal client for the Office HTTP API
+struct OfficeApi {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl OfficeApi {
+    fn new(base_url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .expect("Failed to create HTTP client");
+        Self { base_url, client }
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        self.send(self.client.get(format!("{}{}", self.base_url, path))).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        self.send(self.client.post(format!("{}{}", self.base_url, path)).json(&body)).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<Value> {
+        self.send(self.client.delete(format!("{}{}", self.base_url, path))).await
+    }
+
+    /// Office errors come back as `{"error": "..."}`
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value> {
+        let response = request.send().await.map_err(|e| anyhow!("Office unreachable at {}: {}", self.base_url, e))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body["error"].as_str().map(str::to_string).unwrap_or_else(|| body.to_string());
+            bail!("{} ({})", message, status);
+        }
+        Ok(body)
+    }
+}
+
+// ============================================================================
+// OUTPUT
+// ============================================================================
+
+fn field<'a>(v: &'a Value, key: &str) -> &'a str {
+    v[key].as_str().unwrap_or("-")
+}
+
+fn print_entity(e: &Value) {
+    println!("{}  {}  {}  {}", field(e, "id"), field(e, "name"), field(e, "entity_type"), field(e, "status"));
+}
+
+fn print_session(s: &Value) {
+    println!(
+        "session {}  {} / {}  {}  tokens {}/{}",
+        field(s, "id"),
+        field(s, "session_type"),
+        field(s, "session_mode"),
+        field(s, "status"),
+        s["tokens_consumed"],
+        s["token_budget"]
+    );
+}
+
+/// `--json` prints the document as one line; otherwise `human` summarizes it
+fn emit(json: bool, value: &Value, human: impl FnOnce(&Value)) {
+    if json {
+        println!("{}", value);
+    } else {
+        human(value);
+    }
+}
+
+// ============================================================================
+// COMMANDS
+// ============================================================================
+
+async fn start_session(api: &OfficeApi, entity_id: &str, session: &SessionOptions) -> Result<Value> {
+    let mut body = json!({
+        "session_type": session.session_type,
+        "initiator": session.initiator,
+    });
+    if let Some(budget) = session.token_budget {
+        body["token_budget"] = json!(budget);
+    }
+    api.post(&format!("/entities/{}/sessions", entity_id), body).await
+}
+
+/// Interactive session: one message per stdin line until `/quit` or EOF
+async fn chat(api: &OfficeApi, json: bool, entity_id: &str, session: &SessionOptions) -> Result<()> {
+    let started = start_session(api, entity_id, session).await?;
+    let session_id = started["session"]["id"].as_str().ok_or_else(|| anyhow!("Office returned no session id"))?.to_string();
+    emit(json, &started, |s| {
+        print_session(&s["session"]);
+        println!("Type a message, /quit to end the session.");
+    });
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        if !json {
+            print!("> ");
+            std::io::stdout().flush()?;
+        }
+        let Some(line) = lines.next_line().await? else { break };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == "/quit" {
+            break;
+        }
+        match api.post(&format!("/entities/{}/sessions/{}/message", entity_id, session_id), json!({ "content": line })).await {
+            Ok(reply) => emit(json, &reply, |r| {
+                println!("{}", field(r, "response"));
+                println!("  ({} tokens, {} left)", r["tokens_used"], r["session_remaining"]);
+            }),
+            Err(e) => eprintln!("❌ {}", e),
+        }
+    }
+
+    let ended = api.delete(&format!("/entities/{}/sessions/{}", entity_id, session_id)).await?;
+    emit(json, &ended, |_| println!("Session {} ended", session_id));
+    Ok(())
+}
+
+/// Poll the job's ledger state, printing each change
+async fn watch_job(api: &OfficeApi, json: bool, job_id: &str, interval_secs: u64) -> Result<()> {
+    let mut last: Option<Value> = None;
+    let mut tick = tokio::time::interval(Duration::from_secs(interval_secs));
+    loop {
+        tick.tick().await;
+        let status = api.get(&format!("/jobs/{}/status", job_id)).await?;
+        if last.as_ref() != Some(&status) {
+            emit(json, &status, |s| println!("{}  seq {}  {}", job_id, s["sequence"], field(s, "last_hash")));
+            last = Some(status);
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let api = OfficeApi::new(cli.url);
+    let json = cli.json;
+
+    match cli.command {
+        Command::Health => {
+            let health = api.get("/health").await?;
+            emit(json, &health, |h| println!("{} {} {}", field(h, "service"), field(h, "version"), field(h, "status")));
+        }
+        Command::EntityCreate { name, entity_type, guardian_id } => {
+            let entity = api
+                .post("/entities", json!({ "name": name, "entity_type": entity_type, "guardian_id": guardian_id }))
+                .await?;
+            emit(json, &entity, print_entity);
+        }
+        Command::EntityList => {
+            let entities = api.get("/entities").await?;
+            emit(json, &entities, |list| list.as_array().into_iter().flatten().for_each(print_entity));
+        }
+        Command::EntityGet { entity_id } => {
+            let entity = api.get(&format!("/entities/{}", entity_id)).await?;
+            emit(json, &entity, print_entity);
+        }
+        Command::SessionStart { entity_id, session } => {
+            let started = start_session(&api, &entity_id, &session).await?;
+            emit(json, &started, |s| print_session(&s["session"]));
+        }
+        Command::SessionGet { entity_id, session_id } => {
+            let session = api.get(&format!("/entities/{}/sessions/{}", entity_id, session_id)).await?;
+            emit(json, &session, print_session);
+        }
+        Command::SessionEnd { entity_id, session_id } => {
+            let ended = api.delete(&format!("/entities/{}/sessions/{}", entity_id, session_id)).await?;
+            emit(json, &ended, |_| println!("Session {} ended", session_id));
+        }
+        Command::Send { entity_id, session_id, content } => {
+            let reply = api
+                .post(&format!("/entities/{}/sessions/{}/message", entity_id, session_id), json!({ "content": content }))
+                .await?;
+            emit(json, &reply, |r| println!("{}", field(r, "response")));
+        }
+        Command::Chat { entity_id, session } => chat(&api, json, &entity_id, &session).await?,
+        Command::JobStatus { job_id } => {
+            let status = api.get(&format!("/jobs/{}/status", job_id)).await?;
+            emit(json, &status, |s| println!("{}  seq {}  {}", job_id, s["sequence"], field(s, "last_hash")));
+        }
+        Command::JobWatch { job_id, interval_secs } => watch_job(&api, json, &job_id, interval_secs).await?,
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let default_url = std::env::var("OFFICE_URL").unwrap_or_else(|_| "http://127.0.0.1:8080".to_string());
+
+    let cli = match parse_args(&args, &default_url) {
+        Ok(cli) => cli,
+        Err(e) => {
+            if e.is_empty() {
+                print!("{}", USAGE);
+                std::process::exit(0);
+            }
+            eprintln!("❌ {}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(cli).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> std::result::Result<Cli, String> {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        parse_args(&args, "http://office:8080/")
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        let cli = parse("--json send ent_1 sess_1 hello there").unwrap();
+        assert!(cli.json);
+        assert_eq!(cli.url, "http://office:8080");
+        assert_eq!(
+            cli.command,
+            Command::Send { entity_id: "ent_1".into(), session_id: "sess_1".into(), content: "hello there".into() }
+        );
+
+        let cli = parse("session start ent_1 --type assist --budget 900 --url http://x").unwrap();
+        assert_eq!(cli.url, "http://x");
+        assert_eq!(
+            cli.command,
+            Command::SessionStart {
+                entity_id: "ent_1".into(),
+                session: SessionOptions { session_type: "assist".into(), initiator: "office-cli".into(), token_budget: Some(900) },
+            }
+        );
+
+        assert_eq!(
+            parse("job watch job_1 --interval 0").unwrap().command,
+            Command::JobWatch { job_id: "job_1".into(), interval_secs: 1 }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("send ent_1 sess_1").is_err());
+        assert!(parse("entity create").is_err());
+        assert!(parse("session start ent_1 --budget lots").is_err());
+        assert!(parse("health --verbose").is_err());
+        // No command and --help both mean "print usage"
+        assert_eq!(parse("").unwrap_err(), "");
+        assert_eq!(parse("health --help").unwrap_err(), "");
+    }
+}
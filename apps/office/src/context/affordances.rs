@@ -0,0 +1,336 @@
+//! Affordance Service
+//!
+//! Derives what an entity can do from its capability profile instead of a
+//! fixed list:
+//!
+//! - **ASC scopes**: chat and job affordances only appear when one of the
+//!   entity's ASCs lets it commit to C.Messenger / C.Jobs;
+//! - **Registered tools**: one affordance per tool, with its input schema;
+//! - **Obligations**: one affordance per pending obligation.
+//!
+//! Escalation to the guardian is always available.
+//!
+//! Results are cached per entity and keyed by what a context-frame build
+//! observes (ledger sequence and obligation ids), so rebuilding a frame for
+//! an unchanged entity does not refetch its ASCs.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::asc::AscScopes;
+use crate::entity::EntityId;
+use crate::mcp::UnifiedToolRegistry;
+use crate::ubl_client::{UblAffordance, UblClient, UblObligation};
+use crate::Result;
+
+/// Container of conversation messages
+const MESSENGER_CONTAINER: &str = "C.Messenger";
+/// Container of job cards
+const JOBS_CONTAINER: &str = "C.Jobs";
+
+/// Risk of calling an in-process Office tool
+const NATIVE_TOOL_RISK: f32 = 0.3;
+/// Risk of calling a tool on an external MCP server
+const EXTERNAL_TOOL_RISK: f32 = 0.6;
+
+// ============ Capability Profile ============
+
+/// A registered tool, as far as affordances are concerned
+#[derive(Debug, Clone)]
+pub struct ToolCapability {
+    /// Full name with server prefix (e.g. "office:ubl_query")
+    pub name: String,
+    pub description: Option<String>,
+    pub input_schema: Option<serde_json::Value>,
+    pub is_native: bool,
+}
+
+/// Everything affordances are derived from
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityProfile {
+    /// Scopes of every ASC issued to the entity
+    pub scopes: Vec<AscScopes>,
+    pub tools: Vec<ToolCapability>,
+    pub obligations: Vec<UblObligation>,
+}
+
+impl CapabilityProfile {
+    /// Whether any ASC allows committing `intent_class` to `container`
+    /// (empty lists are unrestricted, as in the kernel's scope check)
+    pub fn permits(&self, container: &str, intent_class: &str) -> bool {
+        self.scopes.iter().any(|s| {
+            (s.containers.is_empty() || s.containers.iter().any(|c| c == container))
+                && (s.intent_classes.is_empty() || s.intent_classes.iter().any(|i| i == intent_class))
+        })
+    }
+
+    /// Affordances in a stable order: scoped actions, obligations (highest
+    /// priority first), tools (by name), escalation
+    pub fn affordances(&self) -> Vec<UblAffordance> {
+        let mut out = Vec::new();
+
+        if self.permits(MESSENGER_CONTAINER, "Observation") {
+            out.push(affordance("chat_reply", "Reply in Chat", "Send a message in the conversation", 0.1, None));
+        }
+        if self.permits(JOBS_CONTAINER, "Observation") {
+            out.push(affordance("propose_job", "Propose a Job", "Create a job card for user approval", 0.3, None));
+        }
+
+        let mut obligations: Vec<&UblObligation> = self.obligations.iter().collect();
+        obligations.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+        for o in obligations {
+            out.push(affordance(
+                &format!("fulfil:{}", o.id),
+                &format!("Fulfil obligation {}", o.id),
+                &o.description,
+                0.2,
+                None,
+            ));
+        }
+
+        let mut tools: Vec<&ToolCapability> = self.tools.iter().collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        for t in tools {
+            out.push(affordance(
+                &format!("tool:{}", t.name),
+                &format!("Call {}", t.name),
+                t.description.as_deref().unwrap_or("Registered tool"),
+                if t.is_native { NATIVE_TOOL_RISK } else { EXTERNAL_TOOL_RISK },
+                t.input_schema.clone(),
+            ));
+        }
+
+        out.push(affordance(
+            "escalate",
+            "Escalate to Guardian",
+            "Request human oversight for complex decision",
+            0.0,
+            None,
+        ));
+        out
+    }
+}
+
+fn affordance(id: &str, name: &str, description: &str, risk_score: f32, parameters: Option<serde_json::Value>) -> UblAffordance {
+    UblAffordance {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        risk_score,
+        parameters,
+    }
+}
+
+// ============ Cache ============
+
+/// What a frame build observed; a different key means a rebuild
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AffordanceKey {
+    pub ledger_sequence: u64,
+    pub obligation_ids: Vec<String>,
+}
+
+impl AffordanceKey {
+    pub fn new(ledger_sequence: u64, obligations: &[UblObligation]) -> Self {
+        let mut obligation_ids: Vec<String> = obligations.iter().map(|o| o.id.clone()).collect();
+        obligation_ids.sort();
+        Self { ledger_sequence, obligation_ids }
+    }
+}
+
+/// Latest affordances per entity
+#[derive(Default)]
+pub struct AffordanceCache {
+    entries: RwLock<HashMap<EntityId, (AffordanceKey, Arc<Vec<UblAffordance>>)>>,
+}
+
+impl AffordanceCache {
+    pub async fn get(&self, entity_id: &EntityId, key: &AffordanceKey) -> Option<Arc<Vec<UblAffordance>>> {
+        let entries = self.entries.read().await;
+        entries
+            .get(entity_id)
+            .filter(|(cached, _)| cached == key)
+            .map(|(_, affordances)| affordances.clone())
+    }
+
+    /// Replaces whatever was cached for the entity
+    pub async fn put(&self, entity_id: &EntityId, key: AffordanceKey, affordances: Arc<Vec<UblAffordance>>) {
+        self.entries.write().await.insert(entity_id.clone(), (key, affordances));
+    }
+}
+
+// ============ Service ============
+
+/// Builds and caches capability-derived affordances
+pub struct AffordanceService {
+    ubl_client: Arc<UblClient>,
+    tools: Option<Arc<UnifiedToolRegistry>>,
+    cache: AffordanceCache,
+}
+
+impl AffordanceService {
+    pub fn new(ubl_client: Arc<UblClient>) -> Self {
+        Self {
+            ubl_client,
+            tools: None,
+            cache: AffordanceCache::default(),
+        }
+    }
+
+    /// Offer one affordance per tool in `registry`
+    pub fn with_tools(mut self, registry: Arc<UnifiedToolRegistry>) -> Self {
+        self.tools = Some(registry);
+        self
+    }
+
+    /// Affordances for an entity whose frame is being built at
+    /// `ledger_sequence` with `obligations`
+    pub async fn affordances_for(
+        &self,
+        entity_id: &EntityId,
+        ledger_sequence: u64,
+        obligations: &[UblObligation],
+    ) -> Result<Arc<Vec<UblAffordance>>> {
+        let key = AffordanceKey::new(ledger_sequence, obligations);
+        if let Some(cached) = self.cache.get(entity_id, &key).await {
+            return Ok(cached);
+        }
+
+        let profile = CapabilityProfile {
+            scopes: self.ubl_client.list_asc_scopes(entity_id).await?,
+            tools: self.tool_capabilities().await,
+            obligations: obligations.to_vec(),
+        };
+        let affordances = Arc::new(profile.affordances());
+        debug!(
+            "🧭 Affordances for {}: {} derived at seq {}",
+            entity_id, affordances.len(), ledger_sequence
+        );
+        self.cache.put(entity_id, key, affordances.clone()).await;
+        Ok(affordances)
+    }
+
+    /// Fetches ledger state and obligations first (outside a frame build)
+    pub async fn current(&self, entity_id: &EntityId) -> Result<Arc<Vec<UblAffordance>>> {
+        let state = self.ubl_client.get_state(entity_id).await?;
+        let obligations = self.ubl_client.get_obligations(entity_id).await.unwrap_or_default();
+        self.affordances_for(entity_id, state.sequence, &obligations).await
+    }
+
+    async fn tool_capabilities(&self) -> Vec<ToolCapability> {
+        let Some(registry) = &self.tools else {
+            return Vec::new();
+        };
+        registry
+            .all_tools()
+            .await
+            .into_iter()
+            .map(|t| ToolCapability {
+                name: t.full_name,
+                description: t.tool.description,
+                input_schema: serde_json::to_value(&t.tool.input_schema).ok(),
+                is_native: t.is_native,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scopes(containers: &[&str], intent_classes: &[&str]) -> AscScopes {
+        AscScopes {
+            containers: containers.iter().map(|s| s.to_string()).collect(),
+            intent_classes: intent_classes.iter().map(|s| s.to_string()).collect(),
+            max_delta: None,
+        }
+    }
+
+    fn obligation(id: &str, priority: u8) -> UblObligation {
+        UblObligation {
+            id: id.to_string(),
+            description: format!("do {}", id),
+            due_at: None,
+            priority,
+            source: "C.Jobs".to_string(),
+        }
+    }
+
+    fn ids(profile: &CapabilityProfile) -> Vec<String> {
+        profile.affordances().into_iter().map(|a| a.id).collect()
+    }
+
+    #[test]
+    fn test_no_asc_only_escalates() {
+        assert_eq!(ids(&CapabilityProfile::default()), vec!["escalate"]);
+    }
+
+    #[test]
+    fn test_scopes_gate_affordances() {
+        let messenger_only = CapabilityProfile {
+            scopes: vec![scopes(&["C.Messenger"], &["Observation"])],
+            ..Default::default()
+        };
+        assert_eq!(ids(&messenger_only), vec!["chat_reply", "escalate"]);
+
+        // Wrong intent class grants nothing
+        let conservation = CapabilityProfile {
+            scopes: vec![scopes(&["C.Messenger", "C.Jobs"], &["Conservation"])],
+            ..Default::default()
+        };
+        assert_eq!(ids(&conservation), vec!["escalate"]);
+
+        // Empty lists are unrestricted
+        let unrestricted = CapabilityProfile { scopes: vec![scopes(&[], &[])], ..Default::default() };
+        assert_eq!(ids(&unrestricted), vec!["chat_reply", "propose_job", "escalate"]);
+    }
+
+    #[test]
+    fn test_tools_and_obligations() {
+        let profile = CapabilityProfile {
+            scopes: vec![],
+            tools: vec![
+                ToolCapability {
+                    name: "github:open_pr".into(),
+                    description: None,
+                    input_schema: Some(serde_json::json!({"type": "object"})),
+                    is_native: false,
+                },
+                ToolCapability {
+                    name: "office:ubl_query".into(),
+                    description: Some("Query the ledger".into()),
+                    input_schema: None,
+                    is_native: true,
+                },
+            ],
+            obligations: vec![obligation("job-low", 3), obligation("job-high", 9)],
+        };
+        let affordances = profile.affordances();
+        let ids: Vec<&str> = affordances.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["fulfil:job-high", "fulfil:job-low", "tool:github:open_pr", "tool:office:ubl_query", "escalate"]
+        );
+        assert_eq!(affordances[2].risk_score, EXTERNAL_TOOL_RISK);
+        assert_eq!(affordances[2].parameters, Some(serde_json::json!({"type": "object"})));
+        assert_eq!(affordances[3].description, "Query the ledger");
+    }
+
+    #[tokio::test]
+    async fn test_cache_keyed_by_frame_inputs() {
+        let cache = AffordanceCache::default();
+        let entity: EntityId = "entity-1".into();
+        let key = AffordanceKey::new(7, &[obligation("b", 1), obligation("a", 1)]);
+        cache.put(&entity, key.clone(), Arc::new(CapabilityProfile::default().affordances())).await;
+
+        // Obligation order does not matter
+        assert!(cache.get(&entity, &AffordanceKey::new(7, &[obligation("a", 1), obligation("b", 1)])).await.is_some());
+        assert!(cache.get(&entity, &AffordanceKey::new(8, &[obligation("a", 1), obligation("b", 1)])).await.is_none());
+        assert!(cache.get(&entity, &AffordanceKey::new(7, &[])).await.is_none());
+        assert!(cache.get(&"entity-2".into(), &key).await.is_none());
+    }
+}
@@ -0,0 +1,588 @@
+//! Context Budget - Token accounting for prompts
+//!
+//! Measures every section of a prompt (narrative, memory, handover,
+//! conversation) against what the model can take. Sections over budget are
+//! split into chunks and summarized map-reduce style: each chunk is
+//! summarized, and the joined summaries are reduced again until they fit.
+//! Without a summarizer, or when summarizing fails, the oldest text is cut
+//! instead.
+//!
+//! Every reduction is recorded as an [`Elision`]. Elisions travel on the
+//! context frame, so the narrator can tell the instance what it is not seeing
+//! and the sanity check can account for records that were condensed away.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::llm::{LlmMessage, LlmRequest, RoutingPreferences, SmartRouter, TaskType};
+use crate::Result;
+
+use super::memory::{HistoricalSynthesis, Memory};
+
+/// Rough estimate used across Office: 4 characters per token
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Smallest summary a chunk is asked to fit in
+const MIN_SUMMARY_TOKENS: u64 = 64;
+
+/// Recent events always kept verbatim when memory is condensed
+const KEEP_RECENT_EVENTS: usize = 5;
+
+/// Marker left where truncation removed text
+const TRUNCATION_MARKER: &str = "[... earlier content elided to fit the context window ...]";
+
+/// Estimated token count of a text
+pub fn estimate_tokens(text: &str) -> u64 {
+    text.len().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Budget configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Prompt tokens available (context window minus the reply)
+    pub max_tokens: u64,
+    /// Largest chunk handed to the summarizer
+    pub chunk_tokens: u64,
+    /// Longest summary requested per chunk
+    pub summary_tokens: u64,
+    /// Reduce rounds before the result is cut to fit
+    pub max_reduce_rounds: u32,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 32_000,
+            chunk_tokens: 4_000,
+            summary_tokens: 400,
+            max_reduce_rounds: 3,
+        }
+    }
+}
+
+impl BudgetConfig {
+    /// Budget for a model window, leaving room for `reply_tokens`
+    pub fn for_window(context_window: u32, reply_tokens: u32) -> Self {
+        Self {
+            max_tokens: context_window.saturating_sub(reply_tokens) as u64,
+            ..Default::default()
+        }
+    }
+}
+
+/// One named part of a prompt
+#[derive(Debug, Clone)]
+pub struct ContextSection {
+    pub name: String,
+    pub text: String,
+    /// Whether the budgeter may condense it
+    pub elidable: bool,
+}
+
+impl ContextSection {
+    /// A section that is always sent verbatim
+    pub fn required(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { name: name.into(), text: text.into(), elidable: false }
+    }
+
+    /// A section the budgeter may summarize or cut
+    pub fn elidable(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { name: name.into(), text: text.into(), elidable: true }
+    }
+
+    pub fn tokens(&self) -> u64 {
+        estimate_tokens(&self.text)
+    }
+}
+
+/// How a section was reduced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElisionStrategy {
+    /// Replaced by a map-reduce summary
+    Summarized,
+    /// Oldest content cut
+    Truncated,
+    /// Left out entirely
+    Dropped,
+}
+
+/// A record of context left out of a prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Elision {
+    /// Section that was reduced (e.g. "memory", "conversation")
+    pub section: String,
+    pub strategy: ElisionStrategy,
+    pub original_tokens: u64,
+    pub kept_tokens: u64,
+    /// Chunks summarized (0 unless summarized)
+    pub chunks: usize,
+    /// Ledger events no longer present verbatim
+    #[serde(default)]
+    pub event_ids: Vec<String>,
+}
+
+impl Elision {
+    /// One-line description for narratives and governance notes
+    pub fn describe(&self) -> String {
+        let what = match self.strategy {
+            ElisionStrategy::Summarized => format!(
+                "summarized from ~{} to ~{} tokens ({} chunks)",
+                self.original_tokens, self.kept_tokens, self.chunks
+            ),
+            ElisionStrategy::Truncated => format!(
+                "cut from ~{} to ~{} tokens, oldest first",
+                self.original_tokens, self.kept_tokens
+            ),
+            ElisionStrategy::Dropped => format!("left out (~{} tokens)", self.original_tokens),
+        };
+        if self.event_ids.is_empty() {
+            format!("{}: {}", self.section, what)
+        } else {
+            format!("{}: {}; {} ledger events not shown verbatim", self.section, what, self.event_ids.len())
+        }
+    }
+}
+
+/// Token use of one section after budgeting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionUsage {
+    pub name: String,
+    pub tokens: u64,
+}
+
+/// Outcome of fitting a prompt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub budget: u64,
+    pub used: u64,
+    pub sections: Vec<SectionUsage>,
+    pub elisions: Vec<Elision>,
+}
+
+impl BudgetReport {
+    /// Required sections alone exceed the budget
+    pub fn over_budget(&self) -> bool {
+        self.used > self.budget
+    }
+}
+
+/// Summarizes one chunk of a section (the map step, and the reduce step
+/// over joined summaries)
+#[async_trait]
+pub trait ChunkSummarizer: Send + Sync {
+    async fn summarize(&self, section: &str, text: &str, max_tokens: u64) -> Result<String>;
+}
+
+#[async_trait]
+impl ChunkSummarizer for SmartRouter {
+    async fn summarize(&self, section: &str, text: &str, max_tokens: u64) -> Result<String> {
+        let request = LlmRequest::new(vec![LlmMessage::user(format!(
+            "Condense this part of the {} in at most {} words. Keep names, ids, numbers, \
+             decisions and open commitments; drop pleasantries and repetition.\n\n{}",
+            section,
+            max_tokens * 3 / 4,
+            text
+        ))])
+        .with_system("You condense context for another model. Never invent facts.")
+        .with_max_tokens(max_tokens as u32)
+        .with_temperature(0.1);
+        let response = self.route(request, TaskType::Writing, &RoutingPreferences::default()).await?;
+        Ok(response.content.trim().to_string())
+    }
+}
+
+/// Fits prompts and context frames into a token budget
+#[derive(Clone)]
+pub struct ContextBudgeter {
+    config: BudgetConfig,
+    summarizer: Option<Arc<dyn ChunkSummarizer>>,
+}
+
+impl ContextBudgeter {
+    /// A budgeter that only cuts; see [`Self::with_summarizer`]
+    pub fn new(config: BudgetConfig) -> Self {
+        Self { config, summarizer: None }
+    }
+
+    /// Summarize oversized sections instead of cutting them
+    pub fn with_summarizer(mut self, summarizer: Arc<dyn ChunkSummarizer>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    pub fn config(&self) -> &BudgetConfig {
+        &self.config
+    }
+
+    /// Fit sections into the budget, condensing the largest elidable section
+    /// first. Required sections are never touched, so the report can still be
+    /// over budget.
+    pub async fn fit(&self, mut sections: Vec<ContextSection>) -> (Vec<ContextSection>, BudgetReport) {
+        let budget = self.config.max_tokens;
+        let mut reduced = vec![false; sections.len()];
+        let mut elisions = Vec::new();
+
+        loop {
+            let used: u64 = sections.iter().map(ContextSection::tokens).sum();
+            if used <= budget {
+                break;
+            }
+            let Some(i) = (0..sections.len())
+                .filter(|&i| sections[i].elidable && !reduced[i] && !sections[i].text.is_empty())
+                .max_by_key(|&i| sections[i].tokens())
+            else {
+                break;
+            };
+            reduced[i] = true;
+
+            let section = &mut sections[i];
+            let target = section.tokens().saturating_sub(used - budget);
+            if target < MIN_SUMMARY_TOKENS {
+                elisions.push(Elision {
+                    section: section.name.clone(),
+                    strategy: ElisionStrategy::Dropped,
+                    original_tokens: section.tokens(),
+                    kept_tokens: 0,
+                    chunks: 0,
+                    event_ids: Vec::new(),
+                });
+                section.text.clear();
+                continue;
+            }
+            let (text, elision) = self.reduce(&section.name, &section.text, target).await;
+            section.text = text;
+            elisions.push(elision);
+        }
+
+        let report = BudgetReport {
+            budget,
+            used: sections.iter().map(ContextSection::tokens).sum(),
+            sections: sections
+                .iter()
+                .map(|s| SectionUsage { name: s.name.clone(), tokens: s.tokens() })
+                .collect(),
+            elisions,
+        };
+        (sections, report)
+    }
+
+    /// Condense `text` to about `target` tokens: summarized when a summarizer
+    /// is set and succeeds, otherwise cut from the front
+    pub async fn reduce(&self, section: &str, text: &str, target: u64) -> (String, Elision) {
+        let original_tokens = estimate_tokens(text);
+        if let Some(summarizer) = &self.summarizer {
+            match self.map_reduce(summarizer.as_ref(), section, text, target).await {
+                Ok((summary, chunks)) => {
+                    let text = format!("[Summary of the {}, condensed to fit the context window]\n{}", section, summary);
+                    let elision = Elision {
+                        section: section.to_string(),
+                        strategy: ElisionStrategy::Summarized,
+                        original_tokens,
+                        kept_tokens: estimate_tokens(&text),
+                        chunks,
+                        event_ids: Vec::new(),
+                    };
+                    return (text, elision);
+                }
+                Err(e) => warn!("Summarizing {} failed, cutting instead: {}", section, e),
+            }
+        }
+
+        let text = keep_tail(text, target);
+        let elision = Elision {
+            section: section.to_string(),
+            strategy: ElisionStrategy::Truncated,
+            original_tokens,
+            kept_tokens: estimate_tokens(&text),
+            chunks: 0,
+            event_ids: Vec::new(),
+        };
+        (text, elision)
+    }
+
+    /// Summarize every chunk, then the joined summaries, until they fit;
+    /// returns the summary and the number of chunks of the original text
+    async fn map_reduce(
+        &self,
+        summarizer: &dyn ChunkSummarizer,
+        section: &str,
+        text: &str,
+        target: u64,
+    ) -> Result<(String, usize)> {
+        let mut pieces = chunk_text(text, self.config.chunk_tokens);
+        let chunks = pieces.len();
+
+        for _ in 0..self.config.max_reduce_rounds.max(1) {
+            let per_piece = (target / pieces.len() as u64).clamp(MIN_SUMMARY_TOKENS, self.config.summary_tokens.max(MIN_SUMMARY_TOKENS));
+            let summaries = try_join_all(pieces.iter().map(|p| summarizer.summarize(section, p, per_piece))).await?;
+            let joined = summaries.join("\n\n");
+            if estimate_tokens(&joined) <= target || pieces.len() == 1 {
+                return Ok((keep_tail(&joined, target), chunks));
+            }
+            pieces = chunk_text(&joined, self.config.chunk_tokens);
+        }
+        Ok((keep_tail(&pieces.join("\n\n"), target), chunks))
+    }
+
+    /// Fit a frame's memory into `budget` tokens. With a summarizer, the older
+    /// half of the recent events becomes a historical synthesis; whatever is
+    /// still over budget is cut by [`Memory::compress_to_budget`].
+    pub async fn fit_memory(&self, memory: &mut Memory, budget: u64) -> Vec<Elision> {
+        let mut elisions = Vec::new();
+        if memory.estimate_tokens() <= budget {
+            return elisions;
+        }
+
+        if let Some(summarizer) = &self.summarizer {
+            if memory.recent_events.len() > KEEP_RECENT_EVENTS {
+                // Newest first: split off the older half, keep the newest verbatim
+                let keep = (memory.recent_events.len() / 2).max(KEEP_RECENT_EVENTS);
+                let older = memory.recent_events.split_off(keep);
+                let transcript: Vec<String> = older
+                    .iter()
+                    .rev()
+                    .map(|e| format!("[{}] {}: {}", e.timestamp.format("%Y-%m-%d %H:%M"), e.event_type, e.summary))
+                    .collect();
+                let transcript = transcript.join("\n");
+
+                match self.map_reduce(summarizer.as_ref(), "event history", &transcript, budget / 4).await {
+                    Ok((narrative, chunks)) => {
+                        elisions.push(Elision {
+                            section: "memory.recent_events".to_string(),
+                            strategy: ElisionStrategy::Summarized,
+                            original_tokens: estimate_tokens(&transcript),
+                            kept_tokens: estimate_tokens(&narrative),
+                            chunks,
+                            event_ids: older.iter().map(|e| e.event_id.clone()).collect(),
+                        });
+                        memory.add_synthesis(HistoricalSynthesis {
+                            period_start: older.last().map(|e| e.timestamp).unwrap_or_default(),
+                            period_end: older.first().map(|e| e.timestamp).unwrap_or_default(),
+                            narrative,
+                            event_count: older.len() as u32,
+                            themes: Vec::new(),
+                        });
+                    }
+                    Err(e) => {
+                        warn!("Summarizing event history failed, cutting instead: {}", e);
+                        memory.recent_events.extend(older);
+                    }
+                }
+            }
+        }
+
+        let before = memory.estimate_tokens();
+        if before > budget {
+            let ids_before: Vec<String> = memory.recent_events.iter().map(|e| e.event_id.clone()).collect();
+            memory.compress_to_budget(budget);
+            let kept: HashSet<&str> = memory.recent_events.iter().map(|e| e.event_id.as_str()).collect();
+            elisions.push(Elision {
+                section: "memory".to_string(),
+                strategy: ElisionStrategy::Truncated,
+                original_tokens: before,
+                kept_tokens: memory.estimate_tokens(),
+                chunks: 0,
+                event_ids: ids_before.into_iter().filter(|id| !kept.contains(id.as_str())).collect(),
+            });
+        }
+        elisions
+    }
+}
+
+/// Split text into chunks of at most `max_tokens`, on line boundaries where
+/// possible
+pub fn chunk_text(text: &str, max_tokens: u64) -> Vec<String> {
+    let max_chars = (max_tokens.max(1) as usize) * CHARS_PER_TOKEN;
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines() {
+        let mut line = line;
+        // A line longer than a chunk is split on char boundaries
+        while line.len() > max_chars {
+            let mut cut = max_chars;
+            while !line.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            chunks.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if !current.is_empty() && current.len() + 1 + line.len() > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() || chunks.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// The newest (last) part of `text` within `max_tokens`, marked as cut
+pub fn keep_tail(text: &str, max_tokens: u64) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+    let budget = (max_tokens as usize * CHARS_PER_TOKEN).saturating_sub(TRUNCATION_MARKER.len() + 1);
+    let mut start = text.len().saturating_sub(budget);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    // Prefer starting on a line boundary
+    if let Some(nl) = text[start..].find('\n') {
+        if nl < budget / 4 {
+            start += nl + 1;
+        }
+    }
+    format!("{}\n{}", TRUNCATION_MARKER, &text[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::memory::{MemoryConfig, MemoryEntry};
+    use chrono::Utc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Summarizes every chunk to a fixed short line, counting calls
+    struct FixedSummarizer {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ChunkSummarizer for FixedSummarizer {
+        async fn summarize(&self, _section: &str, _text: &str, _max_tokens: u64) -> Result<String> {
+            let n = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("summary {}", n))
+        }
+    }
+
+    struct FailingSummarizer;
+
+    #[async_trait]
+    impl ChunkSummarizer for FailingSummarizer {
+        async fn summarize(&self, _section: &str, _text: &str, _max_tokens: u64) -> Result<String> {
+            Err(crate::OfficeError::LlmError("down".into()))
+        }
+    }
+
+    fn config(max_tokens: u64) -> BudgetConfig {
+        BudgetConfig { max_tokens, chunk_tokens: 100, summary_tokens: 50, max_reduce_rounds: 3 }
+    }
+
+    fn lines(n: usize) -> String {
+        (0..n).map(|i| format!("line {:04} of the conversation history", i)).collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn test_chunk_text_respects_limit_and_keeps_content() {
+        let text = lines(100);
+        let chunks = chunk_text(&text, 100);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| estimate_tokens(c) <= 100));
+        assert_eq!(chunks.join("\n"), text);
+
+        let long = "é".repeat(1000);
+        assert!(chunk_text(&long, 10).iter().all(|c| c.len() <= 40));
+    }
+
+    #[test]
+    fn test_keep_tail_keeps_newest() {
+        let text = lines(100);
+        let kept = keep_tail(&text, 50);
+        assert!(estimate_tokens(&kept) <= 50);
+        assert!(kept.starts_with(TRUNCATION_MARKER));
+        assert!(kept.ends_with("line 0099 of the conversation history"));
+        assert_eq!(keep_tail("short", 50), "short");
+    }
+
+    #[tokio::test]
+    async fn test_fit_leaves_small_prompts_alone() {
+        let budgeter = ContextBudgeter::new(config(1_000));
+        let (sections, report) = budgeter
+            .fit(vec![ContextSection::required("task", "do it"), ContextSection::elidable("conversation", "hi")])
+            .await;
+        assert_eq!(sections[1].text, "hi");
+        assert!(report.elisions.is_empty());
+        assert!(!report.over_budget());
+    }
+
+    #[tokio::test]
+    async fn test_fit_map_reduces_elidable_sections() {
+        let summarizer = Arc::new(FixedSummarizer { calls: AtomicUsize::new(0) });
+        let budgeter = ContextBudgeter::new(config(200)).with_summarizer(summarizer.clone());
+        let (sections, report) = budgeter
+            .fit(vec![ContextSection::required("task", "do it"), ContextSection::elidable("conversation", lines(200))])
+            .await;
+
+        assert!(!report.over_budget());
+        assert_eq!(report.elisions.len(), 1);
+        let elision = &report.elisions[0];
+        assert_eq!(elision.strategy, ElisionStrategy::Summarized);
+        assert!(elision.chunks > 1);
+        assert_eq!(summarizer.calls.load(Ordering::SeqCst), elision.chunks);
+        assert!(sections[1].text.contains("summary 0"));
+        assert_eq!(sections[0].text, "do it");
+    }
+
+    #[tokio::test]
+    async fn test_fit_truncates_when_summarizer_fails() {
+        let budgeter = ContextBudgeter::new(config(200)).with_summarizer(Arc::new(FailingSummarizer));
+        let (sections, report) = budgeter.fit(vec![ContextSection::elidable("conversation", lines(200))]).await;
+
+        assert_eq!(report.elisions[0].strategy, ElisionStrategy::Truncated);
+        assert!(!report.over_budget());
+        assert!(sections[0].text.ends_with("line 0199 of the conversation history"));
+    }
+
+    #[tokio::test]
+    async fn test_fit_reports_required_overflow() {
+        let budgeter = ContextBudgeter::new(config(10));
+        let (_, report) = budgeter.fit(vec![ContextSection::required("task", lines(20))]).await;
+        assert!(report.over_budget());
+        assert!(report.elisions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fit_memory_summarizes_older_events() {
+        let mut memory = Memory::new("baseline".into());
+        let memory_config = MemoryConfig { recent_event_count: 100, ..Default::default() };
+        for i in 0..40 {
+            memory.add_event(
+                MemoryEntry {
+                    event_id: format!("0x{:02x}", i),
+                    event_type: "Observation".into(),
+                    timestamp: Utc::now(),
+                    summary: "x".repeat(200),
+                    data: None,
+                    is_bookmarked: false,
+                },
+                &memory_config,
+            );
+        }
+
+        let budgeter = ContextBudgeter::new(config(10_000))
+            .with_summarizer(Arc::new(FixedSummarizer { calls: AtomicUsize::new(0) }));
+        let elisions = budgeter.fit_memory(&mut memory, 1_500).await;
+
+        assert!(memory.estimate_tokens() <= 1_500);
+        assert_eq!(memory.historical_syntheses.len(), 1);
+        let summarized = &elisions[0];
+        assert_eq!(summarized.strategy, ElisionStrategy::Summarized);
+        assert_eq!(summarized.event_ids.len(), 20);
+        // The oldest event (inserted first) is among those summarized
+        assert!(summarized.event_ids.contains(&"0x00".to_string()));
+    }
+}
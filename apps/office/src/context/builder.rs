@@ -4,6 +4,7 @@
 
 use std::sync::Arc;
 
+use tracing::warn;
 
 use crate::entity::{Entity, EntityId};
 use crate::session::SessionType;
@@ -11,6 +12,8 @@ use crate::governance::{Constitution, SanityCheck};
 use crate::ubl_client::UblClient;
 use crate::Result;
 
+use super::affordances::{AffordanceService, CapabilityProfile};
+use super::budget::{estimate_tokens, BudgetConfig, ContextBudgeter};
 use super::frame::{ContextFrame, Affordance, Obligation, GuardianInfo};
 use super::memory::{Memory, MemoryConfig, MemoryEntry};
 
@@ -22,6 +25,8 @@ pub struct ContextFrameBuilder {
     memory_config: MemoryConfig,
     token_budget: u64,
     sanity_check: Option<SanityCheck>,
+    affordances: Option<Arc<AffordanceService>>,
+    budgeter: Option<Arc<ContextBudgeter>>,
 }
 
 impl ContextFrameBuilder {
@@ -38,6 +43,8 @@ impl ContextFrameBuilder {
             memory_config: MemoryConfig::default(),
             token_budget: Self::default_budget(&session_type),
             sanity_check: None,
+            affordances: None,
+            budgeter: None,
         }
     }
 
@@ -69,6 +76,20 @@ impl ContextFrameBuilder {
         self
     }
 
+    /// Share an affordance service (and its cache) across builds;
+    /// without one, affordances are derived from ASC scopes and obligations only
+    pub fn with_affordance_service(mut self, service: Arc<AffordanceService>) -> Self {
+        self.affordances = Some(service);
+        self
+    }
+
+    /// Budgeter used to fit memory and handover into the token budget;
+    /// without one, oversized context is cut rather than summarized
+    pub fn with_budgeter(mut self, budgeter: Arc<ContextBudgeter>) -> Self {
+        self.budgeter = Some(budgeter);
+        self
+    }
+
     /// Build the context frame
     pub async fn build(self) -> Result<ContextFrame> {
         // 1. Query ledger state
@@ -93,10 +114,24 @@ impl ContextFrameBuilder {
             memory.add_event(entry, &self.memory_config);
         }
 
-        // 4. Query affordances
-        let affordances = self.ubl_client.get_affordances(&self.entity.id).await
-            .unwrap_or_default()
-            .into_iter()
+        // 4. Query obligations
+        let ubl_obligations = self.ubl_client.get_obligations(&self.entity.id).await
+            .unwrap_or_default();
+
+        // 5. Derive affordances from ASC scopes, tools and obligations
+        let service = self.affordances.clone()
+            .unwrap_or_else(|| Arc::new(AffordanceService::new(self.ubl_client.clone())));
+        let affordances = service
+            .affordances_for(&self.entity.id, ledger_state.sequence, &ubl_obligations)
+            .await
+            .unwrap_or_else(|e| {
+                // Without scopes only obligations and escalation are offered
+                warn!("Affordance derivation failed for {}: {}", self.entity.id, e);
+                let fallback = CapabilityProfile { obligations: ubl_obligations.clone(), ..Default::default() };
+                Arc::new(fallback.affordances())
+            })
+            .iter()
+            .cloned()
             .map(|a| Affordance {
                 id: a.id,
                 name: a.name,
@@ -107,9 +142,7 @@ impl ContextFrameBuilder {
             })
             .collect();
 
-        // 5. Query obligations
-        let obligations = self.ubl_client.get_obligations(&self.entity.id).await
-            .unwrap_or_default()
+        let obligations = ubl_obligations
             .into_iter()
             .map(|o| Obligation {
                 id: o.id,
@@ -122,16 +155,32 @@ impl ContextFrameBuilder {
             .collect();
 
         // 6. Get previous handover
-        let previous_handover = self.ubl_client
+        let mut previous_handover = self.ubl_client
             .get_last_handover(&self.entity.id)
             .await
             .ok()
             .flatten();
 
-        // 7. Apply sanity check if configured
+        // 7. Fit memory and handover to budget, recording what was elided
+        let budgeter = self.budgeter.clone()
+            .unwrap_or_else(|| Arc::new(ContextBudgeter::new(BudgetConfig::default())));
+        let reserved_tokens = 1000; // Reserve for system prompt, constitution, etc.
+        let memory_budget = self.token_budget.saturating_sub(reserved_tokens);
+        let mut elisions = budgeter.fit_memory(&mut memory, memory_budget).await;
+
+        let handover_budget = memory_budget / 2;
+        if let Some(handover) = previous_handover.as_mut() {
+            if estimate_tokens(handover) > handover_budget {
+                let (reduced, elision) = budgeter.reduce("previous handover", handover, handover_budget).await;
+                *handover = reduced;
+                elisions.push(elision);
+            }
+        }
+
+        // 8. Apply sanity check if configured
         let governance_notes = if let Some(sanity_check) = &self.sanity_check {
             if let Some(handover) = &previous_handover {
-                sanity_check.check(handover, &self.entity.id).await
+                sanity_check.check_with_elisions(handover, &self.entity.id, &elisions).await
                     .unwrap_or_default()
             } else {
                 vec![]
@@ -140,7 +189,7 @@ impl ContextFrameBuilder {
             vec![]
         };
 
-        // 8. Get guardian info
+        // 9. Get guardian info
         let guardian_info = if let Some(guardian_id) = &self.entity.guardian_id {
             self.ubl_client.get_guardian(guardian_id).await.ok().map(|g| {
                 GuardianInfo {
@@ -153,11 +202,6 @@ impl ContextFrameBuilder {
             None
         };
 
-        // 9. Compress memory to fit budget if needed
-        let reserved_tokens = 1000; // Reserve for system prompt, constitution, etc.
-        let memory_budget = self.token_budget.saturating_sub(reserved_tokens);
-        memory.compress_to_budget(memory_budget);
-
         // 10. Build frame
         Ok(ContextFrame::new(
             self.entity.id.clone(),
@@ -172,7 +216,8 @@ impl ContextFrameBuilder {
             governance_notes,
             guardian_info,
             self.token_budget,
-        ))
+        )
+        .with_elisions(elisions))
     }
 }
 
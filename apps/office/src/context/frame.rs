@@ -10,6 +10,7 @@ use hex;
 use crate::entity::EntityId;
 use crate::session::SessionType;
 use crate::governance::Constitution;
+use super::budget::Elision;
 use super::memory::Memory;
 
 /// Hash of a context frame for verification
@@ -88,6 +89,9 @@ pub struct ContextFrame {
     pub guardian_info: Option<GuardianInfo>,
     /// Token budget for this session
     pub token_budget: u64,
+    /// Context condensed or left out to fit the budget
+    #[serde(default)]
+    pub elisions: Vec<Elision>,
     /// Hash of this frame
     pub frame_hash: ContextHash,
 }
@@ -133,6 +137,7 @@ impl ContextFrame {
             governance_notes,
             guardian_info,
             token_budget,
+            elisions: Vec::new(),
             frame_hash: String::new(),
         };
 
@@ -141,6 +146,13 @@ impl ContextFrame {
         frame
     }
 
+    /// Record what was elided to fit the budget (the hash covers it)
+    pub fn with_elisions(mut self, elisions: Vec<Elision>) -> Self {
+        self.elisions = elisions;
+        self.frame_hash = self.calculate_hash();
+        self
+    }
+
     /// Calculate hash of this frame using BLAKE3 (consistent with UBL kernel)
     pub fn calculate_hash(&self) -> ContextHash {
         let mut hasher = blake3::Hasher::new();
@@ -160,6 +172,13 @@ impl ContextFrame {
             hasher.update(constitution_json.as_bytes());
         }
 
+        // Hash elisions (frames without any hash as before)
+        if !self.elisions.is_empty() {
+            if let Ok(elisions_json) = serde_json::to_string(&self.elisions) {
+                hasher.update(elisions_json.as_bytes());
+            }
+        }
+
         let hash = hasher.finalize();
         format!("0x{}", hex::encode(hash.as_bytes()))
     }
@@ -181,6 +200,7 @@ impl ContextFrame {
             obligation_count: self.obligations.len(),
             has_handover: self.previous_handover.is_some(),
             governance_note_count: self.governance_notes.len(),
+            elision_count: self.elisions.len(),
             token_budget: self.token_budget,
             frame_hash: self.frame_hash.clone(),
         }
@@ -198,6 +218,7 @@ pub struct FrameSummary {
     pub obligation_count: usize,
     pub has_handover: bool,
     pub governance_note_count: usize,
+    pub elision_count: usize,
     pub token_budget: u64,
     pub frame_hash: ContextHash,
 }
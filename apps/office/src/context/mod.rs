@@ -1,13 +1,21 @@
 //! Context Module
 //!
-//! Manages context frames, narrative generation, and memory strategies.
+//! Manages context frames, narrative generation, memory strategies and
+//! token budgets.
 
 mod frame;
 mod builder;
 mod narrator;
 mod memory;
+mod affordances;
+mod budget;
 
 pub use frame::{ContextFrame, ContextHash, Affordance, Obligation, ObligationStatus, GuardianInfo, FrameSummary};
 pub use builder::ContextFrameBuilder;
+pub use affordances::{AffordanceService, AffordanceCache, AffordanceKey, CapabilityProfile, ToolCapability};
 pub use narrator::{Narrator, NarrativeConfig, ToolInfo};
 pub use memory::{Memory, MemoryStrategy, MemoryEntry, Bookmark, MemoryConfig, HistoricalSynthesis};
+pub use budget::{
+    estimate_tokens, chunk_text, keep_tail, BudgetConfig, BudgetReport, ChunkSummarizer, ContextBudgeter,
+    ContextSection, Elision, ElisionStrategy, SectionUsage,
+};
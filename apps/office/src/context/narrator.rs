@@ -135,7 +135,13 @@ impl Narrator {
             narrative.push_str("\n\n");
         }
 
-        // 11. Constitution section (always last)
+        // 11. Elided context section
+        if !frame.elisions.is_empty() {
+            narrative.push_str(&self.generate_elisions_section(frame));
+            narrative.push_str("\n\n");
+        }
+
+        // 12. Constitution section (always last)
         narrative.push_str(&self.generate_constitution_section(frame));
 
         narrative
@@ -419,6 +425,18 @@ impl Narrator {
         section
     }
 
+    fn generate_elisions_section(&self, frame: &ContextFrame) -> String {
+        let mut section = String::from("# ELIDED CONTEXT\n\n");
+        section.push_str("Parts of your context were condensed to fit your token budget. ");
+        section.push_str("Summaries are lossy: check the ledger before relying on details from them.\n\n");
+
+        for elision in &frame.elisions {
+            section.push_str(&format!("- {}\n", elision.describe()));
+        }
+
+        section
+    }
+
     fn generate_constitution_section(&self, frame: &ContextFrame) -> String {
         let mut section = String::from("# CONSTITUTION (Behavioral Directives)\n\n");
 
@@ -542,4 +560,39 @@ mod tests {
         assert!(narrative.contains("Best Practices"));
         assert!(narrative.contains("tool_calls"));
     }
+
+    #[test]
+    fn test_narrative_lists_elided_context() {
+        use crate::context::{Elision, ElisionStrategy};
+
+        let frame = ContextFrame::new(
+            "entity_test".to_string(),
+            "Aria".to_string(),
+            SessionType::Work,
+            100,
+            Memory::default(),
+            vec![],
+            vec![],
+            Constitution::default(),
+            None,
+            vec![],
+            None,
+            5000,
+        )
+        .with_elisions(vec![Elision {
+            section: "memory.recent_events".to_string(),
+            strategy: ElisionStrategy::Summarized,
+            original_tokens: 6000,
+            kept_tokens: 400,
+            chunks: 2,
+            event_ids: vec!["0x01".to_string(), "0x02".to_string()],
+        }]);
+
+        let narrative = Narrator::default().generate(&frame);
+        assert!(frame.verify_hash());
+        assert!(narrative.contains("# ELIDED CONTEXT"));
+        assert!(narrative.contains("memory.recent_events: summarized from ~6000 to ~400 tokens (2 chunks); 2 ledger events"));
+        // Constitution stays last
+        assert!(narrative.find("# ELIDED CONTEXT") < narrative.find("# CONSTITUTION"));
+    }
 }
@@ -92,13 +92,11 @@ impl Identity {
         let secret_hex = self.private_key_ref.as_ref()
             .ok_or_else(|| OfficeError::CryptoError("Private key not available".to_string()))?;
 
-        let secret_bytes = hex::decode(secret_hex)
-            .map_err(|e| OfficeError::CryptoError(format!("Invalid secret key hex: {}", e)))?;
-
-        let seed: [u8; 32] = secret_bytes.try_into()
-            .map_err(|_| OfficeError::CryptoError("Invalid secret key length".to_string()))?;
-
-        Ok(KeyPair::from_seed(&seed))
+        // Constant-time decode; the seed buffer is wiped before returning
+        let signing_key = ubl_kernel::signing_key_from_hex(secret_hex.as_bytes())
+            .map_err(|e| OfficeError::CryptoError(format!("Invalid secret key: {}", e)))?;
+        let verifying_key = signing_key.verifying_key();
+        Ok(KeyPair { signing_key, verifying_key })
     }
 
     /// Sign a message using this identity
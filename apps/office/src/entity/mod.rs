@@ -11,9 +11,11 @@ mod instance;
 mod identity;
 mod guardian;
 mod repository;
+pub mod replay;
 
 pub use entity::{Entity, EntityId, EntityParams, EntityType, EntityStatus};
 pub use instance::{Instance, InstanceId, InstanceStatus};
 pub use identity::{Identity, KeyPair};
 pub use guardian::{Guardian, GuardianId};
 pub use repository::{EntityRepository, EntityEvent};
+pub use replay::{EntityReplay, ReplayReport, ReplayedState, Divergence};
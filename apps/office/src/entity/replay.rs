@@ -0,0 +1,473 @@
+//! Entity Replay - Rebuild an Entity's memory from UBL history
+//!
+//! Office keeps entities, handovers and job states in memory; the ledger is
+//! the source of truth. Replay streams the entity's containers from sequence 1
+//! (`POST /sync`), re-derives that state deterministically from the atoms, and
+//! compares it with whatever local state survived.
+//!
+//! - Entity container (`ubl.container_id`): `entity_created`,
+//!   `constitution_updated`, `baseline_updated`, `session_completed`, status
+//!   changes. Dotted types (`entity.created`) are accepted too.
+//! - `C.Jobs`: jobs whose `job.created` names the entity (`assigned_to`,
+//!   `owner_entity_id` or `entity_id`), then every later event for those jobs.
+//!
+//! The same entries always produce the same [`ReplayedState`]; nothing here
+//! reads the clock.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::entity::{Entity, EntityEvent, EntityId, EntityStatus, EntityType};
+use crate::governance::Constitution;
+use crate::job_executor::{JobState, JobStateTracker, TransitionReason};
+use crate::session::Handover;
+use crate::ubl_client::{SyncEntry, UblClient};
+use crate::Result;
+
+/// Container holding job events
+pub const JOBS_CONTAINER: &str = "C.Jobs";
+
+/// Entity memory as derived from the ledger
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedEntity {
+    pub name: String,
+    pub entity_type: EntityType,
+    pub status: EntityStatus,
+    pub constitution: Constitution,
+    pub baseline_narrative: String,
+    pub total_sessions: u64,
+    pub total_tokens_consumed: u64,
+    pub created_at: DateTime<Utc>,
+    /// Time of the last `baseline_updated` (dreaming)
+    pub last_dream_at: Option<DateTime<Utc>>,
+}
+
+/// A handover recorded with a completed session
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedHandover {
+    pub session_id: String,
+    pub content: String,
+    pub tokens_used: u64,
+    pub sequence: u64,
+    pub entry_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A job owned by the entity and the state its events lead to
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedJob {
+    pub job_id: String,
+    pub state: JobState,
+    pub last_sequence: u64,
+    pub last_event: String,
+}
+
+/// Something the ledger says that does not fit Office's model
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayAnomaly {
+    pub container_id: String,
+    pub sequence: u64,
+    pub message: String,
+}
+
+/// Result of folding the ledger
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReplayedState {
+    pub entity: Option<ReplayedEntity>,
+    pub handovers: Vec<ReplayedHandover>,
+    pub jobs: BTreeMap<String, ReplayedJob>,
+    pub anomalies: Vec<ReplayAnomaly>,
+    /// Entries that touched this entity
+    pub events_applied: u64,
+}
+
+/// Local value that differs from the ledger
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Divergence {
+    pub field: String,
+    pub ledger: Value,
+    pub local: Value,
+}
+
+/// Full replay report
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub entity_id: EntityId,
+    /// Last sequence read per container
+    pub containers: BTreeMap<String, u64>,
+    #[serde(flatten)]
+    pub state: ReplayedState,
+    pub divergences: Vec<Divergence>,
+    /// Local state was overwritten with the replayed state
+    pub applied: bool,
+}
+
+/// Deterministic fold of ledger entries into an entity's state
+pub struct EntityReplay {
+    entity_id: EntityId,
+    entity_container: String,
+    state: ReplayedState,
+}
+
+impl EntityReplay {
+    pub fn new(entity_id: &str, entity_container: &str) -> Self {
+        Self {
+            entity_id: entity_id.to_string(),
+            entity_container: entity_container.to_string(),
+            state: ReplayedState::default(),
+        }
+    }
+
+    /// Apply one entry; entries of a container must come in sequence order
+    pub fn apply(&mut self, container_id: &str, entry: &SyncEntry) {
+        let Some(atom) = entry.atom.as_ref() else { return };
+        let Some(event_type) = atom.get("type").and_then(|t| t.as_str()) else { return };
+
+        if container_id == JOBS_CONTAINER {
+            self.apply_job_event(container_id, entry, atom, event_type);
+        } else if container_id == self.entity_container {
+            self.apply_entity_event(container_id, entry, atom, event_type);
+        }
+    }
+
+    pub fn finish(self) -> ReplayedState {
+        self.state
+    }
+
+    fn apply_entity_event(&mut self, container_id: &str, entry: &SyncEntry, atom: &Value, event_type: &str) {
+        if atom.get("entity_id").and_then(|v| v.as_str()) != Some(self.entity_id.as_str()) {
+            return;
+        }
+        let mut normalized = atom.clone();
+        normalized["type"] = Value::String(event_type.replace('.', "_"));
+        let Ok(event) = serde_json::from_value::<EntityEvent>(normalized) else {
+            return;
+        };
+
+        let at = timestamp(entry);
+        self.state.events_applied += 1;
+
+        if let EntityEvent::EntityCreated { name, entity_type, constitution, .. } = event {
+            if self.state.entity.is_some() {
+                self.anomaly(container_id, entry, "entity created twice; keeping the later one");
+            }
+            self.state.entity = Some(ReplayedEntity {
+                name,
+                entity_type,
+                status: EntityStatus::Active,
+                constitution,
+                baseline_narrative: String::new(),
+                total_sessions: 0,
+                total_tokens_consumed: 0,
+                created_at: at,
+                last_dream_at: None,
+            });
+            return;
+        }
+
+        let Some(entity) = self.state.entity.as_mut() else {
+            self.anomaly(container_id, entry, &format!("{} before entity_created", event_type));
+            return;
+        };
+        match event {
+            EntityEvent::EntityCreated { .. } => unreachable!(),
+            EntityEvent::ConstitutionUpdated { constitution, .. } => entity.constitution = constitution,
+            EntityEvent::BaselineUpdated { baseline, .. } => {
+                entity.baseline_narrative = baseline;
+                entity.last_dream_at = Some(at);
+            }
+            EntityEvent::SessionCompleted { session_id, tokens_used, handover, .. } => {
+                entity.total_sessions += 1;
+                entity.total_tokens_consumed += tokens_used;
+                if let Some(content) = handover {
+                    self.state.handovers.push(ReplayedHandover {
+                        session_id,
+                        content,
+                        tokens_used,
+                        sequence: entry.sequence,
+                        entry_hash: entry.entry_hash.clone(),
+                        created_at: at,
+                    });
+                }
+            }
+            EntityEvent::EntitySuspended { .. } => entity.status = EntityStatus::Suspended,
+            EntityEvent::EntityActivated { .. } => entity.status = EntityStatus::Active,
+            EntityEvent::EntityArchived { .. } => entity.status = EntityStatus::Archived,
+        }
+    }
+
+    fn apply_job_event(&mut self, container_id: &str, entry: &SyncEntry, atom: &Value, event_type: &str) {
+        let Some(job_id) = atom.get("job_id").or_else(|| atom.get("id")).and_then(|v| v.as_str()) else {
+            return;
+        };
+
+        if event_type == "job.created" {
+            let owned = ["assigned_to", "owner_entity_id", "entity_id"]
+                .iter()
+                .any(|k| atom.get(*k).and_then(|v| v.as_str()) == Some(self.entity_id.as_str()));
+            if owned && !self.state.jobs.contains_key(job_id) {
+                self.state.events_applied += 1;
+                self.state.jobs.insert(job_id.to_string(), ReplayedJob {
+                    job_id: job_id.to_string(),
+                    state: JobState::Proposed,
+                    last_sequence: entry.sequence,
+                    last_event: event_type.to_string(),
+                });
+            }
+            return;
+        }
+
+        let Some(current) = self.state.jobs.get(job_id).map(|j| j.state) else { return };
+        let Some(to) = job_target_state(event_type, atom) else { return };
+        self.state.events_applied += 1;
+
+        // The ledger wins; Office's FSM only flags transitions it would not make
+        if let Err(e) = JobStateTracker::with_state(current)
+            .transition(to, TransitionReason::Custom(event_type.to_string()))
+        {
+            self.anomaly(container_id, entry, &format!("job {}: {}", job_id, e));
+        }
+        if let Some(job) = self.state.jobs.get_mut(job_id) {
+            job.state = to;
+            job.last_sequence = entry.sequence;
+            job.last_event = event_type.to_string();
+        }
+    }
+
+    fn anomaly(&mut self, container_id: &str, entry: &SyncEntry, message: &str) {
+        self.state.anomalies.push(ReplayAnomaly {
+            container_id: container_id.to_string(),
+            sequence: entry.sequence,
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Job state an event moves to; None for events that do not change state
+fn job_target_state(event_type: &str, atom: &Value) -> Option<JobState> {
+    if let Some(to) = atom.get("to_state").and_then(|v| v.as_str()).and_then(JobState::from_str) {
+        return Some(to);
+    }
+    match event_type {
+        "job.approve" | "job.approved" => Some(JobState::Approved),
+        "job.reject" | "job.rejected" => Some(JobState::Rejected),
+        "job.provide_input" => Some(JobState::InProgress),
+        "job.waiting_input" => Some(JobState::WaitingInput),
+        "job.started" => Some(JobState::InProgress),
+        "job.completed" if atom.get("success").and_then(|v| v.as_bool()) == Some(false) => Some(JobState::Failed),
+        "job.completed" => Some(JobState::Completed),
+        "job.failed" | "job.timeout" => Some(JobState::Failed),
+        "job.cancelled" => Some(JobState::Cancelled),
+        "approval.decided" => match atom.get("decision").and_then(|v| v.as_str()) {
+            Some("approved") => Some(JobState::Approved),
+            Some("rejected") => Some(JobState::Rejected),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn timestamp(entry: &SyncEntry) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(entry.ts_unix_ms).unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+/// Stream `containers` from sequence 1 and fold them
+pub async fn replay_entity(
+    ubl_client: &UblClient,
+    entity_id: &str,
+    entity_container: &str,
+    containers: &[String],
+) -> Result<(ReplayedState, BTreeMap<String, u64>)> {
+    let mut replay = EntityReplay::new(entity_id, entity_container);
+    let mut cursors: BTreeMap<String, u64> = containers.iter().map(|c| (c.clone(), 0)).collect();
+
+    loop {
+        let page = ubl_client.sync(&cursors).await?;
+        for (container_id, entries) in &page.entries {
+            for entry in entries {
+                replay.apply(container_id, entry);
+            }
+        }
+        let advanced = page.cursors.iter().any(|(c, seq)| cursors.get(c).is_some_and(|prev| seq > prev));
+        cursors.extend(page.cursors);
+        if !page.has_more || !advanced {
+            break;
+        }
+    }
+
+    Ok((replay.finish(), cursors))
+}
+
+/// Compare replayed state with local state; empty when they agree
+pub fn diverge(replayed: &ReplayedState, local: Option<&Entity>, local_handovers: &[Handover]) -> Vec<Divergence> {
+    let mut out = Vec::new();
+    let mut diff = |field: &str, ledger: Value, local: Value| {
+        if ledger != local {
+            out.push(Divergence { field: field.to_string(), ledger, local });
+        }
+    };
+
+    match (&replayed.entity, local) {
+        (None, None) => {}
+        (Some(_), None) => diff("entity", Value::Bool(true), Value::Bool(false)),
+        (None, Some(_)) => diff("entity", Value::Bool(false), Value::Bool(true)),
+        (Some(r), Some(l)) => {
+            diff("name", r.name.clone().into(), l.name.clone().into());
+            diff("entity_type", json(&r.entity_type), json(&l.entity_type));
+            diff("status", json(&r.status), json(&l.status));
+            diff("constitution", json(&r.constitution), json(&l.constitution));
+            diff("baseline_narrative", r.baseline_narrative.clone().into(), l.baseline_narrative.clone().into());
+            diff("total_sessions", r.total_sessions.into(), l.total_sessions.into());
+            diff("total_tokens_consumed", r.total_tokens_consumed.into(), l.total_tokens_consumed.into());
+        }
+    }
+
+    // Handovers are matched by session
+    let ledger_sessions: Vec<&str> = replayed.handovers.iter().map(|h| h.session_id.as_str()).collect();
+    let local_sessions: Vec<&str> = local_handovers.iter().map(|h| h.session_id.as_str()).collect();
+    let missing: Vec<&str> = ledger_sessions.iter().filter(|s| !local_sessions.contains(s)).copied().collect();
+    let unrecorded: Vec<&str> = local_sessions.iter().filter(|s| !ledger_sessions.contains(s)).copied().collect();
+    if !missing.is_empty() {
+        diff("handovers.missing_locally", json(&missing), Value::Array(vec![]));
+    }
+    if !unrecorded.is_empty() {
+        diff("handovers.not_in_ledger", Value::Array(vec![]), json(&unrecorded));
+    }
+
+    out
+}
+
+/// Overwrite a local entity's memory with the replayed state (identity is kept)
+pub fn restore(entity: &mut Entity, replayed: &ReplayedEntity) {
+    entity.name = replayed.name.clone();
+    entity.entity_type = replayed.entity_type;
+    entity.status = replayed.status;
+    entity.constitution = replayed.constitution.clone();
+    entity.baseline_narrative = replayed.baseline_narrative.clone();
+    entity.total_sessions = replayed.total_sessions;
+    entity.total_tokens_consumed = replayed.total_tokens_consumed;
+    entity.created_at = replayed.created_at;
+    entity.last_dream_at = replayed.last_dream_at;
+}
+
+fn json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(sequence: u64, atom: Value) -> SyncEntry {
+        SyncEntry {
+            sequence,
+            entry_hash: format!("0x{:02x}", sequence),
+            ts_unix_ms: 1_760_000_000_000 + sequence as i64 * 1000,
+            atom: Some(atom),
+        }
+    }
+
+    fn entity_entries() -> Vec<SyncEntry> {
+        vec![
+            entry(1, json!({
+                "type": "entity_created", "entity_id": "e1", "name": "Ada",
+                "entity_type": "autonomous", "public_key": "pk",
+                "constitution": Constitution::default(),
+            })),
+            entry(2, json!({ "type": "entity_created", "entity_id": "other", "name": "Bob",
+                "entity_type": "autonomous", "public_key": "pk", "constitution": Constitution::default() })),
+            entry(3, json!({ "type": "session.completed", "entity_id": "e1", "session_id": "s1",
+                "tokens_used": 100, "handover": "left off at step 2" })),
+            entry(4, json!({ "type": "baseline_updated", "entity_id": "e1", "baseline": "I review contracts." })),
+            entry(5, json!({ "type": "session_completed", "entity_id": "e1", "session_id": "s2",
+                "tokens_used": 50, "handover": null })),
+        ]
+    }
+
+    fn replay(entries: &[SyncEntry], jobs: &[SyncEntry]) -> ReplayedState {
+        let mut replay = EntityReplay::new("e1", "office");
+        for e in entries {
+            replay.apply("office", e);
+        }
+        for e in jobs {
+            replay.apply(JOBS_CONTAINER, e);
+        }
+        replay.finish()
+    }
+
+    #[test]
+    fn test_replay_entity_memory() {
+        let state = replay(&entity_entries(), &[]);
+        let entity = state.entity.as_ref().unwrap();
+        assert_eq!(entity.name, "Ada");
+        assert_eq!(entity.total_sessions, 2);
+        assert_eq!(entity.total_tokens_consumed, 150);
+        assert_eq!(entity.baseline_narrative, "I review contracts.");
+        assert_eq!(entity.last_dream_at, DateTime::from_timestamp_millis(1_760_000_004_000));
+        assert_eq!(state.handovers.len(), 1);
+        assert_eq!(state.handovers[0].session_id, "s1");
+        assert_eq!(state.events_applied, 4);
+        assert!(state.anomalies.is_empty());
+
+        // Deterministic
+        let again = replay(&entity_entries(), &[]);
+        assert_eq!(json(&state), json(&again));
+    }
+
+    #[test]
+    fn test_replay_job_states() {
+        let jobs = vec![
+            entry(1, json!({ "type": "job.created", "id": "j1", "assigned_to": "e1" })),
+            entry(2, json!({ "type": "job.created", "id": "j2", "assigned_to": "someone_else" })),
+            entry(3, json!({ "type": "approval.decided", "job_id": "j1", "decision": "approved" })),
+            entry(4, json!({ "type": "job.started", "job_id": "j1" })),
+            entry(5, json!({ "type": "job.timeout", "job_id": "j1", "from_state": "in_progress", "to_state": "failed" })),
+            entry(6, json!({ "type": "job.started", "job_id": "j2" })),
+        ];
+        let state = replay(&entity_entries(), &jobs);
+        assert_eq!(state.jobs.len(), 1);
+        assert_eq!(state.jobs["j1"].state, JobState::Failed);
+        assert_eq!(state.jobs["j1"].last_sequence, 5);
+        assert!(state.anomalies.is_empty());
+
+        // Skipping approval is kept but flagged
+        let skipped = vec![
+            entry(1, json!({ "type": "job.created", "job_id": "j3", "owner_entity_id": "e1" })),
+            entry(2, json!({ "type": "job.started", "job_id": "j3" })),
+        ];
+        let state = replay(&[], &skipped);
+        assert_eq!(state.jobs["j3"].state, JobState::InProgress);
+        assert_eq!(state.anomalies.len(), 1);
+    }
+
+    #[test]
+    fn test_divergence_from_local() {
+        let state = replay(&entity_entries(), &[]);
+        let mut local = Entity::new(crate::entity::EntityParams {
+            name: "Ada".to_string(),
+            entity_type: crate::entity::EntityType::Autonomous,
+            guardian_id: None,
+            constitution: None,
+            baseline_narrative: None,
+            metadata: None,
+        })
+        .unwrap();
+
+        let divergences = diverge(&state, Some(&local), &[]);
+        let fields: Vec<&str> = divergences.iter().map(|d| d.field.as_str()).collect();
+        assert!(fields.contains(&"baseline_narrative"));
+        assert!(fields.contains(&"total_sessions"));
+        assert!(fields.contains(&"handovers.missing_locally"));
+
+        restore(&mut local, state.entity.as_ref().unwrap());
+        let divergences = diverge(&state, Some(&local), &[]);
+        assert_eq!(divergences.len(), 1, "{:?}", divergences);
+        assert_eq!(divergences[0].field, "handovers.missing_locally");
+
+        assert_eq!(diverge(&state, None, &[])[0].field, "entity");
+    }
+}
@@ -17,4 +17,4 @@ pub use sanity_check::{SanityCheck, SanityCheckConfig, Claim, Fact, Discrepancy,
 pub use constitution::{Constitution, BehavioralOverride, ConstitutionBuilder};
 pub use dreaming::{DreamingCycle, DreamingConfig, DreamingResult};
 pub use simulation::{Simulation, SimulationConfig, SimulationResult, ActionOutcome, ActionRecommendation, Action};
-pub use provenance::{ProvenanceValidator, ValidatedAction, ProvenanceCheck};
+pub use provenance::{claimed_action, ProvenanceValidator, ValidatedAction, ProvenanceCheck};
@@ -121,6 +121,24 @@ impl Default for ProvenanceValidator {
     }
 }
 
+/// The action a gateway `action_type` ("approve" or "job.approve") claims on `job_id`.
+/// Only type and job_id are compared by `validate_action`, so payload fields stay empty.
+pub fn claimed_action(action_type: &str, job_id: &str) -> Option<CardAction> {
+    let job_id = job_id.to_string();
+    let action = match action_type.strip_prefix("job.").unwrap_or(action_type) {
+        "approve" => CardAction::Approve { job_id },
+        "reject" => CardAction::Reject { job_id, reason_code: None },
+        "request_changes" => CardAction::RequestChanges { job_id },
+        "provide_input" => CardAction::ProvideInput { job_id, input_schema: None },
+        "ack" => CardAction::Acknowledge { job_id },
+        "dispute" => CardAction::Dispute { job_id, reason_code: None },
+        "cancel" => CardAction::Cancel { job_id },
+        "chat.ask" | "chat_ask" => CardAction::ChatAsk { job_id: Some(job_id), prompt_text: String::new() },
+        _ => return None,
+    };
+    Some(action)
+}
+
 /// Check if two actions match (same type and job_id)
 fn actions_match(expected: &CardAction, claimed: &CardAction) -> bool {
     use CardAction::*;
@@ -188,7 +206,7 @@ impl ProvenanceCheck {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::job_executor::cards::ButtonStyle;
+    use crate::job_executor::cards::{ButtonStyle, FormalizeCard};
 
     #[tokio::test]
     async fn test_valid_button_click() {
@@ -265,6 +283,21 @@ mod tests {
         assert!(err.contains("Action mismatch"));
     }
 
+    #[tokio::test]
+    async fn test_claimed_action_from_gateway_type() {
+        let validator = ProvenanceValidator::new();
+        validator.register_card("card_abc", "job_123", FormalizeCard::default_buttons("job_123")).await;
+
+        let approve = claimed_action("approve", "job_123").unwrap();
+        assert!(validator.validate_action("card_abc", "btn_approve_job_123", &approve, None).await.is_ok());
+
+        // Same button replayed against another job
+        let other_job = claimed_action("job.approve", "job_999").unwrap();
+        assert!(validator.validate_action("card_abc", "btn_approve_job_123", &other_job, None).await.is_err());
+
+        assert!(claimed_action("delete_everything", "job_123").is_none());
+    }
+
     #[tokio::test]
     async fn test_missing_required_input() {
         let validator = ProvenanceValidator::new();
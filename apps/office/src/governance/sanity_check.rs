@@ -2,11 +2,13 @@
 //!
 //! Validates claims from handovers against objective facts from the ledger.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::context::Elision;
 use crate::entity::EntityId;
 use crate::ubl_client::UblClient;
 use crate::Result;
@@ -121,6 +123,17 @@ impl SanityCheck {
 
     /// Check a handover and generate governance notes
     pub async fn check(&self, handover: &str, entity_id: &EntityId) -> Result<Vec<String>> {
+        self.check_with_elisions(handover, entity_id, &[]).await
+    }
+
+    /// [`Self::check`] for a frame whose context was condensed: notes say
+    /// when the records behind a claim are not in the context verbatim
+    pub async fn check_with_elisions(
+        &self,
+        handover: &str,
+        entity_id: &EntityId,
+        elisions: &[Elision],
+    ) -> Result<Vec<String>> {
         let claims = self.extract_claims(handover);
 
         if claims.is_empty() {
@@ -132,12 +145,46 @@ impl SanityCheck {
 
         let notes: Vec<String> = discrepancies
             .into_iter()
-            .map(|d| d.governance_note)
+            .map(|d| self.account_for_elisions(d, elisions))
             .collect();
 
         Ok(notes)
     }
 
+    /// The discrepancy's note, extended with what the context is missing
+    fn account_for_elisions(&self, discrepancy: Discrepancy, elisions: &[Elision]) -> String {
+        if elisions.is_empty() {
+            return discrepancy.governance_note;
+        }
+
+        let elided: HashSet<&str> = elisions
+            .iter()
+            .flat_map(|e| e.event_ids.iter().map(String::as_str))
+            .collect();
+        let hidden = discrepancy.contradicting_facts
+            .iter()
+            .filter(|f| elided.contains(f.source_event_id.as_str()))
+            .count();
+
+        if hidden > 0 {
+            format!(
+                "{}\n{} of these records were condensed out of your context; \
+                look them up before relying on your memory of them.",
+                discrepancy.governance_note, hidden
+            )
+        } else if discrepancy.contradicting_facts.is_empty() {
+            let sections: Vec<&str> = elisions.iter().map(|e| e.section.as_str()).collect();
+            format!(
+                "{}\nPart of your context was condensed ({}), so the claim may concern \
+                events you cannot see verbatim.",
+                discrepancy.governance_note,
+                sections.join(", ")
+            )
+        } else {
+            discrepancy.governance_note
+        }
+    }
+
     /// Extract claims from handover text
     fn extract_claims(&self, handover: &str) -> Vec<Claim> {
         if !self.config.keyword_extraction {
@@ -347,4 +394,26 @@ mod tests {
 
         assert!(notes.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_unverifiable_claim_mentions_elided_context() {
+        let sanity_check = SanityCheck::default();
+        let elision = Elision {
+            section: "memory".to_string(),
+            strategy: crate::context::ElisionStrategy::Truncated,
+            original_tokens: 9000,
+            kept_tokens: 4000,
+            chunks: 0,
+            event_ids: vec!["0xabc".to_string()],
+        };
+
+        let handover = "The deploy was a failure and the client is unsatisfied.";
+        let entity = "entity_1".to_string();
+        let plain = sanity_check.check(handover, &entity).await.unwrap();
+        let condensed = sanity_check.check_with_elisions(handover, &entity, &[elision]).await.unwrap();
+
+        assert_eq!(plain.len(), 1);
+        assert!(!plain[0].contains("condensed"));
+        assert!(condensed[0].contains("Part of your context was condensed (memory)"));
+    }
 }
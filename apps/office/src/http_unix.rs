@@ -21,7 +21,10 @@ impl UblEndpoint {
             let base = std::env::var("UBL_BASE")
                 .or_else(|_| std::env::var("UBL_ENDPOINT"))
                 .unwrap_or_else(|_| "http://127.0.0.1:8080".to_string());
-            UblEndpoint::Tcp { base }
+            match crate::ubl_client::unix_socket_path(&base) {
+                Some(socket) => UblEndpoint::Unix { socket: socket.to_string() },
+                None => UblEndpoint::Tcp { base },
+            }
         }
     }
 
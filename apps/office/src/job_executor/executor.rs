@@ -17,13 +17,15 @@ use futures::{Stream, StreamExt};
 use futures::pin_mut;
 use chrono::Utc;
 use tokio::sync::mpsc;
+use ubl_events::{JobCompleted, JobEvent, JobOutcome};
 
 use crate::entity::{Entity, EntityId, EntityParams, EntityType, EntityRepository};
-use crate::context::{ContextFrameBuilder, Narrator, NarrativeConfig};
+use crate::context::{AffordanceService, ContextBudgeter, ContextFrameBuilder, ContextSection, Narrator, NarrativeConfig};
 use crate::session::{Session, SessionType, SessionMode};
 use crate::ubl_client::UblClient;
 use crate::llm::{LlmMessage, LlmRequest, SmartRouter, TaskType, RoutingPreferences};
 use crate::governance::Constitution;
+use crate::observability;
 use crate::{OfficeError, Result};
 
 use super::types::{
@@ -31,6 +33,7 @@ use super::types::{
     ApprovalRequest, ApprovalDecision, ConversationContext, ProgressUpdate,
 };
 use super::conversation_context::ConversationContextBuilder;
+use super::guarded::{GuardedMutation, GuardianApprovals};
 
 /// Job Executor - Executes jobs using LLM entities
 pub struct JobExecutor {
@@ -38,6 +41,9 @@ pub struct JobExecutor {
     entity_repository: Arc<EntityRepository>,
     router: Arc<SmartRouter>,
     container_id: String,
+    affordances: Option<Arc<AffordanceService>>,
+    budgeter: Option<Arc<ContextBudgeter>>,
+    approvals: Option<Arc<GuardianApprovals>>,
 }
 
 impl JobExecutor {
@@ -53,9 +59,32 @@ impl JobExecutor {
             entity_repository,
             router,
             container_id: container_id.to_string(),
+            affordances: None,
+            budgeter: None,
+            approvals: None,
         }
     }
 
+    /// Share an affordance service with the context frames this executor builds
+    pub fn with_affordance_service(mut self, service: Arc<AffordanceService>) -> Self {
+        self.affordances = Some(service);
+        self
+    }
+
+    /// Fit job prompts into the model's window; without a budgeter they are
+    /// sent as built
+    pub fn with_context_budget(mut self, budgeter: Arc<ContextBudgeter>) -> Self {
+        self.budgeter = Some(budgeter);
+        self
+    }
+
+    /// Route jobs of guarded entities through their guardian; without a
+    /// broker such jobs are refused
+    pub fn with_guardian_approvals(mut self, approvals: Arc<GuardianApprovals>) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
     /// Execute a job
     ///
     /// This is the main entry point for job execution.
@@ -65,38 +94,93 @@ impl JobExecutor {
         &self,
         job: Job,
         conversation_context: ConversationContext,
+    ) -> Result<JobResult> {
+        let started = std::time::Instant::now();
+        let result = self.run_job(job, conversation_context).await;
+        // Execution is the job's in_progress state
+        observability::observe(&observability::JOB_STATE_DURATION, &["in_progress"], started.elapsed().as_secs_f64());
+        observability::inc(&observability::JOB_OPS, &["execute", if result.is_ok() { "success" } else { "error" }]);
+        result
+    }
+
+    async fn run_job(
+        &self,
+        job: Job,
+        conversation_context: ConversationContext,
     ) -> Result<JobResult> {
         let start_time = Utc::now();
         
         // 1. Get or create the Entity (The Chair)
         let entity = self.get_or_create_agent_entity(&job.assigned_to).await?;
+
+        // 1b. A guarded entity changes nothing without its guardian's approval
+        if entity.entity_type == EntityType::Guarded {
+            let approvals = self.approvals.as_ref().ok_or_else(|| {
+                OfficeError::PermitDenied(format!("Guarded entity {} has no approval route", entity.id))
+            })?;
+            let mutation = GuardedMutation {
+                job_id: job.id.clone(),
+                title: format!("Execute job: {}", job.title),
+                details: job.description.iter().cloned().collect(),
+                impact: format!("{} runs this job and publishes its result to UBL", entity.id),
+            };
+            approvals.authorize(&self.ubl_client, &entity, mutation).await?;
+        }
         
         // 2. Build context frame from UBL
-        let context = ContextFrameBuilder::new(
+        let mut builder = ContextFrameBuilder::new(
             entity.clone(),
             SessionType::Work,
             self.ubl_client.clone(),
-        )
-        .build()
-        .await?;
+        );
+        if let Some(service) = &self.affordances {
+            builder = builder.with_affordance_service(service.clone());
+        }
+        if let Some(budgeter) = &self.budgeter {
+            builder = builder.with_budgeter(budgeter.clone());
+        }
+        let context = builder.build().await?;
         
         // 3. Generate the Narrative - The onboarding for this ephemeral instance
         let narrator = Narrator::new(NarrativeConfig::default());
         let base_narrative = narrator.generate(&context);
         
-        // Build the full narrative with job context
-        let narrative = format!(
-            "{}\n\n## Current Task\n\n**Job Title:** {}\n**Description:** {}\n**Conversation:** {}\n\n{}\n\n## Your Response\n\nProvide your response to complete this task.",
-            base_narrative,
-            job.title,
-            job.description.as_deref().unwrap_or("No description provided"),
-            conversation_context.conversation_id,
-            ConversationContextBuilder::new(conversation_context.conversation_id.clone())
+        // Build the full narrative with job context; only the conversation
+        // may be condensed to fit the model's window
+        let sections = vec![
+            ContextSection::required("narrative", base_narrative),
+            ContextSection::required("task", format!(
+                "## Current Task\n\n**Job Title:** {}\n**Description:** {}\n**Conversation:** {}",
+                job.title,
+                job.description.as_deref().unwrap_or("No description provided"),
+                conversation_context.conversation_id,
+            )),
+            ContextSection::elidable("conversation", ConversationContextBuilder::new(conversation_context.conversation_id.clone())
                 .with_participants(conversation_context.participants.clone())
                 .with_recent_messages(conversation_context.recent_messages.clone())
                 .with_active_jobs(conversation_context.active_jobs.clone())
-                .to_narrative()
-        );
+                .to_narrative()),
+            ContextSection::required("response", "## Your Response\n\nProvide your response to complete this task."),
+        ];
+        let sections = match &self.budgeter {
+            Some(budgeter) => {
+                let (sections, report) = budgeter.fit(sections).await;
+                for elision in &report.elisions {
+                    tracing::info!("Job {} context: {}", job.id, elision.describe());
+                }
+                if report.over_budget() {
+                    tracing::warn!("Job {} prompt is ~{} tokens, over the {} token budget", job.id, report.used, report.budget);
+                }
+                sections
+            }
+            None => sections,
+        };
+        let narrative = sections
+            .iter()
+            .map(|s| s.text.as_str())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
         
         // 4. Determine task type for smart routing
         let task_type = self.classify_task(&job);
@@ -223,14 +307,17 @@ impl JobExecutor {
     /// Publish job completion event to UBL
     async fn publish_completion_event(&self, job: &Job, result: &JobResult) -> Result<()> {
         // Build event
-        let event = serde_json::json!({
-            "type": "job.completed",
-            "job_id": job.id,
-            "success": result.success,
-            "tokens_used": result.tokens_used,
-            "duration_seconds": result.duration_seconds,
-            "timestamp": Utc::now().to_rfc3339()
-        });
+        let event = JobEvent::Completed(JobCompleted {
+            result: Some(JobOutcome {
+                summary: result.summary.clone(),
+                artifacts: (!result.artifacts.is_empty()).then(|| serde_json::json!(result.artifacts)),
+            }),
+            tokens_used: Some(result.tokens_used),
+            duration_seconds: Some(result.duration_seconds),
+            timestamp: Some(Utc::now().to_rfc3339()),
+            ..JobCompleted::new(&job.id, result.success)
+        })
+        .to_atom();
         
         // Canonicalize and hash
         let canonical = ubl_atom::canonicalize(&event)
@@ -288,6 +375,9 @@ impl JobExecutor {
             entity_repository: self.entity_repository.clone(),
             router: self.router.clone(),
             container_id: self.container_id.clone(),
+            affordances: self.affordances.clone(),
+            budgeter: self.budgeter.clone(),
+            approvals: self.approvals.clone(),
         };
         
         let job_id = job.id.clone();
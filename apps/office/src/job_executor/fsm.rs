@@ -231,11 +231,15 @@ pub struct Transition {
 }
 
 /// Job state tracker - maintains current state with history
+///
+/// Leaving a state records the time spent in it
+/// (`office_job_state_duration_seconds`) when the entry time is known.
 #[derive(Debug, Clone)]
 pub struct JobStateTracker {
     fsm: JobFsm,
     current_state: JobState,
     history: Vec<Transition>,
+    entered_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl JobStateTracker {
@@ -244,14 +248,25 @@ impl JobStateTracker {
             fsm: JobFsm::new(),
             current_state: JobState::Draft,
             history: vec![],
+            entered_at: Some(chrono::Utc::now()),
         }
     }
 
+    /// Resume at a state whose entry time is unknown (no duration is recorded for it)
     pub fn with_state(state: JobState) -> Self {
         Self {
             fsm: JobFsm::new(),
             current_state: state,
             history: vec![],
+            entered_at: None,
+        }
+    }
+
+    /// Resume at a state entered at a known time
+    pub fn with_state_since(state: JobState, entered_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            entered_at: Some(entered_at),
+            ..Self::with_state(state)
         }
     }
 
@@ -270,6 +285,15 @@ impl JobStateTracker {
     /// Transition to a new state
     pub fn transition(&mut self, to: JobState, reason: TransitionReason) -> Result<&Transition> {
         let transition = self.fsm.transition(self.current_state, to, reason)?;
+        if let Some(entered_at) = self.entered_at {
+            let secs = (transition.timestamp - entered_at).num_milliseconds().max(0) as f64 / 1000.0;
+            crate::observability::observe(
+                &crate::observability::JOB_STATE_DURATION,
+                &[self.current_state.as_str()],
+                secs,
+            );
+        }
+        self.entered_at = Some(transition.timestamp);
         self.current_state = to;
         self.history.push(transition);
         Ok(self.history.last().unwrap())
@@ -406,5 +430,18 @@ mod tests {
         assert!(valid.contains(&JobState::Cancelled));
         assert!(!valid.contains(&JobState::Draft));
     }
+
+    #[test]
+    fn test_state_duration_recorded() {
+        let hist = crate::observability::JOB_STATE_DURATION.with_label_values(&["approved"]);
+        let (count, sum) = (hist.get_sample_count(), hist.get_sample_sum());
+
+        let since = chrono::Utc::now() - chrono::Duration::seconds(30);
+        let mut tracker = JobStateTracker::with_state_since(JobState::Approved, since);
+        tracker.start().unwrap();
+
+        assert!(hist.get_sample_count() > count);
+        assert!(hist.get_sample_sum() - sum >= 30.0);
+    }
 }
 
@@ -0,0 +1,325 @@
+//! Guarded Mode - Guardian approval for every mutation
+//!
+//! A guarded entity may not change anything on its own. Each mutation it
+//! attempts (running a job, requesting a permit) opens an approval request
+//! for its Guardian and blocks until the guardian decides or the request
+//! times out. Both the request and the outcome are written to the ledger
+//! (`approval.requested` / `approval.decided` on C.Jobs); a timeout is
+//! recorded as a rejection by `system:timeout`.
+//!
+//! Fail-closed: a guarded entity without a guardian, a ledger write that
+//! fails, a timeout or any decision other than "approved" blocks the mutation.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tokio::sync::{oneshot, Mutex};
+use tracing::{info, warn};
+
+use crate::entity::{Entity, EntityType, GuardianId};
+use crate::ubl_client::UblClient;
+use crate::{OfficeError, Result};
+
+use super::types::{ApprovalDecision, ApprovalRequest, JobId};
+
+/// Container the approval events are recorded in
+pub const APPROVALS_CONTAINER: &str = "C.Jobs";
+
+/// Who decides when the guardian does not answer in time
+pub const TIMEOUT_DECIDER: &str = "system:timeout";
+
+const DECISIONS: &[&str] = &["approved", "rejected", "request_changes"];
+
+/// A mutation a guarded entity wants to perform
+#[derive(Debug, Clone)]
+pub struct GuardedMutation {
+    pub job_id: JobId,
+    pub title: String,
+    pub details: Vec<String>,
+    pub impact: String,
+}
+
+/// An approval waiting on a guardian
+#[derive(Debug, Clone, Serialize)]
+pub struct GuardianApproval {
+    pub guardian_id: GuardianId,
+    #[serde(flatten)]
+    pub request: ApprovalRequest,
+}
+
+/// Returned by [`GuardianApprovals::open`]; wait on it for the decision
+pub struct PendingApproval {
+    pub approval: GuardianApproval,
+    rx: oneshot::Receiver<ApprovalDecision>,
+}
+
+struct Waiting {
+    approval: GuardianApproval,
+    tx: oneshot::Sender<ApprovalDecision>,
+}
+
+/// Broker between guarded entities waiting for approval and their guardians
+pub struct GuardianApprovals {
+    timeout: Duration,
+    pending: Mutex<HashMap<String, Waiting>>,
+}
+
+impl GuardianApprovals {
+    /// Requests not decided within `timeout` are rejected
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Open an approval request with the entity's guardian
+    pub async fn open(&self, entity: &Entity, mutation: GuardedMutation) -> Result<PendingApproval> {
+        let guardian_id = entity.guardian_id.clone().ok_or_else(|| {
+            OfficeError::PermitDenied(format!("Guarded entity {} has no guardian", entity.id))
+        })?;
+        let approval = GuardianApproval {
+            guardian_id,
+            request: ApprovalRequest {
+                approval_id: format!("appr_{}", uuid::Uuid::new_v4()),
+                job_id: mutation.job_id,
+                title: mutation.title,
+                details: mutation.details,
+                impact: mutation.impact,
+                requested_by: entity.id.clone(),
+                requested_at: Utc::now(),
+            },
+        };
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(
+            approval.request.approval_id.clone(),
+            Waiting { approval: approval.clone(), tx },
+        );
+        Ok(PendingApproval { approval, rx })
+    }
+
+    /// Block until the guardian decides; past the timeout the request is
+    /// withdrawn and rejected
+    pub async fn wait(&self, pending: PendingApproval) -> ApprovalDecision {
+        let request = &pending.approval.request;
+        if let Ok(Ok(decision)) = tokio::time::timeout(self.timeout, pending.rx).await {
+            return decision;
+        }
+        self.pending.lock().await.remove(&request.approval_id);
+        ApprovalDecision {
+            approval_id: request.approval_id.clone(),
+            job_id: request.job_id.clone(),
+            decision: "rejected".to_string(),
+            decided_by: TIMEOUT_DECIDER.to_string(),
+            decided_at: Utc::now(),
+            reason: Some(format!("No guardian decision within {}s", self.timeout.as_secs())),
+        }
+    }
+
+    /// Withdraw a request nobody will wait for
+    pub async fn cancel(&self, approval_id: &str) {
+        self.pending.lock().await.remove(approval_id);
+    }
+
+    /// Deliver a guardian's decision. `None` if no guarded approval has this id.
+    pub async fn decide(
+        &self,
+        approval_id: &str,
+        decision: &str,
+        decided_by: &str,
+        reason: Option<String>,
+    ) -> Result<Option<ApprovalDecision>> {
+        if !DECISIONS.contains(&decision) {
+            return Err(OfficeError::GovernanceError(format!("Unknown decision: {}", decision)));
+        }
+        let mut pending = self.pending.lock().await;
+        let Some(waiting) = pending.get(approval_id) else {
+            return Ok(None);
+        };
+        if waiting.approval.guardian_id != decided_by {
+            return Err(OfficeError::PermitDenied(format!(
+                "Only guardian {} can decide {}",
+                waiting.approval.guardian_id, approval_id
+            )));
+        }
+        let waiting = pending.remove(approval_id).expect("checked above");
+        let decision = ApprovalDecision {
+            approval_id: approval_id.to_string(),
+            job_id: waiting.approval.request.job_id.clone(),
+            decision: decision.to_string(),
+            decided_by: decided_by.to_string(),
+            decided_at: Utc::now(),
+            reason,
+        };
+        // The waiter may have timed out in the meantime; its rejection stands
+        let _ = waiting.tx.send(decision.clone());
+        Ok(Some(decision))
+    }
+
+    /// Approvals waiting on a guardian (all guardians if `None`)
+    pub async fn pending(&self, guardian_id: Option<&str>) -> Vec<GuardianApproval> {
+        let pending = self.pending.lock().await;
+        let mut approvals: Vec<GuardianApproval> = pending
+            .values()
+            .filter(|w| guardian_id.is_none_or(|g| w.approval.guardian_id == g))
+            .map(|w| w.approval.clone())
+            .collect();
+        approvals.sort_by_key(|a| a.request.requested_at);
+        approvals
+    }
+
+    /// Gate a mutation by `entity`: a no-op unless the entity is guarded,
+    /// otherwise ask its guardian, record request and outcome in the ledger,
+    /// and fail unless approved
+    pub async fn authorize(
+        &self,
+        ubl_client: &UblClient,
+        entity: &Entity,
+        mutation: GuardedMutation,
+    ) -> Result<Option<ApprovalDecision>> {
+        if entity.entity_type != EntityType::Guarded {
+            return Ok(None);
+        }
+
+        let pending = self.open(entity, mutation).await?;
+        let approval = pending.approval.clone();
+        let requested = serde_json::json!({
+            "type": "approval.requested",
+            "approval_id": approval.request.approval_id,
+            "job_id": approval.request.job_id,
+            "entity_id": entity.id,
+            "guardian_id": approval.guardian_id,
+            "title": approval.request.title,
+            "details": approval.request.details,
+            "impact": approval.request.impact,
+            "timestamp": approval.request.requested_at.to_rfc3339(),
+        });
+        if let Err(e) = ubl_client.publish_event(APPROVALS_CONTAINER, &requested).await {
+            self.cancel(&approval.request.approval_id).await;
+            return Err(OfficeError::PermitDenied(format!("Could not record approval request: {}", e)));
+        }
+        info!(
+            "🛡️ {} awaits guardian {} for {} ({})",
+            entity.id, approval.guardian_id, approval.request.title, approval.request.approval_id
+        );
+
+        let decision = self.wait(pending).await;
+        let decided = serde_json::json!({
+            "type": "approval.decided",
+            "approval_id": decision.approval_id,
+            "job_id": decision.job_id,
+            "entity_id": entity.id,
+            "guardian_id": approval.guardian_id,
+            "decision": decision.decision,
+            "decided_by": decision.decided_by,
+            "reason": decision.reason,
+            "timed_out": decision.decided_by == TIMEOUT_DECIDER,
+            "timestamp": decision.decided_at.to_rfc3339(),
+        });
+        ubl_client
+            .publish_event(APPROVALS_CONTAINER, &decided)
+            .await
+            .map_err(|e| OfficeError::PermitDenied(format!("Could not record guardian decision: {}", e)))?;
+
+        if !decision.is_approved() {
+            warn!("🛡️ {} blocked: {} by {}", approval.request.approval_id, decision.decision, decision.decided_by);
+            return Err(OfficeError::PermitDenied(format!(
+                "Guardian {} {} {}{}",
+                approval.guardian_id,
+                decision.decision,
+                approval.request.title,
+                decision.reason.as_deref().map(|r| format!(": {}", r)).unwrap_or_default()
+            )));
+        }
+        Ok(Some(decision))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::entity::EntityParams;
+
+    fn guarded(guardian_id: Option<&str>) -> Entity {
+        Entity::new(EntityParams {
+            name: "Guarded".to_string(),
+            entity_type: EntityType::Guarded,
+            guardian_id: guardian_id.map(str::to_string),
+            constitution: None,
+            baseline_narrative: None,
+            metadata: None,
+        })
+        .unwrap()
+    }
+
+    fn mutation() -> GuardedMutation {
+        GuardedMutation {
+            job_id: "job_1".to_string(),
+            title: "Deploy".to_string(),
+            details: vec![],
+            impact: "production".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guardian_decision_unblocks() {
+        let approvals = Arc::new(GuardianApprovals::new(Duration::from_secs(5)));
+        let pending = approvals.open(&guarded(Some("g1")), mutation()).await.unwrap();
+        let id = pending.approval.request.approval_id.clone();
+        assert_eq!(approvals.pending(Some("g1")).await.len(), 1);
+        assert!(approvals.pending(Some("g2")).await.is_empty());
+
+        let waiter = {
+            let approvals = approvals.clone();
+            tokio::spawn(async move { approvals.wait(pending).await })
+        };
+
+        // Only the entity's guardian may decide
+        assert!(matches!(
+            approvals.decide(&id, "approved", "g2", None).await,
+            Err(OfficeError::PermitDenied(_))
+        ));
+        let decided = approvals.decide(&id, "approved", "g1", None).await.unwrap().unwrap();
+        assert!(decided.is_approved());
+
+        let decision = waiter.await.unwrap();
+        assert_eq!(decision.decided_by, "g1");
+        assert!(approvals.pending(None).await.is_empty());
+        // Unknown (or already decided) ids are not guarded approvals
+        assert!(approvals.decide(&id, "approved", "g1", None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_rejects() {
+        let approvals = GuardianApprovals::new(Duration::from_millis(10));
+        let pending = approvals.open(&guarded(Some("g1")), mutation()).await.unwrap();
+
+        let decision = approvals.wait(pending).await;
+        assert!(decision.is_rejected());
+        assert_eq!(decision.decided_by, TIMEOUT_DECIDER);
+        assert!(approvals.pending(None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fail_closed() {
+        let approvals = GuardianApprovals::new(Duration::from_secs(5));
+        assert!(matches!(
+            approvals.open(&guarded(None), mutation()).await,
+            Err(OfficeError::PermitDenied(_))
+        ));
+
+        // Unreachable ledger: the request cannot be recorded, so nothing waits
+        let ubl = UblClient::with_generated_key("http://127.0.0.1:9", "office", 200);
+        let err = approvals.authorize(&ubl, &guarded(Some("g1")), mutation()).await;
+        assert!(matches!(err, Err(OfficeError::PermitDenied(_))));
+        assert!(approvals.pending(None).await.is_empty());
+
+        // Non-guarded entities pass through untouched
+        let mut free = guarded(None);
+        free.entity_type = EntityType::Autonomous;
+        assert!(approvals.authorize(&ubl, &free, mutation()).await.unwrap().is_none());
+    }
+}
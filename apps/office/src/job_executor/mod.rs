@@ -7,12 +7,16 @@
 //! - FSM: Strict state machine for job transitions
 //! - Cards: Formalize, Tracking, Finished cards
 //! - Executor: Orchestrates LLM execution with Chair context
+//! - Summarize: Transcript summaries of long conversations, requested by UBL
+//! - Guarded: Guardian approval gate for every mutation by a guarded entity
 
 pub mod types;
 pub mod fsm;
 pub mod cards;
 mod executor;
 mod conversation_context;
+pub mod summarize;
+pub mod guarded;
 
 pub use types::{
     Job, JobId, JobStatus, JobResult, JobProgress, JobStep,
@@ -25,6 +29,8 @@ pub use cards::{
 };
 pub use executor::JobExecutor;
 pub use conversation_context::ConversationContextBuilder;
+pub use summarize::{SummarizeJob, SummarizeRequest, SummarizeResponse};
+pub use guarded::{GuardianApprovals, GuardianApproval, GuardedMutation, PendingApproval};
 
 use crate::{OfficeError, Result};
 
@@ -0,0 +1,165 @@
+//! Transcript Summarization Job
+//!
+//! UBL's summarizer hands Office a segment of a long conversation (messages
+//! in ledger order, each with its `message.created` entry hash). Office
+//! summarizes it through the smart router and returns the text; UBL commits
+//! the `conversation.summarized` atom with the covered entry hashes, so the
+//! summary's provenance lives in the ledger, not here.
+
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{LlmMessage, LlmRequest, RoutingPreferences, SmartRouter, TaskType};
+use crate::{OfficeError, Result};
+
+/// Transcript characters sent to the model at most; older lines are dropped
+const MAX_TRANSCRIPT_CHARS: usize = 24_000;
+
+const SYSTEM_PROMPT: &str = "You summarize a segment of a team conversation for people who were not there. \
+Write a few short paragraphs or bullets: decisions taken, open questions, commitments (who owes what) \
+and anything another participant was asked to do. Refer to participants by their ids. \
+Do not invent facts that are not in the transcript.";
+
+/// One message of the segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentLine {
+    pub message_id: String,
+    pub from: String,
+    /// None when the content was not available to UBL
+    pub content: Option<String>,
+    pub entry_hash: String,
+    pub timestamp_ms: i64,
+}
+
+/// A segment to summarize
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizeRequest {
+    pub conversation_id: String,
+    pub tenant_id: String,
+    /// Text of the previous summary, so the new one can continue it
+    #[serde(default)]
+    pub previous_summary: Option<String>,
+    pub messages: Vec<SegmentLine>,
+}
+
+/// The written summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizeResponse {
+    pub job_id: String,
+    pub summary: String,
+    pub model: String,
+}
+
+/// Summarization job over one conversation segment
+pub struct SummarizeJob {
+    job_id: String,
+    request: SummarizeRequest,
+}
+
+impl SummarizeJob {
+    pub fn new(request: SummarizeRequest) -> Self {
+        Self {
+            job_id: format!("job_sum_{}", uuid::Uuid::new_v4().simple()),
+            request,
+        }
+    }
+
+    pub fn job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    /// Transcript lines oldest first, keeping the newest that fit the budget
+    pub fn transcript(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let mut used = 0;
+        for m in self.request.messages.iter().rev() {
+            let line = format!("[{}] {}: {}", m.timestamp_ms, m.from, m.content.as_deref().unwrap_or("(content unavailable)"));
+            if used + line.len() > MAX_TRANSCRIPT_CHARS && !lines.is_empty() {
+                break;
+            }
+            used += line.len() + 1;
+            lines.push(line);
+        }
+        lines.reverse();
+        lines.join("\n")
+    }
+
+    /// The user prompt: previous summary (if any), then the transcript
+    pub fn prompt(&self) -> String {
+        let mut prompt = String::new();
+        if let Some(previous) = &self.request.previous_summary {
+            prompt.push_str("Summary of the conversation so far:\n");
+            prompt.push_str(previous);
+            prompt.push_str("\n\n");
+        }
+        prompt.push_str(&format!(
+            "Summarize the following {} messages of conversation {}:\n\n",
+            self.request.messages.len(),
+            self.request.conversation_id
+        ));
+        prompt.push_str(&self.transcript());
+        prompt
+    }
+
+    /// Run the job through the smart router
+    pub async fn run(self, router: &SmartRouter) -> Result<SummarizeResponse> {
+        let request = LlmRequest::new(vec![LlmMessage::user(self.prompt())])
+            .with_system(SYSTEM_PROMPT)
+            .with_max_tokens(800)
+            .with_temperature(0.2);
+        let response = router.route(request, TaskType::Writing, &RoutingPreferences::default()).await?;
+
+        let summary = response.content.trim().to_string();
+        if summary.is_empty() {
+            return Err(OfficeError::LlmError("Model returned an empty summary".to_string()));
+        }
+
+        Ok(SummarizeResponse {
+            job_id: self.job_id,
+            summary,
+            model: response.model,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(i: i64, content: &str) -> SegmentLine {
+        SegmentLine {
+            message_id: format!("msg_{}", i),
+            from: "alice".to_string(),
+            content: Some(content.to_string()),
+            entry_hash: format!("0x{:02x}", i),
+            timestamp_ms: i,
+        }
+    }
+
+    #[test]
+    fn test_prompt_includes_previous_summary_and_transcript() {
+        let job = SummarizeJob::new(SummarizeRequest {
+            conversation_id: "conv_1".to_string(),
+            tenant_id: "t1".to_string(),
+            previous_summary: Some("Earlier: budget agreed.".to_string()),
+            messages: vec![line(1, "ship friday?"), line(2, "yes")],
+        });
+        let prompt = job.prompt();
+        assert!(prompt.starts_with("Summary of the conversation so far:\nEarlier: budget agreed."));
+        assert!(prompt.contains("2 messages of conversation conv_1"));
+        assert!(prompt.ends_with("[1] alice: ship friday?\n[2] alice: yes"));
+    }
+
+    #[test]
+    fn test_transcript_keeps_newest_lines() {
+        let long = "x".repeat(MAX_TRANSCRIPT_CHARS / 2);
+        let job = SummarizeJob::new(SummarizeRequest {
+            conversation_id: "conv_1".to_string(),
+            tenant_id: "t1".to_string(),
+            previous_summary: None,
+            messages: vec![line(1, &long), line(2, &long), line(3, "last")],
+        });
+        let transcript = job.transcript();
+        assert!(!transcript.starts_with("[1]"));
+        assert!(transcript.ends_with("[3] alice: last"));
+    }
+}
@@ -73,6 +73,7 @@ pub mod asc;
 pub mod routes;
 pub mod http_unix;
 pub mod mcp;
+pub mod secrets;
 
 // Builder function for tests (Prompt 2: Office integration tests)
 use axum::Router;
@@ -112,7 +113,7 @@ pub use ubl_client::UblClient;
 pub use llm::LlmProvider;
 pub use job_executor::{JobExecutor, Job, JobId, JobResult, ConversationContext};
 pub use task::{Task, TaskId, TaskStatus, TaskExecutor, TaskResult};
-pub use middleware::{PermitMiddleware, PermitRequest, PermitResponse, ConstitutionEnforcer};
+pub use middleware::{PermitMiddleware, PermitRequest, PermitResponse, PermitVerifier, ConstitutionEnforcer};
 pub use mcp::{McpRegistry, McpClient, McpConfig, ToolExecutor, OfficeMcpServer, UnifiedToolRegistry};
 
 use thiserror::Error;
@@ -132,6 +133,10 @@ pub enum OfficeError {
     #[error("UBL client error: {0}")]
     UblError(String),
 
+    /// UBL rejected the request; branch on `code`, not on `message`
+    #[error("UBL rejected request ({code}): {message}")]
+    UblRejected { code: ubl_errors::ErrorCode, message: String },
+
     #[error("LLM provider error: {0}")]
     LlmError(String),
 
@@ -144,6 +149,9 @@ pub enum OfficeError {
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(#[from] ConfigValidationError),
+
     #[error("Job transition error: {0}")]
     JobTransitionError(String),
 
@@ -159,6 +167,9 @@ pub enum OfficeError {
     #[error("Permit denied: {0}")]
     PermitDenied(String),
 
+    #[error("Token budget exhausted: {0}")]
+    BudgetExhausted(String),
+
     #[error("Constitution violation: {0}")]
     ConstitutionViolation(String),
 
@@ -172,10 +183,46 @@ pub enum OfficeError {
     IoError(#[from] std::io::Error),
 }
 
+impl OfficeError {
+    /// Canonical UBL error code, if UBL rejected the request
+    pub fn ubl_code(&self) -> Option<ubl_errors::ErrorCode> {
+        match self {
+            OfficeError::UblRejected { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl ubl_errors::HasErrorCode for OfficeError {
+    fn error_code(&self) -> ubl_errors::ErrorCode {
+        use ubl_errors::ErrorCode;
+        match self {
+            OfficeError::UblRejected { code, .. } => *code,
+            OfficeError::EntityNotFound(_) => ErrorCode::NotFound,
+            OfficeError::SessionError(_) | OfficeError::GovernanceError(_) => ErrorCode::BadRequest,
+            OfficeError::PermitDenied(_) => ErrorCode::PolicyDenied,
+            OfficeError::ConstitutionViolation(_) | OfficeError::PiiViolation(_) => ErrorCode::PolicyViolation,
+            OfficeError::BudgetExhausted(_) => ErrorCode::RateLimited,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, OfficeError>;
 
+/// A configuration value that parsed but is not acceptable
+#[derive(Error, Debug, PartialEq)]
+pub enum ConfigValidationError {
+    #[error("{field} is required")]
+    Missing { field: &'static str },
+
+    #[error("{field} is invalid: {reason}")]
+    Invalid { field: &'static str, reason: String },
+}
+
 /// Configuration for OFFICE runtime
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct OfficeConfig {
     /// Server configuration
     pub server: ServerConfig,
@@ -188,34 +235,105 @@ pub struct OfficeConfig {
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub cors_origins: Vec<String>,
+    /// Serve on this Unix socket instead of `host:port`, for a co-located
+    /// UBL (`OFFICE_URL=unix:///...`)
+    #[serde(default)]
+    pub unix_socket: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct UblConfig {
     pub endpoint: String,
+    /// Also this office's id: the audience UBL permits must be addressed to
     pub container_id: String,
     pub timeout_ms: u64,
+    /// UBL admin public keys (hex) trusted to sign permits; see
+    /// `GET /v1/policy/permit/keys`. Empty means no permit is accepted.
+    #[serde(default)]
+    pub permit_pubkeys: Vec<String>,
+    /// Office's service key (hex seed, literal or secret reference) signing
+    /// calls to UBL's internal routes. Empty: a new key each start.
+    #[serde(default)]
+    pub service_key: secrets::SecretString,
+    /// UBL service public keys (hex) trusted on `/v1/office/*`; UBL logs
+    /// its key at startup
+    #[serde(default)]
+    pub service_pubkeys: Vec<String>,
+    /// Refuse unsigned calls to `/v1/office/*`
+    #[serde(default)]
+    pub require_service_auth: bool,
+    /// PEM client certificate presented to UBL (mTLS), with `tls_client_key`
+    #[serde(default)]
+    pub tls_client_cert: Option<String>,
+    #[serde(default)]
+    pub tls_client_key: Option<String>,
+    /// PEM CA that UBL's certificate must chain to
+    #[serde(default)]
+    pub tls_ca_cert: Option<String>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct LlmConfig {
     pub provider: String,
-    pub api_key: String,
+    /// Literal key or a secret reference (`env:`, `file:`, `aws-sm:`, `gcp-sm:`),
+    /// resolved by [`OfficeConfig::load`]. Never printed by `Debug`.
+    pub api_key: secrets::SecretString,
     pub model: String,
     pub max_tokens: u32,
+    /// Tokens the model accepts per request; job prompts are condensed to
+    /// fit this minus `max_tokens`
+    #[serde(default = "default_context_window")]
+    pub context_window: u32,
     pub temperature: f32,
+    /// Seconds between provider health probes (0 disables them)
+    #[serde(default = "default_probe_interval_secs")]
+    pub probe_interval_secs: u64,
+}
+
+fn default_context_window() -> u32 {
+    200_000
+}
+
+fn default_probe_interval_secs() -> u64 {
+    30
+}
+
+impl std::fmt::Debug for LlmConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LlmConfig")
+            .field("provider", &self.provider)
+            .field("api_key", &secrets::mask(self.api_key.expose()))
+            .field("model", &self.model)
+            .field("max_tokens", &self.max_tokens)
+            .field("context_window", &self.context_window)
+            .field("temperature", &self.temperature)
+            .field("probe_interval_secs", &self.probe_interval_secs)
+            .finish()
+    }
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct GovernanceConfig {
     pub sanity_check_enabled: bool,
     pub dreaming_interval_hours: u32,
     pub dreaming_session_threshold: u32,
     pub simulation_required_risk_score: f32,
+    /// How long a guarded entity's mutation waits for its guardian before
+    /// it is rejected
+    #[serde(default = "default_guardian_approval_timeout_secs")]
+    pub guardian_approval_timeout_secs: u64,
+}
+
+fn default_guardian_approval_timeout_secs() -> u64 {
+    900
 }
 
 impl Default for OfficeConfig {
@@ -225,25 +343,256 @@ impl Default for OfficeConfig {
                 host: "0.0.0.0".to_string(),
                 port: 8080,
                 cors_origins: vec!["*".to_string()],
+                unix_socket: None,
             },
             ubl: UblConfig {
                 endpoint: "http://localhost:8080".to_string(),
                 container_id: "office".to_string(),
                 timeout_ms: 30000,
+                permit_pubkeys: vec![],
+                service_key: secrets::SecretString::default(),
+                service_pubkeys: vec![],
+                require_service_auth: false,
+                tls_client_cert: None,
+                tls_client_key: None,
+                tls_ca_cert: None,
             },
             llm: LlmConfig {
                 provider: "anthropic".to_string(),
-                api_key: String::new(),
+                api_key: secrets::SecretString::default(),
                 model: "claude-3-5-sonnet-20241022".to_string(),
                 max_tokens: 4096,
+                context_window: default_context_window(),
                 temperature: 0.7,
+                probe_interval_secs: default_probe_interval_secs(),
             },
             governance: GovernanceConfig {
                 sanity_check_enabled: true,
                 dreaming_interval_hours: 24,
                 dreaming_session_threshold: 50,
                 simulation_required_risk_score: 0.7,
+                guardian_approval_timeout_secs: default_guardian_approval_timeout_secs(),
             },
         }
     }
 }
+
+impl OfficeConfig {
+    /// Load configuration strictly, layering (lowest to highest precedence):
+    /// built-in defaults, the TOML file at `path`, and `OFFICE__*` env vars.
+    ///
+    /// Unknown keys and type mismatches are errors; nothing falls back silently.
+    pub fn load(path: &str, file_required: bool) -> Result<Self> {
+        let defaults = config::Config::try_from(&Self::default())
+            .map_err(|e| OfficeError::ConfigError(e.to_string()))?;
+        let mut config: Self = config::Config::builder()
+            .add_source(defaults)
+            .add_source(config::File::with_name(path).required(file_required))
+            .add_source(config::Environment::with_prefix("OFFICE").separator("__"))
+            .build()
+            .and_then(|c| c.try_deserialize())
+            .map_err(|e| OfficeError::ConfigError(e.to_string()))?;
+        config.llm.api_key = secrets::resolve(config.llm.api_key.expose())?.into();
+        if !config.ubl.service_key.is_empty() {
+            config.ubl.service_key = secrets::resolve(config.ubl.service_key.expose())?.into();
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check semantic constraints that deserialization cannot express
+    pub fn validate(&self) -> std::result::Result<(), ConfigValidationError> {
+        use ConfigValidationError::{Invalid, Missing};
+
+        if self.server.host.trim().is_empty() {
+            return Err(Missing { field: "server.host" });
+        }
+        if self.server.port == 0 {
+            return Err(Invalid { field: "server.port", reason: "must be 1-65535".into() });
+        }
+        if self.server.unix_socket.as_deref().is_some_and(|path| !path.starts_with('/')) {
+            return Err(Invalid { field: "server.unix_socket", reason: "socket path must be absolute".into() });
+        }
+
+        match reqwest::Url::parse(&self.ubl.endpoint) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(url) if url.scheme() == "unix" => {
+                if ubl_client::unix_socket_path(&self.ubl.endpoint).is_none() {
+                    return Err(Invalid { field: "ubl.endpoint", reason: "unix:// endpoint needs an absolute socket path".into() });
+                }
+            }
+            Ok(url) => return Err(Invalid { field: "ubl.endpoint", reason: format!("unsupported scheme {:?}", url.scheme()) }),
+            Err(e) => return Err(Invalid { field: "ubl.endpoint", reason: e.to_string() }),
+        }
+        if self.ubl.container_id.trim().is_empty() {
+            return Err(Missing { field: "ubl.container_id" });
+        }
+        if self.ubl.timeout_ms == 0 {
+            return Err(Invalid { field: "ubl.timeout_ms", reason: "must be greater than zero".into() });
+        }
+        if let Some(key) = self.ubl.permit_pubkeys.iter().find(|k| k.len() != 64 || hex::decode(k).is_err()) {
+            return Err(Invalid { field: "ubl.permit_pubkeys", reason: format!("{:?} is not a 32-byte hex key", key) });
+        }
+        if !self.ubl.service_key.is_empty() && ubl_kernel::signing_key_from_hex(self.ubl.service_key.expose().as_bytes()).is_err() {
+            return Err(Invalid { field: "ubl.service_key", reason: "not a 32-byte hex seed".into() });
+        }
+        if let Some(key) = self.ubl.service_pubkeys.iter().find(|k| k.len() != 64 || hex::decode(k).is_err()) {
+            return Err(Invalid { field: "ubl.service_pubkeys", reason: format!("{:?} is not a 32-byte hex key", key) });
+        }
+        if self.ubl.require_service_auth && self.ubl.service_pubkeys.is_empty() {
+            return Err(Missing { field: "ubl.service_pubkeys" });
+        }
+        if self.ubl.tls_client_cert.is_some() != self.ubl.tls_client_key.is_some() {
+            return Err(Invalid { field: "ubl.tls_client_cert", reason: "tls_client_cert and tls_client_key must be set together".into() });
+        }
+
+        let provider = self.llm.provider.to_lowercase();
+        let needs_key = match provider.as_str() {
+            "anthropic" | "claude" | "openai" | "gpt" | "gemini" | "google" => true,
+            "local" | "mock" => false,
+            other => return Err(Invalid { field: "llm.provider", reason: format!("unknown provider {:?}", other) }),
+        };
+        if needs_key && self.llm.api_key.expose().trim().is_empty() {
+            return Err(Missing { field: "llm.api_key" });
+        }
+        if self.llm.max_tokens == 0 {
+            return Err(Invalid { field: "llm.max_tokens", reason: "must be greater than zero".into() });
+        }
+        if self.llm.context_window <= self.llm.max_tokens {
+            return Err(Invalid { field: "llm.context_window", reason: "must be greater than llm.max_tokens".into() });
+        }
+        if !(0.0..=2.0).contains(&self.llm.temperature) {
+            return Err(Invalid { field: "llm.temperature", reason: "must be within 0.0-2.0".into() });
+        }
+
+        if !(0.0..=1.0).contains(&self.governance.simulation_required_risk_score) {
+            return Err(Invalid { field: "governance.simulation_required_risk_score", reason: "must be within 0.0-1.0".into() });
+        }
+        if self.governance.guardian_approval_timeout_secs == 0 {
+            return Err(Invalid { field: "governance.guardian_approval_timeout_secs", reason: "must be greater than zero".into() });
+        }
+
+        Ok(())
+    }
+
+    /// Effective configuration as JSON with secrets masked, for startup logs
+    pub fn redacted(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(key) = value.pointer_mut("/llm/api_key") {
+            *key = serde_json::Value::String(secrets::mask(self.llm.api_key.expose()));
+        }
+        if let Some(key) = value.pointer_mut("/ubl/service_key") {
+            *key = serde_json::Value::String(secrets::mask(self.ubl.service_key.expose()));
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_with_local_provider_is_valid() {
+        let mut config = OfficeConfig::default();
+        config.llm.provider = "local".into();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_hosted_provider_requires_api_key() {
+        let config = OfficeConfig::default();
+        assert_eq!(config.validate(), Err(ConfigValidationError::Missing { field: "llm.api_key" }));
+    }
+
+    #[test]
+    fn test_invalid_endpoint_rejected() {
+        let mut config = OfficeConfig::default();
+        config.llm.provider = "mock".into();
+        config.ubl.endpoint = "ubl:3000".into();
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "ubl.endpoint", .. })));
+        config.ubl.endpoint = "unix://ubl.sock".into();
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "ubl.endpoint", .. })));
+        config.ubl.endpoint = "unix:///run/ubl/ubl.sock".into();
+        assert!(config.validate().is_ok());
+
+        config.server.unix_socket = Some("office.sock".into());
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "server.unix_socket", .. })));
+        config.server.unix_socket = Some("/run/office/office.sock".into());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_permit_pubkeys_must_be_hex_keys() {
+        let mut config = OfficeConfig::default();
+        config.llm.provider = "mock".into();
+        config.ubl.permit_pubkeys = vec!["ab".repeat(32)];
+        assert!(config.validate().is_ok());
+        config.ubl.permit_pubkeys.push("not-a-key".into());
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "ubl.permit_pubkeys", .. })));
+    }
+
+    #[test]
+    fn test_service_auth_settings() {
+        let mut config = OfficeConfig::default();
+        config.llm.provider = "mock".into();
+        config.ubl.require_service_auth = true;
+        assert_eq!(config.validate(), Err(ConfigValidationError::Missing { field: "ubl.service_pubkeys" }));
+
+        config.ubl.service_pubkeys = vec!["cd".repeat(32)];
+        config.ubl.service_key = "ef".repeat(32).into();
+        assert!(config.validate().is_ok());
+        assert!(!config.redacted().to_string().contains(&"ef".repeat(32)));
+
+        config.ubl.service_key = "not-a-seed".into();
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "ubl.service_key", .. })));
+
+        config.ubl.service_key = Default::default();
+        config.ubl.tls_client_cert = Some("/etc/office/client.pem".into());
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "ubl.tls_client_cert", .. })));
+    }
+
+    #[test]
+    fn test_context_window_must_leave_room_for_prompt() {
+        let mut config = OfficeConfig::default();
+        config.llm.provider = "mock".into();
+        config.llm.context_window = config.llm.max_tokens;
+        assert!(matches!(config.validate(), Err(ConfigValidationError::Invalid { field: "llm.context_window", .. })));
+    }
+
+    #[test]
+    fn test_guardian_timeout_must_be_positive() {
+        let mut config = OfficeConfig::default();
+        config.llm.provider = "mock".into();
+        config.governance.guardian_approval_timeout_secs = 0;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigValidationError::Invalid { field: "governance.guardian_approval_timeout_secs", .. })
+        ));
+    }
+
+    #[test]
+    fn test_shipped_development_config_loads() {
+        let config = OfficeConfig::load("config/development", true).unwrap();
+        assert_eq!(config.llm.provider, "local");
+    }
+
+    #[test]
+    fn test_unknown_fields_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("office.toml");
+        std::fs::write(&path, "[ubl]\ndefault_container = \"office\"\n").unwrap();
+        let err = OfficeConfig::load(path.to_str().unwrap(), true).unwrap_err();
+        assert!(err.to_string().contains("default_container"), "{}", err);
+    }
+
+    #[test]
+    fn test_redacted_masks_api_key() {
+        let mut config = OfficeConfig::default();
+        config.llm.api_key = "sk-secret".into();
+        let dump = config.redacted().to_string();
+        assert!(!dump.contains("sk-secret"));
+        assert!(dump.contains("***"));
+        assert!(!format!("{:?}", config).contains("sk-secret"));
+    }
+}
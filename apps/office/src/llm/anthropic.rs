@@ -5,11 +5,12 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use super::provider::{LlmProvider, LlmRequest, LlmResponse, LlmUsage, MessageRole};
+use crate::secrets::SecretString;
 use crate::{OfficeError, Result};
 
 /// Anthropic Claude provider
 pub struct AnthropicProvider {
-    api_key: String,
+    api_key: SecretString,
     model: String,
     max_tokens: u32,
     temperature: f32,
@@ -19,7 +20,7 @@ pub struct AnthropicProvider {
 impl AnthropicProvider {
     pub fn new(api_key: &str, model: &str, max_tokens: u32, temperature: f32) -> Self {
         Self {
-            api_key: api_key.to_string(),
+            api_key: SecretString::from(api_key),
             model: model.to_string(),
             max_tokens,
             temperature,
@@ -74,6 +75,10 @@ impl LlmProvider for AnthropicProvider {
         "anthropic"
     }
 
+    fn model(&self) -> &str {
+        &self.model
+    }
+
     async fn chat(&self, request: LlmRequest) -> Result<LlmResponse> {
         // Convert messages
         let mut system = request.system;
@@ -108,7 +113,7 @@ impl LlmProvider for AnthropicProvider {
 
         let response = self.client
             .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", &self.api_key)
+            .header("x-api-key", self.api_key.expose())
             .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
             .json(&anthropic_request)
@@ -146,4 +151,18 @@ impl LlmProvider for AnthropicProvider {
     async fn is_available(&self) -> bool {
         !self.api_key.is_empty()
     }
+
+    async fn probe(&self) -> Result<()> {
+        let response = self.client
+            .get(format!("https://api.anthropic.com/v1/models/{}", self.model))
+            .header("x-api-key", self.api_key.expose())
+            .header("anthropic-version", "2023-06-01")
+            .send()
+            .await
+            .map_err(|e| OfficeError::LlmError(format!("Probe failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(OfficeError::LlmError(format!("Probe failed: {}", response.status())));
+        }
+        Ok(())
+    }
 }
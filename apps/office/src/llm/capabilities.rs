@@ -0,0 +1,264 @@
+//! Provider Capability Registry
+//!
+//! What each provider/model can do: tool calling, streaming, JSON mode,
+//! vision and context size. The SmartRouter consults it to skip models that
+//! cannot serve a task, instead of finding out when the call fails.
+//!
+//! Entries start from a built-in table matched by model prefix and are
+//! refreshed from provider metadata where the API exposes it.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::provider::LlmProvider;
+use super::router::TaskType;
+
+/// Where a capability entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilitySource {
+    /// Built-in table, matched by model prefix
+    Builtin,
+    /// Reported by the provider's model metadata
+    Detected,
+    /// Set explicitly by the operator
+    Configured,
+    /// Model not in the table; assume plain chat only
+    Unknown,
+}
+
+/// What a model supports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    pub tool_calling: bool,
+    pub streaming: bool,
+    pub json_mode: bool,
+    pub vision: bool,
+    /// Prompt plus completion, in tokens
+    pub max_context_tokens: u32,
+    pub source: CapabilitySource,
+}
+
+impl ModelCapabilities {
+    const fn builtin(tool_calling: bool, streaming: bool, json_mode: bool, vision: bool, max_context_tokens: u32) -> Self {
+        Self {
+            tool_calling,
+            streaming,
+            json_mode,
+            vision,
+            max_context_tokens,
+            source: CapabilitySource::Builtin,
+        }
+    }
+
+    /// Conservative entry for models we know nothing about
+    pub fn unknown() -> Self {
+        Self {
+            source: CapabilitySource::Unknown,
+            ..Self::builtin(false, false, false, false, 8_192)
+        }
+    }
+
+    /// Requirements this model does not meet (empty when it can serve them)
+    pub fn missing(&self, req: &CapabilityRequirements) -> Vec<String> {
+        let mut missing = Vec::new();
+        if req.tool_calling && !self.tool_calling {
+            missing.push("tool_calling".to_string());
+        }
+        if req.streaming && !self.streaming {
+            missing.push("streaming".to_string());
+        }
+        if req.json_mode && !self.json_mode {
+            missing.push("json_mode".to_string());
+        }
+        if req.vision && !self.vision {
+            missing.push("vision".to_string());
+        }
+        if req.min_context_tokens > self.max_context_tokens {
+            missing.push(format!(
+                "context {} > {} tokens",
+                req.min_context_tokens, self.max_context_tokens
+            ));
+        }
+        missing
+    }
+
+    pub fn satisfies(&self, req: &CapabilityRequirements) -> bool {
+        self.missing(req).is_empty()
+    }
+}
+
+/// What a request needs from the model that serves it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapabilityRequirements {
+    pub tool_calling: bool,
+    pub streaming: bool,
+    pub json_mode: bool,
+    pub vision: bool,
+    pub min_context_tokens: u32,
+}
+
+impl CapabilityRequirements {
+    /// Baseline needs of a task type
+    pub fn for_task(task: TaskType) -> Self {
+        match task {
+            // Coding and multi-step work drive tools (MCP, git, builds)
+            TaskType::Coding | TaskType::Complex => Self {
+                tool_calling: true,
+                ..Self::default()
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Union of both sets of requirements
+    pub fn merge(mut self, other: &Self) -> Self {
+        self.tool_calling |= other.tool_calling;
+        self.streaming |= other.streaming;
+        self.json_mode |= other.json_mode;
+        self.vision |= other.vision;
+        self.min_context_tokens = self.min_context_tokens.max(other.min_context_tokens);
+        self
+    }
+
+    pub fn with_min_context(mut self, tokens: u32) -> Self {
+        self.min_context_tokens = self.min_context_tokens.max(tokens);
+        self
+    }
+}
+
+/// Built-in capabilities by provider and model prefix; the longest prefix wins
+const BUILTIN: &[(&str, &str, ModelCapabilities)] = &[
+    ("anthropic", "claude-", ModelCapabilities::builtin(true, true, false, true, 200_000)),
+    ("anthropic", "claude-3-5-haiku", ModelCapabilities::builtin(true, true, false, false, 200_000)),
+    ("anthropic", "claude-2", ModelCapabilities::builtin(false, true, false, false, 100_000)),
+    ("openai", "gpt-4o", ModelCapabilities::builtin(true, true, true, true, 128_000)),
+    ("openai", "gpt-4-turbo", ModelCapabilities::builtin(true, true, true, true, 128_000)),
+    ("openai", "gpt-4", ModelCapabilities::builtin(true, true, false, false, 8_192)),
+    ("openai", "gpt-3.5-turbo", ModelCapabilities::builtin(true, true, true, false, 16_385)),
+    ("openai", "o1", ModelCapabilities::builtin(false, false, false, false, 128_000)),
+    ("gemini", "gemini-", ModelCapabilities::builtin(true, true, true, true, 32_768)),
+    ("gemini", "gemini-1.5-flash", ModelCapabilities::builtin(true, true, true, true, 1_048_576)),
+    ("gemini", "gemini-1.5-pro", ModelCapabilities::builtin(true, true, true, true, 2_097_152)),
+    // The mock answers any request shape; vision is the one thing it cannot fake
+    ("local", "", ModelCapabilities::builtin(true, true, true, false, 128_000)),
+];
+
+/// Built-in entry for a provider/model, or `unknown()`
+pub fn builtin_capabilities(provider: &str, model: &str) -> ModelCapabilities {
+    BUILTIN
+        .iter()
+        .filter(|(p, prefix, _)| *p == provider && model.starts_with(prefix))
+        .max_by_key(|(_, prefix, _)| prefix.len())
+        .map(|(_, _, caps)| caps.clone())
+        .unwrap_or_else(ModelCapabilities::unknown)
+}
+
+/// Capabilities per provider/model, overriding the built-in table
+pub struct CapabilityRegistry {
+    entries: RwLock<HashMap<(String, String), ModelCapabilities>>,
+}
+
+impl CapabilityRegistry {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Current capabilities of a provider/model
+    pub async fn get(&self, provider: &str, model: &str) -> ModelCapabilities {
+        let entries = self.entries.read().await;
+        entries
+            .get(&(provider.to_string(), model.to_string()))
+            .cloned()
+            .unwrap_or_else(|| builtin_capabilities(provider, model))
+    }
+
+    /// Override an entry (operator configuration)
+    pub async fn set(&self, provider: &str, model: &str, mut caps: ModelCapabilities) {
+        caps.source = CapabilitySource::Configured;
+        self.entries
+            .write()
+            .await
+            .insert((provider.to_string(), model.to_string()), caps);
+    }
+
+    /// Re-read what the provider reports about its model
+    pub async fn refresh(&self, provider: &dyn LlmProvider) -> ModelCapabilities {
+        let (name, model) = (provider.name(), provider.model());
+        let base = self.get(name, model).await;
+        if base.source == CapabilitySource::Configured {
+            return base;
+        }
+        match provider.capabilities(&base).await {
+            Some(mut detected) => {
+                detected.source = CapabilitySource::Detected;
+                info!(provider = name, model, ?detected, "🔎 Model capabilities detected");
+                self.entries
+                    .write()
+                    .await
+                    .insert((name.to_string(), model.to_string()), detected.clone());
+                detected
+            }
+            None => {
+                if base.source == CapabilitySource::Unknown {
+                    warn!(provider = name, model, "⚠️ Unknown model, assuming plain chat only");
+                }
+                base
+            }
+        }
+    }
+}
+
+impl Default for CapabilityRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_longest_prefix() {
+        let sonnet = builtin_capabilities("anthropic", "claude-3-5-sonnet-20241022");
+        assert!(sonnet.vision && sonnet.tool_calling);
+        assert!(!builtin_capabilities("anthropic", "claude-3-5-haiku-20241022").vision);
+
+        assert_eq!(builtin_capabilities("openai", "gpt-4o-mini").max_context_tokens, 128_000);
+        assert_eq!(builtin_capabilities("openai", "gpt-4-0613").max_context_tokens, 8_192);
+        assert_eq!(builtin_capabilities("openai", "davinci").source, CapabilitySource::Unknown);
+        // Same model name under another provider does not match
+        assert_eq!(builtin_capabilities("gemini", "claude-3").source, CapabilitySource::Unknown);
+    }
+
+    #[test]
+    fn test_missing_requirements() {
+        let caps = builtin_capabilities("openai", "o1-preview");
+        let req = CapabilityRequirements::for_task(TaskType::Coding).with_min_context(200_000);
+        assert_eq!(caps.missing(&req), vec!["tool_calling", "context 200000 > 128000 tokens"]);
+        assert!(caps.satisfies(&CapabilityRequirements::for_task(TaskType::Writing)));
+
+        let merged = CapabilityRequirements::default().merge(&CapabilityRequirements { vision: true, ..Default::default() });
+        assert!(merged.vision && !merged.tool_calling);
+    }
+
+    #[tokio::test]
+    async fn test_configured_overrides_builtin() {
+        let registry = CapabilityRegistry::new();
+        let mut caps = registry.get("openai", "gpt-4").await;
+        assert!(!caps.json_mode);
+
+        caps.json_mode = true;
+        registry.set("openai", "gpt-4", caps).await;
+        let caps = registry.get("openai", "gpt-4").await;
+        assert!(caps.json_mode);
+        assert_eq!(caps.source, CapabilitySource::Configured);
+    }
+}
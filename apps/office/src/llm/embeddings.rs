@@ -0,0 +1,518 @@
+//! Embeddings and the shared embedding cache
+//!
+//! Vector memory embeds ledger text (event summaries, handovers, messages),
+//! and much of it is identical across the entities of a tenant. Embedding is
+//! billed per call, so vectors are cached by content hash:
+//!
+//! - Key: tenant + model name + model version + blake3(text). The entity is
+//!   not part of the key, so every entity of a tenant shares one vector per
+//!   text. Tenants never share entries.
+//! - The model version is part of the key: after an upgrade, vectors from the
+//!   old model are never returned. `purge_stale_versions` drops them eagerly.
+//! - Entries expire after a TTL and the oldest are evicted past `max_entries`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::observability::{inc, EMBEDDING_CACHE};
+use crate::secrets::SecretString;
+use crate::{OfficeError, Result};
+
+/// An embedding model; vectors from different versions are never mixed
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EmbeddingModel {
+    pub name: String,
+    /// Bumped whenever the vectors change for the same input
+    pub version: String,
+    pub dimensions: usize,
+}
+
+impl EmbeddingModel {
+    pub fn new(name: impl Into<String>, version: impl Into<String>, dimensions: usize) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            dimensions,
+        }
+    }
+
+    /// `name@version`, as used in metrics and logs
+    pub fn key(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+/// A provider that turns text into vectors
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn model(&self) -> &EmbeddingModel;
+
+    /// Embed a batch; the result has one vector per input, in order
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+// ============================================================================
+// PROVIDERS
+// ============================================================================
+
+/// OpenAI embeddings endpoint
+pub struct OpenAIEmbeddings {
+    api_key: SecretString,
+    model: EmbeddingModel,
+    client: Client,
+}
+
+impl OpenAIEmbeddings {
+    pub fn new(api_key: &str, model: EmbeddingModel) -> Self {
+        Self {
+            api_key: SecretString::from(api_key),
+            model,
+            client: Client::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAIEmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    dimensions: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIEmbeddingResponse {
+    data: Vec<OpenAIEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddings {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn model(&self) -> &EmbeddingModel {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let request = OpenAIEmbeddingRequest {
+            model: &self.model.name,
+            input: texts,
+            dimensions: self.model.dimensions,
+        };
+
+        let response = self.client
+            .post("https://api.openai.com/v1/embeddings")
+            .header("Authorization", format!("Bearer {}", self.api_key.expose()))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| OfficeError::LlmError(format!("Embedding request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(OfficeError::LlmError(format!("Embedding API error: {}", error_text)));
+        }
+
+        let mut body: OpenAIEmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| OfficeError::LlmError(format!("Failed to parse embeddings: {}", e)))?;
+        body.data.sort_by_key(|d| d.index);
+        Ok(body.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+/// Deterministic hash-derived vectors, for development and tests
+pub struct LocalEmbeddings {
+    model: EmbeddingModel,
+}
+
+impl LocalEmbeddings {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            model: EmbeddingModel::new("local-hash", "1", dimensions),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbeddings {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn model(&self) -> &EmbeddingModel {
+        &self.model
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|text| {
+                let mut reader = blake3::Hasher::new().update(text.as_bytes()).finalize_xof();
+                (0..self.model.dimensions)
+                    .map(|_| {
+                        let mut byte = [0u8; 1];
+                        reader.fill(&mut byte);
+                        byte[0] as f32 / 127.5 - 1.0
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+// ============================================================================
+// CACHE
+// ============================================================================
+
+/// Embedding cache limits
+#[derive(Debug, Clone)]
+pub struct EmbeddingCacheConfig {
+    pub ttl: Duration,
+    pub max_entries: usize,
+}
+
+impl Default for EmbeddingCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(7 * 24 * 3600),
+            max_entries: 100_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    tenant_id: String,
+    model: String,
+    version: String,
+    content_hash: [u8; 32],
+}
+
+impl CacheKey {
+    fn new(tenant_id: &str, model: &EmbeddingModel, text: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            model: model.name.clone(),
+            version: model.version.clone(),
+            content_hash: *blake3::hash(text.as_bytes()).as_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedVector {
+    vector: Arc<Vec<f32>>,
+    stored_at: Instant,
+}
+
+/// Cache counters since startup
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EmbeddingCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Content-addressed embedding cache shared by all entities of a tenant
+pub struct EmbeddingCache {
+    config: EmbeddingCacheConfig,
+    entries: RwLock<HashMap<CacheKey, CachedVector>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EmbeddingCache {
+    pub fn new(config: EmbeddingCacheConfig) -> Self {
+        Self {
+            config,
+            entries: RwLock::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cached vector for this text, unless missing or past its TTL
+    pub async fn get(&self, tenant_id: &str, model: &EmbeddingModel, text: &str) -> Option<Arc<Vec<f32>>> {
+        let key = CacheKey::new(tenant_id, model, text);
+        let found = {
+            let entries = self.entries.read().await;
+            entries
+                .get(&key)
+                .filter(|e| e.stored_at.elapsed() < self.config.ttl)
+                .map(|e| e.vector.clone())
+        };
+        self.count(model, found.is_some());
+        found
+    }
+
+    pub async fn put(&self, tenant_id: &str, model: &EmbeddingModel, text: &str, vector: Arc<Vec<f32>>) {
+        let key = CacheKey::new(tenant_id, model, text);
+        let mut entries = self.entries.write().await;
+        if entries.len() >= self.config.max_entries && !entries.contains_key(&key) {
+            let ttl = self.config.ttl;
+            entries.retain(|_, e| e.stored_at.elapsed() < ttl);
+            if entries.len() >= self.config.max_entries {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.stored_at)
+                    .map(|(k, _)| k.clone())
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, CachedVector { vector, stored_at: Instant::now() });
+    }
+
+    /// Drop vectors of this model computed by any other version; returns how many
+    pub async fn purge_stale_versions(&self, current: &EmbeddingModel) -> usize {
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|k, _| k.model != current.name || k.version == current.version);
+        before - entries.len()
+    }
+
+    /// Drop everything cached for a tenant (e.g. on tenant deletion)
+    pub async fn purge_tenant(&self, tenant_id: &str) -> usize {
+        let mut entries = self.entries.write().await;
+        let before = entries.len();
+        entries.retain(|k, _| k.tenant_id != tenant_id);
+        before - entries.len()
+    }
+
+    pub async fn stats(&self) -> EmbeddingCacheStats {
+        EmbeddingCacheStats {
+            entries: self.entries.read().await.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn count(&self, model: &EmbeddingModel, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        inc(&EMBEDDING_CACHE, &[&model.key(), if hit { "hit" } else { "miss" }]);
+    }
+}
+
+impl Default for EmbeddingCache {
+    fn default() -> Self {
+        Self::new(EmbeddingCacheConfig::default())
+    }
+}
+
+// ============================================================================
+// CACHED EMBEDDER
+// ============================================================================
+
+/// Embeds through the cache; only texts never seen by the tenant reach the provider
+pub struct CachedEmbedder {
+    provider: Arc<dyn EmbeddingProvider>,
+    cache: Arc<EmbeddingCache>,
+}
+
+impl CachedEmbedder {
+    pub fn new(provider: Arc<dyn EmbeddingProvider>, cache: Arc<EmbeddingCache>) -> Self {
+        Self { provider, cache }
+    }
+
+    pub fn model(&self) -> &EmbeddingModel {
+        self.provider.model()
+    }
+
+    pub fn cache(&self) -> &Arc<EmbeddingCache> {
+        &self.cache
+    }
+
+    /// Embed texts for a tenant, one vector per input in order.
+    ///
+    /// Identical texts in the batch are embedded once, and the provider is
+    /// called once for all misses.
+    pub async fn embed(&self, tenant_id: &str, texts: &[String]) -> Result<Vec<Arc<Vec<f32>>>> {
+        let model = self.provider.model().clone();
+        let mut resolved: HashMap<String, Arc<Vec<f32>>> = HashMap::new();
+        let mut missing: Vec<String> = Vec::new();
+
+        for text in texts {
+            if resolved.contains_key(text) || missing.contains(text) {
+                continue;
+            }
+            match self.cache.get(tenant_id, &model, text).await {
+                Some(vector) => {
+                    resolved.insert(text.clone(), vector);
+                }
+                None => missing.push(text.clone()),
+            }
+        }
+
+        if !missing.is_empty() {
+            let vectors = self.provider.embed(&missing).await?;
+            if vectors.len() != missing.len() {
+                return Err(OfficeError::LlmError(format!(
+                    "{} returned {} embeddings for {} inputs",
+                    self.provider.name(),
+                    vectors.len(),
+                    missing.len()
+                )));
+            }
+            for (text, vector) in missing.into_iter().zip(vectors) {
+                if vector.len() != model.dimensions {
+                    return Err(OfficeError::LlmError(format!(
+                        "{} returned a {}-dimension vector, expected {}",
+                        model.key(),
+                        vector.len(),
+                        model.dimensions
+                    )));
+                }
+                let vector = Arc::new(vector);
+                self.cache.put(tenant_id, &model, &text, vector.clone()).await;
+                resolved.insert(text, vector);
+            }
+        }
+
+        Ok(texts.iter().map(|t| resolved[t].clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records every batch it is asked to embed
+    struct CountingProvider {
+        model: EmbeddingModel,
+        calls: Mutex<Vec<Vec<String>>>,
+    }
+
+    impl CountingProvider {
+        fn new(version: &str) -> Self {
+            Self {
+                model: EmbeddingModel::new("test-embed", version, 4),
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn embedded(&self) -> Vec<String> {
+            self.calls.lock().unwrap().iter().flatten().cloned().collect()
+        }
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for CountingProvider {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn model(&self) -> &EmbeddingModel {
+            &self.model
+        }
+
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.calls.lock().unwrap().push(texts.to_vec());
+            Ok(texts.iter().map(|t| vec![t.len() as f32; 4]).collect())
+        }
+    }
+
+    fn texts(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_dedup_within_batch_and_across_entities() {
+        let provider = Arc::new(CountingProvider::new("1"));
+        let cache = Arc::new(EmbeddingCache::default());
+        // Two entities of the same tenant, each with its own embedder
+        let alice = CachedEmbedder::new(provider.clone(), cache.clone());
+        let bob = CachedEmbedder::new(provider.clone(), cache.clone());
+
+        let out = alice.embed("t1", &texts(&["deploy done", "deploy done", "ci red"])).await.unwrap();
+        assert_eq!(out.len(), 3);
+        assert!(Arc::ptr_eq(&out[0], &out[1]));
+        assert_eq!(provider.calls.lock().unwrap().len(), 1);
+
+        bob.embed("t1", &texts(&["ci red", "new text"])).await.unwrap();
+        assert_eq!(provider.embedded(), texts(&["deploy done", "ci red", "new text"]));
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.entries, 3);
+        assert_eq!(stats.hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_do_not_share() {
+        let provider = Arc::new(CountingProvider::new("1"));
+        let embedder = CachedEmbedder::new(provider.clone(), Arc::new(EmbeddingCache::default()));
+
+        embedder.embed("t1", &texts(&["same"])).await.unwrap();
+        embedder.embed("t2", &texts(&["same"])).await.unwrap();
+        assert_eq!(provider.embedded().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_model_upgrade_invalidates() {
+        let cache = Arc::new(EmbeddingCache::default());
+        let v1 = Arc::new(CountingProvider::new("1"));
+        let v2 = Arc::new(CountingProvider::new("2"));
+
+        CachedEmbedder::new(v1.clone(), cache.clone()).embed("t1", &texts(&["hello"])).await.unwrap();
+        CachedEmbedder::new(v2.clone(), cache.clone()).embed("t1", &texts(&["hello"])).await.unwrap();
+        assert_eq!(v2.embedded(), texts(&["hello"]));
+
+        assert_eq!(cache.purge_stale_versions(&v2.model).await, 1);
+        assert!(cache.get("t1", &v2.model, "hello").await.is_some());
+        assert!(cache.get("t1", &v1.model, "hello").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ttl_and_capacity() {
+        let model = EmbeddingModel::new("m", "1", 1);
+        let expiring = EmbeddingCache::new(EmbeddingCacheConfig { ttl: Duration::ZERO, max_entries: 10 });
+        expiring.put("t1", &model, "a", Arc::new(vec![1.0])).await;
+        assert!(expiring.get("t1", &model, "a").await.is_none());
+
+        let small = EmbeddingCache::new(EmbeddingCacheConfig { ttl: Duration::from_secs(60), max_entries: 2 });
+        small.put("t1", &model, "a", Arc::new(vec![1.0])).await;
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        small.put("t1", &model, "b", Arc::new(vec![2.0])).await;
+        small.put("t1", &model, "c", Arc::new(vec![3.0])).await;
+        assert_eq!(small.stats().await.entries, 2);
+        assert!(small.get("t1", &model, "a").await.is_none());
+        assert!(small.get("t1", &model, "c").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dimension_mismatch_is_not_cached() {
+        let provider = Arc::new(LocalEmbeddings::new(8));
+        let cache = Arc::new(EmbeddingCache::default());
+        let embedder = CachedEmbedder::new(provider, cache.clone());
+        let out = embedder.embed("t1", &texts(&["x"])).await.unwrap();
+        assert_eq!(out[0].len(), 8);
+
+        let mut wrong = CountingProvider::new("1");
+        wrong.model.dimensions = 3;
+        let err = CachedEmbedder::new(Arc::new(wrong), cache.clone())
+            .embed("t1", &texts(&["y"]))
+            .await;
+        assert!(err.is_err());
+        assert_eq!(cache.stats().await.entries, 1);
+    }
+}
@@ -4,12 +4,14 @@ use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+use super::capabilities::ModelCapabilities;
 use super::provider::{LlmProvider, LlmRequest, LlmResponse, LlmUsage, MessageRole};
+use crate::secrets::SecretString;
 use crate::{OfficeError, Result};
 
 /// Google Gemini provider
 pub struct GeminiProvider {
-    api_key: String,
+    api_key: SecretString,
     model: String,
     max_tokens: u32,
     temperature: f32,
@@ -19,7 +21,7 @@ pub struct GeminiProvider {
 impl GeminiProvider {
     pub fn new(api_key: &str, model: &str, max_tokens: u32, temperature: f32) -> Self {
         Self {
-            api_key: api_key.to_string(),
+            api_key: SecretString::from(api_key),
             model: model.to_string(),
             max_tokens,
             temperature,
@@ -93,12 +95,25 @@ struct GeminiError {
     status: Option<String>,
 }
 
+/// Model metadata (`GET /v1beta/models/{model}`)
+#[derive(Debug, Deserialize)]
+struct GeminiModelInfo {
+    #[serde(rename = "inputTokenLimit")]
+    input_token_limit: Option<u32>,
+    #[serde(rename = "outputTokenLimit")]
+    output_token_limit: Option<u32>,
+}
+
 #[async_trait]
 impl LlmProvider for GeminiProvider {
     fn name(&self) -> &str {
         "gemini"
     }
 
+    fn model(&self) -> &str {
+        &self.model
+    }
+
     async fn chat(&self, request: LlmRequest) -> Result<LlmResponse> {
         // Build contents array (conversation history)
         let mut contents: Vec<GeminiContent> = Vec::new();
@@ -138,15 +153,17 @@ impl LlmProvider for GeminiProvider {
             },
         };
 
-        // Gemini API URL
+        // Gemini API URL (key goes in a header so it never shows up in
+        // reqwest error messages, which include the request URL)
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model, self.api_key
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
+            self.model
         );
 
         let response = self.client
             .post(&url)
             .header("Content-Type", "application/json")
+            .header("x-goog-api-key", self.api_key.expose())
             .json(&gemini_request)
             .send()
             .await
@@ -201,4 +218,37 @@ impl LlmProvider for GeminiProvider {
     async fn is_available(&self) -> bool {
         !self.api_key.is_empty()
     }
+
+    async fn probe(&self) -> Result<()> {
+        let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}", self.model);
+        let response = self.client
+            .get(&url)
+            .header("x-goog-api-key", self.api_key.expose())
+            .send()
+            .await
+            .map_err(|e| OfficeError::LlmError(format!("Probe failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(OfficeError::LlmError(format!("Probe failed: {}", response.status())));
+        }
+        Ok(())
+    }
+
+    async fn capabilities(&self, known: &ModelCapabilities) -> Option<ModelCapabilities> {
+        let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}", self.model);
+        let response = self.client
+            .get(&url)
+            .header("x-goog-api-key", self.api_key.expose())
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        let info: GeminiModelInfo = response.json().await.ok()?;
+        let input = info.input_token_limit?;
+        Some(ModelCapabilities {
+            max_context_tokens: input.saturating_add(info.output_token_limit.unwrap_or(0)),
+            ..known.clone()
+        })
+    }
 }
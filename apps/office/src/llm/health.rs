@@ -0,0 +1,131 @@
+//! Provider Health - rolling latency and error rates
+//!
+//! Every routed call and every periodic probe adds a sample for its provider.
+//! The SmartRouter reads the last [`HEALTH_WINDOW`] samples: the p95 latency
+//! replaces the profile's static estimate, and for latency-sensitive tasks the
+//! error rate counts against the provider.
+//!
+//! Probes are cheap metadata calls (`LlmProvider::probe`), so a provider that
+//! is down is noticed even when no traffic is routed to it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::observability::{LLM_PROVIDER_ERROR_RATE, LLM_PROVIDER_P95};
+
+/// Samples kept per provider
+pub const HEALTH_WINDOW: usize = 50;
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    latency_ms: u32,
+    ok: bool,
+}
+
+/// Health of one provider over the window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderStats {
+    pub samples: usize,
+    /// p95 latency of successful samples (None until one succeeds)
+    pub p95_ms: Option<u32>,
+    /// Failed samples / samples
+    pub error_rate: f32,
+}
+
+/// Rolling per-provider samples
+#[derive(Default)]
+pub struct ProviderHealth {
+    samples: Mutex<HashMap<String, VecDeque<Sample>>>,
+}
+
+impl ProviderHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a call or probe outcome
+    pub fn record(&self, provider: &str, elapsed: Duration, ok: bool) {
+        let latency_ms = elapsed.as_millis().min(u32::MAX as u128) as u32;
+        let stats = {
+            let Ok(mut samples) = self.samples.lock() else { return };
+            let window = samples.entry(provider.to_string()).or_default();
+            if window.len() == HEALTH_WINDOW {
+                window.pop_front();
+            }
+            window.push_back(Sample { latency_ms, ok });
+            summarize(window)
+        };
+
+        LLM_PROVIDER_ERROR_RATE.with_label_values(&[provider]).set(stats.error_rate as f64);
+        if let Some(p95) = stats.p95_ms {
+            LLM_PROVIDER_P95.with_label_values(&[provider]).set(p95 as f64 / 1000.0);
+        }
+    }
+
+    /// Current stats (None before the first sample)
+    pub fn stats(&self, provider: &str) -> Option<ProviderStats> {
+        let samples = self.samples.lock().ok()?;
+        samples.get(provider).map(summarize)
+    }
+
+    /// Stats of every provider seen so far
+    pub fn snapshot(&self) -> HashMap<String, ProviderStats> {
+        self.samples
+            .lock()
+            .map(|samples| samples.iter().map(|(name, window)| (name.clone(), summarize(window))).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn summarize(window: &VecDeque<Sample>) -> ProviderStats {
+    let mut latencies: Vec<u32> = window.iter().filter(|s| s.ok).map(|s| s.latency_ms).collect();
+    latencies.sort_unstable();
+    // Nearest rank
+    let p95_ms = latencies
+        .len()
+        .checked_sub(1)
+        .map(|last| latencies[(last as f32 * 0.95).ceil() as usize]);
+    let failed = window.len() - latencies.len();
+    ProviderStats {
+        samples: window.len(),
+        p95_ms,
+        error_rate: if window.is_empty() { 0.0 } else { failed as f32 / window.len() as f32 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_p95_and_error_rate() {
+        let health = ProviderHealth::new();
+        assert_eq!(health.stats("test-health"), None);
+
+        for ms in 1..=100 {
+            health.record("test-health", Duration::from_millis(ms), true);
+        }
+        // Only the last HEALTH_WINDOW samples count: 51..=100
+        let stats = health.stats("test-health").unwrap();
+        assert_eq!(stats.samples, HEALTH_WINDOW);
+        assert_eq!(stats.p95_ms, Some(98));
+        assert_eq!(stats.error_rate, 0.0);
+
+        // Failures raise the error rate without skewing latency
+        for _ in 0..10 {
+            health.record("test-health", Duration::from_secs(30), false);
+        }
+        let stats = health.stats("test-health").unwrap();
+        assert_eq!(stats.error_rate, 0.2);
+        assert_eq!(stats.p95_ms, Some(99));
+
+        for _ in 0..HEALTH_WINDOW {
+            health.record("test-health", Duration::from_secs(30), false);
+        }
+        let stats = health.stats("test-health").unwrap();
+        assert_eq!((stats.p95_ms, stats.error_rate), (None, 1.0));
+    }
+}
@@ -36,6 +36,10 @@ impl LlmProvider for LocalProvider {
         "local"
     }
 
+    fn model(&self) -> &str {
+        "local-mock-v1"
+    }
+
     async fn chat(&self, request: LlmRequest) -> Result<LlmResponse> {
         // Generate a mock response based on the last user message
         let last_user_message = request.messages
@@ -72,7 +76,7 @@ impl LlmProvider for LocalProvider {
                 output_tokens,
                 total_tokens: input_tokens + output_tokens,
             },
-            model: "local-mock-v1".to_string(),
+            model: self.model().to_string(),
         })
     }
 
@@ -5,6 +5,7 @@
 //! Architecture:
 //! - Providers are "dumb pipes" that call LLM APIs
 //! - SmartRouter selects the best provider for each task
+//! - ProviderHealth tracks each provider's latency and errors for the router
 //! - All routing logic lives in OFFICE (the brain)
 
 mod provider;
@@ -13,13 +14,24 @@ mod openai;
 mod gemini;
 mod local;
 mod router;
+mod embeddings;
+mod capabilities;
+mod health;
 
 pub use provider::{LlmProvider, LlmRequest, LlmResponse, LlmMessage, LlmUsage, MessageRole};
 pub use anthropic::AnthropicProvider;
 pub use openai::OpenAIProvider;
 pub use gemini::GeminiProvider;
 pub use local::LocalProvider;
-pub use router::{SmartRouter, TaskType, RoutingPreferences, ProviderProfile, default_profiles};
+pub use router::{SmartRouter, TaskType, RoutingPreferences, ProviderProfile, default_profiles, PROBE_TIMEOUT};
+pub use health::{ProviderHealth, ProviderStats, HEALTH_WINDOW};
+pub use capabilities::{
+    builtin_capabilities, CapabilityRegistry, CapabilityRequirements, CapabilitySource, ModelCapabilities,
+};
+pub use embeddings::{
+    CachedEmbedder, EmbeddingCache, EmbeddingCacheConfig, EmbeddingCacheStats, EmbeddingModel,
+    EmbeddingProvider, LocalEmbeddings, OpenAIEmbeddings,
+};
 
 use std::sync::Arc;
 
@@ -30,7 +42,7 @@ pub fn create_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>> {
     match config.provider.to_lowercase().as_str() {
         "anthropic" | "claude" => {
             Ok(Arc::new(AnthropicProvider::new(
-                &config.api_key,
+                config.api_key.expose(),
                 &config.model,
                 config.max_tokens,
                 config.temperature,
@@ -38,7 +50,7 @@ pub fn create_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>> {
         }
         "openai" | "gpt" => {
             Ok(Arc::new(OpenAIProvider::new(
-                &config.api_key,
+                config.api_key.expose(),
                 &config.model,
                 config.max_tokens,
                 config.temperature,
@@ -46,7 +58,7 @@ pub fn create_provider(config: &LlmConfig) -> Result<Arc<dyn LlmProvider>> {
         }
         "gemini" | "google" => {
             Ok(Arc::new(GeminiProvider::new(
-                &config.api_key,
+                config.api_key.expose(),
                 &config.model,
                 config.max_tokens,
                 config.temperature,
@@ -5,11 +5,12 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use super::provider::{LlmProvider, LlmRequest, LlmResponse, LlmUsage, MessageRole};
+use crate::secrets::SecretString;
 use crate::{OfficeError, Result};
 
 /// OpenAI GPT provider
 pub struct OpenAIProvider {
-    api_key: String,
+    api_key: SecretString,
     model: String,
     max_tokens: u32,
     temperature: f32,
@@ -19,7 +20,7 @@ pub struct OpenAIProvider {
 impl OpenAIProvider {
     pub fn new(api_key: &str, model: &str, max_tokens: u32, temperature: f32) -> Self {
         Self {
-            api_key: api_key.to_string(),
+            api_key: SecretString::from(api_key),
             model: model.to_string(),
             max_tokens,
             temperature,
@@ -72,6 +73,10 @@ impl LlmProvider for OpenAIProvider {
         "openai"
     }
 
+    fn model(&self) -> &str {
+        &self.model
+    }
+
     async fn chat(&self, request: LlmRequest) -> Result<LlmResponse> {
         // Convert messages
         let mut messages: Vec<OpenAIMessage> = Vec::new();
@@ -107,7 +112,7 @@ impl LlmProvider for OpenAIProvider {
 
         let response = self.client
             .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Authorization", format!("Bearer {}", self.api_key.expose()))
             .header("Content-Type", "application/json")
             .json(&openai_request)
             .send()
@@ -140,4 +145,17 @@ impl LlmProvider for OpenAIProvider {
     async fn is_available(&self) -> bool {
         !self.api_key.is_empty()
     }
+
+    async fn probe(&self) -> Result<()> {
+        let response = self.client
+            .get(format!("https://api.openai.com/v1/models/{}", self.model))
+            .header("Authorization", format!("Bearer {}", self.api_key.expose()))
+            .send()
+            .await
+            .map_err(|e| OfficeError::LlmError(format!("Probe failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(OfficeError::LlmError(format!("Probe failed: {}", response.status())));
+        }
+        Ok(())
+    }
 }
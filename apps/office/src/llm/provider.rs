@@ -3,7 +3,8 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
-use crate::Result;
+use super::capabilities::ModelCapabilities;
+use crate::{OfficeError, Result};
 
 /// Role of a message
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -121,6 +122,11 @@ pub trait LlmProvider: Send + Sync {
     /// Get provider name
     fn name(&self) -> &str;
 
+    /// Model this provider calls
+    fn model(&self) -> &str {
+        ""
+    }
+
     /// Send a request and get a response
     async fn chat(&self, request: LlmRequest) -> Result<LlmResponse>;
 
@@ -136,4 +142,20 @@ pub trait LlmProvider: Send + Sync {
     async fn is_available(&self) -> bool {
         true
     }
+
+    /// Lightweight liveness check timed by the router's health probes; should
+    /// not consume tokens (e.g. fetch the model's metadata)
+    async fn probe(&self) -> Result<()> {
+        if self.is_available().await {
+            Ok(())
+        } else {
+            Err(OfficeError::LlmError(format!("{} is not configured", self.name())))
+        }
+    }
+
+    /// Capabilities from the provider's model metadata, refining `known`
+    /// (None when the API reports nothing useful)
+    async fn capabilities(&self, _known: &ModelCapabilities) -> Option<ModelCapabilities> {
+        None
+    }
 }
@@ -5,17 +5,31 @@
 //! - Entity preferences
 //! - Cost/speed tradeoffs
 //! - Provider availability
+//! - Model capabilities (tools, context size, ...), see `capabilities`
+//! - Observed health (rolling p95 latency, error rate), see `health`
+//!
+//! Latency-sensitive tasks go to the healthiest provider: the observed p95
+//! drives the speed bonus and the error rate discounts the score. Every
+//! decision is counted in `office_llm_routing_decisions_total`.
 //!
 //! The Router lives in OFFICE because OFFICE knows the context.
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
 
+use super::capabilities::{CapabilityRegistry, CapabilityRequirements};
+use super::health::{ProviderHealth, ProviderStats};
 use super::provider::{LlmProvider, LlmRequest, LlmResponse};
+use crate::observability::{inc, LLM_ROUTING_DECISIONS};
 use crate::{OfficeError, Result};
 
+/// A probe slower than this counts as a failure
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Task types for routing decisions
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -42,6 +56,25 @@ impl Default for TaskType {
     }
 }
 
+impl TaskType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Coding => "coding",
+            Self::Writing => "writing",
+            Self::Analysis => "analysis",
+            Self::Creative => "creative",
+            Self::Quick => "quick",
+            Self::Complex => "complex",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Tasks where a slow answer is a bad answer
+    pub fn is_latency_sensitive(self) -> bool {
+        matches!(self, Self::Quick)
+    }
+}
+
 /// Routing preferences
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RoutingPreferences {
@@ -55,6 +88,9 @@ pub struct RoutingPreferences {
     pub max_cost_cents: Option<u32>,
     /// Maximum latency (in ms) acceptable
     pub max_latency_ms: Option<u32>,
+    /// Capabilities needed beyond the task type's baseline
+    #[serde(default)]
+    pub requires: CapabilityRequirements,
 }
 
 /// Provider capabilities and scoring
@@ -80,11 +116,31 @@ impl ProviderProfile {
 
     /// Calculate total score with preferences
     pub fn calculate_score(&self, task: TaskType, prefs: &RoutingPreferences) -> f32 {
+        self.score(task, prefs, self.avg_latency_ms, prefs.prefer_speed)
+    }
+
+    /// Score with observed health: its p95 replaces `avg_latency_ms`, and for
+    /// latency-sensitive tasks speed always counts and errors discount the score
+    pub fn score_with_health(&self, task: TaskType, prefs: &RoutingPreferences, health: Option<&ProviderStats>) -> f32 {
+        let latency_ms = self.latency_ms(health);
+        if !task.is_latency_sensitive() {
+            return self.score(task, prefs, latency_ms, prefs.prefer_speed);
+        }
+        let reliability = health.map(|h| 1.0 - h.error_rate).unwrap_or(1.0);
+        self.score(task, prefs, latency_ms, true) * reliability
+    }
+
+    /// Observed p95 latency, or the static estimate before any sample
+    pub fn latency_ms(&self, health: Option<&ProviderStats>) -> u32 {
+        health.and_then(|h| h.p95_ms).unwrap_or(self.avg_latency_ms)
+    }
+
+    fn score(&self, task: TaskType, prefs: &RoutingPreferences, latency_ms: u32, prefer_speed: bool) -> f32 {
         let base_score = self.score_for_task(task) as f32;
         
         // Speed bonus (inverse of latency)
-        let speed_modifier = if prefs.prefer_speed {
-            (1000.0 / self.avg_latency_ms as f32).min(2.0)
+        let speed_modifier = if prefer_speed {
+            (1000.0 / latency_ms.max(1) as f32).min(2.0)
         } else {
             1.0
         };
@@ -108,6 +164,10 @@ pub struct SmartRouter {
     profiles: HashMap<String, ProviderProfile>,
     /// Default provider
     default_provider: String,
+    /// What each provider's model can do
+    capabilities: Arc<CapabilityRegistry>,
+    /// Rolling latency and error rates from calls and probes
+    health: Arc<ProviderHealth>,
 }
 
 impl SmartRouter {
@@ -117,6 +177,8 @@ impl SmartRouter {
             providers: HashMap::new(),
             profiles: HashMap::new(),
             default_provider: String::new(),
+            capabilities: Arc::new(CapabilityRegistry::new()),
+            health: Arc::new(ProviderHealth::new()),
         }
     }
 
@@ -140,6 +202,56 @@ impl SmartRouter {
         }
     }
 
+    /// Capability registry consulted when routing
+    pub fn capabilities(&self) -> &Arc<CapabilityRegistry> {
+        &self.capabilities
+    }
+
+    /// Refresh every provider's capabilities from its model metadata
+    pub async fn refresh_capabilities(&self) {
+        for provider in self.providers.values() {
+            self.capabilities.refresh(provider.as_ref()).await;
+        }
+    }
+
+    /// Health tracker consulted when routing
+    pub fn health(&self) -> &Arc<ProviderHealth> {
+        &self.health
+    }
+
+    /// Probe every provider once and record the outcomes
+    pub async fn probe_all(&self) {
+        for (name, provider) in &self.providers {
+            let started = Instant::now();
+            let ok = match tokio::time::timeout(PROBE_TIMEOUT, provider.probe()).await {
+                Ok(Ok(())) => true,
+                Ok(Err(e)) => {
+                    warn!(provider = %name, error = %e, "🩺 LLM provider probe failed");
+                    false
+                }
+                Err(_) => {
+                    warn!(provider = %name, "🩺 LLM provider probe timed out");
+                    false
+                }
+            };
+            let elapsed = started.elapsed();
+            self.health.record(name, elapsed, ok);
+            crate::observability::record_llm_call(name, "probe", elapsed, ok);
+        }
+    }
+
+    /// Probe all providers every `interval` in the background
+    pub fn spawn_probes(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let router = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                router.probe_all().await;
+            }
+        })
+    }
+
     /// Route a request to the best provider
     pub async fn route(
         &self,
@@ -147,8 +259,22 @@ impl SmartRouter {
         task: TaskType,
         prefs: &RoutingPreferences,
     ) -> Result<LlmResponse> {
-        let provider = self.select_provider(task, prefs).await?;
-        provider.chat(request).await
+        let prompt: u64 = request.messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .chain(request.system.as_deref())
+            .map(crate::context::estimate_tokens)
+            .sum();
+        let requirements = CapabilityRequirements::for_task(task)
+            .merge(&prefs.requires)
+            .with_min_context((prompt + request.max_tokens as u64).min(u32::MAX as u64) as u32);
+        let provider = self.select_capable(task, prefs, &requirements).await?;
+        let started = Instant::now();
+        let result = provider.chat(request).await;
+        let elapsed = started.elapsed();
+        self.health.record(provider.name(), elapsed, result.is_ok());
+        crate::observability::record_llm_call(provider.name(), "chat", elapsed, result.is_ok());
+        result
     }
 
     /// Select the best provider for a task
@@ -157,10 +283,35 @@ impl SmartRouter {
         task: TaskType,
         prefs: &RoutingPreferences,
     ) -> Result<Arc<dyn LlmProvider>> {
+        let requirements = CapabilityRequirements::for_task(task).merge(&prefs.requires);
+        self.select_capable(task, prefs, &requirements).await
+    }
+
+    /// Select the best provider whose model meets the requirements
+    pub async fn select_capable(
+        &self,
+        task: TaskType,
+        prefs: &RoutingPreferences,
+        requirements: &CapabilityRequirements,
+    ) -> Result<Arc<dyn LlmProvider>> {
+        // Models that cannot serve this request never get it
+        let mut incapable: HashMap<&str, Vec<String>> = HashMap::new();
+        for (name, provider) in &self.providers {
+            let missing = self.capabilities
+                .get(provider.name(), provider.model())
+                .await
+                .missing(requirements);
+            if !missing.is_empty() {
+                incapable.insert(name.as_str(), missing);
+            }
+        }
+        let capable = |name: &str| !incapable.contains_key(name);
+
         // Check for explicit preference
         if let Some(ref preferred) = prefs.preferred_provider {
             if let Some(provider) = self.providers.get(preferred) {
-                if provider.is_available().await {
+                if capable(preferred) && provider.is_available().await {
+                    record_decision(task, preferred, "preferred");
                     return Ok(provider.clone());
                 }
             }
@@ -170,19 +321,21 @@ impl SmartRouter {
         let mut best_provider: Option<(&str, f32)> = None;
 
         for (name, profile) in &self.profiles {
-            // Skip unavailable providers
-            if !profile.available {
+            // Skip unavailable and incapable providers
+            if !profile.available || !capable(name) {
                 continue;
             }
 
-            // Check latency constraint
+            // Check latency constraint against what the provider actually does
+            let health = self.health.stats(name);
             if let Some(max_latency) = prefs.max_latency_ms {
-                if profile.avg_latency_ms > max_latency {
+                if profile.latency_ms(health.as_ref()) > max_latency {
                     continue;
                 }
             }
 
-            let score = profile.calculate_score(task, prefs);
+            let score = profile.score_with_health(task, prefs, health.as_ref());
+            debug!(provider = %name, task = task.as_str(), score, ?health, "Scored LLM provider");
             
             if best_provider.is_none() || score > best_provider.unwrap().1 {
                 best_provider = Some((name.as_str(), score));
@@ -190,14 +343,31 @@ impl SmartRouter {
         }
 
         // Get the best provider or fall back to default
-        let provider_name = best_provider
-            .map(|(name, _)| name)
-            .unwrap_or(&self.default_provider);
+        let (provider_name, reason) = match best_provider {
+            Some((name, _)) if task.is_latency_sensitive() => (name, "healthiest"),
+            Some((name, _)) => (name, "scored"),
+            None => (self.default_provider.as_str(), "default"),
+        };
+
+        if !capable(provider_name) {
+            let mut reasons: Vec<String> = incapable
+                .iter()
+                .map(|(name, missing)| format!("{} lacks {}", name, missing.join(", ")))
+                .collect();
+            reasons.sort();
+            return Err(OfficeError::LlmError(format!(
+                "No provider can serve {:?} task: {}",
+                task,
+                reasons.join("; ")
+            )));
+        }
 
-        self.providers
+        let provider = self.providers
             .get(provider_name)
             .cloned()
-            .ok_or_else(|| OfficeError::LlmError("No provider available".to_string()))
+            .ok_or_else(|| OfficeError::LlmError("No provider available".to_string()))?;
+        record_decision(task, provider_name, reason);
+        Ok(provider)
     }
 
     /// Get provider by name
@@ -216,6 +386,10 @@ impl SmartRouter {
     }
 }
 
+fn record_decision(task: TaskType, provider: &str, reason: &str) {
+    inc(&LLM_ROUTING_DECISIONS, &[task.as_str(), provider, reason]);
+}
+
 impl Default for SmartRouter {
     fn default() -> Self {
         Self::new()
@@ -332,5 +506,81 @@ mod tests {
         let score = profile.calculate_score(TaskType::Coding, &speed_prefs);
         assert!(score > 80.0); // Should be higher
     }
-}
 
+    fn router_with(models: &[(&str, &str)]) -> SmartRouter {
+        let profiles = default_profiles();
+        let mut router = SmartRouter::new();
+        for (name, model) in models {
+            let provider: Arc<dyn LlmProvider> = match *name {
+                "anthropic" => Arc::new(super::super::AnthropicProvider::new("k", model, 4096, 0.7)),
+                _ => Arc::new(super::super::OpenAIProvider::new("k", model, 4096, 0.7)),
+            };
+            router.register(provider, profiles[*name].clone());
+        }
+        router
+    }
+
+    #[tokio::test]
+    async fn test_incapable_model_is_skipped() {
+        let router = router_with(&[("anthropic", "claude-2.1"), ("openai", "gpt-4o")]);
+        let prefs = RoutingPreferences::default();
+
+        // Claude scores higher on Complex, but claude-2 has no tool calling
+        let picked = router.select_provider(TaskType::Complex, &prefs).await.unwrap();
+        assert_eq!(picked.name(), "openai");
+
+        // Preferring an incapable provider does not override the check
+        let prefs = RoutingPreferences { preferred_provider: Some("anthropic".into()), ..Default::default() };
+        let picked = router.select_provider(TaskType::Coding, &prefs).await.unwrap();
+        assert_eq!(picked.name(), "openai");
+
+        // Requests that fit it still go there
+        let picked = router.select_provider(TaskType::Quick, &prefs).await.unwrap();
+        assert_eq!(picked.name(), "anthropic");
+    }
+
+    #[tokio::test]
+    async fn test_no_capable_model_fails_before_calling() {
+        let router = router_with(&[("anthropic", "claude-2.1")]);
+        let err = router
+            .select_provider(TaskType::Coding, &RoutingPreferences::default())
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("anthropic lacks tool_calling"));
+
+        let prefs = RoutingPreferences {
+            requires: CapabilityRequirements { vision: true, ..Default::default() },
+            ..Default::default()
+        };
+        assert!(router.select_provider(TaskType::Quick, &prefs).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_latency_sensitive_tasks_prefer_healthy_provider() {
+        let router = router_with(&[("anthropic", "claude-3-5-sonnet"), ("openai", "gpt-4o")]);
+        let prefs = RoutingPreferences::default();
+
+        // By profile alone, OpenAI wins quick tasks (75 vs 70)
+        assert_eq!(router.select_provider(TaskType::Quick, &prefs).await.unwrap().name(), "openai");
+
+        // Healthy and fast vs. failing a third of its calls
+        for _ in 0..10 {
+            router.health().record("anthropic", Duration::from_millis(400), true);
+        }
+        for i in 0..9 {
+            router.health().record("openai", Duration::from_millis(400), i % 3 != 0);
+        }
+        assert_eq!(router.select_provider(TaskType::Quick, &prefs).await.unwrap().name(), "anthropic");
+
+        // Other tasks still go by fit (OpenAI writes better)
+        assert_eq!(router.select_provider(TaskType::Writing, &prefs).await.unwrap().name(), "openai");
+
+        // Latency limits apply to the observed p95, not the profile estimate
+        for _ in 0..10 {
+            router.health().record("anthropic", Duration::from_millis(5_000), true);
+        }
+        let prefs = RoutingPreferences { max_latency_ms: Some(3_000), ..Default::default() };
+        assert_eq!(router.select_provider(TaskType::Quick, &prefs).await.unwrap().name(), "openai");
+    }
+}
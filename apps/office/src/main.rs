@@ -1,6 +1,10 @@
 //! OFFICE Server - LLM Operating System HTTP API
 //!
 //! Provides HTTP/WebSocket API for managing LLM entities and sessions.
+//!
+//! Flags:
+//! - `--config <path>`  Config file (default: config/development, optional)
+//! - `--check-config`   Validate configuration, print it redacted, and exit
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -15,6 +19,21 @@ use office::observability::{init_tracing as init_otel_tracing, init_metrics};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Load and validate configuration first: invalid config is fatal, never defaulted
+    let args: Vec<String> = std::env::args().collect();
+    let config = match load_config(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(2);
+        }
+    };
+    if args.iter().any(|a| a == "--check-config") {
+        println!("{}", serde_json::to_string_pretty(&config.redacted())?);
+        println!("✅ Configuration OK");
+        return Ok(());
+    }
+
     // Initialize observability
     init_metrics();
     
@@ -49,17 +68,16 @@ async fn main() -> Result<()> {
 
     info!("Starting OFFICE - LLM Operating System");
 
-    // Load configuration
-    let config = load_config()?;
-    info!("Configuration loaded: {:?}", config.server);
+    info!(config = %config.redacted(), "Configuration loaded");
 
-    // Initialize UBL client with generated signing key
-    let ubl_client = Arc::new(UblClient::with_generated_key(
-        &config.ubl.endpoint,
-        &config.ubl.container_id,
-        config.ubl.timeout_ms,
-    ));
+    // Initialize UBL client with generated signing key and the service key
+    let ubl_client = Arc::new(UblClient::from_config(&config.ubl)?);
     info!("UBL client initialized: {}", config.ubl.endpoint);
+    info!(
+        "Service key: {} ({}; UBL must list it in UBL_OFFICE_PUBKEYS)",
+        ubl_client.service_pubkey_hex(),
+        if config.ubl.service_key.is_empty() { "generated" } else { "configured" }
+    );
 
     // Initialize LLM provider
     let llm_provider = create_provider(&config.llm)?;
@@ -67,12 +85,20 @@ async fn main() -> Result<()> {
 
     // Create application state
     let state = AppState::new(config.clone(), ubl_client, llm_provider);
+    state.smart_router.refresh_capabilities().await;
+    if config.llm.probe_interval_secs > 0 {
+        state.smart_router.spawn_probes(std::time::Duration::from_secs(config.llm.probe_interval_secs));
+        info!("LLM provider health probes every {}s", config.llm.probe_interval_secs);
+    }
     let shared_state = Arc::new(RwLock::new(state));
 
     // Create router
     let app = create_router(shared_state);
 
-    // Bind and serve
+    // Bind and serve: a Unix socket for a co-located UBL, TCP otherwise
+    if let Some(path) = config.server.unix_socket.as_deref() {
+        return serve_unix(path, app).await;
+    }
     let addr = format!("{}:{}", config.server.host, config.server.port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     info!("OFFICE server listening on {}", addr);
@@ -82,15 +108,42 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-fn load_config() -> Result<OfficeConfig> {
-    // Try to load from environment or file, fallback to defaults
-    let config = config::Config::builder()
-        .add_source(config::File::with_name("config/development").required(false))
-        .add_source(config::Environment::with_prefix("OFFICE").separator("__"))
-        .build()
-        .ok()
-        .and_then(|c| c.try_deserialize().ok())
-        .unwrap_or_else(OfficeConfig::default);
+async fn serve_unix(path: &str, app: axum::Router) -> Result<()> {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder;
+    use hyper_util::service::TowerToHyperService;
 
-    Ok(config)
+    let path = std::path::Path::new(path);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _ = std::fs::remove_file(path); // stale socket from a previous run
+    let listener = tokio::net::UnixListener::bind(path)?;
+    info!("OFFICE server listening on unix://{}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if let Err(e) = Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                warn!("Error serving Unix socket connection: {}", e);
+            }
+        });
+    }
+}
+
+fn load_config(args: &[String]) -> Result<OfficeConfig> {
+    // An explicit --config path must exist; the development default is optional
+    match args.iter().position(|a| a == "--config") {
+        Some(i) => {
+            let path = args.get(i + 1).ok_or_else(|| {
+                office::OfficeError::ConfigError("--config requires a path".into())
+            })?;
+            OfficeConfig::load(path, true)
+        }
+        None => OfficeConfig::load("config/development", false),
+    }
 }
@@ -25,6 +25,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde_json::{json, Value};
 use tracing::{info, debug, warn, error};
+use ubl_events::{JobCreated, JobEvent};
 
 use crate::{Result, OfficeError};
 use crate::mcp::protocol::*;
@@ -179,7 +180,9 @@ impl OfficeMcpServer {
                     "title": { "type": "string", "description": "Job title" },
                     "description": { "type": "string", "description": "Job description" },
                     "assigned_to": { "type": "string", "description": "Entity ID to assign to" },
-                    "priority": { "type": "string", "enum": ["low", "normal", "high", "urgent"] }
+                    "priority": { "type": "string", "enum": ["low", "normal", "high", "urgent"] },
+                    "conversation_id": { "type": "string", "description": "Conversation the job comes from" },
+                    "tenant_id": { "type": "string", "description": "Tenant owning the job" }
                 })),
                 required: Some(vec!["title".to_string()]),
                 extra: HashMap::new(),
@@ -587,15 +590,17 @@ impl ToolHandler for JobCreateHandler {
         let job_id = format!("job_{}", uuid::Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
         
         // Publish job.created event to UBL
-        let event = json!({
-            "type": "job.created",
-            "job_id": job_id,
-            "title": title,
-            "description": args.get("description"),
-            "assigned_to": args.get("assigned_to"),
-            "priority": args.get("priority").and_then(|v| v.as_str()).unwrap_or("normal"),
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        });
+        let arg = |name: &str| args.get(name).and_then(|v| v.as_str()).map(String::from);
+        let event = JobEvent::Created(JobCreated {
+            description: arg("description"),
+            assigned_to: arg("assigned_to"),
+            conversation_id: arg("conversation_id"),
+            tenant_id: arg("tenant_id"),
+            ..JobCreated::new(&job_id, title)
+                .with_priority(arg("priority").unwrap_or_else(|| "normal".into()))
+                .with_timestamp(chrono::Utc::now().to_rfc3339())
+        })
+        .to_atom();
         
         match ctx.ubl_client.publish_event("C.Jobs", &event).await {
             Ok(response) => Ok(vec![ToolContent::Text { 
@@ -11,6 +11,7 @@ use tracing::{info, warn, debug};
 
 use crate::{Result, OfficeError};
 use crate::mcp::{McpRegistry, McpTool, ToolContent, CallToolResult};
+use crate::observability;
 
 /// Tool executor for LLM agents
 pub struct ToolExecutor {
@@ -93,6 +94,7 @@ impl ToolExecutor {
                     .join("\n");
                 
                 info!("Tool {} completed successfully", name);
+                observability::inc(&observability::TOOL_EXECUTIONS, &[name, "ok"]);
                 
                 Ok(ToolExecutionResult {
                     success: true,
@@ -103,6 +105,7 @@ impl ToolExecutor {
             }
             Ok(Err(e)) => {
                 warn!("Tool execution failed: {} - {}", name, e);
+                observability::inc(&observability::TOOL_EXECUTIONS, &[name, "error"]);
                 
                 Ok(ToolExecutionResult {
                     success: false,
@@ -113,6 +116,7 @@ impl ToolExecutor {
             }
             Err(_) => {
                 warn!("Tool timed out: {}", name);
+                observability::inc(&observability::TOOL_EXECUTIONS, &[name, "timeout"]);
                 
                 Ok(ToolExecutionResult {
                     success: false,
@@ -5,7 +5,9 @@
 
 mod permit;
 mod constitution;
+pub mod service_auth;
 
-pub use permit::{PermitMiddleware, PermitRequest, PermitResponse, PermitError};
+pub use permit::{PermitMiddleware, PermitRequest, PermitResponse, PermitError, PermitVerifier};
 pub use constitution::{ConstitutionEnforcer, OfficeConstitution};
+pub use service_auth::{ServiceAuthError, ServiceVerifier};
 
@@ -7,13 +7,22 @@
 //! 1. Every mutation calls /v1/policy/permit on UBL
 //! 2. Only Allow responses proceed
 //! 3. Deny = fail-closed (no execution)
+//! 4. The permit is signed by a pinned UBL admin key, addressed to this
+//!    office, unexpired and not replayed ([`PermitVerifier`])
+//! 5. Guarded entities first get their guardian's approval, which becomes
+//!    the request's `approval_ref` ([`PermitMiddleware::request_permit_as`])
 //!
 //! "Office não pode pular o Permit nem registrar recibos fora do UBL."
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::entity::{Entity, EntityType};
+use crate::job_executor::{GuardedMutation, GuardianApprovals};
 use crate::ubl_client::UblClient;
 
 /// Errors from permit middleware
@@ -33,6 +42,18 @@ pub enum PermitError {
 
     #[error("Permit expired")]
     Expired,
+
+    #[error("Permit signature invalid: {0}")]
+    InvalidSignature(String),
+
+    #[error("Permit issued to {got}, not {expected}")]
+    WrongAudience { expected: String, got: String },
+
+    #[error("Permit issued in the future")]
+    NotYetValid,
+
+    #[error("Permit nonce already used")]
+    Replayed,
 }
 
 /// Request for a permit from UBL
@@ -60,65 +81,186 @@ pub struct PermitRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermitResponse {
     /// The permit (if allowed)
+    #[serde(default)]
     pub permit: Option<Permit>,
-    /// Policy hash used for evaluation
-    pub policy_hash: String,
-    /// Subject hash (hash of params)
-    pub subject_hash: String,
     /// Whether permit was granted
     pub allowed: bool,
     /// Denial reason (if denied)
+    #[serde(default)]
     pub denial_reason: Option<String>,
 }
 
-/// A permit from UBL (v1.1 format)
+/// A permit from UBL (`POST /v1/policy/permit`, Console v1.1)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Permit {
-    /// Audience (runner target)
-    pub aud: String,
-    /// JWT ID (unique, single-use)
+    /// Unique, single-use id
     pub jti: String,
-    /// Expiration timestamp (ms)
-    pub exp: u64,
-    /// Signature
+    /// Audience: the office the permit was issued to
+    pub aud: String,
+    pub action: String,
+    pub target: String,
+    pub args: serde_json::Value,
+    pub risk: String,
+    pub plan_hash: String,
+    /// Commits to office, action, target, args, risk, plan, nonce and expiry
+    pub binding_hash: String,
+    /// Hash of UBL's issuance policy
+    pub policy_hash: String,
+    pub nonce: String,
+    pub issued_at_ms: i64,
+    pub exp_ms: i64,
+    pub approver: String,
+    /// Admin public key (hex) UBL says it signed with; must be pinned
+    pub signer: String,
+    /// "ed25519:<base64url>" over [`claims_bytes`]
     pub sig: String,
-    /// Scopes
-    pub scopes: PermitScopes,
 }
 
-/// Scopes within a permit
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PermitScopes {
-    pub tenant_id: String,
-    #[serde(rename = "jobType")]
-    pub job_type: String,
-    pub target: String,
-    pub subject_hash: String,
-    pub policy_hash: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub approval_ref: Option<String>,
+/// Version of the signed claims layout (UBL `crypto::PERMIT_CLAIMS_VERSION`)
+const CLAIMS_VERSION: u32 = 1;
+
+/// Bytes UBL signs for a permit: Json✯Atomic of the claims, mirroring
+/// `crypto::permit_claims_bytes` in ubl-server
+pub fn claims_bytes(permit: &Permit) -> Vec<u8> {
+    let claims = serde_json::json!({
+        "v": CLAIMS_VERSION,
+        "jti": permit.jti,
+        "aud": permit.aud,
+        "action": permit.action,
+        "target": permit.target,
+        "binding_hash": permit.binding_hash,
+        "policy_hash": permit.policy_hash,
+        "nonce": permit.nonce,
+        "iat_ms": permit.issued_at_ms,
+        "exp_ms": permit.exp_ms,
+    });
+    ubl_atom::canonicalize(&claims).unwrap_or_default()
+}
+
+/// Checks a permit before Office acts on it: signature by a pinned UBL admin
+/// key, audience, issue/expiry window and nonce replay.
+pub struct PermitVerifier {
+    /// This office's id; permits for any other audience are rejected
+    office_id: String,
+    /// Pinned admin keys (current and, during rotation, previous)
+    trusted_keys: Vec<VerifyingKey>,
+    /// Tolerated clock difference with UBL for `issued_at_ms`
+    max_skew_ms: i64,
+    /// Nonces seen, with their permit's expiry, until they expire
+    seen_nonces: Mutex<HashMap<String, i64>>,
+}
+
+impl PermitVerifier {
+    /// Verifier trusting `trusted_keys` (hex Ed25519 public keys)
+    pub fn new(office_id: &str, trusted_keys: &[String]) -> Result<Self, PermitError> {
+        let trusted_keys = trusted_keys
+            .iter()
+            .map(|hex_key| {
+                let bytes: [u8; 32] = hex::decode(hex_key)
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| PermitError::InvalidSignature(format!("bad pinned key {:?}", hex_key)))?;
+                VerifyingKey::from_bytes(&bytes).map_err(|e| PermitError::InvalidSignature(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            office_id: office_id.to_string(),
+            trusted_keys,
+            max_skew_ms: 30_000,
+            seen_nonces: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Verify `permit` at `now_ms`; a nonce is accepted at most once
+    pub fn verify(&self, permit: &Permit, now_ms: i64) -> Result<(), PermitError> {
+        self.verify_signature(permit)?;
+
+        if permit.aud != self.office_id {
+            return Err(PermitError::WrongAudience { expected: self.office_id.clone(), got: permit.aud.clone() });
+        }
+        if permit.exp_ms <= now_ms {
+            return Err(PermitError::Expired);
+        }
+        if permit.issued_at_ms > now_ms + self.max_skew_ms {
+            return Err(PermitError::NotYetValid);
+        }
+        if permit.nonce.is_empty() {
+            return Err(PermitError::MissingBinding("permit.nonce".to_string()));
+        }
+        if permit.policy_hash.is_empty() {
+            return Err(PermitError::MissingBinding("permit.policy_hash".to_string()));
+        }
+
+        let mut seen = self.seen_nonces.lock().unwrap();
+        seen.retain(|_, exp_ms| *exp_ms > now_ms);
+        if seen.insert(permit.nonce.clone(), permit.exp_ms).is_some() {
+            return Err(PermitError::Replayed);
+        }
+        Ok(())
+    }
+
+    fn verify_signature(&self, permit: &Permit) -> Result<(), PermitError> {
+        let invalid = |reason: &str| PermitError::InvalidSignature(reason.to_string());
+        let sig_bytes: [u8; 64] = permit
+            .sig
+            .strip_prefix("ed25519:")
+            .and_then(|b64| URL_SAFE_NO_PAD.decode(b64).ok())
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| invalid("expected ed25519:<base64url>"))?;
+        let sig = Signature::from_bytes(&sig_bytes);
+        let msg = claims_bytes(permit);
+        if self.trusted_keys.iter().any(|key| key.verify(&msg, &sig).is_ok()) {
+            Ok(())
+        } else {
+            Err(invalid("not signed by a pinned UBL key"))
+        }
+    }
 }
 
 /// Permit middleware - enforces UBL sovereignty
 pub struct PermitMiddleware {
     ubl_client: Arc<UblClient>,
-    /// Required bindings (from office.constitution.yaml)
-    required_bindings: Vec<String>,
+    verifier: PermitVerifier,
+    approvals: Option<Arc<GuardianApprovals>>,
 }
 
 impl PermitMiddleware {
-    /// Create new permit middleware
-    pub fn new(ubl_client: Arc<UblClient>) -> Self {
-        Self {
-            ubl_client,
-            required_bindings: vec![
-                "permit.scopes.tenant_id".to_string(),
-                "permit.scopes.jobType".to_string(),
-                "permit.scopes.target".to_string(),
-                "permit.scopes.subject_hash".to_string(),
-                "permit.scopes.policy_hash".to_string(),
-            ],
+    /// Create new permit middleware; permits must pass `verifier`
+    pub fn new(ubl_client: Arc<UblClient>, verifier: PermitVerifier) -> Self {
+        Self { ubl_client, verifier, approvals: None }
+    }
+
+    /// Ask guardians before requesting permits for guarded entities;
+    /// without it their permits are denied
+    pub fn with_guardian_approvals(mut self, approvals: Arc<GuardianApprovals>) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
+    /// Request a permit on behalf of `entity`. A guarded entity blocks until
+    /// its guardian approves; the approval id is sent as `approval_ref`.
+    pub async fn request_permit_as(
+        &self,
+        entity: &Entity,
+        mut request: PermitRequest,
+    ) -> Result<PermitResponse, PermitError> {
+        if entity.entity_type == EntityType::Guarded {
+            let approvals = self.approvals.as_ref().ok_or_else(|| PermitError::Denied {
+                reason: format!("guarded entity {} has no approval route", entity.id),
+            })?;
+            let mutation = GuardedMutation {
+                job_id: request.params.get("job_id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                title: format!("{} on {}", request.job_type, request.target),
+                details: vec![request.intent.clone(), request.params.to_string()],
+                impact: format!("Permit for {} as {}", request.job_type, request.actor_id),
+            };
+            let decision = approvals
+                .authorize(&self.ubl_client, entity, mutation)
+                .await
+                .map_err(|e| PermitError::Denied { reason: e.to_string() })?;
+            request.approval_ref = decision.map(|d| d.approval_id);
         }
+        self.request_permit(request).await
     }
 
     /// Request a permit from UBL
@@ -126,12 +268,24 @@ impl PermitMiddleware {
     /// This is the ONLY way to authorize a mutation in Office.
     /// If UBL denies, Office MUST NOT proceed.
     pub async fn request_permit(&self, request: PermitRequest) -> Result<PermitResponse, PermitError> {
+        let job_type = request.job_type.clone();
+        let result = self.decide(request).await;
+        let decision = match &result {
+            Ok(_) => "allow",
+            Err(PermitError::Denied { .. }) => "deny",
+            Err(_) => "error",
+        };
+        crate::observability::inc(&crate::observability::PERMIT_DECISIONS, &[&job_type, decision]);
+        result
+    }
+
+    async fn decide(&self, request: PermitRequest) -> Result<PermitResponse, PermitError> {
         // Validate required fields
         self.validate_request(&request)?;
 
         // Call UBL /v1/policy/permit
         let response = self.ubl_client
-            .request_permit(&request)
+            .request_permit(&self.verifier.office_id, &request)
             .await
             .map_err(|e| PermitError::RequestFailed(e.to_string()))?;
 
@@ -142,12 +296,14 @@ impl PermitMiddleware {
             });
         }
 
-        // Validate permit has all required bindings
-        if let Some(ref permit) = response.permit {
-            self.validate_permit(permit)?;
-        } else {
+        // Verify the permit itself; the response is not trusted
+        let Some(ref permit) = response.permit else {
             return Err(PermitError::InvalidResponse("Permit missing in Allow response".to_string()));
+        };
+        if permit.action != request.job_type || permit.target != request.target {
+            return Err(PermitError::InvalidResponse("Permit does not match request".to_string()));
         }
+        self.verifier.verify(permit, now_ms())?;
 
         Ok(response)
     }
@@ -166,49 +322,19 @@ impl PermitMiddleware {
         Ok(())
     }
 
-    /// Validate permit has all required bindings
-    fn validate_permit(&self, permit: &Permit) -> Result<(), PermitError> {
-        // Check expiration
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as u64)
-            .unwrap_or(0);
-
-        if permit.exp < now_ms {
-            return Err(PermitError::Expired);
-        }
-
-        // Check required scopes
-        if permit.scopes.tenant_id.is_empty() {
-            return Err(PermitError::MissingBinding("permit.scopes.tenant_id".to_string()));
-        }
-        if permit.scopes.job_type.is_empty() {
-            return Err(PermitError::MissingBinding("permit.scopes.jobType".to_string()));
-        }
-        if permit.scopes.target.is_empty() {
-            return Err(PermitError::MissingBinding("permit.scopes.target".to_string()));
-        }
-        if permit.scopes.subject_hash.is_empty() {
-            return Err(PermitError::MissingBinding("permit.scopes.subject_hash".to_string()));
-        }
-        if permit.scopes.policy_hash.is_empty() {
-            return Err(PermitError::MissingBinding("permit.scopes.policy_hash".to_string()));
-        }
-
-        Ok(())
-    }
-
     /// Check if a permit is still valid
     pub fn is_permit_valid(&self, permit: &Permit) -> bool {
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as u64)
-            .unwrap_or(0);
-
-        permit.exp > now_ms
+        permit.exp_ms > now_ms()
     }
 }
 
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
 /// Helper to build permit requests
 pub struct PermitRequestBuilder {
     request: PermitRequest,
@@ -263,6 +389,7 @@ impl PermitRequestBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
 
     #[test]
     fn test_permit_request_builder() {
@@ -278,26 +405,64 @@ mod tests {
         assert_eq!(request.target, "LAB_512");
     }
 
-    #[test]
-    fn test_permit_validation() {
-        let permit = Permit {
-            aud: "runner:LAB_512".to_string(),
+    fn signed_permit(key: &SigningKey, aud: &str, exp_ms: i64) -> Permit {
+        let mut permit = Permit {
             jti: "test-jti".to_string(),
-            exp: u64::MAX, // Far future
-            sig: "test-sig".to_string(),
-            scopes: PermitScopes {
-                tenant_id: "T.UBL".to_string(),
-                job_type: "service.restart".to_string(),
-                target: "LAB_512".to_string(),
-                subject_hash: "abc123".to_string(),
-                policy_hash: "def456".to_string(),
-                approval_ref: None,
-            },
+            aud: aud.to_string(),
+            action: "service.restart".to_string(),
+            target: "LAB_512".to_string(),
+            args: serde_json::json!({"name": "minio"}),
+            risk: "L1".to_string(),
+            plan_hash: "blake3:aa".to_string(),
+            binding_hash: "blake3:bb".to_string(),
+            policy_hash: "blake3:cc".to_string(),
+            nonce: "nonce-1".to_string(),
+            issued_at_ms: 1_000,
+            exp_ms,
+            approver: "session:default".to_string(),
+            signer: hex::encode(key.verifying_key().as_bytes()),
+            sig: String::new(),
         };
+        let sig = key.sign(&claims_bytes(&permit));
+        permit.sig = format!("ed25519:{}", URL_SAFE_NO_PAD.encode(sig.to_bytes()));
+        permit
+    }
 
-        // Would need UblClient mock for full test
-        // Just verify struct works
-        assert_eq!(permit.scopes.tenant_id, "T.UBL");
+    fn verifier(key: &SigningKey) -> PermitVerifier {
+        PermitVerifier::new("office", &[hex::encode(key.verifying_key().as_bytes())]).unwrap()
     }
-}
 
+    #[test]
+    fn test_permit_verification() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let permit = signed_permit(&key, "office", 10_000);
+        let verifier = verifier(&key);
+
+        assert!(verifier.verify(&permit, 2_000).is_ok());
+        // Same nonce again
+        assert!(matches!(verifier.verify(&permit, 2_000), Err(PermitError::Replayed)));
+        // Fresh verifier: expiry and clock skew
+        let verifier = self::verifier(&key);
+        assert!(matches!(verifier.verify(&permit, 10_000), Err(PermitError::Expired)));
+        assert!(matches!(verifier.verify(&permit, -40_000), Err(PermitError::NotYetValid)));
+    }
+
+    #[test]
+    fn test_permit_rejections() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let verifier = verifier(&key);
+
+        // Addressed to another office (validly signed)
+        let other = signed_permit(&key, "other-office", 10_000);
+        assert!(matches!(verifier.verify(&other, 2_000), Err(PermitError::WrongAudience { .. })));
+
+        // Claims edited after signing
+        let mut tampered = signed_permit(&key, "office", 10_000);
+        tampered.exp_ms = 99_000;
+        assert!(matches!(verifier.verify(&tampered, 2_000), Err(PermitError::InvalidSignature(_))));
+
+        // Signed by a key that is not pinned, even if it names itself as signer
+        let rogue = signed_permit(&SigningKey::from_bytes(&[9u8; 32]), "office", 10_000);
+        assert!(matches!(verifier.verify(&rogue, 2_000), Err(PermitError::InvalidSignature(_))));
+    }
+}
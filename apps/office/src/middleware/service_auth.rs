@@ -0,0 +1,212 @@
+//! Service Auth - Signed requests between UBL and Office
+//!
+//! Office and UBL authenticate each other's internal calls with their service
+//! keys. Each request carries:
+//!
+//! - `X-UBL-Service`: sender id (`ubl`, or this office's container id)
+//! - `X-UBL-Timestamp`: Unix milliseconds
+//! - `X-UBL-Signature`: Ed25519ph (context `ubl:service:v2`) over
+//!   `ubl_kernel::service_request_bytes`, which binds method, path and body
+//!
+//! Office signs its calls to UBL's internal routes (permits, commands) and
+//! checks UBL's signature on the gateway-facing `/v1/office/*` routes. A bad
+//! signature is always refused; an unsigned call only with
+//! `ubl.require_service_auth`, so keys can be rolled out first.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::State,
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ed25519_dalek::SigningKey;
+use thiserror::Error;
+use tracing::warn;
+use ubl_kernel::{contexts, service_request_bytes, sign_with, verify_with, SignatureMode};
+
+use crate::api::SharedState;
+use crate::UblConfig;
+
+pub const HEADER_SERVICE: &str = "x-ubl-service";
+pub const HEADER_TIMESTAMP: &str = "x-ubl-timestamp";
+pub const HEADER_SIGNATURE: &str = "x-ubl-signature";
+
+/// Largest accepted difference between the request timestamp and our clock
+pub const MAX_SKEW_MS: i64 = 5 * 60 * 1000;
+
+/// Largest gateway request body buffered for verification (summaries carry
+/// whole conversation segments)
+const MAX_SIGNED_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Why a signed request was refused
+#[derive(Error, Debug, PartialEq)]
+pub enum ServiceAuthError {
+    #[error("request is not signed")]
+    Unsigned,
+
+    #[error("malformed {0} header")]
+    Malformed(&'static str),
+
+    #[error("timestamp outside the allowed {}s window", MAX_SKEW_MS / 1000)]
+    Stale,
+
+    #[error("signature does not match any trusted service key")]
+    BadSignature,
+}
+
+/// Sign a request as `service`. Returned as name/value pairs so both the
+/// client (reqwest) and server (axum) header types can take them.
+pub fn sign_request(
+    key: &SigningKey,
+    service: &str,
+    method: &str,
+    path_and_query: &str,
+    timestamp_ms: i64,
+    body: &[u8],
+) -> Vec<(&'static str, String)> {
+    let message = service_request_bytes(service, method, path_and_query, timestamp_ms, body);
+    let Ok(signature) = sign_with(SignatureMode::Ed25519ph, key, contexts::SERVICE, &message) else {
+        return Vec::new();
+    };
+    vec![
+        (HEADER_SERVICE, service.to_string()),
+        (HEADER_TIMESTAMP, timestamp_ms.to_string()),
+        (HEADER_SIGNATURE, signature),
+    ]
+}
+
+/// Checks UBL's signature on incoming service requests
+#[derive(Debug, Clone)]
+pub struct ServiceVerifier {
+    trusted: Vec<String>,
+    required: bool,
+}
+
+impl ServiceVerifier {
+    pub fn new(trusted: Vec<String>, required: bool) -> Self {
+        Self { trusted, required }
+    }
+
+    pub fn from_config(config: &UblConfig) -> Self {
+        Self::new(config.service_pubkeys.clone(), config.require_service_auth)
+    }
+
+    /// Check a request's service signature; returns the sender's service id
+    pub fn verify(
+        &self,
+        headers: &HeaderMap,
+        method: &str,
+        path_and_query: &str,
+        body: &[u8],
+        now_ms: i64,
+    ) -> Result<String, ServiceAuthError> {
+        let header = |name: &'static str| -> Result<Option<&str>, ServiceAuthError> {
+            headers
+                .get(name)
+                .map(|v| v.to_str().map_err(|_| ServiceAuthError::Malformed(name)))
+                .transpose()
+        };
+        let (service, timestamp, signature) =
+            match (header(HEADER_SERVICE)?, header(HEADER_TIMESTAMP)?, header(HEADER_SIGNATURE)?) {
+                (None, None, None) => return Err(ServiceAuthError::Unsigned),
+                (Some(service), Some(timestamp), Some(signature)) => (service, timestamp, signature),
+                (None, _, _) => return Err(ServiceAuthError::Malformed(HEADER_SERVICE)),
+                (_, None, _) => return Err(ServiceAuthError::Malformed(HEADER_TIMESTAMP)),
+                (_, _, None) => return Err(ServiceAuthError::Malformed(HEADER_SIGNATURE)),
+            };
+        let timestamp: i64 = timestamp
+            .parse()
+            .map_err(|_| ServiceAuthError::Malformed(HEADER_TIMESTAMP))?;
+        if (now_ms - timestamp).abs() > MAX_SKEW_MS {
+            return Err(ServiceAuthError::Stale);
+        }
+
+        let message = service_request_bytes(service, method, path_and_query, timestamp, body);
+        self.trusted
+            .iter()
+            .any(|pubkey| verify_with(SignatureMode::Ed25519ph, pubkey, contexts::SERVICE, &message, signature).is_ok())
+            .then(|| service.to_string())
+            .ok_or(ServiceAuthError::BadSignature)
+    }
+
+    /// Whether a request with this outcome may proceed
+    fn admits(&self, outcome: &Result<String, ServiceAuthError>) -> bool {
+        match outcome {
+            Ok(_) => true,
+            Err(ServiceAuthError::Unsigned) => !self.required,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Middleware for the gateway-facing routes UBL calls
+pub async fn require_service(
+    State(state): State<SharedState>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let verifier = state.read().await.service_verifier.clone();
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_SIGNED_BODY_BYTES).await else {
+        return reject(StatusCode::PAYLOAD_TOO_LARGE, "request body too large".to_string());
+    };
+    let path = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+    let outcome = verifier.verify(
+        &parts.headers,
+        parts.method.as_str(),
+        path,
+        &bytes,
+        chrono::Utc::now().timestamp_millis(),
+    );
+    if !verifier.admits(&outcome) {
+        let error = outcome.err().map(|e| e.to_string()).unwrap_or_default();
+        warn!("🚫 Service auth failed on {}: {}", path, error);
+        return reject(StatusCode::UNAUTHORIZED, format!("service auth: {}", error));
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+fn reject(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_map(pairs: Vec<(&'static str, String)>) -> HeaderMap {
+        pairs.into_iter().map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap())).collect()
+    }
+
+    #[test]
+    fn test_verify_ubl_signature() {
+        let ubl = SigningKey::from_bytes(&[3u8; 32]);
+        let verifier = ServiceVerifier::new(vec![hex::encode(ubl.verifying_key().as_bytes())], false);
+        let body = br#"{"job_id":"job_1"}"#;
+        let headers = header_map(sign_request(&ubl, "ubl", "POST", "/v1/office/job_event", 5_000_000, body));
+
+        assert_eq!(verifier.verify(&headers, "POST", "/v1/office/job_event", body, 5_000_000).unwrap(), "ubl");
+        assert_eq!(
+            verifier.verify(&headers, "POST", "/v1/office/job_event", b"{}", 5_000_000),
+            Err(ServiceAuthError::BadSignature)
+        );
+        assert_eq!(
+            verifier.verify(&headers, "POST", "/v1/office/job_event", body, 5_000_000 - MAX_SKEW_MS - 1),
+            Err(ServiceAuthError::Stale)
+        );
+
+        // Unsigned passes only while auth is optional; forged never does
+        let unsigned = verifier.verify(&HeaderMap::new(), "POST", "/v1/office/job_event", body, 5_000_000);
+        assert!(verifier.admits(&unsigned));
+        assert!(!ServiceVerifier { required: true, ..verifier.clone() }.admits(&unsigned));
+
+        let forger = SigningKey::from_bytes(&[4u8; 32]);
+        let forged = header_map(sign_request(&forger, "ubl", "POST", "/v1/office/job_event", 5_000_000, body));
+        assert!(!verifier.admits(&verifier.verify(&forged, "POST", "/v1/office/job_event", body, 5_000_000)));
+    }
+}
@@ -1,9 +1,31 @@
 //! # Office Runtime Metrics
 //!
-//! Prometheus metrics for Office Runtime operations.
+//! Prometheus metrics for Office Runtime operations, served on `GET /metrics`.
+//!
+//! Observations made through [`observe`] and [`inc`] inside a request also
+//! remember the request's trace id. Scrapers that ask for OpenMetrics
+//! (`Accept: application/openmetrics-text`) get those as exemplars on the
+//! matching bucket or counter; everyone else gets the plain text format.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::Duration;
 
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
 use lazy_static::lazy_static;
-use prometheus::{IntCounterVec, IntGaugeVec, HistogramVec, register_int_counter_vec, register_int_gauge_vec, register_histogram_vec};
+use prometheus::core::{Collector, Metric as _};
+use prometheus::proto::{LabelPair, MetricFamily, MetricType};
+use prometheus::{Encoder, GaugeVec, IntCounterVec, IntGaugeVec, HistogramVec, TextEncoder, register_gauge_vec, register_int_counter_vec, register_int_gauge_vec, register_histogram_vec};
+
+use ubl_errors::ErrorCode;
+
+use super::tracing::current_trace_id;
+
+/// Content type of the OpenMetrics exposition (with exemplars)
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
 
 lazy_static! {
     /// Total entity operations by type and status
@@ -40,6 +62,27 @@ lazy_static! {
         "LLM API call duration in seconds",
         &["provider", "operation"]
     ).unwrap();
+
+    /// Rolling p95 latency per provider (calls and probes)
+    pub static ref LLM_PROVIDER_P95: GaugeVec = register_gauge_vec!(
+        "office_llm_provider_latency_p95_seconds",
+        "Rolling p95 latency of successful LLM calls and probes by provider",
+        &["provider"]
+    ).unwrap();
+
+    /// Rolling error rate per provider (calls and probes)
+    pub static ref LLM_PROVIDER_ERROR_RATE: GaugeVec = register_gauge_vec!(
+        "office_llm_provider_error_rate",
+        "Rolling share of failed LLM calls and probes by provider",
+        &["provider"]
+    ).unwrap();
+
+    /// Provider picked per task type, and why (preferred, scored, healthiest, default)
+    pub static ref LLM_ROUTING_DECISIONS: IntCounterVec = register_int_counter_vec!(
+        "office_llm_routing_decisions_total",
+        "LLM routing decisions by task type, provider and reason",
+        &["task", "provider", "reason"]
+    ).unwrap();
     
     /// Context frame building operations
     pub static ref CONTEXT_OPS: IntCounterVec = register_int_counter_vec!(
@@ -68,6 +111,257 @@ lazy_static! {
         "UBL commit duration in seconds",
         &["container_id"]
     ).unwrap();
+
+    /// Time a job spent in a state before leaving it
+    pub static ref JOB_STATE_DURATION: HistogramVec = register_histogram_vec!(
+        "office_job_state_duration_seconds",
+        "Time a job spent in a state before transitioning out, in seconds",
+        &["state"],
+        vec![1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3600.0, 14400.0, 86400.0]
+    ).unwrap();
+
+    /// Permit decisions from UBL by job type (allow, deny, error)
+    pub static ref PERMIT_DECISIONS: IntCounterVec = register_int_counter_vec!(
+        "office_permit_decisions_total",
+        "Permit decisions by job type and outcome",
+        &["job_type", "decision"]
+    ).unwrap();
+
+    /// Tool executions by tool and outcome (ok, error, timeout)
+    pub static ref TOOL_EXECUTIONS: IntCounterVec = register_int_counter_vec!(
+        "office_tool_executions_total",
+        "Tool executions by tool and outcome",
+        &["tool", "status"]
+    ).unwrap();
+
+    /// Embedding cache lookups by model and result (hit, miss)
+    pub static ref EMBEDDING_CACHE: IntCounterVec = register_int_counter_vec!(
+        "office_embedding_cache_total",
+        "Embedding cache lookups by model and result",
+        &["model", "result"]
+    ).unwrap();
+
+    /// Error responses by catalog code and matched route (see [`count_errors`])
+    pub static ref ERRORS: IntCounterVec = register_int_counter_vec!(
+        "office_errors_total",
+        "Error responses by canonical error code and route",
+        &["code", "route"]
+    ).unwrap();
+
+    /// UBL rejections received by the UBL client, by canonical error code
+    pub static ref UBL_REJECTIONS: IntCounterVec = register_int_counter_vec!(
+        "office_ubl_rejections_total",
+        "UBL rejections by canonical error code",
+        &["code"]
+    ).unwrap();
+}
+
+lazy_static! {
+    /// Latest traced observation per series (and bucket, for histograms)
+    static ref EXEMPLARS: Mutex<HashMap<String, Exemplar>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Clone)]
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    /// Unix seconds
+    timestamp: f64,
+}
+
+/// Observe a histogram value, keeping the current trace as its bucket's exemplar
+pub fn observe(hist: &HistogramVec, labels: &[&str], value: f64) {
+    let child = hist.with_label_values(labels);
+    child.observe(value);
+
+    let Some(trace_id) = current_trace_id() else { return };
+    let metric = child.metric();
+    let le = metric
+        .get_histogram()
+        .get_bucket()
+        .iter()
+        .map(|b| b.get_upper_bound())
+        .find(|bound| value <= *bound)
+        .unwrap_or(f64::INFINITY);
+    let key = bucket_key(&series_key(&family_name(hist), metric.get_label()), le);
+    remember(key, trace_id, value);
+}
+
+/// Increment a counter, keeping the current trace as the series' exemplar
+pub fn inc(counter: &IntCounterVec, labels: &[&str]) {
+    let child = counter.with_label_values(labels);
+    child.inc();
+
+    let Some(trace_id) = current_trace_id() else { return };
+    let key = series_key(&family_name(counter), child.metric().get_label());
+    remember(key, trace_id, 1.0);
+}
+
+/// Count 4xx/5xx responses in `office_errors_total`. Responses built from an
+/// `OfficeError` carry its code; the others are counted under the generic
+/// code for their status.
+pub async fn count_errors(request: Request, next: Next) -> Response {
+    let route = request.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string());
+    let response = next.run(request).await;
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        let code = response
+            .extensions()
+            .get::<ErrorCode>()
+            .copied()
+            .unwrap_or_else(|| ErrorCode::from_http_status(status.as_u16()));
+        inc(&ERRORS, &[code.as_str(), route.as_deref().unwrap_or("unmatched")]);
+    }
+    response
+}
+
+/// Record one LLM call (count by status, latency by provider)
+pub fn record_llm_call(provider: &str, operation: &str, elapsed: Duration, ok: bool) {
+    inc(&LLM_CALLS, &[provider, if ok { "success" } else { "error" }]);
+    observe(&LLM_LATENCY, &[provider, operation], elapsed.as_secs_f64());
+}
+
+fn family_name(collector: &dyn Collector) -> String {
+    collector.desc().first().map(|d| d.fq_name.clone()).unwrap_or_default()
+}
+
+fn series_key(name: &str, labels: &[LabelPair]) -> String {
+    format!("{}{{{}}}", name, render_labels(labels, None))
+}
+
+fn bucket_key(series: &str, le: f64) -> String {
+    format!("{}@{}", series, format_float(le))
+}
+
+fn remember(key: String, trace_id: String, value: f64) {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    if let Ok(mut exemplars) = EXEMPLARS.lock() {
+        exemplars.insert(key, Exemplar { trace_id, value, timestamp });
+    }
+}
+
+/// Encode all registered metrics; returns `(content_type, body)`
+pub fn render_metrics(openmetrics: bool) -> (String, String) {
+    let families = prometheus::gather();
+    if openmetrics {
+        return (OPENMETRICS_CONTENT_TYPE.to_string(), encode_openmetrics(&families));
+    }
+    let encoder = TextEncoder::new();
+    let mut buf = Vec::new();
+    if let Err(e) = encoder.encode(&families, &mut buf) {
+        tracing::error!("Failed to encode metrics: {}", e);
+    }
+    (encoder.format_type().to_string(), String::from_utf8(buf).unwrap_or_default())
+}
+
+/// OpenMetrics text exposition with trace exemplars
+fn encode_openmetrics(families: &[MetricFamily]) -> String {
+    let exemplars = EXEMPLARS.lock().map(|e| e.clone()).unwrap_or_default();
+    let mut out = String::new();
+
+    for family in families {
+        let name = family.get_name();
+        let (base, kind) = match family.get_field_type() {
+            MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+            MetricType::GAUGE => (name, "gauge"),
+            MetricType::HISTOGRAM => (name, "histogram"),
+            _ => (name, "unknown"),
+        };
+        let _ = writeln!(out, "# TYPE {} {}", base, kind);
+        let _ = writeln!(out, "# HELP {} {}", base, family.get_help().replace('\\', "\\\\").replace('\n', "\\n"));
+
+        for metric in family.get_metric() {
+            let labels = metric.get_label();
+            let series = series_key(name, labels);
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    out.push_str(&sample(&format!("{}_total", base), labels, None, metric.get_counter().get_value()));
+                    push_exemplar(&mut out, exemplars.get(&series));
+                }
+                MetricType::GAUGE => {
+                    out.push_str(&sample(name, labels, None, metric.get_gauge().get_value()));
+                    out.push('\n');
+                }
+                MetricType::HISTOGRAM => {
+                    let hist = metric.get_histogram();
+                    let mut bounds: Vec<(f64, u64)> = hist
+                        .get_bucket()
+                        .iter()
+                        .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+                        .collect();
+                    if bounds.last().is_none_or(|(le, _)| le.is_finite()) {
+                        bounds.push((f64::INFINITY, hist.get_sample_count()));
+                    }
+                    for (le, count) in bounds {
+                        let bucket = format!("{}_bucket", name);
+                        out.push_str(&sample(&bucket, labels, Some(le), count as f64));
+                        push_exemplar(&mut out, exemplars.get(&bucket_key(&series, le)));
+                    }
+                    out.push_str(&sample(&format!("{}_count", name), labels, None, hist.get_sample_count() as f64));
+                    out.push('\n');
+                    out.push_str(&sample(&format!("{}_sum", name), labels, None, hist.get_sample_sum()));
+                    out.push('\n');
+                }
+                _ => {
+                    out.push_str(&sample(name, labels, None, metric.get_untyped().get_value()));
+                    out.push('\n');
+                }
+            }
+        }
+    }
+
+    out.push_str("# EOF\n");
+    out
+}
+
+/// One sample line without the trailing newline
+fn sample(name: &str, labels: &[LabelPair], le: Option<f64>, value: f64) -> String {
+    let labels = render_labels(labels, le);
+    if labels.is_empty() {
+        format!("{} {}", name, format_float(value))
+    } else {
+        format!("{}{{{}}} {}", name, labels, format_float(value))
+    }
+}
+
+fn push_exemplar(out: &mut String, exemplar: Option<&Exemplar>) {
+    if let Some(e) = exemplar {
+        let _ = write!(out, " # {{trace_id=\"{}\"}} {} {:.3}", e.trace_id, format_float(e.value), e.timestamp);
+    }
+    out.push('\n');
+}
+
+fn render_labels(labels: &[LabelPair], le: Option<f64>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|l| format!("{}=\"{}\"", l.get_name(), escape_label(l.get_value())))
+        .collect();
+    if let Some(le) = le {
+        parts.push(format!("le=\"{}\"", format_float(le)));
+    }
+    parts.join(",")
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// OpenMetrics number: `+Inf`, integers as `1.0`
+fn format_float(v: f64) -> String {
+    if v.is_infinite() {
+        return if v > 0.0 { "+Inf".to_string() } else { "-Inf".to_string() };
+    }
+    if v.is_nan() {
+        return "NaN".to_string();
+    }
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        format!("{:.1}", v)
+    } else {
+        format!("{}", v)
+    }
 }
 
 /// Initialize metrics (called on startup)
@@ -76,3 +370,80 @@ pub fn init_metrics() {
     // This function can be used for any additional initialization
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::tracing::{with_trace, TraceContext};
+
+    #[test]
+    fn test_format_float() {
+        assert_eq!(format_float(1.0), "1.0");
+        assert_eq!(format_float(0.005), "0.005");
+        assert_eq!(format_float(f64::INFINITY), "+Inf");
+    }
+
+    #[tokio::test]
+    async fn test_errors_counted_by_code_and_route() {
+        use axum::{body::Body, http::StatusCode, response::IntoResponse, routing::get, Router};
+        use tower::Service;
+
+        let coded = || async {
+            let mut response = StatusCode::FORBIDDEN.into_response();
+            response.extensions_mut().insert(ErrorCode::PolicyDenied);
+            response
+        };
+        let mut app = Router::new()
+            .route("/test-errors/permit/:id", get(coded))
+            .route("/test-errors/plain", get(|| async { StatusCode::NOT_FOUND }))
+            .layer(axum::middleware::from_fn(count_errors));
+
+        for uri in ["/test-errors/permit/1", "/test-errors/permit/2", "/test-errors/plain"] {
+            let req = axum::http::Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.call(req).await.unwrap();
+        }
+        assert_eq!(ERRORS.with_label_values(&["POLICY_DENIED", "/test-errors/permit/:id"]).get(), 2);
+        assert_eq!(ERRORS.with_label_values(&["NOT_FOUND", "/test-errors/plain"]).get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_openmetrics_exemplars() {
+        let trace = TraceContext::generate();
+        let trace_id = trace.trace_id.clone();
+        with_trace(trace, async {
+            observe(&JOB_STATE_DURATION, &["test_exemplar_state"], 42.0);
+            inc(&TOOL_EXECUTIONS, &["test_exemplar_tool", "error"]);
+        })
+        .await;
+        // Outside a trace: counted, no exemplar
+        observe(&JOB_STATE_DURATION, &["test_untraced_state"], 2.0);
+
+        let (content_type, body) = render_metrics(true);
+        assert_eq!(content_type, OPENMETRICS_CONTENT_TYPE);
+        assert!(body.ends_with("# EOF\n"));
+        assert!(body.contains("# TYPE office_tool_executions counter\n"));
+
+        let exemplar = format!("# {{trace_id=\"{}\"}}", trace_id);
+        let bucket = body
+            .lines()
+            .find(|l| l.starts_with("office_job_state_duration_seconds_bucket{state=\"test_exemplar_state\",le=\"60.0\"}"))
+            .unwrap();
+        assert!(bucket.contains(&exemplar), "{}", bucket);
+        // Only the bucket the value fell into carries it
+        let lower = body
+            .lines()
+            .find(|l| l.starts_with("office_job_state_duration_seconds_bucket{state=\"test_exemplar_state\",le=\"15.0\"}"))
+            .unwrap();
+        assert!(!lower.contains("trace_id"));
+        let counter = body
+            .lines()
+            .find(|l| l.starts_with("office_tool_executions_total{status=\"error\",tool=\"test_exemplar_tool\"}"))
+            .unwrap();
+        assert!(counter.contains(&exemplar), "{}", counter);
+        assert!(!body.lines().any(|l| l.contains("test_untraced_state") && l.contains("trace_id")));
+
+        let (content_type, body) = render_metrics(false);
+        assert!(content_type.starts_with("text/plain"));
+        assert!(!body.contains("trace_id"));
+    }
+}
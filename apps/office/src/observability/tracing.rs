@@ -2,8 +2,121 @@
 //!
 //! Provides tracing instrumentation for Office Runtime.
 //! OpenTelemetry removed - use simple tracing spans.
+//!
+//! ## Trace context
+//!
+//! Requests carry a W3C `traceparent` (`00-<trace_id>-<parent_id>-<flags>`).
+//! [`trace_context`] adopts the caller's trace id (or starts a new trace) and
+//! keeps it in a task-local for the rest of the request: metrics attach it as
+//! an exemplar and calls to UBL forward it via [`trace_headers`], so Grafana
+//! can jump from a latency bucket to the trace that landed in it.
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use rand::RngCore;
+use tracing::{self, Instrument, Span};
+
+/// W3C trace context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+tokio::task_local! {
+    static CURRENT_TRACE: TraceContext;
+}
+
+/// Trace context of the request being served
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 32 lowercase hex chars, shared by every hop of the trace
+    pub trace_id: String,
+    /// 16 lowercase hex chars identifying this hop
+    pub span_id: String,
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Start a new sampled trace
+    pub fn generate() -> Self {
+        Self { trace_id: random_hex(16), span_id: random_hex(8), sampled: true }
+    }
+
+    /// Parse a `traceparent` header; returns the caller's context
+    pub fn parse(header: &str) -> Option<Self> {
+        let parts: Vec<&str> = header.trim().split('-').collect();
+        let [version, trace_id, parent_id, flags] = parts[..] else {
+            return None;
+        };
+        let is_hex = |s: &str, len: usize| {
+            s.len() == len && s.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        };
+        if !is_hex(version, 2) || version == "ff" || !is_hex(flags, 2) {
+            return None;
+        }
+        let all_zero = |s: &str| s.bytes().all(|b| b == b'0');
+        if !is_hex(trace_id, 32) || all_zero(trace_id) || !is_hex(parent_id, 16) || all_zero(parent_id) {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        Some(Self { trace_id: trace_id.to_string(), span_id: parent_id.to_string(), sampled: flags & 1 == 1 })
+    }
+
+    /// Same trace, new span id for this hop
+    pub fn child(&self) -> Self {
+        Self { trace_id: self.trace_id.clone(), span_id: random_hex(8), sampled: self.sampled }
+    }
+
+    /// `traceparent` value naming this hop as the parent
+    pub fn traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.sampled as u8)
+    }
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    hex::encode(buf)
+}
 
-use tracing::{self, Span};
+/// Trace context of the current task, if any
+pub fn current_trace() -> Option<TraceContext> {
+    CURRENT_TRACE.try_with(|t| t.clone()).ok()
+}
+
+/// Trace id of the current task, if any (used for exemplars)
+pub fn current_trace_id() -> Option<String> {
+    CURRENT_TRACE.try_with(|t| t.trace_id.clone()).ok()
+}
+
+/// Run a future inside a trace context (spawned tasks do not inherit it)
+pub async fn with_trace<F: std::future::Future>(trace: TraceContext, fut: F) -> F::Output {
+    CURRENT_TRACE.scope(trace, fut).await
+}
+
+/// Headers propagating the current trace to UBL; empty outside a trace
+pub fn trace_headers() -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(value) = current_trace().and_then(|t| t.traceparent().parse().ok()) {
+        headers.insert(TRACEPARENT_HEADER, value);
+    }
+    headers
+}
+
+/// Axum middleware: adopt or start a trace for every request
+pub async fn trace_context(req: Request, next: Next) -> Response {
+    let trace = req
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(TraceContext::parse)
+        .map(|parent| parent.child())
+        .unwrap_or_else(TraceContext::generate);
+
+    let span = tracing::info_span!("office.request", trace_id = %trace.trace_id, span_id = %trace.span_id);
+    let traceparent = trace.traceparent();
+    let mut response = with_trace(trace, next.run(req)).instrument(span).await;
+    if let Ok(value) = traceparent.parse() {
+        response.headers_mut().insert(TRACEPARENT_HEADER, value);
+    }
+    response
+}
 
 /// Initialize basic tracing (no OpenTelemetry)
 ///
@@ -76,4 +189,37 @@ mod tests {
         // Just verify the span was created successfully
         assert!(span.is_disabled() || !span.is_disabled());
     }
+
+    #[test]
+    fn test_traceparent_roundtrip() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let parent = TraceContext::parse(header).unwrap();
+        assert_eq!(parent.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert!(parent.sampled);
+        assert_eq!(parent.traceparent(), header);
+
+        let child = parent.child();
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_ne!(child.span_id, parent.span_id);
+
+        for bad in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+        ] {
+            assert!(TraceContext::parse(bad).is_none(), "{}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_is_task_local() {
+        assert!(current_trace_id().is_none());
+        let trace = TraceContext::generate();
+        let id = trace.trace_id.clone();
+        let seen = with_trace(trace, async { current_trace_id() }).await;
+        assert_eq!(seen, Some(id));
+        assert!(trace_headers().is_empty());
+    }
 }
@@ -0,0 +1,225 @@
+//! Secret Providers
+//!
+//! Resolves secret references in configuration (LLM API keys, tokens) so the
+//! secret itself never has to live in a config file:
+//!
+//! - `env:ANTHROPIC_API_KEY` - read from an environment variable
+//! - `file:/run/secrets/anthropic` - read from a file (Docker/K8s secrets)
+//! - `aws-sm:<secret-id>` - AWS Secrets Manager (stub)
+//! - `gcp-sm:<secret-name>` - GCP Secret Manager (stub)
+//!
+//! Anything else is treated as a literal value. Files are re-read on every
+//! resolution, so a rotated secret is picked up on the next reload.
+//!
+//! Resolved values are held in [`SecretString`], which is redacted in `Debug`,
+//! compared in constant time, and zeroized on drop.
+
+use std::fmt;
+
+use ubl_kernel::Zeroize;
+
+use crate::{OfficeError, Result};
+
+/// An API key or token held in memory
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The raw value. Callers must not log it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString({})", mask(&self.0))
+    }
+}
+
+impl PartialEq for SecretString {
+    fn eq(&self, other: &Self) -> bool {
+        ubl_kernel::ct_eq(self.0.as_bytes(), other.0.as_bytes())
+    }
+}
+
+impl Eq for SecretString {}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// A source of named secrets
+pub trait SecretProvider: Send + Sync {
+    /// Scheme prefix handled by this provider (e.g. "env")
+    fn scheme(&self) -> &'static str;
+
+    /// Fetch a secret. `Ok(None)` means it does not exist in this backend.
+    fn get(&self, name: &str) -> Result<Option<String>>;
+}
+
+/// Environment variable backend
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+    fn scheme(&self) -> &'static str {
+        "env"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(std::env::var(name).ok())
+    }
+}
+
+/// File backend; the reference is the file path
+pub struct FileSecretProvider;
+
+impl SecretProvider for FileSecretProvider {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        match std::fs::read_to_string(name) {
+            Ok(contents) => Ok(Some(contents.trim().to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(OfficeError::ConfigError(format!("cannot read secret file {}: {}", name, e))),
+        }
+    }
+}
+
+/// AWS Secrets Manager backend (stub until the SDK is wired in)
+pub struct AwsSecretsManagerProvider;
+
+impl SecretProvider for AwsSecretsManagerProvider {
+    fn scheme(&self) -> &'static str {
+        "aws-sm"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        Err(OfficeError::ConfigError(format!(
+            "AWS Secrets Manager is not available in this build (secret {})",
+            name
+        )))
+    }
+}
+
+/// GCP Secret Manager backend (stub until the SDK is wired in)
+pub struct GcpSecretManagerProvider;
+
+impl SecretProvider for GcpSecretManagerProvider {
+    fn scheme(&self) -> &'static str {
+        "gcp-sm"
+    }
+
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        Err(OfficeError::ConfigError(format!(
+            "GCP Secret Manager is not available in this build (secret {})",
+            name
+        )))
+    }
+}
+
+/// Built-in providers, in lookup order
+fn providers() -> [&'static dyn SecretProvider; 4] {
+    [
+        &EnvSecretProvider,
+        &FileSecretProvider,
+        &AwsSecretsManagerProvider,
+        &GcpSecretManagerProvider,
+    ]
+}
+
+/// Resolve a secret reference. Literals (no known scheme) pass through.
+///
+/// Errors never include the secret value, only the reference.
+pub fn resolve(reference: &str) -> Result<String> {
+    for provider in providers() {
+        if let Some(name) = reference
+            .strip_prefix(provider.scheme())
+            .and_then(|rest| rest.strip_prefix(':'))
+        {
+            return provider.get(name)?.ok_or_else(|| {
+                OfficeError::ConfigError(format!("secret {} not found", reference))
+            });
+        }
+    }
+    Ok(reference.to_string())
+}
+
+/// Mask a secret for display; empty stays empty so "unset" is still visible
+pub fn mask(secret: &str) -> String {
+    if secret.is_empty() {
+        String::new()
+    } else {
+        "***".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_passes_through() {
+        assert_eq!(resolve("sk-literal").unwrap(), "sk-literal");
+        assert_eq!(resolve("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_file_reference_is_read_and_trimmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api_key");
+        std::fs::write(&path, "sk-from-file\n").unwrap();
+        let reference = format!("file:{}", path.display());
+        assert_eq!(resolve(&reference).unwrap(), "sk-from-file");
+
+        // Rotation: rewriting the file changes the next resolution
+        std::fs::write(&path, "sk-rotated\n").unwrap();
+        assert_eq!(resolve(&reference).unwrap(), "sk-rotated");
+    }
+
+    #[test]
+    fn test_missing_secret_error_does_not_leak() {
+        let err = resolve("env:OFFICE_TEST_SECRET_NEVER_SET").unwrap_err();
+        assert!(err.to_string().contains("env:OFFICE_TEST_SECRET_NEVER_SET"));
+    }
+
+    #[test]
+    fn test_secret_string_is_redacted() {
+        let key = SecretString::from("sk-live");
+        assert_eq!(format!("{:?}", key), "SecretString(***)");
+        assert_eq!(key, SecretString::new("sk-live".to_string()));
+        assert_ne!(key, SecretString::from("sk-test"));
+        assert_eq!(serde_json::to_string(&key).unwrap(), "\"sk-live\"");
+    }
+
+    #[test]
+    fn test_cloud_stubs_error() {
+        assert!(resolve("aws-sm:prod/anthropic").is_err());
+        assert!(resolve("gcp-sm:anthropic").is_err());
+    }
+}
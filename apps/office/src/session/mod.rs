@@ -1,13 +1,16 @@
 //! Session Management Module
 //!
-//! Manages session types, modes, handovers, and token budgets.
+//! Manages session types, modes, handovers, and token budgets (shared per
+//! entity across its sessions).
 
 mod session;
 mod handover;
 mod modes;
 mod token_budget;
+mod shared_budget;
 
 pub use session::{Session, SessionId, SessionStatus};
 pub use handover::{Handover, HandoverId};
 pub use modes::{SessionType, SessionMode, SessionConfig};
 pub use token_budget::{TokenBudget, TokenQuota, EntityTokenType};
+pub use shared_budget::{BudgetReservation, EntityBudgets, TokenBucket};
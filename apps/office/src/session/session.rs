@@ -2,13 +2,17 @@
 //!
 //! A session represents a logical interaction between a user/system and an LLM entity.
 
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::entity::{EntityId, InstanceId};
+use crate::{OfficeError, Result};
 use super::modes::{SessionType, SessionMode, SessionConfig};
 use super::handover::Handover;
+use super::shared_budget::{BudgetReservation, TokenBucket};
 
 /// Unique identifier for a session
 pub type SessionId = String;
@@ -68,6 +72,9 @@ pub struct Session {
     pub error: Option<String>,
     /// Session metadata
     pub metadata: serde_json::Value,
+    /// The entity's bucket, shared with its other sessions
+    #[serde(skip)]
+    pub shared_budget: Option<Arc<TokenBucket>>,
 }
 
 impl Session {
@@ -95,6 +102,7 @@ impl Session {
             handover: None,
             error: None,
             metadata: serde_json::json!({}),
+            shared_budget: None,
         }
     }
 
@@ -104,6 +112,12 @@ impl Session {
         self
     }
 
+    /// Draw tokens from the entity's shared bucket as well
+    pub fn with_shared_budget(mut self, bucket: Arc<TokenBucket>) -> Self {
+        self.shared_budget = Some(bucket);
+        self
+    }
+
     /// Start the session
     pub fn start(&mut self) {
         self.status = SessionStatus::Active;
@@ -121,20 +135,61 @@ impl Session {
         self.current_instance_id = None;
     }
 
-    /// Record token usage
-    pub fn consume_tokens(&mut self, count: u64) {
+    /// Record token usage, drawing it from the entity's shared bucket.
+    /// Denied, and not recorded, when the bucket cannot cover it.
+    pub fn consume_tokens(&mut self, count: u64) -> Result<()> {
+        if let Some(bucket) = &self.shared_budget {
+            if !bucket.try_take(count) {
+                return Err(self.exhausted());
+            }
+        }
+        self.record_usage(count);
+        Ok(())
+    }
+
+    /// Hold up to `max` tokens for one LLM call, capped by this session's
+    /// budget and what the entity's sessions have left together
+    pub fn reserve_tokens(&self, max: u64) -> Result<Option<BudgetReservation>> {
+        let Some(bucket) = &self.shared_budget else {
+            return Ok(None);
+        };
+        let own = self.token_budget.saturating_sub(self.tokens_consumed);
+        bucket.reserve(max.min(own)).map(Some).ok_or_else(|| self.exhausted())
+    }
+
+    /// Record usage of a call made under a reservation; the unused part goes
+    /// back to the shared bucket
+    pub fn consume_reserved(&mut self, reservation: Option<BudgetReservation>, count: u64) {
+        if let Some(reservation) = reservation {
+            reservation.settle(count);
+        }
+        self.record_usage(count);
+    }
+
+    fn record_usage(&mut self, count: u64) {
         self.tokens_consumed += count;
         self.message_count += 1;
     }
 
+    fn exhausted(&self) -> OfficeError {
+        OfficeError::BudgetExhausted(format!(
+            "no tokens left for session {} (entity {} shares one budget across its sessions)",
+            self.id, self.entity_id
+        ))
+    }
+
     /// Check if within token budget
     pub fn within_budget(&self) -> bool {
         self.tokens_consumed < self.token_budget
     }
 
-    /// Remaining budget
+    /// Remaining budget: this session's own, capped by the shared bucket
     pub fn remaining_budget(&self) -> u64 {
-        self.token_budget.saturating_sub(self.tokens_consumed)
+        let own = self.token_budget.saturating_sub(self.tokens_consumed);
+        match &self.shared_budget {
+            Some(bucket) => own.min(bucket.available()),
+            None => own,
+        }
     }
 
     /// Pause the session
@@ -219,7 +274,7 @@ mod tests {
         session.set_instance("instance_1".to_string());
         assert_eq!(session.instance_count, 1);
 
-        session.consume_tokens(1000);
+        session.consume_tokens(1000).unwrap();
         assert_eq!(session.tokens_consumed, 1000);
 
         session.complete(None);
@@ -239,11 +294,32 @@ mod tests {
         assert_eq!(session.token_budget, 4000);
         assert_eq!(session.remaining_budget(), 4000);
 
-        session.consume_tokens(3000);
+        session.consume_tokens(3000).unwrap();
         assert_eq!(session.remaining_budget(), 1000);
         assert!(session.within_budget());
 
-        session.consume_tokens(2000);
+        session.consume_tokens(2000).unwrap();
         assert!(!session.within_budget());
     }
+
+    #[test]
+    fn test_sessions_share_entity_bucket() {
+        let bucket = Arc::new(TokenBucket::with_refill(5000, 0.0));
+        let mut first = Session::new("entity_1".to_string(), SessionConfig::work_commit(), "user_1".to_string())
+            .with_shared_budget(bucket.clone());
+        let mut second = Session::new("entity_1".to_string(), SessionConfig::work_commit(), "user_1".to_string())
+            .with_shared_budget(bucket.clone());
+
+        // Each session alone would allow 5000; together they share 5000
+        let held = first.reserve_tokens(4000).unwrap();
+        assert_eq!(second.remaining_budget(), 1000);
+        first.consume_reserved(held, 1500);
+        assert_eq!(second.remaining_budget(), 3500);
+
+        second.consume_tokens(3500).unwrap();
+        assert!(matches!(first.reserve_tokens(100), Err(OfficeError::BudgetExhausted(_))));
+        // Denied usage is not recorded
+        assert!(second.consume_tokens(1).is_err());
+        assert_eq!(second.tokens_consumed, 3500);
+    }
 }
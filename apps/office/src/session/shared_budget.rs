@@ -0,0 +1,195 @@
+//! Shared Budget - One token bucket per entity
+//!
+//! Every session (and so every instance) of an entity draws from the same
+//! bucket, so running instances in parallel cannot multiply the entity's
+//! allowance. The bucket holds at most the entity's daily quota and refills
+//! continuously at that rate per day.
+//!
+//! An LLM call reserves its worst case up front and settles the actual usage
+//! afterwards; the unused part goes back. A reservation dropped without
+//! settling (cancelled call) is released in full.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::entity::{Entity, EntityId};
+use super::token_budget::{EntityTokenType, TokenQuota};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Token bucket shared by all sessions of one entity
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: u64,
+    refill_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Negative after usage overran its reservation; refills pay it back
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket of `capacity` tokens, refilled by `capacity` per day
+    pub fn new(capacity: u64) -> Self {
+        Self::with_refill(capacity, capacity as f64 / SECONDS_PER_DAY)
+    }
+
+    pub fn with_refill(capacity: u64, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            state: Mutex::new(BucketState {
+                tokens: capacity as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Tokens that can be reserved right now
+    pub fn available(&self) -> u64 {
+        self.with_state(|state| state.tokens.max(0.0) as u64)
+    }
+
+    /// Take exactly `count` tokens, or nothing
+    pub fn try_take(&self, count: u64) -> bool {
+        self.with_state(|state| {
+            if state.tokens >= count as f64 {
+                state.tokens -= count as f64;
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    /// Reserve up to `max` tokens; `None` when the bucket is empty
+    pub fn reserve(self: &Arc<Self>, max: u64) -> Option<BudgetReservation> {
+        let tokens = self.with_state(|state| {
+            let granted = (state.tokens.max(0.0) as u64).min(max);
+            state.tokens -= granted as f64;
+            granted
+        });
+        (tokens > 0).then(|| BudgetReservation {
+            bucket: Arc::clone(self),
+            tokens,
+        })
+    }
+
+    fn adjust(&self, delta: f64) {
+        self.with_state(|state| state.tokens = (state.tokens + delta).min(self.capacity as f64));
+    }
+
+    fn with_state<T>(&self, f: impl FnOnce(&mut BucketState) -> T) -> T {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(state.last_refill).as_secs_f64() * self.refill_per_sec;
+        state.tokens = (state.tokens + refill).min(self.capacity as f64);
+        state.last_refill = now;
+        f(&mut state)
+    }
+}
+
+/// Tokens held for one call until it settles
+#[derive(Debug)]
+pub struct BudgetReservation {
+    bucket: Arc<TokenBucket>,
+    tokens: u64,
+}
+
+impl BudgetReservation {
+    pub fn tokens(&self) -> u64 {
+        self.tokens
+    }
+
+    /// Charge `used` tokens: the rest is released, an overrun is owed
+    pub fn settle(mut self, used: u64) {
+        let tokens = std::mem::take(&mut self.tokens);
+        self.bucket.adjust(tokens as f64 - used as f64);
+    }
+}
+
+impl Drop for BudgetReservation {
+    fn drop(&mut self) {
+        if self.tokens > 0 {
+            self.bucket.adjust(self.tokens as f64);
+        }
+    }
+}
+
+/// The buckets of all entities
+#[derive(Debug, Default)]
+pub struct EntityBudgets {
+    buckets: Mutex<HashMap<EntityId, Arc<TokenBucket>>>,
+}
+
+impl EntityBudgets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The entity's bucket, sized by its daily quota on first use
+    pub fn bucket_for(&self, entity: &Entity) -> Arc<TokenBucket> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        buckets
+            .entry(entity.id.clone())
+            .or_insert_with(|| {
+                let quota = TokenQuota::for_entity_type(EntityTokenType::from(entity.entity_type));
+                Arc::new(TokenBucket::new(quota.daily_limit))
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservations_share_one_bucket() {
+        let bucket = Arc::new(TokenBucket::with_refill(1000, 0.0));
+
+        // Parallel instances together never get more than the bucket holds
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let bucket = bucket.clone();
+                std::thread::spawn(move || bucket.reserve(300))
+            })
+            .collect();
+        let reservations: Vec<_> = handles.into_iter().filter_map(|h| h.join().unwrap()).collect();
+        assert_eq!(reservations.iter().map(|r| r.tokens()).sum::<u64>(), 1000);
+        assert_eq!(bucket.available(), 0);
+
+        // Dropped without settling: released in full
+        drop(reservations);
+        assert_eq!(bucket.available(), 1000);
+
+        let a = bucket.reserve(600).unwrap();
+        let b = bucket.reserve(600).unwrap();
+        assert_eq!(b.tokens(), 400);
+        assert!(bucket.reserve(1).is_none());
+        assert!(!bucket.try_take(1));
+
+        // Unused part goes back; an overrun is owed
+        a.settle(100);
+        assert_eq!(bucket.available(), 500);
+        b.settle(900);
+        assert_eq!(bucket.available(), 0);
+    }
+
+    #[test]
+    fn test_refill_caps_at_capacity() {
+        let bucket = Arc::new(TokenBucket::with_refill(100, 1_000_000.0));
+        assert!(bucket.try_take(100));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(bucket.available(), 100);
+    }
+}
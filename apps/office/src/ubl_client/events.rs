@@ -1,11 +1,47 @@
-//! Event Stream (SSE)
+//! Event Stream - the ledger tail of one container
+//!
+//! `UblClient::subscribe` follows a container through `GET /ledger/tail`
+//! (SSE) and falls back to long-polling `GET /ledger/:container_id/poll`
+//! when SSE is not usable:
+//! - over a Unix socket, where responses are read whole
+//! - when the stream is refused (a proxy answering 4xx/5xx, a UBL without it)
+//! - when nothing, not even a heartbeat, arrives within `SSE_PROBE` (a proxy
+//!   buffering the stream), or it keeps being cut short
+//!
+//! Once on long-poll it stays there. Every (re)connect first catches up from
+//! the last sequence seen, so nothing is missed across reconnects, lagged
+//! streams or the switch. Events are `entry.v1` and always carry
+//! `container_id`, `sequence` and `entry_hash`; long-polled ones lack the SSE
+//! envelope's `intent_class`, `event_type` and reconciliation ids.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio::sync::mpsc;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::transport::{SendVia, Transport};
+use super::UblClient;
+use crate::{OfficeError, Result};
 
-use crate::Result;
+/// Event name of tail entries
+pub const ENTRY_EVENT: &str = "entry.v1";
 
-/// An event from the SSE stream
+/// Longest wait for the first bytes of an SSE stream (UBL heartbeats every 15s)
+const SSE_PROBE: Duration = Duration::from_secs(20);
+/// Longest silence on an open SSE stream before reconnecting
+const SSE_READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// Upper bound on one SSE response (UBL closes idle streams after 10 minutes)
+const SSE_MAX_LIFETIME: Duration = Duration::from_secs(3600);
+/// Streams cut short this many times in a row count as unusable
+const SSE_MAX_SHORT_LIVED: u32 = 3;
+/// How long a long poll asks UBL to hold the request
+const POLL_WAIT: Duration = Duration::from_secs(25);
+/// Pause before reconnecting after an error
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// An event from the ledger tail
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamEvent {
     /// Event type
@@ -14,31 +50,37 @@ pub struct StreamEvent {
     pub data: serde_json::Value,
 }
 
+/// How an `EventStream` reaches the tail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TailTransport {
+    /// SSE, falling back to long-poll when it does not work
+    Auto,
+    /// SSE only (reconnects, never falls back)
+    Sse,
+    /// Long-poll only
+    LongPoll,
+}
+
 /// Event stream for real-time ledger updates
 pub struct EventStream {
     receiver: mpsc::Receiver<StreamEvent>,
+    long_polling: Arc<AtomicBool>,
     _handle: tokio::task::JoinHandle<()>,
 }
 
 impl EventStream {
-    /// Connect to an SSE endpoint
-    pub async fn connect(url: &str) -> Result<Self> {
+    /// Follow `container_id` from after `cursor` (a sequence)
+    pub(crate) fn spawn(client: UblClient, container_id: String, cursor: u64, transport: TailTransport) -> Self {
         let (tx, rx) = mpsc::channel(100);
-        let url = url.to_string();
+        let long_polling = Arc::new(AtomicBool::new(false));
+        let tail = Tail { client, container_id, cursor, tx, long_polling: long_polling.clone() };
+        let handle = tokio::spawn(tail.follow(transport));
 
-        let handle = tokio::spawn(async move {
-            // In a real implementation, this would use an SSE client
-            // For now, we just keep the channel open
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-                // Would receive and forward SSE events here
-            }
-        });
-
-        Ok(Self {
+        Self {
             receiver: rx,
+            long_polling,
             _handle: handle,
-        })
+        }
     }
 
     /// Receive the next event
@@ -50,4 +92,285 @@ impl EventStream {
     pub fn try_next(&mut self) -> Option<StreamEvent> {
         self.receiver.try_recv().ok()
     }
+
+    /// Whether the stream is long-polling (chosen, or fallen back to)
+    pub fn is_long_polling(&self) -> bool {
+        self.long_polling.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self._handle.abort();
+    }
+}
+
+/// Why an SSE connection ended
+enum SseEnd {
+    /// Closed by UBL (idle, lagged) or cut; connect again
+    Reconnect,
+    /// Refused or buffered; long-poll instead
+    Unusable(String),
+    /// The `EventStream` was dropped
+    Closed,
+}
+
+/// Page of `GET /ledger/:container_id/poll`
+#[derive(Debug, Deserialize)]
+struct PollPage {
+    entries: Vec<serde_json::Value>,
+    cursor: u64,
+}
+
+/// State of the task behind an `EventStream`
+struct Tail {
+    client: UblClient,
+    container_id: String,
+    /// Last sequence delivered
+    cursor: u64,
+    tx: mpsc::Sender<StreamEvent>,
+    long_polling: Arc<AtomicBool>,
+}
+
+impl Tail {
+    async fn follow(mut self, transport: TailTransport) {
+        let mut sse = match transport {
+            TailTransport::Auto => matches!(self.client.transport, Transport::Tcp),
+            TailTransport::Sse => true,
+            TailTransport::LongPoll => false,
+        };
+        let mut short_lived = 0;
+        loop {
+            if !sse {
+                self.long_polling.store(true, Ordering::Relaxed);
+                match self.poll(self.poll_wait()).await {
+                    Ok(true) => {}
+                    Ok(false) => return,
+                    Err(e) => {
+                        tracing::warn!("UBL tail poll for {} failed: {}", self.container_id, e);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+                continue;
+            }
+
+            let started = Instant::now();
+            let end = self.follow_sse().await;
+            if started.elapsed() < SSE_PROBE {
+                short_lived += 1;
+            } else {
+                short_lived = 0;
+            }
+            let end = match end {
+                SseEnd::Reconnect if short_lived >= SSE_MAX_SHORT_LIVED => {
+                    SseEnd::Unusable(format!("cut short {} times in a row", short_lived))
+                }
+                end => end,
+            };
+            match end {
+                SseEnd::Closed => return,
+                SseEnd::Unusable(reason) if transport == TailTransport::Auto => {
+                    tracing::warn!("UBL tail SSE unusable for {} ({}); long-polling", self.container_id, reason);
+                    sse = false;
+                }
+                SseEnd::Reconnect | SseEnd::Unusable(_) => tokio::time::sleep(RECONNECT_DELAY).await,
+            }
+        }
+    }
+
+    /// Longest hold that still fits the client's request timeout
+    fn poll_wait(&self) -> Duration {
+        POLL_WAIT.min(self.client.timeout.saturating_sub(Duration::from_secs(5)))
+    }
+
+    /// One long poll; false once the `EventStream` is gone
+    async fn poll(&mut self, wait: Duration) -> Result<bool> {
+        let path = format!(
+            "/ledger/{}/poll?cursor={}&wait_ms={}",
+            urlencoding::encode(&self.container_id),
+            self.cursor,
+            wait.as_millis()
+        );
+        let resp = self
+            .client
+            .signed_get(&path)?
+            .timeout(wait + self.client.timeout)
+            .send_via(&self.client.transport)
+            .await
+            .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
+        if !resp.status().is_success() {
+            return Err(super::rejection(resp).await);
+        }
+        let page: PollPage = resp
+            .json()
+            .await
+            .map_err(|e| OfficeError::UblError(format!("Parse failed: {}", e)))?;
+
+        for entry in page.entries {
+            if !self.deliver(entry).await {
+                return Ok(false);
+            }
+        }
+        self.cursor = self.cursor.max(page.cursor);
+        Ok(!self.tx.is_closed())
+    }
+
+    /// Forward an entry past the cursor; false once the `EventStream` is gone
+    async fn deliver(&mut self, data: serde_json::Value) -> bool {
+        let sequence = data.get("sequence").and_then(|s| s.as_u64()).unwrap_or(0);
+        if sequence <= self.cursor {
+            return !self.tx.is_closed();
+        }
+        self.cursor = sequence;
+        let event = StreamEvent { event_type: ENTRY_EVENT.to_string(), data };
+        self.tx.send(event).await.is_ok()
+    }
+
+    async fn follow_sse(&mut self) -> SseEnd {
+        let request = match self.client.signed_get("/ledger/tail") {
+            Ok(request) => request.timeout(SSE_MAX_LIFETIME),
+            Err(e) => return SseEnd::Unusable(e.to_string()),
+        };
+        let mut resp = match request.send_via(&self.client.transport).await {
+            Ok(resp) => resp,
+            Err(e) => return SseEnd::Unusable(e.to_string()),
+        };
+        let is_stream = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if !resp.status().is_success() || !is_stream {
+            return SseEnd::Unusable(format!("HTTP {}", resp.status()));
+        }
+
+        // Subscribed: catch up on what was appended since the cursor
+        loop {
+            let before = self.cursor;
+            match self.poll(Duration::ZERO).await {
+                Ok(true) if self.cursor > before => continue,
+                Ok(true) => break,
+                Ok(false) => return SseEnd::Closed,
+                Err(e) => {
+                    tracing::warn!("UBL tail catch-up for {} failed: {}", self.container_id, e);
+                    return SseEnd::Reconnect;
+                }
+            }
+        }
+
+        let mut buffer = String::new();
+        let mut read_timeout = SSE_PROBE;
+        loop {
+            let chunk = match tokio::time::timeout(read_timeout, resp.chunk()).await {
+                Ok(Ok(Some(chunk))) => chunk,
+                Ok(Ok(None)) | Ok(Err(_)) => return SseEnd::Reconnect,
+                Err(_) if read_timeout == SSE_PROBE => {
+                    return SseEnd::Unusable(format!("no bytes within {:?}", SSE_PROBE));
+                }
+                Err(_) => return SseEnd::Reconnect,
+            };
+            read_timeout = SSE_READ_TIMEOUT;
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            for (event, data) in drain_events(&mut buffer) {
+                match event.as_str() {
+                    ENTRY_EVENT => {
+                        let Ok(data) = serde_json::from_str::<serde_json::Value>(&data) else {
+                            continue;
+                        };
+                        if data.get("container_id").and_then(|c| c.as_str()) != Some(self.container_id.as_str()) {
+                            continue;
+                        }
+                        if !self.deliver(data).await {
+                            return SseEnd::Closed;
+                        }
+                    }
+                    // Terminal events; the next connect catches up
+                    "idle" | "lagged" => return SseEnd::Reconnect,
+                    _ => {}
+                }
+            }
+            if self.tx.is_closed() {
+                return SseEnd::Closed;
+            }
+        }
+    }
+}
+
+/// Complete SSE events at the front of `buffer` as `(event, data)`, removed
+/// from it; comments (heartbeats) are dropped
+fn drain_events(buffer: &mut String) -> Vec<(String, String)> {
+    let mut events = Vec::new();
+    while let Some(end) = buffer.find("\n\n") {
+        let block: String = buffer.drain(..end + 2).collect();
+        let mut event = String::from("message");
+        let mut data: Vec<&str> = Vec::new();
+        for line in block.lines() {
+            if let Some(name) = line.strip_prefix("event:") {
+                event = name.trim().to_string();
+            } else if let Some(value) = line.strip_prefix("data:") {
+                data.push(value.strip_prefix(' ').unwrap_or(value));
+            }
+        }
+        if !data.is_empty() {
+            events.push((event, data.join("\n")));
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_events_keeps_partial_tail() {
+        let mut buffer = String::from(
+            ":heartbeat\n\nevent: entry.v1\ndata: {\"sequence\":1}\n\nevent: idle\ndata: {}\n\nevent: entry.v1\ndata: {\"seq",
+        );
+        let events = drain_events(&mut buffer);
+        assert_eq!(
+            events,
+            vec![
+                ("entry.v1".to_string(), "{\"sequence\":1}".to_string()),
+                ("idle".to_string(), "{}".to_string()),
+            ]
+        );
+        assert_eq!(buffer, "event: entry.v1\ndata: {\"seq");
+    }
+
+    #[tokio::test]
+    async fn test_auto_falls_back_to_long_poll() {
+        use axum::{extract::Query, routing::get, Json, Router};
+        use std::collections::HashMap;
+
+        // A UBL whose tail is blocked (as by a proxy) but whose poll works
+        let app = Router::new()
+            .route("/ledger/tail", get(|| async { (axum::http::StatusCode::BAD_GATEWAY, "blocked") }))
+            .route(
+                "/ledger/:container_id/poll",
+                get(|Query(q): Query<HashMap<String, String>>| async move {
+                    let cursor: u64 = q["cursor"].parse().unwrap();
+                    let entries: Vec<_> = (cursor + 1..=2)
+                        .map(|sequence| serde_json::json!({ "container_id": "C.Office", "sequence": sequence, "entry_hash": "h" }))
+                        .collect();
+                    if entries.is_empty() {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                    }
+                    Json(serde_json::json!({ "container_id": "C.Office", "entries": entries, "cursor": cursor.max(2) }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = UblClient::with_generated_key(&endpoint, "office", 10_000);
+        let mut stream = EventStream::spawn(client, "C.Office".into(), 0, TailTransport::Auto);
+        for expected in 1..=2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), stream.next()).await.unwrap().unwrap();
+            assert_eq!(event.event_type, ENTRY_EVENT);
+            assert_eq!(event.data["sequence"], expected);
+        }
+        assert!(stream.is_long_polling());
+    }
 }
@@ -42,3 +42,28 @@ pub struct LedgerEvent {
     /// Author public key
     pub author_pubkey: String,
 }
+
+/// An entry returned by `POST /sync`, with its atom when stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncEntry {
+    pub sequence: u64,
+    pub entry_hash: String,
+    /// Commit time (unix milliseconds)
+    pub ts_unix_ms: i64,
+    #[serde(default)]
+    pub atom: Option<serde_json::Value>,
+}
+
+/// One page of `POST /sync`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncPage {
+    /// New entries per container, in sequence order
+    #[serde(default)]
+    pub entries: std::collections::BTreeMap<String, Vec<SyncEntry>>,
+    /// Last sequence returned per container
+    #[serde(default)]
+    pub cursors: std::collections::BTreeMap<String, u64>,
+    /// More entries remain; call again with `cursors`
+    #[serde(default)]
+    pub has_more: bool,
+}
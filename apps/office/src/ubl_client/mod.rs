@@ -7,7 +7,10 @@
 //! - **Ledger Operations**: Commit atoms with Ed25519 signatures
 //! - **ASC Validation**: Validate Authorization Scope Certificates (Phase 3)
 //! - **Session Validation**: Validate session tokens via /id/whoami (Phase 6)
-//! - **Event Streaming**: Subscribe to ledger events via SSE
+//! - **Event Streaming**: Follow a container's tail via SSE, or long-poll
+//!   where SSE is blocked (chosen automatically, see `events`)
+//! - **Unix Sockets**: `unix:///run/ubl/ubl.sock` as endpoint reaches a
+//!   co-located UBL (`UBL_LISTEN_UNIX`) without TCP
 //!
 //! ## Usage
 //!
@@ -29,13 +32,15 @@ mod receipts;
 mod events;
 mod trust;
 mod identity_events;
+mod transport;
 
-pub use ledger::{LedgerState, LedgerEvent};
+pub use ledger::{LedgerState, LedgerEvent, SyncEntry, SyncPage};
 pub use affordances::{UblAffordance, UblObligation};
 pub use receipts::Receipt;
-pub use events::EventStream;
+pub use events::{EventStream, StreamEvent, TailTransport};
 pub use trust::{TrustLevel, PolicyChain};
 pub use identity_events::{IdentityEvent, IdentityEventKind, IDENTITY_CONTAINER};
+pub use transport::{unix_socket_path, Transport, TransportError};
 
 use std::time::Duration;
 
@@ -43,12 +48,18 @@ use chrono::{DateTime, Utc};
 use ed25519_dalek::SigningKey;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use ubl_errors::ApiErrorBody;
+pub use ubl_errors::ErrorCode;
 
 use crate::entity::EntityId;
+use transport::SendVia;
+use crate::middleware::service_auth;
+use crate::observability;
 use crate::session::Handover;
 use crate::{OfficeError, Result};
 
 /// UBL Client for ledger operations with Ed25519 signing
+#[derive(Clone)]
 pub struct UblClient {
     endpoint: String,
     container_id: String,
@@ -56,6 +67,10 @@ pub struct UblClient {
     timeout: Duration,
     signing_key: SigningKey,
     pubkey_hex: String,
+    /// Signs calls to UBL's internal routes (see `middleware::service_auth`)
+    service_key: SigningKey,
+    /// TCP, or the Unix socket of a co-located UBL
+    transport: Transport,
 }
 
 impl UblClient {
@@ -70,17 +85,47 @@ impl UblClient {
             });
 
         let pubkey_hex = hex::encode(signing_key.verifying_key().as_bytes());
+        let (endpoint, transport) = Transport::for_endpoint(endpoint, Duration::from_millis(timeout_ms));
 
         Self {
-            endpoint: endpoint.to_string(),
+            endpoint,
             container_id: container_id.to_string(),
             client,
             timeout: Duration::from_millis(timeout_ms),
+            service_key: signing_key.clone(),
             signing_key,
             pubkey_hex,
+            transport,
         }
     }
 
+    /// Create from configuration: generated signing key, the configured
+    /// service key and, if set, a client certificate for mTLS
+    pub fn from_config(config: &crate::UblConfig) -> Result<Self> {
+        let tls_error = |e: String| OfficeError::ConfigError(format!("ubl TLS: {}", e));
+        let read = |path: &str| std::fs::read(path).map_err(|e| tls_error(format!("{}: {}", path, e)));
+
+        let mut builder = Client::builder().timeout(Duration::from_millis(config.timeout_ms));
+        if let (Some(cert), Some(key)) = (&config.tls_client_cert, &config.tls_client_key) {
+            let mut pem = read(cert)?;
+            pem.extend(read(key)?);
+            builder = builder.identity(reqwest::Identity::from_pem(&pem).map_err(|e| tls_error(e.to_string()))?);
+        }
+        if let Some(ca) = &config.tls_ca_cert {
+            let ca = reqwest::Certificate::from_pem(&read(ca)?).map_err(|e| tls_error(e.to_string()))?;
+            builder = builder.add_root_certificate(ca);
+        }
+        let client = builder.build().map_err(|e| tls_error(e.to_string()))?;
+
+        let mut ubl = Self::with_generated_key(&config.endpoint, &config.container_id, config.timeout_ms);
+        ubl.client = client;
+        if !config.service_key.is_empty() {
+            ubl.service_key = ubl_kernel::signing_key_from_hex(config.service_key.expose().as_bytes())
+                .map_err(|e| OfficeError::ConfigError(format!("ubl.service_key: {}", e)))?;
+        }
+        Ok(ubl)
+    }
+
     /// Create with a generated keypair (for testing/development)
     pub fn with_generated_key(endpoint: &str, container_id: &str, timeout_ms: u64) -> Self {
         let signing_key = SigningKey::generate(&mut rand::thread_rng());
@@ -99,10 +144,52 @@ impl UblClient {
         hex::encode(signature.to_bytes())
     }
 
+    /// Public key (hex) UBL must trust for this office's service calls
+    /// (`UBL_OFFICE_PUBKEYS`)
+    pub fn service_pubkey_hex(&self) -> String {
+        hex::encode(self.service_key.verifying_key().as_bytes())
+    }
+
+    /// POST a JSON body to an internal UBL route, signed as this office
+    fn signed_post<T: Serialize>(&self, path: &str, body: &T) -> Result<reqwest::RequestBuilder> {
+        self.signed_request(reqwest::Method::POST, path, serde_json::to_vec(body)?, "application/json")
+    }
+
+    /// GET an internal UBL route, signed as this office
+    fn signed_get(&self, path: &str) -> Result<reqwest::RequestBuilder> {
+        self.signed_request(reqwest::Method::GET, path, Vec::new(), "application/json")
+    }
+
+    /// Send a raw body to an internal UBL route, signed as this office
+    fn signed_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let url = reqwest::Url::parse(&format!("{}{}", self.endpoint, path))
+            .map_err(|e| OfficeError::UblError(format!("Invalid UBL URL: {}", e)))?;
+        // UBL sees the full path and query, including any prefix in the endpoint
+        let signed_path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let timestamp_ms = Utc::now().timestamp_millis();
+        let headers = service_auth::sign_request(&self.service_key, &self.container_id, method.as_str(), &signed_path, timestamp_ms, &body);
+        let mut request = self.client.request(method, url)
+            .headers(observability::trace_headers())
+            .header(reqwest::header::CONTENT_TYPE, content_type);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        Ok(request.body(body))
+    }
+
     /// Health check
     pub async fn health(&self) -> Result<bool> {
         let url = format!("{}/health", self.endpoint);
-        match self.client.get(&url).send().await {
+        match self.client.get(&url).send_via(&self.transport).await {
             Ok(resp) => Ok(resp.status().is_success()),
             Err(_) => Ok(false),
         }
@@ -113,7 +200,7 @@ impl UblClient {
         let url = format!("{}/state/{}", self.endpoint, entity_id);
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -135,19 +222,27 @@ impl UblClient {
     /// Get recent events for an entity from C.Office audit log projection
     /// NOTE: Uses the new /query/office/audit endpoint instead of /ledger/:id/events
     pub async fn get_events(&self, entity_id: &EntityId, limit: usize) -> Result<Vec<LedgerEvent>> {
-        // Use the C.Office audit log projection
-        let url = format!(
-            "{}/query/office/audit?entity_id={}&limit={}",
-            self.endpoint, entity_id, limit
-        );
+        match self.query_audit(&format!("entity_id={}&limit={}", entity_id, limit)).await {
+            Err(OfficeError::UblRejected { .. }) => Ok(vec![]),
+            other => other,
+        }
+    }
+
+    /// A session's events from the C.Office audit log projection, newest first
+    pub async fn get_session_events(&self, session_id: &str, limit: usize) -> Result<Vec<LedgerEvent>> {
+        self.query_audit(&format!("session_id={}&limit={}", session_id, limit)).await
+    }
+
+    async fn query_audit(&self, query: &str) -> Result<Vec<LedgerEvent>> {
+        let url = format!("{}/query/office/audit?{}", self.endpoint, query);
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
         if !resp.status().is_success() {
-            return Ok(vec![]);
+            return Err(rejection(resp).await);
         }
 
         // Parse the response and convert to LedgerEvent format
@@ -199,7 +294,7 @@ impl UblClient {
         );
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -211,42 +306,46 @@ impl UblClient {
             .map_err(|e| OfficeError::UblError(format!("Parse failed: {}", e)))
     }
 
-    /// Get available affordances for an entity
-    /// NOTE: Affordances are derived from available actions in C.Jobs
-    /// For now returns a static list - in production, query C.Jobs/policy
-    pub async fn get_affordances(&self, entity_id: &EntityId) -> Result<Vec<UblAffordance>> {
-        // Static affordances for now - in production these would come from
-        // the entity's policy and available tools
-        Ok(vec![
-            UblAffordance {
-                id: "chat_reply".to_string(),
-                name: "Reply in Chat".to_string(),
-                description: "Send a message in the conversation".to_string(),
-                risk_score: 0.1,
-                parameters: None,
-            },
-            UblAffordance {
-                id: "propose_job".to_string(),
-                name: "Propose a Job".to_string(),
-                description: "Create a job card for user approval".to_string(),
-                risk_score: 0.3,
-                parameters: None,
-            },
-            UblAffordance {
-                id: "execute_tool".to_string(),
-                name: "Execute Tool".to_string(),
-                description: "Call an approved tool with parameters".to_string(),
-                risk_score: 0.5,
-                parameters: None,
-            },
-            UblAffordance {
-                id: "escalate".to_string(),
-                name: "Escalate to Guardian".to_string(),
-                description: "Request human oversight for complex decision".to_string(),
-                risk_score: 0.0,
-                parameters: None,
-            },
-        ])
+    /// Entries (with atoms) after the given sequence per container, via `POST /sync`
+    pub async fn sync(&self, cursors: &std::collections::BTreeMap<String, u64>) -> Result<SyncPage> {
+        let resp = self.signed_post("/sync", &serde_json::json!({ "cursors": cursors }))?
+            .send_via(&self.transport)
+            .await
+            .map_err(|e| OfficeError::UblError(format!("Sync request failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            return Err(rejection(resp).await);
+        }
+
+        resp.json().await
+            .map_err(|e| OfficeError::UblError(format!("Sync parse failed: {}", e)))
+    }
+
+    /// Scopes of the ASCs issued to an entity, via `GET /id/agents/:sid/asc`
+    ///
+    /// An entity without ASCs (or unknown to UBL) gets an empty list.
+    /// Affordances are derived from these by `context::AffordanceService`.
+    pub async fn list_asc_scopes(&self, entity_id: &EntityId) -> Result<Vec<crate::asc::AscScopes>> {
+        let resp = self.signed_get(&format!("/id/agents/{}/asc", entity_id))?
+            .send_via(&self.transport)
+            .await
+            .map_err(|e| OfficeError::UblError(format!("ASC list request failed: {}", e)))?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(vec![]);
+        }
+        if !resp.status().is_success() {
+            return Err(rejection(resp).await);
+        }
+
+        #[derive(serde::Deserialize)]
+        struct AscRow {
+            scopes: crate::asc::AscScopes,
+        }
+
+        let rows: Vec<AscRow> = resp.json().await
+            .map_err(|e| OfficeError::UblError(format!("ASC list parse failed: {}", e)))?;
+        Ok(rows.into_iter().map(|r| r.scopes).collect())
     }
 
     /// Get pending obligations for an entity
@@ -256,7 +355,7 @@ impl UblClient {
         let url = format!("{}/query/jobs?assigned_to={}&status=pending", self.endpoint, entity_id);
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -305,7 +404,7 @@ impl UblClient {
         let url = format!("{}/query/office/entities/{}/handovers/latest", self.endpoint, entity_id);
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -350,7 +449,7 @@ impl UblClient {
         );
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -367,7 +466,7 @@ impl UblClient {
         let url = format!("{}/guardians/{}", self.endpoint, guardian_id);
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -379,12 +478,37 @@ impl UblClient {
             .map_err(|e| OfficeError::UblError(format!("Parse failed: {}", e)))
     }
 
+    /// Feature flags of a container, from its manifest; flags that are
+    /// absent are off
+    pub async fn get_feature_flags(&self, container_id: &str) -> Result<std::collections::BTreeMap<String, bool>> {
+        #[derive(Deserialize)]
+        struct Manifest {
+            #[serde(default)]
+            feature_flags: std::collections::BTreeMap<String, bool>,
+        }
+
+        let url = format!("{}/v1/containers/{}/manifest", self.endpoint, container_id);
+
+        let resp = self.client.get(&url)
+            .send_via(&self.transport)
+            .await
+            .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            return Err(OfficeError::UblError(format!("Manifest unavailable: {}", resp.status())));
+        }
+
+        let manifest: Manifest = resp.json().await
+            .map_err(|e| OfficeError::UblError(format!("Parse failed: {}", e)))?;
+        Ok(manifest.feature_flags)
+    }
+
     /// Get resolved issues
     pub async fn get_resolved_issues(&self, entity_id: &EntityId) -> Result<Vec<ResolvedIssue>> {
         let url = format!("{}/entities/{}/issues?status=resolved", self.endpoint, entity_id);
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -404,7 +528,7 @@ impl UblClient {
         );
 
         let resp = self.client.get(&url)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
@@ -418,27 +542,41 @@ impl UblClient {
 
     /// Commit a link to the ledger
     pub async fn commit(&self, link: LinkCommit) -> Result<CommitResponse> {
+        let started = std::time::Instant::now();
+        let result = self.post_commit(&link).await;
+        observability::inc(&observability::UBL_COMMITS, &[if result.is_ok() { "success" } else { "error" }]);
+        observability::observe(&observability::UBL_COMMIT_LATENCY, &[&link.container_id], started.elapsed().as_secs_f64());
+        result
+    }
+
+    async fn post_commit(&self, link: &LinkCommit) -> Result<CommitResponse> {
         let url = format!("{}/link/commit", self.endpoint);
 
         let resp = self.client.post(&url)
-            .json(&link)
-            .send()
+            .headers(observability::trace_headers())
+            .json(link)
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Request failed: {}", e)))?;
 
         if !resp.status().is_success() {
-            let error_text = resp.text().await.unwrap_or_default();
-            return Err(OfficeError::UblError(format!("Commit failed: {}", error_text)));
+            return Err(rejection(resp).await);
         }
 
         resp.json().await
             .map_err(|e| OfficeError::UblError(format!("Parse failed: {}", e)))
     }
 
-    /// Subscribe to event stream (SSE)
+    /// Follow a container's new entries (SSE, or long-poll where SSE is blocked)
     pub async fn subscribe(&self, entity_id: &EntityId) -> Result<EventStream> {
-        let url = format!("{}/ledger/{}/tail", self.endpoint, entity_id);
-        EventStream::connect(&url).await
+        self.subscribe_via(entity_id, TailTransport::Auto).await
+    }
+
+    /// Follow a container's new entries over a given transport
+    pub async fn subscribe_via(&self, entity_id: &EntityId, transport: TailTransport) -> Result<EventStream> {
+        let container_id = entity_id.to_string();
+        let head = self.get_state(&container_id).await?;
+        Ok(EventStream::spawn(self.clone(), container_id, head.sequence, transport))
     }
 
     /// Commit an atom with proper signing
@@ -503,16 +641,30 @@ impl UblClient {
     /// Request a permit from UBL (v1.1 endpoint)
     ///
     /// This is the canonical way to authorize mutations.
-    /// Office MUST call this before any mutation.
+    /// Office MUST call this before any mutation. The returned permit is
+    /// unverified; `PermitMiddleware` checks it before acting.
     pub async fn request_permit(
         &self,
+        office_id: &str,
         request: &crate::middleware::PermitRequest,
     ) -> Result<crate::middleware::PermitResponse> {
-        let url = format!("{}/v1/policy/permit", self.endpoint);
+        // UBL's wire shape: the job is the action, the rest of the request is the plan
+        let body = serde_json::json!({
+            "office": office_id,
+            "action": request.job_type,
+            "target": request.target,
+            "args": request.params,
+            "plan": {
+                "tenant_id": request.tenant_id,
+                "actor_id": request.actor_id,
+                "intent": request.intent,
+                "context": request.context,
+                "approval_ref": request.approval_ref,
+            },
+        });
 
-        let resp = self.client.post(&url)
-            .json(&request)
-            .send()
+        let resp = self.signed_post("/v1/policy/permit", &body)?
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Permit request failed: {}", e)))?;
 
@@ -522,7 +674,7 @@ impl UblClient {
             if let Ok(denial) = serde_json::from_str::<crate::middleware::PermitResponse>(&error_text) {
                 return Ok(denial);
             }
-            return Err(OfficeError::UblError(format!("Permit request failed: {}", error_text)));
+            return Err(rejection_from_text(&error_text));
         }
 
         resp.json().await
@@ -534,10 +686,8 @@ impl UblClient {
     /// Phase 3: Office calls UBL Kernel HTTP instead of direct DB access.
     /// This is the canonical way to validate ASC tokens.
     pub async fn validate_asc(&self, asc_id: &str) -> Result<AscValidation> {
-        let url = format!("{}/id/asc/{}/validate", self.endpoint, asc_id);
-
-        let resp = self.client.get(&url)
-            .send()
+        let resp = self.signed_get(&format!("/id/asc/{}/validate", asc_id))?
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("ASC validation request failed: {}", e)))?;
 
@@ -574,7 +724,7 @@ impl UblClient {
 
         let resp = self.client.get(&url)
             .header("Authorization", format!("Bearer {}", session_token))
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Session validation request failed: {}", e)))?;
 
@@ -598,17 +748,29 @@ impl UblClient {
 
     /// Issue a command to the UBL (v1.1 endpoint)
     pub async fn issue_command(&self, command: &CommandEnvelope) -> Result<()> {
-        let url = format!("{}/v1/commands/issue", self.endpoint);
-
-        let resp = self.client.post(&url)
-            .json(&command)
-            .send()
+        let resp = self.signed_post("/v1/commands/issue", command)?
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Command issue failed: {}", e)))?;
 
         if !resp.status().is_success() {
-            let error_text = resp.text().await.unwrap_or_default();
-            return Err(OfficeError::UblError(format!("Command issue failed: {}", error_text)));
+            return Err(rejection(resp).await);
+        }
+
+        Ok(())
+    }
+
+    /// Store bytes in UBL's content-addressed blob store under their
+    /// `blake3:<hex>` hash (which UBL re-checks)
+    pub async fn put_blob(&self, content_hash: &str, bytes: Vec<u8>) -> Result<()> {
+        let path = format!("/v1/blobs/{}", content_hash);
+        let resp = self.signed_request(reqwest::Method::PUT, &path, bytes, "application/octet-stream")?
+            .send_via(&self.transport)
+            .await
+            .map_err(|e| OfficeError::UblError(format!("Blob upload failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            return Err(rejection(resp).await);
         }
 
         Ok(())
@@ -619,8 +781,9 @@ impl UblClient {
         let url = format!("{}/v1/exec.finish", self.endpoint);
 
         let resp = self.client.post(&url)
+            .headers(observability::trace_headers())
             .json(&receipt)
-            .send()
+            .send_via(&self.transport)
             .await
             .map_err(|e| OfficeError::UblError(format!("Receipt submit failed: {}", e)))?;
 
@@ -647,6 +810,30 @@ impl UblClient {
     }
 }
 
+/// Turn a non-success UBL response into a typed error
+async fn rejection(resp: reqwest::Response) -> OfficeError {
+    let status = resp.status();
+    let text = resp.text().await.unwrap_or_default();
+    let err = if text.is_empty() {
+        OfficeError::UblRejected {
+            code: ErrorCode::Unknown,
+            message: format!("HTTP {}", status),
+        }
+    } else {
+        rejection_from_text(&text)
+    };
+    if let Some(code) = err.ubl_code() {
+        crate::observability::inc(&crate::observability::UBL_REJECTIONS, &[code.as_str()]);
+    }
+    err
+}
+
+/// Decode a `{"error", "message"}` body (or a legacy plain-text error)
+fn rejection_from_text(text: &str) -> OfficeError {
+    let body = ApiErrorBody::parse(text);
+    OfficeError::UblRejected { code: body.error, message: body.message }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct HandoverResponse {
     content: String,
@@ -796,4 +983,15 @@ mod tests {
         let signature = client.sign(data);
         assert_eq!(signature.len(), 128); // 64 bytes = 128 hex chars
     }
+
+    #[test]
+    fn test_rejection_maps_codes() {
+        let err = rejection_from_text(r#"{"error":"REALITY_DRIFT","message":"RealityDrift"}"#);
+        assert!(matches!(err, OfficeError::UblRejected { code: ErrorCode::RealityDrift, .. }));
+        assert_eq!(err.ubl_code(), Some(ErrorCode::RealityDrift));
+
+        // Older kernels answer with plain text
+        let err = rejection_from_text("SequenceMismatch");
+        assert_eq!(err.ubl_code(), Some(ErrorCode::SequenceMismatch));
+    }
 }
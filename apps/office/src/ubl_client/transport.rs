@@ -0,0 +1,155 @@
+//! Transport - TCP or Unix socket
+//!
+//! `ubl.endpoint` is either an `http(s)://` URL or `unix:///path/to/ubl.sock`
+//! for a UBL co-located with Office (`UBL_LISTEN_UNIX`). Requests are always
+//! built with reqwest against a base URL; over a socket they are sent through
+//! a hyperlocal connector instead, and the response is handed back as a
+//! regular `reqwest::Response`, so callers do not care which one is in use.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use hyper::{Body, Client};
+use hyperlocal::{UnixConnector, Uri as UnixUri};
+use reqwest::{RequestBuilder, Response};
+use thiserror::Error;
+
+/// Authority requests are built against when talking over a socket
+const UNIX_BASE: &str = "http://localhost";
+
+/// Why a request could not be sent
+#[derive(Debug, Error)]
+pub enum TransportError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[error("unix socket {socket}: {reason}")]
+    Unix { socket: String, reason: String },
+}
+
+/// How requests reach UBL
+#[derive(Clone)]
+pub enum Transport {
+    Tcp,
+    Unix {
+        socket: PathBuf,
+        client: Client<UnixConnector, Body>,
+        timeout: Duration,
+    },
+}
+
+impl Transport {
+    /// Split an endpoint into the base URL requests are built against and
+    /// the transport that sends them
+    pub fn for_endpoint(endpoint: &str, timeout: Duration) -> (String, Self) {
+        match unix_socket_path(endpoint) {
+            Some(socket) => (
+                UNIX_BASE.to_string(),
+                Transport::Unix {
+                    socket: PathBuf::from(socket),
+                    client: Client::builder().build(UnixConnector),
+                    timeout,
+                },
+            ),
+            None => (endpoint.trim_end_matches('/').to_string(), Transport::Tcp),
+        }
+    }
+
+    /// Send a request built with the client's reqwest builder
+    pub async fn send(&self, builder: RequestBuilder) -> Result<Response, TransportError> {
+        let (socket, client, timeout) = match self {
+            Transport::Tcp => return Ok(builder.send().await?),
+            Transport::Unix { socket, client, timeout } => (socket, client, *timeout),
+        };
+        let unix_error = |reason: String| TransportError::Unix {
+            socket: socket.display().to_string(),
+            reason,
+        };
+
+        let request = builder.build()?;
+        let path = match request.url().query() {
+            Some(query) => format!("{}?{}", request.url().path(), query),
+            None => request.url().path().to_string(),
+        };
+        let body = request
+            .body()
+            .map(|b| b.as_bytes().map(<[u8]>::to_vec).ok_or_else(|| unix_error("streaming bodies are not supported".into())))
+            .transpose()?
+            .unwrap_or_default();
+
+        let mut hyper_request = hyper::Request::builder()
+            .method(request.method().clone())
+            .uri(UnixUri::new(socket, &path))
+            .body(Body::from(body))
+            .map_err(|e| unix_error(e.to_string()))?;
+        *hyper_request.headers_mut() = request.headers().clone();
+
+        let exchange = async {
+            let response = client.request(hyper_request).await.map_err(|e| unix_error(e.to_string()))?;
+            let (parts, body) = response.into_parts();
+            let bytes = hyper::body::to_bytes(body).await.map_err(|e| unix_error(e.to_string()))?;
+            Ok(Response::from(hyper::Response::from_parts(parts, bytes.to_vec())))
+        };
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .map_err(|_| unix_error(format!("timed out after {:?}", timeout)))?
+    }
+}
+
+/// `builder.send_via(&transport)` in place of `builder.send()`
+pub(crate) trait SendVia {
+    async fn send_via(self, transport: &Transport) -> Result<Response, TransportError>;
+}
+
+impl SendVia for RequestBuilder {
+    async fn send_via(self, transport: &Transport) -> Result<Response, TransportError> {
+        transport.send(self).await
+    }
+}
+
+/// Socket path of a `unix://` endpoint
+pub fn unix_socket_path(endpoint: &str) -> Option<&str> {
+    endpoint.strip_prefix("unix://").filter(|path| path.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_selects_transport() {
+        let timeout = Duration::from_secs(1);
+        let (base, transport) = Transport::for_endpoint("http://ubl:3000/", timeout);
+        assert_eq!(base, "http://ubl:3000");
+        assert!(matches!(transport, Transport::Tcp));
+
+        let (base, transport) = Transport::for_endpoint("unix:///run/ubl/ubl.sock", timeout);
+        assert_eq!(base, UNIX_BASE);
+        assert!(matches!(transport, Transport::Unix { ref socket, .. } if socket == &PathBuf::from("/run/ubl/ubl.sock")));
+
+        assert_eq!(unix_socket_path("unix://relative.sock"), None);
+    }
+
+    #[tokio::test]
+    async fn test_request_over_unix_socket() {
+        let socket = std::env::temp_dir().join(format!("office-ubl-{}.sock", uuid::Uuid::new_v4()));
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let echo = hyper::service::service_fn(|req: hyper::Request<Body>| async move {
+                let path = req.uri().to_string();
+                let body = hyper::body::to_bytes(req.into_body()).await?;
+                let reply = format!("{} {}", path, String::from_utf8_lossy(&body));
+                Ok::<_, hyper::Error>(hyper::Response::new(Body::from(reply)))
+            });
+            let _ = hyper::server::conn::Http::new().serve_connection(stream, echo).await;
+        });
+
+        let (base, transport) = Transport::for_endpoint(&format!("unix://{}", socket.display()), Duration::from_secs(5));
+        let builder = reqwest::Client::new().post(format!("{}/link/commit?dry=1", base)).body("hi");
+        let response = transport.send(builder).await.unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(response.text().await.unwrap(), "/link/commit?dry=1 hi");
+        let _ = std::fs::remove_file(&socket);
+    }
+}
@@ -1,5 +1,7 @@
+mod transport;
 mod ubl_client;
 mod office_client;
 
+pub use transport::Transport;
 pub use ubl_client::*;
 pub use office_client::*;
\ No newline at end of file
@@ -2,23 +2,29 @@ use anyhow::Result;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+use super::transport::{SendVia, Transport};
+
 #[derive(Clone)]
 pub struct OfficeClient {
     base_url: String,
     client: Client,
+    transport: Transport,
 }
 
 impl OfficeClient {
+    /// `base_url` is `http://host:port` or `unix:///path/to/socket`
     pub fn new(base_url: String) -> Self {
+        let (base_url, transport) = Transport::for_base_url(&base_url);
         Self {
             base_url,
             client: Client::new(),
+            transport,
         }
     }
     
     pub async fn health(&self) -> Result<OfficeHealthResponse> {
         let url = format!("{}/health", self.base_url);
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
@@ -27,20 +33,20 @@ impl OfficeClient {
         let resp = self.client
             .post(&url)
             .json(&req)
-            .send()
+            .send_via(&self.transport)
             .await?;
         Ok(resp.json().await?)
     }
     
     pub async fn get_entity(&self, entity_id: &str) -> Result<EntityResponse> {
         let url = format!("{}/entities/{}", self.base_url, entity_id);
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
     pub async fn list_entities(&self) -> Result<Vec<EntityResponse>> {
         let url = format!("{}/entities", self.base_url);
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
@@ -49,7 +55,7 @@ impl OfficeClient {
         let resp = self.client
             .post(&url)
             .json(&req)
-            .send()
+            .send_via(&self.transport)
             .await?;
         Ok(resp.json().await?)
     }
@@ -66,7 +72,7 @@ impl OfficeClient {
             request = request.header("x-ubl-asc", a);
         }
         
-        let resp = request.send().await?;
+        let resp = request.send_via(&self.transport).await?;
         let status = resp.status();
         if !status.is_success() {
             let text = resp.text().await.unwrap_or_default();
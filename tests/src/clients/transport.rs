@@ -0,0 +1,83 @@
+//! TCP or Unix socket transport for the test clients
+//!
+//! A base URL of `unix:///path/to/socket` (a UBL started with
+//! `UBL_LISTEN_UNIX`, or an Office with `server.unix_socket`) sends requests
+//! through a hyperlocal connector; the response comes back as a regular
+//! `reqwest::Response`.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use hyper::{Body, Client};
+use hyperlocal::{UnixConnector, Uri as UnixUri};
+use reqwest::{RequestBuilder, Response};
+
+/// Authority requests are built against when talking over a socket
+const UNIX_BASE: &str = "http://localhost";
+
+#[derive(Clone)]
+pub enum Transport {
+    Tcp,
+    Unix {
+        socket: PathBuf,
+        client: Client<UnixConnector, Body>,
+    },
+}
+
+impl Transport {
+    /// Split a base URL into the URL requests are built against and the
+    /// transport that sends them
+    pub fn for_base_url(base_url: &str) -> (String, Self) {
+        match base_url.strip_prefix("unix://") {
+            Some(socket) => (
+                UNIX_BASE.to_string(),
+                Transport::Unix {
+                    socket: PathBuf::from(socket),
+                    client: Client::builder().build(UnixConnector),
+                },
+            ),
+            None => (base_url.trim_end_matches('/').to_string(), Transport::Tcp),
+        }
+    }
+
+    pub async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+        let (socket, client) = match self {
+            Transport::Tcp => return Ok(builder.send().await?),
+            Transport::Unix { socket, client } => (socket, client),
+        };
+
+        let request = builder.build()?;
+        let path = match request.url().query() {
+            Some(query) => format!("{}?{}", request.url().path(), query),
+            None => request.url().path().to_string(),
+        };
+        let body = match request.body() {
+            Some(body) => body
+                .as_bytes()
+                .ok_or_else(|| anyhow!("streaming bodies are not supported over a unix socket"))?
+                .to_vec(),
+            None => Vec::new(),
+        };
+
+        let mut hyper_request = hyper::Request::builder()
+            .method(request.method().clone())
+            .uri(UnixUri::new(socket, &path))
+            .body(Body::from(body))?;
+        *hyper_request.headers_mut() = request.headers().clone();
+
+        let (parts, body) = client.request(hyper_request).await?.into_parts();
+        let bytes = hyper::body::to_bytes(body).await?;
+        Ok(Response::from(hyper::Response::from_parts(parts, bytes.to_vec())))
+    }
+}
+
+/// `builder.send_via(&transport)` in place of `builder.send()`
+pub(crate) trait SendVia {
+    async fn send_via(self, transport: &Transport) -> Result<Response>;
+}
+
+impl SendVia for RequestBuilder {
+    async fn send_via(self, transport: &Transport) -> Result<Response> {
+        transport.send(self).await
+    }
+}
@@ -2,31 +2,37 @@ use anyhow::Result;
 use eventsource_client::Client as SseClient;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+
+use super::transport::{SendVia, Transport};
 use serde_json::Value;
 
 #[derive(Clone)]
 pub struct UblClient {
     base_url: String,
     client: Client,
+    transport: Transport,
 }
 
 impl UblClient {
+    /// `base_url` is `http://host:port` or `unix:///path/to/socket`
     pub fn new(base_url: String) -> Self {
+        let (base_url, transport) = Transport::for_base_url(&base_url);
         Self {
             base_url,
             client: Client::new(),
+            transport,
         }
     }
     
     pub async fn health(&self) -> Result<HealthResponse> {
         let url = format!("{}/health", self.base_url);
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
     pub async fn bootstrap(&self, tenant_id: &str) -> Result<BootstrapResponse> {
         let url = format!("{}/messenger/bootstrap?tenant_id={}", self.base_url, tenant_id);
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
@@ -35,7 +41,7 @@ impl UblClient {
         let resp = self.client
             .post(&url)
             .json(&req)
-            .send()
+            .send_via(&self.transport)
             .await?;
         Ok(resp.json().await?)
     }
@@ -45,7 +51,7 @@ impl UblClient {
         let resp = self.client
             .post(&url)
             .json(&req)
-            .send()
+            .send_via(&self.transport)
             .await?;
         Ok(resp.json().await?)
     }
@@ -59,19 +65,22 @@ impl UblClient {
         if let Some(c) = cursor {
             url.push_str(&format!("?cursor={}", c));
         }
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
     pub async fn get_job(&self, job_id: &str) -> Result<JobResponse> {
         let url = format!("{}/v1/jobs/{}", self.base_url, job_id);
-        let resp = self.client.get(&url).send().await?;
+        let resp = self.client.get(&url).send_via(&self.transport).await?;
         Ok(resp.json().await?)
     }
     
     /// Subscribe to SSE event stream
     /// Returns a client that can be used with the `eventsource_client::Client` trait
     pub fn subscribe_to_stream(&self, tenant_id: &str) -> Result<impl SseClient> {
+        if matches!(self.transport, Transport::Unix { .. }) {
+            anyhow::bail!("SSE streams are not supported over a unix socket");
+        }
         let url = format!("{}/v1/stream?tenant_id={}", self.base_url, tenant_id);
         let client = eventsource_client::ClientBuilder::for_url(&url)?
             .build();
@@ -89,6 +98,12 @@ pub struct BootstrapResponse {
     pub entities: Vec<Value>,
     pub conversations: Vec<Value>,
     pub messages: Vec<Value>,
+    #[serde(default)]
+    pub presence: Vec<Value>,
+    #[serde(default)]
+    pub pending_jobs: Vec<Value>,
+    #[serde(default)]
+    pub cursors: Value,
 }
 
 #[derive(Debug, Serialize)]
@@ -1,6 +1,7 @@
 pub mod helpers;
 pub mod clients;
 pub mod fixtures;
+pub mod scenario;
 
 pub use helpers::*;
 pub use clients::*;
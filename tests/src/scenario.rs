@@ -0,0 +1,531 @@
+//! Declarative fixture DSL
+//!
+//! A scenario file (TOML, or YAML by extension) declares tenants,
+//! identities, containers and an ordered list of events. Loading it
+//! registers each identity as an agent with an ASC, then signs and
+//! commits every event through `POST /link/commit` exactly like a real
+//! client. The returned [`LoadedScenario`] exposes sids, keys and ledger
+//! positions so tests can assert against them.
+//!
+//! Strings anywhere in the file may use placeholders:
+//! - `${run}` — unique suffix for this load, keeps reruns isolated
+//! - `${tenant.<alias>}`, `${container.<alias>}`
+//! - `${identity.<alias>.sid}`, `${identity.<alias>.pubkey}`
+//! - `${event.<name>.entry_hash|sequence|atom_hash}` — earlier events only
+
+use anyhow::{anyhow, bail, Context, Result};
+use ed25519_dalek::SigningKey;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Link format version signed by the loader (pure Ed25519)
+const LINK_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tenants: Vec<TenantSpec>,
+    #[serde(default)]
+    pub containers: Vec<ContainerSpec>,
+    #[serde(default)]
+    pub identities: Vec<IdentitySpec>,
+    #[serde(default)]
+    pub events: Vec<EventSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantSpec {
+    pub alias: String,
+    /// Defaults to `T.<alias>.${run}`
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContainerSpec {
+    pub alias: String,
+    /// Defaults to `C.<alias>.${run}`
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IdentitySpec {
+    pub alias: String,
+    /// `llm` or `app`
+    #[serde(default = "default_kind")]
+    pub kind: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Container aliases granted by the ASC; all declared containers when empty
+    #[serde(default)]
+    pub containers: Vec<String>,
+    #[serde(default = "default_intent_classes")]
+    pub intent_classes: Vec<String>,
+    #[serde(default)]
+    pub max_delta: Option<i64>,
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventSpec {
+    pub name: String,
+    /// Container alias
+    pub container: String,
+    /// Identity alias that signs and commits the event
+    #[serde(rename = "as")]
+    pub actor: String,
+    #[serde(default = "default_intent_class")]
+    pub intent_class: String,
+    #[serde(default = "default_physics_delta")]
+    pub physics_delta: String,
+    pub atom: Value,
+    #[serde(default)]
+    pub expect: Expect,
+}
+
+/// Expected outcome of committing an event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expect {
+    #[default]
+    Ok,
+    Error,
+}
+
+fn default_kind() -> String {
+    "app".to_string()
+}
+
+fn default_intent_classes() -> Vec<String> {
+    vec!["Observation".to_string()]
+}
+
+fn default_intent_class() -> String {
+    "Observation".to_string()
+}
+
+fn default_physics_delta() -> String {
+    "0".to_string()
+}
+
+fn default_ttl_secs() -> i64 {
+    3600
+}
+
+impl Scenario {
+    /// Parse a scenario file, choosing TOML or YAML by extension
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading scenario {}", path.display()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            Some("yaml") | Some("yml") => Self::from_yaml(&text),
+            other => bail!("unsupported scenario extension: {:?}", other),
+        }
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let scenario: Self = toml::from_str(text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        let scenario: Self = serde_yaml::from_str(text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Check aliases and references before touching the network
+    fn validate(&self) -> Result<()> {
+        let containers: Vec<&str> = self.containers.iter().map(|c| c.alias.as_str()).collect();
+        let identities: Vec<&str> = self.identities.iter().map(|i| i.alias.as_str()).collect();
+
+        for identity in &self.identities {
+            if identity.kind != "llm" && identity.kind != "app" {
+                bail!("identity {}: kind must be llm or app", identity.alias);
+            }
+            for c in &identity.containers {
+                if !containers.contains(&c.as_str()) {
+                    bail!("identity {}: unknown container {}", identity.alias, c);
+                }
+            }
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        for event in &self.events {
+            if seen.contains(&event.name.as_str()) {
+                bail!("duplicate event name {}", event.name);
+            }
+            if !containers.contains(&event.container.as_str()) {
+                bail!("event {}: unknown container {}", event.name, event.container);
+            }
+            if !identities.contains(&event.actor.as_str()) {
+                bail!("event {}: unknown identity {}", event.name, event.actor);
+            }
+            seen.push(event.name.as_str());
+        }
+        Ok(())
+    }
+
+    /// Register identities and commit every event against the UBL server
+    pub async fn load(&self, ubl_url: &str) -> Result<LoadedScenario> {
+        let mut loaded = LoadedScenario {
+            run: Uuid::new_v4().simple().to_string()[..12].to_string(),
+            ..Default::default()
+        };
+
+        for tenant in &self.tenants {
+            let id = match &tenant.id {
+                Some(id) => loaded.interpolate_str(id)?,
+                None => format!("T.{}.{}", tenant.alias, loaded.run),
+            };
+            loaded.tenants.insert(tenant.alias.clone(), id);
+        }
+        for container in &self.containers {
+            let id = match &container.id {
+                Some(id) => loaded.interpolate_str(id)?,
+                None => format!("C.{}.{}", container.alias, loaded.run),
+            };
+            loaded.containers.insert(container.alias.clone(), id);
+        }
+
+        let client = Client::new();
+        for spec in &self.identities {
+            let identity = register_identity(&client, ubl_url, spec, &loaded).await?;
+            println!("🔑 Fixture identity {} → {}", spec.alias, identity.sid);
+            loaded.identities.insert(spec.alias.clone(), identity);
+        }
+
+        for spec in &self.events {
+            let event = commit_event(&client, ubl_url, spec, &loaded).await?;
+            match (spec.expect, &event.error) {
+                (Expect::Ok, Some(err)) => bail!("event {} failed: {}", spec.name, err),
+                (Expect::Error, None) => bail!("event {} committed but was expected to fail", spec.name),
+                _ => {}
+            }
+            loaded.events.push(event);
+        }
+
+        Ok(loaded)
+    }
+}
+
+/// Agent registered for a fixture identity
+#[derive(Debug, Clone)]
+pub struct IdentityHandle {
+    pub sid: String,
+    pub pubkey: String,
+    pub asc_id: String,
+    pub signing_key: SigningKey,
+}
+
+/// Outcome of one fixture event
+#[derive(Debug, Clone)]
+pub struct EventHandle {
+    pub name: String,
+    pub container_id: String,
+    pub atom: Value,
+    pub atom_hash: String,
+    /// Ledger position; `None` when the commit was rejected
+    pub sequence: Option<i64>,
+    pub entry_hash: Option<String>,
+    /// Server error body for rejected commits
+    pub error: Option<String>,
+}
+
+/// Handles produced by [`Scenario::load`]
+#[derive(Debug, Clone, Default)]
+pub struct LoadedScenario {
+    pub run: String,
+    pub tenants: HashMap<String, String>,
+    pub containers: HashMap<String, String>,
+    pub identities: HashMap<String, IdentityHandle>,
+    pub events: Vec<EventHandle>,
+}
+
+impl LoadedScenario {
+    pub fn tenant(&self, alias: &str) -> &str {
+        self.tenants.get(alias).map(String::as_str).unwrap_or_else(|| panic!("no tenant {}", alias))
+    }
+
+    pub fn container(&self, alias: &str) -> &str {
+        self.containers.get(alias).map(String::as_str).unwrap_or_else(|| panic!("no container {}", alias))
+    }
+
+    pub fn identity(&self, alias: &str) -> &IdentityHandle {
+        self.identities.get(alias).unwrap_or_else(|| panic!("no identity {}", alias))
+    }
+
+    pub fn event(&self, name: &str) -> &EventHandle {
+        self.events.iter().find(|e| e.name == name).unwrap_or_else(|| panic!("no event {}", name))
+    }
+
+    /// Replace `${...}` placeholders in every string of a JSON value
+    pub fn interpolate(&self, value: &Value) -> Result<Value> {
+        Ok(match value {
+            Value::String(s) => Value::String(self.interpolate_str(s)?),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.interpolate(v)).collect::<Result<_>>()?),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| Ok((k.clone(), self.interpolate(v)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    pub fn interpolate_str(&self, input: &str) -> Result<String> {
+        let mut out = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| anyhow!("unterminated placeholder in {:?}", input))?;
+            out.push_str(&self.resolve(&rest[start + 2..start + end])?);
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    fn resolve(&self, key: &str) -> Result<String> {
+        let parts: Vec<&str> = key.split('.').collect();
+        let value = match parts.as_slice() {
+            ["run"] => Some(self.run.clone()),
+            ["tenant", alias] => self.tenants.get(*alias).cloned(),
+            ["container", alias] => self.containers.get(*alias).cloned(),
+            ["identity", alias, "sid"] => self.identities.get(*alias).map(|i| i.sid.clone()),
+            ["identity", alias, "pubkey"] => self.identities.get(*alias).map(|i| i.pubkey.clone()),
+            ["event", name, field] => self.events.iter().find(|e| e.name == *name).and_then(|e| match *field {
+                "entry_hash" => e.entry_hash.clone(),
+                "sequence" => e.sequence.map(|s| s.to_string()),
+                "atom_hash" => Some(e.atom_hash.clone()),
+                _ => None,
+            }),
+            _ => None,
+        };
+        value.ok_or_else(|| anyhow!("unresolved placeholder ${{{}}}", key))
+    }
+}
+
+async fn register_identity(
+    client: &Client,
+    ubl_url: &str,
+    spec: &IdentitySpec,
+    loaded: &LoadedScenario,
+) -> Result<IdentityHandle> {
+    let (pubkey, signing_key) = ubl_kernel::generate_keypair();
+    let display_name = match &spec.display_name {
+        Some(name) => loaded.interpolate_str(name)?,
+        None => format!("fixture {} {}", spec.alias, loaded.run),
+    };
+
+    let resp = client
+        .post(format!("{}/id/agents", ubl_url))
+        .json(&json!({
+            "kind": spec.kind,
+            "display_name": display_name,
+            "public_key": pubkey,
+        }))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        bail!("creating agent {}: {} {}", spec.alias, resp.status(), resp.text().await?);
+    }
+    let agent: Value = resp.json().await?;
+    let sid = agent["sid"].as_str().ok_or_else(|| anyhow!("agent response without sid"))?.to_string();
+
+    let containers: Vec<String> = if spec.containers.is_empty() {
+        loaded.containers.values().cloned().collect()
+    } else {
+        spec.containers.iter().map(|alias| loaded.container(alias).to_string()).collect()
+    };
+    let resp = client
+        .post(format!("{}/id/agents/{}/asc", ubl_url, sid))
+        .json(&json!({
+            "containers": containers,
+            "intent_classes": spec.intent_classes,
+            "max_delta": spec.max_delta,
+            "ttl_secs": spec.ttl_secs,
+        }))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        bail!("issuing ASC for {}: {} {}", spec.alias, resp.status(), resp.text().await?);
+    }
+    let asc: Value = resp.json().await?;
+    let asc_id = asc["asc_id"].as_str().unwrap_or_default().to_string();
+
+    Ok(IdentityHandle { sid, pubkey, asc_id, signing_key })
+}
+
+async fn commit_event(
+    client: &Client,
+    ubl_url: &str,
+    spec: &EventSpec,
+    loaded: &LoadedScenario,
+) -> Result<EventHandle> {
+    let container_id = loaded.container(&spec.container).to_string();
+    let identity = loaded.identity(&spec.actor);
+    let atom = loaded.interpolate(&spec.atom)?;
+    let atom_hash = ubl_kernel::hash_atom(&ubl_atom::canonicalize(&atom)?);
+
+    let state: Value = client
+        .get(format!("{}/state/{}", ubl_url, container_id))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let expected_sequence = state["sequence"].as_i64().unwrap_or(0) + 1;
+    let previous_hash = state["last_hash"].as_str().unwrap_or("0x00").to_string();
+
+    let signing_bytes = ubl_atom::canonicalize(&json!({
+        "version": LINK_VERSION,
+        "container_id": container_id,
+        "expected_sequence": expected_sequence,
+        "previous_hash": previous_hash,
+        "atom_hash": atom_hash,
+        "intent_class": spec.intent_class,
+        "physics_delta": spec.physics_delta,
+        "pact": Value::Null,
+    }))?;
+    let signature = ubl_kernel::sign(&identity.signing_key, &signing_bytes);
+
+    let resp = client
+        .post(format!("{}/link/commit", ubl_url))
+        .bearer_auth(&identity.sid)
+        .json(&json!({
+            "version": LINK_VERSION,
+            "container_id": container_id,
+            "expected_sequence": expected_sequence,
+            "previous_hash": previous_hash,
+            "atom_hash": atom_hash,
+            "intent_class": spec.intent_class,
+            "physics_delta": spec.physics_delta,
+            "author_pubkey": identity.pubkey,
+            "signature": signature,
+            "atom": atom,
+        }))
+        .send()
+        .await?;
+
+    let mut event = EventHandle {
+        name: spec.name.clone(),
+        container_id,
+        atom,
+        atom_hash,
+        sequence: None,
+        entry_hash: None,
+        error: None,
+    };
+    if resp.status().is_success() {
+        let body: Value = resp.json().await?;
+        event.sequence = body["entry"]["sequence"].as_i64();
+        event.entry_hash = body["entry"]["entry_hash"].as_str().map(str::to_string);
+    } else {
+        event.error = Some(format!("{} {}", resp.status(), resp.text().await?));
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+name = "sample"
+
+[[tenants]]
+alias = "acme"
+
+[[containers]]
+alias = "messenger"
+
+[[identities]]
+alias = "office"
+kind = "llm"
+
+[[events]]
+name = "created"
+container = "messenger"
+as = "office"
+atom = { type = "conversation.created", tenant_id = "${tenant.acme}" }
+"#;
+
+    #[test]
+    fn parses_toml_with_defaults() {
+        let scenario = Scenario::from_toml(SAMPLE).unwrap();
+        assert_eq!(scenario.identities[0].intent_classes, vec!["Observation"]);
+        assert_eq!(scenario.events[0].actor, "office");
+        assert_eq!(scenario.events[0].physics_delta, "0");
+        assert_eq!(scenario.events[0].expect, Expect::Ok);
+    }
+
+    #[test]
+    fn yaml_matches_toml() {
+        let yaml = r#"
+tenants: [{ alias: acme }]
+containers: [{ alias: messenger }]
+identities: [{ alias: office, kind: llm }]
+events:
+  - name: created
+    container: messenger
+    as: office
+    expect: error
+    atom: { type: conversation.created }
+"#;
+        let scenario = Scenario::from_yaml(yaml).unwrap();
+        assert_eq!(scenario.events[0].expect, Expect::Error);
+        assert_eq!(scenario.events[0].atom["type"], "conversation.created");
+    }
+
+    #[test]
+    fn rejects_unknown_references() {
+        let bad = SAMPLE.replace("\nas = \"office\"", "\nas = \"ghost\"");
+        assert!(Scenario::from_toml(&bad).is_err());
+    }
+
+    #[test]
+    fn interpolates_placeholders() {
+        let mut loaded = LoadedScenario { run: "r1".into(), ..Default::default() };
+        loaded.tenants.insert("acme".into(), "T.acme.r1".into());
+        loaded.events.push(EventHandle {
+            name: "created".into(),
+            container_id: "C.messenger.r1".into(),
+            atom: Value::Null,
+            atom_hash: "ab".into(),
+            sequence: Some(3),
+            entry_hash: Some("cd".into()),
+            error: None,
+        });
+
+        let atom = json!({
+            "tenant_id": "${tenant.acme}",
+            "refs": ["${event.created.entry_hash}@${event.created.sequence}"],
+            "n": 1,
+        });
+        assert_eq!(
+            loaded.interpolate(&atom).unwrap(),
+            json!({ "tenant_id": "T.acme.r1", "refs": ["cd@3"], "n": 1 })
+        );
+        assert!(loaded.interpolate_str("${event.missing.entry_hash}").is_err());
+        assert!(loaded.interpolate_str("${run").is_err());
+    }
+}
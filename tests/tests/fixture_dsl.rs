@@ -0,0 +1,38 @@
+//! Fixture DSL Integration Tests
+//! ═══════════════════════════════════════════════════════════════════════════
+//! Loads a declarative scenario through the real commit pipeline and
+//! asserts on the handles it returns.
+//! ═══════════════════════════════════════════════════════════════════════════
+
+use anyhow::Result;
+use integration_tests::scenario::Scenario;
+
+mod common;
+use common::*;
+
+#[tokio::test]
+async fn test_fixture_conversation_basic() -> Result<()> {
+    println!("🧪 Loading fixture conversation_basic");
+
+    let _ctx = setup_golden_run().await?;
+
+    let scenario = Scenario::from_path(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/conversation_basic.toml"))?;
+    let loaded = scenario.load("http://localhost:8080").await?;
+
+    let conversation = loaded.event("conversation");
+    let greeting = loaded.event("greeting");
+    assert_eq!(conversation.container_id, greeting.container_id);
+    assert_eq!(greeting.sequence, conversation.sequence.map(|s| s + 1));
+
+    let job = loaded.event("job");
+    assert_eq!(job.sequence, Some(1));
+    assert_eq!(job.atom["source_entry"].as_str(), greeting.entry_hash.as_deref());
+    assert_eq!(job.atom["tenant_id"], loaded.tenant("acme"));
+
+    let forged = loaded.event("job_forged");
+    assert!(forged.sequence.is_none());
+    assert!(forged.error.is_some());
+
+    println!("✅ Fixture committed {} events", loaded.events.len());
+    Ok(())
+}
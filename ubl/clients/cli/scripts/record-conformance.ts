@@ -0,0 +1,75 @@
+// Grava links assinados pela CLI como fixtures para o teste Rust da Membrane
+// (ubl-membrane/tests/ts_conformance.rs). Chave fixa => saída reprodutível.
+//
+//   npm run conformance:record
+import fs from 'node:fs';
+import * as ed from '@noble/ed25519';
+import { buildSigningBytes } from '../src/utils/signing.js';
+
+const OUT = new URL('../../../../ubl/kernel/rust/tests/fixtures/ts_commits/cli.json', import.meta.url);
+const PRIV = Buffer.from('9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60', 'hex');
+const CLASSES = ['Observation', 'Conservation', 'Entropy', 'Evolution'];
+
+const I128_MAX = (1n << 127n) - 1n;
+const I128_MIN = -(1n << 127n);
+
+type Case = {
+  name: string;
+  container: string;
+  sequence: bigint;
+  prev: string;
+  atom: string;
+  cls: number;
+  delta: bigint;
+  pact?: { pact_id: string; signatures: string[] };
+  balance?: bigint;
+};
+
+const CASES: Case[] = [
+  { name: 'observation', container: 'aa'.repeat(32), sequence: 1n, prev: '00'.repeat(32), atom: 'cc'.repeat(32), cls: 0, delta: 0n },
+  { name: 'conservation_i128_max', container: 'aa'.repeat(32), sequence: 7n, prev: 'bb'.repeat(32), atom: 'dd'.repeat(32), cls: 1, delta: I128_MAX, balance: 0n },
+  {
+    name: 'entropy_i128_min_with_pact', container: 'wallet', sequence: 1_000_000n, prev: 'ee'.repeat(32), atom: 'ff'.repeat(32),
+    cls: 2, delta: I128_MIN, pact: { pact_id: 'pact_mint', signatures: ['ab'.repeat(64)] },
+  },
+  {
+    name: 'evolution_with_pact', container: 'C.Policy', sequence: 3n, prev: '12'.repeat(32), atom: '34'.repeat(32),
+    cls: 3, delta: 0n, pact: { pact_id: 'pact_policy_v2', signatures: ['cd'.repeat(64), 'ef'.repeat(64)] },
+  },
+];
+
+const fixtures = [];
+for (const c of CASES) {
+  const sb = buildSigningBytes({
+    version: 1,
+    containerHex32: c.container,
+    expectedSequence: c.sequence,
+    previousHashHex32: c.prev,
+    atomHashHex32: c.atom,
+    intentClass: c.cls,
+    physicsDelta: c.delta,
+  });
+  const sig = await ed.signAsync(sb, PRIV);
+  fixtures.push({
+    name: `cli/${c.name}`,
+    producer: 'ubl/clients/cli buildSigningBytes',
+    pipeline: 'membrane',
+    signing_bytes_hex: Buffer.from(sb).toString('hex'),
+    link: {
+      version: 1,
+      container_id: c.container,
+      expected_sequence: Number(c.sequence),
+      previous_hash: c.prev,
+      atom_hash: c.atom,
+      intent_class: CLASSES[c.cls],
+      physics_delta: c.delta.toString(),
+      ...(c.pact ? { pact: c.pact } : {}),
+      author_pubkey: Buffer.from(await ed.getPublicKeyAsync(PRIV)).toString('hex'),
+      signature: Buffer.from(sig).toString('hex'),
+    },
+    state: { physical_balance: (c.balance ?? 0n).toString() },
+  });
+}
+
+fs.writeFileSync(OUT, JSON.stringify(fixtures, null, 2) + '\n');
+console.log(`wrote ${fixtures.length} fixtures to ${OUT.pathname}`);
@@ -6,6 +6,10 @@ export function hexToBytes(hex: string, expectedLen?: number): Uint8Array {
   return new Uint8Array(clean.match(/.{1,2}/g)!.map(b => parseInt(b,16)));
 }
 
+export function utf8(s: string): Uint8Array {
+  return new TextEncoder().encode(s);
+}
+
 export function u8(n: number): Uint8Array {
   if (n < 0 || n > 255) throw new Error('u8 out of range');
   return Uint8Array.from([n]);
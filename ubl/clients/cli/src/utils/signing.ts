@@ -1,5 +1,8 @@
-import { concatBytes, hexToBytes, i128be, u8, u64be } from './bytes.js';
+import { concatBytes, i128be, u8, u64be, utf8 } from './bytes.js';
 
+// Mirrors ubl-link LinkCommit::signing_bytes: the hash fields are taken as
+// their hex text (UTF-8), not decoded to raw bytes. Checked in Rust CI against
+// ubl/kernel/rust/tests/fixtures/ts_commits (npm run conformance:record).
 export function buildSigningBytes(opts: {
   version: number;
   containerHex32: string;
@@ -11,10 +14,10 @@ export function buildSigningBytes(opts: {
 }): Uint8Array {
   return concatBytes(
     u8(opts.version),
-    hexToBytes(opts.containerHex32, 32),
+    utf8(opts.containerHex32),
     u64be(opts.expectedSequence),
-    hexToBytes(opts.previousHashHex32, 32),
-    hexToBytes(opts.atomHashHex32, 32),
+    utf8(opts.previousHashHex32),
+    utf8(opts.atomHashHex32),
     u8(opts.intentClass),
     i128be(opts.physicsDelta),
   );
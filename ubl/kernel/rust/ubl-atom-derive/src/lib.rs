@@ -0,0 +1,196 @@
+//! # UBL Atom Derive
+//!
+//! `#[derive(CanonicalAtom)]` for structs that are committed as atoms, so
+//! the JSON of an event comes from one struct instead of a `json!` literal
+//! repeated at every call site. Use it through `ubl_atom` (feature
+//! `derive`), which defines the traits the generated code implements.
+//!
+//! ## Rules
+//! - Only structs with named fields; each field is a key, named exactly as
+//!   the field unless `#[atom(rename = "...")]` says otherwise
+//! - `Option` fields that are `None` are omitted, never written as `null`
+//! - `#[atom(skip)]` leaves a field out of the atom
+//! - `#[atom(tag = "type", value = "job.created")]` on the struct adds a
+//!   constant key, the way event atoms carry their `type`
+//! - `f32`/`f64` fields are a compile error: floats do not survive
+//!   canonicalization across languages, use integers (cents, basis points,
+//!   milliseconds) or strings
+//! - Two fields (or a field and the tag) with the same key are a compile error
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr, Type};
+
+/// Derive `ubl_atom::CanonicalAtom` and `ubl_atom::AtomField`
+#[proc_macro_derive(CanonicalAtom, attributes(atom))]
+pub fn derive_canonical_atom(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "CanonicalAtom needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "CanonicalAtom can only be derived for structs",
+            ))
+        }
+    };
+
+    let tag = struct_tag(&input)?;
+    let mut keys: Vec<(String, Span)> = Vec::new();
+    if let Some((key, _)) = &tag {
+        keys.push((key.value(), key.span()));
+    }
+
+    let mut inserts = Vec::new();
+    for field in fields {
+        let attrs = FieldAttrs::parse(field)?;
+        if attrs.skip {
+            continue;
+        }
+        reject_floats(&field.ty)?;
+
+        let ident = field.ident.as_ref().expect("named field");
+        let key = attrs
+            .rename
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+        let span = attrs.rename.as_ref().map(LitStr::span).unwrap_or_else(|| ident.span());
+        if keys.iter().any(|(k, _)| *k == key) {
+            return Err(Error::new(span, format!("duplicate atom key `{}`", key)));
+        }
+        keys.push((key.clone(), span));
+
+        inserts.push(quote! {
+            if !::ubl_atom::AtomField::is_absent(&self.#ident) {
+                map.insert(#key.to_string(), ::ubl_atom::AtomField::to_atom_value(&self.#ident));
+            }
+        });
+    }
+
+    let tag_insert = tag.map(|(key, value)| {
+        quote! {
+            map.insert(#key.to_string(), ::ubl_atom::__private::Value::String(#value.to_string()));
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::ubl_atom::CanonicalAtom for #name #ty_generics #where_clause {
+            fn to_atom(&self) -> ::ubl_atom::__private::Value {
+                let mut map = ::ubl_atom::__private::Map::new();
+                #tag_insert
+                #(#inserts)*
+                ::ubl_atom::__private::Value::Object(map)
+            }
+        }
+
+        impl #impl_generics ::ubl_atom::AtomField for #name #ty_generics #where_clause {
+            fn to_atom_value(&self) -> ::ubl_atom::__private::Value {
+                ::ubl_atom::CanonicalAtom::to_atom(self)
+            }
+        }
+    })
+}
+
+/// `#[atom(tag = "...", value = "...")]` on the struct
+fn struct_tag(input: &DeriveInput) -> syn::Result<Option<(LitStr, LitStr)>> {
+    let mut tag = None;
+    let mut value = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("atom")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else if meta.path.is_ident("value") {
+                value = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `tag` or `value`"))
+            }
+        })?;
+    }
+    match (tag, value) {
+        (Some(tag), Some(value)) => Ok(Some((tag, value))),
+        (None, None) => Ok(None),
+        _ => Err(Error::new_spanned(
+            &input.ident,
+            "#[atom(tag = ..., value = ...)] needs both",
+        )),
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    rename: Option<LitStr>,
+    skip: bool,
+}
+
+impl FieldAttrs {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = FieldAttrs::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("atom")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    attrs.rename = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    attrs.skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename` or `skip`"))
+                }
+            })?;
+        }
+        Ok(attrs)
+    }
+}
+
+/// Refuse `f32`/`f64` anywhere in a field type (`Option<f64>`, `Vec<f32>`, ...)
+///
+/// Aliases get past this check but not past the trait bound: floats do not
+/// implement `AtomField`.
+fn reject_floats(ty: &Type) -> syn::Result<()> {
+    match ty {
+        Type::Path(path) => {
+            for segment in &path.path.segments {
+                if segment.ident == "f32" || segment.ident == "f64" {
+                    return Err(Error::new_spanned(
+                        ty,
+                        "float fields are not allowed in canonical atoms; use an integer unit or a string",
+                    ));
+                }
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        if let syn::GenericArgument::Type(inner) = arg {
+                            reject_floats(inner)?;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        Type::Reference(r) => reject_floats(&r.elem),
+        Type::Array(a) => reject_floats(&a.elem),
+        Type::Slice(s) => reject_floats(&s.elem),
+        Type::Paren(p) => reject_floats(&p.elem),
+        Type::Group(g) => reject_floats(&g.elem),
+        Type::Tuple(t) => t.elems.iter().try_for_each(reject_floats),
+        _ => Ok(()),
+    }
+}
@@ -14,6 +14,8 @@
 //! - Keys are sorted lexicographically (recursive)
 //! - No whitespace in output
 //! - Non-finite numbers are rejected
+//! - Nesting depth and per-container child counts are bounded
+//!   (see [`Limits`]), so hostile input cannot stall canonicalization
 //!
 //! ## Example
 //! ```
@@ -24,6 +26,11 @@
 //! let canonical = canonicalize(&data).unwrap();
 //! assert_eq!(canonical, br#"{"a":2,"z":1}"#);
 //! ```
+//!
+//! ## Typed atoms
+//! With the `derive` feature (on by default), `#[derive(CanonicalAtom)]` builds the atom of
+//! a struct (see [`CanonicalAtom`]); prefer it to `json!` for anything
+//! committed from more than one place.
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
@@ -31,6 +38,51 @@
 use serde_json::{Map, Value};
 use thiserror::Error;
 
+mod typed;
+
+pub use typed::{AtomField, CanonicalAtom};
+
+/// Derive [`CanonicalAtom`] for a struct with named fields
+///
+/// Keys are the field names (`#[atom(rename = "...")]` to override),
+/// `None` fields are omitted, `#[atom(skip)]` leaves a field out and
+/// `#[atom(tag = "type", value = "...")]` adds a constant key.
+///
+/// ```
+/// use ubl_atom::CanonicalAtom;
+///
+/// #[derive(CanonicalAtom)]
+/// #[atom(tag = "type", value = "job.progress")]
+/// struct JobProgress {
+///     job_id: String,
+///     percent: u8,
+///     #[atom(rename = "msg")]
+///     message: Option<String>,
+/// }
+///
+/// let event = JobProgress { job_id: "job_1".into(), percent: 40, message: None };
+/// let bytes = event.canonical_bytes().unwrap();
+/// assert_eq!(bytes, br#"{"job_id":"job_1","percent":40,"type":"job.progress"}"#);
+/// ```
+///
+/// Floats are refused at compile time:
+///
+/// ```compile_fail
+/// use ubl_atom::CanonicalAtom;
+///
+/// #[derive(CanonicalAtom)]
+/// struct Quote {
+///     amount: f64,
+/// }
+/// ```
+#[cfg(feature = "derive")]
+pub use ubl_atom_derive::CanonicalAtom;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json::{Map, Value};
+}
+
 /// Errors that can occur during canonicalization
 #[derive(Error, Debug)]
 pub enum AtomError {
@@ -41,11 +93,84 @@ pub enum AtomError {
     /// Non-finite number detected (NaN, Infinity)
     #[error("Non-finite number detected")]
     NonFiniteNumber,
+
+    /// Structural limit exceeded (nesting depth or child count)
+    #[error("Atom exceeds {limit} limit of {max}")]
+    LimitExceeded {
+        /// Which limit was hit: "depth" or "children"
+        limit: &'static str,
+        /// The configured maximum
+        max: usize,
+    },
 }
 
 /// Result type for atom operations
 pub type Result<T> = std::result::Result<T, AtomError>;
 
+impl ubl_errors::HasErrorCode for AtomError {
+    fn error_code(&self) -> ubl_errors::ErrorCode {
+        match self {
+            AtomError::LimitExceeded { .. } => ubl_errors::ErrorCode::AtomLimitExceeded,
+            AtomError::Serialization(_) | AtomError::NonFiniteNumber => ubl_errors::ErrorCode::InvalidAtom,
+        }
+    }
+}
+
+/// Structural limits applied before any value is canonicalized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum nesting depth (a scalar at the root has depth 0)
+    pub max_depth: usize,
+    /// Maximum entries in a single object or array
+    pub max_children: usize,
+}
+
+impl Limits {
+    /// Defaults used by [`canonicalize`]
+    pub const DEFAULT: Limits = Limits {
+        max_depth: 64,
+        max_children: 10_000,
+    };
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Check a value against `limits` without canonicalizing it
+///
+/// Stops at the first violation, so the cost is bounded by the limits and
+/// not by the size of a hostile value.
+pub fn check_limits(value: &Value, limits: &Limits) -> Result<()> {
+    check_limits_at(value, limits, 0)
+}
+
+fn check_limits_at(value: &Value, limits: &Limits, depth: usize) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            check_container(map.len(), limits, depth)?;
+            map.values().try_for_each(|v| check_limits_at(v, limits, depth + 1))
+        }
+        Value::Array(arr) => {
+            check_container(arr.len(), limits, depth)?;
+            arr.iter().try_for_each(|v| check_limits_at(v, limits, depth + 1))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_container(len: usize, limits: &Limits, depth: usize) -> Result<()> {
+    if depth >= limits.max_depth {
+        return Err(AtomError::LimitExceeded { limit: "depth", max: limits.max_depth });
+    }
+    if len > limits.max_children {
+        return Err(AtomError::LimitExceeded { limit: "children", max: limits.max_children });
+    }
+    Ok(())
+}
+
 /// Canonicalize a JSON value to deterministic bytes
 ///
 /// SPEC 5.1: Canonical Function
@@ -53,7 +178,14 @@ pub type Result<T> = std::result::Result<T, AtomError>;
 /// - No whitespace in output
 /// - Arrays preserve order
 /// - Non-finite numbers are rejected
+/// - Values outside [`Limits::DEFAULT`] are rejected
 pub fn canonicalize(value: &Value) -> Result<Vec<u8>> {
+    canonicalize_with_limits(value, &Limits::DEFAULT)
+}
+
+/// Canonicalize with caller-supplied structural limits
+pub fn canonicalize_with_limits(value: &Value, limits: &Limits) -> Result<Vec<u8>> {
+    check_limits(value, limits)?;
     let sorted = sort_keys_recursive(value)?;
     Ok(serde_json::to_vec(&sorted)?)
 }
@@ -178,6 +310,22 @@ mod tests {
         assert_eq!(atom_hash(&v1).unwrap(), atom_hash(&v2).unwrap());
     }
 
+    #[test]
+    fn test_rejects_excessive_depth() {
+        let limits = Limits { max_depth: 3, max_children: 10 };
+        assert!(check_limits(&json!({"a": {"b": {"c": 1}}}), &limits).is_ok());
+        let err = check_limits(&json!({"a": {"b": {"c": [1]}}}), &limits).unwrap_err();
+        assert!(matches!(err, AtomError::LimitExceeded { limit: "depth", max: 3 }));
+    }
+
+    #[test]
+    fn test_rejects_excessive_children() {
+        let limits = Limits { max_depth: 8, max_children: 2 };
+        assert!(canonicalize_with_limits(&json!([1, 2]), &limits).is_ok());
+        let err = canonicalize_with_limits(&json!({"x": [1, 2, 3]}), &limits).unwrap_err();
+        assert!(matches!(err, AtomError::LimitExceeded { limit: "children", max: 2 }));
+    }
+
     #[test]
     fn test_atom_hash_bytes() {
         let v = json!({"test": true});
@@ -0,0 +1,127 @@
+//! Typed atoms
+//!
+//! A struct that derives [`CanonicalAtom`] (feature `derive`) turns into its
+//! atom with stable keys and no floats, instead of being rebuilt with
+//! `json!` wherever it is committed. Field values go through [`AtomField`],
+//! which floats deliberately do not implement.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::Result;
+
+/// A struct committed as an atom
+pub trait CanonicalAtom {
+    /// The atom as a JSON object
+    fn to_atom(&self) -> Value;
+
+    /// Canonical bytes of [`CanonicalAtom::to_atom`]
+    fn canonical_bytes(&self) -> Result<Vec<u8>> {
+        crate::canonicalize(&self.to_atom())
+    }
+
+    /// `atom_hash` of [`CanonicalAtom::to_atom`]
+    fn atom_hash(&self) -> Result<String> {
+        crate::atom_hash(&self.to_atom())
+    }
+}
+
+/// A value allowed in a field of a [`CanonicalAtom`]
+pub trait AtomField {
+    /// The field's JSON value
+    fn to_atom_value(&self) -> Value;
+
+    /// Leave the key out of the atom (`None`)
+    fn is_absent(&self) -> bool {
+        false
+    }
+}
+
+impl<T: AtomField + ?Sized> AtomField for &T {
+    fn to_atom_value(&self) -> Value {
+        (**self).to_atom_value()
+    }
+    fn is_absent(&self) -> bool {
+        (**self).is_absent()
+    }
+}
+
+impl<T: AtomField + ?Sized> AtomField for Box<T> {
+    fn to_atom_value(&self) -> Value {
+        (**self).to_atom_value()
+    }
+    fn is_absent(&self) -> bool {
+        (**self).is_absent()
+    }
+}
+
+impl<T: AtomField> AtomField for Option<T> {
+    fn to_atom_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, AtomField::to_atom_value)
+    }
+    fn is_absent(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl AtomField for str {
+    fn to_atom_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl AtomField for String {
+    fn to_atom_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl AtomField for bool {
+    fn to_atom_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+macro_rules! integer_fields {
+    ($($t:ty),*) => {
+        $(impl AtomField for $t {
+            fn to_atom_value(&self) -> Value {
+                Value::from(*self)
+            }
+        })*
+    };
+}
+
+integer_fields!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: AtomField> AtomField for [T] {
+    fn to_atom_value(&self) -> Value {
+        Value::Array(self.iter().map(AtomField::to_atom_value).collect())
+    }
+}
+
+impl<T: AtomField> AtomField for Vec<T> {
+    fn to_atom_value(&self) -> Value {
+        self.as_slice().to_atom_value()
+    }
+}
+
+impl<T: AtomField> AtomField for BTreeMap<String, T> {
+    fn to_atom_value(&self) -> Value {
+        Value::Object(self.iter().map(|(k, v)| (k.clone(), v.to_atom_value())).collect())
+    }
+}
+
+/// Free-form payloads; finiteness is still checked on canonicalization
+impl AtomField for Value {
+    fn to_atom_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl AtomField for Map<String, Value> {
+    fn to_atom_value(&self) -> Value {
+        Value::Object(self.clone())
+    }
+}
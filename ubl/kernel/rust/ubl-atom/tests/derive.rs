@@ -0,0 +1,77 @@
+//! `#[derive(CanonicalAtom)]` against hand-written atoms
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+use ubl_atom::{atom_hash, canonicalize, CanonicalAtom};
+
+#[derive(CanonicalAtom)]
+#[atom(tag = "type", value = "job.created")]
+struct JobCreated {
+    job_id: String,
+    title: String,
+    priority: Option<u8>,
+    #[atom(rename = "tenant")]
+    tenant_id: Option<String>,
+    labels: Vec<String>,
+    amount_cents: i64,
+    #[atom(skip)]
+    #[allow(dead_code)]
+    local_only: bool,
+}
+
+#[derive(CanonicalAtom)]
+struct Envelope<'a> {
+    r#ref: &'a str,
+    job: JobCreated,
+    meta: BTreeMap<String, u32>,
+    extra: Value,
+}
+
+fn job() -> JobCreated {
+    JobCreated {
+        job_id: "job_1".into(),
+        title: "Quarterly report".into(),
+        priority: None,
+        tenant_id: Some("t1".into()),
+        labels: vec!["finance".into()],
+        amount_cents: -1250,
+        local_only: true,
+    }
+}
+
+#[test]
+fn derived_atom_matches_json_literal() {
+    let expected = json!({
+        "type": "job.created",
+        "job_id": "job_1",
+        "title": "Quarterly report",
+        "tenant": "t1",
+        "labels": ["finance"],
+        "amount_cents": -1250
+    });
+    assert_eq!(job().to_atom(), expected);
+    assert_eq!(job().canonical_bytes().unwrap(), canonicalize(&expected).unwrap());
+    assert_eq!(job().atom_hash().unwrap(), atom_hash(&expected).unwrap());
+}
+
+#[test]
+fn nested_atoms_and_raw_names() {
+    let env = Envelope {
+        r#ref: "r1",
+        job: job(),
+        meta: BTreeMap::from([("b".to_string(), 2), ("a".to_string(), 1)]),
+        extra: json!({"z": true}),
+    };
+    let atom = env.to_atom();
+    assert_eq!(atom["ref"], "r1");
+    assert_eq!(atom["job"]["type"], "job.created");
+    assert!(atom["job"].get("priority").is_none());
+    assert_eq!(
+        String::from_utf8(env.canonical_bytes().unwrap()).unwrap(),
+        format!(
+            r#"{{"extra":{{"z":true}},"job":{},"meta":{{"a":1,"b":2}},"ref":"r1"}}"#,
+            String::from_utf8(job().canonical_bytes().unwrap()).unwrap()
+        )
+    );
+}
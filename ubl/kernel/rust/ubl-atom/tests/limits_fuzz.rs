@@ -0,0 +1,109 @@
+//! Fuzz tests: hostile atoms are rejected in bounded time
+
+use quickcheck::{quickcheck, Arbitrary, Gen};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use ubl_atom::{canonicalize, canonicalize_with_limits, check_limits, AtomError, Limits};
+
+/// Generous bound for debug builds on slow CI machines
+const BUDGET: Duration = Duration::from_millis(500);
+
+/// Random JSON tree (depth driven by the generator size)
+#[derive(Clone, Debug)]
+struct ArbJson(Value);
+
+impl Arbitrary for ArbJson {
+    fn arbitrary(g: &mut Gen) -> Self {
+        ArbJson(arb_value(g, 0))
+    }
+}
+
+fn arb_value(g: &mut Gen, depth: usize) -> Value {
+    let choice = if depth > 12 { u8::arbitrary(g) % 4 } else { u8::arbitrary(g) % 6 };
+    match choice {
+        0 => Value::Null,
+        1 => Value::Bool(bool::arbitrary(g)),
+        2 => json!(i64::arbitrary(g)),
+        3 => Value::String(String::arbitrary(g)),
+        4 => {
+            let len = usize::arbitrary(g) % 6;
+            Value::Array((0..len).map(|_| arb_value(g, depth + 1)).collect())
+        }
+        _ => {
+            let len = usize::arbitrary(g) % 6;
+            Value::Object((0..len).map(|_| (String::arbitrary(g), arb_value(g, depth + 1))).collect())
+        }
+    }
+}
+
+fn depth_of(v: &Value) -> usize {
+    match v {
+        Value::Object(m) => 1 + m.values().map(depth_of).max().unwrap_or(0),
+        Value::Array(a) => 1 + a.iter().map(depth_of).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Build `[[[...]]]` iteratively so the test itself does not recurse
+fn nested_arrays(depth: usize) -> Value {
+    let mut v = Value::Null;
+    for _ in 0..depth {
+        v = Value::Array(vec![v]);
+    }
+    v
+}
+
+quickcheck! {
+    /// Accepting or rejecting is consistent with the value's real depth
+    fn prop_depth_limit_matches_structure(v: ArbJson, max_depth: u8) -> bool {
+        let limits = Limits { max_depth: (max_depth % 16) as usize, max_children: 10_000 };
+        let within = depth_of(&v.0) <= limits.max_depth;
+        match canonicalize_with_limits(&v.0, &limits) {
+            Ok(_) => within,
+            Err(AtomError::LimitExceeded { limit: "depth", .. }) => !within,
+            Err(_) => false,
+        }
+    }
+
+    /// Canonicalization of arbitrary input always finishes within budget
+    fn prop_bounded_time(v: ArbJson) -> bool {
+        let start = Instant::now();
+        let _ = canonicalize(&v.0);
+        start.elapsed() < BUDGET
+    }
+}
+
+#[test]
+fn deeply_nested_atom_is_rejected_quickly() {
+    let hostile = nested_arrays(5_000);
+    let start = Instant::now();
+    let err = canonicalize(&hostile).unwrap_err();
+    assert!(start.elapsed() < BUDGET);
+    assert!(matches!(err, AtomError::LimitExceeded { limit: "depth", .. }));
+
+    // Drop iteratively too: serde_json drops recursively
+    let mut v = hostile;
+    while let Value::Array(mut inner) = v {
+        v = inner.pop().unwrap_or(Value::Null);
+    }
+}
+
+#[test]
+fn wide_atom_is_rejected_quickly() {
+    let hostile = Value::Array(vec![json!({"k": "v"}); 1_000_000]);
+    let start = Instant::now();
+    let err = check_limits(&hostile, &Limits::DEFAULT).unwrap_err();
+    assert!(start.elapsed() < BUDGET);
+    assert!(matches!(err, AtomError::LimitExceeded { limit: "children", .. }));
+}
+
+#[test]
+fn default_limits_accept_realistic_atoms() {
+    let atom = json!({
+        "type": "message.created",
+        "conversation_id": "conv_1",
+        "payload": {"parts": [{"kind": "text", "text": "hello"}], "meta": {"a": {"b": {"c": 1}}}}
+    });
+    assert_eq!(depth_of(&atom), 5);
+    assert!(canonicalize(&atom).is_ok());
+}
@@ -0,0 +1,314 @@
+//! # UBL Errors
+//!
+//! Canonical, machine-readable error codes shared by the kernel crates, the
+//! server, and clients. Clients branch on [`ErrorCode`] instead of matching
+//! human-readable text.
+//!
+//! ## Wire format
+//! Every rejected request carries a JSON body:
+//! ```json
+//! {"error": "REALITY_DRIFT", "message": "RealityDrift"}
+//! ```
+//! Codes are stable: new codes may be added, existing ones are never renamed.
+//! Unknown codes from a newer server decode as [`ErrorCode::Unknown`].
+//!
+//! ## Example
+//! ```
+//! use ubl_errors::{ApiErrorBody, ErrorCode};
+//!
+//! let body = ApiErrorBody::parse(r#"{"error":"REALITY_DRIFT","message":"RealityDrift"}"#);
+//! assert_eq!(body.error, ErrorCode::RealityDrift);
+//! assert!(body.error.is_retryable());
+//! ```
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Canonical error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // Membrane (SPEC-UBL-MEMBRANE V1-V8)
+    /// V1: Unsupported protocol version
+    InvalidVersion,
+    /// V2: Signature does not verify
+    InvalidSignature,
+    /// V3: Link targets a different container
+    InvalidTarget,
+    /// V4: previous_hash does not match the container head
+    RealityDrift,
+    /// V5: expected_sequence does not match the container head
+    SequenceMismatch,
+    /// V6: Physics invariant violated (conservation, observation, ...)
+    PhysicsViolation,
+    /// V7: Required pact missing or unsatisfied
+    PactViolation,
+    /// V8: Evolution without authority
+    UnauthorizedEvolution,
+    /// Evolution payload failed its handler's validation
+    InvalidEvolution,
+
+    // Ledger
+    /// Concurrent append lost a serialization race; safe to retry
+    SerializationConflict,
+
+    // Atoms
+    /// Atom exceeds depth or child-count limits
+    AtomLimitExceeded,
+    /// Atom cannot be canonicalized
+    InvalidAtom,
+
+    // Policy
+    /// Policy pack rejected the atom (PII, FSM, tool pairing)
+    PolicyViolation,
+    /// Policy VM returned Deny
+    PolicyDenied,
+
+    // Console
+    /// Command is not covered by its permit (job type, params, expiry, signature)
+    PermitMismatch,
+
+    // Transport / auth
+    /// Malformed request
+    BadRequest,
+    /// Missing or invalid credentials
+    Unauthorized,
+    /// Authenticated but not allowed
+    Forbidden,
+    /// Resource does not exist
+    NotFound,
+    /// Request body too large
+    PayloadTooLarge,
+    /// Too many requests or connections
+    RateLimited,
+    /// Storage failure
+    DatabaseError,
+    /// Unexpected server failure
+    Internal,
+
+    /// A code this client does not know (newer server)
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    /// All known codes, in catalog order
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::InvalidVersion,
+        ErrorCode::InvalidSignature,
+        ErrorCode::InvalidTarget,
+        ErrorCode::RealityDrift,
+        ErrorCode::SequenceMismatch,
+        ErrorCode::PhysicsViolation,
+        ErrorCode::PactViolation,
+        ErrorCode::UnauthorizedEvolution,
+        ErrorCode::InvalidEvolution,
+        ErrorCode::SerializationConflict,
+        ErrorCode::AtomLimitExceeded,
+        ErrorCode::InvalidAtom,
+        ErrorCode::PolicyViolation,
+        ErrorCode::PolicyDenied,
+        ErrorCode::PermitMismatch,
+        ErrorCode::BadRequest,
+        ErrorCode::Unauthorized,
+        ErrorCode::Forbidden,
+        ErrorCode::NotFound,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::RateLimited,
+        ErrorCode::DatabaseError,
+        ErrorCode::Internal,
+    ];
+
+    /// Wire representation (e.g. `"REALITY_DRIFT"`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InvalidVersion => "INVALID_VERSION",
+            Self::InvalidSignature => "INVALID_SIGNATURE",
+            Self::InvalidTarget => "INVALID_TARGET",
+            Self::RealityDrift => "REALITY_DRIFT",
+            Self::SequenceMismatch => "SEQUENCE_MISMATCH",
+            Self::PhysicsViolation => "PHYSICS_VIOLATION",
+            Self::PactViolation => "PACT_VIOLATION",
+            Self::UnauthorizedEvolution => "UNAUTHORIZED_EVOLUTION",
+            Self::InvalidEvolution => "INVALID_EVOLUTION",
+            Self::SerializationConflict => "SERIALIZATION_CONFLICT",
+            Self::AtomLimitExceeded => "ATOM_LIMIT_EXCEEDED",
+            Self::InvalidAtom => "INVALID_ATOM",
+            Self::PolicyViolation => "POLICY_VIOLATION",
+            Self::PolicyDenied => "POLICY_DENIED",
+            Self::PermitMismatch => "PERMIT_MISMATCH",
+            Self::BadRequest => "BAD_REQUEST",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::NotFound => "NOT_FOUND",
+            Self::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            Self::RateLimited => "RATE_LIMITED",
+            Self::DatabaseError => "DATABASE_ERROR",
+            Self::Internal => "INTERNAL",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// HTTP status the server uses for this code
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Self::InvalidVersion
+            | Self::InvalidTarget
+            | Self::InvalidAtom
+            | Self::InvalidEvolution
+            | Self::BadRequest => 400,
+            Self::InvalidSignature | Self::Unauthorized => 401,
+            Self::PhysicsViolation
+            | Self::PactViolation
+            | Self::UnauthorizedEvolution
+            | Self::PolicyViolation
+            | Self::PolicyDenied
+            | Self::PermitMismatch
+            | Self::Forbidden => 403,
+            Self::NotFound => 404,
+            Self::RealityDrift | Self::SequenceMismatch | Self::SerializationConflict => 409,
+            Self::AtomLimitExceeded | Self::PayloadTooLarge => 413,
+            Self::RateLimited => 429,
+            Self::DatabaseError | Self::Internal | Self::Unknown => 500,
+        }
+    }
+
+    /// Generic code for an HTTP error status, for responses built without one
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            400 => Self::BadRequest,
+            401 => Self::Unauthorized,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            413 => Self::PayloadTooLarge,
+            429 => Self::RateLimited,
+            _ => Self::Internal,
+        }
+    }
+
+    /// Whether the same request may succeed after refreshing state and retrying
+    ///
+    /// Drift and sequence errors mean the client's view of the container head
+    /// is stale: re-read the head, rebuild the link, and resubmit.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RealityDrift | Self::SequenceMismatch | Self::SerializationConflict | Self::RateLimited
+        )
+    }
+
+    /// Best-effort mapping from a legacy plain-text error ("RealityDrift",
+    /// "PactViolation: reason", ...) as returned by older servers
+    pub fn from_legacy_text(text: &str) -> Self {
+        let name = text.split(':').next().unwrap_or_default().trim();
+        match name {
+            "InvalidVersion" => Self::InvalidVersion,
+            "InvalidSignature" => Self::InvalidSignature,
+            "InvalidTarget" => Self::InvalidTarget,
+            "RealityDrift" => Self::RealityDrift,
+            "SequenceMismatch" => Self::SequenceMismatch,
+            "PhysicsViolation" => Self::PhysicsViolation,
+            "PactViolation" => Self::PactViolation,
+            "UnauthorizedEvolution" => Self::UnauthorizedEvolution,
+            "InvalidEvolution" => Self::InvalidEvolution,
+            "SerializationConflict" => Self::SerializationConflict,
+            "AtomLimitExceeded" => Self::AtomLimitExceeded,
+            "PolicyViolation" => Self::PolicyViolation,
+            "PolicyDenied" => Self::PolicyDenied,
+            "DatabaseError" => Self::DatabaseError,
+            other => other.parse().unwrap_or(Self::Unknown),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.iter().copied().find(|c| c.as_str() == s).ok_or(())
+    }
+}
+
+/// JSON body of every error response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiErrorBody {
+    /// Machine-readable code
+    pub error: ErrorCode,
+    /// Human-readable detail; never branch on this
+    pub message: String,
+}
+
+impl ApiErrorBody {
+    /// Build a body for `code`
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { error: code, message: message.into() }
+    }
+
+    /// Decode a response body, falling back to legacy plain-text errors
+    pub fn parse(body: &str) -> Self {
+        match serde_json::from_str::<ApiErrorBody>(body) {
+            Ok(parsed) => parsed,
+            Err(_) => Self::new(ErrorCode::from_legacy_text(body), body),
+        }
+    }
+}
+
+/// Implemented by crate error types that map onto the catalog
+pub trait HasErrorCode {
+    /// The canonical code for this error
+    fn error_code(&self) -> ErrorCode;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_names_match_serde() {
+        for code in ErrorCode::ALL {
+            let json = serde_json::to_string(code).unwrap();
+            assert_eq!(json, format!("\"{}\"", code.as_str()));
+            assert_eq!(code.as_str().parse::<ErrorCode>(), Ok(*code));
+        }
+    }
+
+    #[test]
+    fn test_unknown_code_decodes() {
+        let body = ApiErrorBody::parse(r#"{"error":"SOMETHING_NEW","message":"x"}"#);
+        assert_eq!(body.error, ErrorCode::Unknown);
+    }
+
+    #[test]
+    fn test_legacy_text_is_mapped() {
+        assert_eq!(ApiErrorBody::parse("RealityDrift").error, ErrorCode::RealityDrift);
+        assert_eq!(
+            ApiErrorBody::parse("PactViolation: missing signature").error,
+            ErrorCode::PactViolation
+        );
+        assert_eq!(ApiErrorBody::parse("<html>502</html>").error, ErrorCode::Unknown);
+    }
+
+    #[test]
+    fn test_retryable_codes() {
+        assert!(ErrorCode::RealityDrift.is_retryable());
+        assert!(!ErrorCode::PactViolation.is_retryable());
+        assert_eq!(ErrorCode::SequenceMismatch.http_status(), 409);
+    }
+
+    #[test]
+    fn test_code_from_http_status() {
+        assert_eq!(ErrorCode::from_http_status(404), ErrorCode::NotFound);
+        assert_eq!(ErrorCode::from_http_status(429), ErrorCode::RateLimited);
+        assert_eq!(ErrorCode::from_http_status(502), ErrorCode::Internal);
+    }
+}
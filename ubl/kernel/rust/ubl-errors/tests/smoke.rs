@@ -0,0 +1,2 @@
+#[test]
+fn ubl_errors_smoke() {{ assert!(true); }}
\ No newline at end of file
@@ -0,0 +1,723 @@
+//! # UBL Events
+//!
+//! Canonical payloads of the C.Jobs events. Office emits them, the messenger
+//! gateway commits approvals, and the server projections read them back; all
+//! three build and parse atoms through [`JobEvent`] instead of ad hoc JSON.
+//!
+//! ## Wire format
+//! An event atom is a flat JSON object tagged by `type`:
+//! ```json
+//! {"type": "job.timeout", "job_id": "job_1", "from_state": "in_progress", "to_state": "failed", ...}
+//! ```
+//! Optional fields are omitted when unset. Fields are never renamed; readers
+//! accept the legacy spellings still in the ledger (`id` for `job_id` on
+//! `job.created` and for `approval_id` on `approval.requested`).
+//!
+//! ## Example
+//! ```
+//! use ubl_events::{JobCreated, JobEvent};
+//!
+//! let atom = JobEvent::Created(JobCreated::new("job_1", "Quarterly report").with_tenant("t1")).to_atom();
+//! assert_eq!(atom["type"], "job.created");
+//!
+//! let JobEvent::Created(created) = JobEvent::from_atom(&atom).unwrap() else { unreachable!() };
+//! assert_eq!(created.goal_or_title(), "Quarterly report");
+//! ```
+
+#![deny(unsafe_code)]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Any C.Jobs event, tagged by its atom `type`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum JobEvent {
+    /// A job was proposed (Office ingest, `office.job.create` tool, templates)
+    #[serde(rename = "job.created")]
+    Created(JobCreated),
+    /// Work on the job began
+    #[serde(rename = "job.started")]
+    Started(JobStarted),
+    /// Percent-complete update
+    #[serde(rename = "job.progress")]
+    Progress(JobProgress),
+    /// FSM transition; checked against the job FSM policy on commit
+    #[serde(rename = "job.state_changed")]
+    StateChanged(JobStateChanged),
+    /// The job finished, successfully or not
+    #[serde(rename = "job.completed")]
+    Completed(JobCompleted),
+    /// The job was cancelled
+    #[serde(rename = "job.cancelled")]
+    Cancelled(JobCancelled),
+    /// The job monitor failed a job that stopped reporting
+    #[serde(rename = "job.timeout")]
+    Timeout(JobTimeout),
+    /// Approve button on a job card
+    #[serde(rename = "job.approve")]
+    Approve(JobAction),
+    /// Reject button on a job card
+    #[serde(rename = "job.reject")]
+    Reject(JobAction),
+    /// Input supplied for a job waiting on it
+    #[serde(rename = "job.provide_input")]
+    ProvideInput(JobAction),
+    /// A job asked for a human decision
+    #[serde(rename = "approval.requested")]
+    ApprovalRequested(ApprovalRequested),
+    /// A human decided a pending approval
+    #[serde(rename = "approval.decided")]
+    ApprovalDecided(ApprovalDecided),
+}
+
+impl JobEvent {
+    /// Every atom `type` this crate defines
+    pub const TYPES: &'static [&'static str] = &[
+        "job.created",
+        "job.started",
+        "job.progress",
+        "job.state_changed",
+        "job.completed",
+        "job.cancelled",
+        "job.timeout",
+        "job.approve",
+        "job.reject",
+        "job.provide_input",
+        "approval.requested",
+        "approval.decided",
+    ];
+
+    /// Whether `event_type` is one of [`JobEvent::TYPES`]
+    pub fn is_known(event_type: &str) -> bool {
+        Self::TYPES.contains(&event_type)
+    }
+
+    /// Parse an atom; fails on an unknown `type` or a missing required field
+    pub fn from_atom(atom: &Value) -> Result<Self, serde_json::Error> {
+        Self::deserialize(atom)
+    }
+
+    /// The atom to commit, `type` included
+    pub fn to_atom(&self) -> Value {
+        serde_json::to_value(self).expect("event payloads serialize to JSON")
+    }
+
+    /// The card action event for a gateway `action_type` (`approve`, `reject`,
+    /// `provide_input`, with or without the `job.` prefix)
+    pub fn action(action_type: &str, action: JobAction) -> Option<Self> {
+        match action_type.strip_prefix("job.").unwrap_or(action_type) {
+            "approve" => Some(Self::Approve(action)),
+            "reject" => Some(Self::Reject(action)),
+            "provide_input" => Some(Self::ProvideInput(action)),
+            _ => None,
+        }
+    }
+
+    /// Atom `type`
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::Created(_) => "job.created",
+            Self::Started(_) => "job.started",
+            Self::Progress(_) => "job.progress",
+            Self::StateChanged(_) => "job.state_changed",
+            Self::Completed(_) => "job.completed",
+            Self::Cancelled(_) => "job.cancelled",
+            Self::Timeout(_) => "job.timeout",
+            Self::Approve(_) => "job.approve",
+            Self::Reject(_) => "job.reject",
+            Self::ProvideInput(_) => "job.provide_input",
+            Self::ApprovalRequested(_) => "approval.requested",
+            Self::ApprovalDecided(_) => "approval.decided",
+        }
+    }
+
+    /// The job the event is about
+    pub fn job_id(&self) -> &str {
+        match self {
+            Self::Created(e) => &e.job_id,
+            Self::Started(e) => &e.job_id,
+            Self::Progress(e) => &e.job_id,
+            Self::StateChanged(e) => &e.job_id,
+            Self::Completed(e) => &e.job_id,
+            Self::Cancelled(e) => &e.job_id,
+            Self::Timeout(e) => &e.job_id,
+            Self::Approve(e) | Self::Reject(e) | Self::ProvideInput(e) => &e.job_id,
+            Self::ApprovalRequested(e) => &e.job_id,
+            Self::ApprovalDecided(e) => &e.job_id,
+        }
+    }
+
+    /// Tenant, when the emitter recorded one
+    pub fn tenant_id(&self) -> Option<&str> {
+        match self {
+            Self::Created(e) => e.tenant_id.as_deref(),
+            Self::Started(e) => e.tenant_id.as_deref(),
+            Self::Progress(e) => e.tenant_id.as_deref(),
+            Self::StateChanged(e) => e.tenant_id.as_deref(),
+            Self::Completed(e) => e.tenant_id.as_deref(),
+            Self::Cancelled(e) => e.tenant_id.as_deref(),
+            Self::Timeout(e) => Some(&e.tenant_id),
+            Self::Approve(e) | Self::Reject(e) | Self::ProvideInput(e) => e.tenant_id.as_deref(),
+            Self::ApprovalRequested(e) => e.tenant_id.as_deref(),
+            Self::ApprovalDecided(e) => e.tenant_id.as_deref(),
+        }
+    }
+
+    /// `(from_state, to_state)` of events that move the job FSM
+    pub fn transition(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::StateChanged(e) => Some((&e.from_state, &e.to_state)),
+            Self::Timeout(e) => Some((&e.from_state, &e.to_state)),
+            _ => None,
+        }
+    }
+}
+
+/// `job.created`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobCreated {
+    /// Older atoms name it `id`
+    #[serde(alias = "id")]
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Conversation the job was proposed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// `low`, `normal`, `high` or `urgent`; readers default to `normal`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assigned_to: Option<String>,
+    /// Entity responsible for the job; falls back to `assigned_to`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_entity_id: Option<String>,
+    /// SID of the human who asked for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    /// RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_duration_seconds: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_value: Option<f64>,
+    /// Emitter clock, RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Emitter-specific fields (template provenance, sandbox, ...), carried
+    /// as-is; projections must not depend on them
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
+}
+
+impl JobCreated {
+    /// A job with only its id and title
+    pub fn new(job_id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self { job_id: job_id.into(), title: title.into(), ..Default::default() }
+    }
+
+    pub fn with_tenant(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    pub fn with_conversation(mut self, conversation_id: impl Into<String>) -> Self {
+        self.conversation_id = Some(conversation_id.into());
+        self
+    }
+
+    pub fn with_goal(mut self, goal: impl Into<String>) -> Self {
+        self.goal = Some(goal.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_priority(mut self, priority: impl Into<String>) -> Self {
+        self.priority = Some(priority.into());
+        self
+    }
+
+    pub fn with_owner(mut self, owner_entity_id: impl Into<String>) -> Self {
+        self.owner_entity_id = Some(owner_entity_id.into());
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: impl Into<String>) -> Self {
+        self.timestamp = Some(timestamp.into());
+        self
+    }
+
+    /// Add an emitter-specific field
+    pub fn with_extension(mut self, key: impl Into<String>, value: Value) -> Self {
+        self.extensions.insert(key.into(), value);
+        self
+    }
+
+    /// Owner, else assignee
+    pub fn owner(&self) -> Option<&str> {
+        self.owner_entity_id.as_deref().or(self.assigned_to.as_deref())
+    }
+
+    /// Goal, else description, else title
+    pub fn goal_or_title(&self) -> &str {
+        self.goal.as_deref().or(self.description.as_deref()).unwrap_or(&self.title)
+    }
+}
+
+/// `job.started`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobStarted {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+}
+
+/// `job.progress`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// Percent complete, 0-100
+    #[serde(default)]
+    pub progress: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// `job.state_changed`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobStateChanged {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_entity_id: Option<String>,
+    pub from_state: String,
+    pub to_state: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Entities the job now waits on (presence shows them as blocking)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waiting_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// `job.completed`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobCompleted {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// `false` means the job ran to the end and failed
+    #[serde(default = "succeeded")]
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<JobOutcome>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_used: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+fn succeeded() -> bool {
+    true
+}
+
+impl JobCompleted {
+    /// A completion with only its outcome
+    pub fn new(job_id: impl Into<String>, success: bool) -> Self {
+        Self {
+            job_id: job_id.into(),
+            tenant_id: None,
+            success,
+            completed_at: None,
+            result: None,
+            tokens_used: None,
+            duration_seconds: None,
+            timestamp: None,
+        }
+    }
+
+    /// When the job finished: `completed_at`, else the emitter timestamp
+    pub fn finished_at(&self) -> Option<&str> {
+        self.completed_at.as_deref().or(self.timestamp.as_deref())
+    }
+}
+
+/// What a completed job produced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobOutcome {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// File ids, URLs or artifact records
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts: Option<Value>,
+}
+
+/// `job.cancelled`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobCancelled {
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancelled_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// `job.timeout`: an `in_progress` → `failed` transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobTimeout {
+    pub job_id: String,
+    pub tenant_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_entity_id: Option<String>,
+    pub from_state: String,
+    pub to_state: String,
+    pub reason: String,
+    pub timeout_secs: u64,
+    /// Last activity the monitor saw, unix ms
+    pub last_activity_ms: i64,
+}
+
+impl JobTimeout {
+    /// The monitor's transition for a job silent for `timeout_secs`
+    pub fn new(job_id: impl Into<String>, tenant_id: impl Into<String>, timeout_secs: u64, last_activity_ms: i64) -> Self {
+        Self {
+            job_id: job_id.into(),
+            tenant_id: tenant_id.into(),
+            conversation_id: None,
+            owner_entity_id: None,
+            from_state: "in_progress".into(),
+            to_state: "failed".into(),
+            reason: "timeout".into(),
+            timeout_secs,
+            last_activity_ms,
+        }
+    }
+
+    /// The notice Office receives once the timeout is in the ledger
+    pub fn notice(&self, entry_hash: impl Into<String>) -> JobEventNotice {
+        JobEventNotice {
+            event_type: "job.timeout".into(),
+            job_id: self.job_id.clone(),
+            tenant_id: self.tenant_id.clone(),
+            owner_entity_id: self.owner_entity_id.clone(),
+            from_state: self.from_state.clone(),
+            to_state: self.to_state.clone(),
+            reason: self.reason.clone(),
+            entry_hash: entry_hash.into(),
+        }
+    }
+}
+
+/// `job.approve`, `job.reject`, `job.provide_input`: a card button Office
+/// accepted after checking its provenance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobAction {
+    pub job_id: String,
+    /// Gateway action type, without the `job.` prefix
+    pub action_type: String,
+    pub card_id: String,
+    pub button_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_data: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+}
+
+/// `approval.requested`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequested {
+    /// Older atoms name it `id`
+    #[serde(alias = "id")]
+    pub approval_id: String,
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// What needs approving
+    pub action: String,
+    #[serde(default)]
+    pub reason: String,
+    pub requested_by: String,
+    pub requested_at: String,
+}
+
+/// `approval.decided`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalDecided {
+    pub approval_id: String,
+    pub job_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    /// `approved` or `rejected`
+    pub decision: String,
+    pub decided_by: String,
+    pub decided_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Job transition UBL tells Office about (`POST /v1/office/job_event`); the
+/// event itself is already in the ledger at `entry_hash`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobEventNotice {
+    pub event_type: String,
+    pub job_id: String,
+    pub tenant_id: String,
+    pub owner_entity_id: Option<String>,
+    pub from_state: String,
+    pub to_state: String,
+    pub reason: String,
+    pub entry_hash: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// One fully populated event of every type
+    fn samples() -> Vec<JobEvent> {
+        let action = JobAction {
+            job_id: "job_1".into(),
+            action_type: "approve".into(),
+            card_id: "card_1".into(),
+            button_id: "btn_1".into(),
+            tenant_id: Some("t1".into()),
+            input_data: Some(json!({ "note": "ok" })),
+            timestamp: Some("2026-01-01T00:00:00Z".into()),
+        };
+        let mut timeout = JobTimeout::new("job_1", "t1", 360, 42);
+        timeout.conversation_id = Some("conv_1".into());
+        timeout.owner_entity_id = Some("ent_1".into());
+        vec![
+            JobEvent::Created(JobCreated {
+                assigned_to: Some("ent_2".into()),
+                created_by: Some("sid_1".into()),
+                created_at: Some("2026-01-01T00:00:00Z".into()),
+                estimated_duration_seconds: Some(60),
+                estimated_value: Some(1.5),
+                ..JobCreated::new("job_1", "Report")
+                    .with_tenant("t1")
+                    .with_conversation("conv_1")
+                    .with_goal("Write the report")
+                    .with_description("Q3")
+                    .with_priority("high")
+                    .with_owner("ent_1")
+                    .with_timestamp("2026-01-01T00:00:00Z")
+            }),
+            JobEvent::Started(JobStarted {
+                job_id: "job_1".into(),
+                tenant_id: Some("t1".into()),
+                started_at: Some("2026-01-01T00:00:00Z".into()),
+            }),
+            JobEvent::Progress(JobProgress {
+                job_id: "job_1".into(),
+                tenant_id: Some("t1".into()),
+                progress: 40,
+                message: Some("drafting".into()),
+            }),
+            JobEvent::StateChanged(JobStateChanged {
+                job_id: "job_1".into(),
+                tenant_id: Some("t1".into()),
+                owner_entity_id: Some("ent_1".into()),
+                from_state: "in_progress".into(),
+                to_state: "waiting_input".into(),
+                reason: Some("needs figures".into()),
+                waiting_on: vec!["ent_2".into()],
+                timestamp: Some("2026-01-01T00:00:00Z".into()),
+            }),
+            JobEvent::Completed(JobCompleted {
+                tenant_id: Some("t1".into()),
+                completed_at: Some("2026-01-01T00:00:00Z".into()),
+                result: Some(JobOutcome { summary: Some("done".into()), artifacts: Some(json!(["file_1"])) }),
+                tokens_used: Some(1200),
+                duration_seconds: Some(30),
+                timestamp: Some("2026-01-01T00:00:00Z".into()),
+                ..JobCompleted::new("job_1", true)
+            }),
+            JobEvent::Cancelled(JobCancelled {
+                job_id: "job_1".into(),
+                tenant_id: Some("t1".into()),
+                cancelled_at: Some("2026-01-01T00:00:00Z".into()),
+                reason: Some("duplicate".into()),
+            }),
+            JobEvent::Timeout(timeout),
+            JobEvent::Approve(action.clone()),
+            JobEvent::Reject(JobAction { action_type: "reject".into(), ..action.clone() }),
+            JobEvent::ProvideInput(JobAction { action_type: "provide_input".into(), ..action }),
+            JobEvent::ApprovalRequested(ApprovalRequested {
+                approval_id: "appr_1".into(),
+                job_id: "job_1".into(),
+                tenant_id: Some("t1".into()),
+                action: "send_email".into(),
+                reason: "external recipient".into(),
+                requested_by: "ent_1".into(),
+                requested_at: "2026-01-01T00:00:00Z".into(),
+            }),
+            JobEvent::ApprovalDecided(ApprovalDecided {
+                approval_id: "appr_1".into(),
+                job_id: "job_1".into(),
+                tenant_id: Some("t1".into()),
+                decision: "approved".into(),
+                decided_by: "sid_1".into(),
+                decided_at: "2026-01-01T00:00:00Z".into(),
+                reason: Some("fine".into()),
+            }),
+        ]
+    }
+
+    /// Pinned wire fields per type. Emitters and readers on both sides go
+    /// through these structs, so a change here is a change for all of them.
+    const WIRE_FIELDS: &[(&str, &[&str])] = &[
+        (
+            "job.created",
+            &[
+                "assigned_to", "conversation_id", "created_at", "created_by", "description",
+                "estimated_duration_seconds", "estimated_value", "goal", "job_id", "owner_entity_id",
+                "priority", "tenant_id", "timestamp", "title", "type",
+            ],
+        ),
+        ("job.started", &["job_id", "started_at", "tenant_id", "type"]),
+        ("job.progress", &["job_id", "message", "progress", "tenant_id", "type"]),
+        (
+            "job.state_changed",
+            &["from_state", "job_id", "owner_entity_id", "reason", "tenant_id", "timestamp", "to_state", "type", "waiting_on"],
+        ),
+        (
+            "job.completed",
+            &["completed_at", "duration_seconds", "job_id", "result", "success", "tenant_id", "timestamp", "tokens_used", "type"],
+        ),
+        ("job.cancelled", &["cancelled_at", "job_id", "reason", "tenant_id", "type"]),
+        (
+            "job.timeout",
+            &[
+                "conversation_id", "from_state", "job_id", "last_activity_ms", "owner_entity_id", "reason",
+                "tenant_id", "timeout_secs", "to_state", "type",
+            ],
+        ),
+        ("job.approve", &["action_type", "button_id", "card_id", "input_data", "job_id", "tenant_id", "timestamp", "type"]),
+        ("job.reject", &["action_type", "button_id", "card_id", "input_data", "job_id", "tenant_id", "timestamp", "type"]),
+        (
+            "job.provide_input",
+            &["action_type", "button_id", "card_id", "input_data", "job_id", "tenant_id", "timestamp", "type"],
+        ),
+        (
+            "approval.requested",
+            &["action", "approval_id", "job_id", "reason", "requested_at", "requested_by", "tenant_id", "type"],
+        ),
+        (
+            "approval.decided",
+            &["approval_id", "decided_at", "decided_by", "decision", "job_id", "reason", "tenant_id", "type"],
+        ),
+    ];
+
+    #[test]
+    fn test_wire_fields_are_pinned() {
+        let samples = samples();
+        assert_eq!(samples.len(), JobEvent::TYPES.len(), "a type without a sample");
+        assert_eq!(WIRE_FIELDS.len(), JobEvent::TYPES.len(), "a type without pinned fields");
+
+        for event in samples {
+            let atom = event.to_atom();
+            assert_eq!(atom["type"], event.event_type());
+            assert!(JobEvent::is_known(event.event_type()));
+
+            let fields: Vec<&str> = atom.as_object().unwrap().keys().map(String::as_str).collect();
+            let (_, pinned) = WIRE_FIELDS.iter().find(|(t, _)| *t == event.event_type()).unwrap();
+            assert_eq!(&fields, pinned, "{} wire fields changed", event.event_type());
+
+            assert_eq!(JobEvent::from_atom(&atom).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn test_unset_fields_are_omitted() {
+        let atom = JobEvent::Completed(JobCompleted::new("job_1", false)).to_atom();
+        assert_eq!(atom, json!({ "type": "job.completed", "job_id": "job_1", "success": false }));
+    }
+
+    #[test]
+    fn test_legacy_atoms_decode() {
+        let JobEvent::Created(created) = JobEvent::from_atom(&json!({
+            "type": "job.created",
+            "id": "j1",
+            "assigned_to": "e1",
+            "goal": "Summarize",
+            "fsm": { "initial": "proposed" },
+        }))
+        .unwrap() else {
+            panic!("not job.created")
+        };
+        assert_eq!(created.job_id, "j1");
+        assert_eq!(created.owner(), Some("e1"));
+        assert_eq!(created.goal_or_title(), "Summarize");
+        // Emitter-specific fields survive a round trip, the tag does not leak in
+        assert_eq!(created.extensions.keys().collect::<Vec<_>>(), ["fsm"]);
+        assert_eq!(JobEvent::Created(created).to_atom()["fsm"]["initial"], "proposed");
+
+        let JobEvent::Completed(completed) =
+            JobEvent::from_atom(&json!({ "type": "job.completed", "job_id": "j1", "timestamp": "2026-01-01T00:00:00Z" })).unwrap()
+        else {
+            panic!("not job.completed")
+        };
+        assert!(completed.success);
+        assert_eq!(completed.finished_at(), Some("2026-01-01T00:00:00Z"));
+
+        let requested = json!({
+            "type": "approval.requested", "id": "a1", "job_id": "j1", "action": "pay",
+            "requested_by": "e1", "requested_at": "2026-01-01T00:00:00Z",
+        });
+        assert_eq!(JobEvent::from_atom(&requested).unwrap().job_id(), "j1");
+    }
+
+    #[test]
+    fn test_malformed_atoms_are_refused() {
+        assert!(JobEvent::from_atom(&json!({ "type": "job.unknown", "job_id": "j1" })).is_err());
+        assert!(JobEvent::from_atom(&json!({ "type": "job.state_changed", "job_id": "j1", "to_state": "failed" })).is_err());
+        assert!(JobEvent::from_atom(&json!({ "type": "job.progress", "job_id": 7 })).is_err());
+    }
+
+    #[test]
+    fn test_timeout_is_a_transition() {
+        let event = JobEvent::Timeout(JobTimeout::new("job_1", "t1", 360, 42));
+        assert_eq!(event.transition(), Some(("in_progress", "failed")));
+        assert_eq!(event.tenant_id(), Some("t1"));
+
+        let JobEvent::Timeout(timeout) = event else { unreachable!() };
+        let notice = timeout.notice("entry_1");
+        assert_eq!((notice.event_type.as_str(), notice.reason.as_str()), ("job.timeout", "timeout"));
+
+        let action = JobAction { job_id: "job_1".into(), ..Default::default() };
+        assert!(matches!(JobEvent::action("job.approve", action.clone()), Some(JobEvent::Approve(_))));
+        assert!(JobEvent::action("cancel", action).is_none());
+    }
+}
@@ -6,15 +6,28 @@
 //! ## Features
 //! - BLAKE3 hashing with domain separation
 //! - Ed25519 signing and verification
+//! - Ed25519ph with RFC 8032 context strings (signature mode v2)
 //! - Deterministic operations only
+//! - Constant-time comparison and hex decoding for secret material
+//!
+//! ## Signature modes
+//! The mode is negotiated by the `LinkCommit` version:
+//! - v1: PureEd25519 over raw bytes; callers add their own domain tags
+//! - v2: Ed25519ph (SHA-512 prehash) bound to a context string from
+//!   [`contexts`], so a signature made for one protocol cannot be replayed
+//!   as a valid signature in another
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
 use blake3::Hasher;
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha2::{Digest, Sha512};
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+pub use zeroize::{Zeroize, Zeroizing};
+
 /// Domain prefixes for hash separation
 /// NOTE: atom_hash does NOT use domain tag per JSON✯Atomic binding
 pub mod domains {
@@ -26,6 +39,52 @@ pub mod domains {
     pub const ROOT: &[u8] = b"ubl:root\n";
 }
 
+/// RFC 8032 context strings for v2 (Ed25519ph) signatures
+pub mod contexts {
+    /// Link commits
+    pub const LINK: &[u8] = b"ubl:link:v2";
+    /// Pact signatures
+    pub const PACT: &[u8] = b"ubl:pact:v2";
+    /// Permits and commands issued by the server
+    pub const PERMIT: &[u8] = b"ubl:permit:v2";
+    /// Service-to-service HTTP requests (UBL and Office)
+    pub const SERVICE: &[u8] = b"ubl:service:v2";
+    /// Agent Signing Certificates issued by the authority key
+    pub const ASC: &[u8] = b"ubl:asc:v2";
+    /// Authority key transitions recorded in the ledger
+    pub const KEY_TRANSITION: &[u8] = b"ubl:key-transition:v2";
+    /// Ledger checkpoints signed by the server key
+    pub const CHECKPOINT: &[u8] = b"ubl:checkpoint:v2";
+}
+
+/// Signature mode, negotiated by protocol version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureMode {
+    /// v1: PureEd25519 over the raw message; context is ignored
+    Ed25519,
+    /// v2: Ed25519ph with an RFC 8032 context string
+    Ed25519ph,
+}
+
+impl SignatureMode {
+    /// Mode for a `LinkCommit` version; None if the version is unsupported
+    pub fn for_version(version: u8) -> Option<Self> {
+        match version {
+            1 => Some(SignatureMode::Ed25519),
+            2 => Some(SignatureMode::Ed25519ph),
+            _ => None,
+        }
+    }
+
+    /// Protocol version that selects this mode
+    pub fn version(&self) -> u8 {
+        match self {
+            SignatureMode::Ed25519 => 1,
+            SignatureMode::Ed25519ph => 2,
+        }
+    }
+}
+
 /// Errors from kernel operations
 #[derive(Error, Debug)]
 pub enum KernelError {
@@ -97,6 +156,72 @@ pub fn verify(pubkey_hex: &str, message: &[u8], signature_hex: &str) -> Result<(
     Ok(())
 }
 
+/// Sign with an explicit mode; `context` binds v2 signatures to a protocol
+///
+/// Context strings are limited to 255 bytes by RFC 8032.
+pub fn sign_with(
+    mode: SignatureMode,
+    signing_key: &SigningKey,
+    context: &[u8],
+    message: &[u8],
+) -> Result<String> {
+    match mode {
+        SignatureMode::Ed25519 => Ok(sign(signing_key, message)),
+        SignatureMode::Ed25519ph => {
+            let signature = signing_key
+                .sign_prehashed(Sha512::new().chain_update(message), Some(context))
+                .map_err(|e| KernelError::InvalidKey(e.to_string()))?;
+            Ok(hex::encode(signature.to_bytes()))
+        }
+    }
+}
+
+/// Verify a signature made with [`sign_with`]
+pub fn verify_with(
+    mode: SignatureMode,
+    pubkey_hex: &str,
+    context: &[u8],
+    message: &[u8],
+    signature_hex: &str,
+) -> Result<()> {
+    match mode {
+        SignatureMode::Ed25519 => verify(pubkey_hex, message, signature_hex),
+        SignatureMode::Ed25519ph => {
+            let pubkey_bytes = hex::decode(pubkey_hex)?;
+            let verifying_key = VerifyingKey::try_from(pubkey_bytes.as_slice())
+                .map_err(|e| KernelError::InvalidKey(e.to_string()))?;
+            let sig_bytes = hex::decode(signature_hex)?;
+            let signature = Signature::try_from(sig_bytes.as_slice())
+                .map_err(|e| KernelError::InvalidKey(e.to_string()))?;
+
+            verifying_key
+                .verify_prehashed_strict(Sha512::new().chain_update(message), Some(context), &signature)
+                .map_err(|_| KernelError::SignatureVerification)
+        }
+    }
+}
+
+/// Bytes a service signs (v2, [`contexts::SERVICE`]) to authenticate an
+/// internal HTTP request: sender, method, path with query, timestamp and the
+/// BLAKE3 hash of the body, one per line
+pub fn service_request_bytes(
+    service: &str,
+    method: &str,
+    path_and_query: &str,
+    timestamp_ms: i64,
+    body: &[u8],
+) -> Vec<u8> {
+    format!(
+        "{}\n{}\n{}\n{}\n{}",
+        service,
+        method.to_ascii_uppercase(),
+        path_and_query,
+        timestamp_ms,
+        hash_atom(body)
+    )
+    .into_bytes()
+}
+
 /// Generate a new signing keypair
 pub fn generate_keypair() -> (String, SigningKey) {
     let signing_key = SigningKey::generate(&mut rand::thread_rng());
@@ -109,6 +234,60 @@ pub fn pubkey_from_signing_key(signing_key: &SigningKey) -> String {
     hex::encode(signing_key.verifying_key().as_bytes())
 }
 
+/// Constant-time equality for signatures, tokens and MACs
+///
+/// Only the lengths may leak; the contents are compared without early exit.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Decode one hex digit without branches or table lookups.
+/// Returns (value, 0xff if valid else 0).
+fn ct_hex_nibble(c: u8) -> (u8, u8) {
+    let c = c as i16;
+    let lower = c | 0x20;
+    // -1 (all ones) when the range test holds, 0 otherwise
+    let is_digit = ((0x2f - c) & (c - 0x3a)) >> 8;
+    let is_alpha = ((0x60 - lower) & (lower - 0x67)) >> 8;
+    let value = ((c - 0x30) & is_digit) | ((lower - 0x57) & is_alpha);
+    (value as u8, (is_digit | is_alpha) as u8)
+}
+
+/// Decode hex-encoded secret material in constant time
+///
+/// Unlike `hex::decode`, timing does not depend on the digits, and the
+/// output is wiped when dropped. Only the length is treated as public.
+pub fn decode_hex_secret(encoded: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if encoded.len() % 2 != 0 {
+        return Err(KernelError::InvalidKey("odd-length hex".into()));
+    }
+    let mut out = Zeroizing::new(vec![0u8; encoded.len() / 2]);
+    let mut valid = 0xffu8;
+    for (i, pair) in encoded.chunks_exact(2).enumerate() {
+        let (hi, hi_ok) = ct_hex_nibble(pair[0]);
+        let (lo, lo_ok) = ct_hex_nibble(pair[1]);
+        out[i] = (hi << 4) | lo;
+        valid &= hi_ok & lo_ok;
+    }
+    if valid != 0xff {
+        return Err(KernelError::InvalidKey("invalid hex".into()));
+    }
+    Ok(out)
+}
+
+/// Load an Ed25519 signing key from a hex-encoded 32-byte seed
+///
+/// Intermediate buffers are zeroized; the returned key wipes itself on drop.
+pub fn signing_key_from_hex(encoded: &[u8]) -> Result<SigningKey> {
+    let bytes = decode_hex_secret(encoded)?;
+    let mut seed = Zeroizing::new([0u8; 32]);
+    if bytes.len() != seed.len() {
+        return Err(KernelError::InvalidKey(format!("expected 32 bytes, got {}", bytes.len())));
+    }
+    seed.copy_from_slice(&bytes);
+    Ok(SigningKey::from_bytes(&seed))
+}
+
 /// The genesis hash (32 zero bytes)
 pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
 
@@ -143,6 +322,16 @@ mod tests {
         assert_eq!(atom_hash, raw_blake3, "atom_hash must match raw BLAKE3 (JSON✯Atomic binding)");
     }
 
+    #[test]
+    fn test_service_request_bytes_bind_every_part() {
+        let base = service_request_bytes("office", "post", "/v1/policy/permit", 1, b"{}");
+        assert_eq!(base, service_request_bytes("office", "POST", "/v1/policy/permit", 1, b"{}"));
+        assert_ne!(base, service_request_bytes("ubl", "POST", "/v1/policy/permit", 1, b"{}"));
+        assert_ne!(base, service_request_bytes("office", "POST", "/v1/commands/issue", 1, b"{}"));
+        assert_ne!(base, service_request_bytes("office", "POST", "/v1/policy/permit", 2, b"{}"));
+        assert_ne!(base, service_request_bytes("office", "POST", "/v1/policy/permit", 1, b"[]"));
+    }
+
     #[test]
     fn test_sign_and_verify() {
         let (pubkey, signing_key) = generate_keypair();
@@ -178,6 +367,69 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_ph_sign_and_verify() {
+        let (pubkey, key) = generate_keypair();
+        let message = b"hello world";
+
+        let sig = sign_with(SignatureMode::Ed25519ph, &key, contexts::LINK, message).unwrap();
+        assert!(verify_with(SignatureMode::Ed25519ph, &pubkey, contexts::LINK, message, &sig).is_ok());
+    }
+
+    #[test]
+    fn test_ph_rejects_other_context_and_mode() {
+        let (pubkey, key) = generate_keypair();
+        let message = b"hello world";
+
+        let ph = sign_with(SignatureMode::Ed25519ph, &key, contexts::LINK, message).unwrap();
+        // Same bytes under another protocol's context
+        assert!(verify_with(SignatureMode::Ed25519ph, &pubkey, contexts::PACT, message, &ph).is_err());
+        // A v2 signature is not a valid v1 signature and vice versa
+        assert!(verify(&pubkey, message, &ph).is_err());
+        let pure = sign(&key, message);
+        assert!(verify_with(SignatureMode::Ed25519ph, &pubkey, contexts::LINK, message, &pure).is_err());
+    }
+
+    #[test]
+    fn test_mode_for_version() {
+        assert_eq!(SignatureMode::for_version(1), Some(SignatureMode::Ed25519));
+        assert_eq!(SignatureMode::for_version(2), Some(SignatureMode::Ed25519ph));
+        assert_eq!(SignatureMode::for_version(3), None);
+        assert_eq!(SignatureMode::Ed25519ph.version(), 2);
+    }
+
+    #[test]
+    fn test_decode_hex_secret_matches_hex_crate() {
+        for input in ["", "00", "ff", "0aF9", "deadBEEF", "0123456789abcdefABCDEF"] {
+            let ours = decode_hex_secret(input.as_bytes()).unwrap();
+            assert_eq!(ours.as_slice(), hex::decode(input).unwrap().as_slice(), "{}", input);
+        }
+        for bad in ["0", "zz", "0g", "g0", "@@", "`a", "::"] {
+            assert!(decode_hex_secret(bad.as_bytes()).is_err(), "{}", bad);
+        }
+        // Every byte value: valid iff the hex crate accepts it
+        for b in 0..=255u8 {
+            let input = [b, b'0'];
+            assert_eq!(decode_hex_secret(&input).is_ok(), hex::decode(input).is_ok(), "{:#x}", b);
+        }
+    }
+
+    #[test]
+    fn test_signing_key_from_hex_roundtrip() {
+        let (pubkey, key) = generate_keypair();
+        let encoded = hex::encode(key.to_bytes());
+        let loaded = signing_key_from_hex(encoded.as_bytes()).unwrap();
+        assert_eq!(pubkey_from_signing_key(&loaded), pubkey);
+        assert!(signing_key_from_hex(b"abcd").is_err());
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(b"same", b"same"));
+        assert!(!ct_eq(b"same", b"sane"));
+        assert!(!ct_eq(b"same", b"same-but-longer"));
+    }
+
     #[test]
     fn test_genesis_hash_length() {
         assert_eq!(GENESIS_HASH.len(), 64);
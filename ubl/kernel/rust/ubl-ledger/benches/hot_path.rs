@@ -0,0 +1,121 @@
+//! Commit hot path: allocations and time per commit
+//!
+//! Compares the previous shape of `signing_bytes` (growing a fresh Vec) and
+//! entry hashing (fresh hasher + hex String per entry) against the buffer-
+//! reusing variants. A counting global allocator reports allocations/op.
+//!
+//! Run with `cargo bench -p ubl-ledger --bench hot_path`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use ubl_ledger::{EntryHasher, GENESIS_HASH};
+use ubl_link::{IntentClass, LinkCommit};
+
+struct CountingAlloc;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const ITERATIONS: usize = 100_000;
+
+/// `signing_bytes` as it was: `Vec::new()` grown field by field
+fn legacy_signing_bytes(link: &LinkCommit) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.push(link.version);
+    bytes.extend_from_slice(link.container_id.as_bytes());
+    bytes.extend_from_slice(&link.expected_sequence.to_be_bytes());
+    bytes.extend_from_slice(link.previous_hash.as_bytes());
+    bytes.extend_from_slice(link.atom_hash.as_bytes());
+    bytes.push(link.intent_class.as_byte());
+    bytes.extend_from_slice(&link.physics_delta.to_be_bytes());
+    bytes
+}
+
+/// Entry hashing as it was: fresh hasher and hex String per entry
+fn legacy_entry_hash(link: &LinkCommit, sequence: u64, ts: i128) -> String {
+    let mut h = blake3::Hasher::new();
+    h.update(link.container_id.as_bytes());
+    h.update(&sequence.to_be_bytes());
+    h.update(link.atom_hash.as_bytes());
+    h.update(link.previous_hash.as_bytes());
+    h.update(&ts.to_be_bytes());
+    hex::encode(h.finalize().as_bytes())
+}
+
+fn measure(name: &str, mut f: impl FnMut(usize)) {
+    // Warm up so one-time buffer growth is not charged to the loop
+    f(0);
+    let allocs_before = ALLOCS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        f(i);
+    }
+    let elapsed = start.elapsed();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs_before;
+    println!(
+        "{:<32} {:>6.2} allocs/op {:>8.1} ns/op",
+        name,
+        allocs as f64 / ITERATIONS as f64,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let link = LinkCommit {
+        version: 2,
+        container_id: "C.Messenger".to_string(),
+        expected_sequence: 42,
+        previous_hash: GENESIS_HASH.to_string(),
+        atom_hash: "a".repeat(64),
+        intent_class: IntentClass::Observation,
+        physics_delta: 0,
+        pact: None,
+        author_pubkey: "pk".to_string(),
+        signature: "sig".to_string(),
+    };
+    let ts = 1_700_000_000_000i128;
+
+    println!("commit hot path, {} iterations", ITERATIONS);
+
+    measure("signing_bytes (legacy)", |_| {
+        black_box(legacy_signing_bytes(black_box(&link)));
+    });
+    measure("signing_bytes", |_| {
+        black_box(black_box(&link).signing_bytes());
+    });
+    let mut buf = Vec::new();
+    measure("signing_bytes_into (reused)", |_| {
+        buf.clear();
+        black_box(&link).signing_bytes_into(&mut buf);
+        black_box(&buf);
+    });
+
+    measure("entry hash (legacy)", |i| {
+        black_box(legacy_entry_hash(black_box(&link), i as u64, ts));
+    });
+    let mut hasher = EntryHasher::new();
+    measure("EntryHasher::hash_hex (reused)", |i| {
+        black_box(hasher.hash_hex(&link.container_id, i as u64, &link.atom_hash, &link.previous_hash, ts));
+    });
+}
@@ -0,0 +1,132 @@
+//! Signed checkpoints of a ledger
+//!
+//! Replaying millions of entries to get a container's balance and root is
+//! slow. A [`Checkpoint`] freezes `(sequence, last_hash, physical_balance,
+//! merkle_root)` at one point of the chain, with the Merkle peaks needed to
+//! keep computing roots and the Entropy totals, signed by the server key
+//! (v2, [`ubl_kernel::contexts::CHECKPOINT`]). [`crate::Ledger::from_checkpoint`]
+//! resumes from one after checking it, and only the entries after it are
+//! replayed.
+//!
+//! Issuing them every so many entries and storing them (they serialize to
+//! JSON) is up to the owner of the ledger; see [`crate::Ledger::checkpoint_due`].
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use ubl_kernel::{contexts, SignatureMode};
+
+use crate::{merkle, EntropyTotals, LedgerError, Result, GENESIS_HASH};
+
+/// Signed state of a ledger at `sequence`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Container ID
+    pub container_id: String,
+    /// Last sequence covered (0 for an empty ledger)
+    pub sequence: u64,
+    /// Entry hash at `sequence` (genesis hash when empty)
+    pub last_hash: String,
+    /// Sum of all deltas up to `sequence`
+    pub physical_balance: i128,
+    /// Entropy totals up to `sequence`
+    pub entropy: EntropyTotals,
+    /// Merkle root over entries `1..=sequence`
+    pub merkle_root: String,
+    /// Hex peaks of that tree, largest first (one per set bit of `sequence`)
+    pub peaks: Vec<String>,
+    /// When the checkpoint was issued (unix ms)
+    pub created_at_ms: i64,
+    /// Hex Ed25519 public key of the signer
+    pub signer_pubkey: String,
+    /// Hex Ed25519ph signature over [`Checkpoint::signing_bytes`]
+    pub signature: String,
+}
+
+impl Checkpoint {
+    /// Build and sign a checkpoint
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn sign(
+        key: &SigningKey,
+        container_id: &str,
+        sequence: u64,
+        last_hash: String,
+        physical_balance: i128,
+        entropy: EntropyTotals,
+        peaks: &[Vec<u8>],
+        created_at_ms: i64,
+    ) -> Result<Self> {
+        let mut checkpoint = Checkpoint {
+            container_id: container_id.to_string(),
+            sequence,
+            last_hash,
+            physical_balance,
+            entropy,
+            merkle_root: merkle::root_from_peaks(peaks),
+            peaks: peaks.iter().map(hex::encode).collect(),
+            created_at_ms,
+            signer_pubkey: ubl_kernel::pubkey_from_signing_key(key),
+            signature: String::new(),
+        };
+        checkpoint.signature = ubl_kernel::sign_with(
+            SignatureMode::Ed25519ph,
+            key,
+            contexts::CHECKPOINT,
+            &checkpoint.signing_bytes(),
+        )
+        .map_err(|e| LedgerError::InvalidCheckpoint(e.to_string()))?;
+        Ok(checkpoint)
+    }
+
+    /// Bytes the server signs: every field but the signature, one per line
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.container_id,
+            self.sequence,
+            self.last_hash,
+            self.physical_balance,
+            self.entropy.minted,
+            self.entropy.burned,
+            self.merkle_root,
+            self.peaks.join(","),
+            self.created_at_ms,
+            self.signer_pubkey,
+        )
+        .into_bytes()
+    }
+
+    /// Check the signature against `trusted_pubkey` and that the peaks add
+    /// up to the root; returns the decoded peaks
+    pub fn verify(&self, trusted_pubkey: &str) -> Result<Vec<Vec<u8>>> {
+        let invalid = |reason: &str| Err(LedgerError::InvalidCheckpoint(reason.to_string()));
+
+        if !ubl_kernel::ct_eq(self.signer_pubkey.as_bytes(), trusted_pubkey.as_bytes()) {
+            return invalid("signed by an untrusted key");
+        }
+        ubl_kernel::verify_with(
+            SignatureMode::Ed25519ph,
+            trusted_pubkey,
+            contexts::CHECKPOINT,
+            &self.signing_bytes(),
+            &self.signature,
+        )
+        .map_err(|_| LedgerError::InvalidCheckpoint("bad signature".to_string()))?;
+
+        let peaks = self
+            .peaks
+            .iter()
+            .map(hex::decode)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| LedgerError::InvalidCheckpoint("peak is not hex".to_string()))?;
+        if peaks.len() != self.sequence.count_ones() as usize {
+            return invalid("peak count does not match sequence");
+        }
+        if merkle::root_from_peaks(&peaks) != self.merkle_root {
+            return invalid("peaks do not add up to the merkle root");
+        }
+        if self.sequence == 0 && self.last_hash != GENESIS_HASH {
+            return invalid("empty checkpoint with a last hash");
+        }
+        Ok(peaks)
+    }
+}
@@ -7,15 +7,23 @@
 //! - Append-only (no UPDATE, no DELETE)
 //! - Hash chain (each entry links to previous)
 //! - State is always a projection of history
-//! - Merkle root for daily anchoring
+//! - Merkle root for daily anchoring, with per-entry inclusion proofs
+//! - Signed checkpoints, so a long chain reloads without a full replay
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
+pub mod checkpoint;
+pub mod merkle;
+
+use ed25519_dalek::SigningKey;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ubl_link::{IntentClass, LinkCommit, LinkReceipt};
 
+pub use checkpoint::Checkpoint;
+pub use merkle::{verify_proof, MerkleProof, ProofStep};
+
 /// Errors from ledger operations
 #[derive(Error, Debug)]
 pub enum LedgerError {
@@ -30,11 +38,27 @@ pub enum LedgerError {
     /// Container ID mismatch
     #[error("Container mismatch: expected {expected}, got {actual}")]
     ContainerMismatch { expected: String, actual: String },
+
+    /// Checkpoint signature or contents do not verify
+    #[error("Invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
 }
 
 /// Result type for ledger operations
 pub type Result<T> = std::result::Result<T, LedgerError>;
 
+impl ubl_errors::HasErrorCode for LedgerError {
+    fn error_code(&self) -> ubl_errors::ErrorCode {
+        use ubl_errors::ErrorCode;
+        match self {
+            LedgerError::SequenceMismatch { .. } => ErrorCode::SequenceMismatch,
+            LedgerError::RealityDrift { .. } => ErrorCode::RealityDrift,
+            LedgerError::ContainerMismatch { .. } => ErrorCode::InvalidTarget,
+            LedgerError::InvalidCheckpoint(_) => ErrorCode::InvalidSignature,
+        }
+    }
+}
+
 /// A single entry in the ledger
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LedgerEntry {
@@ -44,14 +68,27 @@ pub struct LedgerEntry {
     pub entry_hash: String,
     /// The original commit
     pub link: LinkCommit,
-    /// Unix timestamp of acceptance
+    /// Unix timestamp of acceptance (seconds; see [`LedgerEntry::timestamp_ms`])
     pub timestamp: i64,
 }
 
+impl LedgerEntry {
+    /// Acceptance time in unix milliseconds, the unit used by all APIs
+    pub fn timestamp_ms(&self) -> i64 {
+        self.timestamp.saturating_mul(1_000)
+    }
+}
+
 /// The immutable ledger for a container
+///
+/// A ledger resumed with [`Ledger::from_checkpoint`] holds only the entries
+/// after its checkpoint; totals and roots carry on from the checkpoint.
 pub struct Ledger {
     container_id: String,
     chain: Vec<LedgerEntry>,
+    base: Option<Checkpoint>,
+    base_peaks: Vec<Vec<u8>>,
+    last_checkpoint: u64,
 }
 
 /// Genesis hash constant
@@ -68,14 +105,67 @@ pub fn compute_entry_hash(
     previous_hash: &str,
     ts_unix_ms: i128,
 ) -> String {
-    use blake3::Hasher;
-    let mut h = Hasher::new();
-    h.update(container_id.as_bytes());
-    h.update(&sequence.to_be_bytes());
-    h.update(link_hash.as_bytes());
-    h.update(previous_hash.as_bytes());
-    h.update(&ts_unix_ms.to_be_bytes());
-    hex::encode(h.finalize().as_bytes())
+    EntryHasher::new()
+        .hash_hex(container_id, sequence, link_hash, previous_hash, ts_unix_ms)
+        .to_owned()
+}
+
+/// Reusable [`compute_entry_hash`] for loops over many entries
+///
+/// Keeps one BLAKE3 hasher (reset per entry) and a hex buffer, so hashing an
+/// entry allocates nothing; copy the `&str` out only when it must outlive
+/// the next call.
+pub struct EntryHasher {
+    hasher: blake3::Hasher,
+    hex: [u8; 64],
+}
+
+impl EntryHasher {
+    /// New hasher with an empty state
+    pub fn new() -> Self {
+        Self {
+            hasher: blake3::Hasher::new(),
+            hex: [0; 64],
+        }
+    }
+
+    /// Raw entry hash
+    pub fn hash(
+        &mut self,
+        container_id: &str,
+        sequence: u64,
+        link_hash: &str,
+        previous_hash: &str,
+        ts_unix_ms: i128,
+    ) -> [u8; 32] {
+        let h = self.hasher.reset();
+        h.update(container_id.as_bytes());
+        h.update(&sequence.to_be_bytes());
+        h.update(link_hash.as_bytes());
+        h.update(previous_hash.as_bytes());
+        h.update(&ts_unix_ms.to_be_bytes());
+        *h.finalize().as_bytes()
+    }
+
+    /// Hex entry hash, borrowed from the internal buffer
+    pub fn hash_hex(
+        &mut self,
+        container_id: &str,
+        sequence: u64,
+        link_hash: &str,
+        previous_hash: &str,
+        ts_unix_ms: i128,
+    ) -> &str {
+        let raw = self.hash(container_id, sequence, link_hash, previous_hash, ts_unix_ms);
+        hex::encode_to_slice(raw, &mut self.hex).expect("64-byte buffer holds a 32-byte hash");
+        std::str::from_utf8(&self.hex).expect("hex is ASCII")
+    }
+}
+
+impl Default for EntryHasher {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Ledger {
@@ -84,7 +174,83 @@ impl Ledger {
         Self {
             container_id,
             chain: Vec::new(),
+            base: None,
+            base_peaks: Vec::new(),
+            last_checkpoint: 0,
+        }
+    }
+
+    /// Resume from a checkpoint signed by `trusted_pubkey`, then replay the
+    /// entries after it (`tail`, in sequence order)
+    ///
+    /// Each tail entry must carry the next sequence and chain onto the
+    /// previous hash, starting from the checkpoint's.
+    pub fn from_checkpoint(
+        checkpoint: Checkpoint,
+        trusted_pubkey: &str,
+        tail: impl IntoIterator<Item = LedgerEntry>,
+    ) -> Result<Self> {
+        let base_peaks = checkpoint.verify(trusted_pubkey)?;
+        let mut ledger = Self {
+            container_id: checkpoint.container_id.clone(),
+            chain: Vec::new(),
+            last_checkpoint: checkpoint.sequence,
+            base: Some(checkpoint),
+            base_peaks,
+        };
+        for entry in tail {
+            if entry.link.container_id != ledger.container_id {
+                return Err(LedgerError::ContainerMismatch {
+                    expected: ledger.container_id.clone(),
+                    actual: entry.link.container_id,
+                });
+            }
+            if entry.sequence != ledger.next_sequence() {
+                return Err(LedgerError::SequenceMismatch {
+                    expected: ledger.next_sequence(),
+                    actual: entry.sequence,
+                });
+            }
+            if entry.link.previous_hash != ledger.last_hash() {
+                return Err(LedgerError::RealityDrift {
+                    expected: ledger.last_hash(),
+                    actual: entry.link.previous_hash,
+                });
+            }
+            ledger.chain.push(entry);
         }
+        Ok(ledger)
+    }
+
+    /// Checkpoint this ledger was resumed from, if any
+    pub fn base_checkpoint(&self) -> Option<&Checkpoint> {
+        self.base.as_ref()
+    }
+
+    /// Sign a checkpoint of the current state with the server key
+    pub fn checkpoint(&mut self, key: &SigningKey, created_at_ms: i64) -> Result<Checkpoint> {
+        let checkpoint = Checkpoint::sign(
+            key,
+            &self.container_id,
+            self.current_sequence(),
+            self.last_hash(),
+            self.physical_balance(),
+            self.entropy_totals(),
+            &self.peaks(),
+            created_at_ms,
+        )?;
+        self.last_checkpoint = checkpoint.sequence;
+        Ok(checkpoint)
+    }
+
+    /// At least `every` entries appended since the last checkpoint issued or
+    /// resumed from
+    pub fn checkpoint_due(&self, every: u64) -> bool {
+        every > 0 && self.current_sequence() >= self.last_checkpoint.saturating_add(every)
+    }
+
+    fn base_sequence(&self) -> u64 {
+        self.base.as_ref().map_or(0, |c| c.sequence)
     }
 
     /// Get the container ID
@@ -94,25 +260,36 @@ impl Ledger {
 
     /// Get the hash of the last entry (or genesis hash)
     pub fn last_hash(&self) -> String {
-        self.chain
-            .last()
-            .map(|e| e.entry_hash.clone())
-            .unwrap_or_else(|| GENESIS_HASH.to_string())
+        match (self.chain.last(), &self.base) {
+            (Some(entry), _) => entry.entry_hash.clone(),
+            (None, Some(base)) => base.last_hash.clone(),
+            (None, None) => GENESIS_HASH.to_string(),
+        }
     }
 
     /// Get the next expected sequence number
     pub fn next_sequence(&self) -> u64 {
-        (self.chain.len() as u64) + 1
+        self.current_sequence() + 1
     }
 
     /// Get the current sequence (0 if empty)
     pub fn current_sequence(&self) -> u64 {
-        self.chain.len() as u64
+        self.base_sequence() + self.chain.len() as u64
     }
 
     /// Get the physical balance (sum of all deltas)
     pub fn physical_balance(&self) -> i128 {
-        self.chain.iter().map(|e| e.link.physics_delta).sum()
+        let base = self.base.as_ref().map_or(0, |c| c.physical_balance);
+        base + self.chain.iter().map(|e| e.link.physics_delta).sum::<i128>()
+    }
+
+    /// Get the supply created and destroyed by Entropy commits
+    pub fn entropy_totals(&self) -> EntropyTotals {
+        let mut totals = self.base.as_ref().map(|c| c.entropy).unwrap_or_default();
+        for entry in &self.chain {
+            totals.record(entry.link.intent_class, entry.link.physics_delta);
+        }
+        totals
     }
 
     /// Append a validated commit to the ledger
@@ -141,26 +318,86 @@ impl Ledger {
         }
     }
 
-    /// Get all entries in the chain
+    /// Get all entries held in memory (those after the checkpoint, if resumed)
     pub fn entries(&self) -> &[LedgerEntry] {
         &self.chain
     }
 
-    /// Get an entry by sequence number
+    /// Get an entry by sequence number; None for entries behind a checkpoint
     pub fn get_entry(&self, sequence: u64) -> Option<&LedgerEntry> {
-        if sequence == 0 || sequence > self.chain.len() as u64 {
+        let base = self.base_sequence();
+        if sequence <= base || sequence > self.current_sequence() {
             return None;
         }
-        Some(&self.chain[(sequence - 1) as usize])
+        Some(&self.chain[(sequence - base - 1) as usize])
     }
 
-    /// Calculate merkle root of all entries (simplified version)
+    /// Merkle root over all entry hashes (see [`merkle`])
     pub fn merkle_root_hex(&self) -> String {
-        if self.chain.is_empty() {
-            return "0".repeat(64);
+        merkle::root_from_peaks(&self.peaks())
+    }
+
+    /// Inclusion proof of the entry at `sequence` against
+    /// [`Ledger::merkle_root_hex`]; check it with [`verify_proof`]
+    ///
+    /// Needs the whole chain: a ledger resumed from a checkpoint proves
+    /// nothing, ask the store that keeps every entry.
+    pub fn prove(&self, sequence: u64) -> Option<MerkleProof> {
+        if self.base.is_some() {
+            return None;
+        }
+        let entry = self.get_entry(sequence)?;
+        let steps = merkle::prove(self.leaves(), (sequence - 1) as usize)?;
+        Some(MerkleProof {
+            sequence,
+            entry_hash: entry.entry_hash.clone(),
+            leaf_count: self.current_sequence(),
+            steps,
+        })
+    }
+
+    fn leaves(&self) -> Vec<Vec<u8>> {
+        self.chain.iter().map(|e| merkle::leaf(&e.entry_hash)).collect()
+    }
+
+    fn peaks(&self) -> Vec<Vec<u8>> {
+        let mut peaks = self.base_peaks.clone();
+        let base = self.base_sequence();
+        for (i, entry) in self.chain.iter().enumerate() {
+            merkle::push_peak(&mut peaks, base + i as u64, merkle::leaf(&entry.entry_hash));
+        }
+        peaks
+    }
+}
+
+/// Cumulative Entropy accounting of a container
+///
+/// Only Entropy commits change supply (SPEC-UBL-CORE §6.2): a positive delta
+/// mints, a negative one burns. Conservation moves are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntropyTotals {
+    /// Sum of positive Entropy deltas
+    pub minted: i128,
+    /// Sum of |negative Entropy deltas|
+    pub burned: i128,
+}
+
+impl EntropyTotals {
+    /// Account one committed delta
+    pub fn record(&mut self, intent_class: IntentClass, physics_delta: i128) {
+        if intent_class != IntentClass::Entropy {
+            return;
+        }
+        if physics_delta > 0 {
+            self.minted = self.minted.saturating_add(physics_delta);
+        } else {
+            self.burned = self.burned.saturating_add(physics_delta.saturating_neg());
         }
-        // Simplified: return last hash
-        self.last_hash()
+    }
+
+    /// Net supply change (minted - burned)
+    pub fn net(&self) -> i128 {
+        self.minted.saturating_sub(self.burned)
     }
 }
 
@@ -175,6 +412,8 @@ pub struct LedgerState {
     pub last_hash: String,
     /// Physical balance
     pub physical_balance: i128,
+    /// Supply minted and burned by Entropy commits
+    pub entropy: EntropyTotals,
     /// Merkle root
     pub merkle_root: String,
 }
@@ -186,6 +425,7 @@ impl From<&Ledger> for LedgerState {
             sequence: ledger.current_sequence(),
             last_hash: ledger.last_hash(),
             physical_balance: ledger.physical_balance(),
+            entropy: ledger.entropy_totals(),
             merkle_root: ledger.merkle_root_hex(),
         }
     }
@@ -210,6 +450,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_entry_hasher_matches_compute_entry_hash() {
+        let mut hasher = EntryHasher::new();
+        for seq in 1..4u64 {
+            let expected = compute_entry_hash("C.Jobs", seq, "link", GENESIS_HASH, 1_700_000_000_000);
+            assert_eq!(hasher.hash_hex("C.Jobs", seq, "link", GENESIS_HASH, 1_700_000_000_000), expected);
+        }
+        assert_ne!(
+            hasher.hash("C.Jobs", 1, "link", GENESIS_HASH, 1),
+            hasher.hash("C.Jobs", 2, "link", GENESIS_HASH, 1)
+        );
+    }
+
     #[test]
     fn test_new_ledger() {
         let ledger = Ledger::new("wallet_alice".to_string());
@@ -256,5 +509,125 @@ mod tests {
         assert_eq!(state.container_id, "wallet");
         assert_eq!(state.sequence, 1);
         assert_eq!(state.physical_balance, 50);
+        // Conservation moves don't change supply
+        assert_eq!(state.entropy, EntropyTotals::default());
+    }
+
+    #[test]
+    fn test_merkle_proofs() {
+        let mut ledger = Ledger::new("wallet".to_string());
+        assert_eq!(ledger.merkle_root_hex(), merkle::EMPTY_ROOT);
+        assert!(ledger.prove(1).is_none());
+
+        let mut roots = Vec::new();
+        for seq in 1..=7u64 {
+            let entry_hash = compute_entry_hash("wallet", seq, "atom", &ledger.last_hash(), seq as i128);
+            ledger.append(make_commit(seq, &ledger.last_hash(), 1), entry_hash);
+            let root = ledger.merkle_root_hex();
+            for proven in 1..=seq {
+                let proof = ledger.prove(proven).unwrap();
+                assert!(verify_proof(&proof, &root), "n={} seq={}", seq, proven);
+            }
+            roots.push(root);
+        }
+        // One entry: the root is its hash; every append moves the root
+        assert_eq!(roots[0], ledger.get_entry(1).unwrap().entry_hash);
+        assert_ne!(roots[5], roots[6]);
+        assert_ne!(roots[6], ledger.last_hash());
+
+        // A proof does not hold for another entry, a stale root or a tampered step
+        let mut proof = ledger.prove(3).unwrap();
+        assert_eq!(proof.leaf_count, 7);
+        assert!(!verify_proof(&proof, &roots[5]));
+        proof.entry_hash = ledger.get_entry(4).unwrap().entry_hash.clone();
+        assert!(!verify_proof(&proof, &roots[6]));
+        let mut proof = ledger.prove(3).unwrap();
+        proof.steps[0].sibling_is_right = !proof.steps[0].sibling_is_right;
+        assert!(!verify_proof(&proof, &roots[6]));
+        assert!(ledger.prove(8).is_none());
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let (pubkey, key) = ubl_kernel::generate_keypair();
+        let mut full = Ledger::new("test".to_string());
+        let mut checkpoint = None;
+        for seq in 1..=11u64 {
+            let entry_hash = compute_entry_hash("test", seq, "atom", &full.last_hash(), seq as i128);
+            let mut commit = make_commit(seq, &full.last_hash(), seq as i128 * 10);
+            if seq % 3 == 0 {
+                commit.intent_class = IntentClass::Entropy;
+            }
+            full.append(commit, entry_hash);
+            // Peaks give the same root as the whole tree
+            assert_eq!(full.merkle_root_hex(), merkle::root_hex(full.leaves()), "n={}", seq);
+            if full.checkpoint_due(7) {
+                checkpoint = Some(full.checkpoint(&key, 1_700_000_000_000).unwrap());
+            }
+        }
+        let checkpoint = checkpoint.unwrap();
+        assert_eq!((checkpoint.sequence, checkpoint.peaks.len()), (7, 3));
+        assert!(!full.checkpoint_due(7));
+
+        let tail: Vec<LedgerEntry> = full.entries()[7..].to_vec();
+        let mut resumed = Ledger::from_checkpoint(checkpoint.clone(), &pubkey, tail.clone()).unwrap();
+        assert_eq!(resumed.current_sequence(), 11);
+        assert!(resumed.get_entry(7).is_none());
+        assert_eq!(resumed.get_entry(8).unwrap().entry_hash, full.get_entry(8).unwrap().entry_hash);
+        let (a, b) = (LedgerState::from(&resumed), LedgerState::from(&full));
+        assert_eq!(
+            (a.sequence, a.last_hash, a.physical_balance, a.entropy, a.merkle_root),
+            (b.sequence, b.last_hash, b.physical_balance, b.entropy, b.merkle_root)
+        );
+        assert!(resumed.prove(9).is_none());
+
+        // Appends after resuming keep the same root as the full ledger
+        let entry_hash = compute_entry_hash("test", 12, "atom", &full.last_hash(), 12);
+        full.append(make_commit(12, &full.last_hash(), 1), entry_hash.clone());
+        resumed.append(make_commit(12, &resumed.last_hash(), 1), entry_hash);
+        assert_eq!(resumed.merkle_root_hex(), full.merkle_root_hex());
+
+        // Tampered, foreign or badly chained input is refused
+        let mut forged = checkpoint.clone();
+        forged.physical_balance += 1;
+        assert!(matches!(
+            Ledger::from_checkpoint(forged, &pubkey, Vec::new()),
+            Err(LedgerError::InvalidCheckpoint(_))
+        ));
+        let (other, _) = ubl_kernel::generate_keypair();
+        assert!(Ledger::from_checkpoint(checkpoint.clone(), &other, Vec::new()).is_err());
+        assert!(matches!(
+            Ledger::from_checkpoint(checkpoint.clone(), &pubkey, tail[1..].to_vec()),
+            Err(LedgerError::SequenceMismatch { expected: 8, actual: 9 })
+        ));
+        let mut broken = tail.clone();
+        broken[1].link.previous_hash = GENESIS_HASH.to_string();
+        assert!(matches!(
+            Ledger::from_checkpoint(checkpoint, &pubkey, broken),
+            Err(LedgerError::RealityDrift { .. })
+        ));
+    }
+
+    #[test]
+    fn test_entropy_totals() {
+        let mut ledger = Ledger::new("wallet".to_string());
+        let mut prev = GENESIS_HASH.to_string();
+        for (seq, (class, delta)) in [
+            (IntentClass::Entropy, 1_000),
+            (IntentClass::Conservation, -400),
+            (IntentClass::Entropy, -250),
+            (IntentClass::Entropy, 50),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut commit = make_commit(seq as u64 + 1, &prev, delta);
+            commit.intent_class = class;
+            prev = ledger.append(commit, format!("hash{}", seq)).entry_hash;
+        }
+
+        let totals = ledger.entropy_totals();
+        assert_eq!((totals.minted, totals.burned, totals.net()), (1_050, 250, 800));
+        assert_eq!(ledger.physical_balance(), 400);
     }
 }
\ No newline at end of file
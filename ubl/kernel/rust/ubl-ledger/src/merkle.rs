@@ -0,0 +1,142 @@
+//! Merkle tree over a container's entry hashes
+//!
+//! Leaves are the entry hashes in sequence order (hex-decoded; a hash that
+//! is not hex is taken as its UTF-8 bytes). Nodes are
+//! `ubl_kernel::hash_merkle(left, right)`; an odd node is promoted unchanged
+//! to the next level, as in observation batches. The root is what gets
+//! anchored daily; a [`MerkleProof`] lets an auditor holding one entry and
+//! the anchored root check that entry without the rest of the chain.
+//!
+//! Because odd nodes are promoted, the tree over `n` leaves is the right fold
+//! of its peaks: the roots of the perfect subtrees given by the bits of `n`,
+//! largest first. Keeping only the peaks (see [`push_peak`]) is enough to go
+//! on appending and computing roots, which is what checkpoints store.
+
+use serde::{Deserialize, Serialize};
+
+/// Root of an empty ledger
+pub const EMPTY_ROOT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One level of an inclusion proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    /// Hex hash of the sibling node
+    pub sibling: String,
+    /// The sibling sits to the right of the running hash
+    pub sibling_is_right: bool,
+}
+
+/// Inclusion proof of one entry in the tree of a ledger
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Sequence of the proven entry
+    pub sequence: u64,
+    /// Its entry hash (the leaf)
+    pub entry_hash: String,
+    /// Entries in the tree the proof was made against
+    pub leaf_count: u64,
+    /// Siblings from the leaf up to the root; promoted levels add no step
+    pub steps: Vec<ProofStep>,
+}
+
+/// Leaf bytes of an entry hash
+pub fn leaf(entry_hash: &str) -> Vec<u8> {
+    hex::decode(entry_hash).unwrap_or_else(|_| entry_hash.as_bytes().to_vec())
+}
+
+/// Hex root over `leaves`; [`EMPTY_ROOT`] when there are none
+pub fn root_hex(leaves: Vec<Vec<u8>>) -> String {
+    let mut level = leaves;
+    if level.is_empty() {
+        return EMPTY_ROOT.to_string();
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    hex::encode(&level[0])
+}
+
+/// Proof for leaf `index` (0-based); None when out of range
+pub fn prove(leaves: Vec<Vec<u8>>, index: usize) -> Option<Vec<ProofStep>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut steps = Vec::new();
+    let mut level = leaves;
+    let mut pos = index;
+    while level.len() > 1 {
+        let sibling = pos ^ 1;
+        if let Some(node) = level.get(sibling) {
+            steps.push(ProofStep {
+                sibling: hex::encode(node),
+                sibling_is_right: sibling > pos,
+            });
+        }
+        level = next_level(&level);
+        pos /= 2;
+    }
+    Some(steps)
+}
+
+/// Check `proof` against an anchored hex `root`
+pub fn verify_proof(proof: &MerkleProof, root: &str) -> bool {
+    let Ok(root) = hex::decode(root) else {
+        return false;
+    };
+    let mut acc = leaf(&proof.entry_hash);
+    for step in &proof.steps {
+        let Ok(sibling) = hex::decode(&step.sibling) else {
+            return false;
+        };
+        acc = if step.sibling_is_right {
+            ubl_kernel::hash_merkle(&acc, &sibling)
+        } else {
+            ubl_kernel::hash_merkle(&sibling, &acc)
+        };
+    }
+    ubl_kernel::ct_eq(&acc, &root)
+}
+
+/// Peaks of the tree over `leaves`, largest subtree first
+pub fn peaks(leaves: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+    let mut peaks = Vec::new();
+    for (count, leaf) in leaves.into_iter().enumerate() {
+        push_peak(&mut peaks, count as u64, leaf);
+    }
+    peaks
+}
+
+/// Append one leaf to the peaks of a tree that held `count` leaves
+pub fn push_peak(peaks: &mut Vec<Vec<u8>>, count: u64, leaf: Vec<u8>) {
+    let mut node = leaf;
+    let mut carry = count;
+    while carry & 1 == 1 {
+        let left = peaks.pop().expect("one peak per set bit of the leaf count");
+        node = ubl_kernel::hash_merkle(&left, &node);
+        carry >>= 1;
+    }
+    peaks.push(node);
+}
+
+/// Hex root of a tree given its peaks; equal to [`root_hex`] over its leaves
+pub fn root_from_peaks(peaks: &[Vec<u8>]) -> String {
+    let Some((last, rest)) = peaks.split_last() else {
+        return EMPTY_ROOT.to_string();
+    };
+    let root = rest
+        .iter()
+        .rev()
+        .fold(last.clone(), |acc, peak| ubl_kernel::hash_merkle(peak, &acc));
+    hex::encode(root)
+}
+
+fn next_level(level: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => ubl_kernel::hash_merkle(left, right),
+            [single] => single.clone(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
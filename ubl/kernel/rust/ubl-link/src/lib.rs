@@ -66,7 +66,7 @@ pub struct PactProof {
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinkCommit {
-    /// SPEC 3.2: Protocol version (must be 1)
+    /// SPEC 3.2: Protocol version (1 = Ed25519, 2 = Ed25519ph with context "ubl:link:v2")
     pub version: u8,
     
     /// SPEC 3.2: Container ID (Hash32 hex)
@@ -104,31 +104,44 @@ impl LinkCommit {
     /// Generate the bytes that must be signed (SPEC-UBL-LINK v1.0 §5)
     /// CRITICAL: Does NOT include pact, author_pubkey, or signature
     pub fn signing_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        
+        let mut bytes = Vec::with_capacity(self.signing_len());
+        self.signing_bytes_into(&mut bytes);
+        bytes
+    }
+
+    /// Exact length of [`LinkCommit::signing_bytes`]
+    pub fn signing_len(&self) -> usize {
+        1 + self.container_id.len() + 8 + self.previous_hash.len() + self.atom_hash.len() + 1 + 16
+    }
+
+    /// Append the signing bytes to `buf` without allocating a new Vec.
+    /// On the hot path keep one buffer and `clear()` it between commits;
+    /// the layout is identical to [`LinkCommit::signing_bytes`].
+    pub fn signing_bytes_into(&self, buf: &mut Vec<u8>) {
+        buf.reserve(self.signing_len());
+
         // Version (1 byte)
-        bytes.push(self.version);
+        buf.push(self.version);
         
         // Container ID
-        bytes.extend_from_slice(self.container_id.as_bytes());
+        buf.extend_from_slice(self.container_id.as_bytes());
         
         // Expected sequence (8 bytes, big-endian)
-        bytes.extend_from_slice(&self.expected_sequence.to_be_bytes());
+        buf.extend_from_slice(&self.expected_sequence.to_be_bytes());
         
         // Previous hash
-        bytes.extend_from_slice(self.previous_hash.as_bytes());
+        buf.extend_from_slice(self.previous_hash.as_bytes());
         
         // Atom hash
-        bytes.extend_from_slice(self.atom_hash.as_bytes());
+        buf.extend_from_slice(self.atom_hash.as_bytes());
         
         // Intent class (1 byte)
-        bytes.push(self.intent_class.as_byte());
+        buf.push(self.intent_class.as_byte());
         
         // Physics delta (16 bytes, big-endian for i128)
-        bytes.extend_from_slice(&self.physics_delta.to_be_bytes());
+        buf.extend_from_slice(&self.physics_delta.to_be_bytes());
         
         // STOP HERE - do NOT include pact, author_pubkey, or signature
-        bytes
     }
 }
 
@@ -181,6 +194,35 @@ mod tests {
         assert_eq!(bytes1, bytes2);
     }
 
+    #[test]
+    fn test_signing_bytes_into_reuses_buffer() {
+        let mut commit = LinkCommit {
+            version: 2,
+            container_id: "C.Jobs".to_string(),
+            expected_sequence: 7,
+            previous_hash: "prev".to_string(),
+            atom_hash: "atom".to_string(),
+            intent_class: IntentClass::Entropy,
+            physics_delta: 5,
+            author_pubkey: "pk".to_string(),
+            signature: "sig".to_string(),
+            pact: None,
+        };
+
+        let mut buf = Vec::new();
+        commit.signing_bytes_into(&mut buf);
+        assert_eq!(buf, commit.signing_bytes());
+        assert_eq!(buf.len(), commit.signing_len());
+
+        // A shorter commit fits the buffer already grown by the first one
+        let capacity = buf.capacity();
+        commit.container_id = "C.Job".to_string();
+        buf.clear();
+        commit.signing_bytes_into(&mut buf);
+        assert_eq!(buf, commit.signing_bytes());
+        assert_eq!(buf.capacity(), capacity);
+    }
+
     #[test]
     fn test_serialization() {
         let commit = LinkCommit {
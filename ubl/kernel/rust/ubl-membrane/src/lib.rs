@@ -12,22 +12,35 @@
 //!
 //! ## Validations
 //! - V1: Version check
-//! - V2: Container ID match
-//! - V3: Signature verification
+//! - V2: Signature verification
+//! - V3: Container ID match
 //! - V4: Reality drift (previous hash)
 //! - V5: Sequence continuity
-//! - V6: Atom hash format
-//! - V7: Physics invariants (conservation, entropy)
+//! - V6: Atom hash format, physics invariants (observation, conservation)
+//! - V7: Entropy pact
+//! - V8: Evolution pact
+//! - V9: Pact proof (threshold, signers, window, scope), with pacts from a
+//!   [`PactResolver`]
+//!
+//! Each is a [`Validator`] in [`validators`]; a [`Membrane`] runs them in
+//! order, stops at the first rejection, and can carry more validators supplied
+//! by the caller (tenant scoping, rate limits) after the standard ones.
+//! V1..V8 only check that a pact is attached; use [`Membrane::with_pacts`] or
+//! [`validate_with_pacts`] to verify it too.
 //!
 //! ## Performance Target
-//! All validations must complete in < 1ms
+//! All validations must complete in < 1ms; [`Membrane::run`] times each one.
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
+pub mod validators;
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
 use thiserror::Error;
-use ubl_link::{IntentClass, LinkCommit};
-use ubl_kernel;
+use ubl_link::LinkCommit;
 
 /// Errors that can occur during membrane validation
 /// SPEC-UBL-MEMBRANE v1.0: Canonical error names (8 total)
@@ -55,7 +68,10 @@ pub enum MembraneError {
 
     /// V6: Physics violation (includes conservation, observation, etc.)
     #[error("V6: Physics violation: {reason}")]
-    PhysicsViolation { reason: String },
+    PhysicsViolation {
+        /// Which invariant broke
+        reason: String,
+    },
 
     /// V7: Pact violation
     #[error("V7: Pact violation")]
@@ -64,11 +80,57 @@ pub enum MembraneError {
     /// V8: Unauthorized evolution
     #[error("V8: Unauthorized evolution")]
     UnauthorizedEvolution,
+
+    /// Refused by a validator supplied by the caller (not one of V1..V8)
+    #[error("{validator}: {reason}")]
+    Rejected {
+        /// [`Validator::name`] of the validator
+        validator: &'static str,
+        /// Catalog code the caller reports
+        code: ubl_errors::ErrorCode,
+        /// Why it refused
+        reason: String,
+    },
+}
+
+impl MembraneError {
+    /// Canonical error name (SPEC-UBL-MEMBRANE v1.0 §8); for a caller's
+    /// validator, its [`Validator::name`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            MembraneError::InvalidVersion => "InvalidVersion",
+            MembraneError::InvalidSignature => "InvalidSignature",
+            MembraneError::InvalidTarget => "InvalidTarget",
+            MembraneError::RealityDrift => "RealityDrift",
+            MembraneError::SequenceMismatch => "SequenceMismatch",
+            MembraneError::PhysicsViolation { .. } => "PhysicsViolation",
+            MembraneError::PactViolation => "PactViolation",
+            MembraneError::UnauthorizedEvolution => "UnauthorizedEvolution",
+            MembraneError::Rejected { validator, .. } => validator,
+        }
+    }
 }
 
 /// Result type for membrane validation
 pub type Result<T> = std::result::Result<T, MembraneError>;
 
+impl ubl_errors::HasErrorCode for MembraneError {
+    fn error_code(&self) -> ubl_errors::ErrorCode {
+        use ubl_errors::ErrorCode;
+        match self {
+            MembraneError::InvalidVersion => ErrorCode::InvalidVersion,
+            MembraneError::InvalidSignature => ErrorCode::InvalidSignature,
+            MembraneError::InvalidTarget => ErrorCode::InvalidTarget,
+            MembraneError::RealityDrift => ErrorCode::RealityDrift,
+            MembraneError::SequenceMismatch => ErrorCode::SequenceMismatch,
+            MembraneError::PhysicsViolation { .. } => ErrorCode::PhysicsViolation,
+            MembraneError::PactViolation => ErrorCode::PactViolation,
+            MembraneError::UnauthorizedEvolution => ErrorCode::UnauthorizedEvolution,
+            MembraneError::Rejected { code, .. } => *code,
+        }
+    }
+}
+
 /// The decision from the membrane
 #[derive(Debug, Clone)]
 pub enum Decision {
@@ -97,84 +159,162 @@ pub struct LedgerState {
     pub physical_balance: i128,
 }
 
-/// Validate a link commit (SPEC-UBL-MEMBRANE v1.0 §6)
-/// Full validation including cryptographic signature verification
-pub fn validate(link: &LinkCommit, state: &LedgerState) -> Result<()> {
-    // V1 - Version check
-    if link.version != 1 {
-        return Err(MembraneError::InvalidVersion);
+/// What a validator sees of a commit
+pub struct Commit<'a> {
+    /// The link being validated
+    pub link: &'a LinkCommit,
+    /// The bytes the author signed
+    pub signing_bytes: &'a [u8],
+    /// The ledger it is appended to
+    pub state: &'a LedgerState,
+}
+
+/// Where V9 finds the pact a proof refers to
+pub trait PactResolver: Send + Sync {
+    /// The pact registered under `pact_id`, if any
+    fn resolve(&self, pact_id: &str) -> Option<ubl_pact::Pact>;
+}
+
+impl PactResolver for ubl_pact::PactRegistry {
+    fn resolve(&self, pact_id: &str) -> Option<ubl_pact::Pact> {
+        self.get(pact_id).cloned()
+    }
+}
+
+impl<T: PactResolver + ?Sized> PactResolver for std::sync::Arc<T> {
+    fn resolve(&self, pact_id: &str) -> Option<ubl_pact::Pact> {
+        (**self).resolve(pact_id)
     }
+}
+
+/// One step of the membrane pipeline
+///
+/// Validators are deterministic and semantically blind: they look at the
+/// link and the ledger state, never at the atom.
+pub trait Validator: Send + Sync {
+    /// Stable name, used in timings and [`MembraneError::Rejected`]
+    fn name(&self) -> &'static str;
 
-    // V2 - Signature verification (SPEC-UBL-MEMBRANE V2)
-    // CRITICAL: This is the core security check
-    let signing_bytes = link.signing_bytes();
-    ubl_kernel::verify(&link.author_pubkey, &signing_bytes, &link.signature)
-        .map_err(|_| MembraneError::InvalidSignature)?;
+    /// Accept the commit or say why not
+    fn check(&self, commit: &Commit<'_>) -> Result<()>;
+}
 
-    // V3 - Container ID match (InvalidTarget)
-    if link.container_id != state.container_id {
-        return Err(MembraneError::InvalidTarget);
+/// Time one validator took
+#[derive(Debug, Clone)]
+pub struct Timing {
+    /// [`Validator::name`]
+    pub validator: &'static str,
+    /// Wall time of its `check`
+    pub elapsed: Duration,
+}
+
+/// Outcome of [`Membrane::run`]
+#[derive(Debug)]
+pub struct Report {
+    /// First rejection, if any
+    pub result: Result<()>,
+    /// Validators that ran, in order (up to and including the one that refused)
+    pub timings: Vec<Timing>,
+}
+
+impl Report {
+    /// Total time of the validators that ran
+    pub fn elapsed(&self) -> Duration {
+        self.timings.iter().map(|t| t.elapsed).sum()
     }
+}
+
+/// Ordered validation pipeline (SPEC-UBL-MEMBRANE v1.0 §6)
+pub struct Membrane {
+    validators: Vec<Box<dyn Validator>>,
+}
 
-    // V4 - Reality drift (causal chain)
-    if link.previous_hash != state.last_hash {
-        return Err(MembraneError::RealityDrift);
+impl Membrane {
+    /// V1..V9: the standard pipeline, verifying pact proofs against `pacts`
+    pub fn with_pacts(pacts: impl PactResolver + 'static) -> Self {
+        Self::standard().with(validators::PactProof::new(pacts))
     }
 
-    // V5 - Sequence continuity
-    if link.expected_sequence != state.next_sequence {
-        return Err(MembraneError::SequenceMismatch);
+    /// A pipeline of exactly these validators
+    pub fn new(validators: Vec<Box<dyn Validator>>) -> Self {
+        Self { validators }
     }
 
-    // V6 - Atom hash format (should be 64 hex chars = 32 bytes)
-    if link.atom_hash.len() != 64 || hex::decode(&link.atom_hash).is_err() {
-        // Allow shorter hashes for testing
-        if link.atom_hash.len() < 4 {
-            return Err(MembraneError::InvalidSignature);
-        }
+    /// V1..V8, in spec order
+    pub fn standard() -> Self {
+        use validators::*;
+        Self::new(vec![
+            Box::new(Version),
+            Box::new(Signature),
+            Box::new(Target),
+            Box::new(Causality),
+            Box::new(Sequence),
+            Box::new(Physics),
+            Box::new(Pact),
+            Box::new(Evolution),
+        ])
     }
 
-    // V6 - Physics invariants
-    match link.intent_class {
-        IntentClass::Observation => {
-            // Observations must have zero delta
-            if link.physics_delta != 0 {
-                return Err(MembraneError::PhysicsViolation {
-                    reason: format!("Observation must have delta=0, got {}", link.physics_delta)
-                });
-            }
-        }
-        IntentClass::Conservation => {
-            // Conservation: balance must remain >= 0
-            let resulting_balance = state.physical_balance + link.physics_delta;
-            if resulting_balance < 0 {
-                return Err(MembraneError::PhysicsViolation {
-                    reason: format!("Conservation requires balance >= 0, would be {}", resulting_balance)
-                });
-            }
-        }
-        IntentClass::Entropy => {
-            // Entropy: requires pact if delta != 0
-            if link.physics_delta != 0 && link.pact.is_none() {
-                return Err(MembraneError::PactViolation);
-            }
-        }
-        IntentClass::Evolution => {
-            // Evolution REQUIRES pact (L5 risk level)
-            // Evolution changes the rules themselves - must be authorized
-            if link.pact.is_none() {
-                return Err(MembraneError::UnauthorizedEvolution);
-            }
-            // Delta must be 0 for Evolution
-            if link.physics_delta != 0 {
-                return Err(MembraneError::PhysicsViolation {
-                    reason: format!("Evolution must have delta=0, got {}", link.physics_delta)
-                });
+    /// Append a validator, run after the ones already there
+    pub fn with(mut self, validator: impl Validator + 'static) -> Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Names of the validators, in order
+    pub fn names(&self) -> Vec<&'static str> {
+        self.validators.iter().map(|v| v.name()).collect()
+    }
+
+    /// Validate a link signed over [`LinkCommit::signing_bytes`]
+    pub fn validate(&self, link: &LinkCommit, state: &LedgerState) -> Result<()> {
+        self.validate_signed(link, &link.signing_bytes(), state)
+    }
+
+    /// Validate a link whose author signed `signing_bytes` (callers with
+    /// their own wire format for links)
+    pub fn validate_signed(&self, link: &LinkCommit, signing_bytes: &[u8], state: &LedgerState) -> Result<()> {
+        let commit = Commit { link, signing_bytes, state };
+        self.validators.iter().try_for_each(|v| v.check(&commit))
+    }
+
+    /// [`Membrane::validate_signed`], timing each validator
+    pub fn run(&self, link: &LinkCommit, signing_bytes: &[u8], state: &LedgerState) -> Report {
+        let commit = Commit { link, signing_bytes, state };
+        let mut timings = Vec::with_capacity(self.validators.len());
+        for validator in &self.validators {
+            let started = Instant::now();
+            let result = validator.check(&commit);
+            timings.push(Timing { validator: validator.name(), elapsed: started.elapsed() });
+            if result.is_err() {
+                return Report { result, timings };
             }
         }
+        Report { result: Ok(()), timings }
     }
+}
+
+impl Default for Membrane {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+fn standard() -> &'static Membrane {
+    static STANDARD: OnceLock<Membrane> = OnceLock::new();
+    STANDARD.get_or_init(Membrane::standard)
+}
+
+/// Validate a link commit (SPEC-UBL-MEMBRANE v1.0 §6)
+/// Full validation including cryptographic signature verification
+pub fn validate(link: &LinkCommit, state: &LedgerState) -> Result<()> {
+    standard().validate(link, state)
+}
 
-    Ok(())
+/// [`validate`], then V9 with pacts from `pacts` at the current time
+pub fn validate_with_pacts(link: &LinkCommit, state: &LedgerState, pacts: &dyn PactResolver) -> Result<()> {
+    validate(link, state)?;
+    validators::verify_pact_proof(link, pacts, validators::now_ms())
 }
 
 /// Quick decide function that returns Decision enum
@@ -188,7 +328,7 @@ pub fn decide(link: &LinkCommit, state: &LedgerState) -> Decision {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ubl_link::PactProof;
+    use ubl_link::{IntentClass, PactProof};
     use ed25519_dalek::SigningKey;
 
     /// Create a properly signed commit for testing
@@ -284,7 +424,7 @@ mod tests {
         let state = make_state(1, "genesis", 0);
         let key = test_keypair();
         let mut commit = make_signed_commit(1, "genesis", 0, IntentClass::Observation, &key);
-        commit.version = 2;
+        commit.version = 3;
         // Re-sign after modification
         commit.signature = ubl_kernel::sign(&key, &commit.signing_bytes());
 
@@ -292,6 +432,26 @@ mod tests {
         assert!(matches!(result, Err(MembraneError::InvalidVersion)));
     }
 
+    #[test]
+    fn test_v2_context_signature() {
+        let state = make_state(1, "genesis", 0);
+        let key = test_keypair();
+        let mut commit = make_signed_commit(1, "genesis", 0, IntentClass::Observation, &key);
+        commit.version = 2;
+        commit.signature = ubl_kernel::sign_with(
+            ubl_kernel::SignatureMode::Ed25519ph,
+            &key,
+            ubl_kernel::contexts::LINK,
+            &commit.signing_bytes(),
+        )
+        .unwrap();
+        assert!(validate(&commit, &state).is_ok());
+
+        // A plain Ed25519 signature does not satisfy v2
+        commit.signature = ubl_kernel::sign(&key, &commit.signing_bytes());
+        assert!(matches!(validate(&commit, &state), Err(MembraneError::InvalidSignature)));
+    }
+
     #[test]
     fn test_container_mismatch() {
         let mut state = make_state(1, "genesis", 0);
@@ -311,6 +471,10 @@ mod tests {
 
         let result = validate(&commit, &state);
         assert!(matches!(result, Err(MembraneError::RealityDrift)));
+        assert_eq!(result.as_ref().unwrap_err().name(), "RealityDrift");
+
+        use ubl_errors::HasErrorCode;
+        assert_eq!(result.unwrap_err().error_code(), ubl_errors::ErrorCode::RealityDrift);
     }
 
     #[test]
@@ -415,4 +579,142 @@ mod tests {
         let decision = decide(&commit, &state);
         assert!(decision.is_accept());
     }
+
+    struct Tenant(&'static str);
+
+    impl Validator for Tenant {
+        fn name(&self) -> &'static str {
+            "tenant"
+        }
+
+        fn check(&self, commit: &Commit<'_>) -> Result<()> {
+            if !commit.link.container_id.starts_with(self.0) {
+                return Err(MembraneError::Rejected {
+                    validator: self.name(),
+                    code: ubl_errors::ErrorCode::Forbidden,
+                    reason: format!("{} is outside tenant {}", commit.link.container_id, self.0),
+                });
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_injected_validator_runs_after_standard() {
+        let state = make_state(1, "genesis", 0);
+        let key = test_keypair();
+        let commit = make_signed_commit(1, "genesis", 0, IntentClass::Observation, &key);
+
+        let membrane = Membrane::standard().with(Tenant("acme."));
+        assert_eq!(membrane.names().len(), 9);
+        let result = membrane.validate(&commit, &state);
+        use ubl_errors::HasErrorCode;
+        assert_eq!(result.as_ref().unwrap_err().name(), "tenant");
+        assert_eq!(result.unwrap_err().error_code(), ubl_errors::ErrorCode::Forbidden);
+        assert!(Membrane::standard().with(Tenant("wal")).validate(&commit, &state).is_ok());
+
+        // Standard rejections still come first
+        let mut bad = commit.clone();
+        bad.signature = "bad_signature".to_string();
+        assert!(matches!(membrane.validate(&bad, &state), Err(MembraneError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_run_times_validators_until_rejection() {
+        let state = make_state(1, "genesis", 0);
+        let key = test_keypair();
+        let commit = make_signed_commit(1, "genesis", 0, IntentClass::Observation, &key);
+        let membrane = Membrane::standard();
+
+        let report = membrane.run(&commit, &commit.signing_bytes(), &state);
+        assert!(report.result.is_ok());
+        let ran: Vec<_> = report.timings.iter().map(|t| t.validator).collect();
+        assert_eq!(ran, membrane.names());
+        assert!(report.elapsed() < Duration::from_millis(1000));
+
+        let drifted = make_signed_commit(1, "wrong_hash", 0, IntentClass::Observation, &key);
+        let report = membrane.run(&drifted, &drifted.signing_bytes(), &state);
+        assert!(matches!(report.result, Err(MembraneError::RealityDrift)));
+        assert_eq!(report.timings.last().unwrap().validator, "V4.causality");
+        assert_eq!(report.timings.len(), 4);
+    }
+
+    /// A 2-of-3 pact over Entropy on `wallet`, and its signers' keys
+    fn make_registry() -> (ubl_pact::PactRegistry, Vec<SigningKey>) {
+        let keys: Vec<SigningKey> = (0..3).map(|_| test_keypair()).collect();
+        let mut registry = ubl_pact::PactRegistry::new();
+        registry.register(ubl_pact::Pact {
+            pact_id: "mint".to_string(),
+            version: 1,
+            scope: ubl_pact::PactScope::Container("wallet".to_string()),
+            intent_classes: vec![ubl_pact::IntentClassRef::Entropy],
+            threshold: 2,
+            signers: keys.iter().map(ubl_kernel::pubkey_from_signing_key).collect(),
+            window: ubl_pact::TimeWindow { not_before: 0, not_after: i64::MAX },
+            risk_level: ubl_pact::RiskLevel::L4,
+        });
+        (registry, keys)
+    }
+
+    fn pact_sign(key: &SigningKey, commit: &LinkCommit) -> String {
+        let message = ubl_pact::build_pact_sign_message(
+            "mint",
+            &commit.atom_hash,
+            &ubl_pact::IntentClassRef::Entropy,
+            commit.physics_delta,
+        );
+        ubl_kernel::sign(key, &message)
+    }
+
+    #[test]
+    fn test_pact_proof_verified_against_registry() {
+        let state = make_state(1, "genesis", 0);
+        let key = test_keypair();
+        let (registry, signers) = make_registry();
+        let membrane = Membrane::with_pacts(std::sync::Arc::new(registry));
+        assert_eq!(membrane.names().last(), Some(&"V9.pact_proof"));
+
+        let mut commit = make_signed_commit(1, "genesis", 1000, IntentClass::Entropy, &key);
+        let both = vec![pact_sign(&signers[0], &commit), pact_sign(&signers[2], &commit)];
+        commit.pact = Some(PactProof { pact_id: "mint".to_string(), signatures: both.clone() });
+        assert!(membrane.validate(&commit, &state).is_ok());
+
+        // Below threshold, the same signer twice, an outsider, an unknown pact
+        commit.pact = Some(PactProof { pact_id: "mint".to_string(), signatures: both[..1].to_vec() });
+        assert!(matches!(membrane.validate(&commit, &state), Err(MembraneError::PactViolation)));
+        commit.pact = Some(PactProof { pact_id: "mint".to_string(), signatures: vec![both[0].clone(), both[0].clone()] });
+        assert!(matches!(membrane.validate(&commit, &state), Err(MembraneError::PactViolation)));
+        let outsider = pact_sign(&key, &commit);
+        commit.pact = Some(PactProof { pact_id: "mint".to_string(), signatures: vec![both[0].clone(), outsider] });
+        assert!(matches!(membrane.validate(&commit, &state), Err(MembraneError::PactViolation)));
+        commit.pact = Some(make_pact("test", vec![]));
+        assert!(matches!(membrane.validate(&commit, &state), Err(MembraneError::PactViolation)));
+        // Presence is all V1..V8 check
+        assert!(validate(&commit, &state).is_ok());
+    }
+
+    #[test]
+    fn test_pact_proof_scope_and_signed_delta() {
+        let key = test_keypair();
+        let (registry, signers) = make_registry();
+
+        let mut commit = make_signed_commit(1, "genesis", 1000, IntentClass::Entropy, &key);
+        let signatures = vec![pact_sign(&signers[0], &commit), pact_sign(&signers[1], &commit)];
+        commit.pact = Some(PactProof { pact_id: "mint".to_string(), signatures });
+        assert!(validate_with_pacts(&commit, &make_state(1, "genesis", 0), &registry).is_ok());
+
+        // Same proof on another container
+        let mut elsewhere = commit.clone();
+        elsewhere.container_id = "vault".to_string();
+        let state = LedgerState { container_id: "vault".to_string(), ..make_state(1, "genesis", 0) };
+        elsewhere.signature = ubl_kernel::sign(&key, &elsewhere.signing_bytes());
+        assert!(matches!(validate_with_pacts(&elsewhere, &state, &registry), Err(MembraneError::PactViolation)));
+
+        // The signers approved 1000, not more
+        let mut inflated = commit.clone();
+        inflated.physics_delta = 1_000_000;
+        inflated.signature = ubl_kernel::sign(&key, &inflated.signing_bytes());
+        let result = validate_with_pacts(&inflated, &make_state(1, "genesis", 0), &registry);
+        assert!(matches!(result, Err(MembraneError::PactViolation)));
+    }
 }
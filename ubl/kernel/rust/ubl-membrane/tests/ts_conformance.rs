@@ -0,0 +1,66 @@
+//! Conformance: links signed by the TS CLI (`buildSigningBytes`) must yield
+//! the same `signing_bytes` and pass the Membrane.
+//!
+//! Fixtures live in `tests/fixtures/ts_commits/cli.json`; see the README there.
+
+use serde::Deserialize;
+use ubl_link::LinkCommit;
+use ubl_membrane::{validate, LedgerState};
+
+#[derive(Deserialize)]
+struct Fixture {
+    name: String,
+    pipeline: String,
+    signing_bytes_hex: String,
+    link: LinkCommit,
+    state: State,
+}
+
+#[derive(Deserialize)]
+struct State {
+    physical_balance: String,
+}
+
+fn fixtures() -> Vec<Fixture> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/fixtures/ts_commits/cli.json");
+    let raw = std::fs::read_to_string(path).expect("read cli.json");
+    serde_json::from_str(&raw).expect("parse cli.json")
+}
+
+#[test]
+fn cli_signing_bytes_match_rust() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty());
+    for f in &fixtures {
+        assert_eq!(f.pipeline, "membrane", "{}", f.name);
+        assert_eq!(hex::encode(f.link.signing_bytes()), f.signing_bytes_hex, "{}: signing_bytes drifted", f.name);
+    }
+}
+
+#[test]
+fn cli_commits_pass_membrane() {
+    for f in fixtures() {
+        let state = LedgerState {
+            container_id: f.link.container_id.clone(),
+            last_hash: f.link.previous_hash.clone(),
+            next_sequence: f.link.expected_sequence,
+            physical_balance: f.state.physical_balance.parse().unwrap(),
+        };
+        if let Err(e) = validate(&f.link, &state) {
+            panic!("{}: rejected by membrane: {:?}", f.name, e);
+        }
+    }
+}
+
+#[test]
+fn tampered_cli_commit_rejected() {
+    let mut f = fixtures().remove(0);
+    f.link.expected_sequence += 1;
+    let state = LedgerState {
+        container_id: f.link.container_id.clone(),
+        last_hash: f.link.previous_hash.clone(),
+        next_sequence: f.link.expected_sequence,
+        physical_balance: 0,
+    };
+    assert!(validate(&f.link, &state).is_err());
+}
@@ -44,6 +44,17 @@ pub enum PactScope {
     Global,
 }
 
+impl PactScope {
+    /// Whether a commit to `container_id` falls under this scope
+    pub fn covers(&self, container_id: &str) -> bool {
+        match self {
+            PactScope::Container(id) => id == container_id,
+            PactScope::Namespace(prefix) => container_id.starts_with(prefix.as_str()),
+            PactScope::Global => true,
+        }
+    }
+}
+
 /// Time window for pact validity per SPEC-UBL-PACT §7
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeWindow {
@@ -107,7 +118,12 @@ pub enum PactError {
     
     /// Not enough valid signatures
     #[error("Insufficient signatures: got {got}, need {need}")]
-    InsufficientSignatures { got: usize, need: u8 },
+    InsufficientSignatures {
+        /// Valid signatures found
+        got: usize,
+        /// The pact threshold
+        need: u8,
+    },
     
     /// Signer not in authorized set
     #[error("Unauthorized signer: {0}")]
@@ -115,7 +131,12 @@ pub enum PactError {
     
     /// Intent class doesn't match pact risk level
     #[error("Risk mismatch: pact is {pact_level:?}, intent requires {required:?}")]
-    RiskMismatch { pact_level: RiskLevel, required: RiskLevel },
+    RiskMismatch {
+        /// Risk level of the pact
+        pact_level: RiskLevel,
+        /// Risk level the intent class requires
+        required: RiskLevel,
+    },
     
     /// Duplicate signature detected
     #[error("Duplicate signature from: {0}")]
@@ -226,7 +247,7 @@ pub fn validate_pact(
 
 /// Build the message that pact signers must sign
 /// Per SPEC-UBL-PACT §8.1
-fn build_pact_sign_message(
+pub fn build_pact_sign_message(
     pact_id: &str,
     atom_hash: &str,
     intent_class: &IntentClassRef,
@@ -346,6 +367,15 @@ mod tests {
         assert!(registry.get("nonexistent").is_none());
     }
 
+    #[test]
+    fn test_scope_covers() {
+        assert!(PactScope::Global.covers("wallet"));
+        assert!(PactScope::Container("wallet".into()).covers("wallet"));
+        assert!(!PactScope::Container("wallet".into()).covers("wallet.2"));
+        assert!(PactScope::Namespace("acme.".into()).covers("acme.jobs"));
+        assert!(!PactScope::Namespace("acme.".into()).covers("other.jobs"));
+    }
+
     #[test]
     fn test_risk_level_ordering() {
         assert!(RiskLevel::L0 < RiskLevel::L1);
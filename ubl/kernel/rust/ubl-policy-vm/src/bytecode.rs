@@ -16,6 +16,9 @@
 
 #![deny(unsafe_code)]
 
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -185,8 +188,9 @@ pub enum Opcode {
 // VALUE TYPE
 // ============================================================================
 
-/// A value on the VM stack
-#[derive(Debug, Clone, PartialEq)]
+/// A value on the VM stack (serialized as the plain JSON value)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
 pub enum Value {
     /// 64-bit signed integer
     I64(i64),
@@ -330,7 +334,7 @@ pub enum BytecodeError {
 }
 
 /// Result type alias for bytecode operations
-pub type Result<T> = std::result::Result<T, BytecodeError>;
+pub type Result<T> = core::result::Result<T, BytecodeError>;
 
 // ============================================================================
 // COMPILED POLICY
@@ -352,6 +356,19 @@ pub struct CompiledPolicy {
     /// Optional Ed25519 signature (hex-encoded)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
+    /// Rule of each rule's Allow opcode, in rule order. Metadata for
+    /// reporting which rule decided; not covered by `hash`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleOffset>,
+}
+
+/// Where a compiled rule allows: the offset of its Allow/AllowWithPact opcode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleOffset {
+    /// `rule_id` from the policy definition
+    pub rule_id: String,
+    /// Offset of the rule's terminal opcode in `code`
+    pub pc: usize,
 }
 
 impl CompiledPolicy {
@@ -365,9 +382,21 @@ impl CompiledPolicy {
             constants,
             hash,
             signature: None,
+            rules: Vec::new(),
         }
     }
 
+    /// Attach the rule table recorded by the compiler
+    pub fn with_rules(mut self, rules: Vec<RuleOffset>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Rule whose terminal opcode is at `pc`
+    pub fn rule_at(&self, pc: usize) -> Option<&str> {
+        self.rules.iter().find(|r| r.pc == pc).map(|r| r.rule_id.as_str())
+    }
+
     /// Verify hash integrity (constant-time comparison)
     pub fn verify_hash(&self) -> bool {
         let computed = compute_policy_hash(&self.code, &self.constants);
@@ -455,11 +484,20 @@ pub struct ExecutionContext {
     pub state: Option<serde_json::Value>,
     /// Current timestamp (milliseconds since epoch)
     pub timestamp: i64,
+    /// Feature flags of the container, read as context fields
+    /// `flag.<name>`; a flag that is not set reads as false
+    pub flags: BTreeMap<String, bool>,
 }
 
+/// Context fields under this prefix are feature flags
+pub const FLAG_PREFIX: &str = "flag.";
+
 impl ExecutionContext {
     /// Get a named context field
     pub fn get(&self, key: &str) -> Value {
+        if let Some(flag) = key.strip_prefix(FLAG_PREFIX) {
+            return Value::Bool(self.flags.get(flag).copied().unwrap_or(false));
+        }
         match key {
             "container_id" => Value::String(self.container_id.clone()),
             "actor" => Value::String(self.actor.clone()),
@@ -575,15 +613,73 @@ impl BytecodeVM {
         policy: &CompiledPolicy,
         context: &ExecutionContext,
     ) -> Result<PolicyResult> {
-        // Validate policy first
-        policy.validate()?;
+        self.execute_metered(policy, context).0
+    }
+
+    /// Execute a compiled policy, also returning the gas it consumed (the
+    /// whole budget when it ran out, 0 when the policy failed validation)
+    pub fn execute_metered(
+        &self,
+        policy: &CompiledPolicy,
+        context: &ExecutionContext,
+    ) -> (Result<PolicyResult>, u64) {
+        let mut vm = VMState {
+            pc: 0,
+            stack: Vec::with_capacity(64),
+            gas: self.config.max_gas,
+            max_stack: self.config.max_stack,
+        };
+        let result = self.run(&mut vm, policy, context);
+        (result, self.config.max_gas - vm.gas)
+    }
 
+    /// Execute a compiled policy, recording every instruction it runs.
+    /// Same result and gas as [`execute_metered`](Self::execute_metered),
+    /// slower: for debugging a policy, not for admission.
+    pub fn execute_traced(
+        &self,
+        policy: &CompiledPolicy,
+        context: &ExecutionContext,
+    ) -> (Result<PolicyResult>, ExecutionTrace) {
         let mut vm = VMState {
             pc: 0,
             stack: Vec::with_capacity(64),
             gas: self.config.max_gas,
             max_stack: self.config.max_stack,
         };
+        let mut steps = Vec::new();
+        let result = self.run_traced(&mut vm, policy, context, Some(&mut steps));
+        let trace = ExecutionTrace {
+            steps,
+            gas_used: self.config.max_gas - vm.gas,
+            max_gas: self.config.max_gas,
+        };
+        (result, trace)
+    }
+
+    /// Gas budget of a single execution
+    pub fn max_gas(&self) -> u64 {
+        self.config.max_gas
+    }
+
+    fn run(
+        &self,
+        vm: &mut VMState,
+        policy: &CompiledPolicy,
+        context: &ExecutionContext,
+    ) -> Result<PolicyResult> {
+        self.run_traced(vm, policy, context, None)
+    }
+
+    fn run_traced(
+        &self,
+        vm: &mut VMState,
+        policy: &CompiledPolicy,
+        context: &ExecutionContext,
+        mut trace: Option<&mut Vec<TraceStep>>,
+    ) -> Result<PolicyResult> {
+        // Validate policy first
+        policy.validate()?;
 
         let code = &policy.code;
         let constants = &policy.constants;
@@ -602,6 +698,9 @@ impl BytecodeVM {
 
             let opcode = code[vm.pc];
             let op_pc = vm.pc;  // Save for error messages
+            if let Some(steps) = trace.as_mut() {
+                steps.push(TraceStep::at(policy, op_pc, &vm.stack, self.config.max_gas - vm.gas));
+            }
             vm.pc += 1;
 
             match opcode {
@@ -856,7 +955,7 @@ impl BytecodeVM {
                     return Ok(PolicyResult::Allow {
                         intent_class,
                         required_pact: None,
-                        constraints: vec![],
+                        constraints: policy.rule_at(op_pc).map(String::from).into_iter().collect(),
                     });
                 }
                 
@@ -867,7 +966,7 @@ impl BytecodeVM {
                     return Ok(PolicyResult::Allow {
                         intent_class,
                         required_pact: Some(pact_id),
-                        constraints: vec![],
+                        constraints: policy.rule_at(op_pc).map(String::from).into_iter().collect(),
                     });
                 }
                 
@@ -894,6 +993,51 @@ impl Default for BytecodeVM {
     }
 }
 
+// ============================================================================
+// EXECUTION TRACE
+// ============================================================================
+
+/// One instruction of a traced execution
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceStep {
+    /// Offset of the instruction
+    pub pc: usize,
+    /// Mnemonic (`db 0xNN` for a byte that is not an opcode)
+    pub op: String,
+    /// Decoded operand, as in the disassembly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operand: Option<String>,
+    /// Stack as the instruction found it, bottom first
+    pub stack: Vec<Value>,
+    /// Gas used so far, this instruction included
+    pub gas_used: u64,
+}
+
+impl TraceStep {
+    fn at(policy: &CompiledPolicy, pc: usize, stack: &[Value], gas_used: u64) -> Self {
+        let instruction = crate::disasm::decode(&policy.code, &policy.constants, pc);
+        Self {
+            pc,
+            op: instruction.as_ref().map(|i| i.mnemonic()).unwrap_or_default(),
+            operand: instruction.and_then(|i| i.operand_text()),
+            stack: stack.to_vec(),
+            gas_used,
+        }
+    }
+}
+
+/// What [`BytecodeVM::execute_traced`] recorded
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionTrace {
+    /// Instructions in execution order; the last one decided or failed.
+    /// Empty when the policy failed validation.
+    pub steps: Vec<TraceStep>,
+    /// Gas consumed
+    pub gas_used: u64,
+    /// Gas budget
+    pub max_gas: u64,
+}
+
 // ============================================================================
 // VM STATE
 // ============================================================================
@@ -1016,7 +1160,8 @@ pub enum PolicyResult {
         intent_class: u8,
         /// Required pact ID (if any)
         required_pact: Option<String>,
-        /// Applied constraints (rule IDs that matched)
+        /// Applied constraints (rule IDs that matched; empty when the
+        /// policy's default allowed)
         constraints: Vec<String>,
     },
     /// Deny the translation
@@ -1047,6 +1192,7 @@ impl PolicyResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     fn make_context(intent_type: &str, amount: i64) -> ExecutionContext {
         ExecutionContext {
@@ -1058,6 +1204,7 @@ mod tests {
             }),
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         }
     }
 
@@ -1104,6 +1251,28 @@ mod tests {
         
         let result = vm.execute(&policy, &ctx);
         assert!(matches!(result, Err(BytecodeError::GasExhausted(_))));
+
+        let (_, gas_used) = vm.execute_metered(&policy, &ctx);
+        assert_eq!(gas_used, 100);
+    }
+
+    #[test]
+    fn test_gas_metering() {
+        let code = vec![
+            0x00,             // Nop
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0, // PushI64(0)
+            0xF0,             // Allow
+        ];
+
+        let policy = CompiledPolicy::new("test", "1.0", code, vec![]);
+        let vm = BytecodeVM::default();
+        let ctx = make_context("test", 0);
+
+        // One unit per instruction executed
+        let (result, gas_used) = vm.execute_metered(&policy, &ctx);
+        assert!(result.unwrap().is_allow());
+        assert_eq!(gas_used, 3);
+        assert_eq!(vm.max_gas(), MAX_GAS);
     }
 
     #[test]
@@ -1196,11 +1365,50 @@ mod tests {
             intent: serde_json::json!({}),
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         };
         let result = vm.execute(&policy, &ctx).unwrap();
         assert!(result.is_allow());
     }
 
+    #[test]
+    fn test_execute_traced() {
+        let code = vec![
+            0x12, 0, 0, // LoadIntent("amount")
+            0x01, 0, 0, 0, 0, 0, 0, 0, 100, // PushI64(100)
+            0x24,       // Gt
+            0x52, 0, 20, // JumpIfNot allow
+            0x02, 0, 1, // PushStr("too much")
+            0xF2,       // Deny
+            0x01, 0, 0, 0, 0, 0, 0, 0, 0, // PushI64(0)
+            0xF0,       // Allow
+        ];
+        let policy = CompiledPolicy::new("test", "1.0", code, vec!["amount".to_string(), "too much".to_string()]);
+        let vm = BytecodeVM::default();
+
+        let ctx = make_context("transfer", 500);
+        let (result, trace) = vm.execute_traced(&policy, &ctx);
+        assert_eq!(result.unwrap(), PolicyResult::Deny { reason: "too much".to_string() });
+        let ops: Vec<&str> = trace.steps.iter().map(|s| s.op.as_str()).collect();
+        assert_eq!(ops, ["LoadIntent", "PushI64", "Gt", "JumpIfNot", "PushStr", "Deny"]);
+        assert_eq!(trace.steps[2].stack, vec![Value::I64(500), Value::I64(100)]);
+        assert_eq!(trace.steps[5].stack, vec![Value::String("too much".to_string())]);
+        assert_eq!(trace.steps[4].operand.as_deref(), Some("#1 \"too much\""));
+        // Same gas as an untraced run
+        assert_eq!(trace.gas_used, vm.execute_metered(&policy, &ctx).1);
+        assert_eq!(trace.steps.last().unwrap().gas_used, trace.gas_used);
+
+        let (result, trace) = vm.execute_traced(&policy, &make_context("transfer", 5));
+        assert!(result.unwrap().is_allow());
+        assert_eq!(trace.steps.last().unwrap().pc, 29);
+
+        let mut tampered = policy.clone();
+        tampered.hash = "tampered".to_string();
+        let (result, trace) = vm.execute_traced(&tampered, &ctx);
+        assert!(matches!(result, Err(BytecodeError::HashMismatch)));
+        assert!(trace.steps.is_empty());
+    }
+
     #[test]
     fn test_constant_time_eq() {
         assert!(constant_time_eq("hello", "hello"));
@@ -5,16 +5,24 @@
 //! > - WASM (execução segura)
 //! > - bytecode verificável
 //!
-//! This module compiles policy rules (expressed in JSON) to bytecode.
+//! This module compiles policy rules (expressed in JSON) to bytecode. Rules
+//! that need `or`, negation or nesting can be written in the text DSL
+//! instead ([`dsl`], [`PolicyCompiler::compile_source`]), which lowers to
+//! the same bytecode.
 //!
 //! ## Security Features
 //! - Validates policy before compilation
 //! - Limits on rules and constraints
 //! - String length validation
 
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use super::bytecode::{CompiledPolicy, Opcode};
+use super::bytecode::{CompiledPolicy, Opcode, RuleOffset, FLAG_PREFIX};
+
+pub mod dsl;
 
 // ============================================================================
 // COMPILER ERRORS
@@ -142,6 +150,29 @@ pub enum Constraint {
         /// Expected field value
         value: String 
     },
+    /// Check a numeric container state field is <= max. The server provides
+    /// `entropy_minted`, `entropy_burned` and `entropy_net` (totals before
+    /// the commit being evaluated), and the author's `actor_accepted`,
+    /// `actor_rejected`, `actor_rejection_pct` (0-100) and
+    /// `actor_recent_violations` (distinct violation codes, last 30 days).
+    StateMax {
+        /// State field name
+        field: String,
+        /// Maximum allowed value
+        max: i64,
+    },
+    /// Check a numeric state field is >= min (fields as for `StateMax`)
+    StateMin {
+        /// State field name
+        field: String,
+        /// Minimum required value
+        min: i64,
+    },
+    /// Check a feature flag of the container is on (unset flags are off)
+    FlagEnabled {
+        /// Flag name
+        flag: String,
+    },
 }
 
 /// Policy definition (collection of rules)
@@ -177,6 +208,7 @@ pub const MAX_CONSTRAINTS_PER_RULE: usize = 32;
 pub struct PolicyCompiler {
     constants: Vec<String>,
     code: Vec<u8>,
+    rules: Vec<RuleOffset>,
 }
 
 impl PolicyCompiler {
@@ -185,6 +217,7 @@ impl PolicyCompiler {
         Self {
             constants: Vec::new(),
             code: Vec::new(),
+            rules: Vec::new(),
         }
     }
 
@@ -227,6 +260,7 @@ impl PolicyCompiler {
     pub fn compile(&mut self, policy: &PolicyDefinition) -> CompiledPolicy {
         self.constants.clear();
         self.code.clear();
+        self.rules.clear();
 
         // Compile each rule as a condition block
         for rule in &policy.rules {
@@ -234,20 +268,30 @@ impl PolicyCompiler {
         }
 
         // Default action at the end
-        if policy.default_deny {
+        self.emit_default(policy.default_deny);
+
+        self.finish(&policy.policy_id, &policy.version)
+    }
+
+    fn emit_default(&mut self, default_deny: bool) {
+        if default_deny {
             self.emit_deny("No matching rule");
         } else {
             // Default allow with Observation
             self.emit_push_i64(0);
             self.emit(Opcode::Allow);
         }
+    }
 
+    /// The policy compiled so far; leaves the compiler empty
+    fn finish(&mut self, policy_id: &str, version: &str) -> CompiledPolicy {
         CompiledPolicy::new(
-            &policy.policy_id,
-            &policy.version,
-            std::mem::take(&mut self.code),
-            std::mem::take(&mut self.constants),
+            policy_id,
+            version,
+            core::mem::take(&mut self.code),
+            core::mem::take(&mut self.constants),
         )
+        .with_rules(core::mem::take(&mut self.rules))
     }
 
     /// Compile with validation
@@ -272,24 +316,34 @@ impl PolicyCompiler {
         }
 
         // All constraints passed - emit Allow with intent class
-        self.emit_push_i64(rule.intent_class.to_byte() as i64);
-        
-        if let Some(ref pact_id) = rule.required_pact {
+        self.emit_allow(&rule.rule_id, rule.intent_class, rule.required_pact.as_deref());
+
+        // Patch all jump addresses to point to after the Allow
+        self.patch_jumps(&jump_positions);
+    }
+
+    /// Allow with the rule's intent class (and pact), recording its offset
+    fn emit_allow(&mut self, rule_id: &str, intent_class: IntentClassSpec, required_pact: Option<&str>) {
+        self.emit_push_i64(intent_class.to_byte() as i64);
+
+        if let Some(pact_id) = required_pact {
             let pact_idx = self.add_constant(pact_id);
             self.emit(Opcode::PushStr);
             self.emit_u16(pact_idx);
-            self.emit(Opcode::AllowWithPact);
+            self.emit_rule_result(rule_id, Opcode::AllowWithPact);
         } else {
-            self.emit(Opcode::Allow);
+            self.emit_rule_result(rule_id, Opcode::Allow);
         }
+    }
 
-        // Patch all jump addresses to point to after the Allow
-        let next_rule_addr = self.code.len();
-        for pos in jump_positions {
-            // JumpIfNot opcode is at `pos`, address is at `pos + 1` (2 bytes)
+    /// Point the jumps at `positions` to the current end of the code
+    fn patch_jumps(&mut self, positions: &[usize]) {
+        let target = self.code.len();
+        for &pos in positions {
+            // Jump opcode is at `pos`, address is at `pos + 1` (2 bytes)
             let addr_pos = pos + 1;
-            self.code[addr_pos] = ((next_rule_addr >> 8) & 0xFF) as u8;
-            self.code[addr_pos + 1] = (next_rule_addr & 0xFF) as u8;
+            self.code[addr_pos] = ((target >> 8) & 0xFF) as u8;
+            self.code[addr_pos + 1] = (target & 0xFF) as u8;
         }
     }
 
@@ -358,6 +412,34 @@ impl PolicyCompiler {
                 self.emit_u16(value_idx);
                 self.emit(Opcode::StrEq);
             }
+
+            Constraint::StateMax { field, max } => {
+                // LoadState(field), PushI64(max), Le
+                let field_idx = self.add_constant(field);
+
+                self.emit(Opcode::LoadState);
+                self.emit_u16(field_idx);
+                self.emit_push_i64(*max);
+                self.emit(Opcode::Le);
+            }
+
+            Constraint::StateMin { field, min } => {
+                // LoadState(field), PushI64(min), Ge
+                let field_idx = self.add_constant(field);
+
+                self.emit(Opcode::LoadState);
+                self.emit_u16(field_idx);
+                self.emit_push_i64(*min);
+                self.emit(Opcode::Ge);
+            }
+
+            Constraint::FlagEnabled { flag } => {
+                // LoadContext("flag.<flag>") is already a boolean
+                let flag_idx = self.add_constant(&format!("{}{}", FLAG_PREFIX, flag));
+
+                self.emit(Opcode::LoadContext);
+                self.emit_u16(flag_idx);
+            }
         }
     }
 
@@ -365,6 +447,12 @@ impl PolicyCompiler {
         self.code.push(op as u8);
     }
 
+    /// Emit a rule's terminal opcode, recording its offset for the VM
+    fn emit_rule_result(&mut self, rule_id: &str, op: Opcode) {
+        self.rules.push(RuleOffset { rule_id: rule_id.to_string(), pc: self.code.len() });
+        self.emit(op);
+    }
+
     fn emit_u16(&mut self, value: u16) {
         self.code.push((value >> 8) as u8);
         self.code.push((value & 0xFF) as u8);
@@ -490,6 +578,7 @@ mod tests {
             intent: serde_json::json!({"type": "observe"}),
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         };
         
         let result = vm.execute(&compiled, &ctx).unwrap();
@@ -502,6 +591,7 @@ mod tests {
             intent: serde_json::json!({"type": "hack"}),
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         };
         
         let result = vm.execute(&compiled, &ctx).unwrap();
@@ -551,6 +641,7 @@ mod tests {
             intent: serde_json::json!({"type": "transfer", "amount": 500}),
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         };
         
         let result = vm.execute(&compiled, &ctx).unwrap();
@@ -569,6 +660,7 @@ mod tests {
             intent: serde_json::json!({"type": "transfer", "amount": 5000}),
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         };
         
         let result = vm.execute(&compiled, &ctx).unwrap();
@@ -580,5 +672,170 @@ mod tests {
             _ => panic!("Expected Allow with pact"),
         }
     }
-}
 
+    #[test]
+    fn test_matched_rule_is_reported() {
+        let rule = |rule_id: &str, value: &str, required_pact: Option<&str>| PolicyRule {
+            rule_id: rule_id.to_string(),
+            applies_to: AppliesTo::Global,
+            intent_class: IntentClassSpec::Observation,
+            constraints: vec![Constraint::IntentTypeEquals { value: value.to_string() }],
+            required_pact: required_pact.map(String::from),
+        };
+        let policy_def = PolicyDefinition {
+            policy_id: "test".to_string(),
+            version: "1.0".to_string(),
+            description: "Test policy".to_string(),
+            rules: vec![rule("observe", "observe", None), rule("escalate", "escalate", Some("approval"))],
+            default_deny: false,
+        };
+        let compiled = PolicyCompiler::new().compile(&policy_def);
+        let rule_ids: Vec<&str> = compiled.rules.iter().map(|r| r.rule_id.as_str()).collect();
+        assert_eq!(rule_ids, ["observe", "escalate"]);
+
+        let vm = BytecodeVM::default();
+        let matched = |intent_type: &str| {
+            let ctx = ExecutionContext {
+                container_id: "C.Test".to_string(),
+                actor: "alice".to_string(),
+                intent: serde_json::json!({"type": intent_type}),
+                state: None,
+                timestamp: 1000,
+                flags: Default::default(),
+            };
+            match vm.execute(&compiled, &ctx).unwrap() {
+                crate::bytecode::PolicyResult::Allow { constraints, .. } => constraints,
+                other => panic!("Expected Allow, got {:?}", other),
+            }
+        };
+        assert_eq!(matched("escalate"), ["escalate"]);
+        assert_eq!(matched("observe"), ["observe"]);
+        // The default allow is no rule
+        assert!(matched("other").is_empty());
+    }
+
+    #[test]
+    fn test_state_max_constraint() {
+        let policy_def = PolicyDefinition {
+            policy_id: "supply_cap".to_string(),
+            version: "1.0".to_string(),
+            description: "Mint until the cap".to_string(),
+            rules: vec![
+                PolicyRule {
+                    rule_id: "mint_under_cap".to_string(),
+                    applies_to: AppliesTo::Global,
+                    intent_class: IntentClassSpec::Entropy,
+                    constraints: vec![
+                        Constraint::IntentTypeEquals { value: "mint".to_string() },
+                        Constraint::StateMax { field: "entropy_minted".to_string(), max: 1_000 },
+                    ],
+                    required_pact: None,
+                },
+            ],
+            default_deny: true,
+        };
+
+        let compiled = PolicyCompiler::new().compile(&policy_def);
+        let vm = BytecodeVM::default();
+        let ctx = |minted: i64| ExecutionContext {
+            container_id: "C.Wallet".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({"type": "mint"}),
+            state: Some(serde_json::json!({"entropy_minted": minted, "entropy_burned": 0})),
+            timestamp: 1000,
+            flags: Default::default(),
+        };
+
+        let result = vm.execute(&compiled, &ctx(1_000)).unwrap();
+        assert!(matches!(result, crate::bytecode::PolicyResult::Allow { intent_class: 2, .. }));
+
+        let result = vm.execute(&compiled, &ctx(1_001)).unwrap();
+        assert!(matches!(result, crate::bytecode::PolicyResult::Deny { .. }));
+    }
+
+    #[test]
+    fn test_state_min_requires_pact_of_poorly_reputed_actors() {
+        let policy_def = PolicyDefinition {
+            policy_id: "reputation".to_string(),
+            version: "1.0".to_string(),
+            description: "Ask a pact of actors refused too often".to_string(),
+            rules: vec![
+                PolicyRule {
+                    rule_id: "low_reputation".to_string(),
+                    applies_to: AppliesTo::Global,
+                    intent_class: IntentClassSpec::Conservation,
+                    constraints: vec![
+                        Constraint::StateMin { field: "actor_rejection_pct".to_string(), min: 25 },
+                    ],
+                    required_pact: Some("review".to_string()),
+                },
+                PolicyRule {
+                    rule_id: "default".to_string(),
+                    applies_to: AppliesTo::Global,
+                    intent_class: IntentClassSpec::Conservation,
+                    constraints: vec![],
+                    required_pact: None,
+                },
+            ],
+            default_deny: true,
+        };
+
+        let compiled = PolicyCompiler::new().compile(&policy_def);
+        let vm = BytecodeVM::default();
+        let ctx = |pct: i64| ExecutionContext {
+            container_id: "C.Wallet".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({"type": "transfer"}),
+            state: Some(serde_json::json!({"actor_rejection_pct": pct})),
+            timestamp: 1000,
+            flags: Default::default(),
+        };
+
+        match vm.execute(&compiled, &ctx(40)).unwrap() {
+            crate::bytecode::PolicyResult::Allow { required_pact, .. } => {
+                assert_eq!(required_pact, Some("review".to_string()));
+            }
+            _ => panic!("Expected Allow with pact"),
+        }
+        match vm.execute(&compiled, &ctx(10)).unwrap() {
+            crate::bytecode::PolicyResult::Allow { required_pact, .. } => assert_eq!(required_pact, None),
+            _ => panic!("Expected Allow"),
+        }
+    }
+
+    #[test]
+    fn test_flag_constraint() {
+        let policy_def = PolicyDefinition {
+            policy_id: "threads".to_string(),
+            version: "1.0".to_string(),
+            description: "Threads only where the flag is on".to_string(),
+            rules: vec![PolicyRule {
+                rule_id: "allow_thread".to_string(),
+                applies_to: AppliesTo::Global,
+                intent_class: IntentClassSpec::Observation,
+                constraints: vec![
+                    Constraint::IntentTypeEquals { value: "thread.reply".to_string() },
+                    Constraint::FlagEnabled { flag: "threading".to_string() },
+                ],
+                required_pact: None,
+            }],
+            default_deny: true,
+        };
+
+        let compiled = PolicyCompiler::new().compile(&policy_def);
+        let vm = BytecodeVM::default();
+        let ctx = |flags: &[(&str, bool)]| ExecutionContext {
+            container_id: "C.Messenger".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({"type": "thread.reply"}),
+            state: None,
+            timestamp: 1000,
+            flags: flags.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        };
+
+        assert!(vm.execute(&compiled, &ctx(&[("threading", true)])).unwrap().is_allow());
+        assert!(!vm.execute(&compiled, &ctx(&[("threading", false)])).unwrap().is_allow());
+        // Unset flags are off
+        assert!(!vm.execute(&compiled, &ctx(&[("reactions", true)])).unwrap().is_allow());
+    }
+}
@@ -0,0 +1,991 @@
+//! Policy DSL - rules as text
+//!
+//! [`PolicyDefinition`](super::PolicyDefinition) rules are a conjunction of
+//! fixed constraints. The DSL writes a rule's condition as an expression,
+//! with `or`, `not`, parentheses and any intent, state or context field:
+//!
+//! ```text
+//! # Transfers up to 10k, more with a pact
+//! rule allow_small_transfer:
+//!     allow conservation when intent.type == "transfer" and intent.amount <= 10000
+//! rule allow_large_transfer:
+//!     allow conservation with pact "high_value_transfer"
+//!     when intent.type == "transfer" and intent.amount > 10000
+//! allow observation when intent.type == "observe" or intent.type == "ping"
+//! allow entropy when intent.type == "mint"
+//!     and state.entropy_minted + intent.amount <= 1000000
+//!     and not (actor starts_with "guest:")
+//! default deny
+//! ```
+//!
+//! Rules are tried in order and the first whose condition holds decides.
+//! `rule <id>:` is optional; unnamed rules are `rule_<n>` (1-based). The last
+//! statement may be `default deny` (also the default when it is absent),
+//! `default deny "<reason>"` or `default allow` (Observation).
+//!
+//! Expressions, loosest first:
+//!
+//! | | |
+//! |---|---|
+//! | `or`, `and`, `not` | on booleans; a rule's condition short-circuits |
+//! | `==` `!=` | any values (missing fields are `null`) |
+//! | `<` `<=` `>` `>=` | integers |
+//! | `starts_with` `ends_with` `contains` | strings |
+//! | `+` `-`, then `*` `/` `%`, unary `-` | integers (saturating) |
+//! | `intent.<f>` `state.<f>` `context.<f>` | fields of the evaluation context |
+//! | `flag.<name>` | a feature flag of the container (false when unset) |
+//! | `actor` `container` `timestamp` | the context itself |
+//! | `"…"`, integers, `true` `false` `null` | literals |
+//!
+//! Operands are type-checked where their type is known (literals, `actor`,
+//! ...); fields are checked by the VM when the policy runs. Every error
+//! carries the span of the offending text ([`DslError::render`]).
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+use serde::Serialize;
+use thiserror::Error;
+
+use super::{IntentClassSpec, PolicyCompiler, MAX_RULES};
+use crate::bytecode::{CompiledPolicy, Opcode, FLAG_PREFIX, MAX_STRING_LENGTH};
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+/// Byte range in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    /// First byte
+    pub start: usize,
+    /// One past the last byte
+    pub end: usize,
+}
+
+impl Span {
+    fn to(self, other: Span) -> Span {
+        Span { start: self.start, end: other.end }
+    }
+}
+
+/// A DSL source that does not compile
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize)]
+#[error("{line}:{column}: {message}")]
+pub struct DslError {
+    /// What is wrong
+    pub message: String,
+    /// Where
+    pub span: Span,
+    /// Line of `span.start` (1-based)
+    pub line: usize,
+    /// Column of `span.start`, in characters (1-based)
+    pub column: usize,
+}
+
+impl DslError {
+    fn at(source: &str, span: Span, message: impl Into<String>) -> Self {
+        let before = &source[..span.start.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Self { message: message.into(), span, line, column }
+    }
+
+    /// The error with the offending line and the span underlined:
+    ///
+    /// ```text
+    /// 1:42: expected an integer, found a string
+    ///     allow conservation when intent.amount <= "ten"
+    ///                                              ^^^^^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[line_start..].find('\n').map_or(source.len(), |i| line_start + i);
+        let line_text = &source[line_start..line_end];
+        let underline = source[start..self.span.end.clamp(start, line_end)].chars().count().max(1);
+        let mut out = String::new();
+        let _ = writeln!(out, "{}", self);
+        let _ = writeln!(out, "    {}", line_text);
+        let _ = write!(out, "    {}{}", " ".repeat(self.column - 1), "^".repeat(underline));
+        out
+    }
+}
+
+// ============================================================================
+// TOKENS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tok {
+    Ident(String),
+    Int(i64),
+    Str(String),
+    /// Operator or punctuation
+    Sym(&'static str),
+    Eof,
+}
+
+impl Tok {
+    fn describe(&self) -> String {
+        match self {
+            Tok::Ident(name) => format!("`{}`", name),
+            Tok::Int(v) => format!("`{}`", v),
+            Tok::Str(s) => format!("{:?}", s),
+            Tok::Sym(s) => format!("`{}`", s),
+            Tok::Eof => "end of input".to_string(),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 16] = ["==", "!=", "<=", ">=", "<", ">", "(", ")", ".", ":", "+", "-", "*", "/", "%", ";"];
+
+/// Words that cannot name a rule or a field
+const KEYWORDS: [&str; 13] = [
+    "rule", "allow", "default", "deny", "when", "with", "pact", "and", "or", "not", "true", "false", "null",
+];
+
+fn lex(source: &str) -> Result<Vec<(Tok, Span)>, DslError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == b'#' {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        let start = i;
+        if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push((Tok::Ident(source[start..i].to_string()), Span { start, end: i }));
+        } else if c.is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'_') {
+                i += 1;
+            }
+            let span = Span { start, end: i };
+            let digits: String = source[start..i].chars().filter(|c| *c != '_').collect();
+            let value = digits
+                .parse::<i64>()
+                .map_err(|_| DslError::at(source, span, "integer out of range (64-bit signed)"))?;
+            tokens.push((Tok::Int(value), span));
+        } else if c == b'"' {
+            let (value, end) = lex_string(source, start)?;
+            i = end;
+            let span = Span { start, end };
+            if value.len() > MAX_STRING_LENGTH {
+                return Err(DslError::at(
+                    source,
+                    span,
+                    format!("string too long: {} bytes (max {})", value.len(), MAX_STRING_LENGTH),
+                ));
+            }
+            tokens.push((Tok::Str(value), span));
+        } else if let Some(sym) = SYMBOLS.iter().find(|s| source[i..].starts_with(**s)) {
+            i += sym.len();
+            tokens.push((Tok::Sym(sym), Span { start, end: i }));
+        } else {
+            let ch = source[i..].chars().next().unwrap_or('?');
+            let span = Span { start, end: i + ch.len_utf8() };
+            return Err(DslError::at(source, span, format!("unexpected character {:?}", ch)));
+        }
+    }
+    tokens.push((Tok::Eof, Span { start: source.len(), end: source.len() }));
+    Ok(tokens)
+}
+
+/// A string literal starting at `start` (the opening quote): its value and
+/// the offset past the closing quote
+fn lex_string(source: &str, start: usize) -> Result<(String, usize), DslError> {
+    let mut value = String::new();
+    let mut chars = source[start + 1..].char_indices();
+    while let Some((offset, ch)) = chars.next() {
+        let at = start + 1 + offset;
+        match ch {
+            '"' => return Ok((value, at + 1)),
+            '\n' => break,
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((o, other)) => {
+                    let span = Span { start: at, end: start + 1 + o + other.len_utf8() };
+                    return Err(DslError::at(source, span, format!("unknown escape \\{}", other)));
+                }
+                None => break,
+            },
+            _ => value.push(ch),
+        }
+    }
+    let end = source[start..].find('\n').map_or(source.len(), |n| start + n);
+    Err(DslError::at(source, Span { start, end }, "unterminated string"))
+}
+
+// ============================================================================
+// SYNTAX
+// ============================================================================
+
+/// A parsed policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    /// Rules in evaluation order
+    pub rules: Vec<Rule>,
+    /// What happens when no rule matches
+    pub default: DefaultAction,
+}
+
+/// `[rule <id>:] allow <class> [with pact "<pact>"] [when <expr>]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    /// Rule identifier
+    pub rule_id: String,
+    /// Intent class allowed
+    pub intent_class: IntentClassSpec,
+    /// Pact required to commit
+    pub required_pact: Option<String>,
+    /// Condition; None always matches
+    pub condition: Option<Expr>,
+    /// The whole rule
+    pub span: Span,
+}
+
+/// Outcome when no rule matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefaultAction {
+    /// Deny with the reason
+    Deny(String),
+    /// Allow Observation
+    Allow,
+}
+
+/// An expression with its span
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    /// What it is
+    pub kind: ExprKind,
+    /// Where it is
+    pub span: Span,
+}
+
+/// Expression forms
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    /// Integer literal
+    Int(i64),
+    /// String literal
+    Str(String),
+    /// `true` / `false`
+    Bool(bool),
+    /// `null`
+    Null,
+    /// `intent.<field>`
+    Intent(String),
+    /// `state.<field>`
+    State(String),
+    /// `context.<field>`
+    Context(String),
+    /// `flag.<name>`
+    Flag(String),
+    /// `actor`
+    Actor,
+    /// `container`
+    Container,
+    /// `timestamp`
+    Timestamp,
+    /// `not <expr>`
+    Not(Box<Expr>),
+    /// `-<expr>`
+    Neg(Box<Expr>),
+    /// `<expr> <op> <expr>`
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+/// Binary operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    /// `or`
+    Or,
+    /// `and`
+    And,
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+    /// `starts_with`
+    StartsWith,
+    /// `ends_with`
+    EndsWith,
+    /// `contains`
+    Contains,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `%`
+    Mod,
+}
+
+impl BinOp {
+    fn opcode(self) -> Opcode {
+        match self {
+            BinOp::Or => Opcode::Or,
+            BinOp::And => Opcode::And,
+            BinOp::Eq => Opcode::Eq,
+            BinOp::Ne => Opcode::Ne,
+            BinOp::Lt => Opcode::Lt,
+            BinOp::Le => Opcode::Le,
+            BinOp::Gt => Opcode::Gt,
+            BinOp::Ge => Opcode::Ge,
+            BinOp::StartsWith => Opcode::StrStartsWith,
+            BinOp::EndsWith => Opcode::StrEndsWith,
+            BinOp::Contains => Opcode::StrContains,
+            BinOp::Add => Opcode::Add,
+            BinOp::Sub => Opcode::Sub,
+            BinOp::Mul => Opcode::Mul,
+            BinOp::Div => Opcode::Div,
+            BinOp::Mod => Opcode::Mod,
+        }
+    }
+
+    /// (operand type, result type)
+    fn types(self) -> (Ty, Ty) {
+        match self {
+            BinOp::Or | BinOp::And => (Ty::Bool, Ty::Bool),
+            BinOp::Eq | BinOp::Ne => (Ty::Any, Ty::Bool),
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => (Ty::Int, Ty::Bool),
+            BinOp::StartsWith | BinOp::EndsWith | BinOp::Contains => (Ty::Str, Ty::Bool),
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => (Ty::Int, Ty::Int),
+        }
+    }
+}
+
+/// Static type of an expression; fields are `Any` until the VM sees them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Int,
+    Str,
+    Bool,
+    Null,
+    Any,
+}
+
+impl Ty {
+    fn name(self) -> &'static str {
+        match self {
+            Ty::Int => "an integer",
+            Ty::Str => "a string",
+            Ty::Bool => "a boolean",
+            Ty::Null => "null",
+            Ty::Any => "a value",
+        }
+    }
+
+    fn fits(self, expected: Ty) -> bool {
+        expected == Ty::Any || self == Ty::Any || self == expected
+    }
+}
+
+/// Parse and type-check a DSL source
+pub fn parse(source: &str) -> Result<Program, DslError> {
+    let tokens = lex(source)?;
+    let program = Parser { source, tokens, pos: 0 }.program()?;
+    for rule in &program.rules {
+        if let Some(condition) = &rule.condition {
+            check(source, condition, Ty::Bool)?;
+        }
+    }
+    Ok(program)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Tok, Span)>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Tok {
+        &self.tokens[self.pos].0
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.pos].1
+    }
+
+    fn advance(&mut self) -> (Tok, Span) {
+        let token = self.tokens[self.pos].clone();
+        if token.0 != Tok::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, span: Span, message: impl Into<String>) -> DslError {
+        DslError::at(self.source, span, message)
+    }
+
+    fn unexpected(&self, expected: &str) -> DslError {
+        self.error(self.span(), format!("expected {}, found {}", expected, self.peek().describe()))
+    }
+
+    fn at_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Tok::Ident(w) if w == word)
+    }
+
+    fn at_sym(&self, sym: &str) -> bool {
+        matches!(self.peek(), Tok::Sym(s) if *s == sym)
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let at = self.at_word(word);
+        if at {
+            self.pos += 1;
+        }
+        at
+    }
+
+    fn eat_sym(&mut self, sym: &str) -> bool {
+        let at = self.at_sym(sym);
+        if at {
+            self.pos += 1;
+        }
+        at
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<Span, DslError> {
+        let span = self.span();
+        if self.eat_word(word) {
+            Ok(span)
+        } else {
+            Err(self.unexpected(&format!("`{}`", word)))
+        }
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<Span, DslError> {
+        let span = self.span();
+        if self.eat_sym(sym) {
+            Ok(span)
+        } else {
+            Err(self.unexpected(&format!("`{}`", sym)))
+        }
+    }
+
+    /// An identifier that is not a keyword
+    fn name(&mut self, what: &str) -> Result<(String, Span), DslError> {
+        match self.peek().clone() {
+            Tok::Ident(name) if !KEYWORDS.contains(&name.as_str()) => Ok((name, self.advance().1)),
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    fn string(&mut self, what: &str) -> Result<(String, Span), DslError> {
+        match self.peek().clone() {
+            Tok::Str(value) => Ok((value, self.advance().1)),
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    fn program(mut self) -> Result<Program, DslError> {
+        let mut rules: Vec<Rule> = Vec::new();
+        let mut default: Option<DefaultAction> = None;
+        loop {
+            while self.eat_sym(";") {}
+            if *self.peek() == Tok::Eof {
+                break;
+            }
+            if default.is_some() {
+                return Err(self.error(self.span(), "nothing may follow `default`"));
+            }
+            if self.at_word("default") {
+                default = Some(self.default()?);
+                continue;
+            }
+            let rule = self.rule(rules.len() + 1)?;
+            if rules.iter().any(|r| r.rule_id == rule.rule_id) {
+                return Err(self.error(rule.span, format!("duplicate rule `{}`", rule.rule_id)));
+            }
+            if rules.len() == MAX_RULES {
+                return Err(self.error(rule.span, format!("too many rules (max {})", MAX_RULES)));
+            }
+            rules.push(rule);
+        }
+        let default = default.unwrap_or_else(|| DefaultAction::Deny("No matching rule".to_string()));
+        Ok(Program { rules, default })
+    }
+
+    fn default(&mut self) -> Result<DefaultAction, DslError> {
+        self.expect_word("default")?;
+        if self.eat_word("allow") {
+            return Ok(DefaultAction::Allow);
+        }
+        if !self.eat_word("deny") {
+            return Err(self.unexpected("`allow` or `deny`"));
+        }
+        let mut reason = "No matching rule".to_string();
+        if let Tok::Str(_) = self.peek() {
+            reason = self.string("a reason")?.0;
+        }
+        Ok(DefaultAction::Deny(reason))
+    }
+
+    fn rule(&mut self, n: usize) -> Result<Rule, DslError> {
+        let start = self.span();
+        let mut rule_id = format!("rule_{}", n);
+        let named = self.eat_word("rule");
+        if named {
+            let (name, span) = self.name("a rule name")?;
+            if name.len() > 256 {
+                return Err(self.error(span, format!("rule name too long: {} bytes (max 256)", name.len())));
+            }
+            rule_id = name;
+            self.expect_sym(":")?;
+        }
+        if !self.eat_word("allow") {
+            return Err(self.unexpected(if named { "`allow`" } else { "`rule`, `allow` or `default`" }));
+        }
+
+        let (class, mut end) = match self.peek().clone() {
+            Tok::Ident(word) => {
+                let class = match word.as_str() {
+                    "observation" => IntentClassSpec::Observation,
+                    "conservation" => IntentClassSpec::Conservation,
+                    "entropy" => IntentClassSpec::Entropy,
+                    "evolution" => IntentClassSpec::Evolution,
+                    _ => return Err(self.unexpected("an intent class (observation, conservation, entropy, evolution)")),
+                };
+                (class, self.advance().1)
+            }
+            _ => return Err(self.unexpected("an intent class (observation, conservation, entropy, evolution)")),
+        };
+
+        let mut required_pact = None;
+        if self.eat_word("with") {
+            self.expect_word("pact")?;
+            let (pact, span) = self.string("a pact id")?;
+            if pact.len() > 256 {
+                return Err(self.error(span, format!("pact id too long: {} bytes (max 256)", pact.len())));
+            }
+            required_pact = Some(pact);
+            end = span;
+        }
+
+        let mut condition = None;
+        if self.eat_word("when") {
+            let expr = self.expr()?;
+            end = expr.span;
+            condition = Some(expr);
+        }
+
+        Ok(Rule { rule_id, intent_class: class, required_pact, condition, span: start.to(end) })
+    }
+
+    fn expr(&mut self) -> Result<Expr, DslError> {
+        self.binary(0)
+    }
+
+    /// Precedence climbing over [`LEVELS`]
+    fn binary(&mut self, level: usize) -> Result<Expr, DslError> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(op) = self.binary_op(LEVELS[level]) {
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            let span = lhs.span.to(rhs.span);
+            lhs = Expr { kind: ExprKind::Binary(op, Box::new(lhs), Box::new(rhs)), span };
+            // Comparisons do not chain: `a < b < c` is an error
+            if level == COMPARISON_LEVEL {
+                if self.binary_op(LEVELS[level]).is_some() {
+                    return Err(self.error(self.span(), "comparisons do not chain; use `and`"));
+                }
+                break;
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn binary_op(&self, ops: &[(&str, BinOp)]) -> Option<BinOp> {
+        let token = match self.peek() {
+            Tok::Ident(word) => word.as_str(),
+            Tok::Sym(sym) => sym,
+            _ => return None,
+        };
+        ops.iter().find(|(text, _)| *text == token).map(|(_, op)| *op)
+    }
+
+    fn unary(&mut self) -> Result<Expr, DslError> {
+        let start = self.span();
+        if self.eat_word("not") {
+            let operand = self.unary_not()?;
+            let span = start.to(operand.span);
+            return Ok(Expr { kind: ExprKind::Not(Box::new(operand)), span });
+        }
+        if self.eat_sym("-") {
+            let operand = self.unary()?;
+            let span = start.to(operand.span);
+            if let ExprKind::Int(v) = operand.kind {
+                return Ok(Expr { kind: ExprKind::Int(v.saturating_neg()), span });
+            }
+            return Ok(Expr { kind: ExprKind::Neg(Box::new(operand)), span });
+        }
+        self.atom()
+    }
+
+    /// Operand of `not`: a comparison, so `not a == b` is `not (a == b)`
+    fn unary_not(&mut self) -> Result<Expr, DslError> {
+        self.binary(COMPARISON_LEVEL)
+    }
+
+    fn atom(&mut self) -> Result<Expr, DslError> {
+        let (token, span) = self.tokens[self.pos].clone();
+        let kind = match token {
+            Tok::Int(v) => ExprKind::Int(v),
+            Tok::Str(s) => ExprKind::Str(s),
+            Tok::Sym("(") => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                let end = self.expect_sym(")")?;
+                return Ok(Expr { kind: inner.kind, span: span.to(end) });
+            }
+            Tok::Ident(word) => match word.as_str() {
+                "true" => ExprKind::Bool(true),
+                "false" => ExprKind::Bool(false),
+                "null" => ExprKind::Null,
+                "actor" => ExprKind::Actor,
+                "container" => ExprKind::Container,
+                "timestamp" => ExprKind::Timestamp,
+                "intent" | "state" | "context" | "flag" => {
+                    self.pos += 1;
+                    self.expect_sym(".")?;
+                    let (field, end) = self.name("a field name")?;
+                    let kind = match word.as_str() {
+                        "intent" => ExprKind::Intent(field),
+                        "state" => ExprKind::State(field),
+                        "flag" => ExprKind::Flag(field),
+                        _ => ExprKind::Context(field),
+                    };
+                    return Ok(Expr { kind, span: span.to(end) });
+                }
+                _ => {
+                    return Err(self.error(
+                        span,
+                        format!("unknown name `{}`; fields are intent.<f>, state.<f>, context.<f> or flag.<name>", word),
+                    ))
+                }
+            },
+            _ => return Err(self.unexpected("an expression")),
+        };
+        self.pos += 1;
+        Ok(Expr { kind, span })
+    }
+}
+
+/// Binary operators by precedence, loosest first
+const LEVELS: [&[(&str, BinOp)]; 5] = [
+    &[("or", BinOp::Or)],
+    &[("and", BinOp::And)],
+    &[
+        ("==", BinOp::Eq),
+        ("!=", BinOp::Ne),
+        ("<=", BinOp::Le),
+        (">=", BinOp::Ge),
+        ("<", BinOp::Lt),
+        (">", BinOp::Gt),
+        ("starts_with", BinOp::StartsWith),
+        ("ends_with", BinOp::EndsWith),
+        ("contains", BinOp::Contains),
+    ],
+    &[("+", BinOp::Add), ("-", BinOp::Sub)],
+    &[("*", BinOp::Mul), ("/", BinOp::Div), ("%", BinOp::Mod)],
+];
+
+const COMPARISON_LEVEL: usize = 2;
+
+/// Type of `expr`, which must fit `expected`
+fn check(source: &str, expr: &Expr, expected: Ty) -> Result<Ty, DslError> {
+    let ty = match &expr.kind {
+        ExprKind::Int(_) | ExprKind::Timestamp => Ty::Int,
+        ExprKind::Str(_) | ExprKind::Actor | ExprKind::Container => Ty::Str,
+        ExprKind::Bool(_) | ExprKind::Flag(_) => Ty::Bool,
+        ExprKind::Null => Ty::Null,
+        ExprKind::Intent(_) | ExprKind::State(_) | ExprKind::Context(_) => Ty::Any,
+        ExprKind::Not(operand) => {
+            check(source, operand, Ty::Bool)?;
+            Ty::Bool
+        }
+        ExprKind::Neg(operand) => {
+            check(source, operand, Ty::Int)?;
+            Ty::Int
+        }
+        ExprKind::Binary(op, lhs, rhs) => {
+            let (operand, result) = op.types();
+            let left = check(source, lhs, operand)?;
+            let right = check(source, rhs, operand)?;
+            if matches!(op, BinOp::Eq | BinOp::Ne) && !left.fits(right) && left != Ty::Null && right != Ty::Null {
+                let verdict = if *op == BinOp::Eq { "never equal" } else { "always different" };
+                return Err(DslError::at(
+                    source,
+                    expr.span,
+                    format!("{} and {} are {}", left.name(), right.name(), verdict),
+                ));
+            }
+            if matches!(op, BinOp::Div | BinOp::Mod) && rhs.kind == ExprKind::Int(0) {
+                return Err(DslError::at(source, rhs.span, "division by zero"));
+            }
+            result
+        }
+    };
+    if !ty.fits(expected) {
+        return Err(DslError::at(
+            source,
+            expr.span,
+            format!("expected {}, found {}", expected.name(), ty.name()),
+        ));
+    }
+    Ok(ty)
+}
+
+// ============================================================================
+// LOWERING
+// ============================================================================
+
+impl PolicyCompiler {
+    /// Compile DSL source (see [the module docs](self)) to bytecode
+    pub fn compile_source(&mut self, policy_id: &str, version: &str, source: &str) -> Result<CompiledPolicy, DslError> {
+        let program = parse(source)?;
+        Ok(self.compile_program(policy_id, version, &program))
+    }
+
+    /// Compile a parsed [`Program`]
+    pub fn compile_program(&mut self, policy_id: &str, version: &str, program: &Program) -> CompiledPolicy {
+        self.constants.clear();
+        self.code.clear();
+        self.rules.clear();
+
+        for rule in &program.rules {
+            // Failing conditions jump past the rule's Allow, to the next rule
+            let mut to_next_rule = Vec::new();
+            if let Some(condition) = &rule.condition {
+                self.jump_unless(condition, false, &mut to_next_rule);
+            }
+            self.emit_allow(&rule.rule_id, rule.intent_class, rule.required_pact.as_deref());
+            self.patch_jumps(&to_next_rule);
+        }
+
+        match &program.default {
+            DefaultAction::Deny(reason) => self.emit_deny(reason),
+            DefaultAction::Allow => self.emit_default(false),
+        }
+        self.finish(policy_id, version)
+    }
+
+    /// Jump (to a patch recorded in `jumps`) when `expr` evaluates to
+    /// `when`, else fall through; `and`/`or`/`not` short-circuit
+    fn jump_unless(&mut self, expr: &Expr, when: bool, jumps: &mut Vec<usize>) {
+        match &expr.kind {
+            ExprKind::Not(operand) => self.jump_unless(operand, !when, jumps),
+            // Jumping on the value that decides the whole: every operand can
+            // take the jump
+            ExprKind::Binary(BinOp::And, lhs, rhs) if !when => {
+                self.jump_unless(lhs, false, jumps);
+                self.jump_unless(rhs, false, jumps);
+            }
+            ExprKind::Binary(BinOp::Or, lhs, rhs) if when => {
+                self.jump_unless(lhs, true, jumps);
+                self.jump_unless(rhs, true, jumps);
+            }
+            // Otherwise the left operand can settle it the other way: skip
+            // the right one
+            ExprKind::Binary(op @ (BinOp::And | BinOp::Or), lhs, rhs) => {
+                let mut settled = Vec::new();
+                self.jump_unless(lhs, *op == BinOp::Or, &mut settled);
+                self.jump_unless(rhs, when, jumps);
+                self.patch_jumps(&settled);
+            }
+            _ => {
+                self.emit_value(expr);
+                jumps.push(self.code.len());
+                self.emit(if when { Opcode::JumpIf } else { Opcode::JumpIfNot });
+                self.emit_u16(0); // Will be patched
+            }
+        }
+    }
+
+    /// Push the value of `expr`
+    fn emit_value(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Int(v) => self.emit_push_i64(*v),
+            ExprKind::Str(s) => {
+                let idx = self.add_constant(s);
+                self.emit(Opcode::PushStr);
+                self.emit_u16(idx);
+            }
+            ExprKind::Bool(true) => self.emit(Opcode::PushTrue),
+            ExprKind::Bool(false) => self.emit(Opcode::PushFalse),
+            ExprKind::Null => self.emit(Opcode::PushNull),
+            ExprKind::Intent(field) => self.emit_load(Opcode::LoadIntent, field),
+            ExprKind::State(field) => self.emit_load(Opcode::LoadState, field),
+            ExprKind::Context(field) => self.emit_load(Opcode::LoadContext, field),
+            ExprKind::Flag(name) => self.emit_load(Opcode::LoadContext, &format!("{}{}", FLAG_PREFIX, name)),
+            ExprKind::Actor => self.emit(Opcode::LoadActor),
+            ExprKind::Container => self.emit(Opcode::LoadContainerId),
+            ExprKind::Timestamp => self.emit(Opcode::LoadTimestamp),
+            ExprKind::Not(operand) => {
+                self.emit_value(operand);
+                self.emit(Opcode::Not);
+            }
+            ExprKind::Neg(operand) => {
+                self.emit_value(operand);
+                self.emit(Opcode::Neg);
+            }
+            ExprKind::Binary(op, lhs, rhs) => {
+                self.emit_value(lhs);
+                self.emit_value(rhs);
+                self.emit(op.opcode());
+            }
+        }
+    }
+
+    fn emit_load(&mut self, op: Opcode, field: &str) {
+        let idx = self.add_constant(field);
+        self.emit(op);
+        self.emit_u16(idx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode::{BytecodeVM, ExecutionContext, PolicyResult};
+    use crate::compiler::create_default_policy;
+    use serde_json::json;
+
+    fn run(policy: &CompiledPolicy, intent: serde_json::Value, state: Option<serde_json::Value>) -> PolicyResult {
+        let ctx = ExecutionContext {
+            container_id: "C.Jobs".to_string(),
+            actor: "alice".to_string(),
+            intent,
+            state,
+            timestamp: 1000,
+            flags: Default::default(),
+        };
+        BytecodeVM::default().execute(policy, &ctx).unwrap()
+    }
+
+    fn compile(source: &str) -> CompiledPolicy {
+        PolicyCompiler::new()
+            .compile_source("test", "1.0", source)
+            .unwrap_or_else(|e| panic!("{}", e.render(source)))
+    }
+
+    const DEFAULT_POLICY: &str = r#"
+        rule allow_observation: allow observation when intent.type == "observe"
+        rule allow_small_transfer:
+            allow conservation when intent.type == "transfer" and intent.amount <= 10000
+        rule allow_large_transfer:
+            allow conservation with pact "high_value_transfer"
+            when intent.type == "transfer" and intent.amount >= 10001
+        rule evolution_requires_pact: allow evolution with pact "evolution_l5" when intent.type == "evolve"
+        default deny
+    "#;
+
+    #[test]
+    fn test_decides_like_the_json_policy() {
+        let json = PolicyCompiler::new().compile(&create_default_policy("C.Jobs"));
+        let dsl = compile(DEFAULT_POLICY);
+        assert_eq!(dsl.rules.iter().map(|r| &r.rule_id).collect::<Vec<_>>(), json.rules.iter().map(|r| &r.rule_id).collect::<Vec<_>>());
+
+        for intent in [
+            json!({"type": "observe"}),
+            json!({"type": "transfer", "amount": 10000}),
+            json!({"type": "transfer", "amount": 10001}),
+            json!({"type": "evolve"}),
+            json!({"type": "hack"}),
+        ] {
+            assert_eq!(run(&dsl, intent.clone(), None), run(&json, intent, None));
+        }
+    }
+
+    #[test]
+    fn test_or_not_and_nesting() {
+        let policy = compile(
+            r#"
+            allow observation when intent.type == "observe" or intent.type == "ping"
+            allow entropy when intent.type == "mint"
+                and (state.entropy_minted + intent.amount <= 1000 or actor == "treasury")
+                and not actor starts_with "guest:"
+            default deny "nope"
+            "#,
+        );
+        assert!(matches!(run(&policy, json!({"type": "ping"}), None), PolicyResult::Allow { intent_class: 0, .. }));
+        let minted = |m: i64| Some(json!({"entropy_minted": m}));
+        assert!(matches!(
+            run(&policy, json!({"type": "mint", "amount": 100}), minted(900)),
+            PolicyResult::Allow { intent_class: 2, .. }
+        ));
+        assert_eq!(
+            run(&policy, json!({"type": "mint", "amount": 101}), minted(900)),
+            PolicyResult::Deny { reason: "nope".to_string() }
+        );
+        // Short-circuits: a `burn` never adds its (missing) amount, which
+        // would fail the evaluation
+        assert_eq!(run(&policy, json!({"type": "burn"}), None), PolicyResult::Deny { reason: "nope".to_string() });
+        assert_eq!(policy.rule_at(policy.rules[1].pc), Some("rule_2"));
+    }
+
+    #[test]
+    fn test_errors_point_at_the_text() {
+        let source = "allow conservation\n    when intent.type == \"transfer\" and intent.amount <= \"ten\"";
+        let err = parse(source).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 57));
+        assert_eq!(&source[err.span.start..err.span.end], "\"ten\"");
+        assert_eq!(err.message, "expected an integer, found a string");
+        assert!(err.render(source).ends_with(&format!("{}^^^^^", " ".repeat(56))), "{}", err.render(source));
+
+        let cases = [
+            ("allow conservation when intent.amount <=", "", "expected an expression, found end of input"),
+            ("allow teleport", "teleport", "expected an intent class"),
+            ("allow observation when 1 < 2 < 3", "<", "comparisons do not chain"),
+            ("allow observation when intent.type == 3 + \"x\"", "\"x\"", "expected an integer, found a string"),
+            ("allow observation when amount > 3", "amount", "unknown name `amount`"),
+            ("allow observation when actor == 7", "actor == 7", "a string and an integer are never equal"),
+            ("allow observation when flag.threading > 0", "flag.threading", "expected an integer, found a boolean"),
+            ("allow observation when (intent.a", "", "expected `)`"),
+            ("allow observation when intent.a == \"x", "\"x", "unterminated string"),
+            ("allow observation when intent.amount / 0 > 1", "0", "division by zero"),
+            ("rule a: allow observation\nrule a: allow entropy", "rule a: allow entropy", "duplicate rule `a`"),
+            ("default deny\nallow observation", "allow", "nothing may follow `default`"),
+            ("allow observation when intent.a @ 1", "@", "unexpected character '@'"),
+        ];
+        for (source, at, message) in cases {
+            let err = parse(source).unwrap_err();
+            assert_eq!(&source[err.span.start..err.span.end], at, "{}", err);
+            assert!(err.message.starts_with(message), "{}", err);
+        }
+    }
+}
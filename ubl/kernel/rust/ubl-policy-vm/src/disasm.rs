@@ -0,0 +1,223 @@
+//! Bytecode disassembler
+//!
+//! [`disassemble`] renders a [`CompiledPolicy`] as annotated text: one
+//! instruction per line with its offset, decoded operands (constants are
+//! shown with their value) and the rule each Allow belongs to. Bytes that do
+//! not decode are listed as `db` so a corrupted policy still reads through.
+//!
+//! ```text
+//! ; policy default_C.Jobs v1.0
+//! ; hash 47d6535e…
+//! ; 122 bytes, 8 constants, 4 rules
+//! 0000  LoadIntent     #0 "type"
+//! 0003  PushStr        #1 "observe"
+//! 0006  StrEq
+//! 0007  JumpIfNot      @0014
+//! 000a  PushI64        0
+//! 0013  Allow                   ; rule allow_observation
+//! ```
+//!
+//! [`decode`] is the same decoder, one instruction at a time; the VM's traced
+//! mode ([`crate::BytecodeVM::execute_traced`]) uses it to label its steps.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use crate::bytecode::{CompiledPolicy, Opcode};
+
+/// Operand of a decoded instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// `PushI64`
+    I64(i64),
+    /// Constant pool index, with the constant if it exists
+    Constant(u16, Option<String>),
+    /// Jump target
+    Address(u16),
+}
+
+/// One decoded instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// Offset in the bytecode
+    pub pc: usize,
+    /// None for a byte that is not an opcode
+    pub opcode: Option<Opcode>,
+    /// The raw opcode byte
+    pub byte: u8,
+    /// Decoded operand, if the opcode takes one and it is complete
+    pub operand: Option<Operand>,
+    /// Bytes taken, operand included (what remains if it is truncated)
+    pub len: usize,
+    /// The operand runs past the end of the bytecode
+    pub truncated: bool,
+}
+
+impl Instruction {
+    /// Mnemonic, or `db 0xNN` for a byte that is not an opcode
+    pub fn mnemonic(&self) -> String {
+        match self.opcode {
+            Some(op) => format!("{:?}", op),
+            None => format!("db 0x{:02x}", self.byte),
+        }
+    }
+
+    /// Operand as text (`#1 "observe"`, `@0011`, `42`), if any
+    pub fn operand_text(&self) -> Option<String> {
+        self.operand.as_ref().map(|operand| match operand {
+            Operand::I64(v) => format!("{}", v),
+            Operand::Constant(idx, Some(value)) => format!("#{} {:?}", idx, value),
+            Operand::Constant(idx, None) => format!("#{} <missing>", idx),
+            Operand::Address(addr) => format!("@{:04x}", addr),
+        })
+    }
+}
+
+/// Opcode for a byte
+pub fn opcode_of(byte: u8) -> Option<Opcode> {
+    use Opcode::*;
+    const ALL: [Opcode; 48] = [
+        Nop, PushI64, PushStr, PushTrue, PushFalse, Pop, Dup, Swap, PushNull,
+        LoadContext, LoadState, LoadIntent, LoadTimestamp, LoadContainerId, LoadActor, HasIntent,
+        Eq, Ne, Lt, Le, Gt, Ge, IsNull, IsNotNull,
+        Add, Sub, Mul, Div, Mod, Neg, Abs,
+        And, Or, Not,
+        Jump, JumpIf, JumpIfNot,
+        StrContains, StrStartsWith, StrEq, StrEndsWith, StrLen, StrLower,
+        Allow, AllowWithPact, Deny, AllowWithConstraints, Halt,
+    ];
+    ALL.into_iter().find(|op| *op as u8 == byte)
+}
+
+/// Decode the instruction at `pc`; None past the end of the bytecode
+pub fn decode(code: &[u8], constants: &[String], pc: usize) -> Option<Instruction> {
+    let byte = *code.get(pc)?;
+    let opcode = opcode_of(byte);
+    let width = match opcode {
+        Some(Opcode::PushI64) => 8,
+        Some(
+            Opcode::PushStr
+            | Opcode::LoadContext
+            | Opcode::LoadState
+            | Opcode::LoadIntent
+            | Opcode::HasIntent
+            | Opcode::Jump
+            | Opcode::JumpIf
+            | Opcode::JumpIfNot,
+        ) => 2,
+        _ => 0,
+    };
+    let rest = &code[pc + 1..];
+    if rest.len() < width {
+        return Some(Instruction { pc, opcode, byte, operand: None, len: 1 + rest.len(), truncated: true });
+    }
+    let operand = match opcode {
+        Some(Opcode::PushI64) => {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&rest[..8]);
+            Some(Operand::I64(i64::from_be_bytes(bytes)))
+        }
+        Some(Opcode::Jump | Opcode::JumpIf | Opcode::JumpIfNot) => {
+            Some(Operand::Address(u16::from_be_bytes([rest[0], rest[1]])))
+        }
+        _ if width == 2 => {
+            let idx = u16::from_be_bytes([rest[0], rest[1]]);
+            Some(Operand::Constant(idx, constants.get(idx as usize).cloned()))
+        }
+        _ => None,
+    };
+    Some(Instruction { pc, opcode, byte, operand, len: 1 + width, truncated: false })
+}
+
+/// Every instruction of `policy`, in order
+pub fn instructions(policy: &CompiledPolicy) -> Vec<Instruction> {
+    let mut out = Vec::new();
+    let mut pc = 0;
+    while let Some(instruction) = decode(&policy.code, &policy.constants, pc) {
+        pc += instruction.len;
+        out.push(instruction);
+    }
+    out
+}
+
+/// Annotated listing of `policy` (see the module docs)
+pub fn disassemble(policy: &CompiledPolicy) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "; policy {} v{}", policy.policy_id, policy.version);
+    let _ = writeln!(out, "; hash {}{}", policy.hash, if policy.verify_hash() { "" } else { " (MISMATCH)" });
+    let _ = writeln!(
+        out,
+        "; {} bytes, {} constants, {} rules",
+        policy.code.len(),
+        policy.constants.len(),
+        policy.rules.len()
+    );
+    for instruction in instructions(policy) {
+        let mut line = format!("{:04x}  {:<14} {}", instruction.pc, instruction.mnemonic(), instruction.operand_text().unwrap_or_default());
+        let note = if instruction.truncated {
+            Some(String::from("truncated operand"))
+        } else if instruction.opcode.is_none() {
+            Some(String::from("not an opcode"))
+        } else {
+            policy.rule_at(instruction.pc).map(|rule| format!("rule {}", rule))
+        };
+        match note {
+            Some(note) => {
+                let _ = write!(line, "{:<width$}; {}", "", note, width = 30usize.saturating_sub(line.len()).max(1));
+            }
+            None => line.truncate(line.trim_end().len()),
+        }
+        let _ = writeln!(out, "{}", line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::{create_default_policy, PolicyCompiler};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_opcode_table_is_complete() {
+        for byte in 0..=u8::MAX {
+            if let Some(op) = opcode_of(byte) {
+                assert_eq!(op as u8, byte);
+            }
+        }
+        assert_eq!(opcode_of(0xF2), Some(Opcode::Deny));
+        assert_eq!(opcode_of(0x99), None);
+    }
+
+    #[test]
+    fn test_disassemble_compiled_policy() {
+        let policy = PolicyCompiler::new().compile(&create_default_policy("C.Jobs"));
+        let listing = disassemble(&policy);
+
+        assert!(listing.starts_with(&format!("; policy {} v{}\n", policy.policy_id, policy.version)));
+        assert!(!listing.contains("MISMATCH"));
+        // Decoding walks the whole bytecode, instruction by instruction
+        let decoded: usize = instructions(&policy).iter().map(|i| i.len).sum();
+        assert_eq!(decoded, policy.code.len());
+        // Every rule's Allow is annotated with it
+        for rule in &policy.rules {
+            assert!(listing.contains(&format!("; rule {}", rule.rule_id)), "{}", listing);
+        }
+        assert!(listing.contains("LoadIntent"));
+    }
+
+    #[test]
+    fn test_disassemble_damaged_bytecode() {
+        let code = vec![0x02, 0x00, 0x00, 0x99, 0x01, 0x00];
+        let policy = CompiledPolicy::new("broken", "1.0", code, vec!["reason".to_string()]);
+        let listing = disassemble(&policy);
+        assert!(listing.contains("0000  PushStr        #0 \"reason\""), "{}", listing);
+        assert!(listing.contains("0003  db 0x99"));
+        assert!(listing.contains("; not an opcode"));
+        assert!(listing.contains("0004  PushI64"));
+        assert!(listing.contains("; truncated operand"));
+    }
+}
@@ -13,7 +13,7 @@
 //! ## Architecture
 //!
 //! ```text
-//! PolicyDefinition (JSON)
+//! PolicyDefinition (JSON) / DSL text
 //!         │
 //!         ▼
 //! ┌───────────────┐
@@ -32,20 +32,50 @@
 //! PolicyResult (Allow/Deny)
 //! ```
 
+//!
+//! ## Features
+//!
+//! - `std` (default): [`PolicyVM`], the registry the server shares between
+//!   requests. Without it the crate is `no_std` + `alloc`: the compiler and
+//!   [`BytecodeVM`] only, with the same bytecode, hashes and decisions.
+//! - `wasm`: a wasm-bindgen binding ([`wasm`]) so the Mind (TypeScript) can
+//!   pre-evaluate policies locally. Build for `wasm32-unknown-unknown`:
+//!
+//! ```text
+//! cargo rustc -p ubl-policy-vm --release --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm --crate-type cdylib
+//! wasm-bindgen --target nodejs --out-dir ubl-policy-vm/pkg \
+//!     target/wasm32-unknown-unknown/release/ubl_policy_vm.wasm
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
+extern crate alloc;
+
 pub mod bytecode;
 pub mod compiler;
-
+pub mod disasm;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use arc_swap::ArcSwap;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 // Re-exports
 pub use bytecode::{
-    BytecodeVM, CompiledPolicy, ExecutionContext, PolicyResult,
-    BytecodeError, Opcode, Value, VMConfig,
+    BytecodeVM, CompiledPolicy, ExecutionContext, PolicyResult, RuleOffset,
+    BytecodeError, Opcode, Value, VMConfig, ExecutionTrace, TraceStep, FLAG_PREFIX,
     // Security limits
     MAX_BYTECODE_SIZE, MAX_CONSTANTS, MAX_STRING_LENGTH,
     MAX_GAS, MAX_STACK_SIZE,
@@ -53,10 +83,12 @@ pub use bytecode::{
     INTENT_CLASS_OBSERVATION, INTENT_CLASS_CONSERVATION,
     INTENT_CLASS_ENTROPY, INTENT_CLASS_EVOLUTION,
 };
+pub use disasm::disassemble;
 pub use compiler::{
     PolicyCompiler, PolicyDefinition, PolicyRule, 
     AppliesTo, IntentClassSpec, Constraint,
     create_default_policy, CompilerError,
+    dsl::DslError,
     MAX_RULES, MAX_CONSTRAINTS_PER_RULE,
 };
 
@@ -85,7 +117,7 @@ pub enum PolicyError {
 }
 
 /// Result type for policy operations
-pub type Result<T> = std::result::Result<T, PolicyError>;
+pub type Result<T> = core::result::Result<T, PolicyError>;
 
 /// Translation decision from TDLN (SPEC-UBL-POLICY v1.0 §6)
 /// Legacy type for compatibility - use PolicyResult from bytecode module
@@ -127,6 +159,18 @@ impl From<PolicyResult> for TranslationDecision {
     }
 }
 
+impl TranslationDecision {
+    /// Rule that allowed; `None` for a denial or the policy's default
+    pub fn matched_rule(&self) -> Option<&str> {
+        match self {
+            TranslationDecision::Allow { constraints, .. } => {
+                constraints.iter().find(|c| c.kind == "applied").map(|c| c.value.as_str())
+            }
+            TranslationDecision::Deny { .. } => None,
+        }
+    }
+}
+
 /// A constraint from policy evaluation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ConstraintSnapshot {
@@ -173,6 +217,10 @@ pub struct EvaluationContext {
     
     /// Timestamp
     pub timestamp: i64,
+
+    /// Feature flags of the container (see [`ExecutionContext::flags`])
+    #[serde(default)]
+    pub flags: BTreeMap<String, bool>,
 }
 
 impl From<EvaluationContext> for ExecutionContext {
@@ -183,60 +231,146 @@ impl From<EvaluationContext> for ExecutionContext {
             intent: ctx.intent,
             state: ctx.state,
             timestamp: ctx.timestamp,
+            flags: ctx.flags,
         }
     }
 }
 
+/// Outcome of one evaluation together with its gas consumption
+#[derive(Debug, Clone)]
+pub struct MeteredDecision {
+    /// The decision, as returned by [`PolicyVM::evaluate`]
+    pub decision: Result<TranslationDecision>,
+    /// Gas consumed (the whole budget if it ran out)
+    pub gas_used: u64,
+    /// Gas budget per evaluation
+    pub max_gas: u64,
+}
+
+impl MeteredDecision {
+    /// Share of the budget consumed, 0.0-1.0
+    pub fn budget_used(&self) -> f64 {
+        if self.max_gas == 0 {
+            return 0.0;
+        }
+        self.gas_used as f64 / self.max_gas as f64
+    }
+}
+
+/// Evaluate one compiled policy: what [`PolicyVM::evaluate_metered`] does
+/// once it has the policy, and what the `wasm` binding does, so a policy
+/// evaluated in either place decides the same
+pub fn evaluate_compiled(vm: &BytecodeVM, policy: &CompiledPolicy, context: &EvaluationContext) -> MeteredDecision {
+    let exec_ctx = ExecutionContext {
+        container_id: context.container_id.clone(),
+        actor: context.actor.clone(),
+        intent: context.intent.clone(),
+        state: context.state.clone(),
+        timestamp: context.timestamp,
+        flags: context.flags.clone(),
+    };
+
+    let (result, gas_used) = vm.execute_metered(policy, &exec_ctx);
+    MeteredDecision {
+        decision: result
+            .map(Into::into)
+            .map_err(|e| PolicyError::ExecutionFailed(e.to_string())),
+        gas_used,
+        max_gas: vm.max_gas(),
+    }
+}
+
+/// Outcome of a traced evaluation
+#[derive(Debug, Clone)]
+pub struct TracedDecision {
+    /// The decision, as [`evaluate_compiled`] returns it
+    pub decision: Result<TranslationDecision>,
+    /// Every instruction run, with the stack and gas at each
+    pub trace: ExecutionTrace,
+}
+
+/// [`evaluate_compiled`] with an instruction trace
+/// ([`BytecodeVM::execute_traced`]); for debugging a policy
+pub fn evaluate_traced(vm: &BytecodeVM, policy: &CompiledPolicy, context: &EvaluationContext) -> TracedDecision {
+    let exec_ctx = ExecutionContext::from(context.clone());
+    let (result, trace) = vm.execute_traced(policy, &exec_ctx);
+    TracedDecision {
+        decision: result
+            .map(Into::into)
+            .map_err(|e| PolicyError::ExecutionFailed(e.to_string())),
+        trace,
+    }
+}
+
+/// Registered policies (policy_id -> CompiledPolicy)
+#[cfg(feature = "std")]
+type PolicyMap = HashMap<String, Arc<CompiledPolicy>>;
+
 /// Policy VM - executes TDLN policies
+///
+/// Shareable as-is (`Arc<PolicyVM>`): the policy map is an immutable
+/// snapshot behind an [`ArcSwap`]. Evaluation loads the current snapshot
+/// without taking a lock; registration builds a new map and swaps it in, so
+/// a hot policy update never waits for, nor blocks, in-flight evaluations.
+/// An evaluation that started before a swap finishes on the policy it loaded.
+#[cfg(feature = "std")]
 pub struct PolicyVM {
-    /// Registered policies (policy_id -> CompiledPolicy)
-    policies: HashMap<String, CompiledPolicy>,
+    /// Current policy snapshot
+    policies: ArcSwap<PolicyMap>,
     /// Bytecode VM for execution
     vm: BytecodeVM,
-    /// Compiler for on-the-fly compilation
-    compiler: PolicyCompiler,
 }
 
+#[cfg(feature = "std")]
 impl PolicyVM {
     /// Create a new policy VM
     pub fn new() -> Self {
         Self {
-            policies: HashMap::new(),
+            policies: ArcSwap::default(),
             vm: BytecodeVM::default(),
-            compiler: PolicyCompiler::new(),
         }
     }
 
     /// Create with custom limits
     pub fn with_limits(max_gas: u64, max_stack: usize) -> Self {
         Self {
-            policies: HashMap::new(),
+            policies: ArcSwap::default(),
             vm: BytecodeVM::new(max_gas, max_stack),
-            compiler: PolicyCompiler::new(),
         }
     }
 
-    /// Register a compiled policy
-    pub fn register_compiled(&mut self, policy: CompiledPolicy) {
-        self.policies.insert(policy.policy_id.clone(), policy);
+    /// Register a compiled policy, replacing any policy with the same id
+    pub fn register_compiled(&self, policy: CompiledPolicy) {
+        self.insert(policy.policy_id.clone(), policy);
     }
 
     /// Register a policy definition (compiles it)
-    pub fn register(&mut self, definition: &PolicyDefinition) {
-        let compiled = self.compiler.compile(definition);
-        self.policies.insert(compiled.policy_id.clone(), compiled);
+    pub fn register(&self, definition: &PolicyDefinition) {
+        let compiled = PolicyCompiler::new().compile(definition);
+        self.register_compiled(compiled);
     }
 
     /// Register a legacy Policy struct
-    pub fn register_legacy(&mut self, policy: Policy) {
+    pub fn register_legacy(&self, policy: Policy) {
         if let Some(compiled) = policy.compiled {
-            self.policies.insert(policy.policy_id, compiled);
+            self.insert(policy.policy_id, compiled);
         }
     }
 
-    /// Get a registered policy
-    pub fn get_policy(&self, policy_id: &str) -> Option<&CompiledPolicy> {
-        self.policies.get(policy_id)
+    /// Swap in a new snapshot with `policy` under `policy_id`. `rcu` retries
+    /// on a concurrent swap, so racing registrations are never lost.
+    fn insert(&self, policy_id: String, policy: CompiledPolicy) {
+        let policy = Arc::new(policy);
+        self.policies.rcu(|current| {
+            let mut next = PolicyMap::clone(current);
+            next.insert(policy_id.clone(), Arc::clone(&policy));
+            next
+        });
+    }
+
+    /// Get a registered policy (as of now; later updates don't affect it)
+    pub fn get_policy(&self, policy_id: &str) -> Option<Arc<CompiledPolicy>> {
+        self.policies.load().get(policy_id).cloned()
     }
 
     /// Evaluate a policy (SPEC-UBL-POLICY v1.0 §6)
@@ -245,42 +379,41 @@ impl PolicyVM {
         policy_id: &str,
         context: &EvaluationContext,
     ) -> Result<TranslationDecision> {
-        let policy = self.policies
-            .get(policy_id)
-            .ok_or_else(|| PolicyError::PolicyNotFound(policy_id.to_string()))?;
-
-        let exec_ctx = ExecutionContext {
-            container_id: context.container_id.clone(),
-            actor: context.actor.clone(),
-            intent: context.intent.clone(),
-            state: context.state.clone(),
-            timestamp: context.timestamp,
-        };
+        self.evaluate_metered(policy_id, context).decision
+    }
 
-        let result = self.vm.execute(policy, &exec_ctx)
-            .map_err(|e| PolicyError::ExecutionFailed(e.to_string()))?;
+    /// Evaluate a policy and report the gas it consumed
+    pub fn evaluate_metered(&self, policy_id: &str, context: &EvaluationContext) -> MeteredDecision {
+        let Some(policy) = self.get_policy(policy_id) else {
+            return MeteredDecision {
+                decision: Err(PolicyError::PolicyNotFound(policy_id.to_string())),
+                gas_used: 0,
+                max_gas: self.vm.max_gas(),
+            };
+        };
 
-        Ok(result.into())
+        evaluate_compiled(&self.vm, &policy, context)
     }
 
     /// Check if a policy is registered
     pub fn has_policy(&self, policy_id: &str) -> bool {
-        self.policies.contains_key(policy_id)
+        self.policies.load().contains_key(policy_id)
     }
 
     /// List all registered policy IDs
-    pub fn list_policies(&self) -> Vec<&str> {
-        self.policies.keys().map(|s| s.as_str()).collect()
+    pub fn list_policies(&self) -> Vec<String> {
+        self.policies.load().keys().cloned().collect()
     }
 }
 
+#[cfg(feature = "std")]
 impl Default for PolicyVM {
     fn default() -> Self {
         Self::new()
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use serde_json::json;
@@ -297,12 +430,13 @@ mod tests {
             intent,
             state: None,
             timestamp: 1000,
+            flags: Default::default(),
         }
     }
 
     #[test]
     fn test_register_and_evaluate() {
-        let mut vm = PolicyVM::new();
+        let vm = PolicyVM::new();
         
         // Create and register a policy
         let definition = PolicyDefinition {
@@ -339,7 +473,7 @@ mod tests {
 
     #[test]
     fn test_default_deny() {
-        let mut vm = PolicyVM::new();
+        let vm = PolicyVM::new();
         
         let definition = PolicyDefinition {
             policy_id: "strict".to_string(),
@@ -370,7 +504,7 @@ mod tests {
 
     #[test]
     fn test_pact_requirement() {
-        let mut vm = PolicyVM::new();
+        let vm = PolicyVM::new();
         
         let definition = PolicyDefinition {
             policy_id: "evolution".to_string(),
@@ -406,7 +540,7 @@ mod tests {
 
     #[test]
     fn test_amount_thresholds() {
-        let mut vm = PolicyVM::new();
+        let vm = PolicyVM::new();
         
         let definition = PolicyDefinition {
             policy_id: "transfer".to_string(),
@@ -460,6 +594,94 @@ mod tests {
         }
     }
 
+    fn versioned_policy(version: u32, intent_type: &str) -> PolicyDefinition {
+        PolicyDefinition {
+            policy_id: "hot".to_string(),
+            version: format!("{}.0", version),
+            description: "Hot-swapped".to_string(),
+            rules: vec![
+                PolicyRule {
+                    rule_id: format!("allow_{}", intent_type),
+                    applies_to: AppliesTo::Global,
+                    intent_class: IntentClassSpec::Observation,
+                    constraints: vec![
+                        Constraint::IntentTypeEquals { value: intent_type.to_string() },
+                    ],
+                    required_pact: None,
+                },
+            ],
+            default_deny: true,
+        }
+    }
+
+    #[test]
+    fn test_vm_is_shareable() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<PolicyVM>();
+    }
+
+    #[test]
+    fn test_concurrent_evaluation_during_hot_updates() {
+        // Even versions allow "observe", odd versions allow "watch". Every
+        // evaluation must see exactly one whole version: one of the two
+        // intents allowed, the other denied, never both or neither.
+        let vm = PolicyVM::new();
+        vm.register(&versioned_policy(0, "observe"));
+
+        let updates = 500;
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..2_000 {
+                        let policy = vm.get_policy("hot").unwrap();
+                        let allows = |intent: &str| {
+                            let ctx = ExecutionContext::from(make_context(intent, None));
+                            vm.vm.execute(&policy, &ctx).unwrap().is_allow()
+                        };
+                        assert_ne!(allows("observe"), allows("watch"), "torn policy v{}", policy.version);
+
+                        // The public path never misses the policy mid-swap
+                        vm.evaluate("hot", &make_context("observe", None)).unwrap();
+                    }
+                });
+            }
+            // Two writers racing: one hot-swaps "hot", the other keeps adding
+            // policies; rcu must not lose either's updates
+            s.spawn(|| {
+                for version in 1..=updates {
+                    let intent = if version % 2 == 0 { "observe" } else { "watch" };
+                    vm.register(&versioned_policy(version, intent));
+                }
+            });
+            s.spawn(|| {
+                for i in 0..updates {
+                    let mut other = versioned_policy(0, "observe");
+                    other.policy_id = format!("other_{}", i);
+                    vm.register(&other);
+                }
+            });
+        });
+
+        assert_eq!(vm.get_policy("hot").unwrap().version, format!("{}.0", updates));
+        assert_eq!(vm.list_policies().len(), updates as usize + 1);
+    }
+
+    #[test]
+    fn test_evaluate_metered() {
+        let vm = PolicyVM::with_limits(1_000, 256);
+        vm.register(&versioned_policy(1, "observe"));
+
+        let metered = vm.evaluate_metered("hot", &make_context("observe", None));
+        assert!(matches!(metered.decision, Ok(TranslationDecision::Allow { .. })));
+        assert!(metered.gas_used > 0);
+        assert_eq!(metered.max_gas, 1_000);
+        assert!(metered.budget_used() > 0.0 && metered.budget_used() < 1.0);
+
+        let missing = vm.evaluate_metered("nonexistent", &make_context("observe", None));
+        assert!(matches!(missing.decision, Err(PolicyError::PolicyNotFound(_))));
+        assert_eq!(missing.gas_used, 0);
+    }
+
     #[test]
     fn test_policy_not_found() {
         let vm = PolicyVM::new();
@@ -0,0 +1,138 @@
+//! JS/TS binding (wasm-bindgen)
+//!
+//! Lets the Mind pre-evaluate a policy before proposing a link. Compilation
+//! and evaluation are the server's own ([`PolicyCompiler`], [`evaluate_compiled`]
+//! on a default [`BytecodeVM`]), so the decision, matched rule and gas are the
+//! ones admission will get. Inputs and outputs are JSON strings in the
+//! server's shapes; `ubl/mind/src/policy.ts` wraps them in types.
+
+use alloc::string::{String, ToString};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    evaluate_compiled, BytecodeVM, CompiledPolicy, EvaluationContext, PolicyCompiler, PolicyDefinition,
+    TranslationDecision,
+};
+
+/// A compiled policy, ready to evaluate
+#[wasm_bindgen(js_name = Policy)]
+pub struct WasmPolicy {
+    compiled: CompiledPolicy,
+    vm: BytecodeVM,
+}
+
+/// Result of [`WasmPolicy::evaluate`]
+#[derive(Serialize)]
+struct Evaluation<'a> {
+    decision: &'a TranslationDecision,
+    /// Rule that allowed; null for a denial or the policy's default
+    rule: Option<&'a str>,
+    gas_used: u64,
+    max_gas: u64,
+}
+
+#[wasm_bindgen(js_class = Policy)]
+impl WasmPolicy {
+    /// Compile a `PolicyDefinition` (JSON), as the server does when it
+    /// registers one
+    pub fn compile(definition_json: &str) -> Result<WasmPolicy, JsError> {
+        Self::compile_json(definition_json).map_err(|e| JsError::new(&e))
+    }
+
+    /// Load a `CompiledPolicy` (JSON); refused if its hash does not match its
+    /// bytecode
+    #[wasm_bindgen(js_name = fromCompiled)]
+    pub fn from_compiled(compiled_json: &str) -> Result<WasmPolicy, JsError> {
+        Self::from_compiled_json(compiled_json).map_err(|e| JsError::new(&e))
+    }
+
+    /// Policy id
+    #[wasm_bindgen(getter, js_name = policyId)]
+    pub fn policy_id(&self) -> String {
+        self.compiled.policy_id.clone()
+    }
+
+    /// BLAKE3 hash of the bytecode; equal to the server's for the same policy
+    #[wasm_bindgen(getter)]
+    pub fn hash(&self) -> String {
+        self.compiled.hash.clone()
+    }
+
+    /// Evaluate an `EvaluationContext` (JSON). Returns
+    /// `{"decision", "rule", "gas_used", "max_gas"}`; throws when the policy
+    /// fails to execute, where admission would refuse the commit.
+    pub fn evaluate(&self, context_json: &str) -> Result<String, JsError> {
+        self.evaluate_json(context_json).map_err(|e| JsError::new(&e))
+    }
+}
+
+impl WasmPolicy {
+    fn new(compiled: CompiledPolicy) -> Self {
+        Self { compiled, vm: BytecodeVM::default() }
+    }
+
+    fn compile_json(definition_json: &str) -> Result<Self, String> {
+        let definition: PolicyDefinition = serde_json::from_str(definition_json).map_err(|e| e.to_string())?;
+        let compiled = PolicyCompiler::new().compile_validated(&definition).map_err(|e| e.to_string())?;
+        Ok(Self::new(compiled))
+    }
+
+    fn from_compiled_json(compiled_json: &str) -> Result<Self, String> {
+        let compiled: CompiledPolicy = serde_json::from_str(compiled_json).map_err(|e| e.to_string())?;
+        compiled.validate().map_err(|e| e.to_string())?;
+        Ok(Self::new(compiled))
+    }
+
+    fn evaluate_json(&self, context_json: &str) -> Result<String, String> {
+        let context: EvaluationContext = serde_json::from_str(context_json).map_err(|e| e.to_string())?;
+        let metered = evaluate_compiled(&self.vm, &self.compiled, &context);
+        let decision = metered.decision.map_err(|e| e.to_string())?;
+        let evaluation = Evaluation {
+            decision: &decision,
+            rule: decision.matched_rule(),
+            gas_used: metered.gas_used,
+            max_gas: metered.max_gas,
+        };
+        serde_json::to_string(&evaluation).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::{create_default_policy, PolicyVM};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_binding_decides_like_the_server() {
+        let definition = create_default_policy("C.Jobs");
+        let server = PolicyVM::new();
+        server.register(&definition);
+
+        let binding = WasmPolicy::compile_json(&serde_json::to_string(&definition).unwrap()).unwrap();
+        assert_eq!(binding.hash(), server.get_policy(&definition.policy_id).unwrap().hash);
+        let reloaded = WasmPolicy::from_compiled_json(&serde_json::to_string(&binding.compiled).unwrap()).unwrap();
+
+        for intent in [json!({"type": "observe"}), json!({"type": "transfer", "amount": 50}), json!({"type": "hack"})] {
+            let context = EvaluationContext {
+                container_id: "C.Jobs".to_string(),
+                actor: "alice".to_string(),
+                intent,
+                state: None,
+                timestamp: 1_700_000_000_000,
+                flags: Default::default(),
+            };
+            let expected = server.evaluate_metered(&definition.policy_id, &context);
+            let got: Value = serde_json::from_str(&binding.evaluate_json(&serde_json::to_string(&context).unwrap()).unwrap()).unwrap();
+            assert_eq!(got["decision"], serde_json::to_value(expected.decision.unwrap()).unwrap());
+            assert_eq!(got["gas_used"], expected.gas_used);
+            let again: Value = serde_json::from_str(&reloaded.evaluate_json(&serde_json::to_string(&context).unwrap()).unwrap()).unwrap();
+            assert_eq!(again, got);
+        }
+
+        let mut tampered = binding.compiled.clone();
+        tampered.code.push(0x00);
+        assert!(WasmPolicy::from_compiled_json(&serde_json::to_string(&tampered).unwrap()).is_err());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Basic sanity checks that the VM compiles and runs correctly.
 
+#![cfg(feature = "std")]
+
 use ubl_policy_vm::PolicyVM;
 
 #[test]
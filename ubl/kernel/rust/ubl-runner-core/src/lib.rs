@@ -14,10 +14,14 @@
 #![warn(missing_docs)]
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+pub mod spool;
+
+pub use spool::{Delivery, ReceiptSpool, ReplayReport, SpoolMetrics, SpooledReceipt};
+
 /// Errors from runner operations
 #[derive(Error, Debug, Clone)]
 pub enum RunnerError {
@@ -40,6 +44,10 @@ pub enum RunnerError {
     /// Timeout
     #[error("Execution timeout")]
     Timeout,
+
+    /// Receipt spool I/O failed
+    #[error("Receipt spool: {0}")]
+    Spool(String),
 }
 
 /// Result type for runner operations
@@ -161,6 +169,16 @@ impl ExecutionReceipt {
     pub fn duration_ms(&self) -> u128 {
         (self.finished_at - self.started_at) / 1_000_000
     }
+
+    /// Start time in unix milliseconds, the unit used by all APIs
+    pub fn started_at_ms(&self) -> i64 {
+        (self.started_at / 1_000_000) as i64
+    }
+
+    /// Finish time in unix milliseconds
+    pub fn finished_at_ms(&self) -> i64 {
+        (self.finished_at / 1_000_000) as i64
+    }
 }
 
 /// Job in the execution queue
@@ -184,7 +202,7 @@ pub struct ExecutionJob {
     /// Priority (higher = more urgent)
     pub priority: i32,
     
-    /// Created timestamp
+    /// Created timestamp (Unix seconds; see [`ExecutionJob::created_at_ms`])
     pub created_at: i64,
     
     /// Retry count
@@ -231,48 +249,290 @@ impl ExecutionJob {
     pub fn retry(&mut self) {
         self.retries += 1;
     }
+
+    /// Creation time in unix milliseconds, the unit used by all APIs
+    pub fn created_at_ms(&self) -> i64 {
+        self.created_at.saturating_mul(1_000)
+    }
+}
+
+/// Default aging interval: a waiting job gains one priority level per 30s
+pub const DEFAULT_AGING_INTERVAL_MS: u64 = 30_000;
+
+/// Job plus its scheduling state inside [`RunnerQueue`]
+#[derive(Debug, Clone)]
+struct QueuedJob {
+    job: ExecutionJob,
+    /// Base priority: the job's own, or a higher inherited one
+    priority: i32,
+    /// Enqueue time (Unix ms); kept across requeues so retries keep their age
+    enqueued_at_ms: i64,
+    /// Insertion counter for FIFO order among equal keys
+    seq: u64,
+    aging_interval_ms: u64,
+}
+
+impl QueuedJob {
+    /// Effective priority at `now` is `priority + waited / interval`. Comparing
+    /// two jobs at the same instant reduces to comparing
+    /// `priority * interval - enqueued_at`, which does not depend on `now`, so
+    /// the heap order stays valid as jobs age.
+    fn key(&self) -> i128 {
+        self.priority as i128 * self.aging_interval_ms as i128 - self.enqueued_at_ms as i128
+    }
+
+    fn effective_priority(&self, now_ms: i64) -> i64 {
+        let waited = (now_ms - self.enqueued_at_ms).max(0) as u64;
+        self.priority as i64 + (waited / self.aging_interval_ms) as i64
+    }
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for QueuedJob {}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Max-heap: larger key first, then earlier insertion
+        self.key().cmp(&other.key()).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Snapshot of what is waiting in a [`RunnerQueue`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Jobs waiting
+    pub len: usize,
+    /// Waiting jobs by base priority
+    pub by_priority: BTreeMap<i32, usize>,
+    /// Waiting jobs by job type
+    pub by_job_type: BTreeMap<String, usize>,
+    /// Jobs whose aged priority is above their base priority
+    pub aged: usize,
+    /// Longest wait (ms)
+    pub oldest_wait_ms: u64,
+    /// Highest effective priority after aging
+    pub max_effective_priority: Option<i64>,
+}
+
+/// One failed execution attempt of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedAttempt {
+    /// Attempt number (1-based)
+    pub attempt: u32,
+    /// Why the attempt failed
+    pub reason: String,
+    /// Receipt produced by the attempt, if execution got that far
+    pub receipt: Option<ExecutionReceipt>,
+    /// Failure time (Unix ms)
+    pub failed_at_ms: i64,
+}
+
+/// A job that exhausted its retries, with the history of every attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The job as last attempted
+    pub job: ExecutionJob,
+    /// Failed attempts, oldest first
+    pub attempts: Vec<FailedAttempt>,
+    /// When the job was dead-lettered (Unix ms)
+    pub dead_lettered_at_ms: i64,
 }
 
 /// Runner queue - manages execution jobs
+///
+/// Ordered by priority with aging: every `aging_interval_ms` spent waiting
+/// adds one level, so low-priority work cannot be starved by a steady stream
+/// of urgent jobs. Push and pop are O(log n).
+///
+/// Jobs that fail more than `max_retries` times move to a dead-letter store
+/// where they can be inspected, edited, requeued or purged.
 pub struct RunnerQueue {
-    jobs: Vec<ExecutionJob>,
+    jobs: BinaryHeap<QueuedJob>,
     max_retries: u32,
+    aging_interval_ms: u64,
+    next_seq: u64,
+    /// Enqueue time of jobs handed out by `dequeue`, restored on `requeue`
+    in_flight: HashMap<String, i64>,
+    /// Failed attempts of jobs that are still being retried
+    attempts: HashMap<String, Vec<FailedAttempt>>,
+    dead_letters: BTreeMap<String, DeadLetter>,
 }
 
 impl RunnerQueue {
     /// Create a new queue
     pub fn new(max_retries: u32) -> Self {
+        Self::with_aging(max_retries, DEFAULT_AGING_INTERVAL_MS)
+    }
+
+    /// Create a queue with a custom aging interval (clamped to at least 1ms)
+    pub fn with_aging(max_retries: u32, aging_interval_ms: u64) -> Self {
         Self {
-            jobs: Vec::new(),
+            jobs: BinaryHeap::new(),
             max_retries,
+            aging_interval_ms: aging_interval_ms.max(1),
+            next_seq: 0,
+            in_flight: HashMap::new(),
+            attempts: HashMap::new(),
+            dead_letters: BTreeMap::new(),
         }
     }
 
     /// Enqueue a job
     pub fn enqueue(&mut self, job: ExecutionJob) {
-        self.jobs.push(job);
-        // Sort by priority (higher first)
-        self.jobs.sort_by(|a, b| b.priority.cmp(&a.priority));
+        self.enqueue_at(job, now_ms());
+    }
+
+    /// Enqueue a job as of `now_ms` (Unix ms)
+    pub fn enqueue_at(&mut self, job: ExecutionJob, now_ms: i64) {
+        let enqueued_at_ms = self.in_flight.remove(&job.job_id).unwrap_or(now_ms);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.jobs.push(QueuedJob {
+            priority: job.priority,
+            job,
+            enqueued_at_ms,
+            seq,
+            aging_interval_ms: self.aging_interval_ms,
+        });
     }
 
     /// Dequeue next job (pull model)
     pub fn dequeue(&mut self) -> Option<ExecutionJob> {
-        if self.jobs.is_empty() {
-            return None;
-        }
-        Some(self.jobs.remove(0))
+        let queued = self.jobs.pop()?;
+        self.in_flight.insert(queued.job.job_id.clone(), queued.enqueued_at_ms);
+        Some(queued.job)
     }
 
     /// Requeue a failed job (with retry limit)
-    pub fn requeue(&mut self, mut job: ExecutionJob) -> bool {
+    ///
+    /// The job keeps its original enqueue time, so it does not lose the
+    /// priority it gained while waiting. Returns false once retries are
+    /// exhausted; the job is then dead-lettered.
+    pub fn requeue(&mut self, job: ExecutionJob) -> bool {
+        self.fail(job, "requeued", None)
+    }
+
+    /// Record a failed attempt and requeue the job, or dead-letter it when
+    /// retries are exhausted. Returns true if the job was requeued.
+    pub fn fail(&mut self, job: ExecutionJob, reason: impl Into<String>, receipt: Option<ExecutionReceipt>) -> bool {
+        self.fail_at(job, reason, receipt, now_ms())
+    }
+
+    /// [`RunnerQueue::fail`] as of `now_ms` (Unix ms)
+    pub fn fail_at(
+        &mut self,
+        mut job: ExecutionJob,
+        reason: impl Into<String>,
+        receipt: Option<ExecutionReceipt>,
+        now_ms: i64,
+    ) -> bool {
+        let attempts = self.attempts.entry(job.job_id.clone()).or_default();
+        attempts.push(FailedAttempt {
+            attempt: job.retries + 1,
+            reason: reason.into(),
+            receipt,
+            failed_at_ms: now_ms,
+        });
+
         if job.retries >= self.max_retries {
+            self.in_flight.remove(&job.job_id);
+            let attempts = self.attempts.remove(&job.job_id).unwrap_or_default();
+            self.dead_letters.insert(
+                job.job_id.clone(),
+                DeadLetter { job, attempts, dead_lettered_at_ms: now_ms },
+            );
             return false; // Max retries exceeded
         }
         job.retry();
-        self.enqueue(job);
+        self.enqueue_at(job, now_ms);
         true
     }
 
+    /// Forget a dequeued job that finished (it will not be requeued)
+    pub fn complete(&mut self, job_id: &str) {
+        self.in_flight.remove(job_id);
+        self.attempts.remove(job_id);
+    }
+
+    /// Dead-lettered jobs, by job id
+    pub fn dead_letters(&self) -> impl Iterator<Item = &DeadLetter> {
+        self.dead_letters.values()
+    }
+
+    /// Look up a dead-lettered job
+    pub fn dead_letter(&self, job_id: &str) -> Option<&DeadLetter> {
+        self.dead_letters.get(job_id)
+    }
+
+    /// Replace the payload of a dead-lettered job before requeueing it
+    pub fn set_dead_letter_payload(&mut self, job_id: &str, payload: HashMap<String, serde_json::Value>) -> bool {
+        match self.dead_letters.get_mut(job_id) {
+            Some(dead) => {
+                dead.job.payload = payload;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move a dead-lettered job back into the queue with a fresh retry budget
+    pub fn requeue_dead_letter(&mut self, job_id: &str) -> bool {
+        match self.dead_letters.remove(job_id) {
+            Some(mut dead) => {
+                dead.job.retries = 0;
+                self.enqueue(dead.job);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop a dead-lettered job for good
+    pub fn purge_dead_letter(&mut self, job_id: &str) -> Option<DeadLetter> {
+        self.dead_letters.remove(job_id)
+    }
+
+    /// Priority inheritance: raise a waiting job to at least `priority`
+    /// (e.g. because urgent work is blocked on it). Returns false if the job
+    /// is not waiting. O(n); the heap is rebuilt.
+    pub fn inherit_priority(&mut self, job_id: &str, priority: i32) -> bool {
+        let mut jobs = std::mem::take(&mut self.jobs).into_vec();
+        let mut found = false;
+        for queued in jobs.iter_mut().filter(|q| q.job.job_id == job_id) {
+            queued.priority = queued.priority.max(priority);
+            found = true;
+        }
+        self.jobs = BinaryHeap::from(jobs);
+        found
+    }
+
+    /// Queue composition as of `now_ms` (Unix ms)
+    pub fn stats(&self, now_ms: i64) -> QueueStats {
+        let mut stats = QueueStats { len: self.jobs.len(), ..QueueStats::default() };
+        for queued in self.jobs.iter() {
+            *stats.by_priority.entry(queued.priority).or_insert(0) += 1;
+            *stats.by_job_type.entry(queued.job.job_type.clone()).or_insert(0) += 1;
+            let effective = queued.effective_priority(now_ms);
+            if effective > queued.priority as i64 {
+                stats.aged += 1;
+            }
+            stats.oldest_wait_ms = stats.oldest_wait_ms.max((now_ms - queued.enqueued_at_ms).max(0) as u64);
+            stats.max_effective_priority = Some(stats.max_effective_priority.map_or(effective, |m| m.max(effective)));
+        }
+        stats
+    }
+
     /// Get queue length
     pub fn len(&self) -> usize {
         self.jobs.len()
@@ -284,6 +544,13 @@ impl RunnerQueue {
     }
 }
 
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
 /// Sandbox configuration (SPEC-UBL-RUNNER v1.0 §5)
 #[derive(Debug, Clone)]
 pub struct SandboxConfig {
@@ -419,4 +686,98 @@ mod tests {
         // Third requeue - should fail (max retries exceeded)
         assert!(!queue.requeue(job));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_queue_aging_prevents_starvation() {
+        let mut queue = RunnerQueue::with_aging(3, 1_000);
+        let t0 = 1_000_000;
+
+        let mut low = ExecutionJob::new("test".to_string(), "low".to_string(), "build".to_string());
+        low.priority = 0;
+        queue.enqueue_at(low, t0);
+
+        // Priority-5 work keeps arriving; after 6s the low job has aged past it
+        for i in 0..10 {
+            let mut high = ExecutionJob::new("test".to_string(), format!("high{}", i), "build".to_string());
+            high.priority = 5;
+            queue.enqueue_at(high, t0 + 6_000 + i);
+        }
+
+        assert_eq!(queue.dequeue().unwrap().trigger_link_hash, "low");
+    }
+
+    #[test]
+    fn test_queue_fifo_within_priority() {
+        let mut queue = RunnerQueue::new(3);
+        for i in 0..5 {
+            let job = ExecutionJob::new("test".to_string(), format!("link{}", i), "build".to_string());
+            queue.enqueue_at(job, 42);
+        }
+        let order: Vec<_> = std::iter::from_fn(|| queue.dequeue()).map(|j| j.trigger_link_hash).collect();
+        assert_eq!(order, vec!["link0", "link1", "link2", "link3", "link4"]);
+    }
+
+    #[test]
+    fn test_queue_priority_inheritance_and_stats() {
+        let mut queue = RunnerQueue::with_aging(3, 1_000);
+        let blocker = ExecutionJob::new("test".to_string(), "blocker".to_string(), "deploy".to_string());
+        let blocker_id = blocker.job_id.clone();
+        queue.enqueue_at(blocker, 0);
+        let mut other = ExecutionJob::new("test".to_string(), "other".to_string(), "build".to_string());
+        other.priority = 3;
+        queue.enqueue_at(other, 0);
+
+        assert!(queue.inherit_priority(&blocker_id, 10));
+        assert!(!queue.inherit_priority("missing", 10));
+
+        let stats = queue.stats(2_500);
+        assert_eq!(stats.len, 2);
+        assert_eq!(stats.by_priority.get(&10), Some(&1));
+        assert_eq!(stats.by_job_type.get("build"), Some(&1));
+        assert_eq!(stats.aged, 2);
+        assert_eq!(stats.oldest_wait_ms, 2_500);
+        assert_eq!(stats.max_effective_priority, Some(12));
+
+        assert_eq!(queue.dequeue().unwrap().job_id, blocker_id);
+    }
+
+    #[test]
+    fn test_dead_letter_requeue_and_purge() {
+        let mut queue = RunnerQueue::new(1);
+        let job = ExecutionJob::new("test".to_string(), "link_abc".to_string(), "build".to_string());
+        let job_id = job.job_id.clone();
+
+        queue.enqueue(job);
+        let job = queue.dequeue().unwrap();
+        let mut receipt = ExecutionReceipt::new("test".to_string(), "link_abc".to_string(), "exec_1".to_string());
+        receipt.mark_failed();
+        assert!(queue.fail(job, "exit code 1", Some(receipt)));
+
+        let job = queue.dequeue().unwrap();
+        assert!(!queue.fail(job, "exit code 2", None));
+        assert!(queue.is_empty());
+
+        let dead = queue.dead_letter(&job_id).unwrap();
+        assert_eq!(dead.attempts.len(), 2);
+        assert_eq!(dead.attempts[0].reason, "exit code 1");
+        assert!(dead.attempts[0].receipt.is_some());
+        assert_eq!(dead.attempts[1].attempt, 2);
+
+        let mut payload = HashMap::new();
+        payload.insert("fixed".to_string(), serde_json::json!(true));
+        assert!(queue.set_dead_letter_payload(&job_id, payload));
+        assert!(queue.requeue_dead_letter(&job_id));
+        assert_eq!(queue.dead_letters().count(), 0);
+
+        let job = queue.dequeue().unwrap();
+        assert_eq!(job.retries, 0);
+        assert_eq!(job.payload.get("fixed"), Some(&serde_json::json!(true)));
+
+        assert!(queue.fail(job, "still broken", None));
+        let job = queue.dequeue().unwrap();
+        assert!(!queue.fail(job, "still broken", None));
+        assert_eq!(queue.dead_letter(&job_id).unwrap().attempts.len(), 2);
+        assert!(queue.purge_dead_letter(&job_id).is_some());
+        assert!(queue.dead_letter(&job_id).is_none());
+    }
+}
@@ -0,0 +1,356 @@
+//! Durable receipt spool
+//!
+//! A runner that finishes a job while the server is unreachable must not
+//! lose the receipt, or the job is eventually timed out as orphaned although
+//! it ran. [`ReceiptSpool`] keeps every signed receipt on disk until the
+//! server acknowledges it:
+//!
+//! 1. After signing, [`ReceiptSpool::push`] the exec.finish body. It is
+//!    written to a temporary file, fsynced and renamed into place, so a crash
+//!    leaves either the whole receipt or nothing.
+//! 2. [`ReceiptSpool::replay`] submits pending receipts oldest first (on
+//!    reconnect, and after each push). An accepted receipt is removed; a
+//!    refused one is moved to `rejected/` for inspection; the first
+//!    unreachable submission stops the pass, the rest wait for the next one.
+//!
+//! Receipts are keyed by execution id: one file per execution, named after
+//! its BLAKE3 hash, and pushing again replaces it. The server deduplicates
+//! by execution id too, so a receipt delivered just before the connection
+//! dropped is acknowledged again on replay (`duplicate: true`) instead of
+//! being refused.
+//!
+//! [`SpoolMetrics`] reports the spool depth, the age of its oldest receipt
+//! and replay latency (time from spooling to acknowledgement), also as
+//! Prometheus text for the runner's metrics endpoint.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::{now_ms, Result, RunnerError};
+
+/// A receipt waiting in the spool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpooledReceipt {
+    /// Execution id (the command id on the console API)
+    pub execution_id: String,
+    /// When the receipt was spooled (Unix ms)
+    pub spooled_at_ms: i64,
+    /// Spool order; breaks ties between receipts spooled in the same ms
+    pub seq: u64,
+    /// Body to submit, signature included
+    pub body: serde_json::Value,
+}
+
+/// Outcome of submitting one spooled receipt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Delivery {
+    /// The server holds the receipt (newly or as a duplicate)
+    Accepted,
+    /// The server refused it for good (bad signature, unknown command...)
+    Rejected(String),
+    /// The server could not be reached or failed; retry later
+    Unreachable,
+}
+
+/// Result of one [`ReceiptSpool::replay`] pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Receipts acknowledged and removed
+    pub accepted: usize,
+    /// Receipts moved to `rejected/`
+    pub rejected: usize,
+    /// Receipts still pending after the pass
+    pub remaining: usize,
+    /// The pass stopped on an unreachable server
+    pub interrupted: bool,
+}
+
+/// Spool counters since the spool was opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpoolMetrics {
+    /// Receipts pending
+    pub depth: usize,
+    /// Age of the oldest pending receipt (ms)
+    pub oldest_age_ms: u64,
+    /// Receipts acknowledged by the server
+    pub replayed_total: u64,
+    /// Receipts refused by the server
+    pub rejected_total: u64,
+    /// Submissions that found the server unreachable
+    pub unreachable_total: u64,
+    /// Sum of replay latencies (ms), over `replayed_total` receipts
+    pub replay_latency_ms_sum: u64,
+    /// Longest replay latency seen (ms)
+    pub replay_latency_ms_max: u64,
+}
+
+impl SpoolMetrics {
+    /// Prometheus text exposition, prefixed `ubl_runner_spool_`
+    pub fn to_prometheus(&self) -> String {
+        let metrics: [(&str, &str, &str, u64); 7] = [
+            ("depth", "gauge", "Receipts waiting in the spool", self.depth as u64),
+            ("oldest_age_ms", "gauge", "Age of the oldest spooled receipt", self.oldest_age_ms),
+            ("replayed_total", "counter", "Spooled receipts acknowledged by the server", self.replayed_total),
+            ("rejected_total", "counter", "Spooled receipts refused by the server", self.rejected_total),
+            ("unreachable_total", "counter", "Receipt submissions that found the server unreachable", self.unreachable_total),
+            ("replay_latency_ms_sum", "counter", "Time from spooling to acknowledgement, summed", self.replay_latency_ms_sum),
+            ("replay_latency_ms_max", "gauge", "Longest time from spooling to acknowledgement", self.replay_latency_ms_max),
+        ];
+        let mut out = String::new();
+        for (name, kind, help, value) in metrics {
+            out.push_str(&format!(
+                "# HELP ubl_runner_spool_{name} {help}\n# TYPE ubl_runner_spool_{name} {kind}\nubl_runner_spool_{name} {value}\n"
+            ));
+        }
+        out
+    }
+}
+
+/// File-backed spool of receipts not yet acknowledged by the server
+pub struct ReceiptSpool {
+    dir: PathBuf,
+    next_seq: u64,
+    metrics: SpoolMetrics,
+}
+
+impl ReceiptSpool {
+    /// Open (or create) the spool in `dir`. Temporary files left by a crash
+    /// mid-write are discarded; they were never acknowledged to the caller.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(dir.join("rejected")).map_err(spool_error)?;
+        for entry in fs::read_dir(&dir).map_err(spool_error)? {
+            let path = entry.map_err(spool_error)?.path();
+            if path.extension().is_some_and(|ext| ext == "tmp") {
+                fs::remove_file(&path).map_err(spool_error)?;
+            }
+        }
+        let mut spool = Self { dir, next_seq: 0, metrics: SpoolMetrics::default() };
+        spool.next_seq = spool.pending()?.iter().map(|r| r.seq + 1).max().unwrap_or(0);
+        Ok(spool)
+    }
+
+    /// Durably store the receipt for `execution_id`, replacing any pending
+    /// one for the same execution
+    pub fn push(&mut self, execution_id: &str, body: serde_json::Value) -> Result<()> {
+        self.push_at(execution_id, body, now_ms())
+    }
+
+    /// [`ReceiptSpool::push`] as of `now_ms` (Unix ms)
+    pub fn push_at(&mut self, execution_id: &str, body: serde_json::Value, now_ms: i64) -> Result<()> {
+        let receipt = SpooledReceipt {
+            execution_id: execution_id.to_string(),
+            spooled_at_ms: now_ms,
+            seq: self.next_seq,
+            body,
+        };
+        let bytes = serde_json::to_vec(&receipt).map_err(|e| RunnerError::Spool(e.to_string()))?;
+        let path = self.path_of(execution_id);
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp).map_err(spool_error)?;
+        file.write_all(&bytes).map_err(spool_error)?;
+        file.sync_all().map_err(spool_error)?;
+        fs::rename(&tmp, &path).map_err(spool_error)?;
+        sync_dir(&self.dir)?;
+        self.next_seq += 1;
+        Ok(())
+    }
+
+    /// Pending receipts, oldest first. A file that does not parse is moved
+    /// to `rejected/` rather than blocking the spool.
+    pub fn pending(&self) -> Result<Vec<SpooledReceipt>> {
+        let mut receipts = Vec::new();
+        for entry in fs::read_dir(&self.dir).map_err(spool_error)? {
+            let path = entry.map_err(spool_error)?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let bytes = fs::read(&path).map_err(spool_error)?;
+            match serde_json::from_slice::<SpooledReceipt>(&bytes) {
+                Ok(receipt) => receipts.push(receipt),
+                Err(_) => self.set_aside(&path)?,
+            }
+        }
+        receipts.sort_by_key(|r| (r.spooled_at_ms, r.seq));
+        Ok(receipts)
+    }
+
+    /// Receipts pending
+    pub fn depth(&self) -> Result<usize> {
+        Ok(self.pending()?.len())
+    }
+
+    /// Drop the receipt for `execution_id` once the server holds it; false
+    /// if none was pending
+    pub fn ack(&mut self, execution_id: &str) -> Result<bool> {
+        match fs::remove_file(self.path_of(execution_id)) {
+            Ok(()) => {
+                sync_dir(&self.dir)?;
+                Ok(true)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(spool_error(e)),
+        }
+    }
+
+    /// Submit pending receipts oldest first (see the module docs)
+    pub fn replay<F>(&mut self, submit: F) -> Result<ReplayReport>
+    where
+        F: FnMut(&SpooledReceipt) -> Delivery,
+    {
+        self.replay_at(now_ms(), submit)
+    }
+
+    /// [`ReceiptSpool::replay`] as of `now_ms` (Unix ms)
+    pub fn replay_at<F>(&mut self, now_ms: i64, mut submit: F) -> Result<ReplayReport>
+    where
+        F: FnMut(&SpooledReceipt) -> Delivery,
+    {
+        let pending = self.pending()?;
+        let mut report = ReplayReport { remaining: pending.len(), ..ReplayReport::default() };
+        for receipt in &pending {
+            match submit(receipt) {
+                Delivery::Accepted => {
+                    self.ack(&receipt.execution_id)?;
+                    let latency = (now_ms - receipt.spooled_at_ms).max(0) as u64;
+                    self.metrics.replayed_total += 1;
+                    self.metrics.replay_latency_ms_sum += latency;
+                    self.metrics.replay_latency_ms_max = self.metrics.replay_latency_ms_max.max(latency);
+                    report.accepted += 1;
+                }
+                Delivery::Rejected(reason) => {
+                    let path = self.path_of(&receipt.execution_id);
+                    self.set_aside(&path)?;
+                    let aside = self.dir.join("rejected").join(path.with_extension("reason").file_name().unwrap_or_default());
+                    fs::write(aside, reason).map_err(spool_error)?;
+                    self.metrics.rejected_total += 1;
+                    report.rejected += 1;
+                }
+                Delivery::Unreachable => {
+                    self.metrics.unreachable_total += 1;
+                    report.interrupted = true;
+                    break;
+                }
+            }
+            report.remaining -= 1;
+        }
+        Ok(report)
+    }
+
+    /// Counters, with depth and oldest age as of `now_ms` (Unix ms)
+    pub fn metrics(&self, now_ms: i64) -> Result<SpoolMetrics> {
+        let pending = self.pending()?;
+        Ok(SpoolMetrics {
+            depth: pending.len(),
+            oldest_age_ms: pending.first().map_or(0, |r| (now_ms - r.spooled_at_ms).max(0) as u64),
+            ..self.metrics.clone()
+        })
+    }
+
+    fn path_of(&self, execution_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", blake3::hash(execution_id.as_bytes()).to_hex()))
+    }
+
+    /// Move a spool file to `rejected/`; its reason, if any, goes next to it
+    fn set_aside(&self, path: &Path) -> Result<()> {
+        let Some(name) = path.file_name() else { return Ok(()) };
+        let rejected = self.dir.join("rejected");
+        fs::rename(path, rejected.join(name)).map_err(spool_error)?;
+        sync_dir(&self.dir)
+    }
+}
+
+/// Make a rename or removal in `dir` durable
+fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    File::open(dir).and_then(|d| d.sync_all()).map_err(spool_error)?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+fn spool_error(e: std::io::Error) -> RunnerError {
+    RunnerError::Spool(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spool_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ubl-spool-{}-{}", name, rand::random::<u64>()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_spool_survives_reopen_and_replays_in_order() {
+        let dir = spool_dir("order");
+        {
+            let mut spool = ReceiptSpool::open(&dir).unwrap();
+            spool.push_at("cmd_b", json!({"command_id": "cmd_b"}), 2_000).unwrap();
+            spool.push_at("cmd_a", json!({"command_id": "cmd_a"}), 1_000).unwrap();
+            spool.push_at("cmd_c", json!({"command_id": "cmd_c"}), 2_000).unwrap();
+            // Resending the same execution replaces its receipt
+            spool.push_at("cmd_a", json!({"command_id": "cmd_a", "v": 2}), 1_500).unwrap();
+        }
+        // A crash mid-write leaves a temporary file behind
+        fs::write(dir.join("partial.tmp"), b"{\"execution_id\"").unwrap();
+
+        let mut spool = ReceiptSpool::open(&dir).unwrap();
+        assert!(!dir.join("partial.tmp").exists());
+        assert_eq!(spool.depth().unwrap(), 3);
+
+        // Offline: the first submission stops the pass
+        let report = spool.replay_at(3_000, |_| Delivery::Unreachable).unwrap();
+        assert!(report.interrupted);
+        assert_eq!(report.remaining, 3);
+
+        let mut seen = Vec::new();
+        let report = spool
+            .replay_at(5_000, |r| {
+                seen.push(r.execution_id.clone());
+                Delivery::Accepted
+            })
+            .unwrap();
+        assert_eq!(seen, vec!["cmd_a", "cmd_b", "cmd_c"]);
+        assert_eq!(report, ReplayReport { accepted: 3, rejected: 0, remaining: 0, interrupted: false });
+
+        let metrics = spool.metrics(5_000).unwrap();
+        assert_eq!(metrics.depth, 0);
+        assert_eq!(metrics.replayed_total, 3);
+        assert_eq!(metrics.unreachable_total, 1);
+        assert_eq!(metrics.replay_latency_ms_sum, 3_500 + 3_000 + 3_000);
+        assert_eq!(metrics.replay_latency_ms_max, 3_500);
+        assert!(metrics.to_prometheus().contains("ubl_runner_spool_replayed_total 3\n"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rejected_and_corrupt_receipts_are_set_aside() {
+        let dir = spool_dir("rejected");
+        let mut spool = ReceiptSpool::open(&dir).unwrap();
+        spool.push_at("cmd_bad", json!({}), 1_000).unwrap();
+        spool.push_at("cmd_ok", json!({}), 2_000).unwrap();
+        fs::write(dir.join("garbage.json"), b"not json").unwrap();
+
+        let report = spool
+            .replay_at(3_000, |r| match r.execution_id.as_str() {
+                "cmd_bad" => Delivery::Rejected("RunnerSigInvalid".to_string()),
+                _ => Delivery::Accepted,
+            })
+            .unwrap();
+        assert_eq!((report.accepted, report.rejected, report.remaining), (1, 1, 0));
+
+        let metrics = spool.metrics(3_000).unwrap();
+        assert_eq!((metrics.depth, metrics.rejected_total), (0, 1));
+        assert!(dir.join("rejected/garbage.json").exists());
+        let aside = fs::read_dir(dir.join("rejected")).unwrap().count();
+        assert_eq!(aside, 3, "rejected receipt, its reason and the corrupt file");
+        assert!(!spool.ack("cmd_ok").unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,612 @@
+//! Multi-signature admin actions — destructive operations behind a pact
+//!
+//! Projection rebuilds, the ledger read-only toggle and container policy
+//! binding changes are never executed on request. An admin (step-up session)
+//! files a pending action naming an L4/L5 pact; other admins, each on their
+//! own step-up WebAuthn session, approve it by signing the action under that
+//! pact. Once the pact threshold of distinct admins is met the collected
+//! signatures are checked as a regular pact proof and the action runs.
+//!
+//! Endpoints (all step-up):
+//! - POST /v1/admin/actions                    → File a pending action
+//! - GET  /v1/admin/actions?status=            → List
+//! - GET  /v1/admin/actions/:action_id         → Inspect (incl. message to sign)
+//! - POST /v1/admin/actions/:action_id/approve → Add a signature; executes at threshold
+//! - POST /v1/admin/actions/:action_id/cancel  → Withdraw a pending action
+//!
+//! Signed message: SPEC-UBL-PACT §8.1 over `action_hash`, intent class
+//! Evolution, delta 0 (see `pact_db::build_pact_sign_message`).
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{PgPool, Row};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::auth::session::Session;
+use crate::id_routes::IdState;
+use crate::pact_db::{self, PactProofInput, PactRecord, PactSignatureInput};
+use crate::policy_registry::PolicyRegistry;
+use crate::timestamps::now_ms;
+
+/// Container the admin pacts must cover
+pub const ADMIN_CONTAINER: &str = "C.Admin";
+/// Intent class admin actions are signed under
+const ADMIN_INTENT_CLASS: &str = "Evolution";
+/// Only L4/L5 pacts may authorize destructive operations
+const MIN_RISK_LEVEL: i16 = 4;
+/// Pending actions expire if not approved within this window
+const ACTION_TTL_MS: i64 = 24 * 60 * 60 * 1000;
+/// Actions listed per page
+const LIST_LIMIT: i64 = 200;
+
+/// Ledger read-only switch, checked before every commit
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Whether commits are currently refused
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Restore the read-only switch from the last executed toggle
+pub async fn load_read_only(pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let enabled: Option<bool> = sqlx::query_scalar(
+        r#"
+        SELECT (params->>'enabled')::BOOLEAN
+        FROM admin_actions
+        WHERE kind = 'read_only' AND status = 'executed'
+        ORDER BY executed_at_ms DESC
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+
+    let enabled = enabled.unwrap_or(false);
+    READ_ONLY.store(enabled, Ordering::Relaxed);
+    Ok(enabled)
+}
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// A destructive operation awaiting multi-admin approval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "params", rename_all = "snake_case")]
+pub enum AdminAction {
+    ProjectionRebuild {},
+    ReadOnly { enabled: bool },
+    PolicyBinding { container_id: String, policy_id: String },
+}
+
+impl AdminAction {
+    fn from_parts(kind: &str, params: Value) -> Result<Self, String> {
+        serde_json::from_value(json!({ "kind": kind, "params": params })).map_err(|e| e.to_string())
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::ProjectionRebuild {} => "projection_rebuild",
+            Self::ReadOnly { .. } => "read_only",
+            Self::PolicyBinding { .. } => "policy_binding",
+        }
+    }
+
+    fn params(&self) -> Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|v| v.get("params").cloned())
+            .unwrap_or_else(|| json!({}))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateActionRequest {
+    pub kind: String,
+    #[serde(default)]
+    pub params: Option<Value>,
+    pub pact_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApproveActionRequest {
+    /// Ed25519 public key (hex) listed in the pact's signers
+    pub signer: String,
+    /// Signature (hex) over `sign_message`
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListActionsParams {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub sid: String,
+    pub signer: String,
+    pub signature: String,
+    pub approved_at_ms: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ActionView {
+    pub action_id: String,
+    pub kind: String,
+    pub params: Value,
+    pub action_hash: String,
+    pub pact_id: String,
+    pub threshold: i16,
+    /// Hex of the bytes each approving admin signs
+    pub sign_message: String,
+    pub status: String,
+    pub requested_by: String,
+    pub approvals: Vec<Approval>,
+    pub result: Option<Value>,
+    pub created_at_ms: i64,
+    pub expires_at_ms: i64,
+    pub executed_at_ms: Option<i64>,
+}
+
+#[derive(Clone)]
+pub struct AdminActionsState {
+    pub pool: PgPool,
+    pub policy_registry: Arc<PolicyRegistry>,
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+pub fn routes(pool: PgPool, id_state: IdState, policy_registry: Arc<PolicyRegistry>) -> Router {
+    Router::new()
+        .route("/v1/admin/actions", get(list_actions).post(create_action))
+        .route("/v1/admin/actions/:action_id", get(get_action))
+        .route("/v1/admin/actions/:action_id/approve", post(approve_action))
+        .route("/v1/admin/actions/:action_id/cancel", post(cancel_action))
+        .route_layer(middleware::from_fn_with_state(id_state, crate::auth::require_stepup::require_stepup))
+        .with_state(AdminActionsState { pool, policy_registry })
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+/// POST /v1/admin/actions — file a pending action
+async fn create_action(
+    State(state): State<AdminActionsState>,
+    Extension(session): Extension<Session>,
+    Json(req): Json<CreateActionRequest>,
+) -> Result<Json<ActionView>, ApiError> {
+    let action = AdminAction::from_parts(&req.kind, req.params.unwrap_or_else(|| json!({})))
+        .map_err(|e| ApiError::new(ErrorCode::BadRequest, format!("InvalidAction: {}", e)))?;
+    if let AdminAction::PolicyBinding { container_id, policy_id } = &action {
+        if container_id.is_empty() || policy_id.is_empty() {
+            return Err(ApiError::new(ErrorCode::BadRequest, "container_id and policy_id are required"));
+        }
+    }
+
+    let pact = load_pact(&state.pool, &req.pact_id).await?;
+    check_admin_pact(&pact).map_err(|e| ApiError::new(ErrorCode::PactViolation, e))?;
+
+    let action_id = format!("adm_{}", uuid::Uuid::new_v4().simple());
+    let action_hash = action_hash(&action_id, &action)?;
+    let now = now_ms();
+
+    sqlx::query(
+        r#"
+        INSERT INTO admin_actions
+          (action_id, kind, params, action_hash, pact_id, requested_by, created_at_ms, expires_at_ms, updated_at_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $7)
+        "#,
+    )
+    .bind(&action_id)
+    .bind(action.kind())
+    .bind(action.params())
+    .bind(&action_hash)
+    .bind(&pact.pact_id)
+    .bind(&session.sid)
+    .bind(now)
+    .bind(now + ACTION_TTL_MS)
+    .execute(&state.pool)
+    .await
+    .map_err(db_error)?;
+
+    info!("🛡️ Admin action filed: {} ({}) by {} under pact {}", action_id, action.kind(), session.sid, pact.pact_id);
+    fetch_view(&state.pool, &action_id).await.map(Json)
+}
+
+/// GET /v1/admin/actions
+async fn list_actions(
+    State(state): State<AdminActionsState>,
+    Query(params): Query<ListActionsParams>,
+) -> Result<Json<Vec<ActionView>>, ApiError> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT action_id FROM admin_actions
+        WHERE ($1::TEXT IS NULL OR status = $1)
+        ORDER BY created_at_ms DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(&params.status)
+    .bind(LIST_LIMIT)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(db_error)?;
+
+    let mut views = Vec::with_capacity(ids.len());
+    for id in ids {
+        views.push(fetch_view(&state.pool, &id).await?);
+    }
+    Ok(Json(views))
+}
+
+/// GET /v1/admin/actions/:action_id
+async fn get_action(
+    State(state): State<AdminActionsState>,
+    Path(action_id): Path<String>,
+) -> Result<Json<ActionView>, ApiError> {
+    fetch_view(&state.pool, &action_id).await.map(Json)
+}
+
+/// POST /v1/admin/actions/:action_id/approve — sign; executes at threshold
+async fn approve_action(
+    State(state): State<AdminActionsState>,
+    Extension(session): Extension<Session>,
+    Path(action_id): Path<String>,
+    Json(req): Json<ApproveActionRequest>,
+) -> Result<Json<ActionView>, ApiError> {
+    let mut tx = state.pool.begin().await.map_err(db_error)?;
+    let row = sqlx::query(
+        r#"
+        SELECT kind, params, action_hash, pact_id, status, approvals, expires_at_ms
+        FROM admin_actions
+        WHERE action_id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(&action_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "AdminActionNotFound"))?;
+
+    let status: String = Row::get(&row, "status");
+    if status != "pending" {
+        return Err(ApiError::new(ErrorCode::BadRequest, format!("admin action is {}", status)));
+    }
+    let now = now_ms();
+    if now > Row::get::<i64, _>(&row, "expires_at_ms") {
+        sqlx::query("UPDATE admin_actions SET status = 'expired', updated_at_ms = $2 WHERE action_id = $1")
+            .bind(&action_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+        return Err(ApiError::new(ErrorCode::BadRequest, "admin action expired"));
+    }
+
+    let mut approvals: Vec<Approval> = serde_json::from_value(Row::get(&row, "approvals"))
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("corrupt approvals: {}", e)))?;
+    if approvals.iter().any(|a| a.sid == session.sid) {
+        return Err(ApiError::new(ErrorCode::BadRequest, "AlreadyApproved"));
+    }
+    if approvals.iter().any(|a| a.signer == req.signer) {
+        return Err(ApiError::new(ErrorCode::BadRequest, "signer already used by another admin"));
+    }
+
+    let pact_id: String = Row::get(&row, "pact_id");
+    let action_hash: String = Row::get(&row, "action_hash");
+    let pact = load_pact(&state.pool, &pact_id).await?;
+    if !pact.signers.contains(&req.signer) {
+        return Err(ApiError::new(ErrorCode::Forbidden, format!("Unauthorized signer: {}", req.signer)));
+    }
+    let message = sign_message(&pact_id, &action_hash);
+    if ubl_kernel::verify(&req.signer, &message, &req.signature).is_err() {
+        return Err(ApiError::new(ErrorCode::InvalidSignature, "AdminApprovalSignatureInvalid"));
+    }
+
+    approvals.push(Approval {
+        sid: session.sid.clone(),
+        signer: req.signer,
+        signature: req.signature,
+        approved_at_ms: now,
+    });
+    let ready = approvals.len() >= pact.threshold as usize;
+    let approvals_json = serde_json::to_value(&approvals).map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))?;
+
+    sqlx::query(
+        r#"
+        UPDATE admin_actions
+        SET approvals = $2, status = $3, updated_at_ms = $4
+        WHERE action_id = $1
+        "#,
+    )
+    .bind(&action_id)
+    .bind(&approvals_json)
+    .bind(if ready { "executing" } else { "pending" })
+    .bind(now)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    info!("✍️ Admin action {} approved by {} ({}/{})", action_id, session.sid, approvals.len(), pact.threshold);
+
+    if ready {
+        let kind: String = Row::get(&row, "kind");
+        let action = AdminAction::from_parts(&kind, Row::get(&row, "params"))
+            .map_err(|e| ApiError::new(ErrorCode::Internal, format!("corrupt admin action: {}", e)))?;
+        let proof = PactProofInput {
+            pact_id: pact_id.clone(),
+            signatures: approvals
+                .iter()
+                .map(|a| PactSignatureInput { signer: a.signer.clone(), signature: a.signature.clone() })
+                .collect(),
+        };
+        let outcome = match pact_db::validate_pact_proof(
+            &state.pool,
+            &proof,
+            ADMIN_CONTAINER,
+            ADMIN_INTENT_CLASS,
+            &action_hash,
+            0,
+            now,
+        )
+        .await
+        {
+            Ok(()) => execute(&state, &action).await,
+            Err(e) => Err(format!("PactViolation: {}", e)),
+        };
+        finish(&state.pool, &action_id, outcome).await?;
+    }
+
+    fetch_view(&state.pool, &action_id).await.map(Json)
+}
+
+/// POST /v1/admin/actions/:action_id/cancel
+async fn cancel_action(
+    State(state): State<AdminActionsState>,
+    Extension(session): Extension<Session>,
+    Path(action_id): Path<String>,
+) -> Result<Json<ActionView>, ApiError> {
+    let updated = sqlx::query(
+        "UPDATE admin_actions SET status = 'cancelled', updated_at_ms = $2 WHERE action_id = $1 AND status = 'pending'",
+    )
+    .bind(&action_id)
+    .bind(now_ms())
+    .execute(&state.pool)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    let view = fetch_view(&state.pool, &action_id).await?;
+    if updated == 0 {
+        return Err(ApiError::new(ErrorCode::BadRequest, format!("admin action is {}", view.status)));
+    }
+
+    info!("🚫 Admin action cancelled: {} by {}", action_id, session.sid);
+    Ok(Json(view))
+}
+
+// =============================================================================
+// EXECUTION
+// =============================================================================
+
+async fn execute(state: &AdminActionsState, action: &AdminAction) -> Result<Value, String> {
+    match action {
+        AdminAction::ProjectionRebuild {} => {
+            crate::projections::rebuild_projections(&state.pool).await.map_err(|e| e.to_string())?;
+            Ok(json!({ "rebuilt": true }))
+        }
+        AdminAction::ReadOnly { enabled } => {
+            READ_ONLY.store(*enabled, Ordering::Relaxed);
+            warn!("🔒 Ledger read-only mode {}", if *enabled { "ENABLED" } else { "disabled" });
+            Ok(json!({ "read_only": enabled }))
+        }
+        AdminAction::PolicyBinding { container_id, policy_id } => {
+            let previous = state.policy_registry.get_policy_for_container(container_id).await;
+            state
+                .policy_registry
+                .set_container_policy(container_id, policy_id)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(json!({ "container_id": container_id, "policy_id": policy_id, "previous_policy_id": previous }))
+        }
+    }
+}
+
+async fn finish(pool: &PgPool, action_id: &str, outcome: Result<Value, String>) -> Result<(), ApiError> {
+    let (status, result) = match outcome {
+        Ok(result) => {
+            info!("✅ Admin action executed: {}", action_id);
+            ("executed", result)
+        }
+        Err(e) => {
+            error!("❌ Admin action failed: {}: {}", action_id, e);
+            ("failed", json!({ "error": e }))
+        }
+    };
+    let now = now_ms();
+    sqlx::query(
+        r#"
+        UPDATE admin_actions
+        SET status = $2, result = $3, executed_at_ms = $4, updated_at_ms = $4
+        WHERE action_id = $1
+        "#,
+    )
+    .bind(action_id)
+    .bind(status)
+    .bind(&result)
+    .bind(now)
+    .execute(pool)
+    .await
+    .map_err(db_error)?;
+    Ok(())
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// BLAKE3 of the canonical action; the atom hash pact signers commit to
+fn action_hash(action_id: &str, action: &AdminAction) -> Result<String, ApiError> {
+    let canonical = ubl_atom::canonicalize(&json!({
+        "action_id": action_id,
+        "kind": action.kind(),
+        "params": action.params(),
+    }))
+    .map_err(|e| ApiError::new(ErrorCode::InvalidAtom, format!("CanonicalizeError: {}", e)))?;
+    Ok(ubl_kernel::hash_atom(&canonical))
+}
+
+fn sign_message(pact_id: &str, action_hash: &str) -> Vec<u8> {
+    pact_db::build_pact_sign_message(pact_id, action_hash, ADMIN_INTENT_CLASS, 0)
+}
+
+/// Admin pacts: L4/L5, at least two signers required, covering `C.Admin` Evolution
+fn check_admin_pact(pact: &PactRecord) -> Result<(), String> {
+    if pact.risk_level < MIN_RISK_LEVEL {
+        return Err(format!("pact {} is L{}, admin actions need L{}+", pact.pact_id, pact.risk_level, MIN_RISK_LEVEL));
+    }
+    if pact.threshold < 2 {
+        return Err(format!("pact {} needs a threshold of at least 2 admins", pact.pact_id));
+    }
+    if !pact.intent_classes.iter().any(|c| c == ADMIN_INTENT_CLASS) {
+        return Err(format!("pact {} does not govern {}", pact.pact_id, ADMIN_INTENT_CLASS));
+    }
+    let covers = match pact.scope_type.as_str() {
+        "global" => true,
+        "container" => pact.scope_value.as_deref() == Some(ADMIN_CONTAINER),
+        "namespace" => ADMIN_CONTAINER.starts_with(pact.scope_value.as_deref().unwrap_or("\u{0}")),
+        _ => false,
+    };
+    if !covers {
+        return Err(format!("pact {} does not cover {}", pact.pact_id, ADMIN_CONTAINER));
+    }
+    Ok(())
+}
+
+async fn load_pact(pool: &PgPool, pact_id: &str) -> Result<PactRecord, ApiError> {
+    pact_db::get_pact(pool, pact_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Unknown pact: {}", pact_id)))
+}
+
+async fn fetch_view(pool: &PgPool, action_id: &str) -> Result<ActionView, ApiError> {
+    let row = sqlx::query(
+        r#"
+        SELECT a.action_id, a.kind, a.params, a.action_hash, a.pact_id, a.status, a.requested_by,
+               a.approvals, a.result, a.created_at_ms, a.expires_at_ms, a.executed_at_ms, p.threshold
+        FROM admin_actions a
+        JOIN pact p ON p.pact_id = a.pact_id
+        WHERE a.action_id = $1
+        "#,
+    )
+    .bind(action_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "AdminActionNotFound"))?;
+
+    let pact_id: String = Row::get(&row, "pact_id");
+    let action_hash: String = Row::get(&row, "action_hash");
+    let approvals = serde_json::from_value(Row::get(&row, "approvals"))
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("corrupt approvals: {}", e)))?;
+
+    Ok(ActionView {
+        action_id: Row::get(&row, "action_id"),
+        kind: Row::get(&row, "kind"),
+        params: Row::get(&row, "params"),
+        sign_message: hex::encode(sign_message(&pact_id, &action_hash)),
+        action_hash,
+        pact_id,
+        threshold: Row::get(&row, "threshold"),
+        status: Row::get(&row, "status"),
+        requested_by: Row::get(&row, "requested_by"),
+        approvals,
+        result: Row::get(&row, "result"),
+        created_at_ms: Row::get(&row, "created_at_ms"),
+        expires_at_ms: Row::get(&row, "expires_at_ms"),
+        executed_at_ms: Row::get(&row, "executed_at_ms"),
+    })
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    ApiError::new(ErrorCode::DatabaseError, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pact(risk_level: i16, threshold: i16, scope_type: &str, scope_value: Option<&str>) -> PactRecord {
+        PactRecord {
+            pact_id: "pact_admin".into(),
+            version: 1,
+            scope_type: scope_type.into(),
+            scope_value: scope_value.map(str::to_string),
+            intent_classes: vec!["Evolution".into()],
+            threshold,
+            signers: vec!["a".into(), "b".into(), "c".into()],
+            not_before: 0,
+            not_after: i64::MAX,
+            risk_level,
+        }
+    }
+
+    #[test]
+    fn test_action_parts_roundtrip() {
+        let action = AdminAction::from_parts("policy_binding", json!({ "container_id": "C.Jobs", "policy_id": "p2" })).unwrap();
+        assert_eq!(action, AdminAction::PolicyBinding { container_id: "C.Jobs".into(), policy_id: "p2".into() });
+        assert_eq!(action.kind(), "policy_binding");
+
+        let rebuild = AdminAction::from_parts("projection_rebuild", json!({})).unwrap();
+        assert_eq!(rebuild.params(), json!({}));
+        assert!(AdminAction::from_parts("drop_ledger", json!({})).is_err());
+        assert!(AdminAction::from_parts("read_only", json!({})).is_err());
+    }
+
+    #[test]
+    fn test_action_hash_binds_id_and_params() {
+        let on = AdminAction::ReadOnly { enabled: true };
+        let off = AdminAction::ReadOnly { enabled: false };
+        let h = action_hash("adm_1", &on).unwrap();
+        assert_eq!(h, action_hash("adm_1", &on).unwrap());
+        assert_ne!(h, action_hash("adm_2", &on).unwrap());
+        assert_ne!(h, action_hash("adm_1", &off).unwrap());
+    }
+
+    #[test]
+    fn test_approval_signature_verifies_as_pact_proof_message() {
+        let (pubkey, key) = ubl_kernel::generate_keypair();
+        let hash = action_hash("adm_1", &AdminAction::ProjectionRebuild {}).unwrap();
+        let signature = ubl_kernel::sign(&key, &sign_message("pact_admin", &hash));
+        let expected = pact_db::build_pact_sign_message("pact_admin", &hash, "Evolution", 0);
+        assert!(ubl_kernel::verify(&pubkey, &expected, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_admin_pact_requirements() {
+        assert!(check_admin_pact(&pact(4, 2, "global", None)).is_ok());
+        assert!(check_admin_pact(&pact(5, 3, "container", Some("C.Admin"))).is_ok());
+        assert!(check_admin_pact(&pact(5, 2, "namespace", Some("C."))).is_ok());
+        assert!(check_admin_pact(&pact(3, 2, "global", None)).is_err());
+        assert!(check_admin_pact(&pact(5, 1, "global", None)).is_err());
+        assert!(check_admin_pact(&pact(5, 2, "container", Some("C.Jobs"))).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! API error responses using the canonical `ubl-errors` catalog
+//!
+//! Every rejection is `{"error": "<CODE>", "message": "<detail>"}` with the
+//! HTTP status taken from the code. The message keeps the legacy text
+//! ("RealityDrift", "PactViolation: ...") for clients that have not migrated.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ubl_errors::{ApiErrorBody, ErrorCode, HasErrorCode};
+
+use crate::db::TangencyError;
+
+/// An error response carrying a catalog code
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        let status = StatusCode::from_u16(code.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        Self { status, code, message: message.into() }
+    }
+
+    /// Build from any crate error that maps onto the catalog
+    pub fn from_coded<E: HasErrorCode + std::fmt::Display>(err: &E) -> Self {
+        Self::new(err.error_code(), err.to_string())
+    }
+}
+
+/// Bridge for helpers that still return `(StatusCode, String)`
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self { status, code: ErrorCode::from_http_status(status.as_u16()), message }
+    }
+}
+
+/// The code also travels as a response extension, for `metrics::count_errors`
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, Json(ApiErrorBody::new(self.code, self.message))).into_response();
+        response.extensions_mut().insert(self.code);
+        response
+    }
+}
+
+/// Membrane rejections, with the legacy messages admission used before it
+/// ran the Membrane
+impl From<ubl_membrane::MembraneError> for ApiError {
+    fn from(err: ubl_membrane::MembraneError) -> Self {
+        use ubl_membrane::MembraneError;
+        let message = match &err {
+            MembraneError::InvalidVersion => "InvalidVersion".to_string(),
+            MembraneError::InvalidSignature => "SignatureInvalid".to_string(),
+            MembraneError::InvalidTarget => "InvalidTarget".to_string(),
+            MembraneError::RealityDrift => "RealityDrift".to_string(),
+            MembraneError::SequenceMismatch => "SequenceMismatch".to_string(),
+            MembraneError::PhysicsViolation { reason } => format!("PhysicsViolation: {}", reason),
+            MembraneError::PactViolation => "PactRequired".to_string(),
+            MembraneError::UnauthorizedEvolution => "UnauthorizedEvolution".to_string(),
+            MembraneError::Rejected { .. } => err.to_string(),
+        };
+        Self::new(err.error_code(), message)
+    }
+}
+
+impl HasErrorCode for TangencyError {
+    fn error_code(&self) -> ErrorCode {
+        match self {
+            TangencyError::InvalidVersion => ErrorCode::InvalidVersion,
+            TangencyError::InvalidTarget => ErrorCode::InvalidTarget,
+            TangencyError::RealityDrift => ErrorCode::RealityDrift,
+            TangencyError::SequenceMismatch => ErrorCode::SequenceMismatch,
+            TangencyError::PactViolation(_) => ErrorCode::PactViolation,
+            TangencyError::SerializationConflict => ErrorCode::SerializationConflict,
+            TangencyError::Quarantined => ErrorCode::Forbidden,
+            TangencyError::DatabaseError(_) => ErrorCode::DatabaseError,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_follows_code() {
+        let err = ApiError::new(ErrorCode::RealityDrift, "RealityDrift");
+        assert_eq!(err.status, StatusCode::CONFLICT);
+
+        let err = ApiError::from((StatusCode::UNAUTHORIZED, "no session".to_string()));
+        assert_eq!(err.code, ErrorCode::Unauthorized);
+    }
+
+    #[test]
+    fn test_tangency_codes() {
+        assert_eq!(TangencyError::SequenceMismatch.error_code(), ErrorCode::SequenceMismatch);
+        assert_eq!(
+            TangencyError::PactViolation("x".into()).error_code(),
+            ErrorCode::PactViolation
+        );
+    }
+
+    #[test]
+    fn test_membrane_errors_keep_legacy_messages() {
+        let err = ApiError::from(ubl_membrane::MembraneError::InvalidSignature);
+        assert_eq!((err.code, err.message.as_str()), (ErrorCode::InvalidSignature, "SignatureInvalid"));
+
+        let err = ApiError::from(ubl_membrane::MembraneError::Rejected {
+            validator: "tenant",
+            code: ErrorCode::Forbidden,
+            reason: "outside tenant".into(),
+        });
+        assert_eq!(err.status, StatusCode::FORBIDDEN);
+        assert_eq!(err.message, "tenant: outside tenant");
+    }
+}
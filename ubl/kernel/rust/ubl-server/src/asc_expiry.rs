@@ -0,0 +1,144 @@
+//! ASC Expiry Monitor
+//!
+//! Background worker that warns before Agent Signing Certificates lapse. An
+//! ASC whose `not_after` falls inside the warning window is reported to
+//! Office once (`expiry_notified_at`), so the owning entity can request a
+//! fresh one from its template before its commits start being refused.
+//! Revoked ASCs have `not_after` in the past and are never reported.
+
+use sqlx::Row;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::messenger_gateway::office_client::{AscExpiryNotice, OfficeClient};
+use sqlx::PgPool;
+
+/// Notices sent per tick; the rest wait for the next one
+const BATCH_LIMIT: i64 = 100;
+
+/// Configuration for ASC expiry notices
+#[derive(Clone)]
+pub struct AscExpiryConfig {
+    /// How often to look for expiring ASCs (in seconds)
+    pub check_interval_secs: u64,
+    /// Warn this many seconds before `not_after`
+    pub warn_before_secs: u64,
+}
+
+impl Default for AscExpiryConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 300,
+            warn_before_secs: 3 * 24 * 3600, // 3 days
+        }
+    }
+}
+
+impl AscExpiryConfig {
+    /// Defaults overridden by `UBL_ASC_EXPIRY_INTERVAL_SECS` / `UBL_ASC_EXPIRY_WARN_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            check_interval_secs: get("UBL_ASC_EXPIRY_INTERVAL_SECS", defaults.check_interval_secs),
+            warn_before_secs: get("UBL_ASC_EXPIRY_WARN_SECS", defaults.warn_before_secs),
+        }
+    }
+}
+
+/// ASC Expiry Monitor - notifies Office ahead of `not_after`
+pub struct AscExpiryMonitor {
+    pool: PgPool,
+    office: OfficeClient,
+    config: AscExpiryConfig,
+}
+
+impl AscExpiryMonitor {
+    pub fn new(pool: PgPool, office_url: String, config: AscExpiryConfig) -> Self {
+        Self { pool, office: OfficeClient::new(office_url), config }
+    }
+
+    /// Start the monitoring loop (runs forever)
+    pub async fn run(self) {
+        info!(
+            "🪪 ASC expiry monitor started - checking every {}s for ASCs expiring within {}s",
+            self.config.check_interval_secs, self.config.warn_before_secs
+        );
+
+        let mut tick = interval(Duration::from_secs(self.config.check_interval_secs));
+
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = self.notify_expiring().await {
+                error!("❌ ASC expiry monitor error: {}", e);
+            }
+        }
+    }
+
+    /// Report ASCs entering the warning window, each once
+    async fn notify_expiring(&self) -> Result<(), sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT a.asc_id::TEXT AS asc_id, a.sid,
+                   (EXTRACT(EPOCH FROM a.not_after) * 1000)::BIGINT AS not_after_ms,
+                   r.tenant_id, r.template
+            FROM id_asc a
+            LEFT JOIN id_asc_request r ON r.asc_id = a.asc_id
+            WHERE a.expiry_notified_at IS NULL
+              AND a.not_after > NOW()
+              AND a.not_after <= NOW() + INTERVAL '1 second' * $1
+            ORDER BY a.not_after
+            LIMIT $2
+            "#,
+        )
+        .bind(self.config.warn_before_secs as i64)
+        .bind(BATCH_LIMIT)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in &rows {
+            let not_after_ms: i64 = row.try_get("not_after_ms").unwrap_or(0);
+            let notice = AscExpiryNotice {
+                asc_id: row.try_get("asc_id").unwrap_or_default(),
+                sid: row.try_get("sid").unwrap_or_default(),
+                tenant_id: row.try_get("tenant_id").ok().flatten(),
+                template: row.try_get("template").ok().flatten(),
+                not_after_ms,
+                expires_in_secs: seconds_left(not_after_ms, crate::timestamps::now_ms()),
+            };
+
+            // Only mark notified once Office accepted it; failures retry next tick
+            match self.office.asc_expiring(&notice).await {
+                Ok(()) => {
+                    sqlx::query("UPDATE id_asc SET expiry_notified_at = NOW() WHERE asc_id = $1::UUID")
+                        .bind(&notice.asc_id)
+                        .execute(&self.pool)
+                        .await?;
+                    info!("🪪 ASC {} of {} expires in {}s - Office notified", notice.asc_id, notice.sid, notice.expires_in_secs);
+                }
+                Err(e) => warn!("⚠️  ASC expiry notice for {} not delivered: {}", notice.asc_id, e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn seconds_left(not_after_ms: i64, now_ms: i64) -> i64 {
+    (not_after_ms - now_ms).max(0) / 1000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seconds_left() {
+        assert_eq!(seconds_left(10_500, 500), 10);
+        assert_eq!(seconds_left(500, 10_500), 0);
+    }
+}
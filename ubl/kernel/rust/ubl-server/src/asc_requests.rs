@@ -0,0 +1,472 @@
+//! ASC scope templates and self-service issuance
+//!
+//! Hand-crafting ASC scope JSON is error prone, so common agent roles are
+//! named templates. An agent asks for an ASC from a template for one tenant,
+//! signing the request with its own Ed25519 key; a tenant owner/admin on a
+//! step-up session approves (issuing the ASC) or rejects it.
+//!
+//! Endpoints:
+//! - GET  /id/asc/templates                      → Available templates
+//! - POST /id/agents/:sid/asc/requests           → Agent files a request (signed)
+//! - GET  /id/asc/requests?tenant_id=&status=    → List (tenant admin, step-up)
+//! - POST /id/asc/requests/:request_id/approve   → Issue the ASC (tenant admin, step-up)
+//! - POST /id/asc/requests/:request_id/reject    → Decline (tenant admin, step-up)
+//!
+//! Signed message: `ubl:asc_request\n<sid>\n<tenant_id>\n<template>\n<ts_ms>`
+//!
+//! Expiry notices for issued ASCs are sent by `asc_expiry`.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{PgPool, Row};
+use tracing::{info, warn};
+use ubl_errors::ErrorCode;
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::auth::session::SessionFlavor;
+use crate::auth::session_db;
+use crate::id_db;
+use crate::id_routes::{issue_asc_with_scopes, IssueAscResp};
+use crate::tenant::{db as tenant_db, types::MemberRole};
+use crate::timestamps::now_ms;
+
+/// Requests signed further than this from server time are refused
+const MAX_CLOCK_SKEW_MS: i64 = 5 * 60 * 1000;
+/// Requests listed per page
+const LIST_LIMIT: i64 = 200;
+
+/// A named ASC scope
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct AscTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub containers: &'static [&'static str],
+    pub intent_classes: &'static [&'static str],
+    pub max_delta: Option<i64>,
+    pub ttl_secs: i64,
+}
+
+impl AscTemplate {
+    /// Scope JSON in the shape `POST /id/agents/:sid/asc` issues
+    pub fn scopes(&self) -> Value {
+        json!({
+            "containers": self.containers,
+            "intent_classes": self.intent_classes,
+            "max_delta": self.max_delta,
+        })
+    }
+}
+
+pub const TEMPLATES: &[AscTemplate] = &[
+    AscTemplate {
+        name: "messenger-bot",
+        description: "Posts and reads messages in C.Messenger",
+        containers: &["C.Messenger"],
+        intent_classes: &["Observation"],
+        max_delta: Some(0),
+        ttl_secs: 7 * 24 * 3600,
+    },
+    AscTemplate {
+        name: "jobs-worker",
+        description: "Drives job lifecycle events in C.Jobs",
+        containers: &["C.Jobs"],
+        intent_classes: &["Observation"],
+        max_delta: Some(0),
+        ttl_secs: 24 * 3600,
+    },
+    AscTemplate {
+        name: "office-agent",
+        description: "LLM entity: conversations, job proposals and results",
+        containers: &["C.Office", "C.Messenger", "C.Jobs"],
+        intent_classes: &["Observation"],
+        max_delta: Some(0),
+        ttl_secs: 24 * 3600,
+    },
+    AscTemplate {
+        name: "auditor",
+        description: "Records audit observations in C.Audit",
+        containers: &["C.Audit"],
+        intent_classes: &["Observation"],
+        max_delta: Some(0),
+        ttl_secs: 30 * 24 * 3600,
+    },
+];
+
+/// Look up a template by name
+pub fn template(name: &str) -> Option<&'static AscTemplate> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+/// Message the agent signs when requesting an ASC
+pub fn request_sign_message(sid: &str, tenant_id: &str, template: &str, ts_ms: i64) -> String {
+    format!("ubl:asc_request\n{}\n{}\n{}\n{}", sid, tenant_id, template, ts_ms)
+}
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAscRequest {
+    pub template: String,
+    pub tenant_id: String,
+    #[serde(default)]
+    pub justification: Option<String>,
+    pub ts_ms: i64,
+    /// Ed25519 signature (hex) over `request_sign_message`
+    pub signature: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DecideRequest {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListRequestsParams {
+    pub tenant_id: String,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AscRequestView {
+    pub request_id: String,
+    pub sid: String,
+    pub tenant_id: String,
+    pub template: String,
+    pub justification: Option<String>,
+    pub status: String,
+    pub requested_at_ms: i64,
+    pub decided_by: Option<String>,
+    pub decided_at_ms: Option<i64>,
+    pub decision_reason: Option<String>,
+    pub asc_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApproveResponse {
+    pub request: AscRequestView,
+    pub asc: IssueAscResp,
+}
+
+pub fn routes(pool: PgPool) -> Router {
+    Router::new()
+        .route("/id/asc/templates", get(list_templates))
+        .route("/id/agents/:sid/asc/requests", post(create_request))
+        .route("/id/asc/requests", get(list_requests))
+        .route("/id/asc/requests/:request_id/approve", post(approve_request))
+        .route("/id/asc/requests/:request_id/reject", post(reject_request))
+        .with_state(pool)
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+/// GET /id/asc/templates
+async fn list_templates() -> Json<Value> {
+    Json(json!({ "templates": TEMPLATES }))
+}
+
+/// POST /id/agents/:sid/asc/requests — agent asks for an ASC from a template
+async fn create_request(
+    State(pool): State<PgPool>,
+    Path(sid): Path<String>,
+    Json(req): Json<CreateAscRequest>,
+) -> Result<Json<AscRequestView>, ApiError> {
+    if template(&req.template).is_none() {
+        return Err(ApiError::new(ErrorCode::BadRequest, format!("Unknown ASC template: {}", req.template)));
+    }
+    if req.tenant_id.is_empty() {
+        return Err(ApiError::new(ErrorCode::BadRequest, "tenant_id is required"));
+    }
+    check_fresh(req.ts_ms, now_ms())?;
+
+    let cred = id_db::get_credential(&pool, &sid, "ed25519")
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "No Ed25519 credential found"))?;
+    let message = request_sign_message(&sid, &req.tenant_id, &req.template, req.ts_ms);
+    ubl_kernel::verify(&hex::encode(&cred.public_key), message.as_bytes(), &req.signature)
+        .map_err(|_| ApiError::new(ErrorCode::InvalidSignature, "ASC request signature does not verify"))?;
+
+    tenant_db::get_tenant(&pool, &req.tenant_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "Tenant not found"))?;
+
+    let request_id = format!("ascreq_{}", Uuid::new_v4().simple());
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO id_asc_request (request_id, sid, tenant_id, template, justification, requested_at_ms)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(&request_id)
+    .bind(&sid)
+    .bind(&req.tenant_id)
+    .bind(&req.template)
+    .bind(&req.justification)
+    .bind(now_ms())
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    if inserted.rows_affected() == 0 {
+        return Err(ApiError::new(
+            ErrorCode::BadRequest,
+            format!("A {} request for {} is already pending", req.template, req.tenant_id),
+        ));
+    }
+
+    info!("📝 ASC requested: {} template={} tenant={} ({})", sid, req.template, req.tenant_id, request_id);
+    load_request(&pool, &request_id).await.map(Json)
+}
+
+/// GET /id/asc/requests?tenant_id=&status=
+async fn list_requests(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Query(params): Query<ListRequestsParams>,
+) -> Result<Json<Value>, ApiError> {
+    require_tenant_admin(&pool, &headers, &params.tenant_id).await?;
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM id_asc_request
+         WHERE tenant_id = $1 AND ($2::TEXT IS NULL OR status = $2)
+         ORDER BY requested_at_ms DESC
+         LIMIT $3",
+        REQUEST_COLUMNS
+    ))
+    .bind(&params.tenant_id)
+    .bind(&params.status)
+    .bind(LIST_LIMIT)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let requests: Vec<AscRequestView> = rows.iter().map(view_from_row).collect();
+    Ok(Json(json!({ "requests": requests })))
+}
+
+/// POST /id/asc/requests/:request_id/approve — issue the templated ASC
+async fn approve_request(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Path(request_id): Path<String>,
+) -> Result<Json<ApproveResponse>, ApiError> {
+    let pending = load_request(&pool, &request_id).await?;
+    let admin = require_tenant_admin(&pool, &headers, &pending.tenant_id).await?;
+    let template = template(&pending.template)
+        .ok_or_else(|| ApiError::new(ErrorCode::BadRequest, format!("Unknown ASC template: {}", pending.template)))?;
+
+    // Claim the request first so concurrent approvals issue a single ASC
+    claim_pending(&pool, &request_id, "approved", &admin, None).await?;
+
+    let asc = match issue_asc_with_scopes(&pool, &pending.sid, template.scopes(), template.ttl_secs).await {
+        Ok(asc) => asc,
+        Err(e) => {
+            // Back to pending so it can be approved again
+            sqlx::query("UPDATE id_asc_request SET status = 'pending', decided_by = NULL, decided_at_ms = NULL WHERE request_id = $1")
+                .bind(&request_id)
+                .execute(&pool)
+                .await
+                .map_err(db_error)?;
+            return Err(e.into());
+        }
+    };
+
+    sqlx::query("UPDATE id_asc_request SET asc_id = $2::UUID WHERE request_id = $1")
+        .bind(&request_id)
+        .bind(&asc.asc_id)
+        .execute(&pool)
+        .await
+        .map_err(db_error)?;
+
+    info!("✅ ASC request {} approved by {}: asc={}", request_id, admin, asc.asc_id);
+    Ok(Json(ApproveResponse { request: load_request(&pool, &request_id).await?, asc }))
+}
+
+/// POST /id/asc/requests/:request_id/reject
+async fn reject_request(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Path(request_id): Path<String>,
+    body: Option<Json<DecideRequest>>,
+) -> Result<Json<AscRequestView>, ApiError> {
+    let pending = load_request(&pool, &request_id).await?;
+    let admin = require_tenant_admin(&pool, &headers, &pending.tenant_id).await?;
+    let reason = body.and_then(|Json(b)| b.reason);
+
+    claim_pending(&pool, &request_id, "rejected", &admin, reason.as_deref()).await?;
+
+    warn!("🚫 ASC request {} rejected by {}", request_id, admin);
+    load_request(&pool, &request_id).await.map(Json)
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+const REQUEST_COLUMNS: &str = "request_id, sid, tenant_id, template, justification, status, requested_at_ms, \
+     decided_by, decided_at_ms, decision_reason, asc_id::TEXT AS asc_id";
+
+fn view_from_row(row: &sqlx::postgres::PgRow) -> AscRequestView {
+    AscRequestView {
+        request_id: row.get("request_id"),
+        sid: row.get("sid"),
+        tenant_id: row.get("tenant_id"),
+        template: row.get("template"),
+        justification: row.get("justification"),
+        status: row.get("status"),
+        requested_at_ms: row.get("requested_at_ms"),
+        decided_by: row.get("decided_by"),
+        decided_at_ms: row.get("decided_at_ms"),
+        decision_reason: row.get("decision_reason"),
+        asc_id: row.get("asc_id"),
+    }
+}
+
+async fn load_request(pool: &PgPool, request_id: &str) -> Result<AscRequestView, ApiError> {
+    sqlx::query(&format!("SELECT {} FROM id_asc_request WHERE request_id = $1", REQUEST_COLUMNS))
+        .bind(request_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?
+        .map(|row| view_from_row(&row))
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "ASC request not found"))
+}
+
+/// Move a pending request to its decision; fails if it was already decided
+async fn claim_pending(
+    pool: &PgPool,
+    request_id: &str,
+    status: &str,
+    admin: &str,
+    reason: Option<&str>,
+) -> Result<(), ApiError> {
+    let updated = sqlx::query(
+        r#"
+        UPDATE id_asc_request
+        SET status = $2, decided_by = $3, decided_at_ms = $4, decision_reason = $5
+        WHERE request_id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(request_id)
+    .bind(status)
+    .bind(admin)
+    .bind(now_ms())
+    .bind(reason)
+    .execute(pool)
+    .await
+    .map_err(db_error)?;
+
+    if updated.rows_affected() == 0 {
+        return Err(ApiError::new(ErrorCode::BadRequest, "ASC request is no longer pending"));
+    }
+    Ok(())
+}
+
+/// Caller must hold a step-up session and be owner/admin of `tenant_id`;
+/// returns the caller's sid
+async fn require_tenant_admin(pool: &PgPool, headers: &HeaderMap, tenant_id: &str) -> Result<String, ApiError> {
+    let token = session_token(headers)
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "missing session"))?;
+    let session = session_db::get_valid(pool, &token)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "invalid or expired session"))?;
+
+    if !matches!(session.flavor, SessionFlavor::StepUp) {
+        return Err(ApiError::new(ErrorCode::Forbidden, "step-up required"));
+    }
+
+    match tenant_db::get_member_role(pool, tenant_id, &session.sid).await.map_err(db_error)? {
+        Some(MemberRole::Owner) | Some(MemberRole::Admin) => Ok(session.sid),
+        _ => Err(ApiError::new(ErrorCode::Forbidden, "tenant owner or admin required")),
+    }
+}
+
+/// Bearer token, or the `session` / `ubl_session` cookie
+fn session_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .map(|s| s.to_string())
+        .or_else(|| {
+            headers.get("cookie")?.to_str().ok()?.split(';').find_map(|part| {
+                let mut kv = part.trim().splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some("session"), Some(v)) | (Some("ubl_session"), Some(v)) => Some(v.to_string()),
+                    _ => None,
+                }
+            })
+        })
+}
+
+fn check_fresh(ts_ms: i64, now_ms: i64) -> Result<(), ApiError> {
+    if (now_ms - ts_ms).abs() > MAX_CLOCK_SKEW_MS {
+        return Err(ApiError::new(ErrorCode::BadRequest, "ts_ms outside the allowed clock skew"));
+    }
+    Ok(())
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    ApiError::new(ErrorCode::DatabaseError, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_are_unique_and_scoped() {
+        for (i, t) in TEMPLATES.iter().enumerate() {
+            assert!(TEMPLATES[i + 1..].iter().all(|o| o.name != t.name), "duplicate template {}", t.name);
+            assert!(!t.containers.is_empty() && !t.intent_classes.is_empty());
+            assert!(t.ttl_secs > 0);
+        }
+        assert!(template("nope").is_none());
+    }
+
+    #[test]
+    fn test_template_scopes_match_issue_shape() {
+        let scopes = template("messenger-bot").unwrap().scopes();
+        assert_eq!(
+            scopes,
+            json!({ "containers": ["C.Messenger"], "intent_classes": ["Observation"], "max_delta": 0 })
+        );
+    }
+
+    #[test]
+    fn test_request_signature_roundtrip() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let pubkey = hex::encode(key.verifying_key().to_bytes());
+        let message = request_sign_message("ubl:sid:bot", "tenant_a", "jobs-worker", 1_000);
+        assert_eq!(message, "ubl:asc_request\nubl:sid:bot\ntenant_a\njobs-worker\n1000");
+
+        let signature = hex::encode(key.sign(message.as_bytes()).to_bytes());
+        assert!(ubl_kernel::verify(&pubkey, message.as_bytes(), &signature).is_ok());
+
+        let other_tenant = request_sign_message("ubl:sid:bot", "tenant_b", "jobs-worker", 1_000);
+        assert!(ubl_kernel::verify(&pubkey, other_tenant.as_bytes(), &signature).is_err());
+    }
+
+    #[test]
+    fn test_check_fresh() {
+        assert!(check_fresh(1_000_000, 1_000_000 + MAX_CLOCK_SKEW_MS).is_ok());
+        assert!(check_fresh(1_000_000, 1_000_001 + MAX_CLOCK_SKEW_MS).is_err());
+    }
+}
@@ -0,0 +1,493 @@
+//! Atom encryption at rest
+//!
+//! Containers listed in `UBL_ENCRYPTED_CONTAINERS` (comma-separated) store
+//! `ledger_atom.atom_data` as a sealed envelope instead of plaintext JSON.
+//!
+//! Envelope encryption: every encrypted container has a random data key
+//! (DEK) that seals its atoms with XChaCha20-Poly1305. DEKs are stored in
+//! `atom_data_key`, wrapped by the master key `atom-master` from the
+//! SecretProvider chain (see `secrets.rs`). Rotating the master key re-wraps
+//! the DEKs only; sealed atoms are never rewritten (ledger_atom is
+//! append-only anyway).
+//!
+//! Sealed `atom_data`:
+//!   {"type": <atom type>, "ubl_sealed": {"v": 1, "dek": <version>, "nonce": <hex>, "ct": <hex>}}
+//!
+//! `type` stays in clear so `atom_type` and event routing keep working; any
+//! other JSON-path query over an encrypted container's atoms sees nothing.
+//! The ciphertext is bound to its container and atom_hash (AAD), so a sealed
+//! atom cannot be replayed under another hash.
+//!
+//! Projections are fed the plaintext atom at commit time; projection rebuilds
+//! and `GET /atom/:hash` (session holders only) open the envelope.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde_json::{json, Value};
+use sqlx::{PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::{OnceLock, RwLock};
+use thiserror::Error;
+use tracing::{info, warn};
+use ubl_kernel::Zeroizing;
+
+use crate::secrets::Secret;
+
+/// SecretProvider ID of the master key; the previous one is `<id>.prev`
+pub const MASTER_KEY_ID: &str = "atom-master";
+/// Field holding the sealed payload in `atom_data`
+pub const SEALED_FIELD: &str = "ubl_sealed";
+const ENVELOPE_VERSION: u64 = 1;
+const NONCE_LEN: usize = 24;
+
+type KeyBytes = Zeroizing<[u8; 32]>;
+
+#[derive(Debug, Error)]
+pub enum AtomCryptoError {
+    #[error("master key unavailable: {0}")]
+    MasterKey(String),
+
+    #[error("data key {0} v{1} is wrapped by unknown master key {2}")]
+    UnknownMaster(String, i32, String),
+
+    #[error("no data key {0} v{1}")]
+    MissingDataKey(String, i32),
+
+    #[error("malformed sealed atom: {0}")]
+    Malformed(String),
+
+    #[error("authentication failed while opening {0}")]
+    Decrypt(&'static str),
+
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+
+static ENCRYPTED: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// Containers whose atoms are sealed, from `UBL_ENCRYPTED_CONTAINERS`
+pub fn encrypted_containers() -> &'static HashSet<String> {
+    ENCRYPTED.get_or_init(|| {
+        parse_container_list(&std::env::var("UBL_ENCRYPTED_CONTAINERS").unwrap_or_default())
+    })
+}
+
+pub fn is_encrypted(container_id: &str) -> bool {
+    encrypted_containers().contains(container_id)
+}
+
+fn parse_container_list(raw: &str) -> HashSet<String> {
+    raw.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+// =============================================================================
+// MASTER KEY
+// =============================================================================
+
+struct MasterKey {
+    /// Fingerprint recorded next to every DEK it wraps
+    id: String,
+    key: KeyBytes,
+}
+
+impl MasterKey {
+    fn from_bytes(key: KeyBytes) -> Self {
+        let mut h = blake3::Hasher::new();
+        h.update(b"ubl:atom-master\n");
+        h.update(key.as_slice());
+        let id = hex::encode(&h.finalize().as_bytes()[..8]);
+        Self { id, key }
+    }
+
+    fn generate() -> Self {
+        let mut key = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(key.as_mut_slice());
+        Self::from_bytes(key)
+    }
+
+    fn to_secret(&self) -> Secret {
+        Secret::new(hex::encode(self.key.as_slice()))
+    }
+}
+
+fn load_master(secret_id: &str) -> Result<Option<MasterKey>, AtomCryptoError> {
+    let Some(secret) = crate::keystore::provider()
+        .get(secret_id)
+        .map_err(|e| AtomCryptoError::MasterKey(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let bytes = Zeroizing::new(
+        hex::decode(secret.expose()).map_err(|_| AtomCryptoError::MasterKey(format!("'{}' is not hex", secret_id)))?,
+    );
+    let key: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| AtomCryptoError::MasterKey(format!("'{}' must be 32 bytes", secret_id)))?;
+    Ok(Some(MasterKey::from_bytes(Zeroizing::new(key))))
+}
+
+/// Current master key, generated and stored on first use
+fn current_master() -> Result<MasterKey, AtomCryptoError> {
+    if let Some(master) = load_master(MASTER_KEY_ID)? {
+        return Ok(master);
+    }
+    let master = MasterKey::generate();
+    crate::keystore::provider()
+        .put(MASTER_KEY_ID, &master.to_secret())
+        .map_err(|e| AtomCryptoError::MasterKey(e.to_string()))?;
+    warn!("🔐 Generated atom master key {}", master.id);
+    Ok(master)
+}
+
+/// Master key with the given fingerprint: the current one or the previous one
+fn master_by_id(kek_id: &str) -> Result<Option<MasterKey>, AtomCryptoError> {
+    for secret_id in [MASTER_KEY_ID.to_string(), format!("{}.prev", MASTER_KEY_ID)] {
+        if let Some(master) = load_master(&secret_id)? {
+            if master.id == kek_id {
+                return Ok(Some(master));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// =============================================================================
+// AEAD
+// =============================================================================
+
+/// `nonce || ciphertext` of `plaintext` under `key`; also seals tenant exports
+pub(crate) fn seal_bytes(key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ct = XChaCha20Poly1305::new(key.into())
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .expect("XChaCha20-Poly1305 encryption does not fail for in-memory buffers");
+    [nonce.as_slice(), ct.as_slice()].concat()
+}
+
+pub(crate) fn open_bytes(key: &[u8; 32], aad: &[u8], sealed: &[u8], what: &'static str) -> Result<Zeroizing<Vec<u8>>, AtomCryptoError> {
+    if sealed.len() < NONCE_LEN {
+        return Err(AtomCryptoError::Malformed(format!("{} shorter than its nonce", what)));
+    }
+    let (nonce, ct) = sealed.split_at(NONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ct, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| AtomCryptoError::Decrypt(what))
+}
+
+fn dek_aad(container_id: &str, version: i32) -> Vec<u8> {
+    format!("ubl:dek\n{}\n{}", container_id, version).into_bytes()
+}
+
+fn atom_aad(container_id: &str, atom_hash: &str) -> Vec<u8> {
+    format!("ubl:atom\n{}\n{}", container_id, atom_hash).into_bytes()
+}
+
+fn unwrap_dek(master: &MasterKey, container_id: &str, version: i32, wrapped: &[u8]) -> Result<KeyBytes, AtomCryptoError> {
+    let bytes = open_bytes(&master.key, &dek_aad(container_id, version), wrapped, "data key")?;
+    let key: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| AtomCryptoError::Malformed("data key must be 32 bytes".into()))?;
+    Ok(Zeroizing::new(key))
+}
+
+/// Seal an atom with a data key
+fn seal_with(dek: &[u8; 32], version: i32, container_id: &str, atom_hash: &str, atom: &Value) -> Value {
+    let plaintext = Zeroizing::new(serde_json::to_vec(atom).expect("JSON values serialize"));
+    let sealed = seal_bytes(dek, &atom_aad(container_id, atom_hash), &plaintext);
+    let (nonce, ct) = sealed.split_at(NONCE_LEN);
+    json!({
+        "type": atom.get("type").cloned().unwrap_or(Value::Null),
+        SEALED_FIELD: {
+            "v": ENVELOPE_VERSION,
+            "dek": version,
+            "nonce": hex::encode(nonce),
+            "ct": hex::encode(ct),
+        },
+    })
+}
+
+/// Parsed envelope: (dek version, nonce || ciphertext)
+fn parse_envelope(stored: &Value) -> Result<(i32, Vec<u8>), AtomCryptoError> {
+    let sealed = &stored[SEALED_FIELD];
+    if sealed["v"].as_u64() != Some(ENVELOPE_VERSION) {
+        return Err(AtomCryptoError::Malformed(format!("unsupported envelope version {}", sealed["v"])));
+    }
+    let version = sealed["dek"]
+        .as_i64()
+        .and_then(|v| i32::try_from(v).ok())
+        .ok_or_else(|| AtomCryptoError::Malformed("missing dek version".into()))?;
+    let field = |name: &str| {
+        sealed[name]
+            .as_str()
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or_else(|| AtomCryptoError::Malformed(format!("missing or non-hex {}", name)))
+    };
+    Ok((version, [field("nonce")?, field("ct")?].concat()))
+}
+
+fn open_with(dek: &[u8; 32], container_id: &str, atom_hash: &str, sealed: &[u8]) -> Result<Value, AtomCryptoError> {
+    let plaintext = open_bytes(dek, &atom_aad(container_id, atom_hash), sealed, "atom")?;
+    serde_json::from_slice(&plaintext).map_err(|e| AtomCryptoError::Malformed(e.to_string()))
+}
+
+/// Whether stored `atom_data` is a sealed envelope
+pub fn is_sealed(stored: &Value) -> bool {
+    stored.get(SEALED_FIELD).is_some()
+}
+
+// =============================================================================
+// DATA KEYS
+// =============================================================================
+
+#[derive(Default)]
+struct KeyCache {
+    /// Latest DEK version per container (used for sealing)
+    current: HashMap<String, i32>,
+    /// Unwrapped DEKs by (container, version)
+    keys: HashMap<(String, i32), KeyBytes>,
+}
+
+static CACHE: OnceLock<RwLock<KeyCache>> = OnceLock::new();
+
+fn cache() -> &'static RwLock<KeyCache> {
+    CACHE.get_or_init(|| RwLock::new(KeyCache::default()))
+}
+
+fn cached_key(container_id: &str, version: i32) -> Option<KeyBytes> {
+    let cache = cache().read().expect("atom key cache poisoned");
+    cache.keys.get(&(container_id.to_string(), version)).cloned()
+}
+
+fn cache_key(container_id: &str, version: i32, key: KeyBytes, current: bool) {
+    let mut cache = cache().write().expect("atom key cache poisoned");
+    if current {
+        cache.current.insert(container_id.to_string(), version);
+    }
+    cache.keys.insert((container_id.to_string(), version), key);
+}
+
+/// Load and unwrap one DEK
+async fn data_key(pool: &PgPool, container_id: &str, version: i32) -> Result<KeyBytes, AtomCryptoError> {
+    if let Some(key) = cached_key(container_id, version) {
+        return Ok(key);
+    }
+    let row = sqlx::query("SELECT wrapped_key, kek_id FROM atom_data_key WHERE container_id = $1 AND dek_version = $2")
+        .bind(container_id)
+        .bind(version)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AtomCryptoError::MissingDataKey(container_id.to_string(), version))?;
+    let kek_id: String = row.get("kek_id");
+    let wrapped: Vec<u8> = row.get("wrapped_key");
+    let master = master_by_id(&kek_id)?
+        .ok_or_else(|| AtomCryptoError::UnknownMaster(container_id.to_string(), version, kek_id.clone()))?;
+    let key = unwrap_dek(&master, container_id, version, &wrapped)?;
+    cache_key(container_id, version, key.clone(), false);
+    Ok(key)
+}
+
+/// Latest DEK of a container, created on the first sealed atom
+async fn current_data_key(pool: &PgPool, container_id: &str) -> Result<(i32, KeyBytes), AtomCryptoError> {
+    let cached = cache().read().expect("atom key cache poisoned").current.get(container_id).copied();
+    if let Some(version) = cached {
+        return Ok((version, data_key(pool, container_id, version).await?));
+    }
+
+    let latest: Option<i32> = sqlx::query_scalar("SELECT MAX(dek_version) FROM atom_data_key WHERE container_id = $1")
+        .bind(container_id)
+        .fetch_one(pool)
+        .await?;
+
+    let version = match latest {
+        Some(version) => version,
+        None => {
+            let master = current_master()?;
+            let mut dek = Zeroizing::new([0u8; 32]);
+            OsRng.fill_bytes(dek.as_mut_slice());
+            let wrapped = seal_bytes(&master.key, &dek_aad(container_id, 1), dek.as_slice());
+            // A concurrent writer may have created it first; theirs wins
+            sqlx::query(
+                r#"
+                INSERT INTO atom_data_key (container_id, dek_version, wrapped_key, kek_id, created_at_ms)
+                VALUES ($1, 1, $2, $3, $4)
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(container_id)
+            .bind(&wrapped)
+            .bind(&master.id)
+            .bind(crate::timestamps::now_ms())
+            .execute(pool)
+            .await?;
+            info!("🔐 Created data key for {} (master {})", container_id, master.id);
+            1
+        }
+    };
+
+    let key = data_key(pool, container_id, version).await?;
+    cache_key(container_id, version, key.clone(), true);
+    Ok((version, key))
+}
+
+// =============================================================================
+// PUBLIC API
+// =============================================================================
+
+/// Seal an atom for storage under the container's current DEK
+pub async fn seal_atom(pool: &PgPool, container_id: &str, atom_hash: &str, atom: &Value) -> Result<Value, AtomCryptoError> {
+    let (version, dek) = current_data_key(pool, container_id).await?;
+    Ok(seal_with(&dek, version, container_id, atom_hash, atom))
+}
+
+/// Plaintext of stored `atom_data`; unsealed atoms are returned as is
+pub async fn open_atom(pool: &PgPool, container_id: &str, atom_hash: &str, stored: &Value) -> Result<Value, AtomCryptoError> {
+    if !is_sealed(stored) {
+        return Ok(stored.clone());
+    }
+    let (version, sealed) = parse_envelope(stored)?;
+    let dek = data_key(pool, container_id, version).await?;
+    open_with(&dek, container_id, atom_hash, &sealed)
+}
+
+#[derive(Debug)]
+pub struct RewrapReport {
+    pub master_key_id: String,
+    pub rewrapped: usize,
+}
+
+/// Re-wrap every DEK not yet under the current master key. Sealed atoms are
+/// untouched. Safe to re-run; resumes an interrupted rotation.
+pub async fn rewrap_data_keys(pool: &PgPool) -> Result<RewrapReport, AtomCryptoError> {
+    let current = current_master()?;
+    let rows = sqlx::query("SELECT container_id, dek_version, wrapped_key, kek_id FROM atom_data_key WHERE kek_id <> $1")
+        .bind(&current.id)
+        .fetch_all(pool)
+        .await?;
+
+    let mut rewrapped = 0;
+    for row in &rows {
+        let container_id: String = row.get("container_id");
+        let version: i32 = row.get("dek_version");
+        let kek_id: String = row.get("kek_id");
+        let wrapped: Vec<u8> = row.get("wrapped_key");
+
+        let old = master_by_id(&kek_id)?
+            .ok_or_else(|| AtomCryptoError::UnknownMaster(container_id.clone(), version, kek_id.clone()))?;
+        let dek = unwrap_dek(&old, &container_id, version, &wrapped)?;
+        let rewrapped_key = seal_bytes(&current.key, &dek_aad(&container_id, version), dek.as_slice());
+
+        sqlx::query(
+            r#"
+            UPDATE atom_data_key
+            SET wrapped_key = $3, kek_id = $4, rewrapped_at_ms = $5
+            WHERE container_id = $1 AND dek_version = $2 AND kek_id = $6
+            "#,
+        )
+        .bind(&container_id)
+        .bind(version)
+        .bind(&rewrapped_key)
+        .bind(&current.id)
+        .bind(crate::timestamps::now_ms())
+        .bind(&kek_id)
+        .execute(pool)
+        .await?;
+        rewrapped += 1;
+    }
+
+    Ok(RewrapReport { master_key_id: current.id, rewrapped })
+}
+
+/// Rotate the master key: finish any pending re-wrap, keep the current key
+/// as `atom-master.prev`, install a fresh key and re-wrap all DEKs under it.
+pub async fn rotate_master_key(pool: &PgPool) -> Result<RewrapReport, AtomCryptoError> {
+    // `.prev` is overwritten below, so nothing may still depend on it
+    rewrap_data_keys(pool).await?;
+
+    let old = current_master()?;
+    let next = MasterKey::generate();
+    let provider = crate::keystore::provider();
+    provider
+        .put(&format!("{}.prev", MASTER_KEY_ID), &old.to_secret())
+        .map_err(|e| AtomCryptoError::MasterKey(e.to_string()))?;
+    provider
+        .put(MASTER_KEY_ID, &next.to_secret())
+        .map_err(|e| AtomCryptoError::MasterKey(e.to_string()))?;
+    warn!("🔄 Rotated atom master key {} → {}", old.id, next.id);
+
+    rewrap_data_keys(pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(byte: u8) -> [u8; 32] {
+        [byte; 32]
+    }
+
+    #[test]
+    fn test_parse_container_list() {
+        let set = parse_container_list(" C.Messenger, ,C.Office ");
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("C.Messenger") && set.contains("C.Office"));
+        assert!(parse_container_list("").is_empty());
+    }
+
+    #[test]
+    fn test_seal_open_roundtrip_keeps_type_in_clear() {
+        let atom = json!({ "type": "message.sent", "payload": { "text": "hi" } });
+        let sealed = seal_with(&key(1), 1, "C.Messenger", "abc", &atom);
+
+        assert!(is_sealed(&sealed));
+        assert_eq!(sealed["type"], "message.sent");
+        assert!(!sealed.to_string().contains("hi"));
+
+        let (version, bytes) = parse_envelope(&sealed).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(open_with(&key(1), "C.Messenger", "abc", &bytes).unwrap(), atom);
+    }
+
+    #[test]
+    fn test_ciphertext_is_bound_to_hash_container_and_key() {
+        let atom = json!({ "type": "x" });
+        let sealed = seal_with(&key(1), 1, "C.A", "h1", &atom);
+        let (_, bytes) = parse_envelope(&sealed).unwrap();
+
+        assert!(matches!(open_with(&key(1), "C.A", "h2", &bytes), Err(AtomCryptoError::Decrypt(_))));
+        assert!(matches!(open_with(&key(1), "C.B", "h1", &bytes), Err(AtomCryptoError::Decrypt(_))));
+        assert!(matches!(open_with(&key(2), "C.A", "h1", &bytes), Err(AtomCryptoError::Decrypt(_))));
+    }
+
+    #[test]
+    fn test_rewrap_preserves_data_key() {
+        let old = MasterKey::from_bytes(Zeroizing::new(key(3)));
+        let new = MasterKey::from_bytes(Zeroizing::new(key(4)));
+        assert_ne!(old.id, new.id);
+
+        let dek = key(9);
+        let wrapped = seal_bytes(&old.key, &dek_aad("C.A", 1), &dek);
+        let unwrapped = unwrap_dek(&old, "C.A", 1, &wrapped).unwrap();
+        let rewrapped = seal_bytes(&new.key, &dek_aad("C.A", 1), unwrapped.as_slice());
+
+        assert_eq!(*unwrap_dek(&new, "C.A", 1, &rewrapped).unwrap(), dek);
+        assert!(unwrap_dek(&old, "C.A", 1, &rewrapped).is_err());
+        assert!(unwrap_dek(&new, "C.A", 2, &rewrapped).is_err());
+    }
+
+    #[test]
+    fn test_plain_atoms_are_not_sealed() {
+        assert!(!is_sealed(&json!({ "type": "job.created" })));
+        assert!(parse_envelope(&json!({ SEALED_FIELD: { "v": 9 } })).is_err());
+    }
+}
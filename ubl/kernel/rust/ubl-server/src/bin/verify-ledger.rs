@@ -48,6 +48,9 @@ async fn verify_container(pool: &PgPool, container_id: &str) -> VerificationResu
     let mut valid_entries = 0;
     let mut expected_prev_hash = "0x00".to_string();
     let mut expected_seq = 1i64;
+    // One hasher and hex buffer for the whole container, reset per entry
+    let mut h = Hasher::new();
+    let mut computed = [0u8; 64];
 
     for entry in &entries {
         let mut entry_errors = Vec::new();
@@ -71,14 +74,15 @@ async fn verify_container(pool: &PgPool, container_id: &str) -> VerificationResu
         }
 
         // Verify entry_hash computation
-        let mut h = Hasher::new();
+        h.reset();
         h.update(b"ubl:ledger\n");
         h.update(entry.container_id.as_bytes());
         h.update(&entry.sequence.to_be_bytes());
         h.update(entry.link_hash.as_bytes());
         h.update(entry.previous_hash.as_bytes());
         h.update(&entry.ts_unix_ms.to_be_bytes());
-        let computed_hash = hex::encode(h.finalize().as_bytes());
+        hex::encode_to_slice(h.finalize().as_bytes(), &mut computed).expect("64-byte buffer holds a 32-byte hash");
+        let computed_hash = std::str::from_utf8(&computed).expect("hex is ASCII");
 
         if entry.entry_hash != computed_hash {
             entry_errors.push(format!(
@@ -0,0 +1,100 @@
+//! Content-addressed blob store for receipt artifacts
+//!
+//! Blobs are stored under `UBL_BLOB_DIR` (default `~/.ubl/blobs`) as
+//! `<aa>/<blake3-hex>`, so identical artifacts from different receipts share
+//! one file. Writes go to a temp file and are renamed into place; a blob that
+//! exists is always complete.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Hash format used by receipts: `blake3:<64 hex>`
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("blake3:{}", blake3::hash(bytes).to_hex())
+}
+
+/// Parse `blake3:<hex>`; returns the hex part
+pub fn parse_hash(hash: &str) -> Option<&str> {
+    let hex = hash.strip_prefix("blake3:")?;
+    (hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit() && !b.is_ascii_uppercase())).then_some(hex)
+}
+
+pub struct BlobStore {
+    dir: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn from_env() -> Self {
+        let dir = std::env::var("UBL_BLOB_DIR").unwrap_or_else(|_| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+            format!("{}/.ubl/blobs", home)
+        });
+        Self::new(dir)
+    }
+
+    fn path(&self, hex: &str) -> PathBuf {
+        self.dir.join(&hex[..2]).join(hex)
+    }
+
+    /// Store `bytes`, returning their content hash
+    pub fn put(&self, bytes: &[u8]) -> std::io::Result<String> {
+        let hash = content_hash(bytes);
+        let hex = &hash["blake3:".len()..];
+        let path = self.path(hex);
+        if path.exists() {
+            return Ok(hash);
+        }
+        let parent = path.parent().expect("blob path has a parent");
+        fs::create_dir_all(parent)?;
+        let tmp = parent.join(format!(".{}.{}", hex, std::process::id()));
+        {
+            let mut f = fs::File::create(&tmp)?;
+            f.write_all(bytes)?;
+            f.sync_all()?;
+        }
+        fs::rename(&tmp, &path)?;
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &str) -> std::io::Result<Option<Vec<u8>>> {
+        let Some(hex) = parse_hash(hash) else {
+            return Ok(None);
+        };
+        match fs::read(self.path(hex)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_get_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("ubl-blobs-{}", std::process::id()));
+        let store = BlobStore::new(&dir);
+
+        let hash = store.put(b"artifact bytes").unwrap();
+        assert_eq!(hash, content_hash(b"artifact bytes"));
+        assert_eq!(store.get(&hash).unwrap().unwrap(), b"artifact bytes");
+        // Idempotent
+        assert_eq!(store.put(b"artifact bytes").unwrap(), hash);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_hash() {
+        assert!(parse_hash(&content_hash(b"x")).is_some());
+        assert!(parse_hash("sha256:abc").is_none());
+        assert!(parse_hash("blake3:../../etc/passwd").is_none());
+    }
+}
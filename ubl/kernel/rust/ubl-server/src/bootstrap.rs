@@ -0,0 +1,479 @@
+//! # Declarative Bootstrap
+//!
+//! A fresh deployment becomes usable from one file instead of a series of
+//! API calls. `UBL_BOOTSTRAP_FILE` names a JSON manifest that the server
+//! applies on every start, before it serves:
+//!
+//! ```json
+//! {
+//!   "policies":   [<PolicyDefinition> + optional "not_before"/"not_after" (ms)],
+//!   "pacts":      [{"pact_id": "pact_ops", "scope_type": "container", "scope_value": "C.Jobs",
+//!                   "intent_classes": ["Evolution"], "threshold": 1, "signers": ["admin"],
+//!                   "not_before": 0, "not_after": 4102444800000, "risk_level": 5}],
+//!   "admin":      {"username": "ops", "display_name": "Operations",
+//!                  "public_key": "<64 hex>", "tenant": "Acme"},
+//!   "containers": [{"id": "C.Jobs", "policy": "default_C.Jobs", "description": "Jobs"}]
+//! }
+//! ```
+//!
+//! Every item is idempotent: what already exists is skipped, never
+//! overwritten. A policy version is registered unless it is stored; a
+//! pact is inserted unless its id exists; the admin subject gets its Ed25519
+//! key and an owned tenant unless it has them; a container without entries
+//! gets a `container.genesis` observation and is bound to its policy. The
+//! signer `"admin"` in a pact stands for the admin's public key.
+//!
+//! The admin acts with its Ed25519 key (pact signatures, ASCs). Its username
+//! is taken by the subject, so people register passkeys under their own.
+//!
+//! A manifest that does not parse or validate stops the server; items that
+//! fail to apply are reported and the server starts anyway. The report
+//! (applied / skipped / failed per item) is logged.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use thiserror::Error;
+use tracing::{info, warn};
+use ubl_policy_vm::PolicyDefinition;
+
+use crate::db::{LinkDraft, GENESIS_PREVIOUS_HASH};
+use crate::policy_registry::{parse_version, ActivationWindow};
+use crate::tenant::{db as tenant_db, MemberRole};
+use crate::AppState;
+
+/// Atom type of the first entry of a bootstrapped container
+pub const GENESIS_TYPE: &str = "container.genesis";
+/// Signer alias for the admin's public key in pacts
+pub const ADMIN_SIGNER: &str = "admin";
+const ACTOR: &str = "system:bootstrap";
+
+#[derive(Debug, Error)]
+pub enum BootstrapError {
+    #[error("cannot read {path}: {reason}")]
+    Read { path: String, reason: String },
+
+    #[error("invalid manifest: {0}")]
+    Parse(String),
+
+    #[error("invalid manifest: {0}")]
+    Invalid(String),
+}
+
+// ============================================================================
+// MANIFEST
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BootstrapManifest {
+    #[serde(default)]
+    pub policies: Vec<PolicySpec>,
+    #[serde(default)]
+    pub pacts: Vec<PactSpec>,
+    #[serde(default)]
+    pub admin: Option<AdminSpec>,
+    #[serde(default)]
+    pub containers: Vec<ContainerSpec>,
+}
+
+/// A policy version with its activation window
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicySpec {
+    #[serde(flatten)]
+    pub definition: PolicyDefinition,
+    #[serde(flatten)]
+    pub window: ActivationWindow,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PactSpec {
+    pub pact_id: String,
+    pub scope_type: String,
+    #[serde(default)]
+    pub scope_value: Option<String>,
+    pub intent_classes: Vec<String>,
+    pub threshold: i16,
+    /// Hex Ed25519 public keys, or `"admin"`
+    pub signers: Vec<String>,
+    pub not_before: i64,
+    pub not_after: i64,
+    pub risk_level: i16,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdminSpec {
+    pub username: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Hex Ed25519 public key of the admin
+    pub public_key: String,
+    /// Name of a tenant the admin owns; created if the admin has none
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContainerSpec {
+    pub id: String,
+    /// Policy bound to the container
+    #[serde(default)]
+    pub policy: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn is_pubkey_hex(key: &str) -> bool {
+    key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+impl BootstrapManifest {
+    /// Read and validate `path`; returns the manifest and its BLAKE3 hash
+    pub fn load(path: &str) -> Result<(Self, String), BootstrapError> {
+        let bytes = std::fs::read(path).map_err(|e| BootstrapError::Read { path: path.into(), reason: e.to_string() })?;
+        let manifest: Self = serde_json::from_slice(&bytes).map_err(|e| BootstrapError::Parse(e.to_string()))?;
+        manifest.validate()?;
+        Ok((manifest, blake3::hash(&bytes).to_hex().to_string()))
+    }
+
+    /// Reject what the database or the registry would refuse item by item
+    pub fn validate(&self) -> Result<(), BootstrapError> {
+        let invalid = |reason: String| Err(BootstrapError::Invalid(reason));
+        let unique = |kind: &str, ids: Vec<&str>| {
+            let mut seen = HashSet::new();
+            match ids.into_iter().find(|id| !seen.insert(*id)) {
+                Some(id) => invalid(format!("{} {} is listed twice", kind, id)),
+                None => Ok(()),
+            }
+        };
+        let versions: Vec<String> =
+            self.policies.iter().map(|p| format!("{} v{}", p.definition.policy_id, p.definition.version)).collect();
+        unique("policy", versions.iter().map(String::as_str).collect())?;
+        unique("pact", self.pacts.iter().map(|p| p.pact_id.as_str()).collect())?;
+        unique("container", self.containers.iter().map(|c| c.id.as_str()).collect())?;
+
+        if let Some(admin) = &self.admin {
+            if admin.username.trim().is_empty() {
+                return invalid("admin.username is empty".into());
+            }
+            if !is_pubkey_hex(&admin.public_key) {
+                return invalid("admin.public_key must be a 64-hex Ed25519 public key".into());
+            }
+        }
+
+        for policy in &self.policies {
+            let (id, version) = (&policy.definition.policy_id, &policy.definition.version);
+            if parse_version(version).is_none() {
+                return invalid(format!("policy {}: version {} is not MAJOR.MINOR[.PATCH]", id, version));
+            }
+            if let Err(e) = policy.window.validate() {
+                return invalid(format!("policy {} v{}: {}", id, version, e));
+            }
+        }
+
+        for pact in &self.pacts {
+            let id = &pact.pact_id;
+            match (pact.scope_type.as_str(), &pact.scope_value) {
+                ("global", None) | ("container" | "namespace", Some(_)) => {}
+                (scope, _) => return invalid(format!("pact {}: scope {} with scope_value {:?}", id, scope, pact.scope_value)),
+            }
+            if pact.intent_classes.is_empty() {
+                return invalid(format!("pact {}: no intent_classes", id));
+            }
+            if pact.threshold < 1 || pact.threshold as usize > pact.signers.len() {
+                return invalid(format!("pact {}: threshold {} with {} signers", id, pact.threshold, pact.signers.len()));
+            }
+            if !(0..=5).contains(&pact.risk_level) {
+                return invalid(format!("pact {}: risk_level must be 0-5", id));
+            }
+            if pact.not_after <= pact.not_before {
+                return invalid(format!("pact {}: not_after must be after not_before", id));
+            }
+            for signer in &pact.signers {
+                if signer == ADMIN_SIGNER && self.admin.is_none() {
+                    return invalid(format!("pact {}: signer \"admin\" without an admin section", id));
+                }
+                if signer != ADMIN_SIGNER && !is_pubkey_hex(signer) {
+                    return invalid(format!("pact {}: signer {} is not a 64-hex public key", id, signer));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Pact signers with `"admin"` replaced by the admin's public key
+    pub fn resolve_signers(&self, pact: &PactSpec) -> Vec<String> {
+        pact.signers
+            .iter()
+            .map(|signer| match (&self.admin, signer.as_str()) {
+                (Some(admin), ADMIN_SIGNER) => admin.public_key.to_lowercase(),
+                _ => signer.to_lowercase(),
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// REPORT
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Applied,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportItem {
+    pub kind: &'static str,
+    pub id: String,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BootstrapReport {
+    pub manifest_hash: String,
+    pub items: Vec<ReportItem>,
+}
+
+impl BootstrapReport {
+    fn record(&mut self, kind: &'static str, id: &str, result: Result<(Outcome, String), String>) {
+        let (outcome, detail) = result.unwrap_or_else(|e| (Outcome::Failed, e));
+        match outcome {
+            Outcome::Failed => warn!("🌱 Bootstrap {} {}: failed: {}", kind, id, detail),
+            _ => info!("🌱 Bootstrap {} {}: {:?} ({})", kind, id, outcome, detail),
+        }
+        self.items.push(ReportItem { kind, id: id.to_string(), outcome, detail });
+    }
+
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.items.iter().filter(|item| item.outcome == outcome).count()
+    }
+}
+
+fn applied(detail: impl Into<String>) -> Result<(Outcome, String), String> {
+    Ok((Outcome::Applied, detail.into()))
+}
+
+fn skipped(detail: impl Into<String>) -> Result<(Outcome, String), String> {
+    Ok((Outcome::Skipped, detail.into()))
+}
+
+// ============================================================================
+// APPLY
+// ============================================================================
+
+/// Apply `manifest`: policies, pacts, admin, then containers
+pub async fn apply(state: &AppState, manifest: &BootstrapManifest, manifest_hash: &str) -> BootstrapReport {
+    let mut report = BootstrapReport { manifest_hash: manifest_hash.to_string(), items: Vec::new() };
+
+    for policy in &manifest.policies {
+        report.record("policy", &policy.definition.policy_id, apply_policy(state, policy).await);
+    }
+    for pact in &manifest.pacts {
+        report.record("pact", &pact.pact_id, apply_pact(state, pact, manifest.resolve_signers(pact)).await);
+    }
+    if let Some(admin) = &manifest.admin {
+        let sid = crate::id_db::compute_person_sid(&admin.username);
+        report.record("admin", &sid, apply_admin_key(state, &sid, admin).await);
+        if let Some(tenant) = &admin.tenant {
+            report.record("tenant", tenant, apply_admin_tenant(state, &sid, tenant).await);
+        }
+    }
+    for container in &manifest.containers {
+        report.record("genesis", &container.id, apply_genesis(state, container, manifest_hash).await);
+        if let Some(policy_id) = &container.policy {
+            report.record("binding", &container.id, apply_binding(state, &container.id, policy_id).await);
+        }
+    }
+
+    info!(
+        "🌱 Bootstrap {}: {} applied, {} skipped, {} failed",
+        &manifest_hash[..12.min(manifest_hash.len())],
+        report.count(Outcome::Applied),
+        report.count(Outcome::Skipped),
+        report.count(Outcome::Failed)
+    );
+    report
+}
+
+/// Stored versions are loaded into the registry before bootstrap runs
+async fn apply_policy(state: &AppState, policy: &PolicySpec) -> Result<(Outcome, String), String> {
+    let definition = &policy.definition;
+    if state.policy_registry.has_version(&definition.policy_id, &definition.version) {
+        return skipped(format!("v{} already registered", definition.version));
+    }
+    state
+        .policy_registry
+        .register_policy_version(definition.clone(), policy.window)
+        .await
+        .map_err(|e| e.to_string())?;
+    applied(format!("registered v{}", definition.version))
+}
+
+async fn apply_pact(state: &AppState, pact: &PactSpec, signers: Vec<String>) -> Result<(Outcome, String), String> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO pact (pact_id, scope_type, scope_value, intent_classes, threshold, signers,
+                          not_before, not_after, risk_level, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (pact_id) DO NOTHING
+        "#,
+    )
+    .bind(&pact.pact_id)
+    .bind(&pact.scope_type)
+    .bind(&pact.scope_value)
+    .bind(&pact.intent_classes)
+    .bind(pact.threshold)
+    .bind(&signers)
+    .bind(pact.not_before)
+    .bind(pact.not_after)
+    .bind(pact.risk_level)
+    .bind(ACTOR)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    if inserted.rows_affected() == 0 {
+        return skipped("exists");
+    }
+    applied(format!("{}-of-{}", pact.threshold, signers.len()))
+}
+
+async fn apply_admin_key(state: &AppState, sid: &str, admin: &AdminSpec) -> Result<(Outcome, String), String> {
+    let display_name = admin.display_name.as_deref().unwrap_or(&admin.username);
+    crate::id_db::create_person(&state.pool, &admin.username, display_name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let public_key = hex::decode(&admin.public_key).map_err(|e| e.to_string())?;
+    match crate::id_db::get_credential(&state.pool, sid, "ed25519").await.map_err(|e| e.to_string())? {
+        Some(cred) if cred.public_key == public_key => skipped("key registered"),
+        Some(cred) => skipped(format!("has another key (v{}); rotate it through the API", cred.key_version)),
+        None => {
+            crate::id_db::create_credential(&state.pool, sid, "ed25519", ACTOR, &public_key, 0)
+                .await
+                .map_err(|e| e.to_string())?;
+            applied("key registered")
+        }
+    }
+}
+
+async fn apply_admin_tenant(state: &AppState, sid: &str, name: &str) -> Result<(Outcome, String), String> {
+    if let Some(tenant_id) = tenant_db::get_user_tenant(&state.pool, sid).await.map_err(|e| e.to_string())? {
+        return skipped(format!("admin belongs to {}", tenant_id));
+    }
+    let tenant = tenant_db::create_tenant(&state.pool, name, sid).await.map_err(|e| e.to_string())?;
+    tenant_db::add_member(&state.pool, &tenant.tenant_id, sid, MemberRole::Owner)
+        .await
+        .map_err(|e| e.to_string())?;
+    applied(format!("created {}", tenant.tenant_id))
+}
+
+async fn apply_genesis(state: &AppState, container: &ContainerSpec, manifest_hash: &str) -> Result<(Outcome, String), String> {
+    match state.ledger.get_state(&container.id).await {
+        Ok(head) => return skipped(format!("at sequence {}", head.sequence)),
+        Err(sqlx::Error::RowNotFound) => {}
+        Err(e) => return Err(e.to_string()),
+    }
+
+    let atom = json!({
+        "container_id": container.id,
+        "description": container.description.as_deref().unwrap_or(""),
+        "manifest_hash": manifest_hash,
+        "policy_id": container.policy,
+        "type": GENESIS_TYPE,
+    });
+    let atom_bytes = ubl_atom::canonicalize(&atom).map_err(|e| format!("CanonicalizeError: {}", e))?;
+    let mut link = LinkDraft {
+        version: 1,
+        container_id: container.id.clone(),
+        expected_sequence: 1,
+        previous_hash: GENESIS_PREVIOUS_HASH.to_string(),
+        atom_hash: blake3::hash(&atom_bytes).to_hex().to_string(),
+        atom: Some(atom),
+        intent_class: "Observation".to_string(),
+        physics_delta: "0".to_string(),
+        author_pubkey: String::new(), // Set by sign_link_draft
+        signature: String::new(),     // Set by sign_link_draft
+        pact: None,
+        tentative_id: None,
+    };
+    crate::messenger_v1::sign_link_draft(&mut link);
+
+    let success = crate::commit_link(state, link, ACTOR).await.map_err(|e| e.message)?;
+    applied(format!("genesis {}", success.entry.entry_hash))
+}
+
+async fn apply_binding(state: &AppState, container_id: &str, policy_id: &str) -> Result<(Outcome, String), String> {
+    if state.policy_registry.get_policy_for_container(container_id).await.as_deref() == Some(policy_id) {
+        return skipped(format!("bound to {}", policy_id));
+    }
+    state
+        .policy_registry
+        .set_container_policy(container_id, policy_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    applied(format!("bound to {}", policy_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADMIN_KEY: &str = "a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1";
+
+    fn manifest(value: serde_json::Value) -> Result<BootstrapManifest, BootstrapError> {
+        let manifest: BootstrapManifest = serde_json::from_value(value).map_err(|e| BootstrapError::Parse(e.to_string()))?;
+        manifest.validate().map(|_| manifest)
+    }
+
+    fn pact(signers: serde_json::Value) -> serde_json::Value {
+        json!({
+            "pact_id": "pact_ops", "scope_type": "container", "scope_value": "C.Jobs",
+            "intent_classes": ["Evolution"], "threshold": 1, "signers": signers,
+            "not_before": 0, "not_after": 1, "risk_level": 5
+        })
+    }
+
+    #[test]
+    fn test_admin_signer_resolves_to_admin_key() {
+        let m = manifest(json!({
+            "admin": { "username": "ops", "public_key": ADMIN_KEY.to_uppercase() },
+            "pacts": [pact(json!(["admin"]))],
+            "containers": [{ "id": "C.Jobs", "policy": "default_C.Jobs" }],
+        }))
+        .unwrap();
+        assert_eq!(m.resolve_signers(&m.pacts[0]), [ADMIN_KEY]);
+    }
+
+    #[test]
+    fn test_invalid_manifests_are_refused() {
+        // "admin" needs an admin section
+        assert!(manifest(json!({ "pacts": [pact(json!(["admin"]))] })).is_err());
+        // Threshold above the signer count
+        let mut two_of_one = pact(json!([ADMIN_KEY]));
+        two_of_one["threshold"] = json!(2);
+        assert!(manifest(json!({ "pacts": [two_of_one] })).is_err());
+        assert!(manifest(json!({ "containers": [{ "id": "C.Jobs" }, { "id": "C.Jobs" }] })).is_err());
+        // Versions side by side, but each once and with a window that opens before it closes
+        let policy = |version: &str, window: serde_json::Value| {
+            let mut p = json!({ "policy_id": "p", "version": version, "description": "", "rules": [], "default_deny": true });
+            p.as_object_mut().unwrap().extend(window.as_object().unwrap().clone());
+            p
+        };
+        let m = manifest(json!({ "policies": [policy("1.0", json!({})), policy("1.1", json!({ "not_before": 5 }))] })).unwrap();
+        assert_eq!(m.policies[1].window.not_before, Some(5));
+        assert!(manifest(json!({ "policies": [policy("1.0", json!({})), policy("1.0", json!({}))] })).is_err());
+        assert!(manifest(json!({ "policies": [policy("1.x", json!({}))] })).is_err());
+        assert!(manifest(json!({ "policies": [policy("1.0", json!({ "not_before": 5, "not_after": 5 }))] })).is_err());
+        // Typos fail instead of being ignored
+        assert!(matches!(manifest(json!({ "container": [] })), Err(BootstrapError::Parse(_))));
+        assert!(manifest(json!({})).is_ok());
+    }
+}
@@ -0,0 +1,255 @@
+//! # Startup Chain Self-Check
+//!
+//! A corrupted database could otherwise serve bad heads silently. Before the
+//! server accepts traffic it re-verifies the last `UBL_CHAIN_CHECK_DEPTH`
+//! entries of every container appended to in the last 30 days:
+//!
+//! - each `entry_hash` is recomputed (SPEC-UBL-LEDGER v1.0 §5.1)
+//! - each entry's `previous_hash` is its predecessor's `entry_hash`, and
+//!   sequences are contiguous (a first entry links to `0x00`)
+//!
+//! A container that fails is quarantined: `PgLedger::append` refuses writes to
+//! it until the chain is repaired and the server restarted. Results are
+//! reported by `/health?verbose=1` and the `ubl_chain_verified{container}`
+//! gauge (1 verified, 0 quarantined).
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use prometheus::{register_int_gauge_vec, IntGaugeVec};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use tracing::{error, info};
+
+use crate::db::{compute_entry_hash, GENESIS_PREVIOUS_HASH};
+use crate::timestamps::now_ms;
+
+/// Containers with an append inside this window are checked
+const ACTIVE_WINDOW_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+lazy_static! {
+    pub static ref CHAIN_VERIFIED: IntGaugeVec = register_int_gauge_vec!(
+        "ubl_chain_verified",
+        "Startup chain self-check by container (1 = verified, 0 = quarantined)",
+        &["container"]
+    ).unwrap();
+
+    static ref RESULTS: RwLock<BTreeMap<String, ContainerCheck>> = RwLock::new(BTreeMap::new());
+}
+
+/// A stored ledger entry, as read back for verification
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StoredEntry {
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+}
+
+/// What is wrong with a chain segment
+#[derive(Debug, Error, PartialEq)]
+pub enum ChainFault {
+    #[error("entry {sequence}: stored entry_hash does not match its contents")]
+    HashMismatch { sequence: i64 },
+
+    #[error("entry {sequence}: previous_hash does not match entry {}", sequence - 1)]
+    BrokenLink { sequence: i64 },
+
+    #[error("entry {found} follows entry {after}")]
+    SequenceGap { after: i64, found: i64 },
+}
+
+/// Outcome of verifying one container
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerCheck {
+    pub container_id: String,
+    pub checked: usize,
+    pub head_sequence: i64,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Verify an ascending run of entries of `container_id`; returns how many
+/// were checked
+pub fn verify_entries(container_id: &str, entries: &[StoredEntry]) -> Result<usize, ChainFault> {
+    for (i, entry) in entries.iter().enumerate() {
+        let recomputed = compute_entry_hash(
+            container_id,
+            entry.sequence,
+            &entry.link_hash,
+            &entry.previous_hash,
+            entry.ts_unix_ms,
+        );
+        if recomputed != entry.entry_hash {
+            return Err(ChainFault::HashMismatch { sequence: entry.sequence });
+        }
+
+        match i.checked_sub(1).map(|prev| &entries[prev]) {
+            Some(prev) if entry.sequence != prev.sequence + 1 => {
+                return Err(ChainFault::SequenceGap { after: prev.sequence, found: entry.sequence });
+            }
+            Some(prev) if entry.previous_hash != prev.entry_hash => {
+                return Err(ChainFault::BrokenLink { sequence: entry.sequence });
+            }
+            None if entry.sequence == 1 && entry.previous_hash != GENESIS_PREVIOUS_HASH => {
+                return Err(ChainFault::BrokenLink { sequence: entry.sequence });
+            }
+            _ => {}
+        }
+    }
+    Ok(entries.len())
+}
+
+/// Whether writes to `container_id` are refused
+pub fn is_quarantined(container_id: &str) -> bool {
+    RESULTS
+        .read()
+        .map(|results| results.get(container_id).is_some_and(|check| !check.ok))
+        .unwrap_or(false)
+}
+
+/// Results of the last self-check, by container
+pub fn results() -> Vec<ContainerCheck> {
+    RESULTS.read().map(|results| results.values().cloned().collect()).unwrap_or_default()
+}
+
+fn record(check: ContainerCheck) {
+    CHAIN_VERIFIED.with_label_values(&[&check.container_id]).set(check.ok as i64);
+    if let Ok(mut results) = RESULTS.write() {
+        results.insert(check.container_id.clone(), check);
+    }
+}
+
+/// Verify the last `depth` entries of every active container. Call once at
+/// startup, before serving. Returns the number of quarantined containers.
+pub async fn run(pool: &PgPool, depth: usize) -> Result<usize, sqlx::Error> {
+    if depth == 0 {
+        info!("⏭️  Chain self-check disabled (UBL_CHAIN_CHECK_DEPTH=0)");
+        return Ok(0);
+    }
+
+    let containers: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT container_id
+        FROM ledger_entry
+        GROUP BY container_id
+        HAVING MAX(ts_unix_ms) >= $1
+        ORDER BY container_id
+        "#,
+    )
+    .bind(now_ms() - ACTIVE_WINDOW_MS)
+    .fetch_all(pool)
+    .await?;
+
+    let mut quarantined = 0;
+    for container_id in containers {
+        let mut entries: Vec<StoredEntry> = sqlx::query_as(
+            r#"
+            SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms
+            FROM ledger_entry
+            WHERE container_id = $1
+            ORDER BY sequence DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(&container_id)
+        .bind(depth as i64)
+        .fetch_all(pool)
+        .await?;
+        entries.reverse();
+
+        let head_sequence = entries.last().map(|e| e.sequence).unwrap_or(0);
+        let check = match verify_entries(&container_id, &entries) {
+            Ok(checked) => ContainerCheck { container_id, checked, head_sequence, ok: true, error: None },
+            Err(fault) => {
+                error!("🚨 Chain self-check failed for {}: {} — writes refused", container_id, fault);
+                quarantined += 1;
+                ContainerCheck {
+                    container_id,
+                    checked: entries.len(),
+                    head_sequence,
+                    ok: false,
+                    error: Some(fault.to_string()),
+                }
+            }
+        };
+        record(check);
+    }
+
+    info!(
+        "🔗 Chain self-check: {} container(s), last {} entries each, {} quarantined",
+        results().len(),
+        depth,
+        quarantined
+    );
+    Ok(quarantined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(container_id: &str, len: i64) -> Vec<StoredEntry> {
+        let mut previous_hash = GENESIS_PREVIOUS_HASH.to_string();
+        (1..=len)
+            .map(|sequence| {
+                let link_hash = format!("atom{}", sequence);
+                let ts_unix_ms = 1_000 * sequence;
+                let entry_hash = compute_entry_hash(container_id, sequence, &link_hash, &previous_hash, ts_unix_ms);
+                StoredEntry {
+                    sequence,
+                    link_hash,
+                    previous_hash: std::mem::replace(&mut previous_hash, entry_hash.clone()),
+                    entry_hash,
+                    ts_unix_ms,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_intact_chain_verifies() {
+        let entries = chain("C.Test", 5);
+        assert_eq!(verify_entries("C.Test", &entries), Ok(5));
+        // A tail window does not need the genesis entry
+        assert_eq!(verify_entries("C.Test", &entries[2..]), Ok(3));
+        // Hashes bind the container
+        assert_eq!(verify_entries("C.Other", &entries), Err(ChainFault::HashMismatch { sequence: 1 }));
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let mut edited = chain("C.Test", 4);
+        edited[2].link_hash = "forged".into();
+        assert_eq!(verify_entries("C.Test", &edited), Err(ChainFault::HashMismatch { sequence: 3 }));
+
+        // Rewritten consistently, but no longer linked to its predecessor
+        let mut relinked = chain("C.Test", 4);
+        relinked[2].previous_hash = "beef".into();
+        relinked[2].entry_hash =
+            compute_entry_hash("C.Test", 3, &relinked[2].link_hash, "beef", relinked[2].ts_unix_ms);
+        assert_eq!(verify_entries("C.Test", &relinked), Err(ChainFault::BrokenLink { sequence: 3 }));
+
+        let mut gapped = chain("C.Test", 4);
+        gapped.remove(1);
+        assert_eq!(verify_entries("C.Test", &gapped), Err(ChainFault::SequenceGap { after: 1, found: 3 }));
+    }
+
+    #[test]
+    fn test_failed_container_is_quarantined() {
+        record(ContainerCheck {
+            container_id: "C.Quarantine".into(),
+            checked: 3,
+            head_sequence: 3,
+            ok: false,
+            error: Some("entry 3: broken".into()),
+        });
+        assert!(is_quarantined("C.Quarantine"));
+        assert!(!is_quarantined("C.Unchecked"));
+        assert_eq!(CHAIN_VERIFIED.with_label_values(&["C.Quarantine"]).get(), 0);
+    }
+}
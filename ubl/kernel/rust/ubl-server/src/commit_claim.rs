@@ -0,0 +1,156 @@
+//! Commit Claims — one replica at a time per signed commit
+//!
+//! Clients retry a commit against another replica when the first one is slow,
+//! so the same signed link can reach two replicas at once. Both would run the
+//! whole admission (signature, policy VM, pacts) only for one of them to lose
+//! the append with a `SequenceMismatch` — and the loser's client sees a
+//! refusal for a commit that actually landed.
+//!
+//! Before admission, `commit_link` takes a Postgres advisory lock keyed by
+//! the link hash (BLAKE3 of the link signing bytes). It is transaction-scoped,
+//! so it goes away on release, on drop, and with the connection of a replica
+//! that dies mid-commit. Once it holds the claim, a replica first checks
+//! whether the link already landed and, if so, answers with that entry
+//! instead of admitting it again (a retry of a landed commit gets the same
+//! answer). Waiting is capped by `UBL_COMMIT_CLAIM_WAIT_MS`
+//! (default 2000); past it the commit is refused as a retryable
+//! `SerializationConflict` (`CommitInFlight`).
+
+use std::sync::OnceLock;
+
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use tracing::{info, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::db::{LedgerEntry, LinkDraft};
+use crate::hlc::Hlc;
+use crate::metrics::COMMIT_CLAIMS;
+
+/// How long a replica waits for another one's claim, by default
+const DEFAULT_WAIT_MS: u64 = 2000;
+
+/// Claim wait, from `UBL_COMMIT_CLAIM_WAIT_MS` once
+fn wait_ms() -> u64 {
+    static WAIT: OnceLock<u64> = OnceLock::new();
+    *WAIT.get_or_init(|| {
+        std::env::var("UBL_COMMIT_CLAIM_WAIT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_WAIT_MS)
+            .max(1)
+    })
+}
+
+/// Advisory lock key of a link hash: its first 8 bytes, big-endian
+pub fn lock_key(link_hash: &str) -> i64 {
+    let bytes = hex::decode(link_hash).unwrap_or_default();
+    let mut key = [0u8; 8];
+    for (dst, src) in key.iter_mut().zip(bytes.iter()) {
+        *dst = *src;
+    }
+    i64::from_be_bytes(key)
+}
+
+/// Outcome of claiming a link
+pub enum Claim {
+    /// This replica holds the claim; admit and append, then release
+    Held(CommitClaim),
+    /// The link landed while we waited; this is its entry
+    Committed(LedgerEntry),
+}
+
+/// A held claim; released explicitly or when dropped
+pub struct CommitClaim {
+    tx: Box<Transaction<'static, Postgres>>,
+}
+
+impl CommitClaim {
+    pub async fn release(self) {
+        if let Err(e) = self.tx.rollback().await {
+            // The connection drops the lock with the transaction anyway
+            warn!("Failed to release commit claim: {}", e);
+        }
+    }
+}
+
+/// Take the claim on `link` (whose signing bytes hash to `link_hash`)
+pub async fn claim(pool: &PgPool, link: &LinkDraft, link_hash: &str) -> Result<Claim, ApiError> {
+    let db_error = |e: sqlx::Error| ApiError::new(ErrorCode::DatabaseError, e.to_string());
+
+    let mut tx = pool.begin().await.map_err(db_error)?;
+    // SET does not take bind parameters; the value is a number we parsed
+    sqlx::query(&format!("SET LOCAL lock_timeout = '{}ms'", wait_ms()))
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+
+    if let Err(e) = sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(lock_key(link_hash))
+        .execute(&mut *tx)
+        .await
+    {
+        // 55P03 = lock_not_available (lock_timeout)
+        let timed_out = matches!(&e, sqlx::Error::Database(db) if db.code().as_deref() == Some("55P03"));
+        if !timed_out {
+            return Err(db_error(e));
+        }
+        COMMIT_CLAIMS.with_label_values(&["timeout"]).inc();
+        warn!("⏳ Commit {} still in flight on another replica after {}ms", &link_hash[..16.min(link_hash.len())], wait_ms());
+        return Err(ApiError::new(ErrorCode::SerializationConflict, "CommitInFlight"));
+    }
+
+    // Whoever held the claim before us may have appended this very link
+    if let Some(entry) = landed(&mut tx, link).await.map_err(db_error)? {
+        COMMIT_CLAIMS.with_label_values(&["duplicate"]).inc();
+        info!("♻️ Commit {}#{} already landed; answering with its entry", entry.container_id, entry.sequence);
+        let _ = tx.rollback().await;
+        return Ok(Claim::Committed(entry));
+    }
+
+    COMMIT_CLAIMS.with_label_values(&["acquired"]).inc();
+    Ok(Claim::Held(CommitClaim { tx: Box::new(tx) }))
+}
+
+/// The entry `link` produced, if it is in the ledger
+async fn landed(tx: &mut Transaction<'static, Postgres>, link: &LinkDraft) -> Result<Option<LedgerEntry>, sqlx::Error> {
+    let row: Option<PgRow> = sqlx::query(
+        r#"
+        SELECT container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms, hlc_wall_ms, hlc_logical
+        FROM ledger_entry
+        WHERE container_id = $1 AND sequence = $2 AND previous_hash = $3 AND link_hash = $4
+        "#,
+    )
+    .bind(&link.container_id)
+    .bind(link.expected_sequence)
+    .bind(&link.previous_hash)
+    .bind(&link.atom_hash)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    Ok(row.map(|r| LedgerEntry {
+        container_id: r.get("container_id"),
+        sequence: r.get("sequence"),
+        link_hash: r.get("link_hash"),
+        previous_hash: r.get("previous_hash"),
+        entry_hash: r.get("entry_hash"),
+        ts_unix_ms: r.get("ts_unix_ms"),
+        hlc: Hlc::stored(r.get("hlc_wall_ms"), r.get("hlc_logical"), r.get("ts_unix_ms")),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_key_is_stable_and_distinct() {
+        let a = ubl_kernel::hash_link(b"link a");
+        let b = ubl_kernel::hash_link(b"link b");
+        assert_eq!(lock_key(&a), lock_key(&a));
+        assert_ne!(lock_key(&a), lock_key(&b));
+        assert_eq!(lock_key("0102030405060708ff"), 0x0102030405060708);
+        assert_eq!(lock_key("not hex"), 0);
+    }
+}
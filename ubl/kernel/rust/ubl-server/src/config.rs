@@ -0,0 +1,504 @@
+//! # Server Configuration
+//!
+//! Startup configuration for the UBL server, read once from the environment.
+//!
+//! Validation is strict: a value that is present but malformed is a typed
+//! [`ConfigError`], never a silent fallback to a default. Unknown variables in
+//! namespaces owned by the server (`WEBAUTHN_*`, `SESSION_*`, `RATE_LIMIT_*`)
+//! are rejected so that typos like `WEBAUTHN_RP_ORIGIN` fail at boot.
+//!
+//! `ubl-server --check-config` validates and prints the redacted effective
+//! configuration without binding a listener.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+use url::Url;
+
+/// Default DSN used in development when DATABASE_URL is unset
+const DEV_DATABASE_URL: &str = "postgres://ubl_dev@localhost:5432/ubl_dev";
+
+/// Namespaces owned by this server. Any variable with one of these prefixes
+/// must be listed in [`KNOWN_KEYS`].
+const OWNED_PREFIXES: &[&str] = &["WEBAUTHN_", "SESSION_", "RATE_LIMIT_"];
+
+/// Every variable the server understands inside its owned namespaces
+const KNOWN_KEYS: &[&str] = &[
+    "WEBAUTHN_ORIGIN",
+    "WEBAUTHN_RP_ID",
+    "WEBAUTHN_RP_NAME",
+    "WEBAUTHN_CHALLENGE_TTL",
+    "WEBAUTHN_STEPUP_TTL",
+    "SESSION_TTL",
+    "SESSION_STEPUP_TTL",
+    "SESSION_COOKIE_NAME",
+    "SESSION_COOKIE_SECURE",
+    "SESSION_COOKIE_SAMESITE",
+    "RATE_LIMIT_MAX_FAILURES",
+    "RATE_LIMIT_LOCKOUT_SECS",
+];
+
+/// Variables that must parse as positive integers when present
+const NUMERIC_KEYS: &[&str] = &[
+    "WEBAUTHN_CHALLENGE_TTL",
+    "WEBAUTHN_STEPUP_TTL",
+    "SESSION_TTL",
+    "SESSION_STEPUP_TTL",
+    "RATE_LIMIT_MAX_FAILURES",
+    "RATE_LIMIT_LOCKOUT_SECS",
+    "UBL_MAX_BODY_BYTES",
+    "UBL_MAX_LINK_BODY_BYTES",
+    "UBL_SYNC_MAX_BYTES",
+    "UBL_RUNNER_TTL_SECS",
+    "UBL_MAX_ARTIFACT_BYTES",
+    "UBL_ARTIFACT_UPLOAD_TTL_SECS",
+    "UBL_MAX_LOG_SEGMENT_BYTES",
+    "UBL_JOB_MONITOR_INTERVAL_SECS",
+    "UBL_JOB_TIMEOUT_SECS",
+    "UBL_SSE_HEARTBEAT_SECS",
+    "UBL_SSE_IDLE_SECS",
+    "UBL_SSE_MAX_PER_TENANT",
+];
+
+/// Default request body limit for JSON routes (1 MiB)
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Default request body limit for `/link/validate` and `/link/commit` (256 KiB)
+const DEFAULT_MAX_LINK_BODY_BYTES: usize = 256 * 1024;
+
+/// Entries per active container verified at startup
+const DEFAULT_CHAIN_CHECK_DEPTH: usize = 100;
+
+/// Share of the gas budget above which a policy evaluation raises an alert
+const DEFAULT_POLICY_GAS_ALERT: f64 = 0.8;
+
+/// Configuration errors detected at startup
+#[derive(Debug, Error, PartialEq)]
+pub enum ConfigError {
+    #[error("{key} is required when UBL_ENV=production")]
+    Missing { key: &'static str },
+
+    #[error("{key} is invalid: {reason}")]
+    Invalid { key: String, reason: String },
+
+    #[error("unknown variable {key} (not a recognized server setting)")]
+    UnknownKey { key: String },
+}
+
+/// Deployment environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+/// Client certificate and CA presented on calls to Office (mTLS)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServiceTls {
+    /// PEM client certificate (`UBL_SERVICE_TLS_CERT`), set with `client_key`
+    pub client_cert: Option<String>,
+    /// PEM private key for `client_cert` (`UBL_SERVICE_TLS_KEY`)
+    pub client_key: Option<String>,
+    /// PEM CA that Office's certificate must chain to (`UBL_SERVICE_TLS_CA`)
+    pub ca_cert: Option<String>,
+}
+
+/// Where the HTTP API listens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Listen {
+    Tcp { port: u16 },
+    Unix { path: String },
+}
+
+/// Effective server configuration
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub environment: Environment,
+    pub database_url: String,
+    pub listen: Listen,
+    pub webauthn_rp_id: String,
+    pub webauthn_origin: Url,
+    pub office_url: Url,
+    pub otlp_endpoint: Option<String>,
+    /// Secret backend name (see `secrets.rs`); values are never held here
+    pub secrets_backend: String,
+    /// Request body limit applied to every route (`UBL_MAX_BODY_BYTES`)
+    pub max_body_bytes: usize,
+    /// Tighter limit for link submission (`UBL_MAX_LINK_BODY_BYTES`)
+    pub max_link_body_bytes: usize,
+    /// Office service keys allowed to call internal routes (`UBL_OFFICE_PUBKEYS`)
+    pub office_pubkeys: Vec<String>,
+    /// Reject unsigned internal traffic (`UBL_REQUIRE_SERVICE_AUTH`)
+    pub require_service_auth: bool,
+    pub service_tls: ServiceTls,
+    /// Last entries of each active container verified at startup
+    /// (`UBL_CHAIN_CHECK_DEPTH`, 0 disables)
+    pub chain_check_depth: usize,
+    /// Fraction of the policy gas budget that triggers a budget alert
+    /// (`UBL_POLICY_GAS_ALERT`, 0 < f <= 1)
+    pub policy_gas_alert: f64,
+    /// Declarative bootstrap manifest applied at start (`UBL_BOOTSTRAP_FILE`)
+    pub bootstrap_file: Option<String>,
+}
+
+impl ServerConfig {
+    /// Load and validate configuration from the process environment
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(&std::env::vars().collect())
+    }
+
+    /// Load and validate configuration from an explicit variable map
+    pub fn from_vars(vars: &BTreeMap<String, String>) -> Result<Self, ConfigError> {
+        let get = |key: &str| vars.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+        for key in vars.keys() {
+            if OWNED_PREFIXES.iter().any(|p| key.starts_with(p)) && !KNOWN_KEYS.contains(&key.as_str()) {
+                return Err(ConfigError::UnknownKey { key: key.clone() });
+            }
+        }
+
+        for &key in NUMERIC_KEYS {
+            if let Some(raw) = get(key) {
+                match raw.parse::<u64>() {
+                    Ok(n) if n > 0 => {}
+                    _ => return Err(invalid(key, format!("expected a positive integer, got {:?}", raw))),
+                }
+            }
+        }
+
+        if let Some(raw) = get("SESSION_COOKIE_SECURE") {
+            if !matches!(raw, "true" | "false" | "1" | "0") {
+                return Err(invalid("SESSION_COOKIE_SECURE", format!("expected true/false, got {:?}", raw)));
+            }
+        }
+        if let Some(raw) = get("SESSION_COOKIE_SAMESITE") {
+            if !matches!(raw, "Strict" | "Lax" | "None") {
+                return Err(invalid("SESSION_COOKIE_SAMESITE", format!("expected Strict, Lax or None, got {:?}", raw)));
+            }
+        }
+
+        let environment = match get("UBL_ENV") {
+            None | Some("development") | Some("dev") => Environment::Development,
+            Some("production") | Some("prod") => Environment::Production,
+            Some(other) => return Err(invalid("UBL_ENV", format!("expected development or production, got {:?}", other))),
+        };
+        let production = environment == Environment::Production;
+
+        // Database
+        let database_url = match get("DATABASE_URL") {
+            Some(url) => url.to_string(),
+            None if production => return Err(ConfigError::Missing { key: "DATABASE_URL" }),
+            None => DEV_DATABASE_URL.to_string(),
+        };
+        let parsed_db = Url::parse(&database_url)
+            .map_err(|e| invalid("DATABASE_URL", e.to_string()))?;
+        if !matches!(parsed_db.scheme(), "postgres" | "postgresql") {
+            return Err(invalid("DATABASE_URL", format!("unsupported scheme {:?}", parsed_db.scheme())));
+        }
+
+        // Listener: a Unix socket takes precedence over TCP
+        let listen = match get("UBL_LISTEN_UNIX") {
+            Some(path) => {
+                if !path.starts_with('/') {
+                    return Err(invalid("UBL_LISTEN_UNIX", "socket path must be absolute".into()));
+                }
+                Listen::Unix { path: path.to_string() }
+            }
+            None => {
+                let raw = get("PORT").unwrap_or("8080");
+                match raw.parse::<u16>() {
+                    Ok(port) if port > 0 => Listen::Tcp { port },
+                    _ => return Err(invalid("PORT", format!("expected 1-65535, got {:?}", raw))),
+                }
+            }
+        };
+
+        // WebAuthn: the RP ID must be the origin host or a registrable suffix of it
+        let webauthn_rp_id = get("WEBAUTHN_RP_ID").unwrap_or("localhost").to_string();
+        let webauthn_origin = parse_http_url("WEBAUTHN_ORIGIN", get("WEBAUTHN_ORIGIN").unwrap_or("http://localhost:8080"))?;
+        let host = webauthn_origin.host_str().unwrap_or_default();
+        if host != webauthn_rp_id && !host.ends_with(&format!(".{}", webauthn_rp_id)) {
+            return Err(invalid(
+                "WEBAUTHN_RP_ID",
+                format!("{:?} does not match origin host {:?}", webauthn_rp_id, host),
+            ));
+        }
+        if production && webauthn_origin.scheme() != "https" {
+            return Err(invalid("WEBAUTHN_ORIGIN", "must use https when UBL_ENV=production".into()));
+        }
+
+        let office_url = parse_office_url(get("OFFICE_URL").unwrap_or("http://localhost:8081"))?;
+
+        let otlp_endpoint = get("OTLP_ENDPOINT").or_else(|| get("JAEGER_ENDPOINT")).map(String::from);
+        if let Some(ref endpoint) = otlp_endpoint {
+            parse_http_url("OTLP_ENDPOINT", endpoint)?;
+        }
+
+        let secrets_backend = get("UBL_SECRETS_BACKEND").unwrap_or("file").to_string();
+        if !matches!(secrets_backend.as_str(), "file" | "aws" | "gcp") {
+            return Err(invalid("UBL_SECRETS_BACKEND", format!("expected file, aws or gcp, got {:?}", secrets_backend)));
+        }
+
+        // Service-to-service auth with Office
+        let office_pubkeys: Vec<String> = get("UBL_OFFICE_PUBKEYS")
+            .map(|raw| raw.split(',').map(str::trim).filter(|k| !k.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        for key in &office_pubkeys {
+            if key.len() != 64 || hex::decode(key).is_err() {
+                return Err(invalid("UBL_OFFICE_PUBKEYS", format!("expected 64-hex Ed25519 public keys, got {:?}", key)));
+            }
+        }
+        let require_service_auth = match get("UBL_REQUIRE_SERVICE_AUTH") {
+            None | Some("false") | Some("0") => false,
+            Some("true") | Some("1") => true,
+            Some(other) => return Err(invalid("UBL_REQUIRE_SERVICE_AUTH", format!("expected true/false, got {:?}", other))),
+        };
+        if require_service_auth && office_pubkeys.is_empty() {
+            return Err(invalid("UBL_OFFICE_PUBKEYS", "required when UBL_REQUIRE_SERVICE_AUTH is set".into()));
+        }
+        let service_tls = ServiceTls {
+            client_cert: get("UBL_SERVICE_TLS_CERT").map(String::from),
+            client_key: get("UBL_SERVICE_TLS_KEY").map(String::from),
+            ca_cert: get("UBL_SERVICE_TLS_CA").map(String::from),
+        };
+        if service_tls.client_cert.is_some() != service_tls.client_key.is_some() {
+            return Err(invalid("UBL_SERVICE_TLS_CERT", "UBL_SERVICE_TLS_CERT and UBL_SERVICE_TLS_KEY must be set together".into()));
+        }
+
+        let byte_limit = |key: &str, default: usize| {
+            get(key).and_then(|raw| raw.parse::<usize>().ok()).unwrap_or(default)
+        };
+        let max_body_bytes = byte_limit("UBL_MAX_BODY_BYTES", DEFAULT_MAX_BODY_BYTES);
+        let max_link_body_bytes = byte_limit("UBL_MAX_LINK_BODY_BYTES", DEFAULT_MAX_LINK_BODY_BYTES);
+        let chain_check_depth = match get("UBL_CHAIN_CHECK_DEPTH") {
+            None => DEFAULT_CHAIN_CHECK_DEPTH,
+            Some(raw) => raw
+                .parse::<usize>()
+                .map_err(|_| invalid("UBL_CHAIN_CHECK_DEPTH", format!("expected a non-negative integer, got {:?}", raw)))?,
+        };
+        let policy_gas_alert = match get("UBL_POLICY_GAS_ALERT") {
+            None => DEFAULT_POLICY_GAS_ALERT,
+            Some(raw) => match raw.parse::<f64>() {
+                Ok(f) if f > 0.0 && f <= 1.0 => f,
+                _ => return Err(invalid("UBL_POLICY_GAS_ALERT", format!("expected a fraction in (0, 1], got {:?}", raw))),
+            },
+        };
+
+        let bootstrap_file = get("UBL_BOOTSTRAP_FILE").map(String::from);
+
+        Ok(Self {
+            environment,
+            database_url,
+            listen,
+            webauthn_rp_id,
+            webauthn_origin,
+            office_url,
+            otlp_endpoint,
+            secrets_backend,
+            max_body_bytes,
+            max_link_body_bytes,
+            office_pubkeys,
+            require_service_auth,
+            service_tls,
+            chain_check_depth,
+            policy_gas_alert,
+            bootstrap_file,
+        })
+    }
+
+    /// A printable view of the configuration with credentials masked
+    pub fn redacted(&self) -> RedactedConfig<'_> {
+        RedactedConfig(self)
+    }
+}
+
+/// Display adapter that never prints credentials
+pub struct RedactedConfig<'a>(&'a ServerConfig);
+
+impl fmt::Display for RedactedConfig<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = self.0;
+        let listen = match &c.listen {
+            Listen::Tcp { port } => format!("tcp://0.0.0.0:{}", port),
+            Listen::Unix { path } => format!("unix://{}", path),
+        };
+        writeln!(f, "environment     = {:?}", c.environment)?;
+        writeln!(f, "database_url    = {}", redact_url(&c.database_url))?;
+        writeln!(f, "listen          = {}", listen)?;
+        writeln!(f, "webauthn_rp_id  = {}", c.webauthn_rp_id)?;
+        writeln!(f, "webauthn_origin = {}", c.webauthn_origin)?;
+        writeln!(f, "office_url      = {}", redact_url(c.office_url.as_str()))?;
+        writeln!(f, "otlp_endpoint   = {}", c.otlp_endpoint.as_deref().map(redact_url).unwrap_or_else(|| "(disabled)".into()))?;
+        writeln!(f, "secrets_backend = {}", c.secrets_backend)?;
+        writeln!(f, "body_limits     = {} bytes (links: {} bytes)", c.max_body_bytes, c.max_link_body_bytes)?;
+        writeln!(f, "chain_check     = last {} entries per active container", c.chain_check_depth)?;
+        writeln!(f, "policy_gas      = alert above {}% of budget", c.policy_gas_alert * 100.0)?;
+        writeln!(f, "bootstrap       = {}", c.bootstrap_file.as_deref().unwrap_or("(disabled)"))?;
+        writeln!(
+            f,
+            "service_auth    = {} office key(s), {}",
+            c.office_pubkeys.len(),
+            if c.require_service_auth { "required" } else { "optional" }
+        )?;
+        write!(
+            f,
+            "service_tls     = {}",
+            match (&c.service_tls.client_cert, &c.service_tls.ca_cert) {
+                (Some(cert), Some(ca)) => format!("client cert {}, CA {}", cert, ca),
+                (Some(cert), None) => format!("client cert {}", cert),
+                (None, Some(ca)) => format!("CA {}", ca),
+                (None, None) => "(disabled)".into(),
+            }
+        )
+    }
+}
+
+/// Mask the password component of a URL, if any
+pub fn redact_url(raw: &str) -> String {
+    match Url::parse(raw) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("***"));
+            url.to_string()
+        }
+        Ok(_) => raw.to_string(),
+        Err(_) => "<unparseable>".to_string(),
+    }
+}
+
+fn parse_http_url(key: &'static str, raw: &str) -> Result<Url, ConfigError> {
+    let url = Url::parse(raw).map_err(|e| invalid(key, e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid(key, format!("expected http(s) URL, got scheme {:?}", url.scheme())));
+    }
+    Ok(url)
+}
+
+/// `OFFICE_URL` may also name a co-located Office's socket: `unix:///run/office.sock`
+fn parse_office_url(raw: &str) -> Result<Url, ConfigError> {
+    if let Some(path) = raw.strip_prefix("unix://") {
+        if !path.starts_with('/') {
+            return Err(invalid("OFFICE_URL", "socket path must be absolute".into()));
+        }
+        return Url::parse(raw).map_err(|e| invalid("OFFICE_URL", e.to_string()));
+    }
+    parse_http_url("OFFICE_URL", raw)
+}
+
+fn invalid(key: &str, reason: String) -> ConfigError {
+    ConfigError::Invalid { key: key.to_string(), reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = ServerConfig::from_vars(&vars(&[])).unwrap();
+        assert_eq!(config.environment, Environment::Development);
+        assert_eq!(config.listen, Listen::Tcp { port: 8080 });
+        assert_eq!(config.webauthn_rp_id, "localhost");
+        assert_eq!(config.max_body_bytes, DEFAULT_MAX_BODY_BYTES);
+        assert_eq!(config.max_link_body_bytes, DEFAULT_MAX_LINK_BODY_BYTES);
+        assert_eq!(config.chain_check_depth, DEFAULT_CHAIN_CHECK_DEPTH);
+        assert_eq!(config.policy_gas_alert, DEFAULT_POLICY_GAS_ALERT);
+    }
+
+    #[test]
+    fn test_malformed_values_are_errors() {
+        let err = ServerConfig::from_vars(&vars(&[("PORT", "80a")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "PORT"));
+
+        let err = ServerConfig::from_vars(&vars(&[("SESSION_TTL", "1h")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "SESSION_TTL"));
+
+        let err = ServerConfig::from_vars(&vars(&[("UBL_MAX_BODY_BYTES", "0")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "UBL_MAX_BODY_BYTES"));
+
+        let err = ServerConfig::from_vars(&vars(&[("DATABASE_URL", "mysql://x@y/z")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "DATABASE_URL"));
+
+        let err = ServerConfig::from_vars(&vars(&[("UBL_CHAIN_CHECK_DEPTH", "-1")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "UBL_CHAIN_CHECK_DEPTH"));
+
+        for raw in ["0", "1.5", "80%"] {
+            let err = ServerConfig::from_vars(&vars(&[("UBL_POLICY_GAS_ALERT", raw)])).unwrap_err();
+            assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "UBL_POLICY_GAS_ALERT"));
+        }
+    }
+
+    #[test]
+    fn test_office_url_accepts_unix_socket() {
+        let config = ServerConfig::from_vars(&vars(&[("OFFICE_URL", "unix:///run/office/office.sock")])).unwrap();
+        assert_eq!(config.office_url.as_str(), "unix:///run/office/office.sock");
+
+        for bad in ["unix://office.sock", "ftp://office"] {
+            let err = ServerConfig::from_vars(&vars(&[("OFFICE_URL", bad)])).unwrap_err();
+            assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "OFFICE_URL"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_unknown_owned_key_rejected() {
+        let err = ServerConfig::from_vars(&vars(&[("WEBAUTHN_RP_ORIGIN", "http://localhost")])).unwrap_err();
+        assert_eq!(err, ConfigError::UnknownKey { key: "WEBAUTHN_RP_ORIGIN".into() });
+    }
+
+    #[test]
+    fn test_rp_id_must_match_origin() {
+        let err = ServerConfig::from_vars(&vars(&[
+            ("WEBAUTHN_RP_ID", "example.com"),
+            ("WEBAUTHN_ORIGIN", "https://evil.com"),
+        ]))
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "WEBAUTHN_RP_ID"));
+
+        let ok = ServerConfig::from_vars(&vars(&[
+            ("WEBAUTHN_RP_ID", "example.com"),
+            ("WEBAUTHN_ORIGIN", "https://id.example.com"),
+        ]));
+        assert!(ok.is_ok());
+    }
+
+    #[test]
+    fn test_service_auth_settings() {
+        let key = "ab".repeat(32);
+        let config = ServerConfig::from_vars(&vars(&[
+            ("UBL_OFFICE_PUBKEYS", &format!("{}, {}", key, "cd".repeat(32))),
+            ("UBL_REQUIRE_SERVICE_AUTH", "true"),
+        ]))
+        .unwrap();
+        assert_eq!(config.office_pubkeys.len(), 2);
+        assert!(config.require_service_auth);
+
+        let err = ServerConfig::from_vars(&vars(&[("UBL_REQUIRE_SERVICE_AUTH", "1")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "UBL_OFFICE_PUBKEYS"));
+
+        let err = ServerConfig::from_vars(&vars(&[("UBL_OFFICE_PUBKEYS", "not-a-key")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "UBL_OFFICE_PUBKEYS"));
+
+        let err = ServerConfig::from_vars(&vars(&[("UBL_SERVICE_TLS_CERT", "/etc/ubl/client.pem")])).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "UBL_SERVICE_TLS_CERT"));
+    }
+
+    #[test]
+    fn test_production_requires_database_url() {
+        let err = ServerConfig::from_vars(&vars(&[("UBL_ENV", "production")])).unwrap_err();
+        assert_eq!(err, ConfigError::Missing { key: "DATABASE_URL" });
+    }
+
+    #[test]
+    fn test_redacted_dump_masks_password() {
+        let config = ServerConfig::from_vars(&vars(&[
+            ("DATABASE_URL", "postgres://ubl:hunter2@db:5432/ubl"),
+        ]))
+        .unwrap();
+        let dump = config.redacted().to_string();
+        assert!(!dump.contains("hunter2"));
+        assert!(dump.contains("postgres://ubl:***@db:5432/ubl"));
+    }
+}
@@ -8,24 +8,63 @@
 //!
 //! Endpoints:
 //! - POST /v1/policy/permit      → Emit Permit (step-up required for L4/L5)
+//! - GET  /v1/policy/permit/keys → Admin public keys that sign permits
 //! - POST /v1/id/stepup/begin    → Begin step-up (returns WebAuthn challenge)
 //! - POST /v1/commands/issue     → Register Command (atomic single-use)
 //! - GET  /v1/query/commands     → List pending commands for Runner
+//!   (`?runner_id=` filters by the runner's capabilities, see `runners`)
+//!
+//! A command must be covered by its permit: same job type (`action`), params
+//! hashing to the permit's args, permit unexpired and its signature valid.
+//! This is checked when the command is issued and again when a runner pulls
+//! it; mismatches are rejected with `PERMIT_MISMATCH` (see `check_binding`).
 //! - POST /v1/exec.finish        → Register Receipt (runner signature required)
+//! - PUT  /v1/receipts/:command_id/artifacts/:name?token= → Upload a declared artifact
+//! - GET  /v1/receipts/:command_id/artifacts/:name → Download an uploaded artifact
+//! - GET  /v1/receipts/:command_id → Receipt with artifact upload status
+//! - PUT  /v1/blobs/:hash        → Store a blob under its `blake3:` hash
+//!   (Office, for the images of artifact cards)
+//!
+//! Permits are signed over `crypto::PermitClaims` (audience, policy hash,
+//! nonce, issue and expiry times) so Office can verify them offline against
+//! pinned keys instead of trusting the response. A permit whose target is a
+//! container also carries its `manifest_hash`, to compare with
+//! `GET /v1/containers/:id/manifest`.
+//!
+//! Office calls permit and command issuance and blob uploads with a service signature
+//! (`service_auth`); a bad one is rejected with 401, as is an unsigned call
+//! when `UBL_REQUIRE_SERVICE_AUTH` is set.
+//!
+//! Receipts that declare artifacts are incomplete until every artifact has
+//! been uploaded to its pre-authorized slot and matches its declared hash.
+//!
+//! exec.finish is idempotent per command: a runner replaying its receipt
+//! spool (`ubl_runner_core::spool`) gets `duplicate: true` back for a receipt
+//! the server already holds, with fresh slots for artifacts still missing.
 
 use axum::{
-    extract::{Query, State},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::StatusCode,
+    middleware,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{get, post, put},
     Json, Router,
 };
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Postgres, Transaction, Row};
+use std::sync::Arc;
+use ubl_errors::ErrorCode;
 use webauthn_rs::prelude::*;
 
+use crate::api_error::ApiError;
+use crate::blob_store::{self, BlobStore};
+use crate::container_manifest;
 use crate::crypto;
+use crate::policy_registry::PolicyRegistry;
+use crate::runners;
+use crate::service_auth;
 use crate::webauthn_store;
 
 // =============================================================================
@@ -36,20 +75,35 @@ use crate::webauthn_store;
 pub struct ConsoleState {
     pub pool: PgPool,
     pub webauthn: Webauthn,
+    pub blobs: Arc<BlobStore>,
+    pub policy_registry: Arc<PolicyRegistry>,
 }
 
 // =============================================================================
 // ROUTES
 // =============================================================================
 
-pub fn routes(pool: PgPool, webauthn: Webauthn) -> Router {
-    let state = ConsoleState { pool, webauthn };
-    Router::new()
+pub fn routes(pool: PgPool, webauthn: Webauthn, policy_registry: Arc<PolicyRegistry>) -> Router {
+    let state = ConsoleState { pool, webauthn, blobs: Arc::new(BlobStore::from_env()), policy_registry };
+    // Called by Office: service signature checked (see service_auth)
+    let internal = Router::new()
         .route("/v1/policy/permit", post(issue_permit))
-        .route("/v1/id/stepup/begin", post(stepup_begin))
         .route("/v1/commands/issue", post(issue_command))
+        .route("/v1/blobs/:hash", put(put_blob))
+        .route_layer(middleware::from_fn(service_auth::require_service));
+    Router::new()
+        .merge(internal)
+        .route("/v1/policy/permit/keys", get(permit_keys))
+        .route("/v1/id/stepup/begin", post(stepup_begin))
         .route("/v1/query/commands", get(query_commands))
         .route("/v1/exec.finish", post(exec_finish))
+        .route(
+            "/v1/receipts/:command_id/artifacts/:name",
+            put(upload_artifact)
+                .get(download_artifact)
+                .layer(DefaultBodyLimit::max(max_artifact_bytes() as usize)),
+        )
+        .route("/v1/receipts/:command_id", get(get_receipt))
         .with_state(state)
 }
 
@@ -79,6 +133,8 @@ fn default_risk() -> String {
 pub struct Permit {
     pub jti: String,
     pub office: String,
+    /// Audience: the office the permit was issued to
+    pub aud: String,
     pub action: String,
     pub target: String,
     pub args: serde_json::Value,
@@ -88,8 +144,26 @@ pub struct Permit {
     pub issued_at_ms: i64,
     pub exp_ms: i64,
     pub binding_hash: String,
+    /// Hash of the issuance rules (TTL per risk, step-up levels)
+    pub policy_hash: String,
     pub approver: String,
+    /// Admin public key (hex) that produced `sig`
+    pub signer: String,
+    /// "ed25519:<base64url>" over `crypto::permit_claims_bytes`
     pub sig: String,
+    /// Hash of the target container's manifest when the target is a
+    /// container (see `container_manifest`); not covered by `sig`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_hash: Option<String>,
+}
+
+/// Keys a verifier should accept; `previous` covers permits signed just
+/// before an admin key rotation
+#[derive(Debug, Serialize)]
+pub struct PermitKeys {
+    pub current: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -104,9 +178,17 @@ pub struct StepUpBeginRequest {
     pub binding_hash: String,
 }
 
+/// Command envelope; `jobType` and `params` must be what the permit covers
 #[derive(Debug, Deserialize)]
 pub struct CommandIssueRequest {
+    #[serde(alias = "jti")]
     pub permit_jti: String,
+    #[serde(rename = "jobType", alias = "job_type")]
+    pub job_type: String,
+    pub params: serde_json::Value,
+    /// Permit as returned by /v1/policy/permit; must be the stored one
+    #[serde(default)]
+    pub permit: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -122,6 +204,8 @@ pub struct QueryCommandsParams {
     pub pending: bool,
     #[serde(default = "default_limit")]
     pub limit: i32,
+    /// When set, only commands this live runner is capable of
+    pub runner_id: Option<String>,
 }
 
 fn default_pending() -> bool { true }
@@ -150,6 +234,41 @@ pub struct ExecFinishRequest {
     pub logs_hash: String,
     pub ret: serde_json::Value,
     pub sig_runner: String, // "ed25519:<base64url>"
+    /// Declared output artifacts; signed as part of the receipt when present
+    #[serde(default)]
+    pub artifacts: Vec<ArtifactDecl>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactDecl {
+    pub name: String,
+    /// "blake3:<hex>"
+    pub hash: String,
+    pub size_bytes: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadSlot {
+    pub name: String,
+    /// Relative URL; PUT the raw bytes here
+    pub upload_url: String,
+    pub expires_at_ms: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadParams {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReceiptArtifactView {
+    pub name: String,
+    pub content_hash: String,
+    pub size_bytes: i64,
+    pub media_type: Option<String>,
+    pub uploaded: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -208,7 +327,7 @@ async fn issue_permit(
     );
 
     // 5. Step-up required for L4/L5
-    let needs_stepup = req.risk == "L4" || req.risk == "L5";
+    let needs_stepup = STEPUP_RISKS.contains(&req.risk.as_str());
     let approver: String;
 
     if needs_stepup {
@@ -273,19 +392,32 @@ async fn issue_permit(
         approver = "session:default".into();
     }
 
-    // 6. Sign the permit with admin key
-    let sig_bytes = crypto::sign_admin_permit(binding_hash.as_bytes());
-    let sig = format!("ed25519:{}", URL_SAFE_NO_PAD.encode(&sig_bytes));
-
-    // 7. Generate JTI and persist
+    // 6. Sign the permit claims with the admin key
     let jti = crypto::uuid_v4();
+    let policy_hash = permit_policy_hash();
+    let claims = crypto::PermitClaims {
+        v: crypto::PERMIT_CLAIMS_VERSION,
+        jti: &jti,
+        aud: &req.office,
+        action: &req.action,
+        target: &req.target,
+        binding_hash: &binding_hash,
+        policy_hash: &policy_hash,
+        nonce: &nonce,
+        iat_ms: now_ms,
+        exp_ms,
+    };
+    let sig_bytes = crypto::sign_admin_permit(&crypto::permit_claims_bytes(&claims));
+    let sig = format!("ed25519:{}", URL_SAFE_NO_PAD.encode(&sig_bytes));
 
+    // 7. Persist
     let insert_result = sqlx::query(
         r#"
         INSERT INTO console_permits
-          (jti, office, action, target, args_json, risk, plan_hash, nonce, issued_at_ms, exp_ms, binding_hash, approver, sig, used)
+          (jti, office, action, target, args_json, risk, plan_hash, nonce, issued_at_ms, exp_ms, binding_hash, approver, sig, used,
+           policy_hash)
         VALUES
-          ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, false)
+          ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, false, $14)
         "#,
     )
     .bind(&jti)
@@ -301,6 +433,7 @@ async fn issue_permit(
     .bind(&binding_hash)
     .bind(&approver)
     .bind(&sig)
+    .bind(&policy_hash)
     .execute(pool)
     .await;
 
@@ -314,9 +447,17 @@ async fn issue_permit(
             .into_response();
     }
 
-    // 8. Return permit
+    // 8. Return permit, with the rules of the target container it was issued under
+    let manifest_hash = match container_manifest::assemble(pool, &state.policy_registry, &req.target).await {
+        Ok(manifest) => manifest.map(|m| m.manifest_hash),
+        Err(e) => {
+            tracing::warn!(permit_target = %req.target, error = %e, "Failed to assemble container manifest for permit");
+            None
+        }
+    };
     let permit = Permit {
         jti,
+        aud: req.office.clone(),
         office: req.office,
         action: req.action,
         target: req.target,
@@ -327,8 +468,11 @@ async fn issue_permit(
         issued_at_ms: now_ms,
         exp_ms,
         binding_hash,
+        policy_hash,
         approver,
+        signer: crypto::admin_pubkey_hex(),
         sig,
+        manifest_hash,
     };
 
     (StatusCode::OK, Json(PermitResponse { permit, allowed: true })).into_response()
@@ -358,7 +502,7 @@ async fn issue_command(
     let permit_row = sqlx::query(
         r#"
         SELECT jti, office, action, target, args_json, risk, plan_hash, nonce,
-               issued_at_ms, exp_ms, binding_hash, approver, sig, used
+               issued_at_ms, exp_ms, binding_hash, approver, sig, used, policy_hash
         FROM console_permits
         WHERE jti = $1
         FOR UPDATE
@@ -386,17 +530,8 @@ async fn issue_command(
         }
     };
 
-    // Extract fields
     let used: bool = Row::get(&row, "used");
-    let exp_ms: i64 = Row::get(&row, "exp_ms");
-    let binding_hash: String = Row::get(&row, "binding_hash");
-    let sig: String = Row::get(&row, "sig");
-    let office: String = Row::get(&row, "office");
-    let action: String = Row::get(&row, "action");
-    let target: String = Row::get(&row, "target");
-    let args_json: serde_json::Value = Row::get(&row, "args_json");
-    let risk: String = Row::get(&row, "risk");
-    let plan_hash: String = Row::get(&row, "plan_hash");
+    let permit = PermitRecord::from_row(&row);
 
     // Validate: not used
     if used {
@@ -408,7 +543,7 @@ async fn issue_command(
     }
 
     // Validate: not expired
-    if now_ms > exp_ms {
+    if now_ms > permit.exp_ms {
         return (
             StatusCode::FORBIDDEN,
             Json(ErrorResponse { error: "PermitExpired".into() }),
@@ -416,8 +551,8 @@ async fn issue_command(
             .into_response();
     }
 
-    // Validate: signature
-    if let Err(e) = crypto::verify_admin_permit_sig(binding_hash.as_bytes(), &sig) {
+    // Validate: signature over the claims
+    if let Err(e) = permit.verify_signature() {
         return (
             StatusCode::FORBIDDEN,
             Json(ErrorResponse { error: format!("PermitSigInvalid: {}", e) }),
@@ -425,6 +560,18 @@ async fn issue_command(
             .into_response();
     }
 
+    // Validate: the permit covers this command
+    let presented = req.permit.as_ref().map(|p| (p.get("jti"), p.get("sig")));
+    if let Some((jti, sig)) = presented {
+        if jti.and_then(|v| v.as_str()) != Some(permit.jti.as_str()) || sig.and_then(|v| v.as_str()) != Some(permit.sig.as_str()) {
+            return ApiError::new(ErrorCode::PermitMismatch, BindingError::PermitBlob.to_string()).into_response();
+        }
+    }
+    if let Err(e) = check_binding(&permit, &req.job_type, &req.params, now_ms) {
+        tracing::warn!(permit_jti = %permit.jti, reason = %e, "Command rejected at issue");
+        return ApiError::new(ErrorCode::PermitMismatch, e.to_string()).into_response();
+    }
+
     // Mark permit as used
     if let Err(e) = sqlx::query("UPDATE console_permits SET used = true WHERE jti = $1")
         .bind(&req.permit_jti)
@@ -440,25 +587,30 @@ async fn issue_command(
 
     // Create command
     let command_id = crypto::uuid_v4();
+    let risk: String = Row::get(&row, "risk");
+    let plan_hash: String = Row::get(&row, "plan_hash");
+    let required_caps = runners::required_capabilities(&permit.args);
 
     if let Err(e) = sqlx::query(
         r#"
         INSERT INTO console_commands
-          (command_id, permit_jti, office, action, target, args_json, risk, plan_hash, binding_hash, pending, created_at_ms)
+          (command_id, permit_jti, office, action, target, args_json, risk, plan_hash, binding_hash, pending, created_at_ms,
+           required_capabilities)
         VALUES
-          ($1, $2, $3, $4, $5, $6, $7, $8, $9, true, $10)
+          ($1, $2, $3, $4, $5, $6, $7, $8, $9, true, $10, $11)
         "#,
     )
     .bind(&command_id)
     .bind(&req.permit_jti)
-    .bind(&office)
-    .bind(&action)
-    .bind(&target)
-    .bind(&args_json)
+    .bind(&permit.office)
+    .bind(&permit.action)
+    .bind(&permit.target)
+    .bind(&permit.args)
     .bind(&risk)
     .bind(&plan_hash)
-    .bind(&binding_hash)
+    .bind(&permit.binding_hash)
     .bind(now_ms)
+    .bind(&required_caps)
     .execute(&mut *tx)
     .await
     {
@@ -488,25 +640,50 @@ async fn query_commands(
 ) -> impl IntoResponse {
     let pool = &state.pool;
 
+    // A runner only sees commands it can execute; unknown or stale runners see none
+    let runner_caps: Option<Vec<String>> = match &params.runner_id {
+        Some(runner_id) => match runners::live_capabilities(pool, runner_id).await {
+            Ok(Some(caps)) => Some(caps),
+            Ok(None) => {
+                tracing::warn!(runner_id = %runner_id, "Command poll from unknown or stale runner");
+                return (StatusCode::OK, Json(Vec::<CommandRow>::new()));
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Runner lookup failed");
+                return (StatusCode::OK, Json(Vec::<CommandRow>::new()));
+            }
+        },
+        None => None,
+    };
+
     let rows = sqlx::query(
         r#"
-        SELECT command_id, permit_jti, office, action, target, args_json, risk, plan_hash, binding_hash, pending, created_at_ms
-        FROM console_commands
-        WHERE target = $1 AND pending = $2
-        ORDER BY created_at_ms ASC
+        SELECT c.command_id, c.permit_jti, c.office, c.action, c.target, c.args_json, c.risk, c.plan_hash,
+               c.binding_hash, c.pending, c.created_at_ms,
+               p.jti AS p_jti, p.office AS p_office, p.action AS p_action, p.target AS p_target,
+               p.args_json AS p_args_json, p.binding_hash AS p_binding_hash, p.policy_hash AS p_policy_hash,
+               p.nonce AS p_nonce, p.issued_at_ms AS p_issued_at_ms, p.exp_ms AS p_exp_ms, p.sig AS p_sig
+        FROM console_commands c
+        JOIN console_permits p ON p.jti = c.permit_jti
+        WHERE c.target = $1 AND c.pending = $2
+          AND ($4::TEXT[] IS NULL OR c.required_capabilities <@ $4)
+        ORDER BY c.created_at_ms ASC
         LIMIT $3
         "#,
     )
     .bind(&params.target)
     .bind(params.pending)
     .bind(params.limit)
+    .bind(&runner_caps)
     .fetch_all(pool)
     .await
     .unwrap_or_default();
 
-    let commands: Vec<CommandRow> = rows
-        .into_iter()
-        .map(|row| CommandRow {
+    // Re-check each pending command against its permit before handing it out
+    let now_ms = now_millis() as i64;
+    let mut commands: Vec<CommandRow> = Vec::with_capacity(rows.len());
+    for row in rows {
+        let command = CommandRow {
             command_id: Row::get(&row, "command_id"),
             permit_jti: Row::get(&row, "permit_jti"),
             office: Row::get(&row, "office"),
@@ -518,8 +695,23 @@ async fn query_commands(
             binding_hash: Row::get(&row, "binding_hash"),
             pending: Row::get(&row, "pending"),
             created_at_ms: Row::get(&row, "created_at_ms"),
-        })
-        .collect();
+        };
+        if command.pending {
+            let permit = PermitRecord::from_prefixed_row(&row, "p_");
+            let verdict = check_binding(&permit, &command.action, &command.args, now_ms)
+                .and_then(|()| {
+                    (command.binding_hash == permit.binding_hash)
+                        .then_some(())
+                        .ok_or(BindingError::BindingHash)
+                })
+                .and_then(|()| permit.verify_signature().map_err(BindingError::Signature));
+            if let Err(e) = verdict {
+                reject_command(pool, &command.command_id, &e).await;
+                continue;
+            }
+        }
+        commands.push(command);
+    }
 
     (StatusCode::OK, Json(commands))
 }
@@ -563,19 +755,15 @@ async fn exec_finish(
     };
 
     let pending: bool = Row::get(&cmd, "pending");
-    if !pending {
-        return (
-            StatusCode::CONFLICT,
-            Json(ErrorResponse { error: "CommandAlreadyFinished".into() }),
-        )
-            .into_response();
-    }
-
     let permit_jti: String = Row::get(&cmd, "permit_jti");
     let binding_hash: String = Row::get(&cmd, "binding_hash");
 
+    if let Err(e) = validate_artifacts(&req.artifacts) {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+    }
+
     // Build receipt payload for signature verification
-    let receipt_payload = serde_json::json!({
+    let mut receipt_payload = serde_json::json!({
         "command_id": req.command_id,
         "permit_jti": permit_jti,
         "binding_hash": binding_hash,
@@ -584,6 +772,10 @@ async fn exec_finish(
         "logs_hash": req.logs_hash,
         "ret": req.ret,
     });
+    // Receipts without artifacts keep the original signed shape
+    if !req.artifacts.is_empty() {
+        receipt_payload["artifacts"] = serde_json::to_value(&req.artifacts).unwrap_or_default();
+    }
 
     // Canonicalize for signature
     let receipt_bytes = match crate::crypto::ubl_atom_compat::canonicalize(&receipt_payload) {
@@ -607,6 +799,31 @@ async fn exec_finish(
             .into_response();
     }
 
+    // A runner replaying its spool after a network failure resends receipts
+    // the server may already hold: the same signed receipt is acknowledged
+    // again, any other one for a finished command is a conflict
+    if !pending {
+        return replayed_receipt(pool, &req, now_ms).await;
+    }
+
+    // Streamed logs: the receipt must commit to the head of the segment chain
+    let log_head: Option<String> = sqlx::query_scalar(
+        "SELECT chain_hash FROM exec_log_segments WHERE execution_id = $1 ORDER BY seq DESC LIMIT 1",
+    )
+    .bind(&req.command_id)
+    .fetch_optional(pool)
+    .await
+    .unwrap_or(None);
+    if let Some(head) = log_head {
+        if head != req.logs_hash {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse { error: format!("LogsHashMismatch: streamed log head is {}", head) }),
+            )
+                .into_response();
+        }
+    }
+
     // Begin transaction
     let mut tx = match pool.begin().await {
         Ok(t) => t,
@@ -619,26 +836,33 @@ async fn exec_finish(
         }
     };
 
-    // Mark command as done
-    if let Err(e) = sqlx::query("UPDATE console_commands SET pending = false WHERE command_id = $1")
+    // Mark command as done; a concurrent copy of this receipt may have won
+    match sqlx::query("UPDATE console_commands SET pending = false WHERE command_id = $1 AND pending")
         .bind(&req.command_id)
         .execute(&mut *tx)
         .await
     {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse { error: e.to_string() }),
-        )
-            .into_response();
+        Ok(done) if done.rows_affected() == 0 => {
+            drop(tx);
+            return replayed_receipt(pool, &req, now_ms).await;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e.to_string() }),
+            )
+                .into_response();
+        }
     }
 
     // Insert receipt
     if let Err(e) = sqlx::query(
         r#"
         INSERT INTO console_receipts
-          (command_id, permit_jti, runner_id, status, logs_hash, ret_json, sig_runner, finished_at_ms)
+          (command_id, permit_jti, runner_id, status, logs_hash, ret_json, sig_runner, finished_at_ms, complete)
         VALUES
-          ($1, $2, $3, $4, $5, $6, $7, $8)
+          ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         "#,
     )
     .bind(&req.command_id)
@@ -649,6 +873,7 @@ async fn exec_finish(
     .bind(&req.ret)
     .bind(&req.sig_runner)
     .bind(now_ms)
+    .bind(req.artifacts.is_empty())
     .execute(&mut *tx)
     .await
     {
@@ -659,6 +884,41 @@ async fn exec_finish(
             .into_response();
     }
 
+    // One upload slot per declared artifact
+    let token_exp_ms = now_ms + artifact_upload_ttl_ms();
+    let mut uploads = Vec::with_capacity(req.artifacts.len());
+    for artifact in &req.artifacts {
+        let token = URL_SAFE_NO_PAD.encode(crypto::rand_bytes_32());
+        if let Err(e) = sqlx::query(
+            r#"
+            INSERT INTO console_receipt_artifacts
+              (command_id, name, content_hash, size_bytes, media_type, upload_token, token_exp_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(&req.command_id)
+        .bind(&artifact.name)
+        .bind(&artifact.hash)
+        .bind(artifact.size_bytes)
+        .bind(&artifact.media_type)
+        .bind(&token)
+        .bind(token_exp_ms)
+        .execute(&mut *tx)
+        .await
+        {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e.to_string() }),
+            )
+                .into_response();
+        }
+        uploads.push(UploadSlot {
+            name: artifact.name.clone(),
+            upload_url: format!("/v1/receipts/{}/artifacts/{}?token={}", req.command_id, artifact.name, token),
+            expires_at_ms: token_exp_ms,
+        });
+    }
+
     // Commit
     if let Err(e) = tx.commit().await {
         return (
@@ -672,10 +932,374 @@ async fn exec_finish(
         command_id = %req.command_id,
         runner_id = %req.runner_id,
         status = %req.status,
+        artifacts = uploads.len(),
         "✅ Execution receipt recorded"
     );
 
-    (StatusCode::OK, Json(serde_json::json!({"ok": true}))).into_response()
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "ok": true,
+            "complete": uploads.is_empty(),
+            "uploads": uploads,
+        })),
+    )
+        .into_response()
+}
+
+/// Answer a receipt for a command that is already finished. Receipts are
+/// deduplicated by execution id (the command id): when the stored receipt is
+/// the one being sent (same runner, same signature over the same payload) it
+/// is acknowledged with `duplicate: true` and fresh upload slots for the
+/// artifacts still missing, since the original tokens may have expired while
+/// the runner was offline. Anything else is `CommandAlreadyFinished`.
+async fn replayed_receipt(pool: &PgPool, req: &ExecFinishRequest, now_ms: i64) -> axum::response::Response {
+    let stored = sqlx::query("SELECT runner_id, sig_runner, complete FROM console_receipts WHERE command_id = $1")
+        .bind(&req.command_id)
+        .fetch_optional(pool)
+        .await;
+    let complete = match stored {
+        Ok(Some(row))
+            if Row::get::<String, _>(&row, "runner_id") == req.runner_id
+                && Row::get::<String, _>(&row, "sig_runner") == req.sig_runner =>
+        {
+            Row::get::<bool, _>(&row, "complete")
+        }
+        Ok(_) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse { error: "CommandAlreadyFinished".into() }),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e.to_string() }),
+            )
+                .into_response();
+        }
+    };
+
+    let token_exp_ms = now_ms + artifact_upload_ttl_ms();
+    let mut uploads = Vec::new();
+    if !complete {
+        let missing = sqlx::query("SELECT name FROM console_receipt_artifacts WHERE command_id = $1 AND uploaded_at_ms IS NULL")
+            .bind(&req.command_id)
+            .fetch_all(pool)
+            .await;
+        let missing = match missing {
+            Ok(rows) => rows,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse { error: e.to_string() }),
+                )
+                    .into_response();
+            }
+        };
+        for row in missing {
+            let name: String = Row::get(&row, "name");
+            let token = URL_SAFE_NO_PAD.encode(crypto::rand_bytes_32());
+            if let Err(e) = sqlx::query(
+                "UPDATE console_receipt_artifacts SET upload_token = $3, token_exp_ms = $4 WHERE command_id = $1 AND name = $2",
+            )
+            .bind(&req.command_id)
+            .bind(&name)
+            .bind(&token)
+            .bind(token_exp_ms)
+            .execute(pool)
+            .await
+            {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse { error: e.to_string() }),
+                )
+                    .into_response();
+            }
+            uploads.push(UploadSlot {
+                upload_url: format!("/v1/receipts/{}/artifacts/{}?token={}", req.command_id, name, token),
+                name,
+                expires_at_ms: token_exp_ms,
+            });
+        }
+    }
+
+    tracing::info!(
+        command_id = %req.command_id,
+        runner_id = %req.runner_id,
+        "🔁 Replayed execution receipt acknowledged"
+    );
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "ok": true,
+            "duplicate": true,
+            "complete": complete,
+            "uploads": uploads,
+        })),
+    )
+        .into_response()
+}
+
+/// PUT /v1/receipts/:command_id/artifacts/:name — Upload a declared artifact
+async fn upload_artifact(
+    State(state): State<ConsoleState>,
+    Path((command_id, name)): Path<(String, String)>,
+    Query(params): Query<UploadParams>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let pool = &state.pool;
+    let now_ms = now_millis() as i64;
+    let fail = |status: StatusCode, error: String| (status, Json(ErrorResponse { error })).into_response();
+
+    let mut tx = match pool.begin().await {
+        Ok(t) => t,
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let slot = sqlx::query(
+        r#"
+        SELECT content_hash, size_bytes, upload_token, token_exp_ms, uploaded_at_ms
+        FROM console_receipt_artifacts
+        WHERE command_id = $1 AND name = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(&command_id)
+    .bind(&name)
+    .fetch_optional(&mut *tx)
+    .await;
+
+    let slot = match slot {
+        Ok(Some(r)) => r,
+        Ok(None) => return fail(StatusCode::NOT_FOUND, "ArtifactSlotNotFound".into()),
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let content_hash: String = Row::get(&slot, "content_hash");
+    let size_bytes: i64 = Row::get(&slot, "size_bytes");
+    let upload_token: String = Row::get(&slot, "upload_token");
+    let token_exp_ms: i64 = Row::get(&slot, "token_exp_ms");
+    let uploaded_at_ms: Option<i64> = Row::get(&slot, "uploaded_at_ms");
+
+    if !ubl_kernel::ct_eq(params.token.as_bytes(), upload_token.as_bytes()) {
+        return fail(StatusCode::FORBIDDEN, "UploadTokenInvalid".into());
+    }
+    if uploaded_at_ms.is_some() {
+        return fail(StatusCode::CONFLICT, "ArtifactAlreadyUploaded".into());
+    }
+    if now_ms > token_exp_ms {
+        return fail(StatusCode::FORBIDDEN, "UploadSlotExpired".into());
+    }
+    if body.len() as i64 != size_bytes {
+        return fail(
+            StatusCode::BAD_REQUEST,
+            format!("ArtifactSizeMismatch: declared {} got {}", size_bytes, body.len()),
+        );
+    }
+    let actual_hash = blob_store::content_hash(&body);
+    if actual_hash != content_hash {
+        return fail(
+            StatusCode::BAD_REQUEST,
+            format!("ArtifactHashMismatch: declared {} got {}", content_hash, actual_hash),
+        );
+    }
+
+    let blobs = state.blobs.clone();
+    match tokio::task::spawn_blocking(move || blobs.put(&body)).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => return fail(StatusCode::INTERNAL_SERVER_ERROR, format!("BlobWriteFailed: {}", e)),
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+
+    if let Err(e) = sqlx::query(
+        "UPDATE console_receipt_artifacts SET uploaded_at_ms = $3 WHERE command_id = $1 AND name = $2",
+    )
+    .bind(&command_id)
+    .bind(&name)
+    .bind(now_ms)
+    .execute(&mut *tx)
+    .await
+    {
+        return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    }
+
+    let complete = sqlx::query_scalar::<_, bool>(
+        r#"
+        UPDATE console_receipts
+        SET complete = NOT EXISTS (
+          SELECT 1 FROM console_receipt_artifacts
+          WHERE command_id = $1 AND uploaded_at_ms IS NULL
+        )
+        WHERE command_id = $1
+        RETURNING complete
+        "#,
+    )
+    .bind(&command_id)
+    .fetch_one(&mut *tx)
+    .await;
+
+    let complete = match complete {
+        Ok(c) => c,
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    if let Err(e) = tx.commit().await {
+        return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    }
+
+    tracing::info!(command_id = %command_id, artifact = %name, complete, "📦 Receipt artifact uploaded");
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "name": name, "content_hash": content_hash, "complete": complete })),
+    )
+        .into_response()
+}
+
+/// GET /v1/receipts/:command_id/artifacts/:name — Download an uploaded artifact
+async fn download_artifact(
+    State(state): State<ConsoleState>,
+    Path((command_id, name)): Path<(String, String)>,
+) -> axum::response::Response {
+    let fail = |status: StatusCode, error: String| (status, Json(ErrorResponse { error })).into_response();
+
+    let slot = sqlx::query(
+        r#"
+        SELECT content_hash, media_type
+        FROM console_receipt_artifacts
+        WHERE command_id = $1 AND name = $2 AND uploaded_at_ms IS NOT NULL
+        "#,
+    )
+    .bind(&command_id)
+    .bind(&name)
+    .fetch_optional(&state.pool)
+    .await;
+
+    let (content_hash, media_type): (String, Option<String>) = match slot {
+        Ok(Some(r)) => (Row::get(&r, "content_hash"), Row::get(&r, "media_type")),
+        Ok(None) => return fail(StatusCode::NOT_FOUND, "ArtifactNotFound".into()),
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let blobs = state.blobs.clone();
+    let hash = content_hash.clone();
+    let bytes = match tokio::task::spawn_blocking(move || blobs.get(&hash)).await {
+        Ok(Ok(Some(b))) => b,
+        Ok(Ok(None)) => return fail(StatusCode::NOT_FOUND, "BlobMissing".into()),
+        Ok(Err(e)) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    let content_type = media_type.unwrap_or_else(|| "application/octet-stream".into());
+    (
+        StatusCode::OK,
+        [
+            (axum::http::header::CONTENT_TYPE, content_type),
+            (axum::http::header::ETAG, format!("\"{}\"", content_hash)),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// PUT /v1/blobs/:hash — Store a blob from Office; the body must hash to `hash`
+async fn put_blob(
+    State(state): State<ConsoleState>,
+    Path(hash): Path<String>,
+    body: axum::body::Bytes,
+) -> axum::response::Response {
+    let fail = |status: StatusCode, error: String| (status, Json(ErrorResponse { error })).into_response();
+
+    if blob_store::parse_hash(&hash).is_none() {
+        return fail(StatusCode::BAD_REQUEST, "InvalidBlobHash".into());
+    }
+    let actual_hash = blob_store::content_hash(&body);
+    if actual_hash != hash {
+        return fail(
+            StatusCode::BAD_REQUEST,
+            format!("BlobHashMismatch: declared {} got {}", hash, actual_hash),
+        );
+    }
+
+    let size_bytes = body.len();
+    let blobs = state.blobs.clone();
+    match tokio::task::spawn_blocking(move || blobs.put(&body)).await {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => return fail(StatusCode::INTERNAL_SERVER_ERROR, format!("BlobWriteFailed: {}", e)),
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+
+    tracing::info!(content_hash = %hash, size_bytes, "📦 Blob stored");
+    (StatusCode::OK, Json(serde_json::json!({ "content_hash": hash, "size_bytes": size_bytes }))).into_response()
+}
+
+/// GET /v1/receipts/:command_id — Receipt with artifact status
+async fn get_receipt(
+    State(state): State<ConsoleState>,
+    Path(command_id): Path<String>,
+) -> impl IntoResponse {
+    let pool = &state.pool;
+
+    let receipt = sqlx::query(
+        r#"
+        SELECT command_id, runner_id, status, logs_hash, ret_json, finished_at_ms, complete
+        FROM console_receipts
+        WHERE command_id = $1
+        "#,
+    )
+    .bind(&command_id)
+    .fetch_optional(pool)
+    .await;
+
+    let row = match receipt {
+        Ok(Some(r)) => r,
+        Ok(None) => {
+            return (StatusCode::NOT_FOUND, Json(ErrorResponse { error: "ReceiptNotFound".into() })).into_response()
+        }
+        Err(e) => {
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error: e.to_string() })).into_response()
+        }
+    };
+
+    let artifacts: Vec<ReceiptArtifactView> = sqlx::query(
+        r#"
+        SELECT name, content_hash, size_bytes, media_type, uploaded_at_ms
+        FROM console_receipt_artifacts
+        WHERE command_id = $1
+        ORDER BY name
+        "#,
+    )
+    .bind(&command_id)
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|a| ReceiptArtifactView {
+        name: Row::get(&a, "name"),
+        content_hash: Row::get(&a, "content_hash"),
+        size_bytes: Row::get(&a, "size_bytes"),
+        media_type: Row::get(&a, "media_type"),
+        uploaded: Row::get::<Option<i64>, _>(&a, "uploaded_at_ms").is_some(),
+    })
+    .collect();
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "command_id": Row::get::<String, _>(&row, "command_id"),
+            "runner_id": Row::get::<String, _>(&row, "runner_id"),
+            "status": Row::get::<String, _>(&row, "status"),
+            "logs_hash": Row::get::<String, _>(&row, "logs_hash"),
+            "ret": Row::get::<serde_json::Value, _>(&row, "ret_json"),
+            "finished_at_ms": Row::get::<i64, _>(&row, "finished_at_ms"),
+            "complete": Row::get::<bool, _>(&row, "complete"),
+            "artifacts": artifacts,
+        })),
+    )
+        .into_response()
 }
 
 // =============================================================================
@@ -689,6 +1313,192 @@ fn now_millis() -> u64 {
         .unwrap_or(0)
 }
 
+/// Largest artifact accepted by the upload route (`UBL_MAX_ARTIFACT_BYTES`, default 64 MiB)
+fn max_artifact_bytes() -> i64 {
+    std::env::var("UBL_MAX_ARTIFACT_BYTES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(64 * 1024 * 1024)
+}
+
+/// Upload slot lifetime (`UBL_ARTIFACT_UPLOAD_TTL_SECS`, default 1 hour)
+fn artifact_upload_ttl_ms() -> i64 {
+    std::env::var("UBL_ARTIFACT_UPLOAD_TTL_SECS")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(3600)
+        * 1000
+}
+
+const MAX_ARTIFACTS_PER_RECEIPT: usize = 64;
+
+/// Names become URL path segments, so keep them to a safe alphabet
+fn validate_artifacts(artifacts: &[ArtifactDecl]) -> Result<(), String> {
+    if artifacts.len() > MAX_ARTIFACTS_PER_RECEIPT {
+        return Err(format!("TooManyArtifacts: max {}", MAX_ARTIFACTS_PER_RECEIPT));
+    }
+    let mut names = std::collections::HashSet::new();
+    for a in artifacts {
+        let name_ok = !a.name.is_empty()
+            && a.name.len() <= 128
+            && !a.name.starts_with('.')
+            && a.name.chars().all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+        if !name_ok {
+            return Err(format!("InvalidArtifactName: {:?}", a.name));
+        }
+        if !names.insert(a.name.as_str()) {
+            return Err(format!("DuplicateArtifact: {}", a.name));
+        }
+        if blob_store::parse_hash(&a.hash).is_none() {
+            return Err(format!("InvalidArtifactHash: {}", a.name));
+        }
+        if a.size_bytes < 0 || a.size_bytes > max_artifact_bytes() {
+            return Err(format!("InvalidArtifactSize: {}", a.name));
+        }
+    }
+    Ok(())
+}
+
+/// GET /v1/policy/permit/keys — Public keys that sign permits
+async fn permit_keys() -> Json<PermitKeys> {
+    Json(PermitKeys {
+        current: crypto::admin_pubkey_hex(),
+        previous: crate::keystore::previous_public_key_hex("admin"),
+    })
+}
+
+// =============================================================================
+// COMMAND ↔ PERMIT BINDING
+// =============================================================================
+
+/// A stored permit, as needed to check a command against it
+struct PermitRecord {
+    jti: String,
+    office: String,
+    action: String,
+    target: String,
+    args: serde_json::Value,
+    binding_hash: String,
+    /// NULL for permits issued before claims were signed
+    policy_hash: Option<String>,
+    nonce: String,
+    issued_at_ms: i64,
+    exp_ms: i64,
+    sig: String,
+}
+
+impl PermitRecord {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Self {
+        Self::from_prefixed_row(row, "")
+    }
+
+    /// Read columns named `<prefix><column>` (for joined queries)
+    fn from_prefixed_row(row: &sqlx::postgres::PgRow, prefix: &str) -> Self {
+        let col = |name: &str| format!("{}{}", prefix, name);
+        Self {
+            jti: Row::get(row, col("jti").as_str()),
+            office: Row::get(row, col("office").as_str()),
+            action: Row::get(row, col("action").as_str()),
+            target: Row::get(row, col("target").as_str()),
+            args: Row::get(row, col("args_json").as_str()),
+            binding_hash: Row::get(row, col("binding_hash").as_str()),
+            policy_hash: Row::get(row, col("policy_hash").as_str()),
+            nonce: Row::get(row, col("nonce").as_str()),
+            issued_at_ms: Row::get(row, col("issued_at_ms").as_str()),
+            exp_ms: Row::get(row, col("exp_ms").as_str()),
+            sig: Row::get(row, col("sig").as_str()),
+        }
+    }
+
+    /// Admin signature over the permit claims
+    fn verify_signature(&self) -> Result<(), &'static str> {
+        let claims = crypto::PermitClaims {
+            v: crypto::PERMIT_CLAIMS_VERSION,
+            jti: &self.jti,
+            aud: &self.office,
+            action: &self.action,
+            target: &self.target,
+            binding_hash: &self.binding_hash,
+            policy_hash: self.policy_hash.as_deref().unwrap_or_default(),
+            nonce: &self.nonce,
+            iat_ms: self.issued_at_ms,
+            exp_ms: self.exp_ms,
+        };
+        crypto::verify_admin_permit_sig(&crypto::permit_claims_bytes(&claims), &self.sig)
+    }
+}
+
+/// Why a command is not covered by its permit
+#[derive(Debug, PartialEq, thiserror::Error)]
+enum BindingError {
+    #[error("PermitExpired")]
+    Expired,
+    #[error("JobTypeMismatch: permit covers {permit:?}, command is {command:?}")]
+    JobType { permit: String, command: String },
+    #[error("ParamsHashMismatch: permit {permit}, command {command}")]
+    ParamsHash { permit: String, command: String },
+    #[error("BindingHashMismatch")]
+    BindingHash,
+    #[error("PermitSigInvalid: {0}")]
+    Signature(&'static str),
+    #[error("PermitBlobMismatch: presented permit is not the stored one")]
+    PermitBlob,
+}
+
+/// Check that `permit` covers running `job_type` with `params` at `now_ms`.
+/// The signature is checked separately ([`PermitRecord::verify_signature`]).
+fn check_binding(
+    permit: &PermitRecord,
+    job_type: &str,
+    params: &serde_json::Value,
+    now_ms: i64,
+) -> Result<(), BindingError> {
+    if now_ms > permit.exp_ms {
+        return Err(BindingError::Expired);
+    }
+    if job_type != permit.action {
+        return Err(BindingError::JobType { permit: permit.action.clone(), command: job_type.to_string() });
+    }
+    let permit_hash = crypto::canonical_plan_hash(&permit.args);
+    let command_hash = crypto::canonical_plan_hash(params);
+    if permit_hash != command_hash {
+        return Err(BindingError::ParamsHash { permit: permit_hash, command: command_hash });
+    }
+    Ok(())
+}
+
+/// Take a command that no longer matches its permit out of the queue
+async fn reject_command(pool: &PgPool, command_id: &str, reason: &BindingError) {
+    tracing::warn!(command_id = %command_id, reason = %reason, "Command rejected at pull");
+    let result = sqlx::query(
+        "UPDATE console_commands SET pending = false, rejected_reason = $2 WHERE command_id = $1 AND pending",
+    )
+    .bind(command_id)
+    .bind(format!("{}: {}", ErrorCode::PermitMismatch, reason))
+    .execute(pool)
+    .await;
+    if let Err(e) = result {
+        tracing::error!(command_id = %command_id, error = %e, "Failed to mark command rejected");
+    }
+}
+
+/// Risk levels that require WebAuthn step-up
+const STEPUP_RISKS: [&str; 2] = ["L4", "L5"];
+
+/// Hash of the rules permits are issued under. Stamped on every permit so a
+/// verifier can tell when the issuance policy changed.
+fn permit_policy_hash() -> String {
+    let ttl_ms: serde_json::Map<String, serde_json::Value> = ["L0", "L1", "L2", "L3", "L4", "L5"]
+        .iter()
+        .map(|r| (r.to_string(), get_ttl_for_risk(r).into()))
+        .collect();
+    crypto::canonical_plan_hash(&serde_json::json!({
+        "policy": "console.permit",
+        "stepup": STEPUP_RISKS,
+        "ttl_ms": ttl_ms,
+    }))
+}
+
 fn get_ttl_for_risk(risk: &str) -> i64 {
     match risk {
         "L0" | "L1" => 10 * 60 * 1000,     // 10 min
@@ -698,3 +1508,109 @@ fn get_ttl_for_risk(risk: &str) -> i64 {
         _ => 5 * 60 * 1000,                 // default 5 min
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decl(name: &str) -> ArtifactDecl {
+        ArtifactDecl {
+            name: name.into(),
+            hash: blob_store::content_hash(name.as_bytes()),
+            size_bytes: name.len() as i64,
+            media_type: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_artifacts() {
+        assert!(validate_artifacts(&[decl("out.tar.gz"), decl("report-1.json")]).is_ok());
+        assert!(validate_artifacts(&[decl("a"), decl("a")]).is_err());
+        assert!(validate_artifacts(&[decl("../etc")]).is_err());
+        assert!(validate_artifacts(&[decl("a/b")]).is_err());
+
+        let mut bad = decl("x");
+        bad.hash = "sha256:00".into();
+        assert!(validate_artifacts(&[bad]).is_err());
+    }
+
+    #[test]
+    fn test_permit_claims_bytes() {
+        let policy_hash = permit_policy_hash();
+        assert_eq!(policy_hash, permit_policy_hash());
+        assert!(policy_hash.starts_with("blake3:"));
+
+        let claims = crypto::PermitClaims {
+            v: crypto::PERMIT_CLAIMS_VERSION,
+            jti: "jti-1",
+            aud: "office",
+            action: "service.restart",
+            target: "LAB_512",
+            binding_hash: "blake3:00",
+            policy_hash: &policy_hash,
+            nonce: "n",
+            iat_ms: 1,
+            exp_ms: 2,
+        };
+        let bytes = crypto::permit_claims_bytes(&claims);
+        assert!(bytes.starts_with(br#"{"action":"service.restart","aud":"office","#));
+
+        let other = crypto::PermitClaims { aud: "other-office", ..claims };
+        assert_ne!(bytes, crypto::permit_claims_bytes(&other));
+    }
+
+    fn permit_record() -> PermitRecord {
+        PermitRecord {
+            jti: "jti-1".into(),
+            office: "office".into(),
+            action: "service.restart".into(),
+            target: "LAB_512".into(),
+            args: serde_json::json!({"name": "minio", "force": false}),
+            binding_hash: "blake3:00".into(),
+            policy_hash: Some(permit_policy_hash()),
+            nonce: "n".into(),
+            issued_at_ms: 1_000,
+            exp_ms: 10_000,
+            sig: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_binding() {
+        let permit = permit_record();
+        // Key order does not matter: params are compared by canonical hash
+        let params = serde_json::json!({"force": false, "name": "minio"});
+        assert_eq!(check_binding(&permit, "service.restart", &params, 5_000), Ok(()));
+
+        assert_eq!(check_binding(&permit, "service.restart", &params, 10_001), Err(BindingError::Expired));
+        assert!(matches!(
+            check_binding(&permit, "service.stop", &params, 5_000),
+            Err(BindingError::JobType { .. })
+        ));
+        let widened = serde_json::json!({"force": true, "name": "minio"});
+        assert!(matches!(
+            check_binding(&permit, "service.restart", &widened, 5_000),
+            Err(BindingError::ParamsHash { .. })
+        ));
+    }
+
+    #[test]
+    fn test_command_envelope_accepts_office_shape() {
+        let req: CommandIssueRequest = serde_json::from_value(serde_json::json!({
+            "jti": "jti-1",
+            "tenant_id": "T.UBL",
+            "jobId": "job-1",
+            "jobType": "service.restart",
+            "params": {"name": "minio"},
+            "permit": {"jti": "jti-1", "sig": "ed25519:x"},
+            "target": "LAB_512",
+            "office_id": "office"
+        }))
+        .unwrap();
+        assert_eq!(req.permit_jti, "jti-1");
+        assert_eq!(req.job_type, "service.restart");
+
+        // jobType and params are required
+        assert!(serde_json::from_value::<CommandIssueRequest>(serde_json::json!({"permit_jti": "jti-1"})).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Container manifests — the rules of a container in one document
+//!
+//! A client building a commit needs to know what the container accepts
+//! before it signs: the policy it is bound to, its physics, the asset its
+//! deltas count and which intents need a pact. The manifest assembles that
+//! from:
+//!
+//! - the genesis entry (first ledger entry of the container);
+//! - the `evolution.container_manifest` in force (allowed intent classes,
+//!   description, `max_atom_bytes`, asset), from the evolution changelog;
+//!   without one every intent class is allowed;
+//! - the policy the registry binds to the container (id, version, hash of
+//!   the compiled bytecode);
+//! - the pact rules of `pact_db::requires_pact`;
+//! - the `evolution.feature_flags` in force (see `feature_flags`), so a flag
+//!   flip changes the hash like any other rule.
+//!
+//! Endpoint:
+//! - GET /v1/containers/:id/manifest → the manifest; `ETag` is its hash and
+//!   `If-None-Match` answers `304`
+//!
+//! `manifest_hash` is the BLAKE3 of the canonical manifest without that
+//! field. Permits whose target is a container carry it (see `console_v1`),
+//! so a client can check it built against the rules the permit was issued
+//! under.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::feature_flags::{self, FlagSet};
+use crate::messenger_v1::etag_matches;
+use crate::pact_db::requires_pact;
+use crate::policy_registry::PolicyRegistry;
+use crate::projections::ChangelogProjection;
+
+/// Atom type of a manifest evolution
+pub const MANIFEST_TYPE: &str = "evolution.container_manifest";
+
+/// Intent classes of a container without a manifest evolution
+const ALL_INTENT_CLASSES: [&str; 4] = ["Observation", "Conservation", "Entropy", "Evolution"];
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerManifest {
+    pub container_id: String,
+    /// First entry of the container; `None` until something is committed
+    pub genesis: Option<GenesisEntry>,
+    /// `None`: no policy bound, the permissive default applies
+    pub policy: Option<BoundPolicy>,
+    pub physics: Physics,
+    pub intent_classes: Vec<String>,
+    pub required_pacts: Vec<PactRule>,
+    pub description: Option<String>,
+    pub max_atom_bytes: Option<u64>,
+    /// Entry of the manifest evolution in force; `None` for the defaults
+    pub evolution_entry_hash: Option<String>,
+    /// Flags in force; a flag that is absent is off
+    pub feature_flags: BTreeMap<String, bool>,
+    /// Entry of the flag set in force; `None` when none was committed
+    pub flags_entry_hash: Option<String>,
+    pub manifest_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GenesisEntry {
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BoundPolicy {
+    pub policy_id: String,
+    pub version: String,
+    /// Hash of the compiled bytecode the VM evaluates
+    pub hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Physics {
+    /// `observation` (every delta is 0) or `conservation` (balances never go
+    /// negative; Entropy mints and burns)
+    pub mode: String,
+    /// Unit `physics_delta` counts, when the manifest names one
+    pub asset: Option<String>,
+}
+
+/// When an intent class needs a pact: `always` or `nonzero_delta`
+#[derive(Debug, Clone, Serialize)]
+pub struct PactRule {
+    pub intent_class: String,
+    pub when: String,
+}
+
+impl ContainerManifest {
+    /// Build from its sources; `manifest` is the `manifest` object of the
+    /// evolution in force
+    fn build(
+        container_id: &str,
+        genesis: Option<GenesisEntry>,
+        policy: Option<BoundPolicy>,
+        manifest: Option<(&Value, String)>,
+        flags: FlagSet,
+    ) -> Self {
+        let (fields, evolution_entry_hash) = match manifest {
+            Some((fields, entry_hash)) => (fields.clone(), Some(entry_hash)),
+            None => (Value::Null, None),
+        };
+        let intent_classes: Vec<String> = match fields["intent_classes"].as_array() {
+            Some(classes) => classes.iter().filter_map(|c| c.as_str().map(String::from)).collect(),
+            None => ALL_INTENT_CLASSES.iter().map(|c| c.to_string()).collect(),
+        };
+        let moves_value = intent_classes.iter().any(|c| c == "Conservation" || c == "Entropy");
+        let required_pacts = intent_classes
+            .iter()
+            .filter_map(|class| {
+                let when = match (requires_pact(class, 0), requires_pact(class, 1)) {
+                    (true, _) => "always",
+                    (false, true) => "nonzero_delta",
+                    (false, false) => return None,
+                };
+                Some(PactRule { intent_class: class.clone(), when: when.into() })
+            })
+            .collect();
+
+        let mut manifest = Self {
+            container_id: container_id.to_string(),
+            genesis,
+            policy,
+            physics: Physics {
+                mode: if moves_value { "conservation" } else { "observation" }.into(),
+                asset: fields["asset"].as_str().map(String::from),
+            },
+            intent_classes,
+            required_pacts,
+            description: fields["description"].as_str().map(String::from),
+            max_atom_bytes: fields["max_atom_bytes"].as_u64(),
+            evolution_entry_hash,
+            feature_flags: flags.flags,
+            flags_entry_hash: flags.entry_hash,
+            manifest_hash: String::new(),
+        };
+        manifest.manifest_hash = manifest.compute_hash();
+        manifest
+    }
+
+    /// BLAKE3 of the canonical manifest without `manifest_hash`
+    fn compute_hash(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("manifest serializes");
+        if let Some(map) = value.as_object_mut() {
+            map.remove("manifest_hash");
+        }
+        let hash = ubl_atom::atom_hash(&value).expect("manifest canonicalizes");
+        format!("blake3:{}", hash)
+    }
+
+    /// Strong ETag over the manifest hash
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.manifest_hash)
+    }
+}
+
+/// Manifest of `container_id`; `None` when the ledger, the policy registry
+/// and the changelog know nothing of it
+pub async fn assemble(
+    pool: &PgPool,
+    registry: &PolicyRegistry,
+    container_id: &str,
+) -> Result<Option<ContainerManifest>, sqlx::Error> {
+    let genesis = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT entry_hash, ts_unix_ms
+        FROM ledger_entry
+        WHERE container_id = $1
+        ORDER BY sequence ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(container_id)
+    .fetch_optional(pool)
+    .await?
+    .map(|(entry_hash, ts_unix_ms)| GenesisEntry { entry_hash, ts_unix_ms });
+
+    let policy = registry.bound_policy(container_id).await.map(|compiled| BoundPolicy {
+        policy_id: compiled.policy_id.clone(),
+        version: compiled.version.clone(),
+        hash: compiled.hash.clone(),
+    });
+
+    let evolution = ChangelogProjection::new(pool.clone())
+        .latest(container_id, MANIFEST_TYPE, "manifest")
+        .await?;
+
+    let flags = feature_flags::load(pool, container_id).await?;
+
+    if genesis.is_none() && policy.is_none() && evolution.is_none() && flags.entry_hash.is_none() {
+        return Ok(None);
+    }
+    let manifest = evolution.as_ref().map(|e| (&e.atom["manifest"], e.entry_hash.clone()));
+    Ok(Some(ContainerManifest::build(container_id, genesis, policy, manifest, flags)))
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+#[derive(Clone)]
+struct ManifestState {
+    pool: PgPool,
+    registry: Arc<PolicyRegistry>,
+}
+
+pub fn routes(pool: PgPool, registry: Arc<PolicyRegistry>) -> Router {
+    Router::new()
+        .route("/v1/containers/:id/manifest", get(get_manifest))
+        .with_state(ManifestState { pool, registry })
+}
+
+/// GET /v1/containers/:id/manifest
+async fn get_manifest(
+    State(state): State<ManifestState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let manifest = assemble(&state.pool, &state.registry, &container_id)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))?
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Container not found: {}", container_id)))?;
+
+    let etag = manifest.etag();
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+    if let Some(inm) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        if etag_matches(inm, &etag) {
+            return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+        }
+    }
+    Ok((cache_headers, Json(manifest)).into_response())
+}
+
+/// Response samples pinned by the API contract tests (see `crate::contracts`)
+#[cfg(test)]
+pub(crate) fn contract_samples() -> Vec<(&'static str, Value)> {
+    vec![("GET /v1/containers/:id/manifest", serde_json::json!(sample()))]
+}
+
+#[cfg(test)]
+fn sample() -> ContainerManifest {
+    let manifest = serde_json::json!({
+        "intent_classes": ["Observation", "Entropy"],
+        "description": "Job ledger",
+        "max_atom_bytes": 65536,
+        "asset": "credits",
+    });
+    ContainerManifest::build(
+        "C.Jobs",
+        Some(GenesisEntry { entry_hash: "g".into(), ts_unix_ms: 1 }),
+        Some(BoundPolicy { policy_id: "default_C.Jobs".into(), version: "1.0".into(), hash: "p".into() }),
+        Some((&manifest, "e".into())),
+        FlagSet {
+            container_id: "C.Jobs".into(),
+            flags: BTreeMap::from([("priority_queue".to_string(), true)]),
+            entry_hash: Some("f".into()),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_from_evolution() {
+        let manifest = sample();
+        assert_eq!(manifest.intent_classes, ["Observation", "Entropy"]);
+        assert_eq!(manifest.physics.mode, "conservation");
+        assert_eq!(manifest.physics.asset.as_deref(), Some("credits"));
+        assert_eq!(manifest.max_atom_bytes, Some(65536));
+        let pacts: Vec<(&str, &str)> =
+            manifest.required_pacts.iter().map(|p| (p.intent_class.as_str(), p.when.as_str())).collect();
+        assert_eq!(pacts, [("Entropy", "nonzero_delta")]);
+    }
+
+    #[test]
+    fn test_defaults_without_manifest_evolution() {
+        let manifest = ContainerManifest::build("C.New", None, None, None, FlagSet::default());
+        assert_eq!(manifest.intent_classes, ALL_INTENT_CLASSES);
+        assert_eq!(manifest.physics.mode, "conservation");
+        assert!(manifest.evolution_entry_hash.is_none());
+        assert!(manifest.feature_flags.is_empty());
+
+        let observe = serde_json::json!({ "intent_classes": ["Observation"] });
+        let manifest = ContainerManifest::build("C.Log", None, None, Some((&observe, "e".into())), FlagSet::default());
+        assert_eq!(manifest.physics.mode, "observation");
+        assert!(manifest.required_pacts.is_empty());
+    }
+
+    #[test]
+    fn test_hash_covers_every_rule() {
+        let manifest = sample();
+        assert!(manifest.manifest_hash.starts_with("blake3:"));
+        assert_eq!(manifest.manifest_hash, sample().manifest_hash);
+        assert_eq!(manifest.etag(), format!("\"{}\"", manifest.manifest_hash));
+
+        let mut rebound = sample();
+        rebound.policy.as_mut().unwrap().hash = "q".into();
+        assert_ne!(rebound.compute_hash(), manifest.manifest_hash);
+
+        let mut flipped = sample();
+        flipped.feature_flags.insert("priority_queue".into(), false);
+        assert_ne!(flipped.compute_hash(), manifest.manifest_hash);
+    }
+}
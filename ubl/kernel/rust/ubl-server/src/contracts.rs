@@ -0,0 +1,173 @@
+//! HTTP API contracts
+//!
+//! Response shapes the TS frontend depends on are pinned as JSON schema
+//! snapshots under `ubl-server/contracts/`. The contract tests serialize a
+//! sample of every response type, derive its schema and fail when it no
+//! longer matches the snapshot while `API_VERSION` is unchanged.
+//!
+//! To change a shape on purpose: bump `API_VERSION`, then re-pin with
+//! `UPDATE_CONTRACTS=1 cargo test -p ubl-server contracts`.
+
+use axum::{http::HeaderValue, response::Response};
+
+/// Version of the HTTP response contracts; bump on any shape change
+pub const API_VERSION: u32 = 5;
+
+/// Response header carrying `API_VERSION`
+pub const API_VERSION_HEADER: &str = "x-ubl-api-version";
+
+/// Stamp every response with the contract version so clients can detect skew
+pub async fn stamp_api_version(mut response: Response) -> Response {
+    response
+        .headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from(API_VERSION));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::path::PathBuf;
+    use ubl_errors::{ApiErrorBody, ErrorCode};
+
+    use crate::db::LedgerEntry;
+    use crate::{CommitSuccess, StateResponse};
+
+    /// Samples for the routes defined in main.rs, plus the shared error body
+    fn root_samples() -> Vec<(&'static str, Value)> {
+        vec![
+            (
+                "POST /link/commit",
+                json!(CommitSuccess {
+                    ok: true,
+                    entry: LedgerEntry {
+                        container_id: "C.Messenger".into(),
+                        sequence: 1,
+                        link_hash: "ab".into(),
+                        previous_hash: "0x00".into(),
+                        entry_hash: "cd".into(),
+                        ts_unix_ms: 1,
+                        hlc: crate::hlc::Hlc::new(1, 0),
+                    },
+                    tentative_id: Some("tmp_1".into()),
+                }),
+            ),
+            ("POST /link/dry-run", json!(crate::dry_run::sample())),
+            (
+                "GET /state/:container_id",
+                json!(StateResponse {
+                    container_id: "C.Messenger".into(),
+                    sequence: 1,
+                    last_hash: "cd".into(),
+                    entry_count: 1,
+                    entropy_minted: "10".into(),
+                    entropy_burned: "4".into(),
+                }),
+            ),
+            ("ERROR *", json!(ApiErrorBody::new(ErrorCode::NotFound, "not found"))),
+        ]
+    }
+
+    fn samples() -> Vec<(&'static str, Value)> {
+        let mut all = root_samples();
+        all.extend(crate::projections::routes::contract_samples());
+        all.extend(crate::id_routes::contract_samples());
+        all.extend(crate::container_manifest::contract_samples());
+        all.extend(crate::messenger_gateway::routes::contract_samples());
+        all
+    }
+
+    /// Structural schema of a sample: types and property names, not values
+    fn schema_of(value: &Value) -> Value {
+        match value {
+            Value::Null => json!({ "type": "null" }),
+            Value::Bool(_) => json!({ "type": "boolean" }),
+            Value::Number(n) if n.is_f64() => json!({ "type": "number" }),
+            Value::Number(_) => json!({ "type": "integer" }),
+            Value::String(_) => json!({ "type": "string" }),
+            Value::Array(items) => match items.first() {
+                Some(first) => json!({ "type": "array", "items": schema_of(first) }),
+                None => json!({ "type": "array" }),
+            },
+            Value::Object(map) if map.is_empty() => json!({ "type": "object" }),
+            Value::Object(map) => json!({
+                "type": "object",
+                "properties": map.iter().map(|(k, v)| (k.clone(), schema_of(v))).collect::<serde_json::Map<_, _>>(),
+            }),
+        }
+    }
+
+    fn contracts_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("contracts")
+    }
+
+    /// `GET /query/jobs/:job_id` → `get_query_jobs_job_id.schema.json`
+    fn contract_file(endpoint: &str) -> String {
+        let slug: String = endpoint
+            .to_ascii_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("_");
+        format!("{}.schema.json", slug)
+    }
+
+    #[test]
+    fn test_schema_of_ignores_values() {
+        let a = schema_of(&json!({ "id": "a", "n": 1, "xs": [1.5], "meta": {} }));
+        let b = schema_of(&json!({ "id": "b", "n": 9, "xs": [0.5, 2.5], "meta": {} }));
+        assert_eq!(a, b);
+        assert_ne!(a, schema_of(&json!({ "id": "a", "n": "1", "xs": [1.5], "meta": {} })));
+        assert_eq!(contract_file("GET /query/jobs/:job_id"), "get_query_jobs_job_id.schema.json");
+    }
+
+    #[test]
+    fn test_responses_match_pinned_contracts() {
+        let update = std::env::var_os("UPDATE_CONTRACTS").is_some();
+        let dir = contracts_dir();
+        let mut failures = Vec::new();
+        let mut expected_files = Vec::new();
+
+        for (endpoint, sample) in samples() {
+            let file = contract_file(endpoint);
+            let path = dir.join(&file);
+            expected_files.push(file);
+
+            let schema = schema_of(&sample);
+            let pinned: Option<Value> = std::fs::read_to_string(&path)
+                .ok()
+                .map(|text| serde_json::from_str(&text).expect("contract snapshot is valid JSON"));
+            let pinned_version = pinned.as_ref().and_then(|p| p["api_version"].as_u64());
+
+            match pinned {
+                Some(p) if p["schema"] == schema => continue,
+                Some(_) if pinned_version == Some(API_VERSION as u64) => failures.push(format!(
+                    "{}: response shape changed without bumping API_VERSION ({})",
+                    endpoint, API_VERSION
+                )),
+                _ if update => {
+                    let snapshot = json!({
+                        "endpoint": endpoint,
+                        "api_version": API_VERSION,
+                        "schema": schema,
+                    });
+                    std::fs::create_dir_all(&dir).unwrap();
+                    std::fs::write(&path, serde_json::to_string_pretty(&snapshot).unwrap() + "\n").unwrap();
+                }
+                Some(_) => failures.push(format!("{}: shape changed for new API_VERSION; re-pin with UPDATE_CONTRACTS=1", endpoint)),
+                None => failures.push(format!("{}: no contract pinned; run with UPDATE_CONTRACTS=1", endpoint)),
+            }
+        }
+
+        // A pinned endpoint that disappeared is a breaking change too
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".schema.json") && !expected_files.contains(&name) {
+                failures.push(format!("{}: pinned contract has no response sample (endpoint removed?)", name));
+            }
+        }
+
+        assert!(failures.is_empty(), "API contract drift:\n  {}", failures.join("\n  "));
+    }
+}
@@ -6,6 +6,7 @@ use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use blake3::Hasher;
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use rand::RngCore;
+use serde::Serialize;
 use sqlx::PgPool;
 
 // =============================================================================
@@ -48,10 +49,19 @@ pub fn sign_admin_permit(msg: &[u8]) -> Vec<u8> {
 
 /// Verify an admin permit signature
 /// Format: "ed25519:<base64url_signature>"
+///
+/// Permits signed just before an admin key rotation remain valid: the
+/// previous key is tried when the current one does not verify.
 pub fn verify_admin_permit_sig(msg: &[u8], sig_tagged: &str) -> Result<(), &'static str> {
     let pubkey_hex = admin_pubkey_hex();
-    crate::keystore::verify(&pubkey_hex, msg, sig_tagged)
-        .map_err(|_| "SignatureVerifyFailed")
+    if crate::keystore::verify(&pubkey_hex, msg, sig_tagged).is_ok() {
+        return Ok(());
+    }
+    match crate::keystore::previous_public_key_hex("admin") {
+        Some(prev) => crate::keystore::verify(&prev, msg, sig_tagged)
+            .map_err(|_| "SignatureVerifyFailed"),
+        None => Err("SignatureVerifyFailed"),
+    }
 }
 
 // =============================================================================
@@ -145,6 +155,72 @@ pub fn canonical_plan_hash(plan_json: &serde_json::Value) -> String {
     format!("blake3:{}", blake3::hash(&bytes).to_hex())
 }
 
+// =============================================================================
+// PERMIT CLAIMS (signed by the admin key)
+// =============================================================================
+
+/// Version of the signed claims layout; bump when fields change
+pub const PERMIT_CLAIMS_VERSION: u32 = 1;
+
+/// The parts of a Permit the admin signature covers.
+///
+/// `binding_hash` already commits to office, action, target, args, risk,
+/// plan, nonce and expiry; the claims add what a verifier outside UBL needs
+/// to check on its own: who the permit is for (`aud`), which issuance policy
+/// produced it, when it was issued and the nonce to detect replays.
+/// Office recomputes these bytes in `middleware::permit` — keep both in step.
+#[derive(Debug, Serialize)]
+pub struct PermitClaims<'a> {
+    pub v: u32,
+    pub jti: &'a str,
+    pub aud: &'a str,
+    pub action: &'a str,
+    pub target: &'a str,
+    pub binding_hash: &'a str,
+    pub policy_hash: &'a str,
+    pub nonce: &'a str,
+    pub iat_ms: i64,
+    pub exp_ms: i64,
+}
+
+/// Canonical (Json✯Atomic) bytes of the claims; this is what gets signed
+pub fn permit_claims_bytes(claims: &PermitClaims) -> Vec<u8> {
+    let value = serde_json::to_value(claims).expect("permit claims serialize");
+    ubl_atom::canonicalize(&value).expect("permit claims are strings and integers")
+}
+
+// =============================================================================
+// ASC SIGNATURES (signed by the admin key)
+// =============================================================================
+
+/// Canonical bytes of an Agent Signing Certificate; what the admin key signs
+/// (v2, `contexts::ASC`). Times are Unix seconds.
+pub fn asc_signing_bytes(
+    sid: &str,
+    public_key: &[u8],
+    scopes: &serde_json::Value,
+    not_before: i64,
+    not_after: i64,
+) -> Vec<u8> {
+    let value = serde_json::json!({
+        "sid": sid,
+        "public_key": hex::encode(public_key),
+        "scopes": scopes,
+        "not_before": not_before,
+        "not_after": not_after,
+    });
+    ubl_atom::canonicalize(&value).expect("ASC claims canonicalize")
+}
+
+/// Sign ASC bytes with the current admin key (raw 64-byte signature)
+pub fn sign_asc(msg: &[u8]) -> Vec<u8> {
+    let key = crate::keystore::load_or_create("admin");
+    ubl_kernel::sign_with(ubl_kernel::SignatureMode::Ed25519ph, &key, ubl_kernel::contexts::ASC, msg)
+        .ok()
+        .and_then(|sig| hex::decode(sig).ok())
+        .unwrap_or_default()
+}
+
 // =============================================================================
 // UBL-ATOM COMPATIBILITY
 // =============================================================================
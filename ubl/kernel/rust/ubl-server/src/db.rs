@@ -6,9 +6,25 @@
 use blake3::Hasher;
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Postgres, Row, Transaction};
-use time::OffsetDateTime;
+use lazy_static::lazy_static;
+use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use std::borrow::Cow;
 use tracing::{info, warn};
 
+use crate::hlc::Hlc;
+
+lazy_static! {
+    pub static ref ATOM_DEDUP_HITS: IntCounter = register_int_counter!(
+        "ubl_atom_dedup_hits_total",
+        "Commits whose atom was already stored (content-addressed)"
+    ).unwrap();
+
+    pub static ref ATOM_DEDUP_SAVED_BYTES: IntGauge = register_int_gauge!(
+        "ubl_atom_dedup_saved_bytes",
+        "Atom bytes not stored again thanks to deduplication, over the whole ledger"
+    ).unwrap();
+}
+
 // Helper trait for getting columns by name (local to this module to avoid conflicts)
 trait DbRowExt {
     fn get_col<T>(&self, col: &str) -> T 
@@ -43,6 +59,11 @@ pub struct LinkDraft {
     /// Pact proof (required for Entropy with delta≠0 and Evolution)
     #[serde(default)]
     pub pact: Option<PactProofDraft>,
+    /// Client-local id of the optimistic UI item this link settles.
+    /// Not signed and never stored in the ledger; echoed in the commit
+    /// response, the SSE tail and projection rows for reconciliation.
+    #[serde(default)]
+    pub tentative_id: Option<String>,
 }
 
 /// Pact proof in link draft
@@ -66,7 +87,10 @@ pub struct LedgerEntry {
     pub link_hash: String,
     pub previous_hash: String,
     pub entry_hash: String,
+    /// Wall clock of the appending replica (part of `entry_hash`)
     pub ts_unix_ms: i64,
+    /// Hybrid logical clock, for ordering across containers (see `hlc`)
+    pub hlc: Hlc,
 }
 
 #[derive(Debug)]
@@ -78,10 +102,34 @@ pub enum TangencyError {
     PactViolation(String),
     /// Fix #12: Serialization conflict (SQLSTATE 40001) - can be retried
     SerializationConflict,
+    /// Container failed the startup chain self-check (see `chain_check`)
+    Quarantined,
     /// General database error
     DatabaseError(String),
 }
 
+/// `previous_hash` of a container's first entry
+pub const GENESIS_PREVIOUS_HASH: &str = "0x00";
+
+/// Compute entry_hash per SPEC-UBL-LEDGER v1.0 §5.1
+/// entry_hash := BLAKE3("ubl:ledger\n" || container_id || sequence || link_hash || previous_hash || timestamp)
+pub fn compute_entry_hash(
+    container_id: &str,
+    sequence: i64,
+    link_hash: &str,
+    previous_hash: &str,
+    ts_unix_ms: i64,
+) -> String {
+    let mut h = Hasher::new();
+    h.update(b"ubl:ledger\n"); // Domain tag per SPEC-UBL-LEDGER v1.0 §5.1
+    h.update(container_id.as_bytes());
+    h.update(&sequence.to_be_bytes()); // Big-endian per spec
+    h.update(link_hash.as_bytes());
+    h.update(previous_hash.as_bytes());
+    h.update(&ts_unix_ms.to_be_bytes()); // Big-endian for consistency
+    hex::encode(h.finalize().as_bytes())
+}
+
 #[derive(Clone)]
 pub struct PgLedger {
     pool: PgPool,
@@ -98,6 +146,11 @@ impl PgLedger {
     /// Fix #12: Retries up to 3 times on serialization conflict (SQLSTATE 40001)
     pub async fn append(&self, link: &LinkDraft) -> Result<LedgerEntry, TangencyError> {
         const MAX_RETRIES: u32 = 3;
+
+        if crate::chain_check::is_quarantined(&link.container_id) {
+            warn!("🚫 Append refused: {} failed the chain self-check", link.container_id);
+            return Err(TangencyError::Quarantined);
+        }
         
         for attempt in 1..=MAX_RETRIES {
             match self.try_append(link).await {
@@ -120,6 +173,17 @@ impl PgLedger {
 
     /// Internal append attempt - may fail with SerializationConflict
     async fn try_append(&self, link: &LinkDraft) -> Result<LedgerEntry, TangencyError> {
+        // Encrypted containers store a sealed envelope (see atom_crypto)
+        let stored_atom = match &link.atom {
+            Some(atom) if crate::atom_crypto::is_encrypted(&link.container_id) => Some(Cow::Owned(
+                crate::atom_crypto::seal_atom(&self.pool, &link.container_id, &link.atom_hash, atom)
+                    .await
+                    .map_err(|e| TangencyError::DatabaseError(format!("atom encryption failed: {}", e)))?,
+            )),
+            Some(atom) => Some(Cow::Borrowed(atom)),
+            None => None,
+        };
+
         // Begin SERIALIZABLE transaction
         let mut tx: Transaction<Postgres> = self
             .pool
@@ -141,7 +205,7 @@ impl PgLedger {
         // Lock and get latest entry (FOR UPDATE)
         let rec: Option<sqlx::postgres::PgRow> = sqlx::query(
             r#"
-            SELECT sequence, entry_hash
+            SELECT sequence, entry_hash, ts_unix_ms, hlc_wall_ms, hlc_logical
             FROM ledger_entry
             WHERE container_id = $1
             ORDER BY sequence DESC
@@ -154,13 +218,14 @@ impl PgLedger {
         .await
         .map_err(|e| Self::classify_error(e))?;
 
-        let (expected_prev, expected_seq) = match rec {
+        let (expected_prev, expected_seq, prev_hlc) = match rec {
             Some(r) => {
                 let entry_hash: String = r.get_col("entry_hash");
                 let sequence: i64 = r.get_col("sequence");
-                (entry_hash, sequence + 1)
+                let hlc = Hlc::stored(r.get_col("hlc_wall_ms"), r.get_col("hlc_logical"), r.get_col("ts_unix_ms"));
+                (entry_hash, sequence + 1, Some(hlc))
             },
-            None => ("0x00".to_string(), 1),
+            None => (GENESIS_PREVIOUS_HASH.to_string(), 1, None),
         };
 
         // Validate causality (SPEC-UBL-MEMBRANE v1.0 §V4)
@@ -174,29 +239,20 @@ impl PgLedger {
         }
 
         // Validate version (SPEC-UBL-MEMBRANE v1.0 §V1)
-        if link.version != 1 {
+        if ubl_kernel::SignatureMode::for_version(link.version).is_none() {
             return Err(TangencyError::InvalidVersion);
         }
 
-        // Compute entry_hash per SPEC-UBL-LEDGER v1.0 §5.1
-        // entry_hash := BLAKE3("ubl:ledger\n" || container_id || sequence || link_hash || previous_hash || timestamp)
-        let ts_unix_ms = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64;
-        let mut h = Hasher::new();
-        h.update(b"ubl:ledger\n"); // Domain tag per SPEC-UBL-LEDGER v1.0 §5.1
-        h.update(link.container_id.as_bytes());
-        let seq_bytes: [u8; 8] = expected_seq.to_be_bytes();
-        h.update(&seq_bytes); // Big-endian per spec
-        h.update(link.atom_hash.as_bytes()); // link_hash = atom_hash reference
-        h.update(expected_prev.as_bytes());
-        let ts_bytes: [u8; 8] = ts_unix_ms.to_be_bytes();
-        h.update(&ts_bytes); // Big-endian for consistency
-        let entry_hash = hex::encode(h.finalize().as_bytes());
+        // link_hash = atom_hash reference
+        let ts_unix_ms = crate::timestamps::now_ms();
+        let entry_hash = compute_entry_hash(&link.container_id, expected_seq, &link.atom_hash, &expected_prev, ts_unix_ms);
+        let hlc = crate::hlc::tick(prev_hlc);
 
         // Insert new entry (SPEC-UBL-LEDGER v1.0 §7.1 - Append-only)
         sqlx::query(
             r#"
-            INSERT INTO ledger_entry (container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6, '{}'::jsonb)
+            INSERT INTO ledger_entry (container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms, hlc_wall_ms, hlc_logical, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, '{}'::jsonb)
             "#,
         )
         .bind(&link.container_id)
@@ -205,12 +261,35 @@ impl PgLedger {
         .bind(&expected_prev)
         .bind(&entry_hash)
         .bind(ts_unix_ms)
+        .bind(hlc.wall_ms)
+        .bind(hlc.logical)
         .execute(&mut *tx)
         .await
         .map_err(|e| Self::classify_error(e))?;
 
-        // Store atom data for projections (if provided)
-        if let Some(ref atom_data) = link.atom {
+        // Spend a use of a usage-limited pact in this same transaction, so
+        // racing commits cannot both take the last one (SPEC-UBL-PACT v1.0)
+        let delta: i128 = link.physics_delta.parse().unwrap_or(0);
+        if let Some(pact) = link.pact.as_ref().filter(|_| crate::pact_db::requires_pact(&link.intent_class, delta)) {
+            use crate::pact_db::PactUse;
+            match crate::pact_db::consume_use(&mut tx, &pact.pact_id, &link.container_id, expected_seq, &link.atom_hash, ts_unix_ms)
+                .await
+                .map_err(Self::classify_error)?
+            {
+                PactUse::Unlimited | PactUse::Spent(_) => {}
+                PactUse::Exhausted => {
+                    return Err(TangencyError::PactViolation(format!("pact {} has no uses left", pact.pact_id)));
+                }
+                PactUse::UnknownPact => {
+                    return Err(TangencyError::PactViolation(format!("unknown pact {}", pact.pact_id)));
+                }
+            }
+        }
+
+        // Store atom data for projections (if provided); identical atoms are
+        // stored once and reference-counted (ledger_atom_refs)
+        let mut dedup_saved = None;
+        if let Some(atom_data) = stored_atom {
             sqlx::query(
                 r#"
                 INSERT INTO ledger_atom (atom_hash, container_id, atom_data, ts_unix_ms)
@@ -220,17 +299,41 @@ impl PgLedger {
             )
             .bind(&link.atom_hash)
             .bind(&link.container_id)
-            .bind(atom_data)
+            .bind(atom_data.as_ref())
             .bind(ts_unix_ms)
             .execute(&mut *tx)
             .await
             .map_err(|e| Self::classify_error(e))?;
+
+            let (ref_count, size_bytes): (i64, i64) = sqlx::query_as(
+                r#"
+                INSERT INTO ledger_atom_refs (atom_hash, ref_count, size_bytes, first_ref_ms, last_ref_ms)
+                SELECT atom_hash, 1, octet_length(atom_data::text), $2, $2
+                FROM ledger_atom WHERE atom_hash = $1
+                ON CONFLICT (atom_hash) DO UPDATE SET
+                    ref_count = ledger_atom_refs.ref_count + 1,
+                    last_ref_ms = EXCLUDED.last_ref_ms
+                RETURNING ref_count, size_bytes
+                "#,
+            )
+            .bind(&link.atom_hash)
+            .bind(ts_unix_ms)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(Self::classify_error)?;
+            if ref_count > 1 {
+                dedup_saved = Some(size_bytes);
+            }
         }
 
         // Commit transaction
         tx.commit().await.map_err(|e| Self::classify_error(e))?;
 
         info!("✅ Ledger append: {} seq={}", link.container_id, expected_seq);
+        if let Some(size_bytes) = dedup_saved {
+            ATOM_DEDUP_HITS.inc();
+            ATOM_DEDUP_SAVED_BYTES.add(size_bytes);
+        }
 
         Ok(LedgerEntry {
             container_id: link.container_id.clone(),
@@ -239,6 +342,72 @@ impl PgLedger {
             previous_hash: expected_prev,
             entry_hash,
             ts_unix_ms,
+            hlc,
+        })
+    }
+
+    /// The entry `append` would write for `link` right now, checked against
+    /// the current head with the same tangency errors. Read-only: no lock is
+    /// taken, no pact use is spent, and a concurrent commit can still move
+    /// the head before the real append.
+    pub async fn preview(&self, link: &LinkDraft) -> Result<LedgerEntry, TangencyError> {
+        if crate::chain_check::is_quarantined(&link.container_id) {
+            return Err(TangencyError::Quarantined);
+        }
+
+        let head: Option<(i64, String, i64, Option<i64>, Option<i32>)> = sqlx::query_as(
+            r#"
+            SELECT sequence, entry_hash, ts_unix_ms, hlc_wall_ms, hlc_logical
+            FROM ledger_entry WHERE container_id = $1 ORDER BY sequence DESC LIMIT 1
+            "#,
+        )
+        .bind(&link.container_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(Self::classify_error)?;
+        let (expected_prev, expected_seq, prev_hlc) = match head {
+            Some((sequence, entry_hash, ts_unix_ms, hlc_wall_ms, hlc_logical)) => {
+                (entry_hash, sequence + 1, Some(Hlc::stored(hlc_wall_ms, hlc_logical, ts_unix_ms)))
+            }
+            None => (GENESIS_PREVIOUS_HASH.to_string(), 1, None),
+        };
+
+        if link.previous_hash != expected_prev {
+            return Err(TangencyError::RealityDrift);
+        }
+        if link.expected_sequence != expected_seq {
+            return Err(TangencyError::SequenceMismatch);
+        }
+        if ubl_kernel::SignatureMode::for_version(link.version).is_none() {
+            return Err(TangencyError::InvalidVersion);
+        }
+
+        // A usage-limited pact must still have a use to spend
+        let delta: i128 = link.physics_delta.parse().unwrap_or(0);
+        if let Some(pact) = link.pact.as_ref().filter(|_| crate::pact_db::requires_pact(&link.intent_class, delta)) {
+            let uses: Option<(i32, Option<i32>)> = sqlx::query_as("SELECT uses, max_uses FROM pact WHERE pact_id = $1")
+                .bind(&pact.pact_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(Self::classify_error)?;
+            match uses {
+                None => return Err(TangencyError::PactViolation(format!("unknown pact {}", pact.pact_id))),
+                Some((uses, Some(max_uses))) if uses >= max_uses => {
+                    return Err(TangencyError::PactViolation(format!("pact {} has no uses left", pact.pact_id)));
+                }
+                Some(_) => {}
+            }
+        }
+
+        let ts_unix_ms = crate::timestamps::now_ms();
+        Ok(LedgerEntry {
+            container_id: link.container_id.clone(),
+            sequence: expected_seq,
+            link_hash: link.atom_hash.clone(),
+            entry_hash: compute_entry_hash(&link.container_id, expected_seq, &link.atom_hash, &expected_prev, ts_unix_ms),
+            previous_hash: expected_prev,
+            ts_unix_ms,
+            hlc: crate::hlc::peek(prev_hlc),
         })
     }
 
@@ -256,11 +425,22 @@ impl PgLedger {
         TangencyError::DatabaseError(e.to_string())
     }
 
+    /// Load the ledger-wide deduplication savings into `ubl_atom_dedup_saved_bytes`
+    pub async fn load_dedup_savings(&self) -> Result<i64, sqlx::Error> {
+        let saved: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM((ref_count - 1) * size_bytes), 0)::BIGINT FROM ledger_atom_refs",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        ATOM_DEDUP_SAVED_BYTES.set(saved);
+        Ok(saved)
+    }
+
     /// Get current state of container
     pub async fn get_state(&self, container_id: &str) -> Result<LedgerEntry, sqlx::Error> {
         let rec: Option<sqlx::postgres::PgRow> = sqlx::query(
             r#"
-            SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms
+            SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms, hlc_wall_ms, hlc_logical
             FROM ledger_entry
             WHERE container_id = $1
             ORDER BY sequence DESC
@@ -279,8 +459,79 @@ impl PgLedger {
                 previous_hash: r.get_col("previous_hash"),
                 entry_hash: r.get_col("entry_hash"),
                 ts_unix_ms: r.get_col("ts_unix_ms"),
+                hlc: Hlc::stored(r.get_col("hlc_wall_ms"), r.get_col("hlc_logical"), r.get_col("ts_unix_ms")),
             }),
             None => Err(sqlx::Error::RowNotFound),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation_link(container_id: &str, sequence: i64, previous_hash: String, atom: &serde_json::Value) -> LinkDraft {
+        LinkDraft {
+            version: 1,
+            container_id: container_id.to_string(),
+            expected_sequence: sequence,
+            previous_hash,
+            atom_hash: blake3::hash(atom.to_string().as_bytes()).to_hex().to_string(),
+            intent_class: "Observation".to_string(),
+            physics_delta: "0".to_string(),
+            author_pubkey: "cd".repeat(32),
+            signature: String::new(),
+            atom: Some(atom.clone()),
+            pact: None,
+            tentative_id: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Needs DATABASE_URL with the ubl/sql schema applied
+    async fn test_identical_atoms_are_stored_once() {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://localhost:5432/ubl_test".to_string());
+        let pool = PgPool::connect(&database_url).await.expect("Failed to connect to test database");
+        let ledger = PgLedger::new(pool.clone());
+        let run = uuid::Uuid::new_v4();
+        let snapshot = serde_json::json!({ "type": "config.snapshot", "run": run.to_string(), "limits": [1, 2, 3] });
+        let (a, b) = (format!("C.Dedup.{}.a", run), format!("C.Dedup.{}.b", run));
+
+        let first = ledger.append(&observation_link(&a, 1, GENESIS_PREVIOUS_HASH.to_string(), &snapshot)).await.unwrap();
+        let hits = ATOM_DEDUP_HITS.get();
+        ledger.append(&observation_link(&a, 2, first.entry_hash, &snapshot)).await.unwrap();
+        ledger.append(&observation_link(&b, 1, GENESIS_PREVIOUS_HASH.to_string(), &snapshot)).await.unwrap();
+        assert_eq!(ATOM_DEDUP_HITS.get() - hits, 2);
+
+        let atom_hash = first.link_hash;
+        let (stored, ref_count): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT (SELECT COUNT(*) FROM ledger_atom WHERE atom_hash = $1),
+                   (SELECT ref_count FROM ledger_atom_refs WHERE atom_hash = $1)
+            "#,
+        )
+        .bind(&atom_hash)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((stored, ref_count), (1, 3));
+
+        // Readers join through link_hash, so every entry still sees its atom
+        let atoms: Vec<Option<serde_json::Value>> = sqlx::query_scalar(
+            r#"
+            SELECT la.atom_data FROM ledger_entry le
+            LEFT JOIN ledger_atom la ON la.atom_hash = le.link_hash
+            WHERE le.container_id IN ($1, $2)
+            "#,
+        )
+        .bind(&a)
+        .bind(&b)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(atoms, vec![Some(snapshot.clone()); 3]);
+
+        assert!(ledger.load_dedup_savings().await.unwrap() >= 2 * snapshot.to_string().len() as i64);
+    }
+}
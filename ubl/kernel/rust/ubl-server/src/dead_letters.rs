@@ -0,0 +1,416 @@
+//! Dead-Letter Queue — jobs that exhausted their retries on a runner
+//!
+//! Runners report dead-lettered jobs (`ubl_runner_core::DeadLetter`) with the
+//! reason and receipt of every attempt. Admins (step-up session) inspect them,
+//! edit the payload, then requeue or purge. Requeued jobs are handed back to
+//! their runner in the next heartbeat response.
+//!
+//! Endpoints:
+//! - POST   /v1/runners/:runner_id/dead-letters        → Report (runner signature required)
+//! - GET    /v1/admin/dead-letters?status=&runner_id=  → List
+//! - GET    /v1/admin/dead-letters/:job_id             → Inspect (job + attempts)
+//! - PATCH  /v1/admin/dead-letters/:job_id             → Replace payload
+//! - POST   /v1/admin/dead-letters/:job_id/requeue     → Requeue
+//! - DELETE /v1/admin/dead-letters/:job_id             → Purge
+//!
+//! Signed message (tagged "ed25519:<base64url>", see `keystore::verify`):
+//! ```text
+//! ubl:runner:dead_letter\n<runner_id>\n<job_id>\n<blake3 of canonical dead_letter JSON>\n<ts_ms>
+//! ```
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use tracing::{info, warn};
+use ubl_errors::ErrorCode;
+use ubl_runner_core::{DeadLetter, ExecutionJob, FailedAttempt};
+
+use crate::api_error::ApiError;
+use crate::auth::session::Session;
+use crate::crypto;
+use crate::id_routes::IdState;
+use crate::timestamps::now_ms;
+
+/// Signed timestamps older or newer than this are rejected (replay window)
+const MAX_CLOCK_SKEW_MS: i64 = 5 * 60 * 1000;
+/// Dead letters listed per page
+const LIST_LIMIT: i64 = 200;
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ReportDeadLetterRequest {
+    pub dead_letter: DeadLetter,
+    pub ts_ms: i64,
+    pub sig_runner: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDeadLettersParams {
+    pub status: Option<String>,
+    pub runner_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditPayloadRequest {
+    pub payload: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadLetterSummary {
+    pub job_id: String,
+    pub runner_id: String,
+    pub job_type: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_reason: Option<String>,
+    pub dead_lettered_at_ms: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadLetterView {
+    pub job_id: String,
+    pub runner_id: String,
+    pub status: String,
+    pub job: ExecutionJob,
+    pub attempts: Vec<FailedAttempt>,
+    pub dead_lettered_at_ms: i64,
+    pub updated_at_ms: i64,
+    pub updated_by: Option<String>,
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+pub fn routes(pool: PgPool, id_state: IdState) -> Router {
+    let admin = Router::new()
+        .route("/v1/admin/dead-letters", get(list_dead_letters))
+        .route(
+            "/v1/admin/dead-letters/:job_id",
+            get(get_dead_letter).patch(edit_payload).delete(purge),
+        )
+        .route("/v1/admin/dead-letters/:job_id/requeue", post(requeue))
+        .route_layer(middleware::from_fn_with_state(id_state, crate::auth::require_stepup::require_stepup));
+
+    Router::new()
+        .route("/v1/runners/:runner_id/dead-letters", post(report_dead_letter))
+        .merge(admin)
+        .with_state(pool)
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+/// POST /v1/runners/:runner_id/dead-letters — runner reports a dead-lettered job
+async fn report_dead_letter(
+    State(pool): State<PgPool>,
+    Path(runner_id): Path<String>,
+    Json(req): Json<ReportDeadLetterRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let now_ms = now_ms();
+    if (now_ms - req.ts_ms).abs() > MAX_CLOCK_SKEW_MS {
+        return Err(ApiError::new(ErrorCode::BadRequest, "StaleRunnerMessage"));
+    }
+
+    let dead = &req.dead_letter;
+    let dead_json = serde_json::to_value(dead).map_err(|e| ApiError::new(ErrorCode::BadRequest, e.to_string()))?;
+    let digest = crypto::ubl_atom_compat::canonicalize(&dead_json)
+        .map(|bytes| blake3::hash(&bytes).to_hex().to_string())
+        .map_err(|e| ApiError::new(ErrorCode::BadRequest, format!("CanonicalizeError: {}", e)))?;
+    let msg = report_message(&runner_id, &dead.job.job_id, &digest, req.ts_ms);
+    crypto::verify_runner_sig(&pool, &runner_id, msg.as_bytes(), &req.sig_runner)
+        .await
+        .map_err(|e| match e.as_str() {
+            "RunnerNotFound" => ApiError::new(ErrorCode::NotFound, e),
+            "RunnerNotActive" => ApiError::new(ErrorCode::Forbidden, e),
+            _ => ApiError::new(ErrorCode::InvalidSignature, format!("RunnerSigInvalid: {}", e)),
+        })?;
+
+    let job_json = serde_json::to_value(&dead.job).map_err(|e| ApiError::new(ErrorCode::BadRequest, e.to_string()))?;
+    let attempts_json =
+        serde_json::to_value(&dead.attempts).map_err(|e| ApiError::new(ErrorCode::BadRequest, e.to_string()))?;
+
+    // A job that dies again after a requeue keeps its earlier attempts
+    let row = sqlx::query(
+        r#"
+        INSERT INTO runner_dead_letters
+          (job_id, runner_id, job_type, job_json, attempts_json, status, dead_lettered_at_ms, updated_at_ms)
+        VALUES ($1, $2, $3, $4, $5, 'dead', $6, $7)
+        ON CONFLICT (job_id) DO UPDATE SET
+          job_json = EXCLUDED.job_json,
+          attempts_json = runner_dead_letters.attempts_json || EXCLUDED.attempts_json,
+          status = 'dead',
+          dead_lettered_at_ms = EXCLUDED.dead_lettered_at_ms,
+          updated_at_ms = EXCLUDED.updated_at_ms
+        WHERE runner_dead_letters.runner_id = EXCLUDED.runner_id
+        RETURNING jsonb_array_length(attempts_json) AS attempts
+        "#,
+    )
+    .bind(&dead.job.job_id)
+    .bind(&runner_id)
+    .bind(&dead.job.job_type)
+    .bind(&job_json)
+    .bind(&attempts_json)
+    .bind(dead.dead_lettered_at_ms)
+    .bind(now_ms)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let Some(row) = row else {
+        return Err(ApiError::new(ErrorCode::Forbidden, "dead letter belongs to another runner"));
+    };
+    let attempts: i32 = Row::get(&row, "attempts");
+
+    warn!("☠️ Job dead-lettered: {} ({}) on {} after {} attempts", dead.job.job_id, dead.job.job_type, runner_id, attempts);
+
+    Ok(Json(serde_json::json!({ "job_id": dead.job.job_id, "status": "dead", "attempts": attempts })))
+}
+
+/// GET /v1/admin/dead-letters
+async fn list_dead_letters(
+    State(pool): State<PgPool>,
+    Query(params): Query<ListDeadLettersParams>,
+) -> Result<Json<Vec<DeadLetterSummary>>, ApiError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT job_id, runner_id, job_type, status, dead_lettered_at_ms,
+               jsonb_array_length(attempts_json) AS attempts,
+               attempts_json -> -1 ->> 'reason' AS last_reason
+        FROM runner_dead_letters
+        WHERE ($1::TEXT IS NULL OR status = $1)
+          AND ($2::TEXT IS NULL OR runner_id = $2)
+        ORDER BY dead_lettered_at_ms DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(&params.status)
+    .bind(&params.runner_id)
+    .bind(LIST_LIMIT)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| DeadLetterSummary {
+            job_id: Row::get(&row, "job_id"),
+            runner_id: Row::get(&row, "runner_id"),
+            job_type: Row::get(&row, "job_type"),
+            status: Row::get(&row, "status"),
+            attempts: Row::get(&row, "attempts"),
+            last_reason: Row::get(&row, "last_reason"),
+            dead_lettered_at_ms: Row::get(&row, "dead_lettered_at_ms"),
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// GET /v1/admin/dead-letters/:job_id
+async fn get_dead_letter(
+    State(pool): State<PgPool>,
+    Path(job_id): Path<String>,
+) -> Result<Json<DeadLetterView>, ApiError> {
+    let row = sqlx::query(
+        r#"
+        SELECT job_id, runner_id, status, job_json, attempts_json,
+               dead_lettered_at_ms, updated_at_ms, updated_by
+        FROM runner_dead_letters
+        WHERE job_id = $1
+        "#,
+    )
+    .bind(&job_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ApiError::new(ErrorCode::NotFound, "DeadLetterNotFound"))?;
+
+    let job: ExecutionJob = serde_json::from_value(Row::get(&row, "job_json"))
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("corrupt job_json: {}", e)))?;
+    let attempts: Vec<FailedAttempt> = serde_json::from_value(Row::get(&row, "attempts_json"))
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("corrupt attempts_json: {}", e)))?;
+
+    Ok(Json(DeadLetterView {
+        job_id: Row::get(&row, "job_id"),
+        runner_id: Row::get(&row, "runner_id"),
+        status: Row::get(&row, "status"),
+        job,
+        attempts,
+        dead_lettered_at_ms: Row::get(&row, "dead_lettered_at_ms"),
+        updated_at_ms: Row::get(&row, "updated_at_ms"),
+        updated_by: Row::get(&row, "updated_by"),
+    }))
+}
+
+/// PATCH /v1/admin/dead-letters/:job_id — replace the job payload
+async fn edit_payload(
+    State(pool): State<PgPool>,
+    Extension(session): Extension<Session>,
+    Path(job_id): Path<String>,
+    Json(req): Json<EditPayloadRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let payload = serde_json::to_value(&req.payload).map_err(|e| ApiError::new(ErrorCode::BadRequest, e.to_string()))?;
+
+    // Only jobs still dead can be edited; a requeued job is the runner's again
+    let updated = sqlx::query(
+        r#"
+        UPDATE runner_dead_letters
+        SET job_json = jsonb_set(job_json, '{payload}', $2),
+            updated_at_ms = $3,
+            updated_by = $4
+        WHERE job_id = $1 AND status = 'dead'
+        "#,
+    )
+    .bind(&job_id)
+    .bind(&payload)
+    .bind(now_ms())
+    .bind(&session.sid)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(not_dead(&pool, &job_id).await);
+    }
+
+    info!("✏️ Dead letter payload edited: {} by {}", job_id, session.sid);
+    Ok(Json(serde_json::json!({ "job_id": job_id, "payload": payload })))
+}
+
+/// POST /v1/admin/dead-letters/:job_id/requeue
+async fn requeue(
+    State(pool): State<PgPool>,
+    Extension(session): Extension<Session>,
+    Path(job_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let updated = sqlx::query(
+        r#"
+        UPDATE runner_dead_letters
+        SET status = 'requeued', updated_at_ms = $2, updated_by = $3
+        WHERE job_id = $1 AND status = 'dead'
+        "#,
+    )
+    .bind(&job_id)
+    .bind(now_ms())
+    .bind(&session.sid)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    if updated == 0 {
+        return Err(not_dead(&pool, &job_id).await);
+    }
+
+    info!("🔁 Dead letter requeued: {} by {}", job_id, session.sid);
+    Ok(Json(serde_json::json!({ "job_id": job_id, "status": "requeued" })))
+}
+
+/// DELETE /v1/admin/dead-letters/:job_id
+async fn purge(
+    State(pool): State<PgPool>,
+    Extension(session): Extension<Session>,
+    Path(job_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let deleted = sqlx::query("DELETE FROM runner_dead_letters WHERE job_id = $1")
+        .bind(&job_id)
+        .execute(&pool)
+        .await
+        .map_err(db_error)?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(ApiError::new(ErrorCode::NotFound, "DeadLetterNotFound"));
+    }
+
+    info!("🗑️ Dead letter purged: {} by {}", job_id, session.sid);
+    Ok(Json(serde_json::json!({ "job_id": job_id, "purged": true })))
+}
+
+// =============================================================================
+// RUNNER HANDOFF
+// =============================================================================
+
+/// Jobs an admin requeued for `runner_id`, marked released.
+/// Retries are reset so the runner gives them a fresh budget.
+pub async fn release_requeued(pool: &PgPool, runner_id: &str) -> Result<Vec<ExecutionJob>, sqlx::Error> {
+    let rows: Vec<serde_json::Value> = sqlx::query_scalar(
+        r#"
+        UPDATE runner_dead_letters
+        SET status = 'released', updated_at_ms = $2
+        WHERE runner_id = $1 AND status = 'requeued'
+        RETURNING job_json
+        "#,
+    )
+    .bind(runner_id)
+    .bind(now_ms())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|v| serde_json::from_value::<ExecutionJob>(v).ok())
+        .map(|mut job| {
+            job.retries = 0;
+            job
+        })
+        .collect())
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+fn report_message(runner_id: &str, job_id: &str, digest: &str, ts_ms: i64) -> String {
+    format!("ubl:runner:dead_letter\n{}\n{}\n{}\n{}", runner_id, job_id, digest, ts_ms)
+}
+
+/// 404 if the job is unknown, 400 if it is no longer dead
+async fn not_dead(pool: &PgPool, job_id: &str) -> ApiError {
+    match sqlx::query_scalar::<_, String>("SELECT status FROM runner_dead_letters WHERE job_id = $1")
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await
+    {
+        Ok(Some(status)) => ApiError::new(ErrorCode::BadRequest, format!("dead letter is {}", status)),
+        Ok(None) => ApiError::new(ErrorCode::NotFound, "DeadLetterNotFound"),
+        Err(e) => db_error(e),
+    }
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    ApiError::new(ErrorCode::DatabaseError, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_letter_survives_json_roundtrip() {
+        // Receipts carry u128 nanosecond timestamps; they must fit the JSONB path
+        let job = ExecutionJob::new("C.Jobs".into(), "link_abc".into(), "build".into());
+        let receipt = ubl_runner_core::ExecutionReceipt::new("C.Jobs".into(), "link_abc".into(), "exec_1".into());
+        let dead = DeadLetter {
+            job,
+            attempts: vec![FailedAttempt { attempt: 1, reason: "exit 1".into(), receipt: Some(receipt), failed_at_ms: 1 }],
+            dead_lettered_at_ms: 2,
+        };
+        let value = serde_json::to_value(&dead.attempts).unwrap();
+        let back: Vec<FailedAttempt> = serde_json::from_value(value).unwrap();
+        assert_eq!(back[0].receipt.as_ref().unwrap().started_at, dead.attempts[0].receipt.as_ref().unwrap().started_at);
+        assert_eq!(report_message("r", "j", "d", 1), "ubl:runner:dead_letter\nr\nj\nd\n1");
+    }
+}
@@ -0,0 +1,317 @@
+//! Commit dry-runs — what a commit would do, without doing it
+//!
+//! `POST /link/dry-run` takes the same signed link and ASC as
+//! `POST /link/commit` and runs the same admission (signature, Policy Pack,
+//! policy VM, pacts, evolution validation, tangency against the head), then
+//! stops before the append. Refusals are the commit's own errors. On success
+//! it answers:
+//!
+//! - `receipt`: the ledger entry the commit would write now; `ts_unix_ms` and
+//!   `entry_hash` change with the time of the real commit;
+//! - `balance`: the container's net `physics_delta` before and after;
+//! - `policy`: which policy decided, its decision and the gas it used;
+//! - `fsm`: the job transition of a `job.state_changed` / `job.timeout`;
+//! - `projections`: the projection tables the commit would write.
+//!
+//! A dry run writes nothing: no entry, no pact use, no analytics, no
+//! projection row. A concurrent commit can still move the head before the
+//! real one.
+
+use serde::Serialize;
+use serde_json::Value;
+use ubl_policy_vm::TranslationDecision;
+
+use crate::db::LedgerEntry;
+use crate::observation_batch;
+use crate::policy_registry::PolicyEvaluation;
+use crate::projections::{ARTIFACT_CARD_TYPE, AUDIT_CONTAINER, EVOLUTION_PREFIX};
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct DryRunResponse {
+    pub ok: bool,
+    pub receipt: LedgerEntry,
+    pub balance: Balance,
+    pub policy: PolicyTrace,
+    pub fsm: Option<FsmTransition>,
+    pub projections: Vec<ProjectionDelta>,
+}
+
+/// Net `physics_delta` of the container (i128 strings)
+#[derive(Debug, Serialize)]
+pub struct Balance {
+    pub before: String,
+    pub delta: String,
+    pub after: String,
+}
+
+impl Balance {
+    pub fn new(before: i128, delta: i128) -> Self {
+        Self {
+            before: before.to_string(),
+            delta: delta.to_string(),
+            after: before.saturating_add(delta).to_string(),
+        }
+    }
+}
+
+/// Outcome of the policy VM for an admitted link
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyTrace {
+    /// `None`: no policy bound, the permissive default applied
+    pub policy_id: Option<String>,
+    /// Version active at commit time that decided
+    pub policy_version: Option<String>,
+    /// `allow`, or `unavailable` when evaluation failed and the commit is
+    /// let through for compatibility
+    pub decision: String,
+    pub required_pact: Option<String>,
+    pub gas_used: u64,
+    pub max_gas: u64,
+    /// Why evaluation was unavailable
+    pub error: Option<String>,
+}
+
+impl PolicyTrace {
+    /// Trace of an evaluation that did not deny
+    pub fn of(evaluation: &PolicyEvaluation) -> Self {
+        let (decision, required_pact, error) = match &evaluation.decision {
+            Ok(TranslationDecision::Allow { required_pact, .. }) => ("allow", required_pact.clone(), None),
+            Ok(TranslationDecision::Deny { reason }) => ("deny", None, Some(reason.clone())),
+            Err(e) => ("unavailable", None, Some(e.to_string())),
+        };
+        Self {
+            policy_id: evaluation.policy_id.clone(),
+            policy_version: evaluation.policy_version.clone(),
+            decision: decision.into(),
+            required_pact,
+            gas_used: evaluation.gas_used,
+            max_gas: evaluation.max_gas,
+            error,
+        }
+    }
+}
+
+/// Job state transition checked against the job FSM
+#[derive(Debug, Clone, Serialize)]
+pub struct FsmTransition {
+    pub job_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A projection table the commit would write
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectionDelta {
+    pub table: &'static str,
+    /// Row the event addresses (job, conversation, message...), when the
+    /// atom names it
+    pub key: Option<String>,
+    /// Rows written; more than one for observation batches
+    pub rows: usize,
+}
+
+// =============================================================================
+// PROJECTION DELTAS
+// =============================================================================
+
+/// Projection tables a commit of `atom` to `container_id` writes. Mirrors the
+/// projection dispatch after an append (see `try_commit_link` in main.rs);
+/// the container analytics row is written for every commit.
+pub fn projection_deltas(container_id: &str, atom: Option<&Value>) -> Vec<ProjectionDelta> {
+    let mut deltas = vec![delta("projection_container_daily", Some(container_id.to_string()))];
+    let Some((atom, event_type)) = atom.and_then(|a| Some((a, a.get("type")?.as_str()?))) else {
+        return deltas;
+    };
+    let str_of = |keys: &[&str]| keys.iter().find_map(|k| atom.get(*k).and_then(|v| v.as_str()).map(String::from));
+    let job_key = || str_of(&["job_id", "id"]);
+
+    if event_type == observation_batch::BATCH_TYPE {
+        let rows = atom.get("observations").and_then(|v| v.as_array()).map_or(0, Vec::len);
+        deltas.push(ProjectionDelta { table: "projection_observations", key: None, rows });
+    }
+    if event_type.starts_with(EVOLUTION_PREFIX) {
+        let target = crate::evolution::changelog_target(container_id, atom);
+        deltas.push(delta("projection_evolution_changelog", Some(target)));
+    }
+    if event_type == ARTIFACT_CARD_TYPE {
+        deltas.push(delta("projection_timeline_items", str_of(&["conversation_id"])));
+        if container_id != "C.Jobs" && job_key().is_some() {
+            deltas.push(delta("projection_job_events", job_key()));
+        }
+    }
+
+    match container_id {
+        "C.Jobs" => {
+            if event_type == crate::job_templates::TEMPLATE_TYPE {
+                deltas.push(delta("projection_job_templates", str_of(&["template_id"])));
+            } else if event_type.starts_with("approval.") {
+                deltas.push(delta("projection_approvals", str_of(&["approval_id", "id"])));
+                deltas.push(delta("projection_jobs", job_key()));
+            } else if ubl_events::JobEvent::is_known(event_type) && !is_card_action(event_type) {
+                deltas.push(delta("projection_jobs", job_key()));
+            }
+            if job_key().is_some() {
+                deltas.push(delta("projection_job_events", job_key()));
+            }
+            if event_type == "tool.result" {
+                deltas.push(delta("projection_job_artifacts", job_key()));
+            }
+            let job_presence = matches!(event_type, "job.state_changed" | "job.started" | "job.completed" | "job.timeout")
+                && str_of(&["owner_entity_id", "assigned_to"]).is_some();
+            let actor = atom
+                .get("actor")
+                .and_then(|a| a.get("entity_id"))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .or_else(|| str_of(&["from", "created_by"]));
+            if job_presence || actor.is_some() {
+                deltas.push(delta("projection_presence", actor.or_else(|| str_of(&["owner_entity_id", "assigned_to"]))));
+            }
+        }
+        "C.Messenger" => {
+            let table = match event_type {
+                "conversation.created" => Some(("projection_conversations", str_of(&["id"]))),
+                "message.created" | "message.read" => Some(("projection_messages", str_of(&["message_id", "id"]))),
+                crate::projections::mentions::MENTION_TYPE => Some(("projection_obligations", str_of(&["message_id"]))),
+                crate::projections::summaries::SUMMARY_TYPE => {
+                    Some(("projection_conversation_summaries", str_of(&["conversation_id"])))
+                }
+                crate::projections::REDACTION_TYPE => Some(("projection_redactions", str_of(&["message_id"]))),
+                _ => None,
+            };
+            if let Some((table, key)) = table {
+                deltas.push(delta(table, key));
+            }
+            if let Some(conversation_id) = str_of(&["conversation_id"]).filter(|c| !c.is_empty()) {
+                deltas.push(delta("projection_timeline_items", Some(conversation_id)));
+            }
+            if let Some(from) = str_of(&["from"]) {
+                deltas.push(delta("projection_presence", Some(from)));
+            }
+        }
+        "C.Office" => {
+            let table = match event_type {
+                "entity.created" | "entity.activated" | "entity.suspended" | "entity.archived"
+                | "constitution.updated" | "baseline.updated" => Some("office_entities"),
+                "session.started" | "session.completed" => Some("office_sessions"),
+                t if t.starts_with("audit.") || t.starts_with("governance.") => Some("office_audit_log"),
+                _ => None,
+            };
+            if let Some(table) = table {
+                deltas.push(delta(table, str_of(&["entity_id", "id"])));
+            }
+        }
+        AUDIT_CONTAINER if event_type == "annotation.created" => {
+            deltas.push(delta("projection_annotations", str_of(&["id"])));
+        }
+        _ => {}
+    }
+    deltas
+}
+
+fn delta(table: &'static str, key: Option<String>) -> ProjectionDelta {
+    ProjectionDelta { table, key, rows: 1 }
+}
+
+/// Job card actions feed only the job timeline
+fn is_card_action(event_type: &str) -> bool {
+    matches!(event_type, "job.state_changed" | "job.approve" | "job.reject" | "job.provide_input")
+}
+
+/// Response sample pinned by the API contract tests (see `crate::contracts`)
+#[cfg(test)]
+pub(crate) fn sample() -> DryRunResponse {
+    let atom = serde_json::json!({
+        "type": "job.state_changed",
+        "job_id": "job_1",
+        "from_state": "queued",
+        "to_state": "running",
+        "actor": { "entity_id": "agent_1" },
+    });
+    DryRunResponse {
+        ok: true,
+        receipt: LedgerEntry {
+            container_id: "C.Jobs".into(),
+            sequence: 43,
+            link_hash: "a".into(),
+            previous_hash: "p".into(),
+            entry_hash: "e".into(),
+            ts_unix_ms: 1,
+            hlc: crate::hlc::Hlc::new(1, 0),
+        },
+        balance: Balance::new(100, -5),
+        policy: PolicyTrace {
+            policy_id: Some("default_C.Jobs".into()),
+            policy_version: Some("1.0".into()),
+            decision: "allow".into(),
+            required_pact: Some("pact_ops".into()),
+            gas_used: 120,
+            max_gas: 10_000,
+            error: Some("Policy not found: default_C.Jobs".into()),
+        },
+        fsm: Some(FsmTransition { job_id: "job_1".into(), from: "queued".into(), to: "running".into() }),
+        projections: projection_deltas("C.Jobs", Some(&atom)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tables(container_id: &str, atom: Value) -> Vec<&'static str> {
+        projection_deltas(container_id, Some(&atom)).into_iter().map(|d| d.table).collect()
+    }
+
+    #[test]
+    fn test_job_and_messenger_deltas() {
+        let transition = json!({ "type": "job.state_changed", "job_id": "job_1", "actor": { "entity_id": "agent_1" } });
+        assert_eq!(
+            tables("C.Jobs", transition),
+            ["projection_container_daily", "projection_job_events", "projection_presence"]
+        );
+        let created = json!({ "type": "job.created", "id": "job_2" });
+        let deltas = projection_deltas("C.Jobs", Some(&created));
+        assert_eq!(deltas[1], delta("projection_jobs", Some("job_2".into())));
+
+        let message = json!({ "type": "message.created", "id": "m1", "conversation_id": "c1", "from": "u1" });
+        assert_eq!(
+            tables("C.Messenger", message),
+            ["projection_container_daily", "projection_messages", "projection_timeline_items", "projection_presence"]
+        );
+    }
+
+    #[test]
+    fn test_cross_container_deltas() {
+        let batch = json!({ "type": "observation.batch", "observations": [{}, {}, {}] });
+        let deltas = projection_deltas("C.Sensors", Some(&batch));
+        assert_eq!(deltas[1], ProjectionDelta { table: "projection_observations", key: None, rows: 3 });
+
+        let evolution = json!({ "type": "evolution.container_manifest", "container_id": "C.Jobs", "manifest": {} });
+        let deltas = projection_deltas("C.Governance", Some(&evolution));
+        assert_eq!(deltas[1], delta("projection_evolution_changelog", Some("C.Jobs".into())));
+
+        assert_eq!(projection_deltas("C.Ops", None), [delta("projection_container_daily", Some("C.Ops".into()))]);
+    }
+
+    #[test]
+    fn test_balance_and_policy_trace() {
+        let balance = Balance::new(100, -5);
+        assert_eq!((balance.before.as_str(), balance.after.as_str()), ("100", "95"));
+
+        let evaluation = PolicyEvaluation {
+            policy_id: None,
+            policy_version: None,
+            decision: Err(crate::policy_registry::RegistryError::EvaluationFailed("gas".into())),
+            gas_used: 0,
+            max_gas: 0,
+        };
+        let trace = PolicyTrace::of(&evaluation);
+        assert_eq!(trace.decision, "unavailable");
+        assert!(trace.error.unwrap().contains("gas"));
+    }
+}
@@ -0,0 +1,718 @@
+//! Evolution handlers — commit-time validation of rule changes
+//!
+//! Evolution links change the rules themselves. The membrane only checks
+//! that they carry a pact and zero delta; this registry checks that the
+//! proposed change is well-formed and would apply cleanly. Each evolution
+//! type is an atom `type` with a handler:
+//!
+//! - `evolution.policy_update`      → `{container_id, policy: PolicyDefinition}`;
+//!   must pass the compiler limits and compile to valid bytecode
+//! - `evolution.container_manifest` → `{container_id, manifest: {intent_classes, ...}}`;
+//!   see `container_manifest` for how it is served
+//! - `evolution.fsm_update`         → `{fsm, initial, states, transitions: [{from, to}]}`;
+//!   every state reachable from `initial`, at least one terminal state
+//! - `evolution.job_template`       → `{template: JobTemplate}` in C.Jobs; see
+//!   `job_templates`
+//! - `evolution.feature_flags`      → `{container_id, flags: {name: bool}}`; see
+//!   `feature_flags`
+//!
+//! `commit_link` runs the handler after pact validation; a failure (or an
+//! Evolution link without an atom, or with an unregistered type) rejects the
+//! commit with `INVALID_EVOLUTION`.
+//!
+//! The changelog diffs at the bottom turn two versions of the same subject
+//! (policy, manifest, FSM, template, flags) into [`FieldChange`]s for the
+//! `projections::changelog` history.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use thiserror::Error;
+use ubl_errors::{ErrorCode, HasErrorCode};
+use ubl_policy_vm::{PolicyCompiler, PolicyDefinition};
+
+use crate::feature_flags::{self, FLAGS_SUBJECT, FLAGS_TYPE, MAX_FLAGS};
+use crate::job_templates::{JobTemplate, TEMPLATE_CONTAINER, TEMPLATE_TYPE};
+
+/// Intent classes a container manifest may allow
+const INTENT_CLASSES: [&str; 4] = ["Observation", "Conservation", "Entropy", "Evolution"];
+/// Longest manifest description accepted
+const MAX_DESCRIPTION_BYTES: usize = 1024;
+/// Longest asset name (the unit of `physics_delta`) accepted
+const MAX_ASSET_BYTES: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum EvolutionError {
+    #[error("Evolution link carries no atom")]
+    MissingAtom,
+
+    #[error("Evolution atom has no type")]
+    MissingType,
+
+    #[error("No handler for evolution type '{0}'")]
+    UnknownType(String),
+
+    #[error("{kind}: {reason}")]
+    Invalid { kind: String, reason: String },
+}
+
+impl HasErrorCode for EvolutionError {
+    fn error_code(&self) -> ErrorCode {
+        ErrorCode::InvalidEvolution
+    }
+}
+
+/// Validates one evolution type
+pub trait EvolutionHandler: Send + Sync {
+    /// Check the atom of an Evolution link committed to `container_id`
+    fn validate(&self, container_id: &str, atom: &Value) -> Result<(), String>;
+}
+
+/// Evolution type → handler
+pub struct EvolutionRegistry {
+    handlers: HashMap<&'static str, Box<dyn EvolutionHandler>>,
+}
+
+impl EvolutionRegistry {
+    /// Registry without handlers: every evolution is rejected
+    pub fn new() -> Self {
+        Self { handlers: HashMap::new() }
+    }
+
+    /// Registry with the built-in policy, manifest, FSM, job template and
+    /// feature flag handlers
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register("evolution.policy_update", PolicyUpdateHandler);
+        registry.register("evolution.container_manifest", ContainerManifestHandler);
+        registry.register("evolution.fsm_update", FsmUpdateHandler);
+        registry.register(TEMPLATE_TYPE, JobTemplateHandler);
+        registry.register(FLAGS_TYPE, FeatureFlagsHandler);
+        registry
+    }
+
+    pub fn register(&mut self, kind: &'static str, handler: impl EvolutionHandler + 'static) {
+        self.handlers.insert(kind, Box::new(handler));
+    }
+
+    /// Validate an Evolution link's atom
+    pub fn validate(&self, container_id: &str, atom: Option<&Value>) -> Result<(), EvolutionError> {
+        let atom = atom.ok_or(EvolutionError::MissingAtom)?;
+        let kind = atom.get("type").and_then(|t| t.as_str()).ok_or(EvolutionError::MissingType)?;
+        let handler = self
+            .handlers
+            .get(kind)
+            .ok_or_else(|| EvolutionError::UnknownType(kind.to_string()))?;
+        handler
+            .validate(container_id, atom)
+            .map_err(|reason| EvolutionError::Invalid { kind: kind.to_string(), reason })
+    }
+}
+
+impl Default for EvolutionRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(atom: &Value) -> Result<T, String> {
+    serde_json::from_value(atom.clone()).map_err(|e| format!("malformed payload: {}", e))
+}
+
+fn require_container(container_id: &str) -> Result<(), String> {
+    if container_id.trim().is_empty() {
+        return Err("container_id is required".into());
+    }
+    Ok(())
+}
+
+// =============================================================================
+// POLICY UPDATE
+// =============================================================================
+
+#[derive(Deserialize)]
+struct PolicyUpdate {
+    container_id: String,
+    policy: PolicyDefinition,
+}
+
+/// `evolution.policy_update`: the new policy must compile within VM limits
+pub struct PolicyUpdateHandler;
+
+impl EvolutionHandler for PolicyUpdateHandler {
+    fn validate(&self, _container_id: &str, atom: &Value) -> Result<(), String> {
+        let update: PolicyUpdate = parse(atom)?;
+        require_container(&update.container_id)?;
+        if update.policy.policy_id.trim().is_empty() || update.policy.version.trim().is_empty() {
+            return Err("policy_id and version are required".into());
+        }
+
+        let compiled = PolicyCompiler::new()
+            .compile_validated(&update.policy)
+            .map_err(|e| format!("policy does not compile: {}", e))?;
+        compiled.validate().map_err(|e| format!("compiled policy exceeds VM limits: {}", e))
+    }
+}
+
+// =============================================================================
+// CONTAINER MANIFEST
+// =============================================================================
+
+#[derive(Deserialize)]
+struct ManifestChange {
+    container_id: String,
+    manifest: Manifest,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    intent_classes: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    max_atom_bytes: Option<u64>,
+    /// Unit the container's `physics_delta` counts, e.g. `USD` or `credits`
+    #[serde(default)]
+    asset: Option<String>,
+}
+
+/// `evolution.container_manifest`: known fields and intent classes only
+pub struct ContainerManifestHandler;
+
+impl EvolutionHandler for ContainerManifestHandler {
+    fn validate(&self, _container_id: &str, atom: &Value) -> Result<(), String> {
+        let change: ManifestChange = parse(atom)?;
+        require_container(&change.container_id)?;
+
+        let manifest = change.manifest;
+        if manifest.intent_classes.is_empty() {
+            return Err("manifest must allow at least one intent class".into());
+        }
+        let mut seen = HashSet::new();
+        for class in &manifest.intent_classes {
+            if !INTENT_CLASSES.contains(&class.as_str()) {
+                return Err(format!("unknown intent class '{}'", class));
+            }
+            if !seen.insert(class) {
+                return Err(format!("duplicate intent class '{}'", class));
+            }
+        }
+        if manifest.description.as_ref().is_some_and(|d| d.len() > MAX_DESCRIPTION_BYTES) {
+            return Err(format!("description exceeds {} bytes", MAX_DESCRIPTION_BYTES));
+        }
+        if manifest.max_atom_bytes == Some(0) {
+            return Err("max_atom_bytes must be positive".into());
+        }
+        if let Some(asset) = &manifest.asset {
+            if asset.trim().is_empty() || asset.len() > MAX_ASSET_BYTES {
+                return Err(format!("asset must be 1 to {} bytes", MAX_ASSET_BYTES));
+            }
+        }
+        Ok(())
+    }
+}
+
+// =============================================================================
+// FSM UPDATE
+// =============================================================================
+
+/// A state machine as evolutions and job templates declare it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsmDefinition {
+    pub fsm: String,
+    pub initial: String,
+    pub states: Vec<String>,
+    pub transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+}
+
+impl FsmDefinition {
+    /// Closed and fully reachable from `initial`, with a terminal state
+    pub fn validate(&self) -> Result<(), String> {
+        if self.fsm.trim().is_empty() {
+            return Err("fsm name is required".into());
+        }
+
+        let mut states = HashSet::new();
+        for state in &self.states {
+            if state.trim().is_empty() || !states.insert(state.as_str()) {
+                return Err(format!("empty or duplicate state '{}'", state));
+            }
+        }
+        if !states.contains(self.initial.as_str()) {
+            return Err(format!("initial state '{}' is not declared", self.initial));
+        }
+
+        let mut outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut seen = HashSet::new();
+        for t in &self.transitions {
+            for end in [&t.from, &t.to] {
+                if !states.contains(end.as_str()) {
+                    return Err(format!("transition {} → {} uses undeclared state '{}'", t.from, t.to, end));
+                }
+            }
+            if !seen.insert(t) {
+                return Err(format!("duplicate transition {} → {}", t.from, t.to));
+            }
+            outgoing.entry(t.from.as_str()).or_default().push(t.to.as_str());
+        }
+
+        if !self.states.iter().any(|s| !outgoing.contains_key(s.as_str())) {
+            return Err("no terminal state (every state has an outgoing transition)".into());
+        }
+
+        let mut reached = HashSet::from([self.initial.as_str()]);
+        let mut queue = VecDeque::from([self.initial.as_str()]);
+        while let Some(state) = queue.pop_front() {
+            for next in outgoing.get(state).into_iter().flatten() {
+                if reached.insert(*next) {
+                    queue.push_back(*next);
+                }
+            }
+        }
+        let mut unreachable: Vec<&str> = states.difference(&reached).copied().collect();
+        if !unreachable.is_empty() {
+            unreachable.sort_unstable();
+            return Err(format!("unreachable states from '{}': {}", self.initial, unreachable.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+/// `evolution.fsm_update`: a closed, fully reachable state machine
+pub struct FsmUpdateHandler;
+
+impl EvolutionHandler for FsmUpdateHandler {
+    fn validate(&self, _container_id: &str, atom: &Value) -> Result<(), String> {
+        parse::<FsmDefinition>(atom)?.validate()
+    }
+}
+
+// =============================================================================
+// JOB TEMPLATE
+// =============================================================================
+
+#[derive(Deserialize)]
+struct TemplateDefinition {
+    template: JobTemplate,
+}
+
+/// `evolution.job_template`: a well-formed template, kept in C.Jobs
+pub struct JobTemplateHandler;
+
+impl EvolutionHandler for JobTemplateHandler {
+    fn validate(&self, container_id: &str, atom: &Value) -> Result<(), String> {
+        if container_id != TEMPLATE_CONTAINER {
+            return Err(format!("job templates are committed to {}", TEMPLATE_CONTAINER));
+        }
+        parse::<TemplateDefinition>(atom)?.template.validate()
+    }
+}
+
+// =============================================================================
+// FEATURE FLAGS
+// =============================================================================
+
+#[derive(Deserialize)]
+struct FlagsChange {
+    container_id: String,
+    flags: BTreeMap<String, bool>,
+}
+
+/// `evolution.feature_flags`: a bounded set of well-named boolean flags
+pub struct FeatureFlagsHandler;
+
+impl EvolutionHandler for FeatureFlagsHandler {
+    fn validate(&self, _container_id: &str, atom: &Value) -> Result<(), String> {
+        let change: FlagsChange = parse(atom)?;
+        require_container(&change.container_id)?;
+        if change.flags.len() > MAX_FLAGS {
+            return Err(format!("at most {} flags per container", MAX_FLAGS));
+        }
+        change.flags.keys().try_for_each(|name| feature_flags::validate_name(name))
+    }
+}
+
+// =============================================================================
+// CHANGELOG DIFFS
+// =============================================================================
+
+/// How a field moved between two versions of an evolved definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOp {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One structured difference, e.g. rule `allow_observe` removed from a policy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Dotted path: `policy.version`, `rules.<rule_id>`, `states.<state>`,
+    /// `transitions.<from>→<to>`, `template.<field>`
+    pub path: String,
+    pub op: ChangeOp,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl FieldChange {
+    /// One changelog line: `+ states.review`, `~ policy.version: 1.0 → 1.1`
+    pub fn render(&self) -> String {
+        match self.op {
+            // Set members (`states.review`) are already named by their path
+            ChangeOp::Added => match self.after.as_ref().and_then(scalar) {
+                Some(value) if !self.path.ends_with(&format!(".{}", value)) => format!("+ {} = {}", self.path, value),
+                _ => format!("+ {}", self.path),
+            },
+            ChangeOp::Removed => format!("- {}", self.path),
+            ChangeOp::Changed => match (self.before.as_ref().and_then(scalar), self.after.as_ref().and_then(scalar)) {
+                (Some(before), Some(after)) => format!("~ {}: {} → {}", self.path, before, after),
+                _ => format!("~ {}", self.path),
+            },
+        }
+    }
+}
+
+/// Scalars render inline; objects and arrays only by path
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Container whose rules an evolution changes: the one the atom names, else
+/// the one it was committed to (FSM updates, job templates)
+pub fn changelog_target(container_id: &str, atom: &Value) -> String {
+    atom["container_id"].as_str().unwrap_or(container_id).to_string()
+}
+
+/// What an evolution replaces, so successive versions pair up: the policy
+/// id, `manifest`, the FSM name, the template id or `flags`
+pub fn changelog_subject(atom: &Value) -> String {
+    let subject = match atom["type"].as_str().unwrap_or_default() {
+        "evolution.policy_update" => atom["policy"]["policy_id"].as_str(),
+        "evolution.container_manifest" => Some("manifest"),
+        "evolution.fsm_update" => atom["fsm"].as_str(),
+        TEMPLATE_TYPE => atom["template"]["template_id"].as_str(),
+        FLAGS_TYPE => Some(FLAGS_SUBJECT),
+        _ => None,
+    };
+    subject.unwrap_or_default().to_string()
+}
+
+/// Differences from `previous` (same type and subject) to `current`; the first
+/// version of a subject diffs against nothing, so everything in it is added.
+/// Unknown evolution types get a field-by-field diff of the atom.
+pub fn changelog_diff(previous: Option<&Value>, current: &Value) -> Vec<FieldChange> {
+    let nothing = Value::Object(Default::default());
+    let before = previous.unwrap_or(&nothing);
+    let mut diff = Diff::default();
+
+    match current["type"].as_str().unwrap_or_default() {
+        "evolution.policy_update" => {
+            let (b, a) = (&before["policy"], &current["policy"]);
+            for field in ["version", "description", "default_deny"] {
+                diff.field(format!("policy.{}", field), &b[field], &a[field]);
+            }
+            diff.keyed("rules", &b["rules"], &a["rules"], |rule| rule["rule_id"].as_str().map(String::from));
+        }
+        "evolution.container_manifest" => {
+            let (b, a) = (&before["manifest"], &current["manifest"]);
+            diff.keyed("intent_classes", &b["intent_classes"], &a["intent_classes"], scalar);
+            for field in ["description", "max_atom_bytes", "asset"] {
+                diff.field(format!("manifest.{}", field), &b[field], &a[field]);
+            }
+        }
+        "evolution.fsm_update" => {
+            diff.field("initial".into(), &before["initial"], &current["initial"]);
+            diff.keyed("states", &before["states"], &current["states"], scalar);
+            diff.keyed("transitions", &before["transitions"], &current["transitions"], |t| {
+                Some(format!("{}→{}", t["from"].as_str()?, t["to"].as_str()?))
+            });
+        }
+        TEMPLATE_TYPE => diff.object("template", &before["template"], &current["template"]),
+        FLAGS_TYPE => diff.object("flags", &before["flags"], &current["flags"]),
+        _ => diff.object("", before, current),
+    }
+    diff.changes
+}
+
+#[derive(Default)]
+struct Diff {
+    changes: Vec<FieldChange>,
+}
+
+impl Diff {
+    fn push(&mut self, path: String, before: &Value, after: &Value) {
+        let op = match (before.is_null(), after.is_null()) {
+            (true, true) => return,
+            (true, false) => ChangeOp::Added,
+            (false, true) => ChangeOp::Removed,
+            (false, false) => ChangeOp::Changed,
+        };
+        let some = |v: &Value| (!v.is_null()).then(|| v.clone());
+        self.changes.push(FieldChange { path, op, before: some(before), after: some(after) });
+    }
+
+    fn field(&mut self, path: String, before: &Value, after: &Value) {
+        if before != after {
+            self.push(path, before, after);
+        }
+    }
+
+    /// Field by field over the union of keys (`type` excluded)
+    fn object(&mut self, prefix: &str, before: &Value, after: &Value) {
+        let keys: std::collections::BTreeSet<&String> = [before, after]
+            .into_iter()
+            .filter_map(Value::as_object)
+            .flat_map(|map| map.keys())
+            .filter(|key| key.as_str() != "type")
+            .collect();
+        for key in keys {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            self.field(path, &before[key.as_str()], &after[key.as_str()]);
+        }
+    }
+
+    /// Arrays matched by `key`: added and removed elements, and field changes
+    /// inside elements present in both
+    fn keyed(&mut self, prefix: &str, before: &Value, after: &Value, key: impl Fn(&Value) -> Option<String>) {
+        let index = |list: &Value| -> Vec<(String, Value)> {
+            list.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|item| Some((key(item)?, item.clone())))
+                .collect()
+        };
+        let (before, after) = (index(before), index(after));
+        let find = |list: &[(String, Value)], k: &str| list.iter().find(|(other, _)| other == k).map(|(_, v)| v.clone());
+
+        for (k, old) in &before {
+            if find(&after, k).is_none() {
+                self.push(format!("{}.{}", prefix, k), old, &Value::Null);
+            }
+        }
+        for (k, new) in &after {
+            let path = format!("{}.{}", prefix, k);
+            match find(&before, k) {
+                None => self.push(path, &Value::Null, new),
+                Some(old) if old.is_object() && new.is_object() => self.object(&path, &old, new),
+                Some(old) => self.field(path, &old, new),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn registry() -> EvolutionRegistry {
+        EvolutionRegistry::with_defaults()
+    }
+
+    fn invalid_reason(result: Result<(), EvolutionError>) -> String {
+        match result {
+            Err(EvolutionError::Invalid { reason, .. }) => reason,
+            other => panic!("expected Invalid, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_and_missing_payloads_are_rejected() {
+        let r = registry();
+        assert!(matches!(r.validate("C.Admin", None), Err(EvolutionError::MissingAtom)));
+        assert!(matches!(r.validate("C.Admin", Some(&json!({}))), Err(EvolutionError::MissingType)));
+        assert!(matches!(
+            r.validate("C.Admin", Some(&json!({ "type": "evolution.nope" }))),
+            Err(EvolutionError::UnknownType(_))
+        ));
+        assert!(matches!(
+            EvolutionRegistry::new().validate("C.Admin", Some(&json!({ "type": "evolution.fsm_update" }))),
+            Err(EvolutionError::UnknownType(_))
+        ));
+        assert_eq!(EvolutionError::MissingAtom.error_code(), ErrorCode::InvalidEvolution);
+    }
+
+    #[test]
+    fn test_policy_update() {
+        let policy = ubl_policy_vm::create_default_policy("C.Jobs");
+        let ok = json!({ "type": "evolution.policy_update", "container_id": "C.Jobs", "policy": policy });
+        assert!(registry().validate("C.Admin", Some(&ok)).is_ok());
+
+        let mut missing_version = ok.clone();
+        missing_version["policy"]["version"] = json!("");
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&missing_version))).contains("version"));
+
+        let malformed = json!({ "type": "evolution.policy_update", "container_id": "C.Jobs", "policy": { "rules": 1 } });
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&malformed))).contains("malformed"));
+    }
+
+    #[test]
+    fn test_container_manifest() {
+        let atom = |classes: Value| {
+            json!({ "type": "evolution.container_manifest", "container_id": "C.Jobs", "manifest": { "intent_classes": classes } })
+        };
+        assert!(registry().validate("C.Admin", Some(&atom(json!(["Observation", "Entropy"])))).is_ok());
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&atom(json!([])))))
+            .contains("at least one"));
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&atom(json!(["Magic"])))))
+            .contains("Magic"));
+
+        let unknown_field = json!({
+            "type": "evolution.container_manifest",
+            "container_id": "C.Jobs",
+            "manifest": { "intent_classes": ["Observation"], "colour": "blue" }
+        });
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&unknown_field))).contains("colour"));
+
+        let mut priced = atom(json!(["Conservation"]));
+        priced["manifest"]["asset"] = json!("USD");
+        assert!(registry().validate("C.Admin", Some(&priced)).is_ok());
+        priced["manifest"]["asset"] = json!(" ");
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&priced))).contains("asset"));
+    }
+
+    #[test]
+    fn test_fsm_update() {
+        let fsm = |transitions: Value| {
+            json!({
+                "type": "evolution.fsm_update",
+                "fsm": "job",
+                "initial": "draft",
+                "states": ["draft", "proposed", "completed"],
+                "transitions": transitions,
+            })
+        };
+        let ok = fsm(json!([{ "from": "draft", "to": "proposed" }, { "from": "proposed", "to": "completed" }]));
+        assert!(registry().validate("C.Jobs", Some(&ok)).is_ok());
+
+        let unreachable = fsm(json!([{ "from": "draft", "to": "completed" }]));
+        assert!(invalid_reason(registry().validate("C.Jobs", Some(&unreachable))).contains("proposed"));
+
+        let undeclared = fsm(json!([{ "from": "draft", "to": "archived" }]));
+        assert!(invalid_reason(registry().validate("C.Jobs", Some(&undeclared))).contains("archived"));
+
+        let cyclic = fsm(json!([
+            { "from": "draft", "to": "proposed" },
+            { "from": "proposed", "to": "completed" },
+            { "from": "completed", "to": "draft" },
+        ]));
+        assert!(invalid_reason(registry().validate("C.Jobs", Some(&cyclic))).contains("terminal"));
+    }
+
+    #[test]
+    fn test_feature_flags() {
+        let atom = |flags: Value| json!({ "type": FLAGS_TYPE, "container_id": "C.Messenger", "flags": flags });
+        assert!(registry().validate("C.Admin", Some(&atom(json!({ "threading": true, "v2_send": false })))).is_ok());
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&atom(json!({ "Threading": true })))))
+            .contains("Threading"));
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&atom(json!({ "threading": "yes" })))))
+            .contains("malformed"));
+
+        let many: serde_json::Map<String, Value> = (0..=MAX_FLAGS).map(|i| (format!("f{}", i), json!(true))).collect();
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&atom(Value::Object(many))))).contains("at most"));
+
+        let v1 = atom(json!({ "threading": false }));
+        let v2 = atom(json!({ "threading": true, "reactions": true }));
+        assert_eq!(changelog_subject(&v2), FLAGS_SUBJECT);
+        let lines: Vec<String> = changelog_diff(Some(&v1), &v2).iter().map(FieldChange::render).collect();
+        assert_eq!(lines, vec!["+ flags.reactions = true", "~ flags.threading: false → true"]);
+    }
+
+    #[test]
+    fn test_policy_changelog_diff() {
+        let policy = ubl_policy_vm::create_default_policy("C.Jobs");
+        let v1 = json!({ "type": "evolution.policy_update", "container_id": "C.Jobs", "policy": policy });
+        let mut v2 = v1.clone();
+        v2["policy"]["version"] = json!("2.0.0");
+        let dropped = v2["policy"]["rules"].as_array_mut().unwrap().remove(0);
+        let dropped_id = dropped["rule_id"].as_str().unwrap();
+
+        assert_eq!(changelog_subject(&v2), policy.policy_id);
+        assert_eq!(changelog_target("C.Admin", &v2), "C.Jobs");
+
+        let changes = changelog_diff(Some(&v1), &v2);
+        let lines: Vec<String> = changes.iter().map(FieldChange::render).collect();
+        assert_eq!(lines, [
+            format!("~ policy.version: {} → 2.0.0", policy.version),
+            format!("- rules.{}", dropped_id),
+        ]);
+        assert_eq!(changes[1].before.as_ref(), Some(&dropped));
+
+        // First version: everything is new
+        let initial = changelog_diff(None, &v1);
+        assert!(initial.iter().all(|c| c.op == ChangeOp::Added));
+        assert!(initial.iter().any(|c| c.path == format!("rules.{}", dropped_id)));
+        assert!(changelog_diff(Some(&v1), &v1).is_empty());
+    }
+
+    #[test]
+    fn test_manifest_and_fsm_changelog_diff() {
+        let manifest = |classes: Value, max: Value| {
+            json!({
+                "type": "evolution.container_manifest",
+                "container_id": "C.Jobs",
+                "manifest": { "intent_classes": classes, "max_atom_bytes": max },
+            })
+        };
+        let changes = changelog_diff(
+            Some(&manifest(json!(["Observation", "Entropy"]), json!(1024))),
+            &manifest(json!(["Observation", "Evolution"]), json!(2048)),
+        );
+        let lines: Vec<String> = changes.iter().map(FieldChange::render).collect();
+        assert_eq!(lines, [
+            "- intent_classes.Entropy",
+            "+ intent_classes.Evolution",
+            "~ manifest.max_atom_bytes: 1024 → 2048",
+        ]);
+
+        let fsm = |states: Value, transitions: Value| {
+            json!({ "type": "evolution.fsm_update", "fsm": "job", "initial": "draft", "states": states, "transitions": transitions })
+        };
+        let v1 = fsm(json!(["draft", "done"]), json!([{ "from": "draft", "to": "done" }]));
+        let v2 = fsm(
+            json!(["draft", "review", "done"]),
+            json!([{ "from": "draft", "to": "review" }, { "from": "review", "to": "done" }]),
+        );
+        assert_eq!(changelog_subject(&v2), "job");
+        assert_eq!(changelog_target("C.Jobs", &v2), "C.Jobs");
+        let lines: Vec<String> = changelog_diff(Some(&v1), &v2).iter().map(FieldChange::render).collect();
+        assert_eq!(lines, [
+            "+ states.review",
+            "- transitions.draft→done",
+            "+ transitions.draft→review",
+            "+ transitions.review→done",
+        ]);
+    }
+
+    #[test]
+    fn test_job_template() {
+        let atom = |version: u32| {
+            json!({
+                "type": "evolution.job_template",
+                "template": {
+                    "template_id": "triage",
+                    "version": version,
+                    "name": "Triage",
+                    "title": "Triage {{ticket}}",
+                    "parameters": { "ticket": { "type": "string", "required": true } },
+                },
+            })
+        };
+        assert!(registry().validate("C.Jobs", Some(&atom(1))).is_ok());
+        assert!(invalid_reason(registry().validate("C.Admin", Some(&atom(1)))).contains("C.Jobs"));
+        assert!(invalid_reason(registry().validate("C.Jobs", Some(&atom(0)))).contains("version"));
+    }
+}
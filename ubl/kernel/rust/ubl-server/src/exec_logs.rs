@@ -0,0 +1,596 @@
+//! Execution Logs — runner log streaming through UBL
+//!
+//! Runners push their output as ordered, hash-chained segments tied to the
+//! execution (`command_id`). Segment bytes go to the blob store; the index and
+//! chain live in `exec_log_segments`.
+//!
+//! Endpoints:
+//! - POST /v1/exec/:execution_id/logs        → Append a segment (runner signature required)
+//! - GET  /v1/exec/:execution_id/logs        → Read the log (`Range: bytes=a-b` supported)
+//! - GET  /v1/exec/:execution_id/logs/tail   → SSE: stored segments from `?from_seq=`, then live
+//!
+//! Chain: `chain_hash(n) = blake3("<chain_hash(n-1)>\n<content_hash(n)>")`,
+//! starting from [`GENESIS_HASH`]. The head is the `logs_hash` the runner
+//! reports in exec.finish; readers get it in the `X-Ubl-Log-Head` header.
+//!
+//! Signed message (tagged "ed25519:<base64url>", see `keystore::verify`):
+//! ```text
+//! ubl:exec:log\n<execution_id>\n<runner_id>\n<seq>\n<prev_hash>\n<content_hash>
+//! ```
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{sse::{Event, Sse}, IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use futures_util::stream::Stream;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::blob_store::{self, BlobStore};
+use crate::crypto;
+use crate::sse::{self, ConnectionRegistry, SseLimits};
+use crate::timestamps::now_ms;
+
+/// `prev_hash` of segment 0
+pub const GENESIS_HASH: &str = "blake3:0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Default largest segment accepted (`UBL_MAX_LOG_SEGMENT_BYTES`)
+const DEFAULT_MAX_SEGMENT_BYTES: usize = 256 * 1024;
+/// Largest slice returned by one read; longer ranges are clipped (206)
+const MAX_READ_BYTES: u64 = 8 * 1024 * 1024;
+
+fn max_segment_bytes() -> usize {
+    std::env::var("UBL_MAX_LOG_SEGMENT_BYTES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_MAX_SEGMENT_BYTES)
+}
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct AppendSegmentRequest {
+    pub runner_id: String,
+    pub seq: i64,
+    pub prev_hash: String,
+    /// Segment bytes, base64 (standard alphabet)
+    pub data: String,
+    pub sig_runner: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppendSegmentResponse {
+    pub seq: i64,
+    pub byte_offset: i64,
+    pub chain_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TailParams {
+    pub from_seq: Option<i64>,
+    pub tenant_id: Option<String>,
+}
+
+/// A stored segment, broadcast to tail subscribers
+#[derive(Debug, Clone)]
+struct LogEvent {
+    execution_id: String,
+    seq: i64,
+    byte_offset: i64,
+    chain_hash: String,
+    data: Bytes,
+}
+
+impl LogEvent {
+    fn to_sse(&self) -> Event {
+        Event::default()
+            .event("segment")
+            .id(self.seq.to_string())
+            .data(
+                serde_json::json!({
+                    "seq": self.seq,
+                    "byte_offset": self.byte_offset,
+                    "chain_hash": self.chain_hash,
+                    "text": String::from_utf8_lossy(&self.data),
+                })
+                .to_string(),
+            )
+    }
+}
+
+#[derive(Clone)]
+struct ExecLogState {
+    pool: PgPool,
+    blobs: Arc<BlobStore>,
+    tx: broadcast::Sender<LogEvent>,
+    limits: SseLimits,
+    connections: ConnectionRegistry,
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+pub fn routes(pool: PgPool) -> Router {
+    let (tx, _rx) = broadcast::channel(1024);
+    let state = ExecLogState {
+        pool,
+        blobs: Arc::new(BlobStore::from_env()),
+        tx,
+        limits: SseLimits::from_env(),
+        connections: ConnectionRegistry::default(),
+    };
+    Router::new()
+        .route("/v1/exec/:execution_id/logs", post(append_segment).get(read_logs))
+        .route("/v1/exec/:execution_id/logs/tail", get(tail_logs))
+        .with_state(state)
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+/// POST /v1/exec/:execution_id/logs — append the next segment
+async fn append_segment(
+    State(state): State<ExecLogState>,
+    Path(execution_id): Path<String>,
+    Json(req): Json<AppendSegmentRequest>,
+) -> Result<Json<AppendSegmentResponse>, ApiError> {
+    let data = STANDARD
+        .decode(&req.data)
+        .map_err(|e| ApiError::new(ErrorCode::BadRequest, format!("invalid base64 data: {}", e)))?;
+    if data.is_empty() {
+        return Err(ApiError::new(ErrorCode::BadRequest, "empty log segment"));
+    }
+    if data.len() > max_segment_bytes() {
+        return Err(ApiError::new(
+            ErrorCode::PayloadTooLarge,
+            format!("log segment exceeds {} bytes", max_segment_bytes()),
+        ));
+    }
+    if req.seq < 0 {
+        return Err(ApiError::new(ErrorCode::BadRequest, "seq must be non-negative"));
+    }
+
+    let content_hash = blob_store::content_hash(&data);
+    let msg = segment_message(&execution_id, &req.runner_id, req.seq, &req.prev_hash, &content_hash);
+    crypto::verify_runner_sig(&state.pool, &req.runner_id, msg.as_bytes(), &req.sig_runner)
+        .await
+        .map_err(|e| match e.as_str() {
+            "RunnerNotFound" => ApiError::new(ErrorCode::NotFound, e),
+            "RunnerNotActive" => ApiError::new(ErrorCode::Forbidden, e),
+            _ => ApiError::new(ErrorCode::InvalidSignature, format!("RunnerSigInvalid: {}", e)),
+        })?;
+
+    let mut tx = state.pool.begin().await.map_err(db_error)?;
+
+    // Locking the command serializes appends for one execution
+    let pending: Option<bool> =
+        sqlx::query_scalar("SELECT pending FROM console_commands WHERE command_id = $1 FOR UPDATE")
+            .bind(&execution_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(db_error)?;
+    match pending {
+        None => return Err(ApiError::new(ErrorCode::NotFound, "CommandNotFound")),
+        Some(false) => return Err(ApiError::new(ErrorCode::BadRequest, "ExecutionFinished")),
+        Some(true) => {}
+    }
+
+    // A retried append of an already stored segment is acknowledged as-is
+    let existing = sqlx::query(
+        "SELECT content_hash, prev_hash, chain_hash, byte_offset FROM exec_log_segments WHERE execution_id = $1 AND seq = $2",
+    )
+    .bind(&execution_id)
+    .bind(req.seq)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    if let Some(row) = existing {
+        let stored_content: String = Row::get(&row, "content_hash");
+        let stored_prev: String = Row::get(&row, "prev_hash");
+        if stored_content == content_hash && stored_prev == req.prev_hash {
+            return Ok(Json(AppendSegmentResponse {
+                seq: req.seq,
+                byte_offset: Row::get(&row, "byte_offset"),
+                chain_hash: Row::get(&row, "chain_hash"),
+            }));
+        }
+        return Err(ApiError::new(ErrorCode::SequenceMismatch, format!("segment {} already stored", req.seq)));
+    }
+
+    let last = sqlx::query(
+        r#"
+        SELECT seq, runner_id, chain_hash, byte_offset + size_bytes AS end_offset
+        FROM exec_log_segments
+        WHERE execution_id = $1
+        ORDER BY seq DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&execution_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    let (expected_seq, expected_prev, byte_offset) = match &last {
+        Some(row) => {
+            let runner_id: String = Row::get(row, "runner_id");
+            if runner_id != req.runner_id {
+                return Err(ApiError::new(ErrorCode::Forbidden, "log belongs to another runner"));
+            }
+            (
+                Row::get::<i64, _>(row, "seq") + 1,
+                Row::get::<String, _>(row, "chain_hash"),
+                Row::get::<i64, _>(row, "end_offset"),
+            )
+        }
+        None => (0, GENESIS_HASH.to_string(), 0),
+    };
+    if req.seq != expected_seq || !ubl_kernel::ct_eq(req.prev_hash.as_bytes(), expected_prev.as_bytes()) {
+        return Err(ApiError::new(
+            ErrorCode::SequenceMismatch,
+            format!("expected seq {} after {}", expected_seq, expected_prev),
+        ));
+    }
+
+    let chain = chain_hash(&expected_prev, &content_hash);
+    let data = Bytes::from(data);
+
+    let blobs = state.blobs.clone();
+    let bytes = data.clone();
+    tokio::task::spawn_blocking(move || blobs.put(&bytes))
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))?
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("BlobWriteFailed: {}", e)))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO exec_log_segments
+          (execution_id, seq, runner_id, content_hash, prev_hash, chain_hash, byte_offset, size_bytes, received_at_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(&execution_id)
+    .bind(req.seq)
+    .bind(&req.runner_id)
+    .bind(&content_hash)
+    .bind(&expected_prev)
+    .bind(&chain)
+    .bind(byte_offset)
+    .bind(data.len() as i64)
+    .bind(now_ms())
+    .execute(&mut *tx)
+    .await
+    .map_err(db_error)?;
+
+    tx.commit().await.map_err(db_error)?;
+
+    debug!("📜 Log segment {}#{} ({} bytes)", execution_id, req.seq, data.len());
+    let _ = state.tx.send(LogEvent {
+        execution_id,
+        seq: req.seq,
+        byte_offset,
+        chain_hash: chain.clone(),
+        data,
+    });
+
+    Ok(Json(AppendSegmentResponse { seq: req.seq, byte_offset, chain_hash: chain }))
+}
+
+/// GET /v1/exec/:execution_id/logs — full log or a byte range
+async fn read_logs(
+    State(state): State<ExecLogState>,
+    Path(execution_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let head = sqlx::query(
+        r#"
+        SELECT chain_hash, byte_offset + size_bytes AS total
+        FROM exec_log_segments
+        WHERE execution_id = $1
+        ORDER BY seq DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&execution_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(db_error)?;
+
+    let (head_hash, total) = match head {
+        Some(row) => (Row::get::<String, _>(&row, "chain_hash"), Row::get::<i64, _>(&row, "total") as u64),
+        None => (GENESIS_HASH.to_string(), 0),
+    };
+
+    let requested = headers.get(header::RANGE).and_then(|h| h.to_str().ok());
+    let (start, end) = match requested {
+        Some(spec) => match parse_range(spec, total) {
+            Some(r) => r,
+            None => {
+                return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{}", total))],
+                )
+                    .into_response())
+            }
+        },
+        None if total == 0 => {
+            return Ok((StatusCode::OK, log_headers(&head_hash, None), Vec::new()).into_response());
+        }
+        None => (0, total - 1),
+    };
+    let end = end.min(start + MAX_READ_BYTES - 1);
+
+    let rows = sqlx::query(
+        r#"
+        SELECT content_hash, byte_offset, size_bytes
+        FROM exec_log_segments
+        WHERE execution_id = $1 AND byte_offset <= $3 AND byte_offset + size_bytes > $2
+        ORDER BY seq
+        "#,
+    )
+    .bind(&execution_id)
+    .bind(start as i64)
+    .bind(end as i64)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(db_error)?;
+
+    let segments: Vec<(String, u64)> = rows
+        .iter()
+        .map(|r| (Row::get::<String, _>(r, "content_hash"), Row::get::<i64, _>(r, "byte_offset") as u64))
+        .collect();
+
+    let blobs = state.blobs.clone();
+    let body = tokio::task::spawn_blocking(move || -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity((end - start + 1) as usize);
+        for (hash, offset) in segments {
+            let bytes = blobs.get(&hash)?.ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, format!("log blob {} missing", hash))
+            })?;
+            let from = start.saturating_sub(offset) as usize;
+            let to = ((end + 1 - offset) as usize).min(bytes.len());
+            out.extend_from_slice(&bytes[from..to]);
+        }
+        Ok(out)
+    })
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))?
+    .map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))?;
+
+    let partial = start > 0 || end + 1 < total;
+    let status = if partial { StatusCode::PARTIAL_CONTENT } else { StatusCode::OK };
+    let content_range = partial.then(|| format!("bytes {}-{}/{}", start, end, total));
+    Ok((status, log_headers(&head_hash, content_range), body).into_response())
+}
+
+/// GET /v1/exec/:execution_id/logs/tail — stored segments, then live ones
+async fn tail_logs(
+    State(state): State<ExecLogState>,
+    Path(execution_id): Path<String>,
+    Query(params): Query<TailParams>,
+    headers: HeaderMap,
+) -> Response {
+    let mut tenant_params = HashMap::new();
+    if let Some(t) = params.tenant_id {
+        tenant_params.insert("tenant_id".to_string(), t);
+    }
+    let tenant = sse::tenant_key(&headers, &tenant_params);
+    let Some(guard) = state.connections.try_acquire(&tenant, state.limits.max_per_tenant) else {
+        return (StatusCode::TOO_MANY_REQUESTS, "SSE connection limit reached for tenant").into_response();
+    };
+
+    // EventSource reconnects resume after the last delivered segment
+    let from_seq = headers
+        .get("last-event-id")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| s.parse::<i64>().ok())
+        .map(|seq| seq + 1)
+        .or(params.from_seq)
+        .unwrap_or(0);
+
+    // Subscribe before reading history so nothing falls between the two
+    let rx = state.tx.subscribe();
+    let stream = tail_stream(state.clone(), execution_id, from_seq, rx, guard);
+    Sse::new(stream).keep_alive(state.limits.keep_alive()).into_response()
+}
+
+fn tail_stream(
+    state: ExecLogState,
+    execution_id: String,
+    from_seq: i64,
+    mut rx: broadcast::Receiver<LogEvent>,
+    guard: sse::ConnectionGuard,
+) -> Pin<Box<dyn Stream<Item = Result<Event, std::convert::Infallible>> + Send>> {
+    let idle = state.limits.idle_timeout;
+    let s = async_stream::stream! {
+        let _guard = guard;
+        let mut next_seq = from_seq;
+
+        match stored_segments(&state, &execution_id, from_seq).await {
+            Ok(events) => {
+                for event in events {
+                    next_seq = event.seq + 1;
+                    yield Ok(event.to_sse());
+                }
+            }
+            Err(e) => {
+                warn!("Log tail replay failed for {}: {}", execution_id, e);
+                yield Ok(sse::terminal_event("error", serde_json::json!({ "message": e })));
+                return;
+            }
+        }
+
+        let finished: Option<bool> = sqlx::query_scalar("SELECT NOT pending FROM console_commands WHERE command_id = $1")
+            .bind(&execution_id)
+            .fetch_optional(&state.pool)
+            .await
+            .unwrap_or(None);
+        if finished.unwrap_or(true) {
+            yield Ok(end_event(next_seq));
+            return;
+        }
+
+        loop {
+            match tokio::time::timeout(idle, rx.recv()).await {
+                Ok(Ok(event)) if event.execution_id == execution_id && event.seq >= next_seq => {
+                    next_seq = event.seq + 1;
+                    yield Ok(event.to_sse());
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(RecvError::Lagged(skipped))) => {
+                    warn!("Log tail subscriber lagged by {} segments, dropping", skipped);
+                    yield Ok(sse::terminal_event("lagged", serde_json::json!({ "skipped": skipped, "next_seq": next_seq })));
+                    break;
+                }
+                Ok(Err(RecvError::Closed)) => break,
+                Err(_) => {
+                    yield Ok(sse::terminal_event("idle", serde_json::json!({ "idle_secs": idle.as_secs(), "next_seq": next_seq })));
+                    break;
+                }
+            }
+        }
+    };
+    Box::pin(s)
+}
+
+/// Segments with `seq >= from_seq`, bytes loaded from the blob store
+async fn stored_segments(state: &ExecLogState, execution_id: &str, from_seq: i64) -> Result<Vec<LogEvent>, String> {
+    let rows = sqlx::query(
+        r#"
+        SELECT seq, byte_offset, chain_hash, content_hash
+        FROM exec_log_segments
+        WHERE execution_id = $1 AND seq >= $2
+        ORDER BY seq
+        "#,
+    )
+    .bind(execution_id)
+    .bind(from_seq)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let blobs = state.blobs.clone();
+    let execution_id = execution_id.to_string();
+    let rows: Vec<(i64, i64, String, String)> = rows
+        .iter()
+        .map(|r| (Row::get(r, "seq"), Row::get(r, "byte_offset"), Row::get(r, "chain_hash"), Row::get(r, "content_hash")))
+        .collect();
+
+    tokio::task::spawn_blocking(move || {
+        rows.into_iter()
+            .map(|(seq, byte_offset, chain_hash, content_hash)| {
+                let data = blobs
+                    .get(&content_hash)
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("log blob {} missing", content_hash))?;
+                Ok(LogEvent { execution_id: execution_id.clone(), seq, byte_offset, chain_hash, data: Bytes::from(data) })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// Next link in the segment chain
+pub fn chain_hash(prev_hash: &str, content_hash: &str) -> String {
+    blob_store::content_hash(format!("{}\n{}", prev_hash, content_hash).as_bytes())
+}
+
+fn segment_message(execution_id: &str, runner_id: &str, seq: i64, prev_hash: &str, content_hash: &str) -> String {
+    format!("ubl:exec:log\n{}\n{}\n{}\n{}\n{}", execution_id, runner_id, seq, prev_hash, content_hash)
+}
+
+/// Single `bytes=` range against a log of `total` bytes; inclusive bounds.
+/// Supports `a-b`, `a-` and `-n` (last n bytes).
+fn parse_range(spec: &str, total: u64) -> Option<(u64, u64)> {
+    let spec = spec.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || total == 0 {
+        return None;
+    }
+    let (a, b) = spec.split_once('-')?;
+    let (start, end) = match (a.trim(), b.trim()) {
+        ("", n) => {
+            let n: u64 = n.parse().ok()?;
+            if n == 0 {
+                return None;
+            }
+            (total.saturating_sub(n), total - 1)
+        }
+        (a, "") => (a.parse().ok()?, total - 1),
+        (a, b) => (a.parse().ok()?, b.parse::<u64>().ok()?.min(total - 1)),
+    };
+    (start <= end && start < total).then_some((start, end))
+}
+
+fn log_headers(head_hash: &str, content_range: Option<String>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, "text/plain; charset=utf-8".parse().unwrap());
+    headers.insert(header::ACCEPT_RANGES, "bytes".parse().unwrap());
+    if let Ok(v) = head_hash.parse() {
+        headers.insert("x-ubl-log-head", v);
+    }
+    if let Some(Ok(v)) = content_range.map(|r| r.parse()) {
+        headers.insert(header::CONTENT_RANGE, v);
+    }
+    headers
+}
+
+fn end_event(next_seq: i64) -> Event {
+    Event::default()
+        .event("end")
+        .data(serde_json::json!({ "reason": "finished", "next_seq": next_seq, "reconnect": false }).to_string())
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    ApiError::new(ErrorCode::DatabaseError, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_links_segments() {
+        let h0 = chain_hash(GENESIS_HASH, &blob_store::content_hash(b"line 1\n"));
+        let h1 = chain_hash(&h0, &blob_store::content_hash(b"line 2\n"));
+        assert!(blob_store::parse_hash(&h1).is_some());
+        // Reordering segments changes the head
+        let r0 = chain_hash(GENESIS_HASH, &blob_store::content_hash(b"line 2\n"));
+        let r1 = chain_hash(&r0, &blob_store::content_hash(b"line 1\n"));
+        assert_ne!(h1, r1);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some((0, 9)));
+        assert_eq!(parse_range("bytes=90-", 100), Some((90, 99)));
+        assert_eq!(parse_range("bytes=-10", 100), Some((90, 99)));
+        assert_eq!(parse_range("bytes=-500", 100), Some((0, 99)));
+        assert_eq!(parse_range("bytes=50-500", 100), Some((50, 99)));
+        assert_eq!(parse_range("bytes=100-", 100), None);
+        assert_eq!(parse_range("bytes=9-0", 100), None);
+        assert_eq!(parse_range("bytes=0-1,5-6", 100), None);
+        assert_eq!(parse_range("items=0-1", 100), None);
+        assert_eq!(parse_range("bytes=0-", 0), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Feature flags — per-container switches, governed like any other rule
+//!
+//! A flag set is an Evolution atom, committed with a pact like a manifest
+//! change, so every switch is in the ledger with who flipped it:
+//!
+//! ```json
+//! {"type": "evolution.feature_flags", "container_id": "C.Messenger",
+//!  "flags": {"threading": true, "reactions": false}}
+//! ```
+//!
+//! Each atom replaces the whole set; a flag left out is off. Names are
+//! lowercase identifiers (`[a-z][a-z0-9_]*`, at most 64 bytes, 128 flags).
+//!
+//! Where flags show up:
+//! - the policy VM reads them as `flag.<name>` (DSL) or the `flag_enabled`
+//!   constraint, through `ExecutionContext::flags`;
+//! - the container manifest lists them (`feature_flags`), so Office gets
+//!   them with the rest of the rules and its ETag changes with them;
+//! - `GET /v1/containers/:id/flags/stream` (SSE) sends a `flags.v1` event
+//!   with the current set on connect and after every change.
+//!
+//! Admission reads flags through [`FeatureFlags`], a cache refreshed every
+//! `UBL_FEATURE_FLAGS_CACHE_SECS` (default 30). A flag change committed on
+//! this replica updates it at once; other replicas see it within that time.
+
+use std::collections::{BTreeMap, HashMap};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Router,
+};
+use futures_util::stream::Stream;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::evolution::changelog_target;
+use crate::projections::ChangelogProjection;
+use crate::sse::{self, TailBus};
+use crate::timestamps::now_ms;
+
+/// Atom type of a flag set
+pub const FLAGS_TYPE: &str = "evolution.feature_flags";
+/// Changelog subject of flag sets
+pub const FLAGS_SUBJECT: &str = "flags";
+/// SSE event carrying a flag set
+pub const FLAGS_EVENT: &str = "flags.v1";
+/// Most flags a container may have
+pub const MAX_FLAGS: usize = 128;
+/// Longest flag name
+pub const MAX_FLAG_NAME_BYTES: usize = 64;
+
+/// A lowercase identifier of at most [`MAX_FLAG_NAME_BYTES`]
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let well_formed = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !well_formed || name.len() > MAX_FLAG_NAME_BYTES {
+        return Err(format!(
+            "flag name '{}' must match [a-z][a-z0-9_]* and be at most {} bytes",
+            name, MAX_FLAG_NAME_BYTES
+        ));
+    }
+    Ok(())
+}
+
+/// Flags of one container
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FlagSet {
+    pub container_id: String,
+    pub flags: BTreeMap<String, bool>,
+    /// Entry of the flag set in force; `None` when none was ever committed
+    pub entry_hash: Option<String>,
+}
+
+impl FlagSet {
+    fn from_atom(container_id: &str, atom: &Value, entry_hash: &str) -> Self {
+        let flags = atom["flags"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, on)| Some((name.clone(), on.as_bool()?)))
+            .collect();
+        Self { container_id: container_id.to_string(), flags, entry_hash: Some(entry_hash.to_string()) }
+    }
+}
+
+/// Flag set of `container_id` from the evolution changelog (uncached)
+pub async fn load(pool: &PgPool, container_id: &str) -> Result<FlagSet, sqlx::Error> {
+    let latest = ChangelogProjection::new(pool.clone())
+        .latest(container_id, FLAGS_TYPE, FLAGS_SUBJECT)
+        .await?;
+    Ok(match latest {
+        Some(entry) => FlagSet::from_atom(container_id, &entry.atom, &entry.entry_hash),
+        None => FlagSet { container_id: container_id.to_string(), ..FlagSet::default() },
+    })
+}
+
+// =============================================================================
+// CACHE
+// =============================================================================
+
+#[derive(Debug, Clone)]
+pub struct FeatureFlagsConfig {
+    pub cache_secs: u64,
+}
+
+impl Default for FeatureFlagsConfig {
+    fn default() -> Self {
+        Self { cache_secs: 30 }
+    }
+}
+
+impl FeatureFlagsConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            cache_secs: std::env::var("UBL_FEATURE_FLAGS_CACHE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.cache_secs),
+        }
+    }
+}
+
+struct Cached {
+    set: Arc<FlagSet>,
+    loaded_ms: i64,
+}
+
+/// Flag sets by container, reloaded once older than the cache time
+pub struct FeatureFlags {
+    pool: PgPool,
+    ttl_ms: i64,
+    cache: Mutex<HashMap<String, Cached>>,
+}
+
+impl FeatureFlags {
+    pub fn new(pool: PgPool, config: FeatureFlagsConfig) -> Self {
+        Self { pool, ttl_ms: (config.cache_secs * 1000) as i64, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Flags of `container_id`
+    pub async fn get(&self, container_id: &str) -> Result<Arc<FlagSet>, sqlx::Error> {
+        if let Some(set) = self.cached(container_id, now_ms()) {
+            return Ok(set);
+        }
+        let set = Arc::new(load(&self.pool, container_id).await?);
+        self.store(set.clone(), now_ms());
+        Ok(set)
+    }
+
+    /// Flags for policy evaluation; none (every flag off) when they cannot be read
+    pub async fn for_policy(&self, container_id: &str) -> BTreeMap<String, bool> {
+        match self.get(container_id).await {
+            Ok(set) => set.flags.clone(),
+            Err(e) => {
+                warn!("⚠️  Feature flags unavailable for {}: {}", container_id, e);
+                BTreeMap::new()
+            }
+        }
+    }
+
+    /// A flag set was just committed to `container_id`: serve it from now on
+    pub fn observe_commit(&self, container_id: &str, atom: &Value, entry_hash: &str) {
+        let target = changelog_target(container_id, atom);
+        let set = FlagSet::from_atom(&target, atom, entry_hash);
+        info!("🚩 Feature flags of {} now {:?}", target, set.flags);
+        self.store(Arc::new(set), now_ms());
+    }
+
+    fn cached(&self, container_id: &str, now_ms: i64) -> Option<Arc<FlagSet>> {
+        let cache = self.cache.lock().unwrap();
+        cache
+            .get(container_id)
+            .filter(|c| now_ms - c.loaded_ms < self.ttl_ms)
+            .map(|c| c.set.clone())
+    }
+
+    fn store(&self, set: Arc<FlagSet>, now_ms: i64) {
+        self.cache.lock().unwrap().insert(set.container_id.clone(), Cached { set, loaded_ms: now_ms });
+    }
+
+    fn refresh_interval(&self) -> Duration {
+        Duration::from_millis(self.ttl_ms.max(1000) as u64)
+    }
+}
+
+/// Cached flag sets; they reload on the next read anyway
+impl crate::maintenance::Maintained for FeatureFlags {
+    fn entries(&self) -> usize {
+        self.cache.lock().unwrap().len()
+    }
+
+    fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize {
+        let mut cache = self.cache.lock().unwrap();
+        let before = cache.len();
+        cache.retain(|_, c| now_ms - c.loaded_ms <= ttl_ms);
+        before - cache.len()
+    }
+
+    fn flush(&self) -> usize {
+        std::mem::take(&mut *self.cache.lock().unwrap()).len()
+    }
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+#[derive(Clone)]
+struct FlagsState {
+    flags: Arc<FeatureFlags>,
+    bus: TailBus,
+}
+
+pub fn routes(flags: Arc<FeatureFlags>, bus: TailBus) -> Router {
+    Router::new()
+        .route("/v1/containers/:id/flags/stream", get(stream_flags))
+        .with_state(FlagsState { flags, bus })
+}
+
+fn flags_event(set: &FlagSet) -> Event {
+    Event::default()
+        .event(FLAGS_EVENT)
+        .id(set.entry_hash.clone().unwrap_or_default())
+        .data(serde_json::to_string(set).unwrap_or_default())
+}
+
+/// GET /v1/containers/:id/flags/stream — the flag set now, then on change
+async fn stream_flags(
+    State(state): State<FlagsState>,
+    Path(container_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    let tenant = sse::tenant_key(&headers, &params);
+    let Some(guard) = state.bus.connections.try_acquire(&tenant, state.bus.limits.max_per_tenant) else {
+        return (StatusCode::TOO_MANY_REQUESTS, "SSE connection limit reached for tenant").into_response();
+    };
+    let keep_alive = state.bus.limits.keep_alive();
+    Sse::new(flag_stream(state, container_id, guard)).keep_alive(keep_alive).into_response()
+}
+
+/// Changes committed here arrive on the tail; those of other replicas are
+/// picked up when the cache refreshes
+fn flag_stream(
+    state: FlagsState,
+    container_id: String,
+    guard: sse::ConnectionGuard,
+) -> Pin<Box<dyn Stream<Item = Result<Event, std::convert::Infallible>> + Send>> {
+    // Subscribe before reading the flags so no change falls in between
+    let mut rx = state.bus.tx.subscribe();
+    let idle = state.bus.limits.idle_timeout;
+    let refresh = state.flags.refresh_interval();
+    let s = async_stream::stream! {
+        let _guard = guard;
+        let mut current: Option<Option<String>> = None;
+        let mut deadline = tokio::time::Instant::now() + idle;
+        loop {
+            match state.flags.get(&container_id).await {
+                Ok(set) if current.as_ref() != Some(&set.entry_hash) => {
+                    current = Some(set.entry_hash.clone());
+                    deadline = tokio::time::Instant::now() + idle;
+                    yield Ok(flags_event(&set));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Flag stream for {} failed: {}", container_id, e);
+                    yield Ok(sse::terminal_event("error", serde_json::json!({ "message": e.to_string() })));
+                    break;
+                }
+            }
+            // Wait for a flag commit, the next refresh or the idle deadline
+            loop {
+                let wait = refresh.min(deadline.saturating_duration_since(tokio::time::Instant::now()));
+                match tokio::time::timeout(wait, rx.recv()).await {
+                    Ok(Ok(entry)) if entry.event_type.as_deref() == Some(FLAGS_TYPE) => break,
+                    Ok(Ok(_)) => continue,
+                    // Missed entries may include a flag change: check
+                    Ok(Err(RecvError::Lagged(_))) => break,
+                    Ok(Err(RecvError::Closed)) => return,
+                    Err(_) => break,
+                }
+            }
+            if tokio::time::Instant::now() >= deadline {
+                yield Ok(sse::terminal_event("idle", serde_json::json!({ "idle_secs": idle.as_secs() })));
+                break;
+            }
+        }
+    };
+    Box::pin(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maintenance::Maintained;
+    use serde_json::json;
+
+    #[test]
+    fn test_flag_names() {
+        for name in ["threading", "v2_composer", "a"] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "Threading", "2fa", "dark-mode", "flag.x", &"x".repeat(MAX_FLAG_NAME_BYTES + 1)] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_updates_the_cache() {
+        let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap();
+        let flags = FeatureFlags::new(pool, FeatureFlagsConfig::default());
+        let atom = json!({
+            "type": FLAGS_TYPE,
+            "container_id": "C.Messenger",
+            "flags": { "threading": true, "reactions": false },
+        });
+        // Committed from a governance container, for C.Messenger
+        flags.observe_commit("C.Governance", &atom, "e1");
+
+        let set = flags.get("C.Messenger").await.unwrap();
+        assert_eq!(set.flags.get("threading"), Some(&true));
+        assert_eq!(set.entry_hash.as_deref(), Some("e1"));
+        assert_eq!(flags.for_policy("C.Messenger").await.len(), 2);
+
+        assert!(flags.cached("C.Messenger", now_ms() + 31_000).is_none(), "stale after the cache time");
+        assert_eq!(flags.evict_idle(now_ms() + 10_000, 5_000), 1);
+        assert_eq!(flags.entries(), 0);
+    }
+}
@@ -0,0 +1,347 @@
+//! # Health
+//!
+//! `GET /health` stays a cheap liveness probe: static status plus build info,
+//! no I/O. `GET /health?verbose=1` reports per component:
+//!
+//! - `database`: a round trip to Postgres and its latency
+//! - `notify`: whether the `ubl_tail` LISTEN connection is up, when the last
+//!   NOTIFY arrived and how many were received since start
+//! - `projections`: for each projected container, the chain head (sequence
+//!   and hash) against the last projected sequence (`projection_state`);
+//!   `lag` is the difference
+//! - `chain`: the startup chain self-check, per verified container (see
+//!   `chain_check`)
+//! - `sse`: open SSE streams (ledger tail, gateway, exec logs), total and
+//!   per tenant
+//!
+//! A verbose probe answers `503` when the database is unreachable, `200`
+//! otherwise (`degraded` while the NOTIFY listener is down or a container is
+//! quarantined).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::chain_check::{self, ContainerCheck};
+use crate::projections::AUDIT_CONTAINER;
+use crate::timestamps::now_ms;
+use crate::AppState;
+
+pub const VERSION: &str = "2.0.0+postgres";
+
+/// Channel the `ubl_notify_minimal` trigger publishes on
+pub const TAIL_CHANNEL: &str = "ubl_tail";
+
+/// Containers whose entries feed the live projections
+pub const PROJECTED_CONTAINERS: [&str; 4] = ["C.Jobs", "C.Messenger", "C.Office", AUDIT_CONTAINER];
+
+/// Bound on each database call made by a verbose probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+static NOTIFY_LISTENING: AtomicBool = AtomicBool::new(false);
+static NOTIFY_LAST_MS: AtomicI64 = AtomicI64::new(0);
+static NOTIFY_RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub package: &'static str,
+    /// Git commit, from `UBL_BUILD_COMMIT` at compile time
+    pub commit: &'static str,
+    pub profile: &'static str,
+}
+
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        package: env!("CARGO_PKG_VERSION"),
+        commit: option_env!("UBL_BUILD_COMMIT").unwrap_or("unknown"),
+        profile: if cfg!(debug_assertions) { "debug" } else { "release" },
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    pub status: &'static str,
+    pub version: &'static str,
+    pub build: BuildInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Components>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Components {
+    pub database: DatabaseHealth,
+    pub notify: NotifyHealth,
+    pub projections: Vec<ProjectionLag>,
+    pub chain: Vec<ContainerCheck>,
+    pub sse: SseHealth,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseHealth {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotifyHealth {
+    pub listening: bool,
+    pub last_received_ms: Option<i64>,
+    pub received: u64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ProjectionLag {
+    pub container_id: String,
+    pub head_sequence: i64,
+    pub head_hash: Option<String>,
+    pub projected_sequence: i64,
+    pub lag: i64,
+}
+
+impl ProjectionLag {
+    fn new(container_id: String, projected_sequence: i64, head: Option<(i64, String)>) -> Self {
+        let (head_sequence, head_hash) = match head {
+            Some((sequence, hash)) => (sequence, Some(hash)),
+            None => (0, None),
+        };
+        Self {
+            container_id,
+            head_sequence,
+            head_hash,
+            projected_sequence,
+            lag: (head_sequence - projected_sequence).max(0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SseHealth {
+    pub open: usize,
+    pub per_tenant: BTreeMap<String, usize>,
+}
+
+/// GET /health[?verbose=1]
+pub async fn route_health(
+    State(state): State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> (StatusCode, Json<HealthResponse>) {
+    let mut response = HealthResponse {
+        status: "healthy",
+        version: VERSION,
+        build: build_info(),
+        components: None,
+    };
+    if !is_verbose(&params) {
+        return (StatusCode::OK, Json(response));
+    }
+
+    let database = probe_database(&state.pool).await;
+    let projections = if database.ok {
+        projection_lag(&state.pool).await.unwrap_or_else(|e| {
+            warn!("⚠️  Health: projection lag query failed: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+    let per_tenant = crate::sse::open_streams();
+    let components = Components {
+        notify: notify_health(),
+        sse: SseHealth { open: per_tenant.values().sum(), per_tenant },
+        database,
+        projections,
+        chain: chain_check::results(),
+    };
+
+    response.status = overall_status(&components);
+    let code = if response.status == "unhealthy" { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK };
+    response.components = Some(components);
+    (code, Json(response))
+}
+
+fn is_verbose(params: &HashMap<String, String>) -> bool {
+    params
+        .get("verbose")
+        .is_some_and(|v| matches!(v.as_str(), "" | "1" | "true" | "yes"))
+}
+
+fn overall_status(components: &Components) -> &'static str {
+    if !components.database.ok {
+        "unhealthy"
+    } else if !components.notify.listening || components.chain.iter().any(|check| !check.ok) {
+        "degraded"
+    } else {
+        "healthy"
+    }
+}
+
+async fn probe_database(pool: &PgPool) -> DatabaseHealth {
+    let started = Instant::now();
+    match tokio::time::timeout(PROBE_TIMEOUT, sqlx::query("SELECT 1").execute(pool)).await {
+        Ok(Ok(_)) => DatabaseHealth {
+            ok: true,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            error: None,
+        },
+        Ok(Err(e)) => DatabaseHealth { ok: false, latency_ms: None, error: Some(e.to_string()) },
+        Err(_) => DatabaseHealth {
+            ok: false,
+            latency_ms: None,
+            error: Some(format!("no answer within {:?}", PROBE_TIMEOUT)),
+        },
+    }
+}
+
+/// Chain head against projected sequence for every tracked container
+async fn projection_lag(pool: &PgPool) -> anyhow::Result<Vec<ProjectionLag>> {
+    let rows: Vec<(String, i64, Option<i64>, Option<String>)> = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        sqlx::query_as(
+            r#"
+            SELECT p.container_id, p.last_sequence, h.sequence, h.entry_hash
+            FROM projection_state p
+            LEFT JOIN LATERAL (
+                SELECT sequence, entry_hash FROM ledger_entry e
+                WHERE e.container_id = p.container_id
+                ORDER BY sequence DESC
+                LIMIT 1
+            ) h ON true
+            ORDER BY p.container_id
+            "#,
+        )
+        .fetch_all(pool),
+    )
+    .await??;
+
+    Ok(rows
+        .into_iter()
+        .map(|(container_id, projected, head_sequence, head_hash)| {
+            ProjectionLag::new(container_id, projected, head_sequence.zip(head_hash))
+        })
+        .collect())
+}
+
+/// Record that the live projections have caught up to `sequence` in
+/// `container_id`. Out-of-order calls never move the mark backwards.
+pub async fn record_projected(pool: PgPool, container_id: String, sequence: i64, entry_hash: String) {
+    if !PROJECTED_CONTAINERS.contains(&container_id.as_str()) {
+        return;
+    }
+    let result = sqlx::query(
+        r#"
+        INSERT INTO projection_state (container_id, last_sequence, last_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (container_id) DO UPDATE
+        SET last_sequence = EXCLUDED.last_sequence, last_hash = EXCLUDED.last_hash
+        WHERE projection_state.last_sequence < EXCLUDED.last_sequence
+        "#,
+    )
+    .bind(&container_id)
+    .bind(sequence)
+    .bind(&entry_hash)
+    .execute(&pool)
+    .await;
+    if let Err(e) = result {
+        warn!("⚠️  Failed to record projection progress for {}: {}", container_id, e);
+    }
+}
+
+fn notify_health() -> NotifyHealth {
+    let last = NOTIFY_LAST_MS.load(Ordering::Relaxed);
+    NotifyHealth {
+        listening: NOTIFY_LISTENING.load(Ordering::Relaxed),
+        last_received_ms: (last > 0).then_some(last),
+        received: NOTIFY_RECEIVED.load(Ordering::Relaxed),
+    }
+}
+
+/// LISTEN on the tail channel for the life of the process, reconnecting
+/// after errors, and note every NOTIFY received
+pub async fn listen_tail(pool: PgPool) {
+    loop {
+        match PgListener::connect_with(&pool).await {
+            Ok(mut listener) => match listener.listen(TAIL_CHANNEL).await {
+                Ok(()) => {
+                    info!("📡 Listening for NOTIFY on '{}'", TAIL_CHANNEL);
+                    NOTIFY_LISTENING.store(true, Ordering::Relaxed);
+                    while let Ok(_notification) = listener.recv().await {
+                        NOTIFY_LAST_MS.store(now_ms(), Ordering::Relaxed);
+                        NOTIFY_RECEIVED.fetch_add(1, Ordering::Relaxed);
+                    }
+                    NOTIFY_LISTENING.store(false, Ordering::Relaxed);
+                    warn!("⚠️  NOTIFY listener on '{}' dropped, reconnecting", TAIL_CHANNEL);
+                }
+                Err(e) => warn!("⚠️  LISTEN {} failed: {}", TAIL_CHANNEL, e),
+            },
+            Err(e) => warn!("⚠️  NOTIFY listener could not connect: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_verbose_flag() {
+        assert!(!is_verbose(&params(&[])));
+        assert!(is_verbose(&params(&[("verbose", "")])));
+        assert!(is_verbose(&params(&[("verbose", "1")])));
+        assert!(is_verbose(&params(&[("verbose", "true")])));
+        assert!(!is_verbose(&params(&[("verbose", "0")])));
+    }
+
+    #[test]
+    fn test_projection_lag() {
+        let lag = ProjectionLag::new("C.Jobs".into(), 40, Some((42, "abc".into())));
+        assert_eq!(lag.lag, 2);
+        assert_eq!(lag.head_hash.as_deref(), Some("abc"));
+
+        // Empty chain, or a mark ahead of a head read a moment earlier
+        assert_eq!(ProjectionLag::new("C.Office".into(), 0, None).lag, 0);
+        assert_eq!(ProjectionLag::new("C.Jobs".into(), 43, Some((42, "abc".into()))).lag, 0);
+    }
+
+    #[test]
+    fn test_overall_status() {
+        let mut components = Components {
+            database: DatabaseHealth { ok: true, latency_ms: Some(1), error: None },
+            notify: NotifyHealth { listening: true, last_received_ms: None, received: 0 },
+            projections: Vec::new(),
+            chain: vec![ContainerCheck {
+                container_id: "C.Jobs".into(),
+                checked: 100,
+                head_sequence: 420,
+                ok: true,
+                error: None,
+            }],
+            sse: SseHealth { open: 0, per_tenant: BTreeMap::new() },
+        };
+        assert_eq!(overall_status(&components), "healthy");
+        components.chain[0].ok = false;
+        assert_eq!(overall_status(&components), "degraded");
+        components.chain[0].ok = true;
+        components.notify.listening = false;
+        assert_eq!(overall_status(&components), "degraded");
+        components.database.ok = false;
+        assert_eq!(overall_status(&components), "unhealthy");
+    }
+}
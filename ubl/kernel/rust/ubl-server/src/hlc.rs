@@ -0,0 +1,162 @@
+//! # Hybrid Logical Clock
+//!
+//! `ts_unix_ms` is the wall clock of the replica that appended an entry, and
+//! replicas in different regions disagree: a container's entries can carry
+//! timestamps that go backwards. Every entry therefore also gets an HLC
+//! (`ledger_entry.hlc_wall_ms`, `hlc_logical`) that is
+//!
+//! - strictly after the previous entry of the same container,
+//! - strictly after the last HLC this node issued, and
+//! - equal to the wall clock whenever the wall clock is ahead of both.
+//!
+//! Inside a container `sequence` is the order; the HLC is for reasoning
+//! across containers (merging tails, "happened before" between a message and
+//! the job it started). It is not part of `entry_hash`.
+//!
+//! On the wire an HLC is a fixed-width string, `{wall_ms:013}.{logical:06}`,
+//! so it sorts as text and survives JavaScript numbers.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use prometheus::{register_int_gauge, IntGauge};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Logical counter ceiling; one more carries into `wall_ms`
+pub const LOGICAL_MAX: i32 = 999_999;
+
+lazy_static! {
+    pub static ref HLC_DRIFT_MS: IntGauge = register_int_gauge!(
+        "ubl_hlc_drift_ms",
+        "How far the last issued HLC is ahead of this node's wall clock (ms)"
+    ).unwrap();
+}
+
+/// Last HLC issued by this node
+static NODE: Mutex<Hlc> = Mutex::new(Hlc::ZERO);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::FromRow)]
+pub struct Hlc {
+    #[sqlx(rename = "hlc_wall_ms")]
+    pub wall_ms: i64,
+    #[sqlx(rename = "hlc_logical")]
+    pub logical: i32,
+}
+
+impl Hlc {
+    pub const ZERO: Self = Self { wall_ms: 0, logical: 0 };
+
+    pub fn new(wall_ms: i64, logical: i32) -> Self {
+        Self { wall_ms, logical }
+    }
+
+    /// HLC of a stored entry; entries from before the clock read as their wall time
+    pub fn stored(wall_ms: Option<i64>, logical: Option<i32>, ts_unix_ms: i64) -> Self {
+        match wall_ms {
+            Some(wall_ms) => Self::new(wall_ms, logical.unwrap_or(0)),
+            None => Self::new(ts_unix_ms, 0),
+        }
+    }
+
+    /// The smallest HLC after this one
+    pub fn successor(self) -> Self {
+        if self.logical < LOGICAL_MAX {
+            Self::new(self.wall_ms, self.logical + 1)
+        } else {
+            Self::new(self.wall_ms + 1, 0)
+        }
+    }
+}
+
+/// HLC for an entry following `prev` in its container, given the last HLC
+/// `node` issued and the wall clock `now_ms`
+pub fn advance(node: Hlc, prev: Hlc, now_ms: i64) -> Hlc {
+    let base = node.max(prev);
+    if now_ms > base.wall_ms {
+        Hlc::new(now_ms, 0)
+    } else {
+        base.successor()
+    }
+}
+
+/// Issue the HLC of an entry appended after `prev` (None for a first entry)
+pub fn tick(prev: Option<Hlc>) -> Hlc {
+    let now_ms = crate::timestamps::now_ms();
+    let mut node = NODE.lock().unwrap_or_else(|e| e.into_inner());
+    *node = advance(*node, prev.unwrap_or(Hlc::ZERO), now_ms);
+    HLC_DRIFT_MS.set((node.wall_ms - now_ms).max(0));
+    *node
+}
+
+/// The HLC `tick` would issue now, without issuing it
+pub fn peek(prev: Option<Hlc>) -> Hlc {
+    let node = *NODE.lock().unwrap_or_else(|e| e.into_inner());
+    advance(node, prev.unwrap_or(Hlc::ZERO), crate::timestamps::now_ms())
+}
+
+impl fmt::Display for Hlc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:013}.{:06}", self.wall_ms, self.logical)
+    }
+}
+
+impl FromStr for Hlc {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (wall, logical) = s.split_once('.').ok_or_else(|| format!("malformed HLC '{}'", s))?;
+        let wall_ms = wall.parse().map_err(|_| format!("malformed HLC wall time '{}'", wall))?;
+        let logical = logical.parse().map_err(|_| format!("malformed HLC counter '{}'", logical))?;
+        if !(0..=LOGICAL_MAX).contains(&logical) {
+            return Err(format!("HLC counter out of range '{}'", logical));
+        }
+        Ok(Self::new(wall_ms, logical))
+    }
+}
+
+impl Serialize for Hlc {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hlc {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_never_goes_backwards() {
+        // Wall clock ahead of everything: HLC is the wall clock
+        assert_eq!(advance(Hlc::new(100, 3), Hlc::new(90, 0), 120), Hlc::new(120, 0));
+        // Previous entry written by a replica 50ms ahead of us
+        let prev = Hlc::new(170, 2);
+        let next = advance(Hlc::new(120, 0), prev, 125);
+        assert_eq!(next, Hlc::new(170, 3));
+        assert!(next > prev);
+        // This node already issued something later than the container head
+        assert_eq!(advance(Hlc::new(200, 0), Hlc::new(170, 2), 125), Hlc::new(200, 1));
+        // Counter overflow carries into the wall time
+        assert_eq!(advance(Hlc::ZERO, Hlc::new(10, LOGICAL_MAX), 5), Hlc::new(11, 0));
+    }
+
+    #[test]
+    fn test_wire_format_sorts_as_text() {
+        let a = Hlc::new(1_760_000_000_000, 9);
+        let b = Hlc::new(1_760_000_000_000, 10);
+        let c = Hlc::new(1_760_000_000_001, 0);
+        assert_eq!(a.to_string(), "1760000000000.000009");
+        assert!(a.to_string() < b.to_string() && b.to_string() < c.to_string());
+        assert_eq!(serde_json::to_value(b).unwrap(), serde_json::json!("1760000000000.000010"));
+        assert_eq!(serde_json::from_value::<Hlc>(serde_json::json!("1760000000001.000000")).unwrap(), c);
+        assert!("1760000000001".parse::<Hlc>().is_err());
+        assert_eq!(Hlc::stored(None, None, 42), Hlc::new(42, 0));
+    }
+}
@@ -0,0 +1,508 @@
+//! # Identity Anomaly Detection
+//!
+//! Per-username rate limits (`rate_limit`) do not see credential stuffing:
+//! one client cycling through many usernames, or one username tried from many
+//! places. The login and register begin routes also screen every attempt by
+//! client:
+//!
+//! - **IP** — `X-Real-IP`, else the first `X-Forwarded-For` hop; the edge
+//!   proxy must set (not forward) these. Each IP has a sliding-window limit
+//!   per route.
+//! - **Device** — BLAKE3 fingerprint of `User-Agent`, `Accept-Language`, the
+//!   platform client hints and an optional `X-UBL-Device-Id`.
+//!
+//! The attempt's [`Signals`] go through an [`AnomalyHook`] for a score; at the
+//! lock threshold the IP, and for a distributed attack the username, are
+//! locked for a while. Every lock is recorded as an `identity.locked`
+//! Observation in `C.Identity`, with subjects hashed (usernames may be emails).
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use axum::http::HeaderMap;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+use crate::db::PgLedger;
+use crate::messenger_gateway::card_provenance::append_signed;
+
+/// Container the identity audit events are recorded in
+pub const IDENTITY_CONTAINER: &str = "C.Identity";
+
+/// Atom type of a lock
+pub const LOCK_TYPE: &str = "identity.locked";
+
+/// Header a client may send to name its device
+const DEVICE_ID_HEADER: &str = "x-ubl-device-id";
+
+// =============================================================================
+// CLIENT CONTEXT
+// =============================================================================
+
+/// Who is asking, as far as the request headers tell
+#[derive(Debug, Clone)]
+pub struct ClientContext {
+    pub ip: String,
+    /// Hex BLAKE3 of the device headers (16 bytes)
+    pub device: String,
+    /// Whether a `User-Agent` was sent; scripted clients often omit it
+    pub has_agent: bool,
+}
+
+impl ClientContext {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::trim).unwrap_or("");
+        let ip = Some(header("x-real-ip"))
+            .filter(|ip| !ip.is_empty())
+            .or_else(|| header("x-forwarded-for").split(',').next().map(str::trim).filter(|ip| !ip.is_empty()))
+            .unwrap_or("unknown")
+            .to_string();
+
+        let mut hasher = blake3::Hasher::new();
+        for name in ["user-agent", "accept-language", "sec-ch-ua", "sec-ch-ua-platform", DEVICE_ID_HEADER] {
+            hasher.update(header(name).as_bytes());
+            hasher.update(b"\n");
+        }
+        Self {
+            ip,
+            device: hex::encode(&hasher.finalize().as_bytes()[..16]),
+            has_agent: !header("user-agent").is_empty(),
+        }
+    }
+}
+
+// =============================================================================
+// SCORING
+// =============================================================================
+
+/// Begin route being screened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdRoute {
+    Login,
+    Register,
+}
+
+impl IdRoute {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Login => "login",
+            Self::Register => "register",
+        }
+    }
+}
+
+/// What the window shows about an attempt, itself included
+#[derive(Debug, Clone, Default)]
+pub struct Signals {
+    /// Distinct usernames tried from the IP
+    pub ip_usernames: usize,
+    /// Logins for usernames that do not exist, from the IP
+    pub ip_misses: usize,
+    /// Distinct IPs that tried the username
+    pub username_ips: usize,
+    /// Distinct devices that tried the username
+    pub username_devices: usize,
+    pub has_agent: bool,
+}
+
+/// Suspicion of an attempt and why
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Score {
+    pub points: u32,
+    pub reasons: Vec<&'static str>,
+}
+
+impl Score {
+    fn add(&mut self, points: u32, reason: &'static str) {
+        if points > 0 {
+            self.points = self.points.saturating_add(points);
+            self.reasons.push(reason);
+        }
+    }
+}
+
+/// Reason that locks the username as well as the IP
+pub const DISTRIBUTED_REASON: &str = "many_ips_per_username";
+
+/// Suspicious-activity scoring; replace [`DefaultScoring`] to tune or to call
+/// out to a risk service
+pub trait AnomalyHook: Send + Sync {
+    fn score(&self, route: IdRoute, signals: &Signals) -> Score;
+}
+
+/// Points over small allowances for shared NATs and people with a few devices
+pub struct DefaultScoring;
+
+impl AnomalyHook for DefaultScoring {
+    fn score(&self, _route: IdRoute, signals: &Signals) -> Score {
+        let over = |count: usize, allowed: usize, points: u32| (count.saturating_sub(allowed) as u32).saturating_mul(points);
+        let mut score = Score::default();
+        score.add(over(signals.ip_usernames, 3, 25), "many_usernames_per_ip");
+        score.add(over(signals.ip_misses, 2, 20), "unknown_usernames");
+        score.add(over(signals.username_ips, 3, 20), DISTRIBUTED_REASON);
+        score.add(over(signals.username_devices, 3, 15), "many_devices_per_username");
+        if !signals.has_agent {
+            score.add(10, "no_user_agent");
+        }
+        score
+    }
+}
+
+// =============================================================================
+// DETECTOR
+// =============================================================================
+
+#[derive(Debug, Clone)]
+pub struct AnomalyConfig {
+    pub window_ms: i64,
+    pub max_login_per_ip: usize,
+    pub max_register_per_ip: usize,
+    pub lock_threshold: u32,
+    pub lock_ms: i64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 10 * 60 * 1000,
+            max_login_per_ip: 30,
+            max_register_per_ip: 10,
+            lock_threshold: 100,
+            lock_ms: 15 * 60 * 1000,
+        }
+    }
+}
+
+/// A lock set by an attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lock {
+    /// `ip` or `username`
+    pub subject_kind: &'static str,
+    pub subject: String,
+    pub route: IdRoute,
+    pub device: String,
+    pub score: Score,
+    pub until_ms: i64,
+}
+
+/// Outcome of screening an attempt
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Allow { score: u32 },
+    /// Over the per-IP window limit
+    Throttled { retry_after_secs: u64 },
+    /// Locked, by an earlier attempt or (`locks` not empty) by this one
+    Locked { retry_after_secs: u64, locks: Vec<Lock> },
+}
+
+#[derive(Debug, Clone)]
+struct Attempt {
+    at_ms: i64,
+    route: IdRoute,
+    ip: String,
+    username: Option<String>,
+    device: String,
+}
+
+#[derive(Default)]
+struct AnomalyState {
+    by_ip: HashMap<String, VecDeque<Attempt>>,
+    by_username: HashMap<String, VecDeque<Attempt>>,
+    misses: HashMap<String, VecDeque<i64>>,
+    /// `ip:<ip>` / `username:<name>` → locked until
+    locks: HashMap<String, i64>,
+}
+
+#[derive(Clone)]
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    hook: Arc<dyn AnomalyHook>,
+    state: Arc<Mutex<AnomalyState>>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig, hook: Arc<dyn AnomalyHook>) -> Self {
+        Self { config, hook, state: Arc::new(Mutex::new(AnomalyState::default())) }
+    }
+
+    /// Screen a begin attempt and record it in the window
+    pub fn check(&self, route: IdRoute, username: Option<&str>, client: &ClientContext, now_ms: i64) -> Verdict {
+        let mut state = self.state.lock().unwrap();
+        let since = now_ms - self.config.window_ms;
+        state.locks.retain(|_, until| *until > now_ms);
+        prune(&mut state.by_ip, since, |a| a.at_ms);
+        prune(&mut state.by_username, since, |a| a.at_ms);
+        prune(&mut state.misses, since, |at| *at);
+
+        let lock_keys = [Some(format!("ip:{}", client.ip)), username.map(|u| format!("username:{}", u))];
+        if let Some(until) = lock_keys.iter().flatten().filter_map(|k| state.locks.get(k)).max() {
+            return Verdict::Locked { retry_after_secs: retry_after(*until, now_ms), locks: Vec::new() };
+        }
+
+        let attempt = Attempt {
+            at_ms: now_ms,
+            route,
+            ip: client.ip.clone(),
+            username: username.map(String::from),
+            device: client.device.clone(),
+        };
+        state.by_ip.entry(client.ip.clone()).or_default().push_back(attempt.clone());
+        if let Some(username) = username {
+            state.by_username.entry(username.to_string()).or_default().push_back(attempt);
+        }
+
+        let from_ip = &state.by_ip[&client.ip];
+        let on_route: Vec<&Attempt> = from_ip.iter().filter(|a| a.route == route).collect();
+        let limit = match route {
+            IdRoute::Login => self.config.max_login_per_ip,
+            IdRoute::Register => self.config.max_register_per_ip,
+        };
+        if on_route.len() > limit {
+            let oldest = on_route[on_route.len() - limit - 1].at_ms;
+            return Verdict::Throttled { retry_after_secs: retry_after(oldest + self.config.window_ms, now_ms) };
+        }
+
+        let for_username = username.and_then(|u| state.by_username.get(u));
+        let signals = Signals {
+            ip_usernames: distinct(from_ip.iter().filter_map(|a| a.username.as_deref())),
+            ip_misses: state.misses.get(&client.ip).map_or(0, VecDeque::len),
+            username_ips: for_username.map_or(0, |q| distinct(q.iter().map(|a| a.ip.as_str()))),
+            username_devices: for_username.map_or(0, |q| distinct(q.iter().map(|a| a.device.as_str()))),
+            has_agent: client.has_agent,
+        };
+        let score = self.hook.score(route, &signals);
+        if score.points < self.config.lock_threshold {
+            return Verdict::Allow { score: score.points };
+        }
+
+        let until_ms = now_ms + self.config.lock_ms;
+        let mut subjects = vec![("ip", client.ip.clone())];
+        if let Some(username) = username.filter(|_| score.reasons.contains(&DISTRIBUTED_REASON)) {
+            subjects.push(("username", username.to_string()));
+        }
+        let locks: Vec<Lock> = subjects
+            .into_iter()
+            .map(|(subject_kind, subject)| Lock {
+                subject_kind,
+                subject,
+                route,
+                device: client.device.clone(),
+                score: score.clone(),
+                until_ms,
+            })
+            .collect();
+        for lock in &locks {
+            state.locks.insert(format!("{}:{}", lock.subject_kind, lock.subject), until_ms);
+        }
+        Verdict::Locked { retry_after_secs: retry_after(until_ms, now_ms), locks }
+    }
+
+    /// A login named a username that does not exist
+    pub fn record_miss(&self, client: &ClientContext, now_ms: i64) {
+        let mut state = self.state.lock().unwrap();
+        state.misses.entry(client.ip.clone()).or_default().push_back(now_ms);
+    }
+}
+
+impl AnomalyState {
+    fn entries(&self) -> usize {
+        self.by_ip.len() + self.by_username.len() + self.misses.len() + self.locks.len()
+    }
+}
+
+/// Attempts older than the TTL (never inside the detection window) and
+/// expired locks; a flush also lifts the active locks
+impl crate::maintenance::Maintained for AnomalyDetector {
+    fn entries(&self) -> usize {
+        self.state.lock().unwrap().entries()
+    }
+
+    fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let before = state.entries();
+        let since = now_ms - ttl_ms.max(self.config.window_ms);
+        state.locks.retain(|_, until| *until > now_ms);
+        prune(&mut state.by_ip, since, |a| a.at_ms);
+        prune(&mut state.by_username, since, |a| a.at_ms);
+        prune(&mut state.misses, since, |at| *at);
+        before - state.entries()
+    }
+
+    fn flush(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let n = state.entries();
+        *state = AnomalyState::default();
+        n
+    }
+}
+
+fn prune<T>(windows: &mut HashMap<String, VecDeque<T>>, since: i64, at: impl Fn(&T) -> i64) {
+    windows.retain(|_, queue| {
+        while queue.front().is_some_and(|item| at(item) <= since) {
+            queue.pop_front();
+        }
+        !queue.is_empty()
+    });
+}
+
+fn distinct<'a>(values: impl Iterator<Item = &'a str>) -> usize {
+    values.collect::<HashSet<_>>().len()
+}
+
+fn retry_after(until_ms: i64, now_ms: i64) -> u64 {
+    ((until_ms - now_ms).max(0) as u64).div_ceil(1000)
+}
+
+// =============================================================================
+// AUDIT
+// =============================================================================
+
+/// `identity.locked` atom; subjects are hashed, never stored raw
+pub fn lock_atom(lock: &Lock, ts_unix_ms: i64) -> serde_json::Value {
+    serde_json::json!({
+        "type": LOCK_TYPE,
+        "subject_kind": lock.subject_kind,
+        "subject_hash": hex::encode(blake3::hash(lock.subject.as_bytes()).as_bytes()),
+        "route": lock.route,
+        "device": lock.device,
+        "score": lock.score.points,
+        "reasons": lock.score.reasons,
+        "until_ms": lock.until_ms,
+        "ts_unix_ms": ts_unix_ms,
+    })
+}
+
+/// Record locks in `C.Identity`
+pub async fn record_locks(pool: PgPool, locks: Vec<Lock>) {
+    let ledger = PgLedger::new(pool);
+    for lock in locks {
+        warn!(
+            subject_kind = lock.subject_kind,
+            subject = %lock.subject,
+            route = lock.route.as_str(),
+            score = lock.score.points,
+            reasons = ?lock.score.reasons,
+            "🚨 Identity lock until {}",
+            lock.until_ms
+        );
+        let atom = lock_atom(&lock, crate::timestamps::now_ms());
+        if let Err(e) = append_signed(&ledger, IDENTITY_CONTAINER, atom).await {
+            error!("Failed to record {} lock: {}", lock.subject_kind, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn client(ip: &str, device: &str) -> ClientContext {
+        ClientContext { ip: ip.into(), device: device.into(), has_agent: true }
+    }
+
+    fn detector() -> AnomalyDetector {
+        AnomalyDetector::new(AnomalyConfig::default(), Arc::new(DefaultScoring))
+    }
+
+    #[test]
+    fn test_client_context_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.1"));
+        headers.insert("user-agent", HeaderValue::from_static("Mozilla/5.0"));
+        let forwarded = ClientContext::from_headers(&headers);
+        assert_eq!(forwarded.ip, "203.0.113.7");
+        assert!(forwarded.has_agent);
+        assert_eq!(forwarded.device.len(), 32);
+
+        headers.insert("x-real-ip", HeaderValue::from_static("198.51.100.2"));
+        headers.insert(DEVICE_ID_HEADER, HeaderValue::from_static("laptop"));
+        let real = ClientContext::from_headers(&headers);
+        assert_eq!(real.ip, "198.51.100.2");
+        assert_ne!(real.device, forwarded.device);
+
+        let bare = ClientContext::from_headers(&HeaderMap::new());
+        assert_eq!((bare.ip.as_str(), bare.has_agent), ("unknown", false));
+    }
+
+    #[test]
+    fn test_per_ip_window_throttles_and_slides() {
+        let detector = detector();
+        let config = AnomalyConfig::default();
+        let ip = client("203.0.113.7", "d1");
+        for i in 0..config.max_register_per_ip {
+            assert!(matches!(detector.check(IdRoute::Register, Some("alice"), &ip, i as i64), Verdict::Allow { .. }));
+        }
+        let throttled = detector.check(IdRoute::Register, Some("alice"), &ip, 100);
+        assert_eq!(throttled, Verdict::Throttled { retry_after_secs: 600 });
+        // Logins from the same IP have their own window
+        assert!(matches!(detector.check(IdRoute::Login, Some("alice"), &ip, 100), Verdict::Allow { .. }));
+        // Once the first attempts slide out, registering is allowed again
+        let later = config.window_ms + config.max_register_per_ip as i64;
+        assert!(matches!(detector.check(IdRoute::Register, Some("alice"), &ip, later), Verdict::Allow { .. }));
+    }
+
+    #[test]
+    fn test_credential_stuffing_locks_the_ip() {
+        let detector = detector();
+        let ip = client("203.0.113.7", "d1");
+        let mut verdict = Verdict::Allow { score: 0 };
+        for (i, user) in ["a", "b", "c", "d", "e", "f", "g", "h"].iter().enumerate() {
+            verdict = detector.check(IdRoute::Login, Some(user), &ip, i as i64);
+            if matches!(verdict, Verdict::Locked { .. }) {
+                break;
+            }
+        }
+        let Verdict::Locked { locks, .. } = verdict else { panic!("expected a lock") };
+        assert_eq!(locks.len(), 1);
+        assert_eq!((locks[0].subject_kind, locks[0].subject.as_str()), ("ip", "203.0.113.7"));
+        assert!(locks[0].score.reasons.contains(&"many_usernames_per_ip"));
+
+        // Locked for any username until the lock expires; other IPs are not
+        let locked = detector.check(IdRoute::Login, Some("zed"), &ip, 10);
+        assert!(matches!(locked, Verdict::Locked { ref locks, .. } if locks.is_empty()));
+        assert!(matches!(detector.check(IdRoute::Login, Some("zed"), &client("198.51.100.2", "d2"), 10), Verdict::Allow { .. }));
+        let expired = AnomalyConfig::default().lock_ms + 10;
+        assert!(matches!(detector.check(IdRoute::Login, Some("zed"), &ip, expired), Verdict::Allow { .. }));
+    }
+
+    #[test]
+    fn test_distributed_attack_locks_the_username() {
+        let detector = detector();
+        let mut locks = Vec::new();
+        for i in 0..12 {
+            let ip = client(&format!("203.0.113.{}", i), &format!("d{}", i));
+            if let Verdict::Locked { locks: set, .. } = detector.check(IdRoute::Login, Some("alice"), &ip, i) {
+                locks = set;
+                break;
+            }
+        }
+        let kinds: Vec<&str> = locks.iter().map(|l| l.subject_kind).collect();
+        assert_eq!(kinds, ["ip", "username"]);
+        let fresh = client("192.0.2.1", "d99");
+        assert!(matches!(detector.check(IdRoute::Login, Some("alice"), &fresh, 20), Verdict::Locked { .. }));
+
+        let atom = lock_atom(&locks[1], 20);
+        assert_eq!(atom["type"], LOCK_TYPE);
+        assert_eq!(atom["subject_hash"], hex::encode(blake3::hash(b"alice").as_bytes()));
+        assert!(!atom.to_string().contains("alice"));
+    }
+
+    #[test]
+    fn test_unknown_usernames_and_bare_clients_score() {
+        let scoring = DefaultScoring;
+        let signals = Signals { ip_misses: 4, has_agent: false, ..Signals::default() };
+        let score = scoring.score(IdRoute::Login, &signals);
+        assert_eq!(score.points, 50);
+        assert_eq!(score.reasons, ["unknown_usernames", "no_user_agent"]);
+
+        let detector = detector();
+        let ip = client("203.0.113.7", "d1");
+        for i in 0..8 {
+            detector.record_miss(&ip, i);
+        }
+        assert!(matches!(detector.check(IdRoute::Login, Some("a"), &ip, 9), Verdict::Locked { .. }));
+    }
+}
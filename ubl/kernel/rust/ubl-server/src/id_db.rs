@@ -68,7 +68,7 @@ pub struct Session {
     pub not_after: OffsetDateTime,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Asc {
     pub asc_id: Uuid,
     pub sid: String,
@@ -636,18 +636,17 @@ pub async fn get_session(pool: &PgPool, session_id: Uuid) -> sqlx::Result<Option
 // ASC OPERATIONS
 // ============================================================================
 
-/// Issue Agent Signing Certificate
+/// Issue Agent Signing Certificate, signed over
+/// `crypto::asc_signing_bytes` for the same validity window
 pub async fn issue_asc(
     pool: &PgPool,
     sid: &str,
     public_key: Vec<u8>,
     scopes: serde_json::Value,
-    ttl_secs: i64,
+    not_before: OffsetDateTime,
+    not_after: OffsetDateTime,
     signature: Vec<u8>,
 ) -> sqlx::Result<Asc> {
-    let not_before = OffsetDateTime::now_utc();
-    let not_after = not_before + time::Duration::seconds(ttl_secs);
-
     let row = sqlx::query!(
         r#"
         INSERT INTO id_asc (sid, public_key, scopes, not_before, not_after, signature)
@@ -897,6 +896,34 @@ pub async fn list_asc(pool: &PgPool, sid: &str) -> sqlx::Result<Vec<Asc>> {
         .collect())
 }
 
+/// Re-sign every unexpired ASC, e.g. after an authority key rotation.
+/// All signatures change in one transaction; returns how many were re-signed.
+pub async fn resign_active_ascs(pool: &PgPool, sign: impl Fn(&Asc) -> Vec<u8>) -> sqlx::Result<usize> {
+    let mut tx = pool.begin().await?;
+    let ascs: Vec<Asc> = sqlx::query_as(
+        r#"
+        SELECT asc_id, sid, public_key, scopes, not_before, not_after, signature
+        FROM id_asc
+        WHERE not_after > $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(OffsetDateTime::now_utc())
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for asc in &ascs {
+        sqlx::query("UPDATE id_asc SET signature = $1 WHERE asc_id = $2")
+            .bind(sign(asc))
+            .bind(asc.asc_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(ascs.len())
+}
+
 /// Revoke ASC (soft delete - mark as expired)
 pub async fn revoke_asc(pool: &PgPool, asc_id: Uuid) -> sqlx::Result<()> {
     let now = OffsetDateTime::now_utc();
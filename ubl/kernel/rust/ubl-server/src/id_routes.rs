@@ -16,6 +16,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 use webauthn_rs::prelude::*;
 
+use crate::id_anomaly::{ClientContext, IdRoute, Verdict};
 use crate::id_db;
 use crate::auth::session::{Session, SessionFlavor};
 use crate::auth::session_db;
@@ -51,6 +52,34 @@ fn parse_client_data_json(base64url_bytes: &[u8]) -> Result<ClientDataJSON, Stri
         .map_err(|e| format!("JSON parse failed: {}", e))
 }
 
+/// Per-IP window and anomaly scoring of a begin route (see id_anomaly.rs);
+/// locks this attempt sets are recorded in `C.Identity` in the background
+fn screen_client(
+    state: &IdState,
+    route: IdRoute,
+    username: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<ClientContext, (StatusCode, String)> {
+    use tracing::warn;
+    let client = ClientContext::from_headers(headers);
+    match state.anomaly.check(route, username, &client, crate::timestamps::now_ms()) {
+        Verdict::Allow { .. } => Ok(client),
+        Verdict::Throttled { retry_after_secs } => {
+            crate::metrics::RATE_LIMIT_REJECTIONS.with_label_values(&[&format!("{}_ip", route.as_str())]).inc();
+            warn!(actor_type="person", ip=%client.ip, device=%client.device, decision="reject", error_code="ip_rate_limited", retry_after_secs=%retry_after_secs);
+            Err((StatusCode::TOO_MANY_REQUESTS, format!("Too many attempts from this address. Retry after {} seconds", retry_after_secs)))
+        }
+        Verdict::Locked { retry_after_secs, locks } => {
+            crate::metrics::ID_DECISIONS.with_label_values(&[route.as_str(), "reject", "anomaly_lock"]).inc();
+            warn!(actor_type="person", ip=%client.ip, device=%client.device, decision="reject", error_code="locked", retry_after_secs=%retry_after_secs);
+            if !locks.is_empty() {
+                tokio::spawn(crate::id_anomaly::record_locks(state.pool.clone(), locks));
+            }
+            Err((StatusCode::TOO_MANY_REQUESTS, format!("Temporarily locked. Retry after {} seconds", retry_after_secs)))
+        }
+    }
+}
+
 fn assert_origin(cdj: &ClientDataJSON) -> Result<(), (StatusCode, String)> {
     let want = std::env::var("WEBAUTHN_ORIGIN")
         .unwrap_or_else(|_| "http://localhost:8080".to_string());
@@ -69,6 +98,7 @@ pub struct IdState {
     pub pool: PgPool,
     pub webauthn: Webauthn,
     pub rate_limiter: crate::rate_limit::RateLimiter,
+    pub anomaly: crate::id_anomaly::AnomalyDetector,
 }
 
 // ============================================================================
@@ -92,10 +122,16 @@ pub struct CreateAgentResp {
 
 #[derive(Debug, Deserialize)]
 pub struct IssueAscReq {
+    /// Named scope template (see `asc_requests::TEMPLATES`); fills in any
+    /// field left empty below
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
     pub containers: Vec<String>,
+    #[serde(default)]
     pub intent_classes: Vec<String>,
     pub max_delta: Option<i128>,
-    pub ttl_secs: i64,
+    pub ttl_secs: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -290,47 +326,87 @@ pub async fn route_issue_asc(
     Path(sid): Path<String>,
     Json(req): Json<IssueAscReq>,
 ) -> Result<Json<IssueAscResp>, (StatusCode, String)> {
+    let (scopes, ttl_secs) = match req.template.as_deref() {
+        Some(name) => {
+            let template = crate::asc_requests::template(name)
+                .ok_or((StatusCode::BAD_REQUEST, format!("Unknown ASC template: {}", name)))?;
+            let mut scopes = template.scopes();
+            if !req.containers.is_empty() {
+                scopes["containers"] = serde_json::json!(req.containers);
+            }
+            if !req.intent_classes.is_empty() {
+                scopes["intent_classes"] = serde_json::json!(req.intent_classes);
+            }
+            if req.max_delta.is_some() {
+                scopes["max_delta"] = serde_json::json!(req.max_delta);
+            }
+            (scopes, req.ttl_secs.unwrap_or(template.ttl_secs))
+        }
+        None => {
+            let ttl_secs = req.ttl_secs
+                .ok_or((StatusCode::BAD_REQUEST, "ttl_secs is required without a template".to_string()))?;
+            let scopes = serde_json::json!({
+                "containers": req.containers,
+                "intent_classes": req.intent_classes,
+                "max_delta": req.max_delta,
+            });
+            (scopes, ttl_secs)
+        }
+    };
+
+    issue_asc_with_scopes(&state.pool, &sid, scopes, ttl_secs).await.map(Json)
+}
+
+/// Issue an ASC for `sid`'s current Ed25519 key (shared with template approval)
+pub(crate) async fn issue_asc_with_scopes(
+    pool: &PgPool,
+    sid: &str,
+    scopes: serde_json::Value,
+    ttl_secs: i64,
+) -> Result<IssueAscResp, (StatusCode, String)> {
     // Verify subject exists
-    let subject = id_db::get_subject(&state.pool, &sid)
+    id_db::get_subject(pool, sid)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or((StatusCode::NOT_FOUND, "Subject not found".to_string()))?;
 
     // Get current credential
-    let cred = id_db::get_credential(&state.pool, &sid, "ed25519")
+    let cred = id_db::get_credential(pool, sid, "ed25519")
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or((StatusCode::NOT_FOUND, "No Ed25519 credential found".to_string()))?;
 
-    // Build scopes JSON
-    let scopes = serde_json::json!({
-        "containers": req.containers,
-        "intent_classes": req.intent_classes,
-        "max_delta": req.max_delta,
-    });
-
-    // TODO: Sign with UBL ID authority key (for now, placeholder)
-    let signature = vec![0u8; 64]; // Placeholder Ed25519 signature
+    // Signed by the UBL ID authority (admin) key
+    let not_before = time::OffsetDateTime::now_utc();
+    let not_after = not_before + time::Duration::seconds(ttl_secs);
+    let signature = crate::crypto::sign_asc(&crate::crypto::asc_signing_bytes(
+        sid,
+        &cred.public_key,
+        &scopes,
+        not_before.unix_timestamp(),
+        not_after.unix_timestamp(),
+    ));
 
     let asc = id_db::issue_asc(
-        &state.pool,
-        &sid,
+        pool,
+        sid,
         cred.public_key.clone(),
         scopes,
-        req.ttl_secs,
+        not_before,
+        not_after,
         signature,
     )
     .await
     .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
-    Ok(Json(IssueAscResp {
+    Ok(IssueAscResp {
         asc_id: asc.asc_id.to_string(),
         sid: asc.sid,
         scopes: asc.scopes,
         not_before: asc.not_before.to_string(),
         not_after: asc.not_after.to_string(),
         signature: hex::encode(asc.signature),
-    }))
+    })
 }
 
 /// GET /id/asc/{asc_id}/validate - Validate an ASC (for Office to call)
@@ -466,7 +542,7 @@ pub async fn route_whoami(
 }
 
 /// Extract session token from cookie or Authorization header
-fn extract_session_token(headers: &HeaderMap) -> Option<String> {
+pub(crate) fn extract_session_token(headers: &HeaderMap) -> Option<String> {
     // Try Authorization: Bearer <token>
     if let Some(auth) = headers.get(axum::http::header::AUTHORIZATION) {
         if let Ok(auth_str) = auth.to_str() {
@@ -496,6 +572,7 @@ fn extract_session_token(headers: &HeaderMap) -> Option<String> {
 /// POST /id/register/begin - Begin WebAuthn registration
 pub async fn route_register_begin(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<RegisterBeginReq>,
 ) -> Result<Json<RegisterBeginResp>, (StatusCode, String)> {
     use tracing::{info, warn};
@@ -508,6 +585,7 @@ pub async fn route_register_begin(
         warn!(actor_type="person", username=%req.username, decision="reject", error_code="rate_limited", retry_after_secs=%retry_after);
         return Err((StatusCode::TOO_MANY_REQUESTS, format!("Rate limited. Retry after {} seconds", retry_after)));
     }
+    screen_client(&state, IdRoute::Register, Some(&req.username), &headers)?;
     
     // 1. Check if user already exists
     let existing = id_db::get_subject_by_username(&state.pool, &req.username)
@@ -658,6 +736,7 @@ pub async fn route_register_finish(
 /// POST /id/login/begin - Begin WebAuthn login
 pub async fn route_login_begin(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<LoginBeginReq>,
 ) -> Result<Json<LoginBeginResp>, (StatusCode, String)> {
     use tracing::{info, warn};
@@ -670,6 +749,7 @@ pub async fn route_login_begin(
         warn!(actor_type="person", username=%req.username, decision="reject", error_code="rate_limited", retry_after_secs=%retry_after);
         return Err((StatusCode::TOO_MANY_REQUESTS, format!("Too many login attempts. Retry after {} seconds", retry_after)));
     }
+    let client = screen_client(&state, IdRoute::Login, Some(&req.username), &headers)?;
     
     // 1. Get subject by username
     let subject = id_db::get_subject_by_username(&state.pool, &req.username)
@@ -679,6 +759,7 @@ pub async fn route_login_begin(
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
         })?
         .ok_or_else(|| {
+            state.anomaly.record_miss(&client, crate::timestamps::now_ms());
             warn!(actor_type="person", username=%req.username, decision="reject", error_code="unknown_credential", latency_ms=start.elapsed().as_millis());
             (StatusCode::NOT_FOUND, "User not found".to_string())
         })?;
@@ -930,9 +1011,11 @@ pub async fn route_login_finish(
 /// POST /id/login/discoverable/begin - Begin discoverable (userless) WebAuthn login
 pub async fn route_login_discoverable_begin(
     State(state): State<IdState>,
+    headers: HeaderMap,
 ) -> Result<Json<LoginBeginResp>, (StatusCode, String)> {
     use tracing::info;
     let start = std::time::Instant::now();
+    screen_client(&state, IdRoute::Login, None, &headers)?;
     
     // 1. Create discoverable authentication challenge (no username needed)
     let (rcr, auth_state) = state.webauthn
@@ -1411,8 +1494,7 @@ pub fn id_router() -> Router<IdState> {
         .route("/id/agents/:sid", get(route_export_agent))
         .route("/id/agents/:sid/asc", post(route_issue_asc))
         .route("/id/agents/:sid/asc", get(route_list_asc))
-        // TODO: Apply require_stepup middleware to these routes
-        // Need to refactor middleware to work with Axum layers
+        // Agent mutations need a step-up admin session (see route_auth::ROUTES)
         .route("/id/agents/:sid/rotate", post(route_rotate_key))
         .route("/id/agents/:sid/asc/:asc_id", delete(route_revoke_asc))
         .route("/id/whoami", get(route_whoami))
@@ -1429,3 +1511,68 @@ pub fn id_router() -> Router<IdState> {
         // 🆕 ASC validation endpoint (for Office to call instead of direct DB access)
         .route("/id/asc/:asc_id/validate", get(route_validate_asc))
 }
+
+/// Response samples pinned by the API contract tests (see `crate::contracts`)
+#[cfg(test)]
+pub(crate) fn contract_samples() -> Vec<(&'static str, serde_json::Value)> {
+    use serde_json::json;
+
+    let asc = IssueAscResp {
+        asc_id: Uuid::nil().to_string(),
+        sid: "ubl:sid:1".into(),
+        scopes: json!({
+            "containers": ["C.Messenger"],
+            "intent_classes": ["Observation"],
+            "max_delta": 1,
+        }),
+        not_before: "t".into(),
+        not_after: "t".into(),
+        signature: "00".into(),
+    };
+    vec![
+        (
+            "POST /id/agents",
+            json!(CreateAgentResp {
+                sid: "ubl:sid:1".into(),
+                kind: "llm".into(),
+                display_name: "n".into(),
+                public_key: "pk".into(),
+            }),
+        ),
+        (
+            "GET /id/agents/:sid",
+            json!(id_db::Subject {
+                sid: "ubl:sid:1".into(),
+                kind: "llm".into(),
+                display_name: "n".into(),
+                status: "active".into(),
+            }),
+        ),
+        ("POST /id/agents/:sid/asc", json!(asc)),
+        ("GET /id/agents/:sid/asc", json!([asc])),
+        (
+            "GET /id/asc/:asc_id/validate",
+            json!(ValidateAscResp {
+                valid: false,
+                asc_id: Uuid::nil().to_string(),
+                owner_sid: "ubl:sid:1".into(),
+                owner_kind: "llm".into(),
+                containers: vec!["C.Messenger".into()],
+                intent_classes: vec!["Observation".into()],
+                max_delta: 1,
+                not_before: "t".into(),
+                not_after: "t".into(),
+                reason: Some("expired".into()),
+            }),
+        ),
+        (
+            "POST /id/agents/:sid/rotate",
+            json!(RotateKeyResp { sid: "ubl:sid:1".into(), key_version: 2, message: "m".into() }),
+        ),
+        (
+            "POST /id/sessions/ict/begin",
+            json!(IcteBeginResp { session_id: "s".into(), not_before: "t".into(), not_after: "t".into() }),
+        ),
+        ("POST /id/sessions/ict/finish", json!(IcteFinishResp { message: "m".into() })),
+    ]
+}
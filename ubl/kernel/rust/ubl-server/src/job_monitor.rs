@@ -1,93 +1,209 @@
 //! Job Monitor - Diamond Checklist #8
 //!
-//! Background worker that monitors jobs for timeouts and marks orphaned jobs.
-//! An orphaned job is one that's stuck in 'in_progress' with no recent activity.
+//! Background worker that finds orphaned jobs: stuck in 'in_progress' with no
+//! activity for longer than the runner sandbox timeout (a crashed runner never
+//! reports back). Each orphan is resolved by a `job.timeout` event committed
+//! to C.Jobs through `commit_link` — the same membrane, FSM policy, ledger
+//! append and projections as `POST /link/commit`. The projection is never
+//! written directly. The owning Office entity is then notified.
 
-use sqlx::{PgPool, Row};
+use sqlx::Row;
 use std::time::Duration;
 use tokio::time::interval;
 use tracing::{info, warn, error};
 
+use crate::db::{LedgerEntry, LinkDraft};
+use ubl_events::{JobEvent, JobTimeout};
+
+use crate::messenger_gateway::office_client::OfficeClient;
+use crate::AppState;
+
+/// Extra time past the sandbox timeout before a silent job is declared orphaned
+pub const TIMEOUT_GRACE_SECS: u64 = 60;
+/// Orphans resolved per tick; the rest wait for the next one
+const BATCH_LIMIT: i64 = 100;
+/// Actor recorded for monitor commits (policy evaluation)
+const ACTOR: &str = "system:job_monitor";
+
 /// Configuration for job monitoring
 #[derive(Clone)]
 pub struct JobMonitorConfig {
     /// How often to check for orphaned jobs (in seconds)
     pub check_interval_secs: u64,
-    /// How long before a job is considered orphaned (in minutes)
-    pub timeout_minutes: i64,
+    /// Seconds without activity before a running job is orphaned
+    pub timeout_secs: u64,
 }
 
 impl Default for JobMonitorConfig {
     fn default() -> Self {
         Self {
             check_interval_secs: 60, // Check every minute
-            timeout_minutes: 30,     // 30 minutes without activity = orphaned
+            timeout_secs: ubl_runner_core::SandboxConfig::default().timeout_secs + TIMEOUT_GRACE_SECS,
+        }
+    }
+}
+
+impl JobMonitorConfig {
+    /// Defaults overridden by `UBL_JOB_MONITOR_INTERVAL_SECS` / `UBL_JOB_TIMEOUT_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            check_interval_secs: get("UBL_JOB_MONITOR_INTERVAL_SECS", defaults.check_interval_secs),
+            timeout_secs: get("UBL_JOB_TIMEOUT_SECS", defaults.timeout_secs),
         }
     }
 }
 
-/// Job Monitor - monitors for orphaned jobs
+/// A running job that went silent
+struct OrphanedJob {
+    tenant_id: String,
+    job_id: String,
+    conversation_id: Option<String>,
+    owner_entity_id: Option<String>,
+    last_activity_ms: i64,
+}
+
+/// Job Monitor - resolves orphaned jobs through the commit path
 pub struct JobMonitor {
-    pool: PgPool,
+    state: AppState,
+    office: OfficeClient,
     config: JobMonitorConfig,
 }
 
 impl JobMonitor {
-    pub fn new(pool: PgPool, config: JobMonitorConfig) -> Self {
-        Self { pool, config }
+    pub fn new(state: AppState, office_url: String, config: JobMonitorConfig) -> Self {
+        Self { state, office: OfficeClient::new(office_url), config }
     }
 
     /// Start the monitoring loop (runs forever)
     pub async fn run(self) {
         info!(
-            "🔍 Job Monitor started - checking every {}s for jobs idle > {}m",
-            self.config.check_interval_secs, self.config.timeout_minutes
+            "🔍 Job Monitor started - checking every {}s for jobs idle > {}s",
+            self.config.check_interval_secs, self.config.timeout_secs
         );
 
         let mut tick = interval(Duration::from_secs(self.config.check_interval_secs));
 
         loop {
             tick.tick().await;
-            
+
             if let Err(e) = self.check_orphaned_jobs().await {
                 error!("❌ Job monitor error: {}", e);
             }
         }
     }
 
-    /// Check for orphaned jobs and mark them as timed out
+    /// Find orphaned jobs and commit a `job.timeout` for each
     async fn check_orphaned_jobs(&self) -> Result<(), sqlx::Error> {
-        // Diamond Checklist #8: Find jobs stuck in 'in_progress' with no recent activity
-        let result = sqlx::query(
+        let rows = sqlx::query(
             r#"
-            UPDATE projection_jobs
-            SET state = 'failed',
-                updated_at = NOW(),
-                last_event_hash = 'timeout_monitor',
-                last_event_seq = last_event_seq
+            SELECT tenant_id, job_id, conversation_id, owner_entity_id,
+                   (EXTRACT(EPOCH FROM COALESCE(last_activity_at, updated_at)) * 1000)::BIGINT AS last_activity_ms
+            FROM projection_jobs
             WHERE state = 'in_progress'
-              AND (last_activity_at IS NULL OR last_activity_at < NOW() - INTERVAL '1 minute' * $1)
-            RETURNING job_id, title, last_activity_at
+              AND COALESCE(last_activity_at, updated_at) < NOW() - INTERVAL '1 second' * $1
+            ORDER BY COALESCE(last_activity_at, updated_at)
+            LIMIT $2
             "#
         )
-        .bind(self.config.timeout_minutes)
-        .fetch_all(&self.pool)
+        .bind(self.config.timeout_secs as i64)
+        .bind(BATCH_LIMIT)
+        .fetch_all(&self.state.pool)
         .await?;
 
-        if !result.is_empty() {
-            for row in &result {
-                let job_id: String = row.try_get("job_id").unwrap_or_default();
-                let title: String = row.try_get("title").unwrap_or_default();
-                warn!(
-                    "⏱️  Job orphaned: {} ({}) - marked as failed",
-                    job_id, title
-                );
+        let mut resolved = 0;
+        for row in &rows {
+            let job = OrphanedJob {
+                tenant_id: row.try_get("tenant_id").unwrap_or_else(|_| "default".to_string()),
+                job_id: row.try_get("job_id").unwrap_or_default(),
+                conversation_id: row.try_get("conversation_id").ok().flatten(),
+                owner_entity_id: row.try_get("owner_entity_id").ok().flatten(),
+                last_activity_ms: row.try_get("last_activity_ms").unwrap_or(0),
+            };
+
+            // A failed commit (e.g. SequenceMismatch from a concurrent writer)
+            // leaves the job in_progress, so it is retried on the next tick
+            match self.commit_timeout(&job).await {
+                Ok(entry_hash) => {
+                    warn!("⏱️  Job orphaned: {} - job.timeout committed", job.job_id);
+                    self.notify_office(&job, entry_hash);
+                    resolved += 1;
+                }
+                Err(e) => warn!("⚠️  job.timeout for {} not committed: {}", job.job_id, e),
             }
-            info!("🔧 Marked {} orphaned jobs as 'failed'", result.len());
+        }
+
+        if resolved > 0 {
+            info!("🔧 Timed out {} orphaned jobs", resolved);
         }
 
         Ok(())
     }
+
+    /// Build, sign and commit the `job.timeout` link; returns the entry hash
+    async fn commit_timeout(&self, job: &OrphanedJob) -> Result<String, String> {
+        let atom = JobEvent::Timeout(timeout_event(job, self.config.timeout_secs)).to_atom();
+        let atom_bytes = ubl_atom::canonicalize(&atom).map_err(|e| format!("CanonicalizeError: {}", e))?;
+
+        let container_id = "C.Jobs";
+        let container_state = match self.state.ledger.get_state(container_id).await {
+            Ok(entry) => entry,
+            Err(sqlx::Error::RowNotFound) => LedgerEntry {
+                container_id: container_id.to_string(),
+                sequence: 0,
+                entry_hash: "0x00".to_string(),
+                previous_hash: "0x00".to_string(),
+                link_hash: "0x00".to_string(),
+                ts_unix_ms: 0,
+                hlc: crate::hlc::Hlc::ZERO,
+            },
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mut link = LinkDraft {
+            version: 1,
+            container_id: container_id.to_string(),
+            expected_sequence: container_state.sequence + 1,
+            previous_hash: container_state.entry_hash,
+            atom_hash: blake3::hash(&atom_bytes).to_hex().to_string(),
+            atom: Some(atom),
+            intent_class: "Observation".to_string(),
+            physics_delta: "0".to_string(),
+            author_pubkey: String::new(), // Set by sign_link_draft
+            signature: String::new(),     // Set by sign_link_draft
+            pact: None,
+            tentative_id: None,
+        };
+        crate::messenger_v1::sign_link_draft(&mut link);
+
+        crate::commit_link(&self.state, link, ACTOR)
+            .await
+            .map(|success| success.entry.entry_hash)
+            .map_err(|e| e.message)
+    }
+
+    /// Fire-and-forget: the ledger is the source of truth, Office may be down
+    fn notify_office(&self, job: &OrphanedJob, entry_hash: String) {
+        let notice = timeout_event(job, self.config.timeout_secs).notice(entry_hash);
+        let office = self.office.clone();
+        tokio::spawn(async move {
+            if let Err(e) = office.job_event(&notice).await {
+                warn!("⚠️  Office not notified of job.timeout for {}: {}", notice.job_id, e);
+            }
+        });
+    }
+}
+
+/// The `job.timeout` event; `from_state`/`to_state` go through the job FSM policy
+fn timeout_event(job: &OrphanedJob, timeout_secs: u64) -> JobTimeout {
+    JobTimeout {
+        conversation_id: job.conversation_id.clone(),
+        owner_entity_id: job.owner_entity_id.clone(),
+        ..JobTimeout::new(&job.job_id, &job.tenant_id, timeout_secs, job.last_activity_ms)
+    }
 }
 
 #[cfg(test)]
@@ -98,6 +214,24 @@ mod tests {
     fn test_default_config() {
         let config = JobMonitorConfig::default();
         assert_eq!(config.check_interval_secs, 60);
-        assert_eq!(config.timeout_minutes, 30);
+        // Sandbox timeout (5 minutes) plus grace
+        assert_eq!(config.timeout_secs, 300 + TIMEOUT_GRACE_SECS);
+    }
+
+    #[test]
+    fn test_timeout_atom_is_fsm_transition() {
+        let job = OrphanedJob {
+            tenant_id: "t1".into(),
+            job_id: "job_1".into(),
+            conversation_id: None,
+            owner_entity_id: Some("entity_1".into()),
+            last_activity_ms: 42,
+        };
+        let atom = JobEvent::Timeout(timeout_event(&job, 360)).to_atom();
+        assert_eq!(atom["type"], "job.timeout");
+        assert_eq!(atom["from_state"], "in_progress");
+        assert_eq!(atom["to_state"], "failed");
+        assert_eq!(atom["owner_entity_id"], "entity_1");
+        assert!(ubl_atom::canonicalize(&atom).is_ok());
     }
 }
@@ -0,0 +1,607 @@
+//! Job templates — parameterized job definitions
+//!
+//! A template is governed like any other evolution: it is committed to C.Jobs
+//! as an `evolution.job_template` atom (Evolution intent, pact required) and
+//! checked by `evolution::JobTemplateHandler` before it lands. The latest
+//! version of each template is projected into `projection_job_templates`.
+//!
+//! A template declares its parameters (type, required, default, allowed
+//! values), the default sandbox its jobs run in, the tools they need and,
+//! optionally, the state machine they follow. `{{param}}` placeholders in the
+//! title and goal are filled from the parameters when a job is instantiated.
+//!
+//! Endpoints:
+//! - GET  /jobs/templates               → Latest version of every template
+//! - GET  /jobs/templates/:id           → One template
+//! - POST /jobs/from-template/:id       → Validate parameters, commit job.created
+//!
+//! The job.created atom names the template id, version and entry hash it was
+//! instantiated from, so every job traces back to the governed definition.
+
+use std::collections::{BTreeMap, HashSet};
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sqlx::PgPool;
+use tracing::{error, info};
+use ubl_errors::ErrorCode;
+use ubl_events::{JobCreated, JobEvent};
+use ubl_runner_core::SandboxConfig;
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::db::PgLedger;
+use crate::evolution::FsmDefinition;
+use crate::messenger_gateway::card_provenance::append_signed;
+use crate::messenger_v1::get_user_from_session;
+use crate::projections::{JobEventsProjection, JobsProjection};
+use crate::timestamps::{now_ms, rfc3339_utc};
+
+/// Atom type of a template definition
+pub const TEMPLATE_TYPE: &str = "evolution.job_template";
+/// Container templates are committed to, and their jobs created in
+pub const TEMPLATE_CONTAINER: &str = "C.Jobs";
+
+/// Longest template id / parameter name accepted
+const MAX_NAME_LEN: usize = 64;
+/// Longest sandbox timeout a template may ask for (24h)
+const MAX_TIMEOUT_SECS: u64 = 24 * 3600;
+/// Job priorities a caller may pick
+const PRIORITIES: [&str; 4] = ["low", "normal", "high", "urgent"];
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+/// A parameterized job definition, as committed in `{type, template}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobTemplate {
+    pub template_id: String,
+    /// Bumped on every change; the projection keeps the highest
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Job title, with `{{param}}` placeholders
+    pub title: String,
+    /// Job goal, with `{{param}}` placeholders; the title when absent
+    #[serde(default)]
+    pub goal: Option<String>,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, ParamSpec>,
+    #[serde(default)]
+    pub sandbox: TemplateSandbox,
+    #[serde(default)]
+    pub required_tools: Vec<String>,
+    #[serde(default)]
+    pub fsm: Option<FsmDefinition>,
+}
+
+/// One declared parameter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamSpec {
+    #[serde(rename = "type")]
+    pub kind: ParamType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// Allowed values; any value of the type when absent
+    #[serde(default, rename = "enum")]
+    pub allowed: Option<Vec<Value>>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+    Object,
+}
+
+impl ParamType {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            ParamType::String => value.is_string(),
+            ParamType::Integer => value.is_i64() || value.is_u64(),
+            ParamType::Number => value.is_number(),
+            ParamType::Boolean => value.is_boolean(),
+            ParamType::Array => value.is_array(),
+            ParamType::Object => value.is_object(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ParamType::String => "string",
+            ParamType::Integer => "integer",
+            ParamType::Number => "number",
+            ParamType::Boolean => "boolean",
+            ParamType::Array => "array",
+            ParamType::Object => "object",
+        }
+    }
+}
+
+/// Default sandbox of a template's jobs (`ubl_runner_core::SandboxConfig`);
+/// fields left out take the runner defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateSandbox {
+    pub timeout_secs: u64,
+    pub max_memory: u64,
+    pub max_cpu: f32,
+    pub network_isolated: bool,
+    pub filesystem_isolated: bool,
+}
+
+impl Default for TemplateSandbox {
+    fn default() -> Self {
+        let defaults = SandboxConfig::default();
+        Self {
+            timeout_secs: defaults.timeout_secs,
+            max_memory: defaults.max_memory,
+            max_cpu: defaults.max_cpu,
+            network_isolated: defaults.network_isolated,
+            filesystem_isolated: defaults.filesystem_isolated,
+        }
+    }
+}
+
+// =============================================================================
+// VALIDATION
+// =============================================================================
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// Names inside `{{...}}`, in order of appearance
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        names.push(rest[start + 2..start + 2 + len].trim());
+        rest = &rest[start + 2 + len + 2..];
+    }
+    names
+}
+
+impl JobTemplate {
+    /// Structural checks run by the evolution handler before commit
+    pub fn validate(&self) -> Result<(), String> {
+        if !valid_name(&self.template_id) {
+            return Err(format!("template_id '{}' must be 1-{} chars of [a-z0-9_-]", self.template_id, MAX_NAME_LEN));
+        }
+        if self.version == 0 {
+            return Err("version starts at 1".into());
+        }
+        if self.name.trim().is_empty() || self.title.trim().is_empty() {
+            return Err("name and title are required".into());
+        }
+
+        for (name, spec) in &self.parameters {
+            if !valid_name(name) {
+                return Err(format!("parameter name '{}' must be 1-{} chars of [a-z0-9_-]", name, MAX_NAME_LEN));
+            }
+            if let Some(allowed) = &spec.allowed {
+                if allowed.is_empty() {
+                    return Err(format!("parameter '{}': enum is empty", name));
+                }
+                if let Some(bad) = allowed.iter().find(|v| !spec.kind.accepts(v)) {
+                    return Err(format!("parameter '{}': enum value {} is not of type {}", name, bad, spec.kind.name()));
+                }
+            }
+            if let Some(default) = &spec.default {
+                if spec.required {
+                    return Err(format!("parameter '{}' is required and has a default", name));
+                }
+                if let Some(problem) = spec.check(name, default) {
+                    return Err(format!("default: {}", problem));
+                }
+            }
+        }
+
+        for text in std::iter::once(&self.title).chain(self.goal.as_ref()) {
+            if let Some(unknown) = placeholders(text).into_iter().find(|p| !self.parameters.contains_key(*p)) {
+                return Err(format!("placeholder '{{{{{}}}}}' is not a declared parameter", unknown));
+            }
+        }
+
+        let mut tools = HashSet::new();
+        for tool in &self.required_tools {
+            if tool.trim().is_empty() || !tools.insert(tool.as_str()) {
+                return Err(format!("empty or duplicate required tool '{}'", tool));
+            }
+        }
+
+        let sandbox = &self.sandbox;
+        if sandbox.timeout_secs == 0 || sandbox.timeout_secs > MAX_TIMEOUT_SECS {
+            return Err(format!("sandbox.timeout_secs must be 1..={}", MAX_TIMEOUT_SECS));
+        }
+        if sandbox.max_memory == 0 || sandbox.max_cpu.is_nan() || sandbox.max_cpu <= 0.0 {
+            return Err("sandbox.max_memory and sandbox.max_cpu must be positive".into());
+        }
+
+        if let Some(fsm) = &self.fsm {
+            fsm.validate().map_err(|e| format!("fsm: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Parameters checked against the template, with defaults filled in.
+    /// Every problem is reported, not just the first.
+    pub fn check_params(&self, provided: &Map<String, Value>) -> Result<Map<String, Value>, Vec<String>> {
+        let mut problems: Vec<String> = provided
+            .keys()
+            .filter(|k| !self.parameters.contains_key(*k))
+            .map(|k| format!("unknown parameter '{}'", k))
+            .collect();
+
+        let mut resolved = Map::new();
+        for (name, spec) in &self.parameters {
+            match provided.get(name).filter(|v| !v.is_null()).or(spec.default.as_ref()) {
+                Some(value) => match spec.check(name, value) {
+                    Some(problem) => problems.push(problem),
+                    None => {
+                        resolved.insert(name.clone(), value.clone());
+                    }
+                },
+                None if spec.required => problems.push(format!("missing required parameter '{}'", name)),
+                None => {}
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(resolved)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Title and goal with placeholders replaced; absent optional parameters
+    /// render as empty
+    pub fn render(&self, params: &Map<String, Value>) -> (String, String) {
+        let title = fill(&self.title, params);
+        let goal = self.goal.as_deref().map(|g| fill(g, params)).unwrap_or_else(|| title.clone());
+        (title, goal)
+    }
+}
+
+fn fill(text: &str, params: &Map<String, Value>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        out.push_str(&rest[..start]);
+        match params.get(rest[start + 2..start + 2 + len].trim()) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(other) => out.push_str(&other.to_string()),
+            None => {}
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+impl ParamSpec {
+    fn check(&self, name: &str, value: &Value) -> Option<String> {
+        if !self.kind.accepts(value) {
+            return Some(format!("parameter '{}' must be of type {}", name, self.kind.name()));
+        }
+        match &self.allowed {
+            Some(allowed) if !allowed.contains(value) => Some(format!("parameter '{}' must be one of {}", name, Value::from(allowed.clone()))),
+            _ => None,
+        }
+    }
+}
+
+// =============================================================================
+// STORE
+// =============================================================================
+
+/// A projected template and the entry that defined it
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredTemplate {
+    #[serde(flatten)]
+    pub template: JobTemplate,
+    pub entry_hash: String,
+    pub sequence: i64,
+}
+
+type TemplateRow = (Value, String, i64);
+
+fn stored((definition, entry_hash, sequence): TemplateRow) -> Result<StoredTemplate, ApiError> {
+    let template = serde_json::from_value(definition)
+        .map_err(|e| ApiError::new(ErrorCode::Internal, format!("Stored template is malformed: {}", e)))?;
+    Ok(StoredTemplate { template, entry_hash, sequence })
+}
+
+async fn load(pool: &PgPool, template_id: &str) -> Result<Option<StoredTemplate>, ApiError> {
+    let row: Option<TemplateRow> = sqlx::query_as(
+        "SELECT definition, last_event_hash, last_event_seq FROM projection_job_templates WHERE template_id = $1",
+    )
+    .bind(template_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(internal)?;
+    row.map(stored).transpose()
+}
+
+fn internal(e: sqlx::Error) -> ApiError {
+    ApiError::new(ErrorCode::Internal, e.to_string())
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+#[derive(Clone)]
+pub struct JobTemplateState {
+    pub pool: PgPool,
+    pub ledger: PgLedger,
+}
+
+pub fn routes(pool: PgPool) -> Router {
+    let ledger = PgLedger::new(pool.clone());
+    Router::new()
+        .route("/jobs/templates", get(list_templates))
+        .route("/jobs/templates/:id", get(get_template))
+        .route("/jobs/from-template/:id", post(create_from_template))
+        .with_state(JobTemplateState { pool, ledger })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FromTemplateRequest {
+    pub conversation_id: String,
+    #[serde(default)]
+    pub parameters: Map<String, Value>,
+    #[serde(default)]
+    pub assigned_to: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FromTemplateResponse {
+    pub job_id: String,
+    pub entry_hash: String,
+    pub sequence: i64,
+    pub template_id: String,
+    pub version: u32,
+    pub title: String,
+}
+
+/// GET /jobs/templates
+async fn list_templates(State(state): State<JobTemplateState>, headers: HeaderMap) -> Result<Json<Value>, ApiError> {
+    get_user_from_session(&state.pool, &headers)
+        .await
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "Not authenticated"))?;
+
+    let rows: Vec<TemplateRow> = sqlx::query_as(
+        "SELECT definition, last_event_hash, last_event_seq FROM projection_job_templates ORDER BY template_id",
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal)?;
+    let templates = rows.into_iter().map(stored).collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(json!({ "templates": templates })))
+}
+
+/// GET /jobs/templates/:id
+async fn get_template(
+    State(state): State<JobTemplateState>,
+    Path(template_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<StoredTemplate>, ApiError> {
+    get_user_from_session(&state.pool, &headers)
+        .await
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "Not authenticated"))?;
+
+    load(&state.pool, &template_id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Job template not found: {}", template_id)))
+}
+
+/// POST /jobs/from-template/:id — instantiate a job in a conversation the
+/// caller belongs to
+async fn create_from_template(
+    State(state): State<JobTemplateState>,
+    Path(template_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<FromTemplateRequest>,
+) -> Result<(StatusCode, Json<FromTemplateResponse>), ApiError> {
+    let user = get_user_from_session(&state.pool, &headers)
+        .await
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "Not authenticated"))?;
+
+    let access = crate::projections::scope::conversation_access(&state.pool, &req.conversation_id)
+        .await
+        .map_err(internal)?;
+    let member = access.is_some_and(|c| c.owner.as_deref() == Some(user.sid.as_str()) || c.participants.contains(&user.sid));
+    if !member {
+        return Err(ApiError::new(ErrorCode::NotFound, "Conversation not found"));
+    }
+
+    let stored = load(&state.pool, &template_id)
+        .await?
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("Job template not found: {}", template_id)))?;
+    let template = &stored.template;
+
+    let priority = req.priority.as_deref().unwrap_or("normal");
+    if !PRIORITIES.contains(&priority) {
+        return Err(ApiError::new(ErrorCode::BadRequest, format!("priority must be one of {}", PRIORITIES.join(", "))));
+    }
+    let parameters = template.check_params(&req.parameters).map_err(|problems| {
+        ApiError::new(ErrorCode::BadRequest, format!("Invalid parameters for {}: {}", template_id, problems.join("; ")))
+    })?;
+    let (title, goal) = template.render(&parameters);
+
+    let job_id = format!("job_{}", Uuid::new_v4().simple());
+    let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
+    let created = JobCreated {
+        assigned_to: req.assigned_to.clone(),
+        created_by: Some(user.sid.clone()),
+        created_at: Some(rfc3339_utc(now_ms())),
+        ..JobCreated::new(&job_id, &title)
+            .with_tenant(tenant_id)
+            .with_conversation(&req.conversation_id)
+            .with_goal(goal)
+            .with_priority(priority)
+            .with_extension("fsm", json!(template.fsm))
+            .with_extension("parameters", json!(parameters))
+            .with_extension("required_tools", json!(template.required_tools))
+            .with_extension("sandbox", json!(template.sandbox))
+            .with_extension(
+                "template",
+                json!({
+                    "entry_hash": stored.entry_hash,
+                    "template_id": template.template_id,
+                    "version": template.version,
+                }),
+            )
+    };
+    let atom = JobEvent::Created(created).to_atom();
+
+    let entry = append_signed(&state.ledger, TEMPLATE_CONTAINER, atom.clone())
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::Internal, e))?;
+
+    // Direct appends are not projected by /link/commit; project here
+    if let Err(e) = JobsProjection::new(state.pool.clone())
+        .process_event("job.created", &atom, &entry.entry_hash, entry.sequence)
+        .await
+    {
+        error!("Failed to project templated job {}: {}", job_id, e);
+    }
+    if let Err(e) = JobEventsProjection::new(state.pool.clone())
+        .process_event("job.created", &atom, &entry.entry_hash, entry.sequence, entry.hlc, tenant_id)
+        .await
+    {
+        error!("Failed to project job event for {}: {}", job_id, e);
+    }
+
+    info!("🧩 Job {} from template {} v{} by {}", job_id, template.template_id, template.version, user.sid);
+    Ok((
+        StatusCode::CREATED,
+        Json(FromTemplateResponse {
+            job_id,
+            entry_hash: entry.entry_hash,
+            sequence: entry.sequence,
+            template_id: template.template_id.clone(),
+            version: template.version,
+            title,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> JobTemplate {
+        serde_json::from_value(json!({
+            "template_id": "weekly-report",
+            "version": 2,
+            "name": "Weekly report",
+            "title": "Report for {{ team }} ({{weeks}} weeks)",
+            "parameters": {
+                "team": { "type": "string", "required": true },
+                "weeks": { "type": "integer", "default": 1 },
+                "format": { "type": "string", "enum": ["pdf", "md"], "default": "md" }
+            },
+            "sandbox": { "timeout_secs": 600 },
+            "required_tools": ["ledger.query", "docs.write"]
+        }))
+        .unwrap()
+    }
+
+    fn params(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_template_validates_and_fills_sandbox_defaults() {
+        let t = template();
+        assert!(t.validate().is_ok());
+        assert_eq!(t.sandbox.timeout_secs, 600);
+        assert_eq!(t.sandbox.max_memory, SandboxConfig::default().max_memory);
+        assert!(t.sandbox.network_isolated);
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        let mut undeclared = template();
+        undeclared.title = "Report for {{owner}}".into();
+        assert!(undeclared.validate().unwrap_err().contains("owner"));
+
+        let mut bad_default = template();
+        bad_default.parameters.get_mut("format").unwrap().default = Some(json!("docx"));
+        assert!(bad_default.validate().unwrap_err().contains("format"));
+
+        let mut dup_tool = template();
+        dup_tool.required_tools.push("docs.write".into());
+        assert!(dup_tool.validate().unwrap_err().contains("docs.write"));
+
+        let mut no_timeout = template();
+        no_timeout.sandbox.timeout_secs = 0;
+        assert!(no_timeout.validate().unwrap_err().contains("timeout"));
+
+        let mut unreachable = template();
+        unreachable.fsm = Some(serde_json::from_value(json!({
+            "fsm": "report", "initial": "draft", "states": ["draft", "done", "lost"],
+            "transitions": [{ "from": "draft", "to": "done" }]
+        })).unwrap());
+        assert!(unreachable.validate().unwrap_err().contains("lost"));
+
+        let unknown_field = json!({ "template_id": "x", "version": 1, "name": "x", "title": "x", "colour": "blue" });
+        assert!(serde_json::from_value::<JobTemplate>(unknown_field).is_err());
+    }
+
+    #[test]
+    fn test_check_params_fills_defaults() {
+        let resolved = template().check_params(&params(json!({ "team": "infra" }))).unwrap();
+        assert_eq!(resolved["team"], "infra");
+        assert_eq!(resolved["weeks"], 1);
+        assert_eq!(resolved["format"], "md");
+    }
+
+    #[test]
+    fn test_check_params_reports_every_problem() {
+        let problems = template()
+            .check_params(&params(json!({ "weeks": "two", "format": "docx", "colour": "blue" })))
+            .unwrap_err();
+        assert_eq!(problems.len(), 4);
+        assert!(problems.iter().any(|p| p.contains("unknown parameter 'colour'")));
+        assert!(problems.iter().any(|p| p.contains("missing required parameter 'team'")));
+        assert!(problems.iter().any(|p| p.contains("'weeks' must be of type integer")));
+        assert!(problems.iter().any(|p| p.contains("'format' must be one of")));
+    }
+
+    #[test]
+    fn test_render_fills_placeholders() {
+        let t = template();
+        let resolved = t.check_params(&params(json!({ "team": "infra", "weeks": 3 }))).unwrap();
+        let (title, goal) = t.render(&resolved);
+        assert_eq!(title, "Report for infra (3 weeks)");
+        assert_eq!(goal, title);
+    }
+}
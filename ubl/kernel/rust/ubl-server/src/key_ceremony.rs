@@ -0,0 +1,161 @@
+//! # Authority Key Ceremony
+//!
+//! The `admin` key signs console permits and Agent Signing Certificates; if it
+//! is lost, none of them can be checked. Two procedures protect it:
+//!
+//! - **Escrow** — `keystore::split` hands `n` custodians one Shamir share
+//!   each; any `k` of them bring the key back with `keystore::restore`.
+//! - **Rotation** — [`rotate_authority`] replaces the key, re-signs every
+//!   unexpired ASC with the new one, and appends a `key.transition` record to
+//!   `C.Identity` signed by both the outgoing and the incoming key, so anyone
+//!   replaying the ledger can follow the chain of authority.
+//!
+//! Both run from the `ubl-server` command line (see README, "Key ceremony").
+
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::warn;
+use ubl_kernel::{contexts, sign_with, verify_with, SignatureMode};
+
+use crate::db::PgLedger;
+use crate::messenger_gateway::card_provenance::append_signed;
+use crate::{crypto, id_db, keystore};
+
+/// Key id of the authority key
+pub const AUTHORITY_KEY_ID: &str = "admin";
+
+/// Container the key transitions are recorded in
+pub const TRANSITION_CONTAINER: &str = "C.Identity";
+
+/// Outcome of an authority rotation
+#[derive(Debug, Serialize)]
+pub struct TransitionReport {
+    pub previous_pubkey: String,
+    pub pubkey: String,
+    pub resigned_ascs: usize,
+    pub entry_hash: String,
+}
+
+/// The signed part of a `key.transition` record
+fn transition_claims(key_id: &str, previous_pubkey: &str, pubkey: &str, resigned_ascs: usize, ts_unix_ms: i64) -> serde_json::Value {
+    serde_json::json!({
+        "type": "key.transition",
+        "key_id": key_id,
+        "previous_pubkey": previous_pubkey,
+        "pubkey": pubkey,
+        "resigned_ascs": resigned_ascs,
+        "ts_unix_ms": ts_unix_ms,
+    })
+}
+
+/// `key.transition` atom: the claims plus the outgoing key's endorsement of
+/// the new key and the new key's proof of possession (v2, `contexts::KEY_TRANSITION`)
+pub fn transition_atom(
+    key_id: &str,
+    previous: &SigningKey,
+    next: &SigningKey,
+    resigned_ascs: usize,
+    ts_unix_ms: i64,
+) -> Result<serde_json::Value, String> {
+    let previous_pubkey = hex::encode(previous.verifying_key().as_bytes());
+    let pubkey = hex::encode(next.verifying_key().as_bytes());
+    let mut atom = transition_claims(key_id, &previous_pubkey, &pubkey, resigned_ascs, ts_unix_ms);
+
+    let bytes = ubl_atom::canonicalize(&atom).map_err(|e| format!("CanonicalizeError: {}", e))?;
+    let sign = |key: &SigningKey| {
+        sign_with(SignatureMode::Ed25519ph, key, contexts::KEY_TRANSITION, &bytes).map_err(|e| e.to_string())
+    };
+    atom["previous_signature"] = sign(previous)?.into();
+    atom["signature"] = sign(next)?.into();
+    Ok(atom)
+}
+
+/// Check both signatures of a `key.transition` atom
+pub fn verify_transition(atom: &serde_json::Value) -> Result<(), String> {
+    let field = |name: &str| atom[name].as_str().ok_or_else(|| format!("missing {}", name));
+    let claims = transition_claims(
+        field("key_id")?,
+        field("previous_pubkey")?,
+        field("pubkey")?,
+        atom["resigned_ascs"].as_u64().ok_or("missing resigned_ascs")? as usize,
+        atom["ts_unix_ms"].as_i64().ok_or("missing ts_unix_ms")?,
+    );
+    let bytes = ubl_atom::canonicalize(&claims).map_err(|e| format!("CanonicalizeError: {}", e))?;
+
+    for (pubkey, signature) in [("previous_pubkey", "previous_signature"), ("pubkey", "signature")] {
+        verify_with(SignatureMode::Ed25519ph, field(pubkey)?, contexts::KEY_TRANSITION, &bytes, field(signature)?)
+            .map_err(|_| format!("{} does not verify", signature))?;
+    }
+    Ok(())
+}
+
+/// Rotate the authority key, re-sign active ASCs and record the transition.
+///
+/// The outgoing key stays available as `admin.prev`, so permits and ASCs
+/// signed a moment before still verify during the grace period.
+pub async fn rotate_authority(pool: &PgPool) -> Result<TransitionReport, String> {
+    let previous = keystore::load_or_create(AUTHORITY_KEY_ID);
+    keystore::rotate(AUTHORITY_KEY_ID)?;
+    let next = keystore::load_or_create(AUTHORITY_KEY_ID);
+
+    let resigned_ascs = id_db::resign_active_ascs(pool, |asc| {
+        crypto::sign_asc(&crypto::asc_signing_bytes(
+            &asc.sid,
+            &asc.public_key,
+            &asc.scopes,
+            asc.not_before.unix_timestamp(),
+            asc.not_after.unix_timestamp(),
+        ))
+    })
+    .await
+    .map_err(|e| format!("re-signing ASCs: {}", e))?;
+
+    let atom = transition_atom(AUTHORITY_KEY_ID, &previous, &next, resigned_ascs, crate::timestamps::now_ms())?;
+    verify_transition(&atom)?;
+    let entry = append_signed(&PgLedger::new(pool.clone()), TRANSITION_CONTAINER, atom)
+        .await
+        .map_err(|e| format!("recording key.transition: {}", e))?;
+
+    let report = TransitionReport {
+        previous_pubkey: hex::encode(previous.verifying_key().as_bytes()),
+        pubkey: hex::encode(next.verifying_key().as_bytes()),
+        resigned_ascs,
+        entry_hash: entry.entry_hash,
+    };
+    warn!(
+        "🔑 Authority key rotated: {} → {}, {} ASC(s) re-signed, recorded at {}",
+        report.previous_pubkey, report.pubkey, report.resigned_ascs, report.entry_hash
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition_is_signed_by_both_keys() {
+        let previous = SigningKey::from_bytes(&[1u8; 32]);
+        let next = SigningKey::from_bytes(&[2u8; 32]);
+        let atom = transition_atom("admin", &previous, &next, 3, 1_700_000_000_000).unwrap();
+        assert_eq!(atom["type"], "key.transition");
+        assert!(verify_transition(&atom).is_ok());
+
+        // The record cannot be edited or re-pointed at another key
+        let mut edited = atom.clone();
+        edited["resigned_ascs"] = 4.into();
+        assert!(verify_transition(&edited).is_err());
+
+        let mut hijacked = atom.clone();
+        let intruder = SigningKey::from_bytes(&[3u8; 32]);
+        hijacked["pubkey"] = hex::encode(intruder.verifying_key().as_bytes()).into();
+        assert!(verify_transition(&hijacked).is_err());
+
+        // Signatures from the wrong key pair do not pass either
+        let mut swapped = atom;
+        let forged = transition_atom("admin", &intruder, &next, 3, 1_700_000_000_000).unwrap();
+        swapped["previous_signature"] = forged["previous_signature"].clone();
+        assert!(verify_transition(&swapped).is_err());
+    }
+}
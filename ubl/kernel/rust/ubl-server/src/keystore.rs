@@ -3,19 +3,31 @@
 //! Gemini P0 #1: Keys must persist across restarts.
 //! Without this, agents lose their identity when the container restarts.
 //!
-//! Storage options:
-//! 1. File-based (default): ~/.ubl/keys/<key_id>.key
-//! 2. Environment variable: UBL_KEY_<KEY_ID>=<hex>
-//! 3. Future: HashiCorp Vault, AWS KMS
+//! Storage is delegated to a [`SecretProvider`] (see `secrets.rs`):
+//! 1. Environment variable: UBL_KEY_<KEY_ID>=<hex>
+//! 2. File-based (default): ~/.ubl/keys/<key_id>.key
+//! 3. AWS / GCP secret managers (stubs, via UBL_SECRETS_BACKEND)
+//!
+//! Rotation keeps the previous key under `<key_id>.prev` so signatures made
+//! just before a rotation can still be verified during the grace period.
+//!
+//! Escrow: [`split`] cuts a key into Shamir shares (`k` of `n` rebuild it) for
+//! separate custodians, and [`restore`] writes it back from any `k` of them.
+//! Each share names its key and carries a fingerprint of the public key, so
+//! shares of different keys or a wrong reconstruction are refused.
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer, Verifier};
 use rand::rngs::OsRng;
-use std::collections::HashMap;
+use sharks::{Share, Sharks};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
-use std::sync::RwLock;
+use std::sync::{OnceLock, RwLock};
 use tracing::{info, warn, error};
+use ubl_kernel::Zeroizing;
+
+use crate::secrets::{self, Secret, SecretProvider};
 
 /// Key storage directory
 fn keys_dir() -> PathBuf {
@@ -30,6 +42,14 @@ fn keys_dir() -> PathBuf {
 /// In-memory cache of loaded keys
 static KEY_CACHE: RwLock<Option<HashMap<String, SigningKey>>> = RwLock::new(None);
 
+/// Secret backend holding the key material
+static PROVIDER: OnceLock<Box<dyn SecretProvider>> = OnceLock::new();
+
+/// Secret backend shared with other key material (e.g. `atom_crypto`)
+pub(crate) fn provider() -> &'static dyn SecretProvider {
+    PROVIDER.get_or_init(|| secrets::from_env(keys_dir())).as_ref()
+}
+
 /// Initialize the keystore
 pub fn init() {
     let dir = keys_dir();
@@ -51,16 +71,36 @@ pub fn init() {
     let mut cache = KEY_CACHE.write().expect("KeyStore lock poisoned");
     *cache = Some(HashMap::new());
     
-    info!("KeyStore initialized at {:?}", dir);
+    info!("KeyStore initialized at {:?} (secrets backend: {})", dir, provider().name());
+}
+
+/// Drop cached keys so the next access re-reads the secret backend.
+/// Call after rotating keys out-of-band (e.g. in the secret manager).
+pub fn reload() {
+    let mut cache = KEY_CACHE.write().expect("KeyStore lock poisoned");
+    if let Some(ref mut map) = *cache {
+        map.clear();
+    }
+    info!("KeyStore cache cleared");
+}
+
+/// Decode a hex-encoded 32-byte secret into a signing key.
+/// Decoding is constant-time and intermediate buffers are zeroized.
+fn decode_key(secret: &Secret) -> Option<SigningKey> {
+    ubl_kernel::signing_key_from_hex(secret.expose()).ok()
+}
+
+fn encode_key(key: &SigningKey) -> Secret {
+    let bytes = Zeroizing::new(key.to_bytes());
+    Secret::new(hex::encode(bytes.as_slice()))
 }
 
 /// Load or create a signing key by ID
 /// 
 /// Priority:
 /// 1. Memory cache
-/// 2. Environment variable: UBL_KEY_<KEY_ID>=<hex>
-/// 3. File: <keys_dir>/<key_id>.key
-/// 4. Generate new and save to file
+/// 2. Secret provider chain (env, then file, or a cloud backend)
+/// 3. Generate new and store through the provider
 pub fn load_or_create(key_id: &str) -> SigningKey {
     // Check cache first
     {
@@ -72,60 +112,26 @@ pub fn load_or_create(key_id: &str) -> SigningKey {
         }
     }
     
-    // Try environment variable
-    let env_key = format!("UBL_KEY_{}", key_id.to_uppercase().replace('-', "_"));
-    if let Ok(hex) = std::env::var(&env_key) {
-        if let Ok(bytes) = hex::decode(hex.trim()) {
-            if bytes.len() == 32 {
-                // Fix #15: Safe because we just checked len == 32
-                let key_bytes: [u8; 32] = bytes.try_into().expect("length already verified");
-                let key = SigningKey::from_bytes(&key_bytes);
+    match provider().get(key_id) {
+        Ok(Some(secret)) => match decode_key(&secret) {
+            Some(key) => {
                 cache_key(key_id, key.clone());
-                info!("Loaded key '{}' from environment", key_id);
+                info!("Loaded key '{}' from {} backend", key_id, provider().name());
                 return key;
             }
-        }
-        warn!("Invalid key in {}, ignoring", env_key);
-    }
-    
-    // Try file
-    let key_path = keys_dir().join(format!("{}.key", key_id));
-    if key_path.exists() {
-        match fs::read_to_string(&key_path) {
-            Ok(hex) => {
-                if let Ok(bytes) = hex::decode(hex.trim()) {
-                    if bytes.len() == 32 {
-                        // Fix #15: Safe because we just checked len == 32
-                        let key_bytes: [u8; 32] = bytes.try_into().expect("length already verified");
-                        let key = SigningKey::from_bytes(&key_bytes);
-                        cache_key(key_id, key.clone());
-                        info!("Loaded key '{}' from {:?}", key_id, key_path);
-                        return key;
-                    }
-                }
-                warn!("Invalid key file {:?}, regenerating", key_path);
-            }
-            Err(e) => {
-                warn!("Could not read {:?}: {}", key_path, e);
-            }
-        }
+            None => warn!("Invalid key material for '{}', regenerating", key_id),
+        },
+        Ok(None) => {}
+        Err(e) => warn!("Could not read key '{}': {}", key_id, e),
     }
     
     // Generate new key
     let key = SigningKey::generate(&mut OsRng);
     
-    // Save to file
-    let hex = hex::encode(key.to_bytes());
-    if let Err(e) = fs::write(&key_path, &hex) {
-        error!("Failed to save key to {:?}: {}", key_path, e);
+    if let Err(e) = provider().put(key_id, &encode_key(&key)) {
+        error!("Failed to persist key '{}': {}", key_id, e);
     } else {
-        // Set restrictive permissions
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let _ = fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600));
-        }
-        info!("Generated and saved new key '{}' to {:?}", key_id, key_path);
+        info!("Generated and saved new key '{}'", key_id);
     }
     
     // Log public key for registration
@@ -136,6 +142,31 @@ pub fn load_or_create(key_id: &str) -> SigningKey {
     key
 }
 
+/// Rotate a key: the current key moves to `<key_id>.prev` and a fresh key
+/// becomes active. Returns the new public key (hex).
+pub fn rotate(key_id: &str) -> Result<String, String> {
+    let current = load_or_create(key_id);
+    let next = SigningKey::generate(&mut OsRng);
+    
+    provider()
+        .put(&format!("{}.prev", key_id), &encode_key(&current))
+        .map_err(|e| e.to_string())?;
+    provider()
+        .put(key_id, &encode_key(&next))
+        .map_err(|e| e.to_string())?;
+    
+    cache_key(key_id, next.clone());
+    let pubkey = hex::encode(next.verifying_key().as_bytes());
+    warn!("Rotated key '{}': new public key {}", key_id, pubkey);
+    Ok(pubkey)
+}
+
+/// Public key (hex) of the key that was active before the last rotation
+pub fn previous_public_key_hex(key_id: &str) -> Option<String> {
+    let secret = provider().get(&format!("{}.prev", key_id)).ok()??;
+    decode_key(&secret).map(|k| hex::encode(k.verifying_key().as_bytes()))
+}
+
 fn cache_key(key_id: &str, key: SigningKey) {
     let mut cache = KEY_CACHE.write().unwrap();
     if let Some(ref mut map) = *cache {
@@ -181,6 +212,103 @@ pub fn verify(pubkey_hex: &str, data: &[u8], sig_tagged: &str) -> Result<(), Str
         .map_err(|_| "Signature verification failed".to_string())
 }
 
+/// Version prefix of an escrow share
+const SHARE_PREFIX: &str = "ubl-share:1";
+
+/// Short fingerprint (hex) of a public key, printed on escrow shares
+pub fn fingerprint(pubkey: &VerifyingKey) -> String {
+    hex::encode(&blake3::hash(pubkey.as_bytes()).as_bytes()[..8])
+}
+
+/// Split a key into `shares` Shamir shares, any `threshold` of which restore
+/// it. Each share is one line of text:
+/// `ubl-share:1:<key_id>:<threshold>:<fingerprint>:<hex>`.
+pub fn split(key_id: &str, threshold: u8, shares: u8) -> Result<Vec<String>, String> {
+    if key_id.is_empty() || key_id.contains(':') {
+        return Err(format!("Invalid key id '{}'", key_id));
+    }
+    if threshold < 2 || shares < threshold {
+        return Err(format!("Need 2 <= threshold <= shares, got {} of {}", threshold, shares));
+    }
+
+    let key = load_or_create(key_id);
+    let secret = Zeroizing::new(key.to_bytes());
+    let fp = fingerprint(&key.verifying_key());
+    let lines = Sharks(threshold)
+        .dealer(secret.as_slice())
+        .take(shares as usize)
+        .map(|share| {
+            let bytes = Zeroizing::new(Vec::from(&share));
+            format!("{}:{}:{}:{}:{}", SHARE_PREFIX, key_id, threshold, fp, hex::encode(bytes.as_slice()))
+        })
+        .collect();
+
+    warn!("Split key '{}' into {} shares (threshold {}), fingerprint {}", key_id, shares, threshold, fp);
+    Ok(lines)
+}
+
+/// Rebuild a key from escrow shares and store it as `key_id`. A different
+/// key already stored under that id is kept as `<key_id>.displaced`.
+/// Returns the restored public key (hex).
+pub fn restore(key_id: &str, shares: &[String]) -> Result<String, String> {
+    let mut threshold = None;
+    let mut expected_fp = None;
+    // Keyed by share index: a share entered twice counts once
+    let mut parsed: BTreeMap<u8, Share> = BTreeMap::new();
+
+    for line in shares.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        let fields: Vec<&str> = line
+            .strip_prefix(SHARE_PREFIX)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or("Not a ubl-share:1 share")?
+            .split(':')
+            .collect();
+        let [share_key, share_threshold, fp, data] = fields[..] else {
+            return Err("Malformed share".into());
+        };
+        if share_key != key_id {
+            return Err(format!("Share is for key '{}', not '{}'", share_key, key_id));
+        }
+        let share_threshold: u8 = share_threshold.parse().map_err(|_| "Malformed share threshold")?;
+        if *threshold.get_or_insert(share_threshold) != share_threshold || *expected_fp.get_or_insert(fp) != fp {
+            return Err("Shares come from different splits".into());
+        }
+
+        let bytes = Zeroizing::new(hex::decode(data).map_err(|_| "Malformed share data")?);
+        let share = Share::try_from(bytes.as_slice())?;
+        parsed.insert(share.x.0, share);
+    }
+
+    let (Some(threshold), Some(expected_fp)) = (threshold, expected_fp) else {
+        return Err("No shares given".into());
+    };
+    if parsed.len() < threshold as usize {
+        return Err(format!("Need {} distinct shares, got {}", threshold, parsed.len()));
+    }
+
+    let secret = Zeroizing::new(Sharks(threshold).recover(parsed.values())?);
+    let bytes: [u8; 32] = secret.as_slice().try_into().map_err(|_| "Shares do not hold an Ed25519 key")?;
+    let key = SigningKey::from_bytes(&bytes);
+    if fingerprint(&key.verifying_key()) != expected_fp {
+        return Err("Shares do not reconstruct the escrowed key (corrupt share?)".into());
+    }
+
+    if let Ok(Some(existing)) = provider().get(key_id) {
+        if decode_key(&existing).is_some_and(|k| k.to_bytes() != key.to_bytes()) {
+            provider()
+                .put(&format!("{}.displaced", key_id), &existing)
+                .map_err(|e| e.to_string())?;
+            warn!("Key '{}' replaced by restore; previous key kept as '{}.displaced'", key_id, key_id);
+        }
+    }
+    provider().put(key_id, &encode_key(&key)).map_err(|e| e.to_string())?;
+    cache_key(key_id, key.clone());
+
+    let pubkey = hex::encode(key.verifying_key().as_bytes());
+    warn!("Restored key '{}' from {} shares: public key {}", key_id, parsed.len(), pubkey);
+    Ok(pubkey)
+}
+
 /// List all key IDs in the keystore
 pub fn list_keys() -> Vec<String> {
     let dir = keys_dir();
@@ -189,7 +317,7 @@ pub fn list_keys() -> Vec<String> {
     if let Ok(entries) = fs::read_dir(&dir) {
         for entry in entries.flatten() {
             if let Some(name) = entry.file_name().to_str() {
-                if name.ends_with(".key") {
+                if name.ends_with(".key") && !name.ends_with(".prev.key") && !name.ends_with(".displaced.key") {
                     keys.push(name.trim_end_matches(".key").to_string());
                 }
             }
@@ -201,21 +329,23 @@ pub fn list_keys() -> Vec<String> {
 
 /// Delete a key (use with caution!)
 pub fn delete_key(key_id: &str) -> Result<(), String> {
-    let key_path = keys_dir().join(format!("{}.key", key_id));
-    
-    if key_path.exists() {
-        fs::remove_file(&key_path).map_err(|e| e.to_string())?;
-        
-        // Remove from cache
-        let mut cache = KEY_CACHE.write().unwrap();
-        if let Some(ref mut map) = *cache {
-            map.remove(key_id);
+    match provider().get(key_id) {
+        Ok(Some(_)) => {
+            provider().delete(key_id).map_err(|e| e.to_string())?;
+            let _ = provider().delete(&format!("{}.prev", key_id));
+            let _ = provider().delete(&format!("{}.displaced", key_id));
+            
+            // Remove from cache
+            let mut cache = KEY_CACHE.write().unwrap();
+            if let Some(ref mut map) = *cache {
+                map.remove(key_id);
+            }
+            
+            warn!("Deleted key '{}'", key_id);
+            Ok(())
         }
-        
-        warn!("Deleted key '{}'", key_id);
-        Ok(())
-    } else {
-        Err("Key not found".into())
+        Ok(None) => Err("Key not found".into()),
+        Err(e) => Err(e.to_string()),
     }
 }
 
@@ -253,5 +383,57 @@ mod tests {
         // Cleanup
         let _ = delete_key(key_id);
     }
+    
+    #[test]
+    fn test_rotate_keeps_previous_key() {
+        init();
+        
+        let key_id = "test-rotate";
+        let before = get_public_key_hex(key_id);
+        let after = rotate(key_id).unwrap();
+        
+        assert_ne!(before, after);
+        assert_eq!(get_public_key_hex(key_id), after);
+        assert_eq!(previous_public_key_hex(key_id), Some(before));
+        
+        // Cleanup
+        let _ = delete_key(key_id);
+    }
+    
+    #[test]
+    fn test_split_and_restore() {
+        init();
+        
+        let key_id = "test-escrow";
+        let original = get_public_key_hex(key_id);
+        let shares = split(key_id, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        
+        // Key lost: a new one gets generated in its place
+        let _ = delete_key(key_id);
+        assert_ne!(get_public_key_hex(key_id), original);
+        
+        // Any three shares bring the original back
+        let restored = restore(key_id, &[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap();
+        assert_eq!(restored, original);
+        assert_eq!(get_public_key_hex(key_id), original);
+        
+        // Two are not enough, even if one is repeated
+        assert!(restore(key_id, &shares[..2]).is_err());
+        assert!(restore(key_id, &[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err());
+        
+        // A corrupted share fails the fingerprint check
+        let mut corrupt = shares[1].clone();
+        let last = corrupt.pop().unwrap();
+        corrupt.push(if last == '0' { '1' } else { '0' });
+        assert!(restore(key_id, &[shares[0].clone(), corrupt, shares[2].clone()]).is_err());
+        
+        // Shares only restore the key they were cut from
+        assert!(restore("test-escrow-other", &shares[..3]).is_err());
+        assert!(split(key_id, 1, 3).is_err());
+        assert!(split(key_id, 4, 3).is_err());
+        
+        // Cleanup
+        let _ = delete_key(key_id);
+    }
 }
-
@@ -0,0 +1,516 @@
+//! Ledger read routes
+//!
+//! - GET /ledger/:container_id/entries?after=|from_seq=&limit=&include=annotations,atoms
+//! - GET /ledger/:container_id/entry/:sequence?include=annotations
+//! - GET /ledger/:container_id/poll?cursor=&wait_ms=&limit= (long poll)
+//! - POST /sync (differential sync across containers)
+//!
+//! `include=annotations` joins C.Audit annotations (see
+//! `projections::annotations`) onto each entry. Entries themselves are never
+//! modified by annotations.
+//!
+//! `include=atoms` adds each entry's atom as stored (sealed for encrypted
+//! containers). Paging with `from_seq` (inclusive) and `next_from_seq` until
+//! a page comes back empty replays a whole container; in-process consumers
+//! use `ledger_replay::LedgerReplay` instead.
+//!
+//! `/sync` takes `{"cursors": {"C.Jobs": 12, "C.Messenger": 40}}` (or the
+//! opaque `cursor` string from a previous response) and returns every newer
+//! entry with its atom, the projection rows those entries touched, and a
+//! combined cursor. Responses stay under `UBL_SYNC_MAX_BYTES` (default
+//! 512 KiB); `has_more` tells the client to call again with the new cursor.
+//!
+//! `/poll` is the fallback for networks that break SSE: it answers at once
+//! with the entries after `cursor` (a sequence), or holds the request until
+//! the container's tail moves or `wait_ms` (default 25s, at most 30s)
+//! passes, and then returns them with the `cursor` for the next call. An
+//! empty `entries` just means "poll again". Waiting polls count against the
+//! tenant's SSE stream limit. The tail is per replica, so a commit landing
+//! on another one is seen by the next poll at the latest.
+//!
+//! Entries come in `sequence` order and carry both the appending replica's
+//! wall clock (`ts_unix_ms`) and the `hlc` string (see `hlc`), which is what
+//! to sort by when merging entries of several containers.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
+use std::time::Duration;
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::hlc::Hlc;
+use crate::projections::{AnnotationRow, AnnotationsProjection};
+use crate::{sse, AppState};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+/// How long `/poll` holds a request by default, and at most
+const DEFAULT_POLL_WAIT: Duration = Duration::from_secs(25);
+const MAX_POLL_WAIT: Duration = Duration::from_secs(30);
+
+/// Default response budget for `/sync`
+const DEFAULT_SYNC_MAX_BYTES: usize = 512 * 1024;
+/// Containers per `/sync` request
+const SYNC_MAX_CONTAINERS: usize = 50;
+/// Rows fetched per container per round-trip
+const SYNC_PAGE: i64 = 200;
+
+/// Projection tables whose rows are returned as deltas for a container
+const SYNC_PROJECTIONS: &[(&str, &str)] = &[
+    ("C.Jobs", "projection_jobs"),
+    ("C.Messenger", "projection_conversations"),
+];
+
+fn sync_max_bytes() -> usize {
+    static MAX: OnceLock<usize> = OnceLock::new();
+    *MAX.get_or_init(|| {
+        std::env::var("UBL_SYNC_MAX_BYTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_SYNC_MAX_BYTES)
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntriesQuery {
+    /// Return entries with sequence > after
+    pub after: Option<i64>,
+    /// Return entries with sequence >= from_seq; takes precedence over `after`
+    pub from_seq: Option<i64>,
+    pub limit: Option<i64>,
+    /// Comma-separated extras: "annotations", "atoms"
+    pub include: Option<String>,
+}
+
+impl EntriesQuery {
+    fn wants(&self, extra: &str) -> bool {
+        self.include
+            .as_deref()
+            .map(|s| s.split(',').any(|part| part.trim() == extra))
+            .unwrap_or(false)
+    }
+
+    fn wants_annotations(&self) -> bool {
+        self.wants("annotations")
+    }
+
+    fn wants_atoms(&self) -> bool {
+        self.wants("atoms")
+    }
+
+    /// Sequence the page starts after
+    fn start_after(&self) -> i64 {
+        match self.from_seq {
+            Some(from) => from.saturating_sub(1),
+            None => self.after.unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct EntryView {
+    pub container_id: String,
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+    #[sqlx(flatten)]
+    pub hlc: Hlc,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<AnnotationRow>>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atom: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntriesResponse {
+    pub container_id: String,
+    pub entries: Vec<EntryView>,
+    /// Pass as `after` to fetch the next page; None when the page was empty
+    pub next_after: Option<i64>,
+    /// Pass as `from_seq` to fetch the next page; None when the page was empty
+    pub next_from_seq: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PollQuery {
+    /// Last sequence the client has (0 for none)
+    pub cursor: Option<i64>,
+    pub wait_ms: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+impl PollQuery {
+    fn wait(&self) -> Duration {
+        self.wait_ms.map(Duration::from_millis).unwrap_or(DEFAULT_POLL_WAIT).min(MAX_POLL_WAIT)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PollResponse {
+    pub container_id: String,
+    pub entries: Vec<EntryView>,
+    /// Pass as `cursor` on the next poll (unchanged when nothing arrived)
+    pub cursor: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SyncRequest {
+    /// container_id -> last sequence the client has
+    #[serde(default)]
+    pub cursors: BTreeMap<String, i64>,
+    /// Combined cursor from a previous response; merged under `cursors`
+    pub cursor: Option<String>,
+    /// Client-side budget; capped by the server budget
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SyncEntry {
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+    #[sqlx(flatten)]
+    pub hlc: Hlc,
+    pub atom: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncResponse {
+    /// New entries per container, in sequence order
+    pub entries: BTreeMap<String, Vec<SyncEntry>>,
+    /// Projection rows touched by the returned entries, keyed by table
+    pub projections: BTreeMap<String, Vec<Value>>,
+    pub cursors: BTreeMap<String, i64>,
+    /// Opaque combined cursor for the next call
+    pub cursor: String,
+    /// More entries remain beyond the byte budget
+    pub has_more: bool,
+    pub bytes: usize,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/ledger/:container_id/entries", get(list_entries))
+        .route("/ledger/:container_id/entry/:sequence", get(get_entry))
+        .route("/ledger/:container_id/poll", get(poll))
+        .route("/sync", post(sync))
+}
+
+async fn entries_after(state: &AppState, container_id: &str, after: i64, limit: i64) -> Result<Vec<EntryView>, ApiError> {
+    sqlx::query_as::<_, EntryView>(
+        r#"
+        SELECT container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms,
+               COALESCE(hlc_wall_ms, ts_unix_ms) AS hlc_wall_ms, COALESCE(hlc_logical, 0) AS hlc_logical
+        FROM ledger_entry
+        WHERE container_id = $1 AND sequence > $2
+        ORDER BY sequence ASC
+        LIMIT $3
+        "#,
+    )
+    .bind(container_id)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))
+}
+
+/// GET /ledger/:container_id/entries
+async fn list_entries(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    Query(query): Query<EntriesQuery>,
+) -> Result<Json<EntriesResponse>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut entries = entries_after(&state, &container_id, query.start_after(), limit).await?;
+
+    if query.wants_annotations() {
+        attach_annotations(&state, &mut entries).await?;
+    }
+    if query.wants_atoms() {
+        attach_atoms(&state, &mut entries).await?;
+    }
+
+    let next_after = entries.last().map(|e| e.sequence);
+    let next_from_seq = next_after.map(|seq| seq + 1);
+    Ok(Json(EntriesResponse { container_id, entries, next_after, next_from_seq }))
+}
+
+/// GET /ledger/:container_id/poll
+async fn poll(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<PollQuery>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<PollResponse>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let cursor = query.cursor.unwrap_or(0);
+
+    // Subscribe before reading, so an entry appended in between wakes us
+    let mut waiter = state.tail_bus.waiter(&container_id);
+    let mut entries = entries_after(&state, &container_id, cursor, limit).await?;
+    if entries.is_empty() {
+        let tenant = sse::tenant_key(&headers, &params);
+        let bus = &state.tail_bus;
+        let _guard = bus.connections.try_acquire(&tenant, bus.limits.max_per_tenant).ok_or_else(|| {
+            ApiError::new(ErrorCode::RateLimited, "SSE connection limit reached for tenant")
+        })?;
+        if waiter.wait(cursor, query.wait()).await {
+            entries = entries_after(&state, &container_id, cursor, limit).await?;
+        }
+    }
+
+    let cursor = entries.last().map(|e| e.sequence).unwrap_or(cursor);
+    Ok(Json(PollResponse { container_id, entries, cursor }))
+}
+
+/// GET /ledger/:container_id/entry/:sequence
+async fn get_entry(
+    State(state): State<AppState>,
+    Path((container_id, sequence)): Path<(String, i64)>,
+    Query(query): Query<EntriesQuery>,
+) -> Result<Json<EntryView>, ApiError> {
+    let entry = sqlx::query_as::<_, EntryView>(
+        r#"
+        SELECT container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms,
+               COALESCE(hlc_wall_ms, ts_unix_ms) AS hlc_wall_ms, COALESCE(hlc_logical, 0) AS hlc_logical
+        FROM ledger_entry
+        WHERE container_id = $1 AND sequence = $2
+        "#,
+    )
+    .bind(&container_id)
+    .bind(sequence)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?
+    .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("No entry {}:{}", container_id, sequence)))?;
+
+    let mut entries = vec![entry];
+    if query.wants_annotations() {
+        attach_annotations(&state, &mut entries).await?;
+    }
+    Ok(Json(entries.remove(0)))
+}
+
+/// POST /sync
+async fn sync(
+    State(state): State<AppState>,
+    Json(req): Json<SyncRequest>,
+) -> Result<Json<SyncResponse>, ApiError> {
+    let mut cursors = match req.cursor.as_deref() {
+        Some(raw) => parse_cursor(raw)
+            .ok_or_else(|| ApiError::new(ErrorCode::BadRequest, "malformed sync cursor"))?,
+        None => BTreeMap::new(),
+    };
+    cursors.extend(req.cursors);
+    if cursors.is_empty() || cursors.len() > SYNC_MAX_CONTAINERS {
+        return Err(ApiError::new(
+            ErrorCode::BadRequest,
+            format!("sync needs between 1 and {} containers", SYNC_MAX_CONTAINERS),
+        ));
+    }
+    let budget = req.max_bytes.unwrap_or(usize::MAX).min(sync_max_bytes());
+
+    let mut entries = BTreeMap::new();
+    let mut projections: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    let mut bytes = 0usize;
+    let mut has_more = false;
+
+    for (container_id, after) in cursors.iter_mut() {
+        if has_more {
+            break;
+        }
+        let rows = sqlx::query_as::<_, SyncEntry>(
+            r#"
+            SELECT le.sequence, le.link_hash, le.previous_hash, le.entry_hash, le.ts_unix_ms,
+                   COALESCE(le.hlc_wall_ms, le.ts_unix_ms) AS hlc_wall_ms, COALESCE(le.hlc_logical, 0) AS hlc_logical,
+                   la.atom_data AS atom
+            FROM ledger_entry le
+            LEFT JOIN ledger_atom la ON la.atom_hash = le.link_hash
+            WHERE le.container_id = $1 AND le.sequence > $2
+            ORDER BY le.sequence ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(&*container_id)
+        .bind(*after)
+        .bind(SYNC_PAGE)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+
+        let fetched = rows.len() as i64;
+        let sizes: Vec<usize> = rows
+            .iter()
+            .map(|r| serde_json::to_vec(r).map(|v| v.len()).unwrap_or(0))
+            .collect();
+        let taken = take_within_budget(&sizes, budget.saturating_sub(bytes), bytes == 0);
+        bytes += sizes[..taken].iter().sum::<usize>();
+        if taken < rows.len() || fetched == SYNC_PAGE {
+            has_more = true;
+        }
+
+        let page: Vec<SyncEntry> = rows.into_iter().take(taken).collect();
+        if let Some(last) = page.last() {
+            let from = *after;
+            *after = last.sequence;
+            if let Some((_, table)) = SYNC_PROJECTIONS.iter().find(|(c, _)| c == container_id) {
+                let rows = projection_delta(&state, table, from, *after).await?;
+                projections.entry(table.to_string()).or_default().extend(rows);
+            }
+            entries.insert(container_id.clone(), page);
+        }
+    }
+
+    let cursor = format_cursor(&cursors);
+    Ok(Json(SyncResponse { entries, projections, cursors, cursor, has_more, bytes }))
+}
+
+/// Rows of `table` last touched by an event in (from, to]
+async fn projection_delta(state: &AppState, table: &str, from: i64, to: i64) -> Result<Vec<Value>, ApiError> {
+    // `table` comes from SYNC_PROJECTIONS, never from the request
+    let sql = format!(
+        "SELECT to_jsonb(t) FROM {} t WHERE t.last_event_seq > $1 AND t.last_event_seq <= $2 ORDER BY t.last_event_seq",
+        table
+    );
+    sqlx::query_scalar::<_, Value>(&sql)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))
+}
+
+/// How many leading items fit in `remaining` bytes. When `must_progress` is
+/// set, at least one item is taken so an oversized entry cannot stall sync.
+fn take_within_budget(sizes: &[usize], remaining: usize, must_progress: bool) -> usize {
+    let mut used = 0usize;
+    for (i, size) in sizes.iter().enumerate() {
+        if used + size > remaining && !(must_progress && i == 0) {
+            return i;
+        }
+        used += size;
+    }
+    sizes.len()
+}
+
+/// `C.Jobs:12,C.Messenger:40` (container ids may contain ':')
+fn format_cursor(cursors: &BTreeMap<String, i64>) -> String {
+    cursors
+        .iter()
+        .map(|(c, seq)| format!("{}:{}", c, seq))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_cursor(raw: &str) -> Option<BTreeMap<String, i64>> {
+    raw.split(',')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (container, seq) = part.rsplit_once(':')?;
+            Some((container.to_string(), seq.parse().ok()?))
+        })
+        .collect()
+}
+
+async fn attach_annotations(state: &AppState, entries: &mut [EntryView]) -> Result<(), ApiError> {
+    let hashes: Vec<String> = entries.iter().map(|e| e.entry_hash.clone()).collect();
+    let rows = AnnotationsProjection::new(state.pool.clone())
+        .for_entries(&hashes)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+
+    let mut by_target: HashMap<String, Vec<AnnotationRow>> = HashMap::new();
+    for row in rows {
+        by_target.entry(row.target_entry_hash.clone()).or_default().push(row);
+    }
+    for entry in entries.iter_mut() {
+        entry.annotations = Some(by_target.remove(&entry.entry_hash).unwrap_or_default());
+    }
+    Ok(())
+}
+
+async fn attach_atoms(state: &AppState, entries: &mut [EntryView]) -> Result<(), ApiError> {
+    let hashes: Vec<String> = entries.iter().map(|e| e.link_hash.clone()).collect();
+    let rows: Vec<(String, Value)> = sqlx::query_as("SELECT atom_hash, atom_data FROM ledger_atom WHERE atom_hash = ANY($1)")
+        .bind(&hashes)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+
+    let mut by_hash: HashMap<String, Value> = rows.into_iter().collect();
+    for entry in entries.iter_mut() {
+        entry.atom = by_hash.remove(&entry.link_hash);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_include_parsing() {
+        let q = |s: Option<&str>| EntriesQuery { after: None, from_seq: None, limit: None, include: s.map(String::from) };
+        assert!(q(Some("annotations")).wants_annotations());
+        assert!(q(Some("atoms, annotations")).wants_annotations());
+        assert!(!q(Some("atoms")).wants_annotations());
+        assert!(q(Some("atoms")).wants_atoms());
+        assert!(!q(None).wants_annotations());
+        assert!(!q(None).wants_atoms());
+    }
+
+    #[test]
+    fn test_from_seq_wins_over_after() {
+        let q = |after, from_seq| EntriesQuery { after, from_seq, limit: None, include: None };
+        assert_eq!(q(None, None).start_after(), 0);
+        assert_eq!(q(Some(7), None).start_after(), 7);
+        assert_eq!(q(Some(7), Some(3)).start_after(), 2);
+        assert_eq!(q(None, Some(i64::MIN)).start_after(), i64::MIN);
+    }
+
+    #[test]
+    fn test_poll_wait_is_capped() {
+        let q = |wait_ms: Option<u64>| PollQuery { cursor: None, wait_ms, limit: None };
+        assert_eq!(q(None).wait(), DEFAULT_POLL_WAIT);
+        assert_eq!(q(Some(0)).wait(), Duration::ZERO);
+        assert_eq!(q(Some(5_000)).wait(), Duration::from_secs(5));
+        assert_eq!(q(Some(600_000)).wait(), MAX_POLL_WAIT);
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let mut cursors = BTreeMap::new();
+        cursors.insert("C.Jobs".to_string(), 12);
+        cursors.insert("repo://tenant/ws".to_string(), 3);
+        let raw = format_cursor(&cursors);
+        assert_eq!(raw, "C.Jobs:12,repo://tenant/ws:3");
+        assert_eq!(parse_cursor(&raw), Some(cursors));
+        assert_eq!(parse_cursor("C.Jobs:abc"), None);
+    }
+
+    #[test]
+    fn test_budget_always_makes_progress() {
+        assert_eq!(take_within_budget(&[10, 10, 10], 25, false), 2);
+        assert_eq!(take_within_budget(&[100, 10], 25, true), 1);
+        assert_eq!(take_within_budget(&[100, 10], 25, false), 0);
+        assert_eq!(take_within_budget(&[], 25, true), 0);
+    }
+}
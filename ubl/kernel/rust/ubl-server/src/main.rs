@@ -9,14 +9,39 @@
 //! - GET  /state/:container_id  
 //! - POST /link/validate
 //! - POST /link/commit
-//! - GET  /ledger/:container_id/tail (SSE)
+//! - GET  /ledger/tail (SSE; entry.v1 envelopes, ?format=legacy for cid:seq, ?containers= within the caller's scope)
 //! - GET  /atom/:hash
+//! - GET  /ledger/:container_id/entries (?from_seq=&limit=&include=annotations,atoms; paged replay)
+//! - GET  /ledger/:container_id/entry/:sequence
+//! - GET  /ledger/:container_id/poll (?cursor=&wait_ms=; long-poll fallback for the tail)
+//! - POST /sync                  (differential sync for mobile/edge)
 //!
 //! Console v1.1 (ADR-001):
 //! - POST /v1/policy/permit       → Issue Permit
 //! - POST /v1/commands/issue      → Register Command
 //! - GET  /v1/query/commands      → List pending (Runner pulls)
-//! - POST /v1/exec.finish         → Register Receipt
+//! - POST /v1/exec.finish         → Register Receipt (returns artifact upload slots)
+//! - PUT  /v1/receipts/:id/artifacts/:name → Upload a declared receipt artifact
+//! - GET  /v1/receipts/:id         → Receipt + artifact completeness
+//! - POST /v1/runners             → Register runner capabilities
+//! - POST /v1/runners/:id/heartbeat
+//! - GET  /v1/runners             (?live=true&capability=)
+//! - POST /v1/exec/:id/logs       → Append hash-chained log segment (runner-signed)
+//! - GET  /v1/exec/:id/logs       (Range: bytes=a-b)
+//! - GET  /v1/exec/:id/logs/tail  → SSE live tail (?from_seq=)
+//! - GET  /v1/containers/:id/flags/stream → SSE feature flags (flags.v1 on connect and on change)
+//! - POST /v1/runners/:id/dead-letters → Report dead-lettered job (runner-signed)
+//! - GET|PATCH|DELETE /v1/admin/dead-letters[/:job_id], POST .../:job_id/requeue (step-up)
+//! - POST|GET /v1/admin/actions[/:id], POST .../:id/approve|cancel → Multi-admin destructive ops (step-up + pact)
+//! - GET  /v1/policy/:id/coverage → Hits per policy rule, unused and over-triggered rules (step-up)
+//! - GET  /v1/policy/:id/history → Policy versions with activation windows, version active at ?at= (step-up)
+//! - POST /v1/policy/debug → Traced policy evaluation with disassembly (step-up)
+//! - GET  /v1/admin/rejections → Recently refused commits with stage and reason (step-up, UBL_REJECTION_AUDIT)
+//! - GET  /v1/admin/maintenance, POST .../flush → In-memory structure sizes; flush them now (step-up)
+//! - POST|GET /v1/admin/public-tokens, DELETE .../:token_id → Public read API tokens (step-up)
+//!
+//! Public read gateway (separate listener, UBL_PUBLIC_READ_ADDR; see public_read::gateway):
+//! - GET  /public/v1/usage, /public/v1/containers/:id/state, /public/v1/containers/:id/entries
 //!
 //! Registry v1.1 (ADR-002):
 //! - GET  /v1/query/registry/projects
@@ -24,45 +49,95 @@
 //!
 //! Identity:
 //! - POST /id/agents (create LLM/App)
-//! - POST /id/agents/{sid}/asc (issue ASC)
+//! - POST /id/agents/{sid}/asc (issue ASC, optionally from a named template)
+//! - GET  /id/asc/templates, POST /id/agents/{sid}/asc/requests → Template ASC requests
+//! - POST /id/asc/requests/{id}/approve|reject (tenant admin, step-up)
 //! - GET  /id/whoami
+//!
+//! Flags:
+//! - --check-config  Validate configuration, print it redacted, and exit
+//! - --rotate-key ID Rotate a keystore key (previous key kept for verification)
+//! - --rotate-atom-master  Rotate the atom encryption master key and re-wrap all data keys
+//! - --rewrap-atom-keys    Re-wrap data keys still under the previous master key (resume)
 
+mod admin_actions;
+mod api_error;
+mod asc_expiry;
+mod atom_crypto;
+mod asc_requests;
+mod commit_claim;
+mod config;
+mod container_manifest;
+mod contracts;
 mod db;
+mod dry_run;
+mod evolution;
+mod feature_flags;
+mod health;
+mod hlc;
+mod observation_batch;
 mod sse;
+mod sse_auth;
+mod id_anomaly;
 mod id_db;
 mod id_routes;
 mod auth;
 mod identity;  // 🆕 New modular identity system
 mod rate_limit;
+mod rejections;
 mod metrics;
+mod mirror;
 mod otel_tracing;
 mod id_ledger;
 mod id_session_token;
 mod repo_routes;
+mod route_auth;
+mod ledger_replay;
+mod ledger_routes;
+mod maintenance;
 mod middleware_require_stepup;
 mod projections;
 mod pact_db;
+mod query_params;
 mod policy_registry;
+mod policy_routes;
+mod public_read;
 mod console_v1;
+mod blob_store;
+mod bootstrap;
+mod chain_check;
+mod runners;
+mod exec_logs;
+mod dead_letters;
 mod registry_v1;
 mod messenger_v1;
 mod messenger_gateway;
 mod policy;
 mod job_monitor; // Diamond Checklist #8: Job timeout monitor
+mod job_templates;
 mod crypto;
 mod webauthn_store;
 mod keystore;
+mod key_ceremony;
+mod secrets;
+mod service_auth;
+mod slo;
 mod snapshots;
 mod tenant;
+mod timestamps;
+#[cfg(test)]
+mod ts_conformance;
 
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, HeaderMap},
-    response::IntoResponse,
+    extract::{DefaultBodyLimit, Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use api_error::ApiError;
 use db::{LedgerEntry, LinkDraft, PgLedger, TangencyError};
+use ubl_errors::ErrorCode;
 use serde::Serialize;
 use sqlx::PgPool;
 use tower_http::cors::{Any, CorsLayer};
@@ -70,7 +145,7 @@ use tracing::{error, info, warn};
 use webauthn_rs::prelude::*;
 
 // UBL Kernel for cryptographic verification
-use ubl_kernel::verify as verify_signature;
+use ubl_kernel::hash_link;
 
 // ============================================================================
 // APPLICATION STATE
@@ -81,29 +156,54 @@ struct AppState {
     pool: PgPool,
     ledger: PgLedger,
     policy_registry: std::sync::Arc<policy_registry::PolicyRegistry>,
-    tail_tx: tokio::sync::broadcast::Sender<(String, String)>, // (container_id, sequence_str) - matches TailBus
+    evolution_registry: std::sync::Arc<evolution::EvolutionRegistry>,
+    /// V1..V8 run on every admitted link
+    membrane: std::sync::Arc<ubl_membrane::Membrane>,
+    tail_tx: tokio::sync::broadcast::Sender<sse::TailEntry>, // matches TailBus
     tail_bus: sse::TailBus, // New: simplified SSE bus
+    /// Cached per-container flags, read by policy evaluation
+    feature_flags: std::sync::Arc<feature_flags::FeatureFlags>,
 }
 
 // ============================================================================
 // TYPES
 // ============================================================================
 
-#[derive(Serialize)]
-struct HealthResponse {
-    status: &'static str,
-    version: &'static str,
-}
-
 #[derive(Serialize)]
 struct Decision {
     decision: &'static str,
+    /// Canonical Membrane error name (`RealityDrift`, ...) of a rejection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'static str>,
+    /// Catalog code of a rejection, as a commit would report it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl Decision {
+    fn of(result: ubl_membrane::Result<()>) -> Self {
+        use ubl_errors::HasErrorCode;
+        match result {
+            Ok(()) => Decision { decision: "Accept", error: None, code: None, reason: None },
+            Err(e) => Decision {
+                decision: "Reject",
+                error: Some(e.name()),
+                code: Some(e.error_code().as_str()),
+                reason: Some(e.to_string()),
+            },
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct CommitSuccess {
     ok: bool,
     entry: LedgerEntry,
+    /// Echo of `LinkDraft::tentative_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tentative_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -112,25 +212,26 @@ struct StateResponse {
     sequence: i64,
     last_hash: String,
     entry_count: i64,
+    /// Supply created by Entropy commits (decimal string: deltas are i128)
+    entropy_minted: String,
+    /// Supply destroyed by Entropy commits
+    entropy_burned: String,
 }
 
 // ============================================================================
 // HANDLERS
 // ============================================================================
 
-/// GET /health
-async fn route_health() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy",
-        version: "2.0.0+postgres",
-    })
-}
-
 /// GET /state/:container_id
 async fn route_state(
     State(state): State<AppState>,
     Path(container_id): Path<String>,
 ) -> Result<Json<StateResponse>, (StatusCode, String)> {
+    let entropy = projections::AnalyticsProjection::new(state.pool.clone())
+        .entropy_totals(&container_id)
+        .await
+        .unwrap_or_default();
+
     match state.ledger.get_state(&container_id).await {
         Ok(entry) => {
             // Get entry count
@@ -147,6 +248,8 @@ async fn route_state(
                 sequence: entry.sequence,
                 last_hash: entry.entry_hash,
                 entry_count: count,
+                entropy_minted: entropy.minted.to_string(),
+                entropy_burned: entropy.burned.to_string(),
             }))
         }
         Err(_) => {
@@ -156,36 +259,117 @@ async fn route_state(
                 sequence: 0,
                 last_hash: "0x00".to_string(),
                 entry_count: 0,
+                entropy_minted: "0".to_string(),
+                entropy_burned: "0".to_string(),
             }))
         }
     }
 }
 
 /// POST /link/validate
-/// Basic validation - in production, inject full Membrane here
+/// Pre-flight: the Membrane (V1..V8) against the current head, nothing
+/// written. A rejection is a 200 with the canonical error name; links that
+/// cannot be read as a commit get the error a commit would.
 async fn route_validate(
-    State(_state): State<AppState>,
-    Json(_link): Json<LinkDraft>,
-) -> Json<Decision> {
-    // TODO: Apply SPEC-UBL-MEMBRANE v1.0 §V1-V9 validations
-    // For now, simplified validation
-    Json(Decision {
-        decision: "Accept",
-    })
+    State(state): State<AppState>,
+    Json(link): Json<LinkDraft>,
+) -> Result<Json<Decision>, ApiError> {
+    let ledger = membrane_state(&state, &link.container_id).await?;
+    let decision = Decision::of(membrane_run(&state.membrane, &link, &ledger)?);
+    info!(
+        "🔎 VALIDATE seq={} container={} → {} {}",
+        link.expected_sequence,
+        link.container_id,
+        decision.decision,
+        decision.error.unwrap_or("")
+    );
+    Ok(Json(decision))
 }
 
+/// Header echoing `LinkDraft::tentative_id` on every commit response,
+/// including rejections, so the UI can roll back the matching optimistic item
+const TENTATIVE_ID_HEADER: &str = "x-ubl-tentative-id";
+/// Upper bound for client-supplied tentative ids
+const MAX_TENTATIVE_ID_LEN: usize = 128;
+
 /// POST /link/commit
 /// Atomic append with SERIALIZABLE transaction + ASC validation
 async fn route_commit(
     State(state): State<AppState>,
     headers: HeaderMap,
     Json(link): Json<LinkDraft>,
-) -> Result<Json<CommitSuccess>, (StatusCode, String)> {
+) -> Response {
+    let tentative_id = link.tentative_id.clone();
+    let mut response = authorize_and_commit(&state, &headers, link).await.into_response();
+    if let Some(value) = tentative_id.and_then(|t| HeaderValue::from_str(&t).ok()) {
+        response.headers_mut().insert(TENTATIVE_ID_HEADER, value);
+    }
+    response
+}
+
+async fn authorize_and_commit(
+    state: &AppState,
+    headers: &HeaderMap,
+    link: LinkDraft,
+) -> Result<Json<CommitSuccess>, ApiError> {
     info!(
         "📝 COMMIT seq={} container={} class={}",
         link.expected_sequence, link.container_id, link.intent_class
     );
 
+    let sid = authorize_link(state, headers, &link).await?;
+    commit_link(state, link, &sid).await.map(Json)
+}
+
+/// POST /link/dry-run
+/// Same authorization and admission as a commit, stopping before the append
+/// (see dry_run.rs)
+async fn route_dry_run(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(link): Json<LinkDraft>,
+) -> Result<Json<dry_run::DryRunResponse>, ApiError> {
+    info!(
+        "🧪 DRY RUN seq={} container={} class={}",
+        link.expected_sequence, link.container_id, link.intent_class
+    );
+
+    let sid = authorize_link(&state, &headers, &link).await?;
+    let admission = admit(&state, &link, &sid).await?;
+    let receipt = state.ledger.preview(&link).await.map_err(|e| tangency_error(&link, e))?;
+    let before = projections::AnalyticsProjection::new(state.pool.clone())
+        .balance(&link.container_id)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, format!("DatabaseError: {}", e)))?;
+
+    Ok(Json(dry_run::DryRunResponse {
+        ok: true,
+        receipt,
+        balance: dry_run::Balance::new(before, link.physics_delta.parse().unwrap_or(0)),
+        policy: admission.policy,
+        fsm: admission.fsm,
+        projections: dry_run::projection_deltas(&link.container_id, link.atom.as_ref()),
+    }))
+}
+
+/// Request checks and ASC authorization shared by commits and dry runs;
+/// returns the session id the link is authorized under
+async fn authorize_link(state: &AppState, headers: &HeaderMap, link: &LinkDraft) -> Result<String, ApiError> {
+    if link.tentative_id.as_ref().is_some_and(|t| t.is_empty() || t.len() > MAX_TENTATIVE_ID_LEN) {
+        return Err(ApiError::new(
+            ErrorCode::BadRequest,
+            format!("tentative_id must be 1-{} characters", MAX_TENTATIVE_ID_LEN),
+        ));
+    }
+
+    // Reject hostile atom shapes before any hashing or policy evaluation
+    if let Some(ref atom) = link.atom {
+        if let Err(e) = ubl_atom::check_limits(atom, &ubl_atom::Limits::DEFAULT) {
+            warn!("❌ Atom rejected: {}", e);
+            return Err(ApiError::from_coded(&e));
+        }
+    }
+
     // Diamond Checklist #5: ASC Validation - REQUIRED
     // Extract authorization header (required for all commits)
     let auth_header = headers.get("authorization")
@@ -224,10 +408,13 @@ async fn route_commit(
 
     info!("✅ ASC VALIDATED sid={} containers={:?}", sid, asc_context.containers);
 
-    // ========================================================================
-    // SIGNATURE VERIFICATION (SPEC-UBL-MEMBRANE v1.0 §V2)
-    // ========================================================================
-    // Build the canonical signing bytes (link without signature)
+    Ok(sid)
+}
+
+/// Canonical signing bytes of a link: the signed fields (no signature or
+/// atom) as sorted-key JSON. TS clients sign the same bytes; see
+/// `tests/fixtures/ts_commits`.
+fn link_signing_bytes(link: &LinkDraft) -> Result<Vec<u8>, ApiError> {
     let signing_data = serde_json::json!({
         "version": link.version,
         "container_id": link.container_id,
@@ -238,23 +425,193 @@ async fn route_commit(
         "physics_delta": link.physics_delta,
         "pact": link.pact,
     });
-    
-    // Canonicalize for verification (sorted keys, no whitespace)
-    let signing_bytes = match ubl_atom::canonicalize(&signing_data) {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            error!("❌ CANONICALIZATION FAILED: {}", e);
-            return Err((StatusCode::BAD_REQUEST, format!("CanonicalizeError: {}", e)));
-        }
+    ubl_atom::canonicalize(&signing_data).map_err(|e| {
+        error!("❌ CANONICALIZATION FAILED: {}", e);
+        ApiError::new(ErrorCode::InvalidAtom, format!("CanonicalizeError: {}", e))
+    })
+}
+
+/// The link as the Membrane sees it
+fn membrane_link(link: &LinkDraft) -> Result<ubl_link::LinkCommit, ApiError> {
+    use ubl_link::IntentClass;
+    let physics = |reason: String| ApiError::new(ErrorCode::PhysicsViolation, format!("PhysicsViolation: {}", reason));
+
+    let intent_class = match link.intent_class.as_str() {
+        "Observation" => IntentClass::Observation,
+        "Conservation" => IntentClass::Conservation,
+        "Entropy" => IntentClass::Entropy,
+        "Evolution" => IntentClass::Evolution,
+        other => return Err(physics(format!("unknown intent class {}", other))),
     };
-    
-    // Verify Ed25519 signature
-    if let Err(e) = verify_signature(&link.author_pubkey, &signing_bytes, &link.signature) {
-        error!("❌ SIGNATURE INVALID: author={} error={}", &link.author_pubkey[..16], e);
-        return Err((StatusCode::FORBIDDEN, "SignatureInvalid".to_string()));
+    let physics_delta = link
+        .physics_delta
+        .parse()
+        .map_err(|_| physics(format!("physics_delta {} is not an integer", link.physics_delta)))?;
+    let expected_sequence = u64::try_from(link.expected_sequence)
+        .map_err(|_| ApiError::new(ErrorCode::SequenceMismatch, "SequenceMismatch"))?;
+
+    Ok(ubl_link::LinkCommit {
+        version: link.version,
+        container_id: link.container_id.clone(),
+        expected_sequence,
+        previous_hash: link.previous_hash.clone(),
+        atom_hash: link.atom_hash.clone(),
+        intent_class,
+        physics_delta,
+        pact: link.pact.as_ref().map(|pact| ubl_link::PactProof {
+            pact_id: pact.pact_id.clone(),
+            signatures: pact.signatures.iter().map(|s| s.signature.clone()).collect(),
+        }),
+        author_pubkey: link.author_pubkey.clone(),
+        signature: link.signature.clone(),
+    })
+}
+
+/// Run the Membrane (SPEC-UBL-MEMBRANE v1.0 §6) over a link against
+/// `ledger`, recording each validator's time. The signature is checked over
+/// `link_signing_bytes`, the bytes clients sign.
+fn membrane_check(
+    membrane: &ubl_membrane::Membrane,
+    link: &LinkDraft,
+    ledger: &ubl_membrane::LedgerState,
+) -> Result<(), ApiError> {
+    membrane_run(membrane, link, ledger)?.map_err(|e| {
+        error!("❌ MEMBRANE REJECTED: author={} {}", &link.author_pubkey[..link.author_pubkey.len().min(16)], e);
+        ApiError::from(e)
+    })
+}
+
+/// The Membrane's verdict on a link; the outer error is for links it cannot
+/// be run on (not canonicalizable, unknown intent class, ...)
+fn membrane_run(
+    membrane: &ubl_membrane::Membrane,
+    link: &LinkDraft,
+    ledger: &ubl_membrane::LedgerState,
+) -> Result<ubl_membrane::Result<()>, ApiError> {
+    let signing_bytes = link_signing_bytes(link)?;
+    let commit = membrane_link(link)?;
+    let report = membrane.run(&commit, &signing_bytes, ledger);
+    for timing in &report.timings {
+        metrics::MEMBRANE_VALIDATOR_SECONDS
+            .with_label_values(&[timing.validator])
+            .observe(timing.elapsed.as_secs_f64());
     }
-    
-    info!("✅ SIGNATURE VERIFIED: author={}", &link.author_pubkey[..16]);
+    Ok(report.result)
+}
+
+/// What the Membrane checks a link against: the container head, and the
+/// balance from the container analytics (which may trail commits in flight).
+/// The append re-checks the head inside its own transaction.
+async fn membrane_state(state: &AppState, container_id: &str) -> Result<ubl_membrane::LedgerState, ApiError> {
+    let db_error = |e: sqlx::Error| ApiError::new(ErrorCode::DatabaseError, format!("DatabaseError: {}", e));
+    let (last_hash, next_sequence) = match state.ledger.get_state(container_id).await {
+        Ok(head) => (head.entry_hash, head.sequence as u64 + 1),
+        Err(sqlx::Error::RowNotFound) => (db::GENESIS_PREVIOUS_HASH.to_string(), 1),
+        Err(e) => return Err(db_error(e)),
+    };
+    let physical_balance = projections::AnalyticsProjection::new(state.pool.clone())
+        .balance(container_id)
+        .await
+        .map_err(db_error)?;
+    Ok(ubl_membrane::LedgerState {
+        container_id: container_id.to_string(),
+        last_hash,
+        next_sequence,
+        physical_balance,
+    })
+}
+
+/// Membrane checks, policy, pact, append and projection for an authorized link.
+///
+/// Shared by `POST /link/commit` and server-originated events (job monitor) so
+/// both take exactly the same path into the ledger. Every outcome, accepted or
+/// refused, is counted in the container analytics and the commit SLOs, except
+/// replays of a link that already landed and `CommitInFlight` (see `commit_claim`).
+async fn commit_link(state: &AppState, link: LinkDraft, actor: &str) -> Result<CommitSuccess, ApiError> {
+    let container_id = link.container_id.clone();
+    let author_pubkey = link.author_pubkey.clone();
+    let intent_class = link.intent_class.clone();
+    let physics_delta: i128 = link.physics_delta.parse().unwrap_or(0);
+
+    // One replica at a time per signed link; a link that already landed is
+    // answered with its entry (unsignable links are refused by admission)
+    let claim = match link_signing_bytes(&link) {
+        Ok(bytes) => match commit_claim::claim(&state.pool, &link, &hash_link(&bytes)).await? {
+            commit_claim::Claim::Held(claim) => Some(claim),
+            commit_claim::Claim::Committed(entry) => {
+                return Ok(CommitSuccess { ok: true, entry, tentative_id: link.tentative_id.clone() });
+            }
+        },
+        Err(_) => None,
+    };
+
+    let attempt = rejections::Attempt::capture(&link, actor);
+    let started = std::time::Instant::now();
+    let result = try_commit_link(state, link, actor).await;
+    if let Some(claim) = claim {
+        claim.release().await;
+    }
+    slo::record_commit(started.elapsed(), result.as_ref().is_err_and(|e| e.status.is_server_error()));
+    if let Err(e) = &result {
+        rejections::count(&container_id, e.code);
+        if let Some(attempt) = attempt {
+            attempt.refused(state.pool.clone(), e);
+        }
+    }
+
+    let outcome = result.as_ref().map(|success| (success.entry.sequence, success.entry.ts_unix_ms)).map_err(|e| e.code);
+    let analytics = projections::AnalyticsProjection::new(state.pool.clone());
+    let reputation = projections::ReputationProjection::new(state.pool.clone());
+    tokio::spawn(async move {
+        let (recorded, reputed) = match outcome {
+            Ok((sequence, ts_unix_ms)) => (
+                analytics
+                    .record_commit(&container_id, sequence, &author_pubkey, &intent_class, physics_delta, ts_unix_ms)
+                    .await,
+                reputation.record_accepted(&author_pubkey, ts_unix_ms).await,
+            ),
+            Err(code) => {
+                let now = timestamps::now_ms();
+                (
+                    analytics.record_rejection(&container_id, now).await,
+                    reputation.record_refusal(&author_pubkey, code, now).await,
+                )
+            }
+        };
+        if let Err(e) = recorded {
+            warn!("Failed to update container analytics for {}: {}", container_id, e);
+        }
+        if let Err(e) = reputed {
+            warn!("Failed to update actor reputation for {}: {}", &author_pubkey[..author_pubkey.len().min(16)], e);
+        }
+    });
+    result
+}
+
+/// What admission learned about a link it let through
+struct Admission {
+    policy: dry_run::PolicyTrace,
+    /// Job transition checked against the job FSM
+    fsm: Option<dry_run::FsmTransition>,
+}
+
+/// Every check a link passes before the append: read-only mode, the
+/// Membrane, Policy Pack, policy VM, pacts and evolution validation
+async fn admit(state: &AppState, link: &LinkDraft, actor: &str) -> Result<Admission, ApiError> {
+    // Read-only mode is toggled through a multi-admin action (admin_actions)
+    if admin_actions::is_read_only() {
+        warn!("🔒 Commit refused: ledger is read-only");
+        return Err(ApiError::new(ErrorCode::Forbidden, "LedgerReadOnly"));
+    }
+
+    // ========================================================================
+    // MEMBRANE (SPEC-UBL-MEMBRANE v1.0): version, signature, target, causality,
+    // sequence, physics, entropy and evolution pacts
+    // ========================================================================
+    let ledger = membrane_state(state, &link.container_id).await?;
+    membrane_check(&state.membrane, link, &ledger)?;
+
+    info!("✅ MEMBRANE PASSED: author={}", &link.author_pubkey[..16]);
 
     // POLICY EVALUATION (SPEC-UBL-POLICY v1.0)
     // Evaluate policy BEFORE pact validation
@@ -264,34 +621,42 @@ async fn route_commit(
         .map(|d| d.as_millis() as i64)
         .unwrap_or(0);
 
-    // Get actor from ASC or default
-    let actor = headers.get("authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|s| auth::extract_sid_from_header(s).ok())
-        .unwrap_or_else(|| "anonymous".to_string());
-
     // Apply Policy Pack v1 checks
+    let mut fsm = None;
     if let Some(ref atom) = link.atom {
         let policy_engine = policy::PolicyEngine::new(state.pool.clone());
         
         // Check for raw PII
         if let Err(e) = policy_engine.check_no_raw_pii(atom) {
             error!("❌ Policy violation: {}", e);
-            return Err((StatusCode::FORBIDDEN, format!("PolicyViolation: {}", e)));
+            return Err(ApiError::new(ErrorCode::PolicyViolation, format!("PolicyViolation: {}", e)));
         }
 
         // Check job FSM if this is a job state change
         if let Some(event_type) = atom.get("type").and_then(|t| t.as_str()) {
-            if event_type == "job.state_changed" {
-                if let (Some(from), Some(to), Some(job_id)) = (
-                    atom.get("from_state").and_then(|v| v.as_str()),
-                    atom.get("to_state").and_then(|v| v.as_str()),
-                    atom.get("job_id").and_then(|v| v.as_str()),
-                ) {
-                    if let Err(e) = policy_engine.validate_job_fsm(job_id, from, to).await {
+            if event_type == "job.state_changed" || event_type == "job.timeout" {
+                let event = ubl_events::JobEvent::from_atom(atom).map_err(|e| {
+                    error!("❌ Malformed {}: {}", event_type, e);
+                    ApiError::new(ErrorCode::PolicyViolation, format!("PolicyViolation: malformed {}: {}", event_type, e))
+                })?;
+                if let Some((from, to)) = event.transition() {
+                    if let Err(e) = policy_engine.validate_job_fsm(event.job_id(), from, to).await {
                         error!("❌ Policy violation: {}", e);
-                        return Err((StatusCode::FORBIDDEN, format!("PolicyViolation: {}", e)));
+                        return Err(ApiError::new(ErrorCode::PolicyViolation, format!("PolicyViolation: {}", e)));
                     }
+                    fsm = Some(dry_run::FsmTransition {
+                        job_id: event.job_id().to_string(),
+                        from: from.to_string(),
+                        to: to.to_string(),
+                    });
+                }
+            }
+
+            // Observation batches: Observation-only, bounded size, root must match
+            if event_type == observation_batch::BATCH_TYPE {
+                if let Err(e) = policy_engine.check_observation_batch(atom, &link.intent_class) {
+                    error!("❌ Policy violation: {}", e);
+                    return Err(ApiError::new(ErrorCode::PolicyViolation, format!("PolicyViolation: {}", e)));
                 }
             }
 
@@ -303,26 +668,46 @@ async fn route_commit(
                 {
                     if let Err(e) = policy_engine.validate_tool_pairing(tool_call_id, event_type).await {
                         error!("❌ Policy violation: {}", e);
-                        return Err((StatusCode::FORBIDDEN, format!("PolicyViolation: {}", e)));
+                        return Err(ApiError::new(ErrorCode::PolicyViolation, format!("PolicyViolation: {}", e)));
                     }
                 }
             }
         }
     }
 
-    // Evaluate policy via registry
-    let policy_decision = state.policy_registry.evaluate(
+    // Evaluate policy via registry; supply totals and the author's
+    // reputation are its state fields
+    let entropy = projections::AnalyticsProjection::new(state.pool.clone())
+        .entropy_totals(&link.container_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("⚠️  Entropy totals unavailable for {}: {}", link.container_id, e);
+            projections::EntropyTotals::default()
+        });
+    let reputation = projections::ReputationProjection::new(state.pool.clone())
+        .summary(&link.author_pubkey, current_time_ms)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("⚠️  Reputation unavailable for {}: {}", &link.author_pubkey[..16], e);
+            projections::ActorReputation::default()
+        });
+    let mut policy_state = entropy.policy_state();
+    if let (Some(fields), serde_json::Value::Object(actor_fields)) = (policy_state.as_object_mut(), reputation.policy_state()) {
+        fields.extend(actor_fields);
+    }
+    let evaluation = state.policy_registry.evaluate(
         &link.container_id,
-        &actor,
+        actor,
         link.atom.as_ref().unwrap_or(&serde_json::json!({})),
-        None,
+        Some(policy_state),
         current_time_ms,
+        state.feature_flags.for_policy(&link.container_id).await,
     ).await;
 
-    match &policy_decision {
+    match &evaluation.decision {
         Ok(ubl_policy_vm::TranslationDecision::Deny { reason }) => {
             error!("❌ POLICY DENIED: {}", reason);
-            return Err((StatusCode::FORBIDDEN, format!("PolicyDenied: {}", reason)));
+            return Err(ApiError::new(ErrorCode::PolicyDenied, format!("PolicyDenied: {}", reason)));
         }
         Ok(ubl_policy_vm::TranslationDecision::Allow { intent_class, required_pact, .. }) => {
             info!("✅ POLICY ALLOWED: intent_class={} pact={:?}", intent_class, required_pact);
@@ -330,7 +715,7 @@ async fn route_commit(
             // Check if policy requires a pact that wasn't provided
             if required_pact.is_some() && link.pact.is_none() {
                 error!("❌ POLICY REQUIRES PACT: {:?}", required_pact);
-                return Err((StatusCode::FORBIDDEN, format!("PolicyRequiresPact: {:?}", required_pact)));
+                return Err(ApiError::new(ErrorCode::PactViolation, format!("PolicyRequiresPact: {:?}", required_pact)));
             }
         }
         Err(e) => {
@@ -364,39 +749,93 @@ async fn route_commit(
                     current_time_ms,
                 ).await {
                     error!("❌ PACT VALIDATION FAILED: {}", e);
-                    return Err((StatusCode::FORBIDDEN, format!("PactViolation: {}", e)));
+                    return Err(ApiError::new(ErrorCode::PactViolation, format!("PactViolation: {}", e)));
                 }
             }
             None => {
                 error!("❌ PACT REQUIRED but not provided for {} with delta={}", link.intent_class, physics_delta);
-                return Err((StatusCode::FORBIDDEN, "PactRequired".to_string()));
+                return Err(ApiError::new(ErrorCode::PactViolation, "PactRequired"));
             }
         }
     }
 
+    // Evolution payloads must be well-formed and apply cleanly (see evolution.rs)
+    if link.intent_class == "Evolution" {
+        if let Err(e) = state.evolution_registry.validate(&link.container_id, link.atom.as_ref()) {
+            error!("❌ EVOLUTION REJECTED: {}", e);
+            return Err(ApiError::new(ErrorCode::InvalidEvolution, format!("InvalidEvolution: {}", e)));
+        }
+    }
+
+    Ok(Admission { policy: dry_run::PolicyTrace::of(&evaluation), fsm })
+}
+
+async fn try_commit_link(state: &AppState, link: LinkDraft, actor: &str) -> Result<CommitSuccess, ApiError> {
+    admit(state, &link, actor).await?;
+
     match state.ledger.append(&link).await {
         Ok(entry) => {
             info!("✅ ACCEPTED seq={} hash={}", entry.sequence, &entry.entry_hash[..8]);
             
             // Broadcast SSE event via TailBus (Postgres NOTIFY will also trigger via trigger)
-            state.tail_bus.notify(link.container_id.clone(), entry.sequence);
+            if let Some(atom) = link.atom.as_ref().filter(|a| a["type"] == feature_flags::FLAGS_TYPE) {
+                state.feature_flags.observe_commit(&link.container_id, atom, &entry.entry_hash);
+            }
+            state.tail_bus.notify(sse::TailEntry::committed(&link, &entry));
             
             // Process projections if atom data was provided
+            let mut projecting = false;
             if let Some(atom_data) = link.atom.clone() {
                 if let Some(event_type) = atom_data.get("type").and_then(|t| t.as_str()).map(|s| s.to_string()) {
                     let pool = state.pool.clone();
                     let container_id = link.container_id.clone();
-                    let atom = atom_data.clone();
+                    let mut atom = atom_data.clone();
+                    // Projection rows carry the client's tentative id (never the ledger atom)
+                    if let (Some(tentative_id), Some(obj)) = (&link.tentative_id, atom.as_object_mut()) {
+                        obj.insert("tentative_id".to_string(), serde_json::json!(tentative_id));
+                    }
                     let entry_hash = entry.entry_hash.clone();
                     let sequence = entry.sequence;
+                    let hlc = entry.hlc;
                     
                     // Process projection in background (non-blocking)
+                    projecting = true;
                     tokio::spawn(async move {
                         // Fix #5: Extract tenant_id from atom, falling back to "default" for migration
                         let tenant_id = atom.get("tenant_id")
                             .and_then(|v| v.as_str())
                             .unwrap_or("default");
                         let event_type = event_type.as_str();
+
+                        // Batches unpack into one row per observation, whatever the container
+                        if event_type == observation_batch::BATCH_TYPE {
+                            let projection = projections::ObservationsProjection::new(pool.clone());
+                            if let Err(e) = projection.process_event(&container_id, event_type, &atom, &entry_hash, sequence).await {
+                                error!("Failed to update observations projection: {}", e);
+                            }
+                        }
+                        
+                        // Evolutions feed the changelog of the container they change, whatever the container
+                        if event_type.starts_with(projections::EVOLUTION_PREFIX) {
+                            let changelog = projections::ChangelogProjection::new(pool.clone());
+                            if let Err(e) = changelog.process_event(&container_id, event_type, &atom, &entry_hash, sequence).await {
+                                error!("Failed to update evolution changelog: {}", e);
+                            }
+                        }
+
+                        // Artifact cards show in their conversation and job drawer, whatever the container
+                        if event_type == projections::ARTIFACT_CARD_TYPE {
+                            let timeline = projections::TimelineProjection::new(pool.clone());
+                            if let Err(e) = timeline.add_artifact_card(tenant_id, &atom, sequence, hlc).await {
+                                error!("Failed to add artifact card to timeline: {}", e);
+                            }
+                            if container_id != "C.Jobs" {
+                                let job_events = projections::JobEventsProjection::new(pool.clone());
+                                if let Err(e) = job_events.process_event(event_type, &atom, &entry_hash, sequence, hlc, tenant_id).await {
+                                    error!("Failed to update job events projection: {}", e);
+                                }
+                            }
+                        }
                         
                         if container_id == "C.Jobs" {
                             // Update main jobs projection
@@ -407,7 +846,7 @@ async fn route_commit(
                             
                             // Update new projection tables
                             let job_events = projections::JobEventsProjection::new(pool.clone());
-                            if let Err(e) = job_events.process_event(event_type, &atom, &entry_hash, sequence, tenant_id).await {
+                            if let Err(e) = job_events.process_event(event_type, &atom, &entry_hash, sequence, hlc, tenant_id).await {
                                 error!("Failed to update job events projection: {}", e);
                             }
                             
@@ -420,7 +859,7 @@ async fn route_commit(
                             }
                             
                             // Update presence based on job state changes
-                            if event_type == "job.state_changed" || event_type == "job.started" || event_type == "job.completed" {
+                            if matches!(event_type, "job.state_changed" | "job.started" | "job.completed" | "job.timeout") {
                                 let presence = projections::PresenceProjection::new(pool.clone());
                                 let job_id = atom.get("job_id").or_else(|| atom.get("id")).and_then(|v| v.as_str());
                                 let owner = atom.get("owner_entity_id").or_else(|| atom.get("assigned_to")).and_then(|v| v.as_str());
@@ -455,7 +894,7 @@ async fn route_commit(
                             if !conversation_id.is_empty() {
                                 let item_type = if event_type == "message.created" { "message" } else { "system" };
                                 let item_data = atom.clone();
-                                if let Err(e) = timeline.add_item(tenant_id, conversation_id, item_type, &item_data, sequence).await {
+                                if let Err(e) = timeline.add_item(tenant_id, conversation_id, item_type, &item_data, sequence, hlc).await {
                                     error!("Failed to update timeline: {}", e);
                                 }
                             }
@@ -466,48 +905,76 @@ async fn route_commit(
                                 let _ = presence.update_activity(tenant_id, from, &entry_hash).await;
                             }
                         } else if container_id == "C.Office" {
-                            let projection = projections::OfficeProjection::new(pool);
+                            let projection = projections::OfficeProjection::new(pool.clone());
                             if let Err(e) = projection.process_event(event_type, &atom, &entry_hash, sequence).await {
                                 error!("Failed to update office projection: {}", e);
                             }
+                        } else if container_id == projections::AUDIT_CONTAINER {
+                            let projection = projections::AnnotationsProjection::new(pool.clone());
+                            if let Err(e) = projection.process_event(event_type, &atom, &entry_hash, sequence).await {
+                                error!("Failed to update audit projection: {}", e);
+                            }
                         }
+
+                        health::record_projected(pool, container_id, sequence, entry_hash).await;
                     });
                 }
             }
+            if !projecting {
+                // Nothing to project: the projections are still caught up
+                tokio::spawn(health::record_projected(
+                    state.pool.clone(),
+                    link.container_id.clone(),
+                    entry.sequence,
+                    entry.entry_hash.clone(),
+                ));
+            }
             
-            Ok(Json(CommitSuccess {
+            Ok(CommitSuccess {
                 ok: true,
                 entry,
-            }))
+                tentative_id: link.tentative_id.clone(),
+            })
         }
-        Err(TangencyError::RealityDrift) => {
+        Err(e) => Err(tangency_error(&link, e)),
+    }
+}
+
+/// API error for a tangency refusal of the ledger
+fn tangency_error(link: &LinkDraft, e: TangencyError) -> ApiError {
+    match e {
+        TangencyError::RealityDrift => {
             error!("❌ REJECTED: RealityDrift");
-            Err((StatusCode::CONFLICT, "RealityDrift".into()))
+            ApiError::new(ErrorCode::RealityDrift, "RealityDrift")
         }
-        Err(TangencyError::SequenceMismatch) => {
+        TangencyError::SequenceMismatch => {
             error!("❌ REJECTED: SequenceMismatch");
-            Err((StatusCode::CONFLICT, "SequenceMismatch".into()))
+            ApiError::new(ErrorCode::SequenceMismatch, "SequenceMismatch")
         }
-        Err(TangencyError::InvalidVersion) => {
+        TangencyError::InvalidVersion => {
             error!("❌ REJECTED: InvalidVersion");
-            Err((StatusCode::BAD_REQUEST, "InvalidVersion".into()))
+            ApiError::new(ErrorCode::InvalidVersion, "InvalidVersion")
         }
-        Err(TangencyError::InvalidTarget) => {
+        TangencyError::InvalidTarget => {
             error!("❌ REJECTED: InvalidTarget");
-            Err((StatusCode::BAD_REQUEST, "InvalidTarget".into()))
+            ApiError::new(ErrorCode::InvalidTarget, "InvalidTarget")
         }
-        Err(TangencyError::PactViolation(reason)) => {
+        TangencyError::PactViolation(reason) => {
             error!("❌ REJECTED: PactViolation - {}", reason);
-            Err((StatusCode::FORBIDDEN, format!("PactViolation: {}", reason)))
+            ApiError::new(ErrorCode::PactViolation, format!("PactViolation: {}", reason))
         }
         // Fix #12: Handle serialization conflicts (should have been retried)
-        Err(TangencyError::SerializationConflict) => {
+        TangencyError::SerializationConflict => {
             error!("❌ REJECTED: SerializationConflict after retries");
-            Err((StatusCode::CONFLICT, "SerializationConflict: please retry".into()))
+            ApiError::new(ErrorCode::SerializationConflict, "SerializationConflict: please retry")
+        }
+        TangencyError::Quarantined => {
+            error!("❌ REJECTED: {} is quarantined", link.container_id);
+            ApiError::new(ErrorCode::Forbidden, "ContainerQuarantined: chain self-check failed")
         }
-        Err(TangencyError::DatabaseError(reason)) => {
+        TangencyError::DatabaseError(reason) => {
             error!("❌ REJECTED: DatabaseError - {}", reason);
-            Err((StatusCode::INTERNAL_SERVER_ERROR, format!("DatabaseError: {}", reason)))
+            ApiError::new(ErrorCode::DatabaseError, format!("DatabaseError: {}", reason))
         }
     }
 }
@@ -516,10 +983,14 @@ async fn route_commit(
 
 /// GET /atom/:hash
 /// Fetch atom data by hash (PHASE 3B)
+///
+/// Atoms of encrypted containers are opened for callers with a valid
+/// session; anyone else gets the sealed envelope (`encrypted: true`).
 async fn route_atom(
     State(state): State<AppState>,
     Path(atom_hash): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, ApiError> {
     #[derive(sqlx::FromRow)]
     struct AtomRow {
         atom_data: serde_json::Value,
@@ -533,24 +1004,39 @@ async fn route_atom(
     .bind(&atom_hash)
     .fetch_optional(&state.pool)
     .await
-    .map_err(|e: sqlx::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(|e: sqlx::Error| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
 
     match result {
-        Some(row) => {
+        Some(mut row) => {
+            let mut encrypted = atom_crypto::is_sealed(&row.atom_data);
+            if encrypted && has_valid_session(&state.pool, &headers).await {
+                row.atom_data = atom_crypto::open_atom(&state.pool, &row.container_id, &atom_hash, &row.atom_data)
+                    .await
+                    .map_err(|e| ApiError::new(ErrorCode::Internal, format!("Atom decryption failed: {}", e)))?;
+                encrypted = false;
+            }
             let response = serde_json::json!({
                 "atom_hash": atom_hash,
                 "container_id": row.container_id,
                 "atom_data": row.atom_data,
-                "ts_unix_ms": row.ts_unix_ms
+                "ts_unix_ms": row.ts_unix_ms,
+                "encrypted": encrypted
             });
             Ok(Json(response))
         }
         None => {
-            Err((StatusCode::NOT_FOUND, format!("Atom not found: {}", atom_hash)))
+            Err(ApiError::new(ErrorCode::NotFound, format!("Atom not found: {}", atom_hash)))
         }
     }
 }
 
+async fn has_valid_session(pool: &PgPool, headers: &HeaderMap) -> bool {
+    match id_routes::extract_session_token(headers) {
+        Some(token) => matches!(auth::session_db::get_valid(pool, &token).await, Ok(Some(_))),
+        None => false,
+    }
+}
+
 // ============================================================================
 // MAIN
 // ============================================================================
@@ -560,12 +1046,30 @@ async fn main() -> anyhow::Result<()> {
     // Load .env
     dotenvy::dotenv().ok();
 
+    // Validate configuration before touching anything else: a bad value is fatal
+    let check_only = std::env::args().any(|a| a == "--check-config");
+    let config = match config::ServerConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ Invalid configuration: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let bootstrap = match config.bootstrap_file.as_deref().map(bootstrap::BootstrapManifest::load).transpose() {
+        Ok(bootstrap) => bootstrap,
+        Err(e) => {
+            eprintln!("❌ Invalid bootstrap manifest: {}", e);
+            std::process::exit(2);
+        }
+    };
+    if check_only {
+        println!("{}", config.redacted());
+        println!("✅ Configuration OK");
+        return Ok(());
+    }
+
     // Initialize OpenTelemetry tracing
-    let otlp_endpoint = std::env::var("OTLP_ENDPOINT")
-        .ok()
-        .or_else(|| std::env::var("JAEGER_ENDPOINT").ok());
-    
-    if let Some(endpoint) = otlp_endpoint.as_deref() {
+    if let Some(endpoint) = config.otlp_endpoint.as_deref() {
         if let Err(e) = otel_tracing::init_tracing("ubl-server", "2.0.0", Some(endpoint)) {
             warn!("Failed to initialize OpenTelemetry tracing: {}. Falling back to basic tracing.", e);
             tracing_subscriber::fmt()
@@ -588,10 +1092,82 @@ async fn main() -> anyhow::Result<()> {
         info!("📝 Basic tracing initialized (OpenTelemetry disabled - set OTLP_ENDPOINT to enable)");
     }
 
+    info!("⚙️  Effective configuration:\n{}", config.redacted());
+
     // Initialize KeyStore (Gemini P0 #1)
     keystore::init();
     info!("🔑 KeyStore initialized");
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|a| a == "--rotate-key") {
+        let key_id = args.get(i + 1).map(String::as_str).unwrap_or("admin");
+        let pubkey = keystore::rotate(key_id).map_err(anyhow::Error::msg)?;
+        println!("🔄 Rotated '{}': new public key {}", key_id, pubkey);
+        return Ok(());
+    }
+    // Key ceremony: escrow shares go to stdout / come from stdin, one per line
+    let flag_value = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1));
+    if let Some(key_id) = flag_value("--split-key") {
+        let count = |flag: &str, default: u8| {
+            flag_value(flag).map_or(Ok(default), |v| v.parse::<u8>().map_err(|_| anyhow::anyhow!("{} expects 1-255", flag)))
+        };
+        let shares = keystore::split(key_id, count("--threshold", 3)?, count("--shares", 5)?).map_err(anyhow::Error::msg)?;
+        for share in shares {
+            println!("{}", share);
+        }
+        return Ok(());
+    }
+    if let Some(key_id) = flag_value("--restore-key") {
+        // Anything that is not a share (e.g. pasted log output) is skipped
+        let shares: Vec<String> = std::io::stdin()
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|line| line.trim_start().starts_with("ubl-share:"))
+            .collect();
+        let pubkey = keystore::restore(key_id, &shares).map_err(anyhow::Error::msg)?;
+        println!("🔑 Restored '{}': public key {}", key_id, pubkey);
+        return Ok(());
+    }
+    if args.iter().any(|a| a == "--rotate-authority") {
+        let pool = PgPool::connect(&config.database_url).await?;
+        let report = key_ceremony::rotate_authority(&pool).await.map_err(anyhow::Error::msg)?;
+        println!(
+            "🔄 Authority key rotated: {} → {}\n   {} ASC(s) re-signed, key.transition recorded at {}",
+            report.previous_pubkey, report.pubkey, report.resigned_ascs, report.entry_hash
+        );
+        return Ok(());
+    }
+    let rotate_atom_master = args.iter().any(|a| a == "--rotate-atom-master");
+    if rotate_atom_master || args.iter().any(|a| a == "--rewrap-atom-keys") {
+        let pool = PgPool::connect(&config.database_url).await?;
+        let report = if rotate_atom_master {
+            atom_crypto::rotate_master_key(&pool).await
+        } else {
+            atom_crypto::rewrap_data_keys(&pool).await
+        }
+        .map_err(anyhow::Error::new)?;
+        println!(
+            "🔄 Atom master key {}: re-wrapped {} data key(s), ciphertexts untouched",
+            report.master_key_id, report.rewrapped
+        );
+        return Ok(());
+    }
+
+    // SIGHUP drops cached keys so out-of-band rotations are picked up
+    #[cfg(unix)]
+    tokio::spawn(async {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut hup) = signal(SignalKind::hangup()) {
+            while hup.recv().await.is_some() {
+                keystore::reload();
+            }
+        }
+    });
     
+    // Service key for UBL ⇄ Office requests (and mTLS material, if configured)
+    service_auth::init(&config)?;
+
     // Load or create admin key (used for signing permits)
     let _admin_pubkey = keystore::get_public_key_hex("admin");
     info!("🔐 Admin public key: {}", _admin_pubkey);
@@ -601,15 +1177,16 @@ async fn main() -> anyhow::Result<()> {
     info!("📸 Snapshots initialized");
 
     // Connect to PostgreSQL
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "postgres://ubl_dev@localhost:5432/ubl_dev".to_string());
+    let database_url = config.database_url.clone();
 
     info!("🔌 Connecting to PostgreSQL...");
     let pool = PgPool::connect(&database_url).await?;
     info!("✅ PostgreSQL connected");
 
     // Initialize policy registry
-    let policy_registry = std::sync::Arc::new(policy_registry::PolicyRegistry::with_pool(pool.clone()));
+    let policy_registry = std::sync::Arc::new(
+        policy_registry::PolicyRegistry::with_pool(pool.clone()).with_gas_alert(config.policy_gas_alert),
+    );
     policy_registry.init_defaults().await;
     
     // Try to load policies from database
@@ -618,44 +1195,122 @@ async fn main() -> anyhow::Result<()> {
     }
     info!("📋 Policy engine initialized");
 
-    // Diamond Checklist #8: Start Job Monitor for orphaned jobs
-    let job_monitor = job_monitor::JobMonitor::new(
-        pool.clone(),
-        job_monitor::JobMonitorConfig::default()
-    );
-    tokio::spawn(async move {
-        job_monitor.run().await;
-    });
-    info!("🔍 Job Monitor started (checks for orphaned jobs every 60s)");
+    // Re-verify recent history before serving; failed containers refuse writes
+    match chain_check::run(&pool, config.chain_check_depth).await {
+        Ok(0) => {}
+        Ok(n) => error!("🚨 {} container(s) failed the chain self-check and are read-only", n),
+        Err(e) => warn!("⚠️  Chain self-check could not run: {}", e),
+    }
+
+    match admin_actions::load_read_only(&pool).await {
+        Ok(true) => warn!("🔒 Ledger is in read-only mode (set by admin action)"),
+        Ok(false) => {}
+        Err(e) => warn!("⚠️  Failed to load read-only mode: {}", e),
+    }
+
+    match db::PgLedger::new(pool.clone()).load_dedup_savings().await {
+        Ok(saved) => info!("🧬 Atom deduplication has saved {} bytes", saved),
+        Err(e) => warn!("⚠️  Failed to load atom deduplication savings: {}", e),
+    }
 
-    // Create TailBus for SSE (simplified - only cid:seq)
+    // Create TailBus for SSE (typed entry.v1 envelopes; ?format=legacy for cid:seq)
     let tail_bus = sse::TailBus::new();
     
-    // Postgres LISTEN/NOTIFY integration
-    // Note: sqlx doesn't have built-in async LISTEN/NOTIFY
-    // The trigger will send NOTIFY, but we'll also notify via TailBus directly in route_commit
-    // For full async LISTEN support, consider using tokio-postgres or pg_listen crate
-    info!("📡 PostgreSQL NOTIFY trigger 'ubl_tail' will be used (trigger created via migration)");
+    // Postgres LISTEN/NOTIFY: the 'ubl_tail' trigger (migration) notifies on every
+    // append. SSE is fed directly from route_commit via TailBus; the listener only
+    // tracks NOTIFY delivery for /health?verbose=1.
+    tokio::spawn(health::listen_tail(pool.clone()));
 
     // Keep tail_tx for AppState compatibility, but also use TailBus
     let state = AppState {
         ledger: PgLedger::new(pool.clone()),
         pool: pool.clone(),
         policy_registry,
+        evolution_registry: std::sync::Arc::new(evolution::EvolutionRegistry::with_defaults()),
+        membrane: std::sync::Arc::new(ubl_membrane::Membrane::standard()),
         tail_tx: tail_bus.clone().tx.clone(),
         tail_bus: tail_bus.clone(),
+        feature_flags: std::sync::Arc::new(feature_flags::FeatureFlags::new(
+            pool.clone(),
+            feature_flags::FeatureFlagsConfig::from_env(),
+        )),
     };
 
-    // Initialize WebAuthn
-    let rp_id = std::env::var("WEBAUTHN_RP_ID")
-        .unwrap_or_else(|_| "localhost".to_string());
-    let rp_origin = std::env::var("WEBAUTHN_ORIGIN")
-        .unwrap_or_else(|_| "http://localhost:8080".to_string());
-    
-    info!("🔐 WebAuthn: rpId={}, origin={}", rp_id, rp_origin);
+    // Declarative bootstrap: containers, policies, pacts and the admin
+    // identity, each applied only if missing
+    if let Some((manifest, manifest_hash)) = &bootstrap {
+        bootstrap::apply(&state, manifest, manifest_hash).await;
+    }
+
+    // Diamond Checklist #8: Start Job Monitor for orphaned jobs
+    // (commits job.timeout through commit_link, so it needs the full AppState)
+    let job_monitor = job_monitor::JobMonitor::new(
+        state.clone(),
+        config.office_url.as_str().trim_end_matches('/').to_string(),
+        job_monitor::JobMonitorConfig::from_env(),
+    );
+    tokio::spawn(async move {
+        job_monitor.run().await;
+    });
+
+    let asc_expiry = asc_expiry::AscExpiryMonitor::new(
+        pool.clone(),
+        config.office_url.as_str().trim_end_matches('/').to_string(),
+        asc_expiry::AscExpiryConfig::from_env(),
+    );
+    tokio::spawn(async move {
+        asc_expiry.run().await;
+    });
+
+    // Send-later: commits scheduled messages to C.Messenger when due
+    let scheduled_sender = messenger_gateway::scheduled::ScheduledSender::new(
+        pool.clone(),
+        config.office_url.as_str().trim_end_matches('/').to_string(),
+        messenger_gateway::scheduled::ScheduledConfig::from_env(),
+    );
+    tokio::spawn(async move {
+        scheduled_sender.run().await;
+    });
+
+    // Pinned summaries of long conversations, written by Office
+    let summarizer = messenger_gateway::summarizer::TranscriptSummarizer::new(
+        pool.clone(),
+        config.office_url.as_str().trim_end_matches('/').to_string(),
+        messenger_gateway::summarizer::SummaryConfig::from_env(),
+    );
+    tokio::spawn(async move {
+        summarizer.run().await;
+    });
+
+    // Tenant exports keep their archive key in memory only
+    tenant::export::fail_interrupted(&pool).await;
+
+    // Projection history for ?as_of= reads, on the tables in UBL_PROJECTION_HISTORY
+    if let Err(e) = projections::history::install(&pool).await {
+        warn!("⚠️ Projection history not installed: {}", e);
+    }
+
+    // Commit SLOs: burn rates on /metrics, alert changes to UBL_SLO_WEBHOOK_URL
+    tokio::spawn(slo::SloMonitor::new(slo::SloConfig::from_env()).run());
+
+    // Refused commits kept for debugging, when UBL_REJECTION_AUDIT is on
+    if let Some(purger) = rejections::RejectionPurger::new(pool.clone(), rejections::config().clone()) {
+        tokio::spawn(purger.run());
+    }
+
+    // Public read gateway: read-only ledger views on their own listener, when UBL_PUBLIC_READ_ADDR is set
+    tokio::spawn(public_read::gateway::serve(pool.clone(), public_read::gateway::PublicReadConfig::from_env()));
+
+    // Analytics mirror: selected containers copied to a read-only BI database
+    if let Some(mirror) = mirror::MirrorWorker::new(pool.clone(), mirror::MirrorConfig::from_env()) {
+        tokio::spawn(mirror.run());
+    }
+
+    // Initialize WebAuthn (origin and RP ID already validated by config)
+    let rp_id = config.webauthn_rp_id.clone();
+    let rp_origin_url = config.webauthn_origin.clone();
     
-    let rp_origin_url = Url::parse(&rp_origin)
-        .expect("Invalid WEBAUTHN_ORIGIN URL");
+    info!("🔐 WebAuthn: rpId={}, origin={}", rp_id, rp_origin_url);
     
     let webauthn = WebauthnBuilder::new(&rp_id, &rp_origin_url)
         .expect("Failed to create WebAuthn builder")
@@ -670,8 +1325,20 @@ async fn main() -> anyhow::Result<()> {
         pool: pool.clone(),
         webauthn,
         rate_limiter: rate_limit::RateLimiter::new(),
+        anomaly: id_anomaly::AnomalyDetector::new(
+            id_anomaly::AnomalyConfig::default(),
+            std::sync::Arc::new(id_anomaly::DefaultScoring),
+        ),
     };
 
+    // Idle entries of in-memory structures evicted on a timer; sizes on /metrics
+    let maintenance = std::sync::Arc::new(maintenance::Maintenance::new(maintenance::MaintenanceConfig::from_env()));
+    maintenance.register("rate_limiter", std::time::Duration::from_secs(6 * 3600), std::sync::Arc::new(id_state.rate_limiter.clone()));
+    maintenance.register("id_anomaly", std::time::Duration::from_secs(3600), std::sync::Arc::new(id_state.anomaly.clone()));
+    maintenance.register("sse_reorder", std::time::Duration::from_secs(3600), std::sync::Arc::new(tail_bus.clone()));
+    maintenance.register("feature_flags", std::time::Duration::from_secs(600), state.feature_flags.clone());
+    tokio::spawn(maintenance.clone().run());
+
     // Projection state
     let projection_state = projections::ProjectionState {
         pool: pool.clone(),
@@ -685,20 +1352,34 @@ async fn main() -> anyhow::Result<()> {
 
     // Build router
     let app = Router::new()
-        .route("/health", get(route_health))
+        .route("/health", get(health::route_health))
         .route("/state/:container_id", get(route_state))
-        .route("/link/validate", post(route_validate))
-        .route("/link/commit", post(route_commit))
+        .route("/link/validate", post(route_validate).layer(DefaultBodyLimit::max(config.max_link_body_bytes)))
+        .route("/link/commit", post(route_commit).layer(DefaultBodyLimit::max(config.max_link_body_bytes)))
+        .route("/link/dry-run", post(route_dry_run).layer(DefaultBodyLimit::max(config.max_link_body_bytes)))
         .route("/atom/:hash", get(route_atom))
         .with_state(state.clone())
         .merge(metrics::metrics_router())
-        .merge(sse::sse_router(tail_bus.clone())) // SSE simplified (only cid:seq)
-        .merge(id_routes::id_router().with_state(id_state))
+        .merge(sse::sse_router(tail_bus.clone(), pool.clone())) // SSE tail (entry.v1, legacy cid:seq on request)
+        .merge(id_routes::id_router().with_state(id_state.clone()))
+        .merge(asc_requests::routes(pool.clone()))
         .merge(id_session_token::router().with_state(state.clone()))
         .merge(repo_routes::router().with_state(state.clone()))
-        .nest("/query", projections::projection_router().with_state(projection_state))
+        .merge(ledger_routes::router().with_state(state.clone()))
+        .nest("/query", projections::projection_router(projection_state))
         // Console v1.1 (ADR-001) — with step-up WebAuthn
-        .merge(console_v1::routes(pool.clone(), webauthn_for_console))
+        .merge(console_v1::routes(pool.clone(), webauthn_for_console, state.policy_registry.clone()))
+        .merge(job_templates::routes(pool.clone()))
+        .merge(container_manifest::routes(pool.clone(), state.policy_registry.clone()))
+        .merge(feature_flags::routes(state.feature_flags.clone(), tail_bus.clone()))
+        .merge(runners::routes(pool.clone()))
+        .merge(exec_logs::routes(pool.clone()))
+        .merge(dead_letters::routes(pool.clone(), id_state.clone()))
+        .merge(admin_actions::routes(pool.clone(), id_state.clone(), state.policy_registry.clone()))
+        .merge(policy_routes::routes(pool.clone(), id_state.clone(), state.policy_registry.clone()))
+        .merge(rejections::routes(pool.clone(), id_state.clone()))
+        .merge(maintenance::routes(maintenance.clone(), id_state.clone()))
+        .merge(public_read::routes(pool.clone(), id_state))
         // Registry v1.1 (ADR-002)
         .merge(registry_v1::routes(pool.clone()))
         // Messenger v1 (C.Messenger boundary)
@@ -706,14 +1387,22 @@ async fn main() -> anyhow::Result<()> {
         // Messenger Gateway v1
         .merge(messenger_gateway::routes(
             pool.clone(),
-            std::env::var("OFFICE_URL").unwrap_or_else(|_| "http://localhost:8081".to_string())
+            config.office_url.as_str().trim_end_matches('/').to_string(),
+            tail_bus.clone(),
+            &maintenance,
         ))
         // Tenant Management (C.Tenant)
         .merge(tenant::tenant_routes().with_state(pool.clone()))
+        .merge(tenant::export::routes(state.clone()))
+        // Deny-by-default: every route above must be declared in route_auth::ROUTES
+        .layer(axum::middleware::from_fn_with_state(pool.clone(), route_auth::deny_by_default))
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
+        .layer(axum::middleware::map_response(contracts::stamp_api_version))
+        .layer(axum::middleware::from_fn(metrics::count_errors))
         .layer(cors);
 
     // Prompt 3: Unix Socket support - REQUIRED for security
-    if let Ok(unix_path) = std::env::var("UBL_LISTEN_UNIX") {
+    if let config::Listen::Unix { path: unix_path } = &config.listen {
         use std::path::Path;
         use std::fs;
         use hyper::server::conn::http1::Builder as Http1Builder;
@@ -721,7 +1410,7 @@ async fn main() -> anyhow::Result<()> {
         use tokio::net::UnixListener;
         use tower_service::Service;
         
-        let p = Path::new(&unix_path);
+        let p = Path::new(unix_path);
         if let Some(dir) = p.parent() {
             fs::create_dir_all(dir)?;
         }
@@ -729,10 +1418,10 @@ async fn main() -> anyhow::Result<()> {
 
         info!("🚀 UBL Server v2.1 — ADR-001 + ADR-002 Compliant");
         info!("   Listening: unix://{}", unix_path);
-        info!("   Database: {}", database_url.split('@').last().unwrap_or("postgres"));
+        info!("   Database: {}", config::redact_url(&database_url));
         info!("   Console v1.1: /v1/policy/permit, /v1/commands/issue, /v1/exec.finish");
         info!("   Registry v1.1: /v1/query/registry/*");
-        info!("   Projections: /query/jobs, /query/conversations/:id/messages, /query/office/*");
+        info!("   Projections: /query/jobs, /query/conversations/:id/messages, /query/office/*, /query/observations");
         info!("   Runner pulls from: GET /v1/query/commands?pending=1");
 
         let listener = UnixListener::bind(p)?;
@@ -755,16 +1444,15 @@ async fn main() -> anyhow::Result<()> {
                 }
             });
         }
-    } else {
-        let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
+    } else if let config::Listen::Tcp { port } = config.listen {
         let addr = format!("0.0.0.0:{}", port);
 
         info!("🚀 UBL Server v2.1 — ADR-001 + ADR-002 Compliant");
         info!("   Listening: http://{}", addr);
-        info!("   Database: {}", database_url.split('@').last().unwrap_or("postgres"));
+        info!("   Database: {}", config::redact_url(&database_url));
         info!("   Console v1.1: /v1/policy/permit, /v1/commands/issue, /v1/exec.finish");
         info!("   Registry v1.1: /v1/query/registry/*");
-        info!("   Projections: /query/jobs, /query/conversations/:id/messages, /query/office/*");
+        info!("   Projections: /query/jobs, /query/conversations/:id/messages, /query/office/*, /query/observations");
         info!("   Runner pulls from: GET /v1/query/commands?pending=1");
 
         let listener = tokio::net::TcpListener::bind(&addr).await?;
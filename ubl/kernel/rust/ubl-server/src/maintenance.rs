@@ -0,0 +1,269 @@
+//! Maintenance of in-memory structures
+//!
+//! Several structures live only in memory and are keyed by whatever clients
+//! send: the WebAuthn rate limiter's buckets and lockouts, the identity
+//! anomaly windows, the gateway's send counters and the SSE tail's reorder
+//! buffer. Most prune themselves on use, which leaves the keys of clients
+//! that never come back. The [`Maintenance`] registry evicts entries idle
+//! past a per-structure TTL every `UBL_MAINTENANCE_INTERVAL_SECS` (default
+//! 60) and publishes sizes as `ubl_memory_entries{structure}`.
+//!
+//! TTLs are set with `UBL_MAINTENANCE_TTL_<STRUCTURE>_SECS` (e.g.
+//! `UBL_MAINTENANCE_TTL_RATE_LIMITER_SECS`). A structure never evicts inside
+//! the window it needs to decide (anomaly window, send rate minute), whatever
+//! its TTL.
+//!
+//! Endpoints (step-up session):
+//! - GET  /v1/admin/maintenance       → structures with their size and TTL
+//! - POST /v1/admin/maintenance/flush → empty `structures` (all if absent) now;
+//!   for incident response, e.g. lifting every lockout at once
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    middleware,
+    routing::{get, post},
+    Json, Router,
+};
+use lazy_static::lazy_static;
+use prometheus::{register_int_counter_vec, register_int_gauge_vec, IntCounterVec, IntGaugeVec};
+use serde::{Deserialize, Serialize};
+use tokio::time::interval;
+use tracing::{info, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::id_routes::IdState;
+use crate::timestamps::now_ms;
+
+lazy_static! {
+    pub static ref MEMORY_ENTRIES: IntGaugeVec = register_int_gauge_vec!(
+        "ubl_memory_entries",
+        "Entries held by in-memory structures, by structure",
+        &["structure"]
+    ).unwrap();
+
+    pub static ref MEMORY_EVICTIONS: IntCounterVec = register_int_counter_vec!(
+        "ubl_memory_evictions_total",
+        "Entries evicted from in-memory structures, by structure and reason (ttl, flush)",
+        &["structure", "reason"]
+    ).unwrap();
+}
+
+/// An in-memory structure the scheduler keeps bounded
+pub trait Maintained: Send + Sync {
+    /// Entries held now
+    fn entries(&self) -> usize;
+    /// Drop entries idle for more than `ttl_ms`; how many went
+    fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize;
+    /// Drop every entry; how many went
+    fn flush(&self) -> usize;
+}
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    pub interval_secs: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self { interval_secs: 60 }
+    }
+}
+
+impl MaintenanceConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            interval_secs: std::env::var("UBL_MAINTENANCE_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs)
+                .max(1),
+        }
+    }
+}
+
+struct Structure {
+    name: &'static str,
+    ttl: Duration,
+    target: Arc<dyn Maintained>,
+}
+
+/// Size of one structure, as the admin endpoint lists it
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureView {
+    pub name: &'static str,
+    pub entries: usize,
+    pub ttl_secs: u64,
+}
+
+/// Registered structures, swept on a timer
+pub struct Maintenance {
+    config: MaintenanceConfig,
+    structures: Mutex<Vec<Structure>>,
+}
+
+impl Maintenance {
+    pub fn new(config: MaintenanceConfig) -> Self {
+        Self { config, structures: Mutex::new(Vec::new()) }
+    }
+
+    /// Keep `target` bounded under `name`; its TTL is `default_ttl` unless
+    /// `UBL_MAINTENANCE_TTL_<NAME>_SECS` is set
+    pub fn register(&self, name: &'static str, default_ttl: Duration, target: Arc<dyn Maintained>) {
+        let ttl = std::env::var(format!("UBL_MAINTENANCE_TTL_{}_SECS", name.to_uppercase()))
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(default_ttl);
+        self.structures.lock().unwrap().push(Structure { name, ttl, target });
+    }
+
+    /// Evict idle entries everywhere and publish sizes; entries evicted
+    pub fn sweep(&self, now_ms: i64) -> usize {
+        let structures = self.structures.lock().unwrap();
+        let mut evicted = 0;
+        for s in structures.iter() {
+            let n = s.target.evict_idle(now_ms, s.ttl.as_millis() as i64);
+            MEMORY_EVICTIONS.with_label_values(&[s.name, "ttl"]).inc_by(n as u64);
+            MEMORY_ENTRIES.with_label_values(&[s.name]).set(s.target.entries() as i64);
+            evicted += n;
+        }
+        evicted
+    }
+
+    /// Empty the named structures (all when `names` is None); entries dropped
+    /// per structure. Unknown names are refused before anything is flushed.
+    pub fn flush(&self, names: Option<&[String]>) -> Result<BTreeMap<&'static str, usize>, String> {
+        let structures = self.structures.lock().unwrap();
+        if let Some(unknown) = names.into_iter().flatten().find(|n| !structures.iter().any(|s| s.name == n.as_str())) {
+            return Err(format!("unknown structure {}", unknown));
+        }
+        let mut flushed = BTreeMap::new();
+        for s in structures.iter().filter(|s| names.is_none_or(|names| names.iter().any(|n| n == s.name))) {
+            let n = s.target.flush();
+            MEMORY_EVICTIONS.with_label_values(&[s.name, "flush"]).inc_by(n as u64);
+            MEMORY_ENTRIES.with_label_values(&[s.name]).set(s.target.entries() as i64);
+            flushed.insert(s.name, n);
+        }
+        Ok(flushed)
+    }
+
+    pub fn structures(&self) -> Vec<StructureView> {
+        self.structures
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| StructureView { name: s.name, entries: s.target.entries(), ttl_secs: s.ttl.as_secs() })
+            .collect()
+    }
+
+    /// Start the sweep loop (runs forever)
+    pub async fn run(self: Arc<Self>) {
+        info!("🧹 In-memory maintenance every {}s", self.config.interval_secs);
+        let mut tick = interval(Duration::from_secs(self.config.interval_secs));
+        loop {
+            tick.tick().await;
+            let evicted = self.sweep(now_ms());
+            if evicted > 0 {
+                info!("🧹 Evicted {} idle in-memory entries", evicted);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FlushRequest {
+    /// Structures to flush; all of them if absent
+    pub structures: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlushResponse {
+    /// Entries dropped, by structure
+    pub flushed: BTreeMap<&'static str, usize>,
+}
+
+pub fn routes(maintenance: Arc<Maintenance>, id_state: IdState) -> Router {
+    Router::new()
+        .route("/v1/admin/maintenance", get(list_structures))
+        .route("/v1/admin/maintenance/flush", post(flush_structures))
+        .route_layer(middleware::from_fn_with_state(id_state, crate::auth::require_stepup::require_stepup))
+        .with_state(maintenance)
+}
+
+/// GET /v1/admin/maintenance
+async fn list_structures(State(maintenance): State<Arc<Maintenance>>) -> Json<Vec<StructureView>> {
+    Json(maintenance.structures())
+}
+
+/// POST /v1/admin/maintenance/flush
+async fn flush_structures(
+    State(maintenance): State<Arc<Maintenance>>,
+    body: Option<Json<FlushRequest>>,
+) -> Result<Json<FlushResponse>, ApiError> {
+    let Json(req) = body.unwrap_or_default();
+    let flushed = maintenance
+        .flush(req.structures.as_deref())
+        .map_err(|e| ApiError::new(ErrorCode::BadRequest, e))?;
+    warn!("🧹 Flushed in-memory structures on request: {:?}", flushed);
+    Ok(Json(FlushResponse { flushed }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Keys with the time they were last touched
+    #[derive(Default)]
+    struct Touched(Mutex<Vec<i64>>);
+
+    impl Maintained for Touched {
+        fn entries(&self) -> usize {
+            self.0.lock().unwrap().len()
+        }
+
+        fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize {
+            let mut touched = self.0.lock().unwrap();
+            let before = touched.len();
+            touched.retain(|at| now_ms - at <= ttl_ms);
+            before - touched.len()
+        }
+
+        fn flush(&self) -> usize {
+            std::mem::take(&mut *self.0.lock().unwrap()).len()
+        }
+    }
+
+    #[test]
+    fn test_sweep_and_flush() {
+        let maintenance = Maintenance::new(MaintenanceConfig::default());
+        let a = Arc::new(Touched(Mutex::new(vec![0, 50_000, 90_000])));
+        let b = Arc::new(Touched(Mutex::new(vec![0])));
+        maintenance.register("test_a", Duration::from_secs(60), a.clone());
+        maintenance.register("test_b", Duration::from_secs(3600), b.clone());
+
+        assert_eq!(maintenance.sweep(100_000), 1);
+        assert_eq!(a.entries(), 2);
+        assert_eq!(b.entries(), 1);
+
+        assert!(maintenance.flush(Some(&["nope".to_string()])).is_err());
+        assert_eq!(a.entries(), 2, "nothing flushed when a name is unknown");
+
+        let flushed = maintenance.flush(Some(&["test_a".to_string()])).unwrap();
+        assert_eq!(flushed.get("test_a"), Some(&2));
+        assert!(!flushed.contains_key("test_b"));
+
+        let flushed = maintenance.flush(None).unwrap();
+        assert_eq!(flushed.get("test_b"), Some(&1));
+        assert!(maintenance.structures().iter().all(|s| s.entries == 0));
+    }
+}
@@ -0,0 +1,385 @@
+//! Card Provenance — button actions must come from a card UBL issued
+//!
+//! When Office proposes a card, the Gateway commits a `card.issued` atom to
+//! C.Jobs with the card hash (blake3 of the canonical card JSON) and the hash
+//! of a fresh nonce, and returns card + hash + nonce to the client. A button
+//! action must present all three; it is accepted only if the card was issued
+//! for that job, the hash and nonce match, the button is on the card with the
+//! same action, and the card has not answered before.
+//!
+//! Rejections are committed to C.Office as `audit.card_provenance_rejected`
+//! and land in `office_audit_log`.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use tracing::{error, warn};
+
+use crate::db::{LedgerEntry, LinkDraft, PgLedger, TangencyError};
+use crate::messenger_v1::{blake3_hex, blake3_hex_bytes, sign_link_draft};
+use crate::timestamps::now_ms;
+
+use super::routes::GatewayState;
+
+/// Appends retried when another writer wins the sequence
+const APPEND_ATTEMPTS: usize = 3;
+
+/// What the client must echo back with a button action
+#[derive(Debug, Clone)]
+pub struct IssuedCard {
+    pub card_hash: String,
+    pub nonce: String,
+}
+
+/// A button as recorded at issuance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IssuedButton {
+    pub button_id: String,
+    /// Card action type, e.g. "job.approve"
+    pub action: String,
+    #[serde(default)]
+    pub requires_input: bool,
+}
+
+/// Issuance record as stored in `card_issuances`
+#[derive(Debug, Clone)]
+pub struct IssuedCardRecord {
+    pub job_id: String,
+    pub card_hash: String,
+    pub nonce_hash: String,
+    pub buttons: Vec<IssuedButton>,
+    pub used_at_ms: Option<i64>,
+}
+
+/// A button action as submitted by the client
+#[derive(Debug, Clone)]
+pub struct PresentedAction<'a> {
+    pub job_id: &'a str,
+    pub card_id: &'a str,
+    pub card_hash: &'a str,
+    pub nonce: &'a str,
+    pub button_id: &'a str,
+    pub action_type: &'a str,
+    pub has_input: bool,
+}
+
+/// blake3 hex of the canonical card JSON
+pub fn card_hash(card: &serde_json::Value) -> Result<String, String> {
+    ubl_atom::canonicalize(card)
+        .map(|bytes| blake3_hex_bytes(&bytes))
+        .map_err(|e| format!("CanonicalizeError: {}", e))
+}
+
+/// Buttons as offered by the card (`buttons[].action.type`)
+fn card_buttons(card: &serde_json::Value) -> Vec<IssuedButton> {
+    card.get("buttons")
+        .and_then(|b| b.as_array())
+        .map(|buttons| {
+            buttons
+                .iter()
+                .filter_map(|b| {
+                    Some(IssuedButton {
+                        button_id: b.get("button_id")?.as_str()?.to_string(),
+                        action: b.get("action")?.get("type")?.as_str()?.to_string(),
+                        requires_input: b.get("requires_input").and_then(|v| v.as_bool()).unwrap_or(false),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Check a presented action against its issuance; returns the rejection reason
+pub fn check_presented(record: &IssuedCardRecord, presented: &PresentedAction) -> Result<(), &'static str> {
+    if record.job_id != presented.job_id {
+        return Err("CardJobMismatch");
+    }
+    if !ubl_kernel::ct_eq(record.card_hash.as_bytes(), presented.card_hash.as_bytes()) {
+        return Err("CardHashMismatch");
+    }
+    if !ubl_kernel::ct_eq(record.nonce_hash.as_bytes(), blake3_hex(presented.nonce).as_bytes()) {
+        return Err("NonceMismatch");
+    }
+    let button = record
+        .buttons
+        .iter()
+        .find(|b| b.button_id == presented.button_id)
+        .ok_or("UnknownButton")?;
+    // Gateway action types drop the "job." prefix ("approve" for "job.approve")
+    if button.action != presented.action_type && button.action.strip_prefix("job.") != Some(presented.action_type) {
+        return Err("ActionMismatch");
+    }
+    if button.requires_input && !presented.has_input {
+        return Err("InputRequired");
+    }
+    if record.used_at_ms.is_some() {
+        return Err("CardAlreadyUsed");
+    }
+    Ok(())
+}
+
+/// Record a card Office just proposed; returns the hash and nonce for the client
+pub async fn issue_card(state: &GatewayState, card: &serde_json::Value, tenant_id: &str) -> Result<IssuedCard, String> {
+    let card_id = card.get("card_id").and_then(|v| v.as_str()).ok_or("card without card_id")?;
+    let job_id = card.get("job_id").and_then(|v| v.as_str()).ok_or("card without job_id")?;
+    let conversation_id = card.get("conversation_id").and_then(|v| v.as_str());
+    let hash = card_hash(card)?;
+    let nonce = URL_SAFE_NO_PAD.encode(crate::crypto::rand_bytes_32());
+    let nonce_hash = blake3_hex(&nonce);
+    let buttons = card_buttons(card);
+    let now_ms = now_ms();
+
+    let atom = serde_json::json!({
+        "type": "card.issued",
+        "card_id": card_id,
+        "job_id": job_id,
+        "conversation_id": conversation_id,
+        "tenant_id": tenant_id,
+        "card_hash": hash,
+        "nonce_hash": nonce_hash,
+        "buttons": buttons,
+        "issued_at_ms": now_ms,
+    });
+    let entry = append_atom(state, "C.Jobs", atom).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO card_issuances
+          (card_id, job_id, tenant_id, conversation_id, card_hash, nonce_hash, buttons, issued_entry_hash, issued_at_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(card_id)
+    .bind(job_id)
+    .bind(tenant_id)
+    .bind(conversation_id)
+    .bind(&hash)
+    .bind(&nonce_hash)
+    .bind(serde_json::to_value(&buttons).map_err(|e| e.to_string())?)
+    .bind(&entry.entry_hash)
+    .bind(now_ms)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(IssuedCard { card_hash: hash, nonce })
+}
+
+/// Verify a button action and claim the card for `actor`.
+/// Err is the rejection reason; the caller audits it.
+pub async fn verify_and_claim(
+    state: &GatewayState,
+    tenant_id: &str,
+    presented: &PresentedAction<'_>,
+    actor: &str,
+) -> Result<(), String> {
+    let row = sqlx::query(
+        r#"
+        SELECT job_id, card_hash, nonce_hash, buttons, used_at_ms
+        FROM card_issuances
+        WHERE card_id = $1 AND tenant_id = $2
+        "#,
+    )
+    .bind(presented.card_id)
+    .bind(tenant_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| format!("DatabaseError: {}", e))?
+    .ok_or("UnknownCard")?;
+
+    let record = IssuedCardRecord {
+        job_id: row.get("job_id"),
+        card_hash: row.get("card_hash"),
+        nonce_hash: row.get("nonce_hash"),
+        buttons: serde_json::from_value(row.get("buttons")).unwrap_or_default(),
+        used_at_ms: row.get("used_at_ms"),
+    };
+    check_presented(&record, presented)?;
+
+    // Two concurrent clicks: only one claims the card
+    let claimed = sqlx::query(
+        r#"
+        UPDATE card_issuances
+        SET used_at_ms = $2, used_by = $3, used_button_id = $4
+        WHERE card_id = $1 AND used_at_ms IS NULL
+        "#,
+    )
+    .bind(presented.card_id)
+    .bind(now_ms())
+    .bind(actor)
+    .bind(presented.button_id)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| format!("DatabaseError: {}", e))?
+    .rows_affected();
+
+    if claimed == 0 {
+        return Err("CardAlreadyUsed".to_string());
+    }
+    Ok(())
+}
+
+/// Give the card back when Office could not process the action
+pub async fn release_claim(state: &GatewayState, card_id: &str, actor: &str) {
+    if let Err(e) = sqlx::query(
+        "UPDATE card_issuances SET used_at_ms = NULL, used_by = NULL, used_button_id = NULL WHERE card_id = $1 AND used_by = $2",
+    )
+    .bind(card_id)
+    .bind(actor)
+    .execute(&state.pool)
+    .await
+    {
+        error!("Failed to release card {}: {}", card_id, e);
+    }
+}
+
+/// Commit `audit.card_provenance_rejected` to C.Office and index it
+pub async fn audit_rejection(state: &GatewayState, tenant_id: &str, presented: &PresentedAction<'_>, actor: &str, reason: &str) {
+    warn!(
+        "🚫 Card provenance rejected: {} card={} button={} job={} by {}",
+        reason, presented.card_id, presented.button_id, presented.job_id, actor
+    );
+
+    let atom = serde_json::json!({
+        "type": "audit.card_provenance_rejected",
+        "entity_id": actor,
+        "session_id": "",
+        "trace_id": presented.card_id,
+        "job_id": presented.job_id,
+        "tenant_id": tenant_id,
+        "card_id": presented.card_id,
+        "button_id": presented.button_id,
+        "action_type": presented.action_type,
+        "presented_card_hash": presented.card_hash,
+        "reason": reason,
+        "ts_ms": now_ms(),
+    });
+
+    match append_atom(state, "C.Office", atom.clone()).await {
+        Ok(entry) => {
+            let projection = crate::projections::OfficeProjection::new(state.pool.clone());
+            if let Err(e) = projection
+                .process_event("audit.card_provenance_rejected", &atom, &entry.entry_hash, entry.sequence)
+                .await
+            {
+                error!("Failed to index provenance rejection: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to audit provenance rejection: {}", e),
+    }
+}
+
+/// Sign with the boundary key and append, retrying on a lost sequence race
+pub(crate) async fn append_atom(state: &GatewayState, container_id: &str, atom: serde_json::Value) -> Result<LedgerEntry, String> {
+    append_signed(&state.ledger, container_id, atom).await
+}
+
+/// [`append_atom`] for callers outside the gateway that hold only the ledger
+pub(crate) async fn append_signed(ledger: &PgLedger, container_id: &str, atom: serde_json::Value) -> Result<LedgerEntry, String> {
+    let atom_bytes = ubl_atom::canonicalize(&atom).map_err(|e| format!("CanonicalizeError: {}", e))?;
+    let atom_hash = blake3_hex_bytes(&atom_bytes);
+
+    let mut last_err = String::new();
+    for _ in 0..APPEND_ATTEMPTS {
+        let container_state = ledger.get_state(container_id).await.unwrap_or_else(|_| LedgerEntry {
+            container_id: container_id.to_string(),
+            sequence: 0,
+            entry_hash: "0x00".to_string(),
+            previous_hash: "0x00".to_string(),
+            link_hash: "0x00".to_string(),
+            ts_unix_ms: 0,
+            hlc: crate::hlc::Hlc::ZERO,
+        });
+
+        let mut link = LinkDraft {
+            version: 1,
+            container_id: container_id.to_string(),
+            expected_sequence: container_state.sequence + 1,
+            previous_hash: container_state.entry_hash,
+            atom_hash: atom_hash.clone(),
+            atom: Some(atom.clone()),
+            intent_class: "Observation".to_string(),
+            physics_delta: "0".to_string(),
+            author_pubkey: String::new(), // Will be set by sign_link_draft
+            signature: String::new(),     // Will be set by sign_link_draft
+            pact: None,
+            tentative_id: None,
+        };
+        sign_link_draft(&mut link);
+
+        match ledger.append(&link).await {
+            Ok(entry) => return Ok(entry),
+            Err(e @ (TangencyError::SequenceMismatch | TangencyError::RealityDrift)) => last_err = format!("{:?}", e),
+            Err(e) => return Err(format!("Commit failed: {:?}", e)),
+        }
+    }
+    Err(format!("Commit failed: {}", last_err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issued(nonce: &str) -> IssuedCardRecord {
+        IssuedCardRecord {
+            job_id: "job_1".into(),
+            card_hash: "h".into(),
+            nonce_hash: blake3_hex(nonce),
+            buttons: vec![
+                IssuedButton { button_id: "btn_approve".into(), action: "job.approve".into(), requires_input: false },
+                IssuedButton { button_id: "btn_changes".into(), action: "job.request_changes".into(), requires_input: true },
+            ],
+            used_at_ms: None,
+        }
+    }
+
+    fn presented<'a>(button_id: &'a str, action_type: &'a str, nonce: &'a str) -> PresentedAction<'a> {
+        PresentedAction {
+            job_id: "job_1",
+            card_id: "card_1",
+            card_hash: "h",
+            nonce,
+            button_id,
+            action_type,
+            has_input: false,
+        }
+    }
+
+    #[test]
+    fn test_check_presented() {
+        let record = issued("n1");
+        assert_eq!(check_presented(&record, &presented("btn_approve", "approve", "n1")), Ok(()));
+        assert_eq!(check_presented(&record, &presented("btn_approve", "job.approve", "n1")), Ok(()));
+        assert_eq!(check_presented(&record, &presented("btn_approve", "approve", "n2")), Err("NonceMismatch"));
+        assert_eq!(check_presented(&record, &presented("btn_approve", "reject", "n1")), Err("ActionMismatch"));
+        assert_eq!(check_presented(&record, &presented("btn_forged", "approve", "n1")), Err("UnknownButton"));
+        assert_eq!(check_presented(&record, &presented("btn_changes", "request_changes", "n1")), Err("InputRequired"));
+
+        let mut other_job = presented("btn_approve", "approve", "n1");
+        other_job.job_id = "job_2";
+        assert_eq!(check_presented(&record, &other_job), Err("CardJobMismatch"));
+
+        let used = IssuedCardRecord { used_at_ms: Some(1), ..issued("n1") };
+        assert_eq!(check_presented(&used, &presented("btn_approve", "approve", "n1")), Err("CardAlreadyUsed"));
+    }
+
+    #[test]
+    fn test_card_buttons_and_hash() {
+        let card = serde_json::json!({
+            "card_id": "card_1",
+            "job_id": "job_1",
+            "buttons": [
+                {"button_id": "btn_approve", "action": {"type": "job.approve", "job_id": "job_1"}, "requires_input": false},
+                {"button_id": "btn_bad"}
+            ]
+        });
+        let buttons = card_buttons(&card);
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0].action, "job.approve");
+
+        // Any edit to the card changes its hash
+        let mut tampered = card.clone();
+        tampered["buttons"][0]["action"]["type"] = "job.cancel".into();
+        assert_ne!(card_hash(&card).unwrap(), card_hash(&tampered).unwrap());
+    }
+}
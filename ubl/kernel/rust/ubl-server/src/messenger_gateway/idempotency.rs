@@ -4,6 +4,11 @@
 //! Format: `idem:{tenant_id}:{action_type}:{resource_id}:{nonce}`
 //!
 //! FIXED: Now uses Postgres instead of in-memory HashMap to survive restarts.
+//!
+//! Message sends are also deduplicated by the client's nonce
+//! (`client_msg_id`) per conversation ([`MessageNonces`]): the send is
+//! claimed before it is committed, so concurrent duplicates never reach the
+//! ledger.
 
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
@@ -142,3 +147,204 @@ struct IdempotencyRow {
     event_ids: Vec<String>,
     created_at: OffsetDateTime,
 }
+
+/// A pending claim older than this is taken over (the sender crashed)
+pub const NONCE_CLAIM_TIMEOUT_MS: i64 = 60_000;
+
+/// Longest accepted `client_msg_id`
+pub const MAX_CLIENT_MSG_ID_LEN: usize = 128;
+
+/// Outcome of claiming a message send by its client nonce
+#[derive(Debug, Clone, PartialEq)]
+pub enum NonceClaim {
+    /// First sighting (or a stale claim taken over): send it
+    Fresh,
+    /// Another request with this nonce is sending it right now
+    InFlight,
+    /// Committed to the ledger, Office has not taken it
+    Committed { message_id: String, entry_hash: String, sequence: i64 },
+    /// Sent; the response to replay
+    Delivered(serde_json::Value),
+    /// Another sender used this nonce in the conversation
+    Foreign,
+}
+
+/// Message sends by (conversation, client nonce)
+#[derive(Clone)]
+pub struct MessageNonces {
+    pool: PgPool,
+}
+
+impl MessageNonces {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Claim the send of `client_msg_id` in `conversation_id`, or learn what
+    /// became of the first send with it
+    pub async fn claim(
+        &self,
+        conversation_id: &str,
+        client_msg_id: &str,
+        tenant_id: &str,
+        from_sid: &str,
+        now_ms: i64,
+    ) -> Result<NonceClaim, sqlx::Error> {
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO gateway_message_nonces
+                (conversation_id, client_msg_id, tenant_id, from_sid, status, claimed_at_ms)
+            VALUES ($1, $2, $3, $4, 'pending', $5)
+            ON CONFLICT (conversation_id, client_msg_id) DO UPDATE SET claimed_at_ms = EXCLUDED.claimed_at_ms
+            WHERE gateway_message_nonces.status = 'pending'
+              AND gateway_message_nonces.from_sid = EXCLUDED.from_sid
+              AND gateway_message_nonces.claimed_at_ms < $6
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(client_msg_id)
+        .bind(tenant_id)
+        .bind(from_sid)
+        .bind(now_ms)
+        .bind(now_ms - NONCE_CLAIM_TIMEOUT_MS)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        if claimed > 0 {
+            return Ok(NonceClaim::Fresh);
+        }
+
+        let row = sqlx::query_as::<_, NonceRow>(
+            r#"
+            SELECT from_sid, status, message_id, entry_hash, sequence, response
+            FROM gateway_message_nonces
+            WHERE conversation_id = $1 AND client_msg_id = $2
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(client_msg_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        // Released between the two statements: the next attempt claims it
+        Ok(row.map_or(NonceClaim::InFlight, |row| row.claim(from_sid)))
+    }
+
+    /// The message is in the ledger
+    pub async fn mark_committed(
+        &self,
+        conversation_id: &str,
+        client_msg_id: &str,
+        message_id: &str,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE gateway_message_nonces
+            SET status = 'committed', message_id = $3, entry_hash = $4, sequence = $5
+            WHERE conversation_id = $1 AND client_msg_id = $2
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(client_msg_id)
+        .bind(message_id)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Office took the message; `response` is what duplicates get back
+    pub async fn mark_delivered(
+        &self,
+        conversation_id: &str,
+        client_msg_id: &str,
+        response: &serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE gateway_message_nonces SET status = 'delivered', response = $3
+            WHERE conversation_id = $1 AND client_msg_id = $2
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(client_msg_id)
+        .bind(response)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Nothing was committed; the nonce can be sent again
+    pub async fn release(&self, conversation_id: &str, client_msg_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM gateway_message_nonces
+            WHERE conversation_id = $1 AND client_msg_id = $2 AND status = 'pending'
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(client_msg_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct NonceRow {
+    from_sid: String,
+    status: String,
+    message_id: Option<String>,
+    entry_hash: Option<String>,
+    sequence: Option<i64>,
+    response: Option<serde_json::Value>,
+}
+
+impl NonceRow {
+    /// What a duplicate send by `from_sid` gets
+    fn claim(self, from_sid: &str) -> NonceClaim {
+        if self.from_sid != from_sid {
+            return NonceClaim::Foreign;
+        }
+        match (self.status.as_str(), self.response, self.message_id, self.entry_hash, self.sequence) {
+            ("delivered", Some(response), ..) => NonceClaim::Delivered(response),
+            (_, _, Some(message_id), Some(entry_hash), Some(sequence)) => {
+                NonceClaim::Committed { message_id, entry_hash, sequence }
+            }
+            _ => NonceClaim::InFlight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(status: &str, from_sid: &str) -> NonceRow {
+        NonceRow {
+            from_sid: from_sid.into(),
+            status: status.into(),
+            message_id: (status != "pending").then(|| "msg_1".into()),
+            entry_hash: (status != "pending").then(|| "h".into()),
+            sequence: (status != "pending").then_some(7),
+            response: (status == "delivered").then(|| serde_json::json!({ "message_id": "msg_1" })),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_sends_get_the_first_outcome() {
+        assert_eq!(row("pending", "alice").claim("alice"), NonceClaim::InFlight);
+        assert_eq!(
+            row("committed", "alice").claim("alice"),
+            NonceClaim::Committed { message_id: "msg_1".into(), entry_hash: "h".into(), sequence: 7 }
+        );
+        assert_eq!(
+            row("delivered", "alice").claim("alice"),
+            NonceClaim::Delivered(serde_json::json!({ "message_id": "msg_1" }))
+        );
+        // Nobody replays someone else's send
+        assert_eq!(row("delivered", "alice").claim("bob"), NonceClaim::Foreign);
+    }
+}
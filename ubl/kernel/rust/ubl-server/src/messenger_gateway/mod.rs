@@ -1,7 +1,9 @@
 //! Messenger Gateway v1
 //!
 //! Thin gateway layer between frontend and UBL/Office.
-//! Handles command routing, idempotency, projection management, and SSE delta emission.
+//! Handles command routing, idempotency, projection management, SSE delta emission,
+//! scheduled (send later) messages, per-sender throttling and transcript
+//! summaries.
 //!
 //! Architecture:
 //! - Frontend → Gateway → Office → UBL
@@ -12,6 +14,10 @@ pub mod projections;
 pub mod sse;
 pub mod idempotency;
 pub mod office_client;
+pub mod card_provenance;
+pub mod scheduled;
+pub mod throttle;
+pub mod summarizer;
 
 pub use routes::{routes, GatewayState};
 
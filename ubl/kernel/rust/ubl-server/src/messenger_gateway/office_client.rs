@@ -1,27 +1,105 @@
 //! Office HTTP Client
 //!
 //! HTTP client for communicating with Office runtime.
-//! Used by Gateway to forward messages and job actions.
+//! Used by Gateway to forward messages and job actions, by the job monitor
+//! to report server-originated job events, by the ASC expiry monitor, by
+//! the sender throttle when it mutes an agent and by the transcript
+//! summarizer. Every request is signed with UBL's service key (see
+//! `service_auth`).
+//!
+//! `OFFICE_URL` is either an http(s) URL or `unix:///path/to/office.sock` for
+//! an Office on the same host, which is then reached without TCP.
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tracing::{error, info};
+use ubl_events::JobEventNotice;
+
+use crate::service_auth;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Office client configuration
+#[derive(Clone)]
 pub struct OfficeClient {
     base_url: String,
-    client: reqwest::Client,
+    transport: Transport,
+}
+
+#[derive(Clone)]
+enum Transport {
+    Http(reqwest::Client),
+    /// One HTTP/1 connection per request over the socket
+    Unix(PathBuf),
+}
+
+/// Status and body of an Office reply
+struct OfficeResponse {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl OfficeResponse {
+    fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
 }
 
 impl OfficeClient {
     /// Create a new Office client
     pub fn new(base_url: String) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        if let Some(socket) = base_url.strip_prefix("unix://") {
+            return Self { base_url: String::new(), transport: Transport::Unix(PathBuf::from(socket)) };
+        }
+        // mTLS material was loaded once by service_auth::init
+        let client = service_auth::http_client(REQUEST_TIMEOUT)
             .expect("Failed to create HTTP client");
         
-        Self { base_url, client }
+        Self { base_url, transport: Transport::Http(client) }
+    }
+
+    /// POST a JSON body to Office, signed with UBL's service key
+    async fn post<T: Serialize>(&self, path: &str, req: &T) -> Result<OfficeResponse, OfficeClientError> {
+        let body = serde_json::to_vec(req).map_err(|e| OfficeClientError::Parse(e.to_string()))?;
+        let client = match &self.transport {
+            Transport::Http(client) => client,
+            Transport::Unix(socket) => {
+                let headers = service_auth::sign_headers("POST", path, &body);
+                return tokio::time::timeout(REQUEST_TIMEOUT, post_unix(socket, path, headers, body))
+                    .await
+                    .map_err(|_| OfficeClientError::Network(format!("unix socket {}: timed out", socket.display())))?
+                    .map_err(|e| OfficeClientError::Network(format!("unix socket {}: {}", socket.display(), e)));
+            }
+        };
+
+        let url = reqwest::Url::parse(&format!("{}{}", self.base_url, path))
+            .map_err(|e| OfficeClientError::Network(e.to_string()))?;
+        // Office sees the full path, including any prefix in base_url
+        let signed_path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let mut request = client.post(url).header(reqwest::header::CONTENT_TYPE, "application/json");
+        for (name, value) in service_auth::sign_headers("POST", &signed_path, &body) {
+            request = request.header(name, value);
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| OfficeClientError::Network(e.to_string()))?;
+        let status = response.status().as_u16();
+        let body = response.bytes().await.map_err(|e| OfficeClientError::Network(e.to_string()))?;
+        Ok(OfficeResponse { status, body: body.to_vec() })
     }
 
     /// Ingest a message from Gateway
@@ -30,27 +108,19 @@ impl OfficeClient {
         &self,
         req: &IngestMessageRequest,
     ) -> Result<IngestMessageResponse, OfficeClientError> {
-        let url = format!("{}/v1/office/ingest_message", self.base_url);
-        
         info!("📨 Gateway → Office: ingest_message conversation={} message={}", 
               req.conversation_id, req.message_id);
         
-        let response = self.client
-            .post(&url)
-            .json(req)
-            .send()
-            .await
-            .map_err(|e| OfficeClientError::Network(e.to_string()))?;
+        let response = self.post("/v1/office/ingest_message", req).await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+        if !response.is_success() {
+            let error_text = response.text();
             error!("❌ Office ingest_message failed: {}", error_text);
             return Err(OfficeClientError::Office(error_text));
         }
 
         let result: IngestMessageResponse = response
             .json()
-            .await
             .map_err(|e| OfficeClientError::Parse(e.to_string()))?;
 
         info!("✅ Office response: action={:?} job_id={:?}", 
@@ -64,33 +134,113 @@ impl OfficeClient {
         &self,
         req: &JobActionRequest,
     ) -> Result<JobActionResponse, OfficeClientError> {
-        let url = format!("{}/v1/office/job_action", self.base_url);
-        
         info!("🔧 Gateway → Office: job_action job={} action={}", 
               req.job_id, req.action_type);
         
-        let response = self.client
-            .post(&url)
-            .json(req)
-            .send()
-            .await
-            .map_err(|e| OfficeClientError::Network(e.to_string()))?;
+        let response = self.post("/v1/office/job_action", req).await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+        if !response.is_success() {
+            let error_text = response.text();
             error!("❌ Office job_action failed: {}", error_text);
             return Err(OfficeClientError::Office(error_text));
         }
 
         let result: JobActionResponse = response
             .json()
-            .await
             .map_err(|e| OfficeClientError::Parse(e.to_string()))?;
 
         info!("✅ Office job_action response: success={}", result.success);
         
         Ok(result)
     }
+
+    /// Notify Office of a UBL-originated job event (already committed)
+    pub async fn job_event(&self, req: &JobEventNotice) -> Result<(), OfficeClientError> {
+        info!("📣 UBL → Office: job_event job={} type={}", req.job_id, req.event_type);
+
+        let response = self.post("/v1/office/job_event", req).await?;
+
+        if !response.is_success() {
+            let error_text = response.text();
+            error!("❌ Office job_event failed: {}", error_text);
+            return Err(OfficeClientError::Office(error_text));
+        }
+
+        Ok(())
+    }
+
+    /// Warn Office that an agent's ASC is about to expire
+    pub async fn asc_expiring(&self, req: &AscExpiryNotice) -> Result<(), OfficeClientError> {
+        info!("📣 UBL → Office: asc_expiring asc={} sid={}", req.asc_id, req.sid);
+
+        let response = self.post("/v1/office/asc_expiring", req).await?;
+
+        if !response.is_success() {
+            let error_text = response.text();
+            error!("❌ Office asc_expiring failed: {}", error_text);
+            return Err(OfficeClientError::Office(error_text));
+        }
+
+        Ok(())
+    }
+
+    /// Tell Office an agent was muted for flooding, for its guardian
+    pub async fn agent_muted(&self, req: &AgentMutedNotice) -> Result<(), OfficeClientError> {
+        info!("📣 UBL → Office: agent_muted sid={} mute={}", req.sid, req.mute_id);
+
+        let response = self.post("/v1/office/agent_muted", req).await?;
+
+        if !response.is_success() {
+            let error_text = response.text();
+            error!("❌ Office agent_muted failed: {}", error_text);
+            return Err(OfficeClientError::Office(error_text));
+        }
+
+        Ok(())
+    }
+
+    /// Have Office run a summarization job over a conversation segment
+    pub async fn summarize_segment(&self, req: &SummarizeRequest) -> Result<SummarizeResponse, OfficeClientError> {
+        info!("🧾 UBL → Office: summarize conversation={} messages={}", req.conversation_id, req.messages.len());
+
+        let response = self.post("/v1/office/summarize", req).await?;
+
+        if !response.is_success() {
+            let error_text = response.text();
+            error!("❌ Office summarize failed: {}", error_text);
+            return Err(OfficeClientError::Office(error_text));
+        }
+
+        response
+            .json()
+            .map_err(|e| OfficeClientError::Parse(e.to_string()))
+    }
+}
+
+async fn post_unix(
+    socket: &Path,
+    path: &str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+) -> Result<OfficeResponse, Box<dyn std::error::Error + Send + Sync>> {
+    use axum::body::Body;
+    use axum::http::{header, Request};
+    use hyper_util::rt::TokioIo;
+
+    let stream = tokio::net::UnixStream::connect(socket).await?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+    tokio::spawn(connection);
+
+    let mut request = Request::post(path)
+        .header(header::HOST, "localhost")
+        .header(header::CONTENT_TYPE, "application/json");
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = sender.send_request(request.body(Body::from(body))?).await?;
+    let status = response.status().as_u16();
+    let body = axum::body::to_bytes(Body::new(response.into_body()), usize::MAX).await?;
+    Ok(OfficeResponse { status, body: body.to_vec() })
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -100,6 +250,9 @@ pub struct IngestMessageRequest {
     pub from: String,
     pub content: String,
     pub tenant_id: String,
+    /// Client nonce of the send, for Office to correlate what it publishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_msg_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +290,52 @@ pub struct JobActionResponse {
     pub event_ids: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AscExpiryNotice {
+    pub asc_id: String,
+    pub sid: String,
+    /// Set when the ASC came from a template request
+    pub tenant_id: Option<String>,
+    pub template: Option<String>,
+    pub not_after_ms: i64,
+    pub expires_in_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentMutedNotice {
+    pub mute_id: String,
+    pub sid: String,
+    pub tenant_id: String,
+    pub conversation_id: String,
+    pub sends_in_window: i32,
+    pub window_secs: i32,
+    pub muted_until_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentLine {
+    pub message_id: String,
+    pub from: String,
+    pub content: Option<String>,
+    pub entry_hash: String,
+    pub timestamp_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizeRequest {
+    pub conversation_id: String,
+    pub tenant_id: String,
+    pub previous_summary: Option<String>,
+    pub messages: Vec<SegmentLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizeResponse {
+    pub job_id: String,
+    pub summary: String,
+    pub model: String,
+}
+
 #[derive(Debug)]
 pub enum OfficeClientError {
     Network(String),
@@ -156,3 +355,34 @@ impl std::fmt::Display for OfficeClientError {
 
 impl std::error::Error for OfficeClientError {}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_post_over_unix_socket() {
+        use axum::{http::Uri, routing::post, Router};
+
+        let socket = std::env::temp_dir().join(format!("ubl-office-{}.sock", uuid::Uuid::new_v4()));
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        let app = Router::new().route(
+            "/v1/office/job_event",
+            post(|uri: Uri, body: String| async move { format!(r#"{{"path":"{}","body":{}}}"#, uri, body) }),
+        );
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = hyper_util::service::TowerToHyperService::new(app);
+            let _ = hyper::server::conn::http1::Builder::new()
+                .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                .await;
+        });
+
+        let client = OfficeClient::new(format!("unix://{}", socket.display()));
+        let response = client.post("/v1/office/job_event", &serde_json::json!({ "job_id": "job_1" })).await.unwrap();
+        assert!(response.is_success());
+        let echoed: serde_json::Value = response.json().unwrap();
+        assert_eq!(echoed["path"], "/v1/office/job_event");
+        assert_eq!(echoed["body"]["job_id"], "job_1");
+        let _ = std::fs::remove_file(&socket);
+    }
+}
@@ -6,21 +6,32 @@
 use axum::{
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    response::IntoResponse,
-    routing::{get, post},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::{PgPool, Row};
 use std::sync::Arc;
+use std::time::Duration;
 use time::OffsetDateTime;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::blob_store::BlobStore;
 use crate::db::PgLedger;
-use crate::messenger_gateway::{idempotency::IdempotencyStore, office_client::OfficeClient, sse::GatewaySSE};
+use crate::maintenance::Maintenance;
+use crate::sse::{ConnectionRegistry, SseLimits, TailBus, TailEntry};
+use crate::messenger_gateway::{card_provenance, idempotency::IdempotencyStore, office_client::OfficeClient, sse::GatewaySSE};
+use crate::messenger_gateway::idempotency::{MessageNonces, NonceClaim, MAX_CLIENT_MSG_ID_LEN};
+use crate::messenger_gateway::scheduled::{CancelError, NewSchedule, ScheduledConfig, ScheduledMessage, ScheduledStore};
+use crate::messenger_gateway::throttle::{Refusal, SenderMute, Throttle, ThrottlePolicy};
+use crate::query_params::{self, Limit};
+use crate::tenant::{db as tenant_db, types::MemberRole};
 
 use super::projections::GatewayProjections;
+use crate::projections::{mentions, MessagesProjection, RedactionsProjection, Visibility};
+use crate::projections::visibility::{MAX_REASON_CHARS, REDACTION_TYPE};
 
 // Reuse helpers from messenger_v1
 use crate::messenger_v1::{get_user_from_session, UserInfo};
@@ -38,34 +49,65 @@ pub struct GatewayState {
     pub ledger: PgLedger,
     pub office_client: Arc<OfficeClient>,
     pub idempotency: Arc<IdempotencyStore>,
+    pub nonces: Arc<MessageNonces>,
     pub projections: Arc<GatewayProjections>,
+    pub sse_connections: ConnectionRegistry,
+    pub sse_limits: SseLimits,
+    pub scheduled: Arc<ScheduledStore>,
+    pub scheduled_config: ScheduledConfig,
+    pub throttle: Arc<Throttle>,
+    pub blobs: Arc<BlobStore>,
+    /// Kernel SSE tail; message commits are announced on it when set
+    pub tail: Option<TailBus>,
+}
+
+impl GatewayState {
+    pub fn new(pool: PgPool, office_url: String) -> Self {
+        Self {
+            ledger: PgLedger::new(pool.clone()),
+            office_client: Arc::new(OfficeClient::new(office_url)),
+            // Fix #4: Persistent idempotency backed by Postgres
+            idempotency: Arc::new(IdempotencyStore::new(pool.clone())),
+            nonces: Arc::new(MessageNonces::new(pool.clone())),
+            projections: Arc::new(GatewayProjections::new(pool.clone())),
+            sse_connections: ConnectionRegistry::default(),
+            sse_limits: SseLimits::from_env(),
+            scheduled: Arc::new(ScheduledStore::new(pool.clone())),
+            scheduled_config: ScheduledConfig::from_env(),
+            throttle: Arc::new(Throttle::new(pool.clone(), ThrottlePolicy::from_env())),
+            blobs: Arc::new(BlobStore::from_env()),
+            tail: None,
+            pool,
+        }
+    }
+
+    /// Announce message commits on the kernel SSE tail
+    pub fn with_tail(mut self, tail: TailBus) -> Self {
+        self.tail = Some(tail);
+        self
+    }
 }
 
 // ============================================================================
 // ROUTES
 // ============================================================================
 
-pub fn routes(pool: PgPool, office_url: String) -> Router {
-    let ledger = PgLedger::new(pool.clone());
-    let office_client = Arc::new(OfficeClient::new(office_url));
-    // Fix #4: Persistent idempotency backed by Postgres
-    let idempotency = Arc::new(IdempotencyStore::new(pool.clone()));
-    let projections = Arc::new(GatewayProjections::new(pool.clone()));
-    
-    let state = GatewayState {
-        pool,
-        ledger,
-        office_client,
-        idempotency,
-        projections,
-    };
+pub fn routes(pool: PgPool, office_url: String, tail: TailBus, maintenance: &Maintenance) -> Router {
+    let state = GatewayState::new(pool, office_url).with_tail(tail);
+    maintenance.register("send_throttle", Duration::from_secs(3600), Arc::new(state.throttle.counter()));
     
     Router::new()
         // Commands
         .route("/v1/conversations/:id/messages", post(post_message))
         .route("/v1/jobs/:id/actions", post(job_action))
+        .route("/v1/conversations/:id/scheduled", post(schedule_message).get(list_scheduled))
+        .route("/v1/scheduled/:id", delete(cancel_scheduled))
+        .route("/v1/mutes", get(list_mutes))
+        .route("/v1/mutes/:sid", delete(lift_mute))
+        .route("/v1/messages/:id/redact", post(redact_message))
         // Queries
         .route("/v1/conversations/:id/timeline", get(get_timeline))
+        .route("/v1/conversations/:id/blobs/:hash", get(get_conversation_blob))
         .route("/v1/jobs/:id", get(get_job))
         // SSE
         .route("/v1/stream", get(get_stream))
@@ -81,14 +123,80 @@ struct PostMessageRequest {
     content: String,
     message_type: Option<String>,
     idempotency_key: Option<String>,
+    /// Optimistic UI id, echoed back (see `LinkDraft::tentative_id`)
+    #[serde(default)]
+    tentative_id: Option<String>,
+    /// Client nonce of this send: however often it is sent, the conversation
+    /// gets one message (see `MessageNonces`)
+    #[serde(default)]
+    client_msg_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct PostMessageResponse {
+pub(crate) struct PostMessageResponse {
     message_id: String,
     hash: String,
     sequence: i64,
     action: String, // "committed" | "office_processing"
+    /// Card proposed by Office; actions on it must echo card_hash + card_nonce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    card: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    card_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    card_nonce: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tentative_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client_msg_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleMessageRequest {
+    content: String,
+    message_type: Option<String>,
+    /// Delivery time, unix milliseconds (UTC)
+    send_at_ms: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListScheduledQuery {
+    /// pending (default) | sending | sent | cancelled | failed
+    status: Option<String>,
+}
+
+/// Statuses `?status=` may name on the scheduled list
+const SCHEDULED_STATUSES: &[&str] = &["pending", "sending", "sent", "cancelled", "failed"];
+
+/// Page size of the timeline (see `crate::query_params`)
+const TIMELINE: Limit = Limit::new(50, 200);
+
+#[derive(Debug, Serialize)]
+struct ScheduledListResponse {
+    items: Vec<ScheduledMessage>,
+}
+
+#[derive(Debug, Serialize)]
+struct MuteListResponse {
+    items: Vec<SenderMute>,
+}
+
+#[derive(Debug, Serialize)]
+struct LiftMuteResponse {
+    sid: String,
+    lifted: Vec<SenderMute>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedactMessageRequest {
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RedactMessageResponse {
+    message_id: String,
+    hash: String,
+    sequence: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -96,6 +204,9 @@ struct JobActionRequest {
     action_type: String,
     button_id: String,
     card_id: String,
+    /// Provenance: hash and nonce returned when the card was issued
+    card_hash: String,
+    nonce: String,
     input_data: Option<serde_json::Value>,
     idempotency_key: Option<String>,
 }
@@ -140,6 +251,13 @@ async fn post_message(
     let user = get_user_from_session(&state.pool, &headers).await
         .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
     
+    if req.client_msg_id.as_ref().is_some_and(|n| n.is_empty() || n.len() > MAX_CLIENT_MSG_ID_LEN) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("client_msg_id must be 1-{} characters", MAX_CLIENT_MSG_ID_LEN),
+        ));
+    }
+    
     // 2. Check idempotency
     // Zona Schengen: Use tenant_id from session
     let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
@@ -163,29 +281,138 @@ async fn post_message(
         }
     }
     
-    // 3. Generate message ID
+    // One message per (conversation, client nonce): a duplicate gets the
+    // first send's outcome
+    if let Some(nonce) = req.client_msg_id.as_deref() {
+        let claim = state.nonces.claim(&conversation_id, nonce, tenant_id, &user.sid, crate::timestamps::now_ms()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        match claim {
+            NonceClaim::Fresh => {}
+            NonceClaim::Delivered(response) => {
+                return Ok(Json(serde_json::from_value(response)
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?));
+            }
+            NonceClaim::Committed { message_id, entry_hash, sequence } => {
+                return Ok(Json(PostMessageResponse {
+                    message_id,
+                    hash: entry_hash,
+                    sequence,
+                    action: "committed".to_string(),
+                    card: None,
+                    card_hash: None,
+                    card_nonce: None,
+                    tentative_id: req.tentative_id,
+                    client_msg_id: Some(nonce.to_string()),
+                }));
+            }
+            NonceClaim::InFlight => {
+                return Err((StatusCode::CONFLICT, format!("Message {} is already being sent", nonce)));
+            }
+            NonceClaim::Foreign => {
+                return Err((StatusCode::CONFLICT, format!("client_msg_id {} is taken in this conversation", nonce)));
+            }
+        }
+    }
+    
+    // 3. Commit message.created to UBL (C.Messenger) first
+    let outgoing = OutgoingMessage {
+        conversation_id: &conversation_id,
+        from: &user.sid,
+        tenant_id,
+        content: &req.content,
+        message_type: req.message_type.as_deref().unwrap_or("text"),
+        tentative_id: req.tentative_id.as_deref(),
+        client_msg_id: req.client_msg_id.as_deref(),
+        schedule_id: None,
+    };
+    let committed = match commit_message(&state, &outgoing).await {
+        Ok(committed) => committed,
+        Err(e) => {
+            // Not in the ledger (a commit that was stays claimed)
+            if let Some(nonce) = outgoing.client_msg_id {
+                if let Err(e) = state.nonces.release(&conversation_id, nonce).await {
+                    warn!("Failed to release client_msg_id {}: {}", nonce, e);
+                }
+            }
+            return Err(e);
+        }
+    };
+    
+    // 4. Call Office to ingest message
+    let (response, event_ids) = deliver_to_office(&state, &outgoing, &committed).await?;
+    if let (Some(nonce), Ok(body)) = (outgoing.client_msg_id, serde_json::to_value(&response)) {
+        if let Err(e) = state.nonces.mark_delivered(&conversation_id, nonce, &body).await {
+            warn!("Failed to record delivery of client_msg_id {}: {}", nonce, e);
+        }
+    }
+    
+    let record = crate::messenger_gateway::idempotency::IdempotencyRecord {
+        status: "completed".to_string(),
+        // Fix #15: Handle JSON serialization errors gracefully
+        response_body: serde_json::to_value(&response).ok(),
+        created_event_ids: event_ids,
+        created_at: OffsetDateTime::now_utc(),
+    };
+    // Fix #4: Async store (ignore errors - idempotency is best-effort)
+    let _ = state.idempotency.store(idempotency_key, tenant_id, record).await;
+    
+    Ok(Json(response))
+}
+
+/// A message about to be committed, sent now or by the scheduler
+pub(crate) struct OutgoingMessage<'a> {
+    pub conversation_id: &'a str,
+    pub from: &'a str,
+    pub tenant_id: &'a str,
+    pub content: &'a str,
+    pub message_type: &'a str,
+    pub tentative_id: Option<&'a str>,
+    /// Client nonce of the send; recorded on the atom
+    pub client_msg_id: Option<&'a str>,
+    /// Set when the message was scheduled; recorded on the atom
+    pub schedule_id: Option<&'a str>,
+}
+
+/// A message.created link accepted by the ledger
+pub(crate) struct CommittedMessage {
+    pub message_id: String,
+    pub entry: crate::db::LedgerEntry,
+}
+
+/// Commit message.created to C.Messenger and store the content off-ledger
+pub(crate) async fn commit_message(
+    state: &GatewayState,
+    msg: &OutgoingMessage<'_>,
+) -> Result<CommittedMessage, (StatusCode, String)> {
+    crate::messenger_v1::require_sender(&state.pool, msg.conversation_id, msg.from).await?;
+    throttle_sender(state, msg).await?;
     let message_id = format!("msg_{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
     
-    // 4. Commit message.created to UBL (C.Messenger) first
     let now = OffsetDateTime::now_utc();
     let now_iso = now.format(&time::format_description::well_known::Rfc3339)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Time format error: {}", e)))?;
     
     // Hash content for privacy
-    let content_hash = crate::messenger_v1::blake3_hex(&req.content);
+    let content_hash = crate::messenger_v1::blake3_hex(msg.content);
     
     // Build canonical atom
     // Fix #5: Include tenant_id for proper isolation
-    let atom = serde_json::json!({
+    let mut atom = serde_json::json!({
         "content_hash": content_hash,
-        "conversation_id": conversation_id.clone(),
+        "conversation_id": msg.conversation_id,
         "created_at": now_iso,
-        "from": user.sid,
+        "from": msg.from,
         "id": message_id.clone(),
-        "message_type": req.message_type.as_deref().unwrap_or("text"),
-        "tenant_id": tenant_id,
+        "message_type": msg.message_type,
+        "tenant_id": msg.tenant_id,
         "type": "message.created"
     });
+    if let Some(schedule_id) = msg.schedule_id {
+        atom["schedule_id"] = serde_json::json!(schedule_id);
+    }
+    if let Some(client_msg_id) = msg.client_msg_id {
+        atom["client_msg_id"] = serde_json::json!(client_msg_id);
+    }
     
     // Canonicalize and hash
     let atom_bytes = ubl_atom::canonicalize(&atom)
@@ -202,6 +429,7 @@ async fn post_message(
             previous_hash: "0x00".to_string(),
             link_hash: "0x00".to_string(),
             ts_unix_ms: 0,
+            hlc: crate::hlc::Hlc::ZERO,
         });
     
     // Build and SIGN link draft (Fix #1: Real Ed25519)
@@ -217,49 +445,177 @@ async fn post_message(
         author_pubkey: String::new(), // Will be set by sign_link_draft
         signature: String::new(),     // Will be set by sign_link_draft
         pact: None,
+        tentative_id: msg.tentative_id.map(str::to_string),
     };
     sign_link_draft(&mut link);
     
     // Commit to ledger
     let entry = state.ledger.append(&link).await
         .map_err(|e| (StatusCode::CONFLICT, format!("Commit failed: {:?}", e)))?;
+    if let Some(client_msg_id) = msg.client_msg_id {
+        if let Err(e) = state.nonces.mark_committed(msg.conversation_id, client_msg_id, &message_id, &entry.entry_hash, entry.sequence).await {
+            error!("Failed to record commit of client_msg_id {}: {}", client_msg_id, e);
+        }
+    }
+    if let Some(tail) = &state.tail {
+        tail.notify(TailEntry::committed(&link, &entry));
+    }
     
     // Store message content
-    crate::messenger_v1::store_message_content(&state.pool, &message_id, &req.content, &content_hash).await
+    crate::messenger_v1::store_message_content(&state.pool, &message_id, msg.content, &content_hash).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
-    // 5. Call Office to ingest message
+    // Project now so announcements reach member timelines
+    let projection = crate::projections::MessagesProjection::new(state.pool.clone());
+    if let Err(e) = projection.process_event("message.created", &atom, &entry.entry_hash, entry.sequence).await {
+        error!("Failed to project message {}: {}", message_id, e);
+    }
+    let timeline = crate::projections::TimelineProjection::new(state.pool.clone());
+    if let Err(e) = timeline.add_item(msg.tenant_id, msg.conversation_id, "message", &atom, entry.sequence, entry.hlc).await {
+        error!("Failed to update timeline: {}", e);
+    }
+    
+    // Mentions are recorded after the message; they never fail the send
+    if let Err(e) = record_mentions(state, msg, &message_id, &entry).await {
+        error!("Failed to record mentions of {}: {}", message_id, e);
+    }
+    
+    Ok(CommittedMessage { message_id, entry })
+}
+
+/// Count the send against the sender's throttle. A send that mutes an agent
+/// is refused like the rest, and Office is told so the guardian hears of it.
+async fn throttle_sender(state: &GatewayState, msg: &OutgoingMessage<'_>) -> Result<(), (StatusCode, String)> {
+    let refusal = match state.throttle.admit(msg.tenant_id, msg.from, msg.conversation_id).await {
+        Ok(()) => return Ok(()),
+        Err(refusal) => refusal,
+    };
+    
+    crate::metrics::RATE_LIMIT_REJECTIONS.with_label_values(&["message_send"]).inc();
+    let muted_until = |mute: &SenderMute| crate::timestamps::rfc3339_utc(mute.muted_until_ms);
+    match refusal {
+        Refusal::Limited { retry_after_secs } => {
+            warn!("🚦 {} rate limited in {} (retry in {}s)", msg.from, msg.conversation_id, retry_after_secs);
+            Err((StatusCode::TOO_MANY_REQUESTS, format!("Too many messages, retry in {}s", retry_after_secs)))
+        }
+        Refusal::Muted(mute) => Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("{} is muted until {}", mute.sid, muted_until(&mute)),
+        )),
+        Refusal::NewlyMuted(mute) => {
+            warn!(
+                "🔇 {} muted until {}: {} sends in {}s in {}",
+                mute.sid, muted_until(&mute), mute.sends_in_window, mute.window_secs, mute.conversation_id
+            );
+            let notice = super::office_client::AgentMutedNotice {
+                mute_id: mute.mute_id.clone(),
+                sid: mute.sid.clone(),
+                tenant_id: msg.tenant_id.to_string(),
+                conversation_id: mute.conversation_id.clone(),
+                sends_in_window: mute.sends_in_window,
+                window_secs: mute.window_secs,
+                muted_until_ms: mute.muted_until_ms,
+            };
+            // The refusal does not wait for Office
+            let office = state.office_client.clone();
+            let throttle = state.throttle.clone();
+            tokio::spawn(async move {
+                match office.agent_muted(&notice).await {
+                    Ok(()) => {
+                        if let Err(e) = throttle.mark_notified(&notice.mute_id).await {
+                            error!("Failed to mark mute {} notified: {}", notice.mute_id, e);
+                        }
+                    }
+                    Err(e) => error!("❌ Guardian notice for mute {} failed: {}", notice.mute_id, e),
+                }
+            });
+            Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Message burst detected; {} is muted until {}", mute.sid, muted_until(&mute)),
+            ))
+        }
+        Refusal::Database(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// Commit one mention.created atom for the conversation members `@`-mentioned
+/// in the message, linked to its ledger entry
+async fn record_mentions(
+    state: &GatewayState,
+    msg: &OutgoingMessage<'_>,
+    message_id: &str,
+    entry: &crate::db::LedgerEntry,
+) -> Result<(), String> {
+    let members = crate::projections::scope::conversation_access(&state.pool, msg.conversation_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|c| c.participants)
+        .unwrap_or_default();
+    let mentioned = mentions::parse_mentions(msg.content, msg.from, &members);
+    if mentioned.is_empty() {
+        return Ok(());
+    }
+    
+    let atom = serde_json::json!({
+        "conversation_id": msg.conversation_id,
+        "from": msg.from,
+        "mentioned": mentioned,
+        "message_entry_hash": entry.entry_hash,
+        "message_id": message_id,
+        "tenant_id": msg.tenant_id,
+        "type": mentions::MENTION_TYPE
+    });
+    let mention_entry = card_provenance::append_atom(state, "C.Messenger", atom.clone()).await?;
+    crate::projections::MessagesProjection::new(state.pool.clone())
+        .process_event(mentions::MENTION_TYPE, &atom, &mention_entry.entry_hash, mention_entry.sequence)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    info!("🔔 {} mentioned {:?} in {}", msg.from, mentioned, message_id);
+    Ok(())
+}
+
+/// Hand a committed message to Office, issuing the card it proposes.
+/// Returns the client response and the events Office created.
+pub(crate) async fn deliver_to_office(
+    state: &GatewayState,
+    msg: &OutgoingMessage<'_>,
+    committed: &CommittedMessage,
+) -> Result<(PostMessageResponse, Vec<String>), (StatusCode, String)> {
     let office_req = super::office_client::IngestMessageRequest {
-        conversation_id: conversation_id.clone(),
-        message_id: message_id.clone(),
-        from: user.sid.clone(),
-        content: req.content.clone(),
-        tenant_id: tenant_id.to_string(),
+        conversation_id: msg.conversation_id.to_string(),
+        message_id: committed.message_id.clone(),
+        from: msg.from.to_string(),
+        content: msg.content.to_string(),
+        tenant_id: msg.tenant_id.to_string(),
+        client_msg_id: msg.client_msg_id.map(str::to_string),
     };
     
     match state.office_client.ingest_message(&office_req).await {
         Ok(office_resp) => {
             info!("✅ Office processed message: action={:?}", office_resp.action);
             
-            // Store idempotency record
-            let response = PostMessageResponse {
-                message_id: message_id.clone(),
-                hash: entry.entry_hash.clone(),
-                sequence: entry.sequence,
-                action: format!("{:?}", office_resp.action),
+            // Record the card in the ledger so its buttons can be verified later
+            let issued = match &office_resp.card {
+                Some(card) => Some(card_provenance::issue_card(state, card, msg.tenant_id).await.map_err(|e| {
+                    error!("❌ Card issuance failed: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("Card issuance failed: {}", e))
+                })?),
+                None => None,
             };
             
-            let record = crate::messenger_gateway::idempotency::IdempotencyRecord {
-                status: "completed".to_string(),
-                // Fix #15: Handle JSON serialization errors gracefully
-                response_body: serde_json::to_value(&response).ok(),
-                created_event_ids: office_resp.event_ids.clone(),
-                created_at: OffsetDateTime::now_utc(),
+            let response = PostMessageResponse {
+                message_id: committed.message_id.clone(),
+                hash: committed.entry.entry_hash.clone(),
+                sequence: committed.entry.sequence,
+                action: format!("{:?}", office_resp.action),
+                card: office_resp.card.clone(),
+                card_hash: issued.as_ref().map(|i| i.card_hash.clone()),
+                card_nonce: issued.map(|i| i.nonce),
+                tentative_id: msg.tentative_id.map(str::to_string),
+                client_msg_id: msg.client_msg_id.map(str::to_string),
             };
-            // Fix #4: Async store (ignore errors - idempotency is best-effort)
-            let _ = state.idempotency.store(idempotency_key, tenant_id, record).await;
-            
-            Ok(Json(response))
+            Ok((response, office_resp.event_ids))
         }
         Err(e) => {
             error!("❌ Office ingest_message failed: {}", e);
@@ -338,6 +694,24 @@ async fn job_action(
         info!("✅ FSM pre-check passed: {} → {} (job: {})", current_state, to_state, job_id);
     }
     
+    // Card provenance: the button must come from a card UBL issued for this job
+    let presented = card_provenance::PresentedAction {
+        job_id: &job_id,
+        card_id: &req.card_id,
+        card_hash: &req.card_hash,
+        nonce: &req.nonce,
+        button_id: &req.button_id,
+        action_type: &req.action_type,
+        has_input: req.input_data.is_some(),
+    };
+    if let Err(reason) = card_provenance::verify_and_claim(&state, tenant_id, &presented, &user.sid).await {
+        if reason.starts_with("DatabaseError") {
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, reason));
+        }
+        card_provenance::audit_rejection(&state, tenant_id, &presented, &user.sid, &reason).await;
+        return Err((StatusCode::FORBIDDEN, format!("CardProvenanceRejected: {}", reason)));
+    }
+    
     // 3. Call Office to handle job action
     let office_req = super::office_client::JobActionRequest {
         job_id: job_id.clone(),
@@ -373,26 +747,315 @@ async fn job_action(
         }
         Err(e) => {
             error!("❌ Office job_action failed: {}", e);
+            // The action never happened; the card can be used again
+            card_provenance::release_claim(&state, &req.card_id, &user.sid).await;
             Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
         }
     }
 }
 
+/// POST /v1/conversations/:id/scheduled
+/// Keep a message for delivery at `send_at_ms`; nothing is committed until then
+async fn schedule_message(
+    State(state): State<GatewayState>,
+    Path(conversation_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<ScheduleMessageRequest>,
+) -> Result<(StatusCode, Json<ScheduledMessage>), (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
+    
+    if req.content.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "content is empty".to_string()));
+    }
+    state.scheduled_config.check_due_at(req.send_at_ms, crate::timestamps::now_ms())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    crate::messenger_v1::require_sender(&state.pool, &conversation_id, &user.sid).await?;
+    
+    let scheduled = state.scheduled.schedule(&NewSchedule {
+        tenant_id,
+        conversation_id: &conversation_id,
+        from_sid: &user.sid,
+        content: &req.content,
+        message_type: req.message_type.as_deref().unwrap_or("text"),
+        due_at_ms: req.send_at_ms,
+    }).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    info!("⏰ {} scheduled {} in {} for {}", user.sid, scheduled.schedule_id, conversation_id, scheduled.due_at_ms);
+    Ok((StatusCode::ACCEPTED, Json(scheduled)))
+}
+
+/// GET /v1/conversations/:id/scheduled
+/// The caller's own scheduled messages in the conversation
+async fn list_scheduled(
+    State(state): State<GatewayState>,
+    Path(conversation_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<ListScheduledQuery>,
+) -> Result<Json<ScheduledListResponse>, (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    
+    let status = query_params::one_of("status", query.status.as_deref(), SCHEDULED_STATUSES, "pending")?;
+    let items = state.scheduled
+        .list(&user.sid, &conversation_id, status)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(ScheduledListResponse { items }))
+}
+
+/// DELETE /v1/scheduled/:id
+/// Cancel one of the caller's messages while it is still pending
+async fn cancel_scheduled(
+    State(state): State<GatewayState>,
+    Path(schedule_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ScheduledMessage>, (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    
+    match state.scheduled.cancel(&schedule_id, &user.sid).await {
+        Ok(cancelled) => {
+            info!("⏰ {} cancelled scheduled message {}", user.sid, schedule_id);
+            Ok(Json(cancelled))
+        }
+        // Other authors' schedules are reported as missing too
+        Err(CancelError::NotFound) => Err((StatusCode::NOT_FOUND, format!("Scheduled message {} not found", schedule_id))),
+        Err(CancelError::NotPending(status)) => Err((
+            StatusCode::CONFLICT,
+            format!("Scheduled message {} is {} and can no longer be cancelled", schedule_id, status),
+        )),
+        Err(CancelError::Database(e)) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// Tenant of a caller who is its owner or admin
+async fn require_tenant_admin(state: &GatewayState, user: &UserInfo) -> Result<String, (StatusCode, String)> {
+    let tenant_id = user.tenant_id.clone()
+        .ok_or((StatusCode::FORBIDDEN, "No tenant in session".to_string()))?;
+    match tenant_db::get_member_role(&state.pool, &tenant_id, &user.sid).await {
+        Ok(Some(MemberRole::Owner)) | Ok(Some(MemberRole::Admin)) => Ok(tenant_id),
+        Ok(_) => Err((StatusCode::FORBIDDEN, "Only owner or admin can manage mutes".to_string())),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+/// GET /v1/mutes
+/// Agents muted for flooding in the caller's tenant (owner/admin)
+async fn list_mutes(
+    State(state): State<GatewayState>,
+    headers: HeaderMap,
+) -> Result<Json<MuteListResponse>, (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    let tenant_id = require_tenant_admin(&state, &user).await?;
+    
+    let items = state.throttle.list_active(&tenant_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(MuteListResponse { items }))
+}
+
+/// DELETE /v1/mutes/:sid
+/// Lift an agent's mute before it runs out (owner/admin)
+async fn lift_mute(
+    State(state): State<GatewayState>,
+    Path(sid): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<LiftMuteResponse>, (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    let tenant_id = require_tenant_admin(&state, &user).await?;
+    
+    let lifted = state.throttle.lift(&tenant_id, &sid, &user.sid).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if lifted.is_empty() {
+        return Err((StatusCode::NOT_FOUND, format!("{} is not muted", sid)));
+    }
+    
+    info!("🔊 {} lifted the mute of {}", user.sid, sid);
+    Ok(Json(LiftMuteResponse { sid, lifted }))
+}
+
+/// POST /v1/messages/:id/redact
+/// Redact a message (its sender, or an owner/admin of the caller's tenant).
+/// Commits `message.redacted`; the message itself stays in the ledger and
+/// reads hide it through `projections::visibility`.
+async fn redact_message(
+    State(state): State<GatewayState>,
+    Path(message_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<RedactMessageRequest>,
+) -> Result<Json<RedactMessageResponse>, (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    let reason = req.reason.trim();
+    if reason.is_empty() || reason.chars().count() > MAX_REASON_CHARS {
+        return Err((StatusCode::BAD_REQUEST, format!("reason must be 1-{} characters", MAX_REASON_CHARS)));
+    }
+
+    let not_found = || (StatusCode::NOT_FOUND, format!("Message {} not found", message_id));
+    let message = MessagesProjection::new(state.pool.clone()).get_message(&message_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(not_found)?;
+    let access = crate::projections::scope::conversation_access(&state.pool, &message.conversation_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let member = access.is_some_and(|c| c.owner.as_deref() == Some(user.sid.as_str()) || c.participants.contains(&user.sid));
+    if !member {
+        return Err(not_found());
+    }
+    if message.from_id != user.sid {
+        let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
+        match tenant_db::get_member_role(&state.pool, tenant_id, &user.sid).await {
+            Ok(Some(MemberRole::Owner)) | Ok(Some(MemberRole::Admin)) => {}
+            Ok(_) => return Err((StatusCode::FORBIDDEN, "Only the sender or a tenant owner/admin can redact".to_string())),
+            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        }
+    }
+
+    let redactions = RedactionsProjection::new(state.pool.clone());
+    let existing = redactions.for_messages(std::slice::from_ref(&message_id)).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if existing.contains_key(&message_id) {
+        return Err((StatusCode::CONFLICT, format!("Message {} is already redacted", message_id)));
+    }
+
+    let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
+    let atom = serde_json::json!({
+        "conversation_id": message.conversation_id,
+        "message_id": message_id,
+        "reason": reason,
+        "redacted_at": crate::timestamps::rfc3339_utc(crate::timestamps::now_ms()),
+        "redacted_by": user.sid,
+        "tenant_id": tenant_id,
+        "type": REDACTION_TYPE
+    });
+    let entry = card_provenance::append_atom(&state, "C.Messenger", atom.clone()).await
+        .map_err(|e| (StatusCode::CONFLICT, e))?;
+
+    if let Err(e) = MessagesProjection::new(state.pool.clone())
+        .process_event(REDACTION_TYPE, &atom, &entry.entry_hash, entry.sequence)
+        .await
+    {
+        error!("Failed to project redaction of {}: {}", message_id, e);
+    }
+    let timeline = crate::projections::TimelineProjection::new(state.pool.clone());
+    if let Err(e) = timeline.add_item(tenant_id, &message.conversation_id, "system", &atom, entry.sequence, entry.hlc).await {
+        error!("Failed to update timeline: {}", e);
+    }
+
+    info!("🪦 {} redacted message {}", user.sid, message_id);
+    Ok(Json(RedactMessageResponse { message_id, hash: entry.entry_hash, sequence: entry.sequence }))
+}
+
 /// GET /v1/conversations/:id/timeline
-/// Query timeline from projections
+/// Query timeline from projections. The first page starts with the latest
+/// conversation summary, pinned (`"pinned": true`).
 async fn get_timeline(
     State(state): State<GatewayState>,
     Path(conversation_id): Path<String>,
+    headers: HeaderMap,
     Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Result<Json<TimelineResponse>, (StatusCode, String)> {
-    // TODO: Query projection_timeline_items
-    // For now, return empty
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    let access = crate::projections::scope::conversation_access(&state.pool, &conversation_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let member = access.is_some_and(|c| c.owner.as_deref() == Some(user.sid.as_str()) || c.participants.contains(&user.sid));
+    if !member {
+        return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string()));
+    }
+    
+    let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
+    let cursor = params.get("cursor").map(String::as_str);
+    let limit = TIMELINE.resolve(params.get("limit").and_then(|l| l.parse().ok()));
+    let items = crate::projections::TimelineProjection::new(state.pool.clone())
+        .get_timeline(tenant_id, &conversation_id, cursor, limit)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // Taken before redacted items are dropped, so paging moves past them
+    let next_cursor = items.iter()
+        .max_by_key(|i| i["hlc"].as_str().unwrap_or_default())
+        .and_then(|i| i["cursor"].as_str())
+        .or(cursor)
+        .unwrap_or("0:0")
+        .to_string();
+    let redactions = RedactionsProjection::new(state.pool.clone())
+        .for_timeline(&items)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut items = Visibility::Member.apply_timeline(items, &redactions);
+    
+    if cursor.is_none() {
+        let latest = crate::projections::SummaryProjection::new(state.pool.clone())
+            .latest(&conversation_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if let Some(summary) = latest {
+            let created_at_ms = crate::timestamps::from_datetime(summary.created_at);
+            items.insert(0, serde_json::json!({
+                "cursor": format!("summary:{}", summary.summary_id),
+                "item_type": "summary",
+                "item_data": summary,
+                "created_at": crate::timestamps::rfc3339_utc(created_at_ms),
+                "created_at_ms": created_at_ms,
+                "pinned": true,
+            }));
+        }
+    }
+    
     Ok(Json(TimelineResponse {
-        items: vec![],
-        cursor: "0:0".to_string(),
+        items,
+        cursor: next_cursor,
     }))
 }
 
+/// GET /v1/conversations/:id/blobs/:hash
+/// Image of an artifact card; only blobs a card in this conversation references
+async fn get_conversation_blob(
+    State(state): State<GatewayState>,
+    Path((conversation_id, content_hash)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let user = get_user_from_session(&state.pool, &headers).await
+        .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    let access = crate::projections::scope::conversation_access(&state.pool, &conversation_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let member = access.is_some_and(|c| c.owner.as_deref() == Some(user.sid.as_str()) || c.participants.contains(&user.sid));
+    if !member {
+        return Err((StatusCode::NOT_FOUND, "Conversation not found".to_string()));
+    }
+
+    let tenant_id = user.tenant_id.as_deref().unwrap_or("default");
+    let mime_type = crate::projections::TimelineProjection::new(state.pool.clone())
+        .artifact_blob_type(tenant_id, &conversation_id, &content_hash)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Blob not found".to_string()))?;
+
+    let blobs = state.blobs.clone();
+    let hash = content_hash.clone();
+    let bytes = tokio::task::spawn_blocking(move || blobs.get(&hash))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Blob not found".to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (axum::http::header::CONTENT_TYPE, mime_type),
+            (axum::http::header::ETAG, format!("\"{}\"", content_hash)),
+            (axum::http::header::CACHE_CONTROL, "private, max-age=31536000, immutable".to_string()),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
 /// GET /v1/jobs/:id
 /// Get job details for drawer
 async fn get_job(
@@ -483,32 +1146,120 @@ async fn get_job(
 }
 
 /// GET /v1/stream
-/// SSE delta stream
+/// SSE delta stream (429 when the tenant is at its connection limit)
 async fn get_stream(
     State(state): State<GatewayState>,
+    headers: HeaderMap,
     Query(params): Query<std::collections::HashMap<String, String>>,
-) -> impl IntoResponse {
-    let tenant_id = params.get("tenant_id").cloned().unwrap_or_else(|| "default".to_string());
+) -> Response {
+    let tenant_id = crate::sse::tenant_key(&headers, &params);
     let cursor = params.get("cursor").cloned();
     
-    let (gateway_sse, sse) = super::sse::GatewaySSE::new();
+    let Some(guard) = state.sse_connections.try_acquire(&tenant_id, state.sse_limits.max_per_tenant) else {
+        warn!("SSE connection limit reached for tenant {}", tenant_id);
+        return (StatusCode::TOO_MANY_REQUESTS, "SSE connection limit reached for tenant").into_response();
+    };
+    let (gateway_sse, sse) = GatewaySSE::new(&state.sse_limits, guard);
     
     // Emit hello event with current cursor
     let current_cursor = cursor.unwrap_or_else(|| "0:0".to_string());
     let _ = gateway_sse.emit(super::sse::DeltaEvent::Hello {
         cursor: current_cursor.clone(),
-    }).await;
+    });
     
     // TODO: Subscribe to UBL SSE tail and forward deltas
-    // For now, emit heartbeat periodically
-    let sse_clone = gateway_sse.clone();
-    tokio::spawn(async move {
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
-            let _ = sse_clone.emit(super::sse::DeltaEvent::Heartbeat).await;
-        }
-    });
+    // Heartbeats stop (and idle clients are evicted) when the stream ends
+    tokio::spawn(gateway_sse.run_heartbeat(state.sse_limits.clone()));
     
-    sse
+    sse.into_response()
 }
 
+
+/// Response samples pinned by the API contract tests (see `crate::contracts`)
+#[cfg(test)]
+pub(crate) fn contract_samples() -> Vec<(&'static str, serde_json::Value)> {
+    use serde_json::json;
+
+    let scheduled_sample = |status: &str| ScheduledMessage {
+        schedule_id: "sched_1".into(),
+        conversation_id: "conv_1".into(),
+        content: "later".into(),
+        message_type: "text".into(),
+        due_at_ms: 1,
+        status: status.into(),
+        attempts: 0,
+        last_error: Some("e".into()),
+        message_id: Some("msg_1".into()),
+        entry_hash: Some("h".into()),
+        created_at_ms: 1,
+    };
+
+    let mute_sample = SenderMute {
+        mute_id: "mute_1".into(),
+        sid: "ubl:sid:agent".into(),
+        conversation_id: "conv_1".into(),
+        sends_in_window: 10,
+        window_secs: 5,
+        muted_at_ms: 1,
+        muted_until_ms: 2,
+        guardian_notified: true,
+    };
+
+    vec![
+        (
+            "POST /v1/conversations/:id/messages",
+            json!(PostMessageResponse {
+                message_id: "msg_1".into(),
+                hash: "h".into(),
+                sequence: 1,
+                action: "committed".into(),
+                card: Some(json!({})),
+                card_hash: Some("h".into()),
+                card_nonce: Some("n".into()),
+                tentative_id: Some("tmp_1".into()),
+                client_msg_id: Some("nonce_1".into()),
+            }),
+        ),
+        (
+            "POST /v1/jobs/:id/actions",
+            json!(JobActionResponse { success: true, event_ids: vec!["e".into()] }),
+        ),
+        (
+            "POST /v1/conversations/:id/scheduled",
+            json!(scheduled_sample("pending")),
+        ),
+        (
+            "GET /v1/conversations/:id/scheduled",
+            json!(ScheduledListResponse { items: vec![scheduled_sample("pending")] }),
+        ),
+        (
+            "DELETE /v1/scheduled/:id",
+            json!(scheduled_sample("cancelled")),
+        ),
+        (
+            "GET /v1/mutes",
+            json!(MuteListResponse { items: vec![mute_sample.clone()] }),
+        ),
+        (
+            "DELETE /v1/mutes/:sid",
+            json!(LiftMuteResponse { sid: "ubl:sid:agent".into(), lifted: vec![mute_sample] }),
+        ),
+        (
+            "GET /v1/conversations/:id/timeline",
+            json!(TimelineResponse { items: vec![json!({})], cursor: "0:0".into() }),
+        ),
+        (
+            "GET /v1/jobs/:id",
+            json!(JobResponse {
+                job_id: "job_1".into(),
+                title: "t".into(),
+                goal: "g".into(),
+                state: "proposed".into(),
+                owner: json!({ "entity_id": "e", "display_name": "n", "kind": "llm" }),
+                available_actions: vec![json!({})],
+                timeline: vec![json!({})],
+                artifacts: vec![json!({})],
+            }),
+        ),
+    ]
+}
@@ -0,0 +1,391 @@
+//! Scheduled Messages (send later)
+//!
+//! `POST /v1/conversations/:id/scheduled` keeps the message in
+//! `gateway_scheduled_messages` with its due time; nothing is committed yet.
+//! [`ScheduledSender`] claims due rows and commits their `message.created`
+//! link through the same path as an immediate send, then hands the message
+//! to Office.
+//!
+//! Only the author sees and cancels a scheduled message, and only while it is
+//! still pending: once the scheduler has claimed it, it is on its way.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use super::routes::{commit_message, deliver_to_office, GatewayState, OutgoingMessage};
+use crate::timestamps::{from_datetime, to_datetime};
+
+/// Rows claimed per tick; the rest wait for the next one
+const BATCH_LIMIT: i64 = 50;
+
+/// Configuration for the scheduler and for accepting schedules
+#[derive(Clone)]
+pub struct ScheduledConfig {
+    /// How often to look for due messages (in seconds)
+    pub check_interval_secs: u64,
+    /// Furthest ahead a message may be scheduled (in seconds)
+    pub max_ahead_secs: u64,
+    /// Failed commits before a message is given up on
+    pub max_attempts: i32,
+}
+
+impl Default for ScheduledConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 5,
+            max_ahead_secs: 90 * 24 * 3600, // 90 days
+            max_attempts: 5,
+        }
+    }
+}
+
+impl ScheduledConfig {
+    /// Defaults overridden by `UBL_SCHEDULED_INTERVAL_SECS` /
+    /// `UBL_SCHEDULED_MAX_AHEAD_SECS` / `UBL_SCHEDULED_MAX_ATTEMPTS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            check_interval_secs: get("UBL_SCHEDULED_INTERVAL_SECS", defaults.check_interval_secs),
+            max_ahead_secs: get("UBL_SCHEDULED_MAX_AHEAD_SECS", defaults.max_ahead_secs),
+            max_attempts: get("UBL_SCHEDULED_MAX_ATTEMPTS", defaults.max_attempts as u64) as i32,
+        }
+    }
+
+    /// Why `due_at_ms` cannot be scheduled at `now_ms`, if it cannot
+    pub fn check_due_at(&self, due_at_ms: i64, now_ms: i64) -> Result<(), String> {
+        if due_at_ms <= now_ms {
+            return Err("send_at_ms must be in the future; send immediately instead".to_string());
+        }
+        if due_at_ms - now_ms > self.max_ahead_secs as i64 * 1000 {
+            return Err(format!("send_at_ms is more than {}s ahead", self.max_ahead_secs));
+        }
+        Ok(())
+    }
+
+    /// Status after a failed commit that was attempt number `attempts`
+    fn status_after_failure(&self, attempts: i32) -> &'static str {
+        if attempts >= self.max_attempts { "failed" } else { "pending" }
+    }
+}
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+/// One scheduled message, as its author sees it (content included)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    pub schedule_id: String,
+    pub conversation_id: String,
+    pub content: String,
+    pub message_type: String,
+    pub due_at_ms: i64,
+    /// pending | sending | sent | cancelled | failed
+    pub status: String,
+    pub attempts: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Set once sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_hash: Option<String>,
+    pub created_at_ms: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct ScheduledRow {
+    schedule_id: String,
+    tenant_id: String,
+    conversation_id: String,
+    from_sid: String,
+    content: String,
+    message_type: String,
+    due_at: OffsetDateTime,
+    status: String,
+    attempts: i32,
+    last_error: Option<String>,
+    message_id: Option<String>,
+    entry_hash: Option<String>,
+    created_at: OffsetDateTime,
+}
+
+impl From<ScheduledRow> for ScheduledMessage {
+    fn from(r: ScheduledRow) -> Self {
+        Self {
+            schedule_id: r.schedule_id,
+            conversation_id: r.conversation_id,
+            content: r.content,
+            message_type: r.message_type,
+            due_at_ms: from_datetime(r.due_at),
+            status: r.status,
+            attempts: r.attempts,
+            last_error: r.last_error,
+            message_id: r.message_id,
+            entry_hash: r.entry_hash,
+            created_at_ms: from_datetime(r.created_at),
+        }
+    }
+}
+
+const COLUMNS: &str = "schedule_id, tenant_id, conversation_id, from_sid, content, message_type, \
+                       due_at, status, attempts, last_error, message_id, entry_hash, created_at";
+
+/// Why a cancel did not happen
+#[derive(Debug)]
+pub enum CancelError {
+    /// No such schedule for this author
+    NotFound,
+    /// Already claimed, sent, cancelled or failed
+    NotPending(String),
+    Database(sqlx::Error),
+}
+
+/// A message to schedule
+pub struct NewSchedule<'a> {
+    pub tenant_id: &'a str,
+    pub conversation_id: &'a str,
+    pub from_sid: &'a str,
+    pub content: &'a str,
+    pub message_type: &'a str,
+    pub due_at_ms: i64,
+}
+
+/// Persistent send-later queue backed by Postgres
+#[derive(Clone)]
+pub struct ScheduledStore {
+    pool: PgPool,
+}
+
+impl ScheduledStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn schedule(&self, new: &NewSchedule<'_>) -> Result<ScheduledMessage, sqlx::Error> {
+        let schedule_id = format!("sched_{}", uuid::Uuid::new_v4().simple());
+        let row = sqlx::query_as::<_, ScheduledRow>(&format!(
+            r#"
+            INSERT INTO gateway_scheduled_messages
+                (schedule_id, tenant_id, conversation_id, from_sid, content, message_type, due_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(&schedule_id)
+        .bind(new.tenant_id)
+        .bind(new.conversation_id)
+        .bind(new.from_sid)
+        .bind(new.content)
+        .bind(new.message_type)
+        .bind(to_datetime(new.due_at_ms))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.into())
+    }
+
+    /// The author's scheduled messages in a conversation with `status`, soonest first
+    pub async fn list(
+        &self,
+        from_sid: &str,
+        conversation_id: &str,
+        status: &str,
+    ) -> Result<Vec<ScheduledMessage>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, ScheduledRow>(&format!(
+            r#"
+            SELECT {COLUMNS} FROM gateway_scheduled_messages
+            WHERE from_sid = $1 AND conversation_id = $2 AND status = $3
+            ORDER BY due_at, schedule_id
+            "#
+        ))
+        .bind(from_sid)
+        .bind(conversation_id)
+        .bind(status)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Cancel a pending message of `from_sid`
+    pub async fn cancel(&self, schedule_id: &str, from_sid: &str) -> Result<ScheduledMessage, CancelError> {
+        let cancelled = sqlx::query_as::<_, ScheduledRow>(&format!(
+            r#"
+            UPDATE gateway_scheduled_messages SET status = 'cancelled', updated_at = NOW()
+            WHERE schedule_id = $1 AND from_sid = $2 AND status = 'pending'
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(schedule_id)
+        .bind(from_sid)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(CancelError::Database)?;
+        if let Some(row) = cancelled {
+            return Ok(row.into());
+        }
+
+        let status: Option<String> = sqlx::query_scalar(
+            "SELECT status FROM gateway_scheduled_messages WHERE schedule_id = $1 AND from_sid = $2",
+        )
+        .bind(schedule_id)
+        .bind(from_sid)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(CancelError::Database)?;
+        Err(status.map_or(CancelError::NotFound, CancelError::NotPending))
+    }
+
+    /// Claim due pending rows (pending → sending); concurrent schedulers skip
+    /// each other's rows
+    async fn claim_due(&self, limit: i64) -> Result<Vec<ScheduledRow>, sqlx::Error> {
+        sqlx::query_as::<_, ScheduledRow>(&format!(
+            r#"
+            UPDATE gateway_scheduled_messages SET status = 'sending', updated_at = NOW()
+            WHERE schedule_id IN (
+                SELECT schedule_id FROM gateway_scheduled_messages
+                WHERE status = 'pending' AND due_at <= NOW()
+                ORDER BY due_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn mark_sent(&self, schedule_id: &str, message_id: &str, entry_hash: &str, sequence: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE gateway_scheduled_messages
+            SET status = 'sent', message_id = $2, entry_hash = $3, sequence = $4,
+                attempts = attempts + 1, last_error = NULL, updated_at = NOW()
+            WHERE schedule_id = $1
+            "#,
+        )
+        .bind(schedule_id)
+        .bind(message_id)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_failed_attempt(&self, schedule_id: &str, attempts: i32, status: &str, error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE gateway_scheduled_messages
+            SET status = $2, attempts = $3, last_error = $4, updated_at = NOW()
+            WHERE schedule_id = $1
+            "#,
+        )
+        .bind(schedule_id)
+        .bind(status)
+        .bind(attempts)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SCHEDULER
+// ============================================================================
+
+/// Scheduled Sender - commits due messages to C.Messenger
+pub struct ScheduledSender {
+    state: GatewayState,
+    config: ScheduledConfig,
+}
+
+impl ScheduledSender {
+    pub fn new(pool: PgPool, office_url: String, config: ScheduledConfig) -> Self {
+        Self { state: GatewayState::new(pool, office_url), config }
+    }
+
+    /// Start the scheduling loop (runs forever)
+    pub async fn run(self) {
+        info!("⏰ Scheduled sender started - checking every {}s", self.config.check_interval_secs);
+
+        let mut tick = interval(Duration::from_secs(self.config.check_interval_secs));
+
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = self.send_due().await {
+                error!("❌ Scheduled sender error: {}", e);
+            }
+        }
+    }
+
+    async fn send_due(&self) -> Result<(), sqlx::Error> {
+        let store = &self.state.scheduled;
+        for row in store.claim_due(BATCH_LIMIT).await? {
+            let outgoing = OutgoingMessage {
+                conversation_id: &row.conversation_id,
+                from: &row.from_sid,
+                tenant_id: &row.tenant_id,
+                content: &row.content,
+                message_type: &row.message_type,
+                tentative_id: None,
+                client_msg_id: None,
+                schedule_id: Some(&row.schedule_id),
+            };
+
+            let committed = match commit_message(&self.state, &outgoing).await {
+                Ok(committed) => committed,
+                Err((_, e)) => {
+                    let attempts = row.attempts + 1;
+                    let status = self.config.status_after_failure(attempts);
+                    warn!("⚠️  Scheduled message {} not committed (attempt {}, now {}): {}", row.schedule_id, attempts, status, e);
+                    store.mark_failed_attempt(&row.schedule_id, attempts, status, &e).await?;
+                    continue;
+                }
+            };
+            store
+                .mark_sent(&row.schedule_id, &committed.message_id, &committed.entry.entry_hash, committed.entry.sequence)
+                .await?;
+            info!("⏰ Scheduled message {} sent as {} (seq {})", row.schedule_id, committed.message_id, committed.entry.sequence);
+
+            // The message is in the ledger either way; Office failing is not a retry
+            if let Err((_, e)) = deliver_to_office(&self.state, &outgoing, &committed).await {
+                warn!("⚠️  Scheduled message {} committed but Office ingest failed: {}", row.schedule_id, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_due_at() {
+        let config = ScheduledConfig { max_ahead_secs: 60, ..Default::default() };
+        assert!(config.check_due_at(1_001, 1_000).is_ok());
+        assert!(config.check_due_at(61_000, 1_000).is_ok());
+        assert!(config.check_due_at(1_000, 1_000).is_err());
+        assert!(config.check_due_at(500, 1_000).is_err());
+        assert!(config.check_due_at(61_001, 1_000).is_err());
+    }
+
+    #[test]
+    fn test_status_after_failure() {
+        let config = ScheduledConfig { max_attempts: 3, ..Default::default() };
+        assert_eq!(config.status_after_failure(1), "pending");
+        assert_eq!(config.status_after_failure(2), "pending");
+        assert_eq!(config.status_after_failure(3), "failed");
+    }
+}
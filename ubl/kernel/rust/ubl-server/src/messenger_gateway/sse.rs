@@ -2,57 +2,156 @@
 //!
 //! Emits SSE deltas to frontend clients.
 //! Events: timeline.append, job.update, presence.update, conversation.update
+//!
+//! Each stream holds a per-tenant connection slot (see `crate::sse`), gets a
+//! `heartbeat` delta every `SseLimits::heartbeat`, and is closed with a
+//! terminal `evicted` delta when the client stops draining its buffer or no
+//! deltas other than heartbeats were sent for `SseLimits::idle_timeout`.
 
 use axum::response::sse::{Event, Sse};
 use futures_util::Stream;
 use serde::Serialize;
 use std::convert::Infallible;
-use tokio::sync::mpsc;
-use tokio_stream::wrappers::ReceiverStream;
-use tokio_stream::StreamExt;
-use tracing::{debug, error};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, warn};
+
+use crate::sse::{ConnectionGuard, SseLimits, SSE_EVICTIONS};
 
 /// SSE delta event types
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum DeltaEvent {
     Hello { cursor: String },
-    TimelineAppend { conversation_id: String, item: serde_json::Value },
+    TimelineAppend {
+        conversation_id: String,
+        item: serde_json::Value,
+        /// Client tentative id of the committed link, for optimistic UI reconciliation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tentative_id: Option<String>,
+    },
     JobUpdate { job_id: String, update: serde_json::Value },
     PresenceUpdate { entity_id: String, state: String },
     ConversationUpdate { conversation_id: String, update: serde_json::Value },
     Heartbeat,
     Error { message: String },
+    /// Terminal: the server is closing this stream (reason: "lagged" | "idle")
+    Evicted { reason: String },
+}
+
+/// Why `emit` refused an event
+#[derive(Debug, PartialEq, Eq)]
+pub enum EmitError {
+    /// Client disconnected
+    Closed,
+    /// Client stopped draining its buffer and is being evicted
+    Lagged,
 }
 
 /// SSE stream handler for Gateway
 #[derive(Clone)]
 pub struct GatewaySSE {
     sender: mpsc::Sender<DeltaEvent>,
+    lagged: Arc<AtomicBool>,
+    last_activity: Arc<Mutex<Instant>>,
 }
 
 impl GatewaySSE {
-    pub fn new() -> (Self, Sse<impl Stream<Item = Result<Event, Infallible>>>) {
-        let (tx, rx) = mpsc::channel(128);
-        
-        let stream = ReceiverStream::new(rx).map(|event| {
-            let json = serde_json::to_string(&event).unwrap_or_default();
-            Ok(Event::default()
-                .event(event_type_name(&event))
-                .data(json))
-        });
-
-        let sse = Sse::new(stream)
-            .keep_alive(axum::response::sse::KeepAlive::default());
-
-        (Self { sender: tx }, sse)
+    /// Open a stream. The connection guard is released when the client goes away.
+    pub fn new(limits: &SseLimits, guard: ConnectionGuard) -> (Self, Sse<impl Stream<Item = Result<Event, Infallible>>>) {
+        let (tx, mut rx) = mpsc::channel(128);
+        let lagged = Arc::new(AtomicBool::new(false));
+
+        let stream = {
+            let lagged = lagged.clone();
+            async_stream::stream! {
+                let _guard = guard;
+                while let Some(event) = rx.recv().await {
+                    if lagged.load(Ordering::Acquire) {
+                        break;
+                    }
+                    yield Ok(to_sse_event(&event));
+                    if matches!(event, DeltaEvent::Evicted { .. }) {
+                        return;
+                    }
+                }
+                if lagged.load(Ordering::Acquire) {
+                    // Skip the backlog; the client resyncs from its cursor on reconnect
+                    SSE_EVICTIONS.with_label_values(&["lagged"]).inc();
+                    yield Ok(to_sse_event(&DeltaEvent::Evicted { reason: "lagged".into() }));
+                }
+            }
+        };
+
+        let sse = Sse::new(stream).keep_alive(limits.keep_alive());
+
+        (
+            Self {
+                sender: tx,
+                lagged,
+                last_activity: Arc::new(Mutex::new(Instant::now())),
+            },
+            sse,
+        )
     }
 
-    pub async fn emit(&self, event: DeltaEvent) -> Result<(), mpsc::error::SendError<DeltaEvent>> {
-        self.sender.send(event).await
+    /// Queue an event without waiting; a full buffer marks the client as lagged
+    pub fn emit(&self, event: DeltaEvent) -> Result<(), EmitError> {
+        if self.lagged.load(Ordering::Acquire) {
+            return Err(EmitError::Lagged);
+        }
+        let is_activity = !matches!(event, DeltaEvent::Heartbeat);
+        match self.sender.try_send(event) {
+            Ok(()) => {
+                if is_activity {
+                    *self.last_activity.lock().expect("activity lock poisoned") = Instant::now();
+                }
+                Ok(())
+            }
+            Err(TrySendError::Full(_)) => {
+                warn!("Gateway SSE client is not draining its buffer, evicting");
+                self.lagged.store(true, Ordering::Release);
+                Err(EmitError::Lagged)
+            }
+            Err(TrySendError::Closed(_)) => Err(EmitError::Closed),
+        }
+    }
+
+    /// Send heartbeats until the client leaves, lags, or goes idle.
+    ///
+    /// Returns once the stream is finished so the task does not outlive it.
+    pub async fn run_heartbeat(self, limits: SseLimits) {
+        let mut ticker = tokio::time::interval(limits.heartbeat);
+        ticker.tick().await; // first tick is immediate
+        loop {
+            tokio::select! {
+                _ = self.sender.closed() => {
+                    debug!("Gateway SSE client disconnected");
+                    return;
+                }
+                _ = ticker.tick() => {}
+            }
+
+            let idle_for = self.last_activity.lock().expect("activity lock poisoned").elapsed();
+            if idle_for >= limits.idle_timeout {
+                SSE_EVICTIONS.with_label_values(&["idle"]).inc();
+                let _ = self.emit(DeltaEvent::Evicted { reason: "idle".into() });
+                return;
+            }
+            if self.emit(DeltaEvent::Heartbeat).is_err() {
+                return;
+            }
+        }
     }
 }
 
+fn to_sse_event(event: &DeltaEvent) -> Event {
+    let json = serde_json::to_string(event).unwrap_or_default();
+    Event::default().event(event_type_name(event)).data(json)
+}
+
 fn event_type_name(event: &DeltaEvent) -> &str {
     match event {
         DeltaEvent::Hello { .. } => "hello",
@@ -62,6 +161,44 @@ fn event_type_name(event: &DeltaEvent) -> &str {
         DeltaEvent::ConversationUpdate { .. } => "conversation.update",
         DeltaEvent::Heartbeat => "heartbeat",
         DeltaEvent::Error { .. } => "error",
+        DeltaEvent::Evicted { .. } => "evicted",
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sse::ConnectionRegistry;
+
+    #[tokio::test]
+    async fn test_full_buffer_marks_client_lagged() {
+        let registry = ConnectionRegistry::default();
+        let guard = registry.try_acquire("T.Gw", 1).unwrap();
+        let (gw, _sse) = GatewaySSE::new(&SseLimits::default(), guard);
+
+        for _ in 0..128 {
+            gw.emit(DeltaEvent::Heartbeat).unwrap();
+        }
+        assert_eq!(gw.emit(DeltaEvent::Heartbeat), Err(EmitError::Lagged));
+        // Once lagged, further emits are refused without touching the buffer
+        assert_eq!(
+            gw.emit(DeltaEvent::Error { message: "x".into() }),
+            Err(EmitError::Lagged)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_task_stops_when_client_leaves() {
+        let registry = ConnectionRegistry::default();
+        let guard = registry.try_acquire("T.Gw", 1).unwrap();
+        let (gw, sse) = GatewaySSE::new(&SseLimits::default(), guard);
+        let task = tokio::spawn(gw.clone().run_heartbeat(SseLimits::default()));
+
+        drop(sse);
+        tokio::time::timeout(std::time::Duration::from_secs(1), task)
+            .await
+            .expect("heartbeat task should exit")
+            .unwrap();
+        assert_eq!(registry.count("T.Gw"), 0);
+    }
+}
@@ -0,0 +1,219 @@
+//! Transcript Summarizer
+//!
+//! Periodically looks for conversations with enough messages past their
+//! latest summary, hands the oldest uncovered segment to Office's
+//! summarization job and commits the result to C.Messenger as a
+//! `conversation.summarized` atom. The atom lists the `message.created`
+//! entry hashes it covers and the hash of the summary text; the text itself
+//! is stored off-ledger like message content.
+//!
+//! The summary is signed by UBL, not by Office: Office only writes the text.
+
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use super::card_provenance::append_atom;
+use super::office_client::{SegmentLine, SummarizeRequest, SummarizeResponse};
+use super::routes::GatewayState;
+use crate::projections::summaries::{SegmentMessage, SummaryProjection, SUMMARY_TYPE};
+use crate::projections::MessagesProjection;
+use crate::timestamps::from_datetime;
+
+/// Conversations summarized per tick; the rest wait for the next one
+const BATCH_LIMIT: i64 = 10;
+
+/// Configuration for the summarizer
+#[derive(Clone)]
+pub struct SummaryConfig {
+    /// How often to look for conversations to summarize (in seconds)
+    pub check_interval_secs: u64,
+    /// Uncovered messages before a conversation gets a new summary
+    pub min_messages: i64,
+    /// Messages covered by one summary at most
+    pub max_messages: i64,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 300,
+            min_messages: 50,
+            max_messages: 200,
+        }
+    }
+}
+
+impl SummaryConfig {
+    /// Defaults overridden by `UBL_SUMMARY_INTERVAL_SECS` /
+    /// `UBL_SUMMARY_MIN_MESSAGES` / `UBL_SUMMARY_MAX_MESSAGES`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        let min_messages = get("UBL_SUMMARY_MIN_MESSAGES", defaults.min_messages as u64).max(1) as i64;
+        Self {
+            check_interval_secs: get("UBL_SUMMARY_INTERVAL_SECS", defaults.check_interval_secs),
+            min_messages,
+            max_messages: (get("UBL_SUMMARY_MAX_MESSAGES", defaults.max_messages as u64) as i64).max(min_messages),
+        }
+    }
+}
+
+/// The `conversation.summarized` atom for a segment (oldest message first)
+pub fn summary_atom(
+    summary_id: &str,
+    conversation_id: &str,
+    tenant_id: &str,
+    segment: &[SegmentMessage],
+    summary: &SummarizeResponse,
+    summary_hash: &str,
+) -> serde_json::Value {
+    let covered: Vec<&str> = segment.iter().map(|m| m.entry_hash.as_str()).collect();
+    serde_json::json!({
+        "conversation_id": conversation_id,
+        "covered_entry_hashes": covered,
+        "from_sequence": segment.first().map(|m| m.entry_seq).unwrap_or_default(),
+        "job_id": summary.job_id,
+        "model": summary.model,
+        "summary_hash": summary_hash,
+        "summary_id": summary_id,
+        "tenant_id": tenant_id,
+        "to_sequence": segment.last().map(|m| m.entry_seq).unwrap_or_default(),
+        "type": SUMMARY_TYPE
+    })
+}
+
+/// Transcript Summarizer - commits summaries of long conversations
+pub struct TranscriptSummarizer {
+    state: GatewayState,
+    config: SummaryConfig,
+}
+
+impl TranscriptSummarizer {
+    pub fn new(pool: PgPool, office_url: String, config: SummaryConfig) -> Self {
+        Self { state: GatewayState::new(pool, office_url), config }
+    }
+
+    /// Start the summarizing loop (runs forever)
+    pub async fn run(self) {
+        info!(
+            "🧾 Transcript summarizer started - checking every {}s (segments of {}..{} messages)",
+            self.config.check_interval_secs, self.config.min_messages, self.config.max_messages
+        );
+
+        let mut tick = interval(Duration::from_secs(self.config.check_interval_secs));
+
+        loop {
+            tick.tick().await;
+
+            if let Err(e) = self.summarize_due().await {
+                error!("❌ Transcript summarizer error: {}", e);
+            }
+        }
+    }
+
+    async fn summarize_due(&self) -> Result<(), sqlx::Error> {
+        let projection = SummaryProjection::new(self.state.pool.clone());
+        let due = projection.due(self.config.min_messages, BATCH_LIMIT).await?;
+
+        for (conversation_id, tenant_id) in due {
+            // One failing conversation does not hold back the others
+            if let Err(e) = self.summarize(&projection, &conversation_id, &tenant_id).await {
+                warn!("⚠️ Summary of {} failed: {}", conversation_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn summarize(&self, projection: &SummaryProjection, conversation_id: &str, tenant_id: &str) -> Result<(), String> {
+        let segment = projection
+            .next_segment(conversation_id, self.config.max_messages)
+            .await
+            .map_err(|e| e.to_string())?;
+        if (segment.len() as i64) < self.config.min_messages {
+            return Ok(());
+        }
+        let previous_summary = projection
+            .latest(conversation_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .and_then(|s| s.summary);
+
+        let request = SummarizeRequest {
+            conversation_id: conversation_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            previous_summary,
+            messages: segment
+                .iter()
+                .map(|m| SegmentLine {
+                    message_id: m.message_id.clone(),
+                    from: m.from_id.clone(),
+                    content: m.content.clone(),
+                    entry_hash: m.entry_hash.clone(),
+                    timestamp_ms: from_datetime(m.timestamp),
+                })
+                .collect(),
+        };
+        let response = self.state.office_client.summarize_segment(&request).await.map_err(|e| e.to_string())?;
+
+        // Text first: an atom whose text is missing cannot be shown
+        let summary_id = format!("sum_{}", uuid::Uuid::new_v4().simple());
+        let summary_hash = crate::messenger_v1::blake3_hex(&response.summary);
+        crate::messenger_v1::store_message_content(&self.state.pool, &summary_id, &response.summary, &summary_hash)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let atom = summary_atom(&summary_id, conversation_id, tenant_id, &segment, &response, &summary_hash);
+        let entry = append_atom(&self.state, "C.Messenger", atom.clone()).await?;
+        MessagesProjection::new(self.state.pool.clone())
+            .process_event(SUMMARY_TYPE, &atom, &entry.entry_hash, entry.sequence)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        info!(
+            "🧾 Summarized {} messages of {} as {} (entry {})",
+            segment.len(), conversation_id, summary_id, entry.entry_hash
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    fn message(seq: i64) -> SegmentMessage {
+        SegmentMessage {
+            message_id: format!("msg_{}", seq),
+            from_id: "alice".to_string(),
+            content: Some("hi".to_string()),
+            content_hash: "h".to_string(),
+            timestamp: OffsetDateTime::UNIX_EPOCH,
+            entry_hash: format!("0x{:02x}", seq),
+            entry_seq: seq,
+        }
+    }
+
+    #[test]
+    fn test_summary_atom_covers_segment() {
+        let segment = vec![message(3), message(7), message(9)];
+        let response = SummarizeResponse {
+            job_id: "job_sum_1".to_string(),
+            summary: "they agreed".to_string(),
+            model: "m".to_string(),
+        };
+        let atom = summary_atom("sum_1", "conv_1", "t1", &segment, &response, "0xabc");
+
+        assert_eq!(atom["type"], SUMMARY_TYPE);
+        assert_eq!(atom["from_sequence"], 3);
+        assert_eq!(atom["to_sequence"], 9);
+        assert_eq!(atom["covered_entry_hashes"], serde_json::json!(["0x03", "0x07", "0x09"]));
+        assert_eq!(atom["summary_hash"], "0xabc");
+        // The text never enters the ledger
+        assert!(!atom.to_string().contains("they agreed"));
+    }
+}
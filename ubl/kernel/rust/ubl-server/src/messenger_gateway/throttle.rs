@@ -0,0 +1,410 @@
+//! Sender Throttling — rate limits and burst muting for gateway sends
+//!
+//! An LLM stuck in a loop can flood a conversation. Every message committed
+//! through the gateway is counted per (tenant, sender): past `max_per_minute`
+//! sends are refused with 429, and `burst_count` sends within
+//! `burst_window_secs` is a burst. A bursting agent (`llm`/`app` subject) is
+//! muted for `mute_secs`; people are only slowed down, never muted.
+//!
+//! Counting is in memory. Mutes are kept in `gateway_sender_mutes`, so they
+//! survive restarts and a tenant admin can lift one early; the gateway tells
+//! Office about each new mute so the agent's guardian hears of it.
+//!
+//! Thresholds default from `UBL_THROTTLE_*`; tenants tune them under
+//! `id_tenant.settings.message_throttle` (`PUT /tenant/message_throttle`).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use time::OffsetDateTime;
+
+use crate::timestamps::{from_datetime, now_ms, to_datetime};
+
+/// Key of a tenant's overrides in `id_tenant.settings`
+pub const SETTINGS_KEY: &str = "message_throttle";
+
+/// Sends are remembered for the per-minute limit, the longest window
+const RATE_WINDOW_MS: i64 = 60_000;
+
+/// Message rate thresholds for one tenant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottlePolicy {
+    /// Sends per sender in any 60s window
+    pub max_per_minute: u32,
+    /// Sends within `burst_window_secs` that make a burst
+    pub burst_count: u32,
+    pub burst_window_secs: u32,
+    /// How long a bursting agent stays muted
+    pub mute_secs: u32,
+}
+
+impl Default for ThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            max_per_minute: 30,
+            burst_count: 10,
+            burst_window_secs: 5,
+            mute_secs: 15 * 60,
+        }
+    }
+}
+
+impl ThrottlePolicy {
+    /// Defaults overridden by `UBL_THROTTLE_MAX_PER_MINUTE` /
+    /// `UBL_THROTTLE_BURST_COUNT` / `UBL_THROTTLE_BURST_WINDOW_SECS` /
+    /// `UBL_THROTTLE_MUTE_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: u32| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        let policy = Self {
+            max_per_minute: get("UBL_THROTTLE_MAX_PER_MINUTE", defaults.max_per_minute),
+            burst_count: get("UBL_THROTTLE_BURST_COUNT", defaults.burst_count),
+            burst_window_secs: get("UBL_THROTTLE_BURST_WINDOW_SECS", defaults.burst_window_secs),
+            mute_secs: get("UBL_THROTTLE_MUTE_SECS", defaults.mute_secs),
+        };
+        if policy.validate().is_ok() { policy } else { defaults }
+    }
+
+    /// This policy with a tenant's `settings.message_throttle` overrides;
+    /// missing values keep the base ones, an invalid result is ignored
+    pub fn for_tenant(&self, settings: &Value) -> Self {
+        let overrides = &settings[SETTINGS_KEY];
+        let get = |key: &str, base: u32| {
+            overrides.get(key)
+                .and_then(Value::as_u64)
+                .and_then(|v| u32::try_from(v).ok())
+                .unwrap_or(base)
+        };
+        let policy = Self {
+            max_per_minute: get("max_per_minute", self.max_per_minute),
+            burst_count: get("burst_count", self.burst_count),
+            burst_window_secs: get("burst_window_secs", self.burst_window_secs),
+            mute_secs: get("mute_secs", self.mute_secs),
+        };
+        if policy.validate().is_ok() { policy } else { *self }
+    }
+
+    /// Why the thresholds cannot be used, if they cannot
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_per_minute == 0 || self.burst_count == 0 || self.mute_secs == 0 {
+            return Err("max_per_minute, burst_count and mute_secs must be positive".to_string());
+        }
+        if self.burst_window_secs == 0 || self.burst_window_secs > 60 {
+            return Err("burst_window_secs must be between 1 and 60".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// COUNTING
+// ============================================================================
+
+/// Outcome of counting one send
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Allowed,
+    /// Over `max_per_minute`
+    Limited { retry_after_secs: u64 },
+    /// This send would complete a burst
+    Burst { sends_in_window: u32 },
+}
+
+/// Recent send times per sender, in memory. Refused sends are not counted.
+#[derive(Clone, Default)]
+pub struct SendCounter {
+    sends: Arc<Mutex<HashMap<String, VecDeque<i64>>>>,
+}
+
+impl SendCounter {
+    pub fn record(&self, key: &str, policy: &ThrottlePolicy, now_ms: i64) -> Verdict {
+        let mut sends = self.sends.lock().unwrap();
+        sends.retain(|_, times| times.back().is_some_and(|t| now_ms - t < RATE_WINDOW_MS));
+
+        let times = sends.entry(key.to_string()).or_default();
+        while times.front().is_some_and(|t| now_ms - t >= RATE_WINDOW_MS) {
+            times.pop_front();
+        }
+
+        if times.len() >= policy.max_per_minute as usize {
+            let oldest = times.front().copied().unwrap_or(now_ms);
+            let retry_after_ms = (oldest + RATE_WINDOW_MS - now_ms).max(0) as u64;
+            return Verdict::Limited { retry_after_secs: retry_after_ms.div_ceil(1000) };
+        }
+
+        let burst_since = now_ms - policy.burst_window_secs as i64 * 1000;
+        let sends_in_window = times.iter().rev().take_while(|t| **t > burst_since).count() as u32 + 1;
+        if sends_in_window >= policy.burst_count {
+            return Verdict::Burst { sends_in_window };
+        }
+
+        times.push_back(now_ms);
+        Verdict::Allowed
+    }
+}
+
+/// Senders whose last send is older than the TTL (never inside the rate
+/// minute)
+impl crate::maintenance::Maintained for SendCounter {
+    fn entries(&self) -> usize {
+        self.sends.lock().unwrap().len()
+    }
+
+    fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize {
+        let mut sends = self.sends.lock().unwrap();
+        let before = sends.len();
+        let ttl_ms = ttl_ms.max(RATE_WINDOW_MS);
+        sends.retain(|_, times| times.back().is_some_and(|t| now_ms - t < ttl_ms));
+        before - sends.len()
+    }
+
+    fn flush(&self) -> usize {
+        let mut sends = self.sends.lock().unwrap();
+        let n = sends.len();
+        sends.clear();
+        n
+    }
+}
+
+// ============================================================================
+// MUTES
+// ============================================================================
+
+/// An agent muted for flooding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderMute {
+    pub mute_id: String,
+    pub sid: String,
+    /// Conversation the burst was detected in
+    pub conversation_id: String,
+    pub sends_in_window: i32,
+    pub window_secs: i32,
+    pub muted_at_ms: i64,
+    pub muted_until_ms: i64,
+    pub guardian_notified: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct MuteRow {
+    mute_id: String,
+    sid: String,
+    conversation_id: String,
+    sends_in_window: i32,
+    window_secs: i32,
+    muted_at: OffsetDateTime,
+    muted_until: OffsetDateTime,
+    guardian_notified_at: Option<OffsetDateTime>,
+}
+
+impl From<MuteRow> for SenderMute {
+    fn from(r: MuteRow) -> Self {
+        Self {
+            mute_id: r.mute_id,
+            sid: r.sid,
+            conversation_id: r.conversation_id,
+            sends_in_window: r.sends_in_window,
+            window_secs: r.window_secs,
+            muted_at_ms: from_datetime(r.muted_at),
+            muted_until_ms: from_datetime(r.muted_until),
+            guardian_notified: r.guardian_notified_at.is_some(),
+        }
+    }
+}
+
+const COLUMNS: &str = "mute_id, sid, conversation_id, sends_in_window, window_secs, \
+                       muted_at, muted_until, guardian_notified_at";
+
+/// Why a send was refused
+#[derive(Debug)]
+pub enum Refusal {
+    Limited { retry_after_secs: u64 },
+    /// The sender is already muted
+    Muted(SenderMute),
+    /// This send muted the sender
+    NewlyMuted(SenderMute),
+    Database(sqlx::Error),
+}
+
+/// Per-sender throttle shared by every gateway send
+#[derive(Clone)]
+pub struct Throttle {
+    pool: PgPool,
+    defaults: ThrottlePolicy,
+    counter: SendCounter,
+}
+
+impl Throttle {
+    pub fn new(pool: PgPool, defaults: ThrottlePolicy) -> Self {
+        Self { pool, defaults, counter: SendCounter::default() }
+    }
+
+    /// In-memory send counts, for [`crate::maintenance`]
+    pub fn counter(&self) -> SendCounter {
+        self.counter.clone()
+    }
+
+    /// Count one send of `sid` to `conversation_id`, or refuse it
+    pub async fn admit(&self, tenant_id: &str, sid: &str, conversation_id: &str) -> Result<(), Refusal> {
+        if let Some(mute) = self.active(tenant_id, sid).await.map_err(Refusal::Database)? {
+            return Err(Refusal::Muted(mute));
+        }
+
+        let (settings, kind): (Option<Value>, Option<String>) = sqlx::query_as(
+            r#"
+            SELECT (SELECT settings FROM id_tenant WHERE tenant_id = $1),
+                   (SELECT kind FROM id_subject WHERE sid = $2)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(sid)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(Refusal::Database)?;
+        let policy = self.defaults.for_tenant(&settings.unwrap_or(Value::Null));
+
+        match self.counter.record(&format!("{}:{}", tenant_id, sid), &policy, now_ms()) {
+            Verdict::Allowed => Ok(()),
+            Verdict::Limited { retry_after_secs } => Err(Refusal::Limited { retry_after_secs }),
+            Verdict::Burst { sends_in_window } if matches!(kind.as_deref(), Some("llm" | "app")) => {
+                let mute = self
+                    .mute(tenant_id, sid, conversation_id, sends_in_window, &policy)
+                    .await
+                    .map_err(Refusal::Database)?;
+                Err(Refusal::NewlyMuted(mute))
+            }
+            Verdict::Burst { .. } => Err(Refusal::Limited { retry_after_secs: policy.burst_window_secs as u64 }),
+        }
+    }
+
+    /// The sender's mute in force, if any
+    pub async fn active(&self, tenant_id: &str, sid: &str) -> Result<Option<SenderMute>, sqlx::Error> {
+        let row = sqlx::query_as::<_, MuteRow>(&format!(
+            r#"
+            SELECT {COLUMNS} FROM gateway_sender_mutes
+            WHERE tenant_id = $1 AND sid = $2 AND lifted_at IS NULL AND muted_until > NOW()
+            ORDER BY muted_until DESC
+            LIMIT 1
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(sid)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn mute(
+        &self,
+        tenant_id: &str,
+        sid: &str,
+        conversation_id: &str,
+        sends_in_window: u32,
+        policy: &ThrottlePolicy,
+    ) -> Result<SenderMute, sqlx::Error> {
+        let mute_id = format!("mute_{}", uuid::Uuid::new_v4().simple());
+        let row = sqlx::query_as::<_, MuteRow>(&format!(
+            r#"
+            INSERT INTO gateway_sender_mutes
+                (mute_id, tenant_id, sid, conversation_id, sends_in_window, window_secs, muted_until)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(&mute_id)
+        .bind(tenant_id)
+        .bind(sid)
+        .bind(conversation_id)
+        .bind(sends_in_window as i32)
+        .bind(policy.burst_window_secs as i32)
+        .bind(to_datetime(now_ms() + policy.mute_secs as i64 * 1000))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.into())
+    }
+
+    /// Mutes in force in a tenant, latest first
+    pub async fn list_active(&self, tenant_id: &str) -> Result<Vec<SenderMute>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, MuteRow>(&format!(
+            r#"
+            SELECT {COLUMNS} FROM gateway_sender_mutes
+            WHERE tenant_id = $1 AND lifted_at IS NULL AND muted_until > NOW()
+            ORDER BY muted_at DESC
+            "#
+        ))
+        .bind(tenant_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// End the sender's mutes in force; returns the mutes lifted
+    pub async fn lift(&self, tenant_id: &str, sid: &str, lifted_by: &str) -> Result<Vec<SenderMute>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, MuteRow>(&format!(
+            r#"
+            UPDATE gateway_sender_mutes SET lifted_at = NOW(), lifted_by = $3
+            WHERE tenant_id = $1 AND sid = $2 AND lifted_at IS NULL AND muted_until > NOW()
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(tenant_id)
+        .bind(sid)
+        .bind(lifted_by)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Record that Office accepted the guardian notice
+    pub async fn mark_notified(&self, mute_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE gateway_sender_mutes SET guardian_notified_at = NOW() WHERE mute_id = $1")
+            .bind(mute_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rate_limit_and_burst() {
+        let counter = SendCounter::default();
+        let policy = ThrottlePolicy { max_per_minute: 5, burst_count: 3, burst_window_secs: 2, mute_secs: 60 };
+
+        // Two quick sends pass, the third inside 2s is a burst and is not counted
+        assert_eq!(counter.record("t:a", &policy, 0), Verdict::Allowed);
+        assert_eq!(counter.record("t:a", &policy, 500), Verdict::Allowed);
+        assert_eq!(counter.record("t:a", &policy, 1_000), Verdict::Burst { sends_in_window: 3 });
+
+        // Spread out, the per-minute limit applies instead
+        assert_eq!(counter.record("t:a", &policy, 10_000), Verdict::Allowed);
+        assert_eq!(counter.record("t:a", &policy, 20_000), Verdict::Allowed);
+        assert_eq!(counter.record("t:a", &policy, 30_000), Verdict::Allowed);
+        assert_eq!(counter.record("t:a", &policy, 40_000), Verdict::Limited { retry_after_secs: 20 });
+
+        // Other senders have their own count; old sends age out
+        assert_eq!(counter.record("t:b", &policy, 40_000), Verdict::Allowed);
+        assert_eq!(counter.record("t:a", &policy, 61_000), Verdict::Allowed);
+    }
+
+    #[test]
+    fn test_tenant_overrides() {
+        let base = ThrottlePolicy::default();
+        let tuned = base.for_tenant(&json!({ SETTINGS_KEY: { "max_per_minute": 120, "mute_secs": 60 } }));
+        assert_eq!(tuned.max_per_minute, 120);
+        assert_eq!(tuned.mute_secs, 60);
+        assert_eq!(tuned.burst_count, base.burst_count);
+
+        // Invalid overrides are ignored as a whole
+        assert_eq!(base.for_tenant(&json!({ SETTINGS_KEY: { "burst_window_secs": 600 } })), base);
+        assert_eq!(base.for_tenant(&json!({})), base);
+        assert_eq!(base.for_tenant(&Value::Null), base);
+    }
+}
@@ -4,9 +4,9 @@
 //! This is the TDLN layer for the Messenger container.
 //!
 //! Endpoints:
-//! - GET  /messenger/bootstrap      → Initial state aggregation
+//! - GET  /messenger/bootstrap      → Initial state aggregation (alias: GET /bootstrap)
 //! - POST /messenger/messages       → Send message (commit to ledger)
-//! - POST /messenger/conversations  → Create workstream
+//! - POST /messenger/conversations  → Create workstream (or broadcast channel)
 //! - POST /messenger/jobs/:id/approve → Approve job (commit to C.Jobs)
 //! - POST /messenger/jobs/:id/reject  → Reject job (commit to C.Jobs)
 //!
@@ -17,6 +17,13 @@
 //! 4. POST /link/commit internally
 //! 5. Return result
 //!
+//! ## Bootstrap caching
+//!
+//! Bootstrap carries one cursor per section and an ETag derived from them
+//! (plus caller and tenant). Cursors are read before any section is loaded,
+//! so a client presenting a matching `If-None-Match` gets `304 Not Modified`
+//! for the price of a single aggregate query.
+//!
 //! ## Signature Strategy (Fix #1)
 //! 
 //! The boundary layer uses a persistent "boundary" key to sign commits.
@@ -28,20 +35,22 @@
 
 use axum::{
     extract::{Path, State},
-    http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use time::OffsetDateTime;
+use ubl_events::{ApprovalDecided, JobEvent};
 use uuid::Uuid;
 
 use crate::auth;
 use crate::db::{LinkDraft, PgLedger};
 use crate::keystore;
-use crate::projections::{JobsProjection, MessagesProjection};
+use crate::projections::mentions::MentionBadge;
+use crate::projections::{broadcasts, JobsProjection, MentionsProjection, MessagesProjection, Visibility};
 
 // ============================================================================
 // STATE
@@ -64,6 +73,7 @@ pub fn routes(pool: PgPool) -> Router {
     Router::new()
         // Aggregation
         .route("/messenger/bootstrap", get(bootstrap))
+        .route("/bootstrap", get(bootstrap))
         // Mutations
         .route("/messenger/messages", post(send_message))
         .route("/messenger/conversations", get(list_conversations))
@@ -82,9 +92,52 @@ pub fn routes(pool: PgPool) -> Router {
 #[derive(Debug, Serialize)]
 pub struct BootstrapResponse {
     pub user: Option<UserInfo>,
+    pub tenant_id: String,
     pub entities: Vec<EntityInfo>,
     pub conversations: Vec<ConversationInfo>,
     pub messages: Vec<MessageInfo>,
+    pub presence: Vec<PresenceInfo>,
+    pub pending_jobs: Vec<PendingJobInfo>,
+    /// Unacknowledged @mentions of the caller, per conversation
+    pub mention_badges: Vec<MentionBadge>,
+    pub cursors: BootstrapCursors,
+}
+
+/// Per-section change cursors; the bootstrap ETag is derived from these.
+///
+/// `conversations` (also covering messages) and `jobs` are the highest
+/// projected ledger sequence; `entities` and `presence` have no sequence and
+/// use their latest change time in unix ms. `mentions` is the highest
+/// sequence of the caller's obligations.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Default, sqlx::FromRow)]
+pub struct BootstrapCursors {
+    pub entities: i64,
+    pub conversations: i64,
+    pub jobs: i64,
+    pub presence: i64,
+    pub mentions: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PresenceInfo {
+    pub entity_id: String,
+    pub state: String,
+    pub job_id: Option<String>,
+    pub since: OffsetDateTime,
+    pub last_seen_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PendingJobInfo {
+    pub job_id: String,
+    pub conversation_id: String,
+    pub title: String,
+    pub state: String,
+    pub owner_entity_id: String,
+    pub waiting_on: Option<Vec<String>>,
+    pub available_actions: Option<serde_json::Value>,
+    pub updated_at: OffsetDateTime,
+    pub last_event_seq: i64,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -135,6 +188,9 @@ pub struct SendMessageRequest {
     // UBL-FIX: Add client_msg_id for idempotency (Diamond Checklist #7)
     #[serde(default)]
     pub client_msg_id: Option<String>,
+    /// Optimistic UI id, echoed back (see `LinkDraft::tentative_id`)
+    #[serde(default)]
+    pub tentative_id: Option<String>,
 }
 
 fn default_message_type() -> String { "text".to_string() }
@@ -144,6 +200,8 @@ pub struct SendMessageResponse {
     pub message_id: String,
     pub hash: String,
     pub sequence: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tentative_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -152,6 +210,12 @@ pub struct CreateConversationRequest {
     pub participants: Vec<String>,
     #[serde(default)]
     pub is_group: bool,
+    /// "chat" (default) or "broadcast"
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Broadcast only: members allowed to post (the creator always is)
+    #[serde(default)]
+    pub senders: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -178,24 +242,39 @@ pub struct ApprovalDecisionResponse {
 // ============================================================================
 
 /// GET /messenger/bootstrap
-/// Aggregates initial state for the frontend
+/// Aggregates initial state for the frontend, for the caller's tenant
 async fn bootstrap(
     State(state): State<MessengerState>,
     headers: HeaderMap,
-) -> Result<Json<BootstrapResponse>, (StatusCode, String)> {
+) -> Result<Response, (StatusCode, String)> {
     // 1. Get current user from session
     let user = get_user_from_session(&state.pool, &headers).await;
+    let user_id = user.as_ref().map(|u| u.sid.as_str()).unwrap_or("demo");
+    let tenant_id = user.as_ref().and_then(|u| u.tenant_id.clone()).unwrap_or_else(|| "default".to_string());
+    
+    // 2. Cursors first: an unchanged snapshot costs one query
+    let cursors = get_bootstrap_cursors(&state.pool, &tenant_id, user_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let etag = bootstrap_etag(user_id, &tenant_id, &cursors);
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "private, no-cache".to_string()),
+    ];
+    if let Some(inm) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        if etag_matches(inm, &etag) {
+            return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+        }
+    }
     
-    // 2. Get entities
+    // 3. Get entities
     let entities = get_entities(&state.pool).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
-    // 3. Get conversations for user
-    let user_id = user.as_ref().map(|u| u.sid.as_str()).unwrap_or("demo");
+    // 4. Get conversations for user
     let conversations = get_user_conversations(&state.pool, user_id).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
-    // 4. Get recent messages (aggregate from all user's conversations)
+    // 5. Get recent messages (aggregate from all user's conversations)
     let mut messages = Vec::new();
     for conv in &conversations {
         let conv_messages = get_conversation_messages(&state.pool, &conv.id, 50).await
@@ -203,12 +282,29 @@ async fn bootstrap(
         messages.extend(conv_messages);
     }
     
-    Ok(Json(BootstrapResponse {
-        user,
-        entities,
-        conversations,
-        messages,
-    }))
+    // 6. Presence and pending jobs (tenant-scoped projections)
+    let presence = get_tenant_presence(&state.pool, &tenant_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let pending_jobs = get_pending_jobs(&state.pool, &tenant_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mention_badges = MentionsProjection::new(state.pool.clone()).badges(user_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((
+        cache_headers,
+        Json(BootstrapResponse {
+            user,
+            tenant_id,
+            entities,
+            conversations,
+            messages,
+            presence,
+            pending_jobs,
+            mention_badges,
+            cursors,
+        }),
+    )
+        .into_response())
 }
 
 /// POST /messenger/messages
@@ -221,6 +317,7 @@ async fn send_message(
     // 1. Get sender from session
     let user = get_user_from_session(&state.pool, &headers).await
         .ok_or((StatusCode::UNAUTHORIZED, "Not authenticated".to_string()))?;
+    require_sender(&state.pool, &req.conversation_id, &user.sid).await?;
     
     // 2. Generate message ID
     let message_id = format!("msg_{}", Uuid::new_v4().to_string().replace("-", "")[..12].to_string());
@@ -262,6 +359,7 @@ async fn send_message(
             previous_hash: "0x00".to_string(),
             link_hash: "0x00".to_string(),
             ts_unix_ms: 0,
+            hlc: crate::hlc::Hlc::ZERO,
         });
     
     // 7. Build and SIGN the link (Fix #1: Real Ed25519)
@@ -277,6 +375,7 @@ async fn send_message(
         author_pubkey: String::new(), // Will be set by sign_link_draft
         signature: String::new(),     // Will be set by sign_link_draft
         pact: None,
+        tentative_id: req.tentative_id.clone(),
     };
     sign_link_draft(&mut link);
     
@@ -288,10 +387,17 @@ async fn send_message(
     store_message_content(&state.pool, &message_id, &req.content, &content_hash).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
+    // 10. Project now so announcements reach member timelines
+    let projection = MessagesProjection::new(state.pool.clone());
+    if let Err(e) = projection.process_event("message.created", &atom, &entry.entry_hash, entry.sequence).await {
+        tracing::error!("Failed to project message {}: {}", message_id, e);
+    }
+    
     Ok(Json(SendMessageResponse {
         message_id,
         hash: entry.entry_hash,
         sequence: entry.sequence,
+        tentative_id: req.tentative_id,
     }))
 }
 
@@ -334,7 +440,7 @@ async fn create_conversation(
     }
     participants.sort(); // Canonical order
     
-    let atom = serde_json::json!({
+    let mut atom = serde_json::json!({
         "created_at": now_iso,
         "created_by": user.sid,
         "id": conv_id,
@@ -344,6 +450,18 @@ async fn create_conversation(
         "tenant_id": tenant_id,
         "type": "conversation.created"
     });
+    match req.kind.as_deref() {
+        None | Some("chat") => {}
+        Some(broadcasts::BROADCAST_KIND) => {
+            let senders = broadcasts::BroadcastLimits::from_env()
+                .senders(&user.sid, &participants, &req.senders)
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            atom["is_group"] = serde_json::json!(true);
+            atom["kind"] = serde_json::json!(broadcasts::BROADCAST_KIND);
+            atom["senders"] = serde_json::json!(senders);
+        }
+        Some(other) => return Err((StatusCode::BAD_REQUEST, format!("Unknown conversation kind: {}", other))),
+    }
     
     // 4. Canonicalize and hash
     let atom_bytes = ubl_atom::canonicalize(&atom)
@@ -360,6 +478,7 @@ async fn create_conversation(
             previous_hash: "0x00".to_string(),
             link_hash: "0x00".to_string(),
             ts_unix_ms: 0,
+            hlc: crate::hlc::Hlc::ZERO,
         });
     
     // 6. Build and SIGN the link (Fix #1: Real Ed25519)
@@ -369,18 +488,25 @@ async fn create_conversation(
         expected_sequence: container_state.sequence + 1,
         previous_hash: container_state.entry_hash.clone(),
         atom_hash: atom_hash.clone(),
-        atom: Some(atom),
+        atom: Some(atom.clone()),
         intent_class: "Observation".to_string(),
         physics_delta: "0".to_string(),
         author_pubkey: String::new(), // Will be set by sign_link_draft
         signature: String::new(),     // Will be set by sign_link_draft
         pact: None,
+        tentative_id: None,
     };
     sign_link_draft(&mut link);
     
     let entry = state.ledger.append(&link).await
         .map_err(|e| (StatusCode::CONFLICT, format!("Commit failed: {:?}", e)))?;
     
+    // Project now: broadcast senders are checked against this row on send
+    let projection = MessagesProjection::new(state.pool.clone());
+    if let Err(e) = projection.process_event("conversation.created", &atom, &entry.entry_hash, entry.sequence).await {
+        tracing::error!("Failed to project conversation {}: {}", conv_id, e);
+    }
+    
     Ok(Json(CreateConversationResponse {
         id: conv_id,
         hash: entry.entry_hash,
@@ -421,7 +547,7 @@ async fn job_decision(
     
     // 2. Get pending approval for this job
     let jobs_projection = JobsProjection::new(state.pool.clone());
-    let approvals = jobs_projection.get_pending_approvals(&job_id).await
+    let approvals = jobs_projection.get_pending_approvals(&job_id, "projection_approvals").await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
     let approval = approvals.first()
@@ -433,16 +559,16 @@ async fn job_decision(
     let now = OffsetDateTime::now_utc();
     let now_iso = now.format(&time::format_description::well_known::Rfc3339).unwrap();
     
-    let atom = serde_json::json!({
-        "approval_id": approval.approval_id,
-        "decided_at": now_iso,
-        "decided_by": user.sid,
-        "decision": decision,
-        "job_id": job_id,
-        "reason": reason.unwrap_or_default(),
-        "tenant_id": tenant_id,
-        "type": "approval.decided"
-    });
+    let atom = JobEvent::ApprovalDecided(ApprovalDecided {
+        approval_id: approval.approval_id.clone(),
+        job_id: job_id.clone(),
+        tenant_id: Some(tenant_id.to_string()),
+        decision: decision.to_string(),
+        decided_by: user.sid.clone(),
+        decided_at: now_iso,
+        reason,
+    })
+    .to_atom();
     
     // 4. Canonicalize
     let atom_bytes = ubl_atom::canonicalize(&atom)
@@ -459,6 +585,7 @@ async fn job_decision(
             previous_hash: "0x00".to_string(),
             link_hash: "0x00".to_string(),
             ts_unix_ms: 0,
+            hlc: crate::hlc::Hlc::ZERO,
         });
     
     // 6. Build and SIGN the link (Fix #1: Real Ed25519)
@@ -474,6 +601,7 @@ async fn job_decision(
         author_pubkey: String::new(), // Will be set by sign_link_draft
         signature: String::new(),     // Will be set by sign_link_draft
         pact: None,
+        tentative_id: None,
     };
     sign_link_draft(&mut link);
     
@@ -545,7 +673,7 @@ async fn get_entities(pool: &PgPool) -> Result<Vec<EntityInfo>, sqlx::Error> {
     let entities: Vec<EntityInfo> = sqlx::query_as(
         r#"
         SELECT sid as id, display_name, kind, NULL as avatar_url, 'online' as status
-        FROM id_subjects
+        FROM id_subject
         WHERE kind IN ('person', 'llm', 'app')
         ORDER BY display_name
         "#
@@ -556,6 +684,76 @@ async fn get_entities(pool: &PgPool) -> Result<Vec<EntityInfo>, sqlx::Error> {
     Ok(entities)
 }
 
+async fn get_bootstrap_cursors(pool: &PgPool, tenant_id: &str, user_id: &str) -> Result<BootstrapCursors, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT
+            (SELECT COALESCE((EXTRACT(EPOCH FROM MAX(created_at)) * 1000)::BIGINT, 0)
+               FROM id_subject WHERE kind IN ('person', 'llm', 'app')) AS entities,
+            (SELECT COALESCE(MAX(last_event_seq), 0) FROM projection_messages) AS conversations,
+            (SELECT COALESCE(MAX(last_event_seq), 0)
+               FROM projection_jobs WHERE tenant_id = $1) AS jobs,
+            (SELECT COALESCE((EXTRACT(EPOCH FROM MAX(GREATEST(since, last_seen_at))) * 1000)::BIGINT, 0)
+               FROM projection_presence WHERE tenant_id = $1) AS presence,
+            (SELECT COALESCE(MAX(last_event_seq), 0)
+               FROM projection_obligations WHERE entity_id = $2) AS mentions
+        "#
+    )
+    .bind(tenant_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Strong ETag over everything that selects or versions the snapshot
+fn bootstrap_etag(user_id: &str, tenant_id: &str, c: &BootstrapCursors) -> String {
+    let key = format!(
+        "bootstrap/v1|{}|{}|{}|{}|{}|{}|{}",
+        user_id, tenant_id, c.entities, c.conversations, c.jobs, c.presence, c.mentions
+    );
+    format!("\"{}\"", &blake3_hex(&key)[..32])
+}
+
+/// `If-None-Match` check: `*` or any listed tag (weak tags compare by value)
+pub(crate) fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|t| t.trim())
+        .any(|t| t == "*" || t.trim_start_matches("W/") == etag)
+}
+
+async fn get_tenant_presence(pool: &PgPool, tenant_id: &str) -> Result<Vec<PresenceInfo>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT entity_id, state, job_id, since, last_seen_at
+        FROM projection_presence
+        WHERE tenant_id = $1
+        ORDER BY entity_id
+        "#
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Jobs still awaiting a decision or running; terminal jobs are fetched on demand
+async fn get_pending_jobs(pool: &PgPool, tenant_id: &str) -> Result<Vec<PendingJobInfo>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT job_id, conversation_id, title, state, owner_entity_id,
+               waiting_on, available_actions, updated_at, last_event_seq
+        FROM projection_jobs
+        WHERE tenant_id = $1
+          AND state IN ('proposed', 'approved', 'in_progress', 'waiting_input')
+        ORDER BY updated_at DESC
+        LIMIT 100
+        "#
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await
+}
+
 async fn get_user_conversations(pool: &PgPool, user_id: &str) -> Result<Vec<ConversationInfo>, sqlx::Error> {
     // Query projection_conversations (if exists) or derive from messages
     let conversations: Vec<ConversationInfo> = sqlx::query_as(
@@ -582,7 +780,7 @@ async fn get_user_conversations(pool: &PgPool, user_id: &str) -> Result<Vec<Conv
 
 async fn get_conversation_messages(pool: &PgPool, conversation_id: &str, limit: i64) -> Result<Vec<MessageInfo>, sqlx::Error> {
     let projection = MessagesProjection::new(pool.clone());
-    let messages = projection.get_messages_by_conversation(conversation_id, limit, None).await?;
+    let messages = projection.get_messages_by_conversation(conversation_id, limit, None, Visibility::Member).await?;
     
     // Convert and fetch content
     let mut result = Vec::new();
@@ -604,6 +802,16 @@ async fn get_conversation_messages(pool: &PgPool, conversation_id: &str, limit:
     Ok(result)
 }
 
+/// Only designated senders may post to a broadcast
+pub async fn require_sender(pool: &PgPool, conversation_id: &str, sid: &str) -> Result<(), (StatusCode, String)> {
+    let allowed = broadcasts::may_post(pool, conversation_id, sid).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !allowed {
+        return Err((StatusCode::FORBIDDEN, format!("Only designated senders may post to broadcast {}", conversation_id)));
+    }
+    Ok(())
+}
+
 pub async fn store_message_content(pool: &PgPool, message_id: &str, content: &str, content_hash: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
@@ -686,3 +894,29 @@ pub fn get_boundary_pubkey() -> String {
     keystore::get_public_key_hex(BOUNDARY_KEY_ID)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bootstrap_etag_tracks_cursors() {
+        let c = BootstrapCursors { entities: 1, conversations: 2, jobs: 3, presence: 4, mentions: 5 };
+        let etag = bootstrap_etag("ubl:sid:a", "T.A", &c);
+        assert_eq!(etag, bootstrap_etag("ubl:sid:a", "T.A", &c));
+        assert_ne!(etag, bootstrap_etag("ubl:sid:a", "T.A", &BootstrapCursors { jobs: 4, ..c }));
+        assert_ne!(etag, bootstrap_etag("ubl:sid:a", "T.A", &BootstrapCursors { mentions: 6, ..c }));
+        assert_ne!(etag, bootstrap_etag("ubl:sid:a", "T.B", &c));
+        assert_ne!(etag, bootstrap_etag("ubl:sid:b", "T.A", &c));
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = "\"abc\"";
+        assert!(etag_matches("\"abc\"", etag));
+        assert!(etag_matches("\"x\", W/\"abc\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("\"abd\"", etag));
+        assert!(!etag_matches("abc", etag));
+    }
+}
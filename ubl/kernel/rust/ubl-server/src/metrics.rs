@@ -3,10 +3,20 @@
 //! Prometheus text format endpoint with proper content-type
 //! Per Dan's patch: Must return proper Prometheus format
 
-use axum::{routing::get, Router, response::IntoResponse};
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
 use std::fmt::Write as _;
-use prometheus::{IntCounterVec, Opts, Encoder, TextEncoder, gather};
+use prometheus::{
+    IntCounterVec, IntGaugeVec, HistogramVec, Opts, Encoder, TextEncoder, gather,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec,
+};
 use lazy_static::lazy_static;
+use ubl_errors::ErrorCode;
 
 lazy_static! {
     pub static ref RATE_LIMIT_REJECTIONS: IntCounterVec = IntCounterVec::new(
@@ -38,6 +48,88 @@ lazy_static! {
         Opts::new("ubl_policy_decisions_total", "Policy decisions by result"),
         &["result"]
     ).unwrap();
+
+    /// Gas per policy evaluation. Buckets span a few instructions up to the
+    /// VM's MAX_GAS (100k).
+    pub static ref POLICY_GAS_USED: HistogramVec = register_histogram_vec!(
+        "ubl_policy_gas_used",
+        "Gas consumed per policy evaluation",
+        &["policy_id"],
+        vec![10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0, 25_000.0, 50_000.0, 100_000.0]
+    ).unwrap();
+
+    pub static ref POLICY_GAS_ALERTS: IntCounterVec = register_int_counter_vec!(
+        "ubl_policy_gas_budget_alerts_total",
+        "Policy evaluations above the gas alert threshold (UBL_POLICY_GAS_ALERT)",
+        &["policy_id"]
+    ).unwrap();
+
+    /// Entries of a mirrored container not yet in the analytics database
+    pub static ref MIRROR_LAG: IntGaugeVec = register_int_gauge_vec!(
+        "ubl_mirror_lag_entries",
+        "Ledger entries not yet copied to the analytics mirror, by container",
+        &["container"]
+    ).unwrap();
+
+    pub static ref MIRROR_ENTRIES: IntCounterVec = register_int_counter_vec!(
+        "ubl_mirror_entries_total",
+        "Ledger entries copied to the analytics mirror, by container",
+        &["container"]
+    ).unwrap();
+
+    /// Requests to the public read gateway
+    pub static ref PUBLIC_READS: IntCounterVec = register_int_counter_vec!(
+        "ubl_public_read_requests_total",
+        "Public read gateway requests, by tier and outcome (ok, cached, unauthorized, forbidden, over_quota)",
+        &["tier", "outcome"]
+    ).unwrap();
+
+    /// Time per Membrane validator (V1..V8); the whole pipeline targets < 1ms
+    pub static ref MEMBRANE_VALIDATOR_SECONDS: HistogramVec = register_histogram_vec!(
+        "ubl_membrane_validator_seconds",
+        "Time spent in each Membrane validator during admission",
+        &["validator"],
+        vec![0.00001, 0.000025, 0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.01]
+    ).unwrap();
+
+    /// In-flight commit claims (see `commit_claim`)
+    pub static ref COMMIT_CLAIMS: IntCounterVec = register_int_counter_vec!(
+        "ubl_commit_claims_total",
+        "Commit claims by outcome (acquired, duplicate, timeout)",
+        &["outcome"]
+    ).unwrap();
+
+    /// Error responses by catalog code and matched route (see [`count_errors`])
+    pub static ref ERRORS: IntCounterVec = register_int_counter_vec!(
+        "ubl_errors_total",
+        "Error responses by canonical error code and route",
+        &["code", "route"]
+    ).unwrap();
+
+    /// Refused commits, whether or not the rejection audit is on
+    pub static ref COMMIT_REJECTIONS: IntCounterVec = register_int_counter_vec!(
+        "ubl_commit_rejections_total",
+        "Refused commits by container, validation stage and canonical error code",
+        &["container", "stage", "code"]
+    ).unwrap();
+}
+
+/// Count 4xx/5xx responses in `ubl_errors_total`. `ApiError` responses carry
+/// their code; the others (`(StatusCode, String)` errors, extractor
+/// rejections) are counted under the generic code for their status.
+pub async fn count_errors(request: Request, next: Next) -> Response {
+    let route = request.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string());
+    let response = next.run(request).await;
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        let code = response
+            .extensions()
+            .get::<ErrorCode>()
+            .copied()
+            .unwrap_or_else(|| ErrorCode::from_http_status(status.as_u16()));
+        ERRORS.with_label_values(&[code.as_str(), route.as_deref().unwrap_or("unmatched")]).inc();
+    }
+    response
 }
 
 /// Metrics router - independent of AppState (no .with_state needed)
@@ -74,3 +166,30 @@ async fn metrics_handler() -> impl IntoResponse {
         body,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, middleware};
+    use tower_service::Service;
+
+    use crate::api_error::ApiError;
+
+    #[tokio::test]
+    async fn test_errors_counted_by_code_and_route() {
+        let mut app = Router::new()
+            .route("/test-metrics/drift/:id", get(|| async { ApiError::new(ErrorCode::RealityDrift, "RealityDrift") }))
+            .route("/test-metrics/plain", get(|| async { (StatusCode::NOT_FOUND, "gone".to_string()) }))
+            .route("/test-metrics/ok", get(|| async { "ok" }))
+            .layer(middleware::from_fn(count_errors));
+
+        for uri in ["/test-metrics/drift/1", "/test-metrics/drift/2", "/test-metrics/plain", "/test-metrics/ok"] {
+            let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.call(req).await.unwrap();
+        }
+        let count = |code: &str, route: &str| ERRORS.with_label_values(&[code, route]).get();
+        assert_eq!(count("REALITY_DRIFT", "/test-metrics/drift/:id"), 2);
+        assert_eq!(count("NOT_FOUND", "/test-metrics/plain"), 1);
+        assert_eq!(count("INTERNAL", "/test-metrics/ok"), 0);
+    }
+}
@@ -0,0 +1,393 @@
+//! Analytics Mirror
+//!
+//! Background worker that copies selected containers into a separate,
+//! read-only analytics database, so BI queries never reach the primary.
+//! For each container in `UBL_MIRROR_CONTAINERS` it copies, into the schema of
+//! `sql/95_mirror/950_analytics_mirror.sql` (applied on start):
+//!
+//! - ledger entries with their atoms, in sequence order, checked to continue
+//!   the chain already mirrored (a mirror fed by another primary stops);
+//! - the container's daily analytics rows (`projection_container_daily`),
+//!   from the day before the previous sync on.
+//!
+//! The cursor is kept in the analytics database, so after downtime the worker
+//! catches up from where the mirror stops, `MAX_BATCHES_PER_TICK` batches per
+//! container per tick. `ubl_mirror_lag_entries` reports the entries each
+//! container is behind. Without `UBL_MIRROR_DATABASE_URL` the worker is off.
+
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::db::GENESIS_PREVIOUS_HASH;
+use crate::timestamps::now_ms;
+
+/// Entries copied per batch
+const BATCH_LIMIT: i64 = 500;
+/// Batches per container per tick; a long catch-up spreads over ticks
+const MAX_BATCHES_PER_TICK: usize = 20;
+/// Schema of the analytics database
+const MIRROR_SCHEMA: &str = include_str!("../../../../sql/95_mirror/950_analytics_mirror.sql");
+
+const DAY_MS: i64 = 24 * 3600 * 1000;
+
+/// Configuration for the analytics mirror
+#[derive(Clone)]
+pub struct MirrorConfig {
+    /// DSN of the analytics database; `None` disables the mirror
+    pub database_url: Option<String>,
+    /// Containers to mirror
+    pub containers: Vec<String>,
+    /// How often to sync (in seconds)
+    pub interval_secs: u64,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self { database_url: None, containers: Vec::new(), interval_secs: 10 }
+    }
+}
+
+impl MirrorConfig {
+    /// `UBL_MIRROR_DATABASE_URL`, `UBL_MIRROR_CONTAINERS` (comma-separated) and
+    /// `UBL_MIRROR_INTERVAL_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            database_url: std::env::var("UBL_MIRROR_DATABASE_URL").ok().filter(|v| !v.trim().is_empty()),
+            containers: parse_containers(&std::env::var("UBL_MIRROR_CONTAINERS").unwrap_or_default()),
+            interval_secs: std::env::var("UBL_MIRROR_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+        }
+    }
+}
+
+fn parse_containers(raw: &str) -> Vec<String> {
+    let mut containers: Vec<String> =
+        raw.split(',').map(str::trim).filter(|c| !c.is_empty()).map(String::from).collect();
+    containers.sort();
+    containers.dedup();
+    containers
+}
+
+/// Last entry mirrored for a container
+#[derive(Debug, Clone, PartialEq)]
+struct Cursor {
+    last_sequence: i64,
+    last_hash: String,
+    updated_at_ms: i64,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self { last_sequence: 0, last_hash: GENESIS_PREVIOUS_HASH.to_string(), updated_at_ms: 0 }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MirroredEntry {
+    sequence: i64,
+    entry_hash: String,
+    previous_hash: String,
+    link_hash: String,
+    event_type: Option<String>,
+    atom: Option<serde_json::Value>,
+    ts_unix_ms: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct DailyRow {
+    day: String,
+    commits: i64,
+    rejected: i64,
+    delta_volume: String,
+    delta_net: String,
+    entropy_minted: String,
+    entropy_burned: String,
+    last_sequence: i64,
+}
+
+/// Analytics Mirror - copies selected containers to the analytics database
+pub struct MirrorWorker {
+    primary: PgPool,
+    mirror: PgPool,
+    config: MirrorConfig,
+}
+
+impl MirrorWorker {
+    /// `None` when no analytics database or no container is configured
+    pub fn new(primary: PgPool, config: MirrorConfig) -> Option<Self> {
+        let url = config.database_url.as_deref()?;
+        if config.containers.is_empty() {
+            warn!("⚠️  UBL_MIRROR_DATABASE_URL is set but UBL_MIRROR_CONTAINERS is empty - analytics mirror off");
+            return None;
+        }
+        let mirror = match PgPoolOptions::new().max_connections(2).connect_lazy(url) {
+            Ok(pool) => pool,
+            Err(e) => {
+                error!("❌ Analytics mirror DSN {} is invalid: {}", crate::config::redact_url(url), e);
+                return None;
+            }
+        };
+        Some(Self { primary, mirror, config })
+    }
+
+    /// Start the sync loop (runs forever)
+    pub async fn run(self) {
+        info!(
+            "🪞 Analytics mirror started - {} every {}s into {}",
+            self.config.containers.join(", "),
+            self.config.interval_secs,
+            crate::config::redact_url(self.config.database_url.as_deref().unwrap_or_default())
+        );
+
+        let mut tick = interval(Duration::from_secs(self.config.interval_secs));
+        let mut schema_ready = false;
+
+        loop {
+            tick.tick().await;
+
+            // The analytics database may come up after us; retry the schema each tick
+            if !schema_ready {
+                match sqlx::raw_sql(MIRROR_SCHEMA).execute(&self.mirror).await {
+                    Ok(_) => schema_ready = true,
+                    Err(e) => {
+                        error!("❌ Analytics mirror schema not applied: {}", e);
+                        continue;
+                    }
+                }
+            }
+
+            for container_id in &self.config.containers {
+                if let Err(e) = self.sync(container_id).await {
+                    error!("❌ Analytics mirror of {} failed: {}", container_id, e);
+                }
+            }
+        }
+    }
+
+    /// Copy what is new in one container and report its lag
+    async fn sync(&self, container_id: &str) -> Result<(), String> {
+        let db = |e: sqlx::Error| e.to_string();
+        let previous = self.cursor(container_id).await.map_err(db)?;
+        let mut cursor = previous.clone();
+
+        for _ in 0..MAX_BATCHES_PER_TICK {
+            let entries: Vec<MirroredEntry> = sqlx::query_as(
+                r#"
+                SELECT e.sequence, e.entry_hash, e.previous_hash, e.link_hash,
+                       a.atom_type AS event_type, a.atom_data AS atom, e.ts_unix_ms
+                FROM ledger_entry e
+                LEFT JOIN ledger_atom a ON a.atom_hash = e.link_hash
+                WHERE e.container_id = $1 AND e.sequence > $2
+                ORDER BY e.sequence
+                LIMIT $3
+                "#,
+            )
+            .bind(container_id)
+            .bind(cursor.last_sequence)
+            .bind(BATCH_LIMIT)
+            .fetch_all(&self.primary)
+            .await
+            .map_err(db)?;
+
+            let Some(last) = entries.last() else { break };
+            if let Some(gap) = chain_break(&cursor, &entries) {
+                return Err(gap);
+            }
+            let next = Cursor {
+                last_sequence: last.sequence,
+                last_hash: last.entry_hash.clone(),
+                updated_at_ms: now_ms(),
+            };
+            self.write_batch(container_id, &entries, &next).await.map_err(db)?;
+            crate::metrics::MIRROR_ENTRIES.with_label_values(&[container_id]).inc_by(entries.len() as u64);
+            cursor = next;
+
+            if (entries.len() as i64) < BATCH_LIMIT {
+                break;
+            }
+        }
+
+        self.copy_daily(container_id, daily_since_ms(previous.updated_at_ms)).await.map_err(db)?;
+
+        let head: Option<i64> = sqlx::query_scalar("SELECT MAX(sequence) FROM ledger_entry WHERE container_id = $1")
+            .bind(container_id)
+            .fetch_one(&self.primary)
+            .await
+            .map_err(db)?;
+        let lag = head.unwrap_or(0) - cursor.last_sequence;
+        crate::metrics::MIRROR_LAG.with_label_values(&[container_id]).set(lag);
+        if cursor.last_sequence > previous.last_sequence {
+            info!("🪞 Mirrored {} to seq={} (lag {})", container_id, cursor.last_sequence, lag);
+        }
+        Ok(())
+    }
+
+    async fn cursor(&self, container_id: &str) -> Result<Cursor, sqlx::Error> {
+        let row: Option<(i64, String, i64)> = sqlx::query_as(
+            "SELECT last_sequence, last_hash, updated_at_ms FROM mirror_cursor WHERE container_id = $1",
+        )
+        .bind(container_id)
+        .fetch_optional(&self.mirror)
+        .await?;
+        Ok(row
+            .map(|(last_sequence, last_hash, updated_at_ms)| Cursor { last_sequence, last_hash, updated_at_ms })
+            .unwrap_or_default())
+    }
+
+    /// Entries and the cursor move together
+    async fn write_batch(&self, container_id: &str, entries: &[MirroredEntry], cursor: &Cursor) -> Result<(), sqlx::Error> {
+        let mut tx = self.mirror.begin().await?;
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO mirror_event (
+                    container_id, sequence, entry_hash, previous_hash, atom_hash, event_type, atom, ts_unix_ms
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (container_id, sequence) DO NOTHING
+                "#,
+            )
+            .bind(container_id)
+            .bind(entry.sequence)
+            .bind(&entry.entry_hash)
+            .bind(&entry.previous_hash)
+            .bind(&entry.link_hash)
+            .bind(&entry.event_type)
+            .bind(&entry.atom)
+            .bind(entry.ts_unix_ms)
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO mirror_cursor (container_id, last_sequence, last_hash, updated_at_ms)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (container_id) DO UPDATE
+            SET last_sequence = EXCLUDED.last_sequence, last_hash = EXCLUDED.last_hash, updated_at_ms = EXCLUDED.updated_at_ms
+            "#,
+        )
+        .bind(container_id)
+        .bind(cursor.last_sequence)
+        .bind(&cursor.last_hash)
+        .bind(cursor.updated_at_ms)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Re-copy the daily rows a sync may have missed: rejections change
+    /// today's row without a ledger entry
+    async fn copy_daily(&self, container_id: &str, since_ms: i64) -> Result<(), sqlx::Error> {
+        let days: Vec<DailyRow> = sqlx::query_as(
+            r#"
+            SELECT day::text AS day, commits, rejected, delta_volume::text AS delta_volume,
+                   delta_net::text AS delta_net, entropy_minted::text AS entropy_minted,
+                   entropy_burned::text AS entropy_burned, last_sequence
+            FROM projection_container_daily
+            WHERE container_id = $1 AND day >= (to_timestamp($2::double precision / 1000) AT TIME ZONE 'UTC')::date
+            "#,
+        )
+        .bind(container_id)
+        .bind(since_ms)
+        .fetch_all(&self.primary)
+        .await?;
+
+        for row in days {
+            sqlx::query(
+                r#"
+                INSERT INTO mirror_container_daily (
+                    container_id, day, commits, rejected, delta_volume, delta_net,
+                    entropy_minted, entropy_burned, last_sequence
+                ) VALUES ($1, $2::date, $3, $4, $5::numeric, $6::numeric, $7::numeric, $8::numeric, $9)
+                ON CONFLICT (container_id, day) DO UPDATE SET
+                    commits = EXCLUDED.commits, rejected = EXCLUDED.rejected,
+                    delta_volume = EXCLUDED.delta_volume, delta_net = EXCLUDED.delta_net,
+                    entropy_minted = EXCLUDED.entropy_minted, entropy_burned = EXCLUDED.entropy_burned,
+                    last_sequence = EXCLUDED.last_sequence
+                "#,
+            )
+            .bind(container_id)
+            .bind(row.day)
+            .bind(row.commits)
+            .bind(row.rejected)
+            .bind(row.delta_volume)
+            .bind(row.delta_net)
+            .bind(row.entropy_minted)
+            .bind(row.entropy_burned)
+            .bind(row.last_sequence)
+            .execute(&self.mirror)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Why `entries` do not continue the mirrored chain, if they don't
+fn chain_break(cursor: &Cursor, entries: &[MirroredEntry]) -> Option<String> {
+    let mut expected = (cursor.last_sequence + 1, cursor.last_hash.as_str());
+    for entry in entries {
+        if (entry.sequence, entry.previous_hash.as_str()) != expected {
+            return Some(format!(
+                "chain break at seq={}: mirror expects seq={} after {}",
+                entry.sequence, expected.0, expected.1
+            ));
+        }
+        expected = (entry.sequence + 1, entry.entry_hash.as_str());
+    }
+    None
+}
+
+/// Daily rows to re-copy: from the UTC day before the previous sync (all of
+/// them on a first sync)
+fn daily_since_ms(previous_sync_ms: i64) -> i64 {
+    if previous_sync_ms == 0 {
+        return 0;
+    }
+    (previous_sync_ms / DAY_MS - 1) * DAY_MS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sequence: i64, previous_hash: &str, entry_hash: &str) -> MirroredEntry {
+        MirroredEntry {
+            sequence,
+            entry_hash: entry_hash.into(),
+            previous_hash: previous_hash.into(),
+            link_hash: "a".into(),
+            event_type: None,
+            atom: None,
+            ts_unix_ms: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_containers() {
+        assert_eq!(parse_containers(" C.Jobs, C.Messenger,,C.Jobs "), ["C.Jobs", "C.Messenger"]);
+        assert!(parse_containers("").is_empty());
+    }
+
+    #[test]
+    fn test_chain_break() {
+        let genesis = Cursor::default();
+        assert_eq!(chain_break(&genesis, &[entry(1, "0x00", "h1"), entry(2, "h1", "h2")]), None);
+
+        let cursor = Cursor { last_sequence: 2, last_hash: "h2".into(), updated_at_ms: 1 };
+        assert_eq!(chain_break(&cursor, &[entry(3, "h2", "h3")]), None);
+        assert!(chain_break(&cursor, &[entry(3, "other", "h3")]).unwrap().contains("seq=3"));
+        assert!(chain_break(&cursor, &[entry(3, "h2", "h3"), entry(5, "h3", "h5")]).is_some());
+    }
+
+    #[test]
+    fn test_daily_since() {
+        assert_eq!(daily_since_ms(0), 0);
+        // 2024-01-02T12:00Z → from 2024-01-01T00:00Z
+        assert_eq!(daily_since_ms(1_704_196_800_000), 1_704_067_200_000);
+    }
+}
@@ -0,0 +1,275 @@
+//! Observation batches — many small observations in one commit
+//!
+//! Presence pings and progress updates are frequent, tiny and individually
+//! uninteresting; committing each one grows the chain by a link per ping.
+//! A batch folds them into a single Observation link whose atom is:
+//!
+//! ```json
+//! {
+//!   "type": "observation.batch",
+//!   "batch_root": "<hex>",
+//!   "observations": [{"type": "presence.heartbeat", ...}, ...]
+//! }
+//! ```
+//!
+//! Each leaf is the observation's own atom hash (BLAKE3 of its canonical
+//! form), so a batched observation hashes exactly as it would if committed
+//! alone. Nodes are `ubl_kernel::hash_merkle(left, right)`; an odd node is
+//! promoted unchanged to the next level. The root lets anyone holding one
+//! unpacked row prove it was part of the committed batch.
+//!
+//! Limits (`UBL_OBSERVATION_BATCH_MAX`, `UBL_OBSERVATION_BATCH_MAX_ITEM_BYTES`)
+//! are enforced by the policy pack at commit time; the projection side
+//! (`projections::ObservationsProjection`) unpacks batches into one row each.
+
+use serde_json::Value;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Atom type of a batch
+pub const BATCH_TYPE: &str = "observation.batch";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BatchError {
+    #[error("observations must be a non-empty array")]
+    Empty,
+
+    #[error("batch of {count} observations exceeds limit of {max}")]
+    TooLarge { count: usize, max: usize },
+
+    #[error("observation {index}: {reason}")]
+    InvalidObservation { index: usize, reason: String },
+
+    #[error("batch_root missing or does not match observations")]
+    RootMismatch,
+}
+
+// ============================================================================
+// LIMITS
+// ============================================================================
+
+/// Size limits for a single batch
+#[derive(Debug, Clone)]
+pub struct BatchLimits {
+    /// Most observations per batch
+    pub max_observations: usize,
+    /// Largest canonical size of one observation
+    pub max_observation_bytes: usize,
+}
+
+impl Default for BatchLimits {
+    fn default() -> Self {
+        Self {
+            max_observations: 500,
+            max_observation_bytes: 4096,
+        }
+    }
+}
+
+impl BatchLimits {
+    /// Defaults overridden by `UBL_OBSERVATION_BATCH_MAX` / `UBL_OBSERVATION_BATCH_MAX_ITEM_BYTES`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: usize| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            max_observations: get("UBL_OBSERVATION_BATCH_MAX", defaults.max_observations),
+            max_observation_bytes: get("UBL_OBSERVATION_BATCH_MAX_ITEM_BYTES", defaults.max_observation_bytes),
+        }
+    }
+}
+
+/// Process-wide limits, read from the environment once
+pub fn limits() -> &'static BatchLimits {
+    static LIMITS: OnceLock<BatchLimits> = OnceLock::new();
+    LIMITS.get_or_init(BatchLimits::from_env)
+}
+
+// ============================================================================
+// MERKLE TREE
+// ============================================================================
+
+/// Leaf hash of one observation (its atom hash)
+pub fn leaf_hash(observation: &Value) -> Result<[u8; 32], String> {
+    ubl_atom::atom_hash_bytes(observation).map_err(|e| e.to_string())
+}
+
+/// Root over leaf hashes; an odd node is promoted to the next level
+pub fn merkle_root(leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+    let mut level: Vec<[u8; 32]> = leaves.to_vec();
+    if level.is_empty() {
+        return None;
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    Some(level[0])
+}
+
+/// Sibling hashes from leaf `index` up to the root, with `true` when the
+/// sibling sits on the right. Promoted levels contribute no step.
+pub fn inclusion_proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<([u8; 32], bool)>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    let mut level: Vec<[u8; 32]> = leaves.to_vec();
+    let mut pos = index;
+    while level.len() > 1 {
+        let sibling = pos ^ 1;
+        if sibling < level.len() {
+            proof.push((level[sibling], sibling > pos));
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+        pos /= 2;
+    }
+    Some(proof)
+}
+
+/// Check a proof produced by [`inclusion_proof`]
+pub fn verify_inclusion(leaf: &[u8; 32], proof: &[([u8; 32], bool)], root: &[u8; 32]) -> bool {
+    let acc = proof.iter().fold(*leaf, |acc, (sibling, sibling_is_right)| {
+        if *sibling_is_right { node(&acc, sibling) } else { node(sibling, &acc) }
+    });
+    ubl_kernel::ct_eq(&acc, root)
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&ubl_kernel::hash_merkle(left, right));
+    out
+}
+
+// ============================================================================
+// VERIFY
+// ============================================================================
+
+/// Check a batch atom against `limits` and return its leaf hashes in order
+pub fn verify_batch(atom: &Value, limits: &BatchLimits) -> Result<Vec<[u8; 32]>, BatchError> {
+    let observations = atom
+        .get("observations")
+        .and_then(|v| v.as_array())
+        .filter(|a| !a.is_empty())
+        .ok_or(BatchError::Empty)?;
+    if observations.len() > limits.max_observations {
+        return Err(BatchError::TooLarge { count: observations.len(), max: limits.max_observations });
+    }
+
+    let leaves = leaves_of(observations, limits.max_observation_bytes)?;
+    let root = merkle_root(&leaves).ok_or(BatchError::Empty)?;
+    let claimed = atom
+        .get("batch_root")
+        .and_then(|v| v.as_str())
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or(BatchError::RootMismatch)?;
+    if !ubl_kernel::ct_eq(&claimed, &root) {
+        return Err(BatchError::RootMismatch);
+    }
+    Ok(leaves)
+}
+
+fn leaves_of(observations: &[Value], max_bytes: usize) -> Result<Vec<[u8; 32]>, BatchError> {
+    if observations.is_empty() {
+        return Err(BatchError::Empty);
+    }
+    observations
+        .iter()
+        .enumerate()
+        .map(|(index, obs)| {
+            let invalid = |reason: String| BatchError::InvalidObservation { index, reason };
+            match obs.get("type").and_then(|t| t.as_str()) {
+                None => return Err(invalid("missing type".into())),
+                Some(BATCH_TYPE) => return Err(invalid("batches cannot be nested".into())),
+                Some(_) => {}
+            }
+            let canonical = ubl_atom::canonicalize(obs).map_err(|e| invalid(e.to_string()))?;
+            if canonical.len() > max_bytes {
+                return Err(invalid(format!("{} bytes exceeds limit of {}", canonical.len(), max_bytes)));
+            }
+            Ok(*blake3::hash(&canonical).as_bytes())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// What an emitter does: hash the observations and attach the root
+    fn build_batch(observations: Vec<Value>) -> Result<Value, BatchError> {
+        let leaves = leaves_of(&observations, usize::MAX)?;
+        let root = merkle_root(&leaves).ok_or(BatchError::Empty)?;
+        Ok(json!({
+            "type": BATCH_TYPE,
+            "batch_root": hex::encode(root),
+            "observations": observations,
+        }))
+    }
+
+    fn obs(n: u64) -> Value {
+        json!({ "type": "presence.heartbeat", "entity_id": "agent-1", "seq": n })
+    }
+
+    #[test]
+    fn test_build_and_verify() {
+        let atom = build_batch((0..5).map(obs).collect()).unwrap();
+        let leaves = verify_batch(&atom, &BatchLimits::default()).unwrap();
+        assert_eq!(leaves.len(), 5);
+        // Leaves are the observations' own atom hashes
+        assert_eq!(leaves[3], ubl_atom::atom_hash_bytes(&obs(3)).unwrap());
+    }
+
+    #[test]
+    fn test_tampered_batch_rejected() {
+        let mut atom = build_batch((0..3).map(obs).collect()).unwrap();
+        atom["observations"][1]["seq"] = json!(99);
+        assert_eq!(verify_batch(&atom, &BatchLimits::default()), Err(BatchError::RootMismatch));
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = BatchLimits { max_observations: 2, max_observation_bytes: 4096 };
+        let atom = build_batch((0..3).map(obs).collect()).unwrap();
+        assert_eq!(verify_batch(&atom, &limits), Err(BatchError::TooLarge { count: 3, max: 2 }));
+
+        let limits = BatchLimits { max_observations: 10, max_observation_bytes: 16 };
+        assert!(matches!(
+            verify_batch(&atom, &limits),
+            Err(BatchError::InvalidObservation { index: 0, .. })
+        ));
+
+        let nested = json!([{ "type": BATCH_TYPE }]);
+        assert!(build_batch(nested.as_array().unwrap().clone()).is_err());
+        assert_eq!(build_batch(vec![]), Err(BatchError::Empty));
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        for n in 1..=7 {
+            let leaves: Vec<[u8; 32]> = (0..n).map(|i| leaf_hash(&obs(i)).unwrap()).collect();
+            let root = merkle_root(&leaves).unwrap();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = inclusion_proof(&leaves, i).unwrap();
+                assert!(verify_inclusion(leaf, &proof, &root), "n={} i={}", n, i);
+            }
+            let other = leaf_hash(&obs(100)).unwrap();
+            assert!(!verify_inclusion(&other, &inclusion_proof(&leaves, 0).unwrap(), &root));
+        }
+    }
+}
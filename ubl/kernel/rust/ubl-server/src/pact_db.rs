@@ -2,7 +2,7 @@
 //! SPEC-UBL-PACT v1.0
 
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::collections::HashSet;
 use tracing::{info, error};
 
@@ -193,7 +193,7 @@ pub async fn validate_pact_proof(
 
 /// Build the message that pact signers must sign
 /// Per SPEC-UBL-PACT §8.1
-fn build_pact_sign_message(
+pub(crate) fn build_pact_sign_message(
     pact_id: &str,
     atom_hash: &str,
     intent_class: &str,
@@ -292,6 +292,72 @@ pub async fn validate_pact_for(
     )))
 }
 
+/// Outcome of spending a use of a pact
+#[derive(Debug, PartialEq)]
+pub enum PactUse {
+    /// No `max_uses`: nothing is counted
+    Unlimited,
+    /// This commit spent use number `n`
+    Spent(i32),
+    /// Every use is already spent
+    Exhausted,
+    /// No such pact
+    UnknownPact,
+}
+
+/// Spend one use of `pact_id` for the entry being appended in `tx`.
+///
+/// Must run inside the append's SERIALIZABLE transaction (see
+/// `PgLedger::try_append`): two commits racing for the last use both update
+/// the pact row, so one of them fails with a serialization conflict and, on
+/// retry, finds the pact exhausted. Unlimited pacts are only read.
+pub async fn consume_use(
+    tx: &mut Transaction<'_, Postgres>,
+    pact_id: &str,
+    container_id: &str,
+    sequence: i64,
+    atom_hash: &str,
+    now_ms: i64,
+) -> Result<PactUse, sqlx::Error> {
+    let max_uses: Option<Option<i32>> = sqlx::query_scalar("SELECT max_uses FROM pact WHERE pact_id = $1")
+        .bind(pact_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+    match max_uses {
+        None => return Ok(PactUse::UnknownPact),
+        Some(None) => return Ok(PactUse::Unlimited),
+        Some(Some(_)) => {}
+    }
+
+    let spent: Option<i32> = sqlx::query_scalar(
+        "UPDATE pact SET uses = uses + 1 WHERE pact_id = $1 AND uses < max_uses RETURNING uses",
+    )
+    .bind(pact_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    let Some(use_number) = spent else {
+        return Ok(PactUse::Exhausted);
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO pact_usage (pact_id, container_id, sequence, atom_hash, use_number, used_at_ms)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(pact_id)
+    .bind(container_id)
+    .bind(sequence)
+    .bind(atom_hash)
+    .bind(use_number)
+    .bind(now_ms)
+    .execute(&mut **tx)
+    .await?;
+
+    info!("🎟️  Pact {} use {} spent by {}#{}", pact_id, use_number, container_id, sequence);
+    Ok(PactUse::Spent(use_number))
+}
+
 /// Pact error type for high-level validation
 #[derive(Debug)]
 pub enum PactError {
@@ -310,3 +376,108 @@ impl std::fmt::Display for PactError {
 
 impl std::error::Error for PactError {}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{LinkDraft, PactProofDraft, PgLedger, TangencyError};
+
+    // Needs a database with the ubl/sql schema applied
+    async fn setup_test_db() -> PgPool {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://localhost:5432/ubl_test".to_string());
+        PgPool::connect(&database_url).await.expect("Failed to connect to test database")
+    }
+
+    async fn create_pact(pool: &PgPool, pact_id: &str, max_uses: Option<i32>) {
+        sqlx::query(
+            r#"
+            INSERT INTO pact (pact_id, scope_type, intent_classes, threshold, signers,
+                              not_before, not_after, risk_level, created_by, max_uses)
+            VALUES ($1, 'global', ARRAY['Evolution'], 1, ARRAY['test-signer'], 0, 9999999999999, 3, 'test', $2)
+            "#,
+        )
+        .bind(pact_id)
+        .bind(max_uses)
+        .execute(pool)
+        .await
+        .expect("Failed to create pact");
+    }
+
+    fn evolution_link(container_id: String, pact_id: &str) -> LinkDraft {
+        LinkDraft {
+            version: 1,
+            container_id,
+            expected_sequence: 1,
+            previous_hash: crate::db::GENESIS_PREVIOUS_HASH.to_string(),
+            atom_hash: "ab".repeat(32),
+            intent_class: "Evolution".to_string(),
+            physics_delta: "0".to_string(),
+            author_pubkey: "cd".repeat(32),
+            signature: String::new(),
+            atom: None,
+            pact: Some(PactProofDraft { pact_id: pact_id.to_string(), signatures: Vec::new() }),
+            tentative_id: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Run with: cargo test --ignored
+    async fn test_single_use_pact_is_consumed_exactly_once() {
+        let pool = setup_test_db().await;
+        let ledger = PgLedger::new(pool.clone());
+        let run = uuid::Uuid::new_v4();
+        let pact_id = format!("pact:test:single-use:{}", run);
+        create_pact(&pool, &pact_id, Some(1)).await;
+
+        // Eight commits to different containers race for the one use
+        let attempts: Vec<_> = (0..8)
+            .map(|i| {
+                let ledger = ledger.clone();
+                let link = evolution_link(format!("C.PactRace.{}.{}", run, i), &pact_id);
+                tokio::spawn(async move { ledger.append(&link).await })
+            })
+            .collect();
+        let mut accepted = 0;
+        for attempt in attempts {
+            match attempt.await.unwrap() {
+                Ok(_) => accepted += 1,
+                Err(TangencyError::PactViolation(_) | TangencyError::SerializationConflict) => {}
+                Err(e) => panic!("unexpected append error: {:?}", e),
+            }
+        }
+        assert_eq!(accepted, 1, "a single-use pact must authorize exactly one commit");
+
+        let (uses, recorded): (i32, i64) = sqlx::query_as(
+            "SELECT uses, (SELECT COUNT(*) FROM pact_usage WHERE pact_id = $1) FROM pact WHERE pact_id = $1",
+        )
+        .bind(&pact_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((uses, recorded), (1, 1));
+
+        // Spent: later commits are refused outright
+        let late = ledger.append(&evolution_link(format!("C.PactRace.{}.late", run), &pact_id)).await;
+        assert!(matches!(late, Err(TangencyError::PactViolation(_))));
+    }
+
+    #[tokio::test]
+    #[ignore] // Run with: cargo test --ignored
+    async fn test_unlimited_pact_is_not_counted() {
+        let pool = setup_test_db().await;
+        let ledger = PgLedger::new(pool.clone());
+        let run = uuid::Uuid::new_v4();
+        let pact_id = format!("pact:test:unlimited:{}", run);
+        create_pact(&pool, &pact_id, None).await;
+
+        for i in 0..3 {
+            ledger.append(&evolution_link(format!("C.PactUnlimited.{}.{}", run, i), &pact_id)).await.unwrap();
+        }
+        let uses: i32 = sqlx::query_scalar("SELECT uses FROM pact WHERE pact_id = $1")
+            .bind(&pact_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(uses, 0);
+    }
+}
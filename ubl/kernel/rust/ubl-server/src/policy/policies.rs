@@ -18,6 +18,7 @@ pub enum PolicyError {
     RawPiiDetected { field: String },
     ToolPairingViolation { tool_call_id: String },
     TenantViolation { reason: String },
+    InvalidObservationBatch { reason: String },
 }
 
 impl std::fmt::Display for PolicyError {
@@ -38,6 +39,9 @@ impl std::fmt::Display for PolicyError {
             PolicyError::TenantViolation { reason } => {
                 write!(f, "Tenant violation: {}", reason)
             }
+            PolicyError::InvalidObservationBatch { reason } => {
+                write!(f, "Invalid observation batch: {}", reason)
+            }
         }
     }
 }
@@ -91,6 +95,23 @@ impl PolicyEngine {
         Ok(())
     }
 
+    /// Validate an observation batch (see observation_batch.rs)
+    ///
+    /// Batches are Observation-only and must stay within the configured
+    /// size limits; the root must match the observations carried.
+    pub fn check_observation_batch(&self, atom: &Value, intent_class: &str) -> PolicyResult<()> {
+        if intent_class != "Observation" {
+            return Err(PolicyError::InvalidObservationBatch {
+                reason: format!("batches must be Observation, got {}", intent_class),
+            });
+        }
+        let leaves = crate::observation_batch::verify_batch(atom, crate::observation_batch::limits())
+            .map_err(|e| PolicyError::InvalidObservationBatch { reason: e.to_string() })?;
+
+        info!("✅ Observation batch valid: {} observations", leaves.len());
+        Ok(())
+    }
+
     /// Validate card provenance
     pub async fn validate_card_provenance(
         &self,
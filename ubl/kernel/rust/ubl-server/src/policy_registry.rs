@@ -6,18 +6,40 @@
 //! > - hash da política compilada
 //!
 //! This module manages which policy applies to which container.
+//!
+//! A policy is registered by version (`MAJOR.MINOR[.PATCH]`, `1.0` being
+//! `1.0.0`); versions are kept side by side and never rewritten, so the same
+//! version always runs the same bytecode. Each version has an activation
+//! window (`not_before` inclusive, `not_after` exclusive, Unix ms, open when
+//! absent) and never applies before it was registered. A commit is evaluated
+//! against the highest version active at its timestamp, which makes any past
+//! evaluation reproducible: later registrations cannot change which version
+//! was active before them. A policy bound to a container with no version
+//! active denies.
+//!
+//! Both the policies and the container mappings are snapshots swapped
+//! atomically, so `evaluate` on the commit path never takes a lock and a
+//! policy update in flight never holds up a commit.
+//!
+//! With a database, every decision also counts a hit for the rule that made
+//! it (`projections::policy_coverage`).
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use tracing::{info, error, warn};
+use tracing::{info, warn};
 
 use ubl_policy_vm::{
-    PolicyVM, PolicyDefinition, CompiledPolicy, EvaluationContext,
-    TranslationDecision, PolicyError, create_default_policy,
+    evaluate_compiled, BytecodeVM, PolicyCompiler, PolicyDefinition, CompiledPolicy, EvaluationContext,
+    MeteredDecision, TranslationDecision, create_default_policy,
 };
 
+use crate::metrics::{POLICY_GAS_ALERTS, POLICY_GAS_USED};
+use crate::projections::policy_coverage::{PolicyCoverageProjection, DEFAULT_RULE};
+use crate::timestamps::now_ms;
+
 /// Policy registry error
 #[derive(Debug)]
 pub enum RegistryError {
@@ -25,6 +47,10 @@ pub enum RegistryError {
     ContainerNotConfigured(String),
     EvaluationFailed(String),
     DatabaseError(String),
+    /// Malformed version or activation window
+    InvalidVersion(String),
+    /// The version is registered with other bytecode
+    VersionConflict { policy_id: String, version: String },
 }
 
 impl std::fmt::Display for RegistryError {
@@ -34,12 +60,28 @@ impl std::fmt::Display for RegistryError {
             Self::ContainerNotConfigured(id) => write!(f, "No policy configured for container: {}", id),
             Self::EvaluationFailed(e) => write!(f, "Policy evaluation failed: {}", e),
             Self::DatabaseError(e) => write!(f, "Database error: {}", e),
+            Self::InvalidVersion(e) => write!(f, "Invalid policy version: {}", e),
+            Self::VersionConflict { policy_id, version } => {
+                write!(f, "Policy {} v{} is already registered with other rules", policy_id, version)
+            }
         }
     }
 }
 
 impl std::error::Error for RegistryError {}
 
+/// One evaluation, with the policy and gas behind its decision
+#[derive(Debug)]
+pub struct PolicyEvaluation {
+    /// `None`: no policy bound, the permissive default applied
+    pub policy_id: Option<String>,
+    /// Version that decided; `None` when none was active
+    pub policy_version: Option<String>,
+    pub decision: Result<TranslationDecision, RegistryError>,
+    pub gas_used: u64,
+    pub max_gas: u64,
+}
+
 /// Container policy mapping
 #[derive(Debug, Clone)]
 pub struct ContainerPolicy {
@@ -48,36 +90,118 @@ pub struct ContainerPolicy {
     pub policy_version: String,
 }
 
+/// When a policy version may decide, in Unix ms: from `not_before`
+/// (inclusive) to `not_after` (exclusive); an absent end is open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivationWindow {
+    #[serde(default)]
+    pub not_before: Option<i64>,
+    #[serde(default)]
+    pub not_after: Option<i64>,
+}
+
+impl ActivationWindow {
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.not_before, self.not_after) {
+            (Some(start), Some(end)) if end <= start => Err("not_after must be after not_before".into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `MAJOR.MINOR.PATCH` as numbers; `MAJOR.MINOR` is `MAJOR.MINOR.0`
+pub fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let parts: Vec<u64> = version.split('.').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [major, minor] => Some((major, minor, 0)),
+        [major, minor, patch] => Some((major, minor, patch)),
+        _ => None,
+    }
+}
+
+/// One registered version of a policy
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyVersion {
+    pub version: String,
+    /// Hash of the compiled bytecode
+    pub hash: String,
+    #[serde(flatten)]
+    pub window: ActivationWindow,
+    /// The version never applies before it was registered
+    pub registered_at_ms: i64,
+    #[serde(skip)]
+    pub compiled: Arc<CompiledPolicy>,
+}
+
+impl PolicyVersion {
+    fn semver(&self) -> (u64, u64, u64) {
+        parse_version(&self.version).unwrap_or_default()
+    }
+
+    /// First instant the version may decide
+    pub fn effective_from_ms(&self) -> i64 {
+        self.window.not_before.unwrap_or(i64::MIN).max(self.registered_at_ms)
+    }
+
+    pub fn is_active_at(&self, at_ms: i64) -> bool {
+        at_ms >= self.effective_from_ms() && self.window.not_after.is_none_or(|end| at_ms < end)
+    }
+}
+
+/// Versions of one policy, lowest first
+type PolicyVersions = HashMap<String, Vec<Arc<PolicyVersion>>>;
+
+/// Highest version active at `at_ms`
+fn active_at(versions: &[Arc<PolicyVersion>], at_ms: i64) -> Option<Arc<PolicyVersion>> {
+    versions.iter().rev().find(|v| v.is_active_at(at_ms)).cloned()
+}
+
 /// Policy registry - manages container -> policy mappings
 pub struct PolicyRegistry {
-    /// Policy VM for evaluation
-    vm: Arc<RwLock<PolicyVM>>,
+    /// Bytecode VM for evaluation
+    vm: BytecodeVM,
+    /// Policy ID -> registered versions
+    policies: ArcSwap<PolicyVersions>,
     /// Container -> Policy ID mapping
-    container_policies: RwLock<HashMap<String, String>>,
+    container_policies: ArcSwap<HashMap<String, String>>,
     /// Database pool for persistence (optional)
     pool: Option<PgPool>,
+    /// Share of the gas budget above which an evaluation raises an alert
+    /// (1.0 unless configured: only evaluations that ran out)
+    gas_alert: f64,
 }
 
 impl PolicyRegistry {
     /// Create a new policy registry
     pub fn new() -> Self {
         Self {
-            vm: Arc::new(RwLock::new(PolicyVM::new())),
-            container_policies: RwLock::new(HashMap::new()),
+            vm: BytecodeVM::default(),
+            policies: ArcSwap::default(),
+            container_policies: ArcSwap::default(),
             pool: None,
+            gas_alert: 1.0,
         }
     }
 
     /// Create with database backing
     pub fn with_pool(pool: PgPool) -> Self {
         Self {
-            vm: Arc::new(RwLock::new(PolicyVM::new())),
-            container_policies: RwLock::new(HashMap::new()),
+            vm: BytecodeVM::default(),
+            policies: ArcSwap::default(),
+            container_policies: ArcSwap::default(),
             pool: Some(pool),
+            gas_alert: 1.0,
         }
     }
 
-    /// Initialize default policies for known containers
+    /// Alert when an evaluation uses more than `fraction` of its gas budget
+    pub fn with_gas_alert(mut self, fraction: f64) -> Self {
+        self.gas_alert = fraction;
+        self
+    }
+
+    /// Initialize default policies for known containers (in force since
+    /// forever, so history before any registration evaluates against them)
     pub async fn init_defaults(&self) {
         let known_containers = vec![
             "C.Jobs",
@@ -87,35 +211,111 @@ impl PolicyRegistry {
             "C.Policy",
         ];
 
-        let mut vm = self.vm.write().await;
-        let mut mappings = self.container_policies.write().await;
-
         for container_id in known_containers {
             let definition = create_default_policy(container_id);
             let policy_id = definition.policy_id.clone();
             
-            vm.register(&definition);
-            mappings.insert(container_id.to_string(), policy_id.clone());
+            if let Err(e) = self.insert_version(&definition, ActivationWindow::default(), 0, true) {
+                warn!("Failed to register default policy {}: {}", policy_id, e);
+                continue;
+            }
+            self.map_container(container_id, &policy_id);
             
             info!("📋 Registered default policy for {}: {}", container_id, policy_id);
         }
     }
 
-    /// Register a policy
+    /// Swap in a mapping snapshot with `container_id -> policy_id`
+    fn map_container(&self, container_id: &str, policy_id: &str) {
+        self.container_policies.rcu(|current| {
+            let mut next = HashMap::clone(current);
+            next.insert(container_id.to_string(), policy_id.to_string());
+            next
+        });
+    }
+
+    /// Compile `definition` and add it to its policy's versions. With
+    /// `replace` (loading), a stored version overrides the one in memory;
+    /// otherwise re-registering a version is a no-op if its bytecode is the
+    /// same, and a conflict if not.
+    fn insert_version(
+        &self,
+        definition: &PolicyDefinition,
+        window: ActivationWindow,
+        registered_at_ms: i64,
+        replace: bool,
+    ) -> Result<Arc<PolicyVersion>, RegistryError> {
+        let key = parse_version(&definition.version).ok_or_else(|| {
+            RegistryError::InvalidVersion(format!(
+                "{} v{}: expected MAJOR.MINOR[.PATCH]",
+                definition.policy_id, definition.version
+            ))
+        })?;
+        window
+            .validate()
+            .map_err(|e| RegistryError::InvalidVersion(format!("{} v{}: {}", definition.policy_id, definition.version, e)))?;
+        let compiled = PolicyCompiler::new().compile(definition);
+        let candidate = Arc::new(PolicyVersion {
+            version: definition.version.clone(),
+            hash: compiled.hash.clone(),
+            window,
+            registered_at_ms,
+            compiled: Arc::new(compiled),
+        });
+
+        // `rcu` may run the closure again on a concurrent swap; the outcome
+        // is the one of the snapshot that was swapped in
+        let mut outcome = Ok(candidate.clone());
+        self.policies.rcu(|current| {
+            let mut next = PolicyVersions::clone(current);
+            let versions = next.entry(definition.policy_id.clone()).or_default();
+            outcome = match versions.iter().position(|v| v.semver() == key) {
+                Some(i) if replace => {
+                    versions[i] = candidate.clone();
+                    Ok(candidate.clone())
+                }
+                Some(i) if versions[i].hash == candidate.hash => Ok(versions[i].clone()),
+                Some(_) => Err(RegistryError::VersionConflict {
+                    policy_id: definition.policy_id.clone(),
+                    version: definition.version.clone(),
+                }),
+                None => {
+                    versions.push(candidate.clone());
+                    versions.sort_by_key(|v| v.semver());
+                    Ok(candidate.clone())
+                }
+            };
+            next
+        });
+        outcome
+    }
+
+    /// Register a policy, in force from now on
     pub async fn register_policy(&self, definition: PolicyDefinition) -> Result<String, RegistryError> {
+        self.register_policy_version(definition, ActivationWindow::default()).await
+    }
+
+    /// Register a version of a policy, active within `window` (and not
+    /// before now)
+    pub async fn register_policy_version(
+        &self,
+        definition: PolicyDefinition,
+        window: ActivationWindow,
+    ) -> Result<String, RegistryError> {
         let policy_id = definition.policy_id.clone();
-        
-        let mut vm = self.vm.write().await;
-        vm.register(&definition);
+        let version = self.insert_version(&definition, window, now_ms(), false)?;
         
         // Persist to database if available
         if let Some(ref pool) = self.pool {
-            if let Err(e) = self.persist_policy(pool, &definition).await {
+            if let Err(e) = self.persist_policy(pool, &definition, &version).await {
                 warn!("Failed to persist policy {}: {}", policy_id, e);
             }
         }
         
-        info!("📋 Registered policy: {} v{}", policy_id, definition.version);
+        info!(
+            "📋 Registered policy: {} v{} (from {:?} until {:?})",
+            policy_id, definition.version, version.window.not_before, version.window.not_after
+        );
         Ok(policy_id)
     }
 
@@ -126,18 +326,12 @@ impl PolicyRegistry {
         policy_id: &str,
     ) -> Result<(), RegistryError> {
         // Verify policy exists
-        {
-            let vm = self.vm.read().await;
-            if !vm.has_policy(policy_id) {
-                return Err(RegistryError::PolicyNotFound(policy_id.to_string()));
-            }
+        if !self.policies.load().contains_key(policy_id) {
+            return Err(RegistryError::PolicyNotFound(policy_id.to_string()));
         }
 
         // Update mapping
-        {
-            let mut mappings = self.container_policies.write().await;
-            mappings.insert(container_id.to_string(), policy_id.to_string());
-        }
+        self.map_container(container_id, policy_id);
 
         // Persist to database if available
         if let Some(ref pool) = self.pool {
@@ -152,11 +346,53 @@ impl PolicyRegistry {
 
     /// Get the policy ID for a container
     pub async fn get_policy_for_container(&self, container_id: &str) -> Option<String> {
-        let mappings = self.container_policies.read().await;
-        mappings.get(container_id).cloned()
+        self.container_policies.load().get(container_id).cloned()
+    }
+
+    /// The compiled policy bound to a container, as commits are evaluated
+    /// now, if any
+    pub async fn bound_policy(&self, container_id: &str) -> Option<Arc<CompiledPolicy>> {
+        self.bound_policy_at(container_id, now_ms()).await
+    }
+
+    /// The compiled policy a commit to `container_id` at `at_ms` is evaluated with
+    pub async fn bound_policy_at(&self, container_id: &str, at_ms: i64) -> Option<Arc<CompiledPolicy>> {
+        let policy_id = self.container_policies.load().get(container_id).cloned()?;
+        self.compiled_policy_at(&policy_id, at_ms)
+    }
+
+    /// A registered policy by id: the version active now, else the highest
+    pub fn compiled_policy(&self, policy_id: &str) -> Option<Arc<CompiledPolicy>> {
+        let policies = self.policies.load();
+        let versions = policies.get(policy_id)?;
+        active_at(versions, now_ms())
+            .or_else(|| versions.last().cloned())
+            .map(|v| v.compiled.clone())
     }
 
-    /// Evaluate policy for a container
+    /// The version of `policy_id` active at `at_ms`
+    pub fn compiled_policy_at(&self, policy_id: &str, at_ms: i64) -> Option<Arc<CompiledPolicy>> {
+        active_at(self.policies.load().get(policy_id)?, at_ms).map(|v| v.compiled.clone())
+    }
+
+    /// Every registered version of `policy_id`, lowest first
+    pub fn history(&self, policy_id: &str) -> Option<Vec<Arc<PolicyVersion>>> {
+        self.policies.load().get(policy_id).cloned()
+    }
+
+    /// Whether `version` of `policy_id` is registered
+    pub fn has_version(&self, policy_id: &str, version: &str) -> bool {
+        let key = parse_version(version);
+        self.policies
+            .load()
+            .get(policy_id)
+            .is_some_and(|versions| versions.iter().any(|v| Some(v.semver()) == key))
+    }
+
+    /// Evaluate policy for a container with the version active at
+    /// `timestamp`, reporting which policy and version decided and the gas
+    /// it used (commit dry-runs report them). `flags` are the container's
+    /// feature flags (see `feature_flags`).
     pub async fn evaluate(
         &self,
         container_id: &str,
@@ -164,26 +400,55 @@ impl PolicyRegistry {
         intent: &serde_json::Value,
         state: Option<serde_json::Value>,
         timestamp: i64,
-    ) -> Result<TranslationDecision, RegistryError> {
+        flags: BTreeMap<String, bool>,
+    ) -> PolicyEvaluation {
         // Get policy ID for container
-        let policy_id = {
-            let mappings = self.container_policies.read().await;
-            mappings.get(container_id).cloned()
-        };
+        let policy_id = self.container_policies.load().get(container_id).cloned();
 
         let policy_id = match policy_id {
             Some(id) => id,
             None => {
                 // No policy configured - use a permissive default (allow Observation)
                 warn!("⚠️  No policy for container {}, using permissive default", container_id);
-                return Ok(TranslationDecision::Allow {
-                    intent_class: 0x00,
-                    required_pact: None,
-                    constraints: vec![],
-                });
+                return PolicyEvaluation {
+                    policy_id: None,
+                    policy_version: None,
+                    decision: Ok(TranslationDecision::Allow {
+                        intent_class: 0x00,
+                        required_pact: None,
+                        constraints: vec![],
+                    }),
+                    gas_used: 0,
+                    max_gas: 0,
+                };
             }
         };
 
+        let version = match self.policies.load().get(&policy_id) {
+            None => {
+                return PolicyEvaluation {
+                    decision: Err(RegistryError::PolicyNotFound(policy_id.clone())),
+                    policy_id: Some(policy_id),
+                    policy_version: None,
+                    gas_used: 0,
+                    max_gas: self.vm.max_gas(),
+                };
+            }
+            Some(versions) => active_at(versions, timestamp),
+        };
+        let Some(version) = version else {
+            warn!("⚠️  No version of policy {} is active at {}", policy_id, timestamp);
+            return PolicyEvaluation {
+                decision: Ok(TranslationDecision::Deny {
+                    reason: format!("no version of policy {} is active at {}", policy_id, timestamp),
+                }),
+                policy_id: Some(policy_id),
+                policy_version: None,
+                gas_used: 0,
+                max_gas: self.vm.max_gas(),
+            };
+        };
+
         // Build context
         let context = EvaluationContext {
             container_id: container_id.to_string(),
@@ -191,12 +456,60 @@ impl PolicyRegistry {
             intent: intent.clone(),
             state,
             timestamp,
+            flags,
         };
 
         // Evaluate
-        let vm = self.vm.read().await;
-        vm.evaluate(&policy_id, &context)
-            .map_err(|e| RegistryError::EvaluationFailed(e.to_string()))
+        let metered = evaluate_compiled(&self.vm, &version.compiled, &context);
+        self.record_gas(&policy_id, &metered);
+        self.record_coverage(&policy_id, &version, &metered, timestamp);
+        PolicyEvaluation {
+            decision: metered.decision.map_err(|e| RegistryError::EvaluationFailed(e.to_string())),
+            gas_used: metered.gas_used,
+            max_gas: metered.max_gas,
+            policy_id: Some(policy_id),
+            policy_version: Some(version.version.clone()),
+        }
+    }
+
+    /// Gas histogram per policy, plus a log line and alert counter when an
+    /// evaluation crosses the configured share of its budget
+    fn record_gas(&self, policy_id: &str, metered: &MeteredDecision) {
+        POLICY_GAS_USED
+            .with_label_values(&[policy_id])
+            .observe(metered.gas_used as f64);
+
+        if self.exceeds_gas_alert(metered) {
+            POLICY_GAS_ALERTS.with_label_values(&[policy_id]).inc();
+            warn!(
+                "⛽ Policy {} used {}/{} gas ({:.0}% of budget, alert at {:.0}%)",
+                policy_id,
+                metered.gas_used,
+                metered.max_gas,
+                metered.budget_used() * 100.0,
+                self.gas_alert * 100.0,
+            );
+        }
+    }
+
+    /// Count a hit for the rule that decided (in the background)
+    fn record_coverage(&self, policy_id: &str, version: &PolicyVersion, metered: &MeteredDecision, timestamp: i64) {
+        let (Some(pool), Ok(decision)) = (&self.pool, &metered.decision) else {
+            return;
+        };
+        let policy_version = version.version.clone();
+        let rule_id = decision.matched_rule().unwrap_or(DEFAULT_RULE).to_string();
+        let projection = PolicyCoverageProjection::new(pool.clone());
+        let policy_id = policy_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = projection.record_hit(&policy_id, &policy_version, &rule_id, timestamp).await {
+                warn!("Failed to record coverage of {}/{}: {}", policy_id, rule_id, e);
+            }
+        });
+    }
+
+    fn exceeds_gas_alert(&self, metered: &MeteredDecision) -> bool {
+        metered.budget_used() >= self.gas_alert
     }
 
     /// Check if policy evaluation is required for an intent class
@@ -209,8 +522,14 @@ impl PolicyRegistry {
         }
     }
 
-    /// Persist policy to database (internal)
-    async fn persist_policy(&self, pool: &PgPool, definition: &PolicyDefinition) -> Result<(), sqlx::Error> {
+    /// Persist a policy version to database (internal); `policy_definitions`
+    /// keeps the last registered one
+    async fn persist_policy(
+        &self,
+        pool: &PgPool,
+        definition: &PolicyDefinition,
+        version: &PolicyVersion,
+    ) -> Result<(), sqlx::Error> {
         // Serialize rules
         let rules_json = serde_json::to_value(&definition.rules).unwrap_or(serde_json::Value::Null);
         
@@ -233,6 +552,26 @@ impl PolicyRegistry {
         )
         .execute(pool)
         .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO policy_versions (policy_id, version, description, rules, default_deny,
+                                         bytecode_hash, not_before, not_after, registered_at_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (policy_id, version) DO NOTHING
+            "#,
+        )
+        .bind(&definition.policy_id)
+        .bind(&definition.version)
+        .bind(&definition.description)
+        .bind(serde_json::to_value(&definition.rules).unwrap_or(serde_json::Value::Null))
+        .bind(definition.default_deny)
+        .bind(&version.hash)
+        .bind(version.window.not_before)
+        .bind(version.window.not_after)
+        .bind(version.registered_at_ms)
+        .execute(pool)
+        .await?;
         
         Ok(())
     }
@@ -267,19 +606,18 @@ impl PolicyRegistry {
             return Ok(()); // No database configured
         };
 
-        // Load policy definitions
-        let policies: Vec<PolicyDefRow> = sqlx::query_as!(
-            PolicyDefRow,
+        // Load every policy version
+        let policies: Vec<PolicyVersionRow> = sqlx::query_as(
             r#"
-            SELECT policy_id, version, description, rules, default_deny
-            FROM policy_definitions
-            "#
+            SELECT policy_id, version, description, rules, default_deny,
+                   not_before, not_after, registered_at_ms
+            FROM policy_versions
+            "#,
         )
         .fetch_all(pool)
         .await
         .map_err(|e| RegistryError::DatabaseError(e.to_string()))?;
 
-        let mut vm = self.vm.write().await;
         for row in policies {
             let rules: Vec<ubl_policy_vm::PolicyRule> = serde_json::from_value(row.rules)
                 .unwrap_or_default();
@@ -291,9 +629,12 @@ impl PolicyRegistry {
                 rules,
                 default_deny: row.default_deny,
             };
+            let window = ActivationWindow { not_before: row.not_before, not_after: row.not_after };
             
-            vm.register(&definition);
-            info!("📋 Loaded policy from DB: {}", row.policy_id);
+            match self.insert_version(&definition, window, row.registered_at_ms, true) {
+                Ok(_) => info!("📋 Loaded policy from DB: {} v{}", row.policy_id, definition.version),
+                Err(e) => warn!("⚠️  Skipped stored policy {}: {}", row.policy_id, e),
+            }
         }
 
         // Load container mappings
@@ -308,9 +649,8 @@ impl PolicyRegistry {
         .await
         .map_err(|e| RegistryError::DatabaseError(e.to_string()))?;
 
-        let mut container_policies = self.container_policies.write().await;
         for row in mappings {
-            container_policies.insert(row.container_id.clone(), row.policy_id.clone());
+            self.map_container(&row.container_id, &row.policy_id);
             info!("📋 Loaded container mapping: {} -> {}", row.container_id, row.policy_id);
         }
 
@@ -324,13 +664,16 @@ impl Default for PolicyRegistry {
     }
 }
 
-#[derive(Debug)]
-struct PolicyDefRow {
+#[derive(Debug, sqlx::FromRow)]
+struct PolicyVersionRow {
     policy_id: String,
     version: String,
     description: String,
     rules: serde_json::Value,
     default_deny: bool,
+    not_before: Option<i64>,
+    not_after: Option<i64>,
+    registered_at_ms: i64,
 }
 
 #[derive(Debug)]
@@ -343,6 +686,7 @@ struct ContainerMappingRow {
 mod tests {
     use super::*;
     use serde_json::json;
+    use ubl_policy_vm::PolicyError;
 
     #[tokio::test]
     async fn test_init_defaults() {
@@ -364,9 +708,87 @@ mod tests {
             &json!({"type": "observe"}),
             None,
             1000,
+            BTreeMap::new(),
         ).await;
 
-        assert!(result.is_ok());
+        assert!(result.decision.is_ok());
+        assert_eq!(result.policy_id.as_deref(), Some("default_C.Jobs"));
+        assert!(result.gas_used > 0);
+    }
+
+    #[test]
+    fn test_gas_alert_threshold() {
+        let registry = PolicyRegistry::new().with_gas_alert(0.5);
+        let metered = |gas_used| MeteredDecision {
+            decision: Err(PolicyError::Timeout),
+            gas_used,
+            max_gas: 1_000,
+        };
+
+        assert!(!registry.exceeds_gas_alert(&metered(499)));
+        assert!(registry.exceeds_gas_alert(&metered(500)));
+        assert!(registry.exceeds_gas_alert(&metered(1_000)));
+
+        // Unconfigured: only running out alerts
+        assert!(!PolicyRegistry::new().exceeds_gas_alert(&metered(999)));
+        assert!(PolicyRegistry::new().exceeds_gas_alert(&metered(1_000)));
+    }
+
+    #[tokio::test]
+    async fn test_version_active_at_commit_time() {
+        let registry = PolicyRegistry::new();
+        registry.init_defaults().await;
+        let mut v2 = create_default_policy("C.Jobs");
+        v2.version = "1.1.0".into();
+        v2.rules.clear();
+        v2.default_deny = true;
+        let window = ActivationWindow { not_before: Some(now_ms() + 60_000), not_after: None };
+        registry.register_policy_version(v2.clone(), window).await.unwrap();
+
+        let at = |ts| registry.compiled_policy_at("default_C.Jobs", ts).map(|p| p.version.clone());
+        assert_eq!(at(1000).as_deref(), Some("1.0"));
+        assert_eq!(at(now_ms() + 120_000).as_deref(), Some("1.1.0"));
+        let intent = json!({"type": "observe"});
+        let evaluate = |ts| registry.evaluate("C.Jobs", "alice", &intent, None, ts, BTreeMap::new());
+        assert_eq!(evaluate(1000).await.policy_version.as_deref(), Some("1.0"));
+        let later = evaluate(now_ms() + 120_000).await;
+        assert_eq!(later.policy_version.as_deref(), Some("1.1.0"));
+        assert!(matches!(later.decision, Ok(TranslationDecision::Deny { .. })));
+
+        // Versions are immutable; the same bytecode again is a no-op
+        assert!(registry.register_policy(v2.clone()).await.is_ok());
+        v2.default_deny = false;
+        assert!(matches!(registry.register_policy(v2).await, Err(RegistryError::VersionConflict { .. })));
+        let history = registry.history("default_C.Jobs").unwrap();
+        assert_eq!(history.iter().map(|v| v.version.as_str()).collect::<Vec<_>>(), ["1.0", "1.1.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_activation_window() {
+        let registry = PolicyRegistry::new();
+        registry.init_defaults().await;
+        let mut expiring = create_default_policy("C.Pacts");
+        expiring.version = "2.0".into();
+        let window = ActivationWindow { not_before: None, not_after: Some(now_ms() + 60_000) };
+        registry.register_policy_version(expiring.clone(), window).await.unwrap();
+
+        // Never before registration, and the window is exclusive at its end
+        let active = registry.history("default_C.Pacts").unwrap()[1].clone();
+        assert!(!active.is_active_at(1000));
+        assert!(active.is_active_at(now_ms()));
+        assert!(!active.is_active_at(now_ms() + 60_000));
+        assert_eq!(registry.compiled_policy_at("default_C.Pacts", now_ms()).unwrap().version, "2.0");
+        assert_eq!(registry.compiled_policy_at("default_C.Pacts", now_ms() + 60_000).unwrap().version, "1.0");
+
+        expiring.version = "3.0.0-rc1".into();
+        assert!(matches!(
+            registry.register_policy(expiring.clone()).await,
+            Err(RegistryError::InvalidVersion(_))
+        ));
+        expiring.version = "3.0".into();
+        let backwards = ActivationWindow { not_before: Some(10), not_after: Some(10) };
+        assert!(registry.register_policy_version(expiring, backwards).await.is_err());
+        assert_eq!(parse_version("1.0"), parse_version("1.0.0"));
     }
 
     #[tokio::test]
@@ -380,10 +802,12 @@ mod tests {
             &json!({"type": "observe"}),
             None,
             1000,
+            BTreeMap::new(),
         ).await;
 
         // Should return permissive default
-        assert!(matches!(result, Ok(TranslationDecision::Allow { .. })));
+        assert!(matches!(result.decision, Ok(TranslationDecision::Allow { .. })));
+        assert_eq!((result.policy_id, result.gas_used), (None, 0));
     }
 }
 
@@ -0,0 +1,220 @@
+//! Policy operator routes
+//!
+//! Endpoints (step-up session):
+//! - GET /v1/policy/:id/coverage?over_share= → hits per rule of the
+//!   registered version, with the unused rules and the rules deciding more
+//!   than `over_share` (default 0.5) of evaluations
+//! - POST /v1/policy/debug → run one evaluation with an instruction trace
+//!   (opcode, stack and gas at each step) next to the policy's disassembly
+//! - GET /v1/policy/:id/history?at= → every registered version with its
+//!   activation window, and the version active at `at` (default now)
+//!
+//! Coverage is counted by the registry as policies evaluate
+//! (`projections::policy_coverage`); a policy re-registered under a new
+//! version starts from zero.
+//!
+//! The debugger evaluates an inline `definition` (compiled as registration
+//! would) or DSL `source` (`ubl_policy_vm::compiler::dsl`), else the
+//! registered `policy_id`, else the policy bound to `container_id`, in the
+//! version active at `timestamp`. It runs on a fresh VM and records neither gas nor coverage.
+//! Without `flags`, the container's feature flags in force are used.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use ubl_errors::ErrorCode;
+use ubl_policy_vm::{
+    disassemble, evaluate_traced, BytecodeVM, CompiledPolicy, EvaluationContext, ExecutionTrace, PolicyCompiler,
+    PolicyDefinition, TranslationDecision,
+};
+
+use crate::api_error::ApiError;
+use crate::feature_flags;
+use crate::id_routes::IdState;
+use crate::policy_registry::{PolicyRegistry, PolicyVersion};
+use crate::projections::policy_coverage::{self, PolicyCoverage, PolicyCoverageProjection, OVER_TRIGGERED_SHARE};
+use crate::timestamps::now_ms;
+
+#[derive(Clone)]
+struct PolicyRoutesState {
+    pool: PgPool,
+    policy_registry: Arc<PolicyRegistry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoverageQuery {
+    pub over_share: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    /// Unix milliseconds; now if absent
+    pub at: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolicyHistory {
+    pub policy_id: String,
+    pub at: i64,
+    /// Version a commit at `at` is evaluated with; `None` denies
+    pub active_version: Option<String>,
+    /// Lowest first
+    pub versions: Vec<PolicyVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DebugRequest {
+    pub policy_id: Option<String>,
+    pub definition: Option<PolicyDefinition>,
+    /// Policy DSL text, compiled as `policy_id` (default "debug")
+    pub source: Option<String>,
+    pub container_id: String,
+    pub actor: String,
+    pub intent: serde_json::Value,
+    pub state: Option<serde_json::Value>,
+    /// Unix milliseconds; now if absent
+    pub timestamp: Option<i64>,
+    /// Feature flags to evaluate with; the container's own if absent
+    pub flags: Option<BTreeMap<String, bool>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DebugResponse {
+    pub policy_id: String,
+    pub version: String,
+    pub hash: String,
+    pub disassembly: String,
+    /// Absent when execution failed (see `error`)
+    pub decision: Option<TranslationDecision>,
+    pub rule: Option<String>,
+    pub error: Option<String>,
+    pub trace: ExecutionTrace,
+}
+
+pub fn routes(pool: PgPool, id_state: IdState, policy_registry: Arc<PolicyRegistry>) -> Router {
+    Router::new()
+        .route("/v1/policy/:id/coverage", get(get_coverage))
+        .route("/v1/policy/:id/history", get(get_history))
+        .route("/v1/policy/debug", post(debug_policy))
+        .route_layer(middleware::from_fn_with_state(id_state, crate::auth::require_stepup::require_stepup))
+        .with_state(PolicyRoutesState { pool, policy_registry })
+}
+
+/// GET /v1/policy/:id/coverage
+async fn get_coverage(
+    State(state): State<PolicyRoutesState>,
+    Path(policy_id): Path<String>,
+    Query(query): Query<CoverageQuery>,
+) -> Result<Json<PolicyCoverage>, ApiError> {
+    let over_share = query.over_share.unwrap_or(OVER_TRIGGERED_SHARE);
+    if !(over_share > 0.0 && over_share <= 1.0) {
+        return Err(ApiError::new(ErrorCode::BadRequest, "over_share must be in (0, 1]"));
+    }
+    let policy = state
+        .policy_registry
+        .compiled_policy(&policy_id)
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("PolicyNotFound: {}", policy_id)))?;
+
+    let hits = PolicyCoverageProjection::new(state.pool)
+        .hits(&policy_id, &policy.version)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, format!("DatabaseError: {}", e)))?;
+    let rule_ids: Vec<&str> = policy.rules.iter().map(|r| r.rule_id.as_str()).collect();
+
+    Ok(Json(policy_coverage::coverage(&policy_id, &policy.version, &rule_ids, &hits, over_share)))
+}
+
+/// GET /v1/policy/:id/history
+async fn get_history(
+    State(state): State<PolicyRoutesState>,
+    Path(policy_id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<PolicyHistory>, ApiError> {
+    let versions = state
+        .policy_registry
+        .history(&policy_id)
+        .ok_or_else(|| ApiError::new(ErrorCode::NotFound, format!("PolicyNotFound: {}", policy_id)))?;
+    let at = query.at.unwrap_or_else(now_ms);
+    let active_version = versions.iter().rev().find(|v| v.is_active_at(at)).map(|v| v.version.clone());
+
+    Ok(Json(PolicyHistory {
+        policy_id,
+        at,
+        active_version,
+        versions: versions.iter().map(|v| v.as_ref().clone()).collect(),
+    }))
+}
+
+/// POST /v1/policy/debug
+async fn debug_policy(
+    State(state): State<PolicyRoutesState>,
+    Json(req): Json<DebugRequest>,
+) -> Result<Json<DebugResponse>, ApiError> {
+    let timestamp = req.timestamp.unwrap_or_else(now_ms);
+    let policy = resolve_policy(&state.policy_registry, &req, timestamp).await?;
+    let flags = match req.flags {
+        Some(flags) => flags,
+        None => feature_flags::load(&state.pool, &req.container_id)
+            .await
+            .map_err(|e| ApiError::new(ErrorCode::DatabaseError, format!("DatabaseError: {}", e)))?
+            .flags,
+    };
+    let context = EvaluationContext {
+        container_id: req.container_id,
+        actor: req.actor,
+        intent: req.intent,
+        state: req.state,
+        timestamp,
+        flags,
+    };
+
+    let traced = evaluate_traced(&BytecodeVM::default(), &policy, &context);
+    let (decision, error) = match traced.decision {
+        Ok(decision) => (Some(decision), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    Ok(Json(DebugResponse {
+        policy_id: policy.policy_id.clone(),
+        version: policy.version.clone(),
+        hash: policy.hash.clone(),
+        disassembly: disassemble(&policy),
+        rule: decision.as_ref().and_then(|d| d.matched_rule()).map(str::to_string),
+        decision,
+        error,
+        trace: traced.trace,
+    }))
+}
+
+/// Inline definition or source, else registered policy, else the container's
+/// (registered ones in the version active at `at`)
+async fn resolve_policy(registry: &PolicyRegistry, req: &DebugRequest, at: i64) -> Result<Arc<CompiledPolicy>, ApiError> {
+    if let Some(definition) = &req.definition {
+        let compiled = PolicyCompiler::new()
+            .compile_validated(definition)
+            .map_err(|e| ApiError::new(ErrorCode::BadRequest, format!("policy does not compile: {}", e)))?;
+        return Ok(Arc::new(compiled));
+    }
+    if let Some(source) = &req.source {
+        let policy_id = req.policy_id.as_deref().unwrap_or("debug");
+        let compiled = PolicyCompiler::new()
+            .compile_source(policy_id, "dsl", source)
+            .map_err(|e| ApiError::new(ErrorCode::BadRequest, format!("policy does not compile: {}", e.render(source))))?;
+        return Ok(Arc::new(compiled));
+    }
+    match &req.policy_id {
+        Some(policy_id) => registry.compiled_policy_at(policy_id, at).ok_or_else(|| {
+            ApiError::new(ErrorCode::NotFound, format!("No version of policy {} active at {}", policy_id, at))
+        }),
+        None => registry.bound_policy_at(&req.container_id, at).await.ok_or_else(|| {
+            ApiError::new(ErrorCode::NotFound, format!("No policy active for container {} at {}", req.container_id, at))
+        }),
+    }
+}
@@ -0,0 +1,440 @@
+//! Container Analytics — daily activity rollups per container
+//!
+//! Fed by `commit_link` for every commit, accepted or not: accepted commits
+//! add to `commits`, the delta volume and the author's count for the (UTC)
+//! day; refused ones add to `rejected`. Accepted commits are applied at most
+//! once per ledger entry (`last_sequence` guard). Rejections never reach the
+//! ledger, so a replay cannot rebuild them.
+//!
+//! Entropy commits also add to `entropy_minted` (positive delta) or
+//! `entropy_burned` (negative delta); summed over all days these are the
+//! container's supply accounting ([`AnalyticsProjection::entropy_totals`]).
+//!
+//! `GET /query/analytics/containers` reads the rollups over a day range.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::{Date, Duration, Month};
+
+use crate::timestamps::to_datetime;
+
+/// Days returned when the caller gives no range
+pub const DEFAULT_RANGE_DAYS: i64 = 30;
+
+/// Longest range one query may cover
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+/// One container on one day
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ContainerDay {
+    #[serde(skip)]
+    pub container_id: String,
+    /// `YYYY-MM-DD`, UTC
+    pub day: String,
+    pub commits: i64,
+    pub rejected: i64,
+    pub active_authors: i64,
+    /// Sum of |physics_delta| (decimal string: deltas are i128)
+    pub delta_volume: String,
+    /// Sum of physics_delta
+    pub delta_net: String,
+    /// Sum of positive Entropy deltas
+    pub entropy_minted: String,
+    /// Sum of |negative Entropy deltas|
+    pub entropy_burned: String,
+}
+
+/// Supply created and destroyed by Entropy commits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntropyTotals {
+    pub minted: i128,
+    pub burned: i128,
+}
+
+impl EntropyTotals {
+    /// Minted and burned amounts of one commit (zero unless Entropy)
+    pub fn of(intent_class: &str, physics_delta: i128) -> Self {
+        match intent_class {
+            "Entropy" if physics_delta > 0 => Self { minted: physics_delta, burned: 0 },
+            "Entropy" => Self { minted: 0, burned: physics_delta.saturating_neg() },
+            _ => Self::default(),
+        }
+    }
+
+    /// Policy state fields (`Constraint::StateMax`). The VM works on i64, so
+    /// larger totals saturate.
+    pub fn policy_state(&self) -> serde_json::Value {
+        let clamp = |v: i128| i64::try_from(v).unwrap_or(if v < 0 { i64::MIN } else { i64::MAX });
+        serde_json::json!({
+            "entropy_minted": clamp(self.minted),
+            "entropy_burned": clamp(self.burned),
+            "entropy_net": clamp(self.minted.saturating_sub(self.burned)),
+        })
+    }
+}
+
+/// Activity of one container over the queried range
+#[derive(Debug, Clone, Serialize)]
+pub struct ContainerStats {
+    pub container_id: String,
+    pub commits: i64,
+    pub rejected: i64,
+    /// rejected / (commits + rejected); 0 without traffic
+    pub error_rate: f64,
+    /// Distinct authors over the whole range
+    pub active_authors: i64,
+    pub delta_volume: String,
+    pub delta_net: String,
+    pub entropy_minted: String,
+    pub entropy_burned: String,
+    /// Days with activity, oldest first
+    pub days: Vec<ContainerDay>,
+}
+
+/// Parse a `YYYY-MM-DD` day
+pub fn parse_day(s: &str) -> Option<Date> {
+    let mut parts = s.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Resolve an inclusive `[from, to]` range: `to` defaults to `today`, `from`
+/// to [`DEFAULT_RANGE_DAYS`] before `to`
+pub fn resolve_range(from: Option<&str>, to: Option<&str>, today: Date) -> Result<(Date, Date), String> {
+    let parse = |name: &str, value: &str| parse_day(value).ok_or_else(|| format!("{} must be YYYY-MM-DD", name));
+    let to = to.map(|v| parse("to", v)).transpose()?.unwrap_or(today);
+    let from = from
+        .map(|v| parse("from", v))
+        .transpose()?
+        .unwrap_or(to - Duration::days(DEFAULT_RANGE_DAYS - 1));
+    if from > to {
+        return Err("from is after to".into());
+    }
+    if (to - from).whole_days() >= MAX_RANGE_DAYS {
+        return Err(format!("range is limited to {} days", MAX_RANGE_DAYS));
+    }
+    Ok((from, to))
+}
+
+/// Group daily rows (ordered by container, day) into per-container stats;
+/// `authors` holds the distinct author count of each container over the range
+fn summarize(days: Vec<ContainerDay>, authors: &BTreeMap<String, i64>) -> Vec<ContainerStats> {
+    let mut stats: BTreeMap<String, (ContainerStats, i128, i128, EntropyTotals)> = BTreeMap::new();
+    for day in days {
+        let (entry, volume, net, entropy) = stats.entry(day.container_id.clone()).or_insert_with(|| {
+            (
+                ContainerStats {
+                    container_id: day.container_id.clone(),
+                    commits: 0,
+                    rejected: 0,
+                    error_rate: 0.0,
+                    active_authors: authors.get(&day.container_id).copied().unwrap_or(0),
+                    delta_volume: String::new(),
+                    delta_net: String::new(),
+                    entropy_minted: String::new(),
+                    entropy_burned: String::new(),
+                    days: Vec::new(),
+                },
+                0,
+                0,
+                EntropyTotals::default(),
+            )
+        });
+        entry.commits += day.commits;
+        entry.rejected += day.rejected;
+        *volume += day.delta_volume.parse::<i128>().unwrap_or(0);
+        *net += day.delta_net.parse::<i128>().unwrap_or(0);
+        entropy.minted += day.entropy_minted.parse::<i128>().unwrap_or(0);
+        entropy.burned += day.entropy_burned.parse::<i128>().unwrap_or(0);
+        entry.days.push(day);
+    }
+
+    stats
+        .into_values()
+        .map(|(mut entry, volume, net, entropy)| {
+            let attempts = entry.commits + entry.rejected;
+            entry.error_rate = if attempts > 0 { entry.rejected as f64 / attempts as f64 } else { 0.0 };
+            entry.delta_volume = volume.to_string();
+            entry.delta_net = net.to_string();
+            entry.entropy_minted = entropy.minted.to_string();
+            entry.entropy_burned = entropy.burned.to_string();
+            entry
+        })
+        .collect()
+}
+
+/// Container analytics projection handler
+pub struct AnalyticsProjection {
+    pool: PgPool,
+}
+
+impl AnalyticsProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Count an accepted commit (once per ledger entry)
+    pub async fn record_commit(
+        &self,
+        container_id: &str,
+        sequence: i64,
+        author_pubkey: &str,
+        intent_class: &str,
+        physics_delta: i128,
+        ts_unix_ms: i64,
+    ) -> Result<(), sqlx::Error> {
+        let day = to_datetime(ts_unix_ms).date();
+        let entropy = EntropyTotals::of(intent_class, physics_delta);
+        let mut tx = self.pool.begin().await?;
+
+        let applied = sqlx::query(
+            r#"
+            INSERT INTO projection_container_daily
+                (container_id, day, commits, delta_volume, delta_net, entropy_minted, entropy_burned, last_sequence)
+            VALUES ($1, $2, 1, abs($3::numeric), $3::numeric, $5::numeric, $6::numeric, $4)
+            ON CONFLICT (container_id, day) DO UPDATE SET
+                commits = projection_container_daily.commits + 1,
+                delta_volume = projection_container_daily.delta_volume + EXCLUDED.delta_volume,
+                delta_net = projection_container_daily.delta_net + EXCLUDED.delta_net,
+                entropy_minted = projection_container_daily.entropy_minted + EXCLUDED.entropy_minted,
+                entropy_burned = projection_container_daily.entropy_burned + EXCLUDED.entropy_burned,
+                last_sequence = EXCLUDED.last_sequence
+            WHERE projection_container_daily.last_sequence < EXCLUDED.last_sequence
+            "#,
+        )
+        .bind(container_id)
+        .bind(day)
+        .bind(physics_delta.to_string())
+        .bind(sequence)
+        .bind(entropy.minted.to_string())
+        .bind(entropy.burned.to_string())
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if applied > 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO projection_container_daily_author (container_id, day, author_pubkey, commits)
+                VALUES ($1, $2, $3, 1)
+                ON CONFLICT (container_id, day, author_pubkey)
+                DO UPDATE SET commits = projection_container_daily_author.commits + 1
+                "#,
+            )
+            .bind(container_id)
+            .bind(day)
+            .bind(author_pubkey)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    /// Count a refused commit
+    pub async fn record_rejection(&self, container_id: &str, ts_unix_ms: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO projection_container_daily (container_id, day, rejected)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (container_id, day)
+            DO UPDATE SET rejected = projection_container_daily.rejected + 1
+            "#,
+        )
+        .bind(container_id)
+        .bind(to_datetime(ts_unix_ms).date())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Stats of every container (or one) active in `[from, to]`
+    pub async fn containers(&self, from: Date, to: Date, container_id: Option<&str>) -> Result<Vec<ContainerStats>, sqlx::Error> {
+        let days: Vec<ContainerDay> = sqlx::query_as(
+            r#"
+            SELECT d.container_id, d.day::text AS day, d.commits, d.rejected,
+                   (SELECT COUNT(*) FROM projection_container_daily_author a
+                     WHERE a.container_id = d.container_id AND a.day = d.day) AS active_authors,
+                   d.delta_volume::text AS delta_volume, d.delta_net::text AS delta_net,
+                   d.entropy_minted::text AS entropy_minted, d.entropy_burned::text AS entropy_burned
+            FROM projection_container_daily d
+            WHERE d.day BETWEEN $1 AND $2
+              AND ($3::text IS NULL OR d.container_id = $3)
+            ORDER BY d.container_id, d.day
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(container_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let authors: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT container_id, COUNT(DISTINCT author_pubkey)
+            FROM projection_container_daily_author
+            WHERE day BETWEEN $1 AND $2
+              AND ($3::text IS NULL OR container_id = $3)
+            GROUP BY container_id
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(container_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summarize(days, &authors.into_iter().collect()))
+    }
+
+    /// Cumulative Entropy accounting of a container, over all days
+    pub async fn entropy_totals(&self, container_id: &str) -> Result<EntropyTotals, sqlx::Error> {
+        let (minted, burned): (String, String) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(entropy_minted), 0)::text, COALESCE(SUM(entropy_burned), 0)::text
+            FROM projection_container_daily
+            WHERE container_id = $1
+            "#,
+        )
+        .bind(container_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(EntropyTotals {
+            minted: minted.parse().unwrap_or(0),
+            burned: burned.parse().unwrap_or(0),
+        })
+    }
+
+    /// Net of every committed `physics_delta` of a container, over all days
+    pub async fn balance(&self, container_id: &str) -> Result<i128, sqlx::Error> {
+        let (net,): (String,) = sqlx::query_as(
+            "SELECT COALESCE(SUM(delta_net), 0)::text FROM projection_container_daily WHERE container_id = $1",
+        )
+        .bind(container_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(net.parse().unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    fn day(container_id: &str, day: &str, commits: i64, rejected: i64, volume: &str, net: &str) -> ContainerDay {
+        ContainerDay {
+            container_id: container_id.into(),
+            day: day.into(),
+            commits,
+            rejected,
+            active_authors: 1,
+            delta_volume: volume.into(),
+            delta_net: net.into(),
+            entropy_minted: "0".into(),
+            entropy_burned: "0".into(),
+        }
+    }
+
+    #[test]
+    fn test_range_defaults_and_limits() {
+        let today = date!(2026 - 10 - 16);
+        assert_eq!(resolve_range(None, None, today), Ok((date!(2026 - 09 - 17), today)));
+        assert_eq!(
+            resolve_range(Some("2026-01-01"), Some("2026-01-31"), today),
+            Ok((date!(2026 - 01 - 01), date!(2026 - 01 - 31)))
+        );
+        assert!(resolve_range(Some("2026-02-01"), Some("2026-01-31"), today).is_err());
+        assert!(resolve_range(Some("2024-01-01"), Some("2026-01-31"), today).is_err());
+        assert!(resolve_range(Some("2026-13-01"), None, today).is_err());
+        assert!(resolve_range(None, Some("yesterday"), today).is_err());
+    }
+
+    #[test]
+    fn test_summarize_rolls_days_up() {
+        // Deltas beyond i64 still add up exactly
+        let big = (i64::MAX as i128 * 4).to_string();
+        let days = vec![
+            day("C.Jobs", "2026-10-15", 3, 1, "10", "-4"),
+            day("C.Jobs", "2026-10-16", 5, 0, &big, &big),
+            day("C.Messenger", "2026-10-16", 0, 2, "0", "0"),
+        ];
+        let authors = BTreeMap::from([("C.Jobs".to_string(), 2)]);
+        let stats = summarize(days, &authors);
+
+        assert_eq!(stats.len(), 2);
+        let jobs = &stats[0];
+        assert_eq!((jobs.commits, jobs.rejected, jobs.active_authors), (8, 1, 2));
+        assert_eq!(jobs.error_rate, 1.0 / 9.0);
+        assert_eq!(jobs.delta_volume, (i64::MAX as i128 * 4 + 10).to_string());
+        assert_eq!(jobs.delta_net, (i64::MAX as i128 * 4 - 4).to_string());
+        assert_eq!(jobs.days.len(), 2);
+
+        // Only refusals: everything failed, nobody committed
+        let messenger = &stats[1];
+        assert_eq!((messenger.error_rate, messenger.active_authors), (1.0, 0));
+    }
+
+    #[test]
+    fn test_entropy_accounting() {
+        assert_eq!(EntropyTotals::of("Entropy", 500), EntropyTotals { minted: 500, burned: 0 });
+        assert_eq!(EntropyTotals::of("Entropy", -200), EntropyTotals { minted: 0, burned: 200 });
+        // Moves between holders don't change supply
+        assert_eq!(EntropyTotals::of("Conservation", -200), EntropyTotals::default());
+
+        let days = vec![
+            ContainerDay {
+                entropy_minted: "500".into(),
+                entropy_burned: "200".into(),
+                ..day("C.Wallet", "2026-10-15", 2, 0, "700", "300")
+            },
+            ContainerDay { entropy_minted: "50".into(), ..day("C.Wallet", "2026-10-16", 1, 0, "50", "50") },
+        ];
+        let stats = summarize(days, &BTreeMap::new());
+        assert_eq!((stats[0].entropy_minted.as_str(), stats[0].entropy_burned.as_str()), ("550", "200"));
+
+        let huge = EntropyTotals { minted: i128::MAX, burned: 3 };
+        let state = huge.policy_state();
+        assert_eq!(state["entropy_minted"], i64::MAX);
+        assert_eq!(state["entropy_burned"], 3);
+        assert_eq!(state["entropy_net"], i64::MAX);
+    }
+
+    #[tokio::test]
+    #[ignore] // Needs DATABASE_URL with the ubl/sql schema applied
+    async fn test_rollups_from_commits() {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://localhost:5432/ubl_test".to_string());
+        let pool = PgPool::connect(&database_url).await.expect("Failed to connect to test database");
+        let analytics = AnalyticsProjection::new(pool);
+        let container_id = format!("C.Analytics.{}", uuid::Uuid::new_v4());
+        let ts = 1_792_108_800_000; // 2026-10-16T00:00:00Z
+
+        analytics.record_commit(&container_id, 1, "author-a", "Entropy", 5, ts).await.unwrap();
+        // The same entry seen twice counts once
+        analytics.record_commit(&container_id, 1, "author-a", "Entropy", 5, ts).await.unwrap();
+        analytics.record_commit(&container_id, 2, "author-b", "Conservation", -3, ts + 1).await.unwrap();
+        analytics.record_rejection(&container_id, ts + 2).await.unwrap();
+
+        let day = date!(2026 - 10 - 16);
+        let stats = analytics.containers(day, day, Some(&container_id)).await.unwrap();
+        assert_eq!(stats.len(), 1);
+        let stats = &stats[0];
+        assert_eq!((stats.commits, stats.rejected, stats.active_authors), (2, 1, 2));
+        assert_eq!((stats.delta_volume.as_str(), stats.delta_net.as_str()), ("8", "2"));
+        assert_eq!(stats.days[0].day, "2026-10-16");
+        assert_eq!((stats.entropy_minted.as_str(), stats.entropy_burned.as_str()), ("5", "0"));
+        assert_eq!(
+            analytics.entropy_totals(&container_id).await.unwrap(),
+            EntropyTotals { minted: 5, burned: 0 }
+        );
+
+        let next_day = date!(2026 - 10 - 17);
+        assert!(analytics.containers(next_day, next_day, Some(&container_id)).await.unwrap().is_empty());
+    }
+}
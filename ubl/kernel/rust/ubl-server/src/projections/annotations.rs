@@ -0,0 +1,133 @@
+//! # C.Audit Annotations Projection
+//!
+//! Auditors attach findings to a historical entry by committing an
+//! `annotation.created` atom to C.Audit that references the target's
+//! `entry_hash`. The annotated entry is never touched; this projection only
+//! indexes annotations by target so reads can join them back.
+//!
+//! Atom shape:
+//! ```json
+//! {
+//!   "type": "annotation.created",
+//!   "annotation_id": "ann_...",
+//!   "target": {"entry_hash": "...", "container_id": "C.Jobs", "sequence": 42},
+//!   "author": "sid:...",
+//!   "kind": "finding",
+//!   "body": "Amount does not match invoice #123",
+//!   "evidence": [{"uri": "s3://...", "sha256": "...", "media_type": "application/pdf"}],
+//!   "ts_ms": 1735689600000
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{debug, info};
+
+/// Container holding annotation events
+pub const AUDIT_CONTAINER: &str = "C.Audit";
+
+/// Annotations Projection Handler
+pub struct AnnotationsProjection {
+    pool: PgPool,
+}
+
+impl AnnotationsProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Process an event and update projections
+    pub async fn process_event(
+        &self,
+        event_type: &str,
+        atom: &Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> anyhow::Result<()> {
+        match event_type {
+            "annotation.created" => self.handle_annotation_created(atom, entry_hash, sequence).await,
+            _ => {
+                debug!("Ignoring unknown audit event type: {}", event_type);
+                Ok(())
+            }
+        }
+    }
+
+    async fn handle_annotation_created(&self, atom: &Value, entry_hash: &str, sequence: i64) -> anyhow::Result<()> {
+        let target = atom.get("target").cloned().unwrap_or(Value::Null);
+        let target_entry_hash = target
+            .get("entry_hash")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("annotation.created without target.entry_hash"))?;
+        let annotation_id = atom
+            .get("annotation_id")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| format!("ann_{}", &entry_hash[..entry_hash.len().min(16)]));
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_annotations
+                (annotation_id, target_entry_hash, target_container_id, target_sequence,
+                 author, kind, body, evidence, created_at_ms, entry_hash, sequence)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (annotation_id) DO NOTHING
+            "#,
+        )
+        .bind(&annotation_id)
+        .bind(target_entry_hash)
+        .bind(target.get("container_id").and_then(|v| v.as_str()))
+        .bind(target.get("sequence").and_then(|v| v.as_i64()))
+        .bind(atom.get("author").and_then(|v| v.as_str()).unwrap_or("unknown"))
+        .bind(atom.get("kind").and_then(|v| v.as_str()).unwrap_or("note"))
+        .bind(atom.get("body").and_then(|v| v.as_str()).unwrap_or(""))
+        .bind(atom.get("evidence").cloned().unwrap_or_else(|| serde_json::json!([])))
+        .bind(atom.get("ts_ms").and_then(|v| v.as_i64()).unwrap_or(0))
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        info!("✅ Audit projection: annotation {} on {}", annotation_id, target_entry_hash);
+        Ok(())
+    }
+
+    /// All annotations on the given entries, oldest first
+    pub async fn for_entries(&self, entry_hashes: &[String]) -> anyhow::Result<Vec<AnnotationRow>> {
+        if entry_hashes.is_empty() {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query_as::<_, AnnotationRow>(
+            r#"
+            SELECT annotation_id, target_entry_hash, author, kind, body, evidence,
+                   created_at_ms, entry_hash, sequence
+            FROM projection_annotations
+            WHERE target_entry_hash = ANY($1)
+            ORDER BY sequence ASC
+            "#,
+        )
+        .bind(entry_hashes)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+}
+
+// =============================================================================
+// Query Types
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AnnotationRow {
+    pub annotation_id: String,
+    pub target_entry_hash: String,
+    pub author: String,
+    pub kind: String,
+    pub body: String,
+    pub evidence: Value,
+    pub created_at_ms: i64,
+    /// The annotation's own C.Audit entry
+    pub entry_hash: String,
+    pub sequence: i64,
+}
@@ -0,0 +1,149 @@
+//! Job Board — kanban view over `projection_jobs`
+//!
+//! Groups a tenant's jobs by state, each card carrying its assignee and the
+//! conversation it was born in (`conversation_id` on `job.created`, joined
+//! to `projection_conversations` for the name). Read-only; rows are scoped
+//! to the caller like every other `/query` route.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+use super::scope::{ScopedTable, Viewer};
+
+/// Column order of the board; states not listed here are appended after
+pub const BOARD_STATES: &[&str] = &[
+    "draft",
+    "proposed",
+    "approved",
+    "in_progress",
+    "waiting_input",
+    "completed",
+    "rejected",
+    "cancelled",
+    "failed",
+];
+
+/// Most cards returned per board
+const BOARD_LIMIT: i64 = 500;
+
+/// One job on the board
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct BoardCard {
+    pub job_id: String,
+    pub title: String,
+    pub state: String,
+    /// Owner entity the job is assigned to
+    pub assignee: String,
+    pub waiting_on: Option<Vec<String>>,
+    /// Originating conversation; None for jobs not created from one
+    pub conversation_id: Option<String>,
+    pub conversation_name: Option<String>,
+    pub updated_at: OffsetDateTime,
+    pub last_event_seq: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub state: String,
+    pub jobs: Vec<BoardCard>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Board {
+    pub tenant_id: String,
+    pub columns: Vec<BoardColumn>,
+}
+
+/// Group cards into columns: every known state (possibly empty) in board
+/// order, then unknown states in order of first appearance. Card order
+/// within a column is preserved.
+pub fn group_into_columns(cards: Vec<BoardCard>) -> Vec<BoardColumn> {
+    let mut columns: Vec<BoardColumn> = BOARD_STATES
+        .iter()
+        .map(|s| BoardColumn { state: s.to_string(), jobs: Vec::new() })
+        .collect();
+    for card in cards {
+        match columns.iter_mut().find(|c| c.state == card.state) {
+            Some(column) => column.jobs.push(card),
+            None => columns.push(BoardColumn { state: card.state.clone(), jobs: vec![card] }),
+        }
+    }
+    columns
+}
+
+/// Board projection reader
+pub struct BoardProjection {
+    pool: PgPool,
+}
+
+impl BoardProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Board of `tenant_id` as `viewer` may see it, most recently updated first
+    pub async fn board(&self, viewer: &Viewer, tenant_id: &str) -> Result<Board, sqlx::Error> {
+        let sql = format!(
+            r#"
+            SELECT projection_jobs.job_id, projection_jobs.title, projection_jobs.state,
+                   projection_jobs.owner_entity_id AS assignee, projection_jobs.waiting_on,
+                   NULLIF(projection_jobs.conversation_id, '') AS conversation_id,
+                   c.name AS conversation_name,
+                   projection_jobs.updated_at, projection_jobs.last_event_seq
+            FROM projection_jobs
+            LEFT JOIN projection_conversations c ON c.conversation_id = projection_jobs.conversation_id
+            WHERE projection_jobs.tenant_id = $1 AND {}
+            ORDER BY projection_jobs.updated_at DESC
+            LIMIT $2
+            "#,
+            viewer.filter(ScopedTable::Jobs, 3)
+        );
+        let query = sqlx::query_as::<_, BoardCard>(&sql).bind(tenant_id).bind(BOARD_LIMIT);
+        let cards = viewer.bind(query).fetch_all(&self.pool).await?;
+
+        Ok(Board {
+            tenant_id: tenant_id.to_string(),
+            columns: group_into_columns(cards),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(job_id: &str, state: &str) -> BoardCard {
+        BoardCard {
+            job_id: job_id.into(),
+            title: job_id.into(),
+            state: state.into(),
+            assignee: "ent_office".into(),
+            waiting_on: None,
+            conversation_id: Some("conv_1".into()),
+            conversation_name: None,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+            last_event_seq: 1,
+        }
+    }
+
+    #[test]
+    fn test_group_into_columns() {
+        let columns = group_into_columns(vec![
+            card("j1", "in_progress"),
+            card("j2", "proposed"),
+            card("j3", "in_progress"),
+            card("j4", "archived"),
+        ]);
+
+        let states: Vec<&str> = columns.iter().map(|c| c.state.as_str()).collect();
+        let mut expected = BOARD_STATES.to_vec();
+        expected.push("archived");
+        assert_eq!(states, expected);
+
+        let in_progress = columns.iter().find(|c| c.state == "in_progress").unwrap();
+        let ids: Vec<&str> = in_progress.jobs.iter().map(|j| j.job_id.as_str()).collect();
+        assert_eq!(ids, vec!["j1", "j3"]);
+        assert!(columns.iter().find(|c| c.state == "completed").unwrap().jobs.is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+//! Broadcast Channels — one-to-many announcements over C.Messenger
+//!
+//! A conversation created with `kind: "broadcast"` lists designated
+//! `senders`; nobody else may post to it (checked with [`may_post`] before a
+//! message is committed). An announcement is a single `message.created`
+//! link: this projection fans it out into one delivery row per member, and
+//! `message.read` marks the member's row read. Delivery and read counts are
+//! aggregated per announcement by the `projection_broadcast_stats` view.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::info;
+
+/// `kind` of a broadcast conversation (anything else is a chat)
+pub const BROADCAST_KIND: &str = "broadcast";
+
+/// Limits on broadcast membership, checked when the channel is created
+#[derive(Debug, Clone)]
+pub struct BroadcastLimits {
+    /// Members an announcement fans out to at most
+    pub max_members: usize,
+    pub max_senders: usize,
+}
+
+impl Default for BroadcastLimits {
+    fn default() -> Self {
+        Self { max_members: 10_000, max_senders: 20 }
+    }
+}
+
+impl BroadcastLimits {
+    /// Defaults overridden by `UBL_BROADCAST_MAX_MEMBERS` / `UBL_BROADCAST_MAX_SENDERS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: usize| {
+            std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            max_members: get("UBL_BROADCAST_MAX_MEMBERS", defaults.max_members),
+            max_senders: get("UBL_BROADCAST_MAX_SENDERS", defaults.max_senders),
+        }
+    }
+
+    /// Canonical sender list for a new channel: the creator always sends,
+    /// every sender must be a member
+    pub fn senders(&self, creator: &str, participants: &[String], senders: &[String]) -> Result<Vec<String>, String> {
+        if participants.len() > self.max_members {
+            return Err(format!("broadcast has {} members, limit is {}", participants.len(), self.max_members));
+        }
+        let mut out: Vec<String> = senders.to_vec();
+        out.push(creator.to_string());
+        out.sort();
+        out.dedup();
+        if let Some(outsider) = out.iter().find(|s| !participants.contains(s)) {
+            return Err(format!("sender {} is not a member of the broadcast", outsider));
+        }
+        if out.len() > self.max_senders {
+            return Err(format!("broadcast has {} senders, limit is {}", out.len(), self.max_senders));
+        }
+        Ok(out)
+    }
+}
+
+/// Delivery and read counts of one announcement
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AnnouncementStats {
+    pub message_id: String,
+    pub from_id: String,
+    pub delivered: i64,
+    pub read: i64,
+    pub delivered_at: OffsetDateTime,
+    pub last_read_at: Option<OffsetDateTime>,
+}
+
+/// One announcement in a member's timeline
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InboxItem {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub conversation_name: Option<String>,
+    pub from_id: String,
+    pub content_hash: String,
+    pub delivered_at: OffsetDateTime,
+    pub read_at: Option<OffsetDateTime>,
+}
+
+/// Whether `sid` may post to `conversation_id`; only broadcasts restrict it
+pub async fn may_post(pool: &PgPool, conversation_id: &str, sid: &str) -> Result<bool, sqlx::Error> {
+    let row: Option<(String, Vec<String>)> = sqlx::query_as(
+        "SELECT kind, senders FROM projection_conversations WHERE conversation_id = $1",
+    )
+    .bind(conversation_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match row {
+        Some((kind, senders)) if kind == BROADCAST_KIND => senders.iter().any(|s| s == sid),
+        _ => true,
+    })
+}
+
+/// Broadcast projection handler
+pub struct BroadcastProjection {
+    pool: PgPool,
+}
+
+impl BroadcastProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Fan a message out to every member but its author, if its conversation
+    /// is a broadcast; returns the rows delivered
+    pub async fn fan_out(
+        &self,
+        message_id: &str,
+        conversation_id: &str,
+        from_id: &str,
+        tenant_id: &str,
+        sequence: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO projection_broadcast_deliveries
+                (message_id, member_id, conversation_id, tenant_id, last_event_seq)
+            SELECT $1, member, c.conversation_id, $4, $5
+            FROM projection_conversations c, unnest(c.participants) AS member
+            WHERE c.conversation_id = $2 AND c.kind = 'broadcast' AND member <> $3
+            ON CONFLICT (message_id, member_id) DO NOTHING
+            "#,
+        )
+        .bind(message_id)
+        .bind(conversation_id)
+        .bind(from_id)
+        .bind(tenant_id)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        let delivered = result.rows_affected();
+        if delivered > 0 {
+            info!("📣 Announcement {} in {} fanned out to {} members", message_id, conversation_id, delivered);
+        }
+        Ok(delivered)
+    }
+
+    /// Mark the member's delivery read (first read wins)
+    pub async fn mark_read(&self, message_id: &str, member_id: &str, sequence: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE projection_broadcast_deliveries
+            SET read_at = NOW(), last_event_seq = $3
+            WHERE message_id = $1 AND member_id = $2 AND read_at IS NULL AND last_event_seq < $3
+            "#,
+        )
+        .bind(message_id)
+        .bind(member_id)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Announcements delivered to `member_id`, newest first
+    pub async fn inbox(&self, member_id: &str, unread_only: bool, limit: i64) -> Result<Vec<InboxItem>, sqlx::Error> {
+        sqlx::query_as::<_, InboxItem>(
+            r#"
+            SELECT d.message_id, d.conversation_id, c.name AS conversation_name,
+                   m.from_id, m.content_hash, d.delivered_at, d.read_at
+            FROM projection_broadcast_deliveries d
+            JOIN projection_messages m ON m.message_id = d.message_id
+            LEFT JOIN projection_conversations c ON c.conversation_id = d.conversation_id
+            WHERE d.member_id = $1 AND (NOT $2 OR d.read_at IS NULL)
+            ORDER BY d.delivered_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(member_id)
+        .bind(unread_only)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Per-announcement counts of a broadcast, newest first
+    pub async fn stats(&self, conversation_id: &str, limit: i64) -> Result<Vec<AnnouncementStats>, sqlx::Error> {
+        sqlx::query_as::<_, AnnouncementStats>(
+            r#"
+            SELECT s.message_id, m.from_id, s.delivered, s.read, s.delivered_at, s.last_read_at
+            FROM projection_broadcast_stats s
+            JOIN projection_messages m ON m.message_id = s.message_id
+            WHERE s.conversation_id = $1
+            ORDER BY s.delivered_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(conversation_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Creator, senders and tenant of a broadcast; None for chats
+    pub async fn channel(&self, conversation_id: &str) -> Result<Option<(String, Vec<String>, Option<String>)>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT created_by, senders, tenant_id FROM projection_conversations
+            WHERE conversation_id = $1 AND kind = 'broadcast'
+            "#,
+        )
+        .bind(conversation_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_senders_include_creator_and_must_be_members() {
+        let limits = BroadcastLimits::default();
+        let participants = members(&["alice", "bob", "carol"]);
+
+        assert_eq!(limits.senders("alice", &participants, &[]).unwrap(), members(&["alice"]));
+        assert_eq!(
+            limits.senders("carol", &participants, &members(&["bob", "carol"])).unwrap(),
+            members(&["bob", "carol"])
+        );
+        assert!(limits.senders("alice", &participants, &members(&["mallory"])).is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = BroadcastLimits { max_members: 3, max_senders: 1 };
+        assert!(limits.senders("a", &members(&["a", "b", "c", "d"]), &[]).is_err());
+        assert!(limits.senders("a", &members(&["a", "b"]), &members(&["b"])).is_err());
+        assert!(limits.senders("a", &members(&["a", "b"]), &[]).is_ok());
+    }
+}
@@ -0,0 +1,176 @@
+//! # Evolution Changelog Projection
+//!
+//! Records every Evolution atom (`evolution.*`) with a structured diff against
+//! the previous version of the same subject, so a container's rule changes
+//! read as a history instead of a list of atoms. Subjects, targets and diffs
+//! come from `crate::evolution`:
+//!
+//! - policy updates diff by version, description, `default_deny` and rules
+//!   (matched by `rule_id`);
+//! - manifests by intent classes, description, `max_atom_bytes` and asset;
+//! - FSM updates by initial state, states and transitions;
+//! - anything else field by field.
+//!
+//! Evolutions may be committed to any container; rows are listed by the
+//! container whose rules changed.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::info;
+
+use crate::evolution::{self, FieldChange};
+use crate::query_params::Cursor;
+
+/// Atom type prefix of every evolution
+pub const EVOLUTION_PREFIX: &str = "evolution.";
+
+/// One evolution of a container's rules, with what it changed
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EvolutionEntry {
+    pub entry_hash: String,
+    /// Container the Evolution link was committed to
+    pub container_id: String,
+    pub sequence: i64,
+    pub target_container_id: String,
+    pub evolution_type: String,
+    pub subject: String,
+    /// Entry of the version this one replaced; `None` for the first
+    pub previous_entry_hash: Option<String>,
+    pub atom: Value,
+    /// `[FieldChange]`
+    pub changes: Value,
+    /// `changes` rendered one line each
+    pub summary: Vec<String>,
+    pub committed_at_ms: i64,
+}
+
+/// Evolution Changelog Projection Handler
+pub struct ChangelogProjection {
+    pool: PgPool,
+}
+
+impl ChangelogProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record an evolution; other event types are ignored. Returns whether a
+    /// row was written.
+    pub async fn process_event(
+        &self,
+        container_id: &str,
+        event_type: &str,
+        atom: &Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<bool, sqlx::Error> {
+        if !event_type.starts_with(EVOLUTION_PREFIX) {
+            return Ok(false);
+        }
+        let target = evolution::changelog_target(container_id, atom);
+        let subject = evolution::changelog_subject(atom);
+
+        let previous: Option<(String, Value)> = sqlx::query_as(
+            r#"
+            SELECT entry_hash, atom
+            FROM projection_evolution_changelog
+            WHERE target_container_id = $1 AND evolution_type = $2 AND subject = $3 AND entry_hash <> $4
+            ORDER BY committed_at_ms DESC, sequence DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(&target)
+        .bind(event_type)
+        .bind(&subject)
+        .bind(entry_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let changes = evolution::changelog_diff(previous.as_ref().map(|(_, atom)| atom), atom);
+        let summary: Vec<String> = changes.iter().map(FieldChange::render).collect();
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO projection_evolution_changelog (
+                entry_hash, container_id, sequence, target_container_id, evolution_type, subject,
+                previous_entry_hash, atom, changes, summary, committed_at_ms
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
+                COALESCE((SELECT ts_unix_ms FROM ledger_entry WHERE container_id = $2 AND sequence = $3), $11)
+            )
+            ON CONFLICT (entry_hash) DO NOTHING
+            "#,
+        )
+        .bind(entry_hash)
+        .bind(container_id)
+        .bind(sequence)
+        .bind(&target)
+        .bind(event_type)
+        .bind(&subject)
+        .bind(previous.as_ref().map(|(hash, _)| hash.as_str()))
+        .bind(atom)
+        .bind(serde_json::to_value(&changes).unwrap_or_default())
+        .bind(&summary)
+        .bind(crate::timestamps::now_ms())
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if inserted {
+            info!("🧬 Evolution {} on {} ({}): {} change(s)", event_type, target, subject, changes.len());
+        }
+        Ok(inserted)
+    }
+
+    /// Version of a subject in force: its newest evolution
+    pub async fn latest(
+        &self,
+        target_container_id: &str,
+        evolution_type: &str,
+        subject: &str,
+    ) -> Result<Option<EvolutionEntry>, sqlx::Error> {
+        sqlx::query_as::<_, EvolutionEntry>(
+            r#"
+            SELECT entry_hash, container_id, sequence, target_container_id, evolution_type, subject,
+                   previous_entry_hash, atom, changes, summary, committed_at_ms
+            FROM projection_evolution_changelog
+            WHERE target_container_id = $1 AND evolution_type = $2 AND subject = $3
+            ORDER BY committed_at_ms DESC, sequence DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(target_container_id)
+        .bind(evolution_type)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Evolutions of `target_container_id` before the cursor (commit time,
+    /// sequence), newest first
+    pub async fn history(
+        &self,
+        target_container_id: &str,
+        limit: i64,
+        before: Cursor,
+    ) -> Result<Vec<EvolutionEntry>, sqlx::Error> {
+        sqlx::query_as::<_, EvolutionEntry>(
+            r#"
+            SELECT entry_hash, container_id, sequence, target_container_id, evolution_type, subject,
+                   previous_entry_hash, atom, changes, summary, committed_at_ms
+            FROM projection_evolution_changelog
+            WHERE target_container_id = $1 AND (committed_at_ms, sequence) < ($2, $3)
+            ORDER BY committed_at_ms DESC, sequence DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(target_container_id)
+        .bind(before.key)
+        .bind(before.tiebreak)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
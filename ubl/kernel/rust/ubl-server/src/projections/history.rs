@@ -0,0 +1,170 @@
+//! Projection history (slowly changing dimensions)
+//!
+//! Projections keep current rows only. For the tables selected with
+//! `UBL_PROJECTION_HISTORY`, a trigger (`ubl/sql/10_projections/135_projection_history.sql`)
+//! also keeps every version of a row with the window it was current in, so
+//! "when did this job become blocked" is a query rather than a ledger replay.
+//!
+//! Read routes take `?as_of=<unix ms>` and run their usual query against
+//! [`AsOf::source`]: the live table, or the versions current at that instant
+//! under the table's name, so column lists and scope filters apply unchanged.
+
+use std::sync::OnceLock;
+
+use axum::http::StatusCode;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+/// Projections that can keep history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tracked {
+    Jobs,
+    Approvals,
+}
+
+impl Tracked {
+    pub const ALL: [Tracked; 2] = [Tracked::Jobs, Tracked::Approvals];
+
+    /// Name in `UBL_PROJECTION_HISTORY`
+    pub fn name(self) -> &'static str {
+        match self {
+            Tracked::Jobs => "jobs",
+            Tracked::Approvals => "approvals",
+        }
+    }
+
+    pub fn table(self) -> &'static str {
+        match self {
+            Tracked::Jobs => "projection_jobs",
+            Tracked::Approvals => "projection_approvals",
+        }
+    }
+
+    fn key_column(self) -> &'static str {
+        match self {
+            Tracked::Jobs => "job_id",
+            Tracked::Approvals => "approval_id",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+}
+
+/// Which projections keep history; none by default
+#[derive(Debug, Clone, Default)]
+pub struct HistoryConfig {
+    pub tracked: Vec<Tracked>,
+}
+
+impl HistoryConfig {
+    /// `UBL_PROJECTION_HISTORY`: comma-separated projection names, or `all`
+    pub fn from_env() -> Self {
+        Self::parse(&std::env::var("UBL_PROJECTION_HISTORY").unwrap_or_default())
+    }
+
+    fn parse(value: &str) -> Self {
+        let mut tracked = Vec::new();
+        for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if name == "all" {
+                return Self { tracked: Tracked::ALL.to_vec() };
+            }
+            match Tracked::parse(name) {
+                Some(t) if !tracked.contains(&t) => tracked.push(t),
+                Some(_) => {}
+                None => warn!("UBL_PROJECTION_HISTORY: unknown projection {}", name),
+            }
+        }
+        Self { tracked }
+    }
+
+    pub fn keeps(&self, projection: Tracked) -> bool {
+        self.tracked.contains(&projection)
+    }
+}
+
+/// Process-wide history configuration, read from the environment once
+pub fn config() -> &'static HistoryConfig {
+    static CONFIG: OnceLock<HistoryConfig> = OnceLock::new();
+    CONFIG.get_or_init(HistoryConfig::from_env)
+}
+
+/// Install the history trigger on the selected tables and drop it from the
+/// others. Versions already kept stay readable in the database but are not
+/// served while their projection is unselected.
+pub async fn install(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let config = config();
+    for projection in Tracked::ALL {
+        sqlx::query("SELECT projection_history_track($1, $2, $3)")
+            .bind(projection.table())
+            .bind(projection.key_column())
+            .bind(config.keeps(projection))
+            .execute(pool)
+            .await?;
+    }
+    if !config.tracked.is_empty() {
+        let names: Vec<_> = config.tracked.iter().map(|t| t.name()).collect();
+        info!("🕰️ Projection history kept for {}", names.join(", "));
+    }
+    Ok(())
+}
+
+/// `?as_of=<unix ms>`: read the projection as it was at that instant
+#[derive(Debug, Default, Deserialize)]
+pub struct AsOf {
+    pub as_of: Option<i64>,
+}
+
+impl AsOf {
+    /// What to select `projection`'s rows from. Without `as_of`, the table
+    /// itself; a 400 if its history is not kept.
+    pub fn source(&self, projection: Tracked) -> Result<String, (StatusCode, String)> {
+        self.source_in(config(), projection)
+    }
+
+    fn source_in(&self, config: &HistoryConfig, projection: Tracked) -> Result<String, (StatusCode, String)> {
+        let table = projection.table();
+        let Some(at) = self.as_of else {
+            return Ok(table.to_string());
+        };
+        if !config.keeps(projection) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("history is not kept for {} (UBL_PROJECTION_HISTORY)", projection.name()),
+            ));
+        }
+        Ok(format!(
+            "(SELECT (jsonb_populate_record(NULL::{table}, row_data)).* FROM projection_history \
+             WHERE table_name = '{table}' AND valid_from_ms <= {at} \
+             AND (valid_to_ms IS NULL OR valid_to_ms > {at})) AS {table}"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_parse() {
+        assert!(HistoryConfig::parse("").tracked.is_empty());
+        assert_eq!(HistoryConfig::parse("jobs, jobs,bogus").tracked, vec![Tracked::Jobs]);
+        assert_eq!(HistoryConfig::parse("approvals,all").tracked, Tracked::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_source() {
+        let config = HistoryConfig::parse("jobs");
+        let now = AsOf::default();
+        assert_eq!(now.source_in(&config, Tracked::Approvals).unwrap(), "projection_approvals");
+
+        let then = AsOf { as_of: Some(1_700_000_000_000) };
+        let source = then.source_in(&config, Tracked::Jobs).unwrap();
+        assert!(source.contains("NULL::projection_jobs"));
+        assert!(source.contains("valid_from_ms <= 1700000000000"));
+        assert!(source.ends_with("AS projection_jobs"));
+        assert_eq!(then.source_in(&config, Tracked::Approvals).unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
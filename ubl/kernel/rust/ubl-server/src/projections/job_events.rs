@@ -1,11 +1,18 @@
 //! Job Events Projection — Timeline items for job drawer
 //!
 //! Builds timeline items from job events for the job drawer UI.
-//! Events: job.created, job.state_changed, tool.called, tool.result, approval.decided
+//! Events: job.created, job.state_changed, job.timeout, tool.called, tool.result,
+//! tool.artifact_card, approval.decided
+//!
+//! Items are read in the HLC order of their entries: artifact cards come from
+//! other containers than C.Jobs, so sequences alone do not order a drawer.
 
 use sqlx::PgPool;
 use time::OffsetDateTime;
 use tracing::{info, error};
+use ubl_events::JobEvent;
+
+use crate::hlc::Hlc;
 
 /// Job events projection handler
 pub struct JobEventsProjection {
@@ -24,6 +31,7 @@ impl JobEventsProjection {
         atom: &serde_json::Value,
         entry_hash: &str,
         sequence: i64,
+        hlc: Hlc,
         tenant_id: &str,
     ) -> Result<(), sqlx::Error> {
         let job_id = atom.get("job_id")
@@ -45,24 +53,25 @@ impl JobEventsProjection {
             .unwrap_or("system")
             .to_string();
 
-        sqlx::query!(
+        sqlx::query(
             r#"
             INSERT INTO projection_job_events (
                 tenant_id, job_id, cursor, ts, event_id, event_type,
-                actor_entity_id, timeline_item
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                actor_entity_id, timeline_item, hlc
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             ON CONFLICT (tenant_id, job_id, cursor) DO UPDATE SET
                 timeline_item = EXCLUDED.timeline_item
             "#,
-            tenant_id,
-            job_id,
-            cursor,
-            ts,
-            entry_hash,
-            event_type,
-            actor_entity_id,
-            timeline_item
         )
+        .bind(tenant_id)
+        .bind(job_id)
+        .bind(&cursor)
+        .bind(ts)
+        .bind(entry_hash)
+        .bind(event_type)
+        .bind(&actor_entity_id)
+        .bind(&timeline_item)
+        .bind(hlc.to_string())
         .execute(&self.pool)
         .await?;
 
@@ -76,43 +85,69 @@ impl JobEventsProjection {
         atom: &serde_json::Value,
         ts: &OffsetDateTime,
     ) -> Result<serde_json::Value, sqlx::Error> {
-        let item = match event_type {
-            "job.created" => serde_json::json!({
+        let timestamp_ms = crate::timestamps::from_datetime(*ts);
+        let timestamp = crate::timestamps::rfc3339_utc(timestamp_ms);
+        let event = if JobEvent::is_known(event_type) { JobEvent::from_atom(atom).ok() } else { None };
+        let item = match (event_type, event) {
+            (_, Some(JobEvent::Created(e))) => serde_json::json!({
                 "type": "job_created",
-                "title": atom.get("title").and_then(|v| v.as_str()).unwrap_or("Job created"),
-                "description": atom.get("description").and_then(|v| v.as_str()),
-                "timestamp": ts.to_string(),
+                "title": if e.title.is_empty() { "Job created" } else { e.title.as_str() },
+                "description": e.description,
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
             }),
-            "job.state_changed" => serde_json::json!({
+            (_, Some(JobEvent::StateChanged(e))) => serde_json::json!({
                 "type": "state_changed",
-                "from": atom.get("from_state").and_then(|v| v.as_str()),
-                "to": atom.get("to_state").and_then(|v| v.as_str()),
-                "reason": atom.get("reason").and_then(|v| v.as_str()),
-                "timestamp": ts.to_string(),
+                "from": e.from_state,
+                "to": e.to_state,
+                "reason": e.reason,
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
+            }),
+            (_, Some(JobEvent::Timeout(e))) => serde_json::json!({
+                "type": "state_changed",
+                "from": e.from_state,
+                "to": e.to_state,
+                "reason": "timeout",
+                "timeout_secs": e.timeout_secs,
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
+            }),
+            (_, Some(JobEvent::ApprovalDecided(e))) => serde_json::json!({
+                "type": "approval_decided",
+                "decision": e.decision,
+                "reason": e.reason,
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
             }),
-            "tool.called" => serde_json::json!({
+            ("tool.called", _) => serde_json::json!({
                 "type": "tool_called",
                 "tool_name": atom.get("tool_name").and_then(|v| v.as_str()),
                 "purpose": atom.get("purpose").and_then(|v| v.as_str()),
-                "timestamp": ts.to_string(),
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
             }),
-            "tool.result" => serde_json::json!({
+            ("tool.result", _) => serde_json::json!({
                 "type": "tool_result",
                 "tool_name": atom.get("tool_name").and_then(|v| v.as_str()),
                 "status": atom.get("status").and_then(|v| v.as_str()),
                 "success": atom.get("status").and_then(|v| v.as_str()) == Some("success"),
-                "timestamp": ts.to_string(),
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
             }),
-            "approval.decided" => serde_json::json!({
-                "type": "approval_decided",
-                "decision": atom.get("decision").and_then(|v| v.as_str()),
-                "reason": atom.get("reason").and_then(|v| v.as_str()),
-                "timestamp": ts.to_string(),
+            ("tool.artifact_card", _) => serde_json::json!({
+                "type": "artifact_card",
+                "tool_name": atom.get("tool_name").and_then(|v| v.as_str()),
+                "tool_call_id": atom.get("tool_call_id").and_then(|v| v.as_str()),
+                "card": atom.get("card"),
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
             }),
             _ => serde_json::json!({
                 "type": "unknown",
                 "event_type": event_type,
-                "timestamp": ts.to_string(),
+                "timestamp": timestamp,
+                "timestamp_ms": timestamp_ms,
             }),
         };
 
@@ -126,22 +161,21 @@ impl JobEventsProjection {
         job_id: &str,
         limit: i64,
     ) -> Result<Vec<serde_json::Value>, sqlx::Error> {
-        let rows = sqlx::query!(
+        // HLC order: `ts` is this replica's clock when it projected
+        sqlx::query_scalar(
             r#"
             SELECT timeline_item
             FROM projection_job_events
             WHERE tenant_id = $1 AND job_id = $2
-            ORDER BY ts DESC
+            ORDER BY hlc DESC NULLS LAST
             LIMIT $3
             "#,
-            tenant_id,
-            job_id,
-            limit
         )
+        .bind(tenant_id)
+        .bind(job_id)
+        .bind(limit)
         .fetch_all(&self.pool)
-        .await?;
-
-        Ok(rows.into_iter().map(|r| r.timeline_item).collect())
+        .await
     }
 }
 
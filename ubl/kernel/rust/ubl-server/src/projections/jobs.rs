@@ -3,7 +3,11 @@
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use time::OffsetDateTime;
-use tracing::{info, error};
+use tracing::{info, warn, error};
+use ubl_events::{
+    ApprovalDecided, ApprovalRequested, JobCancelled, JobCompleted, JobCreated, JobEvent, JobProgress, JobStarted,
+    JobTimeout,
+};
 
 /// Job record in projection
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -66,39 +70,60 @@ impl JobsProjection {
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        match event_type {
-            "job.created" => self.handle_job_created(atom, entry_hash, sequence).await,
-            "job.started" => self.handle_job_started(atom, entry_hash, sequence).await,
-            "job.progress" => self.handle_job_progress(atom, entry_hash, sequence).await,
-            "job.completed" => self.handle_job_completed(atom, entry_hash, sequence).await,
-            "job.cancelled" => self.handle_job_cancelled(atom, entry_hash, sequence).await,
-            "approval.requested" => self.handle_approval_requested(atom, entry_hash, sequence).await,
-            "approval.decided" => self.handle_approval_decided(atom, entry_hash, sequence).await,
-            _ => {
-                info!("Unknown job event type: {}", event_type);
-                Ok(())
+        if event_type == crate::job_templates::TEMPLATE_TYPE {
+            return self.handle_template(atom, entry_hash, sequence).await;
+        }
+        if !JobEvent::is_known(event_type) {
+            info!("Unknown job event type: {}", event_type);
+            return Ok(());
+        }
+        let event = match JobEvent::from_atom(atom) {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Malformed {} atom at seq={} not projected: {}", event_type, sequence, e);
+                return Ok(());
             }
+        };
+        let tenant_id = event.tenant_id().unwrap_or("default").to_string();
+
+        match event {
+            JobEvent::Created(e) => self.handle_job_created(&e, &tenant_id, entry_hash, sequence).await,
+            JobEvent::Started(e) => self.handle_job_started(&e, &tenant_id, entry_hash, sequence).await,
+            JobEvent::Progress(e) => self.handle_job_progress(&e, entry_hash, sequence).await,
+            JobEvent::Completed(e) => self.handle_job_completed(&e, &tenant_id, entry_hash, sequence).await,
+            JobEvent::Cancelled(e) => self.handle_job_cancelled(&e, &tenant_id, entry_hash, sequence).await,
+            JobEvent::Timeout(e) => self.handle_job_timeout(&e, entry_hash, sequence).await,
+            JobEvent::ApprovalRequested(e) => self.handle_approval_requested(&e, entry_hash, sequence).await,
+            JobEvent::ApprovalDecided(e) => self.handle_approval_decided(&e, entry_hash, sequence).await,
+            // Transitions and card actions only feed the timeline and presence
+            JobEvent::StateChanged(_) | JobEvent::Approve(_) | JobEvent::Reject(_) | JobEvent::ProvideInput(_) => Ok(()),
         }
     }
 
     async fn handle_job_created(
         &self,
-        atom: &serde_json::Value,
+        event: &JobCreated,
+        tenant_id: &str,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let job_id = atom["id"].as_str().unwrap_or_default();
-        let conversation_id = atom["conversation_id"].as_str().unwrap_or_default();
-        let title = atom["title"].as_str().unwrap_or_default();
-        let description = atom["description"].as_str().unwrap_or_default();
-        let priority = atom["priority"].as_str().unwrap_or("normal");
-        let assigned_to = atom["assigned_to"].as_str();
-        let created_by = atom["created_by"].as_str().unwrap_or_default();
-        let created_at = atom["created_at"].as_str().unwrap_or_default();
-        let estimated_duration = atom["estimated_duration_seconds"].as_i64().map(|v| v as i32);
-        let estimated_value = atom["estimated_value"].as_f64();
+        let job_id = event.job_id.as_str();
+        // Originating conversation; kept on both tables so the board can link back
+        let conversation_id = event.conversation_id.as_deref().unwrap_or_default();
+        let title = if event.title.is_empty() { event.goal.as_deref().unwrap_or_default() } else { &event.title };
+        let description = event.description.as_deref().unwrap_or_default();
+        let goal = event.goal_or_title();
+        let priority = event.priority.as_deref().unwrap_or("normal");
+        let assigned_to = event.assigned_to.as_deref();
+        let owner = event.owner().unwrap_or_default();
+        let created_by = event.created_by.as_deref().unwrap_or_default();
+        let created_at = event.created_at.as_deref().unwrap_or_default();
+        let estimated_duration = event.estimated_duration_seconds.map(|v| v as i32);
+        let estimated_value = event.estimated_value;
+        let now = time::OffsetDateTime::now_utc();
 
-        sqlx::query(
+        // Update old table
+        let _ = sqlx::query(
             r#"
             INSERT INTO projection_jobs (
                 job_id, conversation_id, title, description, status, priority,
@@ -106,6 +131,7 @@ impl JobsProjection {
                 estimated_value, last_event_hash, last_event_seq
             ) VALUES ($1, $2, $3, $4, 'pending', $5, $6, $7, $8::timestamptz, $9, $10, $11, $12)
             ON CONFLICT (job_id) DO UPDATE SET
+                conversation_id = EXCLUDED.conversation_id,
                 title = EXCLUDED.title,
                 description = EXCLUDED.description,
                 priority = EXCLUDED.priority,
@@ -127,7 +153,37 @@ impl JobsProjection {
         .bind(entry_hash)
         .bind(sequence)
         .execute(&self.pool)
-        .await?;
+        .await;
+
+        // Update new table (Diamond Checklist #2: causal ordering)
+        let _ = sqlx::query(
+            r#"
+            INSERT INTO projection_jobs (
+                tenant_id, job_id, conversation_id, title, goal, state, owner_entity_id,
+                created_at, updated_at, last_activity_at, last_event_hash, last_event_seq
+            ) VALUES ($1, $2, $3, $4, $5, 'proposed', $6, $7, $7, $7, $8, $9)
+            ON CONFLICT (job_id) DO UPDATE SET
+                conversation_id = EXCLUDED.conversation_id,
+                title = EXCLUDED.title,
+                goal = EXCLUDED.goal,
+                owner_entity_id = EXCLUDED.owner_entity_id,
+                updated_at = EXCLUDED.updated_at,
+                last_event_hash = EXCLUDED.last_event_hash,
+                last_event_seq = EXCLUDED.last_event_seq
+            WHERE projection_jobs.last_event_seq < EXCLUDED.last_event_seq
+            "#
+        )
+        .bind(tenant_id)
+        .bind(job_id)
+        .bind(conversation_id)
+        .bind(title)
+        .bind(goal)
+        .bind(owner)
+        .bind(now)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await;
 
         info!("📋 Job created: {} - {}", job_id, title);
         Ok(())
@@ -135,13 +191,13 @@ impl JobsProjection {
 
     async fn handle_job_started(
         &self,
-        atom: &serde_json::Value,
+        event: &JobStarted,
+        tenant_id: &str,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let job_id = atom["job_id"].as_str().unwrap_or_default();
-        let started_at = atom["started_at"].as_str().unwrap_or_default();
-        let tenant_id = atom.get("tenant_id").and_then(|v| v.as_str()).unwrap_or("default");
+        let job_id = event.job_id.as_str();
+        let started_at = event.started_at.as_deref().unwrap_or_default();
         let now = time::OffsetDateTime::now_utc();
 
         // Update old table (Diamond Checklist #2: causal ordering)
@@ -183,13 +239,13 @@ impl JobsProjection {
 
     async fn handle_job_progress(
         &self,
-        atom: &serde_json::Value,
+        event: &JobProgress,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let job_id = atom["job_id"].as_str().unwrap_or_default();
-        let progress = atom["progress"].as_i64().unwrap_or(0) as i32;
-        let message = atom["message"].as_str();
+        let job_id = event.job_id.as_str();
+        let progress = event.progress;
+        let message = event.message.as_deref();
 
         sqlx::query(
             r#"
@@ -213,22 +269,24 @@ impl JobsProjection {
 
     async fn handle_job_completed(
         &self,
-        atom: &serde_json::Value,
+        event: &JobCompleted,
+        tenant_id: &str,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let job_id = atom["job_id"].as_str().unwrap_or_default();
-        let completed_at = atom["completed_at"].as_str().unwrap_or_default();
-        let summary = atom["result"]["summary"].as_str();
-        let artifacts = atom["result"]["artifacts"].clone();
-        let tenant_id = atom.get("tenant_id").and_then(|v| v.as_str()).unwrap_or("default");
+        let job_id = event.job_id.as_str();
+        let completed_at = event.finished_at().unwrap_or_default();
+        let summary = event.result.as_ref().and_then(|r| r.summary.as_deref());
+        let artifacts = event.result.as_ref().and_then(|r| r.artifacts.clone());
+        // A job that ran to the end and failed is not completed
+        let status = if event.success { "completed" } else { "failed" };
         let now = time::OffsetDateTime::now_utc();
 
         // Update old table (Diamond Checklist #2)
         let _ = sqlx::query(
             r#"
             UPDATE projection_jobs
-            SET status = 'completed', completed_at = $2::timestamptz,
+            SET status = $7, completed_at = $2::timestamptz,
                 progress = 100, result_summary = $3, result_artifacts = $4,
                 last_event_hash = $5, last_event_seq = $6
             WHERE job_id = $1 AND last_event_seq < $6
@@ -240,6 +298,7 @@ impl JobsProjection {
         .bind(artifacts)
         .bind(entry_hash)
         .bind(sequence)
+        .bind(status)
         .execute(&self.pool)
         .await;
 
@@ -247,7 +306,7 @@ impl JobsProjection {
         let _ = sqlx::query(
             r#"
             UPDATE projection_jobs
-            SET state = 'completed', updated_at = $2, last_activity_at = $2,
+            SET state = $6, updated_at = $2, last_activity_at = $2,
                 last_event_hash = $3, last_event_seq = $4
             WHERE tenant_id = $5 AND job_id = $1 AND last_event_seq < $4
             "#
@@ -257,22 +316,23 @@ impl JobsProjection {
         .bind(entry_hash)
         .bind(sequence)
         .bind(tenant_id)
+        .bind(status)
         .execute(&self.pool)
         .await;
 
-        info!("✅ Job completed: {}", job_id);
+        info!("✅ Job {}: {}", status, job_id);
         Ok(())
     }
 
     async fn handle_job_cancelled(
         &self,
-        atom: &serde_json::Value,
+        event: &JobCancelled,
+        tenant_id: &str,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let job_id = atom["job_id"].as_str().unwrap_or_default();
-        let cancelled_at = atom["cancelled_at"].as_str().unwrap_or_default();
-        let tenant_id = atom.get("tenant_id").and_then(|v| v.as_str()).unwrap_or("default");
+        let job_id = event.job_id.as_str();
+        let cancelled_at = event.cancelled_at.as_deref().unwrap_or_default();
         let now = time::OffsetDateTime::now_utc();
 
         // Update old table (Diamond Checklist #2)
@@ -312,18 +372,63 @@ impl JobsProjection {
         Ok(())
     }
 
+    async fn handle_job_timeout(
+        &self,
+        event: &JobTimeout,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (job_id, tenant_id) = (event.job_id.as_str(), event.tenant_id.as_str());
+        let now = time::OffsetDateTime::now_utc();
+
+        // Update old table (Diamond Checklist #2)
+        let _ = sqlx::query(
+            r#"
+            UPDATE projection_jobs
+            SET status = 'failed', last_event_hash = $2, last_event_seq = $3
+            WHERE job_id = $1 AND last_event_seq < $3
+            "#
+        )
+        .bind(job_id)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await;
+
+        // Update new table (Diamond Checklist #2)
+        // Only a job still running times out; a late completion wins the race
+        let _ = sqlx::query(
+            r#"
+            UPDATE projection_jobs
+            SET state = 'failed', updated_at = $2, last_activity_at = $2,
+                last_event_hash = $3, last_event_seq = $4
+            WHERE tenant_id = $5 AND job_id = $1 AND state = 'in_progress' AND last_event_seq < $4
+            "#
+        )
+        .bind(job_id)
+        .bind(now)
+        .bind(entry_hash)
+        .bind(sequence)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await;
+
+        info!("⏱️  Job timed out: {}", job_id);
+        Ok(())
+    }
+
     async fn handle_approval_requested(
         &self,
-        atom: &serde_json::Value,
+        event: &ApprovalRequested,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let approval_id = atom["id"].as_str().unwrap_or_default();
-        let job_id = atom["job_id"].as_str().unwrap_or_default();
-        let action = atom["action"].as_str().unwrap_or_default();
-        let reason = atom["reason"].as_str().unwrap_or_default();
-        let requested_by = atom["requested_by"].as_str().unwrap_or_default();
-        let requested_at = atom["requested_at"].as_str().unwrap_or_default();
+        let approval_id = event.approval_id.as_str();
+        let job_id = event.job_id.as_str();
+        let action = event.action.as_str();
+        let reason = event.reason.as_str();
+        let requested_by = event.requested_by.as_str();
+        let requested_at = event.requested_at.as_str();
 
         sqlx::query(
             r#"
@@ -366,15 +471,15 @@ impl JobsProjection {
 
     async fn handle_approval_decided(
         &self,
-        atom: &serde_json::Value,
+        event: &ApprovalDecided,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let approval_id = atom["approval_id"].as_str().unwrap_or_default();
-        let decided_by = atom["decided_by"].as_str().unwrap_or_default();
-        let decided_at = atom["decided_at"].as_str().unwrap_or_default();
-        let decision = atom["decision"].as_str().unwrap_or_default();
-        let decision_reason = atom["reason"].as_str();
+        let approval_id = event.approval_id.as_str();
+        let decided_by = event.decided_by.as_str();
+        let decided_at = event.decided_at.as_str();
+        let decision = event.decision.as_str();
+        let decision_reason = event.reason.as_deref();
 
         // Update approval (Diamond Checklist #2)
         sqlx::query(
@@ -425,9 +530,50 @@ impl JobsProjection {
         Ok(())
     }
 
-    /// Query jobs by conversation
-    pub async fn get_jobs_by_conversation(&self, conversation_id: &str) -> Result<Vec<Job>, sqlx::Error> {
-        sqlx::query_as::<_, Job>(
+    /// Keep the latest version of a governed job template; replays of older
+    /// versions leave it alone
+    async fn handle_template(
+        &self,
+        atom: &serde_json::Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        let template = &atom["template"];
+        let template_id = template["template_id"].as_str().unwrap_or_default();
+        let version = template["version"].as_i64().unwrap_or_default() as i32;
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_job_templates (
+                template_id, version, name, definition, last_event_hash, last_event_seq
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (template_id) DO UPDATE SET
+                version = EXCLUDED.version,
+                name = EXCLUDED.name,
+                definition = EXCLUDED.definition,
+                updated_at = NOW(),
+                last_event_hash = EXCLUDED.last_event_hash,
+                last_event_seq = EXCLUDED.last_event_seq
+            WHERE projection_job_templates.version < EXCLUDED.version
+            "#
+        )
+        .bind(template_id)
+        .bind(version)
+        .bind(template["name"].as_str().unwrap_or(template_id))
+        .bind(template)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        info!("🧩 Job template {} v{}", template_id, version);
+        Ok(())
+    }
+
+    /// Query jobs by conversation; `from` is `projection_jobs` or an as-of
+    /// view of it (`history::AsOf::source`)
+    pub async fn get_jobs_by_conversation(&self, conversation_id: &str, from: &str) -> Result<Vec<Job>, sqlx::Error> {
+        let sql = format!(
             r#"
             SELECT job_id, conversation_id, 
                    COALESCE(title, '') as title, 
@@ -441,19 +587,20 @@ impl JobsProjection {
                    result_artifacts, estimated_duration_seconds,
                    estimated_value,
                    last_event_hash, last_event_seq
-            FROM projection_jobs
+            FROM {from}
             WHERE conversation_id = $1
             ORDER BY created_at DESC
             "#
-        )
-        .bind(conversation_id)
-        .fetch_all(&self.pool)
-        .await
+        );
+        sqlx::query_as::<_, Job>(&sql)
+            .bind(conversation_id)
+            .fetch_all(&self.pool)
+            .await
     }
 
-    /// Query job by ID
-    pub async fn get_job(&self, job_id: &str) -> Result<Option<Job>, sqlx::Error> {
-        sqlx::query_as::<_, Job>(
+    /// Query job by ID, from `projection_jobs` or an as-of view of it
+    pub async fn get_job(&self, job_id: &str, from: &str) -> Result<Option<Job>, sqlx::Error> {
+        let sql = format!(
             r#"
             SELECT job_id, conversation_id, 
                    COALESCE(title, '') as title, 
@@ -467,31 +614,64 @@ impl JobsProjection {
                    result_artifacts, estimated_duration_seconds,
                    estimated_value,
                    last_event_hash, last_event_seq
-            FROM projection_jobs
+            FROM {from}
             WHERE job_id = $1
             "#
-        )
-        .bind(job_id)
-        .fetch_optional(&self.pool)
-        .await
+        );
+        sqlx::query_as::<_, Job>(&sql)
+            .bind(job_id)
+            .fetch_optional(&self.pool)
+            .await
     }
 
-    /// Query pending approvals for a job
-    pub async fn get_pending_approvals(&self, job_id: &str) -> Result<Vec<Approval>, sqlx::Error> {
-        sqlx::query_as!(
-            Approval,
+    /// Query pending approvals for a job, from `projection_approvals` or an
+    /// as-of view of it
+    pub async fn get_pending_approvals(&self, job_id: &str, from: &str) -> Result<Vec<Approval>, sqlx::Error> {
+        let sql = format!(
             r#"
             SELECT approval_id, job_id, action, reason, requested_by, requested_at,
                    status, decided_by, decided_at, decision, decision_reason,
                    last_event_hash, last_event_seq
-            FROM projection_approvals
+            FROM {from}
             WHERE job_id = $1 AND status = 'pending'
             ORDER BY requested_at DESC
-            "#,
-            job_id
-        )
-        .fetch_all(&self.pool)
-        .await
+            "#
+        );
+        sqlx::query_as::<_, Approval>(&sql)
+            .bind(job_id)
+            .fetch_all(&self.pool)
+            .await
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore] // Needs DATABASE_URL with the ubl/sql schema applied
+    async fn test_typed_events_project() {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://localhost:5432/ubl_test".to_string());
+        let pool = PgPool::connect(&database_url).await.expect("Failed to connect to test database");
+        let projection = JobsProjection::new(pool.clone());
+        let job_id = format!("job_{}", uuid::Uuid::new_v4().simple());
+
+        // Legacy spelling: `id` and no title
+        let created = serde_json::json!({ "type": "job.created", "id": job_id, "goal": "Reconcile", "tenant_id": "t_events" });
+        projection.process_event("job.created", &created, "h1", 1).await.unwrap();
+        // Missing job_id: skipped, not an error
+        let malformed = serde_json::json!({ "type": "job.completed", "success": true });
+        projection.process_event("job.completed", &malformed, "h2", 2).await.unwrap();
+
+        let failed = JobCompleted { tenant_id: Some("t_events".into()), ..JobCompleted::new(&job_id, false) };
+        projection.process_event("job.completed", &JobEvent::Completed(failed).to_atom(), "h3", 3).await.unwrap();
+
+        let (title, state): (String, String) = sqlx::query_as("SELECT title, state FROM projection_jobs WHERE job_id = $1")
+            .bind(&job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!((title.as_str(), state.as_str()), ("Reconcile", "failed"));
+    }
+}
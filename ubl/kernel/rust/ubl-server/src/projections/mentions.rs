@@ -0,0 +1,194 @@
+//! Mentions — `@entity` in messages become obligations
+//!
+//! The gateway parses mentions with [`parse_mentions`] when a message is
+//! sent and commits one `mention.created` atom carrying the message id, its
+//! ledger entry hash and the mentioned entities. This projection opens one
+//! obligation per mentioned entity and acknowledges it when that entity
+//! reads the message. Open obligations per conversation are the mention
+//! badges of bootstrap.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::info;
+
+/// Atom type recording the mentions of one message
+pub const MENTION_TYPE: &str = "mention.created";
+
+/// Mentioned entities in `content`, in order of first appearance.
+///
+/// A mention is `@` at the start of the text or after whitespace, followed
+/// by an entity id (letters, digits, `_ - . :`); trailing punctuation is not
+/// part of it. Only `members` count, and never the author.
+pub fn parse_mentions(content: &str, from: &str, members: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = content.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_boundary = prev.is_none_or(char::is_whitespace);
+        prev = Some(c);
+        if c != '@' || !at_boundary {
+            continue;
+        }
+        let start = i + 1;
+        let mut end = start;
+        while let Some(&(j, d)) = chars.peek() {
+            if !(d.is_alphanumeric() || matches!(d, '_' | '-' | '.' | ':')) {
+                break;
+            }
+            end = j + d.len_utf8();
+            prev = Some(d);
+            chars.next();
+        }
+        let id = content[start..end].trim_end_matches(['.', ':', '-']);
+        if !id.is_empty() && id != from && members.iter().any(|m| m == id) && !out.iter().any(|o| o == id) {
+            out.push(id.to_string());
+        }
+    }
+    out
+}
+
+/// Open mentions of an entity in one conversation
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MentionBadge {
+    pub conversation_id: String,
+    pub count: i64,
+    pub latest_message_id: String,
+    pub latest_at: OffsetDateTime,
+}
+
+/// One open obligation
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Obligation {
+    pub obligation_id: String,
+    pub kind: String,
+    pub conversation_id: String,
+    pub message_id: String,
+    pub message_entry_hash: String,
+    pub from_id: String,
+    pub created_at: OffsetDateTime,
+}
+
+/// Mentions projection handler
+pub struct MentionsProjection {
+    pool: PgPool,
+}
+
+impl MentionsProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Open one obligation per entity of a `mention.created` atom
+    pub async fn handle_mention_created(
+        &self,
+        atom: &serde_json::Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        let message_id = atom["message_id"].as_str().unwrap_or_default();
+        let mentioned: Vec<String> = atom["mentioned"]
+            .as_array()
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_obligations (
+                obligation_id, tenant_id, entity_id, kind, conversation_id, message_id,
+                message_entry_hash, from_id, last_event_hash, last_event_seq
+            )
+            SELECT 'mention:' || $1 || ':' || entity, $2, entity, 'mention', $3, $1, $4, $5, $6, $7
+            FROM unnest($8::TEXT[]) AS entity
+            ON CONFLICT (obligation_id) DO NOTHING
+            "#,
+        )
+        .bind(message_id)
+        .bind(atom["tenant_id"].as_str().unwrap_or("default"))
+        .bind(atom["conversation_id"].as_str().unwrap_or_default())
+        .bind(atom["message_entry_hash"].as_str().unwrap_or_default())
+        .bind(atom["from"].as_str().unwrap_or_default())
+        .bind(entry_hash)
+        .bind(sequence)
+        .bind(&mentioned)
+        .execute(&self.pool)
+        .await?;
+
+        info!("🔔 Mentions in {}: {:?}", message_id, mentioned);
+        Ok(())
+    }
+
+    /// Reading the message acknowledges the reader's mention obligation
+    pub async fn acknowledge(&self, message_id: &str, entity_id: &str, entry_hash: &str, sequence: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE projection_obligations
+            SET acknowledged_at = NOW(), last_event_hash = $3, last_event_seq = $4
+            WHERE message_id = $1 AND entity_id = $2 AND acknowledged_at IS NULL AND last_event_seq < $4
+            "#,
+        )
+        .bind(message_id)
+        .bind(entity_id)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Open mention counts of `entity_id` per conversation
+    pub async fn badges(&self, entity_id: &str) -> Result<Vec<MentionBadge>, sqlx::Error> {
+        sqlx::query_as::<_, MentionBadge>(
+            r#"
+            SELECT conversation_id, COUNT(*)::BIGINT AS count,
+                   (ARRAY_AGG(message_id ORDER BY last_event_seq DESC))[1] AS latest_message_id,
+                   MAX(created_at) AS latest_at
+            FROM projection_obligations
+            WHERE entity_id = $1 AND acknowledged_at IS NULL
+            GROUP BY conversation_id
+            ORDER BY latest_at DESC
+            "#,
+        )
+        .bind(entity_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Open obligations of `entity_id`, newest first
+    pub async fn open(&self, entity_id: &str, limit: i64) -> Result<Vec<Obligation>, sqlx::Error> {
+        sqlx::query_as::<_, Obligation>(
+            r#"
+            SELECT obligation_id, kind, conversation_id, message_id, message_entry_hash, from_id, created_at
+            FROM projection_obligations
+            WHERE entity_id = $1 AND acknowledged_at IS NULL
+            ORDER BY last_event_seq DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(entity_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_mentions() {
+        let m = members(&["alice", "bob", "ubl:sid:carol"]);
+        assert_eq!(parse_mentions("hey @bob and @ubl:sid:carol.", "alice", &m), vec!["bob", "ubl:sid:carol"]);
+        // Repeats, the author and non-members are dropped
+        assert_eq!(parse_mentions("@bob @bob @alice @mallory", "alice", &m), vec!["bob"]);
+        // Not at a word boundary: e-mail addresses are not mentions
+        assert!(parse_mentions("mail bob@bob.com", "alice", &m).is_empty());
+        assert_eq!(parse_mentions("@bob: ping", "alice", &m), vec!["bob"]);
+        assert!(parse_mentions("@ alone", "alice", &m).is_empty());
+    }
+}
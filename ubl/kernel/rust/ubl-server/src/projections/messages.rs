@@ -1,10 +1,19 @@
-//! C.Messenger Projection — Message state derived from message.* events
+//! C.Messenger Projection — Conversation and message state derived from
+//! conversation.*, message.* and mention.* events
+//!
+//! Message reads go through `visibility`, so redacted messages are hidden
+//! (members) or shown as placeholders (admins).
 
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use time::OffsetDateTime;
 use tracing::info;
 
+use super::broadcasts::BroadcastProjection;
+use super::mentions::{MentionsProjection, MENTION_TYPE};
+use super::summaries::{SummaryProjection, SUMMARY_TYPE};
+use super::visibility::{Redaction, RedactionsProjection, Visibility, REDACTION_TYPE};
+
 /// Message record in projection
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Message {
@@ -17,6 +26,10 @@ pub struct Message {
     pub read_by: Vec<String>,
     pub last_event_hash: String,
     pub last_event_seq: i64,
+    /// Tombstone, on the placeholders admins see in place of redacted messages
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<Redaction>,
 }
 
 /// Messages projection handler
@@ -38,8 +51,12 @@ impl MessagesProjection {
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
         match event_type {
+            "conversation.created" => self.handle_conversation_created(atom, entry_hash, sequence).await,
             "message.created" => self.handle_message_created(atom, entry_hash, sequence).await,
             "message.read" => self.handle_message_read(atom, entry_hash, sequence).await,
+            MENTION_TYPE => MentionsProjection::new(self.pool.clone()).handle_mention_created(atom, entry_hash, sequence).await,
+            SUMMARY_TYPE => SummaryProjection::new(self.pool.clone()).handle_summarized(atom, entry_hash, sequence).await,
+            REDACTION_TYPE => RedactionsProjection::new(self.pool.clone()).handle_redacted(atom, entry_hash, sequence).await,
             _ => {
                 info!("Unknown message event type: {}", event_type);
                 Ok(())
@@ -47,28 +64,73 @@ impl MessagesProjection {
         }
     }
 
+    async fn handle_conversation_created(
+        &self,
+        atom: &serde_json::Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        let conversation_id = atom["id"].as_str().unwrap_or_default();
+        let strings = |key: &str| -> Vec<String> {
+            atom[key].as_array()
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default()
+        };
+        let kind = atom["kind"].as_str().unwrap_or("chat");
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_conversations (
+                conversation_id, name, is_group, participants, created_by, created_at,
+                kind, senders, tenant_id, last_event_hash, last_event_seq
+            ) VALUES ($1, $2, $3, $4, $5, $6::timestamptz, $7, $8, $9, $10, $11)
+            ON CONFLICT (conversation_id) DO NOTHING
+            "#
+        )
+        .bind(conversation_id)
+        .bind(atom["name"].as_str())
+        .bind(atom["is_group"].as_bool().unwrap_or(false))
+        .bind(strings("participants"))
+        .bind(atom["created_by"].as_str().unwrap_or_default())
+        .bind(atom["created_at"].as_str().unwrap_or_default())
+        .bind(kind)
+        .bind(strings("senders"))
+        .bind(atom["tenant_id"].as_str())
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        info!("💬 Conversation created: {} ({})", conversation_id, kind);
+        Ok(())
+    }
+
     async fn handle_message_created(
         &self,
         atom: &serde_json::Value,
         entry_hash: &str,
         sequence: i64,
     ) -> Result<(), sqlx::Error> {
-        let message_id = atom["message_id"].as_str().unwrap_or_default();
+        // Boundary and gateway atoms carry `id` / `created_at`
+        let message_id = atom["message_id"].as_str().or_else(|| atom["id"].as_str()).unwrap_or_default();
         let conversation_id = atom["conversation_id"].as_str().unwrap_or_default();
         let from_id = atom["from"].as_str().unwrap_or_default();
         let content_hash = atom["content_hash"].as_str().unwrap_or_default();
-        let timestamp = atom["timestamp"].as_str().unwrap_or_default();
+        let timestamp = atom["timestamp"].as_str().or_else(|| atom["created_at"].as_str()).unwrap_or_default();
         let message_type = atom["message_type"].as_str().unwrap_or("text");
         // UBL-FIX: Extract client_msg_id for idempotency (Diamond Checklist #7)
         let client_msg_id = atom["client_msg_id"].as_str();
+        // Injected by commit_link from LinkDraft::tentative_id (not in the ledger atom)
+        let tentative_id = atom["tentative_id"].as_str();
 
         // UBL-FIX: Use client_msg_id in insert for idempotent message creation
         sqlx::query(
             r#"
             INSERT INTO projection_messages (
                 message_id, conversation_id, from_id, content_hash, timestamp,
-                message_type, client_msg_id, last_event_hash, last_event_seq
-            ) VALUES ($1, $2, $3, $4, $5::timestamptz, $6, $7, $8, $9)
+                message_type, client_msg_id, last_event_hash, last_event_seq, tentative_id,
+                entry_hash, entry_seq
+            ) VALUES ($1, $2, $3, $4, $5::timestamptz, $6, $7, $8, $9, $10, $8, $9)
             ON CONFLICT (message_id) DO NOTHING
             "#
         )
@@ -81,10 +143,17 @@ impl MessagesProjection {
         .bind(client_msg_id)
         .bind(entry_hash)
         .bind(sequence)
+        .bind(tentative_id)
         .execute(&self.pool)
         .await?;
 
         info!("💬 Message created: {} in {} (client_id: {:?})", message_id, conversation_id, client_msg_id);
+
+        // Announcements fan out to members here, not as one commit each
+        let tenant_id = atom["tenant_id"].as_str().unwrap_or("default");
+        BroadcastProjection::new(self.pool.clone())
+            .fan_out(message_id, conversation_id, from_id, tenant_id, sequence)
+            .await?;
         Ok(())
     }
 
@@ -113,34 +182,67 @@ impl MessagesProjection {
         .execute(&self.pool)
         .await?;
 
+        BroadcastProjection::new(self.pool.clone())
+            .mark_read(message_id, read_by, sequence)
+            .await?;
+        MentionsProjection::new(self.pool.clone())
+            .acknowledge(message_id, read_by, entry_hash, sequence)
+            .await?;
+
         info!("👁️ Message read: {} by {}", message_id, read_by);
         Ok(())
     }
 
-    /// Query messages by conversation
+    /// Query messages by conversation, with redactions applied for `visibility`
     pub async fn get_messages_by_conversation(
         &self,
         conversation_id: &str,
         limit: i64,
         before_seq: Option<i64>,
+        visibility: Visibility,
     ) -> Result<Vec<Message>, sqlx::Error> {
         let before = before_seq.unwrap_or(i64::MAX);
         
-        sqlx::query_as::<_, Message>(
+        let sql = format!(
             r#"
             SELECT message_id, conversation_id, from_id, content_hash, timestamp,
                    message_type, COALESCE(read_by, ARRAY[]::text[]) as read_by, 
                    last_event_hash, last_event_seq
             FROM projection_messages
-            WHERE conversation_id = $1 AND last_event_seq < $2
+            WHERE conversation_id = $1 AND last_event_seq < $2 AND {}
             ORDER BY timestamp DESC
             LIMIT $3
+            "#,
+            visibility.filter("projection_messages.message_id")
+        );
+        let messages = sqlx::query_as::<_, Message>(&sql)
+            .bind(conversation_id)
+            .bind(before)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+        if visibility == Visibility::Member {
+            return Ok(messages);
+        }
+
+        let ids: Vec<String> = messages.iter().map(|m| m.message_id.clone()).collect();
+        let redactions = RedactionsProjection::new(self.pool.clone()).for_messages(&ids).await?;
+        Ok(visibility.apply_messages(messages, &redactions))
+    }
+
+    /// One message, redacted or not; for command checks, not for display
+    pub async fn get_message(&self, message_id: &str) -> Result<Option<Message>, sqlx::Error> {
+        sqlx::query_as::<_, Message>(
+            r#"
+            SELECT message_id, conversation_id, from_id, content_hash, timestamp,
+                   message_type, COALESCE(read_by, ARRAY[]::text[]) as read_by,
+                   last_event_hash, last_event_seq
+            FROM projection_messages
+            WHERE message_id = $1
             "#
         )
-        .bind(conversation_id)
-        .bind(before)
-        .bind(limit)
-        .fetch_all(&self.pool)
+        .bind(message_id)
+        .fetch_optional(&self.pool)
         .await
     }
 
@@ -7,6 +7,8 @@
 //! - Projections listen to SSE tail (LISTEN/NOTIFY)
 //! - Each event updates the relevant projection table
 //! - Projections can be rebuilt from scratch by replaying the ledger
+//! - Query routes are row-scoped to the caller's session (see `scope`)
+//! - Selected projections also keep row history for `?as_of=` reads (see `history`)
 
 mod jobs;
 mod messages;
@@ -17,6 +19,19 @@ mod job_events;
 mod artifacts;
 mod presence;
 mod timeline;
+mod annotations;
+mod observations;
+mod board;
+pub mod broadcasts;
+pub mod mentions;
+pub mod summaries;
+pub mod analytics;
+pub mod reputation;
+pub mod policy_coverage;
+pub mod changelog;
+pub mod history;
+pub mod scope;
+pub mod visibility;
 
 pub use jobs::JobsProjection;
 pub use messages::MessagesProjection;
@@ -26,7 +41,17 @@ pub use routes::{projection_router, ProjectionState};
 pub use job_events::JobEventsProjection;
 pub use artifacts::ArtifactsProjection;
 pub use presence::PresenceProjection;
-pub use timeline::TimelineProjection;
+pub use timeline::{TimelineProjection, ARTIFACT_CARD_TYPE};
+pub use annotations::{AnnotationsProjection, AnnotationRow, AUDIT_CONTAINER};
+pub use observations::ObservationsProjection;
+pub use board::BoardProjection;
+pub use broadcasts::BroadcastProjection;
+pub use mentions::MentionsProjection;
+pub use summaries::SummaryProjection;
+pub use analytics::{AnalyticsProjection, EntropyTotals};
+pub use reputation::{ActorReputation, ReputationProjection};
+pub use changelog::{ChangelogProjection, EVOLUTION_PREFIX};
+pub use visibility::{RedactionsProjection, Visibility, REDACTION_TYPE};
 
 use serde::{Deserialize, Serialize};
 
@@ -0,0 +1,211 @@
+//! # Observation Batch Projection
+//!
+//! Unpacks `observation.batch` atoms (see `observation_batch.rs`) into one
+//! `projection_observations` row per observation, keyed by the batch's
+//! `entry_hash` and the observation's index. Each row keeps its leaf hash and
+//! the batch root so a reader can prove membership without the whole batch.
+//!
+//! Batches may be committed to any container; the container is recorded on
+//! every row.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::info;
+
+use super::scope::{ScopedTable, Viewer};
+use crate::observation_batch::{self, BATCH_TYPE};
+use crate::query_params::Cursor;
+
+/// Observation Batch Projection Handler
+pub struct ObservationsProjection {
+    pool: PgPool,
+}
+
+impl ObservationsProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Unpack a batch; other event types are ignored
+    pub async fn process_event(
+        &self,
+        container_id: &str,
+        event_type: &str,
+        atom: &Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> anyhow::Result<usize> {
+        if event_type != BATCH_TYPE {
+            return Ok(0);
+        }
+        let observations = atom
+            .get("observations")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("observation.batch without observations"))?;
+        let batch_root = atom.get("batch_root").and_then(|v| v.as_str()).unwrap_or_default();
+
+        let mut tx = self.pool.begin().await?;
+        for (index, obs) in observations.iter().enumerate() {
+            let leaf_hash = observation_batch::leaf_hash(obs).map_err(anyhow::Error::msg)?;
+            let tenant_id = obs
+                .get("tenant_id")
+                .or_else(|| atom.get("tenant_id"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("default");
+            let entity_id = obs
+                .get("entity_id")
+                .or_else(|| obs.get("from"))
+                .and_then(|v| v.as_str());
+
+            sqlx::query(
+                r#"
+                INSERT INTO projection_observations
+                    (entry_hash, batch_index, container_id, sequence, tenant_id, entity_id,
+                     obs_type, leaf_hash, batch_root, data, ts_ms)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (entry_hash, batch_index) DO NOTHING
+                "#,
+            )
+            .bind(entry_hash)
+            .bind(index as i32)
+            .bind(container_id)
+            .bind(sequence)
+            .bind(tenant_id)
+            .bind(entity_id)
+            .bind(obs.get("type").and_then(|v| v.as_str()).unwrap_or_default())
+            .bind(hex::encode(leaf_hash))
+            .bind(batch_root)
+            .bind(obs)
+            .bind(obs.get("ts_ms").and_then(|v| v.as_i64()))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        info!("✅ Observations projection: {} rows from {} seq={}", observations.len(), container_id, sequence);
+        Ok(observations.len())
+    }
+
+    /// Most recent observations readable by `viewer` before the cursor
+    /// (sequence, batch index), newest first, optionally filtered
+    pub async fn list(
+        &self,
+        viewer: &Viewer,
+        container_id: Option<&str>,
+        entity_id: Option<&str>,
+        obs_type: Option<&str>,
+        limit: i64,
+        before: Cursor,
+    ) -> anyhow::Result<Vec<ObservationRow>> {
+        let sql = format!(
+            r#"
+            SELECT entry_hash, batch_index, container_id, sequence, tenant_id, entity_id,
+                   obs_type, leaf_hash, batch_root, data, ts_ms
+            FROM projection_observations
+            WHERE ($1::TEXT IS NULL OR container_id = $1)
+              AND ($2::TEXT IS NULL OR entity_id = $2)
+              AND ($3::TEXT IS NULL OR obs_type = $3)
+              AND (sequence, batch_index) < ($4, $5)
+              AND {}
+            ORDER BY sequence DESC, batch_index DESC
+            LIMIT $6
+            "#,
+            viewer.filter(ScopedTable::Observations, 7)
+        );
+        let query = sqlx::query_as::<_, ObservationRow>(&sql)
+            .bind(container_id)
+            .bind(entity_id)
+            .bind(obs_type)
+            .bind(before.key)
+            .bind(before.tiebreak)
+            .bind(limit);
+        let rows = viewer.bind(query).fetch_all(&self.pool).await?;
+        Ok(rows)
+    }
+
+    /// Merkle inclusion proof for one unpacked observation, or None if the
+    /// row does not exist. Built from the batch's stored leaf hashes and
+    /// checked against its root before being returned.
+    pub async fn proof(&self, entry_hash: &str, batch_index: i32) -> anyhow::Result<Option<ObservationProof>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT leaf_hash, batch_root
+            FROM projection_observations
+            WHERE entry_hash = $1
+            ORDER BY batch_index ASC
+            "#,
+        )
+        .bind(entry_hash)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let Some((leaf_hex, root_hex)) = usize::try_from(batch_index).ok().and_then(|i| rows.get(i)).cloned() else {
+            return Ok(None);
+        };
+        let leaves = rows
+            .iter()
+            .map(|(leaf, _)| decode_hash(leaf))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let index = batch_index as usize;
+        let steps = observation_batch::inclusion_proof(&leaves, index).unwrap_or_default();
+        if !observation_batch::verify_inclusion(&leaves[index], &steps, &decode_hash(&root_hex)?) {
+            anyhow::bail!("projection rows of {} do not match batch_root", entry_hash);
+        }
+
+        Ok(Some(ObservationProof {
+            entry_hash: entry_hash.to_string(),
+            batch_index,
+            leaf_hash: leaf_hex,
+            batch_root: root_hex,
+            proof: steps
+                .into_iter()
+                .map(|(hash, right)| ProofStep { hash: hex::encode(hash), right })
+                .collect(),
+        }))
+    }
+}
+
+fn decode_hash(hex_str: &str) -> anyhow::Result<[u8; 32]> {
+    let bytes = hex::decode(hex_str)?;
+    bytes.try_into().map_err(|_| anyhow::anyhow!("hash is not 32 bytes"))
+}
+
+// =============================================================================
+// Query Types
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ObservationRow {
+    /// Entry of the batch this observation was committed in
+    pub entry_hash: String,
+    pub batch_index: i32,
+    pub container_id: String,
+    pub sequence: i64,
+    pub tenant_id: String,
+    pub entity_id: Option<String>,
+    pub obs_type: String,
+    pub leaf_hash: String,
+    pub batch_root: String,
+    pub data: Value,
+    pub ts_ms: Option<i64>,
+}
+
+/// Path from a leaf to the batch root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObservationProof {
+    pub entry_hash: String,
+    pub batch_index: i32,
+    pub leaf_hash: String,
+    pub batch_root: String,
+    /// Siblings bottom-up; fold with `hash_merkle(acc, hash)` when `right`,
+    /// else `hash_merkle(hash, acc)`
+    pub proof: Vec<ProofStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub hash: String,
+    /// Sibling is the right-hand node
+    pub right: bool,
+}
@@ -370,5 +370,8 @@ pub struct AuditRow {
     pub event_type: String,
     pub event_data: Value,
     pub created_at_ms: i64,
+    /// Ledger receipt of the atom this row was projected from
+    pub entry_hash: Option<String>,
+    pub sequence: Option<i64>,
 }
 
@@ -0,0 +1,175 @@
+//! Policy Rule Coverage — which rules actually decide
+//!
+//! Fed by `PolicyRegistry::evaluate`: every evaluation that produced a
+//! decision adds a hit to the rule that allowed (the VM reports it, see
+//! `TranslationDecision::matched_rule`) or to [`DEFAULT_RULE`] when it fell
+//! through to the policy's default. Hits are kept per policy version.
+//!
+//! [`coverage`] turns the hits and the rules of the compiled policy into the
+//! report of `GET /v1/policy/:id/coverage`.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+/// Rule id under which the policy's default decision is counted
+pub const DEFAULT_RULE: &str = "(default)";
+
+/// Share of evaluations above which a rule is reported as over-triggered
+pub const OVER_TRIGGERED_SHARE: f64 = 0.5;
+
+/// Stored hits of one rule
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct RuleHits {
+    pub rule_id: String,
+    pub hits: i64,
+    pub first_hit_ms: i64,
+    pub last_hit_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleCoverage {
+    pub rule_id: String,
+    pub hits: i64,
+    /// hits / evaluations of this version
+    pub share: f64,
+    pub first_hit_ms: Option<i64>,
+    pub last_hit_ms: Option<i64>,
+    /// `unused`, `over_triggered` or `active`
+    pub status: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyCoverage {
+    pub policy_id: String,
+    pub version: String,
+    pub evaluations: i64,
+    /// Rules in evaluation order, then the default
+    pub rules: Vec<RuleCoverage>,
+    pub unused: Vec<String>,
+    pub over_triggered: Vec<String>,
+}
+
+/// Coverage of `rule_ids` (in policy order) given the stored `hits`
+pub fn coverage(policy_id: &str, version: &str, rule_ids: &[&str], hits: &[RuleHits], over_share: f64) -> PolicyCoverage {
+    let evaluations: i64 = hits.iter().map(|h| h.hits).sum();
+    let rules: Vec<RuleCoverage> = rule_ids
+        .iter()
+        .copied()
+        .chain(std::iter::once(DEFAULT_RULE))
+        .map(|rule_id| {
+            let stored = hits.iter().find(|h| h.rule_id == rule_id);
+            let count = stored.map_or(0, |h| h.hits);
+            let share = if evaluations > 0 { count as f64 / evaluations as f64 } else { 0.0 };
+            let status = match count {
+                0 => "unused",
+                _ if share > over_share => "over_triggered",
+                _ => "active",
+            };
+            RuleCoverage {
+                rule_id: rule_id.to_string(),
+                hits: count,
+                share,
+                first_hit_ms: stored.map(|h| h.first_hit_ms),
+                last_hit_ms: stored.map(|h| h.last_hit_ms),
+                status,
+            }
+        })
+        .collect();
+
+    let with_status = |status: &str| {
+        rules
+            .iter()
+            .filter(|r| r.rule_id != DEFAULT_RULE && r.status == status)
+            .map(|r| r.rule_id.clone())
+            .collect()
+    };
+    PolicyCoverage {
+        policy_id: policy_id.to_string(),
+        version: version.to_string(),
+        evaluations,
+        unused: with_status("unused"),
+        over_triggered: with_status("over_triggered"),
+        rules,
+    }
+}
+
+/// Policy rule coverage projection handler
+pub struct PolicyCoverageProjection {
+    pool: PgPool,
+}
+
+impl PolicyCoverageProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Count one evaluation decided by `rule_id`
+    pub async fn record_hit(&self, policy_id: &str, version: &str, rule_id: &str, ts_unix_ms: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO projection_policy_rule_hits (policy_id, policy_version, rule_id, hits, first_hit_ms, last_hit_ms)
+            VALUES ($1, $2, $3, 1, $4, $4)
+            ON CONFLICT (policy_id, policy_version, rule_id) DO UPDATE SET
+                hits = projection_policy_rule_hits.hits + 1,
+                last_hit_ms = GREATEST(projection_policy_rule_hits.last_hit_ms, EXCLUDED.last_hit_ms)
+            "#,
+        )
+        .bind(policy_id)
+        .bind(version)
+        .bind(rule_id)
+        .bind(ts_unix_ms)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Hits per rule of one policy version
+    pub async fn hits(&self, policy_id: &str, version: &str) -> Result<Vec<RuleHits>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT rule_id, hits, first_hit_ms, last_hit_ms
+            FROM projection_policy_rule_hits
+            WHERE policy_id = $1 AND policy_version = $2
+            "#,
+        )
+        .bind(policy_id)
+        .bind(version)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(rule_id: &str, hits: i64) -> RuleHits {
+        RuleHits { rule_id: rule_id.into(), hits, first_hit_ms: 1, last_hit_ms: 2 }
+    }
+
+    #[test]
+    fn test_coverage_flags_unused_and_over_triggered_rules() {
+        let report = coverage(
+            "p",
+            "1.0",
+            &["observe", "transfer", "legacy"],
+            &[hits("observe", 80), hits("transfer", 15), hits(DEFAULT_RULE, 5)],
+            OVER_TRIGGERED_SHARE,
+        );
+        assert_eq!(report.evaluations, 100);
+        assert_eq!(report.unused, ["legacy"]);
+        assert_eq!(report.over_triggered, ["observe"]);
+        let ids: Vec<&str> = report.rules.iter().map(|r| r.rule_id.as_str()).collect();
+        assert_eq!(ids, ["observe", "transfer", "legacy", DEFAULT_RULE]);
+        assert_eq!(report.rules[2].last_hit_ms, None);
+        assert_eq!(report.rules[3].status, "active");
+    }
+
+    #[test]
+    fn test_coverage_without_evaluations() {
+        let report = coverage("p", "1.0", &["observe"], &[], OVER_TRIGGERED_SHARE);
+        assert_eq!(report.evaluations, 0);
+        assert_eq!(report.unused, ["observe"]);
+        assert!(report.rules.iter().all(|r| r.share == 0.0));
+    }
+}
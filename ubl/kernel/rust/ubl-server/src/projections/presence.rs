@@ -11,6 +11,8 @@ use sqlx::PgPool;
 use time::{OffsetDateTime, Duration};
 use tracing::info;
 
+use crate::timestamps::{from_datetime, rfc3339_utc};
+
 /// Presence projection handler
 pub struct PresenceProjection {
     pool: PgPool,
@@ -159,8 +161,10 @@ impl PresenceProjection {
                 "entity_id": entity_id,
                 "state": r.state,
                 "job_id": r.job_id,
-                "since": r.since.to_string(),
-                "last_seen_at": r.last_seen_at.to_string(),
+                "since": rfc3339_utc(from_datetime(r.since)),
+                "since_ms": from_datetime(r.since),
+                "last_seen_at": rfc3339_utc(from_datetime(r.last_seen_at)),
+                "last_seen_at_ms": from_datetime(r.last_seen_at),
             })
         }))
     }
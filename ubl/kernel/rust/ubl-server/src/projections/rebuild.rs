@@ -1,11 +1,13 @@
 //! Projection rebuild from ledger events
 //!
 //! This module handles rebuilding projections from the ledger.
-//! Used on startup or to repair corrupted projections.
+//! Used on startup or to repair corrupted projections. The ledger is read
+//! through `LedgerReplay`, a page at a time.
 
 use sqlx::PgPool;
 use tracing::{info, error};
-use super::{JobsProjection, MessagesProjection};
+use crate::ledger_replay::LedgerReplay;
+use super::{AnnotationsProjection, ChangelogProjection, JobsProjection, MessagesProjection, ObservationsProjection, AUDIT_CONTAINER};
 
 /// Rebuild all projections from the ledger
 pub async fn rebuild_projections(pool: &PgPool) -> Result<(), sqlx::Error> {
@@ -13,55 +15,91 @@ pub async fn rebuild_projections(pool: &PgPool) -> Result<(), sqlx::Error> {
 
     let jobs = JobsProjection::new(pool.clone());
     let messages = MessagesProjection::new(pool.clone());
+    let annotations = AnnotationsProjection::new(pool.clone());
+    let observations = ObservationsProjection::new(pool.clone());
+    let changelog = ChangelogProjection::new(pool.clone());
 
-    // Get all atoms ordered by container and sequence
-    let atoms = sqlx::query!(
-        r#"
-        SELECT 
-            la.container_id,
-            la.atom_hash,
-            la.atom_data,
-            le.entry_hash,
-            le.sequence
-        FROM ledger_atom la
-        JOIN ledger_entry le ON le.link_hash = la.atom_hash
-        ORDER BY le.container_id, le.sequence ASC
-        "#
-    )
-    .fetch_all(pool)
-    .await?;
+    // Stream every entry in container and sequence order, a page at a time
+    let mut replay = LedgerReplay::all(pool.clone());
 
     let mut jobs_count = 0;
     let mut messages_count = 0;
+    let mut annotations_count = 0;
+    let mut observations_count = 0;
+    let mut evolutions_count = 0;
 
-    for atom in atoms {
-        let event_type = atom.atom_data["type"].as_str().unwrap_or("");
+    while let Some(entry) = replay.next().await? {
+        let Some(stored) = entry.atom.as_ref() else { continue };
+        // Encrypted containers store sealed atoms; projections need plaintext
+        let atom_data = match crate::atom_crypto::open_atom(pool, &entry.container_id, &entry.link_hash, stored).await {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Failed to open sealed atom {}: {}", entry.link_hash, e);
+                continue;
+            }
+        };
+        let event_type = atom_data["type"].as_str().unwrap_or("");
+
+        // Observation batches may live in any container
+        match observations.process_event(
+            &entry.container_id,
+            event_type,
+            &atom_data,
+            &entry.entry_hash,
+            entry.sequence,
+        ).await {
+            Ok(n) => observations_count += n,
+            Err(e) => error!("Failed to unpack observation batch: {}", e),
+        }
+
+        // So may evolutions
+        match changelog.process_event(
+            &entry.container_id,
+            event_type,
+            &atom_data,
+            &entry.entry_hash,
+            entry.sequence,
+        ).await {
+            Ok(true) => evolutions_count += 1,
+            Ok(false) => {}
+            Err(e) => error!("Failed to record evolution: {}", e),
+        }
         
-        if atom.container_id == "C.Jobs" {
+        if entry.container_id == "C.Jobs" {
             if let Err(e) = jobs.process_event(
                 event_type,
-                &atom.atom_data,
-                &atom.entry_hash,
-                atom.sequence,
+                &atom_data,
+                &entry.entry_hash,
+                entry.sequence,
             ).await {
                 error!("Failed to process job event: {}", e);
             }
             jobs_count += 1;
-        } else if atom.container_id == "C.Messenger" {
+        } else if entry.container_id == "C.Messenger" {
             if let Err(e) = messages.process_event(
                 event_type,
-                &atom.atom_data,
-                &atom.entry_hash,
-                atom.sequence,
+                &atom_data,
+                &entry.entry_hash,
+                entry.sequence,
             ).await {
                 error!("Failed to process message event: {}", e);
             }
             messages_count += 1;
+        } else if entry.container_id == AUDIT_CONTAINER {
+            if let Err(e) = annotations.process_event(
+                event_type,
+                &atom_data,
+                &entry.entry_hash,
+                entry.sequence,
+            ).await {
+                error!("Failed to process audit event: {}", e);
+            }
+            annotations_count += 1;
         }
     }
 
     // Update projection state
-    for container_id in ["C.Jobs", "C.Messenger"] {
+    for container_id in ["C.Jobs", "C.Messenger", AUDIT_CONTAINER] {
         if let Ok(Some(last)) = sqlx::query!(
             r#"
             SELECT sequence, entry_hash
@@ -91,8 +129,8 @@ pub async fn rebuild_projections(pool: &PgPool) -> Result<(), sqlx::Error> {
     }
 
     info!(
-        "✅ Projection rebuild complete: {} job events, {} message events",
-        jobs_count, messages_count
+        "✅ Projection rebuild complete: {} job events, {} message events, {} annotations, {} observations, {} evolutions",
+        jobs_count, messages_count, annotations_count, observations_count, evolutions_count
     );
 
     Ok(())
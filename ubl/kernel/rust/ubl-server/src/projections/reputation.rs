@@ -0,0 +1,186 @@
+//! Actor Reputation — commit history per author key, for policies
+//!
+//! Fed by `commit_link` next to the container analytics: accepted commits
+//! add to the author's `accepted`; refusals that are the author's doing
+//! ([`is_violation`]) add to `rejected` and to the count of their error code.
+//! Refusals raised before the signature verified (or by a head race) say
+//! nothing about the key and are not counted.
+//!
+//! Admission hands [`ActorReputation::policy_state`] to the policy VM with
+//! the Entropy totals, so a rule can require a pact of a poorly reputed
+//! actor (`Constraint::StateMin` on `actor_rejection_pct`).
+
+use serde::Serialize;
+use sqlx::PgPool;
+use ubl_errors::ErrorCode;
+
+/// Violations older than this are no longer "recent" (30 days)
+pub const RECENT_VIOLATION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Reputation summary of one author key
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ActorReputation {
+    pub accepted: i64,
+    pub rejected: i64,
+    /// Violation codes seen within [`RECENT_VIOLATION_MS`], most recent first
+    pub recent_violations: Vec<String>,
+}
+
+impl ActorReputation {
+    /// rejected / (accepted + rejected) as a whole percentage; 0 without
+    /// history
+    pub fn rejection_pct(&self) -> i64 {
+        let total = self.accepted.saturating_add(self.rejected);
+        if total <= 0 {
+            return 0;
+        }
+        (self.rejected as i128 * 100 / total as i128) as i64
+    }
+
+    /// Policy state fields (`Constraint::StateMax` / `StateMin`)
+    pub fn policy_state(&self) -> serde_json::Value {
+        serde_json::json!({
+            "actor_accepted": self.accepted,
+            "actor_rejected": self.rejected,
+            "actor_rejection_pct": self.rejection_pct(),
+            "actor_recent_violations": self.recent_violations.len() as i64,
+        })
+    }
+}
+
+/// Refusals that count against the author: all are raised once the link
+/// signature has verified
+pub fn is_violation(code: ErrorCode) -> bool {
+    matches!(
+        code,
+        ErrorCode::PhysicsViolation
+            | ErrorCode::PactViolation
+            | ErrorCode::UnauthorizedEvolution
+            | ErrorCode::InvalidEvolution
+            | ErrorCode::PolicyViolation
+            | ErrorCode::PolicyDenied
+    )
+}
+
+/// Actor reputation projection handler
+pub struct ReputationProjection {
+    pool: PgPool,
+}
+
+impl ReputationProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Count an accepted commit
+    pub async fn record_accepted(&self, author_pubkey: &str, ts_unix_ms: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO projection_actor_reputation (author_pubkey, accepted, updated_at_ms)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (author_pubkey) DO UPDATE SET
+                accepted = projection_actor_reputation.accepted + 1,
+                updated_at_ms = EXCLUDED.updated_at_ms
+            "#,
+        )
+        .bind(author_pubkey)
+        .bind(ts_unix_ms)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Count a refusal; anything but a violation is ignored
+    pub async fn record_refusal(&self, author_pubkey: &str, code: ErrorCode, ts_unix_ms: i64) -> Result<(), sqlx::Error> {
+        if !is_violation(code) {
+            return Ok(());
+        }
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO projection_actor_reputation (author_pubkey, rejected, updated_at_ms)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (author_pubkey) DO UPDATE SET
+                rejected = projection_actor_reputation.rejected + 1,
+                updated_at_ms = EXCLUDED.updated_at_ms
+            "#,
+        )
+        .bind(author_pubkey)
+        .bind(ts_unix_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_actor_violations (author_pubkey, code, count, last_at_ms)
+            VALUES ($1, $2, 1, $3)
+            ON CONFLICT (author_pubkey, code) DO UPDATE SET
+                count = projection_actor_violations.count + 1,
+                last_at_ms = GREATEST(projection_actor_violations.last_at_ms, EXCLUDED.last_at_ms)
+            "#,
+        )
+        .bind(author_pubkey)
+        .bind(code.as_str())
+        .bind(ts_unix_ms)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    /// Reputation of `author_pubkey` as of `now_ms` (empty for unknown keys)
+    pub async fn summary(&self, author_pubkey: &str, now_ms: i64) -> Result<ActorReputation, sqlx::Error> {
+        let counts: Option<(i64, i64)> = sqlx::query_as(
+            "SELECT accepted, rejected FROM projection_actor_reputation WHERE author_pubkey = $1",
+        )
+        .bind(author_pubkey)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some((accepted, rejected)) = counts else {
+            return Ok(ActorReputation::default());
+        };
+
+        let recent_violations: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT code FROM projection_actor_violations
+            WHERE author_pubkey = $1 AND last_at_ms >= $2
+            ORDER BY last_at_ms DESC, code
+            "#,
+        )
+        .bind(author_pubkey)
+        .bind(now_ms.saturating_sub(RECENT_VIOLATION_MS))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ActorReputation { accepted, rejected, recent_violations })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_state() {
+        let reputation = ActorReputation {
+            accepted: 7,
+            rejected: 3,
+            recent_violations: vec!["POLICY_DENIED".into(), "PACT_VIOLATION".into()],
+        };
+        let state = reputation.policy_state();
+        assert_eq!(state["actor_rejection_pct"], 30);
+        assert_eq!(state["actor_recent_violations"], 2);
+        assert_eq!(ActorReputation::default().policy_state()["actor_rejection_pct"], 0);
+    }
+
+    #[test]
+    fn test_only_author_violations_count() {
+        assert!(is_violation(ErrorCode::PolicyDenied));
+        assert!(is_violation(ErrorCode::PactViolation));
+        // Anyone can send a link with someone else's key, and head races
+        // are nobody's fault
+        assert!(!is_violation(ErrorCode::InvalidSignature));
+        assert!(!is_violation(ErrorCode::RealityDrift));
+        assert!(!is_violation(ErrorCode::Forbidden));
+    }
+}
@@ -1,20 +1,34 @@
 //! HTTP API routes for projections
 //!
 //! These are read-only query endpoints that hit projection tables.
+//! Every route requires a session; rows of shared containers are scoped to
+//! it by `super::scope`.
 
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    middleware,
     routing::get,
-    Json, Router,
+    Extension, Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 
-use super::{JobsProjection, MessagesProjection, OfficeProjection};
+use super::{AnalyticsProjection, BoardProjection, BroadcastProjection, ChangelogProjection, JobsProjection, MentionsProjection, MessagesProjection, ObservationsProjection, OfficeProjection, SummaryProjection};
+use super::analytics::{self, ContainerStats};
+use super::board::Board;
+use super::changelog::EvolutionEntry;
+use super::broadcasts::{AnnouncementStats, InboxItem};
+use super::mentions::Obligation;
+use super::summaries::{ConversationSummary, SummaryDetail};
 use super::jobs::{Job, Approval};
 use super::messages::Message;
+use super::observations::{ObservationProof, ObservationRow};
 use super::office::{EntityRow, SessionRow, HandoverRow, AuditRow};
+use super::history::{AsOf, Tracked};
+use super::scope::{self, RowAccess, ScopedTable, Viewer, ViewerRole};
+use super::visibility::Visibility;
+use crate::query_params::{non_empty, Cursor, Limit, PageQuery};
 
 /// Shared state for projection routes
 #[derive(Clone)]
@@ -22,12 +36,11 @@ pub struct ProjectionState {
     pub pool: PgPool,
 }
 
-/// Query params for pagination
-#[derive(Debug, Deserialize)]
-pub struct PaginationQuery {
-    pub limit: Option<i64>,
-    pub before_seq: Option<i64>,
-}
+/// Page sizes (see `crate::query_params`)
+const PAGE: Limit = Limit::new(50, 100);
+const SUMMARIES: Limit = Limit::new(20, 100);
+const HANDOVERS: Limit = Limit::new(20, 50);
+const LOGS: Limit = Limit::new(100, 500);
 
 /// API response wrapper
 #[derive(Debug, Serialize)]
@@ -36,16 +49,35 @@ pub struct ApiResponse<T> {
     pub data: T,
 }
 
-/// Create projection router
-pub fn projection_router() -> Router<ProjectionState> {
+/// Response of the keyset-paged routes; `next_cursor` (pass it back as
+/// `?cursor=`) is absent on the last page
+#[derive(Debug, Serialize)]
+pub struct PagedResponse<T> {
+    pub ok: bool,
+    pub data: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Create projection router, with the session resolved once for every route
+pub fn projection_router(state: ProjectionState) -> Router {
     Router::new()
         // Jobs
         .route("/jobs", get(list_jobs))
         .route("/jobs/:job_id", get(get_job))
         .route("/jobs/:job_id/approvals", get(get_job_approvals))
         .route("/conversations/:conversation_id/jobs", get(get_conversation_jobs))
+        .route("/board", get(get_board))
         // Messages
         .route("/conversations/:conversation_id/messages", get(get_conversation_messages))
+        // Broadcasts (announcements fanned out per member)
+        .route("/broadcasts/inbox", get(get_broadcast_inbox))
+        .route("/conversations/:conversation_id/broadcast_stats", get(get_broadcast_stats))
+        // Summaries of long conversations, with the messages each one covers
+        .route("/conversations/:conversation_id/summaries", get(list_summaries))
+        .route("/conversations/:conversation_id/summaries/:summary_id", get(get_summary))
+        // Obligations (open @mentions of the caller)
+        .route("/obligations", get(list_obligations))
         // Office (C.Office projections)
         .route("/office/entities", get(list_entities))
         .route("/office/entities/:entity_id", get(get_entity))
@@ -53,17 +85,42 @@ pub fn projection_router() -> Router<ProjectionState> {
         .route("/office/entities/:entity_id/handovers", get(get_entity_handovers))
         .route("/office/entities/:entity_id/handovers/latest", get(get_latest_handover))
         .route("/office/audit", get(list_audit))
+        // Observations unpacked from observation.batch atoms
+        .route("/observations", get(list_observations))
+        .route("/observations/:entry_hash/:batch_index/proof", get(get_observation_proof))
+        // Analytics (console dashboard)
+        .route("/analytics/containers", get(get_container_analytics))
+        // Evolution changelog (console governance tab)
+        .route("/evolution/:container_id", get(get_evolution_changelog))
+        .route_layer(middleware::from_fn_with_state(state.clone(), scope::resolve_viewer))
+        .with_state(state)
+}
+
+/// Not found unless `viewer` may read the row; hides existence from everyone else
+fn require_readable(
+    viewer: &Viewer,
+    access: Result<Option<RowAccess>, sqlx::Error>,
+    what: &str,
+) -> Result<(), (StatusCode, String)> {
+    match access.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))? {
+        Some(row) if viewer.can_read(&row) => Ok(()),
+        _ => Err((StatusCode::NOT_FOUND, format!("{} not found", what))),
+    }
 }
 
-/// GET /query/jobs — List all jobs (paginated)
+/// GET /query/jobs — List jobs visible to the caller (paginated; `?as_of=`
+/// when job history is kept)
 async fn list_jobs(
     State(state): State<ProjectionState>,
-    Query(query): Query<PaginationQuery>,
+    Extension(viewer): Extension<Viewer>,
+    Query(query): Query<PageQuery>,
+    Query(at): Query<AsOf>,
 ) -> Result<Json<ApiResponse<Vec<Job>>>, (StatusCode, String)> {
-    let limit = query.limit.unwrap_or(50).min(100);
+    let limit = PAGE.resolve(query.limit);
     let before_seq = query.before_seq.unwrap_or(i64::MAX);
+    let from = at.source(Tracked::Jobs)?;
 
-    let jobs = sqlx::query_as::<_, Job>(
+    let sql = format!(
         r#"
         SELECT job_id, conversation_id, 
                COALESCE(title, '') as title, 
@@ -77,30 +134,37 @@ async fn list_jobs(
                result_artifacts, estimated_duration_seconds,
                estimated_value,
                last_event_hash, last_event_seq
-        FROM projection_jobs
-        WHERE last_event_seq < $1
+        FROM {}
+        WHERE last_event_seq < $1 AND {}
         ORDER BY created_at DESC
         LIMIT $2
-        "#
-    )
-    .bind(before_seq)
-    .bind(limit)
-    .fetch_all(&state.pool)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        "#,
+        from,
+        viewer.filter(ScopedTable::Jobs, 3)
+    );
+    let jobs = viewer
+        .bind(sqlx::query_as::<_, Job>(&sql).bind(before_seq).bind(limit))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(ApiResponse { ok: true, data: jobs }))
 }
 
-/// GET /query/jobs/:job_id — Get single job
+/// GET /query/jobs/:job_id — Get single job (`?as_of=` for the version
+/// current then)
 async fn get_job(
     State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
     Path(job_id): Path<String>,
+    Query(at): Query<AsOf>,
 ) -> Result<Json<ApiResponse<Job>>, (StatusCode, String)> {
+    require_readable(&viewer, scope::job_access(&state.pool, &job_id).await, "Job")?;
+    let from = at.source(Tracked::Jobs)?;
     let projection = JobsProjection::new(state.pool);
     
     let job = projection
-        .get_job(&job_id)
+        .get_job(&job_id, &from)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "Job not found".to_string()))?;
@@ -109,14 +173,19 @@ async fn get_job(
 }
 
 /// GET /query/jobs/:job_id/approvals — Get pending approvals for job
+/// (`?as_of=`: the ones pending then)
 async fn get_job_approvals(
     State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
     Path(job_id): Path<String>,
+    Query(at): Query<AsOf>,
 ) -> Result<Json<ApiResponse<Vec<Approval>>>, (StatusCode, String)> {
+    require_readable(&viewer, scope::job_access(&state.pool, &job_id).await, "Job")?;
+    let from = at.source(Tracked::Approvals)?;
     let projection = JobsProjection::new(state.pool);
     
     let approvals = projection
-        .get_pending_approvals(&job_id)
+        .get_pending_approvals(&job_id, &from)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
@@ -124,37 +193,173 @@ async fn get_job_approvals(
 }
 
 /// GET /query/conversations/:conversation_id/jobs — Jobs in conversation
+/// (`?as_of=` as for a single job)
 async fn get_conversation_jobs(
     State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
     Path(conversation_id): Path<String>,
+    Query(at): Query<AsOf>,
 ) -> Result<Json<ApiResponse<Vec<Job>>>, (StatusCode, String)> {
+    require_readable(&viewer, scope::conversation_access(&state.pool, &conversation_id).await, "Conversation")?;
+    let from = at.source(Tracked::Jobs)?;
     let projection = JobsProjection::new(state.pool);
     
     let jobs = projection
-        .get_jobs_by_conversation(&conversation_id)
+        .get_jobs_by_conversation(&conversation_id, &from)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(ApiResponse { ok: true, data: jobs }))
 }
 
-/// GET /query/conversations/:conversation_id/messages — Messages in conversation
+/// Query params for the job board
+#[derive(Debug, Deserialize)]
+pub struct BoardQuery {
+    /// Defaults to the caller's tenant
+    pub tenant: Option<String>,
+}
+
+/// Query params for the broadcast inbox
+#[derive(Debug, Deserialize)]
+pub struct InboxQuery {
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub unread: bool,
+}
+
+/// GET /query/broadcasts/inbox — Announcements delivered to the caller
+async fn get_broadcast_inbox(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(query): Query<InboxQuery>,
+) -> Result<Json<ApiResponse<Vec<InboxItem>>>, (StatusCode, String)> {
+    let limit = PAGE.resolve(query.limit);
+    let items = BroadcastProjection::new(state.pool)
+        .inbox(&viewer.sid, query.unread, limit)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: items }))
+}
+
+/// GET /query/conversations/:conversation_id/broadcast_stats — Delivery and
+/// read counts per announcement, for the broadcast's senders and admins
+async fn get_broadcast_stats(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(conversation_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<ApiResponse<Vec<AnnouncementStats>>>, (StatusCode, String)> {
+    let projection = BroadcastProjection::new(state.pool);
+    let channel = projection
+        .channel(&conversation_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // Members see announcements, not who read them
+    let allowed = channel.is_some_and(|(created_by, senders, tenant_id)| {
+        viewer.can_read(&RowAccess { tenant_id, owner: Some(created_by), participants: senders })
+    });
+    if !allowed {
+        return Err((StatusCode::NOT_FOUND, "Broadcast not found".to_string()));
+    }
+
+    let stats = projection
+        .stats(&conversation_id, PAGE.resolve(query.limit))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: stats }))
+}
+
+/// GET /query/obligations — The caller's open obligations (mentions), newest first
+async fn list_obligations(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<ApiResponse<Vec<Obligation>>>, (StatusCode, String)> {
+    let obligations = MentionsProjection::new(state.pool)
+        .open(&viewer.sid, PAGE.resolve(query.limit))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: obligations }))
+}
+
+/// GET /query/board?tenant= — Jobs grouped by state, with conversation and assignee
+async fn get_board(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(query): Query<BoardQuery>,
+) -> Result<Json<ApiResponse<Board>>, (StatusCode, String)> {
+    let tenant_id = query.tenant.unwrap_or_else(|| viewer.tenant_id.clone());
+
+    let board = BoardProjection::new(state.pool)
+        .board(&viewer, &tenant_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: board }))
+}
+
+/// GET /query/conversations/:conversation_id/messages — Messages in
+/// conversation; admins see redacted ones as placeholders with the reason
 async fn get_conversation_messages(
     State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
     Path(conversation_id): Path<String>,
-    Query(query): Query<PaginationQuery>,
+    Query(query): Query<PageQuery>,
 ) -> Result<Json<ApiResponse<Vec<Message>>>, (StatusCode, String)> {
-    let limit = query.limit.unwrap_or(50).min(100);
+    require_readable(&viewer, scope::conversation_access(&state.pool, &conversation_id).await, "Conversation")?;
+    let limit = PAGE.resolve(query.limit);
     let projection = MessagesProjection::new(state.pool);
     
     let messages = projection
-        .get_messages_by_conversation(&conversation_id, limit, query.before_seq)
+        .get_messages_by_conversation(&conversation_id, limit, query.before_seq, Visibility::for_viewer(&viewer))
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     Ok(Json(ApiResponse { ok: true, data: messages }))
 }
 
+/// GET /query/conversations/:conversation_id/summaries — Summaries of the
+/// conversation, latest (pinned) first
+async fn list_summaries(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(conversation_id): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<ApiResponse<Vec<ConversationSummary>>>, (StatusCode, String)> {
+    require_readable(&viewer, scope::conversation_access(&state.pool, &conversation_id).await, "Conversation")?;
+    let summaries = SummaryProjection::new(state.pool)
+        .list(&conversation_id, SUMMARIES.resolve(query.limit))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: summaries }))
+}
+
+/// GET /query/conversations/:conversation_id/summaries/:summary_id — One
+/// summary with the messages it covers, in ledger order
+async fn get_summary(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Path((conversation_id, summary_id)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<SummaryDetail>>, (StatusCode, String)> {
+    require_readable(&viewer, scope::conversation_access(&state.pool, &conversation_id).await, "Conversation")?;
+    let projection = SummaryProjection::new(state.pool);
+    let summary = projection
+        .get(&conversation_id, &summary_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Summary not found".to_string()))?;
+    let messages = projection
+        .covered(&summary)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: SummaryDetail { summary, messages } }))
+}
+
 // =============================================================================
 // OFFICE PROJECTION ROUTES
 // =============================================================================
@@ -162,8 +367,11 @@ async fn get_conversation_messages(
 /// Query params for Office audit
 #[derive(Debug, Deserialize)]
 pub struct AuditQuery {
+    #[serde(default, deserialize_with = "non_empty")]
     pub entity_id: Option<String>,
+    #[serde(default, deserialize_with = "non_empty")]
     pub session_id: Option<String>,
+    #[serde(default, deserialize_with = "non_empty")]
     pub event_type: Option<String>,
     pub limit: Option<i64>,
 }
@@ -171,9 +379,9 @@ pub struct AuditQuery {
 /// GET /query/office/entities — List all LLM entities
 async fn list_entities(
     State(state): State<ProjectionState>,
-    Query(query): Query<PaginationQuery>,
+    Query(query): Query<PageQuery>,
 ) -> Result<Json<ApiResponse<Vec<EntityRow>>>, (StatusCode, String)> {
-    let limit = query.limit.unwrap_or(50).min(100);
+    let limit = PAGE.resolve(query.limit);
 
     let entities: Vec<EntityRow> = sqlx::query_as!(
         EntityRow,
@@ -224,9 +432,9 @@ async fn get_entity(
 async fn get_entity_sessions(
     State(state): State<ProjectionState>,
     Path(entity_id): Path<String>,
-    Query(query): Query<PaginationQuery>,
+    Query(query): Query<PageQuery>,
 ) -> Result<Json<ApiResponse<Vec<SessionRow>>>, (StatusCode, String)> {
-    let limit = query.limit.unwrap_or(50).min(100);
+    let limit = PAGE.resolve(query.limit);
 
     let sessions: Vec<SessionRow> = sqlx::query_as!(
         SessionRow,
@@ -252,9 +460,9 @@ async fn get_entity_sessions(
 async fn get_entity_handovers(
     State(state): State<ProjectionState>,
     Path(entity_id): Path<String>,
-    Query(query): Query<PaginationQuery>,
+    Query(query): Query<PageQuery>,
 ) -> Result<Json<ApiResponse<Vec<HandoverRow>>>, (StatusCode, String)> {
-    let limit = query.limit.unwrap_or(20).min(50);
+    let limit = HANDOVERS.resolve(query.limit);
 
     let handovers: Vec<HandoverRow> = sqlx::query_as!(
         HandoverRow,
@@ -303,37 +511,24 @@ async fn list_audit(
     State(state): State<ProjectionState>,
     Query(query): Query<AuditQuery>,
 ) -> Result<Json<ApiResponse<Vec<AuditRow>>>, (StatusCode, String)> {
-    let limit = query.limit.unwrap_or(100).min(500);
+    let limit = LOGS.resolve(query.limit);
 
-    // Build dynamic query based on filters
-    let mut sql = String::from(
-        "SELECT audit_id, entity_id, session_id, job_id, trace_id, 
-                event_type, event_data, created_at_ms
-         FROM office_audit_log WHERE 1=1"
-    );
-    
-    if query.entity_id.is_some() {
-        sql.push_str(" AND entity_id = $1");
-    }
-    if query.session_id.is_some() {
-        sql.push_str(" AND session_id = $2");
-    }
-    if query.event_type.is_some() {
-        sql.push_str(" AND event_type = $3");
-    }
-    
-    sql.push_str(" ORDER BY created_at_ms DESC LIMIT $4");
-
-    // For simplicity, use a simpler query
+    // Unset filters match everything
     let audits: Vec<AuditRow> = sqlx::query_as!(
         AuditRow,
         r#"
         SELECT audit_id, entity_id, session_id, job_id, trace_id,
-               event_type, event_data, created_at_ms
+               event_type, event_data, created_at_ms, entry_hash, sequence
         FROM office_audit_log
+        WHERE ($1::text IS NULL OR entity_id = $1)
+          AND ($2::text IS NULL OR session_id = $2)
+          AND ($3::text IS NULL OR event_type = $3)
         ORDER BY created_at_ms DESC
-        LIMIT $1
+        LIMIT $4
         "#,
+        query.entity_id,
+        query.session_id,
+        query.event_type,
         limit
     )
     .fetch_all(&state.pool)
@@ -343,3 +538,397 @@ async fn list_audit(
     Ok(Json(ApiResponse { ok: true, data: audits }))
 }
 
+/// Query params for unpacked observations; `cursor` (or the older
+/// `before_seq`) pages back by (sequence, batch index)
+#[derive(Debug, Deserialize)]
+pub struct ObservationsQuery {
+    #[serde(default, deserialize_with = "non_empty")]
+    pub container_id: Option<String>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub entity_id: Option<String>,
+    #[serde(rename = "type", default, deserialize_with = "non_empty")]
+    pub obs_type: Option<String>,
+    pub limit: Option<i64>,
+    pub before_seq: Option<i64>,
+    pub cursor: Option<Cursor>,
+}
+
+/// GET /query/observations — Observations unpacked from batches, newest first
+async fn list_observations(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(query): Query<ObservationsQuery>,
+) -> Result<Json<PagedResponse<Vec<ObservationRow>>>, (StatusCode, String)> {
+    let limit = LOGS.resolve(query.limit);
+    let before = Cursor::resolve(query.cursor, query.before_seq);
+
+    let rows = ObservationsProjection::new(state.pool)
+        .list(
+            &viewer,
+            query.container_id.as_deref(),
+            query.entity_id.as_deref(),
+            query.obs_type.as_deref(),
+            limit,
+            before,
+        )
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let next_cursor = Cursor::next(&rows, limit, |r| Cursor::new(r.sequence, r.batch_index.into()));
+
+    Ok(Json(PagedResponse { ok: true, data: rows, next_cursor }))
+}
+
+/// GET /query/observations/:entry_hash/:batch_index/proof — Merkle path to the batch root
+async fn get_observation_proof(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Path((entry_hash, batch_index)): Path<(String, i32)>,
+) -> Result<Json<ApiResponse<ObservationProof>>, (StatusCode, String)> {
+    require_readable(
+        &viewer,
+        scope::observation_access(&state.pool, &entry_hash, batch_index).await,
+        "Observation",
+    )?;
+    let proof = ObservationsProjection::new(state.pool)
+        .proof(&entry_hash, batch_index)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Observation not found".to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: proof }))
+}
+
+/// Query params for container analytics; days are `YYYY-MM-DD` (UTC, inclusive)
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    #[serde(default, deserialize_with = "non_empty")]
+    pub from: Option<String>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub to: Option<String>,
+    #[serde(default, deserialize_with = "non_empty")]
+    pub container_id: Option<String>,
+}
+
+/// GET /query/analytics/containers — Daily activity per container (operators only)
+async fn get_container_analytics(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Json<ApiResponse<Vec<ContainerStats>>>, (StatusCode, String)> {
+    // Containers span tenants: per-container totals are an operator view
+    if viewer.role != ViewerRole::Operator {
+        return Err((StatusCode::FORBIDDEN, "Container analytics require an operator session".to_string()));
+    }
+    let today = time::OffsetDateTime::now_utc().date();
+    let (from, to) = analytics::resolve_range(query.from.as_deref(), query.to.as_deref(), today)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let stats = AnalyticsProjection::new(state.pool)
+        .containers(from, to, query.container_id.as_deref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ApiResponse { ok: true, data: stats }))
+}
+
+/// Query params for the evolution changelog; `cursor` (or the older
+/// `before_ms`) pages back by commit time
+#[derive(Debug, Deserialize)]
+pub struct ChangelogQuery {
+    pub limit: Option<i64>,
+    pub before_ms: Option<i64>,
+    pub cursor: Option<Cursor>,
+}
+
+/// GET /query/evolution/:container_id — Rule changes of a container with diffs, newest first (operators only)
+async fn get_evolution_changelog(
+    State(state): State<ProjectionState>,
+    Extension(viewer): Extension<Viewer>,
+    Path(container_id): Path<String>,
+    Query(query): Query<ChangelogQuery>,
+) -> Result<Json<PagedResponse<Vec<EvolutionEntry>>>, (StatusCode, String)> {
+    // Container rules are shared by every tenant writing to it
+    if viewer.role != ViewerRole::Operator {
+        return Err((StatusCode::FORBIDDEN, "Evolution history requires an operator session".to_string()));
+    }
+    let limit = PAGE.resolve(query.limit);
+    let before = Cursor::resolve(query.cursor, query.before_ms);
+
+    let entries = ChangelogProjection::new(state.pool)
+        .history(&container_id, limit, before)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let next_cursor = Cursor::next(&entries, limit, |e| Cursor::new(e.committed_at_ms, e.sequence));
+
+    Ok(Json(PagedResponse { ok: true, data: entries, next_cursor }))
+}
+
+/// Response samples pinned by the API contract tests (see `crate::contracts`)
+#[cfg(test)]
+pub(crate) fn contract_samples() -> Vec<(&'static str, serde_json::Value)> {
+    use serde_json::json;
+    use time::OffsetDateTime;
+    use super::summaries::SegmentMessage;
+
+    let ts = OffsetDateTime::UNIX_EPOCH;
+    let job = Job {
+        job_id: "job_1".into(),
+        conversation_id: "conv_1".into(),
+        title: "t".into(),
+        description: "d".into(),
+        status: "pending".into(),
+        priority: "normal".into(),
+        assigned_to: Some("agent".into()),
+        created_by: "user".into(),
+        created_at: ts,
+        started_at: Some(ts),
+        completed_at: Some(ts),
+        cancelled_at: Some(ts),
+        progress: Some(1),
+        progress_message: Some("m".into()),
+        result_summary: Some("s".into()),
+        result_artifacts: Some(json!({})),
+        estimated_duration_seconds: Some(1),
+        estimated_value: Some(1.5),
+        last_event_hash: "h".into(),
+        last_event_seq: 1,
+    };
+    let approval = Approval {
+        approval_id: "apr_1".into(),
+        job_id: "job_1".into(),
+        action: "approve".into(),
+        reason: "r".into(),
+        requested_by: "agent".into(),
+        requested_at: ts,
+        status: "pending".into(),
+        decided_by: Some("user".into()),
+        decided_at: Some(ts),
+        decision: Some("approved".into()),
+        decision_reason: Some("ok".into()),
+        last_event_hash: "h".into(),
+        last_event_seq: 1,
+    };
+    let message = Message {
+        message_id: "msg_1".into(),
+        conversation_id: "conv_1".into(),
+        from_id: "user".into(),
+        content_hash: "h".into(),
+        timestamp: ts,
+        message_type: "text".into(),
+        read_by: vec!["user".into()],
+        last_event_hash: "h".into(),
+        last_event_seq: 1,
+        redaction: None,
+    };
+    let entity = EntityRow {
+        entity_id: "ent_1".into(),
+        name: "n".into(),
+        entity_type: "autonomous".into(),
+        public_key: "pk".into(),
+        status: "active".into(),
+        constitution: Some(json!({})),
+        baseline_narrative: Some("b".into()),
+        total_sessions: 1,
+        total_tokens_used: 1,
+        created_at_ms: 1,
+        updated_at_ms: 1,
+    };
+    let session = SessionRow {
+        session_id: "ses_1".into(),
+        entity_id: "ent_1".into(),
+        session_type: "work".into(),
+        mode: "commitment".into(),
+        token_budget: 1,
+        tokens_used: Some(1),
+        duration_ms: Some(1),
+        status: "completed".into(),
+        started_at_ms: 1,
+        completed_at_ms: Some(1),
+    };
+    let handover = HandoverRow {
+        handover_id: "ho_1".into(),
+        entity_id: "ent_1".into(),
+        session_id: "ses_1".into(),
+        content: json!({}),
+        created_at_ms: 1,
+    };
+    let audit = AuditRow {
+        audit_id: "aud_1".into(),
+        entity_id: "ent_1".into(),
+        session_id: "ses_1".into(),
+        job_id: Some("job_1".into()),
+        trace_id: "tr".into(),
+        event_type: "e".into(),
+        event_data: json!({}),
+        created_at_ms: 1,
+        entry_hash: Some("cd".into()),
+        sequence: Some(1),
+    };
+
+    let observation = ObservationRow {
+        entry_hash: "h".into(),
+        batch_index: 0,
+        container_id: "C.Messenger".into(),
+        sequence: 1,
+        tenant_id: "default".into(),
+        entity_id: Some("agent".into()),
+        obs_type: "presence.heartbeat".into(),
+        leaf_hash: "l".into(),
+        batch_root: "r".into(),
+        data: json!({}),
+        ts_ms: Some(1),
+    };
+
+    let proof = ObservationProof {
+        entry_hash: "h".into(),
+        batch_index: 0,
+        leaf_hash: "l".into(),
+        batch_root: "r".into(),
+        proof: vec![super::observations::ProofStep { hash: "s".into(), right: true }],
+    };
+
+    let card = super::board::BoardCard {
+        job_id: "job_1".into(),
+        title: "t".into(),
+        state: "proposed".into(),
+        assignee: "agent".into(),
+        waiting_on: Some(vec!["user".into()]),
+        conversation_id: Some("conv_1".into()),
+        conversation_name: Some("n".into()),
+        updated_at: ts,
+        last_event_seq: 1,
+    };
+    let board = Board {
+        tenant_id: "T.UBL".into(),
+        columns: vec![super::board::BoardColumn { state: "proposed".into(), jobs: vec![card] }],
+    };
+
+    let inbox_item = InboxItem {
+        message_id: "msg_1".into(),
+        conversation_id: "conv_1".into(),
+        conversation_name: Some("n".into()),
+        from_id: "user".into(),
+        content_hash: "h".into(),
+        delivered_at: ts,
+        read_at: Some(ts),
+    };
+    let announcement = AnnouncementStats {
+        message_id: "msg_1".into(),
+        from_id: "user".into(),
+        delivered: 2,
+        read: 1,
+        delivered_at: ts,
+        last_read_at: Some(ts),
+    };
+
+    let obligation = Obligation {
+        obligation_id: "mention:msg_1:user".into(),
+        kind: "mention".into(),
+        conversation_id: "conv_1".into(),
+        message_id: "msg_1".into(),
+        message_entry_hash: "h".into(),
+        from_id: "agent".into(),
+        created_at: ts,
+    };
+
+    let summary = ConversationSummary {
+        summary_id: "sum_1".into(),
+        conversation_id: "conv_1".into(),
+        from_sequence: 1,
+        to_sequence: 2,
+        covered_entry_hashes: vec!["h".into()],
+        message_count: 1,
+        summary_hash: "h".into(),
+        summary: Some("s".into()),
+        job_id: Some("job_sum_1".into()),
+        model: Some("m".into()),
+        created_at: ts,
+        last_event_hash: "h".into(),
+    };
+    let covered = SegmentMessage {
+        message_id: "msg_1".into(),
+        from_id: "user".into(),
+        content: Some("c".into()),
+        content_hash: "h".into(),
+        timestamp: ts,
+        entry_hash: "h".into(),
+        entry_seq: 1,
+    };
+
+    let container_stats = ContainerStats {
+        container_id: "C.Jobs".into(),
+        commits: 1,
+        rejected: 1,
+        error_rate: 0.5,
+        active_authors: 1,
+        delta_volume: "0".into(),
+        delta_net: "0".into(),
+        entropy_minted: "0".into(),
+        entropy_burned: "0".into(),
+        days: vec![analytics::ContainerDay {
+            container_id: "C.Jobs".into(),
+            day: "2026-01-01".into(),
+            commits: 1,
+            rejected: 1,
+            active_authors: 1,
+            delta_volume: "0".into(),
+            delta_net: "0".into(),
+            entropy_minted: "0".into(),
+            entropy_burned: "0".into(),
+        }],
+    };
+
+    let change = crate::evolution::FieldChange {
+        path: "policy.version".into(),
+        op: crate::evolution::ChangeOp::Changed,
+        before: Some(json!("1.0")),
+        after: Some(json!("1.1")),
+    };
+    let evolution = EvolutionEntry {
+        entry_hash: "h".into(),
+        container_id: "C.Admin".into(),
+        sequence: 2,
+        target_container_id: "C.Jobs".into(),
+        evolution_type: "evolution.policy_update".into(),
+        subject: "default_C.Jobs".into(),
+        previous_entry_hash: Some("h0".into()),
+        atom: json!({ "type": "evolution.policy_update" }),
+        summary: vec![change.render()],
+        changes: json!([change]),
+        committed_at_ms: 1,
+    };
+
+    let ok = |data: serde_json::Value| json!(ApiResponse { ok: true, data });
+    vec![
+        ("GET /query/jobs", ok(json!([job]))),
+        ("GET /query/jobs/:job_id", ok(json!(job))),
+        ("GET /query/jobs/:job_id/approvals", ok(json!([approval]))),
+        ("GET /query/conversations/:conversation_id/jobs", ok(json!([job]))),
+        ("GET /query/conversations/:conversation_id/messages", ok(json!([message]))),
+        ("GET /query/board", ok(json!(board))),
+        ("GET /query/broadcasts/inbox", ok(json!([inbox_item]))),
+        ("GET /query/conversations/:conversation_id/broadcast_stats", ok(json!([announcement]))),
+        ("GET /query/conversations/:conversation_id/summaries", ok(json!([summary]))),
+        (
+            "GET /query/conversations/:conversation_id/summaries/:summary_id",
+            ok(json!(SummaryDetail { summary: summary.clone(), messages: vec![covered] })),
+        ),
+        ("GET /query/obligations", ok(json!([obligation]))),
+        ("GET /query/office/entities", ok(json!([entity]))),
+        ("GET /query/office/entities/:entity_id", ok(json!(entity))),
+        ("GET /query/office/entities/:entity_id/sessions", ok(json!([session]))),
+        ("GET /query/office/entities/:entity_id/handovers", ok(json!([handover]))),
+        ("GET /query/office/entities/:entity_id/handovers/latest", ok(json!(Some(handover)))),
+        ("GET /query/office/audit", ok(json!([audit]))),
+        (
+            "GET /query/observations",
+            json!(PagedResponse { ok: true, data: vec![observation], next_cursor: Some(Cursor::new(1, 0)) }),
+        ),
+        ("GET /query/observations/:entry_hash/:batch_index/proof", ok(json!(proof))),
+        ("GET /query/analytics/containers", ok(json!([container_stats]))),
+        (
+            "GET /query/evolution/:container_id",
+            json!(PagedResponse { ok: true, data: vec![evolution], next_cursor: Some(Cursor::new(1, 2)) }),
+        ),
+    ]
+}
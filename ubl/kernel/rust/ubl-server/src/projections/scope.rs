@@ -0,0 +1,310 @@
+//! Row-level scoping for projection queries
+//!
+//! Shared containers (C.Jobs, C.Messenger) keep rows for many entities and
+//! tenants in the same tables. Every `/query` route runs behind
+//! [`resolve_viewer`], which turns the caller's session into a [`Viewer`];
+//! a request without a valid session never reaches a handler.
+//!
+//! One rule decides what a viewer may read:
+//!
+//! - an **operator** (step-up admin session with no tenant) reads every row;
+//! - a **tenant admin** (step-up admin session) reads every row of its tenant;
+//! - a **member** reads rows of its tenant that it owns (`owner_entity_id`)
+//!   or whose conversation lists it among `participants`.
+//!
+//! Conversations and messages carry no tenant, so for them only ownership
+//! or participation counts, whatever the role (operators excepted).
+//!
+//! Single rows (a job, a conversation, an observation) are checked with
+//! [`Viewer::can_read`]; list queries append [`Viewer::filter`], the same
+//! rule rendered as SQL. A row the viewer may
+//! not read is reported as not found, never as forbidden.
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use sqlx::{postgres::PgArguments, query::QueryAs, PgPool, Postgres};
+use tracing::warn;
+
+use super::routes::ProjectionState;
+use crate::auth::session::{Session, SessionFlavor};
+use crate::auth::session_db;
+
+/// Tenant assumed for sessions and rows that carry none
+pub const DEFAULT_TENANT: &str = "default";
+
+// =============================================================================
+// VIEWER
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewerRole {
+    Member,
+    TenantAdmin,
+    Operator,
+}
+
+impl ViewerRole {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ViewerRole::Member => "member",
+            ViewerRole::TenantAdmin => "tenant_admin",
+            ViewerRole::Operator => "operator",
+        }
+    }
+}
+
+/// Authenticated subject of a projection query
+#[derive(Debug, Clone)]
+pub struct Viewer {
+    pub sid: String,
+    pub tenant_id: String,
+    pub role: ViewerRole,
+}
+
+/// Ownership of one row, as far as scoping is concerned
+#[derive(Debug, Clone, Default)]
+pub struct RowAccess {
+    /// None for rows that carry no tenant (conversations)
+    pub tenant_id: Option<String>,
+    pub owner: Option<String>,
+    pub participants: Vec<String>,
+}
+
+/// Which table a list filter is rendered for
+#[derive(Debug, Clone, Copy)]
+pub enum ScopedTable {
+    /// `projection_jobs`: tenant, owner, conversation participants
+    Jobs,
+    /// `projection_observations`: tenant, observed entity
+    Observations,
+}
+
+impl Viewer {
+    /// Same step-up + admin test as `require_stepup`
+    pub fn from_session(session: &Session) -> Self {
+        let is_admin = session.flavor == SessionFlavor::StepUp
+            && session.scope.get("role").and_then(|v| v.as_str()) == Some("admin");
+        let role = match (is_admin, &session.tenant_id) {
+            (true, None) => ViewerRole::Operator,
+            (true, Some(_)) => ViewerRole::TenantAdmin,
+            (false, _) => ViewerRole::Member,
+        };
+        Self {
+            sid: session.sid.clone(),
+            tenant_id: session.tenant_id.clone().unwrap_or_else(|| DEFAULT_TENANT.to_string()),
+            role,
+        }
+    }
+
+    pub fn can_read(&self, row: &RowAccess) -> bool {
+        if self.role == ViewerRole::Operator {
+            return true;
+        }
+        let tenant_admin = match row.tenant_id.as_deref() {
+            Some(t) if t != self.tenant_id => return false,
+            // Without a tenant on the row, admin rights cannot be bounded
+            Some(_) => self.role == ViewerRole::TenantAdmin,
+            None => false,
+        };
+        tenant_admin
+            || row.owner.as_deref() == Some(self.sid.as_str())
+            || row.participants.iter().any(|p| *p == self.sid)
+    }
+
+    /// SQL predicate for `table`, using placeholders `$first` (sid),
+    /// `$first+1` (tenant) and `$first+2` (role); bind them with [`Viewer::bind`]
+    pub fn filter(&self, table: ScopedTable, first: usize) -> String {
+        let (sid, tenant, role) = (first, first + 1, first + 2);
+        let participant = |conversation: &str| {
+            format!(
+                "EXISTS (SELECT 1 FROM projection_conversations pc \
+                 WHERE pc.conversation_id = {conversation} AND ${sid} = ANY(pc.participants))"
+            )
+        };
+        match table {
+            ScopedTable::Jobs => format!(
+                "(${role} = 'operator' OR (projection_jobs.tenant_id = ${tenant} AND (${role} = 'tenant_admin' \
+                 OR projection_jobs.owner_entity_id = ${sid} OR {})))",
+                participant("projection_jobs.conversation_id")
+            ),
+            ScopedTable::Observations => format!(
+                "(${role} = 'operator' OR (projection_observations.tenant_id = ${tenant} AND (${role} = 'tenant_admin' \
+                 OR projection_observations.entity_id = ${sid})))"
+            ),
+        }
+    }
+
+    /// Bind the placeholders of [`Viewer::filter`], in order
+    pub fn bind<'q, O>(
+        &'q self,
+        query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        query.bind(&self.sid).bind(&self.tenant_id).bind(self.role.as_str())
+    }
+}
+
+// =============================================================================
+// ROW LOOKUPS
+// =============================================================================
+
+/// Tenant, owner and conversation participants of a job
+pub async fn job_access(pool: &PgPool, job_id: &str) -> Result<Option<RowAccess>, sqlx::Error> {
+    let row: Option<(String, String, Vec<String>)> = sqlx::query_as(
+        r#"
+        SELECT j.tenant_id, j.owner_entity_id, COALESCE(c.participants, '{}') AS participants
+        FROM projection_jobs j
+        LEFT JOIN projection_conversations c ON c.conversation_id = j.conversation_id
+        WHERE j.job_id = $1
+        "#,
+    )
+    .bind(job_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(tenant_id, owner, participants)| RowAccess {
+        tenant_id: Some(tenant_id),
+        owner: Some(owner),
+        participants,
+    }))
+}
+
+/// Creator and participants of a conversation
+pub async fn conversation_access(pool: &PgPool, conversation_id: &str) -> Result<Option<RowAccess>, sqlx::Error> {
+    let row: Option<(String, Vec<String>)> = sqlx::query_as(
+        "SELECT created_by, participants FROM projection_conversations WHERE conversation_id = $1",
+    )
+    .bind(conversation_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(created_by, participants)| RowAccess {
+        tenant_id: None,
+        owner: Some(created_by),
+        participants,
+    }))
+}
+
+/// Tenant and observed entity of one unpacked observation
+pub async fn observation_access(
+    pool: &PgPool,
+    entry_hash: &str,
+    batch_index: i32,
+) -> Result<Option<RowAccess>, sqlx::Error> {
+    let row: Option<(String, Option<String>)> = sqlx::query_as(
+        "SELECT tenant_id, entity_id FROM projection_observations WHERE entry_hash = $1 AND batch_index = $2",
+    )
+    .bind(entry_hash)
+    .bind(batch_index)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|(tenant_id, entity_id)| RowAccess {
+        tenant_id: Some(tenant_id),
+        owner: entity_id,
+        participants: Vec::new(),
+    }))
+}
+
+// =============================================================================
+// MIDDLEWARE
+// =============================================================================
+
+/// Resolve the session into a [`Viewer`] request extension, or reject with 401
+pub async fn resolve_viewer(
+    State(state): State<ProjectionState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, String)> {
+    let token = crate::id_routes::extract_session_token(req.headers())
+        .ok_or((StatusCode::UNAUTHORIZED, "missing session".to_string()))?;
+
+    let session = session_db::get_valid(&state.pool, &token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| {
+            warn!(path = %req.uri().path(), "🔒 Projection query with invalid or expired session");
+            (StatusCode::UNAUTHORIZED, "invalid or expired session".to_string())
+        })?;
+
+    req.extensions_mut().insert(Viewer::from_session(&session));
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewer(sid: &str, tenant: &str, role: ViewerRole) -> Viewer {
+        Viewer { sid: sid.into(), tenant_id: tenant.into(), role }
+    }
+
+    fn job(tenant: &str, owner: &str, participants: &[&str]) -> RowAccess {
+        RowAccess {
+            tenant_id: Some(tenant.into()),
+            owner: Some(owner.into()),
+            participants: participants.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_viewer_from_session() {
+        let member = Session::new_regular_with_tenant("ubl:sid:alice", Some("T.A".into()));
+        let v = Viewer::from_session(&member);
+        assert_eq!((v.tenant_id.as_str(), v.role), ("T.A", ViewerRole::Member));
+
+        let v = Viewer::from_session(&Session::new_regular("ubl:sid:alice"));
+        assert_eq!((v.tenant_id.as_str(), v.role), (DEFAULT_TENANT, ViewerRole::Member));
+
+        let admin = Session::new_stepup_with_tenant("ubl:sid:root", Some("T.A".into()));
+        assert_eq!(Viewer::from_session(&admin).role, ViewerRole::TenantAdmin);
+        assert_eq!(Viewer::from_session(&Session::new_stepup("ubl:sid:root")).role, ViewerRole::Operator);
+    }
+
+    #[test]
+    fn test_member_sees_only_own_or_participating_rows() {
+        let alice = viewer("alice", "T.A", ViewerRole::Member);
+        assert!(alice.can_read(&job("T.A", "alice", &[])));
+        assert!(alice.can_read(&job("T.A", "bob", &["bob", "alice"])));
+
+        // Same tenant, not involved
+        assert!(!alice.can_read(&job("T.A", "bob", &["bob", "carol"])));
+        // Other tenant, even when owner or participant
+        assert!(!alice.can_read(&job("T.B", "alice", &["alice"])));
+        // Rows without a tenant still need involvement
+        let conversation = RowAccess { owner: Some("bob".into()), ..Default::default() };
+        assert!(!alice.can_read(&conversation));
+    }
+
+    #[test]
+    fn test_admins_bounded_by_tenant() {
+        let admin = viewer("root", "T.A", ViewerRole::TenantAdmin);
+        assert!(admin.can_read(&job("T.A", "bob", &[])));
+        assert!(!admin.can_read(&job("T.B", "bob", &[])));
+        // Conversations carry no tenant, so admin rights do not reach them
+        let conversation = RowAccess { owner: Some("bob".into()), ..Default::default() };
+        assert!(!admin.can_read(&conversation));
+
+        let operator = viewer("root", DEFAULT_TENANT, ViewerRole::Operator);
+        assert!(operator.can_read(&job("T.B", "bob", &[])));
+        assert!(operator.can_read(&conversation));
+    }
+
+    #[test]
+    fn test_filter_placeholders() {
+        let v = viewer("alice", "T.A", ViewerRole::Member);
+        let jobs = v.filter(ScopedTable::Jobs, 3);
+        assert!(jobs.contains("projection_jobs.tenant_id = $4"));
+        assert!(jobs.contains("projection_jobs.owner_entity_id = $3"));
+        assert!(jobs.contains("$3 = ANY(pc.participants)"));
+        assert!(jobs.contains("$5 = 'operator'"));
+        assert!(!jobs.contains("$6"));
+
+        let observations = v.filter(ScopedTable::Observations, 1);
+        assert!(observations.contains("projection_observations.tenant_id = $2"));
+        assert!(observations.contains("projection_observations.entity_id = $1"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Conversation Summaries — pinned digests of long conversations
+//!
+//! The gateway's summarizer commits one `conversation.summarized` atom per
+//! segment, listing the `message.created` entry hashes it covers and the hash
+//! of the summary text (kept off-ledger in `message_content`, keyed by
+//! summary id). Segments are contiguous: each one starts after the previous
+//! summary's `to_sequence`. The latest summary is pinned at the top of the
+//! conversation timeline; drill-down returns the messages it covers.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::info;
+
+use super::visibility::Visibility;
+
+/// Atom type of a committed summary
+pub const SUMMARY_TYPE: &str = "conversation.summarized";
+
+/// One summary of a conversation segment, text included
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ConversationSummary {
+    pub summary_id: String,
+    pub conversation_id: String,
+    pub from_sequence: i64,
+    pub to_sequence: i64,
+    pub covered_entry_hashes: Vec<String>,
+    pub message_count: i32,
+    pub summary_hash: String,
+    /// None if the off-ledger text was not stored
+    pub summary: Option<String>,
+    pub job_id: Option<String>,
+    pub model: Option<String>,
+    pub created_at: OffsetDateTime,
+    /// Entry of the conversation.summarized link
+    pub last_event_hash: String,
+}
+
+/// A message covered by a summary, or waiting for one
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SegmentMessage {
+    pub message_id: String,
+    pub from_id: String,
+    pub content: Option<String>,
+    pub content_hash: String,
+    pub timestamp: OffsetDateTime,
+    pub entry_hash: String,
+    pub entry_seq: i64,
+}
+
+/// A summary with the messages it covers (drill-down)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryDetail {
+    pub summary: ConversationSummary,
+    pub messages: Vec<SegmentMessage>,
+}
+
+const COLUMNS: &str = "s.summary_id, s.conversation_id, s.from_sequence, s.to_sequence, \
+                       s.covered_entry_hashes, s.message_count, s.summary_hash, c.content AS summary, \
+                       s.job_id, s.model, s.created_at, s.last_event_hash";
+
+/// Summaries projection handler
+pub struct SummaryProjection {
+    pool: PgPool,
+}
+
+impl SummaryProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a `conversation.summarized` atom
+    pub async fn handle_summarized(
+        &self,
+        atom: &serde_json::Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        let summary_id = atom["summary_id"].as_str().unwrap_or_default();
+        let covered: Vec<String> = atom["covered_entry_hashes"]
+            .as_array()
+            .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_conversation_summaries (
+                summary_id, conversation_id, tenant_id, from_sequence, to_sequence,
+                covered_entry_hashes, message_count, summary_hash, job_id, model,
+                last_event_hash, last_event_seq
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (summary_id) DO NOTHING
+            "#,
+        )
+        .bind(summary_id)
+        .bind(atom["conversation_id"].as_str().unwrap_or_default())
+        .bind(atom["tenant_id"].as_str().unwrap_or("default"))
+        .bind(atom["from_sequence"].as_i64().unwrap_or_default())
+        .bind(atom["to_sequence"].as_i64().unwrap_or_default())
+        .bind(&covered)
+        .bind(covered.len() as i32)
+        .bind(atom["summary_hash"].as_str().unwrap_or_default())
+        .bind(atom["job_id"].as_str())
+        .bind(atom["model"].as_str())
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        info!("🧾 Summary {} covers {} messages", summary_id, covered.len());
+        Ok(())
+    }
+
+    /// The pinned (most recent) summary of a conversation
+    pub async fn latest(&self, conversation_id: &str) -> Result<Option<ConversationSummary>, sqlx::Error> {
+        sqlx::query_as::<_, ConversationSummary>(&format!(
+            r#"
+            SELECT {COLUMNS}
+            FROM projection_conversation_summaries s
+            LEFT JOIN message_content c ON c.message_id = s.summary_id
+            WHERE s.conversation_id = $1
+            ORDER BY s.to_sequence DESC
+            LIMIT 1
+            "#
+        ))
+        .bind(conversation_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Summaries of a conversation, newest segment first
+    pub async fn list(&self, conversation_id: &str, limit: i64) -> Result<Vec<ConversationSummary>, sqlx::Error> {
+        sqlx::query_as::<_, ConversationSummary>(&format!(
+            r#"
+            SELECT {COLUMNS}
+            FROM projection_conversation_summaries s
+            LEFT JOIN message_content c ON c.message_id = s.summary_id
+            WHERE s.conversation_id = $1
+            ORDER BY s.to_sequence DESC
+            LIMIT $2
+            "#
+        ))
+        .bind(conversation_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get(&self, conversation_id: &str, summary_id: &str) -> Result<Option<ConversationSummary>, sqlx::Error> {
+        sqlx::query_as::<_, ConversationSummary>(&format!(
+            r#"
+            SELECT {COLUMNS}
+            FROM projection_conversation_summaries s
+            LEFT JOIN message_content c ON c.message_id = s.summary_id
+            WHERE s.conversation_id = $1 AND s.summary_id = $2
+            "#
+        ))
+        .bind(conversation_id)
+        .bind(summary_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Messages whose entry hashes a summary lists, in ledger order; redacted
+    /// ones are left out for every reader
+    pub async fn covered(&self, summary: &ConversationSummary) -> Result<Vec<SegmentMessage>, sqlx::Error> {
+        let sql = format!(
+            r#"
+            SELECT m.message_id, m.from_id, c.content, m.content_hash, m.timestamp, m.entry_hash, m.entry_seq
+            FROM projection_messages m
+            LEFT JOIN message_content c ON c.message_id = m.message_id
+            WHERE m.conversation_id = $1 AND m.entry_hash = ANY($2) AND {}
+            ORDER BY m.entry_seq
+            "#,
+            Visibility::Member.filter("m.message_id")
+        );
+        sqlx::query_as::<_, SegmentMessage>(&sql)
+            .bind(&summary.conversation_id)
+            .bind(&summary.covered_entry_hashes)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Conversations with at least `min_messages` messages after their latest
+    /// summary, as (conversation_id, tenant_id), most backlog first
+    pub async fn due(&self, min_messages: i64, limit: i64) -> Result<Vec<(String, String)>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT m.conversation_id, COALESCE(MAX(c.tenant_id), 'default') AS tenant_id
+            FROM projection_messages m
+            JOIN projection_conversations c ON c.conversation_id = m.conversation_id
+            WHERE m.entry_seq > COALESCE(
+                (SELECT MAX(s.to_sequence) FROM projection_conversation_summaries s
+                 WHERE s.conversation_id = m.conversation_id), 0)
+            GROUP BY m.conversation_id
+            HAVING COUNT(*) >= $1
+            ORDER BY COUNT(*) DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(min_messages)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Up to `limit` oldest messages not covered by a summary yet; redacted
+    /// messages are never summarized
+    pub async fn next_segment(&self, conversation_id: &str, limit: i64) -> Result<Vec<SegmentMessage>, sqlx::Error> {
+        let sql = format!(
+            r#"
+            SELECT m.message_id, m.from_id, c.content, m.content_hash, m.timestamp, m.entry_hash, m.entry_seq
+            FROM projection_messages m
+            LEFT JOIN message_content c ON c.message_id = m.message_id
+            WHERE m.conversation_id = $1 AND m.entry_seq > COALESCE(
+                (SELECT MAX(to_sequence) FROM projection_conversation_summaries
+                 WHERE conversation_id = $1), 0)
+              AND {}
+            ORDER BY m.entry_seq
+            LIMIT $2
+            "#,
+            Visibility::Member.filter("m.message_id")
+        );
+        sqlx::query_as::<_, SegmentMessage>(&sql)
+            .bind(conversation_id)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+    }
+}
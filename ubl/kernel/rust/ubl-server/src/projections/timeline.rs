@@ -2,11 +2,27 @@
 //!
 //! Optimized timeline view for conversations (messages + job cards).
 //! Combines messages and job cards in a single sorted view.
+//!
+//! Office's `tool.artifact_card` atoms (tables, diffs, image references from
+//! tool results) land here as `artifact_card` items, from whatever container
+//! Office audits into; images are served by the gateway's blob route.
+//!
+//! Items are ordered by the HLC of their entry (see `hlc`): sequence order
+//! for a container, causal order across the containers a timeline mixes.
+//! `created_at` is when this replica projected the item, for display only.
 
 use sqlx::PgPool;
 use time::OffsetDateTime;
 use tracing::info;
 
+use crate::hlc::Hlc;
+
+/// Atom type of a tool result artifact card
+pub const ARTIFACT_CARD_TYPE: &str = "tool.artifact_card";
+
+/// Timeline item type of an artifact card
+pub const ARTIFACT_CARD_ITEM: &str = "artifact_card";
+
 /// Timeline projection handler
 pub struct TimelineProjection {
     pool: PgPool,
@@ -17,7 +33,7 @@ impl TimelineProjection {
         Self { pool }
     }
 
-    /// Add a timeline item (message or job card)
+    /// Add a timeline item (message or job card); `hlc` is the entry's
     pub async fn add_item(
         &self,
         tenant_id: &str,
@@ -25,25 +41,27 @@ impl TimelineProjection {
         item_type: &str,
         item_data: &serde_json::Value,
         sequence: i64,
+        hlc: Hlc,
     ) -> Result<(), sqlx::Error> {
         let ts = OffsetDateTime::now_utc();
         let cursor = format!("{}:{}", sequence, ts.unix_timestamp());
 
-        sqlx::query!(
+        sqlx::query(
             r#"
             INSERT INTO projection_timeline_items (
-                tenant_id, conversation_id, cursor, item_type, item_data, created_at
-            ) VALUES ($1, $2, $3, $4, $5, $6)
+                tenant_id, conversation_id, cursor, item_type, item_data, created_at, hlc
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
             ON CONFLICT (tenant_id, conversation_id, cursor) DO UPDATE SET
                 item_data = EXCLUDED.item_data
             "#,
-            tenant_id,
-            conversation_id,
-            cursor,
-            item_type,
-            item_data,
-            ts
         )
+        .bind(tenant_id)
+        .bind(conversation_id)
+        .bind(&cursor)
+        .bind(item_type)
+        .bind(item_data)
+        .bind(ts)
+        .bind(hlc.to_string())
         .execute(&self.pool)
         .await?;
 
@@ -51,6 +69,49 @@ impl TimelineProjection {
         Ok(())
     }
 
+    /// Add an artifact card to its conversation's timeline; false when the
+    /// card names no conversation
+    pub async fn add_artifact_card(
+        &self,
+        tenant_id: &str,
+        atom: &serde_json::Value,
+        sequence: i64,
+        hlc: Hlc,
+    ) -> Result<bool, sqlx::Error> {
+        let Some(conversation_id) = atom.get("conversation_id").and_then(|v| v.as_str()).filter(|c| !c.is_empty()) else {
+            return Ok(false);
+        };
+        self.add_item(tenant_id, conversation_id, ARTIFACT_CARD_ITEM, atom, sequence, hlc).await?;
+        Ok(true)
+    }
+
+    /// Mime type of a blob referenced by an artifact card in the conversation;
+    /// None when no card there references it
+    pub async fn artifact_blob_type(
+        &self,
+        tenant_id: &str,
+        conversation_id: &str,
+        content_hash: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let row: Option<(Option<String>,)> = sqlx::query_as(
+            r#"
+            SELECT item_data->'card'->>'mime_type'
+            FROM projection_timeline_items
+            WHERE tenant_id = $1 AND conversation_id = $2
+              AND item_type = $3
+              AND item_data->'card'->>'content_hash' = $4
+            LIMIT 1
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(conversation_id)
+        .bind(ARTIFACT_CARD_ITEM)
+        .bind(content_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|(mime_type,)| mime_type.unwrap_or_else(|| "application/octet-stream".to_string())))
+    }
+
     /// Get timeline for a conversation
     pub async fn get_timeline(
         &self,
@@ -64,11 +125,14 @@ impl TimelineProjection {
         let rows = if let Some(cursor_val) = cursor {
             sqlx::query(
                 r#"
-                SELECT cursor, item_type, item_data, created_at
+                SELECT cursor, item_type, item_data, created_at, hlc
                 FROM projection_timeline_items
                 WHERE tenant_id = $1 AND conversation_id = $2
-                  AND cursor > $3
-                ORDER BY created_at ASC
+                  AND hlc > COALESCE((
+                      SELECT hlc FROM projection_timeline_items
+                      WHERE tenant_id = $1 AND conversation_id = $2 AND cursor = $3
+                  ), '')
+                ORDER BY hlc ASC
                 LIMIT $4
                 "#
             )
@@ -81,10 +145,10 @@ impl TimelineProjection {
         } else {
             sqlx::query(
                 r#"
-                SELECT cursor, item_type, item_data, created_at
+                SELECT cursor, item_type, item_data, created_at, hlc
                 FROM projection_timeline_items
                 WHERE tenant_id = $1 AND conversation_id = $2
-                ORDER BY created_at DESC
+                ORDER BY hlc DESC NULLS LAST
                 LIMIT $3
                 "#
             )
@@ -100,13 +164,50 @@ impl TimelineProjection {
             let item_type: String = r.get("item_type");
             let item_data: serde_json::Value = r.get("item_data");
             let created_at: time::OffsetDateTime = r.get("created_at");
+            let created_at_ms = crate::timestamps::from_datetime(created_at);
+            let hlc: Option<String> = r.get("hlc");
             serde_json::json!({
                 "cursor": cursor,
+                "hlc": hlc,
                 "item_type": item_type,
                 "item_data": item_data,
-                "created_at": created_at.to_string(),
+                "created_at": crate::timestamps::rfc3339_utc(created_at_ms),
+                "created_at_ms": created_at_ms,
             })
         }).collect())
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore] // Needs DATABASE_URL with the ubl/sql schema applied
+    async fn test_artifact_blob_is_scoped_to_its_conversation() {
+        let database_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgres://localhost:5432/ubl_test".to_string());
+        let pool = PgPool::connect(&database_url).await.expect("Failed to connect to test database");
+        let timeline = TimelineProjection::new(pool);
+        let conversation_id = format!("conv_{}", uuid::Uuid::new_v4());
+        let hash = format!("blake3:{}", "ab".repeat(32));
+        let card = serde_json::json!({
+            "type": ARTIFACT_CARD_TYPE,
+            "conversation_id": conversation_id,
+            "card": { "card_id": "acard_t_0", "kind": "image", "content_hash": hash, "mime_type": "image/png" },
+        });
+
+        assert!(timeline.add_artifact_card("default", &card, 1, Hlc::new(1, 0)).await.unwrap());
+        assert_eq!(
+            timeline.artifact_blob_type("default", &conversation_id, &hash).await.unwrap().as_deref(),
+            Some("image/png")
+        );
+        // Another conversation or tenant cannot reach the blob through this card
+        assert_eq!(timeline.artifact_blob_type("default", "conv_other", &hash).await.unwrap(), None);
+        assert_eq!(timeline.artifact_blob_type("other", &conversation_id, &hash).await.unwrap(), None);
+
+        // Cards without a conversation stay out of timelines
+        assert!(!timeline.add_artifact_card("default", &serde_json::json!({ "type": ARTIFACT_CARD_TYPE }), 2, Hlc::new(2, 0)).await.unwrap());
+    }
+}
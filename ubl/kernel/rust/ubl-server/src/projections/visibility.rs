@@ -0,0 +1,293 @@
+//! Redaction visibility for message reads
+//!
+//! Redaction is append-only: a `message.redacted` atom in C.Messenger names
+//! the message and the reason, and the message row, its off-ledger content
+//! and its ledger entry stay where they are. This module is the one place
+//! that decides what a reader sees afterwards, so every read path (messages,
+//! timeline, summary segments) hides the same things:
+//!
+//! - a **member** never sees a redacted message, nor anything pointing at it
+//!   (mentions, the redaction record itself);
+//! - an **admin** (tenant admin or operator on `/query`) sees a placeholder
+//!   in its place with who redacted it, when and why, never the content.
+//!
+//! List queries append [`Visibility::filter`]; rows already loaded go through
+//! [`Visibility::apply_messages`] and [`Visibility::apply_timeline`] with the
+//! tombstones from [`RedactionsProjection::for_messages`].
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::info;
+
+use super::messages::Message;
+use super::scope::{Viewer, ViewerRole};
+
+/// Atom type redacting one message
+pub const REDACTION_TYPE: &str = "message.redacted";
+
+/// Longest accepted redaction reason, in characters
+pub const MAX_REASON_CHARS: usize = 500;
+
+/// Tombstone of a redacted message
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Redaction {
+    pub message_id: String,
+    pub redacted_by: String,
+    pub reason: String,
+    pub redacted_at: OffsetDateTime,
+    /// Ledger entry of the `message.redacted` atom
+    pub last_event_hash: String,
+}
+
+/// What a reader sees of redacted messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Redacted messages are gone
+    Member,
+    /// Redacted messages are placeholders carrying the tombstone
+    Admin,
+}
+
+impl Visibility {
+    /// Admins per `scope`: step-up admin sessions, tenant-bound or not
+    pub fn for_viewer(viewer: &Viewer) -> Self {
+        match viewer.role {
+            ViewerRole::Member => Visibility::Member,
+            ViewerRole::TenantAdmin | ViewerRole::Operator => Visibility::Admin,
+        }
+    }
+
+    /// SQL predicate over a message id column. Admins keep every row and get
+    /// placeholders from [`Visibility::apply_messages`] instead.
+    pub fn filter(&self, message_id_column: &str) -> String {
+        match self {
+            Visibility::Member => format!(
+                "NOT EXISTS (SELECT 1 FROM projection_redactions pr WHERE pr.message_id = {message_id_column})"
+            ),
+            Visibility::Admin => "TRUE".to_string(),
+        }
+    }
+
+    /// Drop (members) or blank (admins) redacted messages
+    pub fn apply_messages(&self, messages: Vec<Message>, redactions: &HashMap<String, Redaction>) -> Vec<Message> {
+        messages
+            .into_iter()
+            .filter_map(|mut message| match redactions.get(&message.message_id) {
+                None => Some(message),
+                Some(_) if *self == Visibility::Member => None,
+                Some(redaction) => {
+                    // The hash would let a reader confirm a guess of the content
+                    message.content_hash = String::new();
+                    message.redaction = Some(redaction.clone());
+                    Some(message)
+                }
+            })
+            .collect()
+    }
+
+    /// Timeline items (`get_timeline` shape) with redactions applied: items
+    /// about a redacted message are dropped for members; for admins a
+    /// message item becomes a placeholder and the rest stay as they are.
+    pub fn apply_timeline(
+        &self,
+        items: Vec<serde_json::Value>,
+        redactions: &HashMap<String, Redaction>,
+    ) -> Vec<serde_json::Value> {
+        items
+            .into_iter()
+            .filter_map(|mut item| {
+                if item["item_data"]["type"] == REDACTION_TYPE {
+                    return (*self == Visibility::Admin).then_some(item);
+                }
+                let redaction = timeline_message_id(&item).and_then(|id| redactions.get(id));
+                match (redaction, self) {
+                    (None, _) => Some(item),
+                    (Some(_), Visibility::Member) => None,
+                    (Some(redaction), Visibility::Admin) => {
+                        if item["item_type"] == "message" {
+                            item["item_data"] = placeholder(&item["item_data"], redaction);
+                        }
+                        Some(item)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// Message a timeline item is about: the message itself, or the one a
+/// mention or redaction names
+pub fn timeline_message_id(item: &serde_json::Value) -> Option<&str> {
+    let data = &item["item_data"];
+    data["message_id"]
+        .as_str()
+        .or_else(|| if item["item_type"] == "message" { data["id"].as_str() } else { None })
+}
+
+/// What an admin sees instead of a redacted message atom
+fn placeholder(message: &serde_json::Value, redaction: &Redaction) -> serde_json::Value {
+    let redacted_at = crate::timestamps::from_datetime(redaction.redacted_at);
+    serde_json::json!({
+        "type": "message.created",
+        "id": redaction.message_id,
+        "conversation_id": message["conversation_id"],
+        "from": message["from"],
+        "created_at": message["created_at"],
+        "redacted": {
+            "by": redaction.redacted_by,
+            "reason": redaction.reason,
+            "at": crate::timestamps::rfc3339_utc(redacted_at),
+            "entry_hash": redaction.last_event_hash,
+        },
+    })
+}
+
+/// Redactions projection handler
+pub struct RedactionsProjection {
+    pool: PgPool,
+}
+
+impl RedactionsProjection {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// `message.redacted`: record the tombstone; the first redaction of a
+    /// message wins
+    pub async fn handle_redacted(
+        &self,
+        atom: &serde_json::Value,
+        entry_hash: &str,
+        sequence: i64,
+    ) -> Result<(), sqlx::Error> {
+        let message_id = atom["message_id"].as_str().unwrap_or_default();
+        let redacted_by = atom["redacted_by"].as_str().unwrap_or_default();
+
+        sqlx::query(
+            r#"
+            INSERT INTO projection_redactions (
+                message_id, conversation_id, tenant_id, redacted_by, reason, redacted_at,
+                last_event_hash, last_event_seq
+            ) VALUES ($1, $2, $3, $4, $5, $6::timestamptz, $7, $8)
+            ON CONFLICT (message_id) DO NOTHING
+            "#,
+        )
+        .bind(message_id)
+        .bind(atom["conversation_id"].as_str().unwrap_or_default())
+        .bind(atom["tenant_id"].as_str().unwrap_or("default"))
+        .bind(redacted_by)
+        .bind(atom["reason"].as_str().unwrap_or_default())
+        .bind(atom["redacted_at"].as_str().unwrap_or_default())
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        // Nobody owes attention to a message they can no longer read
+        sqlx::query(
+            r#"
+            UPDATE projection_obligations
+            SET acknowledged_at = NOW(), last_event_hash = $2, last_event_seq = $3
+            WHERE message_id = $1 AND acknowledged_at IS NULL AND last_event_seq < $3
+            "#,
+        )
+        .bind(message_id)
+        .bind(entry_hash)
+        .bind(sequence)
+        .execute(&self.pool)
+        .await?;
+
+        info!("🪦 Message redacted: {} by {}", message_id, redacted_by);
+        Ok(())
+    }
+
+    /// Tombstones of the given messages, by message id
+    pub async fn for_messages(&self, message_ids: &[String]) -> Result<HashMap<String, Redaction>, sqlx::Error> {
+        if message_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = sqlx::query_as::<_, Redaction>(
+            r#"
+            SELECT message_id, redacted_by, reason, redacted_at, last_event_hash
+            FROM projection_redactions
+            WHERE message_id = ANY($1)
+            "#,
+        )
+        .bind(message_ids)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| (r.message_id.clone(), r)).collect())
+    }
+
+    /// Tombstones for whatever messages these timeline items are about
+    pub async fn for_timeline(&self, items: &[serde_json::Value]) -> Result<HashMap<String, Redaction>, sqlx::Error> {
+        let ids: Vec<String> = items.iter().filter_map(timeline_message_id).map(String::from).collect();
+        self.for_messages(&ids).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn redactions() -> HashMap<String, Redaction> {
+        let redaction = Redaction {
+            message_id: "msg_2".into(),
+            redacted_by: "ubl:sid:admin".into(),
+            reason: "posted a password".into(),
+            redacted_at: OffsetDateTime::UNIX_EPOCH,
+            last_event_hash: "h_redact".into(),
+        };
+        HashMap::from([("msg_2".to_string(), redaction)])
+    }
+
+    fn item(item_type: &str, data: serde_json::Value) -> serde_json::Value {
+        json!({ "cursor": "1:0", "item_type": item_type, "item_data": data })
+    }
+
+    fn timeline() -> Vec<serde_json::Value> {
+        vec![
+            item("message", json!({ "type": "message.created", "id": "msg_1", "from": "alice" })),
+            item("message", json!({ "type": "message.created", "id": "msg_2", "from": "bob", "content_hash": "abc" })),
+            item("system", json!({ "type": "mention.created", "message_id": "msg_2" })),
+            item("system", json!({ "type": REDACTION_TYPE, "message_id": "msg_2", "reason": "posted a password" })),
+        ]
+    }
+
+    #[test]
+    fn test_members_never_see_redacted_messages() {
+        let items = Visibility::Member.apply_timeline(timeline(), &redactions());
+        let ids: Vec<Option<&str>> = items.iter().map(timeline_message_id).collect();
+        assert_eq!(ids, [Some("msg_1")]);
+    }
+
+    #[test]
+    fn test_admins_see_placeholders_with_reason() {
+        let items = Visibility::Admin.apply_timeline(timeline(), &redactions());
+        assert_eq!(items.len(), 4);
+
+        let placeholder = &items[1]["item_data"];
+        assert_eq!(placeholder["id"], "msg_2");
+        assert_eq!(placeholder["from"], "bob");
+        assert_eq!(placeholder["redacted"]["reason"], "posted a password");
+        assert_eq!(placeholder["redacted"]["by"], "ubl:sid:admin");
+        assert!(placeholder.get("content_hash").is_none());
+        // Untouched when not redacted
+        assert_eq!(items[0]["item_data"]["from"], "alice");
+    }
+
+    #[test]
+    fn test_member_filter_excludes_tombstoned_rows() {
+        assert!(Visibility::Member.filter("m.message_id").contains("pr.message_id = m.message_id"));
+        assert_eq!(Visibility::Admin.filter("m.message_id"), "TRUE");
+
+        let admin = Viewer { sid: "root".into(), tenant_id: "T.A".into(), role: ViewerRole::TenantAdmin };
+        assert_eq!(Visibility::for_viewer(&admin), Visibility::Admin);
+        let member = Viewer { role: ViewerRole::Member, ..admin };
+        assert_eq!(Visibility::for_viewer(&member), Visibility::Member);
+    }
+}
@@ -0,0 +1,476 @@
+//! Public read gateway — its own listener, read-only, token-gated
+//!
+//! Off unless `UBL_PUBLIC_READ_ADDR` is set (e.g. `0.0.0.0:8090`). The
+//! gateway is a separate router on a separate socket: admin, identity,
+//! commit and query routes are simply not there, and no session cookie or
+//! admin credential is ever read. Every request needs
+//! `Authorization: Bearer ubl_pub_...` (see [`super`]).
+//!
+//! Each request counts against the token's daily quota, cached or not;
+//! past it the gateway answers 429 with `Retry-After` set to the next UTC
+//! midnight. Responses are cached in memory per URL for
+//! `UBL_PUBLIC_READ_CACHE_SECS` (default 30), up to
+//! `UBL_PUBLIC_READ_CACHE_ENTRIES` (default 1000).
+//!
+//! Only entry headers are served, never atoms: message content has its own
+//! visibility and redaction rules, and encrypted containers stay sealed.
+//!
+//! Endpoints:
+//! - GET /public/v1/usage                                    → Token tier and today's usage
+//! - GET /public/v1/containers/:container_id/state           → Head, entry count, last entry time
+//! - GET /public/v1/containers/:container_id/entries?after=&limit= → Entry headers, in sequence order
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderValue, Method},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tower_http::cors::{Any, CorsLayer};
+use tracing::{error, info};
+use ubl_errors::ErrorCode;
+
+use super::hash_token;
+use crate::api_error::ApiError;
+use crate::hlc::Hlc;
+use crate::metrics::PUBLIC_READS;
+use crate::timestamps::now_ms;
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 500;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Configuration of the public read gateway
+#[derive(Debug, Clone)]
+pub struct PublicReadConfig {
+    /// Listen address; None keeps the gateway off
+    pub addr: Option<String>,
+    /// How long a response is served from cache
+    pub cache_ttl_secs: u64,
+    /// Cached responses kept at most
+    pub cache_max_entries: usize,
+}
+
+impl Default for PublicReadConfig {
+    fn default() -> Self {
+        Self {
+            addr: None,
+            cache_ttl_secs: 30,
+            cache_max_entries: 1000,
+        }
+    }
+}
+
+impl PublicReadConfig {
+    /// Defaults overridden by `UBL_PUBLIC_READ_ADDR`, `UBL_PUBLIC_READ_CACHE_SECS`
+    /// and `UBL_PUBLIC_READ_CACHE_ENTRIES`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            addr: std::env::var("UBL_PUBLIC_READ_ADDR").ok().filter(|s| !s.trim().is_empty()),
+            cache_ttl_secs: std::env::var("UBL_PUBLIC_READ_CACHE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.cache_ttl_secs),
+            cache_max_entries: std::env::var("UBL_PUBLIC_READ_CACHE_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.cache_max_entries),
+        }
+    }
+}
+
+/// Responses by URL, each until its expiry
+pub struct ResponseCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self { ttl, max_entries, entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn get(&self, key: &str, now: Instant) -> Option<Value> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key).filter(|(expires, _)| *expires > now).map(|(_, v)| v.clone())
+    }
+
+    pub fn put(&self, key: String, value: Value, now: Instant) {
+        if self.ttl.is_zero() || self.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, (expires, _)| *expires > now);
+            if entries.len() >= self.max_entries {
+                // Still full of live responses: drop the one expiring first
+                if let Some(oldest) = entries.iter().min_by_key(|(_, (expires, _))| *expires).map(|(k, _)| k.clone()) {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (now + self.ttl, value));
+    }
+}
+
+#[derive(Clone)]
+pub struct GatewayState {
+    pub pool: PgPool,
+    pub cache: Arc<ResponseCache>,
+}
+
+/// Token of the current request, after the quota was counted
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PublicToken {
+    pub token_id: String,
+    pub tenant_id: String,
+    pub tier: String,
+    pub requests_per_day: i64,
+    pub containers: Vec<String>,
+    #[sqlx(default)]
+    pub requests_today: i64,
+}
+
+impl PublicToken {
+    fn allows(&self, container_id: &str) -> bool {
+        self.containers.iter().any(|c| c == container_id)
+    }
+}
+
+/// Seconds until the next UTC midnight, when quotas reset
+fn secs_until_reset(now_ms: i64) -> i64 {
+    ((DAY_MS - now_ms.rem_euclid(DAY_MS)) + 999) / 1000
+}
+
+/// Router of the gateway; nothing else is ever mounted on its listener
+pub fn router(state: GatewayState) -> Router {
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET])
+        .allow_headers([header::AUTHORIZATION]);
+
+    Router::new()
+        .route("/public/v1/usage", get(usage))
+        .route("/public/v1/containers/:container_id/state", get(container_state))
+        .route("/public/v1/containers/:container_id/entries", get(list_entries))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+        .layer(middleware::map_response(crate::contracts::stamp_api_version))
+        .layer(cors)
+}
+
+/// Serve the gateway on `config.addr` (runs forever); returns at once when off
+pub async fn serve(pool: PgPool, config: PublicReadConfig) {
+    let Some(addr) = config.addr.clone() else {
+        return;
+    };
+    let state = GatewayState {
+        pool,
+        cache: Arc::new(ResponseCache::new(
+            Duration::from_secs(config.cache_ttl_secs),
+            config.cache_max_entries,
+        )),
+    };
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Public read gateway could not bind {}: {}", addr, e);
+            return;
+        }
+    };
+    info!("🔓 Public read gateway on http://{} (cache {}s)", addr, config.cache_ttl_secs);
+    if let Err(e) = axum::serve(listener, router(state)).await {
+        error!("Public read gateway stopped: {}", e);
+    }
+}
+
+/// Resolve the bearer token and count the request against its quota
+async fn authorize(State(state): State<GatewayState>, mut req: Request<Body>, next: Next) -> Response {
+    let Some(token) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| t.starts_with(super::TOKEN_PREFIX))
+    else {
+        PUBLIC_READS.with_label_values(&["none", "unauthorized"]).inc();
+        return ApiError::new(ErrorCode::Unauthorized, "public read token required").into_response();
+    };
+
+    let found = sqlx::query_as::<_, PublicToken>(
+        r#"
+        SELECT token_id, tenant_id, tier, requests_per_day, containers
+        FROM public_read_tokens
+        WHERE token_hash = $1 AND revoked_at_ms IS NULL
+        "#,
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&state.pool)
+    .await;
+    let mut token = match found {
+        Ok(Some(token)) => token,
+        Ok(None) => {
+            PUBLIC_READS.with_label_values(&["none", "unauthorized"]).inc();
+            return ApiError::new(ErrorCode::Unauthorized, "unknown or revoked public read token").into_response();
+        }
+        Err(e) => return ApiError::new(ErrorCode::DatabaseError, e.to_string()).into_response(),
+    };
+
+    let counted = sqlx::query_scalar::<_, i64>(
+        r#"
+        INSERT INTO public_read_usage (token_id, day, requests)
+        VALUES ($1, (now() AT TIME ZONE 'UTC')::DATE, 1)
+        ON CONFLICT (token_id, day) DO UPDATE SET requests = public_read_usage.requests + 1
+        RETURNING requests
+        "#,
+    )
+    .bind(&token.token_id)
+    .fetch_one(&state.pool)
+    .await;
+    token.requests_today = match counted {
+        Ok(n) => n,
+        Err(e) => return ApiError::new(ErrorCode::DatabaseError, e.to_string()).into_response(),
+    };
+    if token.requests_today > token.requests_per_day {
+        PUBLIC_READS.with_label_values(&[&token.tier, "over_quota"]).inc();
+        let mut response = ApiError::new(
+            ErrorCode::RateLimited,
+            format!("daily quota of {} requests used", token.requests_per_day),
+        )
+        .into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(secs_until_reset(now_ms())));
+        return response;
+    }
+
+    req.extensions_mut().insert(token);
+    next.run(req).await
+}
+
+/// Serve `key` from cache or compute it; checks the token's containers first
+async fn cached<F, Fut>(
+    state: &GatewayState,
+    token: &PublicToken,
+    container_id: Option<&str>,
+    key: String,
+    compute: F,
+) -> Result<Response, ApiError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<Value, ApiError>>,
+{
+    if let Some(container_id) = container_id {
+        if !token.allows(container_id) {
+            PUBLIC_READS.with_label_values(&[&token.tier, "forbidden"]).inc();
+            return Err(ApiError::new(
+                ErrorCode::Forbidden,
+                format!("token does not cover {}", container_id),
+            ));
+        }
+    }
+
+    let (body, outcome) = match state.cache.get(&key, Instant::now()) {
+        Some(body) => (body, "cached"),
+        None => {
+            let body = compute().await?;
+            state.cache.put(key, body.clone(), Instant::now());
+            (body, "ok")
+        }
+    };
+    PUBLIC_READS.with_label_values(&[&token.tier, outcome]).inc();
+
+    let mut response = Json(body).into_response();
+    if let Ok(value) = HeaderValue::from_str(&format!("public, max-age={}", state.cache.ttl.as_secs())) {
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
+    Ok(response)
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct UsageResponse {
+    pub token_id: String,
+    pub tenant_id: String,
+    pub tier: String,
+    pub requests_per_day: i64,
+    /// Including this request
+    pub requests_today: i64,
+    pub resets_in_secs: i64,
+    pub containers: Vec<String>,
+}
+
+/// GET /public/v1/usage — never cached
+async fn usage(Extension(token): Extension<PublicToken>) -> Json<UsageResponse> {
+    PUBLIC_READS.with_label_values(&[&token.tier, "ok"]).inc();
+    Json(UsageResponse {
+        token_id: token.token_id,
+        tenant_id: token.tenant_id,
+        tier: token.tier,
+        requests_per_day: token.requests_per_day,
+        requests_today: token.requests_today,
+        resets_in_secs: secs_until_reset(now_ms()),
+        containers: token.containers,
+    })
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PublicState {
+    pub container_id: String,
+    pub sequence: i64,
+    pub last_hash: String,
+    pub entry_count: i64,
+    pub last_ts_unix_ms: Option<i64>,
+}
+
+/// GET /public/v1/containers/:container_id/state
+async fn container_state(
+    State(state): State<GatewayState>,
+    Extension(token): Extension<PublicToken>,
+    Path(container_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let key = format!("state:{}", container_id);
+    cached(&state, &token, Some(&container_id), key, || async {
+        let head = sqlx::query_as::<_, PublicState>(
+            r#"
+            SELECT $1::TEXT AS container_id,
+                   COALESCE(MAX(sequence), 0) AS sequence,
+                   COALESCE((SELECT entry_hash FROM ledger_entry
+                             WHERE container_id = $1 ORDER BY sequence DESC LIMIT 1),
+                            $2) AS last_hash,
+                   COUNT(*) AS entry_count,
+                   MAX(ts_unix_ms) AS last_ts_unix_ms
+            FROM ledger_entry
+            WHERE container_id = $1
+            "#,
+        )
+        .bind(&container_id)
+        .bind(crate::db::GENESIS_PREVIOUS_HASH)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+        serde_json::to_value(head).map_err(|e| ApiError::new(ErrorCode::Internal, e.to_string()))
+    })
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PublicEntriesQuery {
+    /// Entries with sequence > after
+    pub after: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PublicEntry {
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+    #[sqlx(flatten)]
+    pub hlc: Hlc,
+}
+
+/// GET /public/v1/containers/:container_id/entries
+async fn list_entries(
+    State(state): State<GatewayState>,
+    Extension(token): Extension<PublicToken>,
+    Path(container_id): Path<String>,
+    Query(query): Query<PublicEntriesQuery>,
+) -> Result<Response, ApiError> {
+    let after = query.after.unwrap_or(0).max(0);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let key = format!("entries:{}:{}:{}", container_id, after, limit);
+    cached(&state, &token, Some(&container_id), key, || async {
+        let entries = sqlx::query_as::<_, PublicEntry>(
+            r#"
+            SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms,
+                   COALESCE(hlc_wall_ms, ts_unix_ms) AS hlc_wall_ms, COALESCE(hlc_logical, 0) AS hlc_logical
+            FROM ledger_entry
+            WHERE container_id = $1 AND sequence > $2
+            ORDER BY sequence ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(&container_id)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+        let next_after = entries.last().map(|e| e.sequence);
+        Ok(serde_json::json!({
+            "container_id": container_id,
+            "entries": entries,
+            "next_after": next_after,
+        }))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_expires_and_stays_bounded() {
+        let cache = ResponseCache::new(Duration::from_secs(30), 2);
+        let t0 = Instant::now();
+        cache.put("a".into(), serde_json::json!(1), t0);
+        cache.put("b".into(), serde_json::json!(2), t0 + Duration::from_secs(1));
+        assert_eq!(cache.get("a", t0 + Duration::from_secs(29)), Some(serde_json::json!(1)));
+        assert_eq!(cache.get("a", t0 + Duration::from_secs(30)), None);
+
+        // Full: the entry expiring first makes room
+        cache.put("c".into(), serde_json::json!(3), t0 + Duration::from_secs(2));
+        assert_eq!(cache.get("a", t0 + Duration::from_secs(2)), None);
+        assert!(cache.get("b", t0 + Duration::from_secs(2)).is_some());
+        assert!(cache.get("c", t0 + Duration::from_secs(2)).is_some());
+
+        let off = ResponseCache::new(Duration::ZERO, 10);
+        off.put("a".into(), serde_json::json!(1), t0);
+        assert_eq!(off.get("a", t0), None);
+    }
+
+    #[test]
+    fn test_quota_resets_at_utc_midnight() {
+        assert_eq!(secs_until_reset(0), 86_400);
+        assert_eq!(secs_until_reset(DAY_MS - 1), 1);
+        assert_eq!(secs_until_reset(3 * DAY_MS + 3_600_000), 82_800);
+    }
+
+    #[test]
+    fn test_gateway_mounts_only_public_reads() {
+        let source = include_str!("gateway.rs");
+        let source = source.split("\n#[cfg(test)]\nmod tests").next().unwrap();
+        let paths: Vec<&str> = source
+            .split(".route(\"")
+            .skip(1)
+            .map(|s| s.split('"').next().unwrap())
+            .collect();
+        assert_eq!(paths.len(), 3);
+        for path in paths {
+            assert!(path.starts_with("/public/v1/"), "{}", path);
+            assert!(
+                !crate::route_auth::ROUTES.iter().any(|(_, p, _)| *p == path),
+                "{} is also on the main listener",
+                path
+            );
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Public Read API — tokens for read-only ledger views
+//!
+//! Tenants publishing transparency reports expose a container's head and
+//! entry headers to anyone holding a public read token. Tokens are issued
+//! here, on the main listener, by an admin; they are served by the gateway
+//! (see [`gateway`]) on its own listener, which mounts nothing else.
+//!
+//! A token names its tenant, the containers it may read and a [`Tier`]
+//! fixing its requests per UTC day. Only its BLAKE3 hash is stored; the
+//! token is returned once, on creation.
+//!
+//! Endpoints (step-up session):
+//! - POST   /v1/admin/public-tokens            → Issue a token (shown once)
+//! - GET    /v1/admin/public-tokens?tenant_id= → List, with today's usage
+//! - DELETE /v1/admin/public-tokens/:token_id  → Revoke
+
+pub mod gateway;
+
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    routing::{delete, get},
+    Extension, Json, Router,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::info;
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::auth::session::Session;
+use crate::id_routes::IdState;
+use crate::timestamps::now_ms;
+
+/// Prefix of every public read token
+pub const TOKEN_PREFIX: &str = "ubl_pub_";
+/// Containers a single token may name
+const MAX_CONTAINERS: usize = 50;
+
+/// Quota tier of a public read token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    /// Occasional reads (a published report)
+    Basic,
+    /// Dashboards polling a few containers
+    Standard,
+    /// Partners mirroring reports
+    Partner,
+}
+
+impl Tier {
+    /// Requests allowed per UTC day
+    pub fn requests_per_day(self) -> i64 {
+        match self {
+            Tier::Basic => 1_000,
+            Tier::Standard => 50_000,
+            Tier::Partner => 1_000_000,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tier::Basic => "basic",
+            Tier::Standard => "standard",
+            Tier::Partner => "partner",
+        }
+    }
+}
+
+impl std::str::FromStr for Tier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "basic" => Ok(Tier::Basic),
+            "standard" => Ok(Tier::Standard),
+            "partner" => Ok(Tier::Partner),
+            other => Err(format!("unknown tier {} (basic, standard, partner)", other)),
+        }
+    }
+}
+
+/// Stored form of a token
+pub fn hash_token(token: &str) -> String {
+    blake3::hash(token.as_bytes()).to_hex().to_string()
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(bytes))
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTokenRequest {
+    pub tenant_id: String,
+    pub label: String,
+    pub tier: Tier,
+    pub containers: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateTokenResponse {
+    pub token_id: String,
+    /// The token itself; not retrievable later
+    pub token: String,
+    pub tier: Tier,
+    pub requests_per_day: i64,
+    pub containers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListTokensQuery {
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct TokenView {
+    pub token_id: String,
+    pub tenant_id: String,
+    pub label: String,
+    pub tier: String,
+    pub requests_per_day: i64,
+    pub containers: Vec<String>,
+    pub created_by: String,
+    pub created_at_ms: i64,
+    pub revoked_at_ms: Option<i64>,
+    /// Requests made today (UTC)
+    pub requests_today: i64,
+}
+
+pub fn routes(pool: PgPool, id_state: IdState) -> Router {
+    Router::new()
+        .route("/v1/admin/public-tokens", get(list_tokens).post(create_token))
+        .route("/v1/admin/public-tokens/:token_id", delete(revoke_token))
+        .route_layer(middleware::from_fn_with_state(id_state, crate::auth::require_stepup::require_stepup))
+        .with_state(pool)
+}
+
+/// POST /v1/admin/public-tokens
+async fn create_token(
+    State(pool): State<PgPool>,
+    Extension(session): Extension<Session>,
+    Json(req): Json<CreateTokenRequest>,
+) -> Result<Json<CreateTokenResponse>, ApiError> {
+    let mut containers = req.containers;
+    containers.sort();
+    containers.dedup();
+    if req.tenant_id.is_empty() || req.label.is_empty() {
+        return Err(ApiError::new(ErrorCode::BadRequest, "tenant_id and label are required"));
+    }
+    if containers.is_empty() || containers.len() > MAX_CONTAINERS || containers.iter().any(String::is_empty) {
+        return Err(ApiError::new(
+            ErrorCode::BadRequest,
+            format!("a token reads between 1 and {} containers", MAX_CONTAINERS),
+        ));
+    }
+
+    let token_id = format!("pub_{}", uuid::Uuid::new_v4().simple());
+    let token = new_token();
+    sqlx::query(
+        r#"
+        INSERT INTO public_read_tokens
+          (token_id, token_hash, tenant_id, label, tier, requests_per_day, containers, created_by, created_at_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(&token_id)
+    .bind(hash_token(&token))
+    .bind(&req.tenant_id)
+    .bind(&req.label)
+    .bind(req.tier.as_str())
+    .bind(req.tier.requests_per_day())
+    .bind(&containers)
+    .bind(&session.sid)
+    .bind(now_ms())
+    .execute(&pool)
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+
+    info!(
+        "🔓 Public read token {} ({}) issued for tenant {} by {}: {:?}",
+        token_id,
+        req.tier.as_str(),
+        req.tenant_id,
+        session.sid,
+        containers
+    );
+    Ok(Json(CreateTokenResponse {
+        token_id,
+        token,
+        tier: req.tier,
+        requests_per_day: req.tier.requests_per_day(),
+        containers,
+    }))
+}
+
+/// GET /v1/admin/public-tokens
+async fn list_tokens(
+    State(pool): State<PgPool>,
+    Query(query): Query<ListTokensQuery>,
+) -> Result<Json<Vec<TokenView>>, ApiError> {
+    let rows = sqlx::query_as::<_, TokenView>(
+        r#"
+        SELECT t.token_id, t.tenant_id, t.label, t.tier, t.requests_per_day, t.containers,
+               t.created_by, t.created_at_ms, t.revoked_at_ms,
+               COALESCE(u.requests, 0) AS requests_today
+        FROM public_read_tokens t
+        LEFT JOIN public_read_usage u
+          ON u.token_id = t.token_id AND u.day = (now() AT TIME ZONE 'UTC')::DATE
+        WHERE ($1::TEXT IS NULL OR t.tenant_id = $1)
+        ORDER BY t.created_at_ms DESC
+        "#,
+    )
+    .bind(&query.tenant_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?;
+
+    Ok(Json(rows))
+}
+
+/// DELETE /v1/admin/public-tokens/:token_id
+async fn revoke_token(
+    State(pool): State<PgPool>,
+    Extension(session): Extension<Session>,
+    Path(token_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let revoked = sqlx::query(
+        "UPDATE public_read_tokens SET revoked_at_ms = $2 WHERE token_id = $1 AND revoked_at_ms IS NULL",
+    )
+    .bind(&token_id)
+    .bind(now_ms())
+    .execute(&pool)
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::DatabaseError, e.to_string()))?
+    .rows_affected();
+    if revoked == 0 {
+        return Err(ApiError::new(ErrorCode::NotFound, format!("No active public token {}", token_id)));
+    }
+
+    info!("🔒 Public read token {} revoked by {}", token_id, session.sid);
+    Ok(Json(serde_json::json!({ "token_id": token_id, "revoked": true })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers_round_trip_and_grow() {
+        for tier in [Tier::Basic, Tier::Standard, Tier::Partner] {
+            assert_eq!(tier.as_str().parse::<Tier>().unwrap(), tier);
+        }
+        assert!(Tier::Basic.requests_per_day() < Tier::Standard.requests_per_day());
+        assert!(Tier::Standard.requests_per_day() < Tier::Partner.requests_per_day());
+        assert!("gold".parse::<Tier>().is_err());
+    }
+
+    #[test]
+    fn test_tokens_are_random_and_stored_hashed() {
+        let (a, b) = (new_token(), new_token());
+        assert_ne!(a, b);
+        assert!(a.starts_with(TOKEN_PREFIX));
+        assert_eq!(a.len(), TOKEN_PREFIX.len() + 64);
+        assert_eq!(hash_token(&a), hash_token(&a));
+        assert!(!hash_token(&a).contains(&a[TOKEN_PREFIX.len()..]));
+    }
+}
@@ -0,0 +1,187 @@
+//! Query parameters shared by the read routes
+//!
+//! Projection, registry and timeline handlers used to read `limit`, page
+//! positions and filters each their own way: some clamped `limit`, some only
+//! capped it (a negative one reached Postgres as a 500), `?entity_id=`
+//! filtered on the empty string. They parse through these types instead:
+//!
+//! - [`Limit`]: each route's default and maximum page size; anything asked
+//!   for is clamped to `1..=max`
+//! - [`Cursor`]: opaque keyset position (sort key plus tie-breaker) a route
+//!   hands out as `next_cursor` and takes back as `?cursor=`; a malformed
+//!   one is a 400
+//! - [`non_empty`]: filters where an empty value means "unset"
+//! - [`one_of`]: enumerated filters (`?status=`), unknown values are a 400
+
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Page size of a route: `default` when not asked for, never above `max`
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub default: i64,
+    pub max: i64,
+}
+
+impl Limit {
+    pub const fn new(default: i64, max: i64) -> Self {
+        Self { default, max }
+    }
+
+    /// The page size to query with
+    pub fn resolve(&self, requested: Option<i64>) -> i64 {
+        requested.unwrap_or(self.default).clamp(1, self.max)
+    }
+}
+
+/// `?limit=&before_seq=`, the plain page query of most list routes
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<i64>,
+    pub before_seq: Option<i64>,
+}
+
+/// Keyset position: rows strictly before `(key, tiebreak)` in the route's
+/// descending order. Serialized as an opaque URL-safe token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: i64,
+    pub tiebreak: i64,
+}
+
+/// Version tag inside the token, so the encoding can change later
+const CURSOR_TAG: &str = "c1";
+
+impl Cursor {
+    pub fn new(key: i64, tiebreak: i64) -> Self {
+        Self { key, tiebreak }
+    }
+
+    /// Cursor of a route paging by `key` alone (the old `before_*` params)
+    pub fn before(key: i64) -> Self {
+        Self { key, tiebreak: i64::MIN }
+    }
+
+    /// Where a page starts: `cursor`, else the route's older `before_*`
+    /// param, else the newest row
+    pub fn resolve(cursor: Option<Cursor>, before: Option<i64>) -> Self {
+        cursor.unwrap_or_else(|| Self::before(before.unwrap_or(i64::MAX)))
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}:{}", CURSOR_TAG, self.key, self.tiebreak))
+    }
+
+    pub fn decode(token: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(token).ok()?).ok()?;
+        let mut parts = raw.split(':');
+        if parts.next()? != CURSOR_TAG {
+            return None;
+        }
+        let key = parts.next()?.parse().ok()?;
+        let tiebreak = parts.next()?.parse().ok()?;
+        parts.next().is_none().then_some(Self { key, tiebreak })
+    }
+
+    /// Where the next page starts: after the last row, if the page was full
+    pub fn next<T>(rows: &[T], limit: i64, position: impl Fn(&T) -> Cursor) -> Option<Self> {
+        match rows.last() {
+            Some(last) if rows.len() as i64 >= limit => Some(position(last)),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        Cursor::decode(&token).ok_or_else(|| serde::de::Error::custom("invalid cursor"))
+    }
+}
+
+/// `deserialize_with` for optional filters: `?x=` is the same as no `x`
+pub fn non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.filter(|v| !v.trim().is_empty()))
+}
+
+/// An enumerated filter: `value` if it is one of `allowed`, else `default`
+/// when unset; anything else is a 400 naming the allowed values
+pub fn one_of<'a>(
+    name: &str,
+    value: Option<&'a str>,
+    allowed: &[&'a str],
+    default: &'a str,
+) -> Result<&'a str, (StatusCode, String)> {
+    match value.filter(|v| !v.is_empty()) {
+        None => Ok(default),
+        Some(v) => allowed.iter().copied().find(|a| *a == v).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, format!("{} must be one of {}", name, allowed.join(", ")))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_is_clamped() {
+        let limit = Limit::new(50, 100);
+        assert_eq!(limit.resolve(None), 50);
+        assert_eq!(limit.resolve(Some(7)), 7);
+        assert_eq!(limit.resolve(Some(1000)), 100);
+        assert_eq!(limit.resolve(Some(0)), 1);
+        assert_eq!(limit.resolve(Some(-5)), 1);
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor::new(1_700_000_000_000, -3);
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("c0:1:2")), None);
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode("c1:1:2:3")), None);
+        assert_eq!(Cursor::resolve(Some(cursor), Some(9)), cursor);
+        assert_eq!(Cursor::resolve(None, Some(9)), Cursor::before(9));
+        assert_eq!(Cursor::resolve(None, None).key, i64::MAX);
+
+        let parsed: Cursor = serde_json::from_value(serde_json::json!(cursor.encode())).unwrap();
+        assert_eq!(parsed, cursor);
+        assert!(serde_json::from_value::<Cursor>(serde_json::json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_next_cursor_only_after_full_page() {
+        let rows = [(5, 0), (4, 1)];
+        let at = |r: &(i64, i64)| Cursor::new(r.0, r.1);
+        assert_eq!(Cursor::next(&rows, 2, at), Some(Cursor::new(4, 1)));
+        assert_eq!(Cursor::next(&rows, 3, at), None);
+        assert_eq!(Cursor::next(&rows[..0], 2, at), None);
+    }
+
+    #[test]
+    fn test_filters() {
+        #[derive(Deserialize)]
+        struct Q {
+            #[serde(default, deserialize_with = "non_empty")]
+            entity_id: Option<String>,
+        }
+        let q: Q = serde_json::from_value(serde_json::json!({ "entity_id": "" })).unwrap();
+        assert_eq!(q.entity_id, None);
+        let q: Q = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(q.entity_id, None);
+
+        let allowed = ["pending", "sent"];
+        assert_eq!(one_of("status", None, &allowed, "pending").unwrap(), "pending");
+        assert_eq!(one_of("status", Some("sent"), &allowed, "pending").unwrap(), "sent");
+        assert_eq!(one_of("status", Some("bogus"), &allowed, "pending").unwrap_err().0, StatusCode::BAD_REQUEST);
+    }
+}
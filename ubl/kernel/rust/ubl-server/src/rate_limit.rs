@@ -108,6 +108,31 @@ impl RateLimiter {
     }
 }
 
+/// Buckets past their start and lockouts past their last failure by more
+/// than the TTL; a flush lifts every lockout
+impl crate::maintenance::Maintained for RateLimiter {
+    fn entries(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.buckets.len() + state.failures.len()
+    }
+
+    fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let before = state.buckets.len() + state.failures.len();
+        state.buckets.retain(|_, (_, start)| now_ms - crate::timestamps::from_datetime(*start) <= ttl_ms);
+        state.failures.retain(|_, f| now_ms - f.last_fail_epoch * 1000 <= ttl_ms);
+        before - state.buckets.len() - state.failures.len()
+    }
+
+    fn flush(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let n = state.buckets.len() + state.failures.len();
+        state.buckets.clear();
+        state.failures.clear();
+        n
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +149,20 @@ mod tests {
         // Block 4th request
         assert!(limiter.check("user1", 3, 60).is_err());
     }
+
+    #[test]
+    fn test_evict_idle_lockouts() {
+        use crate::maintenance::Maintained;
+        let limiter = RateLimiter::new();
+        limiter.on_fail("old");
+        limiter.on_fail("recent");
+        limiter.state.lock().unwrap().failures.get_mut("old").unwrap().last_fail_epoch -= 7200;
+        assert!(limiter.check("user1", 3, 60).is_ok());
+
+        let now_ms = crate::timestamps::now_ms();
+        assert_eq!(limiter.evict_idle(now_ms, 3_600_000), 1);
+        assert_eq!(limiter.get_failures("old"), 0);
+        assert_eq!(limiter.get_failures("recent"), 1);
+        assert_eq!(limiter.flush(), 2);
+    }
 }
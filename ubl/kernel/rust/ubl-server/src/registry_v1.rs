@@ -14,6 +14,8 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 
+use crate::query_params::{non_empty, Limit};
+
 /// State for registry routes
 #[derive(Clone)]
 pub struct RegistryState {
@@ -34,9 +36,14 @@ pub fn routes(pool: PgPool) -> Router {
 #[derive(Debug, Deserialize)]
 pub struct ListProjectsQuery {
     pub tenant_id: String,
+    #[serde(default, deserialize_with = "non_empty")]
     pub q: Option<String>,
+    pub limit: Option<i64>,
 }
 
+/// Page size of the project list (see `crate::query_params`)
+const PROJECTS: Limit = Limit::new(100, 200);
+
 #[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct ProjectRow {
     pub tenant_id: String,
@@ -91,6 +98,7 @@ async fn list_projects(
     Query(params): Query<ListProjectsQuery>,
 ) -> impl IntoResponse {
     let pool = &state.pool;
+    let limit = PROJECTS.resolve(params.limit);
     
     let projects: Vec<ProjectRow> = if let Some(q) = &params.q {
         let pattern = format!("%{}%", q);
@@ -100,11 +108,12 @@ async fn list_projects(
             FROM registry_projects
             WHERE tenant_id = $1 AND (name ILIKE $2 OR project_id ILIKE $2)
             ORDER BY last_activity DESC
-            LIMIT 100
+            LIMIT $3
             "#
         )
         .bind(&params.tenant_id)
         .bind(&pattern)
+        .bind(limit)
         .fetch_all(pool)
         .await
         .unwrap_or_default()
@@ -115,10 +124,11 @@ async fn list_projects(
             FROM registry_projects
             WHERE tenant_id = $1
             ORDER BY last_activity DESC
-            LIMIT 100
+            LIMIT $2
             "#
         )
         .bind(&params.tenant_id)
+        .bind(limit)
         .fetch_all(pool)
         .await
         .unwrap_or_default()
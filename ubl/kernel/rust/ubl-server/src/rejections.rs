@@ -0,0 +1,352 @@
+//! Commit Rejections — short-lived audit of refused commits
+//!
+//! A refused commit otherwise leaves only a log line and an error response,
+//! which is gone by the time a client's bug report arrives. With
+//! `UBL_REJECTION_AUDIT=1`, `commit_link` records every refusal (link
+//! header, atom, catalog code and message, the validation stage that refused
+//! it, and how long admission took) in `commit_rejections`.
+//!
+//! Retention is deliberately short: rows are purged after
+//! `UBL_REJECTION_RETENTION_SECS` (default 72h), atoms are cleared after
+//! `UBL_REJECTION_ATOM_RETENTION_SECS` (default 1h; 0 keeps none), and atoms
+//! of encrypted containers are never kept.
+//!
+//! Endpoints (step-up session):
+//! - GET /v1/admin/rejections?container_id=&author_pubkey=&stage=&code=&since_ms=&until_ms=&before=&limit=
+//!   → newest first; pass the last `rejection_id` as `before` for the next page
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Query, State},
+    middleware,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use tracing::{info, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::db::LinkDraft;
+use crate::id_routes::IdState;
+use crate::timestamps::now_ms;
+
+/// Rejections listed per page by default, and at most
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 500;
+
+/// Configuration of the rejection audit
+#[derive(Debug, Clone)]
+pub struct RejectionAuditConfig {
+    /// Record refused commits at all
+    pub enabled: bool,
+    /// Rows older than this are deleted
+    pub retention_secs: u64,
+    /// Atoms older than this are cleared (0: never stored)
+    pub atom_retention_secs: u64,
+    /// How often the purge runs
+    pub purge_interval_secs: u64,
+}
+
+impl Default for RejectionAuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_secs: 72 * 3600,
+            atom_retention_secs: 3600,
+            purge_interval_secs: 300,
+        }
+    }
+}
+
+impl RejectionAuditConfig {
+    /// Defaults overridden by `UBL_REJECTION_AUDIT`, `UBL_REJECTION_RETENTION_SECS`,
+    /// `UBL_REJECTION_ATOM_RETENTION_SECS` and `UBL_REJECTION_PURGE_INTERVAL_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let get = |key: &str, default: u64| std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+        Self {
+            enabled: std::env::var("UBL_REJECTION_AUDIT")
+                .map(|s| s == "true" || s == "1")
+                .unwrap_or(defaults.enabled),
+            retention_secs: get("UBL_REJECTION_RETENTION_SECS", defaults.retention_secs),
+            atom_retention_secs: get("UBL_REJECTION_ATOM_RETENTION_SECS", defaults.atom_retention_secs),
+            purge_interval_secs: get("UBL_REJECTION_PURGE_INTERVAL_SECS", defaults.purge_interval_secs).max(1),
+        }
+    }
+}
+
+/// Process-wide audit configuration, read from the environment once
+pub fn config() -> &'static RejectionAuditConfig {
+    static CONFIG: OnceLock<RejectionAuditConfig> = OnceLock::new();
+    CONFIG.get_or_init(RejectionAuditConfig::from_env)
+}
+
+/// Validation stage that refused a commit, from its catalog code
+pub fn stage_of(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::Forbidden => "gate",
+        ErrorCode::InvalidVersion | ErrorCode::InvalidSignature => "membrane",
+        ErrorCode::PolicyViolation
+        | ErrorCode::PolicyDenied
+        | ErrorCode::PhysicsViolation
+        | ErrorCode::AtomLimitExceeded
+        | ErrorCode::InvalidAtom => "policy",
+        ErrorCode::PactViolation => "pact",
+        ErrorCode::UnauthorizedEvolution | ErrorCode::InvalidEvolution => "evolution",
+        ErrorCode::RealityDrift
+        | ErrorCode::SequenceMismatch
+        | ErrorCode::InvalidTarget
+        | ErrorCode::SerializationConflict => "tangency",
+        ErrorCode::DatabaseError => "storage",
+        _ => "other",
+    }
+}
+
+/// Count a refused commit in `ubl_commit_rejections_total`
+pub fn count(container_id: &str, code: ErrorCode) {
+    crate::metrics::COMMIT_REJECTIONS
+        .with_label_values(&[container_id, stage_of(code), code.as_str()])
+        .inc();
+}
+
+/// A commit attempt, captured before admission in case it is refused
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    container_id: String,
+    author_pubkey: String,
+    actor: String,
+    intent_class: String,
+    physics_delta: String,
+    expected_sequence: i64,
+    previous_hash: String,
+    atom_hash: String,
+    atom: Option<Value>,
+    started: Instant,
+}
+
+impl Attempt {
+    /// None when the audit is off
+    pub fn capture(link: &LinkDraft, actor: &str) -> Option<Self> {
+        let config = config();
+        if !config.enabled {
+            return None;
+        }
+        let keep_atom = config.atom_retention_secs > 0 && !crate::atom_crypto::is_encrypted(&link.container_id);
+        Some(Self {
+            container_id: link.container_id.clone(),
+            author_pubkey: link.author_pubkey.clone(),
+            actor: actor.to_string(),
+            intent_class: link.intent_class.clone(),
+            physics_delta: link.physics_delta.clone(),
+            expected_sequence: link.expected_sequence,
+            previous_hash: link.previous_hash.clone(),
+            atom_hash: link.atom_hash.clone(),
+            atom: link.atom.clone().filter(|_| keep_atom),
+            started: Instant::now(),
+        })
+    }
+
+    /// Record the refusal in the background
+    pub fn refused(self, pool: PgPool, error: &ApiError) {
+        let elapsed_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        let (code, message) = (error.code, error.message.clone());
+        tokio::spawn(async move {
+            if let Err(e) = self.insert(&pool, code, &message, elapsed_ms).await {
+                warn!("Failed to record rejected commit on {}: {}", self.container_id, e);
+            }
+        });
+    }
+
+    async fn insert(&self, pool: &PgPool, code: ErrorCode, message: &str, elapsed_ms: f64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO commit_rejections (
+                container_id, author_pubkey, actor, intent_class, physics_delta, expected_sequence,
+                previous_hash, atom_hash, atom, stage, code, message, elapsed_ms, rejected_at_ms
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(&self.container_id)
+        .bind(&self.author_pubkey)
+        .bind(&self.actor)
+        .bind(&self.intent_class)
+        .bind(&self.physics_delta)
+        .bind(self.expected_sequence)
+        .bind(&self.previous_hash)
+        .bind(&self.atom_hash)
+        .bind(&self.atom)
+        .bind(stage_of(code))
+        .bind(code.as_str())
+        .bind(message)
+        .bind(elapsed_ms)
+        .bind(now_ms())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Deletes expired rejections and clears expired atoms
+pub struct RejectionPurger {
+    pool: PgPool,
+    config: RejectionAuditConfig,
+}
+
+impl RejectionPurger {
+    /// None when the audit is off
+    pub fn new(pool: PgPool, config: RejectionAuditConfig) -> Option<Self> {
+        config.enabled.then_some(Self { pool, config })
+    }
+
+    /// Start the purge loop (runs forever)
+    pub async fn run(self) {
+        info!(
+            "🧾 Rejection audit on - keeping rows {}s, atoms {}s",
+            self.config.retention_secs, self.config.atom_retention_secs
+        );
+        let mut tick = tokio::time::interval(Duration::from_secs(self.config.purge_interval_secs));
+        loop {
+            tick.tick().await;
+            match self.purge(now_ms()).await {
+                Ok((0, _)) => {}
+                Ok((deleted, _)) => info!("🧾 Purged {} expired commit rejections", deleted),
+                Err(e) => warn!("Commit rejection purge failed: {}", e),
+            }
+        }
+    }
+
+    /// (rows deleted, atoms cleared)
+    async fn purge(&self, now_ms: i64) -> Result<(u64, u64), sqlx::Error> {
+        let deleted = sqlx::query("DELETE FROM commit_rejections WHERE rejected_at_ms < $1")
+            .bind(now_ms - (self.config.retention_secs as i64) * 1000)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        let cleared = sqlx::query("UPDATE commit_rejections SET atom = NULL WHERE atom IS NOT NULL AND rejected_at_ms < $1")
+            .bind(now_ms - (self.config.atom_retention_secs as i64) * 1000)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok((deleted, cleared))
+    }
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ListRejectionsQuery {
+    pub container_id: Option<String>,
+    pub author_pubkey: Option<String>,
+    pub stage: Option<String>,
+    pub code: Option<String>,
+    pub since_ms: Option<i64>,
+    pub until_ms: Option<i64>,
+    /// Only rejections with a smaller id (paging)
+    pub before: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RejectionView {
+    pub rejection_id: i64,
+    pub container_id: String,
+    pub author_pubkey: String,
+    pub actor: String,
+    pub intent_class: String,
+    pub physics_delta: String,
+    pub expected_sequence: i64,
+    pub previous_hash: String,
+    pub atom_hash: String,
+    /// None once past the atom retention window
+    pub atom: Option<Value>,
+    pub stage: String,
+    pub code: String,
+    pub message: String,
+    pub elapsed_ms: f64,
+    pub rejected_at_ms: i64,
+}
+
+pub fn routes(pool: PgPool, id_state: IdState) -> Router {
+    Router::new()
+        .route("/v1/admin/rejections", get(list_rejections))
+        .route_layer(middleware::from_fn_with_state(id_state, crate::auth::require_stepup::require_stepup))
+        .with_state(pool)
+}
+
+/// GET /v1/admin/rejections
+async fn list_rejections(
+    State(pool): State<PgPool>,
+    Query(query): Query<ListRejectionsQuery>,
+) -> Result<Json<Vec<RejectionView>>, ApiError> {
+    if let Some(code) = query.code.as_deref() {
+        if code.parse::<ErrorCode>().is_err() {
+            return Err(ApiError::new(ErrorCode::BadRequest, format!("unknown error code {}", code)));
+        }
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let rows = sqlx::query_as::<_, RejectionView>(
+        r#"
+        SELECT rejection_id, container_id, author_pubkey, actor, intent_class, physics_delta,
+               expected_sequence, previous_hash, atom_hash, atom, stage, code, message,
+               elapsed_ms, rejected_at_ms
+        FROM commit_rejections
+        WHERE ($1::TEXT IS NULL OR container_id = $1)
+          AND ($2::TEXT IS NULL OR author_pubkey = $2)
+          AND ($3::TEXT IS NULL OR stage = $3)
+          AND ($4::TEXT IS NULL OR code = $4)
+          AND ($5::BIGINT IS NULL OR rejected_at_ms >= $5)
+          AND ($6::BIGINT IS NULL OR rejected_at_ms < $6)
+          AND ($7::BIGINT IS NULL OR rejection_id < $7)
+        ORDER BY rejection_id DESC
+        LIMIT $8
+        "#,
+    )
+    .bind(&query.container_id)
+    .bind(&query.author_pubkey)
+    .bind(&query.stage)
+    .bind(&query.code)
+    .bind(query.since_ms)
+    .bind(query.until_ms)
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ApiError::new(ErrorCode::DatabaseError, format!("DatabaseError: {}", e)))?;
+
+    Ok(Json(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_commit_refusal_has_a_stage() {
+        assert_eq!(stage_of(ErrorCode::InvalidSignature), "membrane");
+        assert_eq!(stage_of(ErrorCode::PolicyDenied), "policy");
+        assert_eq!(stage_of(ErrorCode::PactViolation), "pact");
+        assert_eq!(stage_of(ErrorCode::InvalidEvolution), "evolution");
+        assert_eq!(stage_of(ErrorCode::RealityDrift), "tangency");
+        assert_eq!(stage_of(ErrorCode::Forbidden), "gate");
+        // The membrane, ledger and policy codes are all staged
+        for code in &ErrorCode::ALL[..ErrorCode::ALL.iter().position(|c| *c == ErrorCode::PermitMismatch).unwrap()] {
+            assert_ne!(stage_of(*code), "other", "{:?}", code);
+        }
+        assert_eq!(stage_of(ErrorCode::NotFound), "other");
+    }
+
+    #[test]
+    fn test_audit_is_off_by_default() {
+        let config = RejectionAuditConfig::default();
+        assert!(!config.enabled);
+        assert!(config.atom_retention_secs < config.retention_secs);
+    }
+}
@@ -0,0 +1,395 @@
+//! # Route Authorization Matrix
+//!
+//! Every route the server mounts declares who may call it in [`ROUTES`]. The
+//! [`deny_by_default`] layer, applied around the whole router in `main.rs`,
+//! looks up the matched route and:
+//!
+//! - refuses routes missing from the matrix (`403`), so a new route stays
+//!   closed until someone declares it;
+//! - requires a valid session for [`Access::Session`], and a step-up admin
+//!   session for [`Access::StepUp`];
+//! - checks the service signature for [`Access::Service`] (`service_auth`);
+//! - lets [`Access::Public`] and [`Access::Signed`] through. Signed requests
+//!   carry their own proof (link, runner or agent signature, upload token),
+//!   which the handler verifies.
+//!
+//! The layer is the floor, not the whole policy: handlers keep their own
+//! membership, tenant-admin and scope checks. The tests read the router
+//! sources and fail on a route without an entry or an entry without a route.
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::{Method, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sqlx::PgPool;
+use tracing::{error, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::auth::session_db;
+use crate::projections::scope::{Viewer, ViewerRole};
+use crate::service_auth;
+
+/// Who may call a route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Anyone: health, metrics, login ceremonies, public keys
+    Public,
+    /// A valid session (Bearer token or `session` cookie)
+    Session,
+    /// A step-up session with the admin role (same test as `require_stepup`)
+    StepUp,
+    /// A service-signed request from Office
+    Service,
+    /// Proof in the request itself, verified by the handler
+    Signed,
+}
+
+use Access::*;
+
+/// Method and full path (as mounted, with nest prefixes) of every route
+pub const ROUTES: &[(&str, &str, Access)] = &[
+    // Ledger core (main.rs)
+    ("GET", "/health", Public),
+    // Chain head only; clients need it to build the next link
+    ("GET", "/state/:container_id", Public),
+    ("POST", "/link/validate", Signed),
+    ("POST", "/link/commit", Signed),
+    ("POST", "/link/dry-run", Signed),
+    ("GET", "/atom/:hash", Session),
+    ("GET", "/metrics", Public),
+    // Service signature or session, resolved to a container scope by the handler (sse_auth)
+    ("GET", "/ledger/tail", Signed),
+    ("GET", "/ledger/:container_id/entries", Service),
+    ("GET", "/ledger/:container_id/entry/:sequence", Service),
+    ("GET", "/ledger/:container_id/poll", Service),
+    ("POST", "/sync", Service),
+    ("POST", "/repo/presign", Session),
+    ("POST", "/repo/commit-ref", Session),
+    // Identity
+    ("POST", "/id/register/begin", Public),
+    ("POST", "/id/register/finish", Public),
+    ("POST", "/id/login/begin", Public),
+    ("POST", "/id/login/finish", Public),
+    ("POST", "/id/login/discoverable/begin", Public),
+    ("POST", "/id/login/discoverable/finish", Public),
+    // Reports `authenticated: false` without a session
+    ("GET", "/id/whoami", Public),
+    ("POST", "/id/stepup/begin", Session),
+    ("POST", "/id/stepup/finish", Session),
+    ("POST", "/id/sessions/ict/begin", Session),
+    ("POST", "/id/sessions/ict/finish", Session),
+    ("POST", "/id/session/token", Session),
+    ("POST", "/id/agents", StepUp),
+    ("GET", "/id/agents/:sid", Session),
+    ("POST", "/id/agents/:sid/asc", StepUp),
+    ("GET", "/id/agents/:sid/asc", Service),
+    ("POST", "/id/agents/:sid/rotate", StepUp),
+    ("DELETE", "/id/agents/:sid/asc/:asc_id", StepUp),
+    ("GET", "/id/asc/:asc_id/validate", Service),
+    ("GET", "/id/asc/templates", Public),
+    ("POST", "/id/agents/:sid/asc/requests", Signed),
+    ("GET", "/id/asc/requests", Session),
+    ("POST", "/id/asc/requests/:request_id/approve", Session),
+    ("POST", "/id/asc/requests/:request_id/reject", Session),
+    // Projections
+    ("GET", "/query/jobs", Session),
+    ("GET", "/query/jobs/:job_id", Session),
+    ("GET", "/query/jobs/:job_id/approvals", Session),
+    ("GET", "/query/conversations/:conversation_id/jobs", Session),
+    ("GET", "/query/board", Session),
+    ("GET", "/query/conversations/:conversation_id/messages", Session),
+    ("GET", "/query/broadcasts/inbox", Session),
+    ("GET", "/query/conversations/:conversation_id/broadcast_stats", Session),
+    ("GET", "/query/conversations/:conversation_id/summaries", Session),
+    ("GET", "/query/conversations/:conversation_id/summaries/:summary_id", Session),
+    ("GET", "/query/obligations", Session),
+    ("GET", "/query/office/entities", Session),
+    ("GET", "/query/office/entities/:entity_id", Session),
+    ("GET", "/query/office/entities/:entity_id/sessions", Session),
+    ("GET", "/query/office/entities/:entity_id/handovers", Session),
+    ("GET", "/query/office/entities/:entity_id/handovers/latest", Session),
+    ("GET", "/query/office/audit", Session),
+    ("GET", "/query/observations", Session),
+    ("GET", "/query/observations/:entry_hash/:batch_index/proof", Session),
+    ("GET", "/query/analytics/containers", Session),
+    ("GET", "/query/evolution/:container_id", Session),
+    ("GET", "/v1/query/registry/projects", Session),
+    ("GET", "/v1/query/registry/project/:project_id", Session),
+    // Console and runners
+    ("POST", "/v1/policy/permit", Service),
+    ("POST", "/v1/commands/issue", Service),
+    ("PUT", "/v1/blobs/:hash", Service),
+    ("GET", "/v1/policy/permit/keys", Public),
+    ("POST", "/v1/id/stepup/begin", Session),
+    // Runner poll; only commands a live runner's capabilities match
+    ("GET", "/v1/query/commands", Public),
+    ("POST", "/v1/exec.finish", Signed),
+    ("PUT", "/v1/receipts/:command_id/artifacts/:name", Signed),
+    ("GET", "/v1/receipts/:command_id/artifacts/:name", Session),
+    ("GET", "/v1/receipts/:command_id", Session),
+    ("POST", "/v1/exec/:execution_id/logs", Signed),
+    ("GET", "/v1/exec/:execution_id/logs", Session),
+    ("GET", "/v1/exec/:execution_id/logs/tail", Session),
+    ("POST", "/v1/runners", Signed),
+    ("GET", "/v1/runners", Session),
+    ("POST", "/v1/runners/:runner_id/heartbeat", Signed),
+    ("POST", "/v1/runners/:runner_id/dead-letters", Signed),
+    ("GET", "/jobs/templates", Session),
+    ("GET", "/jobs/templates/:id", Session),
+    ("POST", "/jobs/from-template/:id", Session),
+    // Rules only, like the chain head; clients read them before building a link
+    ("GET", "/v1/containers/:id/manifest", Public),
+    ("GET", "/v1/containers/:id/flags/stream", Public),
+    // Operator
+    ("GET", "/v1/admin/dead-letters", StepUp),
+    ("GET", "/v1/admin/dead-letters/:job_id", StepUp),
+    ("PATCH", "/v1/admin/dead-letters/:job_id", StepUp),
+    ("DELETE", "/v1/admin/dead-letters/:job_id", StepUp),
+    ("POST", "/v1/admin/dead-letters/:job_id/requeue", StepUp),
+    ("GET", "/v1/admin/actions", StepUp),
+    ("POST", "/v1/admin/actions", StepUp),
+    ("GET", "/v1/admin/actions/:action_id", StepUp),
+    ("POST", "/v1/admin/actions/:action_id/approve", StepUp),
+    ("POST", "/v1/admin/actions/:action_id/cancel", StepUp),
+    ("GET", "/v1/policy/:id/coverage", StepUp),
+    ("GET", "/v1/policy/:id/history", StepUp),
+    ("POST", "/v1/policy/debug", StepUp),
+    ("GET", "/v1/admin/rejections", StepUp),
+    ("GET", "/v1/admin/maintenance", StepUp),
+    ("POST", "/v1/admin/maintenance/flush", StepUp),
+    ("GET", "/v1/admin/public-tokens", StepUp),
+    ("POST", "/v1/admin/public-tokens", StepUp),
+    ("DELETE", "/v1/admin/public-tokens/:token_id", StepUp),
+    // Messenger
+    ("GET", "/messenger/bootstrap", Session),
+    ("GET", "/bootstrap", Session),
+    ("POST", "/messenger/messages", Session),
+    ("GET", "/messenger/conversations", Session),
+    ("POST", "/messenger/conversations", Session),
+    ("POST", "/messenger/jobs/:job_id/approve", Session),
+    ("POST", "/messenger/jobs/:job_id/reject", Session),
+    ("GET", "/messenger/entities", Session),
+    ("POST", "/v1/conversations/:id/messages", Session),
+    ("POST", "/v1/jobs/:id/actions", Session),
+    ("POST", "/v1/conversations/:id/scheduled", Session),
+    ("GET", "/v1/conversations/:id/scheduled", Session),
+    ("DELETE", "/v1/scheduled/:id", Session),
+    ("GET", "/v1/mutes", Session),
+    ("DELETE", "/v1/mutes/:sid", Session),
+    ("POST", "/v1/messages/:id/redact", Session),
+    ("GET", "/v1/conversations/:id/timeline", Session),
+    ("GET", "/v1/conversations/:id/blobs/:hash", Session),
+    ("GET", "/v1/jobs/:id", Session),
+    ("GET", "/v1/stream", Session),
+    // Tenants
+    ("POST", "/tenant", Session),
+    ("GET", "/tenant", Session),
+    ("GET", "/tenant/members", Session),
+    ("POST", "/tenant/invite", Session),
+    ("POST", "/tenant/join", Session),
+    ("GET", "/tenant/preferences", Session),
+    ("PUT", "/tenant/preferences", Session),
+    ("GET", "/tenant/message_throttle", Session),
+    ("PUT", "/tenant/message_throttle", Session),
+    ("POST", "/tenant/exports", Session),
+    ("GET", "/tenant/exports/:export_id", Session),
+    // Download token from the export status
+    ("GET", "/tenant/exports/:export_id/download", Signed),
+];
+
+/// Declared access of a route (HEAD is served by the GET route)
+pub fn access(method: &Method, path: &str) -> Option<Access> {
+    let method = if method == Method::HEAD { "GET" } else { method.as_str() };
+    ROUTES
+        .iter()
+        .find(|(m, p, _)| *m == method && *p == path)
+        .map(|(_, _, access)| *access)
+}
+
+/// Enforce [`ROUTES`] on every matched request
+pub async fn deny_by_default(
+    State(pool): State<PgPool>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    // Unmatched requests fall through to the router's 404 / 405
+    let Some(route) = req.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string()) else {
+        return next.run(req).await;
+    };
+
+    let Some(access) = access(req.method(), &route) else {
+        error!(method = %req.method(), route = %route, "🚫 Route missing from the authorization matrix");
+        return ApiError::new(ErrorCode::Forbidden, "RouteNotDeclared").into_response();
+    };
+
+    match access {
+        Public | Signed => next.run(req).await,
+        Service => service_auth::require_service(req, next).await.into_response(),
+        Session | StepUp => {
+            let Some(token) = crate::id_routes::extract_session_token(req.headers()) else {
+                return ApiError::new(ErrorCode::Unauthorized, "missing session").into_response();
+            };
+            let session = match session_db::get_valid(&pool, &token).await {
+                Ok(Some(session)) => session,
+                Ok(None) => {
+                    warn!(route = %route, "🔒 Request with invalid or expired session");
+                    return ApiError::new(ErrorCode::Unauthorized, "invalid or expired session").into_response();
+                }
+                Err(e) => return ApiError::new(ErrorCode::DatabaseError, e.to_string()).into_response(),
+            };
+            if access == StepUp && Viewer::from_session(&session).role == ViewerRole::Member {
+                warn!(route = %route, sid = %session.sid, "🔒 Step-up required");
+                return ApiError::new(ErrorCode::Forbidden, "step-up required").into_response();
+            }
+            next.run(req).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    use axum::{http::StatusCode, middleware, routing::get, Router};
+
+    /// Files that define mounted routes: (module merged in main.rs, nest prefix, source)
+    const ROUTER_SOURCES: &[(&str, &str, &str)] = &[
+        ("main", "", include_str!("main.rs")),
+        ("metrics", "", include_str!("metrics.rs")),
+        ("sse", "", include_str!("sse.rs")),
+        ("id_routes", "", include_str!("id_routes.rs")),
+        ("asc_requests", "", include_str!("asc_requests.rs")),
+        ("id_session_token", "", include_str!("id_session_token.rs")),
+        ("repo_routes", "", include_str!("repo_routes.rs")),
+        ("ledger_routes", "", include_str!("ledger_routes.rs")),
+        ("projections", "/query", include_str!("projections/routes.rs")),
+        ("console_v1", "", include_str!("console_v1.rs")),
+        ("job_templates", "", include_str!("job_templates.rs")),
+        ("container_manifest", "", include_str!("container_manifest.rs")),
+        ("feature_flags", "", include_str!("feature_flags.rs")),
+        ("runners", "", include_str!("runners.rs")),
+        ("exec_logs", "", include_str!("exec_logs.rs")),
+        ("dead_letters", "", include_str!("dead_letters.rs")),
+        ("admin_actions", "", include_str!("admin_actions.rs")),
+        ("policy_routes", "", include_str!("policy_routes.rs")),
+        ("rejections", "", include_str!("rejections.rs")),
+        ("maintenance", "", include_str!("maintenance.rs")),
+        ("public_read", "", include_str!("public_read/mod.rs")),
+        ("registry_v1", "", include_str!("registry_v1.rs")),
+        ("messenger_v1", "", include_str!("messenger_v1.rs")),
+        ("messenger_gateway", "", include_str!("messenger_gateway/routes.rs")),
+        ("tenant", "", include_str!("tenant/routes.rs")),
+        ("tenant", "", include_str!("tenant/export.rs")),
+    ];
+
+    const METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+    /// `(METHOD, path)` of every `.route(...)` outside the test module
+    fn routes_in(prefix: &str, source: &str) -> Vec<(String, String)> {
+        let source = source.split("\n#[cfg(test)]\nmod tests").next().unwrap_or(source);
+        let mut found = Vec::new();
+        for (start, _) in source.match_indices(".route(") {
+            let args = &source[start + ".route(".len()..];
+            let path = args.split('"').nth(1).expect("route path literal");
+
+            // Method routers are the calls directly inside `.route(...)`
+            let (mut depth, mut token) = (0, String::new());
+            for c in args.chars() {
+                match c {
+                    '(' => {
+                        if depth == 0 && METHODS.contains(&token.as_str()) {
+                            found.push((token.to_uppercase(), format!("{}{}", prefix, path)));
+                        }
+                        depth += 1;
+                        token.clear();
+                    }
+                    ')' if depth == 0 => break,
+                    ')' => depth -= 1,
+                    c if c.is_alphanumeric() || c == '_' => token.push(c),
+                    _ => token.clear(),
+                }
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_every_route_has_a_declared_access() {
+        let mounted: BTreeSet<(String, String)> =
+            ROUTER_SOURCES.iter().flat_map(|(_, prefix, source)| routes_in(prefix, source)).collect();
+        assert!(mounted.len() > 100, "router sources not parsed: {:?}", mounted);
+
+        let undeclared: Vec<_> = mounted
+            .iter()
+            .filter(|(method, path)| access(&method.parse().unwrap(), path).is_none())
+            .collect();
+        assert!(undeclared.is_empty(), "routes missing from route_auth::ROUTES: {:?}", undeclared);
+
+        let stale: Vec<_> = ROUTES
+            .iter()
+            .filter(|(method, path, _)| !mounted.contains(&(method.to_string(), path.to_string())))
+            .collect();
+        assert!(stale.is_empty(), "route_auth::ROUTES entries without a route: {:?}", stale);
+
+        let declared: BTreeSet<_> = ROUTES.iter().map(|(m, p, _)| (*m, *p)).collect();
+        assert_eq!(declared.len(), ROUTES.len(), "route declared twice");
+    }
+
+    #[test]
+    fn test_every_mounted_router_is_walked() {
+        let main = ROUTER_SOURCES[0].2;
+        let app = &main[main.find("let app = Router::new()").unwrap()..];
+        let app = &app[..app.find(';').unwrap()];
+        let modules: BTreeSet<&str> = app
+            .split(".merge(")
+            .skip(1)
+            .chain(app.split(".nest(").skip(1).map(|s| s.split_once(", ").map_or(s, |(_, m)| m)))
+            .map(|s| s.split("::").next().unwrap().trim())
+            .collect();
+        for module in modules {
+            assert!(
+                ROUTER_SOURCES.iter().any(|(name, _, _)| *name == module),
+                "{} is mounted in main.rs but its routes are not walked",
+                module
+            );
+        }
+    }
+
+    #[test]
+    fn test_sensitive_routes_are_not_public() {
+        for (method, path) in [("GET", "/atom/:hash"), ("GET", "/query/jobs"), ("POST", "/sync"), ("GET", "/v1/jobs/:id")] {
+            assert_ne!(access(&method.parse().unwrap(), path), Some(Public), "{} {}", method, path);
+        }
+        assert_eq!(access(&Method::HEAD, "/health"), Some(Public));
+        assert_eq!(access(&Method::POST, "/health"), None);
+    }
+
+    #[tokio::test]
+    async fn test_layer_denies_by_default() {
+        use tower_service::Service;
+
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let query = Router::new().route("/jobs", get(|| async { "jobs" }));
+        let mut app = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/undeclared", get(|| async { "open" }))
+            .nest("/query", query)
+            .layer(middleware::from_fn_with_state(pool, deny_by_default));
+
+        let mut status = |uri: &str| {
+            let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let response = app.call(req);
+            async move { response.await.unwrap().status() }
+        };
+        assert_eq!(status("/health").await, StatusCode::OK);
+        assert_eq!(status("/undeclared").await, StatusCode::FORBIDDEN);
+        // Nested routes are matched with their prefix
+        assert_eq!(status("/query/jobs").await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/nowhere").await, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,377 @@
+//! Runner Registry — capability registration, heartbeats, discovery
+//!
+//! Runners announce what they can execute so the console only hands them
+//! commands they can handle.
+//!
+//! Endpoints:
+//! - POST /v1/runners                      → Register (self-signed by the runner key)
+//! - POST /v1/runners/:runner_id/heartbeat → Liveness (signed by the registered key);
+//!   the response carries dead-lettered jobs an admin requeued
+//! - GET  /v1/runners?live=&capability=    → List runners
+//!
+//! A runner is live while its last heartbeat is within `UBL_RUNNER_TTL_SECS`
+//! (default 60). `GET /v1/query/commands?runner_id=` only returns commands
+//! whose `required_capabilities` are a subset of a live runner's labels.
+//!
+//! Signed messages (tagged "ed25519:<base64url>", see `keystore::verify`):
+//! ```text
+//! ubl:runner:register\n<runner_id>\n<pubkey>\n<cap,cap,...>\n<max_concurrency>\n<version>\n<ts_ms>
+//! ubl:runner:heartbeat\n<runner_id>\n<ts_ms>\n<in_flight>
+//! ```
+
+use axum::{
+    extract::{Path, Query, State},
+    routing::post,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+use ubl_errors::ErrorCode;
+
+use crate::api_error::ApiError;
+use crate::crypto;
+use crate::timestamps::now_ms;
+
+/// Default liveness window
+const DEFAULT_RUNNER_TTL_SECS: u64 = 60;
+/// Signed timestamps older or newer than this are rejected (replay window)
+const MAX_CLOCK_SKEW_MS: i64 = 5 * 60 * 1000;
+const MAX_CAPABILITIES: usize = 32;
+const MAX_CAPABILITY_LEN: usize = 64;
+
+/// Placeholder key used by seeded runners that have never registered
+const UNREGISTERED_PUBKEY: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+fn runner_ttl_ms() -> i64 {
+    static TTL: OnceLock<i64> = OnceLock::new();
+    *TTL.get_or_init(|| {
+        let secs = std::env::var("UBL_RUNNER_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_RUNNER_TTL_SECS);
+        (secs * 1000) as i64
+    })
+}
+
+// =============================================================================
+// TYPES
+// =============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRunnerRequest {
+    pub runner_id: String,
+    pub pubkey_ed25519: String,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default = "default_concurrency")]
+    pub max_concurrency: i32,
+    pub version: String,
+    #[serde(default)]
+    pub zone: Option<String>,
+    pub ts_ms: i64,
+    pub sig_runner: String,
+}
+
+fn default_concurrency() -> i32 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeartbeatRequest {
+    pub ts_ms: i64,
+    #[serde(default)]
+    pub in_flight: i32,
+    pub sig_runner: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListRunnersParams {
+    #[serde(default)]
+    pub live: bool,
+    pub capability: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunnerView {
+    pub runner_id: String,
+    pub zone: String,
+    pub capabilities: Vec<String>,
+    pub max_concurrency: i32,
+    pub version: Option<String>,
+    pub in_flight: i32,
+    pub last_heartbeat_ms: Option<i64>,
+    pub live: bool,
+}
+
+// =============================================================================
+// ROUTES
+// =============================================================================
+
+pub fn routes(pool: PgPool) -> Router {
+    Router::new()
+        .route("/v1/runners", post(register_runner).get(list_runners))
+        .route("/v1/runners/:runner_id/heartbeat", post(heartbeat))
+        .with_state(pool)
+}
+
+// =============================================================================
+// HANDLERS
+// =============================================================================
+
+/// POST /v1/runners — register or update a runner
+async fn register_runner(
+    State(pool): State<PgPool>,
+    Json(req): Json<RegisterRunnerRequest>,
+) -> Result<Json<RunnerView>, ApiError> {
+    let now_ms = now_ms();
+    check_fresh(req.ts_ms, now_ms)?;
+    let capabilities = normalize_capabilities(&req.capabilities)?;
+    if req.max_concurrency < 1 {
+        return Err(ApiError::new(ErrorCode::BadRequest, "max_concurrency must be at least 1"));
+    }
+
+    // Proof of possession: the runner signs with the key it registers
+    let msg = register_message(&req, &capabilities);
+    crate::keystore::verify(&req.pubkey_ed25519, msg.as_bytes(), &req.sig_runner)
+        .map_err(|e| ApiError::new(ErrorCode::InvalidSignature, format!("RunnerSigInvalid: {}", e)))?;
+
+    // A registered runner id cannot be taken over with a different key
+    let existing: Option<String> = sqlx::query_scalar("SELECT pubkey_ed25519 FROM ubl_runners WHERE runner_id = $1")
+        .bind(&req.runner_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(db_error)?;
+    if let Some(key) = existing {
+        if key != UNREGISTERED_PUBKEY && key != req.pubkey_ed25519 {
+            return Err(ApiError::new(ErrorCode::Forbidden, "RunnerKeyMismatch"));
+        }
+    }
+
+    let zone = req.zone.clone().unwrap_or_else(|| req.runner_id.clone());
+    sqlx::query(
+        r#"
+        INSERT INTO ubl_runners
+          (runner_id, pubkey_ed25519, is_active, zone, updated_at_ms,
+           capabilities, max_concurrency, version, registered_at_ms, last_heartbeat_ms, in_flight)
+        VALUES ($1, $2, true, $3, $4, $5, $6, $7, $4, $4, 0)
+        ON CONFLICT (runner_id) DO UPDATE SET
+          pubkey_ed25519 = EXCLUDED.pubkey_ed25519,
+          zone = EXCLUDED.zone,
+          updated_at_ms = EXCLUDED.updated_at_ms,
+          capabilities = EXCLUDED.capabilities,
+          max_concurrency = EXCLUDED.max_concurrency,
+          version = EXCLUDED.version,
+          registered_at_ms = EXCLUDED.registered_at_ms,
+          last_heartbeat_ms = EXCLUDED.last_heartbeat_ms
+        "#,
+    )
+    .bind(&req.runner_id)
+    .bind(&req.pubkey_ed25519)
+    .bind(&zone)
+    .bind(now_ms)
+    .bind(&capabilities)
+    .bind(req.max_concurrency)
+    .bind(&req.version)
+    .execute(&pool)
+    .await
+    .map_err(db_error)?;
+
+    info!("🏃 Runner registered: {} v{} caps={:?}", req.runner_id, req.version, capabilities);
+
+    Ok(Json(RunnerView {
+        runner_id: req.runner_id,
+        zone,
+        capabilities,
+        max_concurrency: req.max_concurrency,
+        version: Some(req.version),
+        in_flight: 0,
+        last_heartbeat_ms: Some(now_ms),
+        live: true,
+    }))
+}
+
+/// POST /v1/runners/:runner_id/heartbeat
+async fn heartbeat(
+    State(pool): State<PgPool>,
+    Path(runner_id): Path<String>,
+    Json(req): Json<HeartbeatRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let now_ms = now_ms();
+    check_fresh(req.ts_ms, now_ms)?;
+
+    let msg = heartbeat_message(&runner_id, req.ts_ms, req.in_flight);
+    crypto::verify_runner_sig(&pool, &runner_id, msg.as_bytes(), &req.sig_runner)
+        .await
+        .map_err(|e| match e.as_str() {
+            "RunnerNotFound" => ApiError::new(ErrorCode::NotFound, e),
+            "RunnerNotActive" => ApiError::new(ErrorCode::Forbidden, e),
+            _ => ApiError::new(ErrorCode::InvalidSignature, format!("RunnerSigInvalid: {}", e)),
+        })?;
+
+    // Liveness uses server time, not the runner's clock
+    sqlx::query("UPDATE ubl_runners SET last_heartbeat_ms = $2, in_flight = $3 WHERE runner_id = $1")
+        .bind(&runner_id)
+        .bind(now_ms)
+        .bind(req.in_flight.max(0))
+        .execute(&pool)
+        .await
+        .map_err(db_error)?;
+
+    // Dead letters an admin requeued go back to their runner
+    let requeued = crate::dead_letters::release_requeued(&pool, &runner_id).await.map_err(db_error)?;
+
+    Ok(Json(serde_json::json!({ "runner_id": runner_id, "ttl_ms": runner_ttl_ms(), "requeued": requeued })))
+}
+
+/// GET /v1/runners — list registered runners
+async fn list_runners(
+    State(pool): State<PgPool>,
+    Query(params): Query<ListRunnersParams>,
+) -> Result<Json<Vec<RunnerView>>, ApiError> {
+    let now_ms = now_ms();
+    let live_after = now_ms - runner_ttl_ms();
+
+    let rows = sqlx::query(
+        r#"
+        SELECT runner_id, zone, capabilities, max_concurrency, version, in_flight, last_heartbeat_ms
+        FROM ubl_runners
+        WHERE is_active
+          AND ($1 = false OR last_heartbeat_ms >= $2)
+          AND ($3::TEXT IS NULL OR $3 = ANY(capabilities))
+        ORDER BY runner_id
+        "#,
+    )
+    .bind(params.live)
+    .bind(live_after)
+    .bind(&params.capability)
+    .fetch_all(&pool)
+    .await
+    .map_err(db_error)?;
+
+    let runners = rows
+        .into_iter()
+        .map(|row| {
+            let last_heartbeat_ms: Option<i64> = Row::get(&row, "last_heartbeat_ms");
+            RunnerView {
+                runner_id: Row::get(&row, "runner_id"),
+                zone: Row::get(&row, "zone"),
+                capabilities: Row::get(&row, "capabilities"),
+                max_concurrency: Row::get(&row, "max_concurrency"),
+                version: Row::get(&row, "version"),
+                in_flight: Row::get(&row, "in_flight"),
+                last_heartbeat_ms,
+                live: last_heartbeat_ms.is_some_and(|ts| ts >= live_after),
+            }
+        })
+        .collect();
+
+    Ok(Json(runners))
+}
+
+// =============================================================================
+// DISPATCH HELPERS
+// =============================================================================
+
+/// Capabilities of a runner that is active and live; None otherwise
+pub async fn live_capabilities(pool: &PgPool, runner_id: &str) -> Result<Option<Vec<String>>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT capabilities FROM ubl_runners
+        WHERE runner_id = $1 AND is_active AND last_heartbeat_ms >= $2
+        "#,
+    )
+    .bind(runner_id)
+    .bind(now_ms() - runner_ttl_ms())
+    .fetch_optional(pool)
+    .await
+}
+
+/// `required_capabilities` from permit args; the permit signature covers them
+pub fn required_capabilities(args: &serde_json::Value) -> Vec<String> {
+    args.get("required_capabilities")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+// =============================================================================
+// HELPERS
+// =============================================================================
+
+/// Validate labels, then sort and dedup so the signed form is canonical
+fn normalize_capabilities(caps: &[String]) -> Result<Vec<String>, ApiError> {
+    if caps.len() > MAX_CAPABILITIES {
+        return Err(ApiError::new(
+            ErrorCode::BadRequest,
+            format!("at most {} capabilities", MAX_CAPABILITIES),
+        ));
+    }
+    let valid = |c: &str| {
+        !c.is_empty()
+            && c.len() <= MAX_CAPABILITY_LEN
+            && c.chars().all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || "._:-".contains(ch))
+    };
+    if let Some(bad) = caps.iter().find(|c| !valid(c)) {
+        return Err(ApiError::new(ErrorCode::BadRequest, format!("invalid capability label {:?}", bad)));
+    }
+    let mut caps = caps.to_vec();
+    caps.sort();
+    caps.dedup();
+    Ok(caps)
+}
+
+fn check_fresh(ts_ms: i64, now_ms: i64) -> Result<(), ApiError> {
+    if (now_ms - ts_ms).abs() > MAX_CLOCK_SKEW_MS {
+        warn!("Runner message outside clock window: ts={} now={}", ts_ms, now_ms);
+        return Err(ApiError::new(ErrorCode::BadRequest, "StaleRunnerMessage"));
+    }
+    Ok(())
+}
+
+fn register_message(req: &RegisterRunnerRequest, capabilities: &[String]) -> String {
+    format!(
+        "ubl:runner:register\n{}\n{}\n{}\n{}\n{}\n{}",
+        req.runner_id,
+        req.pubkey_ed25519,
+        capabilities.join(","),
+        req.max_concurrency,
+        req.version,
+        req.ts_ms
+    )
+}
+
+fn heartbeat_message(runner_id: &str, ts_ms: i64, in_flight: i32) -> String {
+    format!("ubl:runner:heartbeat\n{}\n{}\n{}", runner_id, ts_ms, in_flight)
+}
+
+fn db_error(e: sqlx::Error) -> ApiError {
+    ApiError::new(ErrorCode::DatabaseError, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_are_canonical() {
+        let caps = normalize_capabilities(&["shell".into(), "docker".into(), "shell".into()]).unwrap();
+        assert_eq!(caps, vec!["docker", "shell"]);
+        assert!(normalize_capabilities(&["Shell".into()]).is_err());
+        assert!(normalize_capabilities(&["".into()]).is_err());
+    }
+
+    #[test]
+    fn test_required_capabilities_from_args() {
+        let args = serde_json::json!({"required_capabilities": ["gpu", "docker"], "cmd": "x"});
+        assert_eq!(required_capabilities(&args), vec!["gpu", "docker"]);
+        assert!(required_capabilities(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_clock_window() {
+        assert!(check_fresh(1_000_000, 1_000_000 + MAX_CLOCK_SKEW_MS).is_ok());
+        assert!(check_fresh(1_000_000, 1_000_001 + MAX_CLOCK_SKEW_MS).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! # Secret Providers
+//!
+//! Abstraction over where secret material (signing keys, API tokens) lives.
+//! The keystore resolves keys through a [`SecretProvider`] chain instead of
+//! reading env vars and files directly.
+//!
+//! Backends (selected with `UBL_SECRETS_BACKEND`):
+//! - `file` (default): env override `UBL_KEY_<ID>`, then `<UBL_KEYS_DIR>/<id>.key`
+//! - `aws`: AWS Secrets Manager (stub, not yet wired to the SDK)
+//! - `gcp`: GCP Secret Manager (stub, not yet wired to the SDK)
+//!
+//! Secret values never implement `Display`, and their `Debug` output is
+//! redacted, so they cannot leak through `tracing` or config dumps. They are
+//! zeroized on drop and compared in constant time.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+use ubl_kernel::{Zeroize, Zeroizing};
+
+/// Secret material. Deliberately has no `Display` and a redacted `Debug`.
+#[derive(Clone)]
+pub struct Secret(Vec<u8>);
+
+impl Secret {
+    pub fn new(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+
+    /// Access the raw bytes. Callers must not log the result.
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+impl PartialEq for Secret {
+    fn eq(&self, other: &Self) -> bool {
+        ubl_kernel::ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Secret {}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("secret backend '{0}' is not available in this build")]
+    Unsupported(&'static str),
+
+    #[error("backend '{0}' is read-only")]
+    ReadOnly(&'static str),
+
+    #[error("secret I/O error for '{id}': {reason}")]
+    Io { id: String, reason: String },
+}
+
+/// A source of named secrets
+pub trait SecretProvider: Send + Sync {
+    /// Backend name, safe to log
+    fn name(&self) -> &'static str;
+
+    /// Fetch a secret by ID. `Ok(None)` means "not stored here".
+    fn get(&self, id: &str) -> Result<Option<Secret>, SecretError>;
+
+    /// Store (or replace) a secret. Used for generation and rotation.
+    fn put(&self, _id: &str, _secret: &Secret) -> Result<(), SecretError> {
+        Err(SecretError::ReadOnly(self.name()))
+    }
+
+    /// Remove a secret, if present
+    fn delete(&self, _id: &str) -> Result<(), SecretError> {
+        Err(SecretError::ReadOnly(self.name()))
+    }
+}
+
+/// Reads `<prefix><ID>` from the environment (ID upper-cased, `-` → `_`)
+pub struct EnvSecretProvider {
+    prefix: String,
+}
+
+impl EnvSecretProvider {
+    pub fn new(prefix: &str) -> Self {
+        Self { prefix: prefix.to_string() }
+    }
+
+    fn var_name(&self, id: &str) -> String {
+        format!("{}{}", self.prefix, id.to_uppercase().replace(['-', '.'], "_"))
+    }
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Secret>, SecretError> {
+        Ok(std::env::var(self.var_name(id))
+            .ok()
+            .map(|v| Secret::new(Zeroizing::new(v).trim().as_bytes())))
+    }
+}
+
+/// Stores each secret as `<dir>/<id>.key` with 0600 permissions
+pub struct FileSecretProvider {
+    dir: PathBuf,
+}
+
+impl FileSecretProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.key", id))
+    }
+}
+
+impl SecretProvider for FileSecretProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Secret>, SecretError> {
+        let path = self.path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(|s| Some(Secret::new(Zeroizing::new(s).trim().as_bytes())))
+            .map_err(|e| SecretError::Io { id: id.to_string(), reason: e.to_string() })
+    }
+
+    fn put(&self, id: &str, secret: &Secret) -> Result<(), SecretError> {
+        let path = self.path(id);
+        fs::write(&path, secret.expose())
+            .map_err(|e| SecretError::Io { id: id.to_string(), reason: e.to_string() })?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+        }
+        Ok(())
+    }
+
+    fn delete(&self, id: &str) -> Result<(), SecretError> {
+        let path = self.path(id);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| SecretError::Io { id: id.to_string(), reason: e.to_string() })?;
+        }
+        Ok(())
+    }
+}
+
+/// AWS Secrets Manager backend (stub)
+///
+/// Secret IDs map to `<prefix>/<id>`. Wire to `aws-sdk-secretsmanager` when
+/// deploying on AWS; until then every call reports `Unsupported`.
+pub struct AwsSecretsManagerProvider {
+    pub prefix: String,
+}
+
+impl SecretProvider for AwsSecretsManagerProvider {
+    fn name(&self) -> &'static str {
+        "aws"
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Secret>, SecretError> {
+        tracing::debug!("aws secret {}/{} requested", self.prefix, id);
+        Err(SecretError::Unsupported("aws"))
+    }
+}
+
+/// GCP Secret Manager backend (stub)
+///
+/// Secret IDs map to `projects/<project>/secrets/<id>/versions/latest`.
+pub struct GcpSecretManagerProvider {
+    pub project: String,
+}
+
+impl SecretProvider for GcpSecretManagerProvider {
+    fn name(&self) -> &'static str {
+        "gcp"
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Secret>, SecretError> {
+        tracing::debug!("gcp secret projects/{}/secrets/{} requested", self.project, id);
+        Err(SecretError::Unsupported("gcp"))
+    }
+}
+
+/// Tries providers in order; writes go to the first writable one
+pub struct ChainedSecretProvider {
+    providers: Vec<Box<dyn SecretProvider>>,
+}
+
+impl ChainedSecretProvider {
+    pub fn new(providers: Vec<Box<dyn SecretProvider>>) -> Self {
+        Self { providers }
+    }
+}
+
+impl SecretProvider for ChainedSecretProvider {
+    fn name(&self) -> &'static str {
+        "chain"
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Secret>, SecretError> {
+        for p in &self.providers {
+            if let Some(secret) = p.get(id)? {
+                return Ok(Some(secret));
+            }
+        }
+        Ok(None)
+    }
+
+    fn put(&self, id: &str, secret: &Secret) -> Result<(), SecretError> {
+        let mut last = SecretError::ReadOnly("chain");
+        for p in &self.providers {
+            match p.put(id, secret) {
+                Ok(()) => return Ok(()),
+                Err(SecretError::ReadOnly(_)) => continue,
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+
+    fn delete(&self, id: &str) -> Result<(), SecretError> {
+        for p in &self.providers {
+            match p.delete(id) {
+                Ok(()) | Err(SecretError::ReadOnly(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Build the provider chain for the configured backend
+pub fn from_env(keys_dir: PathBuf) -> Box<dyn SecretProvider> {
+    match std::env::var("UBL_SECRETS_BACKEND").as_deref() {
+        Ok("aws") => Box::new(AwsSecretsManagerProvider {
+            prefix: std::env::var("UBL_SECRETS_PREFIX").unwrap_or_else(|_| "ubl".into()),
+        }),
+        Ok("gcp") => Box::new(GcpSecretManagerProvider {
+            project: std::env::var("UBL_SECRETS_PROJECT").unwrap_or_default(),
+        }),
+        _ => Box::new(ChainedSecretProvider::new(vec![
+            Box::new(EnvSecretProvider::new("UBL_KEY_")),
+            Box::new(FileSecretProvider::new(keys_dir)),
+        ])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_debug_is_redacted() {
+        let s = Secret::new("super-secret");
+        assert_eq!(format!("{:?}", s), "Secret(***)");
+    }
+
+    #[test]
+    fn test_secret_eq() {
+        assert_eq!(Secret::new("abc"), Secret::new("abc"));
+        assert_ne!(Secret::new("abc"), Secret::new("abd"));
+    }
+
+    #[test]
+    fn test_file_provider_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("ubl-secrets-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let p = FileSecretProvider::new(&dir);
+
+        assert!(p.get("k1").unwrap().is_none());
+        p.put("k1", &Secret::new("abcd")).unwrap();
+        assert_eq!(p.get("k1").unwrap().unwrap().expose(), b"abcd");
+        p.delete("k1").unwrap();
+        assert!(p.get("k1").unwrap().is_none());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_chain_writes_skip_read_only() {
+        let dir = std::env::temp_dir().join(format!("ubl-secrets-chain-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let chain = ChainedSecretProvider::new(vec![
+            Box::new(EnvSecretProvider::new("UBL_TEST_NEVER_SET_")),
+            Box::new(FileSecretProvider::new(&dir)),
+        ]);
+
+        chain.put("k2", &Secret::new("beef")).unwrap();
+        assert_eq!(chain.get("k2").unwrap().unwrap().expose(), b"beef");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cloud_stubs_report_unsupported() {
+        let aws = AwsSecretsManagerProvider { prefix: "ubl".into() };
+        assert!(matches!(aws.get("admin"), Err(SecretError::Unsupported("aws"))));
+    }
+}
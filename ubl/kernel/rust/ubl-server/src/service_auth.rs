@@ -0,0 +1,258 @@
+//! # Service-to-Service Authentication
+//!
+//! Requests between UBL and Office carry a signature from the sending
+//! service's key:
+//!
+//! - `X-UBL-Service`: sender id (`ubl`, or Office's container id)
+//! - `X-UBL-Timestamp`: Unix milliseconds
+//! - `X-UBL-Signature`: Ed25519ph (context `ubl:service:v2`) over
+//!   [`ubl_kernel::service_request_bytes`]
+//!
+//! The receiver checks the signature against its configured peer keys and
+//! rejects timestamps more than [`MAX_SKEW_MS`] away from its own clock.
+//!
+//! UBL signs everything it sends to Office with its `service` key. On the
+//! internal routes Office calls (`/v1/policy/permit`, `/v1/commands/issue`) a
+//! bad signature is always rejected; an unsigned request only when
+//! `UBL_REQUIRE_SERVICE_AUTH` is set, so keys can be rolled out first.
+//!
+//! With `UBL_SERVICE_TLS_*` set, calls to Office also present a client
+//! certificate (mTLS) for the TLS terminator in front of Office to verify.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use ed25519_dalek::SigningKey;
+use thiserror::Error;
+use tracing::{info, warn};
+use ubl_kernel::{contexts, service_request_bytes, sign_with, verify_with, SignatureMode};
+
+use crate::config::{ServerConfig, ServiceTls};
+use crate::keystore;
+use crate::timestamps::now_ms;
+
+/// Service id UBL signs as
+pub const SERVICE_ID: &str = "ubl";
+
+/// Keystore id of UBL's service key
+pub const SERVICE_KEY_ID: &str = "service";
+
+pub const HEADER_SERVICE: &str = "x-ubl-service";
+pub const HEADER_TIMESTAMP: &str = "x-ubl-timestamp";
+pub const HEADER_SIGNATURE: &str = "x-ubl-signature";
+
+/// Largest accepted difference between the request timestamp and our clock
+pub const MAX_SKEW_MS: i64 = 5 * 60 * 1000;
+
+/// Why a signed request was refused
+#[derive(Debug, Error, PartialEq)]
+pub enum ServiceAuthError {
+    #[error("request is not signed")]
+    Unsigned,
+
+    #[error("malformed {0} header")]
+    Malformed(&'static str),
+
+    #[error("timestamp outside the allowed {}s window", MAX_SKEW_MS / 1000)]
+    Stale,
+
+    #[error("signature does not match any trusted service key")]
+    BadSignature,
+}
+
+struct ServiceAuth {
+    key: SigningKey,
+    office_pubkeys: Vec<String>,
+    required: bool,
+    tls: ServiceTls,
+    max_body_bytes: usize,
+}
+
+static SERVICE_AUTH: OnceLock<ServiceAuth> = OnceLock::new();
+
+/// Load UBL's service key and the Office keys it trusts, and check the mTLS
+/// material loads. Call once at startup, after `keystore::init()`.
+pub fn init(config: &ServerConfig) -> anyhow::Result<()> {
+    let key = keystore::load_or_create(SERVICE_KEY_ID);
+    info!(
+        "🤝 Service key: {} (trusting {} Office key(s), {})",
+        ubl_kernel::pubkey_from_signing_key(&key),
+        config.office_pubkeys.len(),
+        if config.require_service_auth { "required" } else { "optional" }
+    );
+    let _ = SERVICE_AUTH.set(ServiceAuth {
+        key,
+        office_pubkeys: config.office_pubkeys.clone(),
+        required: config.require_service_auth,
+        tls: config.service_tls.clone(),
+        max_body_bytes: config.max_body_bytes,
+    });
+    http_client(Duration::from_secs(30))?;
+    Ok(())
+}
+
+/// HTTP client for calls to Office, presenting the mTLS identity if configured
+pub fn http_client(timeout: Duration) -> anyhow::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(auth) = SERVICE_AUTH.get() {
+        if let (Some(cert), Some(key)) = (&auth.tls.client_cert, &auth.tls.client_key) {
+            let mut pem = std::fs::read(cert)?;
+            pem.extend(std::fs::read(key)?);
+            builder = builder.identity(reqwest::Identity::from_pem(&pem)?);
+        }
+        if let Some(ca) = &auth.tls.ca_cert {
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&std::fs::read(ca)?)?);
+        }
+    }
+    Ok(builder.build()?)
+}
+
+/// Headers authenticating a request from UBL. Empty before [`init`].
+pub fn sign_headers(method: &str, path_and_query: &str, body: &[u8]) -> Vec<(&'static str, String)> {
+    match SERVICE_AUTH.get() {
+        Some(auth) => sign_request(&auth.key, SERVICE_ID, method, path_and_query, now_ms(), body),
+        None => Vec::new(),
+    }
+}
+
+/// Sign a request as `service`. Returned as name/value pairs so both the
+/// server (axum) and the client (reqwest) header types can take them.
+pub fn sign_request(
+    key: &SigningKey,
+    service: &str,
+    method: &str,
+    path_and_query: &str,
+    timestamp_ms: i64,
+    body: &[u8],
+) -> Vec<(&'static str, String)> {
+    let message = service_request_bytes(service, method, path_and_query, timestamp_ms, body);
+    let Ok(signature) = sign_with(SignatureMode::Ed25519ph, key, contexts::SERVICE, &message) else {
+        return Vec::new();
+    };
+    vec![
+        (HEADER_SERVICE, service.to_string()),
+        (HEADER_TIMESTAMP, timestamp_ms.to_string()),
+        (HEADER_SIGNATURE, signature),
+    ]
+}
+
+/// Check a request's service signature; returns the sender's service id
+pub fn verify_request(
+    trusted: &[String],
+    headers: &HeaderMap,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+    now_ms: i64,
+) -> Result<String, ServiceAuthError> {
+    let header = |name: &'static str| -> Result<Option<&str>, ServiceAuthError> {
+        headers
+            .get(name)
+            .map(|v| v.to_str().map_err(|_| ServiceAuthError::Malformed(name)))
+            .transpose()
+    };
+    let (service, timestamp, signature) =
+        match (header(HEADER_SERVICE)?, header(HEADER_TIMESTAMP)?, header(HEADER_SIGNATURE)?) {
+            (None, None, None) => return Err(ServiceAuthError::Unsigned),
+            (Some(service), Some(timestamp), Some(signature)) => (service, timestamp, signature),
+            (None, _, _) => return Err(ServiceAuthError::Malformed(HEADER_SERVICE)),
+            (_, None, _) => return Err(ServiceAuthError::Malformed(HEADER_TIMESTAMP)),
+            (_, _, None) => return Err(ServiceAuthError::Malformed(HEADER_SIGNATURE)),
+        };
+    let timestamp: i64 = timestamp
+        .parse()
+        .map_err(|_| ServiceAuthError::Malformed(HEADER_TIMESTAMP))?;
+    if (now_ms - timestamp).abs() > MAX_SKEW_MS {
+        return Err(ServiceAuthError::Stale);
+    }
+
+    let message = service_request_bytes(service, method, path_and_query, timestamp, body);
+    trusted
+        .iter()
+        .any(|pubkey| verify_with(SignatureMode::Ed25519ph, pubkey, contexts::SERVICE, &message, signature).is_ok())
+        .then(|| service.to_string())
+        .ok_or(ServiceAuthError::BadSignature)
+}
+
+/// Check the service signature of a bodyless request inside a handler, for
+/// routes that also take other credentials (`sse_auth`). `Unsigned` before
+/// [`init`] and for requests without the headers, whether or not
+/// `UBL_REQUIRE_SERVICE_AUTH` is set.
+pub fn verify_headers(headers: &HeaderMap, method: &str, path_and_query: &str) -> Result<String, ServiceAuthError> {
+    let Some(auth) = SERVICE_AUTH.get() else {
+        return Err(ServiceAuthError::Unsigned);
+    };
+    verify_request(&auth.office_pubkeys, headers, method, path_and_query, &[], now_ms())
+}
+
+/// Middleware for internal routes called by Office
+pub async fn require_service(req: Request<Body>, next: Next) -> Result<Response, (StatusCode, String)> {
+    let Some(auth) = SERVICE_AUTH.get() else {
+        return Ok(next.run(req).await);
+    };
+
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, auth.max_body_bytes)
+        .await
+        .map_err(|_| (StatusCode::PAYLOAD_TOO_LARGE, "request body too large".to_string()))?;
+    let path = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+    match verify_request(&auth.office_pubkeys, &parts.headers, parts.method.as_str(), path, &bytes, now_ms()) {
+        Ok(_) => {}
+        Err(ServiceAuthError::Unsigned) if !auth.required => {}
+        Err(e) => {
+            warn!(decision = "reject", path, error = %e, "🚫 Service auth failed");
+            return Err((StatusCode::UNAUTHORIZED, format!("service auth: {}", e)));
+        }
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_map(pairs: Vec<(&'static str, String)>) -> HeaderMap {
+        pairs.into_iter().map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap())).collect()
+    }
+
+    #[test]
+    fn test_signed_request_round_trip() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let trusted = vec![ubl_kernel::pubkey_from_signing_key(&key)];
+        let body = br#"{"action":"deploy"}"#;
+        let headers = header_map(sign_request(&key, "office", "POST", "/v1/policy/permit", 1_000_000, body));
+
+        let verify = |headers: &HeaderMap, path: &str, body: &[u8], now: i64| {
+            verify_request(&trusted, headers, "POST", path, body, now)
+        };
+        assert_eq!(verify(&headers, "/v1/policy/permit", body, 1_000_000).unwrap(), "office");
+
+        // Body, path and clock are all bound
+        assert_eq!(verify(&headers, "/v1/policy/permit", b"{}", 1_000_000), Err(ServiceAuthError::BadSignature));
+        assert_eq!(verify(&headers, "/v1/commands/issue", body, 1_000_000), Err(ServiceAuthError::BadSignature));
+        assert_eq!(
+            verify(&headers, "/v1/policy/permit", body, 1_000_000 + MAX_SKEW_MS + 1),
+            Err(ServiceAuthError::Stale)
+        );
+
+        // Untrusted key, missing and partial headers
+        let other = SigningKey::from_bytes(&[8u8; 32]);
+        let forged = header_map(sign_request(&other, "office", "POST", "/v1/policy/permit", 1_000_000, body));
+        assert_eq!(verify(&forged, "/v1/policy/permit", body, 1_000_000), Err(ServiceAuthError::BadSignature));
+        assert_eq!(verify(&HeaderMap::new(), "/v1/policy/permit", body, 1_000_000), Err(ServiceAuthError::Unsigned));
+        let mut partial = headers.clone();
+        partial.remove(HEADER_SIGNATURE);
+        assert_eq!(
+            verify(&partial, "/v1/policy/permit", body, 1_000_000),
+            Err(ServiceAuthError::Malformed(HEADER_SIGNATURE))
+        );
+    }
+}
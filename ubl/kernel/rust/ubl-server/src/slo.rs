@@ -0,0 +1,409 @@
+//! Commit SLOs and error-budget burn alerts
+//!
+//! Every `commit_link` outcome is recorded here ([`record_commit`]) against
+//! two objectives:
+//!
+//! - **latency**: `UBL_SLO_P99_LATENCY_MS` — at most 1% of commits may take
+//!   longer (the p99 target);
+//! - **availability**: `UBL_SLO_AVAILABILITY` — the share of commits that
+//!   must not fail server-side (5xx). Refusals (policy, pacts, physics) are
+//!   the ledger working and do not spend the budget.
+//!
+//! Outcomes are kept per minute for the longest window (6h). Each tick the
+//! [`SloMonitor`] computes the burn rate (share of bad commits / budget) over
+//! 5m, 30m, 1h and 6h and publishes it as `ubl_slo_burn_rate`. Alerts use
+//! the multi-window rule: **page** when 1h and 5m both burn above
+//! `UBL_SLO_FAST_BURN` (default 14.4, 2% of a 30-day budget in an hour),
+//! **ticket** when 6h and 30m burn above `UBL_SLO_SLOW_BURN` (default 6).
+//! A change of alert state (firing or resolved) is POSTed as JSON to
+//! `UBL_SLO_WEBHOOK_URL`; without it alerts are only logged and counted.
+
+use lazy_static::lazy_static;
+use prometheus::{
+    register_gauge_vec, register_histogram, register_int_counter_vec, GaugeVec, Histogram, IntCounterVec,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::timestamps::now_ms;
+
+const MINUTE_MS: i64 = 60_000;
+/// Minutes of history kept: the longest window
+const HISTORY_MINUTES: usize = 360;
+
+lazy_static! {
+    pub static ref COMMIT_LATENCY: Histogram = register_histogram!(
+        "ubl_commit_latency_seconds",
+        "Time spent in commit_link, admission to append",
+        vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+    ).unwrap();
+
+    pub static ref SLO_BURN_RATE: GaugeVec = register_gauge_vec!(
+        "ubl_slo_burn_rate",
+        "Error-budget burn rate by SLO and window (1 = spending exactly the budget)",
+        &["slo", "window"]
+    ).unwrap();
+
+    pub static ref SLO_BUDGET_REMAINING: GaugeVec = register_gauge_vec!(
+        "ubl_slo_error_budget_remaining",
+        "Share of the error budget left over the last 6h, by SLO",
+        &["slo"]
+    ).unwrap();
+
+    pub static ref SLO_ALERTS: IntCounterVec = register_int_counter_vec!(
+        "ubl_slo_alerts_total",
+        "SLO burn alerts fired, by SLO and severity",
+        &["slo", "severity"]
+    ).unwrap();
+
+    static ref HISTORY: Mutex<History> = Mutex::new(History::default());
+}
+
+/// Latency target used by [`record_commit`]; set by [`SloMonitor::new`]
+static P99_LATENCY_MS: AtomicU64 = AtomicU64::new(500);
+
+/// Targets and alert thresholds
+#[derive(Debug, Clone)]
+pub struct SloConfig {
+    /// Latency the p99 of commits must stay under
+    pub p99_latency_ms: u64,
+    /// Share of commits that must not fail server-side
+    pub availability: f64,
+    pub fast_burn: f64,
+    pub slow_burn: f64,
+    /// Receives alert state changes; `None` only logs them
+    pub webhook_url: Option<String>,
+    /// How often burn rates are computed (in seconds)
+    pub interval_secs: u64,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            p99_latency_ms: 500,
+            availability: 0.999,
+            fast_burn: 14.4,
+            slow_burn: 6.0,
+            webhook_url: None,
+            interval_secs: 60,
+        }
+    }
+}
+
+impl SloConfig {
+    /// `UBL_SLO_P99_LATENCY_MS`, `UBL_SLO_AVAILABILITY`, `UBL_SLO_FAST_BURN`,
+    /// `UBL_SLO_SLOW_BURN`, `UBL_SLO_WEBHOOK_URL` and `UBL_SLO_INTERVAL_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            p99_latency_ms: var("UBL_SLO_P99_LATENCY_MS")
+                .and_then(|v| v.parse().ok())
+                .filter(|&ms| ms > 0)
+                .unwrap_or(defaults.p99_latency_ms),
+            availability: var("UBL_SLO_AVAILABILITY")
+                .and_then(|v| v.parse().ok())
+                .filter(|&f: &f64| f > 0.0 && f < 1.0)
+                .unwrap_or(defaults.availability),
+            fast_burn: var("UBL_SLO_FAST_BURN").and_then(|v| v.parse().ok()).unwrap_or(defaults.fast_burn),
+            slow_burn: var("UBL_SLO_SLOW_BURN").and_then(|v| v.parse().ok()).unwrap_or(defaults.slow_burn),
+            webhook_url: var("UBL_SLO_WEBHOOK_URL"),
+            interval_secs: var("UBL_SLO_INTERVAL_SECS")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.interval_secs),
+        }
+    }
+
+    /// Error budget of an SLO: the share of commits allowed to be bad
+    fn budget(&self, slo: Slo) -> f64 {
+        match slo {
+            Slo::Latency => 0.01,
+            Slo::Availability => 1.0 - self.availability,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Slo {
+    Latency,
+    Availability,
+}
+
+impl Slo {
+    const ALL: [Slo; 2] = [Slo::Latency, Slo::Availability];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Slo::Latency => "latency",
+            Slo::Availability => "availability",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Page,
+    Ticket,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Page => "page",
+            Severity::Ticket => "ticket",
+        }
+    }
+}
+
+/// Windows and their length in minutes
+const WINDOWS: [(&str, usize); 4] = [("5m", 5), ("30m", 30), ("1h", 60), ("6h", 360)];
+
+/// (severity, long window, short window): both must burn above the threshold
+const RULES: [(Severity, &str, &str); 2] = [(Severity::Page, "1h", "5m"), (Severity::Ticket, "6h", "30m")];
+
+/// Commit outcomes of one minute
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    minute: i64,
+    total: u64,
+    slow: u64,
+    failed: u64,
+}
+
+/// Ring of per-minute buckets, indexed by minute
+#[derive(Debug)]
+struct History {
+    buckets: Vec<Bucket>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self { buckets: vec![Bucket::default(); HISTORY_MINUTES] }
+    }
+}
+
+impl History {
+    fn record(&mut self, now_ms: i64, slow: bool, failed: bool) {
+        let minute = now_ms / MINUTE_MS;
+        let bucket = &mut self.buckets[minute.rem_euclid(HISTORY_MINUTES as i64) as usize];
+        if bucket.minute != minute {
+            *bucket = Bucket { minute, ..Bucket::default() };
+        }
+        bucket.total += 1;
+        bucket.slow += slow as u64;
+        bucket.failed += failed as u64;
+    }
+
+    /// (total, bad) over the last `minutes` minutes, the current one included
+    fn window(&self, now_ms: i64, minutes: usize, slo: Slo) -> (u64, u64) {
+        let current = now_ms / MINUTE_MS;
+        self.buckets
+            .iter()
+            .filter(|b| b.total > 0 && b.minute <= current && b.minute > current - minutes as i64)
+            .fold((0, 0), |(total, bad), b| {
+                let b_bad = match slo {
+                    Slo::Latency => b.slow,
+                    Slo::Availability => b.failed,
+                };
+                (total + b.total, bad + b_bad)
+            })
+    }
+}
+
+/// Share of bad commits divided by the budget; 0 without commits
+pub fn burn_rate(total: u64, bad: u64, budget: f64) -> f64 {
+    if total == 0 || budget <= 0.0 {
+        return 0.0;
+    }
+    (bad as f64 / total as f64) / budget
+}
+
+/// Record one `commit_link` outcome: how long it took and whether it failed
+/// server-side
+pub fn record_commit(elapsed: Duration, server_error: bool) {
+    COMMIT_LATENCY.observe(elapsed.as_secs_f64());
+    let slow = elapsed.as_millis() as u64 > P99_LATENCY_MS.load(Ordering::Relaxed);
+    if let Ok(mut history) = HISTORY.lock() {
+        history.record(now_ms(), slow, server_error);
+    }
+}
+
+/// Burn rates of one tick, by SLO and window
+pub type BurnRates = BTreeMap<(Slo, &'static str), f64>;
+
+fn burn_rates(history: &History, config: &SloConfig, now_ms: i64) -> BurnRates {
+    let mut rates = BurnRates::new();
+    for slo in Slo::ALL {
+        for (window, minutes) in WINDOWS {
+            let (total, bad) = history.window(now_ms, minutes, slo);
+            rates.insert((slo, window), burn_rate(total, bad, config.budget(slo)));
+        }
+    }
+    rates
+}
+
+/// Alerts whose rule holds for `rates`
+pub fn firing(rates: &BurnRates, config: &SloConfig) -> Vec<(Slo, Severity)> {
+    let mut alerts = Vec::new();
+    for slo in Slo::ALL {
+        for (severity, long, short) in RULES {
+            let threshold = match severity {
+                Severity::Page => config.fast_burn,
+                Severity::Ticket => config.slow_burn,
+            };
+            let above = |window| rates.get(&(slo, window)).copied().unwrap_or(0.0) > threshold;
+            if above(long) && above(short) {
+                alerts.push((slo, severity));
+            }
+        }
+    }
+    alerts
+}
+
+/// Webhook payload for an alert state change
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertEvent {
+    pub slo: Slo,
+    pub severity: Severity,
+    /// `firing` or `resolved`
+    pub status: &'static str,
+    pub burn_rate_long: f64,
+    pub burn_rate_short: f64,
+    pub threshold: f64,
+    pub target: String,
+    pub at_ms: i64,
+}
+
+/// SLO Monitor - publishes burn rates and delivers alert changes
+pub struct SloMonitor {
+    config: SloConfig,
+    client: reqwest::Client,
+    active: Vec<(Slo, Severity)>,
+}
+
+impl SloMonitor {
+    pub fn new(config: SloConfig) -> Self {
+        P99_LATENCY_MS.store(config.p99_latency_ms, Ordering::Relaxed);
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { config, client, active: Vec::new() }
+    }
+
+    /// Start the evaluation loop (runs forever)
+    pub async fn run(mut self) {
+        info!(
+            "📈 SLO monitor: p99 < {}ms, availability {}%, every {}s{}",
+            self.config.p99_latency_ms,
+            self.config.availability * 100.0,
+            self.config.interval_secs,
+            if self.config.webhook_url.is_some() { ", webhook on" } else { "" }
+        );
+        let mut ticker = interval(Duration::from_secs(self.config.interval_secs.max(1)));
+        loop {
+            ticker.tick().await;
+            self.tick().await;
+        }
+    }
+
+    async fn tick(&mut self) {
+        let now = now_ms();
+        let rates = match HISTORY.lock() {
+            Ok(history) => burn_rates(&history, &self.config, now),
+            Err(_) => return,
+        };
+        for ((slo, window), rate) in &rates {
+            SLO_BURN_RATE.with_label_values(&[slo.as_str(), window]).set(*rate);
+            if *window == "6h" {
+                SLO_BUDGET_REMAINING.with_label_values(&[slo.as_str()]).set(1.0 - rate);
+            }
+        }
+
+        let firing = firing(&rates, &self.config);
+        for &(slo, severity) in firing.iter().filter(|a| !self.active.contains(a)) {
+            SLO_ALERTS.with_label_values(&[slo.as_str(), severity.as_str()]).inc();
+            warn!("🔥 SLO {} burning: {} alert", slo.as_str(), severity.as_str());
+            self.notify(self.event(&rates, slo, severity, "firing", now)).await;
+        }
+        for &(slo, severity) in self.active.iter().filter(|a| !firing.contains(a)) {
+            info!("✅ SLO {} {} alert resolved", slo.as_str(), severity.as_str());
+            self.notify(self.event(&rates, slo, severity, "resolved", now)).await;
+        }
+        self.active = firing;
+    }
+
+    fn event(&self, rates: &BurnRates, slo: Slo, severity: Severity, status: &'static str, at_ms: i64) -> AlertEvent {
+        let (_, long, short) = RULES.into_iter().find(|(s, _, _)| *s == severity).unwrap_or(RULES[0]);
+        AlertEvent {
+            slo,
+            severity,
+            status,
+            burn_rate_long: rates.get(&(slo, long)).copied().unwrap_or(0.0),
+            burn_rate_short: rates.get(&(slo, short)).copied().unwrap_or(0.0),
+            threshold: match severity {
+                Severity::Page => self.config.fast_burn,
+                Severity::Ticket => self.config.slow_burn,
+            },
+            target: match slo {
+                Slo::Latency => format!("p99 < {}ms", self.config.p99_latency_ms),
+                Slo::Availability => format!("{}%", self.config.availability * 100.0),
+            },
+            at_ms,
+        }
+    }
+
+    async fn notify(&self, event: AlertEvent) {
+        let Some(url) = &self.config.webhook_url else { return };
+        match self.client.post(url).json(&event).send().await {
+            Ok(resp) if resp.status().is_success() => {}
+            Ok(resp) => warn!("SLO webhook answered {}", resp.status()),
+            Err(e) => error!("SLO webhook failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    #[test]
+    fn test_windows_only_count_recent_minutes() {
+        let mut history = History::default();
+        // 100 commits 10 minutes ago, 1 failed; 10 just now, 5 slow
+        for i in 0..100 {
+            history.record(NOW - 10 * MINUTE_MS, false, i == 0);
+        }
+        for i in 0..10 {
+            history.record(NOW, i < 5, false);
+        }
+        assert_eq!(history.window(NOW, 5, Slo::Latency), (10, 5));
+        assert_eq!(history.window(NOW, 30, Slo::Availability), (110, 1));
+        // A bucket reused after the ring wraps starts empty
+        history.record(NOW + HISTORY_MINUTES as i64 * MINUTE_MS, false, false);
+        assert_eq!(history.window(NOW + HISTORY_MINUTES as i64 * MINUTE_MS, 5, Slo::Latency), (1, 0));
+    }
+
+    #[test]
+    fn test_page_needs_both_windows() {
+        let config = SloConfig::default();
+        assert!((burn_rate(1000, 5, config.budget(Slo::Availability)) - 5.0).abs() < 1e-9);
+        assert_eq!(burn_rate(0, 0, 0.01), 0.0);
+
+        let mut rates = BurnRates::new();
+        rates.insert((Slo::Availability, "1h"), 20.0);
+        rates.insert((Slo::Availability, "5m"), 1.0);
+        assert!(firing(&rates, &config).is_empty());
+        rates.insert((Slo::Availability, "5m"), 30.0);
+        assert_eq!(firing(&rates, &config), [(Slo::Availability, Severity::Page)]);
+    }
+}
@@ -12,6 +12,7 @@ use sqlx::PgPool;
 use std::fs;
 use std::path::PathBuf;
 use tracing::{info, warn, error};
+use crate::timestamps::now_ms;
 
 /// Snapshot metadata + state
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,9 +127,9 @@ pub async fn get_events_since(
             e.link_hash,
             e.previous_hash,
             e.ts_unix_ms,
-            a.data as atom
+            a.atom_data as atom
         FROM ledger_entry e
-        LEFT JOIN ledger_atom a ON e.link_hash = a.hash
+        LEFT JOIN ledger_atom a ON e.link_hash = a.atom_hash
         WHERE e.container_id = $1 AND e.sequence > $2
         ORDER BY e.sequence ASC
         "#,
@@ -172,14 +173,6 @@ pub fn should_snapshot(current_sequence: i64) -> bool {
     current_sequence > 0 && current_sequence % SNAPSHOT_INTERVAL == 0
 }
 
-fn now_ms() -> i64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64
-}
-
 // =============================================================================
 // JOBS PROJECTION STATE (for snapshot)
 // =============================================================================
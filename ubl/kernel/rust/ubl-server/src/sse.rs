@@ -1,46 +1,811 @@
-//! SSE tail endpoint - simplified to emit only cid:seq
-//! Reset: payload MINÚSCULO para evitar limite 8KB do PostgreSQL NOTIFY
+//! SSE tail endpoint
 //!
-//! Emits only: "container_id:sequence" (ex: "repo://tenant/ws:42")
-//! Client fetches full entry via GET /ledger/:container_id/entry/:sequence if needed
+//! Each committed entry is emitted as a typed envelope under a versioned
+//! event name, so clients can react without refetching:
+//!
+//! ```text
+//! event: entry.v1
+//! data: {"v":1,"container_id":"C.Jobs","sequence":42,"entry_hash":"…",
+//!        "hlc":"1760000000000.000000","intent_class":"Observation",
+//!        "event_type":"job.created","tentative_id":null,"client_msg_id":null}
+//! ```
+//!
+//! `tentative_id` is set when the commit settled an optimistic client item;
+//! `client_msg_id` is the client's nonce of a message send.
+//! Incompatible envelope changes get a new event name (`entry.v2`), never a
+//! new shape under an old one.
+//!
+//! Within a container, entries are emitted strictly in `sequence` order: an
+//! entry whose predecessor has not been notified yet (two commits finishing
+//! in the opposite order) is held for up to `REORDER_WINDOW`, then released
+//! past the gap (a commit path that does not notify the tail). `hlc` orders
+//! entries across containers (see `hlc`).
+//!
+//! Legacy format (`GET /ledger/tail?format=legacy`): the original minimal
+//! stream — `entry` events carrying only "container_id:sequence" (ex:
+//! "repo://tenant/ws:42"), followed by a `reconcile` event
+//! (`{container_id, sequence, entry_hash, tentative_id, client_msg_id}`) for
+//! commits with either. Legacy clients fetch the full entry via
+//! GET /ledger/:container_id/entry/:sequence.
+//!
+//! Connection hygiene:
+//! - Heartbeat comments every `UBL_SSE_HEARTBEAT_SECS` (default 15s) so dead
+//!   peers surface as write errors and proxies keep the stream open
+//! - At most `UBL_SSE_MAX_PER_TENANT` (default 100) streams per tenant; extra
+//!   connections get 429
+//! - Streams with no entries for `UBL_SSE_IDLE_SECS` (default 600s) receive a
+//!   terminal `idle` event and are closed (EventSource reconnects)
+//! - A subscriber that falls behind the broadcast buffer receives a terminal
+//!   `lagged` event and is dropped; the TailBus never waits for slow readers
+//!
+//! Access: the stream carries only the containers its caller may follow,
+//! resolved when it opens and checked again while it lasts (`sse_auth`); a
+//! revoked session or ASC gets a terminal `revoked` event.
+//!
+//! Where proxies buffer or cut SSE, `GET /ledger/:container_id/poll` (see
+//! `ledger_routes`) long-polls instead, waiting on the same bus through a
+//! [`TailWaiter`] and counting against the same per-tenant limit.
 
-use axum::{routing::get, response::sse::{Sse, Event}, Router};
-use futures_util::stream::{Stream, StreamExt};
-use tokio_stream::wrappers::BroadcastStream;
-use tokio::sync::broadcast;
+use axum::{
+    extract::Query,
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{sse::{Event, KeepAlive, Sse}, IntoResponse},
+    routing::get,
+    Router,
+};
+use futures_util::stream::Stream;
+use lazy_static::lazy_static;
+use prometheus::{register_int_counter_vec, register_int_gauge_vec, IntCounterVec, IntGaugeVec};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+
+use crate::hlc::Hlc;
+use crate::sse_auth::{self, TailScope};
+
+lazy_static! {
+    pub static ref SSE_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "ubl_sse_connections",
+        "Open SSE connections by tenant",
+        &["tenant"]
+    ).unwrap();
+
+    pub static ref SSE_EVICTIONS: IntCounterVec = register_int_counter_vec!(
+        "ubl_sse_evictions_total",
+        "SSE streams closed by the server, by reason (idle, lagged, limit, revoked)",
+        &["reason"]
+    ).unwrap();
+}
+
+/// Tunables for long-lived SSE streams
+#[derive(Debug, Clone)]
+pub struct SseLimits {
+    pub heartbeat: Duration,
+    pub idle_timeout: Duration,
+    pub max_per_tenant: usize,
+}
+
+impl Default for SseLimits {
+    fn default() -> Self {
+        Self {
+            heartbeat: Duration::from_secs(15),
+            idle_timeout: Duration::from_secs(600),
+            max_per_tenant: 100,
+        }
+    }
+}
+
+impl SseLimits {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let secs = |key: &str, default: Duration| {
+            std::env::var(key)
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        Self {
+            heartbeat: secs("UBL_SSE_HEARTBEAT_SECS", defaults.heartbeat),
+            idle_timeout: secs("UBL_SSE_IDLE_SECS", defaults.idle_timeout),
+            max_per_tenant: std::env::var("UBL_SSE_MAX_PER_TENANT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.max_per_tenant),
+        }
+    }
+
+    pub fn keep_alive(&self) -> KeepAlive {
+        KeepAlive::new().interval(self.heartbeat).text("heartbeat")
+    }
+}
+
+/// Counts open streams per tenant
+#[derive(Clone, Default)]
+pub struct ConnectionRegistry {
+    open: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+/// Open streams per tenant across every registry (ledger tail, gateway, exec
+/// logs), read back from the `ubl_sse_connections` gauge
+pub fn open_streams() -> BTreeMap<String, usize> {
+    use prometheus::core::Collector;
+    SSE_CONNECTIONS
+        .collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .filter_map(|metric| {
+            let tenant = metric.get_label().iter().find(|l| l.get_name() == "tenant")?;
+            let open = metric.get_gauge().get_value() as usize;
+            (open > 0).then(|| (tenant.get_value().to_string(), open))
+        })
+        .collect()
+}
+
+/// Releases the tenant's connection slot when the stream is dropped
+pub struct ConnectionGuard {
+    registry: ConnectionRegistry,
+    tenant: String,
+}
+
+impl ConnectionRegistry {
+    /// Reserve a slot for `tenant`, or `None` if it is at its limit
+    pub fn try_acquire(&self, tenant: &str, max: usize) -> Option<ConnectionGuard> {
+        let mut open = self.open.lock().expect("SSE registry lock poisoned");
+        let count = open.entry(tenant.to_string()).or_insert(0);
+        if *count >= max {
+            SSE_EVICTIONS.with_label_values(&["limit"]).inc();
+            return None;
+        }
+        *count += 1;
+        SSE_CONNECTIONS.with_label_values(&[tenant]).inc();
+        Some(ConnectionGuard { registry: self.clone(), tenant: tenant.to_string() })
+    }
+
+    pub fn count(&self, tenant: &str) -> usize {
+        self.open.lock().expect("SSE registry lock poisoned").get(tenant).copied().unwrap_or(0)
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let mut open = self.registry.open.lock().expect("SSE registry lock poisoned");
+        if let Some(count) = open.get_mut(&self.tenant) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                open.remove(&self.tenant);
+            }
+        }
+        SSE_CONNECTIONS.with_label_values(&[&self.tenant]).dec();
+    }
+}
+
+/// Tenant key for connection limits: `x-tenant-id` header, then `?tenant_id=`
+pub fn tenant_key(headers: &HeaderMap, params: &HashMap<String, String>) -> String {
+    headers
+        .get("x-tenant-id")
+        .and_then(|h| h.to_str().ok())
+        .map(String::from)
+        .or_else(|| params.get("tenant_id").cloned())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// Terminal event sent before the server closes a stream
+pub fn terminal_event(reason: &str, detail: serde_json::Value) -> Event {
+    SSE_EVICTIONS.with_label_values(&[reason]).inc();
+    Event::default()
+        .event(reason)
+        .data(serde_json::json!({ "reason": reason, "detail": detail, "reconnect": true }).to_string())
+}
+
+/// Event name of the current envelope version
+pub const ENTRY_EVENT: &str = "entry.v1";
+/// Envelope version carried in `v`
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// One committed entry on the tail
+#[derive(Debug, Clone)]
+pub struct TailEntry {
+    pub container_id: String,
+    pub sequence: i64,
+    pub entry_hash: String,
+    /// HLC of the entry, for merging tails of several containers
+    pub hlc: Hlc,
+    pub intent_class: String,
+    /// Atom `type`, when the commit carried an atom
+    pub event_type: Option<String>,
+    /// Set when the commit settled an optimistic client item
+    pub tentative_id: Option<String>,
+    /// Client nonce of a message send (atom `client_msg_id`)
+    pub client_msg_id: Option<String>,
+}
+
+impl TailEntry {
+    /// The tail entry of `link`, just appended as `entry`
+    pub fn committed(link: &crate::db::LinkDraft, entry: &crate::db::LedgerEntry) -> Self {
+        let atom_str = |key: &str| link.atom.as_ref().and_then(|a| a.get(key)).and_then(|v| v.as_str()).map(String::from);
+        Self {
+            container_id: link.container_id.clone(),
+            sequence: entry.sequence,
+            entry_hash: entry.entry_hash.clone(),
+            hlc: entry.hlc,
+            intent_class: link.intent_class.clone(),
+            event_type: atom_str("type"),
+            tentative_id: link.tentative_id.clone(),
+            client_msg_id: atom_str("client_msg_id"),
+        }
+    }
+}
+
+/// Longest an entry waits for a missing predecessor before it is emitted anyway
+pub const REORDER_WINDOW: Duration = Duration::from_millis(250);
+
+/// Per-container sequence order of the tail
+#[derive(Debug, Default)]
+struct Reorder {
+    containers: HashMap<String, ContainerOrder>,
+}
+
+#[derive(Debug)]
+struct ContainerOrder {
+    /// Sequence expected next
+    next: i64,
+    /// Entries that overtook `next`
+    held: BTreeMap<i64, TailEntry>,
+    /// Last entry seen, unix ms
+    touched_ms: i64,
+}
+
+impl Reorder {
+    /// Entries ready to emit now that `entry` arrived, in sequence order.
+    /// The first entry seen of a container, and entries older than the
+    /// expected one (late, after a gap was released), pass straight through.
+    fn push(&mut self, entry: TailEntry) -> Vec<TailEntry> {
+        let touched_ms = crate::timestamps::now_ms();
+        let Some(order) = self.containers.get_mut(&entry.container_id) else {
+            let next = entry.sequence + 1;
+            let order = ContainerOrder { next, held: BTreeMap::new(), touched_ms };
+            self.containers.insert(entry.container_id.clone(), order);
+            return vec![entry];
+        };
+        order.touched_ms = touched_ms;
+        if entry.sequence < order.next {
+            return vec![entry];
+        }
+        if entry.sequence > order.next {
+            order.held.insert(entry.sequence, entry);
+            return Vec::new();
+        }
+        order.next += 1;
+        let mut ready = vec![entry];
+        while let Some(held) = order.held.remove(&order.next) {
+            order.next += 1;
+            ready.push(held);
+        }
+        ready
+    }
+
+    /// Give up waiting on `container_id`'s gap: everything held, in order
+    fn release(&mut self, container_id: &str) -> Vec<TailEntry> {
+        let Some(order) = self.containers.get_mut(container_id) else {
+            return Vec::new();
+        };
+        let held = std::mem::take(&mut order.held);
+        if let Some(last) = held.keys().next_back() {
+            order.next = last + 1;
+        }
+        held.into_values().collect()
+    }
+
+    fn is_holding(&self, container_id: &str) -> bool {
+        self.containers.get(container_id).is_some_and(|o| !o.held.is_empty())
+    }
+
+    /// Forget containers with no gap open and no entry for `ttl_ms`; their
+    /// next entry passes straight through, as a first one does
+    fn evict_idle(&mut self, now_ms: i64, ttl_ms: i64) -> usize {
+        let before = self.containers.len();
+        self.containers.retain(|_, o| !o.held.is_empty() || now_ms - o.touched_ms <= ttl_ms);
+        before - self.containers.len()
+    }
+}
+
+/// Typed payload of an `entry.v1` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryEnvelope {
+    pub v: u32,
+    pub container_id: String,
+    pub sequence: i64,
+    pub entry_hash: String,
+    /// Absent from envelopes sent before the clock existed
+    #[serde(default)]
+    pub hlc: Hlc,
+    pub intent_class: String,
+    pub event_type: Option<String>,
+    pub tentative_id: Option<String>,
+    pub client_msg_id: Option<String>,
+}
+
+impl From<&TailEntry> for EntryEnvelope {
+    fn from(entry: &TailEntry) -> Self {
+        Self {
+            v: ENVELOPE_VERSION,
+            container_id: entry.container_id.clone(),
+            sequence: entry.sequence,
+            entry_hash: entry.entry_hash.clone(),
+            hlc: entry.hlc,
+            intent_class: entry.intent_class.clone(),
+            event_type: entry.event_type.clone(),
+            tentative_id: entry.tentative_id.clone(),
+            client_msg_id: entry.client_msg_id.clone(),
+        }
+    }
+}
+
+/// Wire format of a tail stream, chosen with `?format=`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TailFormat {
+    /// `entry.v1` JSON envelopes (default)
+    Typed,
+    /// "cid:seq" `entry` events plus `reconcile` events
+    Legacy,
+}
+
+impl TailFormat {
+    pub fn from_params(params: &HashMap<String, String>) -> Self {
+        match params.get("format").map(String::as_str) {
+            Some("legacy") => Self::Legacy,
+            _ => Self::Typed,
+        }
+    }
+
+    /// SSE events for one entry in this format
+    fn events(self, entry: &TailEntry) -> Vec<Event> {
+        match self {
+            Self::Typed => {
+                let data = serde_json::to_string(&EntryEnvelope::from(entry)).unwrap_or_default();
+                vec![Event::default().event(ENTRY_EVENT).data(data)]
+            }
+            Self::Legacy => {
+                let mut events = vec![Event::default()
+                    .event("entry")
+                    .data(format!("{}:{}", entry.container_id, entry.sequence))];
+                if entry.tentative_id.is_some() || entry.client_msg_id.is_some() {
+                    let data = serde_json::json!({
+                        "container_id": entry.container_id,
+                        "sequence": entry.sequence,
+                        "entry_hash": entry.entry_hash,
+                        "tentative_id": entry.tentative_id,
+                        "client_msg_id": entry.client_msg_id,
+                    });
+                    events.push(Event::default().event("reconcile").data(data.to_string()));
+                }
+                events
+            }
+        }
+    }
+}
+
+/// What a tail subscriber got while waiting
+enum Recv {
+    Entry(TailEntry),
+    Lagged(u64),
+    Closed,
+    TimedOut,
+}
+
+/// Next entry on `rx`, waiting at most `timeout`; shared by SSE streams and
+/// long polls
+async fn recv_within(rx: &mut broadcast::Receiver<TailEntry>, timeout: Duration) -> Recv {
+    match tokio::time::timeout(timeout, rx.recv()).await {
+        Ok(Ok(entry)) => Recv::Entry(entry),
+        Ok(Err(RecvError::Lagged(skipped))) => Recv::Lagged(skipped),
+        Ok(Err(RecvError::Closed)) => Recv::Closed,
+        Err(_) => Recv::TimedOut,
+    }
+}
+
+/// A subscription to one container's tail, taken before reading the ledger
+/// so nothing appended in between is missed
+pub struct TailWaiter {
+    rx: broadcast::Receiver<TailEntry>,
+    container_id: String,
+}
+
+impl TailWaiter {
+    /// Wait until `container_id` gets an entry past `after`, for at most
+    /// `timeout`. True when the ledger may have something new (including when
+    /// this waiter fell behind the bus); false on timeout.
+    pub async fn wait(&mut self, after: i64, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            match recv_within(&mut self.rx, remaining).await {
+                Recv::Entry(entry) if entry.container_id == self.container_id && entry.sequence > after => return true,
+                Recv::Entry(_) => continue,
+                Recv::Lagged(_) => return true,
+                Recv::Closed | Recv::TimedOut => return false,
+            }
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct TailBus {
-    pub tx: broadcast::Sender<(String, String)>, // (container_id, sequence_str)
+    pub tx: broadcast::Sender<TailEntry>,
+    pub limits: SseLimits,
+    pub connections: ConnectionRegistry,
+    reorder: Arc<Mutex<Reorder>>,
 }
 
 impl TailBus {
     pub fn new() -> Self {
+        Self::with_limits(SseLimits::from_env())
+    }
+
+    pub fn with_limits(limits: SseLimits) -> Self {
         let (tx, _rx) = broadcast::channel(1024);
-        Self { tx }
-    }
-    
-    pub fn notify(&self, container_id: String, sequence: i64) {
-        let _ = self.tx.send((container_id, sequence.to_string()));
-    }
-    
-    pub fn stream(&self) -> Pin<Box<dyn Stream<Item = Result<Event, std::convert::Infallible>> + Send>> {
-        let rx = self.tx.subscribe();
-        let s = BroadcastStream::new(rx).map(|msg| {
-            let (cid, seq) = msg.unwrap_or_else(|_| ("_".into(), "0".into()));
-            Ok(Event::default().event("entry").data(format!("{cid}:{seq}")))
-        });
+        Self { tx, limits, connections: ConnectionRegistry::default(), reorder: Arc::default() }
+    }
+
+    pub fn notify(&self, entry: TailEntry) {
+        let container_id = entry.container_id.clone();
+        let mut reorder = self.reorder.lock().expect("SSE reorder lock poisoned");
+        let was_holding = reorder.is_holding(&container_id);
+        for ready in reorder.push(entry) {
+            let _ = self.tx.send(ready);
+        }
+        if was_holding || !reorder.is_holding(&container_id) {
+            return;
+        }
+        // A new gap opened: release it after the window if it is still open
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                let bus = self.clone();
+                runtime.spawn(async move {
+                    tokio::time::sleep(REORDER_WINDOW).await;
+                    bus.release(&container_id);
+                });
+            }
+            Err(_) => {
+                for held in reorder.release(&container_id) {
+                    let _ = self.tx.send(held);
+                }
+            }
+        }
+    }
+
+    fn release(&self, container_id: &str) {
+        let mut reorder = self.reorder.lock().expect("SSE reorder lock poisoned");
+        let held = reorder.release(container_id);
+        if !held.is_empty() {
+            debug!("SSE tail: released {} entries of {} past a sequence gap", held.len(), container_id);
+        }
+        for entry in held {
+            let _ = self.tx.send(entry);
+        }
+    }
+
+    /// Subscribe to one container's tail for a long poll
+    pub fn waiter(&self, container_id: &str) -> TailWaiter {
+        TailWaiter { rx: self.tx.subscribe(), container_id: container_id.to_string() }
+    }
+
+    /// Subscribe to the tail. The guard is held for the life of the stream;
+    /// only entries of containers in `scope` are sent, and `revoked` firing
+    /// ends it.
+    pub fn stream(
+        &self,
+        guard: ConnectionGuard,
+        format: TailFormat,
+        scope: TailScope,
+        revoked: Option<oneshot::Receiver<&'static str>>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Event, std::convert::Infallible>> + Send>> {
+        let mut rx = self.tx.subscribe();
+        let idle = self.limits.idle_timeout;
+        let s = async_stream::stream! {
+            let _guard = guard;
+            let revoked = async move {
+                match revoked {
+                    Some(rx) => match rx.await {
+                        Ok(reason) => reason,
+                        // The check stopped without a verdict: keep going
+                        Err(_) => std::future::pending().await,
+                    },
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::pin!(revoked);
+            // Entries of other containers do not keep the stream alive
+            let mut deadline = tokio::time::Instant::now() + idle;
+            loop {
+                let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+                let next = tokio::select! {
+                    reason = &mut revoked => Err(reason),
+                    recv = recv_within(&mut rx, remaining) => Ok(recv),
+                };
+                match next {
+                    Err(reason) => {
+                        debug!("SSE tail: {} revoked, closing the stream", reason);
+                        yield Ok(terminal_event("revoked", serde_json::json!({ "credential": reason })));
+                        break;
+                    }
+                    Ok(Recv::Entry(entry)) if !scope.permits(&entry.container_id) => continue,
+                    Ok(Recv::Entry(entry)) => {
+                        deadline = tokio::time::Instant::now() + idle;
+                        for event in format.events(&entry) {
+                            yield Ok(event);
+                        }
+                    }
+                    Ok(Recv::Lagged(skipped)) => {
+                        warn!("SSE subscriber lagged by {} entries, dropping", skipped);
+                        yield Ok(terminal_event("lagged", serde_json::json!({ "skipped": skipped })));
+                        break;
+                    }
+                    Ok(Recv::Closed) => break,
+                    Ok(Recv::TimedOut) => {
+                        yield Ok(terminal_event("idle", serde_json::json!({ "idle_secs": idle.as_secs() })));
+                        break;
+                    }
+                }
+            }
+        };
         Box::pin(s)
     }
 }
 
-pub fn sse_router(bus: TailBus) -> Router {
+/// The reorder buffer's containers; a flush emits whatever is held first
+impl crate::maintenance::Maintained for TailBus {
+    fn entries(&self) -> usize {
+        self.reorder.lock().expect("SSE reorder lock poisoned").containers.len()
+    }
+
+    fn evict_idle(&self, now_ms: i64, ttl_ms: i64) -> usize {
+        self.reorder.lock().expect("SSE reorder lock poisoned").evict_idle(now_ms, ttl_ms)
+    }
+
+    fn flush(&self) -> usize {
+        let mut reorder = self.reorder.lock().expect("SSE reorder lock poisoned");
+        let containers: Vec<String> = reorder.containers.keys().cloned().collect();
+        for container_id in &containers {
+            for held in reorder.release(container_id) {
+                let _ = self.tx.send(held);
+            }
+        }
+        reorder.containers.clear();
+        containers.len()
+    }
+}
+
+pub fn sse_router(bus: TailBus, pool: sqlx::PgPool) -> Router {
     Router::new().route("/ledger/tail", get({
         let bus = bus.clone();
-        move || async move {
-            let stream = bus.stream();
-            Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::new())
+        move |method: Method, uri: Uri, headers: HeaderMap, Query(params): Query<HashMap<String, String>>| async move {
+            let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/ledger/tail");
+            let auth = match sse_auth::resolve(&pool, &headers, &method, path, &params).await {
+                Ok(auth) => auth,
+                Err(e) => return e.into_response(),
+            };
+            let tenant = tenant_key(&headers, &params);
+            let Some(guard) = bus.connections.try_acquire(&tenant, bus.limits.max_per_tenant) else {
+                return (StatusCode::TOO_MANY_REQUESTS, "SSE connection limit reached for tenant").into_response();
+            };
+            let format = TailFormat::from_params(&params);
+            debug!("SSE tail client connected (tenant {}, {:?}, {} open)", tenant, format, bus.connections.count(&tenant));
+            let revoked = auth.watch(pool.clone());
+            Sse::new(bus.stream(guard, format, auth.scope, revoked)).keep_alive(bus.limits.keep_alive()).into_response()
         }
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn entry(seq: i64, tentative_id: Option<&str>) -> TailEntry {
+        TailEntry {
+            container_id: "C.Test".into(),
+            sequence: seq,
+            entry_hash: "abc".into(),
+            hlc: Hlc::new(1_760_000_000_000, seq as i32),
+            intent_class: "Observation".into(),
+            event_type: Some("message.created".into()),
+            tentative_id: tentative_id.map(String::from),
+            client_msg_id: None,
+        }
+    }
+
+    #[test]
+    fn test_registry_enforces_per_tenant_limit() {
+        let registry = ConnectionRegistry::default();
+        let a1 = registry.try_acquire("T.A", 2).unwrap();
+        let _a2 = registry.try_acquire("T.A", 2).unwrap();
+        assert!(registry.try_acquire("T.A", 2).is_none());
+        // Other tenants are unaffected
+        assert!(registry.try_acquire("T.B", 2).is_some());
+
+        drop(a1);
+        assert_eq!(registry.count("T.A"), 1);
+        assert_eq!(open_streams().get("T.A"), Some(&1));
+        assert!(registry.try_acquire("T.A", 2).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_lagging_subscriber_gets_terminal_event() {
+        let bus = TailBus::with_limits(SseLimits::default());
+        let guard = bus.connections.try_acquire("T.Lag", 10).unwrap();
+        let mut stream = bus.stream(guard, TailFormat::Typed, TailScope::all(), None);
+
+        // Overflow the 1024-slot broadcast buffer before the reader polls
+        for seq in 0..1100 {
+            bus.notify(entry(seq, None));
+        }
+
+        let mut events = 0;
+        while let Some(Ok(_)) = stream.next().await {
+            events += 1;
+        }
+        // Exactly one terminal event, and the stream ends; the slot is released
+        assert_eq!(events, 1);
+        assert_eq!(bus.connections.count("T.Lag"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_idle_stream_is_evicted() {
+        let bus = TailBus::with_limits(SseLimits {
+            idle_timeout: Duration::from_millis(20),
+            ..SseLimits::default()
+        });
+        let guard = bus.connections.try_acquire("T.Idle", 10).unwrap();
+        let events: Vec<_> = bus.stream(guard, TailFormat::Typed, TailScope::all(), None).collect().await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(bus.connections.count("T.Idle"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_follows_entry() {
+        let bus = TailBus::with_limits(SseLimits {
+            idle_timeout: Duration::from_millis(20),
+            ..SseLimits::default()
+        });
+        let guard = bus.connections.try_acquire("T.Rec", 10).unwrap();
+        let stream = bus.stream(guard, TailFormat::Legacy, TailScope::all(), None);
+
+        bus.notify(entry(1, None));
+        bus.notify(entry(2, Some("tmp_1")));
+
+        let events: Vec<String> = stream.map(|e| format!("{:?}", e.unwrap())).collect().await;
+        // entry, entry, reconcile, idle
+        assert_eq!(events.len(), 4);
+        assert!(events[0].contains("C.Test:1"));
+        assert!(events[2].contains("reconcile") && events[2].contains("tmp_1"));
+        assert!(!events[1].contains("tmp_1"));
+    }
+
+    #[tokio::test]
+    async fn test_typed_envelope_carries_entry() {
+        let bus = TailBus::with_limits(SseLimits {
+            idle_timeout: Duration::from_millis(20),
+            ..SseLimits::default()
+        });
+        let guard = bus.connections.try_acquire("T.Typed", 10).unwrap();
+        let stream = bus.stream(guard, TailFormat::Typed, TailScope::all(), None);
+
+        bus.notify(entry(2, Some("tmp_1")));
+
+        let events: Vec<String> = stream.map(|e| format!("{:?}", e.unwrap())).collect().await;
+        // One entry.v1 event (tentative id inline, no separate reconcile), then idle
+        assert_eq!(events.len(), 2);
+        assert!(events[0].contains(ENTRY_EVENT));
+        for field in ["entry_hash", "abc", "message.created", "tmp_1", "Observation"] {
+            assert!(events[0].contains(field), "{} missing from {}", field, events[0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_carries_only_its_scope() {
+        let bus = TailBus::with_limits(SseLimits {
+            idle_timeout: Duration::from_millis(40),
+            ..SseLimits::default()
+        });
+        let guard = bus.connections.try_acquire("T.Scope", 10).unwrap();
+        let stream = bus.stream(guard, TailFormat::Legacy, TailScope::only(["C.Test".to_string()]), None);
+
+        bus.notify(TailEntry { container_id: "C.Other".into(), ..entry(1, None) });
+        bus.notify(entry(1, None));
+
+        let events: Vec<String> = stream.map(|e| format!("{:?}", e.unwrap())).collect().await;
+        // C.Test:1, idle
+        assert_eq!(events.len(), 2);
+        assert!(events[0].contains("C.Test:1"));
+        assert!(events.iter().all(|e| !e.contains("C.Other")));
+    }
+
+    #[tokio::test]
+    async fn test_revoked_stream_is_closed() {
+        let bus = TailBus::with_limits(SseLimits::default());
+        let guard = bus.connections.try_acquire("T.Revoked", 10).unwrap();
+        let (tx, rx) = oneshot::channel();
+        let mut stream = bus.stream(guard, TailFormat::Typed, TailScope::all(), Some(rx));
+
+        tx.send("session").unwrap();
+        let event = format!("{:?}", stream.next().await.unwrap().unwrap());
+        assert!(event.contains("revoked") && event.contains("session"));
+        assert!(stream.next().await.is_none());
+        assert_eq!(bus.connections.count("T.Revoked"), 0);
+    }
+
+    #[test]
+    fn test_reorder_emits_in_sequence_order() {
+        let seqs = |entries: Vec<TailEntry>| entries.into_iter().map(|e| e.sequence).collect::<Vec<_>>();
+        let mut reorder = Reorder::default();
+        assert_eq!(seqs(reorder.push(entry(4, None))), [4]);
+        // 6 finished committing before 5
+        assert!(reorder.push(entry(6, None)).is_empty());
+        assert!(reorder.is_holding("C.Test"));
+        assert_eq!(seqs(reorder.push(entry(5, None))), [5, 6]);
+
+        // 8 never arrives (a commit path that does not notify): released past the gap
+        assert!(reorder.push(entry(9, None)).is_empty());
+        assert_eq!(seqs(reorder.release("C.Test")), [9]);
+        assert_eq!(seqs(reorder.push(entry(10, None))), [10]);
+        // Too late to be held back for
+        assert_eq!(seqs(reorder.push(entry(8, None))), [8]);
+    }
+
+    #[tokio::test]
+    async fn test_gap_is_released_after_window() {
+        let bus = TailBus::with_limits(SseLimits::default());
+        let mut rx = bus.tx.subscribe();
+        bus.notify(entry(1, None));
+        bus.notify(entry(3, None));
+        assert_eq!(rx.recv().await.unwrap().sequence, 1);
+        assert!(rx.try_recv().is_err());
+        let held = tokio::time::timeout(REORDER_WINDOW * 4, rx.recv()).await.unwrap().unwrap();
+        assert_eq!(held.sequence, 3);
+    }
+
+    #[tokio::test]
+    async fn test_waiter_wakes_on_its_container_only() {
+        let bus = TailBus::with_limits(SseLimits::default());
+
+        let mut waiter = bus.waiter("C.Test");
+        bus.notify(TailEntry { container_id: "C.Other".into(), ..entry(1, None) });
+        assert!(!waiter.wait(0, Duration::from_millis(20)).await);
+
+        let mut waiter = bus.waiter("C.Test");
+        bus.notify(entry(1, None));
+        assert!(waiter.wait(0, Duration::from_millis(20)).await);
+
+        // Already seen: keeps waiting
+        let mut waiter = bus.waiter("C.Test");
+        bus.notify(entry(2, None));
+        assert!(!waiter.wait(2, Duration::from_millis(20)).await);
+    }
+
+    #[test]
+    fn test_format_from_params() {
+        let params = |v: &str| HashMap::from([("format".to_string(), v.to_string())]);
+        assert_eq!(TailFormat::from_params(&HashMap::new()), TailFormat::Typed);
+        assert_eq!(TailFormat::from_params(&params("legacy")), TailFormat::Legacy);
+        assert_eq!(TailFormat::from_params(&params("v1")), TailFormat::Typed);
+
+        let envelope = serde_json::to_value(EntryEnvelope::from(&entry(7, None))).unwrap();
+        assert_eq!(envelope["v"], 1);
+        assert_eq!(envelope["sequence"], 7);
+        assert!(envelope["tentative_id"].is_null());
+
+        // A message send's nonce rides along for optimistic reconciliation
+        let sent = TailEntry { client_msg_id: Some("nonce_1".into()), ..entry(8, None) };
+        let envelope = serde_json::to_value(EntryEnvelope::from(&sent)).unwrap();
+        assert_eq!(envelope["client_msg_id"], "nonce_1");
+        let legacy = TailFormat::Legacy.events(&sent);
+        assert_eq!(legacy.len(), 2);
+    }
+}
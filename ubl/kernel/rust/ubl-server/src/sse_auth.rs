@@ -0,0 +1,214 @@
+//! Who may follow which containers on the ledger tail
+//!
+//! `GET /ledger/tail` resolves the caller once, when the stream opens:
+//!
+//! - a service-signed request (Office) follows every container
+//! - a session of an operator (step-up admin without a tenant) follows every
+//!   container
+//! - a session of an agent follows the containers of its active ASC; an ASC
+//!   without a container scope is not restricted, as for commits
+//! - any other session is refused: members follow their conversations
+//!   through `GET /v1/stream`, which filters by membership
+//!
+//! `?containers=C.Jobs,C.Messenger` narrows the stream; asking for a
+//! container outside the caller's scope is a 403. Entries of other
+//! containers are never sent.
+//!
+//! Sessions and ASCs are checked again every `UBL_SSE_REAUTH_SECS` (default
+//! 30s). A stream whose session expired or was revoked, or whose ASC was
+//! revoked or replaced, gets a terminal `revoked` event and is closed.
+
+use axum::http::{HeaderMap, Method};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::warn;
+use ubl_errors::ErrorCode;
+use uuid::Uuid;
+
+use crate::api_error::ApiError;
+use crate::auth::session_db;
+use crate::projections::scope::{Viewer, ViewerRole};
+use crate::service_auth::{self, ServiceAuthError};
+use crate::{id_db, id_routes};
+
+/// Containers a tail stream may carry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TailScope {
+    /// None: every container
+    containers: Option<BTreeSet<String>>,
+}
+
+impl TailScope {
+    pub fn all() -> Self {
+        Self { containers: None }
+    }
+
+    pub fn only<I: IntoIterator<Item = String>>(containers: I) -> Self {
+        Self { containers: Some(containers.into_iter().collect()) }
+    }
+
+    pub fn permits(&self, container_id: &str) -> bool {
+        self.containers.as_ref().is_none_or(|c| c.contains(container_id))
+    }
+
+    /// The scope for `?containers=`: the requested containers if all of
+    /// them are permitted, else the first that is not
+    pub fn narrow(self, requested: Option<&str>) -> Result<Self, String> {
+        let Some(requested) = requested else {
+            return Ok(self);
+        };
+        let wanted: BTreeSet<String> = requested
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(String::from)
+            .collect();
+        if wanted.is_empty() {
+            return Ok(self);
+        }
+        match wanted.iter().find(|c| !self.permits(c)) {
+            Some(denied) => Err(denied.clone()),
+            None => Ok(Self::only(wanted)),
+        }
+    }
+}
+
+/// What a stream was opened with, checked again while it lasts
+#[derive(Debug, Clone)]
+enum Credential {
+    Service,
+    Session { token: String },
+    Agent { token: String, sid: String, asc_id: Uuid },
+}
+
+/// A resolved subscriber: what it may see, and how to tell it was revoked
+#[derive(Debug, Clone)]
+pub struct TailAuth {
+    pub scope: TailScope,
+    credential: Credential,
+}
+
+fn reauth_interval() -> Duration {
+    let secs = std::env::var("UBL_SSE_REAUTH_SECS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(30);
+    Duration::from_secs(secs.max(1))
+}
+
+/// Resolve the caller of `method path_and_query` (see the module docs)
+pub async fn resolve(
+    pool: &sqlx::PgPool,
+    headers: &HeaderMap,
+    method: &Method,
+    path_and_query: &str,
+    params: &HashMap<String, String>,
+) -> Result<TailAuth, ApiError> {
+    let auth = match service_auth::verify_headers(headers, method.as_str(), path_and_query) {
+        Ok(_) => TailAuth { scope: TailScope::all(), credential: Credential::Service },
+        Err(ServiceAuthError::Unsigned) => resolve_session(pool, headers).await?,
+        Err(e) => return Err(ApiError::new(ErrorCode::Unauthorized, format!("service auth: {}", e))),
+    };
+    let scope = auth.scope.narrow(params.get("containers").map(String::as_str)).map_err(|denied| {
+        ApiError::new(ErrorCode::Forbidden, format!("container {} is outside the caller's tail scope", denied))
+    })?;
+    Ok(TailAuth { scope, ..auth })
+}
+
+async fn resolve_session(pool: &sqlx::PgPool, headers: &HeaderMap) -> Result<TailAuth, ApiError> {
+    let db = |e: sqlx::Error| ApiError::new(ErrorCode::DatabaseError, e.to_string());
+    let token = id_routes::extract_session_token(headers)
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "missing session or service signature"))?;
+    let session = session_db::get_valid(pool, &token)
+        .await
+        .map_err(db)?
+        .ok_or_else(|| ApiError::new(ErrorCode::Unauthorized, "invalid or expired session"))?;
+
+    if Viewer::from_session(&session).role == ViewerRole::Operator {
+        return Ok(TailAuth { scope: TailScope::all(), credential: Credential::Session { token } });
+    }
+    let Some(asc) = id_db::get_active_asc(pool, &session.sid).await.map_err(db)? else {
+        return Err(ApiError::new(ErrorCode::Forbidden, "the ledger tail is for services and agents; use /v1/stream"));
+    };
+    let containers: Vec<String> = asc
+        .scopes
+        .get("containers")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    let scope = if containers.is_empty() { TailScope::all() } else { TailScope::only(containers) };
+    Ok(TailAuth { scope, credential: Credential::Agent { token, sid: session.sid, asc_id: asc.asc_id } })
+}
+
+impl TailAuth {
+    /// Why the credential no longer holds, if it does not
+    async fn revoked(&self, pool: &sqlx::PgPool) -> Result<Option<&'static str>, sqlx::Error> {
+        let (token, asc) = match &self.credential {
+            Credential::Service => return Ok(None),
+            Credential::Session { token } => (token, None),
+            Credential::Agent { token, sid, asc_id } => (token, Some((sid, asc_id))),
+        };
+        if session_db::get_valid(pool, token).await?.is_none() {
+            return Ok(Some("session"));
+        }
+        if let Some((sid, asc_id)) = asc {
+            let active = id_db::get_active_asc(pool, sid).await?;
+            if active.map(|a| a.asc_id) != Some(*asc_id) {
+                return Ok(Some("asc"));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Fires with the reason when the credential is revoked; None for
+    /// services, which are not checked again. The check stops with the stream.
+    pub fn watch(&self, pool: sqlx::PgPool) -> Option<oneshot::Receiver<&'static str>> {
+        if matches!(self.credential, Credential::Service) {
+            return None;
+        }
+        let (mut tx, rx) = oneshot::channel();
+        let auth = self.clone();
+        let every = reauth_interval();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(every);
+            tick.tick().await;
+            loop {
+                tokio::select! {
+                    _ = tx.closed() => return,
+                    _ = tick.tick() => {}
+                }
+                match auth.revoked(&pool).await {
+                    Ok(None) => {}
+                    Ok(Some(reason)) => {
+                        let _ = tx.send(reason);
+                        return;
+                    }
+                    Err(e) => warn!("SSE tail: credential check failed, keeping the stream: {}", e),
+                }
+            }
+        });
+        Some(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_permits_and_narrows() {
+        let all = TailScope::all();
+        assert!(all.permits("C.Anything"));
+        assert_eq!(all.clone().narrow(None).unwrap(), all);
+        assert_eq!(all.clone().narrow(Some(" , ")).unwrap(), all);
+        assert_eq!(all.narrow(Some("C.Jobs")).unwrap(), TailScope::only(["C.Jobs".to_string()]));
+
+        let agent = TailScope::only(["C.Jobs".to_string(), "C.Messenger".to_string()]);
+        assert!(agent.permits("C.Jobs"));
+        assert!(!agent.permits("C.Audit"));
+        let narrowed = agent.clone().narrow(Some("C.Messenger")).unwrap();
+        assert!(narrowed.permits("C.Messenger") && !narrowed.permits("C.Jobs"));
+        assert_eq!(agent.narrow(Some("C.Jobs,C.Audit")).unwrap_err(), "C.Audit");
+    }
+}
@@ -93,6 +93,28 @@ pub async fn get_tenant(pool: &PgPool, tenant_id: &str) -> Result<Option<Tenant>
     }))
 }
 
+/// Merge keys into tenant settings; returns the updated settings
+pub async fn merge_settings(
+    pool: &PgPool,
+    tenant_id: &str,
+    patch: &serde_json::Value,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        UPDATE id_tenant
+        SET settings = settings || $2::jsonb
+        WHERE tenant_id = $1 AND status != 'deleted'
+        RETURNING settings
+        "#
+    )
+    .bind(tenant_id)
+    .bind(patch)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row.map(|r| r.get("settings")))
+}
+
 // ============================================================================
 // MEMBER OPERATIONS
 // ============================================================================
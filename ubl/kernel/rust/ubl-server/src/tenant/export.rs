@@ -0,0 +1,817 @@
+//! # Tenant Data Export
+//!
+//! A tenant leaving the platform takes everything with it. The owner starts
+//! an export; a background task bundles:
+//!
+//! - `ledger/<container>.json`: every container holding an entry whose atom
+//!   names the tenant (`tenant_id`), or a `repo://<tenant>/…` container. The
+//!   whole chain is exported so it verifies from genesis, and it is verified
+//!   (`chain_check::verify_entries`) before it is packed; atoms are included
+//!   for the tenant's entries only, opened when the container is encrypted.
+//! - `projections/<table>.json`: the tenant's rows of each projection.
+//! - `identity/*.json`: the tenant, its members and their public credentials.
+//!   Sessions and challenges are not exported.
+//! - `blobs/<hex>` and `blobs/index.json`: receipt artifacts of commands
+//!   approved by a member.
+//!
+//! The archive is `{"manifest": …, "files": {<path>: <base64>}}`; the
+//! manifest lists each file with its BLAKE3 hash and size, and the chain
+//! heads. It is sealed with XChaCha20-Poly1305 under a random key bound to
+//! the export id (AAD `ubl:tenant_export\n<export_id>`). The key is in the
+//! start response only; the server never stores it, so a running export
+//! does not survive a restart.
+//!
+//! The sealed archive goes to the blob store and a `tenant.exported`
+//! observation (archive and manifest hashes) is committed to C.Tenant.
+//!
+//! ## Routes
+//! - `POST /tenant/exports` - Start an export (owner); returns the archive key
+//! - `GET /tenant/exports/:export_id` - Status, manifest and download URL
+//! - `GET /tenant/exports/:export_id/download?token=` - The sealed archive
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::{engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{PgPool, Row};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{error, info, warn};
+use ubl_kernel::Zeroizing;
+
+use super::db;
+use super::routes::caller_tenant;
+use super::types::MemberRole;
+use crate::atom_crypto;
+use crate::blob_store::{self, BlobStore};
+use crate::chain_check::{self, ChainFault, StoredEntry};
+use crate::db::{LedgerEntry, LinkDraft};
+use crate::timestamps::now_ms;
+use crate::AppState;
+
+/// `format` of the manifest
+pub const FORMAT: &str = "ubl.tenant_export.v1";
+/// Atom type of the observation recording a finished export
+pub const EXPORTED_TYPE: &str = "tenant.exported";
+const TENANT_CONTAINER: &str = "C.Tenant";
+const ACTOR: &str = "system:tenant_export";
+/// Ledger entries read per query
+const PAGE: i64 = 1000;
+
+/// Snapshots of the tenant's rows: (archive path, FROM clause with the tenant
+/// as `$1`, aliased `t`)
+const SNAPSHOTS: &[(&str, &str)] = &[
+    ("projections/projection_conversations.json", "projection_conversations t WHERE COALESCE(t.tenant_id, 'default') = $1"),
+    (
+        "projections/projection_messages.json",
+        "projection_messages t WHERE t.conversation_id IN \
+         (SELECT conversation_id FROM projection_conversations WHERE COALESCE(tenant_id, 'default') = $1)",
+    ),
+    (
+        "projections/message_content.json",
+        "message_content t WHERE t.message_id IN \
+         (SELECT m.message_id FROM projection_messages m JOIN projection_conversations c USING (conversation_id) \
+          WHERE COALESCE(c.tenant_id, 'default') = $1)",
+    ),
+    ("projections/projection_jobs.json", "projection_jobs t WHERE t.tenant_id = $1"),
+    ("projections/projection_job_events.json", "projection_job_events t WHERE t.tenant_id = $1"),
+    ("projections/projection_job_artifacts.json", "projection_job_artifacts t WHERE t.tenant_id = $1"),
+    ("projections/projection_presence.json", "projection_presence t WHERE t.tenant_id = $1"),
+    ("projections/projection_timeline_items.json", "projection_timeline_items t WHERE t.tenant_id = $1"),
+    ("projections/projection_observations.json", "projection_observations t WHERE t.tenant_id = $1"),
+    ("projections/projection_broadcast_deliveries.json", "projection_broadcast_deliveries t WHERE t.tenant_id = $1"),
+    ("projections/projection_obligations.json", "projection_obligations t WHERE t.tenant_id = $1"),
+    ("projections/projection_conversation_summaries.json", "projection_conversation_summaries t WHERE t.tenant_id = $1"),
+    ("projections/projection_redactions.json", "projection_redactions t WHERE t.tenant_id = $1"),
+    ("projections/card_issuances.json", "card_issuances t WHERE t.tenant_id = $1"),
+    ("projections/gateway_scheduled_messages.json", "gateway_scheduled_messages t WHERE t.tenant_id = $1"),
+    ("identity/tenant.json", "id_tenant t WHERE t.tenant_id = $1"),
+    (
+        "identity/members.json",
+        "(SELECT m.*, s.kind, s.display_name, s.status FROM id_tenant_member m JOIN id_subject s USING (sid) \
+          WHERE m.tenant_id = $1) t",
+    ),
+    (
+        "identity/credentials.json",
+        "(SELECT c.sid, c.credential_kind, c.credential_id, c.public_key, c.key_version, c.created_at \
+          FROM id_credential c JOIN id_tenant_member m ON m.sid = c.sid WHERE m.tenant_id = $1) t",
+    ),
+    (
+        "identity/webauthn_credentials.json",
+        "(SELECT w.cred_id, w.user_id, w.public_key_cbor, w.transports, w.created_at_ms \
+          FROM id_webauthn_credentials w JOIN id_tenant_member m ON m.sid = w.user_id WHERE m.tenant_id = $1) t",
+    ),
+];
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("database error: {0}")]
+    Db(#[from] sqlx::Error),
+
+    #[error("chain of {container_id} does not verify: {fault}")]
+    Chain { container_id: String, fault: ChainFault },
+
+    #[error("atom {atom_hash} could not be opened: {reason}")]
+    Atom { atom_hash: String, reason: String },
+
+    #[error("blob {hash}: {reason}")]
+    Blob { hash: String, reason: String },
+
+    #[error("export exceeds {max} bytes")]
+    TooLarge { max: usize },
+
+    #[error("observation not committed: {0}")]
+    Commit(String),
+}
+
+// ============================================================================
+// CONFIG
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct ExportConfig {
+    /// Largest archive (before sealing) an export may produce
+    pub max_bytes: usize,
+    /// Lifetime of a download URL
+    pub url_ttl_ms: i64,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1024 * 1024 * 1024,
+            url_ttl_ms: 24 * 60 * 60 * 1000,
+        }
+    }
+}
+
+impl ExportConfig {
+    /// `UBL_EXPORT_MAX_BYTES`, `UBL_EXPORT_URL_TTL_SECS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_bytes: std::env::var("UBL_EXPORT_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.max_bytes),
+            url_ttl_ms: std::env::var("UBL_EXPORT_URL_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .map(|secs| secs * 1000)
+                .unwrap_or(defaults.url_ttl_ms),
+        }
+    }
+}
+
+// ============================================================================
+// ARCHIVE
+// ============================================================================
+
+/// A file of the archive, as listed in the manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: String,
+    /// `blake3:<hex>` of the file bytes
+    pub hash: String,
+    pub bytes: usize,
+}
+
+/// Head of an exported chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedChain {
+    pub container_id: String,
+    pub path: String,
+    pub head_sequence: i64,
+    pub head_hash: String,
+    /// Entries of the tenant, exported with their atom
+    pub tenant_entries: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub format: String,
+    pub export_id: String,
+    pub tenant_id: String,
+    pub created_at_ms: i64,
+    pub containers: Vec<ExportedChain>,
+    pub files: Vec<ManifestFile>,
+}
+
+/// Files of an export by path, within the size limit
+pub struct Bundle {
+    files: BTreeMap<String, Vec<u8>>,
+    size: usize,
+    max_bytes: usize,
+}
+
+impl Bundle {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { files: BTreeMap::new(), size: 0, max_bytes }
+    }
+
+    pub fn add(&mut self, path: String, bytes: Vec<u8>) -> Result<(), ExportError> {
+        self.size += bytes.len();
+        if self.size > self.max_bytes {
+            return Err(ExportError::TooLarge { max: self.max_bytes });
+        }
+        self.files.insert(path, bytes);
+        Ok(())
+    }
+
+    pub fn add_json<T: Serialize>(&mut self, path: String, value: &T) -> Result<(), ExportError> {
+        let bytes = serde_json::to_vec_pretty(value).expect("export files serialize");
+        self.add(path, bytes)
+    }
+
+    /// The manifest and the archive bytes
+    pub fn pack(
+        self,
+        export_id: &str,
+        tenant_id: &str,
+        containers: Vec<ExportedChain>,
+    ) -> (ExportManifest, Vec<u8>) {
+        let manifest = ExportManifest {
+            format: FORMAT.into(),
+            export_id: export_id.into(),
+            tenant_id: tenant_id.into(),
+            created_at_ms: now_ms(),
+            containers,
+            files: self
+                .files
+                .iter()
+                .map(|(path, bytes)| ManifestFile {
+                    path: path.clone(),
+                    hash: blob_store::content_hash(bytes),
+                    bytes: bytes.len(),
+                })
+                .collect(),
+        };
+        let files: BTreeMap<&String, String> = self.files.iter().map(|(path, bytes)| (path, STANDARD.encode(bytes))).collect();
+        let archive = serde_json::to_vec(&json!({ "manifest": manifest, "files": files })).expect("archive serializes");
+        (manifest, archive)
+    }
+}
+
+fn archive_aad(export_id: &str) -> Vec<u8> {
+    format!("ubl:tenant_export\n{}", export_id).into_bytes()
+}
+
+/// Seal an archive under the export's key
+pub fn seal_archive(key: &[u8; 32], export_id: &str, archive: &[u8]) -> Vec<u8> {
+    atom_crypto::seal_bytes(key, &archive_aad(export_id), archive)
+}
+
+/// Archive path of a container's chain
+pub fn chain_path(container_id: &str) -> String {
+    let name: String = container_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    format!("ledger/{}.json", name)
+}
+
+/// Whether an entry belongs to the tenant: its atom names it, or the
+/// container is one of the tenant's repositories
+pub fn owned_by(tenant_id: &str, container_id: &str, atom: Option<&Value>) -> bool {
+    let repo_owner = container_id.strip_prefix("repo://").and_then(|repo| repo.split('/').next());
+    repo_owner == Some(tenant_id)
+        || atom.and_then(|a| a.get("tenant_id")).and_then(Value::as_str) == Some(tenant_id)
+}
+
+/// A chain entry as exported; `atom` only on the tenant's entries
+#[derive(Debug, Serialize)]
+struct ExportedEntry {
+    sequence: i64,
+    link_hash: String,
+    previous_hash: String,
+    entry_hash: String,
+    ts_unix_ms: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    atom: Option<Value>,
+}
+
+// ============================================================================
+// EXPORT TASK
+// ============================================================================
+
+#[derive(Clone)]
+struct ExportState {
+    app: AppState,
+    blobs: Arc<BlobStore>,
+    config: ExportConfig,
+}
+
+/// A finished export
+struct Delivered {
+    archive_hash: String,
+    archive_bytes: usize,
+    manifest: ExportManifest,
+    entry_hash: String,
+}
+
+async fn run(state: ExportState, export_id: String, tenant_id: String, requested_by: String, key: Zeroizing<[u8; 32]>) {
+    let pool = state.app.pool.clone();
+    let result = match build(&state, &export_id, &tenant_id, &requested_by, &key).await {
+        Ok(done) => {
+            info!(
+                "📦 Tenant {} exported: {} ({} files, {} bytes)",
+                tenant_id,
+                export_id,
+                done.manifest.files.len(),
+                done.archive_bytes
+            );
+            sqlx::query(
+                r#"
+                UPDATE tenant_exports
+                SET status = 'ready', archive_hash = $2, archive_bytes = $3, manifest = $4,
+                    entry_hash = $5, completed_at_ms = $6
+                WHERE export_id = $1
+                "#,
+            )
+            .bind(&export_id)
+            .bind(&done.archive_hash)
+            .bind(done.archive_bytes as i64)
+            .bind(serde_json::to_value(&done.manifest).unwrap_or(Value::Null))
+            .bind(&done.entry_hash)
+            .bind(now_ms())
+            .execute(&pool)
+            .await
+        }
+        Err(e) => {
+            error!("❌ Tenant {} export {} failed: {}", tenant_id, export_id, e);
+            sqlx::query("UPDATE tenant_exports SET status = 'failed', error = $2, completed_at_ms = $3 WHERE export_id = $1")
+                .bind(&export_id)
+                .bind(e.to_string())
+                .bind(now_ms())
+                .execute(&pool)
+                .await
+        }
+    };
+    if let Err(e) = result {
+        error!("Failed to record outcome of export {}: {}", export_id, e);
+    }
+}
+
+async fn build(
+    state: &ExportState,
+    export_id: &str,
+    tenant_id: &str,
+    requested_by: &str,
+    key: &[u8; 32],
+) -> Result<Delivered, ExportError> {
+    let pool = &state.app.pool;
+    let mut bundle = Bundle::new(state.config.max_bytes);
+
+    let containers = export_ledger(pool, tenant_id, &mut bundle).await?;
+    for (path, from) in SNAPSHOTS {
+        let rows: Value = sqlx::query_scalar(&format!("SELECT COALESCE(jsonb_agg(to_jsonb(t)), '[]'::jsonb) FROM {}", from))
+            .bind(tenant_id)
+            .fetch_one(pool)
+            .await?;
+        bundle.add_json(path.to_string(), &rows)?;
+    }
+    export_blobs(pool, &state.blobs, tenant_id, &mut bundle).await?;
+
+    let (manifest, archive) = bundle.pack(export_id, tenant_id, containers);
+    let sealed = seal_archive(key, export_id, &archive);
+    let archive_bytes = sealed.len();
+    let blobs = state.blobs.clone();
+    let archive_hash = tokio::task::spawn_blocking(move || blobs.put(&sealed))
+        .await
+        .map_err(|e| ExportError::Blob { hash: "archive".into(), reason: e.to_string() })?
+        .map_err(|e| ExportError::Blob { hash: "archive".into(), reason: e.to_string() })?;
+
+    let manifest_bytes = ubl_atom::canonicalize(&serde_json::to_value(&manifest).expect("manifest serializes"))
+        .map_err(|e| ExportError::Commit(e.to_string()))?;
+    let atom = json!({
+        "archive_hash": archive_hash,
+        "containers": manifest.containers.len(),
+        "export_id": export_id,
+        "files": manifest.files.len(),
+        "manifest_hash": blob_store::content_hash(&manifest_bytes),
+        "requested_by": requested_by,
+        "tenant_id": tenant_id,
+        "type": EXPORTED_TYPE,
+    });
+    let entry_hash = commit_observation(&state.app, atom).await?;
+
+    Ok(Delivered { archive_hash, archive_bytes, manifest, entry_hash })
+}
+
+/// Chains holding the tenant's entries, verified, into `ledger/`
+async fn export_ledger(pool: &PgPool, tenant_id: &str, bundle: &mut Bundle) -> Result<Vec<ExportedChain>, ExportError> {
+    let container_ids: Vec<String> = sqlx::query_scalar("SELECT DISTINCT container_id FROM ledger_entry ORDER BY container_id")
+        .fetch_all(pool)
+        .await?;
+
+    let mut chains = Vec::new();
+    for container_id in container_ids {
+        let mut headers: Vec<StoredEntry> = Vec::new();
+        let mut entries: Vec<ExportedEntry> = Vec::new();
+        let mut tenant_entries = 0;
+        loop {
+            let after = headers.last().map_or(0, |e| e.sequence);
+            let rows = sqlx::query(
+                r#"
+                SELECT e.sequence, e.link_hash, e.previous_hash, e.entry_hash, e.ts_unix_ms, a.atom_data
+                FROM ledger_entry e
+                LEFT JOIN ledger_atom a ON a.atom_hash = e.link_hash
+                WHERE e.container_id = $1 AND e.sequence > $2
+                ORDER BY e.sequence
+                LIMIT $3
+                "#,
+            )
+            .bind(&container_id)
+            .bind(after)
+            .bind(PAGE)
+            .fetch_all(pool)
+            .await?;
+            if rows.is_empty() {
+                break;
+            }
+            for row in rows {
+                let header = StoredEntry {
+                    sequence: row.get("sequence"),
+                    link_hash: row.get("link_hash"),
+                    previous_hash: row.get("previous_hash"),
+                    entry_hash: row.get("entry_hash"),
+                    ts_unix_ms: row.get("ts_unix_ms"),
+                };
+                let atom = match row.get::<Option<Value>, _>("atom_data") {
+                    Some(stored) => Some(
+                        atom_crypto::open_atom(pool, &container_id, &header.link_hash, &stored)
+                            .await
+                            .map_err(|e| ExportError::Atom { atom_hash: header.link_hash.clone(), reason: e.to_string() })?,
+                    ),
+                    None => None,
+                };
+                let owned = owned_by(tenant_id, &container_id, atom.as_ref());
+                tenant_entries += usize::from(owned);
+                entries.push(ExportedEntry {
+                    sequence: header.sequence,
+                    link_hash: header.link_hash.clone(),
+                    previous_hash: header.previous_hash.clone(),
+                    entry_hash: header.entry_hash.clone(),
+                    ts_unix_ms: header.ts_unix_ms,
+                    atom: atom.filter(|_| owned),
+                });
+                headers.push(header);
+            }
+        }
+        if tenant_entries == 0 {
+            continue;
+        }
+
+        chain_check::verify_entries(&container_id, &headers)
+            .map_err(|fault| ExportError::Chain { container_id: container_id.clone(), fault })?;
+        let head = headers.last().expect("a chain with tenant entries is not empty");
+        let chain = ExportedChain {
+            container_id: container_id.clone(),
+            path: chain_path(&container_id),
+            head_sequence: head.sequence,
+            head_hash: head.entry_hash.clone(),
+            tenant_entries,
+        };
+        bundle.add_json(chain.path.clone(), &json!({ "container_id": container_id, "entries": entries }))?;
+        chains.push(chain);
+    }
+    Ok(chains)
+}
+
+/// Receipt artifacts of commands approved by a member, into `blobs/`
+async fn export_blobs(pool: &PgPool, blobs: &Arc<BlobStore>, tenant_id: &str, bundle: &mut Bundle) -> Result<(), ExportError> {
+    let artifacts: Vec<Value> = sqlx::query_scalar(
+        r#"
+        SELECT jsonb_build_object('command_id', a.command_id, 'name', a.name, 'content_hash', a.content_hash,
+                                  'size_bytes', a.size_bytes, 'media_type', a.media_type)
+        FROM console_receipt_artifacts a
+        JOIN console_commands c ON c.command_id = a.command_id
+        JOIN console_permits p ON p.jti = c.permit_jti
+        JOIN id_tenant_member m ON m.sid = p.approver AND m.tenant_id = $1
+        WHERE a.uploaded_at_ms IS NOT NULL
+        ORDER BY a.command_id, a.name
+        "#,
+    )
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+
+    for hash in artifacts.iter().filter_map(|a| a["content_hash"].as_str()) {
+        let Some(hex) = blob_store::parse_hash(hash) else { continue };
+        let path = format!("blobs/{}", hex);
+        if bundle.files.contains_key(&path) {
+            continue;
+        }
+        let (store, owned_hash) = (blobs.clone(), hash.to_string());
+        let bytes = tokio::task::spawn_blocking(move || store.get(&owned_hash))
+            .await
+            .map_err(|e| ExportError::Blob { hash: hash.into(), reason: e.to_string() })?
+            .map_err(|e| ExportError::Blob { hash: hash.into(), reason: e.to_string() })?
+            .ok_or_else(|| ExportError::Blob { hash: hash.into(), reason: "missing from the blob store".into() })?;
+        bundle.add(path, bytes)?;
+    }
+    bundle.add_json("blobs/index.json".into(), &artifacts)
+}
+
+/// Commit the `tenant.exported` observation to C.Tenant; returns the entry hash
+async fn commit_observation(state: &AppState, atom: Value) -> Result<String, ExportError> {
+    let atom_bytes = ubl_atom::canonicalize(&atom).map_err(|e| ExportError::Commit(format!("CanonicalizeError: {}", e)))?;
+    let container_state = match state.ledger.get_state(TENANT_CONTAINER).await {
+        Ok(entry) => entry,
+        Err(sqlx::Error::RowNotFound) => LedgerEntry {
+            container_id: TENANT_CONTAINER.to_string(),
+            sequence: 0,
+            entry_hash: "0x00".to_string(),
+            previous_hash: "0x00".to_string(),
+            link_hash: "0x00".to_string(),
+            ts_unix_ms: 0,
+            hlc: crate::hlc::Hlc::ZERO,
+        },
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut link = LinkDraft {
+        version: 1,
+        container_id: TENANT_CONTAINER.to_string(),
+        expected_sequence: container_state.sequence + 1,
+        previous_hash: container_state.entry_hash,
+        atom_hash: blake3::hash(&atom_bytes).to_hex().to_string(),
+        atom: Some(atom),
+        intent_class: "Observation".to_string(),
+        physics_delta: "0".to_string(),
+        author_pubkey: String::new(), // Set by sign_link_draft
+        signature: String::new(),     // Set by sign_link_draft
+        pact: None,
+        tentative_id: None,
+    };
+    crate::messenger_v1::sign_link_draft(&mut link);
+
+    crate::commit_link(state, link, ACTOR)
+        .await
+        .map(|success| success.entry.entry_hash)
+        .map_err(|e| ExportError::Commit(e.message))
+}
+
+/// Mark exports left running by a previous process as failed: their key is gone
+pub async fn fail_interrupted(pool: &PgPool) {
+    match sqlx::query(
+        "UPDATE tenant_exports SET status = 'failed', error = 'interrupted by a restart', completed_at_ms = $1 WHERE status = 'running'",
+    )
+    .bind(now_ms())
+    .execute(pool)
+    .await
+    {
+        Ok(done) if done.rows_affected() > 0 => warn!("📦 {} tenant exports interrupted by the restart", done.rows_affected()),
+        Ok(_) => {}
+        Err(e) => warn!("Could not fail interrupted tenant exports: {}", e),
+    }
+}
+
+// ============================================================================
+// ROUTES
+// ============================================================================
+
+type RouteError = (StatusCode, Json<Value>);
+
+fn db_error(e: sqlx::Error) -> RouteError {
+    error!("Tenant export database error: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+}
+
+/// Only the owner takes the tenant's data out
+async fn caller_owner(pool: &PgPool, headers: &HeaderMap) -> Result<(String, String), RouteError> {
+    let (sid, tenant_id) = caller_tenant(pool, headers).await?;
+    match db::get_member_role(pool, &tenant_id, &sid).await.map_err(db_error)? {
+        Some(MemberRole::Owner) => Ok((sid, tenant_id)),
+        _ => Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Only the owner can export tenant data" })))),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct StartExportResponse {
+    export_id: String,
+    status: &'static str,
+    /// Hex key the archive is sealed with; shown once, never stored
+    archive_key: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportStatusResponse {
+    export_id: String,
+    tenant_id: String,
+    status: String,
+    requested_by: String,
+    requested_at_ms: i64,
+    completed_at_ms: Option<i64>,
+    archive_hash: Option<String>,
+    archive_bytes: Option<i64>,
+    manifest: Option<Value>,
+    /// Entry hash of the `tenant.exported` observation
+    entry_hash: Option<String>,
+    download_url: Option<String>,
+    download_expires_at_ms: Option<i64>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DownloadParams {
+    token: String,
+}
+
+/// POST /tenant/exports - Start an export of the caller's tenant
+async fn start_export(
+    State(state): State<ExportState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<StartExportResponse>), RouteError> {
+    let pool = &state.app.pool;
+    let (sid, tenant_id) = caller_owner(pool, &headers).await?;
+
+    let export_id = format!("exp_{}", uuid::Uuid::new_v4().simple());
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO tenant_exports (export_id, tenant_id, requested_by, status, requested_at_ms)
+        VALUES ($1, $2, $3, 'running', $4)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(&export_id)
+    .bind(&tenant_id)
+    .bind(&sid)
+    .bind(now_ms())
+    .execute(pool)
+    .await
+    .map_err(db_error)?;
+    if inserted.rows_affected() == 0 {
+        return Err((StatusCode::CONFLICT, Json(json!({ "error": "An export of this tenant is already running" }))));
+    }
+
+    let key = Zeroizing::new(crate::crypto::rand_bytes_32());
+    let archive_key = hex::encode(key.as_slice());
+    info!("📦 Tenant {} export {} started by {}", tenant_id, export_id, sid);
+    tokio::spawn(run(state.clone(), export_id.clone(), tenant_id, sid, key));
+
+    Ok((StatusCode::ACCEPTED, Json(StartExportResponse { export_id, status: "running", archive_key })))
+}
+
+/// GET /tenant/exports/:export_id - Status; renews an expired download URL
+async fn get_export(
+    State(state): State<ExportState>,
+    Path(export_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ExportStatusResponse>, RouteError> {
+    let pool = &state.app.pool;
+    let (_, tenant_id) = caller_owner(pool, &headers).await?;
+
+    let row = sqlx::query("SELECT * FROM tenant_exports WHERE export_id = $1 AND tenant_id = $2")
+        .bind(&export_id)
+        .bind(&tenant_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({ "error": "Export not found" }))))?;
+
+    let status: String = row.get("status");
+    let mut download: Option<(String, i64)> = row
+        .get::<Option<String>, _>("download_token")
+        .zip(row.get::<Option<i64>, _>("download_exp_ms"))
+        .filter(|(_, exp)| *exp > now_ms());
+    if status == "ready" && download.is_none() {
+        let token = URL_SAFE_NO_PAD.encode(crate::crypto::rand_bytes_32());
+        let exp = now_ms() + state.config.url_ttl_ms;
+        sqlx::query("UPDATE tenant_exports SET download_token = $2, download_exp_ms = $3 WHERE export_id = $1")
+            .bind(&export_id)
+            .bind(&token)
+            .bind(exp)
+            .execute(pool)
+            .await
+            .map_err(db_error)?;
+        download = Some((token, exp));
+    }
+    let download = download.filter(|_| status == "ready");
+
+    Ok(Json(ExportStatusResponse {
+        download_url: download.as_ref().map(|(token, _)| format!("/tenant/exports/{}/download?token={}", export_id, token)),
+        download_expires_at_ms: download.map(|(_, exp)| exp),
+        export_id,
+        tenant_id,
+        status,
+        requested_by: row.get("requested_by"),
+        requested_at_ms: row.get("requested_at_ms"),
+        completed_at_ms: row.get("completed_at_ms"),
+        archive_hash: row.get("archive_hash"),
+        archive_bytes: row.get("archive_bytes"),
+        manifest: row.get("manifest"),
+        entry_hash: row.get("entry_hash"),
+        error: row.get("error"),
+    }))
+}
+
+/// GET /tenant/exports/:export_id/download?token= - The sealed archive
+async fn download_export(
+    State(state): State<ExportState>,
+    Path(export_id): Path<String>,
+    Query(params): Query<DownloadParams>,
+) -> Response {
+    let fail = |status: StatusCode, error: &str| (status, Json(json!({ "error": error }))).into_response();
+
+    let row = sqlx::query(
+        "SELECT archive_hash, download_token, download_exp_ms FROM tenant_exports WHERE export_id = $1 AND status = 'ready'",
+    )
+    .bind(&export_id)
+    .fetch_optional(&state.app.pool)
+    .await;
+    let row = match row {
+        Ok(Some(row)) => row,
+        Ok(None) => return fail(StatusCode::NOT_FOUND, "Export not found"),
+        Err(e) => return db_error(e).into_response(),
+    };
+
+    let token: Option<String> = row.get("download_token");
+    let exp: Option<i64> = row.get("download_exp_ms");
+    if !token.is_some_and(|t| ubl_kernel::ct_eq(params.token.as_bytes(), t.as_bytes())) {
+        return fail(StatusCode::FORBIDDEN, "Invalid download token");
+    }
+    if exp.is_none_or(|exp| now_ms() > exp) {
+        return fail(StatusCode::FORBIDDEN, "Download URL expired");
+    }
+
+    let archive_hash: String = row.get("archive_hash");
+    let (blobs, hash) = (state.blobs.clone(), archive_hash.clone());
+    let bytes = match tokio::task::spawn_blocking(move || blobs.get(&hash)).await {
+        Ok(Ok(Some(bytes))) => bytes,
+        Ok(Ok(None)) => return fail(StatusCode::NOT_FOUND, "Archive missing"),
+        Ok(Err(e)) => return fail(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        Err(e) => return fail(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}.ublx\"", export_id)),
+            (header::ETAG, format!("\"{}\"", archive_hash)),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// Build the tenant export router
+pub fn routes(app: AppState) -> Router {
+    let state = ExportState {
+        app,
+        blobs: Arc::new(BlobStore::from_env()),
+        config: ExportConfig::from_env(),
+    };
+    Router::new()
+        .route("/tenant/exports", post(start_export))
+        .route("/tenant/exports/:export_id", get(get_export))
+        .route("/tenant/exports/:export_id/download", get(download_export))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ownership_of_entries() {
+        let atom = json!({ "type": "message.created", "tenant_id": "acme" });
+        assert!(owned_by("acme", "C.Messenger", Some(&atom)));
+        assert!(!owned_by("globex", "C.Messenger", Some(&atom)));
+        assert!(!owned_by("acme", "C.Messenger", None));
+        // Repositories are the tenant's whole
+        assert!(owned_by("acme", "repo://acme/site", None));
+        assert!(!owned_by("acme", "repo://acme-labs/site", None));
+
+        assert_eq!(chain_path("repo://acme/site"), "ledger/repo___acme_site.json");
+    }
+
+    #[test]
+    fn test_sealed_archive_lists_every_file() {
+        let mut bundle = Bundle::new(1024);
+        bundle.add_json("identity/tenant.json".into(), &json!([{ "tenant_id": "acme" }])).unwrap();
+        bundle.add("blobs/ab".into(), b"artifact".to_vec()).unwrap();
+        assert!(matches!(bundle.add("blobs/big".into(), vec![0; 1024]), Err(ExportError::TooLarge { max: 1024 })));
+
+        let (manifest, archive) = bundle.pack("exp_1", "acme", Vec::new());
+        assert_eq!(manifest.format, FORMAT);
+        let blob = manifest.files.iter().find(|f| f.path == "blobs/ab").unwrap();
+        assert_eq!((blob.hash.as_str(), blob.bytes), (blob_store::content_hash(b"artifact").as_str(), 8));
+
+        let key = [7u8; 32];
+        let sealed = seal_archive(&key, "exp_1", &archive);
+        let opened = atom_crypto::open_bytes(&key, &archive_aad("exp_1"), &sealed, "archive").unwrap();
+        let unpacked: Value = serde_json::from_slice(&opened).unwrap();
+        assert_eq!(STANDARD.decode(unpacked["files"]["blobs/ab"].as_str().unwrap()).unwrap(), b"artifact");
+        // Bound to its export
+        assert!(atom_crypto::open_bytes(&key, &archive_aad("exp_2"), &sealed, "archive").is_err());
+    }
+}
@@ -14,8 +14,13 @@
 //! - `GET /tenant/members` - List tenant members
 //! - `POST /tenant/invite` - Create invite code
 //! - `POST /tenant/join` - Join tenant with invite code
+//! - `GET|PUT /tenant/preferences` - Timezone and locale preferences
+//! - `GET|PUT /tenant/message_throttle` - Gateway message rate limits
+//! - `POST /tenant/exports`, `GET /tenant/exports/:export_id[/download]` -
+//!   Sealed export of all the tenant's data (see `export`)
 
 pub mod db;
+pub mod export;
 pub mod routes;
 pub mod types;
 
@@ -6,6 +6,10 @@
 //! - GET /tenant/members - List tenant members
 //! - POST /tenant/invite - Create invite code
 //! - POST /tenant/join - Join tenant with invite code
+//! - GET /tenant/preferences - Timezone and locale used when rendering times
+//! - PUT /tenant/preferences - Update them (owner/admin)
+//! - GET /tenant/message_throttle - Message rate limits applied by the gateway
+//! - PUT /tenant/message_throttle - Tune them (owner/admin)
 
 use axum::{
     extract::State,
@@ -19,6 +23,8 @@ use tracing::{error, info};
 
 use super::db;
 use super::types::*;
+use crate::messenger_gateway::throttle::{self, ThrottlePolicy};
+use crate::timestamps::{TenantTimePrefs, TimeZonePref};
 
 // ============================================================================
 // SESSION HELPER
@@ -305,6 +311,183 @@ async fn join_tenant(
     Ok(Json(JoinTenantResponse { tenant }))
 }
 
+/// Resolve the caller's tenant id (401 / 404 as for the other tenant routes)
+pub(super) async fn caller_tenant(
+    pool: &PgPool,
+    headers: &HeaderMap,
+) -> Result<(String, String), (StatusCode, Json<serde_json::Value>)> {
+    let session = get_session(pool, headers).await.ok_or_else(|| {
+        (StatusCode::UNAUTHORIZED, Json(json!({ "error": "Authentication required" })))
+    })?;
+    let tenant_id = db::get_user_tenant(pool, &session.sid)
+        .await
+        .map_err(|e| {
+            error!("Failed to get user tenant: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "User has no tenant" }))
+        ))?;
+    Ok((session.sid, tenant_id))
+}
+
+/// Refuse callers who are not owner or admin of the tenant
+async fn require_owner_or_admin(
+    pool: &PgPool,
+    tenant_id: &str,
+    sid: &str,
+    what: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let role = db::get_member_role(pool, tenant_id, sid)
+        .await
+        .map_err(|e| {
+            error!("Failed to get member role: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?;
+    match role {
+        Some(MemberRole::Owner) | Some(MemberRole::Admin) => Ok(()),
+        _ => Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": format!("Only owner or admin can change {}", what) }))
+        ))
+    }
+}
+
+/// GET /tenant/preferences - Timezone and locale for rendering (reports, UI)
+async fn get_preferences(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+) -> Result<Json<TenantPreferencesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let (_, tenant_id) = caller_tenant(&pool, &headers).await?;
+    let tenant = db::get_tenant(&pool, &tenant_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to get tenant: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Tenant not found" }))
+        ))?;
+
+    Ok(Json(TenantPreferencesResponse::new(
+        tenant_id,
+        TenantTimePrefs::from_settings(&tenant.settings),
+    )))
+}
+
+/// PUT /tenant/preferences - Update timezone and/or locale
+async fn update_preferences(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Json(req): Json<UpdatePreferencesRequest>,
+) -> Result<Json<TenantPreferencesResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let (sid, tenant_id) = caller_tenant(&pool, &headers).await?;
+    require_owner_or_admin(&pool, &tenant_id, &sid, "preferences").await?;
+
+    let mut patch = serde_json::Map::new();
+    if let Some(tz) = req.timezone {
+        // Store the normalized form (`utc` -> `UTC`, `+5:30` rejected)
+        let zone = TimeZonePref::parse(&tz).ok_or_else(|| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "timezone must be UTC or ±HH:MM" }))
+        ))?;
+        patch.insert("timezone".into(), json!(zone.to_string()));
+    }
+    if let Some(locale) = req.locale {
+        if !crate::timestamps::valid_locale(&locale) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "locale must be a BCP 47 tag" }))
+            ));
+        }
+        patch.insert("locale".into(), json!(locale));
+    }
+
+    let settings = db::merge_settings(&pool, &tenant_id, &serde_json::Value::Object(patch))
+        .await
+        .map_err(|e| {
+            error!("Failed to update tenant settings: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Tenant not found" }))
+        ))?;
+
+    info!("🕐 Tenant {} preferences updated by {}", tenant_id, sid);
+
+    Ok(Json(TenantPreferencesResponse::new(
+        tenant_id,
+        TenantTimePrefs::from_settings(&settings),
+    )))
+}
+
+/// GET /tenant/message_throttle - Thresholds the gateway applies to senders
+async fn get_message_throttle(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+) -> Result<Json<MessageThrottleResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let (_, tenant_id) = caller_tenant(&pool, &headers).await?;
+    let tenant = db::get_tenant(&pool, &tenant_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to get tenant: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Tenant not found" }))
+        ))?;
+
+    Ok(Json(MessageThrottleResponse {
+        tenant_id,
+        policy: ThrottlePolicy::from_env().for_tenant(&tenant.settings),
+    }))
+}
+
+/// PUT /tenant/message_throttle - Tune message rate limits; omitted fields
+/// keep their current value
+async fn update_message_throttle(
+    State(pool): State<PgPool>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateMessageThrottleRequest>,
+) -> Result<Json<MessageThrottleResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let (sid, tenant_id) = caller_tenant(&pool, &headers).await?;
+    require_owner_or_admin(&pool, &tenant_id, &sid, "message limits").await?;
+
+    let tenant = db::get_tenant(&pool, &tenant_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to get tenant: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Tenant not found" }))
+        ))?;
+    let current = ThrottlePolicy::from_env().for_tenant(&tenant.settings);
+    let policy = ThrottlePolicy {
+        max_per_minute: req.max_per_minute.unwrap_or(current.max_per_minute),
+        burst_count: req.burst_count.unwrap_or(current.burst_count),
+        burst_window_secs: req.burst_window_secs.unwrap_or(current.burst_window_secs),
+        mute_secs: req.mute_secs.unwrap_or(current.mute_secs),
+    };
+    policy.validate().map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))))?;
+
+    db::merge_settings(&pool, &tenant_id, &json!({ throttle::SETTINGS_KEY: policy }))
+        .await
+        .map_err(|e| {
+            error!("Failed to update tenant settings: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" })))
+        })?;
+
+    info!("🚦 Tenant {} message throttle set by {}: {:?}", tenant_id, sid, policy);
+
+    Ok(Json(MessageThrottleResponse { tenant_id, policy }))
+}
+
 // ============================================================================
 // ROUTER
 // ============================================================================
@@ -316,4 +499,6 @@ pub fn tenant_routes() -> Router<PgPool> {
         .route("/tenant/members", get(list_members))
         .route("/tenant/invite", post(create_invite))
         .route("/tenant/join", post(join_tenant))
+        .route("/tenant/preferences", get(get_preferences).put(update_preferences))
+        .route("/tenant/message_throttle", get(get_message_throttle).put(update_message_throttle))
 }
@@ -155,3 +155,52 @@ pub struct CreateInviteRequest {
 pub struct CreateInviteResponse {
     pub invite: InviteCode,
 }
+
+/// Time rendering preferences (`id_tenant.settings.timezone` / `.locale`)
+#[derive(Debug, Serialize)]
+pub struct TenantPreferencesResponse {
+    pub tenant_id: String,
+    #[serde(flatten)]
+    pub preferences: crate::timestamps::TenantTimePrefs,
+    /// Server time rendered in the tenant zone, as reports will show it
+    pub local_now: String,
+    /// Report day bucket (`YYYY-MM-DD`) the current time falls into
+    pub local_date: String,
+}
+
+impl TenantPreferencesResponse {
+    pub fn new(tenant_id: String, preferences: crate::timestamps::TenantTimePrefs) -> Self {
+        let zone = crate::timestamps::TimeZonePref::parse(&preferences.timezone)
+            .unwrap_or(crate::timestamps::TimeZonePref::UTC);
+        let now = crate::timestamps::now_ms();
+        Self {
+            tenant_id,
+            preferences,
+            local_now: zone.rfc3339(now),
+            local_date: zone.local_date(now),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePreferencesRequest {
+    pub timezone: Option<String>,
+    pub locale: Option<String>,
+}
+
+/// Message rate limits in force for a tenant (`id_tenant.settings.message_throttle`
+/// over the server defaults)
+#[derive(Debug, Serialize)]
+pub struct MessageThrottleResponse {
+    pub tenant_id: String,
+    #[serde(flatten)]
+    pub policy: crate::messenger_gateway::throttle::ThrottlePolicy,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMessageThrottleRequest {
+    pub max_per_minute: Option<u32>,
+    pub burst_count: Option<u32>,
+    pub burst_window_secs: Option<u32>,
+    pub mute_secs: Option<u32>,
+}
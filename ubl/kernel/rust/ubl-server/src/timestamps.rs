@@ -0,0 +1,174 @@
+//! Timestamps - millisecond UTC everywhere
+//!
+//! Ledger entries, receipts and projections historically mixed unix seconds,
+//! milliseconds and nanoseconds, and projections rendered `TIMESTAMPTZ` with
+//! `OffsetDateTime::to_string()` (not RFC 3339). New APIs expose explicit
+//! `*_ms` fields (unix milliseconds, UTC) and, where a string is useful,
+//! RFC 3339 in UTC with millisecond precision built here.
+//!
+//! Local time only exists at the edges: a tenant's `timezone` preference is
+//! applied when rendering for people (reports), never when storing.
+
+use serde::{Deserialize, Serialize};
+use time::macros::format_description;
+use time::{OffsetDateTime, UtcOffset};
+
+/// Current time in unix milliseconds (UTC)
+pub fn now_ms() -> i64 {
+    from_datetime(OffsetDateTime::now_utc())
+}
+
+/// Unix milliseconds of a datetime (any offset)
+pub fn from_datetime(dt: OffsetDateTime) -> i64 {
+    (dt.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// Datetime in UTC for unix milliseconds; out-of-range values clamp to the epoch
+pub fn to_datetime(ms: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp_nanos(ms as i128 * 1_000_000)
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+/// RFC 3339 in UTC with millisecond precision: `2026-10-16T15:58:10.123Z`
+pub fn rfc3339_utc(ms: i64) -> String {
+    let fmt = format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+    to_datetime(ms).format(&fmt).unwrap_or_default()
+}
+
+/// Tenant timezone preference: `UTC` or a fixed offset such as `-03:00`.
+///
+/// Named IANA zones need a tz database the server does not ship; clients
+/// resolve those themselves and store the offset they want reports in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZonePref(UtcOffset);
+
+impl TimeZonePref {
+    pub const UTC: Self = Self(UtcOffset::UTC);
+
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("UTC") || s == "Z" {
+            return Some(Self::UTC);
+        }
+        let (sign, rest) = match s.as_bytes().first()? {
+            b'+' => (1, &s[1..]),
+            b'-' => (-1, &s[1..]),
+            _ => return None,
+        };
+        let (h, m) = rest.split_once(':')?;
+        if h.len() != 2 || m.len() != 2 {
+            return None;
+        }
+        let (h, m): (i8, i8) = (h.parse().ok()?, m.parse().ok()?);
+        if h > 14 || m > 59 {
+            return None;
+        }
+        UtcOffset::from_hms(sign * h, sign * m, 0).ok().map(Self)
+    }
+
+    /// RFC 3339 in this zone with millisecond precision
+    pub fn rfc3339(&self, ms: i64) -> String {
+        if self.0.is_utc() {
+            return rfc3339_utc(ms);
+        }
+        let fmt = format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3][offset_hour sign:mandatory]:[offset_minute]"
+        );
+        to_datetime(ms).to_offset(self.0).format(&fmt).unwrap_or_default()
+    }
+
+    /// Calendar day in this zone (`YYYY-MM-DD`), for per-day report buckets
+    pub fn local_date(&self, ms: i64) -> String {
+        let fmt = format_description!("[year]-[month]-[day]");
+        to_datetime(ms).to_offset(self.0).format(&fmt).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for TimeZonePref {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_utc() {
+            return write!(f, "UTC");
+        }
+        let (h, m, _) = self.0.as_hms();
+        let sign = if self.0.is_negative() { '-' } else { '+' };
+        write!(f, "{}{:02}:{:02}", sign, h.unsigned_abs(), m.unsigned_abs())
+    }
+}
+
+/// Rendering preferences stored in `id_tenant.settings`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantTimePrefs {
+    /// `UTC` or `±HH:MM`
+    pub timezone: String,
+    /// BCP 47 tag passed through to clients, e.g. `pt-BR`
+    pub locale: String,
+}
+
+impl Default for TenantTimePrefs {
+    fn default() -> Self {
+        Self { timezone: "UTC".to_string(), locale: "en-US".to_string() }
+    }
+}
+
+impl TenantTimePrefs {
+    /// Read from tenant settings; missing or invalid values fall back to defaults
+    pub fn from_settings(settings: &serde_json::Value) -> Self {
+        let defaults = Self::default();
+        let timezone = settings.get("timezone")
+            .and_then(|v| v.as_str())
+            .and_then(TimeZonePref::parse)
+            .map(|tz| tz.to_string())
+            .unwrap_or(defaults.timezone);
+        let locale = settings.get("locale")
+            .and_then(|v| v.as_str())
+            .filter(|l| valid_locale(l))
+            .map(|l| l.to_string())
+            .unwrap_or(defaults.locale);
+        Self { timezone, locale }
+    }
+}
+
+/// Loose BCP 47 shape check: alphanumeric subtags joined by `-`
+pub fn valid_locale(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= 35
+        && tag.split('-').all(|p| !p.is_empty() && p.len() <= 8 && p.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversions() {
+        let ms = 1_760_630_290_123;
+        assert_eq!(from_datetime(to_datetime(ms)), ms);
+        assert_eq!(rfc3339_utc(ms), "2025-10-16T15:58:10.123Z");
+    }
+
+    #[test]
+    fn test_timezone_pref() {
+        let ms = 1_760_630_290_123;
+        let tz = TimeZonePref::parse("-03:00").unwrap();
+        assert_eq!(tz.to_string(), "-03:00");
+        assert_eq!(tz.rfc3339(ms), "2025-10-16T12:58:10.123-03:00");
+        assert_eq!(TimeZonePref::parse("utc"), Some(TimeZonePref::UTC));
+        assert_eq!(TimeZonePref::parse("+05:30").unwrap().to_string(), "+05:30");
+        // Day boundary follows the zone
+        let late = 1_760_659_200_000; // 2025-10-17T00:00:00Z
+        assert_eq!(TimeZonePref::UTC.local_date(late), "2025-10-17");
+        assert_eq!(tz.local_date(late), "2025-10-16");
+        for bad in ["America/Sao_Paulo", "+3:00", "+15:00", "03:00", ""] {
+            assert!(TimeZonePref::parse(bad).is_none(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_prefs_from_settings() {
+        let prefs = TenantTimePrefs::from_settings(&serde_json::json!({ "timezone": "+01:00", "locale": "pt-BR" }));
+        assert_eq!(prefs.timezone, "+01:00");
+        assert_eq!(prefs.locale, "pt-BR");
+        let fallback = TenantTimePrefs::from_settings(&serde_json::json!({ "timezone": "Mars/Olympus", "locale": "x y" }));
+        assert_eq!(fallback, TenantTimePrefs::default());
+    }
+}
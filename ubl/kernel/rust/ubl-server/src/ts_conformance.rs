@@ -0,0 +1,81 @@
+//! Conformance: links signed by the messenger frontend (`signLink` in
+//! `apps/messenger/frontend/src/services/crypto.ts`) must canonicalize to the
+//! bytes the frontend signed and pass the Membrane of `POST /link/commit`.
+//!
+//! Fixtures live in `tests/fixtures/ts_commits/frontend.json`; see the README
+//! there for how to re-record them.
+
+use serde::Deserialize;
+
+use ubl_membrane::{LedgerState, Membrane};
+
+use crate::api_error::ApiError;
+use crate::db::LinkDraft;
+use crate::{link_signing_bytes, membrane_check, membrane_run, Decision};
+
+#[derive(Deserialize)]
+struct Fixture {
+    name: String,
+    pipeline: String,
+    signing_bytes_hex: String,
+    link: LinkDraft,
+}
+
+/// The Membrane against a ledger whose head the link expects
+fn admit(link: &LinkDraft) -> Result<(), ApiError> {
+    let ledger = LedgerState {
+        container_id: link.container_id.clone(),
+        last_hash: link.previous_hash.clone(),
+        next_sequence: link.expected_sequence as u64,
+        physical_balance: 0,
+    };
+    membrane_check(&Membrane::standard(), link, &ledger)
+}
+
+fn fixtures() -> Vec<Fixture> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/fixtures/ts_commits/frontend.json");
+    let raw = std::fs::read_to_string(path).expect("read frontend.json");
+    serde_json::from_str(&raw).expect("parse frontend.json")
+}
+
+#[test]
+fn frontend_signing_bytes_match_server() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty());
+    for f in &fixtures {
+        assert_eq!(f.pipeline, "admission", "{}", f.name);
+        let bytes = link_signing_bytes(&f.link).unwrap();
+        assert_eq!(hex::encode(bytes), f.signing_bytes_hex, "{}: signing bytes drifted", f.name);
+    }
+}
+
+#[test]
+fn frontend_links_pass_membrane() {
+    for f in fixtures() {
+        if let Err(e) = admit(&f.link) {
+            panic!("{}: rejected: {}", f.name, e.message);
+        }
+    }
+}
+
+#[test]
+fn tampered_frontend_link_rejected() {
+    let mut f = fixtures().remove(0);
+    f.link.physics_delta = "1".into();
+    assert_eq!(admit(&f.link).unwrap_err().code, ubl_errors::ErrorCode::InvalidSignature);
+}
+
+#[test]
+fn drifted_frontend_link_preflight_names_the_rule() {
+    let f = fixtures().remove(0);
+    let ledger = LedgerState {
+        container_id: f.link.container_id.clone(),
+        last_hash: "0".repeat(64),
+        next_sequence: f.link.expected_sequence as u64,
+        physical_balance: 0,
+    };
+    let decision = Decision::of(membrane_run(&Membrane::standard(), &f.link, &ledger).unwrap());
+    assert_eq!(decision.decision, "Reject");
+    assert_eq!(decision.error, Some("RealityDrift"));
+    assert_eq!(decision.code, Some("REALITY_DRIFT"));
+}
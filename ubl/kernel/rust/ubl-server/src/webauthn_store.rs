@@ -10,10 +10,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use webauthn_rs::prelude::*;
 
-fn now_ms() -> i64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64
-}
+use crate::timestamps::now_ms;
 
 // =============================================================================
 // REQUEST/RESPONSE TYPES
@@ -183,7 +180,7 @@ pub async fn verify_stepup_assertion(
     if now_ms() > exp_ms {
         return Err("StepUpChallengeExpired".into());
     }
-    if binding_hash != expected_binding_hash {
+    if !ubl_kernel::ct_eq(binding_hash.as_bytes(), expected_binding_hash.as_bytes()) {
         return Err(format!(
             "StepUpBindingMismatch: expected {} got {}",
             expected_binding_hash, binding_hash
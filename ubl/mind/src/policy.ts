@@ -0,0 +1,125 @@
+/**
+ * Local policy pre-evaluation
+ *
+ * Runs the kernel's Policy VM compiled to WebAssembly (ubl-policy-vm with
+ * the `wasm` feature), so the Mind can tell whether a link will pass policy
+ * before proposing it. Same compiler, same bytecode hash, same VM and gas:
+ * the decision is the one admission will reach. It is advisory only; the
+ * server still decides.
+ *
+ * Build the module first (see ubl-policy-vm's crate docs):
+ *   wasm-bindgen --target nodejs --out-dir ubl-policy-vm/pkg ...
+ */
+
+// =============================================================================
+// TYPES (the server's JSON shapes)
+// =============================================================================
+
+/** Context a policy is evaluated against */
+export interface EvaluationContext {
+  readonly container_id: string;
+  readonly actor: string;
+  readonly intent: unknown;
+  readonly state?: unknown;
+  /** Unix milliseconds */
+  readonly timestamp: number;
+  /** Container feature flags, read by policies as `flag.<name>`; absent is off */
+  readonly flags?: Readonly<Record<string, boolean>>;
+}
+
+export interface ConstraintSnapshot {
+  readonly kind: string;
+  readonly value: string;
+}
+
+export type TranslationDecision =
+  | {
+      readonly Allow: {
+        readonly intent_class: number;
+        readonly required_pact: string | null;
+        readonly constraints: ConstraintSnapshot[];
+      };
+    }
+  | { readonly Deny: { readonly reason: string } };
+
+export interface PolicyEvaluation {
+  readonly decision: TranslationDecision;
+  /** Rule that allowed; null for a denial or the policy's default */
+  readonly rule: string | null;
+  readonly gas_used: number;
+  readonly max_gas: number;
+}
+
+/** What wasm-bindgen generates for `ubl_policy_vm::wasm` */
+interface WasmPolicy {
+  readonly policyId: string;
+  readonly hash: string;
+  evaluate(contextJson: string): string;
+  free(): void;
+}
+
+interface WasmModule {
+  Policy: {
+    compile(definitionJson: string): WasmPolicy;
+    fromCompiled(compiledJson: string): WasmPolicy;
+  };
+}
+
+// =============================================================================
+// LOADING
+// =============================================================================
+
+const DEFAULT_MODULE = new URL(
+  '../../kernel/rust/ubl-policy-vm/pkg/ubl_policy_vm.js',
+  import.meta.url
+).href;
+
+const modules = new Map<string, Promise<WasmModule>>();
+
+function load(path: string): Promise<WasmModule> {
+  let module = modules.get(path);
+  if (!module) {
+    module = import(path).then((m) => (m.default ?? m) as WasmModule);
+    modules.set(path, module);
+  }
+  return module;
+}
+
+// =============================================================================
+// POLICY
+// =============================================================================
+
+export class LocalPolicy {
+  private constructor(private readonly inner: WasmPolicy) {}
+
+  /** Compile a PolicyDefinition as the server does when registering it */
+  static async compile(definition: unknown, modulePath = DEFAULT_MODULE): Promise<LocalPolicy> {
+    const wasm = await load(modulePath);
+    return new LocalPolicy(wasm.Policy.compile(JSON.stringify(definition)));
+  }
+
+  /** Load a CompiledPolicy; throws if its hash does not match its bytecode */
+  static async fromCompiled(compiled: unknown, modulePath = DEFAULT_MODULE): Promise<LocalPolicy> {
+    const wasm = await load(modulePath);
+    return new LocalPolicy(wasm.Policy.fromCompiled(JSON.stringify(compiled)));
+  }
+
+  get policyId(): string {
+    return this.inner.policyId;
+  }
+
+  /** BLAKE3 bytecode hash; equal to the server's for the same policy */
+  get hash(): string {
+    return this.inner.hash;
+  }
+
+  /** Throws when the policy fails to execute (admission would refuse) */
+  evaluate(context: EvaluationContext): PolicyEvaluation {
+    return JSON.parse(this.inner.evaluate(JSON.stringify(context))) as PolicyEvaluation;
+  }
+
+  /** Release the WebAssembly memory held by the policy */
+  free(): void {
+    this.inner.free();
+  }
+}
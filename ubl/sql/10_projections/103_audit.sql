@@ -0,0 +1,36 @@
+-- ============================================================================
+-- UBL Audit Projections - v1.0
+-- ============================================================================
+-- C.Audit Projections: annotations (external evidence attached to entries)
+--
+-- Annotations never mutate the annotated entry. Each one is its own
+-- annotation.created atom in C.Audit that references a target entry_hash;
+-- this table only joins them back to their targets for reads.
+
+-- ============================================================================
+-- ANNOTATIONS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS projection_annotations (
+  annotation_id        TEXT PRIMARY KEY,
+  target_entry_hash    TEXT NOT NULL,
+  target_container_id  TEXT,
+  target_sequence      BIGINT,
+  author               TEXT NOT NULL,
+  kind                 TEXT NOT NULL DEFAULT 'note',   -- note, finding, evidence, correction
+  body                 TEXT NOT NULL DEFAULT '',
+  evidence             JSONB NOT NULL DEFAULT '[]'::jsonb, -- [{uri, sha256, media_type}]
+  created_at_ms        BIGINT NOT NULL,
+  entry_hash           TEXT NOT NULL,                   -- the annotation's own C.Audit entry
+  sequence             BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_annotations_target ON projection_annotations(target_entry_hash);
+CREATE INDEX IF NOT EXISTS idx_annotations_target_seq ON projection_annotations(target_container_id, target_sequence);
+CREATE INDEX IF NOT EXISTS idx_annotations_author ON projection_annotations(author);
+
+COMMENT ON TABLE projection_annotations IS 'Append-only annotations on ledger entries (C.Audit annotation.created)';
+
+INSERT INTO projection_state (container_id, last_sequence, last_hash)
+VALUES ('C.Audit', 0, '0x00')
+ON CONFLICT (container_id) DO NOTHING;
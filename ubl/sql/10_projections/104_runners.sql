@@ -0,0 +1,30 @@
+-- ============================================================================
+-- UBL Runner Registry - v1.0
+-- ============================================================================
+-- Extends ubl_runners (100_console.sql) with capability labels, concurrency,
+-- version and heartbeat liveness, and tags console commands with the
+-- capabilities a runner needs to pick them up.
+--
+-- A runner is live when last_heartbeat_ms is within UBL_RUNNER_TTL_SECS.
+
+-- ============================================================================
+-- RUNNERS
+-- ============================================================================
+
+ALTER TABLE ubl_runners ADD COLUMN IF NOT EXISTS capabilities TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE ubl_runners ADD COLUMN IF NOT EXISTS max_concurrency INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE ubl_runners ADD COLUMN IF NOT EXISTS version TEXT;
+ALTER TABLE ubl_runners ADD COLUMN IF NOT EXISTS registered_at_ms BIGINT;
+ALTER TABLE ubl_runners ADD COLUMN IF NOT EXISTS last_heartbeat_ms BIGINT;
+ALTER TABLE ubl_runners ADD COLUMN IF NOT EXISTS in_flight INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_ubl_runners_heartbeat ON ubl_runners(last_heartbeat_ms);
+CREATE INDEX IF NOT EXISTS idx_ubl_runners_capabilities ON ubl_runners USING GIN (capabilities);
+
+-- ============================================================================
+-- COMMAND REQUIREMENTS
+-- ============================================================================
+
+-- Copied from the permit's args.required_capabilities when the command is
+-- issued (the permit binding_hash covers args, so this is signed).
+ALTER TABLE console_commands ADD COLUMN IF NOT EXISTS required_capabilities TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,26 @@
+-- ============================================================================
+-- UBL Receipt Artifacts - v1.0
+-- ============================================================================
+-- Runners declare artifacts (name + blake3 hash + size) in exec.finish. The
+-- server answers with one pre-authorized upload slot per artifact; the
+-- receipt stays incomplete until every declared artifact has been uploaded
+-- and its bytes hash to the declared value.
+
+ALTER TABLE console_receipts ADD COLUMN IF NOT EXISTS complete BOOLEAN NOT NULL DEFAULT true;
+
+CREATE TABLE IF NOT EXISTS console_receipt_artifacts (
+  command_id        TEXT NOT NULL REFERENCES console_receipts(command_id),
+  name              TEXT NOT NULL,
+  content_hash      TEXT NOT NULL,   -- "blake3:<hex>", signed as part of the receipt
+  size_bytes        BIGINT NOT NULL,
+  media_type        TEXT,
+  upload_token      TEXT NOT NULL,   -- random, single slot
+  token_exp_ms      BIGINT NOT NULL,
+  uploaded_at_ms    BIGINT,
+  PRIMARY KEY (command_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_receipt_artifacts_pending
+  ON console_receipt_artifacts(command_id) WHERE uploaded_at_ms IS NULL;
+CREATE INDEX IF NOT EXISTS idx_console_receipts_incomplete
+  ON console_receipts(finished_at_ms) WHERE NOT complete;
@@ -0,0 +1,26 @@
+-- ============================================================================
+-- UBL Execution Logs - v1.0
+-- ============================================================================
+-- Runners stream execution logs as ordered segments. Segment bytes live in
+-- the blob store; this table keeps the index and the hash chain:
+--
+--   chain_hash(n) = blake3("<chain_hash(n-1)>\n<content_hash(n)>")
+--
+-- with chain_hash(-1) = "blake3:" || 64 zeros. The head of the chain is what
+-- the runner reports as `logs_hash` in exec.finish.
+
+CREATE TABLE IF NOT EXISTS exec_log_segments (
+  execution_id      TEXT NOT NULL REFERENCES console_commands(command_id),
+  seq               BIGINT NOT NULL CHECK (seq >= 0),
+  runner_id         TEXT NOT NULL,
+  content_hash      TEXT NOT NULL,   -- "blake3:<hex>" of the segment bytes
+  prev_hash         TEXT NOT NULL,   -- chain_hash of seq - 1
+  chain_hash        TEXT NOT NULL,
+  byte_offset       BIGINT NOT NULL, -- offset of this segment in the full log
+  size_bytes        BIGINT NOT NULL,
+  received_at_ms    BIGINT NOT NULL,
+  PRIMARY KEY (execution_id, seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_exec_log_segments_offset
+  ON exec_log_segments(execution_id, byte_offset);
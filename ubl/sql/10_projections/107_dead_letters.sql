@@ -0,0 +1,24 @@
+-- ============================================================================
+-- UBL Runner Dead Letters - v1.0
+-- ============================================================================
+-- Jobs that exhaust their retries on a runner are reported here with the
+-- history of every attempt (reason + receipt). Admins inspect them, edit the
+-- payload, and either requeue (handed back to the runner on its next
+-- heartbeat) or purge them.
+--
+-- status: dead → requeued → released (runner picked it up)
+
+CREATE TABLE IF NOT EXISTS runner_dead_letters (
+  job_id              TEXT PRIMARY KEY,
+  runner_id           TEXT NOT NULL,
+  job_type            TEXT NOT NULL,
+  job_json            JSONB NOT NULL,   -- ubl_runner_core::ExecutionJob
+  attempts_json       JSONB NOT NULL DEFAULT '[]',  -- FailedAttempt[], oldest first, across requeues
+  status              TEXT NOT NULL DEFAULT 'dead' CHECK (status IN ('dead', 'requeued', 'released')),
+  dead_lettered_at_ms BIGINT NOT NULL,
+  updated_at_ms       BIGINT NOT NULL,
+  updated_by          TEXT              -- sid of the admin that last edited/requeued
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letters_status ON runner_dead_letters(status, dead_lettered_at_ms);
+CREATE INDEX IF NOT EXISTS idx_dead_letters_runner ON runner_dead_letters(runner_id, status);
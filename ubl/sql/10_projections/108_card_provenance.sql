@@ -0,0 +1,26 @@
+-- ============================================================================
+-- UBL Card Provenance - v1.0
+-- ============================================================================
+-- Every card the Gateway hands out is recorded by a `card.issued` atom in
+-- C.Jobs carrying the card hash and the hash of a one-time nonce. This table
+-- indexes those atoms so button actions can be checked without scanning the
+-- ledger. The nonce itself is only ever returned to the client.
+--
+-- A card answers once: the first accepted action sets used_at_ms.
+
+CREATE TABLE IF NOT EXISTS card_issuances (
+  card_id           TEXT PRIMARY KEY,
+  job_id            TEXT NOT NULL,
+  tenant_id         TEXT NOT NULL,
+  conversation_id   TEXT,
+  card_hash         TEXT NOT NULL,   -- blake3 hex of the canonical card JSON
+  nonce_hash        TEXT NOT NULL,   -- blake3 hex of the nonce given to the client
+  buttons           JSONB NOT NULL,  -- [{button_id, action, requires_input}]
+  issued_entry_hash TEXT NOT NULL,   -- ledger entry of the card.issued atom
+  issued_at_ms      BIGINT NOT NULL,
+  used_at_ms        BIGINT,
+  used_by           TEXT,
+  used_button_id    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_card_issuances_job ON card_issuances(tenant_id, job_id);
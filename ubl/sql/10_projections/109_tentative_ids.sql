@@ -0,0 +1,13 @@
+-- ============================================================================
+-- UBL Tentative IDs - v1.0
+-- ============================================================================
+-- Clients may attach a tentative_id to a LinkDraft so an optimistic UI item
+-- can be matched to its ledger entry. The id is not part of the signed link
+-- and never reaches ledger_atom; it is only echoed in the commit response,
+-- the SSE tail and the projection rows below.
+
+ALTER TABLE projection_messages ADD COLUMN IF NOT EXISTS tentative_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_projection_messages_tentative
+  ON projection_messages(conversation_id, tentative_id)
+  WHERE tentative_id IS NOT NULL;
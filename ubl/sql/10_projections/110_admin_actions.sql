@@ -0,0 +1,34 @@
+-- ============================================================================
+-- UBL Admin Actions - v1.0
+-- ============================================================================
+-- Destructive admin operations (projection rebuild, ledger read-only toggle,
+-- container policy binding) are not executed on request. They are recorded
+-- here as pending actions and run only once enough distinct admins, each on
+-- a step-up WebAuthn session, have signed the action under an L4/L5 pact.
+--
+-- What signers sign (SPEC-UBL-PACT §8.1, intent class Evolution, delta 0):
+--   ubl:pact\n <pact_id> <action_hash> 0x03 <0 as i128 BE>
+-- action_hash = BLAKE3 of canonical {action_id, kind, params}
+--
+-- status: pending → executing → executed | failed
+--         pending → cancelled | expired
+
+CREATE TABLE IF NOT EXISTS admin_actions (
+  action_id       TEXT PRIMARY KEY,
+  kind            TEXT NOT NULL CHECK (kind IN ('projection_rebuild', 'read_only', 'policy_binding')),
+  params          JSONB NOT NULL DEFAULT '{}',
+  action_hash     TEXT NOT NULL,
+  pact_id         TEXT NOT NULL REFERENCES pact(pact_id),
+  status          TEXT NOT NULL DEFAULT 'pending'
+                  CHECK (status IN ('pending', 'executing', 'executed', 'failed', 'cancelled', 'expired')),
+  requested_by    TEXT NOT NULL,    -- sid of the requesting admin
+  approvals       JSONB NOT NULL DEFAULT '[]',  -- [{sid, signer, signature, approved_at_ms}]
+  result          JSONB,            -- execution outcome or error
+  created_at_ms   BIGINT NOT NULL,
+  expires_at_ms   BIGINT NOT NULL,
+  executed_at_ms  BIGINT,
+  updated_at_ms   BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_actions_status ON admin_actions(status, created_at_ms);
+CREATE INDEX IF NOT EXISTS idx_admin_actions_kind ON admin_actions(kind, executed_at_ms);
@@ -0,0 +1,39 @@
+-- ============================================================================
+-- UBL ASC Requests - v1.0
+-- ============================================================================
+-- Self-service Agent Signing Certificate issuance. An agent asks for an ASC
+-- from a named scope template (messenger-bot, jobs-worker, ...) for one
+-- tenant; a tenant owner/admin on a step-up session approves or rejects it.
+-- Approval issues the ASC with the template's scopes and TTL.
+--
+-- What the agent signs with its Ed25519 credential:
+--   ubl:asc_request\n<sid>\n<tenant_id>\n<template>\n<ts_ms>
+--
+-- status: pending → approved | rejected
+
+CREATE TABLE IF NOT EXISTS id_asc_request (
+  request_id       TEXT PRIMARY KEY,
+  sid              TEXT NOT NULL REFERENCES id_subject(sid) ON DELETE CASCADE,
+  tenant_id        TEXT NOT NULL,
+  template         TEXT NOT NULL,
+  justification    TEXT,
+  status           TEXT NOT NULL DEFAULT 'pending'
+                   CHECK (status IN ('pending', 'approved', 'rejected')),
+  requested_at_ms  BIGINT NOT NULL,
+  decided_by       TEXT,             -- sid of the approving/rejecting admin
+  decided_at_ms    BIGINT,
+  decision_reason  TEXT,
+  asc_id           UUID REFERENCES id_asc(asc_id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_id_asc_request_tenant ON id_asc_request(tenant_id, status, requested_at_ms);
+
+-- One open request per agent/tenant/template
+CREATE UNIQUE INDEX IF NOT EXISTS idx_id_asc_request_pending
+  ON id_asc_request(sid, tenant_id, template) WHERE status = 'pending';
+
+-- Expiry notifications: set once Office has been told the ASC is about to lapse
+ALTER TABLE id_asc ADD COLUMN IF NOT EXISTS expiry_notified_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_id_asc_expiry_pending
+  ON id_asc(not_after) WHERE expiry_notified_at IS NULL;
@@ -0,0 +1,23 @@
+-- ============================================================================
+-- UBL Atom Encryption at Rest - v1.0
+-- ============================================================================
+-- Containers listed in UBL_ENCRYPTED_CONTAINERS store ledger_atom.atom_data
+-- as a sealed envelope ({"type", "ubl_sealed": {v, dek, nonce, ct}}). Each
+-- container's data key (DEK) lives here, wrapped (XChaCha20-Poly1305,
+-- nonce || ciphertext) by the master key "atom-master" from the secret
+-- provider. kek_id is the fingerprint of the wrapping master key.
+--
+-- Master key rotation (ubl-server --rotate-atom-master) re-wraps these rows
+-- only; sealed atoms are never rewritten.
+
+CREATE TABLE IF NOT EXISTS atom_data_key (
+  container_id     TEXT NOT NULL,
+  dek_version      INTEGER NOT NULL DEFAULT 1,
+  wrapped_key      BYTEA NOT NULL,
+  kek_id           TEXT NOT NULL,
+  created_at_ms    BIGINT NOT NULL,
+  rewrapped_at_ms  BIGINT,
+  PRIMARY KEY (container_id, dek_version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_atom_data_key_kek ON atom_data_key(kek_id);
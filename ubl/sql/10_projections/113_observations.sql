@@ -0,0 +1,26 @@
+-- ============================================================================
+-- UBL Observation Batches - v1.0
+-- ============================================================================
+-- An observation.batch atom carries many small observations (presence,
+-- progress) in one Observation link, with a Merkle root over their atom
+-- hashes. This projection unpacks each batch into one row per observation.
+-- leaf_hash + batch_root let a reader prove a row belongs to its batch.
+
+CREATE TABLE IF NOT EXISTS projection_observations (
+  entry_hash    TEXT NOT NULL,
+  batch_index   INTEGER NOT NULL,
+  container_id  TEXT NOT NULL,
+  sequence      BIGINT NOT NULL,
+  tenant_id     TEXT NOT NULL DEFAULT 'default',
+  entity_id     TEXT,
+  obs_type      TEXT NOT NULL,
+  leaf_hash     TEXT NOT NULL,
+  batch_root    TEXT NOT NULL,
+  data          JSONB NOT NULL,
+  ts_ms         BIGINT,
+  PRIMARY KEY (entry_hash, batch_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_projection_observations_container ON projection_observations(container_id, sequence DESC);
+CREATE INDEX IF NOT EXISTS idx_projection_observations_entity ON projection_observations(entity_id, sequence DESC);
+CREATE INDEX IF NOT EXISTS idx_projection_observations_type ON projection_observations(obs_type, sequence DESC);
@@ -0,0 +1,12 @@
+-- ============================================================================
+-- UBL Signed Permits - v1.0
+-- ============================================================================
+-- Console permits are signed over their claims (audience, policy hash, nonce,
+-- issue and expiry times) instead of the bare binding_hash, so Office can
+-- verify them itself. policy_hash is part of the signed claims and has to be
+-- kept to re-verify the permit when it is consumed.
+--
+-- Rows issued before this migration have no policy_hash and no longer verify;
+-- they expire within minutes, so nothing is backfilled.
+
+ALTER TABLE console_permits ADD COLUMN IF NOT EXISTS policy_hash TEXT;
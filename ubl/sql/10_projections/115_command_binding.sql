@@ -0,0 +1,9 @@
+-- ============================================================================
+-- UBL Command ↔ Permit Binding - v1.0
+-- ============================================================================
+-- Commands are re-checked against their permit when a runner pulls them
+-- (job type, params hash, expiry, signature). A command that no longer
+-- matches is taken out of the queue (pending = false) and the reason kept
+-- here instead of being handed to a runner.
+
+ALTER TABLE console_commands ADD COLUMN IF NOT EXISTS rejected_reason TEXT;
@@ -0,0 +1,38 @@
+-- ============================================================================
+-- UBL Scheduled Messages - v1.0
+-- ============================================================================
+-- "Send later" through the Messenger Gateway. The command is kept here with
+-- its due time; nothing reaches the ledger until the scheduler commits the
+-- message.created link at due_at. Content stays out of the ledger exactly
+-- as for immediate sends (only its hash goes into the atom).
+--
+-- pending → sending (claimed by the scheduler) → sent | pending (retry) | failed
+-- pending → cancelled (by the author, only while pending)
+
+CREATE TABLE IF NOT EXISTS gateway_scheduled_messages (
+  schedule_id      TEXT PRIMARY KEY,
+  tenant_id        TEXT NOT NULL,
+  conversation_id  TEXT NOT NULL,
+  from_sid         TEXT NOT NULL,
+  content          TEXT NOT NULL,
+  message_type     TEXT NOT NULL DEFAULT 'text',
+  due_at           TIMESTAMPTZ NOT NULL,
+  status           TEXT NOT NULL DEFAULT 'pending'
+                   CHECK (status IN ('pending', 'sending', 'sent', 'cancelled', 'failed')),
+  attempts         INTEGER NOT NULL DEFAULT 0,
+  last_error       TEXT,
+  message_id       TEXT,
+  entry_hash       TEXT,
+  sequence         BIGINT,
+  created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Scheduler scan: oldest due pending first
+CREATE INDEX IF NOT EXISTS idx_gateway_scheduled_due
+  ON gateway_scheduled_messages(due_at) WHERE status = 'pending';
+-- Author's view of a conversation
+CREATE INDEX IF NOT EXISTS idx_gateway_scheduled_author
+  ON gateway_scheduled_messages(from_sid, conversation_id, due_at);
+
+COMMENT ON TABLE gateway_scheduled_messages IS 'Messages scheduled for later delivery; committed to C.Messenger at due_at';
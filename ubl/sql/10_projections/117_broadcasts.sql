@@ -0,0 +1,43 @@
+-- ============================================================================
+-- UBL Broadcast Channels - v1.0
+-- ============================================================================
+-- A broadcast conversation is one-to-many: only its designated senders may
+-- post. Each announcement is one message.created link; the projection fans
+-- it out into one delivery row per member instead of N commits, and read
+-- receipts (message.read) land on the member's row.
+
+ALTER TABLE projection_conversations
+  ADD COLUMN IF NOT EXISTS kind TEXT NOT NULL DEFAULT 'chat' CHECK (kind IN ('chat', 'broadcast'));
+ALTER TABLE projection_conversations ADD COLUMN IF NOT EXISTS senders TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE projection_conversations ADD COLUMN IF NOT EXISTS tenant_id TEXT;
+
+CREATE TABLE IF NOT EXISTS projection_broadcast_deliveries (
+  message_id       TEXT NOT NULL,
+  member_id        TEXT NOT NULL,
+  conversation_id  TEXT NOT NULL,
+  tenant_id        TEXT NOT NULL DEFAULT 'default',
+  delivered_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  read_at          TIMESTAMPTZ,
+  last_event_seq   BIGINT NOT NULL,
+  PRIMARY KEY (message_id, member_id)
+);
+
+-- A member's announcements, newest first
+CREATE INDEX IF NOT EXISTS idx_broadcast_deliveries_member
+  ON projection_broadcast_deliveries(member_id, delivered_at DESC);
+CREATE INDEX IF NOT EXISTS idx_broadcast_deliveries_conversation
+  ON projection_broadcast_deliveries(conversation_id, message_id);
+
+-- Delivery and read counts per announcement
+CREATE OR REPLACE VIEW projection_broadcast_stats AS
+SELECT message_id,
+       conversation_id,
+       tenant_id,
+       COUNT(*)::BIGINT       AS delivered,
+       COUNT(read_at)::BIGINT AS read,
+       MIN(delivered_at)      AS delivered_at,
+       MAX(read_at)           AS last_read_at
+FROM projection_broadcast_deliveries
+GROUP BY message_id, conversation_id, tenant_id;
+
+COMMENT ON TABLE projection_broadcast_deliveries IS 'Per-member fan-out of broadcast announcements (derived from message.created / message.read)';
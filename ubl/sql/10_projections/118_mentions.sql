@@ -0,0 +1,30 @@
+-- ============================================================================
+-- UBL Mentions → Obligations - v1.0
+-- ============================================================================
+-- The gateway parses @mentions when a message is sent and commits one
+-- mention.created atom (linked to the message's entry hash) to C.Messenger.
+-- The projection turns it into one open obligation per mentioned entity;
+-- the obligation is acknowledged when that entity reads the message
+-- (message.read). Bootstrap counts open mentions per conversation as badges.
+
+CREATE TABLE IF NOT EXISTS projection_obligations (
+  obligation_id       TEXT PRIMARY KEY,           -- '<kind>:<message_id>:<entity_id>'
+  tenant_id           TEXT NOT NULL DEFAULT 'default',
+  entity_id           TEXT NOT NULL,              -- who owes attention
+  kind                TEXT NOT NULL CHECK (kind IN ('mention')),
+  conversation_id     TEXT NOT NULL,
+  message_id          TEXT NOT NULL,
+  message_entry_hash  TEXT NOT NULL,              -- ledger entry of the message
+  from_id             TEXT NOT NULL,
+  created_at          TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  acknowledged_at     TIMESTAMPTZ,
+  last_event_hash     TEXT NOT NULL,
+  last_event_seq      BIGINT NOT NULL
+);
+
+-- Open obligations of an entity, per conversation (badges)
+CREATE INDEX IF NOT EXISTS idx_obligations_open
+  ON projection_obligations(entity_id, conversation_id) WHERE acknowledged_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_obligations_message ON projection_obligations(message_id);
+
+COMMENT ON TABLE projection_obligations IS 'Open obligations per entity (mentions), derived from mention.created / message.read';
@@ -0,0 +1,30 @@
+-- ============================================================================
+-- UBL Sender Mutes - v1.0
+-- ============================================================================
+-- Agents muted by the Messenger Gateway after a burst of sends (an LLM stuck
+-- in a loop). While a mute is active the gateway refuses the agent's messages
+-- in that tenant. A mute ends at muted_until or when a tenant admin lifts it.
+-- Rate counting itself is in memory; only the mutes are kept, so they survive
+-- restarts and can be audited.
+
+CREATE TABLE IF NOT EXISTS gateway_sender_mutes (
+  mute_id               TEXT PRIMARY KEY,
+  tenant_id             TEXT NOT NULL,
+  sid                   TEXT NOT NULL,
+  -- Conversation the burst was detected in
+  conversation_id       TEXT NOT NULL,
+  sends_in_window       INTEGER NOT NULL,
+  window_secs           INTEGER NOT NULL,
+  muted_at              TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  muted_until           TIMESTAMPTZ NOT NULL,
+  lifted_at             TIMESTAMPTZ,
+  lifted_by             TEXT,
+  -- Set once Office accepted the notice for the agent's guardian
+  guardian_notified_at  TIMESTAMPTZ
+);
+
+-- Gateway check on every send, admin listing
+CREATE INDEX IF NOT EXISTS idx_gateway_sender_mutes_active
+  ON gateway_sender_mutes(tenant_id, sid, muted_until) WHERE lifted_at IS NULL;
+
+COMMENT ON TABLE gateway_sender_mutes IS 'Agents muted by the Messenger Gateway for flooding; active until muted_until unless lifted';
@@ -0,0 +1,43 @@
+-- ============================================================================
+-- UBL Conversation Summaries - v1.0
+-- ============================================================================
+-- Long conversations are summarized segment by segment. The gateway's
+-- summarizer picks the oldest messages not covered by a summary yet, Office
+-- runs a summarization job over them and the result is committed to
+-- C.Messenger as a conversation.summarized atom listing the entry hashes it
+-- covers. Like message content, the summary text stays off-ledger
+-- (message_content, keyed by summary_id); the atom carries its hash.
+--
+-- Segments are contiguous in C.Messenger sequence: a summary covers every
+-- message of its conversation in [from_sequence, to_sequence].
+
+-- Ledger position of each message's message.created link (last_event_* moves
+-- on message.read); summaries cover these
+ALTER TABLE projection_messages ADD COLUMN IF NOT EXISTS entry_hash TEXT;
+ALTER TABLE projection_messages ADD COLUMN IF NOT EXISTS entry_seq BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_proj_messages_entry_seq
+  ON projection_messages(conversation_id, entry_seq);
+
+CREATE TABLE IF NOT EXISTS projection_conversation_summaries (
+  summary_id            TEXT PRIMARY KEY,
+  conversation_id       TEXT NOT NULL,
+  tenant_id             TEXT NOT NULL,
+  from_sequence         BIGINT NOT NULL,
+  to_sequence           BIGINT NOT NULL,
+  covered_entry_hashes  TEXT[] NOT NULL,
+  message_count         INTEGER NOT NULL,
+  summary_hash          TEXT NOT NULL,
+  -- Office job that wrote the summary, and the model it used
+  job_id                TEXT,
+  model                 TEXT,
+  created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_event_hash       TEXT NOT NULL,
+  last_event_seq        BIGINT NOT NULL
+);
+
+-- Latest summary of a conversation, and where the next segment starts
+CREATE INDEX IF NOT EXISTS idx_proj_summaries_conversation
+  ON projection_conversation_summaries(conversation_id, to_sequence DESC);
+
+COMMENT ON TABLE projection_conversation_summaries IS 'Derived from conversation.summarized events in C.Messenger; text in message_content';
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- UBL Job Templates - v1.0
+-- ============================================================================
+-- Parameterized job definitions, governed as `evolution.job_template` atoms
+-- in C.Jobs (Evolution intent, pact required). The latest version of each
+-- template is kept here; `POST /jobs/from-template/:id` instantiates it as a
+-- job.created atom recording the template version and entry it came from.
+-- Older versions stay in the ledger.
+
+CREATE TABLE IF NOT EXISTS projection_job_templates (
+  template_id       TEXT PRIMARY KEY,
+  version           INTEGER NOT NULL,
+  name              TEXT NOT NULL,
+  -- The template as committed (parameters, sandbox, required_tools, fsm)
+  definition        JSONB NOT NULL,
+  updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  last_event_hash   TEXT NOT NULL,
+  last_event_seq    BIGINT NOT NULL
+);
+
+COMMENT ON TABLE projection_job_templates IS 'Latest version of each governed job template (evolution.job_template in C.Jobs)';
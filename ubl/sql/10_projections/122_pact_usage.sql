@@ -0,0 +1,43 @@
+-- ============================================================================
+-- UBL Pact Usage - v1.0
+-- ============================================================================
+-- Usage-limited pacts. A pact with max_uses authorizes at most that many
+-- commits. PgLedger consumes a use inside the same SERIALIZABLE transaction
+-- as the append it authorizes, so concurrent commits cannot both spend the
+-- last use: the loser hits a serialization conflict, retries, and finds the
+-- pact exhausted. Pacts without max_uses (NULL) are unlimited and are not
+-- written to on use.
+
+ALTER TABLE pact ADD COLUMN IF NOT EXISTS max_uses INTEGER;
+ALTER TABLE pact ADD COLUMN IF NOT EXISTS uses INTEGER NOT NULL DEFAULT 0;
+
+DO $$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'valid_pact_uses') THEN
+    ALTER TABLE pact ADD CONSTRAINT valid_pact_uses
+      CHECK (max_uses IS NULL OR (max_uses > 0 AND uses <= max_uses));
+  END IF;
+END $$;
+
+-- Pacts stay immutable; the use counter is the one column that may advance
+CREATE OR REPLACE FUNCTION forbid_pact_mutation() RETURNS trigger AS $$
+BEGIN
+  IF (to_jsonb(NEW) - 'uses') = (to_jsonb(OLD) - 'uses') AND NEW.uses > OLD.uses THEN
+    RETURN NEW;
+  END IF;
+  RAISE EXCEPTION 'pacts are immutable - create a new pact instead';
+END $$ LANGUAGE plpgsql;
+
+-- Which entry spent each use of a limited pact
+CREATE TABLE IF NOT EXISTS pact_usage (
+  pact_id       TEXT    NOT NULL REFERENCES pact(pact_id),
+  container_id  TEXT    NOT NULL,
+  sequence      BIGINT  NOT NULL,
+  atom_hash     TEXT    NOT NULL,
+  use_number    INTEGER NOT NULL,
+  used_at_ms    BIGINT  NOT NULL,
+  PRIMARY KEY (pact_id, use_number),
+  UNIQUE (container_id, sequence)
+);
+
+COMMENT ON TABLE pact_usage IS 'Uses of usage-limited pacts, one row per authorized ledger entry';
@@ -0,0 +1,35 @@
+-- ============================================================================
+-- UBL Container Analytics - v1.0
+-- ============================================================================
+-- Daily activity per container for the console dashboard, maintained by the
+-- commit path (projections/analytics.rs) and read by
+-- GET /query/analytics/containers. Days are UTC.
+--
+-- Accepted commits are applied once per ledger entry (guarded by
+-- last_sequence). Rejected commits never reach the ledger, so `rejected` is
+-- counted as they happen and cannot be rebuilt by a replay.
+
+CREATE TABLE IF NOT EXISTS projection_container_daily (
+  container_id   TEXT    NOT NULL,
+  day            DATE    NOT NULL,
+  commits        BIGINT  NOT NULL DEFAULT 0,
+  rejected       BIGINT  NOT NULL DEFAULT 0,
+  delta_volume   NUMERIC NOT NULL DEFAULT 0,  -- sum of |physics_delta|
+  delta_net      NUMERIC NOT NULL DEFAULT 0,  -- sum of physics_delta
+  last_sequence  BIGINT  NOT NULL DEFAULT 0,
+  PRIMARY KEY (container_id, day)
+);
+
+CREATE INDEX IF NOT EXISTS idx_container_daily_day ON projection_container_daily(day);
+
+-- Distinct authors per container and day (active authors over any range)
+CREATE TABLE IF NOT EXISTS projection_container_daily_author (
+  container_id   TEXT    NOT NULL,
+  day            DATE    NOT NULL,
+  author_pubkey  TEXT    NOT NULL,
+  commits        BIGINT  NOT NULL DEFAULT 0,
+  PRIMARY KEY (container_id, day, author_pubkey)
+);
+
+COMMENT ON TABLE projection_container_daily IS 'Per-container daily commit, rejection and delta rollups';
+COMMENT ON TABLE projection_container_daily_author IS 'Per-container daily commit counts by author';
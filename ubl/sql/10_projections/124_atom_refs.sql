@@ -0,0 +1,30 @@
+-- ============================================================================
+-- UBL Atom Reference Counts - v1.0
+-- ============================================================================
+-- ledger_atom is content-addressed: an atom committed again (the same config
+-- snapshot, the same card) is stored once and every ledger_entry points at it
+-- through link_hash. This table counts those references so the savings are
+-- visible (ubl_atom_dedup_saved_bytes) and a future compaction knows which
+-- atoms are shared.
+--
+-- Maintained inside the append transaction (db.rs); ledger_atom itself stays
+-- append-only. size_bytes is the stored JSON text, sealed or not.
+
+CREATE TABLE IF NOT EXISTS ledger_atom_refs (
+  atom_hash    TEXT    PRIMARY KEY,
+  ref_count    BIGINT  NOT NULL,
+  size_bytes   BIGINT  NOT NULL,
+  first_ref_ms BIGINT  NOT NULL,
+  last_ref_ms  BIGINT  NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_atom_refs_shared
+  ON ledger_atom_refs(ref_count) WHERE ref_count > 1;
+
+-- Backfill from the entries already committed
+INSERT INTO ledger_atom_refs (atom_hash, ref_count, size_bytes, first_ref_ms, last_ref_ms)
+SELECT la.atom_hash, COUNT(*), octet_length(la.atom_data::text), MIN(le.ts_unix_ms), MAX(le.ts_unix_ms)
+FROM ledger_atom la
+JOIN ledger_entry le ON le.link_hash = la.atom_hash
+GROUP BY la.atom_hash, la.atom_data
+ON CONFLICT (atom_hash) DO NOTHING;
@@ -0,0 +1,23 @@
+-- ============================================================================
+-- UBL Message Redactions (tombstones) - v1.0
+-- ============================================================================
+-- Redaction is append-only: the gateway commits a message.redacted atom to
+-- C.Messenger and the message, its ledger entry and its message_content row
+-- stay in place. This table holds one tombstone per redacted message; every
+-- read path consults it through projections::visibility, so members never
+-- see the message and admins see a placeholder with the reason.
+
+CREATE TABLE IF NOT EXISTS projection_redactions (
+  message_id       TEXT PRIMARY KEY,           -- first redaction wins
+  conversation_id  TEXT NOT NULL,
+  tenant_id        TEXT NOT NULL DEFAULT 'default',
+  redacted_by      TEXT NOT NULL,
+  reason           TEXT NOT NULL,
+  redacted_at      TIMESTAMPTZ NOT NULL,
+  last_event_hash  TEXT NOT NULL,              -- entry of the message.redacted atom
+  last_event_seq   BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_redactions_conversation ON projection_redactions(conversation_id);
+
+COMMENT ON TABLE projection_redactions IS 'Tombstones of redacted messages, derived from message.redacted events in C.Messenger';
@@ -0,0 +1,15 @@
+-- ============================================================================
+-- UBL Entropy Accounting - v1.0
+-- ============================================================================
+-- Supply created and destroyed by Entropy commits, rolled up per container
+-- and day next to the other analytics (projections/analytics.rs). A positive
+-- Entropy delta mints, a negative one burns; other intent classes never
+-- change these columns. Cumulative totals are the sum over all days and are
+-- served by GET /state/:container_id and handed to policies as state.
+--
+-- Totals are per container: containers hold a single asset today. Once
+-- multi-asset lands, the rollup gains an asset column and key.
+
+ALTER TABLE projection_container_daily
+  ADD COLUMN IF NOT EXISTS entropy_minted NUMERIC NOT NULL DEFAULT 0,  -- sum of positive Entropy deltas
+  ADD COLUMN IF NOT EXISTS entropy_burned NUMERIC NOT NULL DEFAULT 0;  -- sum of |negative Entropy deltas|
@@ -0,0 +1,31 @@
+-- ============================================================================
+-- UBL Evolution Changelog - v1.0
+-- ============================================================================
+-- One row per Evolution atom (evolution.* types), keyed by its ledger entry.
+-- target_container_id is the container whose rules changed (the atom's
+-- container_id, else the container it was committed to); subject pairs
+-- successive versions of the same thing (policy id, 'manifest', FSM name,
+-- template id). changes holds the structured diff against the previous row
+-- with the same target, type and subject (see ubl-server evolution.rs);
+-- summary holds the same diff rendered one line per change.
+
+CREATE TABLE IF NOT EXISTS projection_evolution_changelog (
+  entry_hash           TEXT PRIMARY KEY,
+  container_id         TEXT NOT NULL,          -- container the link was committed to
+  sequence             BIGINT NOT NULL,
+  target_container_id  TEXT NOT NULL,
+  evolution_type       TEXT NOT NULL,
+  subject              TEXT NOT NULL,
+  previous_entry_hash  TEXT,                   -- NULL for the first version
+  atom                 JSONB NOT NULL,
+  changes              JSONB NOT NULL DEFAULT '[]'::jsonb,
+  summary              TEXT[] NOT NULL DEFAULT '{}',
+  committed_at_ms      BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_evolution_changelog_target
+  ON projection_evolution_changelog(target_container_id, committed_at_ms DESC);
+CREATE INDEX IF NOT EXISTS idx_evolution_changelog_subject
+  ON projection_evolution_changelog(target_container_id, evolution_type, subject, committed_at_ms DESC);
+
+COMMENT ON TABLE projection_evolution_changelog IS 'Evolution history per container with diffs between versions, derived from evolution.* atoms';
@@ -0,0 +1,31 @@
+-- ============================================================================
+-- UBL Actor Reputation - v1.0
+-- ============================================================================
+-- Commit history per author key, maintained by the commit path
+-- (projections/reputation.rs) and handed to policies as state, so rules can
+-- ask a pact of actors that keep getting refused.
+--
+-- Only refusals that are the author's doing count: policy, pact, physics and
+-- evolution violations, all raised after the link signature verified. A bad
+-- signature or a head race never touches a key's reputation. Like
+-- `projection_container_daily.rejected`, refusals never reach the ledger and
+-- cannot be rebuilt by a replay.
+
+CREATE TABLE IF NOT EXISTS projection_actor_reputation (
+  author_pubkey  TEXT    PRIMARY KEY,
+  accepted       BIGINT  NOT NULL DEFAULT 0,
+  rejected       BIGINT  NOT NULL DEFAULT 0,
+  updated_at_ms  BIGINT  NOT NULL
+);
+
+-- Violations per author and error code (e.g. POLICY_DENIED)
+CREATE TABLE IF NOT EXISTS projection_actor_violations (
+  author_pubkey  TEXT    NOT NULL,
+  code           TEXT    NOT NULL,
+  count          BIGINT  NOT NULL DEFAULT 0,
+  last_at_ms     BIGINT  NOT NULL,
+  PRIMARY KEY (author_pubkey, code)
+);
+
+COMMENT ON TABLE projection_actor_reputation IS 'Accepted and refused commit counts per author key';
+COMMENT ON TABLE projection_actor_violations IS 'Refusals per author key and error code, with the latest time';
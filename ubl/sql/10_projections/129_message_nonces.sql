@@ -0,0 +1,35 @@
+-- ============================================================================
+-- UBL Message Nonces - v1.0
+-- ============================================================================
+-- Single materialization of gateway message sends. A client sending
+-- POST /v1/conversations/:id/messages with a `client_msg_id` (its nonce for
+-- that send) claims (conversation_id, client_msg_id) here before anything is
+-- committed (messenger_gateway/idempotency.rs). A double click or a retry
+-- with the same nonce gets the first send's outcome back instead of a second
+-- message:
+--
+-- - pending:   being sent; concurrent duplicates get 409 until it settles
+--              (or until the claim is stale, after a crash)
+-- - committed: message.created is in the ledger; Office has not answered
+-- - delivered: Office took it; `response` is replayed as is
+--
+-- The nonce also rides in the message.created atom (`client_msg_id`, unique
+-- per conversation in projection_messages) and on the SSE tail.
+
+CREATE TABLE IF NOT EXISTS gateway_message_nonces (
+  conversation_id  TEXT    NOT NULL,
+  client_msg_id    TEXT    NOT NULL,
+  tenant_id        TEXT    NOT NULL,
+  from_sid         TEXT    NOT NULL,
+  status           TEXT    NOT NULL CHECK (status IN ('pending', 'committed', 'delivered')),
+  message_id       TEXT,
+  entry_hash       TEXT,
+  sequence         BIGINT,
+  response         JSONB,
+  claimed_at_ms    BIGINT  NOT NULL,
+  PRIMARY KEY (conversation_id, client_msg_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_gateway_message_nonces_claimed ON gateway_message_nonces(claimed_at_ms);
+
+COMMENT ON TABLE gateway_message_nonces IS 'Gateway message sends by client nonce, for single materialization';
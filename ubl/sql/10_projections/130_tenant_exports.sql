@@ -0,0 +1,36 @@
+-- ============================================================================
+-- UBL Tenant Exports - v1.0
+-- ============================================================================
+-- One row per export job (tenant/export.rs). A tenant owner starts an export;
+-- a background task bundles the tenant's ledger entries (verified chains),
+-- projection rows, receipt blobs and identity records, seals the archive
+-- with a key handed to the owner once and never stored, puts the sealed
+-- bytes in the blob store and records a `tenant.exported` observation in
+-- C.Tenant.
+--
+-- The key lives only in the task's memory: a `running` export does not
+-- survive a restart and is marked failed on start.
+
+CREATE TABLE IF NOT EXISTS tenant_exports (
+  export_id         TEXT    PRIMARY KEY,
+  tenant_id         TEXT    NOT NULL,
+  requested_by      TEXT    NOT NULL,
+  status            TEXT    NOT NULL CHECK (status IN ('running', 'ready', 'failed')),
+  archive_hash      TEXT,             -- "blake3:<hex>" of the sealed archive
+  archive_bytes     BIGINT,
+  manifest          JSONB,            -- file paths and hashes, chain heads
+  entry_hash        TEXT,             -- the tenant.exported observation
+  download_token    TEXT,             -- random, renewed once expired
+  download_exp_ms   BIGINT,
+  error             TEXT,
+  requested_at_ms   BIGINT  NOT NULL,
+  completed_at_ms   BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_exports_tenant ON tenant_exports(tenant_id, requested_at_ms DESC);
+
+-- One export at a time per tenant
+CREATE UNIQUE INDEX IF NOT EXISTS uq_tenant_exports_running
+  ON tenant_exports(tenant_id) WHERE status = 'running';
+
+COMMENT ON TABLE tenant_exports IS 'Tenant data export jobs and their sealed archives';
@@ -0,0 +1,22 @@
+-- ============================================================================
+-- UBL Policy Rule Coverage - v1.0
+-- ============================================================================
+-- How often each rule of a policy decided an evaluation, maintained by the
+-- policy registry (projections/policy_coverage.rs) and read by
+-- GET /v1/policy/:id/coverage to find dead and over-triggered rules.
+--
+-- Counted per policy version, so a rewritten policy starts from zero.
+-- Evaluations that fell through to the policy's default (allow or deny) are
+-- counted under rule_id '(default)'. Dry runs are evaluations and count too.
+
+CREATE TABLE IF NOT EXISTS projection_policy_rule_hits (
+  policy_id       TEXT    NOT NULL,
+  policy_version  TEXT    NOT NULL,
+  rule_id         TEXT    NOT NULL,
+  hits            BIGINT  NOT NULL DEFAULT 0,
+  first_hit_ms    BIGINT  NOT NULL,
+  last_hit_ms     BIGINT  NOT NULL,
+  PRIMARY KEY (policy_id, policy_version, rule_id)
+);
+
+COMMENT ON TABLE projection_policy_rule_hits IS 'Evaluations decided per policy rule and version';
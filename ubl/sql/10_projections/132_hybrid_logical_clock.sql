@@ -0,0 +1,41 @@
+-- ============================================================================
+-- UBL Hybrid Logical Clock - v1.0
+-- ============================================================================
+-- ts_unix_ms is the wall clock of whichever replica appended an entry, so
+-- two replicas with skewed clocks can write a container's entries with
+-- timestamps that go backwards. Each entry now also carries a hybrid logical
+-- clock (hlc.rs): never behind the previous entry of its container nor the
+-- last one the appending node issued, and within skew of the wall clock.
+--
+-- - Inside a container, order by sequence. Always.
+-- - Across containers, order by (hlc_wall_ms, hlc_logical); ts_unix_ms is
+--   kept for display and stays in entry_hash, the HLC does not.
+--
+-- ledger_entry is append-only, so entries written before this migration keep
+-- NULL and read as (ts_unix_ms, 0).
+
+ALTER TABLE ledger_entry ADD COLUMN IF NOT EXISTS hlc_wall_ms BIGINT;
+ALTER TABLE ledger_entry ADD COLUMN IF NOT EXISTS hlc_logical INTEGER;
+
+CREATE INDEX IF NOT EXISTS ix_ledger_entry_hlc
+  ON ledger_entry (hlc_wall_ms, hlc_logical)
+  WHERE hlc_wall_ms IS NOT NULL;
+
+-- Conversation timelines and job drawers mix entries of several containers
+-- (artifact cards come from wherever Office audits into), so their rows are
+-- read in HLC order, the same as sequence order within one container. Rows
+-- projected before this migration get their projection time as HLC.
+ALTER TABLE projection_job_events ADD COLUMN IF NOT EXISTS hlc TEXT;
+ALTER TABLE projection_timeline_items ADD COLUMN IF NOT EXISTS hlc TEXT;
+
+UPDATE projection_job_events
+   SET hlc = lpad((extract(epoch FROM ts) * 1000)::BIGINT::TEXT, 13, '0') || '.000000'
+ WHERE hlc IS NULL;
+UPDATE projection_timeline_items
+   SET hlc = lpad((extract(epoch FROM created_at) * 1000)::BIGINT::TEXT, 13, '0') || '.000000'
+ WHERE hlc IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_proj_job_events_job_hlc
+  ON projection_job_events(tenant_id, job_id, hlc DESC);
+CREATE INDEX IF NOT EXISTS idx_proj_timeline_conv_hlc
+  ON projection_timeline_items(tenant_id, conversation_id, hlc DESC);
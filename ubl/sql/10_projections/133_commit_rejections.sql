@@ -0,0 +1,43 @@
+-- ============================================================================
+-- UBL Commit Rejections - v1.0
+-- ============================================================================
+-- Short-lived audit of commits the membrane refused, so a client's failing
+-- commits can be debugged after the fact (rejections.rs). Off unless
+-- UBL_REJECTION_AUDIT=1; read through GET /v1/admin/rejections (step-up).
+--
+-- stage: where the commit was refused
+--   gate      ledger read-only or container quarantined
+--   membrane  version or signature
+--   policy    Policy Pack, policy VM, atom limits
+--   pact      missing or invalid pact
+--   evolution evolution payload
+--   tangency  previous_hash / sequence against the head
+--   storage   database errors
+--
+-- Rows are purged after UBL_REJECTION_RETENTION_SECS. Atoms are kept for a
+-- shorter UBL_REJECTION_ATOM_RETENTION_SECS, then cleared; atoms of encrypted
+-- containers are never kept.
+
+CREATE TABLE IF NOT EXISTS commit_rejections (
+  rejection_id       BIGSERIAL PRIMARY KEY,
+  container_id       TEXT    NOT NULL,
+  author_pubkey      TEXT    NOT NULL,
+  actor              TEXT    NOT NULL,
+  intent_class       TEXT    NOT NULL,
+  physics_delta      TEXT    NOT NULL,
+  expected_sequence  BIGINT  NOT NULL,
+  previous_hash      TEXT    NOT NULL,
+  atom_hash          TEXT    NOT NULL,
+  atom               JSONB,
+  stage              TEXT    NOT NULL,
+  code               TEXT    NOT NULL,
+  message            TEXT    NOT NULL,
+  elapsed_ms         DOUBLE PRECISION NOT NULL,
+  rejected_at_ms     BIGINT  NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_commit_rejections_at ON commit_rejections(rejected_at_ms DESC);
+CREATE INDEX IF NOT EXISTS idx_commit_rejections_container ON commit_rejections(container_id, rejected_at_ms DESC);
+CREATE INDEX IF NOT EXISTS idx_commit_rejections_author ON commit_rejections(author_pubkey, rejected_at_ms DESC);
+
+COMMENT ON TABLE commit_rejections IS 'Refused commits with stage and reason, purged after the retention window';
@@ -0,0 +1,35 @@
+-- ============================================================================
+-- UBL Public Read Tokens - v1.0
+-- ============================================================================
+-- Read-only ledger views for the public (transparency reports), served by a
+-- separate listener (UBL_PUBLIC_READ_ADDR) that mounts nothing else; see
+-- public_read/. Each token is issued by an admin for a tenant, names the
+-- containers it may read and carries the daily quota of its tier.
+--
+-- Only the BLAKE3 hash of a token is stored; the token itself is shown once.
+-- Usage is counted per token and UTC day.
+
+CREATE TABLE IF NOT EXISTS public_read_tokens (
+  token_id          TEXT PRIMARY KEY,
+  token_hash        TEXT    NOT NULL UNIQUE,
+  tenant_id         TEXT    NOT NULL,
+  label             TEXT    NOT NULL,
+  tier              TEXT    NOT NULL,
+  requests_per_day  BIGINT  NOT NULL,
+  containers        TEXT[]  NOT NULL,
+  created_by        TEXT    NOT NULL,
+  created_at_ms     BIGINT  NOT NULL,
+  revoked_at_ms     BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_public_read_tokens_tenant ON public_read_tokens(tenant_id);
+
+CREATE TABLE IF NOT EXISTS public_read_usage (
+  token_id  TEXT   NOT NULL REFERENCES public_read_tokens(token_id),
+  day       DATE   NOT NULL,
+  requests  BIGINT NOT NULL DEFAULT 0,
+  PRIMARY KEY (token_id, day)
+);
+
+COMMENT ON TABLE public_read_tokens IS 'API tokens of the public read gateway, with tier quota and allowed containers';
+COMMENT ON TABLE public_read_usage IS 'Requests per public read token and UTC day';
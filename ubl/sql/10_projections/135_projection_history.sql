@@ -0,0 +1,114 @@
+-- ============================================================================
+-- UBL Projection History - v1.0
+-- ============================================================================
+-- Projections keep current rows only; "when did this job become blocked"
+-- used to mean replaying the ledger. For the projections selected with
+-- UBL_PROJECTION_HISTORY, every version of a row is also kept here with the
+-- window it was current in (slowly changing dimension, type 2), and the
+-- read routes answer ?as_of=<unix ms> from it (projections/history.rs).
+--
+-- valid_from_ms  ledger time of the event that produced the version
+--                (wall time if the event is not in ledger_entry)
+-- valid_to_ms    when the next version replaced it; NULL while current
+--
+-- A version is the whole row as JSON, so projection columns added later
+-- need no change here. Rebuilds do not duplicate versions: replayed events
+-- fail the projections' last_event_seq guards and touch no rows.
+--
+-- The server (re)installs the trigger on the selected tables at startup and
+-- drops it from the others; versions already kept stay readable. History
+-- starts when a table is first selected, from its rows' last events.
+
+CREATE TABLE IF NOT EXISTS projection_history (
+  version_id     BIGSERIAL PRIMARY KEY,
+  table_name     TEXT    NOT NULL,
+  row_key        TEXT    NOT NULL,
+  valid_from_ms  BIGINT  NOT NULL,
+  valid_to_ms    BIGINT,
+  entry_hash     TEXT,
+  row_data       JSONB   NOT NULL,
+  CONSTRAINT valid_history_window CHECK (valid_to_ms IS NULL OR valid_to_ms >= valid_from_ms)
+);
+
+CREATE INDEX IF NOT EXISTS idx_projection_history_row
+  ON projection_history(table_name, row_key, valid_from_ms DESC);
+CREATE INDEX IF NOT EXISTS idx_projection_history_window
+  ON projection_history(table_name, valid_from_ms, valid_to_ms);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_projection_history_current
+  ON projection_history(table_name, row_key) WHERE valid_to_ms IS NULL;
+
+-- Ledger time of an entry, wall time if it is not in the ledger
+CREATE OR REPLACE FUNCTION projection_history_time(p_entry_hash TEXT) RETURNS BIGINT AS $$
+  SELECT COALESCE(
+    (SELECT ts_unix_ms FROM ledger_entry WHERE entry_hash = p_entry_hash LIMIT 1),
+    (extract(epoch FROM clock_timestamp()) * 1000)::BIGINT
+  )
+$$ LANGUAGE sql STABLE;
+
+-- Row trigger: TG_ARGV[0] is the table's key column
+CREATE OR REPLACE FUNCTION projection_history_capture() RETURNS trigger AS $$
+DECLARE
+  v_key   TEXT;
+  v_hash  TEXT;
+  v_at    BIGINT;
+  v_from  BIGINT;
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    v_key := to_jsonb(OLD) ->> TG_ARGV[0];
+    UPDATE projection_history
+       SET valid_to_ms = GREATEST(valid_from_ms, (extract(epoch FROM clock_timestamp()) * 1000)::BIGINT)
+     WHERE table_name = TG_TABLE_NAME AND row_key = v_key AND valid_to_ms IS NULL;
+    RETURN OLD;
+  END IF;
+
+  v_key := to_jsonb(NEW) ->> TG_ARGV[0];
+  v_hash := to_jsonb(NEW) ->> 'last_event_hash';
+
+  -- The same event touching the row again amends its version
+  UPDATE projection_history
+     SET row_data = to_jsonb(NEW)
+   WHERE table_name = TG_TABLE_NAME AND row_key = v_key AND valid_to_ms IS NULL
+     AND entry_hash IS NOT DISTINCT FROM v_hash;
+  IF FOUND THEN
+    RETURN NEW;
+  END IF;
+
+  -- Windows never run backwards, whatever the clocks of the events say
+  SELECT valid_from_ms INTO v_from
+    FROM projection_history
+   WHERE table_name = TG_TABLE_NAME AND row_key = v_key AND valid_to_ms IS NULL;
+  v_at := GREATEST(projection_history_time(v_hash), COALESCE(v_from, 0));
+
+  UPDATE projection_history
+     SET valid_to_ms = v_at
+   WHERE table_name = TG_TABLE_NAME AND row_key = v_key AND valid_to_ms IS NULL;
+  INSERT INTO projection_history (table_name, row_key, valid_from_ms, entry_hash, row_data)
+  VALUES (TG_TABLE_NAME, v_key, v_at, v_hash, to_jsonb(NEW));
+  RETURN NEW;
+END $$ LANGUAGE plpgsql;
+
+-- Start or stop keeping history of a projection table. Starting seeds the
+-- current version of rows that have none yet.
+CREATE OR REPLACE FUNCTION projection_history_track(p_table TEXT, p_key_column TEXT, p_enabled BOOLEAN)
+RETURNS void AS $$
+BEGIN
+  EXECUTE format('DROP TRIGGER IF EXISTS projection_history ON %I', p_table);
+  IF NOT p_enabled THEN
+    RETURN;
+  END IF;
+
+  EXECUTE format(
+    'INSERT INTO projection_history (table_name, row_key, valid_from_ms, entry_hash, row_data)
+     SELECT %L, t.%I::TEXT, projection_history_time(t.last_event_hash), t.last_event_hash, to_jsonb(t)
+       FROM %I t
+      WHERE NOT EXISTS (
+        SELECT 1 FROM projection_history h
+         WHERE h.table_name = %L AND h.row_key = t.%I::TEXT AND h.valid_to_ms IS NULL)',
+    p_table, p_key_column, p_table, p_table, p_key_column);
+  EXECUTE format(
+    'CREATE TRIGGER projection_history AFTER INSERT OR UPDATE OR DELETE ON %I
+     FOR EACH ROW EXECUTE FUNCTION projection_history_capture(%L)',
+    p_table, p_key_column);
+END $$ LANGUAGE plpgsql;
+
+COMMENT ON TABLE projection_history IS 'Versions of projection rows with their validity window, for ?as_of= reads';
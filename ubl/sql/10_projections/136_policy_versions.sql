@@ -0,0 +1,35 @@
+-- ============================================================================
+-- UBL Policy Versions - v1.0
+-- ============================================================================
+-- Every registered version of a policy, side by side, with its activation
+-- window (Unix ms; not_before inclusive, not_after exclusive, NULL = open).
+-- The registry (policy_registry.rs) evaluates a commit against the highest
+-- version active at its timestamp, so rows are never rewritten: a version
+-- runs the same bytecode forever. Served by GET /v1/policy/:id/history.
+--
+-- policy_definitions keeps the last registered version of each policy (its
+-- id is what container_policies references).
+--
+-- Versions that existed before this table are backfilled as registered at 0:
+-- in force for all of history, as they were evaluated then.
+
+CREATE TABLE IF NOT EXISTS policy_versions (
+  policy_id         TEXT    NOT NULL,
+  version           TEXT    NOT NULL,
+  description       TEXT    NOT NULL,
+  rules             JSONB   NOT NULL DEFAULT '[]'::jsonb,
+  default_deny      BOOLEAN NOT NULL DEFAULT true,
+  bytecode_hash     TEXT,
+  not_before        BIGINT,
+  not_after         BIGINT,
+  registered_at_ms  BIGINT  NOT NULL,
+  PRIMARY KEY (policy_id, version),
+  CONSTRAINT policy_versions_window CHECK (not_after IS NULL OR not_before IS NULL OR not_after > not_before)
+);
+
+INSERT INTO policy_versions (policy_id, version, description, rules, default_deny, bytecode_hash, registered_at_ms)
+SELECT policy_id, version, description, rules, default_deny, bytecode_hash, 0
+FROM policy_definitions
+ON CONFLICT (policy_id, version) DO NOTHING;
+
+COMMENT ON TABLE policy_versions IS 'SPEC-UBL-POLICY v1.0: Policy versions with activation windows';
@@ -0,0 +1,54 @@
+-- ============================================================================
+-- UBL Analytics Mirror - v1.0
+-- ============================================================================
+-- Schema of the read-only analytics database (UBL_MIRROR_DATABASE_URL), NOT
+-- of the primary: it is not in MIGRATION_ORDER.txt. The mirror worker
+-- (ubl-server/src/mirror.rs) applies it on start and then copies, for each
+-- container in UBL_MIRROR_CONTAINERS:
+--
+-- - every ledger entry with its atom, as stored (sealed for encrypted
+--   containers), in sequence order;
+-- - the container's daily analytics rows.
+--
+-- The cursor lives here, next to the data it describes, so a worker that was
+-- down or pointed at a fresh database catches up from where this one stops.
+-- BI users get read access to this database only.
+
+CREATE TABLE IF NOT EXISTS mirror_cursor (
+  container_id   TEXT    PRIMARY KEY,
+  last_sequence  BIGINT  NOT NULL DEFAULT 0,
+  last_hash      TEXT    NOT NULL DEFAULT '0x00',
+  updated_at_ms  BIGINT  NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS mirror_event (
+  container_id   TEXT    NOT NULL,
+  sequence       BIGINT  NOT NULL,
+  entry_hash     TEXT    NOT NULL,
+  previous_hash  TEXT    NOT NULL,
+  atom_hash      TEXT    NOT NULL,
+  event_type     TEXT,
+  atom           JSONB,            -- NULL when the commit carried no atom
+  ts_unix_ms     BIGINT  NOT NULL,
+  PRIMARY KEY (container_id, sequence)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mirror_event_type ON mirror_event(container_id, event_type);
+CREATE INDEX IF NOT EXISTS idx_mirror_event_ts ON mirror_event(ts_unix_ms);
+
+CREATE TABLE IF NOT EXISTS mirror_container_daily (
+  container_id   TEXT    NOT NULL,
+  day            DATE    NOT NULL,
+  commits        BIGINT  NOT NULL,
+  rejected       BIGINT  NOT NULL,
+  delta_volume   NUMERIC NOT NULL,
+  delta_net      NUMERIC NOT NULL,
+  entropy_minted NUMERIC NOT NULL,
+  entropy_burned NUMERIC NOT NULL,
+  last_sequence  BIGINT  NOT NULL,
+  PRIMARY KEY (container_id, day)
+);
+
+COMMENT ON TABLE mirror_cursor IS 'Last ledger entry mirrored per container';
+COMMENT ON TABLE mirror_event IS 'Ledger entries and atoms of mirrored containers';
+COMMENT ON TABLE mirror_container_daily IS 'Copy of projection_container_daily for mirrored containers';